@@ -0,0 +1,39 @@
+//! Support for per-account sequence numbers.
+//!
+//! Private chains which require strict ordering of the deploys sent by a single account can opt in
+//! to sequence number based replay protection (see
+//! [`EngineConfig::account_sequence_enforced`](super::EngineConfig::account_sequence_enforced)).
+//! In that mode every deploy has to carry the next sequence number of its account as a payment
+//! runtime argument named [`ARG_ACCOUNT_SEQUENCE`], and the engine rejects any deploy whose
+//! sequence number doesn't directly follow the last one executed for that account.  The sequence
+//! number is used up together with the payment, i.e. also by deploys whose session code fails, but
+//! not by deploys rejected before their payment could be collected.
+//!
+//! The last used sequence number of an account is stored as a `u64` under a key derived from the
+//! account hash.  Accounts created before the mode was activated simply have no entry yet, which is
+//! treated as a last used sequence number of `0`, so no eager migration of existing account records
+//! is required when the mode is switched on by a protocol upgrade.
+use casper_types::{
+    account::{AccountHash, ACCOUNT_HASH_LENGTH},
+    crypto, CLValue, Key, StoredValue,
+};
+
+/// Name of the payment runtime argument carrying the sequence number of a deploy.
+pub const ARG_ACCOUNT_SEQUENCE: &str = "account_sequence";
+
+const ACCOUNT_SEQUENCE_KEY_PREFIX: &[u8] = b"account-sequence";
+
+/// Returns the global state key under which the last sequence number used by the account
+/// `account_hash` is stored.
+pub fn account_sequence_key(account_hash: AccountHash) -> Key {
+    let mut preimage = Vec::with_capacity(ACCOUNT_SEQUENCE_KEY_PREFIX.len() + ACCOUNT_HASH_LENGTH);
+    preimage.extend_from_slice(ACCOUNT_SEQUENCE_KEY_PREFIX);
+    preimage.extend_from_slice(account_hash.as_bytes());
+    Key::Hash(crypto::blake2b(preimage))
+}
+
+/// Returns the stored value recording `sequence` as the last sequence number used by an account.
+pub(crate) fn account_sequence_stored_value(sequence: u64) -> StoredValue {
+    // Serializing a `u64` can't fail.
+    StoredValue::CLValue(CLValue::from_t(sequence).expect("should create CLValue from u64"))
+}
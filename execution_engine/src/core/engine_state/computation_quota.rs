@@ -0,0 +1,106 @@
+//! Support for gas-free execution limited by per-account computation quotas.
+//!
+//! Consortium chains which don't want a token-denominated fee market can opt in to a computation
+//! quota (see
+//! [`EngineConfig::computation_quota_per_era`](super::EngineConfig::computation_quota_per_era)).
+//! In that mode payment code is never executed and nothing is charged: instead every account may
+//! spend at most the quota's worth of gas per era.  A deploy declares its gas limit through the
+//! `amount` argument of its (unexecuted) standard payment, as it would on a paying chain, and is
+//! rejected up front if that limit exceeds what is left of its account's quota.  The gas actually
+//! used, also by failed sessions, is then deducted from the quota.  Native transfers use up the
+//! fixed cost of a wasmless transfer.
+//!
+//! The usage of an account is stored under a key derived from the account hash, together with the
+//! era it was recorded in.  Usage recorded in an earlier era is stale, so quotas replenish at era
+//! boundaries without the engine having to reset every account's record.
+use casper_types::{
+    account::{AccountHash, ACCOUNT_HASH_LENGTH},
+    crypto, CLValue, CLValueError, EraId, Gas, Key, StoredValue, U512,
+};
+
+const COMPUTATION_QUOTA_KEY_PREFIX: &[u8] = b"computation-quota";
+
+/// Returns the global state key under which the computation quota usage of the account
+/// `account_hash` is stored.
+pub fn computation_quota_key(account_hash: AccountHash) -> Key {
+    let mut preimage = Vec::with_capacity(COMPUTATION_QUOTA_KEY_PREFIX.len() + ACCOUNT_HASH_LENGTH);
+    preimage.extend_from_slice(COMPUTATION_QUOTA_KEY_PREFIX);
+    preimage.extend_from_slice(account_hash.as_bytes());
+    Key::Hash(crypto::blake2b(preimage))
+}
+
+/// The gas an account has spent of its computation quota.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuotaUsage {
+    /// The era the gas was spent in.
+    pub era_id: EraId,
+    /// The gas spent in that era.
+    pub gas_used: Gas,
+}
+
+impl QuotaUsage {
+    /// Returns the gas left to the account in `era_id` under a quota of `quota` per era.
+    pub fn remaining(&self, quota: Gas, era_id: EraId) -> Gas {
+        if self.era_id != era_id {
+            return quota;
+        }
+        quota.checked_sub(self.gas_used).unwrap_or_default()
+    }
+
+    /// Returns the usage after spending another `gas` in `era_id`.
+    pub fn spend(&self, era_id: EraId, gas: Gas) -> QuotaUsage {
+        let gas_used = if self.era_id == era_id {
+            Gas::new(self.gas_used.value().saturating_add(gas.value()))
+        } else {
+            gas
+        };
+        QuotaUsage { era_id, gas_used }
+    }
+
+    /// Parses a usage from the value stored under [`computation_quota_key`].
+    pub fn from_cl_value(cl_value: CLValue) -> Result<Self, CLValueError> {
+        let (era_id, gas_used) = cl_value.into_t::<(EraId, U512)>()?;
+        Ok(QuotaUsage {
+            era_id,
+            gas_used: Gas::new(gas_used),
+        })
+    }
+
+    /// Returns the stored value recording this usage.
+    pub(crate) fn stored_value(&self) -> StoredValue {
+        // Serializing an `(EraId, U512)` can't fail.
+        let cl_value = CLValue::from_t((self.era_id, self.gas_used.value()))
+            .expect("should create CLValue from (EraId, U512)");
+        StoredValue::CLValue(cl_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_replenish_quota_in_later_era() {
+        let quota = Gas::from(1_000_u64);
+        let usage = QuotaUsage::default().spend(EraId::new(3), Gas::from(400_u64));
+        assert_eq!(usage.remaining(quota, EraId::new(3)), Gas::from(600_u64));
+
+        let usage = usage.spend(EraId::new(3), Gas::from(700_u64));
+        assert_eq!(usage.gas_used, Gas::from(1_100_u64));
+        assert_eq!(usage.remaining(quota, EraId::new(3)), Gas::default());
+
+        assert_eq!(usage.remaining(quota, EraId::new(4)), quota);
+        let usage = usage.spend(EraId::new(4), Gas::from(100_u64));
+        assert_eq!(usage.gas_used, Gas::from(100_u64));
+    }
+
+    #[test]
+    fn should_roundtrip_stored_value() {
+        let usage = QuotaUsage::default().spend(EraId::new(7), Gas::from(42_u64));
+        let cl_value = match usage.stored_value() {
+            StoredValue::CLValue(cl_value) => cl_value,
+            other => panic!("unexpected stored value {:?}", other),
+        };
+        assert_eq!(QuotaUsage::from_cl_value(cl_value).unwrap(), usage);
+    }
+}
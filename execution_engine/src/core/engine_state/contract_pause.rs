@@ -0,0 +1,66 @@
+//! Support for pausing all the contracts of a contract package.
+//!
+//! From the protocol version configured in [`EngineConfig`](super::EngineConfig), the owner of a
+//! contract package, i.e. the holder of its access key, can pause it through the
+//! `casper_set_contract_package_paused` host function.  While a package is paused, every call from
+//! outside the package to an entry point of any of its contract versions fails with
+//! [`Error::ContractPackagePaused`](crate::core::execution::Error::ContractPackagePaused),
+//! whether the call names the package or a contract hash directly, and whether it is made by a
+//! deploy or by another contract.  Calls between the package's own contracts still succeed, so
+//! that a call in progress when the package is paused can complete.  Reading the flag is charged
+//! like a `casper_read_value` call.
+//!
+//! The flag is kept out of the contract package itself so that its serialization is unchanged.  It
+//! is stored as a `bool` under [`contract_package_pause_key`]; a package with no value there is not
+//! paused.
+use casper_types::{
+    crypto, CLValue, CLValueError, ContractPackageHash, Key, StoredValue, KEY_HASH_LENGTH,
+};
+
+const CONTRACT_PACKAGE_PAUSE_KEY_PREFIX: &[u8] = b"contract-package-pause";
+
+/// Returns the global state key under which the paused flag of the contract package
+/// `contract_package_hash` is stored.
+pub fn contract_package_pause_key(contract_package_hash: ContractPackageHash) -> Key {
+    let mut preimage =
+        Vec::with_capacity(CONTRACT_PACKAGE_PAUSE_KEY_PREFIX.len() + KEY_HASH_LENGTH);
+    preimage.extend_from_slice(CONTRACT_PACKAGE_PAUSE_KEY_PREFIX);
+    preimage.extend_from_slice(contract_package_hash.as_bytes());
+    Key::Hash(crypto::blake2b(preimage))
+}
+
+/// Returns the value to store under [`contract_package_pause_key`].
+pub fn paused_flag_stored_value(paused: bool) -> Result<StoredValue, CLValueError> {
+    CLValue::from_t(paused).map(StoredValue::CLValue)
+}
+
+/// Parses the value stored under [`contract_package_pause_key`], if any.
+pub fn is_paused(maybe_stored_value: Option<StoredValue>) -> Result<bool, CLValueError> {
+    match maybe_stored_value {
+        Some(StoredValue::CLValue(cl_value)) => cl_value.into_t(),
+        // A value of any other type can't have been written by the engine.
+        Some(_) | None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_paused_flag() {
+        assert!(!is_paused(None).unwrap());
+        for paused in [true, false] {
+            let stored_value = paused_flag_stored_value(paused).unwrap();
+            assert_eq!(is_paused(Some(stored_value)).unwrap(), paused);
+        }
+    }
+
+    #[test]
+    fn should_derive_distinct_keys_per_package() {
+        let key_1 = contract_package_pause_key(ContractPackageHash::new([1; 32]));
+        let key_2 = contract_package_pause_key(ContractPackageHash::new([2; 32]));
+        assert_ne!(key_1, key_2);
+        assert_ne!(key_1, Key::Hash([1; 32]));
+    }
+}
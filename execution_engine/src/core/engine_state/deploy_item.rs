@@ -2,9 +2,14 @@
 
 use std::collections::BTreeSet;
 
-use casper_types::{account::AccountHash, DeployHash};
+use casper_types::{
+    account::AccountHash, system::mint, CLType, DeployHash, Key, PublicKey, URef, U512,
+};
 
-use crate::core::engine_state::executable_deploy_item::ExecutableDeployItem;
+use crate::{
+    core::engine_state::executable_deploy_item::ExecutableDeployItem,
+    storage::global_state::prefetch::PrefetchTarget,
+};
 
 type GasPrice = u64;
 
@@ -26,6 +31,16 @@ pub struct DeployItem {
     /// A unique identifier of the deploy.
     /// Currently it is the hash of the deploy header (see `DeployHeader` in the `types` crate).
     pub deploy_hash: DeployHash,
+    /// The priority fee in motes offered to the block proposer.
+    ///
+    /// From the protocol version set via
+    /// [`EngineConfig::with_priority_fees_version`](super::EngineConfig::with_priority_fees_version),
+    /// the fee is taken out of the payment purse before session code runs: it reduces the gas
+    /// available to the session rather than being collected from the account separately, and it
+    /// is paid to the proposer in full however much gas is used.  If the payment purse doesn't
+    /// hold enough beyond the cost of the payment code, only what is left is paid.  Native
+    /// transfers don't run payment code and don't pay a priority fee.
+    pub priority_fee: U512,
 }
 
 impl DeployItem {
@@ -45,6 +60,60 @@ impl DeployItem {
             gas_price,
             authorization_keys,
             deploy_hash,
+            priority_fee: U512::zero(),
         }
     }
+
+    /// Sets the priority fee in motes offered to the block proposer.
+    pub fn with_priority_fee(mut self, priority_fee: U512) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
+    /// Returns the values executing this deploy is likely to read, for prefetching.
+    pub fn prefetch_targets(&self) -> Vec<PrefetchTarget> {
+        let mut targets = vec![PrefetchTarget::Key(Key::Account(self.address))];
+        for item in [&self.payment, &self.session] {
+            let target = match item {
+                ExecutableDeployItem::ModuleBytes { .. } => None,
+                ExecutableDeployItem::StoredContractByHash { hash, .. } => {
+                    Some(PrefetchTarget::Key(Key::from(*hash)))
+                }
+                ExecutableDeployItem::StoredVersionedContractByHash { hash, .. } => {
+                    Some(PrefetchTarget::Key(Key::from(*hash)))
+                }
+                ExecutableDeployItem::StoredContractByName { name, .. }
+                | ExecutableDeployItem::StoredVersionedContractByName { name, .. } => {
+                    Some(PrefetchTarget::NamedKey {
+                        account_hash: self.address,
+                        name: name.clone(),
+                    })
+                }
+                ExecutableDeployItem::Transfer { args } => {
+                    args.get(mint::ARG_TARGET).and_then(|cl_value| {
+                        let key = match cl_value.cl_type() {
+                            CLType::URef => {
+                                Key::Balance(cl_value.clone().into_t::<URef>().ok()?.addr())
+                            }
+                            CLType::ByteArray(32) => {
+                                Key::Account(cl_value.clone().into_t::<AccountHash>().ok()?)
+                            }
+                            CLType::Key => cl_value.clone().into_t::<Key>().ok()?,
+                            CLType::PublicKey => Key::Account(
+                                cl_value
+                                    .clone()
+                                    .into_t::<PublicKey>()
+                                    .ok()?
+                                    .to_account_hash(),
+                            ),
+                            _ => return None,
+                        };
+                        Some(PrefetchTarget::Key(key))
+                    })
+                }
+            };
+            targets.extend(target);
+        }
+        targets
+    }
 }
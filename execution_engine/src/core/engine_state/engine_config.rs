@@ -1,5 +1,8 @@
 //! Support for runtime configuration of the execution engine - as an integral property of the
 //! `EngineState` instance.
+use casper_types::{Gas, ProtocolVersion};
+
+use super::storage_rent::StorageRentRate;
 use crate::shared::{system_config::SystemConfig, wasm_config::WasmConfig};
 
 /// Default value for a maximum query depth configuration option.
@@ -12,6 +15,8 @@ pub const DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT: u32 = 12;
 pub const DEFAULT_MINIMUM_DELEGATION_AMOUNT: u64 = 500 * 1_000_000_000;
 /// Default value for strict argument checking.
 pub const DEFAULT_STRICT_ARGUMENT_CHECKING: bool = false;
+/// Default value for enforcing per-account sequence numbers.
+pub const DEFAULT_ACCOUNT_SEQUENCE_ENFORCED: bool = false;
 /// 91 days / 7 days in a week = 13 weeks
 /// Length of total vesting schedule in days.
 const VESTING_SCHEDULE_LENGTH_DAYS: usize = 91;
@@ -37,6 +42,34 @@ pub struct EngineConfig {
     vesting_schedule_period_millis: u64,
     wasm_config: WasmConfig,
     system_config: SystemConfig,
+    /// This flag indicates if deploys have to carry consecutive per-account sequence numbers.
+    account_sequence_enforced: bool,
+    /// The protocol version from which blocks commit to a Merkle tree of their execution results.
+    execution_results_merkle_tree_version: Option<ProtocolVersion>,
+    /// The protocol version from which deploys may pay a priority fee to the block proposer.
+    priority_fees_version: Option<ProtocolVersion>,
+    /// The protocol version from which deploys are charged the fixed prices of payment lanes.
+    payment_lanes_version: Option<ProtocolVersion>,
+    /// The gas each account may use per era when execution is gas-free, or `0` if deploys pay for
+    /// their gas.
+    computation_quota_per_era: u64,
+    /// This flag indicates if deploys are refused writes to protected system keys.
+    protect_system_keys: bool,
+    /// The storage rent charged per stored byte per era, or `0` if contracts pay no rent.
+    storage_rent_per_byte: u64,
+    /// The number of eras a contract may leave its storage rent unpaid before being marked as
+    /// evicted.
+    storage_rent_grace_eras: u64,
+    /// The protocol version from which redelegations move at the next era end instead of waiting
+    /// out the unbonding delay.
+    direct_redelegation_version: Option<ProtocolVersion>,
+    /// The number of eras a delegator has to wait between direct redelegations.
+    redelegation_cooldown_eras: u64,
+    /// The protocol version from which contract package owners may pause calls into their
+    /// packages.
+    contract_package_pause_version: Option<ProtocolVersion>,
+    /// The protocol version from which only inactive bids with a stake left may be activated.
+    strict_bid_activation_version: Option<ProtocolVersion>,
 }
 
 impl Default for EngineConfig {
@@ -50,6 +83,18 @@ impl Default for EngineConfig {
             vesting_schedule_period_millis: DEFAULT_VESTING_SCHEDULE_LENGTH_MILLIS,
             wasm_config: WasmConfig::default(),
             system_config: SystemConfig::default(),
+            account_sequence_enforced: DEFAULT_ACCOUNT_SEQUENCE_ENFORCED,
+            execution_results_merkle_tree_version: None,
+            priority_fees_version: None,
+            payment_lanes_version: None,
+            computation_quota_per_era: 0,
+            protect_system_keys: false,
+            storage_rent_per_byte: 0,
+            storage_rent_grace_eras: 0,
+            direct_redelegation_version: None,
+            redelegation_cooldown_eras: 0,
+            contract_package_pause_version: None,
+            strict_bid_activation_version: None,
         }
     }
 }
@@ -76,9 +121,91 @@ impl EngineConfig {
             vesting_schedule_period_millis,
             wasm_config,
             system_config,
+            account_sequence_enforced: DEFAULT_ACCOUNT_SEQUENCE_ENFORCED,
+            execution_results_merkle_tree_version: None,
+            priority_fees_version: None,
+            payment_lanes_version: None,
+            computation_quota_per_era: 0,
+            protect_system_keys: false,
+            storage_rent_per_byte: 0,
+            storage_rent_grace_eras: 0,
+            direct_redelegation_version: None,
+            redelegation_cooldown_eras: 0,
+            contract_package_pause_version: None,
+            strict_bid_activation_version: None,
         }
     }
 
+    /// Sets the flag enforcing per-account sequence numbers on deploys.
+    pub fn with_account_sequence_enforced(mut self, value: bool) -> Self {
+        self.account_sequence_enforced = value;
+        self
+    }
+
+    /// Sets the protocol version from which blocks commit to a Merkle tree of their execution
+    /// results.
+    pub fn with_execution_results_merkle_tree_version(mut self, version: ProtocolVersion) -> Self {
+        self.execution_results_merkle_tree_version = Some(version);
+        self
+    }
+
+    /// Sets the protocol version from which deploys may pay a priority fee to the block proposer.
+    pub fn with_priority_fees_version(mut self, version: ProtocolVersion) -> Self {
+        self.priority_fees_version = Some(version);
+        self
+    }
+
+    /// Sets the gas each account may use per era, making execution gas-free if non-zero.
+    pub fn with_computation_quota_per_era(mut self, gas: u64) -> Self {
+        self.computation_quota_per_era = gas;
+        self
+    }
+
+    /// Sets the flag refusing deploys writes to protected system keys.
+    pub fn with_protect_system_keys(mut self, value: bool) -> Self {
+        self.protect_system_keys = value;
+        self
+    }
+
+    /// Sets the storage rent charged per stored byte per era and the number of eras a contract may
+    /// leave it unpaid, enabling storage rent if `motes_per_byte` is non-zero.
+    pub fn with_storage_rent(mut self, motes_per_byte: u64, grace_eras: u64) -> Self {
+        self.storage_rent_per_byte = motes_per_byte;
+        self.storage_rent_grace_eras = grace_eras;
+        self
+    }
+
+    /// Sets the protocol version from which redelegations move at the next era end, and the number
+    /// of eras a delegator has to wait between them.
+    pub fn with_direct_redelegation(
+        mut self,
+        version: ProtocolVersion,
+        cooldown_eras: u64,
+    ) -> Self {
+        self.direct_redelegation_version = Some(version);
+        self.redelegation_cooldown_eras = cooldown_eras;
+        self
+    }
+
+    /// Sets the protocol version from which deploys are charged the fixed prices of payment lanes.
+    pub fn with_payment_lanes_version(mut self, version: ProtocolVersion) -> Self {
+        self.payment_lanes_version = Some(version);
+        self
+    }
+
+    /// Sets the protocol version from which contract package owners may pause calls into their
+    /// packages.
+    pub fn with_contract_package_pause_version(mut self, version: ProtocolVersion) -> Self {
+        self.contract_package_pause_version = Some(version);
+        self
+    }
+
+    /// Sets the protocol version from which only inactive bids with a stake left may be activated.
+    pub fn with_strict_bid_activation_version(mut self, version: ProtocolVersion) -> Self {
+        self.strict_bid_activation_version = Some(version);
+        self
+    }
+
     /// Returns the current max associated keys config.
     pub fn max_associated_keys(&self) -> u32 {
         self.max_associated_keys
@@ -113,4 +240,89 @@ impl EngineConfig {
     pub fn vesting_schedule_period_millis(&self) -> u64 {
         self.vesting_schedule_period_millis
     }
+
+    /// Get the engine config's account sequence enforcement flag.
+    ///
+    /// If enabled, deploys are replay-protected by per-account sequence numbers in addition to
+    /// their TTL and hash.
+    pub fn account_sequence_enforced(&self) -> bool {
+        self.account_sequence_enforced
+    }
+
+    /// Returns `true` if blocks executed under `protocol_version` commit to a Merkle tree of their
+    /// execution results, allowing single results to be proven.
+    pub fn execution_results_merkle_tree_enabled(&self, protocol_version: ProtocolVersion) -> bool {
+        self.execution_results_merkle_tree_version
+            .map_or(false, |version| protocol_version >= version)
+    }
+
+    /// Returns `true` if deploys executed under `protocol_version` may pay a priority fee to the
+    /// block proposer.
+    pub fn priority_fees_enabled(&self, protocol_version: ProtocolVersion) -> bool {
+        self.priority_fees_version
+            .map_or(false, |version| protocol_version >= version)
+    }
+
+    /// Returns `true` if deploys executed under `protocol_version` are charged the fixed prices of
+    /// payment lanes, see [`EngineState::payment_lanes`](super::EngineState::payment_lanes).
+    pub fn payment_lanes_enabled(&self, protocol_version: ProtocolVersion) -> bool {
+        self.payment_lanes_version
+            .map_or(false, |version| protocol_version >= version)
+    }
+
+    /// Returns the gas each account may use per era, or `None` if deploys pay for their gas.
+    ///
+    /// If set, payment code is skipped and deploys are limited by their account's computation
+    /// quota instead, see [`computation_quota`](super::computation_quota).
+    pub fn computation_quota_per_era(&self) -> Option<Gas> {
+        if self.computation_quota_per_era == 0 {
+            return None;
+        }
+        Some(Gas::from(self.computation_quota_per_era))
+    }
+
+    /// Returns `true` if the effects of deploys may not write to protected system keys, see
+    /// [`EngineState::protected_system_keys`](super::EngineState::protected_system_keys).
+    pub fn protect_system_keys(&self) -> bool {
+        self.protect_system_keys
+    }
+
+    /// Returns the rate contracts are charged storage rent at, or `None` if they pay no rent, see
+    /// [`storage_rent`](super::storage_rent).
+    pub fn storage_rent(&self) -> Option<StorageRentRate> {
+        if self.storage_rent_per_byte == 0 {
+            return None;
+        }
+        Some(StorageRentRate {
+            motes_per_byte_per_era: self.storage_rent_per_byte,
+            grace_eras: self.storage_rent_grace_eras,
+        })
+    }
+
+    /// Returns the number of eras a delegator has to wait between redelegations if redelegations
+    /// executed under `protocol_version` move at the next era end, or `None` if they wait out the
+    /// unbonding delay.
+    pub fn direct_redelegation_cooldown_eras(
+        &self,
+        protocol_version: ProtocolVersion,
+    ) -> Option<u64> {
+        match self.direct_redelegation_version {
+            Some(version) if protocol_version >= version => Some(self.redelegation_cooldown_eras),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if contract packages may be paused under `protocol_version`, and calls into
+    /// paused packages fail.
+    pub fn contract_package_pause_enabled(&self, protocol_version: ProtocolVersion) -> bool {
+        self.contract_package_pause_version
+            .map_or(false, |version| protocol_version >= version)
+    }
+
+    /// Returns `true` if activating a bid under `protocol_version` fails unless the bid is
+    /// inactive and has a stake left.
+    pub fn strict_bid_activation_enabled(&self, protocol_version: ProtocolVersion) -> bool {
+        self.strict_bid_activation_version
+            .map_or(false, |version| protocol_version >= version)
+    }
 }
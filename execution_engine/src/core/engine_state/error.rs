@@ -3,7 +3,7 @@ use datasize::DataSize;
 use thiserror::Error;
 
 use casper_hashing::Digest;
-use casper_types::{bytesrepr, system::mint, ApiError, ProtocolVersion};
+use casper_types::{bytesrepr, system::mint, ApiError, Gas, Key, ProtocolVersion, U512};
 
 use crate::{
     core::{
@@ -83,6 +83,21 @@ pub enum Error {
     /// Missing checksum registry.
     #[error("Missing checksum registry")]
     MissingChecksumRegistry,
+    /// The checksum registry holds no Merkle root of the block's execution results, e.g. because
+    /// the block was executed before such roots were introduced.
+    #[error("Missing execution results Merkle root")]
+    MissingExecutionResultsMerkleRoot,
+    /// The given execution results don't match the Merkle root held in the checksum registry.
+    #[error("Execution results Merkle root mismatch: expected {expected}, computed {computed}")]
+    ExecutionResultsMerkleRootMismatch {
+        /// The Merkle root held in the checksum registry.
+        expected: Digest,
+        /// The Merkle root computed from the given execution results.
+        computed: Digest,
+    },
+    /// Failed to construct a Merkle proof.
+    #[error("Merkle proof construction error: {0}")]
+    MerkleConstruction(String),
     /// An attempt to push to the runtime stack while already at the maximum height.
     #[error("Runtime stack overflow")]
     RuntimeStackOverflow,
@@ -95,6 +110,20 @@ pub enum Error {
     /// Failed to convert the StoredValue into WithdrawPurse.
     #[error("Failed to convert the stored value to a withdraw purse")]
     FailedToGetWithdrawPurses,
+    /// Failed to get the unbonding purses stored under the Key::Unbond matching a Key::Withdraw.
+    #[error("Failed to get the unbonding purses to migrate withdraw purses into")]
+    FailedToGetUnbondingPurses,
+    /// The total amount held in withdraw and unbonding purses changed while migrating legacy
+    /// withdraw purses into unbonding purses.
+    #[error(
+        "Withdraw purse migration changed the total unbonding amount from {before} to {after}"
+    )]
+    WithdrawMigrationMismatch {
+        /// The total amount held in withdraw and unbonding purses before the migration.
+        before: U512,
+        /// The total amount held in unbonding purses after the migration.
+        after: U512,
+    },
     /// Failed to retrieve the unbonding delay from the auction state.
     #[error("Failed to retrieve the unbonding delay from the auction state")]
     FailedToRetrieveUnbondingDelay,
@@ -104,6 +133,35 @@ pub enum Error {
     /// Failed to put a trie node into global state because some of its children were missing.
     #[error("Failed to put a trie into global state because some of its children were missing")]
     MissingTrieNodeChildren(Vec<Digest>),
+    /// The deploy's account sequence number is missing or doesn't directly follow the last one
+    /// used by the account.
+    #[error("Invalid account sequence: last used {last}, provided {actual:?}")]
+    InvalidAccountSequence {
+        /// The last sequence number used by the account.
+        last: u64,
+        /// The sequence number provided by the deploy, if any.
+        actual: Option<u64>,
+    },
+    /// The deploy's session or payment code is not permitted by the session code policy.
+    #[error("Session or payment code not permitted by the session code policy")]
+    DisallowedSessionCode,
+    /// Payment lanes are enabled, but none admits the deploy.
+    #[error("Deploy is not admitted by any payment lane")]
+    NoPaymentLane,
+    /// Execution is limited by computation quotas, but the deploy doesn't declare a gas limit.
+    #[error("Deploy doesn't declare a gas limit as its payment amount")]
+    MissingGasLimit,
+    /// The deploy's gas limit exceeds what is left of its account's computation quota.
+    #[error("Gas limit {requested} exceeds remaining computation quota {remaining}")]
+    ComputationQuotaExceeded {
+        /// The gas limit declared by the deploy.
+        requested: Gas,
+        /// The gas left of the account's quota in the current era.
+        remaining: Gas,
+    },
+    /// A read-only view call attempted to modify global state.
+    #[error("Read-only view call attempted to write to {0}")]
+    ViewWrite(Key),
 }
 
 impl Error {
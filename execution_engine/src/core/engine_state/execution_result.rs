@@ -6,7 +6,7 @@ use casper_types::{
     bytesrepr::FromBytes, CLTyped, CLValue, Gas, Key, Motes, StoredValue, TransferAddr,
 };
 
-use super::error;
+use super::{error, host_function_deprecations::DeprecatedHostFunction};
 use crate::{
     core::execution::Error as ExecError,
     shared::{execution_journal::ExecutionJournal, transform::Transform},
@@ -49,6 +49,8 @@ pub enum ExecutionResult {
         cost: Gas,
         /// Journal of execution.
         execution_journal: ExecutionJournal,
+        /// Deprecated host functions imported by the executed modules.
+        deprecation_warnings: Vec<DeprecatedHostFunction>,
     },
     /// Execution was finished successfully
     Success {
@@ -58,6 +60,8 @@ pub enum ExecutionResult {
         cost: Gas,
         /// Journal of execution.
         execution_journal: ExecutionJournal,
+        /// Deprecated host functions imported by the executed modules.
+        deprecation_warnings: Vec<DeprecatedHostFunction>,
     },
 }
 
@@ -67,6 +71,7 @@ impl Default for ExecutionResult {
             execution_journal: Default::default(),
             transfers: Default::default(),
             cost: Default::default(),
+            deprecation_warnings: Vec::new(),
         }
     }
 }
@@ -94,6 +99,7 @@ impl ExecutionResult {
             transfers: Vec::default(),
             cost: Gas::default(),
             execution_journal: Default::default(),
+            deprecation_warnings: Vec::new(),
         }
     }
 
@@ -166,21 +172,25 @@ impl ExecutionResult {
                 error,
                 transfers,
                 execution_journal,
+                deprecation_warnings,
                 ..
             } => ExecutionResult::Failure {
                 error,
                 transfers,
                 cost,
                 execution_journal,
+                deprecation_warnings,
             },
             ExecutionResult::Success {
                 transfers,
                 execution_journal,
+                deprecation_warnings,
                 ..
             } => ExecutionResult::Success {
                 transfers,
                 cost,
                 execution_journal,
+                deprecation_warnings,
             },
         }
     }
@@ -195,21 +205,25 @@ impl ExecutionResult {
                 error,
                 cost,
                 execution_journal,
+                deprecation_warnings,
                 ..
             } => ExecutionResult::Failure {
                 error,
                 transfers,
                 cost,
                 execution_journal,
+                deprecation_warnings,
             },
             ExecutionResult::Success {
                 cost,
                 execution_journal,
+                deprecation_warnings,
                 ..
             } => ExecutionResult::Success {
                 transfers,
                 cost,
                 execution_journal,
+                deprecation_warnings,
             },
         }
     }
@@ -225,24 +239,67 @@ impl ExecutionResult {
                 transfers,
                 cost,
                 execution_journal: _,
+                deprecation_warnings,
             } => ExecutionResult::Failure {
                 error,
                 transfers,
                 cost,
                 execution_journal,
+                deprecation_warnings,
             },
             ExecutionResult::Success {
                 transfers,
                 cost,
                 execution_journal: _,
+                deprecation_warnings,
             } => ExecutionResult::Success {
                 transfers,
                 cost,
                 execution_journal,
+                deprecation_warnings,
             },
         }
     }
 
+    /// Returns the deprecated host functions imported by the executed modules, regardless of
+    /// variant.
+    pub fn deprecation_warnings(&self) -> &[DeprecatedHostFunction] {
+        match self {
+            ExecutionResult::Failure {
+                deprecation_warnings,
+                ..
+            } => deprecation_warnings,
+            ExecutionResult::Success {
+                deprecation_warnings,
+                ..
+            } => deprecation_warnings,
+        }
+    }
+
+    /// Returns a new execution result with additional deprecation warnings, skipping those already
+    /// recorded.
+    ///
+    /// This method preserves the [`ExecutionResult`] variant and updates the
+    /// `deprecation_warnings` field only.
+    pub fn with_deprecation_warnings(mut self, warnings: Vec<DeprecatedHostFunction>) -> Self {
+        let deprecation_warnings = match &mut self {
+            ExecutionResult::Failure {
+                deprecation_warnings,
+                ..
+            } => deprecation_warnings,
+            ExecutionResult::Success {
+                deprecation_warnings,
+                ..
+            } => deprecation_warnings,
+        };
+        for warning in warnings {
+            if !deprecation_warnings.contains(&warning) {
+                deprecation_warnings.push(warning);
+            }
+        }
+        self
+    }
+
     /// Returns error value, if possible.
     ///
     /// Returns a reference to a wrapped [`error::Error`] instance if the object is a failure
@@ -328,6 +385,7 @@ impl ExecutionResult {
             execution_journal,
             transfers,
             cost: gas_cost,
+            deprecation_warnings: Vec::new(),
         })
     }
 
@@ -349,6 +407,7 @@ impl From<&ExecutionResult> for casper_types::ExecutionResult {
                 transfers,
                 cost,
                 execution_journal,
+                ..
             } => casper_types::ExecutionResult::Success {
                 effect: execution_journal.into(),
                 transfers: transfers.clone(),
@@ -359,6 +418,7 @@ impl From<&ExecutionResult> for casper_types::ExecutionResult {
                 transfers,
                 cost,
                 execution_journal,
+                ..
             } => casper_types::ExecutionResult::Failure {
                 effect: execution_journal.into(),
                 transfers: transfers.clone(),
@@ -376,6 +436,7 @@ impl From<ExecutionResult> for casper_types::ExecutionResult {
                 transfers,
                 cost,
                 execution_journal,
+                ..
             } => casper_types::ExecutionResult::Success {
                 effect: execution_journal.into(),
                 transfers,
@@ -386,6 +447,7 @@ impl From<ExecutionResult> for casper_types::ExecutionResult {
                 transfers,
                 cost,
                 execution_journal,
+                ..
             } => casper_types::ExecutionResult::Failure {
                 effect: execution_journal.into(),
                 transfers,
@@ -468,6 +530,27 @@ impl ExecutionResultBuilder {
         payment_cost + session_cost
     }
 
+    /// Returns the deprecation warnings of the payment, session and finalize execution results,
+    /// without duplicates.
+    fn deprecation_warnings(&self) -> Vec<DeprecatedHostFunction> {
+        let mut deprecation_warnings: Vec<DeprecatedHostFunction> = Vec::new();
+        for result in [
+            &self.payment_execution_result,
+            &self.session_execution_result,
+            &self.finalize_execution_result,
+        ]
+        .iter()
+        .filter_map(|result| result.as_ref())
+        {
+            for warning in result.deprecation_warnings() {
+                if !deprecation_warnings.contains(warning) {
+                    deprecation_warnings.push(warning.clone());
+                }
+            }
+        }
+        deprecation_warnings
+    }
+
     /// Returns transfers from a session's execution result.
     ///
     /// If the session's execution result is not supplied then an empty [`Vec`] is returned.
@@ -485,6 +568,7 @@ impl ExecutionResultBuilder {
         let mut error: Option<error::Error> = None;
         let mut transfers = self.transfers();
         let cost = self.total_cost();
+        let deprecation_warnings = self.deprecation_warnings();
 
         let mut journal = match self.payment_execution_result {
             Some(result @ ExecutionResult::Failure { .. }) => return Ok(result),
@@ -500,8 +584,7 @@ impl ExecutionResultBuilder {
             Some(ExecutionResult::Failure {
                 error: session_error,
                 transfers: session_transfers,
-                execution_journal: _,
-                cost: _,
+                ..
             }) => {
                 error = Some(session_error);
                 transfers = session_transfers;
@@ -515,9 +598,10 @@ impl ExecutionResultBuilder {
         match self.finalize_execution_result {
             Some(ExecutionResult::Failure { .. }) => {
                 // payment_code_spec_5_a: Finalization Error should only ever be raised here
-                return Ok(ExecutionResult::precondition_failure(
-                    error::Error::Finalization,
-                ));
+                return Ok(
+                    ExecutionResult::precondition_failure(error::Error::Finalization)
+                        .with_deprecation_warnings(deprecation_warnings),
+                );
             }
             Some(ExecutionResult::Success {
                 execution_journal, ..
@@ -530,12 +614,14 @@ impl ExecutionResultBuilder {
                 transfers,
                 cost,
                 execution_journal: journal,
+                deprecation_warnings,
             }),
             Some(error) => Ok(ExecutionResult::Failure {
                 error,
                 transfers,
                 cost,
                 execution_journal: journal,
+                deprecation_warnings,
             }),
         }
     }
@@ -0,0 +1,262 @@
+//! Support for proving the execution result of a single deploy in a block.
+//!
+//! From the protocol version set in the chainspec onwards, the checksum registry of a block's
+//! global state holds the root of a Merkle tree with one leaf per deploy executed in the block,
+//! in execution order. A proof of a single execution result then consists of the proof of the
+//! checksum registry under the block's state root hash and the path from the result's leaf to the
+//! root of that tree, both of which are logarithmic in size.
+use casper_hashing::{Digest, IndexedMerkleProof};
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    ExecutionResult, Key, StoredValue,
+};
+use thiserror::Error;
+
+use crate::{
+    core::engine_state::checksum_registry::ChecksumRegistry,
+    storage::trie::merkle_proof::TrieMerkleProof,
+};
+
+/// The name of the checksum registry entry holding the Merkle root of a block's execution results.
+pub const EXECUTION_RESULTS_MERKLE_ROOT_NAME: &str = "execution_results_merkle_root";
+
+/// Returns the Merkle tree leaf representing `execution_result`.
+pub fn execution_result_leaf(
+    execution_result: &ExecutionResult,
+) -> Result<Digest, bytesrepr::Error> {
+    Ok(Digest::hash(execution_result.to_bytes()?))
+}
+
+/// Returns the root of the Merkle tree over `execution_results`, one leaf per result.
+pub fn execution_results_merkle_root(
+    execution_results: &[ExecutionResult],
+) -> Result<Digest, bytesrepr::Error> {
+    let leaves = execution_results
+        .iter()
+        .map(execution_result_leaf)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Digest::hash_merkle_tree(leaves))
+}
+
+/// Error returned when an [`ExecutionResultProof`] fails to verify.
+#[derive(Debug, Error)]
+pub enum ExecutionResultProofError {
+    /// The trie proof is not a proof of the checksum registry.
+    #[error("trie proof is for {0} rather than the checksum registry")]
+    InvalidKey(Key),
+    /// The trie proof could not be hashed.
+    #[error("failed to compute state root hash of trie proof: {0}")]
+    TrieMerkleProof(bytesrepr::Error),
+    /// The trie proof is for a different state root hash.
+    #[error(
+        "trie proof is for state root hash {proof_state_root_hash}, expected {state_root_hash}"
+    )]
+    StateRootHashMismatch {
+        /// The state root hash computed from the trie proof.
+        proof_state_root_hash: Digest,
+        /// The state root hash the proof was checked against.
+        state_root_hash: Digest,
+    },
+    /// The proven value is not a checksum registry holding an execution results Merkle root.
+    #[error("proven value holds no execution results Merkle root")]
+    MissingMerkleRoot,
+    /// The execution result could not be hashed.
+    #[error("failed to hash execution result: {0}")]
+    ExecutionResult(bytesrepr::Error),
+    /// The Merkle proof is malformed or does not start at the execution result's leaf.
+    #[error("Merkle proof does not prove the given execution result")]
+    InvalidMerkleProof,
+    /// The Merkle proof leads to a root other than the one in the checksum registry.
+    #[error("Merkle proof has root {proof_root}, checksum registry holds {registry_root}")]
+    MerkleRootMismatch {
+        /// The root computed from the Merkle proof.
+        proof_root: Digest,
+        /// The root held in the checksum registry.
+        registry_root: Digest,
+    },
+}
+
+/// A proof that a given execution result is part of a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutionResultProof {
+    checksum_registry_proof: TrieMerkleProof<Key, StoredValue>,
+    merkle_proof: IndexedMerkleProof,
+}
+
+impl ExecutionResultProof {
+    /// Creates a new [`ExecutionResultProof`].
+    pub fn new(
+        checksum_registry_proof: TrieMerkleProof<Key, StoredValue>,
+        merkle_proof: IndexedMerkleProof,
+    ) -> Self {
+        ExecutionResultProof {
+            checksum_registry_proof,
+            merkle_proof,
+        }
+    }
+
+    /// Returns the proof of the checksum registry under the block's state root hash.
+    pub fn checksum_registry_proof(&self) -> &TrieMerkleProof<Key, StoredValue> {
+        &self.checksum_registry_proof
+    }
+
+    /// Returns the proof of the execution result's leaf in the execution results Merkle tree.
+    pub fn merkle_proof(&self) -> &IndexedMerkleProof {
+        &self.merkle_proof
+    }
+
+    /// Returns the position of the proven execution result within its block.
+    pub fn index(&self) -> u64 {
+        self.merkle_proof.index()
+    }
+
+    /// Checks that `execution_result` is part of the block with the given state root hash.
+    pub fn verify(
+        &self,
+        state_root_hash: Digest,
+        execution_result: &ExecutionResult,
+    ) -> Result<(), ExecutionResultProofError> {
+        let key = *self.checksum_registry_proof.key();
+        if key != Key::ChecksumRegistry {
+            return Err(ExecutionResultProofError::InvalidKey(key));
+        }
+
+        let proof_state_root_hash = self
+            .checksum_registry_proof
+            .compute_state_hash()
+            .map_err(ExecutionResultProofError::TrieMerkleProof)?;
+        if proof_state_root_hash != state_root_hash {
+            return Err(ExecutionResultProofError::StateRootHashMismatch {
+                proof_state_root_hash,
+                state_root_hash,
+            });
+        }
+
+        let registry_root = self
+            .checksum_registry_proof
+            .value()
+            .as_cl_value()
+            .and_then(|cl_value| cl_value.clone().into_t::<ChecksumRegistry>().ok())
+            .and_then(|registry| registry.get(EXECUTION_RESULTS_MERKLE_ROOT_NAME).copied())
+            .ok_or(ExecutionResultProofError::MissingMerkleRoot)?;
+
+        let leaf = execution_result_leaf(execution_result)
+            .map_err(ExecutionResultProofError::ExecutionResult)?;
+        if self.merkle_proof.verify().is_err() || self.merkle_proof.merkle_proof()[0] != leaf {
+            return Err(ExecutionResultProofError::InvalidMerkleProof);
+        }
+
+        let proof_root = self.merkle_proof.root_hash();
+        if proof_root != registry_root {
+            return Err(ExecutionResultProofError::MerkleRootMismatch {
+                proof_root,
+                registry_root,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl ToBytes for ExecutionResultProof {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut result = bytesrepr::allocate_buffer(self)?;
+        result.append(&mut self.checksum_registry_proof.to_bytes()?);
+        result.append(&mut self.merkle_proof.to_bytes()?);
+        Ok(result)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.checksum_registry_proof.serialized_length() + self.merkle_proof.serialized_length()
+    }
+}
+
+impl FromBytes for ExecutionResultProof {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (checksum_registry_proof, remainder) = FromBytes::from_bytes(bytes)?;
+        let (merkle_proof, remainder) = FromBytes::from_bytes(remainder)?;
+        Ok((
+            ExecutionResultProof {
+                checksum_registry_proof,
+                merkle_proof,
+            },
+            remainder,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use rand::Rng;
+
+    use casper_types::CLValue;
+
+    use super::*;
+
+    fn proof_and_state_root_hash(
+        execution_results: &[ExecutionResult],
+        index: u64,
+    ) -> (ExecutionResultProof, Digest) {
+        let mut checksum_registry = ChecksumRegistry::new();
+        checksum_registry.insert(
+            EXECUTION_RESULTS_MERKLE_ROOT_NAME,
+            execution_results_merkle_root(execution_results).unwrap(),
+        );
+        let checksum_registry_proof = TrieMerkleProof::new(
+            Key::ChecksumRegistry,
+            StoredValue::CLValue(CLValue::from_t(checksum_registry).unwrap()),
+            VecDeque::new(),
+        );
+        let state_root_hash = checksum_registry_proof.compute_state_hash().unwrap();
+        let leaves = execution_results
+            .iter()
+            .map(|execution_result| execution_result_leaf(execution_result).unwrap());
+        let merkle_proof = IndexedMerkleProof::new(leaves, index).unwrap();
+        (
+            ExecutionResultProof::new(checksum_registry_proof, merkle_proof),
+            state_root_hash,
+        )
+    }
+
+    #[test]
+    fn should_verify_every_execution_result_of_a_block() {
+        let mut rng = rand::thread_rng();
+        let execution_results: Vec<ExecutionResult> = (0..7).map(|_| rng.gen()).collect();
+
+        for (index, execution_result) in execution_results.iter().enumerate() {
+            let (proof, state_root_hash) =
+                proof_and_state_root_hash(&execution_results, index as u64);
+            bytesrepr::test_serialization_roundtrip(&proof);
+            proof.verify(state_root_hash, execution_result).unwrap();
+        }
+    }
+
+    #[test]
+    fn should_reject_other_execution_results_and_state_roots() {
+        let mut rng = rand::thread_rng();
+        let execution_results: Vec<ExecutionResult> = (0..3).map(|_| rng.gen()).collect();
+        let (proof, state_root_hash) = proof_and_state_root_hash(&execution_results, 1);
+
+        assert!(matches!(
+            proof.verify(state_root_hash, &execution_results[0]),
+            Err(ExecutionResultProofError::InvalidMerkleProof)
+        ));
+        assert!(matches!(
+            proof.verify(Digest::hash([1; 32]), &execution_results[1]),
+            Err(ExecutionResultProofError::StateRootHashMismatch { .. })
+        ));
+
+        let (other_block_proof, other_state_root_hash) =
+            proof_and_state_root_hash(&execution_results[..2], 1);
+        let mixed_proof = ExecutionResultProof::new(
+            other_block_proof.checksum_registry_proof().clone(),
+            proof.merkle_proof().clone(),
+        );
+        assert!(matches!(
+            mixed_proof.verify(other_state_root_hash, &execution_results[1]),
+            Err(ExecutionResultProofError::MerkleRootMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,48 @@
+//! Support for obtaining pending unbonds from the auction system.
+use casper_hashing::Digest;
+use casper_types::{system::auction::UnbondingPurses, ProtocolVersion};
+
+/// Represents a request to obtain the pending unbonds in the auction system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetUnbondsRequest {
+    state_hash: Digest,
+    protocol_version: ProtocolVersion,
+}
+
+impl GetUnbondsRequest {
+    /// Creates new request.
+    pub fn new(state_hash: Digest, protocol_version: ProtocolVersion) -> Self {
+        GetUnbondsRequest {
+            state_hash,
+            protocol_version,
+        }
+    }
+
+    /// Returns state root hash.
+    pub fn state_hash(&self) -> Digest {
+        self.state_hash
+    }
+
+    /// Returns the protocol version the unbonds are processed under.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
+}
+
+/// Represents a result of a `get_unbonds` request.
+#[derive(Debug)]
+pub enum GetUnbondsResult {
+    /// Invalid state root hash.
+    RootNotFound,
+    /// Contains the pending unbonds returned from the global state.
+    Success {
+        /// Pending unbonding purses, keyed by the account hash of the validator unbonded from.
+        unbonding_purses: UnbondingPurses,
+        /// The number of eras after its creation an unbonding purse is paid out.
+        unbonding_delay: u64,
+        /// The number of eras after its creation a redelegating unbonding purse moves to its new
+        /// validator, which is `0` if redelegations move at the end of the era they're requested
+        /// in.
+        redelegation_delay: u64,
+    },
+}
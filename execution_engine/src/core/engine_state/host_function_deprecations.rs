@@ -0,0 +1,213 @@
+//! Support for retiring legacy host functions from a given protocol version onwards, so that the
+//! host API can evolve without breaking contracts overnight.
+//!
+//! Each [`DeprecatedHostFunction`] names a host function and the protocol version from which it
+//! is deprecated.  From then on, a deploy instantiating a module which imports it has the
+//! deprecation recorded in its execution result's
+//! [deprecation warnings](crate::core::engine_state::ExecutionResult::deprecation_warnings), and a
+//! function whose deprecation [rejects](DeprecationAction::Reject) it fails the execution when
+//! called.  Modules which merely import a rejected function still run, so that
+//! contracts only fail along the paths which actually use it.
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    ProtocolVersion,
+};
+
+use crate::core::resolvers;
+
+const WARN_TAG: u8 = 0;
+const REJECT_TAG: u8 = 1;
+
+/// What happens to a deprecated host function once its deprecation is in force.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeprecationAction {
+    /// Calls still succeed, but the deprecation is recorded in the execution results of deploys
+    /// importing the function.
+    Warn,
+    /// Calls fail the execution with
+    /// [`Error::DeprecatedHostFunction`](crate::core::execution::Error::DeprecatedHostFunction).
+    Reject,
+}
+
+/// A host function deprecated from a given protocol version onwards.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct DeprecatedHostFunction {
+    /// The name the host function is imported under, e.g. `casper_load_named_keys`.
+    pub name: String,
+    /// The first protocol version at which the deprecation is in force.
+    pub since: ProtocolVersion,
+    /// What happens to the host function once the deprecation is in force.
+    pub action: DeprecationAction,
+}
+
+/// The deprecated host functions of a network.
+///
+/// If empty, every host function is available.
+#[derive(Clone, DataSize, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HostFunctionDeprecations(Vec<DeprecatedHostFunction>);
+
+impl HostFunctionDeprecations {
+    /// Creates the deprecations from a list.
+    pub fn new(deprecations: Vec<DeprecatedHostFunction>) -> Self {
+        HostFunctionDeprecations(deprecations)
+    }
+
+    /// Returns the deprecations.
+    pub fn deprecations(&self) -> &[DeprecatedHostFunction] {
+        &self.0
+    }
+
+    /// Returns the index and the deprecation of the host function `name`, if its deprecation is in
+    /// force at `protocol_version`.
+    pub fn in_force(
+        &self,
+        name: &str,
+        protocol_version: ProtocolVersion,
+    ) -> Option<(usize, &DeprecatedHostFunction)> {
+        self.0.iter().enumerate().find(|(_, deprecation)| {
+            deprecation.name == name && deprecation.since <= protocol_version
+        })
+    }
+
+    /// Returns the names which aren't host functions, e.g. due to a typo.
+    pub fn unknown_names(&self) -> Vec<&str> {
+        self.0
+            .iter()
+            .map(|deprecation| deprecation.name.as_str())
+            .filter(|name| !resolvers::is_host_function(name))
+            .collect()
+    }
+}
+
+impl ToBytes for DeprecationAction {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let tag = match self {
+            DeprecationAction::Warn => WARN_TAG,
+            DeprecationAction::Reject => REJECT_TAG,
+        };
+        Ok(vec![tag])
+    }
+
+    fn serialized_length(&self) -> usize {
+        U8_SERIALIZED_LENGTH
+    }
+}
+
+impl FromBytes for DeprecationAction {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, remainder) = u8::from_bytes(bytes)?;
+        match tag {
+            WARN_TAG => Ok((DeprecationAction::Warn, remainder)),
+            REJECT_TAG => Ok((DeprecationAction::Reject, remainder)),
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+impl ToBytes for DeprecatedHostFunction {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.name.to_bytes()?);
+        buffer.extend(self.since.to_bytes()?);
+        buffer.extend(self.action.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.name.serialized_length()
+            + self.since.serialized_length()
+            + self.action.serialized_length()
+    }
+}
+
+impl FromBytes for DeprecatedHostFunction {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (name, remainder) = String::from_bytes(bytes)?;
+        let (since, remainder) = ProtocolVersion::from_bytes(remainder)?;
+        let (action, remainder) = DeprecationAction::from_bytes(remainder)?;
+        let deprecation = DeprecatedHostFunction {
+            name,
+            since,
+            action,
+        };
+        Ok((deprecation, remainder))
+    }
+}
+
+impl ToBytes for HostFunctionDeprecations {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+impl FromBytes for HostFunctionDeprecations {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (deprecations, remainder) = Vec::<DeprecatedHostFunction>::from_bytes(bytes)?;
+        Ok((HostFunctionDeprecations(deprecations), remainder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deprecation(name: &str, since: u32, action: DeprecationAction) -> DeprecatedHostFunction {
+        DeprecatedHostFunction {
+            name: name.to_string(),
+            since: ProtocolVersion::from_parts(1, since, 0),
+            action,
+        }
+    }
+
+    #[test]
+    fn should_be_in_force_from_protocol_version() {
+        let deprecations = HostFunctionDeprecations::new(vec![
+            deprecation("casper_load_named_keys", 5, DeprecationAction::Warn),
+            deprecation("casper_get_blocktime", 6, DeprecationAction::Reject),
+        ]);
+        let in_force = |name, minor| {
+            deprecations
+                .in_force(name, ProtocolVersion::from_parts(1, minor, 0))
+                .map(|(index, deprecation)| (index, deprecation.action))
+        };
+        assert_eq!(in_force("casper_load_named_keys", 4), None);
+        assert_eq!(
+            in_force("casper_load_named_keys", 5),
+            Some((0, DeprecationAction::Warn))
+        );
+        assert_eq!(in_force("casper_get_blocktime", 5), None);
+        assert_eq!(
+            in_force("casper_get_blocktime", 7),
+            Some((1, DeprecationAction::Reject))
+        );
+        assert_eq!(in_force("casper_write", 7), None);
+        assert!(deprecations.unknown_names().is_empty());
+
+        let typo = HostFunctionDeprecations::new(vec![deprecation(
+            "casper_get_blocktme",
+            5,
+            DeprecationAction::Reject,
+        )]);
+        assert_eq!(typo.unknown_names(), vec!["casper_get_blocktme"]);
+    }
+
+    #[test]
+    fn bytesrepr_roundtrip() {
+        bytesrepr::test_serialization_roundtrip(&HostFunctionDeprecations::default());
+        bytesrepr::test_serialization_roundtrip(&HostFunctionDeprecations::new(vec![
+            deprecation("casper_load_named_keys", 5, DeprecationAction::Warn),
+            deprecation("casper_get_blocktime", 6, DeprecationAction::Reject),
+        ]));
+    }
+}
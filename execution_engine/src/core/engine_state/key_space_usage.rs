@@ -0,0 +1,240 @@
+//! Accounting of the global state occupied by each account and contract.
+//!
+//! Chains weighing a state-rent policy need to know how global state is spread over the entities
+//! which would pay for it.  [`KeySpaceUsage`] attributes the values stored at a state root to the
+//! accounts and contracts owning them:
+//!
+//! * an account owns its own record, and the values under the URefs in its named keys and its main
+//!   purse,
+//! * a contract owns its own record, its Wasm, its package, and the values under the URefs in its
+//!   named keys,
+//!
+//! where the values under a URef are its own value, its balance if it is a purse and the entries of
+//! the dictionaries it seeds.  For contracts this extends the footprint charged by
+//! [`storage_rent`](super::storage_rent) with the package and purse balances.
+//!
+//! A value reachable from several owners, e.g. a URef handed from an account to a contract, is
+//! attributed once only: accounts are accounted for before contracts, each in order of their keys,
+//! and the first owner reaching a value takes it.  Values no owner reaches, such as era info or
+//! bids, aren't attributed.
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap, HashSet},
+};
+
+use serde::Serialize;
+
+use casper_hashing::Digest;
+use casper_types::{account::AccountHash, ContractHash, Key, URefAddr};
+
+/// An entity global state is attributed to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum StorageOwner {
+    /// An account.
+    Account(AccountHash),
+    /// A contract.
+    Contract(ContractHash),
+}
+
+/// The values stored under a set of keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct StorageFootprint {
+    /// The total serialized length of the values.
+    pub stored_bytes: u64,
+    /// The number of keys the values are stored under.
+    pub stored_keys: u64,
+}
+
+impl StorageFootprint {
+    /// Adds the values of `other`.
+    pub(crate) fn add(&mut self, other: StorageFootprint) {
+        self.stored_bytes += other.stored_bytes;
+        self.stored_keys += other.stored_keys;
+    }
+
+    /// Adds a single value of `stored_bytes`.
+    pub(crate) fn add_value(&mut self, stored_bytes: u64) {
+        self.stored_bytes += stored_bytes;
+        self.stored_keys += 1;
+    }
+}
+
+/// The global state attributed to an owner.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct StorageUsage {
+    /// The owner.
+    pub owner: StorageOwner,
+    /// The values attributed to the owner.
+    pub footprint: StorageFootprint,
+}
+
+/// The attribution of the global state at a state root to its owners.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct KeySpaceUsage {
+    /// The state root accounted for.
+    pub state_root_hash: Digest,
+    /// The number of accounts.
+    pub accounts: u64,
+    /// The number of contracts.
+    pub contracts: u64,
+    /// The values attributed to all owners.
+    pub attributed: StorageFootprint,
+    /// The owners with the largest footprints, largest first.
+    pub largest: Vec<StorageUsage>,
+}
+
+/// Tallies the values attributed to each owner while global state is walked.
+///
+/// Owners are accounted for one at a time: the values reachable from an owner are claimed while it
+/// is walked, and its footprint is added once complete.  So only the largest footprints, the
+/// dictionaries not yet attributed and the keys reachable from several owners are held.
+#[derive(Debug)]
+pub(crate) struct KeySpaceAccountant {
+    /// The footprint of the entries of each dictionary not yet attributed, by the address of its
+    /// seed URef.
+    dictionaries: BTreeMap<URefAddr, StorageFootprint>,
+    /// The keys other than those of owners themselves which are attributed already.
+    claimed: HashSet<Key>,
+    limit: usize,
+    accounts: u64,
+    contracts: u64,
+    attributed: StorageFootprint,
+    /// The `limit` largest footprints as stored bytes, owner and stored keys, smallest on top.
+    largest: BinaryHeap<Reverse<(u64, Reverse<StorageOwner>, u64)>>,
+}
+
+impl KeySpaceAccountant {
+    /// Creates an accountant for a global state holding `dictionaries`, which lists the `limit`
+    /// owners with the largest footprints.
+    pub(crate) fn new(dictionaries: BTreeMap<URefAddr, StorageFootprint>, limit: usize) -> Self {
+        KeySpaceAccountant {
+            dictionaries,
+            claimed: HashSet::new(),
+            limit,
+            accounts: 0,
+            contracts: 0,
+            attributed: StorageFootprint::default(),
+            largest: BinaryHeap::new(),
+        }
+    }
+
+    /// Claims the value under `key` for the owner being walked, returning `false` if it is
+    /// attributed already.
+    pub(crate) fn claim(&mut self, key: Key) -> bool {
+        self.claimed.insert(key)
+    }
+
+    /// Claims the entries of the dictionary seeded by the URef at `seed_uref_addr` for the owner
+    /// being walked, returning their footprint unless they are attributed already.
+    pub(crate) fn claim_dictionary(
+        &mut self,
+        seed_uref_addr: URefAddr,
+    ) -> Option<StorageFootprint> {
+        self.dictionaries.remove(&seed_uref_addr)
+    }
+
+    /// Adds the complete footprint of `owner`.
+    pub(crate) fn add_owner(&mut self, owner: StorageOwner, footprint: StorageFootprint) {
+        match owner {
+            StorageOwner::Account(_) => self.accounts += 1,
+            StorageOwner::Contract(_) => self.contracts += 1,
+        }
+        self.attributed.add(footprint);
+        self.largest.push(Reverse((
+            footprint.stored_bytes,
+            Reverse(owner),
+            footprint.stored_keys,
+        )));
+        if self.largest.len() > self.limit {
+            self.largest.pop();
+        }
+    }
+
+    /// Returns the usage at `state_root_hash`.
+    pub(crate) fn finish(self, state_root_hash: Digest) -> KeySpaceUsage {
+        // Sorting the reversed entries lists the largest footprints first, and the smallest owners
+        // first among equal footprints.
+        let largest = self
+            .largest
+            .into_sorted_vec()
+            .into_iter()
+            .map(
+                |Reverse((stored_bytes, Reverse(owner), stored_keys))| StorageUsage {
+                    owner,
+                    footprint: StorageFootprint {
+                        stored_bytes,
+                        stored_keys,
+                    },
+                },
+            )
+            .collect();
+        KeySpaceUsage {
+            state_root_hash,
+            accounts: self.accounts,
+            contracts: self.contracts,
+            attributed: self.attributed,
+            largest,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{AccessRights, URef};
+
+    use super::*;
+
+    #[test]
+    fn should_attribute_each_value_once() {
+        let account = StorageOwner::Account(AccountHash::new([1; 32]));
+        let contract = StorageOwner::Contract(ContractHash::new([2; 32]));
+        let shared_uref = URef::new([3; 32], AccessRights::NONE);
+        let shared_uref_addr = shared_uref.addr();
+        let dictionaries = vec![(
+            shared_uref_addr,
+            StorageFootprint {
+                stored_bytes: 50,
+                stored_keys: 5,
+            },
+        )]
+        .into_iter()
+        .collect();
+        let mut accountant = KeySpaceAccountant::new(dictionaries, 1);
+
+        let mut account_footprint = StorageFootprint::default();
+        account_footprint.add_value(100);
+        assert!(accountant.claim(Key::URef(shared_uref)));
+        account_footprint.add_value(10);
+        account_footprint.add(accountant.claim_dictionary(shared_uref_addr).unwrap());
+        accountant.add_owner(account, account_footprint);
+        // The contract was handed the account's URef: it is attributed to the account only.
+        let mut contract_footprint = StorageFootprint::default();
+        contract_footprint.add_value(300);
+        assert!(!accountant.claim(Key::URef(shared_uref)));
+        assert!(accountant.claim_dictionary(shared_uref_addr).is_none());
+        accountant.add_owner(contract, contract_footprint);
+
+        let state_root_hash = Digest::hash([0]);
+        let usage = accountant.finish(state_root_hash);
+        assert_eq!(usage.state_root_hash, state_root_hash);
+        assert_eq!(usage.accounts, 1);
+        assert_eq!(usage.contracts, 1);
+        assert_eq!(
+            usage.attributed,
+            StorageFootprint {
+                stored_bytes: 460,
+                stored_keys: 8,
+            }
+        );
+        assert_eq!(
+            usage.largest,
+            vec![StorageUsage {
+                owner: contract,
+                footprint: StorageFootprint {
+                    stored_bytes: 300,
+                    stored_keys: 1,
+                },
+            }]
+        );
+    }
+}
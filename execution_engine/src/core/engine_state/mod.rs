@@ -1,7 +1,10 @@
 //!  This module contains all the execution related code.
+pub mod account_sequence;
 pub mod balance;
 pub mod chainspec_registry;
 pub mod checksum_registry;
+pub mod computation_quota;
+pub mod contract_pause;
 pub mod deploy_item;
 pub mod engine_config;
 pub mod era_validators;
@@ -10,46 +13,62 @@ pub mod executable_deploy_item;
 pub mod execute_request;
 pub mod execution_effect;
 pub mod execution_result;
+pub mod execution_results_proof;
 pub mod genesis;
 pub mod get_bids;
+pub mod get_unbonds;
+pub mod host_function_deprecations;
+pub mod key_space_usage;
 pub mod op;
+pub mod payment_lanes;
 pub mod query;
+pub mod query_cache;
 pub mod run_genesis_request;
+pub mod session_code_policy;
 pub mod step;
+pub mod storage_rent;
 pub mod system_contract_registry;
+pub mod total_stake;
 mod transfer;
 pub mod upgrade;
+pub mod uref_access;
+pub mod view;
 
 use std::{
     cell::RefCell,
-    collections::{BTreeMap, BTreeSet},
+    cmp,
+    collections::{BTreeMap, BTreeSet, HashSet},
     convert::TryFrom,
+    iter,
+    path::Path,
     rc::Rc,
+    sync::Arc,
 };
 
 use num::Zero;
 use num_rational::Ratio;
 use once_cell::sync::Lazy;
-use tracing::{debug, error};
+use tracing::{debug, error, info};
 
-use casper_hashing::Digest;
+use casper_hashing::{Digest, IndexedMerkleProof};
 use casper_types::{
     account::{Account, AccountHash},
     bytesrepr::ToBytes,
     contracts::NamedKeys,
     system::{
         auction::{
-            EraValidators, ARG_ERA_END_TIMESTAMP_MILLIS, ARG_EVICTED_VALIDATORS,
-            ARG_REWARD_FACTORS, ARG_VALIDATOR_PUBLIC_KEYS, AUCTION_DELAY_KEY,
-            LOCKED_FUNDS_PERIOD_KEY, SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY, UNBONDING_DELAY_KEY,
-            VALIDATOR_SLOTS_KEY,
+            EraInfo, EraRewardReport, EraValidators, UnbondingPurse, ARG_ERA_END_TIMESTAMP_MILLIS,
+            ARG_EVICTED_VALIDATORS, ARG_REWARD_FACTORS, ARG_VALIDATOR_PUBLIC_KEYS,
+            AUCTION_DELAY_KEY, ERA_ID_KEY, LOCKED_FUNDS_PERIOD_KEY,
+            SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY, UNBONDING_DELAY_KEY, VALIDATOR_SLOTS_KEY,
         },
         handle_payment,
-        mint::{self, ROUND_SEIGNIORAGE_RATE_KEY},
+        mint::{self, ROUND_SEIGNIORAGE_RATE_KEY, TOTAL_SUPPLY_KEY},
         AUCTION, HANDLE_PAYMENT, MINT, STANDARD_PAYMENT,
     },
-    AccessRights, ApiError, BlockTime, CLValue, ContractHash, DeployHash, DeployInfo, Gas, Key,
-    KeyTag, Motes, Phase, ProtocolVersion, PublicKey, RuntimeArgs, StoredValue, URef, U512,
+    AccessRights, ApiError, BlockTime, CLValue, Contract, ContractHash, DeployHash, DeployInfo,
+    EraId, Gas, Key, KeyTag, Motes, Phase, ProtocolVersion, PublicKey, RuntimeArgs, StoredValue,
+    URef, URefAddr, U512,
 };
 
 pub use self::{
@@ -64,34 +83,68 @@ pub use self::{
     execute_request::ExecuteRequest,
     execution::Error as ExecError,
     execution_result::{ExecutionResult, ForcedTransferResult},
+    execution_results_proof::{
+        ExecutionResultProof, ExecutionResultProofError, EXECUTION_RESULTS_MERKLE_ROOT_NAME,
+    },
     genesis::{ExecConfig, GenesisAccount, GenesisConfig, GenesisSuccess},
     get_bids::{GetBidsRequest, GetBidsResult},
+    get_unbonds::{GetUnbondsRequest, GetUnbondsResult},
+    host_function_deprecations::{
+        DeprecatedHostFunction, DeprecationAction, HostFunctionDeprecations,
+    },
+    key_space_usage::{KeySpaceUsage, StorageFootprint, StorageOwner, StorageUsage},
+    payment_lanes::{LaneKind, PaymentLane, PaymentLanes},
     query::{QueryRequest, QueryResult},
+    query_cache::QueryCache,
     run_genesis_request::RunGenesisRequest,
+    session_code_policy::SessionCodePolicy,
     step::{RewardItem, SlashItem, StepError, StepRequest, StepSuccess},
     system_contract_registry::SystemContractRegistry,
+    total_stake::{TotalStake, TotalStakeRequest, TotalStakeResult},
     transfer::{TransferArgs, TransferRuntimeArgsBuilder, TransferTargetMode},
     upgrade::{UpgradeConfig, UpgradeSuccess},
+    uref_access::{URefAccess, URefAccessIndex, URefAccessRequest, URefAccessResult, URefGrant},
+    view::{ViewRequest, ViewResult},
 };
 use crate::{
     core::{
         engine_state::{
+            account_sequence::{
+                account_sequence_key, account_sequence_stored_value, ARG_ACCOUNT_SEQUENCE,
+            },
+            computation_quota::{computation_quota_key, QuotaUsage},
             executable_deploy_item::ExecutionKind,
             execution_result::{ExecutionResultBuilder, ExecutionResults},
             genesis::GenesisInstaller,
+            key_space_usage::KeySpaceAccountant,
+            payment_lanes::PaymentLanes,
+            storage_rent::{storage_rent_key, StorageRentRate, STORAGE_RENT_PURSE_NAMED_KEY},
             upgrade::{ProtocolUpgradeError, SystemUpgrader},
         },
         execution::{self, DirectSystemContractCall, Executor},
         runtime::RuntimeStack,
+        runtime_context::dictionary::DictionaryValue,
         tracking_copy::{TrackingCopy, TrackingCopyExt},
     },
-    shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
+    shared::{
+        additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform,
+        wasm_prep_cache::PreprocessedModuleCache,
+    },
     storage::{
         global_state::{
-            lmdb::LmdbGlobalState, scratch::ScratchGlobalState, CommitProvider, StateProvider,
-            StateReader,
+            backup::{self, BackupError, BackupManifest},
+            checkpoint::{self, Checkpoint, CheckpointError},
+            in_memory::InMemoryGlobalState,
+            lmdb::{LmdbGlobalState, ScratchWriteTimings},
+            prefetch::TriePrefetch,
+            scratch::ScratchGlobalState,
+            state_root_anchors::StateRootAnchor,
+            CommitError, CommitProvider, StateProvider, StateReader,
         },
         trie::{merkle_proof::TrieMerkleProof, TrieRaw},
+        trie_store::{
+            counters::TrieStoreCounts, integrity::TrieIntegrityReport, stats::TrieStoreStats,
+        },
     },
     system::auction,
 };
@@ -118,6 +171,11 @@ pub const WASMLESS_TRANSFER_FIXED_GAS_PRICE: u64 = 1;
 pub struct EngineState<S> {
     config: EngineConfig,
     state: S,
+    preprocessed_module_cache: Option<Arc<PreprocessedModuleCache>>,
+    query_cache: Option<Arc<QueryCache>>,
+    session_code_policy: SessionCodePolicy,
+    payment_lanes: PaymentLanes,
+    host_function_deprecations: Arc<HostFunctionDeprecations>,
 }
 
 impl EngineState<ScratchGlobalState> {
@@ -125,6 +183,36 @@ impl EngineState<ScratchGlobalState> {
     pub fn into_inner(self) -> ScratchGlobalState {
         self.state
     }
+
+    /// Returns every key written so far along with the serialized size of the value written to it.
+    pub fn written_value_sizes(&self) -> Vec<(Key, usize)> {
+        self.state.written_value_sizes()
+    }
+
+    /// Starts prefetching, at `state_root`, the values the given deploys are likely to read.
+    ///
+    /// Does nothing unless prefetching was enabled on the global state the scratch state was
+    /// created from.
+    pub fn prefetch_deploys<'a>(
+        &self,
+        state_root: Digest,
+        deploy_items: impl IntoIterator<Item = &'a DeployItem>,
+    ) -> TriePrefetch {
+        let mut seen = HashSet::new();
+        let targets = deploy_items
+            .into_iter()
+            .flat_map(DeployItem::prefetch_targets)
+            .filter(|target| seen.insert(target.clone()))
+            .collect();
+        self.state.prefetch(state_root, targets)
+    }
+}
+
+impl EngineState<InMemoryGlobalState> {
+    /// Gets underlying InMemoryGlobalState
+    pub fn get_state(&self) -> &InMemoryGlobalState {
+        &self.state
+    }
 }
 
 impl EngineState<LmdbGlobalState> {
@@ -146,20 +234,125 @@ impl EngineState<LmdbGlobalState> {
         EngineState {
             config: self.config,
             state: self.state.create_scratch(),
+            preprocessed_module_cache: self.preprocessed_module_cache.clone(),
+            // Scratch state is never queried through the engine state.
+            query_cache: None,
+            session_code_policy: self.session_code_policy.clone(),
+            payment_lanes: self.payment_lanes.clone(),
+            host_function_deprecations: Arc::clone(&self.host_function_deprecations),
         }
     }
 
+    /// Checks that every trie below `state_root` is present and intact, listing at most `limit`
+    /// missing or corrupted tries.
+    pub fn verify_trie_integrity(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        limit: usize,
+    ) -> Result<TrieIntegrityReport, Error> {
+        Ok(self
+            .state
+            .verify_trie_integrity(correlation_id, state_root, limit)?)
+    }
+
+    /// Gathers statistics about the trie store, including the shape of the trie below
+    /// `state_root` if given.
+    pub fn trie_store_stats(&self, state_root: Option<Digest>) -> Result<TrieStoreStats, Error> {
+        Ok(self.state.trie_store_stats(state_root)?)
+    }
+
+    /// Writes a hot backup of global state at `state_root` to a new tarball at `path`, streaming
+    /// the database into it while commits carry on.
+    pub fn backup_global_state(
+        &self,
+        state_root: Digest,
+        path: &Path,
+    ) -> Result<BackupManifest, BackupError> {
+        backup::create_backup(&self.state, CorrelationId::new(), state_root, path)
+    }
+
+    /// Copies global state into a checkpoint at `state_root` in the directory `dir`, which can be
+    /// opened like any other global state directory.
+    pub fn create_checkpoint(
+        &self,
+        state_root: Digest,
+        dir: &Path,
+    ) -> Result<Checkpoint, CheckpointError> {
+        checkpoint::create_checkpoint(&self.state, CorrelationId::new(), state_root, dir)
+    }
+
+    /// Returns the digests of the tries quarantined because they failed to deserialize, which are
+    /// to be fetched from peers again.
+    pub fn quarantined_tries(&self) -> Result<Vec<Digest>, Error> {
+        Ok(self.state.quarantined_tries()?)
+    }
+
+    /// Returns the counts of the operations performed on the trie store since they were last
+    /// taken, resetting them.
+    pub fn take_trie_store_counts(&self) -> TrieStoreCounts {
+        self.state.trie_store().take_counts()
+    }
+
     /// Writes state cached in an EngineState<ScratchEngineState> to LMDB.
+    ///
+    /// If `maybe_anchor` is given and state root anchors are enabled in global state, the new state
+    /// root is anchored to that block within the transaction committing it.
+    ///
+    /// Returns the new state root hash along with the time spent in each phase of the write.
     pub fn write_scratch_to_db(
         &self,
         state_root_hash: Digest,
         scratch_global_state: ScratchGlobalState,
-    ) -> Result<Digest, Error> {
-        let stored_values = scratch_global_state.into_inner();
+        maybe_anchor: Option<StateRootAnchor>,
+    ) -> Result<(Digest, ScratchWriteTimings), Error> {
+        // Reuse the tries cached while prefetching, if any.
+        let (stored_values, scratch_trie_store) = scratch_global_state.into_parts();
         self.state
-            .put_stored_values(CorrelationId::new(), state_root_hash, stored_values)
+            .put_stored_values_with(
+                scratch_trie_store,
+                CorrelationId::new(),
+                state_root_hash,
+                stored_values,
+                maybe_anchor,
+            )
             .map_err(Into::into)
     }
+
+    /// Records that `state_root_hash` was produced by the block at `block_height` in `era_id`, if
+    /// state root anchors are enabled in global state.
+    ///
+    /// Meant for state roots not written by `write_scratch_to_db`, such as those committed by
+    /// genesis and upgrades, or synchronized from peers.
+    pub fn anchor_state_root(
+        &self,
+        state_root_hash: Digest,
+        block_height: u64,
+        era_id: EraId,
+    ) -> Result<(), Error> {
+        self.state
+            .anchor_state_root(state_root_hash, block_height, era_id)
+            .map_err(Into::into)
+    }
+
+    /// Returns the block which produced `state_root_hash`, if it was anchored.
+    pub fn state_root_anchor(
+        &self,
+        state_root_hash: &Digest,
+    ) -> Result<Option<StateRootAnchor>, Error> {
+        Ok(self.state.state_root_anchor(state_root_hash)?)
+    }
+
+    /// Anchors the state roots of `blocks`, given in order of descending block height, unless
+    /// already anchored to the same or a later block.
+    ///
+    /// Returns how many state roots were anchored.
+    pub fn backfill_state_root_anchors(
+        &self,
+        blocks: &[(Digest, StateRootAnchor)],
+    ) -> Result<usize, Error> {
+        Ok(self.state.backfill_state_root_anchors(blocks)?)
+    }
 }
 
 impl<S> EngineState<S>
@@ -169,7 +362,96 @@ where
 {
     /// Creates new engine state.
     pub fn new(state: S, config: EngineConfig) -> EngineState<S> {
-        EngineState { config, state }
+        EngineState {
+            config,
+            state,
+            preprocessed_module_cache: None,
+            query_cache: None,
+            session_code_policy: SessionCodePolicy::Unrestricted,
+            payment_lanes: PaymentLanes::default(),
+            host_function_deprecations: Arc::default(),
+        }
+    }
+
+    /// Sets the cache used to avoid preprocessing the same Wasm module bytes repeatedly.
+    ///
+    /// The cache is cleared whenever a protocol upgrade is committed.
+    pub fn with_preprocessed_module_cache(
+        mut self,
+        preprocessed_module_cache: PreprocessedModuleCache,
+    ) -> Self {
+        self.preprocessed_module_cache = Some(Arc::new(preprocessed_module_cache));
+        self
+    }
+
+    /// Sets the cache of results of [`run_query`](Self::run_query).
+    pub fn with_query_cache(mut self, query_cache: QueryCache) -> Self {
+        self.query_cache = Some(Arc::new(query_cache));
+        self
+    }
+
+    /// Sets the policy restricting which session and payment Wasm deploys may execute.
+    pub fn with_session_code_policy(mut self, session_code_policy: SessionCodePolicy) -> Self {
+        self.session_code_policy = session_code_policy;
+        self
+    }
+
+    /// Returns the policy restricting which session and payment Wasm deploys may execute.
+    pub fn session_code_policy(&self) -> &SessionCodePolicy {
+        &self.session_code_policy
+    }
+
+    /// Sets the lanes charging deploys fixed prices instead of the gas they use.
+    pub fn with_payment_lanes(mut self, payment_lanes: PaymentLanes) -> Self {
+        self.payment_lanes = payment_lanes;
+        self
+    }
+
+    /// Returns the lanes charging deploys fixed prices instead of the gas they use.
+    pub fn payment_lanes(&self) -> &PaymentLanes {
+        &self.payment_lanes
+    }
+
+    /// Sets the host functions deprecated from given protocol versions onwards.
+    pub fn with_host_function_deprecations(
+        mut self,
+        host_function_deprecations: HostFunctionDeprecations,
+    ) -> Self {
+        self.set_host_function_deprecations(host_function_deprecations);
+        self
+    }
+
+    /// Replaces the host functions deprecated from given protocol versions onwards.
+    pub fn set_host_function_deprecations(
+        &mut self,
+        host_function_deprecations: HostFunctionDeprecations,
+    ) {
+        self.host_function_deprecations = Arc::new(host_function_deprecations);
+    }
+
+    /// Returns the host functions deprecated from given protocol versions onwards.
+    pub fn host_function_deprecations(&self) -> &HostFunctionDeprecations {
+        &self.host_function_deprecations
+    }
+
+    /// Returns the fixed price of executing a deploy with the given session code under
+    /// `protocol_version`.
+    ///
+    /// Returns `Ok(None)` if payment lanes are disabled or not yet in effect, and an error if no
+    /// lane admits the deploy.
+    fn fixed_cost(
+        &self,
+        session: &ExecutableDeployItem,
+        protocol_version: ProtocolVersion,
+    ) -> Result<Option<Motes>, Error> {
+        if !self.payment_lanes.is_enabled() || !self.config.payment_lanes_enabled(protocol_version)
+        {
+            return Ok(None);
+        }
+        self.payment_lanes
+            .lane_for_session(session)
+            .map(|(_, lane)| Some(lane.fixed_cost()))
+            .ok_or(Error::NoPaymentLane)
     }
 
     /// Returns engine config.
@@ -426,6 +708,10 @@ where
                 .write(locked_funds_period_key, value);
         }
 
+        if upgrade_config.migrate_legacy_withdraws() {
+            Self::migrate_legacy_withdraws(correlation_id, &mut tracking_copy.borrow_mut())?;
+        }
+
         // apply the arbitrary modifications
         for (key, value) in upgrade_config.global_state_update() {
             tracking_copy.borrow_mut().write(*key, value.clone());
@@ -443,6 +729,13 @@ where
             )
             .map_err(Into::into)?;
 
+        // modules preprocessed under the old protocol version won't be executed again
+        if let Some(cache) = &self.preprocessed_module_cache {
+            if let Err(error) = cache.clear() {
+                error!(%error, "failed to clear preprocessed module cache");
+            }
+        }
+
         // return result and effects
         Ok(UpgradeSuccess {
             post_state_hash,
@@ -450,6 +743,120 @@ where
         })
     }
 
+    /// Moves all legacy withdraw purses stored under `Key::Withdraw` into the unbonding purses
+    /// stored under the matching `Key::Unbond`, leaving an empty list under each `Key::Withdraw`
+    /// so that repeating the migration is a no-op.
+    ///
+    /// Every withdraw purse is kept, as it still awaits being paid out.  The migration fails if the
+    /// total amount held in all withdraw and unbonding purses across global state afterwards
+    /// differs from the total held beforehand.
+    fn migrate_legacy_withdraws(
+        correlation_id: CorrelationId,
+        tracking_copy: &mut TrackingCopy<S::Reader>,
+    ) -> Result<(), Error> {
+        let total_before = Self::total_withdrawing_amount(correlation_id, tracking_copy)?;
+
+        let withdraw_keys = tracking_copy
+            .get_keys(correlation_id, &KeyTag::Withdraw)
+            .map_err(|_| Error::FailedToGetWithdrawKeys)?;
+
+        let mut migrated_count = 0;
+
+        for withdraw_key in withdraw_keys {
+            let withdraw_purses = tracking_copy
+                .read(correlation_id, &withdraw_key)
+                .map_err(|_| Error::FailedToGetStoredWithdraws)?
+                .ok_or(Error::FailedToGetStoredWithdraws)?
+                .as_withdraw()
+                .ok_or(Error::FailedToGetWithdrawPurses)?
+                .to_owned();
+            if withdraw_purses.is_empty() {
+                continue;
+            }
+
+            let unbond_key = withdraw_key
+                .withdraw_to_unbond()
+                .ok_or(Error::FailedToGetWithdrawKeys)?;
+            let mut unbonding_purses = match tracking_copy
+                .read(correlation_id, &unbond_key)
+                .map_err(|_| Error::FailedToGetUnbondingPurses)?
+            {
+                Some(StoredValue::Unbonding(unbonding_purses)) => unbonding_purses,
+                Some(_) => return Err(Error::FailedToGetUnbondingPurses),
+                None => Vec::new(),
+            };
+
+            migrated_count += withdraw_purses.len();
+
+            unbonding_purses.extend(withdraw_purses.into_iter().map(UnbondingPurse::from));
+            tracking_copy.write(unbond_key, StoredValue::Unbonding(unbonding_purses));
+            tracking_copy.write(withdraw_key, StoredValue::Withdraw(Vec::new()));
+        }
+
+        // Re-read everything, including the purses just written, rather than trusting those in
+        // hand.
+        let total_after = Self::total_withdrawing_amount(correlation_id, tracking_copy)?;
+        if total_before != total_after {
+            error!(%total_before, %total_after, "withdraw purse migration changed total amount");
+            return Err(Error::WithdrawMigrationMismatch {
+                before: total_before,
+                after: total_after,
+            });
+        }
+
+        if migrated_count > 0 {
+            info!(
+                migrated_count,
+                total_amount = %total_after,
+                "migrated legacy withdraw purses into unbonding purses"
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the total amount held in all withdraw purses and unbonding purses in global state,
+    /// as seen through `tracking_copy`.
+    fn total_withdrawing_amount(
+        correlation_id: CorrelationId,
+        tracking_copy: &mut TrackingCopy<S::Reader>,
+    ) -> Result<U512, Error> {
+        let mut total = U512::zero();
+
+        let withdraw_keys = tracking_copy
+            .get_keys(correlation_id, &KeyTag::Withdraw)
+            .map_err(|_| Error::FailedToGetWithdrawKeys)?;
+        for withdraw_key in withdraw_keys {
+            let withdraw_purses = tracking_copy
+                .read(correlation_id, &withdraw_key)
+                .map_err(|_| Error::FailedToGetStoredWithdraws)?
+                .ok_or(Error::FailedToGetStoredWithdraws)?;
+            let withdraw_purses = withdraw_purses
+                .as_withdraw()
+                .ok_or(Error::FailedToGetWithdrawPurses)?;
+            total = withdraw_purses
+                .iter()
+                .fold(total, |total, purse| total + *purse.amount());
+        }
+
+        let unbond_keys = tracking_copy
+            .get_keys(correlation_id, &KeyTag::Unbond)
+            .map_err(|_| Error::FailedToGetUnbondingPurses)?;
+        for unbond_key in unbond_keys {
+            let unbonding_purses = tracking_copy
+                .read(correlation_id, &unbond_key)
+                .map_err(|_| Error::FailedToGetUnbondingPurses)?
+                .ok_or(Error::FailedToGetUnbondingPurses)?;
+            let unbonding_purses = unbonding_purses
+                .as_unbonding()
+                .ok_or(Error::FailedToGetUnbondingPurses)?;
+            total = unbonding_purses
+                .iter()
+                .fold(total, |total, purse| total + *purse.amount());
+        }
+
+        Ok(total)
+    }
+
     /// Creates a new tracking copy instance.
     pub fn tracking_copy(&self, hash: Digest) -> Result<Option<TrackingCopy<S::Reader>>, Error> {
         match self.state.checkout(hash).map_err(Into::into)? {
@@ -458,6 +865,89 @@ where
         }
     }
 
+    /// Attributes the global state at `state_root` to the accounts and contracts owning it, listing
+    /// the `limit` owners with the largest footprints.
+    ///
+    /// See [`key_space_usage`] for what each owner is attributed.  This reads all of global state
+    /// under `Key::Account`, `Key::Hash` and `Key::Dictionary` keys, so can take a long time on a
+    /// large global state.  The state is walked leaf by leaf, and owners are read in batches of
+    /// those whose keys share their first byte, so only a batch, the largest footprints and the
+    /// values reachable from several owners are held in memory.
+    pub fn key_space_usage(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        limit: usize,
+    ) -> Result<KeySpaceUsage, Error> {
+        let reader = self
+            .state
+            .checkout(state_root)
+            .map_err(Into::into)?
+            .ok_or(Error::RootNotFound(state_root))?;
+
+        let mut dictionary_footprints: BTreeMap<URefAddr, StorageFootprint> = BTreeMap::new();
+        reader
+            .visit_leaves_with_prefix(
+                correlation_id,
+                &[KeyTag::Dictionary as u8],
+                &mut |_, value| {
+                    if let Some((seed_uref_addr, entry_bytes)) = dictionary_entry(value) {
+                        dictionary_footprints
+                            .entry(seed_uref_addr)
+                            .or_default()
+                            .add_value(entry_bytes);
+                    }
+                    true
+                },
+            )
+            .map_err(|error| Error::Exec(error.into()))?;
+        let mut accountant = KeySpaceAccountant::new(dictionary_footprints, limit);
+
+        for key_tag in [KeyTag::Account, KeyTag::Hash] {
+            for first_byte in 0..=u8::MAX {
+                let mut owners = Vec::new();
+                reader
+                    .visit_leaves_with_prefix(
+                        correlation_id,
+                        &[key_tag as u8, first_byte],
+                        &mut |key, value| {
+                            if matches!(
+                                (&key, &value),
+                                (Key::Account(_), StoredValue::Account(_))
+                                    | (Key::Hash(_), StoredValue::Contract(_))
+                            ) {
+                                owners.push((key, value));
+                            }
+                            true
+                        },
+                    )
+                    .map_err(|error| Error::Exec(error.into()))?;
+                for (key, value) in owners {
+                    match (key, value) {
+                        (_, StoredValue::Account(account)) => Self::attribute_account(
+                            correlation_id,
+                            &reader,
+                            &mut accountant,
+                            account,
+                        )?,
+                        (Key::Hash(hash_addr), StoredValue::Contract(contract)) => {
+                            Self::attribute_contract(
+                                correlation_id,
+                                &reader,
+                                &mut accountant,
+                                ContractHash::new(hash_addr),
+                                contract,
+                            )?
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        Ok(accountant.finish(state_root))
+    }
+
     /// Executes a query.
     ///
     /// For a given root [`Key`] it does a path lookup through the named keys.
@@ -468,6 +958,14 @@ where
         correlation_id: CorrelationId,
         query_request: QueryRequest,
     ) -> Result<QueryResult, Error> {
+        if let Some(result) = self
+            .query_cache
+            .as_ref()
+            .and_then(|cache| cache.get(&query_request))
+        {
+            return Ok(result);
+        }
+
         let tracking_copy = match self.tracking_copy(query_request.state_hash())? {
             Some(tracking_copy) => Rc::new(RefCell::new(tracking_copy)),
             None => return Ok(QueryResult::RootNotFound),
@@ -475,7 +973,7 @@ where
 
         let tracking_copy = tracking_copy.borrow();
 
-        Ok(tracking_copy
+        let result: QueryResult = tracking_copy
             .query(
                 correlation_id,
                 self.config(),
@@ -483,7 +981,11 @@ where
                 query_request.path(),
             )
             .map_err(|err| Error::Exec(err.into()))?
-            .into())
+            .into();
+        if let Some(cache) = &self.query_cache {
+            cache.insert(&query_request, &result);
+        }
+        Ok(result)
     }
 
     /// Runs a deploy execution request.
@@ -498,7 +1000,9 @@ where
         correlation_id: CorrelationId,
         mut exec_request: ExecuteRequest,
     ) -> Result<ExecutionResults, Error> {
-        let executor = Executor::new(*self.config());
+        let executor = Executor::new(*self.config())
+            .with_preprocessed_module_cache(self.preprocessed_module_cache.clone())
+            .with_host_function_deprecations(Arc::clone(&self.host_function_deprecations));
 
         let deploys = exec_request.take_deploys();
         let mut results = ExecutionResults::with_capacity(deploys.len());
@@ -565,6 +1069,390 @@ where
         Ok(account)
     }
 
+    /// Checks the sequence number passed in `payment_args` against the last one used by the
+    /// account.
+    ///
+    /// Returns the sequence number to be recorded for the account once the deploy is finalized, or
+    /// `None` if per-account sequence numbers are not enforced.
+    fn check_account_sequence(
+        &self,
+        correlation_id: CorrelationId,
+        account_hash: AccountHash,
+        payment_args: &RuntimeArgs,
+        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+    ) -> Result<Option<u64>, Error> {
+        if !self.config.account_sequence_enforced() {
+            return Ok(None);
+        }
+
+        let last = tracking_copy
+            .borrow_mut()
+            .get_account_sequence(correlation_id, account_hash)?;
+
+        let actual = payment_args
+            .get(ARG_ACCOUNT_SEQUENCE)
+            .and_then(|cl_value| cl_value.clone().into_t::<u64>().ok());
+
+        match last.checked_add(1) {
+            Some(expected) if actual == Some(expected) => Ok(Some(expected)),
+            _ => Err(Error::InvalidAccountSequence { last, actual }),
+        }
+    }
+
+    /// Returns the priority fee offered by `deploy_item`.
+    ///
+    /// This is zero if priority fees are not enabled under `protocol_version`.
+    fn requested_priority_fee(
+        &self,
+        protocol_version: ProtocolVersion,
+        deploy_item: &DeployItem,
+    ) -> Motes {
+        if !self.config.priority_fees_enabled(protocol_version) {
+            return Motes::zero();
+        }
+        Motes::new(deploy_item.priority_fee)
+    }
+
+    /// Reads the current era from the auction contract.
+    fn current_era_id(
+        &self,
+        correlation_id: CorrelationId,
+        tracking_copy: &mut TrackingCopy<<S as StateProvider>::Reader>,
+    ) -> Result<EraId, Error> {
+        let system_contract_registry = tracking_copy.get_system_contracts(correlation_id)?;
+        let auction_hash = system_contract_registry.get(AUCTION).ok_or_else(|| {
+            error!("Missing system auction contract hash");
+            Error::MissingSystemContractHash(AUCTION.to_string())
+        })?;
+        let auction_contract = tracking_copy.get_contract(correlation_id, *auction_hash)?;
+        let era_id_key = auction_contract
+            .named_keys()
+            .get(ERA_ID_KEY)
+            .copied()
+            .ok_or(Error::FailedToRetrieveEraId)?;
+        match tracking_copy
+            .get(correlation_id, &era_id_key)
+            .map_err(Into::into)?
+        {
+            Some(StoredValue::CLValue(cl_value)) => cl_value
+                .into_t::<EraId>()
+                .map_err(|_| Error::FailedToRetrieveEraId),
+            _ => Err(Error::FailedToRetrieveEraId),
+        }
+    }
+
+    /// Checks the gas limit declared by a deploy against what is left of its account's
+    /// computation quota in the current era.
+    ///
+    /// Returns the current era and the account's usage, to be updated once the deploy has been
+    /// executed.
+    fn check_computation_quota(
+        &self,
+        correlation_id: CorrelationId,
+        account_hash: AccountHash,
+        quota: Gas,
+        gas_limit: Gas,
+        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+    ) -> Result<(EraId, QuotaUsage), Error> {
+        let mut tracking_copy = tracking_copy.borrow_mut();
+        let era_id = self.current_era_id(correlation_id, &mut tracking_copy)?;
+        let usage = tracking_copy.get_computation_quota_usage(correlation_id, account_hash)?;
+        let remaining = usage.remaining(quota, era_id);
+        if gas_limit > remaining {
+            return Err(Error::ComputationQuotaExceeded {
+                requested: gas_limit,
+                remaining,
+            });
+        }
+        Ok((era_id, usage))
+    }
+
+    /// Returns the gas left of the computation quota of the account `account_hash` in the
+    /// current era, or `None` if execution isn't limited by computation quotas.
+    pub fn get_remaining_computation_quota(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Digest,
+        account_hash: AccountHash,
+    ) -> Result<Option<Gas>, Error> {
+        let quota = match self.config.computation_quota_per_era() {
+            Some(quota) => quota,
+            None => return Ok(None),
+        };
+        let mut tracking_copy = match self.tracking_copy(state_hash)? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Err(Error::RootNotFound(state_hash)),
+        };
+        let era_id = self.current_era_id(correlation_id, &mut tracking_copy)?;
+        let usage = tracking_copy.get_computation_quota_usage(correlation_id, account_hash)?;
+        Ok(Some(usage.remaining(quota, era_id)))
+    }
+
+    /// Executes the session code of a deploy on a gas-free chain, without running its payment
+    /// code.
+    ///
+    /// The session runs with the gas limit declared as the deploy's payment amount, and the gas it
+    /// uses is deducted from its account's computation quota, see [`computation_quota`].
+    #[allow(clippy::too_many_arguments)]
+    fn execute_within_quota(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        protocol_version: ProtocolVersion,
+        blocktime: BlockTime,
+        account: &Account,
+        authorization_keys: BTreeSet<AccountHash>,
+        payment: &ExecutableDeployItem,
+        gas_price: u64,
+        deploy_hash: DeployHash,
+        session_execution_kind: ExecutionKind,
+        session_args: RuntimeArgs,
+        maybe_account_sequence: Option<u64>,
+        quota: Gas,
+        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+    ) -> ExecutionResult {
+        let gas_limit = match payment.payment_amount(gas_price) {
+            Some(gas_limit) => gas_limit,
+            None => return ExecutionResult::precondition_failure(Error::MissingGasLimit),
+        };
+        let account_hash = account.account_hash();
+        let (era_id, usage) = match self.check_computation_quota(
+            correlation_id,
+            account_hash,
+            quota,
+            gas_limit,
+            Rc::clone(&tracking_copy),
+        ) {
+            Ok(checked) => checked,
+            Err(error) => return ExecutionResult::precondition_failure(error),
+        };
+
+        let base_tracking_copy = tracking_copy.borrow();
+        let session_tracking_copy = Rc::new(RefCell::new(base_tracking_copy.fork()));
+        let session_stack = RuntimeStack::from_account_hash(
+            account_hash,
+            self.config.max_runtime_call_stack_height() as usize,
+        );
+        let mut session_named_keys = account.named_keys().clone();
+        let session_result = executor.exec(
+            session_execution_kind,
+            session_args,
+            account,
+            &mut session_named_keys,
+            account.extract_access_rights(),
+            authorization_keys,
+            blocktime,
+            deploy_hash,
+            gas_limit,
+            protocol_version,
+            correlation_id,
+            Rc::clone(&session_tracking_copy),
+            Phase::Session,
+            session_stack,
+        );
+        debug!("Session result: {:?}", session_result);
+
+        self.finalize_within_quota(
+            session_result,
+            session_tracking_copy,
+            &base_tracking_copy,
+            account,
+            deploy_hash,
+            era_id,
+            usage,
+            maybe_account_sequence,
+        )
+    }
+
+    /// Executes a native transfer on a gas-free chain, without collecting payment.
+    ///
+    /// The transfer uses up the fixed cost of a wasmless transfer from its account's computation
+    /// quota, whether it succeeds or not.
+    #[allow(clippy::too_many_arguments)]
+    fn transfer_within_quota(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        protocol_version: ProtocolVersion,
+        blocktime: BlockTime,
+        account: &Account,
+        authorization_keys: BTreeSet<AccountHash>,
+        deploy_hash: DeployHash,
+        transfer_args: RuntimeArgs,
+        maybe_account_sequence: Option<u64>,
+        quota: Gas,
+        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+    ) -> ExecutionResult {
+        let cost = Gas::from(self.config.system_config().wasmless_transfer_cost());
+        let (era_id, usage) = match self.check_computation_quota(
+            correlation_id,
+            account.account_hash(),
+            quota,
+            cost,
+            Rc::clone(&tracking_copy),
+        ) {
+            Ok(checked) => checked,
+            Err(error) => return ExecutionResult::precondition_failure(error),
+        };
+
+        let base_tracking_copy = tracking_copy.borrow();
+        let transfer_tracking_copy = Rc::new(RefCell::new(base_tracking_copy.fork()));
+        let session_result = match self.native_transfer_without_payment(
+            correlation_id,
+            executor,
+            protocol_version,
+            blocktime,
+            account,
+            authorization_keys,
+            deploy_hash,
+            transfer_args,
+            Rc::clone(&transfer_tracking_copy),
+        ) {
+            Ok(session_result) => session_result,
+            Err(error) => ExecutionResult::Failure {
+                error,
+                transfers: Vec::new(),
+                cost: Gas::zero(),
+                execution_journal: Default::default(),
+                deprecation_warnings: Vec::new(),
+            },
+        }
+        .with_cost(cost);
+
+        self.finalize_within_quota(
+            session_result,
+            transfer_tracking_copy,
+            &base_tracking_copy,
+            account,
+            deploy_hash,
+            era_id,
+            usage,
+            maybe_account_sequence,
+        )
+    }
+
+    /// Runs the system calls of a native transfer, creating the target account if necessary.
+    #[allow(clippy::too_many_arguments)]
+    fn native_transfer_without_payment(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        protocol_version: ProtocolVersion,
+        blocktime: BlockTime,
+        account: &Account,
+        authorization_keys: BTreeSet<AccountHash>,
+        deploy_hash: DeployHash,
+        transfer_args: RuntimeArgs,
+        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+    ) -> Result<ExecutionResult, Error> {
+        let gas_limit = Gas::new(U512::from(std::u64::MAX));
+        let mut runtime_args_builder = TransferRuntimeArgsBuilder::new(transfer_args);
+
+        if let TransferTargetMode::CreateAccount(public_key) =
+            runtime_args_builder.transfer_target_mode(correlation_id, Rc::clone(&tracking_copy))?
+        {
+            let (maybe_uref, create_purse_result): (Option<URef>, ExecutionResult) = executor
+                .call_system_contract(
+                    DirectSystemContractCall::CreatePurse,
+                    RuntimeArgs::new(),
+                    account,
+                    authorization_keys.clone(),
+                    blocktime,
+                    deploy_hash,
+                    gas_limit,
+                    protocol_version,
+                    correlation_id,
+                    Rc::clone(&tracking_copy),
+                    Phase::Session,
+                    self.get_new_system_call_stack(),
+                    U512::zero(),
+                );
+            let main_purse = maybe_uref.ok_or_else(|| {
+                create_purse_result
+                    .take_error()
+                    .unwrap_or_else(|| Error::reverter(ApiError::Transfer))
+            })?;
+            let new_account = Account::create(public_key, Default::default(), main_purse);
+            tracking_copy
+                .borrow_mut()
+                .write(Key::Account(public_key), StoredValue::Account(new_account));
+        }
+
+        let transfer_args =
+            runtime_args_builder.build(account, correlation_id, Rc::clone(&tracking_copy))?;
+        let amount = transfer_args.amount();
+        let runtime_args = RuntimeArgs::try_from(transfer_args).map_err(ExecError::from)?;
+
+        let (_, transfer_result): (Option<Result<(), u8>>, ExecutionResult) = executor
+            .call_system_contract(
+                DirectSystemContractCall::Transfer,
+                runtime_args,
+                account,
+                authorization_keys,
+                blocktime,
+                deploy_hash,
+                gas_limit,
+                protocol_version,
+                correlation_id,
+                tracking_copy,
+                Phase::Session,
+                self.get_new_system_call_stack(),
+                amount,
+            );
+        Ok(transfer_result)
+    }
+
+    /// Records a deploy executed on a gas-free chain, along with its use of the computation quota
+    /// and its sequence number, if any.
+    ///
+    /// The effects of a failed deploy are dropped in favour of `base_tracking_copy`, but the gas it
+    /// used still counts against the quota.
+    #[allow(clippy::too_many_arguments)]
+    fn finalize_within_quota(
+        &self,
+        session_result: ExecutionResult,
+        session_tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+        base_tracking_copy: &TrackingCopy<<S as StateProvider>::Reader>,
+        account: &Account,
+        deploy_hash: DeployHash,
+        era_id: EraId,
+        usage: QuotaUsage,
+        maybe_account_sequence: Option<u64>,
+    ) -> ExecutionResult {
+        let account_hash = account.account_hash();
+        let effects_tracking_copy = if session_result.is_failure() {
+            Rc::new(RefCell::new(base_tracking_copy.fork()))
+        } else {
+            session_tracking_copy
+        };
+        {
+            let mut effects_tracking_copy = effects_tracking_copy.borrow_mut();
+            let deploy_info = DeployInfo::new(
+                deploy_hash,
+                session_result.transfers(),
+                account_hash,
+                account.main_purse(),
+                session_result.cost().value(),
+            );
+            effects_tracking_copy.write(
+                Key::DeployInfo(deploy_hash),
+                StoredValue::DeployInfo(deploy_info),
+            );
+            effects_tracking_copy.write(
+                computation_quota_key(account_hash),
+                usage.spend(era_id, session_result.cost()).stored_value(),
+            );
+            if let Some(account_sequence) = maybe_account_sequence {
+                effects_tracking_copy.write(
+                    account_sequence_key(account_hash),
+                    account_sequence_stored_value(account_sequence),
+                );
+            }
+        }
+        let execution_journal = effects_tracking_copy.borrow().execution_journal();
+        session_result.with_journal(execution_journal)
+    }
+
     /// Get the balance of a passed purse referenced by its [`URef`].
     pub fn get_purse_balance(
         &self,
@@ -631,6 +1519,34 @@ where
             Err(e) => return Ok(ExecutionResult::precondition_failure(e)),
         };
 
+        let maybe_account_sequence = match self.check_account_sequence(
+            correlation_id,
+            account_hash,
+            deploy_item.payment.args(),
+            Rc::clone(&tracking_copy),
+        ) {
+            Ok(maybe_account_sequence) => maybe_account_sequence,
+            Err(error) => return Ok(ExecutionResult::precondition_failure(error)),
+        };
+
+        // On gas-free chains the transfer is limited by the account's computation quota instead of
+        // being paid for.
+        if let Some(quota) = self.config.computation_quota_per_era() {
+            return Ok(self.transfer_within_quota(
+                correlation_id,
+                executor,
+                protocol_version,
+                blocktime,
+                &account,
+                authorization_keys,
+                deploy_item.deploy_hash,
+                deploy_item.session.args().clone(),
+                maybe_account_sequence,
+                quota,
+                tracking_copy,
+            ));
+        }
+
         let proposer_addr = proposer.to_account_hash();
         let proposer_account = match tracking_copy
             .borrow_mut()
@@ -666,9 +1582,15 @@ where
 
         let gas_limit = Gas::new(U512::from(std::u64::MAX));
 
-        let wasmless_transfer_gas_cost = Gas::new(U512::from(
-            self.config().system_config().wasmless_transfer_cost(),
-        ));
+        // Under a payment lane the transfer costs the lane's fixed price instead.
+        let wasmless_transfer_gas_cost =
+            match self.fixed_cost(&deploy_item.session, protocol_version) {
+                Ok(Some(fixed_cost)) => Gas::new(fixed_cost.value()),
+                Ok(None) => Gas::new(U512::from(
+                    self.config().system_config().wasmless_transfer_cost(),
+                )),
+                Err(error) => return Ok(ExecutionResult::precondition_failure(error)),
+            };
 
         let wasmless_transfer_motes = match Motes::from_gas(
             wasmless_transfer_gas_cost,
@@ -1025,6 +1947,13 @@ where
             let tc = tracking_copy.borrow();
             let finalization_tc = Rc::new(RefCell::new(tc.fork()));
 
+            if let Some(account_sequence) = maybe_account_sequence {
+                finalization_tc.borrow_mut().write(
+                    account_sequence_key(account_hash),
+                    account_sequence_stored_value(account_sequence),
+                );
+            }
+
             let finalize_payment_stack = self.get_new_system_call_stack();
             handle_payment_access_rights.extend(&[payment_uref, proposer_purse]);
 
@@ -1043,7 +1972,7 @@ where
                     Phase::FinalizePayment,
                     finalize_payment_stack,
                     // Spending limit is cost of wasmless execution.
-                    U512::from(self.config().system_config().wasmless_transfer_cost()),
+                    wasmless_transfer_motes.value(),
                 );
 
             finalize_result
@@ -1105,6 +2034,25 @@ where
     ) -> Result<ExecutionResult, Error> {
         // spec: https://casperlabs.atlassian.net/wiki/spaces/EN/pages/123404576/Payment+code+execution+specification
 
+        // Permissioned networks may only allow known Wasm to run; reject anything else before
+        // touching global state.
+        if !self
+            .session_code_policy
+            .is_allowed(&deploy_item.payment, Phase::Payment)
+            || !self
+                .session_code_policy
+                .is_allowed(&deploy_item.session, Phase::Session)
+        {
+            return Ok(ExecutionResult::precondition_failure(
+                Error::DisallowedSessionCode,
+            ));
+        }
+
+        let fixed_cost = match self.fixed_cost(&deploy_item.session, protocol_version) {
+            Ok(fixed_cost) => fixed_cost,
+            Err(error) => return Ok(ExecutionResult::precondition_failure(error)),
+        };
+
         // Create tracking copy (which functions as a deploy context)
         // validation_spec_2: prestate_hash check
         // do this second; as there is no reason to proceed if the prestate hash is invalid
@@ -1134,6 +2082,19 @@ where
             }
         };
 
+        // Reject replayed or out-of-order deploys before anything gets executed or charged.
+        let maybe_account_sequence = match self.check_account_sequence(
+            correlation_id,
+            account.account_hash(),
+            deploy_item.payment.args(),
+            Rc::clone(&tracking_copy),
+        ) {
+            Ok(maybe_account_sequence) => maybe_account_sequence,
+            Err(error) => return Ok(ExecutionResult::precondition_failure(error)),
+        };
+
+        let requested_priority_fee = self.requested_priority_fee(protocol_version, &deploy_item);
+
         let payment = deploy_item.payment;
         let session = deploy_item.session;
         let deploy_hash = deploy_item.deploy_hash;
@@ -1157,6 +2118,27 @@ where
             }
         };
 
+        // On gas-free chains payment code is skipped entirely, and the session is limited by its
+        // account's computation quota instead.
+        if let Some(quota) = self.config.computation_quota_per_era() {
+            return Ok(self.execute_within_quota(
+                correlation_id,
+                executor,
+                protocol_version,
+                blocktime,
+                &account,
+                authorization_keys,
+                &payment,
+                deploy_item.gas_price,
+                deploy_hash,
+                session_execution_kind,
+                session_args,
+                maybe_account_sequence,
+                quota,
+                tracking_copy,
+            ));
+        }
+
         // Get account main purse balance key
         // validation_spec_5: account main purse minimum balance
         let account_main_purse_balance_key: Key = {
@@ -1415,7 +2397,32 @@ where
             };
 
             match ExecutionResult::new_payment_code_error(
-                error,
+                error,
+                max_payment_cost,
+                account_main_purse_balance,
+                gas_cost,
+                account_main_purse_balance_key,
+                proposer_main_purse_balance_key,
+            ) {
+                Ok(execution_result) => return Ok(execution_result),
+                Err(error) => return Ok(ExecutionResult::precondition_failure(error)),
+            }
+        };
+
+        // Under a payment lane, the payment must cover the lane's fixed price up front, as that is
+        // what will be charged however little gas the session uses.
+        if fixed_cost.map_or(false, |fixed_cost| payment_purse_balance < fixed_cost) {
+            let gas_cost = match Gas::from_motes(max_payment_cost, deploy_item.gas_price) {
+                Some(gas) => gas,
+                None => {
+                    return Ok(ExecutionResult::precondition_failure(
+                        Error::GasConversionOverflow,
+                    ))
+                }
+            };
+
+            match ExecutionResult::new_payment_code_error(
+                Error::InsufficientPayment,
                 max_payment_cost,
                 account_main_purse_balance,
                 gas_cost,
@@ -1425,7 +2432,7 @@ where
                 Ok(execution_result) => return Ok(execution_result),
                 Err(error) => return Ok(ExecutionResult::precondition_failure(error)),
             }
-        };
+        }
 
         // Transfer the contents of the rewards purse to block proposer
         execution_result_builder.set_payment_execution_result(payment_result);
@@ -1443,23 +2450,34 @@ where
 
         let mut session_named_keys = account.named_keys().clone();
 
+        // The priority fee is set aside before session code runs, as far as the payment purse
+        // covers it beyond the cost of the payment code, or the fixed price of the deploy's lane.
+        let priority_fee = match fixed_cost
+            .or_else(|| Motes::from_gas(payment_result_cost, deploy_item.gas_price))
+            .and_then(|base_cost| payment_purse_balance.checked_sub(base_cost))
+        {
+            Some(available) => cmp::min(requested_priority_fee, available),
+            None => Motes::zero(),
+        };
+
         let mut session_result = {
             // payment_code_spec_3_b_i: if (balance of handle payment pay purse) >= (gas spent
             // during payment code execution) * gas_price, yes session
-            // session_code_spec_1: gas limit = ((balance of handle payment payment purse) /
-            // gas_price)
+            // session_code_spec_1: gas limit = ((balance of handle payment payment purse) -
+            // priority fee) / gas_price)
             // - (gas spent during payment execution)
-            let session_gas_limit: Gas =
-                match Gas::from_motes(payment_purse_balance, deploy_item.gas_price)
-                    .and_then(|gas| gas.checked_sub(payment_result_cost))
-                {
-                    Some(gas) => gas,
-                    None => {
-                        return Ok(ExecutionResult::precondition_failure(
-                            Error::GasConversionOverflow,
-                        ))
-                    }
-                };
+            let session_gas_limit: Gas = match payment_purse_balance
+                .checked_sub(priority_fee)
+                .and_then(|session_budget| Gas::from_motes(session_budget, deploy_item.gas_price))
+                .and_then(|gas| gas.checked_sub(payment_result_cost))
+            {
+                Some(gas) => gas,
+                None => {
+                    return Ok(ExecutionResult::precondition_failure(
+                        Error::GasConversionOverflow,
+                    ))
+                }
+            };
 
             executor.exec(
                 session_execution_kind,
@@ -1541,12 +2559,23 @@ where
             let post_session_tc = post_session_rc.borrow();
             let finalization_tc = Rc::new(RefCell::new(post_session_tc.fork()));
 
+            if let Some(account_sequence) = maybe_account_sequence {
+                finalization_tc.borrow_mut().write(
+                    account_sequence_key(account.account_hash()),
+                    account_sequence_stored_value(account_sequence),
+                );
+            }
+
             let handle_payment_args = {
                 //((gas spent during payment code execution) + (gas spent during session code execution)) * gas_price
-                let finalize_cost_motes = match Motes::from_gas(
-                    execution_result_builder.total_cost(),
-                    deploy_item.gas_price,
-                ) {
+                // or the fixed price of the deploy's payment lane, + priority fee
+                let finalize_cost_motes = match fixed_cost
+                    .or_else(|| {
+                        let total_cost = execution_result_builder.total_cost();
+                        Motes::from_gas(total_cost, deploy_item.gas_price)
+                    })
+                    .and_then(|cost| cost.checked_add(priority_fee))
+                {
                     Some(motes) => motes,
                     None => {
                         return Ok(ExecutionResult::precondition_failure(
@@ -1654,6 +2683,130 @@ where
             .map_err(|err| Error::Exec(err.into()))
     }
 
+    /// Apply effects of executing a deploy.
+    ///
+    /// Like [`apply_effect`](Self::apply_effect), but the effects are refused if they write to any
+    /// of `protected_keys`, as returned by
+    /// [`protected_system_keys`](Self::protected_system_keys).
+    pub fn apply_deploy_effect(
+        &self,
+        correlation_id: CorrelationId,
+        pre_state_hash: Digest,
+        protected_keys: &BTreeSet<Key>,
+        effects: AdditiveMap<Key, Transform>,
+    ) -> Result<Digest, Error> {
+        let protected_write = effects.iter().find(|(key, transform)| {
+            !matches!(transform, Transform::Identity) && protected_keys.contains(&key.normalize())
+        });
+        if let Some((key, _)) = protected_write {
+            error!(%key, "refusing deploy effects writing to a protected system key");
+            return Err(CommitError::ProtectedKeyWrite(*key).into());
+        }
+        self.apply_effect(correlation_id, pre_state_hash, effects)
+    }
+
+    /// Returns the normalized keys under `state_hash` which deploys may not write to, or an empty
+    /// set if protection of system keys is disabled.
+    ///
+    /// These are the mint's total supply and the auction's seigniorage recipients snapshot.  Those
+    /// are only ever updated by system contracts at genesis, upgrades and era ends, so such a write
+    /// from a deploy can only come from a bug in the host.  For the same reason, the keys don't
+    /// change while the deploys of a block are executed, and only need to be looked up once per
+    /// block.
+    pub fn protected_system_keys(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Digest,
+    ) -> Result<BTreeSet<Key>, Error> {
+        if !self.config.protect_system_keys() {
+            return Ok(BTreeSet::new());
+        }
+        let mut tracking_copy = match self.tracking_copy(state_hash)? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Err(Error::RootNotFound(state_hash)),
+        };
+        let system_contracts = tracking_copy.get_system_contracts(correlation_id)?;
+        let mut protected_keys = BTreeSet::new();
+        for (contract_name, key_name) in [
+            (MINT, TOTAL_SUPPLY_KEY),
+            (AUCTION, SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY),
+        ] {
+            let contract_hash = *system_contracts
+                .get(contract_name)
+                .ok_or_else(|| Error::MissingSystemContractHash(contract_name.to_string()))?;
+            let contract = tracking_copy.get_contract(correlation_id, contract_hash)?;
+            if let Some(key) = contract.named_keys().get(key_name) {
+                protected_keys.insert(key.normalize());
+            }
+        }
+        Ok(protected_keys)
+    }
+
+    /// Calls a contract's entry point in read-only mode, without a deploy or payment.
+    ///
+    /// The call is made from the caller's account context and is bounded by the request's gas
+    /// limit.  Its effects are never committed, and a call which writes to global state fails with
+    /// [`Error::ViewWrite`], so view entry points can't depend on side effects.
+    pub fn call_view(
+        &self,
+        correlation_id: CorrelationId,
+        view_request: ViewRequest,
+    ) -> Result<ViewResult, Error> {
+        let tracking_copy = match self.tracking_copy(view_request.state_hash())? {
+            Some(tracking_copy) => Rc::new(RefCell::new(tracking_copy)),
+            None => return Ok(ViewResult::RootNotFound),
+        };
+        let account = match tracking_copy
+            .borrow_mut()
+            .get_account(correlation_id, view_request.caller())
+        {
+            Ok(account) => account,
+            Err(error) => {
+                return Ok(ViewResult::Failure {
+                    error: error.into(),
+                    cost: Gas::zero(),
+                })
+            }
+        };
+
+        let executor = Executor::new(*self.config())
+            .with_preprocessed_module_cache(self.preprocessed_module_cache.clone())
+            .with_host_function_deprecations(Arc::clone(&self.host_function_deprecations));
+        let stack = RuntimeStack::from_account_hash(
+            account.account_hash(),
+            self.config.max_runtime_call_stack_height() as usize,
+        );
+        let (result, execution_journal, cost) = executor.exec_view(
+            view_request.contract_hash(),
+            view_request.entry_point(),
+            view_request.args().clone(),
+            &account,
+            view_request.block_time(),
+            view_request.gas_limit(),
+            view_request.protocol_version(),
+            correlation_id,
+            tracking_copy,
+            stack,
+        );
+
+        if let Some((key, _)) = execution_journal
+            .iter()
+            .find(|(_, transform)| *transform != Transform::Identity)
+        {
+            return Ok(ViewResult::Failure {
+                error: Error::ViewWrite(*key),
+                cost,
+            });
+        }
+        Ok(match result {
+            Ok(value) => ViewResult::Success { value, cost },
+            Err(error) => ViewResult::Failure {
+                error: error.into(),
+                cost,
+            },
+        })
+    }
+
     /// Gets a trie object for given state root hash.
     pub fn get_trie_full(
         &self,
@@ -1684,6 +2837,45 @@ where
         }
     }
 
+    /// Returns the hashes of the tries missing from the global state among `state_root` and its
+    /// children: the state root itself if it is missing, or else its missing children.
+    ///
+    /// Tries are only written once all their children are present, so an empty result means the
+    /// state root is complete without walking the trie below it.
+    pub fn missing_state_root_tries(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+    ) -> Result<Vec<Digest>, Error>
+    where
+        Error: From<S::Error>,
+    {
+        match self.state.get_trie_full(correlation_id, &state_root)? {
+            Some(trie_raw) => Ok(self
+                .state
+                .missing_children(correlation_id, trie_raw.inner())?),
+            None => Ok(vec![state_root]),
+        }
+    }
+
+    /// Returns the hashes of the first `limit` tries below `state_root` missing from the global
+    /// state, e.g. to assess how far a partially synced state root is from being complete.
+    ///
+    /// Returns an empty vector if the state root is complete.
+    pub fn missing_descendants(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        limit: usize,
+    ) -> Result<Vec<Digest>, Error>
+    where
+        Error: From<S::Error>,
+    {
+        Ok(self
+            .state
+            .missing_descendants(correlation_id, state_root, limit)?)
+    }
+
     /// Obtains validator weights for given era.
     ///
     /// This skips execution of auction's `get_era_validator` entry point logic to avoid creating an
@@ -1759,28 +2951,245 @@ where
         correlation_id: CorrelationId,
         get_bids_request: GetBidsRequest,
     ) -> Result<GetBidsResult, Error> {
-        let tracking_copy = match self.tracking_copy(get_bids_request.state_hash())? {
-            Some(tracking_copy) => Rc::new(RefCell::new(tracking_copy)),
+        let state_view = match self
+            .state
+            .checkout(get_bids_request.state_hash())
+            .map_err(|err| Error::Exec(err.into()))?
+        {
+            Some(state_view) => state_view,
             None => return Ok(GetBidsResult::RootNotFound),
         };
 
-        let mut tracking_copy = tracking_copy.borrow_mut();
+        let mut bids = BTreeMap::new();
+        state_view
+            .visit_leaves_with_prefix(correlation_id, &[KeyTag::Bid as u8], &mut |_, value| {
+                if let StoredValue::Bid(bid) = value {
+                    bids.insert(bid.validator_public_key().clone(), *bid);
+                }
+                true
+            })
+            .map_err(|err| Error::Exec(err.into()))?;
+
+        Ok(GetBidsResult::Success { bids })
+    }
+
+    /// Reads the values under `keys` at `state_root_hash`, within a single read transaction where
+    /// the global state supports it.
+    ///
+    /// Returns the values in the order of `keys`, or [`Error::RootNotFound`] if the state root
+    /// isn't found.
+    pub fn read_many(
+        &self,
+        correlation_id: CorrelationId,
+        state_root_hash: Digest,
+        keys: &[Key],
+    ) -> Result<Vec<Option<StoredValue>>, Error> {
+        self.state
+            .read_many(correlation_id, state_root_hash, keys)
+            .map_err(|err| Error::Exec(err.into()))?
+            .ok_or(Error::RootNotFound(state_root_hash))
+    }
+
+    /// Obtains the unbonding purses awaiting payout, along with the auction's unbonding delay.
+    pub fn get_unbonds(
+        &self,
+        correlation_id: CorrelationId,
+        get_unbonds_request: GetUnbondsRequest,
+    ) -> Result<GetUnbondsResult, Error> {
+        let state_hash = get_unbonds_request.state_hash();
+        let mut tracking_copy = match self.tracking_copy(state_hash)? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(GetUnbondsResult::RootNotFound),
+        };
+
+        let auction_hash = self.get_system_auction_hash(correlation_id, state_hash)?;
+        let auction_contract = tracking_copy.get_contract(correlation_id, auction_hash)?;
+        let unbonding_delay_key = auction_contract
+            .named_keys()
+            .get(UNBONDING_DELAY_KEY)
+            .copied()
+            .ok_or(Error::FailedToRetrieveUnbondingDelay)?;
+        let unbonding_delay = match tracking_copy
+            .get(correlation_id, &unbonding_delay_key)
+            .map_err(Into::into)?
+        {
+            Some(StoredValue::CLValue(cl_value)) => cl_value
+                .into_t::<u64>()
+                .map_err(|_| Error::FailedToRetrieveUnbondingDelay)?,
+            _ => return Err(Error::FailedToRetrieveUnbondingDelay),
+        };
+        let redelegation_delay = match self
+            .config
+            .direct_redelegation_cooldown_eras(get_unbonds_request.protocol_version())
+        {
+            Some(_) => 0,
+            None => unbonding_delay,
+        };
+
+        let unbond_keys: Vec<Key> = tracking_copy
+            .get_keys(correlation_id, &KeyTag::Unbond)
+            .map_err(|err| Error::Exec(err.into()))?
+            .into_iter()
+            .collect();
+        let unbonds = self.read_many(correlation_id, state_hash, &unbond_keys)?;
+
+        let mut unbonding_purses = BTreeMap::new();
+        for (key, maybe_unbond) in unbond_keys.iter().zip(unbonds) {
+            let account_hash = match key {
+                Key::Unbond(account_hash) => *account_hash,
+                _ => return Err(Error::InvalidKeyVariant),
+            };
+            match maybe_unbond {
+                Some(StoredValue::Unbonding(purses)) if !purses.is_empty() => {
+                    unbonding_purses.insert(account_hash, purses);
+                }
+                Some(StoredValue::Unbonding(_)) | None => (),
+                Some(_) => return Err(Error::FailedToGetUnbondingPurses),
+            }
+        }
+
+        Ok(GetUnbondsResult::Success {
+            unbonding_purses,
+            unbonding_delay,
+            redelegation_delay,
+        })
+    }
+
+    /// Builds the index of which accounts, contracts and contract packages hold which `URef`s
+    /// under the given state root.
+    ///
+    /// Returns `None` if the state root is not found.
+    pub fn build_uref_access_index(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Digest,
+    ) -> Result<Option<URefAccessIndex>, Error> {
+        let mut tracking_copy = match self.tracking_copy(state_hash)? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(None),
+        };
+
+        let mut index = URefAccessIndex::default();
+        for key_tag in [KeyTag::Account, KeyTag::Hash] {
+            let keys = tracking_copy
+                .get_keys(correlation_id, &key_tag)
+                .map_err(|err| Error::Exec(err.into()))?;
+            for key in keys {
+                if let Some(value) = tracking_copy
+                    .get(correlation_id, &key)
+                    .map_err(Into::into)?
+                {
+                    index.insert_entity(key, &value);
+                }
+            }
+        }
+        Ok(Some(index))
+    }
+
+    /// Lists the accounts, contracts and contract packages holding the requested `URef`, along
+    /// with the access rights each has been granted.
+    ///
+    /// This scans all accounts and contracts under the state root, so is expensive.
+    pub fn get_uref_access(
+        &self,
+        correlation_id: CorrelationId,
+        uref_access_request: URefAccessRequest,
+    ) -> Result<URefAccessResult, Error> {
+        let index =
+            match self.build_uref_access_index(correlation_id, uref_access_request.state_hash())? {
+                Some(index) => index,
+                None => return Ok(URefAccessResult::RootNotFound),
+            };
+        let accesses = index.accesses(&uref_access_request.uref_addr()).to_vec();
+        Ok(URefAccessResult::Success { accesses })
+    }
+
+    /// Gets the total amount staked by an account: the stake in its own validator bid, the amounts
+    /// it delegated to validators, and the amounts unbonding to it.
+    ///
+    /// Each bid and unbond entry contributing to the total is returned along with a Merkle proof.
+    pub fn get_total_stake(
+        &self,
+        correlation_id: CorrelationId,
+        total_stake_request: TotalStakeRequest,
+    ) -> Result<TotalStakeResult, Error> {
+        let mut tracking_copy = match self.tracking_copy(total_stake_request.state_hash())? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(TotalStakeResult::RootNotFound),
+        };
+        let public_key = total_stake_request.public_key();
+
+        let mut total_stake = TotalStake::default();
 
         let bid_keys = tracking_copy
             .get_keys(correlation_id, &KeyTag::Bid)
             .map_err(|err| Error::Exec(err.into()))?;
+        for key in bid_keys.iter() {
+            let proof = match tracking_copy
+                .reader()
+                .read_with_proof(correlation_id, key)
+                .map_err(Into::into)?
+            {
+                Some(proof) => proof,
+                None => continue,
+            };
+            let bid = match proof.value() {
+                StoredValue::Bid(bid) => bid,
+                _ => continue,
+            };
 
-        let mut bids = BTreeMap::new();
+            let mut is_relevant = false;
+            if bid.validator_public_key() == public_key {
+                total_stake.validator_stake = *bid.staked_amount();
+                is_relevant = true;
+            }
+            if let Some(delegator) = bid.delegators().get(public_key) {
+                total_stake.delegations.insert(
+                    bid.validator_public_key().clone(),
+                    *delegator.staked_amount(),
+                );
+                is_relevant = true;
+            }
+            if is_relevant {
+                total_stake.proofs.push(proof);
+            }
+        }
 
-        for key in bid_keys.iter() {
-            if let Some(StoredValue::Bid(bid)) =
-                tracking_copy.get(correlation_id, key).map_err(Into::into)?
+        let unbond_keys = tracking_copy
+            .get_keys(correlation_id, &KeyTag::Unbond)
+            .map_err(|err| Error::Exec(err.into()))?;
+        for key in unbond_keys.iter() {
+            let proof = match tracking_copy
+                .reader()
+                .read_with_proof(correlation_id, key)
+                .map_err(Into::into)?
             {
-                bids.insert(bid.validator_public_key().clone(), *bid);
+                Some(proof) => proof,
+                None => continue,
             };
+            let unbonding_purses = match proof.value() {
+                StoredValue::Unbonding(unbonding_purses) => unbonding_purses,
+                _ => continue,
+            };
+
+            let mut is_relevant = false;
+            for unbonding_purse in unbonding_purses
+                .iter()
+                .filter(|purse| purse.unbonder_public_key() == public_key)
+            {
+                total_stake.unbonding = total_stake
+                    .unbonding
+                    .saturating_add(*unbonding_purse.amount());
+                is_relevant = true;
+            }
+            if is_relevant {
+                total_stake.proofs.push(proof);
+            }
         }
 
-        Ok(GetBidsResult::Success { bids })
+        Ok(TotalStakeResult::Success {
+            total_stake: Box::new(total_stake),
+        })
     }
 
     /// Executes a step request.
@@ -1795,7 +3204,8 @@ where
             Ok(Some(tracking_copy)) => Rc::new(RefCell::new(tracking_copy)),
         };
 
-        let executor = Executor::new(*self.config());
+        let executor = Executor::new(*self.config())
+            .with_host_function_deprecations(Arc::clone(&self.host_function_deprecations));
 
         let system_account_addr = PublicKey::System.to_account_hash();
 
@@ -1856,6 +3266,16 @@ where
             return Err(StepError::DistributeError(exec_error));
         }
 
+        let era_id = step_request.next_era_id.saturating_sub(1);
+        let era_info = tracking_copy
+            .borrow_mut()
+            .read(correlation_id, &Key::EraInfo(era_id))
+            .map_err(|error| StepError::TrackingCopyError(Error::Exec(error.into())))?
+            .and_then(|stored_value| stored_value.as_era_info().cloned())
+            .unwrap_or_else(EraInfo::new);
+        let total_supply_before_slashing =
+            Self::read_total_supply(correlation_id, &mut tracking_copy.borrow_mut())?;
+
         let slashed_validators: Vec<PublicKey> = step_request.slashed_validators();
 
         if !slashed_validators.is_empty() {
@@ -1891,6 +3311,14 @@ where
             }
         }
 
+        let total_supply_after_slashing =
+            Self::read_total_supply(correlation_id, &mut tracking_copy.borrow_mut())?;
+        let reward_report = EraRewardReport::new(
+            era_id,
+            &era_info,
+            total_supply_before_slashing.saturating_sub(total_supply_after_slashing),
+        );
+
         let run_auction_args = RuntimeArgs::try_new(|args| {
             args.insert(
                 ARG_ERA_END_TIMESTAMP_MILLIS,
@@ -1929,6 +3357,16 @@ where
             return Err(StepError::AuctionError(exec_error));
         }
 
+        if let Some(rate) = self.config.storage_rent() {
+            Self::charge_storage_rent(
+                correlation_id,
+                &mut tracking_copy.borrow_mut(),
+                rate,
+                era_id,
+            )
+            .map_err(StepError::StorageRentError)?;
+        }
+
         let execution_effect = tracking_copy.borrow().effect();
         let execution_journal = tracking_copy.borrow().execution_journal();
 
@@ -1945,9 +3383,228 @@ where
         Ok(StepSuccess {
             post_state_hash,
             execution_journal,
+            reward_report,
         })
     }
 
+    /// Returns the key under which the mint records the total supply of motes.
+    fn total_supply_key(
+        correlation_id: CorrelationId,
+        tracking_copy: &mut TrackingCopy<S::Reader>,
+    ) -> Result<Key, Error> {
+        let mint_hash = *tracking_copy
+            .get_system_contracts(correlation_id)?
+            .get(MINT)
+            .ok_or_else(|| Error::MissingSystemContractHash(MINT.to_string()))?;
+        let mint_contract = tracking_copy.get_contract(correlation_id, mint_hash)?;
+        mint_contract
+            .named_keys()
+            .get(TOTAL_SUPPLY_KEY)
+            .copied()
+            .ok_or_else(|| Error::Mint(format!("missing {}", TOTAL_SUPPLY_KEY)))
+    }
+
+    /// Reads the total supply of motes as recorded by the mint.
+    fn read_total_supply(
+        correlation_id: CorrelationId,
+        tracking_copy: &mut TrackingCopy<S::Reader>,
+    ) -> Result<U512, Error> {
+        let total_supply_key = Self::total_supply_key(correlation_id, tracking_copy)?;
+        tracking_copy
+            .read(correlation_id, &total_supply_key)
+            .map_err(|error| Error::Exec(error.into()))?
+            .and_then(|stored_value| stored_value.as_cl_value().cloned())
+            .and_then(|cl_value| cl_value.into_t::<U512>().ok())
+            .ok_or_else(|| Error::Mint(format!("invalid {}", TOTAL_SUPPLY_KEY)))
+    }
+
+    /// Charges every contract listed in the storage rent registry rent for the era `era_id`,
+    /// burning the rent paid.
+    ///
+    /// See [`storage_rent`] for what a contract is charged for.  The bytes each contract stores are
+    /// counted as they are written, so this only reads the registry and each contract's record,
+    /// named keys and rent purse.
+    fn charge_storage_rent(
+        correlation_id: CorrelationId,
+        tracking_copy: &mut TrackingCopy<S::Reader>,
+        rate: StorageRentRate,
+        era_id: EraId,
+    ) -> Result<(), Error> {
+        let mut burned = U512::zero();
+        for contract_hash in tracking_copy.get_storage_rent_registry(correlation_id)? {
+            let contract_key = Key::Hash(contract_hash.value());
+            let contract = match Self::peek(correlation_id, tracking_copy, &contract_key)? {
+                Some(StoredValue::Contract(contract)) => contract,
+                _ => continue,
+            };
+            let previous_record =
+                tracking_copy.get_storage_rent_record(correlation_id, contract_hash)?;
+
+            let rent = rate.rent(previous_record.stored_bytes);
+            let rent_purse = contract
+                .named_keys()
+                .get(STORAGE_RENT_PURSE_NAMED_KEY)
+                .and_then(Key::as_uref);
+            let paid = match rent_purse {
+                Some(purse) => {
+                    let balance_key = Key::Balance(purse.addr());
+                    let balance = Self::peek(correlation_id, tracking_copy, &balance_key)?
+                        .and_then(|value| value.as_cl_value().cloned())
+                        .and_then(|cl_value| cl_value.into_t::<U512>().ok());
+                    match balance {
+                        Some(balance) if balance >= rent => {
+                            let cl_value = CLValue::from_t(balance - rent)
+                                .map_err(|error| Error::Exec(error.into()))?;
+                            tracking_copy.write(balance_key, StoredValue::CLValue(cl_value));
+                            burned += rent;
+                            true
+                        }
+                        _ => false,
+                    }
+                }
+                None => false,
+            };
+
+            let record = previous_record.charge(rate, era_id, paid);
+            if record.evicted && !previous_record.evicted {
+                info!(
+                    %contract_hash,
+                    %era_id,
+                    unpaid_eras = record.unpaid_eras,
+                    "contract marked as evicted for unpaid storage rent"
+                );
+            }
+            tracking_copy.write(storage_rent_key(contract_hash), record.stored_value());
+        }
+
+        if !burned.is_zero() {
+            let total_supply_key = Self::total_supply_key(correlation_id, tracking_copy)?;
+            let total_supply = Self::read_total_supply(correlation_id, tracking_copy)?;
+            let cl_value = CLValue::from_t(total_supply.saturating_sub(burned))
+                .map_err(|error| Error::Exec(error.into()))?;
+            tracking_copy.write(total_supply_key, StoredValue::CLValue(cl_value));
+        }
+        Ok(())
+    }
+
+    /// Returns the footprint of the entries of each dictionary, by the address of its seed URef.
+    fn dictionary_footprints(
+        correlation_id: CorrelationId,
+        tracking_copy: &mut TrackingCopy<S::Reader>,
+    ) -> Result<BTreeMap<URefAddr, StorageFootprint>, Error> {
+        let mut footprints: BTreeMap<URefAddr, StorageFootprint> = BTreeMap::new();
+        let dictionary_keys = tracking_copy
+            .get_keys(correlation_id, &KeyTag::Dictionary)
+            .map_err(|error| Error::Exec(error.into()))?;
+        for key in dictionary_keys {
+            let entry = Self::peek(correlation_id, tracking_copy, &key)?.and_then(dictionary_entry);
+            if let Some((seed_uref_addr, entry_bytes)) = entry {
+                footprints
+                    .entry(seed_uref_addr)
+                    .or_default()
+                    .add_value(entry_bytes);
+            }
+        }
+        Ok(footprints)
+    }
+
+    /// Attributes `account`, and the values under the URefs in its named keys and its main purse,
+    /// to the account.
+    fn attribute_account(
+        correlation_id: CorrelationId,
+        reader: &S::Reader,
+        accountant: &mut KeySpaceAccountant,
+        account: Account,
+    ) -> Result<(), Error> {
+        let mut footprint = StorageFootprint::default();
+        footprint.add_value(account.serialized_length() as u64);
+        let urefs = iter::once(account.main_purse()).chain(
+            account
+                .named_keys()
+                .values()
+                .filter_map(Key::as_uref)
+                .copied(),
+        );
+        for uref in urefs {
+            Self::claim_uref(correlation_id, reader, accountant, &uref, &mut footprint)?;
+        }
+        accountant.add_owner(StorageOwner::Account(account.account_hash()), footprint);
+        Ok(())
+    }
+
+    /// Attributes `contract`, its Wasm, its package and the values under the URefs in its named
+    /// keys to the contract.
+    fn attribute_contract(
+        correlation_id: CorrelationId,
+        reader: &S::Reader,
+        accountant: &mut KeySpaceAccountant,
+        contract_hash: ContractHash,
+        contract: Contract,
+    ) -> Result<(), Error> {
+        let mut footprint = StorageFootprint::default();
+        footprint.add_value(contract.serialized_length() as u64);
+        let wasm_key = Key::Hash(contract.contract_wasm_hash().value());
+        let package_key = Key::Hash(contract.contract_package_hash().value());
+        for key in [wasm_key, package_key] {
+            Self::claim_key(correlation_id, reader, accountant, key, &mut footprint)?;
+        }
+        for uref in contract.named_keys().values().filter_map(Key::as_uref) {
+            Self::claim_uref(correlation_id, reader, accountant, uref, &mut footprint)?;
+        }
+        accountant.add_owner(StorageOwner::Contract(contract_hash), footprint);
+        Ok(())
+    }
+
+    /// Adds the value under `uref`, its balance if it is a purse and the entries of the dictionary
+    /// it seeds to `footprint`, unless they are attributed already.
+    fn claim_uref(
+        correlation_id: CorrelationId,
+        reader: &S::Reader,
+        accountant: &mut KeySpaceAccountant,
+        uref: &URef,
+        footprint: &mut StorageFootprint,
+    ) -> Result<(), Error> {
+        let uref_key = Key::URef(uref.remove_access_rights());
+        let balance_key = Key::Balance(uref.addr());
+        for key in [uref_key, balance_key] {
+            Self::claim_key(correlation_id, reader, accountant, key, footprint)?;
+        }
+        if let Some(dictionary_footprint) = accountant.claim_dictionary(uref.addr()) {
+            footprint.add(dictionary_footprint);
+        }
+        Ok(())
+    }
+
+    /// Adds the value under `key` to `footprint`, unless it is attributed already.
+    fn claim_key(
+        correlation_id: CorrelationId,
+        reader: &S::Reader,
+        accountant: &mut KeySpaceAccountant,
+        key: Key,
+        footprint: &mut StorageFootprint,
+    ) -> Result<(), Error> {
+        let value = reader
+            .read(correlation_id, &key.normalize())
+            .map_err(|error| Error::Exec(error.into()))?;
+        if let Some(value) = value {
+            if accountant.claim(key) {
+                footprint.add_value(value.serialized_length() as u64);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the value under `key` without recording the read in the journal of `tracking_copy`.
+    fn peek(
+        correlation_id: CorrelationId,
+        tracking_copy: &mut TrackingCopy<S::Reader>,
+        key: &Key,
+    ) -> Result<Option<StoredValue>, Error> {
+        tracking_copy
+            .get(correlation_id, &key.normalize())
+            .map_err(|error| Error::Exec(error.into()))
+    }
+
     /// Gets the balance of a given public key.
     pub fn get_balance(
         &self,
@@ -2113,16 +3770,63 @@ where
             .map_err(Into::into)?;
         maybe_proof.ok_or(Error::MissingChecksumRegistry)
     }
+
+    /// Returns a proof that the execution result at `index` is part of the block with the given
+    /// state root hash.
+    ///
+    /// `execution_results` must hold all execution results of the block in execution order.
+    pub fn get_execution_result_proof(
+        &self,
+        correlation_id: CorrelationId,
+        state_root_hash: Digest,
+        execution_results: &[casper_types::ExecutionResult],
+        index: u64,
+    ) -> Result<ExecutionResultProof, Error> {
+        let checksum_registry_proof =
+            self.get_checksum_registry_proof(correlation_id, state_root_hash)?;
+        let expected = checksum_registry_proof
+            .value()
+            .as_cl_value()
+            .and_then(|cl_value| cl_value.clone().into_t::<ChecksumRegistry>().ok())
+            .and_then(|registry| registry.get(EXECUTION_RESULTS_MERKLE_ROOT_NAME).copied())
+            .ok_or(Error::MissingExecutionResultsMerkleRoot)?;
+
+        let leaves = execution_results
+            .iter()
+            .map(execution_results_proof::execution_result_leaf)
+            .collect::<Result<Vec<_>, _>>()?;
+        let merkle_proof = IndexedMerkleProof::new(leaves, index)
+            .map_err(|error| Error::MerkleConstruction(error.to_string()))?;
+        let computed = merkle_proof.root_hash();
+        if computed != expected {
+            return Err(Error::ExecutionResultsMerkleRootMismatch { expected, computed });
+        }
+
+        Ok(ExecutionResultProof::new(
+            checksum_registry_proof,
+            merkle_proof,
+        ))
+    }
+}
+
+/// Returns the address of the seed URef of the dictionary entry `value`, and the entry's serialized
+/// length, if `value` is a dictionary entry.
+fn dictionary_entry(value: StoredValue) -> Option<(URefAddr, u64)> {
+    let cl_value = match value {
+        StoredValue::CLValue(cl_value) => cl_value,
+        _ => return None,
+    };
+    let entry_bytes = cl_value.serialized_length() as u64;
+    let seed_uref_addr = cl_value
+        .into_t::<DictionaryValue>()
+        .ok()
+        .and_then(|value| URefAddr::try_from(value.seed_uref_addr()).ok())?;
+    Some((seed_uref_addr, entry_bytes))
 }
 
 fn should_charge_for_errors_in_wasm(execution_result: &ExecutionResult) -> bool {
     match execution_result {
-        ExecutionResult::Failure {
-            error,
-            transfers: _,
-            cost: _,
-            execution_journal: _,
-        } => match error {
+        ExecutionResult::Failure { error, .. } => match error {
             Error::Exec(err) => match err {
                 ExecError::WasmPreprocessing(_) | ExecError::UnsupportedWasmStart => true,
                 ExecError::Storage(_)
@@ -2170,7 +3874,12 @@ fn should_charge_for_errors_in_wasm(execution_result: &ExecutionResult) -> bool
                 | ExecError::RuntimeStackOverflow
                 | ExecError::ValueTooLarge
                 | ExecError::MissingRuntimeStack
-                | ExecError::DisabledContract(_) => false,
+                | ExecError::DisabledContract(_)
+                | ExecError::ReturnValueTooLarge { .. }
+                | ExecError::ContractPackagePaused(_)
+                | ExecError::DeprecatedHostFunction(_)
+                | ExecError::InactiveHostFunction(_)
+                | ExecError::CallerNotAllowed { .. } => false,
             },
             Error::WasmPreprocessing(_) => true,
             Error::WasmSerialization(_) => true,
@@ -2192,13 +3901,23 @@ fn should_charge_for_errors_in_wasm(execution_result: &ExecutionResult) -> bool
             | Error::MissingSystemContractRegistry
             | Error::MissingSystemContractHash(_)
             | Error::MissingChecksumRegistry
+            | Error::MissingExecutionResultsMerkleRoot
+            | Error::ExecutionResultsMerkleRootMismatch { .. }
+            | Error::MerkleConstruction(_)
             | Error::RuntimeStackOverflow
             | Error::FailedToGetWithdrawKeys
             | Error::FailedToGetStoredWithdraws
             | Error::FailedToGetWithdrawPurses
+            | Error::FailedToGetUnbondingPurses
+            | Error::WithdrawMigrationMismatch { .. }
             | Error::FailedToRetrieveUnbondingDelay
             | Error::FailedToRetrieveEraId
-            | Error::MissingTrieNodeChildren(_) => false,
+            | Error::MissingTrieNodeChildren(_)
+            | Error::InvalidAccountSequence { .. }
+            | Error::DisallowedSessionCode
+            | Error::NoPaymentLane
+            | Error::MissingGasLimit
+            | Error::ComputationQuotaExceeded { .. } => false,
         },
         ExecutionResult::Success { .. } => false,
     }
@@ -0,0 +1,262 @@
+//! Support for charging deploys fixed prices per category instead of metering their gas, as
+//! preferred by some private networks.
+//!
+//! Each [`PaymentLane`] admits either native transfers or deploys running session code up to a
+//! given size. A deploy is assigned to the first lane admitting it and is charged the lane's fixed
+//! cost however much gas it uses; gas is still metered to bound execution by the payment made. A
+//! deploy admitted by no lane can't be executed. Block proposers also limit the number of deploys
+//! per lane in a block.
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    Motes, U512,
+};
+
+use crate::core::engine_state::ExecutableDeployItem;
+
+const NATIVE_TRANSFER_TAG: u8 = 0;
+const SESSION_TAG: u8 = 1;
+
+/// The category of deploys a lane admits.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LaneKind {
+    /// Native transfers.
+    NativeTransfer,
+    /// Deploys running session code, whether Wasm carried by the deploy or a stored contract.
+    Session,
+}
+
+/// A category of deploys charged a fixed price.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct PaymentLane {
+    /// The name of the lane, used in logs and errors.
+    pub name: String,
+    /// The category of deploys admitted.
+    pub kind: LaneKind,
+    /// The largest session Wasm admitted, in bytes.  Calls to stored contracts carry no Wasm.
+    ///
+    /// Ignored for native transfers.
+    pub max_session_size: u32,
+    /// The price charged for each deploy in the lane, in motes.
+    pub fixed_cost: u64,
+    /// The maximum number of deploys from the lane a block may contain.
+    pub max_per_block: u32,
+}
+
+impl PaymentLane {
+    /// Returns `true` if a deploy which is a native transfer or not, as given by `is_transfer`, and
+    /// carries `session_size` bytes of session Wasm belongs in this lane.
+    pub fn admits(&self, is_transfer: bool, session_size: usize) -> bool {
+        match self.kind {
+            LaneKind::NativeTransfer => is_transfer,
+            LaneKind::Session => !is_transfer && session_size <= self.max_session_size as usize,
+        }
+    }
+
+    /// Returns the price charged for each deploy in the lane.
+    pub fn fixed_cost(&self) -> Motes {
+        Motes::new(U512::from(self.fixed_cost))
+    }
+}
+
+/// The payment lanes of a network, in order of precedence.
+///
+/// If empty, deploys are charged for the gas they use.
+#[derive(Clone, DataSize, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PaymentLanes(Vec<PaymentLane>);
+
+impl PaymentLanes {
+    /// Creates lanes from a list in order of precedence.
+    pub fn new(lanes: Vec<PaymentLane>) -> Self {
+        PaymentLanes(lanes)
+    }
+
+    /// Returns `true` if deploys are charged fixed prices rather than for the gas they use.
+    pub fn is_enabled(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    /// Returns the lanes in order of precedence.
+    pub fn lanes(&self) -> &[PaymentLane] {
+        &self.0
+    }
+
+    /// Returns the index and definition of the first lane admitting a deploy which is a native
+    /// transfer or not, as given by `is_transfer`, and carries `session_size` bytes of session
+    /// Wasm.
+    pub fn lane_for(
+        &self,
+        is_transfer: bool,
+        session_size: usize,
+    ) -> Option<(usize, &PaymentLane)> {
+        self.0
+            .iter()
+            .enumerate()
+            .find(|(_, lane)| lane.admits(is_transfer, session_size))
+    }
+
+    /// Returns the index and definition of the first lane admitting a deploy with the given
+    /// session code.
+    pub fn lane_for_session(
+        &self,
+        session: &ExecutableDeployItem,
+    ) -> Option<(usize, &PaymentLane)> {
+        self.lane_for(session.is_transfer(), session_wasm_size(session))
+    }
+}
+
+/// Returns the size of the Wasm carried by `session`, which is zero unless it is module bytes.
+pub fn session_wasm_size(session: &ExecutableDeployItem) -> usize {
+    match session {
+        ExecutableDeployItem::ModuleBytes { module_bytes, .. } => module_bytes.len(),
+        _ => 0,
+    }
+}
+
+impl ToBytes for LaneKind {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        match self {
+            LaneKind::NativeTransfer => NATIVE_TRANSFER_TAG.to_bytes(),
+            LaneKind::Session => SESSION_TAG.to_bytes(),
+        }
+    }
+
+    fn serialized_length(&self) -> usize {
+        U8_SERIALIZED_LENGTH
+    }
+}
+
+impl FromBytes for LaneKind {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, remainder) = u8::from_bytes(bytes)?;
+        match tag {
+            NATIVE_TRANSFER_TAG => Ok((LaneKind::NativeTransfer, remainder)),
+            SESSION_TAG => Ok((LaneKind::Session, remainder)),
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+impl ToBytes for PaymentLane {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.name.to_bytes()?);
+        buffer.extend(self.kind.to_bytes()?);
+        buffer.extend(self.max_session_size.to_bytes()?);
+        buffer.extend(self.fixed_cost.to_bytes()?);
+        buffer.extend(self.max_per_block.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.name.serialized_length()
+            + self.kind.serialized_length()
+            + self.max_session_size.serialized_length()
+            + self.fixed_cost.serialized_length()
+            + self.max_per_block.serialized_length()
+    }
+}
+
+impl FromBytes for PaymentLane {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (name, remainder) = String::from_bytes(bytes)?;
+        let (kind, remainder) = LaneKind::from_bytes(remainder)?;
+        let (max_session_size, remainder) = u32::from_bytes(remainder)?;
+        let (fixed_cost, remainder) = u64::from_bytes(remainder)?;
+        let (max_per_block, remainder) = u32::from_bytes(remainder)?;
+        let lane = PaymentLane {
+            name,
+            kind,
+            max_session_size,
+            fixed_cost,
+            max_per_block,
+        };
+        Ok((lane, remainder))
+    }
+}
+
+impl ToBytes for PaymentLanes {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+impl FromBytes for PaymentLanes {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (lanes, remainder) = Vec::<PaymentLane>::from_bytes(bytes)?;
+        Ok((PaymentLanes(lanes), remainder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{contracts::DEFAULT_ENTRY_POINT_NAME, ContractHash, RuntimeArgs};
+
+    use super::*;
+
+    fn lane(name: &str, kind: LaneKind, max_session_size: u32) -> PaymentLane {
+        PaymentLane {
+            name: name.to_string(),
+            kind,
+            max_session_size,
+            fixed_cost: 1_000,
+            max_per_block: 10,
+        }
+    }
+
+    fn module_bytes(size: usize) -> ExecutableDeployItem {
+        ExecutableDeployItem::ModuleBytes {
+            module_bytes: vec![0; size].into(),
+            args: RuntimeArgs::new(),
+        }
+    }
+
+    #[test]
+    fn should_assign_deploys_to_first_admitting_lane() {
+        let lanes = PaymentLanes::new(vec![
+            lane("transfer", LaneKind::NativeTransfer, 0),
+            lane("small", LaneKind::Session, 100),
+            lane("large", LaneKind::Session, 10_000),
+        ]);
+        let lane_index = |session: &ExecutableDeployItem| {
+            lanes.lane_for_session(session).map(|(index, _)| index)
+        };
+
+        let transfer = ExecutableDeployItem::Transfer {
+            args: RuntimeArgs::new(),
+        };
+        let stored_contract = ExecutableDeployItem::StoredContractByHash {
+            hash: ContractHash::new([1; 32]),
+            entry_point: DEFAULT_ENTRY_POINT_NAME.to_string(),
+            args: RuntimeArgs::new(),
+        };
+        assert_eq!(lane_index(&transfer), Some(0));
+        assert_eq!(lane_index(&stored_contract), Some(1));
+        assert_eq!(lane_index(&module_bytes(100)), Some(1));
+        assert_eq!(lane_index(&module_bytes(101)), Some(2));
+        assert_eq!(lane_index(&module_bytes(10_001)), None);
+
+        let no_transfers = PaymentLanes::new(vec![lane("small", LaneKind::Session, 100)]);
+        assert!(no_transfers.lane_for_session(&transfer).is_none());
+        assert!(!PaymentLanes::default().is_enabled());
+    }
+
+    #[test]
+    fn bytesrepr_roundtrip() {
+        bytesrepr::test_serialization_roundtrip(&PaymentLanes::default());
+        bytesrepr::test_serialization_roundtrip(&PaymentLanes::new(vec![
+            lane("transfer", LaneKind::NativeTransfer, 0),
+            lane("small", LaneKind::Session, 100),
+        ]));
+    }
+}
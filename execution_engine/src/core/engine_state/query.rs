@@ -7,7 +7,7 @@ use crate::{
 };
 
 /// Result of a global state query request.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum QueryResult {
     /// Invalid state root hash.
     RootNotFound,
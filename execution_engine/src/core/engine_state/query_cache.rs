@@ -0,0 +1,225 @@
+//! In-memory cache of global state query results.
+//!
+//! Global state under a given root never changes, so the result of querying a key and path under
+//! that root can be cached for as long as it is useful. This speeds up repeated queries, e.g. RPC
+//! clients polling popular contracts, without any risk of serving stale values.
+//!
+//! Entries are grouped by state root. Queries concentrate on the most recent roots and older roots
+//! go cold together, so once the cache exceeds its size limit all results of the least recently
+//! used root are evicted at once.
+use std::{collections::HashMap, sync::Mutex};
+
+use casper_hashing::Digest;
+use casper_types::{bytesrepr::ToBytes, Key};
+
+use super::query::{QueryRequest, QueryResult};
+
+/// The approximate size in bytes of caching `result` for a query of `key` and `path`.
+fn entry_size(key: &Key, path: &[String], result: &QueryResult) -> u64 {
+    let request_size = key.serialized_length() + path.iter().map(String::len).sum::<usize>();
+    let result_size = match result {
+        QueryResult::Success { value, proofs } => {
+            value.serialized_length() + proofs.serialized_length()
+        }
+        QueryResult::ValueNotFound(message) | QueryResult::CircularReference(message) => {
+            message.len()
+        }
+        QueryResult::RootNotFound | QueryResult::DepthLimit { .. } => 0,
+    };
+    (request_size + result_size) as u64
+}
+
+/// The cached results under a single state root.
+#[derive(Debug, Default)]
+struct RootEntries {
+    results: HashMap<(Key, Vec<String>), QueryResult>,
+    /// Total size in bytes of the cached results.
+    size: u64,
+    /// Last use of any of the results, by the cache's logical clock.
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheIndex {
+    roots: HashMap<Digest, RootEntries>,
+    /// Total size in bytes of all cached results.
+    total_size: u64,
+    /// Logical clock used to order uses of state roots.
+    clock: u64,
+}
+
+impl CacheIndex {
+    fn remove_root(&mut self, state_root_hash: &Digest) -> bool {
+        match self.roots.remove(state_root_hash) {
+            Some(entries) => {
+                self.total_size -= entries.size;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the least recently used state root.
+    fn least_recently_used(&self) -> Option<Digest> {
+        self.roots
+            .iter()
+            .min_by_key(|(_, entries)| entries.last_used)
+            .map(|(state_root_hash, _)| *state_root_hash)
+    }
+}
+
+/// A size-limited in-memory cache of global state query results, keyed by state root, key and
+/// path.
+#[derive(Debug)]
+pub struct QueryCache {
+    max_size: u64,
+    index: Mutex<CacheIndex>,
+}
+
+impl QueryCache {
+    /// Creates an empty cache holding at most `max_size` bytes of results.
+    pub fn new(max_size: u64) -> Self {
+        QueryCache {
+            max_size,
+            index: Mutex::new(CacheIndex::default()),
+        }
+    }
+
+    /// Returns the cached result of `request`, if any.
+    pub fn get(&self, request: &QueryRequest) -> Option<QueryResult> {
+        let mut index = self.index.lock().expect("query cache index lock poisoned");
+        index.clock += 1;
+        let clock = index.clock;
+        let entries = index.roots.get_mut(&request.state_hash())?;
+        let result = entries
+            .results
+            .get(&(request.key(), request.path().to_vec()))?
+            .clone();
+        entries.last_used = clock;
+        Some(result)
+    }
+
+    /// Caches the result of `request`, evicting the least recently used state roots if needed.
+    ///
+    /// A missing root is never cached, as it may yet be synced or committed.
+    pub fn insert(&self, request: &QueryRequest, result: &QueryResult) {
+        if matches!(result, QueryResult::RootNotFound) {
+            return;
+        }
+        let size = entry_size(&request.key(), request.path(), result);
+        if size > self.max_size {
+            return;
+        }
+
+        let mut index = self.index.lock().expect("query cache index lock poisoned");
+        index.clock += 1;
+        let clock = index.clock;
+        let entries = index.roots.entry(request.state_hash()).or_default();
+        entries.last_used = clock;
+        let key = (request.key(), request.path().to_vec());
+        if entries.results.insert(key, result.clone()).is_some() {
+            // Results under a root never change, so the size is unaffected.
+            return;
+        }
+        entries.size += size;
+        index.total_size += size;
+
+        while index.total_size > self.max_size {
+            match index.least_recently_used() {
+                Some(state_root_hash) => {
+                    index.remove_root(&state_root_hash);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Evicts all results cached under `state_root_hash`.
+    ///
+    /// Returns `true` if any were cached.
+    pub fn invalidate_root(&self, state_root_hash: &Digest) -> bool {
+        self.index
+            .lock()
+            .expect("query cache index lock poisoned")
+            .remove_root(state_root_hash)
+    }
+
+    /// Returns the total size in bytes of the cached results.
+    pub fn size(&self) -> u64 {
+        self.index
+            .lock()
+            .expect("query cache index lock poisoned")
+            .total_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{CLValue, StoredValue};
+
+    use super::*;
+
+    fn request(root: u8, key: u8) -> QueryRequest {
+        QueryRequest::new(
+            Digest::hash([root]),
+            Key::Hash([key; 32]),
+            vec!["path".to_string()],
+        )
+    }
+
+    fn success(value: u64) -> QueryResult {
+        QueryResult::Success {
+            value: Box::new(StoredValue::CLValue(CLValue::from_t(value).unwrap())),
+            proofs: vec![],
+        }
+    }
+
+    fn cached_value(cache: &QueryCache, request: &QueryRequest) -> Option<StoredValue> {
+        match cache.get(request)? {
+            QueryResult::Success { value, .. } => Some(*value),
+            other => panic!("unexpected cached result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_evict_least_recently_used_root() {
+        let entry = entry_size(&request(0, 0).key(), request(0, 0).path(), &success(0));
+        let cache = QueryCache::new(3 * entry);
+
+        cache.insert(&request(1, 1), &success(1));
+        cache.insert(&request(1, 2), &success(2));
+        cache.insert(&request(2, 1), &success(3));
+        cache.insert(&request(2, 1), &success(3));
+        assert_eq!(cache.size(), 3 * entry);
+
+        // Root 1 is used more recently than root 2, so inserting a fourth result evicts root 2.
+        assert!(cached_value(&cache, &request(1, 1)).is_some());
+        cache.insert(&request(3, 1), &success(4));
+        assert!(cached_value(&cache, &request(2, 1)).is_none());
+        assert_eq!(
+            cached_value(&cache, &request(1, 2)),
+            Some(StoredValue::CLValue(CLValue::from_t(2_u64).unwrap()))
+        );
+        assert!(cached_value(&cache, &request(3, 1)).is_some());
+
+        assert!(cache.invalidate_root(&Digest::hash([1])));
+        assert!(cache.get(&request(1, 1)).is_none());
+        assert_eq!(cache.size(), entry);
+    }
+
+    #[test]
+    fn should_not_cache_missing_root() {
+        let cache = QueryCache::new(u64::MAX);
+        cache.insert(&request(1, 1), &QueryResult::RootNotFound);
+        assert!(cache.get(&request(1, 1)).is_none());
+
+        cache.insert(
+            &request(1, 1),
+            &QueryResult::ValueNotFound("gone".to_string()),
+        );
+        assert!(matches!(
+            cache.get(&request(1, 1)),
+            Some(QueryResult::ValueNotFound(_))
+        ));
+    }
+}
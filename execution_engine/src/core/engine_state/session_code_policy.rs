@@ -0,0 +1,152 @@
+//! Support for restricting which Wasm deploys may execute, as needed by permissioned networks.
+use std::collections::BTreeSet;
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use casper_hashing::Digest;
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    Phase,
+};
+
+use crate::core::engine_state::ExecutableDeployItem;
+
+const UNRESTRICTED_TAG: u8 = 0;
+const ALLOW_LIST_TAG: u8 = 1;
+const STORED_CONTRACTS_ONLY_TAG: u8 = 2;
+
+/// Policy deciding which session and payment code a deploy may carry.
+///
+/// Calls to stored contracts, native transfers and standard payment are accepted under every
+/// policy, since they don't run Wasm supplied by the deploy itself.
+#[derive(Clone, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionCodePolicy {
+    /// Any Wasm is accepted.
+    Unrestricted,
+    /// Only Wasm whose Blake2b hash is in the list is accepted.
+    AllowList(BTreeSet<Digest>),
+    /// No Wasm is accepted; deploys may only call stored contracts.
+    StoredContractsOnly,
+}
+
+impl Default for SessionCodePolicy {
+    fn default() -> Self {
+        SessionCodePolicy::Unrestricted
+    }
+}
+
+impl SessionCodePolicy {
+    /// Returns `true` if `deploy_item` may be executed in `phase` under this policy.
+    pub fn is_allowed(&self, deploy_item: &ExecutableDeployItem, phase: Phase) -> bool {
+        let module_bytes = match deploy_item {
+            ExecutableDeployItem::ModuleBytes { module_bytes, .. } => module_bytes,
+            ExecutableDeployItem::StoredContractByHash { .. }
+            | ExecutableDeployItem::StoredContractByName { .. }
+            | ExecutableDeployItem::StoredVersionedContractByHash { .. }
+            | ExecutableDeployItem::StoredVersionedContractByName { .. }
+            | ExecutableDeployItem::Transfer { .. } => return true,
+        };
+        if deploy_item.is_standard_payment(phase) {
+            return true;
+        }
+        match self {
+            SessionCodePolicy::Unrestricted => true,
+            SessionCodePolicy::AllowList(allowed_hashes) => {
+                allowed_hashes.contains(&Digest::hash(module_bytes.as_slice()))
+            }
+            SessionCodePolicy::StoredContractsOnly => false,
+        }
+    }
+}
+
+impl ToBytes for SessionCodePolicy {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        match self {
+            SessionCodePolicy::Unrestricted => buffer.push(UNRESTRICTED_TAG),
+            SessionCodePolicy::AllowList(allowed_hashes) => {
+                buffer.push(ALLOW_LIST_TAG);
+                buffer.append(&mut allowed_hashes.to_bytes()?);
+            }
+            SessionCodePolicy::StoredContractsOnly => buffer.push(STORED_CONTRACTS_ONLY_TAG),
+        }
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        U8_SERIALIZED_LENGTH
+            + match self {
+                SessionCodePolicy::AllowList(allowed_hashes) => allowed_hashes.serialized_length(),
+                SessionCodePolicy::Unrestricted | SessionCodePolicy::StoredContractsOnly => 0,
+            }
+    }
+}
+
+impl FromBytes for SessionCodePolicy {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, remainder) = u8::from_bytes(bytes)?;
+        match tag {
+            UNRESTRICTED_TAG => Ok((SessionCodePolicy::Unrestricted, remainder)),
+            ALLOW_LIST_TAG => {
+                let (allowed_hashes, remainder) = BTreeSet::<Digest>::from_bytes(remainder)?;
+                Ok((SessionCodePolicy::AllowList(allowed_hashes), remainder))
+            }
+            STORED_CONTRACTS_ONLY_TAG => Ok((SessionCodePolicy::StoredContractsOnly, remainder)),
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{contracts::DEFAULT_ENTRY_POINT_NAME, ContractHash, RuntimeArgs};
+
+    use super::*;
+
+    fn module_bytes(bytes: &[u8]) -> ExecutableDeployItem {
+        ExecutableDeployItem::ModuleBytes {
+            module_bytes: bytes.to_vec().into(),
+            args: RuntimeArgs::new(),
+        }
+    }
+
+    #[test]
+    fn should_only_allow_listed_wasm() {
+        let allowed = module_bytes(b"allowed");
+        let other = module_bytes(b"other");
+        let stored_contract = ExecutableDeployItem::StoredContractByHash {
+            hash: ContractHash::new([1; 32]),
+            entry_point: DEFAULT_ENTRY_POINT_NAME.to_string(),
+            args: RuntimeArgs::new(),
+        };
+        let standard_payment = module_bytes(&[]);
+
+        let allow_list =
+            SessionCodePolicy::AllowList(BTreeSet::from([Digest::hash(b"allowed".as_slice())]));
+        assert!(allow_list.is_allowed(&allowed, Phase::Session));
+        assert!(!allow_list.is_allowed(&other, Phase::Session));
+        assert!(!allow_list.is_allowed(&other, Phase::Payment));
+        assert!(allow_list.is_allowed(&stored_contract, Phase::Session));
+        assert!(allow_list.is_allowed(&standard_payment, Phase::Payment));
+
+        let stored_only = SessionCodePolicy::StoredContractsOnly;
+        assert!(!stored_only.is_allowed(&allowed, Phase::Session));
+        assert!(!stored_only.is_allowed(&standard_payment, Phase::Session));
+        assert!(stored_only.is_allowed(&standard_payment, Phase::Payment));
+        assert!(stored_only.is_allowed(&stored_contract, Phase::Session));
+
+        assert!(SessionCodePolicy::Unrestricted.is_allowed(&other, Phase::Session));
+    }
+
+    #[test]
+    fn bytesrepr_roundtrip() {
+        bytesrepr::test_serialization_roundtrip(&SessionCodePolicy::Unrestricted);
+        bytesrepr::test_serialization_roundtrip(&SessionCodePolicy::StoredContractsOnly);
+        bytesrepr::test_serialization_roundtrip(&SessionCodePolicy::AllowList(BTreeSet::from([
+            Digest::hash([1]),
+            Digest::hash([2]),
+        ])));
+    }
+}
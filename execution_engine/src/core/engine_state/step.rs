@@ -5,7 +5,9 @@
 use std::{collections::BTreeMap, vec::Vec};
 
 use casper_hashing::Digest;
-use casper_types::{bytesrepr, CLValueError, EraId, ProtocolVersion, PublicKey};
+use casper_types::{
+    bytesrepr, system::auction::EraRewardReport, CLValueError, EraId, ProtocolVersion, PublicKey,
+};
 
 use crate::{
     core::{engine_state::Error, execution, runtime::stack::RuntimeStackOverflow},
@@ -151,6 +153,9 @@ pub enum StepError {
     /// Error executing a distribute operation.
     #[error("Distribute error: {0}")]
     DistributeError(Error),
+    /// Error charging storage rent.
+    #[error("Storage rent error: {0}")]
+    StorageRentError(Error),
     /// Invalid protocol version.
     #[error("Invalid protocol version: {0}")]
     InvalidProtocolVersion(ProtocolVersion),
@@ -193,4 +198,6 @@ pub struct StepSuccess {
     pub post_state_hash: Digest,
     /// Effects of executing a step request.
     pub execution_journal: ExecutionJournal,
+    /// The rewards distributed and the tokens burned by the step.
+    pub reward_report: EraRewardReport,
 }
@@ -0,0 +1,178 @@
+//! Support for charging contracts rent for the global state they occupy.
+//!
+//! Private chains worried about unbounded growth of global state can opt in to storage rent (see
+//! [`EngineConfig::storage_rent`](super::EngineConfig::storage_rent)).  At every step, each
+//! non-system contract is charged a fixed number of motes per byte it stores for the era which just
+//! ended.
+//!
+//! The bytes a contract stores are counted as they are written, so that charging rent doesn't need
+//! to walk global state.  A contract stores the contract itself and its Wasm, and every value it
+//! writes under a URef or a dictionary item key while executing, net of the values overwritten.
+//! Values written by accounts' session code aren't charged to any contract.  Contracts are charged
+//! from the first write counted for them while storage rent is enabled, and are listed under
+//! [`storage_rent_registry_key`].
+//!
+//! Rent is taken from the purse under the contract's [`STORAGE_RENT_PURSE_NAMED_KEY`] named key and
+//! burned, reducing the total supply.  A contract without such a purse, or whose purse can't cover
+//! the full rent, pays nothing and falls into arrears.  Once it has been in arrears for more than
+//! the configured grace eras it is marked as evicted.  Eviction is only recorded: the contract and
+//! its data stay in global state, and paying the rent in a later era clears the mark.
+//!
+//! The outcome of the last charge is stored per contract under [`storage_rent_key`].
+use casper_types::{
+    crypto, CLValue, CLValueError, ContractHash, EraId, Key, StoredValue, KEY_HASH_LENGTH, U512,
+};
+
+/// The named key of a contract under which the purse its storage rent is paid from is stored.
+pub const STORAGE_RENT_PURSE_NAMED_KEY: &str = "storage_rent_purse";
+
+const STORAGE_RENT_KEY_PREFIX: &[u8] = b"storage-rent";
+
+const STORAGE_RENT_REGISTRY_PREIMAGE: &[u8] = b"storage-rent-registry";
+
+/// Returns the global state key under which the storage rent record of the contract
+/// `contract_hash` is stored.
+pub fn storage_rent_key(contract_hash: ContractHash) -> Key {
+    let mut preimage = Vec::with_capacity(STORAGE_RENT_KEY_PREFIX.len() + KEY_HASH_LENGTH);
+    preimage.extend_from_slice(STORAGE_RENT_KEY_PREFIX);
+    preimage.extend_from_slice(contract_hash.as_bytes());
+    Key::Hash(crypto::blake2b(preimage))
+}
+
+/// Returns the global state key under which the hashes of all the contracts charged storage rent
+/// are stored.
+pub fn storage_rent_registry_key() -> Key {
+    Key::Hash(crypto::blake2b(STORAGE_RENT_REGISTRY_PREIMAGE))
+}
+
+/// The rate storage rent is charged at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StorageRentRate {
+    /// The motes charged per stored byte per era.
+    pub motes_per_byte_per_era: u64,
+    /// The number of eras a contract may be in arrears before it is marked as evicted.
+    pub grace_eras: u64,
+}
+
+impl StorageRentRate {
+    /// Returns the rent for storing `stored_bytes` for one era.
+    pub fn rent(&self, stored_bytes: u64) -> U512 {
+        U512::from(stored_bytes) * U512::from(self.motes_per_byte_per_era)
+    }
+}
+
+/// The outcome of the last storage rent charge of a contract.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StorageRentRecord {
+    /// The era rent was last charged for.
+    pub era_id: EraId,
+    /// The number of bytes the contract stores.
+    pub stored_bytes: u64,
+    /// The number of consecutive eras, up to and including `era_id`, the rent wasn't paid for.
+    pub unpaid_eras: u64,
+    /// Whether the contract has been in arrears for longer than the grace period.
+    pub evicted: bool,
+}
+
+impl StorageRentRecord {
+    /// Returns the record after the contract overwrote `old_bytes` with `new_bytes`.
+    pub fn resize(&self, old_bytes: u64, new_bytes: u64) -> StorageRentRecord {
+        StorageRentRecord {
+            stored_bytes: self
+                .stored_bytes
+                .saturating_add(new_bytes)
+                .saturating_sub(old_bytes),
+            ..*self
+        }
+    }
+
+    /// Returns the record after charging rent for the stored bytes in `era_id`, `paid` being
+    /// whether the contract paid it.
+    pub fn charge(&self, rate: StorageRentRate, era_id: EraId, paid: bool) -> StorageRentRecord {
+        let unpaid_eras = if paid {
+            0
+        } else {
+            self.unpaid_eras.saturating_add(1)
+        };
+        StorageRentRecord {
+            era_id,
+            stored_bytes: self.stored_bytes,
+            unpaid_eras,
+            evicted: unpaid_eras > rate.grace_eras,
+        }
+    }
+
+    /// Parses a record from the value stored under [`storage_rent_key`].
+    pub fn from_cl_value(cl_value: CLValue) -> Result<Self, CLValueError> {
+        let (era_id, (stored_bytes, unpaid_eras), evicted) =
+            cl_value.into_t::<(EraId, (u64, u64), bool)>()?;
+        Ok(StorageRentRecord {
+            era_id,
+            stored_bytes,
+            unpaid_eras,
+            evicted,
+        })
+    }
+
+    /// Returns the stored value holding this record.
+    pub(crate) fn stored_value(&self) -> StoredValue {
+        // Serializing an `(EraId, (u64, u64), bool)` can't fail.
+        let cl_value = CLValue::from_t((
+            self.era_id,
+            (self.stored_bytes, self.unpaid_eras),
+            self.evicted,
+        ))
+        .expect("should create CLValue from (EraId, (u64, u64), bool)");
+        StoredValue::CLValue(cl_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: StorageRentRate = StorageRentRate {
+        motes_per_byte_per_era: 3,
+        grace_eras: 2,
+    };
+
+    #[test]
+    fn should_mark_evicted_after_grace_eras_and_clear_once_paid() {
+        assert_eq!(RATE.rent(1_000), U512::from(3_000));
+
+        let record = StorageRentRecord::default()
+            .resize(0, 100)
+            .charge(RATE, EraId::new(1), false);
+        assert_eq!(record.unpaid_eras, 1);
+        assert!(!record.evicted);
+        let record = record.charge(RATE, EraId::new(2), false);
+        assert!(!record.evicted);
+        let record = record.resize(30, 50).charge(RATE, EraId::new(3), false);
+        assert_eq!(record.unpaid_eras, 3);
+        assert_eq!(record.stored_bytes, 120);
+        assert!(record.evicted);
+
+        let record = record.charge(RATE, EraId::new(4), true);
+        assert_eq!(
+            record,
+            StorageRentRecord {
+                era_id: EraId::new(4),
+                stored_bytes: 120,
+                unpaid_eras: 0,
+                evicted: false,
+            }
+        );
+    }
+
+    #[test]
+    fn should_roundtrip_stored_value() {
+        let record = StorageRentRecord::default()
+            .resize(0, 42)
+            .charge(RATE, EraId::new(7), false);
+        let cl_value = match record.stored_value() {
+            StoredValue::CLValue(cl_value) => cl_value,
+            other => panic!("unexpected stored value {:?}", other),
+        };
+        assert_eq!(StorageRentRecord::from_cl_value(cl_value).unwrap(), record);
+    }
+}
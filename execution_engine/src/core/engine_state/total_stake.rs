@@ -0,0 +1,85 @@
+//! Support for obtaining the total amount staked by an account.
+use std::collections::BTreeMap;
+
+use casper_hashing::Digest;
+use casper_types::{Key, PublicKey, StoredValue, U512};
+
+use crate::storage::trie::merkle_proof::TrieMerkleProof;
+
+/// Represents a request to obtain the total amount staked by an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TotalStakeRequest {
+    state_hash: Digest,
+    public_key: PublicKey,
+}
+
+impl TotalStakeRequest {
+    /// Creates a new [`TotalStakeRequest`].
+    pub fn new(state_hash: Digest, public_key: PublicKey) -> Self {
+        TotalStakeRequest {
+            state_hash,
+            public_key,
+        }
+    }
+
+    /// Returns a state hash.
+    pub fn state_hash(&self) -> Digest {
+        self.state_hash
+    }
+
+    /// Returns the public key of the account whose stake is requested.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+}
+
+/// The amounts staked by an account, broken down by where they are held.
+#[derive(Debug, Default)]
+pub struct TotalStake {
+    /// The amount staked in the account's own validator bid, if any.
+    pub validator_stake: U512,
+    /// The amounts delegated by the account, keyed by the validator they are delegated to.
+    pub delegations: BTreeMap<PublicKey, U512>,
+    /// The amount held in unbonding purses awaiting payout to the account.
+    pub unbonding: U512,
+    /// Proofs of every bid and unbond entry the amounts above were taken from.
+    ///
+    /// These prove that the included entries exist under the requested state root hash, but not
+    /// that no other entries relating to the account exist.
+    pub proofs: Vec<TrieMerkleProof<Key, StoredValue>>,
+}
+
+impl TotalStake {
+    /// Returns the sum of the validator stake, all delegations and all unbonding amounts.
+    pub fn total(&self) -> U512 {
+        self.delegations
+            .values()
+            .fold(self.validator_stake, |total, amount| {
+                total.saturating_add(*amount)
+            })
+            .saturating_add(self.unbonding)
+    }
+}
+
+/// Represents a result of a `get_total_stake` request.
+#[derive(Debug)]
+pub enum TotalStakeResult {
+    /// Invalid state root hash.
+    RootNotFound,
+    /// Contains the amounts staked by the account.
+    Success {
+        /// The amounts staked by the account.
+        total_stake: Box<TotalStake>,
+    },
+}
+
+impl TotalStakeResult {
+    /// Returns wrapped [`TotalStake`] if this represents a successful query result.
+    pub fn into_success(self) -> Option<TotalStake> {
+        if let Self::Success { total_stake } = self {
+            Some(*total_stake)
+        } else {
+            None
+        }
+    }
+}
@@ -53,6 +53,7 @@ pub struct UpgradeConfig {
     new_unbonding_delay: Option<u64>,
     global_state_update: BTreeMap<Key, StoredValue>,
     chainspec_registry: ChainspecRegistry,
+    migrate_legacy_withdraws: bool,
 }
 
 impl UpgradeConfig {
@@ -70,6 +71,7 @@ impl UpgradeConfig {
         new_unbonding_delay: Option<u64>,
         global_state_update: BTreeMap<Key, StoredValue>,
         chainspec_registry: ChainspecRegistry,
+        migrate_legacy_withdraws: bool,
     ) -> Self {
         UpgradeConfig {
             pre_state_hash,
@@ -83,6 +85,7 @@ impl UpgradeConfig {
             new_unbonding_delay,
             global_state_update,
             chainspec_registry,
+            migrate_legacy_withdraws,
         }
     }
 
@@ -141,6 +144,11 @@ impl UpgradeConfig {
         &self.chainspec_registry
     }
 
+    /// Returns whether legacy withdraw purses should be migrated into unbonding purses.
+    pub fn migrate_legacy_withdraws(&self) -> bool {
+        self.migrate_legacy_withdraws
+    }
+
     /// Sets new pre state hash.
     pub fn with_pre_state_hash(&mut self, pre_state_hash: Digest) {
         self.pre_state_hash = pre_state_hash;
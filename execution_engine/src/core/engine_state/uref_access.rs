@@ -0,0 +1,222 @@
+//! Support for auditing which entities hold a given [`URef`].
+use std::collections::BTreeMap;
+
+use casper_hashing::Digest;
+use casper_types::{AccessRights, Key, StoredValue, URef, URefAddr};
+
+/// Represents a request to list the entities holding a [`URef`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct URefAccessRequest {
+    state_hash: Digest,
+    uref_addr: URefAddr,
+}
+
+impl URefAccessRequest {
+    /// Creates a new [`URefAccessRequest`].
+    pub fn new(state_hash: Digest, uref_addr: URefAddr) -> Self {
+        URefAccessRequest {
+            state_hash,
+            uref_addr,
+        }
+    }
+
+    /// Returns a state hash.
+    pub fn state_hash(&self) -> Digest {
+        self.state_hash
+    }
+
+    /// Returns the address of the [`URef`] whose holders are requested.
+    pub fn uref_addr(&self) -> URefAddr {
+        self.uref_addr
+    }
+}
+
+/// How an entity holds a [`URef`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum URefGrant {
+    /// The `URef` is stored in the entity's named keys under the given name.
+    NamedKey(String),
+    /// The `URef` is the main purse of the account.
+    MainPurse,
+    /// The `URef` is the access key of the contract package.
+    ContractPackageAccessKey,
+}
+
+/// A single grant of access to a [`URef`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct URefAccess {
+    /// The account, contract or contract package holding the `URef`.
+    pub holder: Key,
+    /// How the `URef` is held.
+    pub grant: URefGrant,
+    /// The access rights the holder has been granted.
+    pub access_rights: AccessRights,
+}
+
+/// A reverse index from [`URef`] addresses to the entities holding them under a state root.
+///
+/// Built on demand by scanning all accounts and contracts, so it is expensive and intended for
+/// audits rather than for use during execution.
+#[derive(Debug, Default)]
+pub struct URefAccessIndex {
+    accesses: BTreeMap<URefAddr, Vec<URefAccess>>,
+}
+
+impl URefAccessIndex {
+    /// Returns all grants of access to the `URef` with the given address.
+    pub fn accesses(&self, uref_addr: &URefAddr) -> &[URefAccess] {
+        self.accesses
+            .get(uref_addr)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of distinct `URef` addresses held by any entity.
+    pub fn len(&self) -> usize {
+        self.accesses.len()
+    }
+
+    /// Returns `true` if no entity holds any `URef`.
+    pub fn is_empty(&self) -> bool {
+        self.accesses.is_empty()
+    }
+
+    /// Records the `URef`s held by the entity stored under `holder`.
+    ///
+    /// Values other than accounts, contracts and contract packages are ignored.
+    pub(crate) fn insert_entity(&mut self, holder: Key, value: &StoredValue) {
+        match value {
+            StoredValue::Account(account) => {
+                self.insert(holder, URefGrant::MainPurse, account.main_purse());
+                self.insert_named_keys(holder, account.named_keys().iter());
+            }
+            StoredValue::Contract(contract) => {
+                self.insert_named_keys(holder, contract.named_keys().iter());
+            }
+            StoredValue::ContractPackage(contract_package) => {
+                self.insert(
+                    holder,
+                    URefGrant::ContractPackageAccessKey,
+                    contract_package.access_key(),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn insert_named_keys<'a, I>(&mut self, holder: Key, named_keys: I)
+    where
+        I: Iterator<Item = (&'a String, &'a Key)>,
+    {
+        for (name, key) in named_keys {
+            if let Some(uref) = key.as_uref() {
+                self.insert(holder, URefGrant::NamedKey(name.clone()), *uref);
+            }
+        }
+    }
+
+    fn insert(&mut self, holder: Key, grant: URefGrant, uref: URef) {
+        self.accesses
+            .entry(uref.addr())
+            .or_default()
+            .push(URefAccess {
+                holder,
+                grant,
+                access_rights: uref.access_rights(),
+            });
+    }
+}
+
+/// Represents a result of a `get_uref_access` request.
+#[derive(Debug)]
+pub enum URefAccessResult {
+    /// Invalid state root hash.
+    RootNotFound,
+    /// Contains all grants of access to the requested `URef`.
+    Success {
+        /// The grants, ordered by holder.
+        accesses: Vec<URefAccess>,
+    },
+}
+
+impl URefAccessResult {
+    /// Returns the wrapped grants if this represents a successful query result.
+    pub fn into_success(self) -> Option<Vec<URefAccess>> {
+        if let Self::Success { accesses } = self {
+            Some(accesses)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{
+        account::{Account, AccountHash},
+        contracts::NamedKeys,
+        ContractPackage, ContractPackageHash, ContractWasm,
+    };
+
+    use super::*;
+
+    #[test]
+    fn should_index_named_keys_and_main_purses() {
+        let audited = URef::new([1; 32], AccessRights::READ_ADD_WRITE);
+        let main_purse = URef::new([2; 32], AccessRights::READ_ADD_WRITE);
+
+        let mut named_keys = NamedKeys::new();
+        named_keys.insert(
+            "purse".to_string(),
+            Key::URef(audited.with_access_rights(AccessRights::ADD)),
+        );
+        named_keys.insert(
+            "account".to_string(),
+            Key::Account(AccountHash::new([3; 32])),
+        );
+        let account = Account::create(AccountHash::new([4; 32]), named_keys, main_purse);
+        let account_key = Key::Account(AccountHash::new([4; 32]));
+        let contract_package = ContractPackage::new(
+            audited,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        let package_key = Key::from(ContractPackageHash::new([5; 32]));
+
+        let mut index = URefAccessIndex::default();
+        index.insert_entity(account_key, &StoredValue::Account(account));
+        index.insert_entity(package_key, &StoredValue::ContractPackage(contract_package));
+        index.insert_entity(
+            Key::Hash([6; 32]),
+            &StoredValue::ContractWasm(ContractWasm::new(vec![])),
+        );
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(
+            index.accesses(&audited.addr()),
+            &[
+                URefAccess {
+                    holder: account_key,
+                    grant: URefGrant::NamedKey("purse".to_string()),
+                    access_rights: AccessRights::ADD,
+                },
+                URefAccess {
+                    holder: package_key,
+                    grant: URefGrant::ContractPackageAccessKey,
+                    access_rights: AccessRights::READ_ADD_WRITE,
+                },
+            ]
+        );
+        assert_eq!(
+            index.accesses(&main_purse.addr()),
+            &[URefAccess {
+                holder: account_key,
+                grant: URefGrant::MainPurse,
+                access_rights: AccessRights::READ_ADD_WRITE,
+            }]
+        );
+        assert!(index.accesses(&[7; 32]).is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+//! Support for calling a contract's entry point in read-only mode, without a deploy.
+use casper_hashing::Digest;
+use casper_types::{
+    account::AccountHash, BlockTime, CLValue, ContractHash, Gas, ProtocolVersion, RuntimeArgs,
+};
+
+use crate::core::engine_state::Error;
+
+/// Represents a request to call a contract's entry point without committing any effects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewRequest {
+    state_hash: Digest,
+    protocol_version: ProtocolVersion,
+    block_time: BlockTime,
+    caller: AccountHash,
+    contract_hash: ContractHash,
+    entry_point: String,
+    args: RuntimeArgs,
+    gas_limit: Gas,
+}
+
+impl ViewRequest {
+    /// Creates new request.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        state_hash: Digest,
+        protocol_version: ProtocolVersion,
+        block_time: BlockTime,
+        caller: AccountHash,
+        contract_hash: ContractHash,
+        entry_point: String,
+        args: RuntimeArgs,
+        gas_limit: Gas,
+    ) -> Self {
+        ViewRequest {
+            state_hash,
+            protocol_version,
+            block_time,
+            caller,
+            contract_hash,
+            entry_point,
+            args,
+            gas_limit,
+        }
+    }
+
+    /// Returns state root hash.
+    pub fn state_hash(&self) -> Digest {
+        self.state_hash
+    }
+
+    /// Returns protocol version.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
+
+    /// Returns the block time the call observes.
+    pub fn block_time(&self) -> BlockTime {
+        self.block_time
+    }
+
+    /// Returns the account the call is made from.
+    pub fn caller(&self) -> AccountHash {
+        self.caller
+    }
+
+    /// Returns the hash of the contract to call.
+    pub fn contract_hash(&self) -> ContractHash {
+        self.contract_hash
+    }
+
+    /// Returns the name of the entry point to call.
+    pub fn entry_point(&self) -> &str {
+        &self.entry_point
+    }
+
+    /// Returns the arguments passed to the entry point.
+    pub fn args(&self) -> &RuntimeArgs {
+        &self.args
+    }
+
+    /// Returns the gas the call may use.
+    pub fn gas_limit(&self) -> Gas {
+        self.gas_limit
+    }
+
+    /// Sets the gas the call may use.
+    pub fn with_gas_limit(mut self, gas_limit: Gas) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+}
+
+/// Represents a result of a `call_view` request.
+#[derive(Debug)]
+pub enum ViewResult {
+    /// Invalid state root hash.
+    RootNotFound,
+    /// The entry point returned a value without writing to global state.
+    Success {
+        /// The value returned by the entry point.
+        value: CLValue,
+        /// The gas used by the call.
+        cost: Gas,
+    },
+    /// The call failed, ran out of gas or attempted to write to global state.
+    Failure {
+        /// The error the call failed with.
+        error: Error,
+        /// The gas used by the call.
+        cost: Gas,
+    },
+}
@@ -4,7 +4,7 @@ use thiserror::Error;
 
 use casper_types::{
     account::{AddKeyFailure, RemoveKeyFailure, SetThresholdFailure, UpdateKeyFailure},
-    bytesrepr, system, AccessRights, ApiError, CLType, CLValueError, ContractHash,
+    bytesrepr, system, AccessRights, AllowedCaller, ApiError, CLType, CLValueError, ContractHash,
     ContractPackageHash, ContractVersionKey, ContractWasmHash, Key, StoredValueTypeMismatch, URef,
 };
 
@@ -173,6 +173,31 @@ pub enum Error {
     /// Contract is disabled.
     #[error("Contract is disabled")]
     DisabledContract(ContractHash),
+    /// A contract returned a value larger than the chainspec allows.
+    #[error("Return value of {size} bytes exceeds maximum of {max} bytes")]
+    ReturnValueTooLarge {
+        /// Size in bytes of the serialized return value.
+        size: usize,
+        /// Maximum size in bytes of a return value.
+        max: u32,
+    },
+    /// Contract package is paused by its owner.
+    #[error("Contract package is paused")]
+    ContractPackagePaused(ContractPackageHash),
+    /// A host function rejected by a deprecation in force was called.
+    #[error("Host function {0} is deprecated")]
+    DeprecatedHostFunction(String),
+    /// A host function was called under a protocol version preceding its activation.
+    #[error("Host function {0} is not active yet")]
+    InactiveHostFunction(String),
+    /// An entry point restricted to allowed callers was called by a caller not among them.
+    #[error("Caller {caller:?} is not allowed to call entry point {entry_point}")]
+    CallerNotAllowed {
+        /// The name of the entry point.
+        entry_point: String,
+        /// The account or contract which called the entry point.
+        caller: AllowedCaller,
+    },
 }
 
 impl From<wasm_prep::PreprocessingError> for Error {
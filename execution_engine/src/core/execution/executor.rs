@@ -1,26 +1,29 @@
-use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc, sync::Arc};
 
 use casper_types::{
     account::{Account, AccountHash},
     bytesrepr::FromBytes,
     contracts::NamedKeys,
     system::{auction, handle_payment, mint, AUCTION, HANDLE_PAYMENT, MINT},
-    BlockTime, CLTyped, ContextAccessRights, DeployHash, EntryPointType, Gas, Key, Phase,
-    ProtocolVersion, RuntimeArgs, StoredValue, U512,
+    BlockTime, CLTyped, CLValue, ContextAccessRights, ContractHash, DeployHash, EntryPointType,
+    Gas, Key, Phase, ProtocolVersion, RuntimeArgs, StoredValue, U512,
 };
 
 use crate::{
     core::{
         engine_state::{
-            executable_deploy_item::ExecutionKind, execution_result::ExecutionResult, EngineConfig,
-            ExecError,
+            executable_deploy_item::ExecutionKind, execution_result::ExecutionResult,
+            host_function_deprecations::HostFunctionDeprecations, EngineConfig, ExecError,
         },
         execution::{address_generator::AddressGenerator, Error},
         runtime::{Runtime, RuntimeStack},
         runtime_context::RuntimeContext,
         tracking_copy::{TrackingCopy, TrackingCopyExt},
     },
-    shared::newtypes::CorrelationId,
+    shared::{
+        execution_journal::ExecutionJournal, newtypes::CorrelationId,
+        wasm_prep_cache::PreprocessedModuleCache,
+    },
     storage::global_state::StateReader,
 };
 
@@ -35,12 +38,36 @@ fn try_get_amount(runtime_args: &RuntimeArgs) -> Result<U512, ExecError> {
 /// Executor object deals with execution of WASM modules.
 pub struct Executor {
     config: EngineConfig,
+    preprocessed_module_cache: Option<Arc<PreprocessedModuleCache>>,
+    host_function_deprecations: Arc<HostFunctionDeprecations>,
 }
 
 impl Executor {
     /// Creates new executor object.
     pub fn new(config: EngineConfig) -> Self {
-        Executor { config }
+        Executor {
+            config,
+            preprocessed_module_cache: None,
+            host_function_deprecations: Arc::default(),
+        }
+    }
+
+    /// Sets the deprecated host functions.
+    pub fn with_host_function_deprecations(
+        mut self,
+        host_function_deprecations: Arc<HostFunctionDeprecations>,
+    ) -> Self {
+        self.host_function_deprecations = host_function_deprecations;
+        self
+    }
+
+    /// Sets the cache used to avoid preprocessing the same module bytes repeatedly.
+    pub fn with_preprocessed_module_cache(
+        mut self,
+        preprocessed_module_cache: Option<Arc<PreprocessedModuleCache>>,
+    ) -> Self {
+        self.preprocessed_module_cache = preprocessed_module_cache;
+        self
     }
 
     /// Executes a WASM module.
@@ -100,12 +127,15 @@ impl Executor {
             spending_limit,
         );
 
-        let mut runtime = Runtime::new(self.config, context);
+        let mut runtime = Runtime::new(self.config, context)
+            .with_host_function_deprecations(Arc::clone(&self.host_function_deprecations));
 
         let result = match execution_kind {
-            ExecutionKind::Module(module_bytes) => {
-                runtime.execute_module_bytes(&module_bytes, stack)
-            }
+            ExecutionKind::Module(module_bytes) => runtime.execute_module_bytes(
+                &module_bytes,
+                stack,
+                self.preprocessed_module_cache.as_deref(),
+            ),
             ExecutionKind::Contract {
                 contract_hash,
                 entry_point_name,
@@ -122,16 +152,76 @@ impl Executor {
                 execution_journal: runtime.context().execution_journal(),
                 transfers: runtime.context().transfers().to_owned(),
                 cost: runtime.context().gas_counter(),
+                deprecation_warnings: runtime.deprecation_warnings(),
             },
             Err(error) => ExecutionResult::Failure {
                 error: error.into(),
                 execution_journal: runtime.context().execution_journal(),
                 transfers: runtime.context().transfers().to_owned(),
                 cost: runtime.context().gas_counter(),
+                deprecation_warnings: runtime.deprecation_warnings(),
             },
         }
     }
 
+    /// Calls a contract's entry point on behalf of `account` without spending from its purse,
+    /// returning the value it returns along with the execution journal and gas used.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn exec_view<R>(
+        &self,
+        contract_hash: ContractHash,
+        entry_point_name: &str,
+        args: RuntimeArgs,
+        account: &Account,
+        blocktime: BlockTime,
+        gas_limit: Gas,
+        protocol_version: ProtocolVersion,
+        correlation_id: CorrelationId,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+        stack: RuntimeStack,
+    ) -> (Result<CLValue, Error>, ExecutionJournal, Gas)
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<Error>,
+    {
+        // No deploy backs a view call, so addresses are generated from an all-zero hash; they are
+        // only observable by writes, which aren't permitted.
+        let deploy_hash = DeployHash::new([0; 32]);
+        let address_generator = {
+            let generator = AddressGenerator::new(deploy_hash.as_bytes(), Phase::Session);
+            Rc::new(RefCell::new(generator))
+        };
+        let mut named_keys = account.named_keys().clone();
+
+        let context = self.create_runtime_context(
+            EntryPointType::Session,
+            args.clone(),
+            &mut named_keys,
+            account.extract_access_rights(),
+            Key::from(account.account_hash()),
+            account,
+            BTreeSet::from([account.account_hash()]),
+            blocktime,
+            deploy_hash,
+            gas_limit,
+            address_generator,
+            protocol_version,
+            correlation_id,
+            tracking_copy,
+            Phase::Session,
+            U512::zero(),
+        );
+
+        let mut runtime = Runtime::new(self.config, context)
+            .with_host_function_deprecations(Arc::clone(&self.host_function_deprecations));
+        let result = runtime.call_contract_with_stack(contract_hash, entry_point_name, args, stack);
+        (
+            result,
+            runtime.context().execution_journal(),
+            runtime.context().gas_counter(),
+        )
+    }
+
     /// Executes standard payment code natively.
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn exec_standard_payment<R>(
@@ -190,19 +280,22 @@ impl Executor {
 
         // Standard payment is executed in the calling account's context; the stack already
         // captures that.
-        let mut runtime = Runtime::new(self.config, runtime_context);
+        let mut runtime = Runtime::new(self.config, runtime_context)
+            .with_host_function_deprecations(Arc::clone(&self.host_function_deprecations));
 
         match runtime.call_host_standard_payment(stack) {
             Ok(()) => ExecutionResult::Success {
                 execution_journal: runtime.context().execution_journal(),
                 transfers: runtime.context().transfers().to_owned(),
                 cost: runtime.context().gas_counter(),
+                deprecation_warnings: runtime.deprecation_warnings(),
             },
             Err(error) => ExecutionResult::Failure {
                 execution_journal,
                 error: error.into(),
                 transfers: runtime.context().transfers().to_owned(),
                 cost: runtime.context().gas_counter(),
+                deprecation_warnings: runtime.deprecation_warnings(),
             },
         }
     }
@@ -305,7 +398,8 @@ impl Executor {
             remaining_spending_limit,
         );
 
-        let mut runtime = Runtime::new(self.config, runtime_context);
+        let mut runtime = Runtime::new(self.config, runtime_context)
+            .with_host_function_deprecations(Arc::clone(&self.host_function_deprecations));
 
         // DO NOT alter this logic to call a system contract directly (such as via mint_internal,
         // etc). Doing so would bypass necessary context based security checks in some use cases. It
@@ -321,6 +415,7 @@ impl Executor {
                     execution_journal: runtime.context().execution_journal(),
                     transfers: runtime.context().transfers().to_owned(),
                     cost: runtime.context().gas_counter(),
+                    deprecation_warnings: runtime.deprecation_warnings(),
                 }
                 .take_with_ret(ret),
                 Err(error) => ExecutionResult::Failure {
@@ -328,6 +423,7 @@ impl Executor {
                     error: Error::CLValue(error).into(),
                     transfers: runtime.context().transfers().to_owned(),
                     cost: runtime.context().gas_counter(),
+                    deprecation_warnings: runtime.deprecation_warnings(),
                 }
                 .take_without_ret(),
             },
@@ -336,6 +432,7 @@ impl Executor {
                 error: error.into(),
                 transfers: runtime.context().transfers().to_owned(),
                 cost: runtime.context().gas_counter(),
+                deprecation_warnings: runtime.deprecation_warnings(),
             }
             .take_without_ret(),
         }
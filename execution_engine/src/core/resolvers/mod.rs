@@ -4,29 +4,65 @@ pub mod memory_resolver;
 pub(crate) mod v1_function_index;
 mod v1_resolver;
 
-use wasmi::ModuleImportResolver;
+use wasmi::{ModuleImportResolver, Signature};
 
 use casper_types::ProtocolVersion;
 
 use self::error::ResolverError;
-use crate::{core::resolvers::memory_resolver::MemoryResolver, shared::wasm_config::WasmConfig};
+use crate::{
+    core::{
+        engine_state::host_function_deprecations::{DeprecationAction, HostFunctionDeprecations},
+        resolvers::memory_resolver::MemoryResolver,
+    },
+    shared::wasm_config::WasmConfig,
+};
+
+/// The offset added to the index of a deprecation to make up the function index a host function
+/// rejected by it resolves to, well beyond the indices of the host functions themselves.
+pub(crate) const REJECTED_FUNCTION_INDEX_OFFSET: usize = 1 << 16;
 
 /// Creates a module resolver for given protocol version.
 ///
 /// * `protocol_version` Version of the protocol. Can't be lower than 1.
+/// * `deprecations` Host functions rejected by a deprecation in force at `protocol_version` resolve
+///   to [`REJECTED_FUNCTION_INDEX_OFFSET`] plus the index of their deprecation.
 pub(crate) fn create_module_resolver(
     protocol_version: ProtocolVersion,
     wasm_config: &WasmConfig,
+    deprecations: &HostFunctionDeprecations,
 ) -> Result<impl ModuleImportResolver + MemoryResolver, ResolverError> {
     // TODO: revisit how protocol_version check here is meant to combine with upgrade
     if protocol_version >= ProtocolVersion::V1_0_0 {
-        return Ok(v1_resolver::RuntimeModuleImportResolver::new(
-            wasm_config.max_memory,
-        ));
+        let rejected = deprecations
+            .deprecations()
+            .iter()
+            .enumerate()
+            .filter(|(_, deprecation)| {
+                deprecation.action == DeprecationAction::Reject
+                    && deprecation.since <= protocol_version
+            })
+            .map(|(index, deprecation)| {
+                (
+                    deprecation.name.clone(),
+                    REJECTED_FUNCTION_INDEX_OFFSET + index,
+                )
+            })
+            .collect();
+        return Ok(
+            v1_resolver::RuntimeModuleImportResolver::new(wasm_config.max_memory)
+                .with_rejected(rejected),
+        );
     }
     Err(ResolverError::UnknownProtocolVersion(protocol_version))
 }
 
+/// Returns `true` if `name` is the name of a host function.
+pub(crate) fn is_host_function(name: &str) -> bool {
+    v1_resolver::RuntimeModuleImportResolver::new(0)
+        .resolve_func(name, &Signature::new(&[][..], None))
+        .is_ok()
+}
+
 #[cfg(test)]
 mod tests {
     use casper_types::ProtocolVersion;
@@ -36,13 +72,21 @@ mod tests {
 
     #[test]
     fn resolve_invalid_module() {
-        assert!(
-            create_module_resolver(ProtocolVersion::default(), &WasmConfig::default()).is_err()
-        );
+        assert!(create_module_resolver(
+            ProtocolVersion::default(),
+            &WasmConfig::default(),
+            &HostFunctionDeprecations::default()
+        )
+        .is_err());
     }
 
     #[test]
     fn protocol_version_1_always_resolves() {
-        assert!(create_module_resolver(ProtocolVersion::V1_0_0, &WasmConfig::default()).is_ok());
+        assert!(create_module_resolver(
+            ProtocolVersion::V1_0_0,
+            &WasmConfig::default(),
+            &HostFunctionDeprecations::default()
+        )
+        .is_ok());
     }
 }
@@ -59,6 +59,11 @@ pub(crate) enum FunctionIndex {
     LoadAuthorizationKeys,
     RandomBytes,
     DictionaryReadFuncIndex,
+    ReadHostBufferChunkIndex,
+    AddAllowedCallerIndex,
+    RemoveAllowedCallerIndex,
+    TransferBatchIndex,
+    SetContractPackagePausedIndex,
 }
 
 impl From<FunctionIndex> for usize {
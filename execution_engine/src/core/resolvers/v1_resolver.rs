@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, collections::BTreeMap};
 
 use wasmi::{
     memory_units::Pages, Error as InterpreterError, FuncInstance, FuncRef, MemoryDescriptor,
@@ -12,6 +12,8 @@ use super::{
 pub(crate) struct RuntimeModuleImportResolver {
     memory: RefCell<Option<MemoryRef>>,
     max_memory: u32,
+    /// The function indices the names of rejected host functions resolve to instead.
+    rejected: BTreeMap<String, usize>,
 }
 
 impl RuntimeModuleImportResolver {
@@ -19,8 +21,14 @@ impl RuntimeModuleImportResolver {
         Self {
             memory: RefCell::new(None),
             max_memory,
+            rejected: BTreeMap::new(),
         }
     }
+
+    pub(crate) fn with_rejected(mut self, rejected: BTreeMap<String, usize>) -> Self {
+        self.rejected = rejected;
+        self
+    }
 }
 
 impl MemoryResolver for RuntimeModuleImportResolver {
@@ -37,8 +45,12 @@ impl ModuleImportResolver for RuntimeModuleImportResolver {
     fn resolve_func(
         &self,
         field_name: &str,
-        _signature: &Signature,
+        signature: &Signature,
     ) -> Result<FuncRef, InterpreterError> {
+        if let Some(index) = self.rejected.get(field_name) {
+            // Still resolved, so that only modules actually calling the function fail.
+            return Ok(FuncInstance::alloc_host(signature.clone(), *index));
+        }
         let func_ref = match field_name {
             "casper_read_value" => FuncInstance::alloc_host(
                 Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
@@ -241,6 +253,26 @@ impl ModuleImportResolver for RuntimeModuleImportResolver {
                 Signature::new(&[ValueType::I32; 2][..], Some(ValueType::I32)),
                 FunctionIndex::RandomBytes.into(),
             ),
+            "casper_read_host_buffer_chunk" => FuncInstance::alloc_host(
+                Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
+                FunctionIndex::ReadHostBufferChunkIndex.into(),
+            ),
+            "casper_add_allowed_caller" => FuncInstance::alloc_host(
+                Signature::new(&[ValueType::I32; 6][..], Some(ValueType::I32)),
+                FunctionIndex::AddAllowedCallerIndex.into(),
+            ),
+            "casper_remove_allowed_caller" => FuncInstance::alloc_host(
+                Signature::new(&[ValueType::I32; 6][..], Some(ValueType::I32)),
+                FunctionIndex::RemoveAllowedCallerIndex.into(),
+            ),
+            "casper_transfer_batch" => FuncInstance::alloc_host(
+                Signature::new(&[ValueType::I32; 4][..], Some(ValueType::I32)),
+                FunctionIndex::TransferBatchIndex.into(),
+            ),
+            "casper_set_contract_package_paused" => FuncInstance::alloc_host(
+                Signature::new(&[ValueType::I32; 3][..], Some(ValueType::I32)),
+                FunctionIndex::SetContractPackagePausedIndex.into(),
+            ),
             _ => {
                 return Err(InterpreterError::Function(format!(
                     "host module doesn't export function with name {}",
@@ -39,6 +39,13 @@ where
     R: StateReader<Key, StoredValue>,
     R::Error: Into<execution::Error>,
 {
+    fn new_uref<T: ToBytes + CLTyped>(&mut self, init: T) -> Result<URef, Error> {
+        let cl_value = CLValue::from_t(init).map_err(|_| Error::CLValue)?;
+        self.context
+            .new_uref(StoredValue::CLValue(cl_value))
+            .map_err(|exec_error| <Option<Error>>::from(exec_error).unwrap_or(Error::Storage))
+    }
+
     fn read<T: FromBytes + CLTyped>(&mut self, uref: URef) -> Result<Option<T>, Error> {
         match self.context.read_gs(&uref.into()) {
             Ok(Some(StoredValue::CLValue(cl_value))) => {
@@ -129,6 +136,12 @@ where
         self.context.named_keys_get(name).cloned()
     }
 
+    fn put_key(&mut self, name: &str, key: Key) -> Result<(), Error> {
+        self.context
+            .put_key(name.to_string(), key)
+            .map_err(|exec_error| <Option<Error>>::from(exec_error).unwrap_or(Error::Storage))
+    }
+
     fn get_keys(&mut self, key_tag: &KeyTag) -> Result<BTreeSet<Key>, Error> {
         self.context.get_keys(key_tag).map_err(|_| Error::Storage)
     }
@@ -140,6 +153,16 @@ where
     fn vesting_schedule_period_millis(&self) -> u64 {
         self.config.vesting_schedule_period_millis()
     }
+
+    fn direct_redelegation_cooldown_eras(&self) -> Option<u64> {
+        self.config
+            .direct_redelegation_cooldown_eras(self.context.protocol_version())
+    }
+
+    fn strict_bid_activation(&self) -> bool {
+        self.config
+            .strict_bid_activation_enabled(self.context.protocol_version())
+    }
 }
 
 impl<'a, R> MintProvider for Runtime<'a, R>
@@ -6,7 +6,7 @@ use casper_types::{
     account::AccountHash,
     api_error,
     bytesrepr::{self, ToBytes},
-    contracts::{ContractPackageStatus, EntryPoints, NamedKeys},
+    contracts::{AllowedCaller, ContractPackageStatus, EntryPoints, NamedKeys},
     crypto,
     system::auction::EraInfo,
     ApiError, ContractHash, ContractPackageHash, ContractVersion, EraId, Gas, Group, Key,
@@ -15,7 +15,7 @@ use casper_types::{
 
 use super::{args::Args, Error, Runtime};
 use crate::{
-    core::resolvers::v1_function_index::FunctionIndex,
+    core::resolvers::{v1_function_index::FunctionIndex, REJECTED_FUNCTION_INDEX_OFFSET},
     shared::host_function_costs::{Cost, HostFunction, DEFAULT_HOST_FUNCTION_NEW_DICTIONARY},
     storage::global_state::StateReader,
 };
@@ -30,6 +30,15 @@ where
         index: usize,
         args: RuntimeArgs,
     ) -> Result<Option<RuntimeValue>, Trap> {
+        if let Some(position) = index.checked_sub(REJECTED_FUNCTION_INDEX_OFFSET) {
+            let name = self
+                .host_function_deprecations
+                .deprecations()
+                .get(position)
+                .map(|deprecation| deprecation.name.clone())
+                .unwrap_or_default();
+            return Err(Error::DeprecatedHostFunction(name).into());
+        }
         let func = FunctionIndex::try_from(index).expect("unknown function index");
 
         let host_function_costs = self.config.wasm_config().take_host_function_costs();
@@ -513,6 +522,25 @@ where
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
             }
 
+            FunctionIndex::ReadHostBufferChunkIndex => {
+                // args(0) = offset into the host buffer to read from
+                // args(1) = pointer to Wasm memory where to write
+                // args(2) = size of the destination in Wasm memory
+                // args(3) = pointer to Wasm memory where to write the number of bytes written
+                let (offset, dest_ptr, dest_size, bytes_written_ptr) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.read_host_buffer_chunk,
+                    [offset, dest_ptr, dest_size, bytes_written_ptr],
+                )?;
+                let ret = self.read_host_buffer_chunk(
+                    offset as usize,
+                    dest_ptr,
+                    dest_size as usize,
+                    bytes_written_ptr,
+                )?;
+                Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
+            }
+
             FunctionIndex::CreateContractPackageAtHash => {
                 // args(0) = pointer to wasm memory where to write 32-byte Hash address
                 // args(1) = pointer to wasm memory where to write 32-byte access key address
@@ -876,6 +904,110 @@ where
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
             }
 
+            FunctionIndex::AddAllowedCallerIndex => {
+                // args(0) = pointer to contract hash in wasm memory
+                // args(1) = size of contract hash in wasm memory
+                // args(2) = pointer to entry point name in wasm memory
+                // args(3) = size of entry point name in wasm memory
+                // args(4) = pointer to serialized allowed caller in wasm memory
+                // args(5) = size of serialized allowed caller in wasm memory
+                let (
+                    contract_hash_ptr,
+                    contract_hash_size,
+                    entry_point_name_ptr,
+                    entry_point_name_size,
+                    caller_ptr,
+                    caller_size,
+                ) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.add_allowed_caller,
+                    [
+                        contract_hash_ptr,
+                        contract_hash_size,
+                        entry_point_name_ptr,
+                        entry_point_name_size,
+                        caller_ptr,
+                        caller_size,
+                    ],
+                )?;
+                let contract_hash: ContractHash =
+                    self.t_from_mem(contract_hash_ptr, contract_hash_size)?;
+                let entry_point_name: String =
+                    self.t_from_mem(entry_point_name_ptr, entry_point_name_size)?;
+                let caller: AllowedCaller = self.t_from_mem(caller_ptr, caller_size)?;
+                let ret =
+                    self.update_allowed_callers(contract_hash, &entry_point_name, caller, true)?;
+                Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
+            }
+
+            FunctionIndex::RemoveAllowedCallerIndex => {
+                // args(0) = pointer to contract hash in wasm memory
+                // args(1) = size of contract hash in wasm memory
+                // args(2) = pointer to entry point name in wasm memory
+                // args(3) = size of entry point name in wasm memory
+                // args(4) = pointer to serialized allowed caller in wasm memory
+                // args(5) = size of serialized allowed caller in wasm memory
+                let (
+                    contract_hash_ptr,
+                    contract_hash_size,
+                    entry_point_name_ptr,
+                    entry_point_name_size,
+                    caller_ptr,
+                    caller_size,
+                ) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.remove_allowed_caller,
+                    [
+                        contract_hash_ptr,
+                        contract_hash_size,
+                        entry_point_name_ptr,
+                        entry_point_name_size,
+                        caller_ptr,
+                        caller_size,
+                    ],
+                )?;
+                let contract_hash: ContractHash =
+                    self.t_from_mem(contract_hash_ptr, contract_hash_size)?;
+                let entry_point_name: String =
+                    self.t_from_mem(entry_point_name_ptr, entry_point_name_size)?;
+                let caller: AllowedCaller = self.t_from_mem(caller_ptr, caller_size)?;
+                let ret =
+                    self.update_allowed_callers(contract_hash, &entry_point_name, caller, false)?;
+                Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
+            }
+
+            FunctionIndex::TransferBatchIndex => {
+                // args(0) = pointer to array of bytes in Wasm memory of a source purse
+                // args(1) = length of array of bytes in Wasm memory of a source purse
+                // args(2) = pointer to serialized list of (account hash, amount, id) transfers
+                // args(3) = size of serialized list of transfers
+                let (source_ptr, source_size, transfers_ptr, transfers_size) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.transfer_batch,
+                    [source_ptr, source_size, transfers_ptr, transfers_size],
+                )?;
+                let source_purse: URef = self.t_from_mem(source_ptr, source_size)?;
+                let transfers: Vec<(AccountHash, U512, Option<u64>)> =
+                    self.t_from_mem(transfers_ptr, transfers_size)?;
+                let ret = self.transfer_batch(source_purse, transfers)?;
+                Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
+            }
+
+            FunctionIndex::SetContractPackagePausedIndex => {
+                // args(0) = pointer to package hash in wasm memory
+                // args(1) = size of package hash in wasm memory
+                // args(2) = 1 to pause the package, 0 to resume it
+                let (package_key_ptr, package_key_size, paused) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.set_contract_package_paused,
+                    [package_key_ptr, package_key_size, paused],
+                )?;
+                let contract_package_hash = self.t_from_mem(package_key_ptr, package_key_size)?;
+                let result =
+                    self.set_contract_package_paused(contract_package_hash, paused != 0)?;
+                Ok(Some(RuntimeValue::I32(api_error::i32_from(result))))
+            }
+
             FunctionIndex::Blake2b => {
                 let (in_ptr, in_size, out_ptr, out_size) = Args::parse(args)?;
                 self.charge_host_function_call(
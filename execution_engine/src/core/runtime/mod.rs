@@ -10,23 +10,25 @@ mod standard_payment_internal;
 mod utils;
 
 use std::{
+    cell::RefCell,
     cmp,
     collections::{BTreeMap, BTreeSet},
     convert::{TryFrom, TryInto},
-    iter::FromIterator,
+    rc::Rc,
+    sync::Arc,
 };
 
 use parity_wasm::elements::Module;
 use tracing::error;
-use wasmi::{MemoryRef, Trap, TrapKind};
+use wasmi::{MemoryRef, ModuleRef, Trap, TrapKind};
 
 use casper_types::{
     account::{Account, AccountHash, ActionType, Weight},
     bytesrepr::{self, Bytes, FromBytes, ToBytes},
     contracts::{
-        self, Contract, ContractPackage, ContractPackageStatus, ContractVersion, ContractVersions,
-        DisabledVersions, EntryPoint, EntryPointAccess, EntryPoints, Group, Groups, NamedKeys,
-        DEFAULT_ENTRY_POINT_NAME,
+        self, AllowedCaller, Contract, ContractPackage, ContractPackageStatus, ContractVersion,
+        ContractVersions, DisabledVersions, EntryPoint, EntryPointAccess, EntryPoints, Group,
+        Groups, NamedKeys, DEFAULT_ENTRY_POINT_NAME,
     },
     system::{
         self,
@@ -37,12 +39,17 @@ use casper_types::{
     AccessRights, ApiError, CLTyped, CLValue, ContextAccessRights, ContractHash,
     ContractPackageHash, ContractVersionKey, ContractWasm, DeployHash, EntryPointType, EraId, Gas,
     GrantedAccess, Key, NamedArg, Parameter, Phase, PublicKey, RuntimeArgs, StoredValue, Transfer,
-    TransferResult, TransferredTo, URef, DICTIONARY_ITEM_KEY_MAX_LENGTH, U512,
+    TransferResult, TransferredTo, URef, DICTIONARY_ITEM_KEY_MAX_LENGTH, MAX_TRANSFER_BATCH_SIZE,
+    U512,
 };
 
 use crate::{
     core::{
-        engine_state::EngineConfig,
+        engine_state::{
+            contract_pause,
+            host_function_deprecations::{DeprecatedHostFunction, HostFunctionDeprecations},
+            EngineConfig,
+        },
         execution::{self, Error},
         runtime::host_function_flag::HostFunctionFlag,
         runtime_context::{self, RuntimeContext},
@@ -51,6 +58,7 @@ use crate::{
     shared::{
         host_function_costs::{Cost, HostFunction},
         wasm_prep::{self, PreprocessingError},
+        wasm_prep_cache::PreprocessedModuleCache,
     },
     storage::global_state::StateReader,
     system::{
@@ -79,6 +87,10 @@ pub struct Runtime<'a, R> {
     context: RuntimeContext<'a, R>,
     stack: Option<RuntimeStack>,
     host_function_flag: HostFunctionFlag,
+    host_function_deprecations: Arc<HostFunctionDeprecations>,
+    /// The deprecated host functions imported by the modules instantiated so far, shared with the
+    /// runtimes created for calls into contracts.
+    deprecation_warnings: Rc<RefCell<Vec<DeprecatedHostFunction>>>,
 }
 
 impl<'a, R> Runtime<'a, R>
@@ -96,9 +108,50 @@ where
             context,
             stack: None,
             host_function_flag: HostFunctionFlag::default(),
+            host_function_deprecations: Arc::default(),
+            deprecation_warnings: Rc::default(),
         }
     }
 
+    /// Sets the deprecated host functions, applied to every module instantiated by this runtime
+    /// and by those it creates for calls into contracts.
+    pub(crate) fn with_host_function_deprecations(
+        mut self,
+        host_function_deprecations: Arc<HostFunctionDeprecations>,
+    ) -> Self {
+        self.host_function_deprecations = host_function_deprecations;
+        self
+    }
+
+    /// Returns the deprecated host functions imported by the modules instantiated so far,
+    /// including those of calls into contracts.
+    pub(crate) fn deprecation_warnings(&self) -> Vec<DeprecatedHostFunction> {
+        self.deprecation_warnings.borrow().clone()
+    }
+
+    /// Instantiates `module`, recording the deprecated host functions it imports.
+    fn instance_and_memory(&self, module: Module) -> Result<(ModuleRef, MemoryRef), Error> {
+        let protocol_version = self.context.protocol_version();
+        {
+            let mut deprecation_warnings = self.deprecation_warnings.borrow_mut();
+            for deprecation in wasm_prep::deprecated_imports(
+                &module,
+                &self.host_function_deprecations,
+                protocol_version,
+            ) {
+                if !deprecation_warnings.contains(deprecation) {
+                    deprecation_warnings.push(deprecation.clone());
+                }
+            }
+        }
+        utils::instance_and_memory(
+            module,
+            protocol_version,
+            self.config.wasm_config(),
+            &self.host_function_deprecations,
+        )
+    }
+
     /// Creates a new runtime instance by cloning the config, and host function flag from `self`.
     fn new_invocation_runtime(
         &self,
@@ -116,6 +169,8 @@ where
             context,
             stack: Some(stack),
             host_function_flag: self.host_function_flag.clone(),
+            host_function_deprecations: Arc::clone(&self.host_function_deprecations),
+            deprecation_warnings: Rc::clone(&self.deprecation_warnings),
         }
     }
 
@@ -134,6 +189,8 @@ where
             context,
             stack: Some(stack),
             host_function_flag: self.host_function_flag.clone(),
+            host_function_deprecations: Arc::clone(&self.host_function_deprecations),
+            deprecation_warnings: Rc::clone(&self.deprecation_warnings),
         }
     }
 
@@ -464,6 +521,14 @@ where
     /// type is `Trap`, indicating that this function will always kill the current Wasm instance.
     fn ret(&mut self, value_ptr: u32, value_size: usize) -> Trap {
         self.host_buffer = None;
+        let max_call_result_size = self.config.wasm_config().max_call_result_size;
+        if value_size > max_call_result_size as usize {
+            return Error::ReturnValueTooLarge {
+                size: value_size,
+                max: max_call_result_size,
+            }
+            .into();
+        }
         let memory = match self.try_get_memory() {
             Ok(memory) => memory,
             Err(error) => return Trap::from(error),
@@ -1024,12 +1089,14 @@ where
         &mut self,
         module_bytes: &Bytes,
         stack: RuntimeStack,
+        preprocessed_module_cache: Option<&PreprocessedModuleCache>,
     ) -> Result<CLValue, Error> {
-        let protocol_version = self.context.protocol_version();
         let wasm_config = self.config.wasm_config();
-        let module = wasm_prep::preprocess(*wasm_config, module_bytes)?;
-        let (instance, memory) =
-            utils::instance_and_memory(module.clone(), protocol_version, wasm_config)?;
+        let module = match preprocessed_module_cache {
+            Some(cache) => cache.get_or_preprocess(*wasm_config, module_bytes)?,
+            None => wasm_prep::preprocess(*wasm_config, module_bytes)?,
+        };
+        let (instance, memory) = self.instance_and_memory(module.clone())?;
         self.memory = Some(memory);
         self.module = Some(module);
         self.stack = Some(stack);
@@ -1193,6 +1260,39 @@ where
             }
         };
 
+        // A paused package can't be called into, whichever of its contracts is named, except by
+        // its own contracts.
+        let contract_package_hash = contract.contract_package_hash();
+        let is_call_within_package = match self.try_get_stack()?.current_frame() {
+            Some(CallStackElement::StoredSession {
+                contract_package_hash: caller_package_hash,
+                ..
+            })
+            | Some(CallStackElement::StoredContract {
+                contract_package_hash: caller_package_hash,
+                ..
+            }) => *caller_package_hash == contract_package_hash,
+            Some(CallStackElement::Session { .. }) | None => false,
+        };
+        if self
+            .config
+            .contract_package_pause_enabled(self.context.protocol_version())
+            && !is_call_within_package
+        {
+            let pause_key = contract_pause::contract_package_pause_key(contract_package_hash);
+            // The flag is read like any value read by the contract.
+            let read_cost = self
+                .config
+                .wasm_config()
+                .take_host_function_costs()
+                .read_value
+                .calculate_gas_cost([0, pause_key.serialized_length() as u32, 0]);
+            self.gas(read_cost)?;
+            if contract_pause::is_paused(self.context.read_gs_direct(&pause_key)?)? {
+                return Err(Error::ContractPackagePaused(contract_package_hash));
+            }
+        }
+
         let entry_point = contract
             .entry_point(entry_point_name)
             .cloned()
@@ -1202,6 +1302,8 @@ where
         // if public, allowed
         // if not public, restricted to user group access
         self.validate_group_membership(&contract_package, entry_point.access())?;
+        // if restricted to callers, the calling account or contract has to be listed
+        self.validate_allowed_caller(entry_point_name, entry_point.access())?;
 
         if self.config.strict_argument_checking() {
             let entry_point_args_lookup: BTreeMap<&str, &Parameter> = entry_point
@@ -1349,12 +1451,7 @@ where
             access_rights,
             context_args,
         );
-        let protocol_version = self.context.protocol_version();
-        let (instance, memory) = utils::instance_and_memory(
-            module.clone(),
-            protocol_version,
-            self.config.wasm_config(),
-        )?;
+        let (instance, memory) = self.instance_and_memory(module.clone())?;
         let runtime = &mut Runtime::new_invocation_runtime(self, context, module, memory, stack);
 
         let result = instance.invoke_export(entry_point.name(), &[], runtime);
@@ -1493,6 +1590,12 @@ where
         Ok(Ok(()))
     }
 
+    /// Loads the named keys of the current context into the host buffer.
+    ///
+    /// Wasm can read the host buffer byte by byte, so the order of the entries is part of the
+    /// consensus-critical behavior of this host function: they are written in ascending order of
+    /// name, as held by `NamedKeys`, regardless of the order the keys were added in.  Any host
+    /// function returning a collection is expected to provide the same guarantee.
     fn load_named_keys(
         &mut self,
         total_keys_ptr: u32,
@@ -1768,6 +1871,36 @@ where
         Ok(Ok(()))
     }
 
+    fn set_contract_package_paused(
+        &mut self,
+        contract_package_hash: ContractPackageHash,
+        paused: bool,
+    ) -> Result<Result<(), ApiError>, Error> {
+        if !self
+            .config
+            .contract_package_pause_enabled(self.context.protocol_version())
+        {
+            return Err(Error::InactiveHostFunction(
+                "casper_set_contract_package_paused".to_string(),
+            ));
+        }
+
+        let contract_package = self
+            .context
+            .get_validated_contract_package(contract_package_hash)?;
+
+        // A locked package promises its callers it will never change, so it can't be paused.
+        if contract_package.is_locked() {
+            return Err(Error::LockedContract(contract_package_hash));
+        }
+
+        let key = contract_pause::contract_package_pause_key(contract_package_hash);
+        let value = contract_pause::paused_flag_stored_value(paused)?;
+        self.context.metered_write_gs_unsafe(key, value)?;
+
+        Ok(Ok(()))
+    }
+
     /// Writes function address (`hash_bytes`) into the Wasm memory (at
     /// `dest_ptr` pointer).
     fn function_address(&mut self, hash_bytes: [u8; 32], dest_ptr: u32) -> Result<(), Trap> {
@@ -2272,6 +2405,191 @@ where
         }
     }
 
+    /// Transfers motes from `source` purse to each of the target accounts of `transfers`, creating
+    /// the accounts which do not exist yet.
+    ///
+    /// The whole batch is checked before anything is written: the target accounts are looked up,
+    /// and the balance of `source` is read once and checked against the total amount, leaving out
+    /// transfers back to `source` itself.  Each account the batch creates is charged the cost of a
+    /// `transfer_to_account` call, as if it had been created by one.  The mint then debits `source`
+    /// once for the whole batch and credits each target purse.  The batch is atomic: if any part
+    /// of it fails, all its effects on global state, including the creation of new accounts, are
+    /// rolled back.
+    fn transfer_batch(
+        &mut self,
+        source: URef,
+        transfers: Vec<(AccountHash, U512, Option<u64>)>,
+    ) -> Result<Result<(), ApiError>, Error> {
+        let _scoped_host_function_flag = self.host_function_flag.enter_host_function_scope();
+
+        if transfers.len() > MAX_TRANSFER_BATCH_SIZE {
+            return Ok(Err(ApiError::InvalidArgument));
+        }
+
+        self.context.validate_uref(&source)?;
+
+        let mut targets = Vec::with_capacity(transfers.len());
+        for (target, amount, id) in transfers {
+            let target_key = Key::Account(target);
+            let maybe_target_purse = match self.context.read_account(&target_key)? {
+                None => None,
+                Some(StoredValue::Account(account)) => {
+                    let target_purse = account.main_purse_add_only();
+                    if source.with_access_rights(AccessRights::ADD) == target_purse {
+                        // Transferring to the source purse itself is a no-op.
+                        continue;
+                    }
+                    Some(target_purse)
+                }
+                Some(_) => return Err(Error::AccountNotFound(target_key)),
+            };
+            targets.push((target, maybe_target_purse, amount, id));
+        }
+
+        let total_amount = targets
+            .iter()
+            .try_fold(U512::zero(), |total, (_, _, amount, _)| {
+                total.checked_add(*amount)
+            });
+        let source_balance = self.get_balance(source)?.unwrap_or_default();
+        match total_amount {
+            Some(total_amount) if total_amount <= source_balance => (),
+            _ => return Ok(Err(mint::Error::InsufficientFunds.into())),
+        }
+
+        let new_accounts = targets
+            .iter()
+            .filter(|(_, maybe_target_purse, _, _)| maybe_target_purse.is_none())
+            .map(|(target, _, _, _)| target)
+            .collect::<BTreeSet<_>>()
+            .len();
+        let new_account_cost = self
+            .config
+            .wasm_config()
+            .take_host_function_costs()
+            .transfer_to_account
+            .cost();
+        self.gas(Gas::new(
+            U512::from(new_account_cost) * U512::from(new_accounts),
+        ))?;
+
+        let checkpoint = self.context.checkpoint();
+        let mut new_purses = BTreeMap::new();
+        let result = self.make_transfer_batch(source, targets, &mut new_purses);
+
+        // We granted access rights to the purses created for new accounts as part of the
+        // `mint_create` calls, and we need to remove them to avoid leakage of access rights.
+        for new_purse in new_purses.values() {
+            self.context
+                .remove_access(new_purse.addr(), new_purse.access_rights());
+        }
+        if !matches!(result, Ok(Ok(()))) {
+            self.context.rollback(checkpoint);
+        }
+        result
+    }
+
+    /// Creates the accounts missing from `targets`, recording their purses in `new_purses`, and
+    /// has the mint make the transfers of the batch.
+    fn make_transfer_batch(
+        &mut self,
+        source: URef,
+        targets: Vec<(AccountHash, Option<URef>, U512, Option<u64>)>,
+        new_purses: &mut BTreeMap<AccountHash, URef>,
+    ) -> Result<Result<(), ApiError>, Error> {
+        let mint_contract_hash = self.get_mint_contract()?;
+
+        let mut transfers = Vec::with_capacity(targets.len());
+        for (target, maybe_target_purse, amount, id) in targets {
+            let target_purse = match maybe_target_purse {
+                Some(target_purse) => target_purse,
+                None => match new_purses.get(&target) {
+                    Some(new_purse) => new_purse.with_access_rights(AccessRights::ADD),
+                    None => {
+                        let new_purse = self.mint_create(mint_contract_hash)?;
+                        new_purses.insert(target, new_purse);
+                        let account = Account::create(target, Default::default(), new_purse);
+                        self.context.write_account(Key::Account(target), account)?;
+                        new_purse.with_access_rights(AccessRights::ADD)
+                    }
+                },
+            };
+            transfers.push((target, target_purse, amount, id));
+        }
+
+        match self.mint_transfer_batch(mint_contract_hash, source, transfers)? {
+            Ok(()) => Ok(Ok(())),
+            Err(mint_error) => Ok(Err(mint_error.into())),
+        }
+    }
+
+    /// Calls the mint to make all the transfers of a batch, see [`Mint::transfer_batch`].
+    ///
+    /// The batch isn't an entry point of the mint contract, so unlike
+    /// [`call_host_mint`](Self::call_host_mint) this is only reachable from the host.
+    fn mint_transfer_batch(
+        &mut self,
+        mint_contract_hash: ContractHash,
+        source: URef,
+        transfers: Vec<(AccountHash, URef, U512, Option<u64>)>,
+    ) -> Result<Result<(), mint::Error>, Error> {
+        let gas_counter = self.gas_counter();
+
+        let mint_contract = self
+            .context
+            .state()
+            .borrow_mut()
+            .get_contract(self.context.correlation_id(), mint_contract_hash)?;
+        let mut named_keys = mint_contract.named_keys().to_owned();
+        let mut access_rights = mint_contract.extract_access_rights(mint_contract_hash);
+        access_rights.extend(&[source]);
+        let target_purses: Vec<URef> = transfers
+            .iter()
+            .map(|(_, target_purse, _, _)| *target_purse)
+            .collect();
+        access_rights.extend(&target_purses);
+
+        let stack = {
+            let mut stack = self.try_get_stack()?.clone();
+            stack.push(CallStackElement::stored_contract(
+                mint_contract.contract_package_hash(),
+                mint_contract_hash,
+            ))?;
+            stack
+        };
+
+        let runtime_context = self.context.new_from_self(
+            Key::from(mint_contract_hash),
+            EntryPointType::Contract,
+            &mut named_keys,
+            access_rights,
+            RuntimeArgs::new(),
+        );
+        let mut mint_runtime = self.new_with_stack(runtime_context, stack);
+
+        let transfer_cost = self.config.system_config().mint_costs().transfer;
+        let result = mint_runtime
+            .charge_system_contract_call(transfer_cost)
+            .map(|()| Mint::transfer_batch(&mut mint_runtime, source, transfers));
+
+        self.gas(match mint_runtime.gas_counter().checked_sub(gas_counter) {
+            None => gas_counter,
+            Some(new_gas) => new_gas,
+        })?;
+
+        let result = result?;
+        if let Err(mint::Error::GasLimit) = result {
+            return Err(Error::GasLimit);
+        }
+        if result.is_ok() {
+            self.context
+                .set_remaining_spending_limit(mint_runtime.context.remaining_spending_limit());
+            let transfers = self.context.transfers_mut();
+            *transfers = mint_runtime.context.transfers().to_owned();
+        }
+        Ok(result)
+    }
+
     /// Transfers `amount` of motes from `source` purse to `target` purse.
     #[allow(clippy::too_many_arguments)]
     fn transfer_from_purse_to_purse(
@@ -2414,6 +2732,50 @@ where
         Ok(())
     }
 
+    /// Copies up to `dest_size` bytes of the host buffer, starting at `offset`, into Wasm memory.
+    ///
+    /// Unlike [`Self::read_host_buffer`], this lets a caller read a large value in several calls
+    /// without allocating room for all of it at once.  The host buffer is only cleared once its
+    /// last byte has been read.
+    fn read_host_buffer_chunk(
+        &mut self,
+        offset: usize,
+        dest_ptr: u32,
+        dest_size: usize,
+        bytes_written_ptr: u32,
+    ) -> Result<Result<(), ApiError>, Error> {
+        let serialized_value = match &self.host_buffer {
+            None => return Ok(Err(ApiError::HostBufferEmpty)),
+            Some(cl_value) => cl_value.inner_bytes(),
+        };
+        if offset >= serialized_value.len() {
+            return Ok(Err(ApiError::InvalidArgument));
+        }
+
+        let end = offset.saturating_add(dest_size).min(serialized_value.len());
+        let chunk = serialized_value[offset..end].to_vec();
+        if end == serialized_value.len() {
+            self.host_buffer = None;
+        }
+        if let Err(error) = self.try_get_memory()?.set(dest_ptr, &chunk) {
+            return Err(Error::Interpreter(error.into()));
+        }
+
+        // Never panics, as the chunk is no larger than `dest_size`, which was passed as a `u32`.
+        let bytes_written: u32 = chunk
+            .len()
+            .try_into()
+            .expect("chunk size should fit within limit");
+        if let Err(error) = self
+            .try_get_memory()?
+            .set(bytes_written_ptr, &bytes_written.to_le_bytes())
+        {
+            return Err(Error::Interpreter(error.into()));
+        }
+
+        Ok(Ok(()))
+    }
+
     fn read_host_buffer(
         &mut self,
         dest_ptr: u32,
@@ -2535,6 +2897,65 @@ where
         })
     }
 
+    /// Enforce caller restrictions (if any) on attempts to call an `EntryPoint`.
+    fn validate_allowed_caller(
+        &self,
+        entry_point_name: &str,
+        access: &EntryPointAccess,
+    ) -> Result<(), Error> {
+        let callers = match access {
+            EntryPointAccess::Callers(callers) => callers,
+            EntryPointAccess::Public | EntryPointAccess::Groups(_) => return Ok(()),
+        };
+        let caller = match self.try_get_stack()?.current_frame() {
+            Some(CallStackElement::Session { account_hash })
+            | Some(CallStackElement::StoredSession { account_hash, .. }) => {
+                AllowedCaller::Account(*account_hash)
+            }
+            Some(CallStackElement::StoredContract { contract_hash, .. }) => {
+                AllowedCaller::Contract(*contract_hash)
+            }
+            None => return Err(Error::InvalidContext),
+        };
+        if !callers.contains(&caller) {
+            return Err(Error::CallerNotAllowed {
+                entry_point: entry_point_name.to_string(),
+                caller,
+            });
+        }
+        Ok(())
+    }
+
+    /// Allows `caller` to call an entry point of a contract if `allow` is `true`, or stops
+    /// allowing it otherwise.
+    ///
+    /// Only the holder of the contract package's access key may change the allowed callers.
+    fn update_allowed_callers(
+        &mut self,
+        contract_hash: ContractHash,
+        entry_point_name: &str,
+        caller: AllowedCaller,
+        allow: bool,
+    ) -> Result<Result<(), ApiError>, Error> {
+        let contract_key = Key::from(contract_hash);
+        let mut contract: Contract = self.context.read_gs_typed(&contract_key)?;
+        self.context
+            .get_validated_contract_package(contract.contract_package_hash())?;
+
+        let result = if allow {
+            contract.add_allowed_caller(entry_point_name, caller)
+        } else {
+            contract.remove_allowed_caller(entry_point_name, &caller)
+        };
+        if let Err(error) = result {
+            return Ok(Err(error.into()));
+        }
+
+        self.context
+            .metered_write_gs_unsafe(contract_key, contract)?;
+        Ok(Ok(()))
+    }
+
     /// Remove a user group from access to a contract
     fn remove_contract_user_group(
         &mut self,
@@ -2561,7 +2982,7 @@ where
             };
             for entry_point in entry_points {
                 match entry_point.access() {
-                    EntryPointAccess::Public => {
+                    EntryPointAccess::Public | EntryPointAccess::Callers(_) => {
                         continue;
                     }
                     EntryPointAccess::Groups(groups) => {
@@ -2876,6 +3297,7 @@ where
         }
     }
 
+    /// Loads the authorization keys of the deploy into the host buffer, in ascending order.
     fn load_authorization_keys(
         &mut self,
         len_ptr: u32,
@@ -2886,9 +3308,10 @@ where
             return Ok(Err(ApiError::HostBufferFull));
         }
 
-        // A set of keys is converted into a vector so it can be written to a host buffer
-        let authorization_keys =
-            Vec::from_iter(self.context.authorization_keys().clone().into_iter());
+        // A set of keys is converted into a vector so it can be written to a host buffer.  The
+        // vector keeps the ascending order of the set.
+        let authorization_keys: Vec<AccountHash> =
+            self.context.authorization_keys().iter().copied().collect();
 
         let total_keys: u32 = match authorization_keys.len().try_into() {
             Ok(value) => value,
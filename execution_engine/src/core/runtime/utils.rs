@@ -10,6 +10,7 @@ use casper_types::{
 
 use crate::{
     core::{
+        engine_state::host_function_deprecations::HostFunctionDeprecations,
         execution::Error,
         resolvers::{self, memory_resolver::MemoryResolver},
     },
@@ -24,14 +25,18 @@ use crate::{
 /// The WASM module is also validated to not have a "start" section as we currently don't support
 /// running it.
 ///
+/// Imports of host functions rejected by a deprecation in force at `protocol_version` resolve to
+/// functions failing when called.
+///
 /// Both [`ModuleRef`] and a [`MemoryRef`] are ready to be executed.
 pub(super) fn instance_and_memory(
     parity_module: Module,
     protocol_version: ProtocolVersion,
     wasm_config: &WasmConfig,
+    deprecations: &HostFunctionDeprecations,
 ) -> Result<(ModuleRef, MemoryRef), Error> {
     let module = wasmi::Module::from_parity_wasm_module(parity_module)?;
-    let resolver = resolvers::create_module_resolver(protocol_version, wasm_config)?;
+    let resolver = resolvers::create_module_resolver(protocol_version, wasm_config, deprecations)?;
     let mut imports = ImportsBuilder::new();
     imports.push_resolver("env", &resolver);
     let not_started_module = ModuleInstance::new(&module, &imports)?;
@@ -35,6 +35,11 @@ impl DictionaryValue {
     pub fn into_cl_value(self) -> CLValue {
         self.cl_value
     }
+
+    /// Returns the address of the [`casper_types::URef`] the dictionary was created with.
+    pub fn seed_uref_addr(&self) -> &[u8] {
+        self.seed_uref_addr.as_slice()
+    }
 }
 
 impl CLTyped for DictionaryValue {
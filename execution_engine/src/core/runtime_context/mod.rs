@@ -25,10 +25,14 @@ use casper_types::{
 
 use crate::{
     core::{
-        engine_state::{execution_effect::ExecutionEffect, EngineConfig, SystemContractRegistry},
+        engine_state::{
+            execution_effect::ExecutionEffect,
+            storage_rent::{storage_rent_key, storage_rent_registry_key},
+            EngineConfig, SystemContractRegistry,
+        },
         execution::{AddressGenerator, Error},
         runtime_context::dictionary::DictionaryValue,
-        tracking_copy::{AddResult, TrackingCopy, TrackingCopyExt},
+        tracking_copy::{AddResult, TrackingCopy, TrackingCopyCheckpoint, TrackingCopyExt},
     },
     shared::{execution_journal::ExecutionJournal, newtypes::CorrelationId},
     storage::global_state::StateReader,
@@ -641,6 +645,17 @@ where
         self.tracking_copy.borrow().execution_journal()
     }
 
+    /// Records the current state of the tracking copy, so that all changes made to global state
+    /// afterwards can be undone.
+    pub(crate) fn checkpoint(&self) -> TrackingCopyCheckpoint {
+        self.tracking_copy.borrow().checkpoint()
+    }
+
+    /// Undoes all changes made to global state since `checkpoint` was taken.
+    pub(crate) fn rollback(&mut self, checkpoint: TrackingCopyCheckpoint) {
+        self.tracking_copy.borrow_mut().rollback(checkpoint)
+    }
+
     /// Returns list of transfers.
     pub fn transfers(&self) -> &Vec<TransferAddr> {
         &self.transfers
@@ -913,15 +928,68 @@ where
         K: Into<Key>,
         V: Into<StoredValue>,
     {
+        let key = key.into();
         let stored_value = value.into();
 
         // Charge for amount as measured by serialized length
         let bytes_count = stored_value.serialized_length();
         self.charge_gas_storage(bytes_count)?;
 
-        self.tracking_copy
-            .borrow_mut()
-            .write(key.into(), stored_value);
+        if self.engine_config.storage_rent().is_some() {
+            self.count_stored_bytes(&key, &stored_value)?;
+        }
+
+        self.tracking_copy.borrow_mut().write(key, stored_value);
+        Ok(())
+    }
+
+    /// Counts the bytes of a write towards the storage rent of the contract storing them, see
+    /// [`storage_rent`](crate::core::engine_state::storage_rent).
+    fn count_stored_bytes(&mut self, key: &Key, stored_value: &StoredValue) -> Result<(), Error> {
+        let contract_hash = match (key, stored_value) {
+            (Key::Hash(hash_addr), StoredValue::Contract(_)) => ContractHash::new(*hash_addr),
+            (Key::URef(_), _) | (Key::Dictionary(_), _) => match self.base_key.into_hash() {
+                Some(hash_addr) => ContractHash::new(hash_addr),
+                None => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+        if self.is_system_contract(&contract_hash)? {
+            return Ok(());
+        }
+
+        let mut tracking_copy = self.tracking_copy.borrow_mut();
+        let old_value = tracking_copy
+            .get(self.correlation_id, &key.normalize())
+            .map_err(Into::into)?;
+        let old_bytes = old_value.as_ref().map_or(0, ToBytes::serialized_length);
+        let mut new_bytes = stored_value.serialized_length();
+        if let (StoredValue::Contract(contract), None) = (stored_value, &old_value) {
+            // A new contract is also charged for its Wasm, which is written just before it.
+            let wasm_key = Key::Hash(contract.contract_wasm_hash().value());
+            if let Some(wasm) = tracking_copy
+                .get(self.correlation_id, &wasm_key)
+                .map_err(Into::into)?
+            {
+                new_bytes += wasm.serialized_length();
+            }
+        }
+
+        let record_key = storage_rent_key(contract_hash);
+        let is_registered = tracking_copy
+            .get(self.correlation_id, &record_key)
+            .map_err(Into::into)?
+            .is_some();
+        if !is_registered {
+            let mut registry = tracking_copy.get_storage_rent_registry(self.correlation_id)?;
+            registry.push(contract_hash);
+            let cl_value = CLValue::from_t(registry)?;
+            tracking_copy.write(storage_rent_registry_key(), StoredValue::CLValue(cl_value));
+        }
+        let record = tracking_copy
+            .get_storage_rent_record(self.correlation_id, contract_hash)?
+            .resize(old_bytes as u64, new_bytes as u64);
+        tracking_copy.write(record_key, record.stored_value());
         Ok(())
     }
 
@@ -8,7 +8,12 @@ use casper_types::{
 
 use crate::{
     core::{
-        engine_state::{ChecksumRegistry, SystemContractRegistry},
+        engine_state::{
+            account_sequence::account_sequence_key,
+            computation_quota::{computation_quota_key, QuotaUsage},
+            storage_rent::{storage_rent_key, storage_rent_registry_key, StorageRentRecord},
+            ChecksumRegistry, SystemContractRegistry,
+        },
         execution,
         tracking_copy::TrackingCopy,
     },
@@ -96,6 +101,33 @@ pub trait TrackingCopyExt<R> {
         &mut self,
         correlation_id: CorrelationId,
     ) -> Result<Option<ChecksumRegistry>, Self::Error>;
+
+    /// Gets the last sequence number used by the account, or `0` if it hasn't used one yet.
+    fn get_account_sequence(
+        &mut self,
+        correlation_id: CorrelationId,
+        account_hash: AccountHash,
+    ) -> Result<u64, Self::Error>;
+
+    /// Gets the computation quota usage of the account, or the default if it hasn't used any yet.
+    fn get_computation_quota_usage(
+        &mut self,
+        correlation_id: CorrelationId,
+        account_hash: AccountHash,
+    ) -> Result<QuotaUsage, Self::Error>;
+
+    /// Gets the storage rent record of the contract, or the default if it was never charged rent.
+    fn get_storage_rent_record(
+        &mut self,
+        correlation_id: CorrelationId,
+        contract_hash: ContractHash,
+    ) -> Result<StorageRentRecord, Self::Error>;
+
+    /// Gets the hashes of the contracts charged storage rent.
+    fn get_storage_rent_registry(
+        &mut self,
+        correlation_id: CorrelationId,
+    ) -> Result<Vec<ContractHash>, Self::Error>;
 }
 
 impl<R> TrackingCopyExt<R> for TrackingCopy<R>
@@ -290,4 +322,70 @@ where
             None => Ok(None),
         }
     }
+
+    fn get_account_sequence(
+        &mut self,
+        correlation_id: CorrelationId,
+        account_hash: AccountHash,
+    ) -> Result<u64, Self::Error> {
+        let key = account_sequence_key(account_hash);
+        match self.get(correlation_id, &key).map_err(Into::into)? {
+            Some(StoredValue::CLValue(cl_value)) => {
+                let sequence: u64 = CLValue::into_t(cl_value).map_err(Self::Error::from)?;
+                Ok(sequence)
+            }
+            Some(other) => Err(execution::Error::TypeMismatch(
+                StoredValueTypeMismatch::new("CLValue".to_string(), other.type_name()),
+            )),
+            None => Ok(0),
+        }
+    }
+
+    fn get_computation_quota_usage(
+        &mut self,
+        correlation_id: CorrelationId,
+        account_hash: AccountHash,
+    ) -> Result<QuotaUsage, Self::Error> {
+        let key = computation_quota_key(account_hash);
+        match self.get(correlation_id, &key).map_err(Into::into)? {
+            Some(StoredValue::CLValue(cl_value)) => {
+                QuotaUsage::from_cl_value(cl_value).map_err(Self::Error::from)
+            }
+            Some(other) => Err(execution::Error::TypeMismatch(
+                StoredValueTypeMismatch::new("CLValue".to_string(), other.type_name()),
+            )),
+            None => Ok(QuotaUsage::default()),
+        }
+    }
+
+    fn get_storage_rent_record(
+        &mut self,
+        correlation_id: CorrelationId,
+        contract_hash: ContractHash,
+    ) -> Result<StorageRentRecord, Self::Error> {
+        let key = storage_rent_key(contract_hash);
+        match self.get(correlation_id, &key).map_err(Into::into)? {
+            Some(StoredValue::CLValue(cl_value)) => {
+                StorageRentRecord::from_cl_value(cl_value).map_err(Self::Error::from)
+            }
+            Some(other) => Err(execution::Error::TypeMismatch(
+                StoredValueTypeMismatch::new("CLValue".to_string(), other.type_name()),
+            )),
+            None => Ok(StorageRentRecord::default()),
+        }
+    }
+
+    fn get_storage_rent_registry(
+        &mut self,
+        correlation_id: CorrelationId,
+    ) -> Result<Vec<ContractHash>, Self::Error> {
+        let key = storage_rent_registry_key();
+        match self.get(correlation_id, &key).map_err(Into::into)? {
+            Some(StoredValue::CLValue(cl_value)) => cl_value.into_t().map_err(Self::Error::from),
+            Some(other) => Err(execution::Error::TypeMismatch(
+                StoredValueTypeMismatch::new("CLValue".to_string(), other.type_name()),
+            )),
+            None => Ok(Vec::new()),
+        }
+    }
 }
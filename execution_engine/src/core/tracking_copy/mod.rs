@@ -32,7 +32,10 @@ use crate::{
         newtypes::CorrelationId,
         transform::{self, Transform},
     },
-    storage::{global_state::StateReader, trie::merkle_proof::TrieMerkleProof},
+    storage::{
+        global_state::StateReader,
+        trie::{merkle_proof::TrieMerkleProof, path_proof::TriePathProof},
+    },
 };
 
 /// Result of a query on a `TrackingCopy`.
@@ -201,6 +204,18 @@ impl<M: Meter<Key, StoredValue>> TrackingCopyCache<M> {
         key_set.insert(key);
     }
 
+    fn mutations(&self) -> CachedMutations {
+        CachedMutations {
+            muts_cached: self.muts_cached.clone(),
+            key_tag_muts_cached: self.key_tag_muts_cached.clone(),
+        }
+    }
+
+    fn restore_mutations(&mut self, mutations: CachedMutations) {
+        self.muts_cached = mutations.muts_cached;
+        self.key_tag_muts_cached = mutations.key_tag_muts_cached;
+    }
+
     /// Gets value from `key` in the cache.
     pub fn get(&mut self, key: &Key) -> Option<&StoredValue> {
         if let Some(value) = self.muts_cached.get(key) {
@@ -221,6 +236,18 @@ impl<M: Meter<Key, StoredValue>> TrackingCopyCache<M> {
     }
 }
 
+/// The writes and adds cached by a `TrackingCopyCache`.
+struct CachedMutations {
+    muts_cached: HashMap<Key, StoredValue>,
+    key_tag_muts_cached: HashMap<KeyTag, BTreeSet<Key>>,
+}
+
+/// The state of a `TrackingCopy` at some point, which it can later be rolled back to.
+pub(crate) struct TrackingCopyCheckpoint {
+    mutations: CachedMutations,
+    journal_len: usize,
+}
+
 /// An interface for the global state that caches all operations (reads and writes) instead of
 /// applying them directly to the state. This way the state remains unmodified, while the user can
 /// interact with it as if it was being modified in real time.
@@ -290,6 +317,23 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
         TrackingCopy::new(self)
     }
 
+    /// Records the current state, so that all writes and adds made afterwards can be undone by
+    /// passing the returned checkpoint to [`rollback`](Self::rollback).
+    pub(crate) fn checkpoint(&self) -> TrackingCopyCheckpoint {
+        TrackingCopyCheckpoint {
+            mutations: self.cache.mutations(),
+            journal_len: self.journal.len(),
+        }
+    }
+
+    /// Undoes all writes and adds made since `checkpoint` was taken.
+    ///
+    /// Reads made since are kept in the read cache, as they reflect the unchanged global state.
+    pub(crate) fn rollback(&mut self, checkpoint: TrackingCopyCheckpoint) {
+        self.cache.restore_mutations(checkpoint.mutations);
+        self.journal.truncate(checkpoint.journal_len);
+    }
+
     pub(super) fn get(
         &mut self,
         correlation_id: CorrelationId,
@@ -595,6 +639,14 @@ impl<R: StateReader<Key, StoredValue>> StateReader<Key, StoredValue> for &Tracki
         self.reader.read_with_proof(correlation_id, key)
     }
 
+    fn read_with_path_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TriePathProof<Key, StoredValue>>, Self::Error> {
+        self.reader.read_with_path_proof(correlation_id, key)
+    }
+
     fn keys_with_prefix(
         &self,
         correlation_id: CorrelationId,
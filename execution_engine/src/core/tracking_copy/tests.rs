@@ -20,7 +20,7 @@ use crate::{
     shared::{execution_journal::ExecutionJournal, newtypes::CorrelationId, transform::Transform},
     storage::{
         global_state::{in_memory::InMemoryGlobalState, StateProvider, StateReader},
-        trie::merkle_proof::TrieMerkleProof,
+        trie::{merkle_proof::TrieMerkleProof, path_proof::TriePathProof},
     },
 };
 
@@ -69,6 +69,14 @@ impl StateReader<Key, StoredValue> for CountingDb {
         Ok(None)
     }
 
+    fn read_with_path_proof(
+        &self,
+        _correlation_id: CorrelationId,
+        _key: &Key,
+    ) -> Result<Option<TriePathProof<Key, StoredValue>>, Self::Error> {
+        Ok(None)
+    }
+
     fn keys_with_prefix(
         &self,
         _correlation_id: CorrelationId,
@@ -158,6 +166,38 @@ fn tracking_copy_write() {
     );
 }
 
+#[test]
+fn tracking_copy_rollback() {
+    let correlation_id = CorrelationId::new();
+    let counter = Rc::new(Cell::new(0));
+    let db = CountingDb::new(counter);
+    let mut tc = TrackingCopy::new(db);
+    let k1 = Key::Hash([1u8; 32]);
+    let k2 = Key::Hash([2u8; 32]);
+
+    let one = StoredValue::CLValue(CLValue::from_t(1_i32).unwrap());
+    let two = StoredValue::CLValue(CLValue::from_t(2_i32).unwrap());
+
+    tc.write(k1, one.clone());
+    let checkpoint = tc.checkpoint();
+
+    tc.write(k1, two.clone());
+    tc.write(k2, two.clone());
+    assert_eq!(tc.read(correlation_id, &k1).unwrap(), Some(two));
+
+    tc.rollback(checkpoint);
+
+    // Only the write made before the checkpoint is left, both in the cache and the journal.
+    assert_eq!(
+        tc.journal,
+        ExecutionJournal::new(vec![(k1, Transform::Write(one.clone()))])
+    );
+    assert_eq!(tc.read(correlation_id, &k1).unwrap(), Some(one));
+    // The db returns the read count for keys which were never written.
+    let zero = StoredValue::CLValue(CLValue::from_t(0_i32).unwrap());
+    assert_eq!(tc.read(correlation_id, &k2).unwrap(), Some(zero));
+}
+
 #[test]
 fn tracking_copy_add_i32() {
     let correlation_id = CorrelationId::new();
@@ -12,3 +12,4 @@ pub mod transform;
 pub mod utils;
 pub mod wasm_config;
 pub mod wasm_prep;
+pub mod wasm_prep_cache;
@@ -35,6 +35,11 @@ impl ExecutionJournal {
         self.0.push(entry)
     }
 
+    /// Drops all but the first `len` transforms.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.0.truncate(len)
+    }
+
     /// Returns an iterator over the journal entries.
     pub fn iter(&self) -> impl Iterator<Item = &(Key, Transform)> {
         self.0.iter()
@@ -67,6 +67,8 @@ const DEFAULT_SET_ACTION_THRESHOLD_COST: u32 = 74_000;
 const DEFAULT_TRANSFER_FROM_PURSE_TO_ACCOUNT_COST: u32 = 2_500_000_000;
 const DEFAULT_TRANSFER_FROM_PURSE_TO_PURSE_COST: u32 = 82_000;
 const DEFAULT_TRANSFER_TO_ACCOUNT_COST: u32 = 2_500_000_000;
+const DEFAULT_TRANSFER_BATCH_COST: u32 = 2_500_000_000;
+const DEFAULT_TRANSFER_BATCH_TRANSFERS_SIZE_WEIGHT: u32 = 10_000_000;
 const DEFAULT_UPDATE_ASSOCIATED_KEY_COST: u32 = 4_200;
 
 const DEFAULT_WRITE_COST: u32 = 14_000;
@@ -286,6 +288,16 @@ pub struct HostFunctionCosts {
     pub blake2b: HostFunction<[Cost; 4]>,
     /// Cost of calling the `next address` host function.
     pub random_bytes: HostFunction<[Cost; 2]>,
+    /// Cost of calling the `read_host_buffer_chunk` host function.
+    pub read_host_buffer_chunk: HostFunction<[Cost; 4]>,
+    /// Cost of calling the `add_allowed_caller` host function.
+    pub add_allowed_caller: HostFunction<[Cost; 6]>,
+    /// Cost of calling the `remove_allowed_caller` host function.
+    pub remove_allowed_caller: HostFunction<[Cost; 6]>,
+    /// Cost of calling the `transfer_batch` host function.
+    pub transfer_batch: HostFunction<[Cost; 4]>,
+    /// Cost of calling the `set_contract_package_paused` host function.
+    pub set_contract_package_paused: HostFunction<[Cost; 3]>,
 }
 
 impl Default for HostFunctionCosts {
@@ -404,6 +416,27 @@ impl Default for HostFunctionCosts {
             ),
             blake2b: HostFunction::default(),
             random_bytes: HostFunction::default(),
+            read_host_buffer_chunk: HostFunction::new(
+                DEFAULT_READ_HOST_BUFFER_COST,
+                [
+                    NOT_USED,
+                    NOT_USED,
+                    DEFAULT_READ_HOST_BUFFER_DEST_SIZE_WEIGHT,
+                    NOT_USED,
+                ],
+            ),
+            add_allowed_caller: HostFunction::new(DEFAULT_FIXED_COST, [NOT_USED; 6]),
+            remove_allowed_caller: HostFunction::new(DEFAULT_FIXED_COST, [NOT_USED; 6]),
+            transfer_batch: HostFunction::new(
+                DEFAULT_TRANSFER_BATCH_COST,
+                [
+                    NOT_USED,
+                    NOT_USED,
+                    NOT_USED,
+                    DEFAULT_TRANSFER_BATCH_TRANSFERS_SIZE_WEIGHT,
+                ],
+            ),
+            set_contract_package_paused: HostFunction::new(DEFAULT_FIXED_COST, [NOT_USED; 3]),
         }
     }
 }
@@ -454,6 +487,11 @@ impl ToBytes for HostFunctionCosts {
         ret.append(&mut self.print.to_bytes()?);
         ret.append(&mut self.blake2b.to_bytes()?);
         ret.append(&mut self.random_bytes.to_bytes()?);
+        ret.append(&mut self.read_host_buffer_chunk.to_bytes()?);
+        ret.append(&mut self.add_allowed_caller.to_bytes()?);
+        ret.append(&mut self.remove_allowed_caller.to_bytes()?);
+        ret.append(&mut self.transfer_batch.to_bytes()?);
+        ret.append(&mut self.set_contract_package_paused.to_bytes()?);
         Ok(ret)
     }
 
@@ -501,6 +539,11 @@ impl ToBytes for HostFunctionCosts {
             + self.print.serialized_length()
             + self.blake2b.serialized_length()
             + self.random_bytes.serialized_length()
+            + self.read_host_buffer_chunk.serialized_length()
+            + self.add_allowed_caller.serialized_length()
+            + self.remove_allowed_caller.serialized_length()
+            + self.transfer_batch.serialized_length()
+            + self.set_contract_package_paused.serialized_length()
     }
 }
 
@@ -549,6 +592,11 @@ impl FromBytes for HostFunctionCosts {
         let (print, rem) = FromBytes::from_bytes(rem)?;
         let (blake2b, rem) = FromBytes::from_bytes(rem)?;
         let (random_bytes, rem) = FromBytes::from_bytes(rem)?;
+        let (read_host_buffer_chunk, rem) = FromBytes::from_bytes(rem)?;
+        let (add_allowed_caller, rem) = FromBytes::from_bytes(rem)?;
+        let (remove_allowed_caller, rem) = FromBytes::from_bytes(rem)?;
+        let (transfer_batch, rem) = FromBytes::from_bytes(rem)?;
+        let (set_contract_package_paused, rem) = FromBytes::from_bytes(rem)?;
         Ok((
             HostFunctionCosts {
                 read_value,
@@ -594,6 +642,11 @@ impl FromBytes for HostFunctionCosts {
                 print,
                 blake2b,
                 random_bytes,
+                read_host_buffer_chunk,
+                add_allowed_caller,
+                remove_allowed_caller,
+                transfer_batch,
+                set_contract_package_paused,
             },
             rem,
         ))
@@ -646,6 +699,11 @@ impl Distribution<HostFunctionCosts> for Standard {
             print: rng.gen(),
             blake2b: rng.gen(),
             random_bytes: rng.gen(),
+            read_host_buffer_chunk: rng.gen(),
+            add_allowed_caller: rng.gen(),
+            remove_allowed_caller: rng.gen(),
+            transfer_batch: rng.gen(),
+            set_contract_package_paused: rng.gen(),
         }
     }
 }
@@ -706,6 +764,11 @@ pub mod gens {
             print in host_function_cost_arb(),
             blake2b in host_function_cost_arb(),
             random_bytes in host_function_cost_arb(),
+            read_host_buffer_chunk in host_function_cost_arb(),
+            add_allowed_caller in host_function_cost_arb(),
+            remove_allowed_caller in host_function_cost_arb(),
+            transfer_batch in host_function_cost_arb(),
+            set_contract_package_paused in host_function_cost_arb(),
         ) -> HostFunctionCosts {
             HostFunctionCosts {
                 read_value,
@@ -751,6 +814,11 @@ pub mod gens {
                 print,
                 blake2b,
                 random_bytes,
+                read_host_buffer_chunk,
+                add_allowed_caller,
+                remove_allowed_caller,
+                transfer_batch,
+                set_contract_package_paused,
             }
         }
     }
@@ -13,6 +13,8 @@ use super::{
 pub const DEFAULT_WASM_MAX_MEMORY: u32 = 64;
 /// Default maximum stack height.
 pub const DEFAULT_MAX_STACK_HEIGHT: u32 = 188;
+/// Default maximum size in bytes of a value returned by a called contract.
+pub const DEFAULT_MAX_CALL_RESULT_SIZE: u32 = 1024 * 1024;
 
 /// Configuration of the Wasm execution environment.
 ///
@@ -24,6 +26,8 @@ pub struct WasmConfig {
     pub max_memory: u32,
     /// Max stack height (native WebAssembly stack limiter).
     pub max_stack_height: u32,
+    /// Maximum size in bytes of the serialized value a contract can return to its caller.
+    pub max_call_result_size: u32,
     /// Wasm opcode costs table.
     opcode_costs: OpcodeCosts,
     /// Storage costs.
@@ -37,6 +41,7 @@ impl WasmConfig {
     pub const fn new(
         max_memory: u32,
         max_stack_height: u32,
+        max_call_result_size: u32,
         opcode_costs: OpcodeCosts,
         storage_costs: StorageCosts,
         host_function_costs: HostFunctionCosts,
@@ -44,6 +49,7 @@ impl WasmConfig {
         Self {
             max_memory,
             max_stack_height,
+            max_call_result_size,
             opcode_costs,
             storage_costs,
             host_function_costs,
@@ -71,6 +77,7 @@ impl Default for WasmConfig {
         Self {
             max_memory: DEFAULT_WASM_MAX_MEMORY,
             max_stack_height: DEFAULT_MAX_STACK_HEIGHT,
+            max_call_result_size: DEFAULT_MAX_CALL_RESULT_SIZE,
             opcode_costs: OpcodeCosts::default(),
             storage_costs: StorageCosts::default(),
             host_function_costs: HostFunctionCosts::default(),
@@ -84,6 +91,7 @@ impl ToBytes for WasmConfig {
 
         ret.append(&mut self.max_memory.to_bytes()?);
         ret.append(&mut self.max_stack_height.to_bytes()?);
+        ret.append(&mut self.max_call_result_size.to_bytes()?);
         ret.append(&mut self.opcode_costs.to_bytes()?);
         ret.append(&mut self.storage_costs.to_bytes()?);
         ret.append(&mut self.host_function_costs.to_bytes()?);
@@ -94,6 +102,7 @@ impl ToBytes for WasmConfig {
     fn serialized_length(&self) -> usize {
         self.max_memory.serialized_length()
             + self.max_stack_height.serialized_length()
+            + self.max_call_result_size.serialized_length()
             + self.opcode_costs.serialized_length()
             + self.storage_costs.serialized_length()
             + self.host_function_costs.serialized_length()
@@ -104,6 +113,7 @@ impl FromBytes for WasmConfig {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
         let (max_memory, rem) = FromBytes::from_bytes(bytes)?;
         let (max_stack_height, rem) = FromBytes::from_bytes(rem)?;
+        let (max_call_result_size, rem) = FromBytes::from_bytes(rem)?;
         let (opcode_costs, rem) = FromBytes::from_bytes(rem)?;
         let (storage_costs, rem) = FromBytes::from_bytes(rem)?;
         let (host_function_costs, rem) = FromBytes::from_bytes(rem)?;
@@ -112,6 +122,7 @@ impl FromBytes for WasmConfig {
             WasmConfig {
                 max_memory,
                 max_stack_height,
+                max_call_result_size,
                 opcode_costs,
                 storage_costs,
                 host_function_costs,
@@ -126,6 +137,7 @@ impl Distribution<WasmConfig> for Standard {
         WasmConfig {
             max_memory: rng.gen(),
             max_stack_height: rng.gen(),
+            max_call_result_size: rng.gen(),
             opcode_costs: rng.gen(),
             storage_costs: rng.gen(),
             host_function_costs: rng.gen(),
@@ -148,6 +160,7 @@ pub mod gens {
         pub fn wasm_config_arb() (
             max_memory in num::u32::ANY,
             max_stack_height in num::u32::ANY,
+            max_call_result_size in num::u32::ANY,
             opcode_costs in opcode_costs_arb(),
             storage_costs in storage_costs_arb(),
             host_function_costs in host_function_costs_arb(),
@@ -155,6 +168,7 @@ pub mod gens {
             WasmConfig {
                 max_memory,
                 max_stack_height,
+                max_call_result_size,
                 opcode_costs,
                 storage_costs,
                 host_function_costs,
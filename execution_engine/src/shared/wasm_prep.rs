@@ -5,7 +5,12 @@ use parity_wasm::elements::{
 use pwasm_utils::{self, stack_height};
 use thiserror::Error;
 
+use casper_types::ProtocolVersion;
+
 use super::wasm_config::WasmConfig;
+use crate::core::engine_state::host_function_deprecations::{
+    DeprecatedHostFunction, HostFunctionDeprecations,
+};
 
 const DEFAULT_GAS_MODULE_NAME: &str = "env";
 /// Name of the internal gas function injected by [`pwasm_utils::inject_gas_counter`].
@@ -402,6 +407,33 @@ pub fn preprocess(
     Ok(module)
 }
 
+/// Returns the deprecations in force at `protocol_version` of the host functions imported by
+/// `module`.
+pub fn deprecated_imports<'a>(
+    module: &Module,
+    deprecations: &'a HostFunctionDeprecations,
+    protocol_version: ProtocolVersion,
+) -> Vec<&'a DeprecatedHostFunction> {
+    if deprecations.deprecations().is_empty() {
+        return Vec::new();
+    }
+    module
+        .import_section()
+        .map(|import_section| import_section.entries())
+        .unwrap_or(&[])
+        .iter()
+        .filter(|import| {
+            import.module() == DEFAULT_GAS_MODULE_NAME
+                && matches!(import.external(), External::Function(_))
+        })
+        .filter_map(|import| {
+            deprecations
+                .in_force(import.field(), protocol_version)
+                .map(|(_, deprecation)| deprecation)
+        })
+        .collect()
+}
+
 /// Returns a parity Module from the given bytes without making modifications or checking limits.
 pub fn deserialize(module_bytes: &[u8]) -> Result<Module, PreprocessingError> {
     parity_wasm::deserialize_buffer::<Module>(module_bytes).map_err(Into::into)
@@ -416,6 +448,7 @@ mod tests {
     };
 
     use super::*;
+    use crate::core::engine_state::host_function_deprecations::DeprecationAction;
 
     #[test]
     fn should_not_panic_on_empty_memory() {
@@ -586,4 +619,30 @@ mod tests {
             error,
         );
     }
+
+    #[test]
+    fn should_flag_imports_of_deprecated_host_functions() {
+        let module = builder::module()
+            .import()
+            .path("env", "casper_get_blocktime")
+            .with_external(External::Function(0))
+            .build()
+            .import()
+            .path("env", "casper_write")
+            .with_external(External::Function(0))
+            .build()
+            .build();
+        let deprecation = DeprecatedHostFunction {
+            name: "casper_get_blocktime".to_string(),
+            since: ProtocolVersion::from_parts(1, 5, 0),
+            action: DeprecationAction::Reject,
+        };
+        let deprecations = HostFunctionDeprecations::new(vec![deprecation.clone()]);
+
+        assert!(deprecated_imports(&module, &deprecations, ProtocolVersion::V1_0_0).is_empty());
+        assert_eq!(
+            deprecated_imports(&module, &deprecations, ProtocolVersion::from_parts(1, 5, 0)),
+            vec![&deprecation]
+        );
+    }
 }
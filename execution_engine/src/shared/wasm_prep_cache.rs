@@ -0,0 +1,386 @@
+//! On-disk cache of preprocessed Wasm modules.
+//!
+//! Preprocessing (validation, gas counter injection and stack height limiting) is repeated every
+//! time the same module bytes are executed. This cache stores the resulting module bytes on disk,
+//! keyed by the hash of the original bytes and by a version of the preprocessing parameters, so
+//! that executions of known modules, including ones after a restart, can skip it.
+//!
+//! Cached modules are trusted to have been instrumented, so the cache directory must only be
+//! writable by the node.
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use parity_wasm::elements::Module;
+use tracing::{debug, warn};
+
+use casper_hashing::Digest;
+use casper_types::bytesrepr::{self, ToBytes};
+
+use super::{
+    wasm_config::WasmConfig,
+    wasm_prep::{self, PreprocessingError},
+};
+
+/// Version of the preprocessing logic itself.
+///
+/// Must be bumped whenever [`wasm_prep::preprocess`] changes how it transforms modules.
+const PREPROCESSOR_VERSION: u8 = 1;
+
+/// Extension of the files holding cached modules.
+const CACHE_FILE_EXTENSION: &str = "wasm";
+
+/// Returns a digest identifying the inputs to preprocessing other than the module itself.
+///
+/// Only the parts of [`WasmConfig`] which affect instrumentation are included, so a change to the
+/// host function or storage costs does not invalidate cached modules.
+fn cost_table_version(wasm_config: &WasmConfig) -> Result<Digest, bytesrepr::Error> {
+    let mut bytes = vec![PREPROCESSOR_VERSION];
+    bytes.append(&mut wasm_config.max_memory.to_bytes()?);
+    bytes.append(&mut wasm_config.max_stack_height.to_bytes()?);
+    bytes.append(&mut wasm_config.opcode_costs().to_bytes()?);
+    Ok(Digest::hash(bytes))
+}
+
+#[derive(Debug, Default)]
+struct CacheIndex {
+    /// Size in bytes and last use of each cached module, by file name.
+    entries: HashMap<String, (u64, u64)>,
+    /// Total size in bytes of all cached modules.
+    total_size: u64,
+    /// Logical clock used to order uses of cached modules.
+    clock: u64,
+}
+
+impl CacheIndex {
+    fn touch(&mut self, file_name: &str) -> bool {
+        self.clock += 1;
+        match self.entries.get_mut(file_name) {
+            Some((_, last_used)) => {
+                *last_used = self.clock;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&mut self, file_name: String, size: u64) {
+        self.clock += 1;
+        if let Some((old_size, _)) = self.entries.insert(file_name, (size, self.clock)) {
+            self.total_size -= old_size;
+        }
+        self.total_size += size;
+    }
+
+    fn remove(&mut self, file_name: &str) {
+        if let Some((size, _)) = self.entries.remove(file_name) {
+            self.total_size -= size;
+        }
+    }
+
+    /// Returns the least recently used entry.
+    fn least_recently_used(&self) -> Option<String> {
+        self.entries
+            .iter()
+            .min_by_key(|(_, (_, last_used))| *last_used)
+            .map(|(file_name, _)| file_name.clone())
+    }
+}
+
+/// A size-limited on-disk cache of preprocessed Wasm modules.
+///
+/// Once the total size of cached modules exceeds the configured limit, the least recently used
+/// modules are evicted. Failures to read or write the cache are logged and otherwise ignored, as
+/// the module can always be preprocessed again.
+#[derive(Debug)]
+pub struct PreprocessedModuleCache {
+    dir: PathBuf,
+    max_size: u64,
+    index: Mutex<CacheIndex>,
+}
+
+impl PreprocessedModuleCache {
+    /// Opens the cache in `dir`, creating the directory if it doesn't exist.
+    ///
+    /// Modules already present in `dir` are retained, ordered for eviction by modification time.
+    pub fn open<P: AsRef<Path>>(dir: P, max_size: u64) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let mut existing = Vec::new();
+        for dir_entry in fs::read_dir(&dir)? {
+            let dir_entry = dir_entry?;
+            let path = dir_entry.path();
+            if path.extension().and_then(|extension| extension.to_str())
+                != Some(CACHE_FILE_EXTENSION)
+            {
+                continue;
+            }
+            let metadata = dir_entry.metadata()?;
+            if let Some(file_name) = path.file_name().and_then(|name| name.to_str()) {
+                existing.push((metadata.modified()?, file_name.to_string(), metadata.len()));
+            }
+        }
+        existing.sort();
+
+        let mut index = CacheIndex::default();
+        for (_, file_name, size) in existing {
+            index.insert(file_name, size);
+        }
+
+        let cache = PreprocessedModuleCache {
+            dir,
+            max_size,
+            index: Mutex::new(index),
+        };
+        cache.evict();
+        Ok(cache)
+    }
+
+    /// Returns the preprocessed form of `module_bytes`, from the cache if present.
+    ///
+    /// On a cache miss the module is preprocessed and the result is added to the cache.
+    pub fn get_or_preprocess(
+        &self,
+        wasm_config: WasmConfig,
+        module_bytes: &[u8],
+    ) -> Result<Module, PreprocessingError> {
+        let file_name = match cost_table_version(&wasm_config) {
+            Ok(version) => format!(
+                "{}-{}.{}",
+                base16::encode_lower(&Digest::hash(module_bytes)),
+                base16::encode_lower(&version),
+                CACHE_FILE_EXTENSION
+            ),
+            Err(error) => {
+                warn!(%error, "failed to compute cost table version, bypassing module cache");
+                return wasm_prep::preprocess(wasm_config, module_bytes);
+            }
+        };
+
+        if let Some(module) = self.get(&file_name) {
+            return Ok(module);
+        }
+
+        let module = wasm_prep::preprocess(wasm_config, module_bytes)?;
+        self.put(file_name, &module);
+        Ok(module)
+    }
+
+    /// Removes all cached modules.
+    ///
+    /// Should be called when the protocol is upgraded, as modules preprocessed under the previous
+    /// cost table will not be used again.
+    pub fn clear(&self) -> io::Result<()> {
+        let mut index = self.index.lock().expect("module cache index lock poisoned");
+        for file_name in index.entries.keys() {
+            match fs::remove_file(self.dir.join(file_name)) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                _ => (),
+            }
+        }
+        *index = CacheIndex::default();
+        Ok(())
+    }
+
+    /// Returns the total size in bytes of all cached modules.
+    pub fn size(&self) -> u64 {
+        self.index
+            .lock()
+            .expect("module cache index lock poisoned")
+            .total_size
+    }
+
+    fn get(&self, file_name: &str) -> Option<Module> {
+        if !self
+            .index
+            .lock()
+            .expect("module cache index lock poisoned")
+            .touch(file_name)
+        {
+            return None;
+        }
+
+        let path = self.dir.join(file_name);
+        let result = fs::read(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| wasm_prep::deserialize(&bytes).map_err(|error| error.to_string()));
+        match result {
+            Ok(module) => Some(module),
+            Err(error) => {
+                warn!(path = %path.display(), %error, "failed to read cached module, removing");
+                let _ = fs::remove_file(&path);
+                self.index
+                    .lock()
+                    .expect("module cache index lock poisoned")
+                    .remove(file_name);
+                None
+            }
+        }
+    }
+
+    fn put(&self, file_name: String, module: &Module) {
+        let bytes = match parity_wasm::serialize(module.clone()) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                warn!(%error, "failed to serialize preprocessed module for caching");
+                return;
+            }
+        };
+        let size = bytes.len() as u64;
+        if size > self.max_size {
+            return;
+        }
+
+        // Write to a temporary file first, so a crash never leaves a truncated module in place.
+        let path = self.dir.join(&file_name);
+        let temp_path = path.with_extension("tmp");
+        if let Err(error) =
+            fs::write(&temp_path, &bytes).and_then(|()| fs::rename(&temp_path, &path))
+        {
+            warn!(path = %path.display(), %error, "failed to write cached module");
+            let _ = fs::remove_file(&temp_path);
+            return;
+        }
+
+        self.index
+            .lock()
+            .expect("module cache index lock poisoned")
+            .insert(file_name, size);
+        self.evict();
+    }
+
+    /// Evicts least recently used modules until the cache is within its size limit.
+    fn evict(&self) {
+        let mut index = self.index.lock().expect("module cache index lock poisoned");
+        while index.total_size > self.max_size {
+            let file_name = match index.least_recently_used() {
+                Some(file_name) => file_name,
+                None => break,
+            };
+            let path = self.dir.join(&file_name);
+            if let Err(error) = fs::remove_file(&path) {
+                if error.kind() != io::ErrorKind::NotFound {
+                    warn!(path = %path.display(), %error, "failed to evict cached module");
+                }
+            }
+            debug!(path = %path.display(), "evicted cached module");
+            index.remove(&file_name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parity_wasm::builder;
+
+    use super::*;
+
+    fn module_bytes(export_name: &str) -> Vec<u8> {
+        let module = builder::module()
+            .function()
+            .signature()
+            .build()
+            .body()
+            .build()
+            .build()
+            .memory()
+            .with_min(1)
+            .build()
+            .export()
+            .field(export_name)
+            .internal()
+            .func(0)
+            .build()
+            .build();
+        parity_wasm::serialize(module).expect("should serialize")
+    }
+
+    #[test]
+    fn should_return_cached_module_after_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let module_bytes = module_bytes("call");
+        let expected = parity_wasm::serialize(
+            wasm_prep::preprocess(WasmConfig::default(), &module_bytes).unwrap(),
+        )
+        .unwrap();
+
+        let cache = PreprocessedModuleCache::open(dir.path(), u64::MAX).unwrap();
+        let module = cache
+            .get_or_preprocess(WasmConfig::default(), &module_bytes)
+            .unwrap();
+        assert_eq!(parity_wasm::serialize(module).unwrap(), expected);
+        assert!(cache.size() > 0);
+        drop(cache);
+
+        let cache = PreprocessedModuleCache::open(dir.path(), u64::MAX).unwrap();
+        assert!(cache.size() > 0);
+        let file_name = fs::read_dir(dir.path())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .file_name();
+        let cached = cache.get(file_name.to_str().unwrap()).unwrap();
+        assert_eq!(parity_wasm::serialize(cached).unwrap(), expected);
+    }
+
+    #[test]
+    fn should_key_by_cost_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let module_bytes = module_bytes("call");
+        let cache = PreprocessedModuleCache::open(dir.path(), u64::MAX).unwrap();
+
+        let mut wasm_config = WasmConfig::default();
+        let _ = cache.get_or_preprocess(wasm_config, &module_bytes).unwrap();
+        wasm_config.max_stack_height += 1;
+        let _ = cache.get_or_preprocess(wasm_config, &module_bytes).unwrap();
+
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn should_evict_least_recently_used_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = module_bytes("a");
+        let second = module_bytes("b");
+        let third = module_bytes("c");
+
+        let cache = PreprocessedModuleCache::open(dir.path(), u64::MAX).unwrap();
+        let _ = cache.get_or_preprocess(WasmConfig::default(), &first);
+        let _ = cache.get_or_preprocess(WasmConfig::default(), &second);
+        let max_size = cache.size();
+        drop(cache);
+
+        // The modules only differ in their export name, so there is room for exactly two of them.
+        // Use the first one again so that the second is the least recently used when the third
+        // is added.
+        let cache = PreprocessedModuleCache::open(dir.path(), max_size).unwrap();
+        let _ = cache.get_or_preprocess(WasmConfig::default(), &first);
+        let _ = cache.get_or_preprocess(WasmConfig::default(), &third);
+
+        assert_eq!(cache.size(), max_size);
+        let cached: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        let first_prefix = base16::encode_lower(&Digest::hash(&first));
+        let second_prefix = base16::encode_lower(&Digest::hash(&second));
+        assert!(cached.iter().any(|name| name.starts_with(&first_prefix)));
+        assert!(!cached.iter().any(|name| name.starts_with(&second_prefix)));
+    }
+
+    #[test]
+    fn should_clear_all_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = PreprocessedModuleCache::open(dir.path(), u64::MAX).unwrap();
+        let _ = cache.get_or_preprocess(WasmConfig::default(), &module_bytes("call"));
+        cache.clear().unwrap();
+
+        assert_eq!(cache.size(), 0);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -11,10 +11,10 @@ pub mod trie;
 /// Merkle Trie storage.
 pub mod trie_store;
 
-const MAX_DBS: u32 = 2;
+const MAX_DBS: u32 = 10;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-support"))]
 pub(crate) const DEFAULT_TEST_MAX_DB_SIZE: usize = 52_428_800; // 50 MiB
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-support"))]
 pub(crate) const DEFAULT_TEST_MAX_READERS: u32 = 512;
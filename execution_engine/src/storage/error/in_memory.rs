@@ -1,4 +1,4 @@
-use std::sync;
+use std::{io, sync};
 
 use thiserror::Error;
 
@@ -15,6 +15,14 @@ pub enum Error {
     /// Concurrency error.
     #[error("Another thread panicked while holding a lock")]
     Poison,
+
+    /// Error writing a value read from the store.
+    #[error("I/O error: {0}")]
+    Io(String),
+
+    /// A snapshot file is malformed or doesn't hold a complete state.
+    #[error("Invalid global state snapshot")]
+    InvalidSnapshot,
 }
 
 impl From<bytesrepr::Error> for Error {
@@ -23,6 +31,12 @@ impl From<bytesrepr::Error> for Error {
     }
 }
 
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error.to_string())
+    }
+}
+
 impl<T> From<sync::PoisonError<T>> for Error {
     fn from(_error: sync::PoisonError<T>) -> Self {
         Error::Poison
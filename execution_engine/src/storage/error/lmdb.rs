@@ -1,8 +1,9 @@
-use std::sync;
+use std::{io, sync};
 
 use lmdb as lmdb_external;
 use thiserror::Error;
 
+use casper_hashing::Digest;
 use casper_types::bytesrepr;
 
 use crate::storage::{error::in_memory, global_state::CommitError};
@@ -19,6 +20,14 @@ pub enum Error {
     #[error("{0}")]
     BytesRepr(bytesrepr::Error),
 
+    /// A stored trie couldn't be deserialized, and was quarantined.
+    #[error("Corrupted trie {0} quarantined: {1}")]
+    CorruptedTrie(Digest, bytesrepr::Error),
+
+    /// A state root to be retained is missing from the trie store.
+    #[error("State root {0} not found")]
+    RootNotFound(Digest),
+
     /// Concurrency error.
     #[error("Another thread panicked while holding a lock")]
     Poison,
@@ -26,6 +35,18 @@ pub enum Error {
     /// Error committing to execution engine.
     #[error(transparent)]
     CommitError(#[from] CommitError),
+
+    /// Error compressing or decompressing a trie.
+    #[error("Trie compression error: {0}")]
+    Compression(String),
+
+    /// Error writing a value read from the store.
+    #[error("I/O error: {0}")]
+    Io(String),
+
+    /// A snapshot file is malformed or doesn't hold a complete state.
+    #[error("Invalid global state snapshot")]
+    InvalidSnapshot,
 }
 
 impl wasmi::HostError for Error {}
@@ -36,6 +57,12 @@ impl From<bytesrepr::Error> for Error {
     }
 }
 
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error.to_string())
+    }
+}
+
 impl<T> From<sync::PoisonError<T>> for Error {
     fn from(_error: sync::PoisonError<T>) -> Self {
         Error::Poison
@@ -47,6 +74,8 @@ impl From<in_memory::Error> for Error {
         match error {
             in_memory::Error::BytesRepr(error) => Error::BytesRepr(error),
             in_memory::Error::Poison => Error::Poison,
+            in_memory::Error::Io(error) => Error::Io(error),
+            in_memory::Error::InvalidSnapshot => Error::InvalidSnapshot,
         }
     }
 }
@@ -0,0 +1,401 @@
+//! Hot backups of the LMDB global state.
+//!
+//! A backup is a tarball holding a [checkpoint](super::checkpoint) of global state along with a
+//! manifest of the state roots it includes.  The database is streamed by LMDB straight into the
+//! tarball from a single read transaction, so commits carry on meanwhile and no copy is staged on
+//! disk.  The manifest lists the anchored state roots complete both before and after the copy,
+//! which are therefore complete in the copy too.
+//!
+//! The tarball holds the files of the checkpoint followed by `manifest.json`, so that unpacking
+//! it gives a directory which opens like any checkpoint.
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_hashing::Digest;
+use casper_types::EraId;
+
+use crate::{
+    shared::newtypes::CorrelationId,
+    storage::{
+        error,
+        global_state::{
+            checkpoint::{CheckpointError, CHECKPOINT_STATE_ROOT_FILENAME},
+            lmdb::LmdbGlobalState,
+            StateProvider,
+        },
+        transaction_source::lmdb::EE_DB_FILENAME,
+    },
+};
+
+/// The name of the manifest in a backup tarball.
+pub const BACKUP_MANIFEST_FILENAME: &str = "manifest.json";
+
+const TAR_BLOCK_SIZE: usize = 512;
+/// Sizes from 8 GiB up don't fit in the octal size field of a ustar header.
+const MAX_OCTAL_SIZE: u64 = 0o77_777_777_777;
+
+/// Error returned when creating a backup.
+#[derive(Debug, Error)]
+pub enum BackupError {
+    /// The state root to back up is not in global state.
+    #[error(transparent)]
+    Checkpoint(#[from] CheckpointError),
+    /// Copying the database or listing the anchored state roots failed.
+    #[error("global state error: {0}")]
+    GlobalState(#[from] error::Error),
+    /// The manifest couldn't be serialized.
+    #[error("failed to serialize backup manifest: {0}")]
+    Manifest(#[from] serde_json::Error),
+    /// Writing the tarball failed.
+    #[error("failed to write backup tarball: {0}")]
+    Write(io::Error),
+    /// Accessing the backup file failed.
+    #[error("i/o error in {path}: {error}")]
+    Io {
+        /// The path which couldn't be accessed.
+        path: PathBuf,
+        /// The underlying error.
+        error: io::Error,
+    },
+}
+
+/// A state root included in a backup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupStateRoot {
+    /// The state root.
+    pub state_root: Digest,
+    /// The height of the block which produced it.
+    pub block_height: u64,
+    /// The era of the block which produced it.
+    pub era_id: EraId,
+}
+
+/// A file of a backup tarball other than the manifest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// The name of the file in the tarball.
+    pub name: String,
+    /// The size of the file in bytes.
+    pub size_bytes: u64,
+}
+
+/// The manifest of a backup.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// The state root the backup was taken at.
+    pub state_root: Digest,
+    /// The anchored state roots complete in the backup, in order of block height.  Empty unless
+    /// state root anchors are enabled.
+    pub state_roots: Vec<BackupStateRoot>,
+    /// The files preceding the manifest in the tarball.
+    pub files: Vec<BackupFile>,
+}
+
+impl Display for BackupManifest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "backup of state root {}", self.state_root)?;
+        match (self.state_roots.first(), self.state_roots.last()) {
+            (Some(first), Some(last)) => writeln!(
+                f,
+                "{} anchored state roots from block {} to block {}",
+                self.state_roots.len(),
+                first.block_height,
+                last.block_height
+            )?,
+            _ => writeln!(f, "no anchored state roots")?,
+        }
+        for file in &self.files {
+            writeln!(f, "  {}: {} bytes", file.name, file.size_bytes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a backup of global state at `state_root` to a new file at `path`.
+///
+/// Fails if `path` already exists.  The file is removed again if the backup fails.
+pub fn create_backup(
+    state: &LmdbGlobalState,
+    correlation_id: CorrelationId,
+    state_root: Digest,
+    path: &Path,
+) -> Result<BackupManifest, BackupError> {
+    let io_error = |error| BackupError::Io {
+        path: path.to_path_buf(),
+        error,
+    };
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(io_error)?;
+    let result = write_backup(state, correlation_id, state_root, &mut file)
+        .and_then(|manifest| file.sync_all().map(|()| manifest).map_err(io_error));
+    if result.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+/// Writes a backup of global state at `state_root` to `file` as a tarball, from the file's
+/// current offset.
+pub fn write_backup(
+    state: &LmdbGlobalState,
+    correlation_id: CorrelationId,
+    state_root: Digest,
+    file: &mut File,
+) -> Result<BackupManifest, BackupError> {
+    if state.get_trie_full(correlation_id, &state_root)?.is_none() {
+        return Err(CheckpointError::RootNotFound(state_root).into());
+    }
+    let anchored_before: BTreeSet<Digest> = state
+        .anchored_state_roots()?
+        .into_iter()
+        .map(|(state_root, _)| state_root)
+        .collect();
+
+    // The size of the database is only known once copied, so its header is written afterwards.
+    let header_offset = file.stream_position().map_err(BackupError::Write)?;
+    file.write_all(&[0; TAR_BLOCK_SIZE])
+        .map_err(BackupError::Write)?;
+    state.environment().copy_to_file(file, true)?;
+    let end_offset = file.stream_position().map_err(BackupError::Write)?;
+    let db_size = end_offset - header_offset - TAR_BLOCK_SIZE as u64;
+    write_tar_padding(file, db_size)
+        .and_then(|()| file.seek(SeekFrom::Start(header_offset)))
+        .and_then(|_| file.write_all(&tar_header(EE_DB_FILENAME, db_size)?))
+        .and_then(|()| file.seek(SeekFrom::End(0)))
+        .map_err(BackupError::Write)?;
+
+    let state_root_bytes = base16::encode_lower(&state_root).into_bytes();
+    write_tar_entry(
+        file,
+        CHECKPOINT_STATE_ROOT_FILENAME,
+        state_root_bytes.len() as u64,
+        &mut state_root_bytes.as_slice(),
+    )
+    .map_err(BackupError::Write)?;
+
+    let manifest = BackupManifest {
+        state_root,
+        state_roots: state
+            .anchored_state_roots()?
+            .into_iter()
+            .filter(|(state_root, _)| anchored_before.contains(state_root))
+            .map(|(state_root, anchor)| BackupStateRoot {
+                state_root,
+                block_height: anchor.block_height,
+                era_id: anchor.era_id,
+            })
+            .collect(),
+        files: vec![
+            BackupFile {
+                name: EE_DB_FILENAME.to_string(),
+                size_bytes: db_size,
+            },
+            BackupFile {
+                name: CHECKPOINT_STATE_ROOT_FILENAME.to_string(),
+                size_bytes: state_root_bytes.len() as u64,
+            },
+        ],
+    };
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)?;
+    write_tar_entry(
+        file,
+        BACKUP_MANIFEST_FILENAME,
+        manifest_bytes.len() as u64,
+        &mut manifest_bytes.as_slice(),
+    )
+    .and_then(|()| file.write_all(&[0; 2 * TAR_BLOCK_SIZE]))
+    .map_err(BackupError::Write)?;
+    Ok(manifest)
+}
+
+/// Writes a regular file entry of `size` bytes read from `source` to a tarball.
+fn write_tar_entry<W: Write, R: Read>(
+    writer: &mut W,
+    name: &str,
+    size: u64,
+    source: &mut R,
+) -> io::Result<()> {
+    writer.write_all(&tar_header(name, size)?)?;
+    let copied = io::copy(&mut source.take(size), writer)?;
+    if copied != size {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("{} shrank while being backed up", name),
+        ));
+    }
+    write_tar_padding(writer, size)
+}
+
+/// Pads a tarball entry of `size` bytes to a whole number of blocks.
+fn write_tar_padding<W: Write>(writer: &mut W, size: u64) -> io::Result<()> {
+    let padding = (TAR_BLOCK_SIZE - (size % TAR_BLOCK_SIZE as u64) as usize) % TAR_BLOCK_SIZE;
+    writer.write_all(&[0; TAR_BLOCK_SIZE][..padding])
+}
+
+/// Returns the ustar header of a regular file.
+fn tar_header(name: &str, size: u64) -> io::Result<[u8; TAR_BLOCK_SIZE]> {
+    let mut header = [0u8; TAR_BLOCK_SIZE];
+    if name.len() >= 100 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("file name {} too long for a tar header", name),
+        ));
+    }
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[108..116].copy_from_slice(b"0000000\0");
+    header[116..124].copy_from_slice(b"0000000\0");
+    if size <= MAX_OCTAL_SIZE {
+        header[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+    } else {
+        // The GNU base-256 encoding, understood by all common tar implementations.
+        header[124] = 0x80;
+        header[128..136].copy_from_slice(&size.to_be_bytes());
+    }
+    header[136..148].copy_from_slice(b"00000000000\0");
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    // The checksum is computed with its own field taken as spaces.
+    header[148..156].copy_from_slice(b"        ");
+    let checksum: u32 = header.iter().map(|byte| u32::from(*byte)).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str, sync::Arc};
+
+    use lmdb::DatabaseFlags;
+    use tempfile::tempdir;
+
+    use casper_types::{account::AccountHash, CLValue, Key, StoredValue};
+
+    use super::*;
+    use crate::{
+        shared::{additive_map::AdditiveMap, transform::Transform},
+        storage::{
+            global_state::CommitProvider, transaction_source::lmdb::LmdbEnvironment,
+            trie_store::lmdb::LmdbTrieStore, DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS,
+        },
+    };
+
+    /// Splits a tarball into the names and contents of its entries.
+    fn untar(mut tarball: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut entries = Vec::new();
+        loop {
+            let (header, rest) = tarball.split_at(TAR_BLOCK_SIZE);
+            if header.iter().all(|byte| *byte == 0) {
+                assert!(rest.iter().all(|byte| *byte == 0));
+                return entries;
+            }
+            let name_len = header[..100].iter().position(|byte| *byte == 0).unwrap();
+            let name = str::from_utf8(&header[..name_len]).unwrap().to_string();
+            let size_field = str::from_utf8(&header[124..135]).unwrap();
+            let size = usize::from_str_radix(size_field, 8).unwrap();
+            let mut checksum_header = header.to_vec();
+            checksum_header[148..156].copy_from_slice(b"        ");
+            let checksum: u32 = checksum_header.iter().map(|byte| u32::from(*byte)).sum();
+            let checksum_field = str::from_utf8(&header[148..154]).unwrap();
+            assert_eq!(u32::from_str_radix(checksum_field, 8).unwrap(), checksum);
+            entries.push((name, rest[..size].to_vec()));
+            let padded_size = (size + TAR_BLOCK_SIZE - 1) / TAR_BLOCK_SIZE * TAR_BLOCK_SIZE;
+            tarball = &rest[padded_size..];
+        }
+    }
+
+    #[test]
+    fn should_write_backup_tarball_with_manifest() {
+        let correlation_id = CorrelationId::new();
+        let live_dir = tempdir().unwrap();
+        let environment = Arc::new(
+            LmdbEnvironment::new(
+                live_dir.path(),
+                DEFAULT_TEST_MAX_DB_SIZE,
+                DEFAULT_TEST_MAX_READERS,
+                true,
+            )
+            .unwrap(),
+        );
+        let trie_store =
+            Arc::new(LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+        let state = LmdbGlobalState::empty(environment, trie_store)
+            .unwrap()
+            .with_state_root_anchors()
+            .unwrap();
+        let mut effects = AdditiveMap::new();
+        let value = StoredValue::CLValue(CLValue::from_t(1i32).unwrap());
+        effects.insert(
+            Key::Account(AccountHash::new([1; 32])),
+            Transform::Write(value),
+        );
+        let root = state
+            .commit(correlation_id, state.empty_state_root_hash(), effects)
+            .unwrap();
+        state.anchor_state_root(root, 1, EraId::new(0)).unwrap();
+
+        let backup_dir = tempdir().unwrap();
+        let backup_path = backup_dir.path().join("backup.tar");
+        let manifest = create_backup(&state, correlation_id, root, &backup_path).unwrap();
+        assert_eq!(manifest.state_root, root);
+        assert_eq!(
+            manifest.state_roots,
+            vec![BackupStateRoot {
+                state_root: root,
+                block_height: 1,
+                era_id: EraId::new(0),
+            }]
+        );
+
+        let tarball = fs::read(&backup_path).unwrap();
+        let entries = untar(&tarball);
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                EE_DB_FILENAME,
+                CHECKPOINT_STATE_ROOT_FILENAME,
+                BACKUP_MANIFEST_FILENAME,
+            ]
+        );
+        let parsed: BackupManifest = serde_json::from_slice(&entries[2].1).unwrap();
+        assert_eq!(parsed, manifest);
+        assert_eq!(entries[0].1.len() as u64, manifest.files[0].size_bytes);
+        assert_eq!(entries[1].1, base16::encode_lower(&root).into_bytes());
+
+        // The unpacked database opens like any checkpoint, with the backed up state root.
+        let restored_dir = tempdir().unwrap();
+        fs::write(restored_dir.path().join(EE_DB_FILENAME), &entries[0].1).unwrap();
+        let environment = Arc::new(
+            LmdbEnvironment::new(
+                restored_dir.path(),
+                DEFAULT_TEST_MAX_DB_SIZE,
+                DEFAULT_TEST_MAX_READERS,
+                true,
+            )
+            .unwrap(),
+        );
+        let trie_store = Arc::new(LmdbTrieStore::open(&environment, None).unwrap());
+        let restored = LmdbGlobalState::new(environment, trie_store, state.empty_state_root_hash());
+        assert!(restored.checkout(root).unwrap().is_some());
+
+        // The backup doesn't overwrite an existing file.
+        assert!(matches!(
+            create_backup(&state, correlation_id, root, &backup_path),
+            Err(BackupError::Io { .. })
+        ));
+        assert!(backup_path.exists());
+    }
+}
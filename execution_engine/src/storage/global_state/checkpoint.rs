@@ -0,0 +1,219 @@
+//! Checkpoints of the LMDB global state.
+//!
+//! A checkpoint is a copy of the whole LMDB environment backing global state, i.e. the trie store
+//! together with any auxiliary databases kept in the same environment, taken while the node keeps
+//! executing blocks.  Since tries are never modified once written, every state root present when
+//! the copy starts is complete in the copy, so a checkpoint is a consistent backup of the state
+//! root it was taken at.
+//!
+//! A checkpoint directory holds the copied database, which can be opened like any other global
+//! state directory, and a metadata file naming the state root the checkpoint was taken at.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use casper_hashing::Digest;
+
+use crate::{
+    shared::newtypes::CorrelationId,
+    storage::{
+        error,
+        global_state::{lmdb::LmdbGlobalState, StateProvider},
+        transaction_source::lmdb::EE_DB_FILENAME,
+    },
+};
+
+/// The name of the file in a checkpoint directory which holds the checkpoint's state root.
+pub const CHECKPOINT_STATE_ROOT_FILENAME: &str = "state_root_hash";
+
+/// Error returned when creating or reading a checkpoint.
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    /// Creating or reading the checkpoint directory failed.
+    #[error("i/o error in {path}: {error}")]
+    Io {
+        /// The path which couldn't be accessed.
+        path: PathBuf,
+        /// The underlying error.
+        error: io::Error,
+    },
+    /// Reading global state or copying the database failed.
+    #[error("global state error: {0}")]
+    GlobalState(#[from] error::Error),
+    /// The state root to checkpoint is not in global state.
+    #[error("state root {0} not found")]
+    RootNotFound(Digest),
+    /// The checkpoint directory already holds a database.
+    #[error("{0} already holds a database")]
+    AlreadyExists(PathBuf),
+    /// The checkpoint's metadata file doesn't hold a valid state root hash.
+    #[error("invalid checkpoint metadata in {0}")]
+    InvalidMetadata(PathBuf),
+}
+
+/// A checkpoint of global state.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Checkpoint {
+    /// The state root the checkpoint was taken at.
+    pub state_root: Digest,
+    /// The directory holding the checkpoint.
+    pub dir: PathBuf,
+    /// The size of the copied database in bytes.
+    pub size_bytes: u64,
+    /// How long copying the database took.
+    pub duration: Duration,
+}
+
+/// Copies the environment of `state` into `dir`, recording `state_root` as the checkpoint's state
+/// root.
+///
+/// `dir` is created if missing.  The copy is compacted, so it is usually smaller than the live
+/// database.
+pub fn create_checkpoint(
+    state: &LmdbGlobalState,
+    correlation_id: CorrelationId,
+    state_root: Digest,
+    dir: &Path,
+) -> Result<Checkpoint, CheckpointError> {
+    if state.get_trie_full(correlation_id, &state_root)?.is_none() {
+        return Err(CheckpointError::RootNotFound(state_root));
+    }
+    let db_path = dir.join(EE_DB_FILENAME);
+    if db_path.exists() {
+        return Err(CheckpointError::AlreadyExists(dir.to_path_buf()));
+    }
+    fs::create_dir_all(dir).map_err(|error| CheckpointError::Io {
+        path: dir.to_path_buf(),
+        error,
+    })?;
+
+    let start = Instant::now();
+    state.environment().copy_to(dir, true)?;
+    let duration = start.elapsed();
+
+    let metadata_path = dir.join(CHECKPOINT_STATE_ROOT_FILENAME);
+    fs::write(&metadata_path, base16::encode_lower(&state_root)).map_err(|error| {
+        CheckpointError::Io {
+            path: metadata_path,
+            error,
+        }
+    })?;
+    let size_bytes = fs::metadata(&db_path)
+        .map_err(|error| CheckpointError::Io {
+            path: db_path,
+            error,
+        })?
+        .len();
+    Ok(Checkpoint {
+        state_root,
+        dir: dir.to_path_buf(),
+        size_bytes,
+        duration,
+    })
+}
+
+/// Reads the state root a checkpoint in `dir` was taken at.
+pub fn read_checkpoint_state_root(dir: &Path) -> Result<Digest, CheckpointError> {
+    let metadata_path = dir.join(CHECKPOINT_STATE_ROOT_FILENAME);
+    let hex = fs::read_to_string(&metadata_path).map_err(|error| CheckpointError::Io {
+        path: metadata_path.clone(),
+        error,
+    })?;
+    Digest::from_hex(hex.trim()).map_err(|_| CheckpointError::InvalidMetadata(metadata_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use lmdb::DatabaseFlags;
+    use tempfile::tempdir;
+
+    use casper_types::{account::AccountHash, CLValue, Key, StoredValue};
+
+    use super::*;
+    use crate::{
+        shared::{additive_map::AdditiveMap, transform::Transform},
+        storage::{
+            global_state::{CommitProvider, StateReader},
+            transaction_source::lmdb::LmdbEnvironment,
+            trie_store::lmdb::LmdbTrieStore,
+            DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS,
+        },
+    };
+
+    fn account_key(i: u8) -> Key {
+        Key::Account(AccountHash::new([i; 32]))
+    }
+
+    fn commit_value(state: &LmdbGlobalState, root: Digest, i: u8) -> Digest {
+        let mut effects = AdditiveMap::new();
+        let value = StoredValue::CLValue(CLValue::from_t(i32::from(i)).unwrap());
+        effects.insert(account_key(i), Transform::Write(value));
+        state.commit(CorrelationId::new(), root, effects).unwrap()
+    }
+
+    #[test]
+    fn should_create_and_open_checkpoint() {
+        let correlation_id = CorrelationId::new();
+        let live_dir = tempdir().unwrap();
+        let environment = Arc::new(
+            LmdbEnvironment::new(
+                live_dir.path(),
+                DEFAULT_TEST_MAX_DB_SIZE,
+                DEFAULT_TEST_MAX_READERS,
+                true,
+            )
+            .unwrap(),
+        );
+        let trie_store =
+            Arc::new(LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+        let state = LmdbGlobalState::empty(environment, trie_store).unwrap();
+        let root = commit_value(&state, state.empty_state_root_hash(), 1);
+
+        let checkpoint_dir = tempdir().unwrap();
+        let missing_root = Digest::hash(b"missing");
+        assert!(matches!(
+            create_checkpoint(&state, correlation_id, missing_root, checkpoint_dir.path()),
+            Err(CheckpointError::RootNotFound(_))
+        ));
+        let checkpoint =
+            create_checkpoint(&state, correlation_id, root, checkpoint_dir.path()).unwrap();
+        assert_eq!(checkpoint.state_root, root);
+        assert!(checkpoint.size_bytes > 0);
+        assert!(matches!(
+            create_checkpoint(&state, correlation_id, root, checkpoint_dir.path()),
+            Err(CheckpointError::AlreadyExists(_))
+        ));
+
+        // Commits after the checkpoint don't show up in it.
+        let later_root = commit_value(&state, root, 2);
+
+        assert_eq!(
+            read_checkpoint_state_root(checkpoint_dir.path()).unwrap(),
+            root
+        );
+        let environment = Arc::new(
+            LmdbEnvironment::new(
+                checkpoint_dir.path(),
+                DEFAULT_TEST_MAX_DB_SIZE,
+                DEFAULT_TEST_MAX_READERS,
+                true,
+            )
+            .unwrap(),
+        );
+        let trie_store = Arc::new(LmdbTrieStore::open(&environment, None).unwrap());
+        let restored = LmdbGlobalState::new(environment, trie_store, state.empty_state_root_hash());
+        let view = restored.checkout(root).unwrap().unwrap();
+        assert!(view
+            .read(correlation_id, &account_key(1))
+            .unwrap()
+            .is_some());
+        assert!(restored.checkout(later_root).unwrap().is_none());
+    }
+}
@@ -0,0 +1,287 @@
+//! Compaction of the LMDB global state.
+//!
+//! LMDB never shrinks its database file: pages freed by pruning or by overwritten auxiliary data
+//! are reused for later writes, but stay part of the file.  Compacting rewrites the file without
+//! its free pages.
+//!
+//! The file can't be replaced while it is open, so compaction is requested while the node runs and
+//! performed the next time global state is opened, before anything else reads or writes it.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::storage::{
+    error,
+    transaction_source::lmdb::{LmdbEnvironment, EE_DB_FILENAME},
+};
+
+/// The name of the file in the global state directory whose presence requests compaction.
+const COMPACTION_REQUESTED_FILENAME: &str = "compaction_requested";
+/// The name of the directory the compacted copy of the database is written to.
+const COMPACTION_DIR_NAME: &str = "compaction";
+
+/// Error returned when requesting or performing compaction.
+#[derive(Debug, Error)]
+pub enum CompactionError {
+    /// Accessing the global state directory failed.
+    #[error("i/o error in {path}: {error}")]
+    Io {
+        /// The path which couldn't be accessed.
+        path: PathBuf,
+        /// The underlying error.
+        error: io::Error,
+    },
+    /// Opening or copying the database failed.
+    #[error("global state error: {0}")]
+    GlobalState(#[from] error::Error),
+    /// The file system doesn't have room for a compacted copy of the database.
+    #[error(
+        "not enough free space to compact global state: {required} bytes required, {available} \
+        available"
+    )]
+    InsufficientSpace {
+        /// The size of the database, which the compacted copy may take up at most.
+        required: u64,
+        /// The free space available in the global state directory.
+        available: u64,
+    },
+}
+
+/// The outcome of compacting the global state database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Compaction {
+    /// The size of the database file before compacting, in bytes.
+    pub size_before: u64,
+    /// The size of the database file after compacting, in bytes.
+    pub size_after: u64,
+    /// How long compacting took.
+    pub duration: Duration,
+}
+
+/// Requests that the global state database in `dir` be compacted the next time it is opened.
+pub fn request_compaction(dir: &Path) -> Result<(), CompactionError> {
+    let path = dir.join(COMPACTION_REQUESTED_FILENAME);
+    fs::write(&path, b"").map_err(|error| CompactionError::Io { path, error })
+}
+
+/// Returns whether compaction of the global state database in `dir` has been requested.
+pub fn is_compaction_requested(dir: &Path) -> bool {
+    dir.join(COMPACTION_REQUESTED_FILENAME).exists()
+}
+
+/// Withdraws a request to compact the global state database in `dir`, if any.
+pub fn clear_compaction_request(dir: &Path) -> Result<(), CompactionError> {
+    let path = dir.join(COMPACTION_REQUESTED_FILENAME);
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(CompactionError::Io { path, error }),
+    }
+}
+
+/// Compacts the global state database in `dir` if compaction has been requested, clearing the
+/// request.
+///
+/// See [`compact`] for the requirements on the database.
+pub fn compact_if_requested(
+    dir: &Path,
+    map_size: usize,
+    max_readers: u32,
+) -> Result<Option<Compaction>, CompactionError> {
+    if !is_compaction_requested(dir) {
+        return Ok(None);
+    }
+    let compaction = compact(dir, map_size, max_readers)?;
+    clear_compaction_request(dir)?;
+    Ok(Some(compaction))
+}
+
+/// Rewrites the global state database in `dir` without its free pages.
+///
+/// The database must not be open, in this process or any other, as writes made while compacting
+/// would be lost.  The compacted copy replaces the database file only once fully written, so an
+/// interruption leaves the original database in place.  As the copy may be as large as the
+/// database, compaction is refused unless there is at least as much free space.
+pub fn compact(
+    dir: &Path,
+    map_size: usize,
+    max_readers: u32,
+) -> Result<Compaction, CompactionError> {
+    let db_path = dir.join(EE_DB_FILENAME);
+    let size_before = file_size(&db_path)?;
+    let compaction_dir = dir.join(COMPACTION_DIR_NAME);
+    // Discard the partial copy of an interrupted compaction, if any.
+    if compaction_dir.exists() {
+        fs::remove_dir_all(&compaction_dir).map_err(|error| CompactionError::Io {
+            path: compaction_dir.clone(),
+            error,
+        })?;
+    }
+    let available = fs2::available_space(dir).map_err(|error| CompactionError::Io {
+        path: dir.to_path_buf(),
+        error,
+    })?;
+    if available < size_before {
+        return Err(CompactionError::InsufficientSpace {
+            required: size_before,
+            available,
+        });
+    }
+    fs::create_dir_all(&compaction_dir).map_err(|error| CompactionError::Io {
+        path: compaction_dir.clone(),
+        error,
+    })?;
+
+    let start = Instant::now();
+    {
+        let environment = LmdbEnvironment::new(dir, map_size, max_readers, false)?;
+        environment.copy_to(&compaction_dir, true)?;
+    }
+    let compacted_path = compaction_dir.join(EE_DB_FILENAME);
+    let size_after = file_size(&compacted_path)?;
+    fs::rename(&compacted_path, &db_path).map_err(|error| CompactionError::Io {
+        path: db_path,
+        error,
+    })?;
+    let duration = start.elapsed();
+    fs::remove_dir_all(&compaction_dir).map_err(|error| CompactionError::Io {
+        path: compaction_dir,
+        error,
+    })?;
+    Ok(Compaction {
+        size_before,
+        size_after,
+        duration,
+    })
+}
+
+fn file_size(path: &Path) -> Result<u64, CompactionError> {
+    fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .map_err(|error| CompactionError::Io {
+            path: path.to_path_buf(),
+            error,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use lmdb::DatabaseFlags;
+    use tempfile::tempdir;
+
+    use casper_hashing::Digest;
+    use casper_types::{account::AccountHash, CLValue, Key, StoredValue};
+
+    use super::*;
+    use crate::{
+        shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
+        storage::{
+            global_state::{lmdb::LmdbGlobalState, CommitProvider, StateProvider, StateReader},
+            trie_store::lmdb::LmdbTrieStore,
+            DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS,
+        },
+    };
+
+    fn account_key(i: u8) -> Key {
+        Key::Account(AccountHash::new([i; 32]))
+    }
+
+    fn open_state(dir: &Path, state_root: Option<Digest>) -> LmdbGlobalState {
+        let environment = Arc::new(
+            LmdbEnvironment::new(
+                dir,
+                DEFAULT_TEST_MAX_DB_SIZE,
+                DEFAULT_TEST_MAX_READERS,
+                false,
+            )
+            .unwrap(),
+        );
+        match state_root {
+            Some(state_root) => {
+                let trie_store = Arc::new(LmdbTrieStore::open(&environment, None).unwrap());
+                LmdbGlobalState::new(environment, trie_store, state_root)
+            }
+            None => {
+                let trie_store = Arc::new(
+                    LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap(),
+                );
+                LmdbGlobalState::empty(environment, trie_store).unwrap()
+            }
+        }
+    }
+
+    #[test]
+    fn should_compact_requested_database_keeping_its_tries() {
+        let correlation_id = CorrelationId::new();
+        let dir = tempdir().unwrap();
+        let (empty_root, root) = {
+            let state = open_state(dir.path(), None);
+            let mut root = state.empty_state_root_hash();
+            for i in 0..=u8::MAX {
+                let mut effects = AdditiveMap::new();
+                let value = StoredValue::CLValue(CLValue::from_t(vec![i; 1024]).unwrap());
+                effects.insert(account_key(i), Transform::Write(value));
+                root = state.commit(correlation_id, root, effects).unwrap();
+            }
+            // Pruning every root but the last frees the pages of the intermediate tries.
+            state.prune(correlation_id, &[root], 100).unwrap();
+            (state.empty_state_root_hash(), root)
+        };
+
+        assert!(compact_if_requested(
+            dir.path(),
+            DEFAULT_TEST_MAX_DB_SIZE,
+            DEFAULT_TEST_MAX_READERS
+        )
+        .unwrap()
+        .is_none());
+        request_compaction(dir.path()).unwrap();
+        assert!(is_compaction_requested(dir.path()));
+        let compaction = compact_if_requested(
+            dir.path(),
+            DEFAULT_TEST_MAX_DB_SIZE,
+            DEFAULT_TEST_MAX_READERS,
+        )
+        .unwrap()
+        .expect("should compact");
+        assert!(compaction.size_after < compaction.size_before);
+        assert!(!is_compaction_requested(dir.path()));
+        assert!(!dir.path().join(COMPACTION_DIR_NAME).exists());
+
+        let state = open_state(dir.path(), Some(empty_root));
+        let view = state.checkout(root).unwrap().expect("should have root");
+        for i in 0..=u8::MAX {
+            assert!(view
+                .read(correlation_id, &account_key(i))
+                .unwrap()
+                .is_some());
+        }
+    }
+
+    #[test]
+    fn should_keep_request_after_failed_compaction_until_cleared() {
+        let dir = tempdir().unwrap();
+        request_compaction(dir.path()).unwrap();
+        // There is no database to compact.
+        assert!(matches!(
+            compact_if_requested(
+                dir.path(),
+                DEFAULT_TEST_MAX_DB_SIZE,
+                DEFAULT_TEST_MAX_READERS
+            ),
+            Err(CompactionError::Io { .. })
+        ));
+        assert!(is_compaction_requested(dir.path()));
+
+        clear_compaction_request(dir.path()).unwrap();
+        assert!(!is_compaction_requested(dir.path()));
+        clear_compaction_request(dir.path()).unwrap();
+    }
+}
@@ -1,8 +1,17 @@
-use std::{ops::Deref, sync::Arc};
+use std::{
+    convert::TryFrom,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    ops::Deref,
+    path::Path,
+    sync::Arc,
+};
 
 use casper_hashing::Digest;
-use casper_types::{Key, StoredValue};
+use casper_types::{bytesrepr::Bytes, Key, StoredValue};
 
+#[cfg(any(test, feature = "test-support"))]
+use crate::storage::transaction_source::fault_injection::{FaultConfig, FaultStats};
 use crate::{
     shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
     storage::{
@@ -16,20 +25,30 @@ use crate::{
             Transaction, TransactionSource,
         },
         trie::{
-            merkle_proof::TrieMerkleProof, operations::create_hashed_empty_trie, Trie, TrieRaw,
+            merkle_proof::TrieMerkleProof, operations::create_hashed_empty_trie,
+            path_proof::TriePathProof, Trie, TrieRaw,
         },
         trie_store::{
             in_memory::InMemoryTrieStore,
             operations::{
-                self, keys_with_prefix, missing_children, put_trie, read, read_with_proof,
-                ReadResult, WriteResult,
+                self, keys_with_prefix, leaves_with_prefix, missing_children, put_trie, read,
+                read_with_proof, ReadResult, WriteResult,
             },
+            TrieStore,
         },
     },
 };
 
+const SNAPSHOT_MAGIC: &[u8; 8] = b"CSPRSNAP";
+const SNAPSHOT_VERSION: u8 = 1;
+
 /// Global state implemented purely in memory only. No state is saved to disk. This is mostly
 /// used for testing purposes.
+///
+/// The whole state can be written to a snapshot file with
+/// [`write_snapshot`](InMemoryGlobalState::write_snapshot) and restored from it with
+/// [`read_snapshot`](InMemoryGlobalState::read_snapshot), e.g. to keep test fixtures in a single
+/// file.
 pub struct InMemoryGlobalState {
     /// Environment for `InMemoryGlobalState`.
     /// Basically empty because this global state does not support transactions.
@@ -111,10 +130,68 @@ impl InMemoryGlobalState {
         Ok((state, current_root))
     }
 
+    /// Writes the whole state to a snapshot file at `path`, along with `state_root`, the state
+    /// root to resume from once restored.
+    ///
+    /// The file holds every trie of the state, so it can be restored at any state root it holds.
+    /// The same state always produces the same file.
+    pub fn write_snapshot(&self, state_root: Digest, path: &Path) -> Result<(), error::Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(SNAPSHOT_MAGIC)?;
+        writer.write_all(&[SNAPSHOT_VERSION])?;
+        writer.write_all(&state_root.value())?;
+        self.environment.write_snapshot(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Restores a state from a snapshot file written by
+    /// [`write_snapshot`](InMemoryGlobalState::write_snapshot), returning it along with the state
+    /// root recorded in the file.
+    ///
+    /// Fails if any trie below that state root is missing from the file.
+    pub fn read_snapshot(path: &Path) -> Result<(Self, Digest), error::Error> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = [0; SNAPSHOT_MAGIC.len() + 1 + Digest::LENGTH];
+        reader.read_exact(&mut header)?;
+        if &header[..SNAPSHOT_MAGIC.len()] != SNAPSHOT_MAGIC
+            || header[SNAPSHOT_MAGIC.len()] != SNAPSHOT_VERSION
+        {
+            return Err(in_memory::Error::InvalidSnapshot.into());
+        }
+        let state_root = Digest::try_from(&header[SNAPSHOT_MAGIC.len() + 1..])
+            .map_err(|_| in_memory::Error::InvalidSnapshot)?;
+
+        let environment = Arc::new(InMemoryEnvironment::read_snapshot(reader)?);
+        let trie_store = Arc::new(InMemoryTrieStore::new(&environment, None));
+        let (empty_root_hash, _) = create_hashed_empty_trie::<Key, StoredValue>()?;
+        let state = InMemoryGlobalState::new(environment, trie_store, empty_root_hash);
+        if !state
+            .missing_descendants(CorrelationId::new(), state_root, 1)?
+            .is_empty()
+        {
+            return Err(in_memory::Error::InvalidSnapshot.into());
+        }
+        Ok((state, state_root))
+    }
+
     /// Returns the empty root hash owned by this `InMemoryGlobalState`.
     pub fn empty_root_hash(&self) -> Digest {
         self.empty_root_hash
     }
+
+    /// Starts injecting faults into reads of this state as described by `config`, or stops
+    /// injecting them if `config` is `None`.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn set_fault_config(&self, config: Option<FaultConfig>) -> Result<(), error::Error> {
+        Ok(self.environment.set_fault_config(config)?)
+    }
+
+    /// Returns the statistics of faults injected since they were last configured.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn fault_stats(&self) -> Result<FaultStats, error::Error> {
+        Ok(self.environment.fault_stats()?)
+    }
 }
 
 impl StateReader<Key, StoredValue> for InMemoryGlobalStateView {
@@ -174,6 +251,25 @@ impl StateReader<Key, StoredValue> for InMemoryGlobalStateView {
         Ok(ret)
     }
 
+    fn read_with_path_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TriePathProof<Key, StoredValue>>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let ret =
+            match self
+                .store
+                .read_with_path_proof(correlation_id, &txn, &self.root_hash, key)?
+            {
+                ReadResult::Found(proof) => Some(proof),
+                ReadResult::NotFound => None,
+                ReadResult::RootNotFound => panic!("InMemoryGlobalState has invalid root"),
+            };
+        txn.commit()?;
+        Ok(ret)
+    }
+
     fn keys_with_prefix(
         &self,
         correlation_id: CorrelationId,
@@ -197,6 +293,30 @@ impl StateReader<Key, StoredValue> for InMemoryGlobalStateView {
         txn.commit()?;
         Ok(ret)
     }
+
+    fn visit_leaves_with_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        prefix: &[u8],
+        visit: &mut dyn FnMut(Key, StoredValue) -> bool,
+    ) -> Result<(), Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let leaves_iter = leaves_with_prefix::<Key, StoredValue, _, _>(
+            correlation_id,
+            &txn,
+            self.store.deref(),
+            &self.root_hash,
+            prefix,
+        );
+        for result in leaves_iter {
+            let (key, value) = result?;
+            if !visit(key, value) {
+                break;
+            }
+        }
+        txn.commit()?;
+        Ok(())
+    }
 }
 
 impl CommitProvider for InMemoryGlobalState {
@@ -245,9 +365,16 @@ impl StateProvider for InMemoryGlobalState {
         trie_key: &Digest,
     ) -> Result<Option<TrieRaw>, Self::Error> {
         let txn = self.environment.create_read_txn()?;
-        let ret: Option<TrieRaw> =
-            Store::<Digest, Trie<Digest, StoredValue>>::get_raw(&*self.trie_store, &txn, trie_key)?
-                .map(TrieRaw::new);
+        // The trie is written straight into the returned buffer, rather than copied out of an
+        // intermediate one.
+        let mut trie_bytes = Vec::new();
+        let ret: Option<TrieRaw> = Store::<Digest, Trie<Digest, StoredValue>>::get_into(
+            &*self.trie_store,
+            &txn,
+            trie_key,
+            &mut trie_bytes,
+        )?
+        .then(|| TrieRaw::new(Bytes::from(trie_bytes)));
         txn.commit()?;
         Ok(ret)
     }
@@ -265,6 +392,28 @@ impl StateProvider for InMemoryGlobalState {
         Ok(trie_hash)
     }
 
+    fn put_tries(
+        &self,
+        correlation_id: CorrelationId,
+        tries: &[&[u8]],
+    ) -> Result<Vec<Digest>, Self::Error> {
+        let mut txn = self.environment.create_read_write_txn()?;
+        let mut trie_hashes = Vec::with_capacity(tries.len());
+        for trie in tries {
+            trie_hashes.push(put_trie::<
+                Key,
+                StoredValue,
+                InMemoryReadWriteTransaction,
+                InMemoryTrieStore,
+                Self::Error,
+            >(
+                correlation_id, &mut txn, &self.trie_store, trie
+            )?);
+        }
+        txn.commit()?;
+        Ok(trie_hashes)
+    }
+
     /// Finds all of the keys of missing directly descendant `Trie<Key,StoredValue>` values.
     fn missing_children(
         &self,
@@ -283,10 +432,37 @@ impl StateProvider for InMemoryGlobalState {
         txn.commit()?;
         Ok(missing_descendants)
     }
+
+    /// Finds the first `limit` missing `Trie<Key,StoredValue>` values below `state_root`.
+    fn missing_descendants(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        limit: usize,
+    ) -> Result<Vec<Digest>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let missing_hashes = operations::missing_descendants::<
+            Key,
+            StoredValue,
+            InMemoryReadTransaction,
+            InMemoryTrieStore,
+            Self::Error,
+        >(
+            correlation_id,
+            &txn,
+            self.trie_store.deref(),
+            state_root,
+            limit,
+        )?;
+        txn.commit()?;
+        Ok(missing_hashes)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+
     use casper_hashing::Digest;
     use casper_types::{account::AccountHash, CLValue};
 
@@ -424,6 +600,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn restores_state_from_snapshot() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("state.snapshot");
+        state.write_snapshot(root_hash, &path).unwrap();
+
+        let (restored, restored_root_hash) = InMemoryGlobalState::read_snapshot(&path).unwrap();
+        assert_eq!(restored_root_hash, root_hash);
+        assert_eq!(restored.empty_root(), state.empty_root());
+        let checkout = restored.checkout(root_hash).unwrap().unwrap();
+        for TestPair { key, value } in create_test_pairs().iter().cloned() {
+            assert_eq!(Some(value), checkout.read(correlation_id, &key).unwrap());
+        }
+
+        // The restored state writes the same snapshot.
+        let copy_path = temp_dir.path().join("copy.snapshot");
+        restored.write_snapshot(root_hash, &copy_path).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), fs::read(&copy_path).unwrap());
+
+        // A snapshot missing tries below its state root is rejected.
+        let partial = InMemoryGlobalState::empty().unwrap();
+        partial.write_snapshot(root_hash, &path).unwrap();
+        assert!(matches!(
+            InMemoryGlobalState::read_snapshot(&path),
+            Err(error::Error::InvalidSnapshot)
+        ));
+
+        // So is a file which isn't a snapshot at all.
+        fs::write(&path, vec![0; 64]).unwrap();
+        assert!(matches!(
+            InMemoryGlobalState::read_snapshot(&path),
+            Err(error::Error::InvalidSnapshot)
+        ));
+    }
+
     #[test]
     fn initial_state_has_the_expected_hash() {
         let correlation_id = CorrelationId::new();
@@ -434,4 +647,49 @@ mod tests {
         let (_, root_hash) = InMemoryGlobalState::from_pairs(correlation_id, &[]).unwrap();
         assert_eq!(expected_bytes, root_hash.into_vec())
     }
+
+    #[test]
+    fn finds_missing_descendants_of_partial_state() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state();
+        assert!(state
+            .missing_descendants(correlation_id, root_hash, usize::MAX)
+            .unwrap()
+            .is_empty());
+
+        // Copy only the root trie into an empty state.
+        let root_trie = state
+            .get_trie_full(correlation_id, &root_hash)
+            .unwrap()
+            .unwrap();
+        let partial_state = InMemoryGlobalState::empty().unwrap();
+        let unknown_root = Digest::hash([1]);
+        assert_eq!(
+            partial_state
+                .missing_descendants(correlation_id, unknown_root, usize::MAX)
+                .unwrap(),
+            vec![unknown_root]
+        );
+        partial_state
+            .put_trie(correlation_id, root_trie.inner())
+            .unwrap();
+
+        let mut missing = partial_state
+            .missing_descendants(correlation_id, root_hash, usize::MAX)
+            .unwrap();
+        let mut expected = partial_state
+            .missing_children(correlation_id, root_trie.inner())
+            .unwrap();
+        missing.sort();
+        expected.sort();
+        assert!(!missing.is_empty());
+        assert_eq!(missing, expected);
+        assert_eq!(
+            partial_state
+                .missing_descendants(correlation_id, root_hash, 1)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
 }
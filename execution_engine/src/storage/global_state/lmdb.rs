@@ -1,30 +1,64 @@
-use std::{collections::HashMap, ops::Deref, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use lmdb::{Cursor, Database, RwTransaction, WriteFlags};
 
 use casper_hashing::Digest;
-use casper_types::{Key, StoredValue};
+use casper_types::{bytesrepr::Bytes, EraId, Key, StoredValue};
 
 use crate::{
     shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
     storage::{
         error,
         global_state::{
-            commit, put_stored_values, scratch::ScratchGlobalState, CommitProvider, StateProvider,
-            StateReader,
+            commit, put_stored_values,
+            scratch::ScratchGlobalState,
+            state_root_anchors::{StateRootAnchor, StateRootAnchors},
+            CommitProvider, StateProvider, StateReader,
         },
         store::Store,
-        transaction_source::{lmdb::LmdbEnvironment, Transaction, TransactionSource},
+        transaction_source::{
+            lmdb::{LmdbEnvironment, LmdbReadTransaction, LmdbReadWriteTransaction},
+            Transaction, TransactionSource,
+        },
         trie::{
-            merkle_proof::TrieMerkleProof, operations::create_hashed_empty_trie, Trie, TrieRaw,
+            merkle_proof::TrieMerkleProof, operations::create_hashed_empty_trie,
+            path_proof::TriePathProof, Trie, TrieRaw,
         },
         trie_store::{
-            lmdb::{LmdbTrieStore, ScratchTrieStore},
+            dedup::{TrieDedupReport, TrieDedupWalk},
+            integrity::TrieIntegrityReport,
+            lmdb::{LmdbTrieStore, ScratchTrieStore, DEFAULT_WRITE_BATCH_SIZE_BYTES},
             operations::{
-                keys_with_prefix, missing_children, put_trie, read, read_with_proof, ReadResult,
+                keys_with_prefix, leaves_with_prefix, missing_children, missing_descendants,
+                put_trie, reachable_tries, read, read_many, read_with_proof, verify_trie_integrity,
+                MissingDescendantsWalk, ReachableTries, ReadResult, TrieIntegrityWalk,
             },
+            scratch_cache::{self, ScratchCache},
+            stats::{TrieStoreStatWalk, TrieStoreStats},
+            TrieStore,
         },
     },
 };
 
+/// The default total size in bytes of tries read from LMDB cached while writing stored values.
+pub const DEFAULT_SCRATCH_CACHE_SIZE: u64 = 536_870_912; // 512 MiB
+
+/// The most tries looked up per read transaction when verifying the integrity of a trie.
+const VERIFY_TRIE_BATCH_LEN: usize = 64 * 1024;
+
+/// The most tries read per read transaction when gathering trie store statistics.
+const TRIE_STORE_STATS_BATCH_LEN: usize = 64 * 1024;
+
+/// The most reachable tries held in memory at once while pruning, around 1 GiB worth of hashes.
+const PRUNE_MAX_REACHABLE_TRIES: usize = 16 * 1024 * 1024;
+
 /// Global state implemented against LMDB as a backing data store.
 pub struct LmdbGlobalState {
     /// Environment for LMDB.
@@ -34,6 +68,46 @@ pub struct LmdbGlobalState {
     // TODO: make this a lazy-static
     /// Empty root hash used for a new trie.
     pub(crate) empty_root_hash: Digest,
+    /// Maximum size in bytes of tries read from LMDB cached while writing stored values.
+    pub(crate) scratch_cache_size: u64,
+    /// Directory under which new tries are spilled while writing stored values, and the size in
+    /// bytes they may take in memory before being spilled.
+    pub(crate) scratch_spill: Option<(PathBuf, u64)>,
+    /// Databases anchoring state roots to the blocks which produced them.
+    pub(crate) state_root_anchors: Option<StateRootAnchors>,
+    /// Whether scratch global states prefetch the values blocks are about to read.
+    pub(crate) trie_prefetch: bool,
+}
+
+/// Time spent in each phase of writing the values cached in a scratch global state to LMDB, along
+/// with the amount of data written.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ScratchWriteTimings {
+    /// Time spent building and validating the new tries in memory.
+    pub scratch_validation: Duration,
+    /// Time spent writing the new tries to LMDB.
+    pub lmdb_write: Duration,
+    /// The number of trie lookups served from the scratch cache.
+    pub cache_hits: u64,
+    /// The number of trie lookups which had to read LMDB.
+    pub cache_misses: u64,
+    /// The number of new tries spilled out of memory before being written to LMDB.
+    pub tries_spilled: u64,
+    /// The number of tries written to LMDB.
+    pub tries_written: usize,
+    /// The total size of the tries written to LMDB, in bytes.
+    pub bytes_written: usize,
+    /// The number of LMDB transactions the tries were written in.
+    pub write_batches: usize,
+}
+
+/// The outcome of pruning unreachable tries from global state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PruneResult {
+    /// The number of tries reachable from the retained state roots.
+    pub reachable_tries: usize,
+    /// The number of unreachable tries deleted.
+    pub deleted_tries: usize,
 }
 
 /// Represents a "view" of global state at a particular root hash.
@@ -74,26 +148,208 @@ impl LmdbGlobalState {
             environment,
             trie_store,
             empty_root_hash,
+            scratch_cache_size: DEFAULT_SCRATCH_CACHE_SIZE,
+            scratch_spill: None,
+            state_root_anchors: None,
+            trie_prefetch: false,
+        }
+    }
+
+    /// Sets the maximum size in bytes of tries read from LMDB which are cached while writing
+    /// stored values.
+    pub fn with_scratch_cache_size(mut self, scratch_cache_size: u64) -> Self {
+        self.scratch_cache_size = scratch_cache_size;
+        self
+    }
+
+    /// Enables spilling new tries to a temporary database under `dir` while writing stored values,
+    /// once those held in memory exceed `max_dirty_size` bytes.
+    pub fn with_scratch_spill(mut self, dir: PathBuf, max_dirty_size: u64) -> Self {
+        self.scratch_spill = Some((dir, max_dirty_size));
+        self
+    }
+
+    /// Removes the temporary databases of new tries spilled under `dir` and left behind by a
+    /// process which didn't shut down cleanly, returning how many were removed.
+    ///
+    /// Must be called before any global state spilling under `dir` is used.
+    pub fn remove_stale_scratch_spills(dir: &Path) -> Result<usize, error::Error> {
+        scratch_cache::remove_stale_spill_dirs(dir)
+    }
+
+    /// Enables prefetching, in the scratch global states created from this one, the values the
+    /// deploys of a block are about to read.
+    pub fn with_trie_prefetch(mut self, enabled: bool) -> Self {
+        self.trie_prefetch = enabled;
+        self
+    }
+
+    /// Enables anchoring state roots to the blocks which produced them, either as they are written
+    /// by `put_stored_values_with` or via `anchor_state_root`.
+    pub fn with_state_root_anchors(mut self) -> Result<Self, error::Error> {
+        self.state_root_anchors = Some(StateRootAnchors::open(&self.environment)?);
+        Ok(self)
+    }
+
+    /// Records that `state_root` was produced by the block at `block_height` in `era_id`.
+    ///
+    /// Does nothing if state root anchors aren't enabled.
+    pub fn anchor_state_root(
+        &self,
+        state_root: Digest,
+        block_height: u64,
+        era_id: EraId,
+    ) -> Result<(), error::Error> {
+        let anchor = StateRootAnchor {
+            block_height,
+            era_id,
+        };
+        self.environment.write_with_map_growth(|| {
+            let mut txn = self.environment.create_read_write_txn()?;
+            self.put_anchor(&mut txn, &state_root, Some(anchor))?;
+            txn.commit()?;
+            Ok(())
+        })
+    }
+
+    /// Anchors the state roots of `blocks`, given in order of descending block height, which
+    /// aren't already anchored to the same or a later block, all within a single transaction.
+    ///
+    /// Returns how many state roots were anchored.
+    pub fn backfill_state_root_anchors(
+        &self,
+        blocks: &[(Digest, StateRootAnchor)],
+    ) -> Result<usize, error::Error> {
+        let anchors = match &self.state_root_anchors {
+            Some(anchors) => anchors,
+            None => return Ok(0),
+        };
+        self.environment.write_with_map_growth(|| {
+            let mut txn = self.environment.create_read_write_txn()?;
+            let mut anchored = 0;
+            for (state_root, anchor) in blocks {
+                // Reads within the transaction see the anchors put earlier in it.
+                match anchors.get(&*txn, state_root)? {
+                    Some(existing) if existing.block_height >= anchor.block_height => continue,
+                    _ => anchors.put(&mut txn, state_root, *anchor)?,
+                }
+                anchored += 1;
+            }
+            txn.commit()?;
+            Ok(anchored)
+        })
+    }
+
+    /// Anchors `state_root` within `txn` if both `maybe_anchor` is given and state root anchors
+    /// are enabled.
+    fn put_anchor(
+        &self,
+        txn: &mut RwTransaction,
+        state_root: &Digest,
+        maybe_anchor: Option<StateRootAnchor>,
+    ) -> Result<(), error::Error> {
+        match (&self.state_root_anchors, maybe_anchor) {
+            (Some(anchors), Some(anchor)) => anchors.put(txn, state_root, anchor),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the block which produced `state_root`, if it was anchored.
+    pub fn state_root_anchor(
+        &self,
+        state_root: &Digest,
+    ) -> Result<Option<StateRootAnchor>, error::Error> {
+        let anchors = match &self.state_root_anchors {
+            Some(anchors) => anchors,
+            None => return Ok(None),
+        };
+        let txn = self.environment.create_read_txn()?;
+        let maybe_anchor = anchors.get(txn.lmdb_txn(), state_root)?;
+        txn.commit()?;
+        Ok(maybe_anchor)
+    }
+
+    /// Returns the state roots produced by blocks of `era_id`, along with the heights of those
+    /// blocks, in order of height.
+    pub fn state_roots_in_era(&self, era_id: EraId) -> Result<Vec<(u64, Digest)>, error::Error> {
+        let anchors = match &self.state_root_anchors {
+            Some(anchors) => anchors,
+            None => return Ok(Vec::new()),
+        };
+        let txn = self.environment.create_read_txn()?;
+        let state_roots = anchors.state_roots_in_era(txn.lmdb_txn(), era_id)?;
+        txn.commit()?;
+        Ok(state_roots)
+    }
+
+    /// Returns every anchored state root still present in the trie store, along with the block
+    /// which produced it, in order of block height.
+    pub fn anchored_state_roots(&self) -> Result<Vec<(Digest, StateRootAnchor)>, error::Error> {
+        let anchors = match &self.state_root_anchors {
+            Some(anchors) => anchors,
+            None => return Ok(Vec::new()),
+        };
+        let txn = self.environment.create_read_txn()?;
+        let mut anchored = Vec::new();
+        for (state_root, anchor) in anchors.all(txn.lmdb_txn())? {
+            // Pruning removes the tries of a state root but leaves its anchor.
+            if Store::<Digest, Trie<Key, StoredValue>>::exists(
+                &*self.trie_store,
+                &txn,
+                &state_root,
+            )? {
+                anchored.push((state_root, anchor));
+            }
         }
+        txn.commit()?;
+        Ok(anchored)
     }
 
     /// Creates an in-memory cache for changes written.
+    ///
+    /// If prefetching is enabled, the scratch state holds the trie store its values are later
+    /// written with, so that the tries read while prefetching are cached for the write.
     pub fn create_scratch(&self) -> ScratchGlobalState {
-        ScratchGlobalState::new(
+        let scratch = ScratchGlobalState::new(
             Arc::clone(&self.environment),
             Arc::clone(&self.trie_store),
             self.empty_root_hash,
         )
+        .with_prefetch(self.trie_prefetch);
+        if self.trie_prefetch {
+            scratch.with_scratch_trie_store(self.get_scratch_store())
+        } else {
+            scratch
+        }
     }
 
     /// Write stored values to LMDB.
+    ///
+    /// Returns the new state root hash along with the time spent in each phase of the write.
     pub fn put_stored_values(
         &self,
         correlation_id: CorrelationId,
         prestate_hash: Digest,
         stored_values: HashMap<Key, StoredValue>,
-    ) -> Result<Digest, error::Error> {
-        let scratch_trie = self.get_scratch_store();
+    ) -> Result<(Digest, ScratchWriteTimings), error::Error> {
+        self.put_stored_values_with(None, correlation_id, prestate_hash, stored_values, None)
+    }
+
+    /// Writes stored values to LMDB through `scratch_trie` if given, which may already cache some
+    /// of the tries read.
+    ///
+    /// If `maybe_anchor` is given, the new state root is anchored to that block within the
+    /// transaction committing it.
+    pub(crate) fn put_stored_values_with(
+        &self,
+        scratch_trie: Option<ScratchTrieStore>,
+        correlation_id: CorrelationId,
+        prestate_hash: Digest,
+        stored_values: HashMap<Key, StoredValue>,
+        maybe_anchor: Option<StateRootAnchor>,
+    ) -> Result<(Digest, ScratchWriteTimings), error::Error> {
+        let scratch_trie = scratch_trie.unwrap_or_else(|| self.get_scratch_store());
+        let start = Instant::now();
         let new_state_root = put_stored_values::<_, _, error::Error>(
             &scratch_trie,
             &scratch_trie,
@@ -101,13 +357,40 @@ impl LmdbGlobalState {
             prestate_hash,
             stored_values,
         )?;
-        scratch_trie.write_root_to_db(new_state_root)?;
-        Ok(new_state_root)
+        let scratch_validation = start.elapsed();
+        let start = Instant::now();
+        let stats = self.environment.write_with_map_growth(|| {
+            scratch_trie.clone().write_root_to_db(
+                new_state_root,
+                DEFAULT_WRITE_BATCH_SIZE_BYTES,
+                |txn| self.put_anchor(txn, &new_state_root, maybe_anchor),
+            )
+        })?;
+        let cache_stats = scratch_trie.cache_stats()?;
+        let timings = ScratchWriteTimings {
+            scratch_validation,
+            lmdb_write: start.elapsed(),
+            cache_hits: cache_stats.hits,
+            cache_misses: cache_stats.misses,
+            tries_spilled: cache_stats.spilled,
+            tries_written: stats.tries_written,
+            bytes_written: stats.bytes_written,
+            write_batches: stats.batches,
+        };
+        Ok((new_state_root, timings))
     }
 
     /// Gets a scratch trie store.
     fn get_scratch_store(&self) -> ScratchTrieStore {
-        ScratchTrieStore::new(Arc::clone(&self.trie_store), Arc::clone(&self.environment))
+        let mut cache = ScratchCache::new(self.scratch_cache_size);
+        if let Some((dir, max_dirty_size)) = &self.scratch_spill {
+            cache = cache.with_spill(dir.clone(), *max_dirty_size);
+        }
+        ScratchTrieStore::new(
+            Arc::clone(&self.trie_store),
+            Arc::clone(&self.environment),
+            cache,
+        )
     }
 
     /// Get a reference to the lmdb global state's environment.
@@ -126,6 +409,278 @@ impl LmdbGlobalState {
     pub fn empty_state_root_hash(&self) -> Digest {
         self.empty_root_hash
     }
+
+    /// Deletes all tries which are not reachable from any of `retained_roots` or the empty root.
+    ///
+    /// Fails with `RootNotFound` if any of the retained roots is missing from the trie store.
+    ///
+    /// The hash space is split into ranges of leading trie hash bytes, small enough for the tries
+    /// reachable within each range to number at most `PRUNE_MAX_REACHABLE_TRIES`, bounding the
+    /// memory used.  For each range, the reachable tries are collected in a single read
+    /// transaction, then the range of the trie store is scanned, deleting at most `batch_size`
+    /// unreachable tries per read-write transaction so that commits are never blocked for long.
+    ///
+    /// Pruning must not run concurrently with commits on top of state roots which are not
+    /// retained, as such a commit can write a trie which pruning has found unreachable and is about
+    /// to delete.
+    pub fn prune(
+        &self,
+        correlation_id: CorrelationId,
+        retained_roots: &[Digest],
+        batch_size: usize,
+    ) -> Result<PruneResult, error::Error> {
+        self.prune_with_max_reachable_tries(
+            correlation_id,
+            retained_roots,
+            batch_size,
+            PRUNE_MAX_REACHABLE_TRIES,
+        )
+    }
+
+    fn prune_with_max_reachable_tries(
+        &self,
+        correlation_id: CorrelationId,
+        retained_roots: &[Digest],
+        batch_size: usize,
+        max_reachable_tries: usize,
+    ) -> Result<PruneResult, error::Error> {
+        let batch_size = batch_size.max(1);
+        let mut result = PruneResult::default();
+        // The number of ranges the hash space is split into, doubled whenever a range holds too
+        // many reachable tries.  Ranges only ever get narrower, so `range_start` stays aligned.
+        let mut range_count: usize = 1;
+        let mut range_start: usize = 0;
+        while range_start <= usize::from(u8::MAX) {
+            let range_end = range_start + 256 / range_count - 1;
+            let hash_range = range_start as u8..=range_end as u8;
+            // A range can't be split any further; its reachable tries are collected regardless.
+            let max_tries = if range_count == 256 {
+                usize::MAX
+            } else {
+                max_reachable_tries
+            };
+            let txn = self.environment.create_read_txn()?;
+            let reachable_tries = reachable_tries::<
+                Key,
+                StoredValue,
+                LmdbReadTransaction,
+                LmdbTrieStore,
+                error::Error,
+            >(
+                correlation_id,
+                &txn,
+                self.trie_store.deref(),
+                retained_roots,
+                hash_range.clone(),
+                max_tries,
+            )?;
+            txn.commit()?;
+            let reachable = match reachable_tries {
+                ReachableTries::Found(reachable) => reachable,
+                ReachableTries::RootNotFound(state_root) => {
+                    return Err(error::Error::RootNotFound(state_root))
+                }
+                ReachableTries::TooMany => {
+                    range_count *= 2;
+                    continue;
+                }
+            };
+
+            for db in self.trie_store.databases() {
+                let mut resume_from = vec![*hash_range.start()];
+                loop {
+                    let (deleted, next_key) = self.environment.write_with_map_growth(|| {
+                        self.delete_unreachable_tries(
+                            db,
+                            &reachable,
+                            &resume_from,
+                            *hash_range.end(),
+                            batch_size,
+                        )
+                    })?;
+                    result.deleted_tries += deleted;
+                    match next_key {
+                        Some(next_key) => resume_from = next_key,
+                        None => break,
+                    }
+                }
+            }
+            result.reachable_tries += reachable.len();
+            range_start = range_end + 1;
+        }
+        Ok(result)
+    }
+
+    /// Walks the trie below `state_root`, reporting the first `limit` tries which are missing or
+    /// corrupted along with their paths.
+    ///
+    /// This reads every trie below the state root, so it is slow on a large global state.  The walk
+    /// is split over short read transactions, so that it doesn't keep pages freed by commits in the
+    /// meantime from being reused.  Tries pruned during the walk are reported missing.
+    pub fn verify_trie_integrity(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        limit: usize,
+    ) -> Result<TrieIntegrityReport, error::Error> {
+        self.verify_trie_integrity_in_batches(
+            correlation_id,
+            state_root,
+            limit,
+            VERIFY_TRIE_BATCH_LEN,
+        )
+    }
+
+    fn verify_trie_integrity_in_batches(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        limit: usize,
+        batch_len: usize,
+    ) -> Result<TrieIntegrityReport, error::Error> {
+        let mut walk = TrieIntegrityWalk::new(state_root, limit);
+        while !walk.is_finished() {
+            let txn = self.environment.create_read_txn()?;
+            verify_trie_integrity::<
+                Key,
+                StoredValue,
+                LmdbReadTransaction,
+                LmdbTrieStore,
+                error::Error,
+            >(
+                correlation_id,
+                &txn,
+                self.trie_store.deref(),
+                &mut walk,
+                batch_len,
+            )?;
+            txn.commit()?;
+        }
+        Ok(walk.into_report())
+    }
+
+    /// Returns the digests of the tries quarantined because their stored bytes failed to
+    /// deserialize, persisting any newly quarantined ones so that they survive a restart.
+    ///
+    /// Quarantined tries are reported missing while syncing global state, so that syncing any state
+    /// root below which they are fetches them from peers again.
+    pub fn quarantined_tries(&self) -> Result<Vec<Digest>, error::Error> {
+        self.trie_store.quarantined_tries(&self.environment)
+    }
+
+    /// Finds the first `limit` tries below `state_root` which are missing from the trie store,
+    /// looking up at most `batch_len` tries per read transaction.
+    ///
+    /// Unlike [`StateProvider::missing_descendants`], which searches within a single read
+    /// transaction, this doesn't keep the pages of old state roots pinned during a long search.
+    pub fn missing_descendants_in_batches(
+        &self,
+        state_root: Digest,
+        limit: usize,
+        batch_len: usize,
+    ) -> Result<Vec<Digest>, error::Error> {
+        let batch_len = batch_len.max(1);
+        let mut walk = MissingDescendantsWalk::new(state_root, limit);
+        while !walk.is_finished() {
+            let txn = self.environment.create_read_txn()?;
+            walk.step::<Key, StoredValue, LmdbReadTransaction, LmdbTrieStore, error::Error>(
+                &txn,
+                self.trie_store.deref(),
+                batch_len,
+            )?;
+            txn.commit()?;
+        }
+        Ok(walk.into_missing())
+    }
+
+    /// Gathers statistics about every trie in the trie store, walking the trie below `state_root`
+    /// too if given.
+    ///
+    /// At most `TRIE_STORE_STATS_BATCH_LEN` tries are read per read transaction, so that the walk
+    /// doesn't keep the pages of old state roots pinned.
+    pub fn trie_store_stats(
+        &self,
+        state_root: Option<Digest>,
+    ) -> Result<TrieStoreStats, error::Error> {
+        let mut walk = TrieStoreStatWalk::new(state_root);
+        while !walk.is_finished() {
+            let txn = self.environment.create_read_txn()?;
+            self.trie_store
+                .stat(txn.lmdb_txn(), &mut walk, TRIE_STORE_STATS_BATCH_LEN)?;
+            txn.commit()?;
+        }
+        Ok(walk.into_stats())
+    }
+
+    /// Computes how the tries below `state_roots` are shared between them, e.g. how much storage
+    /// pruning any one of them would free.
+    ///
+    /// Only the frontier of the walk is held in memory, and at most `batch_size` tries are read per
+    /// read transaction, so that a long walk doesn't keep the pages of old state roots pinned.
+    pub fn trie_dedup_report(
+        &self,
+        state_roots: &[Digest],
+        batch_size: usize,
+    ) -> Result<TrieDedupReport, error::Error> {
+        let batch_size = batch_size.max(1);
+        let mut walk = TrieDedupWalk::new(state_roots);
+        while !walk.is_done() {
+            let txn = self.environment.create_read_txn()?;
+            walk.step::<Key, StoredValue, LmdbReadTransaction, LmdbTrieStore, error::Error>(
+                &txn,
+                self.trie_store.deref(),
+                batch_size,
+            )?;
+            txn.commit()?;
+        }
+        Ok(walk.into_report())
+    }
+
+    /// Deletes at most `batch_size` tries not in `reachable` in a single transaction, scanning the
+    /// trie store database `db` from `resume_from` up to the tries whose hashes start with
+    /// `last_leading_byte`.
+    ///
+    /// The empty root is never deleted.  Returns the number of deleted tries and the key to resume
+    /// the scan from, if any.
+    fn delete_unreachable_tries(
+        &self,
+        db: Database,
+        reachable: &HashSet<Digest>,
+        resume_from: &[u8],
+        last_leading_byte: u8,
+        batch_size: usize,
+    ) -> Result<(usize, Option<Vec<u8>>), error::Error> {
+        let mut txn = self.environment.create_read_write_txn()?;
+        let mut deleted = 0;
+        let mut next_key = None;
+        {
+            let mut cursor = txn.open_rw_cursor(db)?;
+            for row in cursor.iter_from(resume_from) {
+                let (raw_key, _) = row?;
+                if raw_key
+                    .first()
+                    .map_or(false, |byte| *byte > last_leading_byte)
+                {
+                    break;
+                }
+                if deleted == batch_size {
+                    next_key = Some(raw_key.to_vec());
+                    break;
+                }
+                let is_reachable = Digest::try_from(raw_key)
+                    .map(|trie_key| {
+                        trie_key == self.empty_root_hash || reachable.contains(&trie_key)
+                    })
+                    .unwrap_or(true);
+                if !is_reachable {
+                    cursor.del(WriteFlags::empty())?;
+                    deleted += 1;
+                }
+            }
+        }
+        txn.commit()?;
+        Ok((deleted, next_key))
+    }
 }
 
 impl StateReader<Key, StoredValue> for LmdbGlobalStateView {
@@ -137,7 +692,7 @@ impl StateReader<Key, StoredValue> for LmdbGlobalStateView {
         key: &Key,
     ) -> Result<Option<StoredValue>, Self::Error> {
         let txn = self.environment.create_read_txn()?;
-        let ret = match read::<Key, StoredValue, lmdb::RoTransaction, LmdbTrieStore, Self::Error>(
+        let ret = match read::<Key, StoredValue, LmdbReadTransaction, LmdbTrieStore, Self::Error>(
             correlation_id,
             &txn,
             self.store.deref(),
@@ -161,7 +716,7 @@ impl StateReader<Key, StoredValue> for LmdbGlobalStateView {
         let ret = match read_with_proof::<
             Key,
             StoredValue,
-            lmdb::RoTransaction,
+            LmdbReadTransaction,
             LmdbTrieStore,
             Self::Error,
         >(
@@ -179,6 +734,25 @@ impl StateReader<Key, StoredValue> for LmdbGlobalStateView {
         Ok(ret)
     }
 
+    fn read_with_path_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TriePathProof<Key, StoredValue>>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let ret =
+            match self
+                .store
+                .read_with_path_proof(correlation_id, &txn, &self.root_hash, key)?
+            {
+                ReadResult::Found(proof) => Some(proof),
+                ReadResult::NotFound => None,
+                ReadResult::RootNotFound => panic!("LmdbGlobalState has invalid root"),
+            };
+        txn.commit()?;
+        Ok(ret)
+    }
+
     fn keys_with_prefix(
         &self,
         correlation_id: CorrelationId,
@@ -202,6 +776,30 @@ impl StateReader<Key, StoredValue> for LmdbGlobalStateView {
         txn.commit()?;
         Ok(ret)
     }
+
+    fn visit_leaves_with_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        prefix: &[u8],
+        visit: &mut dyn FnMut(Key, StoredValue) -> bool,
+    ) -> Result<(), Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let leaves_iter = leaves_with_prefix::<Key, StoredValue, _, _>(
+            correlation_id,
+            &txn,
+            self.store.deref(),
+            &self.root_hash,
+            prefix,
+        );
+        for result in leaves_iter {
+            let (key, value) = result?;
+            if !visit(key, value) {
+                break;
+            }
+        }
+        txn.commit()?;
+        Ok(())
+    }
 }
 
 impl CommitProvider for LmdbGlobalState {
@@ -211,14 +809,15 @@ impl CommitProvider for LmdbGlobalState {
         prestate_hash: Digest,
         effects: AdditiveMap<Key, Transform>,
     ) -> Result<Digest, Self::Error> {
-        commit::<LmdbEnvironment, LmdbTrieStore, _, Self::Error>(
-            &self.environment,
-            &self.trie_store,
-            correlation_id,
-            prestate_hash,
-            effects,
-        )
-        .map_err(Into::into)
+        self.environment.write_with_map_growth(|| {
+            commit::<LmdbEnvironment, LmdbTrieStore, _, Self::Error>(
+                &self.environment,
+                &self.trie_store,
+                correlation_id,
+                prestate_hash,
+                effects.clone(),
+            )
+        })
     }
 }
 
@@ -249,24 +848,77 @@ impl StateProvider for LmdbGlobalState {
         trie_key: &Digest,
     ) -> Result<Option<TrieRaw>, Self::Error> {
         let txn = self.environment.create_read_txn()?;
-        let ret: Option<TrieRaw> =
-            Store::<Digest, Trie<Digest, StoredValue>>::get_raw(&*self.trie_store, &txn, trie_key)?
-                .map(TrieRaw::new);
+        // The trie is streamed straight into the returned buffer, decompressing it on the way if
+        // needed, rather than copied out of an intermediate one.
+        let mut trie_bytes = Vec::new();
+        let ret: Option<TrieRaw> = Store::<Digest, Trie<Digest, StoredValue>>::get_into(
+            &*self.trie_store,
+            &txn,
+            trie_key,
+            &mut trie_bytes,
+        )?
+        .then(|| TrieRaw::new(Bytes::from(trie_bytes)));
         txn.commit()?;
         Ok(ret)
     }
 
     fn put_trie(&self, correlation_id: CorrelationId, trie: &[u8]) -> Result<Digest, Self::Error> {
-        let mut txn = self.environment.create_read_write_txn()?;
-        let trie_hash = put_trie::<
-            Key,
-            StoredValue,
-            lmdb::RwTransaction,
-            LmdbTrieStore,
-            Self::Error,
-        >(correlation_id, &mut txn, &self.trie_store, trie)?;
+        self.environment.write_with_map_growth(|| {
+            let mut txn = self.environment.create_read_write_txn()?;
+            let trie_hash = put_trie::<
+                Key,
+                StoredValue,
+                LmdbReadWriteTransaction,
+                LmdbTrieStore,
+                Self::Error,
+            >(correlation_id, &mut txn, &self.trie_store, trie)?;
+            txn.commit()?;
+            Ok(trie_hash)
+        })
+    }
+
+    fn put_tries(
+        &self,
+        correlation_id: CorrelationId,
+        tries: &[&[u8]],
+    ) -> Result<Vec<Digest>, Self::Error> {
+        self.environment.write_with_map_growth(|| {
+            let mut txn = self.environment.create_read_write_txn()?;
+            let mut trie_hashes = Vec::with_capacity(tries.len());
+            for trie in tries {
+                trie_hashes.push(put_trie::<
+                    Key,
+                    StoredValue,
+                    LmdbReadWriteTransaction,
+                    LmdbTrieStore,
+                    Self::Error,
+                >(
+                    correlation_id, &mut txn, &self.trie_store, trie
+                )?);
+            }
+            txn.commit()?;
+            Ok(trie_hashes)
+        })
+    }
+
+    /// Reads the values under `keys` within a single read transaction, fetching tries shared by
+    /// the paths to several keys only once.
+    fn read_many(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        keys: &[Key],
+    ) -> Result<Option<Vec<Option<StoredValue>>>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let values = read_many::<Key, StoredValue, _, _, Self::Error>(
+            correlation_id,
+            &txn,
+            self.trie_store.deref(),
+            &state_root,
+            keys,
+        )?;
         txn.commit()?;
-        Ok(trie_hash)
+        Ok(values)
     }
 
     /// Finds all of the keys of missing directly descendant `Trie<K,V>` values.
@@ -279,26 +931,61 @@ impl StateProvider for LmdbGlobalState {
         let missing_hashes = missing_children::<
             Key,
             StoredValue,
-            lmdb::RoTransaction,
+            LmdbReadTransaction,
             LmdbTrieStore,
             Self::Error,
         >(correlation_id, &txn, self.trie_store.deref(), trie_raw)?;
         txn.commit()?;
         Ok(missing_hashes)
     }
+
+    /// Finds the first `limit` missing `Trie<K,V>` values below `state_root`.
+    fn missing_descendants(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        limit: usize,
+    ) -> Result<Vec<Digest>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let missing_hashes = missing_descendants::<
+            Key,
+            StoredValue,
+            LmdbReadTransaction,
+            LmdbTrieStore,
+            Self::Error,
+        >(
+            correlation_id,
+            &txn,
+            self.trie_store.deref(),
+            state_root,
+            limit,
+        )?;
+        txn.commit()?;
+        Ok(missing_hashes)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use lmdb::DatabaseFlags;
+    use lmdb::{DatabaseFlags, Transaction as _};
     use tempfile::tempdir;
 
     use casper_hashing::Digest;
-    use casper_types::{account::AccountHash, CLValue};
+    use casper_types::{
+        account::AccountHash,
+        bytesrepr::{self, ToBytes},
+        CLValue, KeyTag,
+    };
 
     use super::*;
     use crate::storage::{
-        trie_store::operations::{write, WriteResult},
+        global_state::CommitError,
+        trie_store::{
+            compression::DEFAULT_COMPRESSION_LEVEL,
+            counters::TrieStoreCounts,
+            integrity::TrieIntegrityProblemKind,
+            operations::{write, WriteResult},
+        },
         DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS,
     };
 
@@ -392,6 +1079,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn visits_leaves_with_prefix_in_key_order() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let checkout = state.checkout(root_hash).unwrap().unwrap();
+
+        let mut visited = Vec::new();
+        checkout
+            .visit_leaves_with_prefix(
+                correlation_id,
+                &[KeyTag::Account as u8],
+                &mut |key, value| {
+                    visited.push(TestPair { key, value });
+                    true
+                },
+            )
+            .unwrap();
+        let expected = create_test_pairs();
+        assert_eq!(visited.len(), expected.len());
+        for (visited, expected) in visited.iter().zip(expected.iter()) {
+            assert_eq!(visited.key, expected.key);
+            assert_eq!(visited.value, expected.value);
+        }
+
+        let mut visited_count = 0;
+        checkout
+            .visit_leaves_with_prefix(correlation_id, &[], &mut |_, _| {
+                visited_count += 1;
+                false
+            })
+            .unwrap();
+        assert_eq!(visited_count, 1);
+
+        checkout
+            .visit_leaves_with_prefix(correlation_id, &[KeyTag::Bid as u8], &mut |key, _| {
+                panic!("unexpected key {}", key)
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn reads_many_keys_in_order() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let missing_key = Key::Account(AccountHash::new([3_u8; 32]));
+        let pairs = create_test_pairs();
+        let keys = [pairs[1].key, missing_key, pairs[0].key, pairs[1].key];
+
+        let values = state
+            .read_many(correlation_id, root_hash, &keys)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                Some(pairs[1].value.clone()),
+                None,
+                Some(pairs[0].value.clone()),
+                Some(pairs[1].value.clone()),
+            ]
+        );
+        assert!(state
+            .read_many(correlation_id, Digest::hash(b"missing"), &keys)
+            .unwrap()
+            .is_none());
+    }
+
     #[test]
     fn checkout_fails_if_unknown_hash_is_given() {
         let (state, _) = create_test_state(create_test_pairs);
@@ -466,4 +1220,632 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn prune_deletes_tries_unreachable_from_retained_roots() {
+        let correlation_id = CorrelationId::new();
+        let test_pairs_updated = create_test_pairs_updated();
+
+        let (state, root_hash) = create_test_state(create_test_pairs);
+
+        let effects: AdditiveMap<Key, Transform> = {
+            let mut tmp = AdditiveMap::new();
+            for TestPair { key, value } in &test_pairs_updated {
+                tmp.insert(*key, Transform::Write(value.to_owned()));
+            }
+            tmp
+        };
+        let updated_hash = state.commit(correlation_id, root_hash, effects).unwrap();
+
+        // Retaining both roots prunes nothing but the tries of the intermediate state created
+        // while writing the original pairs one by one.
+        let result = state
+            .prune(correlation_id, &[root_hash, updated_hash], 1)
+            .unwrap();
+        assert!(result.reachable_tries > 0);
+        let result_again = state
+            .prune(correlation_id, &[root_hash, updated_hash], 1)
+            .unwrap();
+        assert_eq!(result_again.deleted_tries, 0);
+        assert_eq!(result_again.reachable_tries, result.reachable_tries);
+
+        let result = state.prune(correlation_id, &[updated_hash], 1).unwrap();
+        assert!(result.deleted_tries > 0);
+        assert!(state.checkout(root_hash).unwrap().is_none());
+        assert!(state.checkout(state.empty_root_hash).unwrap().is_some());
+
+        let updated_checkout = state.checkout(updated_hash).unwrap().unwrap();
+        for TestPair { key, value } in test_pairs_updated.iter().cloned() {
+            assert_eq!(
+                Some(value),
+                updated_checkout.read(correlation_id, &key).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn prune_in_narrow_hash_ranges_matches_prune_in_one_range() {
+        let correlation_id = CorrelationId::new();
+        let test_pairs_updated = create_test_pairs_updated();
+        let effects: AdditiveMap<Key, Transform> = {
+            let mut tmp = AdditiveMap::new();
+            for TestPair { key, value } in &test_pairs_updated {
+                tmp.insert(*key, Transform::Write(value.to_owned()));
+            }
+            tmp
+        };
+
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let updated_hash = state
+            .commit(correlation_id, root_hash, effects.clone())
+            .unwrap();
+        let result = state.prune(correlation_id, &[updated_hash], 1).unwrap();
+
+        // Allowing a single reachable trie per range splits the hash space as far as it goes.
+        let (narrow_state, narrow_root_hash) = create_test_state(create_test_pairs);
+        let narrow_updated_hash = narrow_state
+            .commit(correlation_id, narrow_root_hash, effects)
+            .unwrap();
+        assert_eq!(narrow_updated_hash, updated_hash);
+        let narrow_result = narrow_state
+            .prune_with_max_reachable_tries(correlation_id, &[updated_hash], 1, 1)
+            .unwrap();
+        assert_eq!(narrow_result, result);
+
+        let updated_checkout = narrow_state.checkout(updated_hash).unwrap().unwrap();
+        for TestPair { key, value } in test_pairs_updated {
+            assert_eq!(
+                Some(value),
+                updated_checkout.read(correlation_id, &key).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn prune_fails_on_missing_retained_root() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let missing_root = Digest::hash(b"missing state root");
+
+        assert_eq!(
+            state.prune(correlation_id, &[root_hash, missing_root], 1),
+            Err(error::Error::RootNotFound(missing_root))
+        );
+        // Nothing was deleted.
+        assert!(state.checkout(root_hash).unwrap().is_some());
+        let result = state.prune(correlation_id, &[root_hash], 1).unwrap();
+        assert!(result.deleted_tries > 0);
+    }
+
+    #[test]
+    fn trie_dedup_report_splits_unique_and_shared_tries() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let new_pair = &create_test_pairs_updated()[2];
+        let mut effects = AdditiveMap::new();
+        effects.insert(new_pair.key, Transform::Write(new_pair.value.clone()));
+        let updated_hash = state.commit(correlation_id, root_hash, effects).unwrap();
+
+        let _ = state.trie_store.take_counts();
+        let report = state
+            .trie_dedup_report(&[root_hash, updated_hash], 1_000)
+            .unwrap();
+        assert_eq!(report.missing_tries, 0);
+        // Every distinct trie is read once, including leaves moved to the path of their full key.
+        assert_eq!(state.trie_store.take_counts().gets, report.total_tries);
+        // The leaves of the original pairs are shared, while each root has a root node of its own.
+        assert!(report.shared_tries >= 2);
+        for footprint in &report.state_roots {
+            assert!(footprint.unique_tries >= 1);
+            assert_eq!(
+                footprint.tries - footprint.unique_tries,
+                report.shared_tries
+            );
+            assert_eq!(
+                footprint.bytes - footprint.unique_bytes,
+                report.shared_bytes
+            );
+        }
+        assert_eq!(
+            report.total_tries,
+            report.shared_tries
+                + report.state_roots[0].unique_tries
+                + report.state_roots[1].unique_tries
+        );
+
+        // Walking a trie at a time gives the same report.
+        assert_eq!(
+            state
+                .trie_dedup_report(&[root_hash, updated_hash], 1)
+                .unwrap(),
+            report
+        );
+    }
+
+    #[test]
+    fn verify_trie_integrity_reports_missing_and_corrupted_tries() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let report = state
+            .verify_trie_integrity(correlation_id, root_hash, 10)
+            .unwrap();
+        assert!(report.is_intact());
+        assert!(report.tries_checked >= 3);
+        // Walking a trie at a time across transactions checks the same tries.
+        assert_eq!(
+            state
+                .verify_trie_integrity_in_batches(correlation_id, root_hash, 10, 1)
+                .unwrap(),
+            report
+        );
+        // A walk stopped before checking anything doesn't find the state root intact.
+        let report = state
+            .verify_trie_integrity(correlation_id, root_hash, 0)
+            .unwrap();
+        assert!(report.truncated);
+        assert!(!report.is_intact());
+
+        // Find the leaves of both pairs, and corrupt one while deleting the other.
+        let leaf_hashes: Vec<Digest> = create_test_pairs()
+            .iter()
+            .map(|TestPair { key, value }| {
+                Trie::<Key, StoredValue>::leaf(*key, value.clone())
+                    .trie_hash()
+                    .unwrap()
+            })
+            .collect();
+        {
+            let mut txn = state.environment.create_read_write_txn().unwrap();
+            state
+                .trie_store
+                .put_raw(&mut txn, &leaf_hashes[0], b"garbage")
+                .unwrap();
+            txn.del(state.trie_store.get_db(), &leaf_hashes[1], None)
+                .unwrap();
+            txn.commit().unwrap();
+        }
+
+        let report = state
+            .verify_trie_integrity(correlation_id, root_hash, 10)
+            .unwrap();
+        assert!(!report.truncated);
+        assert_eq!(report.problems.len(), 2);
+        for (TestPair { key, .. }, leaf_hash) in create_test_pairs().iter().zip(&leaf_hashes) {
+            let problem = report
+                .problems
+                .iter()
+                .find(|problem| problem.trie_key == *leaf_hash)
+                .unwrap();
+            assert!(key.to_bytes().unwrap().starts_with(&problem.path));
+        }
+        assert!(report
+            .problems
+            .iter()
+            .any(|problem| matches!(problem.kind, TrieIntegrityProblemKind::HashMismatch { .. })));
+        assert!(report
+            .problems
+            .iter()
+            .any(|problem| problem.kind == TrieIntegrityProblemKind::Missing));
+
+        let report = state
+            .verify_trie_integrity(correlation_id, root_hash, 1)
+            .unwrap();
+        assert_eq!(report.problems.len(), 1);
+        assert!(report.truncated);
+    }
+
+    #[test]
+    fn finds_missing_descendants_in_batches() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let mut leaf_hashes = Vec::new();
+        {
+            let mut txn = state.environment.create_read_write_txn().unwrap();
+            for TestPair { key, value } in create_test_pairs() {
+                let leaf_hash = Trie::<Key, StoredValue>::leaf(key, value)
+                    .trie_hash()
+                    .unwrap();
+                assert!(Store::<Digest, Trie<Key, StoredValue>>::delete(
+                    &*state.trie_store,
+                    &mut txn,
+                    &leaf_hash
+                )
+                .unwrap());
+                leaf_hashes.push(leaf_hash);
+            }
+            txn.commit().unwrap();
+        }
+
+        for limit in [1, 10] {
+            let expected = state
+                .missing_descendants(correlation_id, root_hash, limit)
+                .unwrap();
+            assert_eq!(expected.len(), limit.min(leaf_hashes.len()));
+            assert!(expected.iter().all(|hash| leaf_hashes.contains(hash)));
+            for batch_len in [0, 1, 1000] {
+                assert_eq!(
+                    state
+                        .missing_descendants_in_batches(root_hash, limit, batch_len)
+                        .unwrap(),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted_tries_are_quarantined_until_written_again() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let TestPair { key, value } = create_test_pairs()[0].clone();
+        let leaf = Trie::<Key, StoredValue>::leaf(key, value.clone());
+        let leaf_hash = leaf.trie_hash().unwrap();
+        {
+            let mut txn = state.environment.create_read_write_txn().unwrap();
+            state
+                .trie_store
+                .put_raw(&mut txn, &leaf_hash, b"garbage")
+                .unwrap();
+            txn.commit().unwrap();
+        }
+        assert!(state.quarantined_tries().unwrap().is_empty());
+
+        let checkout = state.checkout(root_hash).unwrap().unwrap();
+        assert!(matches!(
+            checkout.read(correlation_id, &key),
+            Err(error::Error::CorruptedTrie(digest, _)) if digest == leaf_hash
+        ));
+        assert_eq!(state.quarantined_tries().unwrap(), vec![leaf_hash]);
+
+        // Syncing finds the corrupted leaf missing, and it isn't served to peers.
+        assert_eq!(
+            state
+                .missing_descendants(correlation_id, root_hash, 10)
+                .unwrap(),
+            vec![leaf_hash]
+        );
+        assert!(state
+            .get_trie_full(correlation_id, &leaf_hash)
+            .unwrap()
+            .is_none());
+        assert!(LmdbTrieStore::open(&state.environment, None)
+            .unwrap()
+            .is_quarantined(&leaf_hash));
+
+        // Writing the leaf fetched from a peer releases it.
+        assert_eq!(
+            state
+                .put_trie(correlation_id, &leaf.to_bytes().unwrap())
+                .unwrap(),
+            leaf_hash
+        );
+        assert!(state.quarantined_tries().unwrap().is_empty());
+        assert_eq!(checkout.read(correlation_id, &key).unwrap(), Some(value));
+        assert!(!LmdbTrieStore::open(&state.environment, None)
+            .unwrap()
+            .is_quarantined(&leaf_hash));
+    }
+
+    #[test]
+    fn trie_store_stats_count_tries_by_kind_and_depth() {
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let stats = state.trie_store_stats(Some(root_hash)).unwrap();
+        assert_eq!(
+            stats.entries,
+            stats.leaves + stats.nodes + stats.extensions + stats.unparsable
+        );
+        assert_eq!(stats.unparsable, 0);
+        assert!(stats.leaves >= 2);
+        assert_eq!(stats.branching_factors.values().sum::<u64>(), stats.nodes);
+
+        let depths = stats.depths.unwrap();
+        assert_eq!(depths.state_root, root_hash);
+        assert_eq!(depths.missing_tries, 0);
+        assert_eq!(depths.leaf_depths.values().sum::<u64>(), 2);
+        assert!(!depths.leaf_depths.contains_key(&0));
+
+        // Reading a single trie per transaction gathers the same statistics.
+        let mut walk = TrieStoreStatWalk::new(Some(root_hash));
+        let mut txns = 0;
+        while !walk.is_finished() {
+            let txn = state.environment.create_read_txn().unwrap();
+            state.trie_store.stat(txn.lmdb_txn(), &mut walk, 1).unwrap();
+            txn.commit().unwrap();
+            txns += 1;
+        }
+        assert!(txns as u64 > stats.entries);
+        let batched_stats = walk.into_stats();
+        assert_eq!(batched_stats.entries, stats.entries);
+        assert_eq!(batched_stats.branching_factors, stats.branching_factors);
+        assert_eq!(batched_stats.depths, Some(depths));
+
+        assert!(state.trie_store_stats(None).unwrap().depths.is_none());
+    }
+
+    #[test]
+    fn compressed_tries_are_read_back_after_reopening_without_compression() {
+        let correlation_id = CorrelationId::new();
+        let temp_dir = tempdir().unwrap();
+        let environment = Arc::new(
+            LmdbEnvironment::new(
+                &temp_dir.path(),
+                DEFAULT_TEST_MAX_DB_SIZE,
+                DEFAULT_TEST_MAX_READERS,
+                true,
+            )
+            .unwrap(),
+        );
+        let trie_store = LmdbTrieStore::new(&environment, None, DatabaseFlags::empty())
+            .unwrap()
+            .with_compression(&environment, DEFAULT_COMPRESSION_LEVEL)
+            .unwrap();
+        let state = LmdbGlobalState::empty(Arc::clone(&environment), Arc::new(trie_store)).unwrap();
+
+        let key = Key::Account(AccountHash::new([3u8; 32]));
+        let value = StoredValue::CLValue(CLValue::from_t(vec![3u8; 64 * 1024]).unwrap());
+        let mut effects = AdditiveMap::new();
+        effects.insert(key, Transform::Write(value.clone()));
+        let root_hash = state
+            .commit(correlation_id, state.empty_root_hash, effects)
+            .unwrap();
+
+        let stats = state.trie_store_stats(None).unwrap();
+        assert_eq!(stats.compressed, 1);
+        assert!(stats.total_bytes < 64 * 1024);
+        assert!(state
+            .verify_trie_integrity(correlation_id, root_hash, 10)
+            .unwrap()
+            .problems
+            .is_empty());
+
+        let reopened = LmdbGlobalState::new(
+            Arc::clone(&environment),
+            Arc::new(LmdbTrieStore::open(&environment, None).unwrap()),
+            state.empty_root_hash,
+        );
+        let checkout = reopened.checkout(root_hash).unwrap().unwrap();
+        assert_eq!(checkout.read(correlation_id, &key).unwrap(), Some(value));
+    }
+
+    #[test]
+    fn trie_store_counts_operations() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let _ = state.trie_store().take_counts();
+
+        let key = Key::Hash([7; 32]);
+        let value = StoredValue::CLValue(CLValue::from_t(7_i32).unwrap());
+        let stored_values = vec![(key, value.clone())].into_iter().collect();
+        let (new_root, timings) = state
+            .put_stored_values(correlation_id, root_hash, stored_values)
+            .unwrap();
+        let counts = state.trie_store().take_counts();
+        assert_eq!(counts.puts, timings.tries_written as u64);
+        assert_eq!(counts.bytes_written, timings.bytes_written as u64);
+        assert!(counts.scratch_puts >= counts.puts);
+        assert!(counts.scratch_gets > 0);
+
+        let checkout = state.checkout(new_root).unwrap().unwrap();
+        assert_eq!(checkout.read(correlation_id, &key).unwrap(), Some(value));
+        let counts = state.trie_store().take_counts();
+        assert!(counts.gets > 0);
+        assert!(counts.bytes_read > 0);
+        assert_eq!((counts.puts, counts.scratch_gets), (0, 0));
+        assert_eq!(state.trie_store().take_counts(), TrieStoreCounts::default());
+    }
+
+    #[test]
+    fn should_anchor_state_roots_as_they_are_committed() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_0) = create_test_state(create_test_pairs);
+        let state = state.with_state_root_anchors().unwrap();
+        let anchor = |block_height, era_id| StateRootAnchor {
+            block_height,
+            era_id: EraId::new(era_id),
+        };
+        let mut stored_values = HashMap::new();
+        stored_values.insert(
+            Key::Hash([1; 32]),
+            StoredValue::CLValue(CLValue::from_t(1_i32).unwrap()),
+        );
+
+        let (root_1, _) = state
+            .put_stored_values_with(
+                None,
+                correlation_id,
+                root_0,
+                stored_values,
+                Some(anchor(1, 0)),
+            )
+            .unwrap();
+        assert_eq!(
+            state.state_root_anchor(&root_1).unwrap(),
+            Some(anchor(1, 0))
+        );
+
+        // A block leaving global state unchanged writes no tries, but still moves the anchor.
+        let (root_2, timings) = state
+            .put_stored_values_with(
+                None,
+                correlation_id,
+                root_1,
+                HashMap::new(),
+                Some(anchor(2, 1)),
+            )
+            .unwrap();
+        assert_eq!((root_2, timings.tries_written), (root_1, 0));
+        assert_eq!(
+            state.state_root_anchor(&root_1).unwrap(),
+            Some(anchor(2, 1))
+        );
+
+        // Backfilling doesn't move anchors back to earlier blocks.
+        let backfilled = state
+            .backfill_state_root_anchors(&[(root_1, anchor(1, 0)), (root_0, anchor(0, 0))])
+            .unwrap();
+        assert_eq!(backfilled, 1);
+        assert_eq!(
+            state.state_root_anchor(&root_1).unwrap(),
+            Some(anchor(2, 1))
+        );
+        assert_eq!(
+            state.state_root_anchor(&root_0).unwrap(),
+            Some(anchor(0, 0))
+        );
+        assert_eq!(
+            state.state_roots_in_era(EraId::new(1)).unwrap(),
+            vec![(2, root_1)]
+        );
+    }
+
+    #[test]
+    fn scratch_tries_are_written_in_batches() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let stored_values: HashMap<Key, StoredValue> = (0..100_u8)
+            .map(|i| {
+                let key = Key::Hash([i; 32]);
+                let value = StoredValue::CLValue(CLValue::from_t(i32::from(i)).unwrap());
+                (key, value)
+            })
+            .collect();
+
+        let scratch_trie = state.get_scratch_store();
+        let new_root = put_stored_values::<_, _, error::Error>(
+            &scratch_trie,
+            &scratch_trie,
+            correlation_id,
+            root_hash,
+            stored_values.clone(),
+        )
+        .unwrap();
+        // A batch size of one byte commits every trie in its own transaction.
+        let stats = scratch_trie
+            .write_root_to_db(new_root, 1, |_| Ok(()))
+            .unwrap();
+        assert!(stats.tries_written > stored_values.len());
+        assert_eq!(stats.batches, stats.tries_written);
+
+        let checkout = state.checkout(new_root).unwrap().unwrap();
+        for (key, value) in stored_values {
+            assert_eq!(Some(value), checkout.read(correlation_id, &key).unwrap());
+        }
+        let report = state
+            .verify_trie_integrity(correlation_id, new_root, 10)
+            .unwrap();
+        assert!(report.is_intact());
+    }
+
+    #[test]
+    fn spilled_scratch_tries_are_committed() {
+        let correlation_id = CorrelationId::new();
+        let spill_dir = tempdir().unwrap();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        // Only a few KiB of new tries are kept in memory, the rest are spilled.
+        let state = state.with_scratch_spill(spill_dir.path().to_path_buf(), 4096);
+        let stored_values: HashMap<Key, StoredValue> = (0..100_u8)
+            .map(|i| {
+                let key = Key::Hash([i; 32]);
+                let value = StoredValue::CLValue(CLValue::from_t(i32::from(i)).unwrap());
+                (key, value)
+            })
+            .collect();
+
+        let (new_root, timings) = state
+            .put_stored_values(correlation_id, root_hash, stored_values.clone())
+            .unwrap();
+        assert!(timings.tries_spilled > 0);
+
+        let checkout = state.checkout(new_root).unwrap().unwrap();
+        for (key, value) in stored_values {
+            assert_eq!(Some(value), checkout.read(correlation_id, &key).unwrap());
+        }
+    }
+
+    /// Asserts that every trie in the store has all of its children in the store, returning the
+    /// number of tries.
+    fn assert_only_complete_subtries(state: &LmdbGlobalState) -> usize {
+        let txn = state.environment.env().begin_ro_txn().unwrap();
+        let mut trie_count = 0;
+        for db in state.trie_store.databases() {
+            let mut cursor = txn.open_ro_cursor(db).unwrap();
+            for row in cursor.iter_start() {
+                let (digest, stored_bytes) = row.unwrap();
+                let trie_bytes = state.trie_store.decode(stored_bytes).unwrap();
+                let trie: Trie<Key, StoredValue> =
+                    bytesrepr::deserialize_from_slice(trie_bytes).unwrap();
+                for child in trie.iter_descendants() {
+                    assert!(
+                        state
+                            .trie_store
+                            .get_stored(&txn, child.as_ref())
+                            .unwrap()
+                            .is_some(),
+                        "trie {:?} is missing its child {}",
+                        digest,
+                        child
+                    );
+                }
+                trie_count += 1;
+            }
+        }
+        trie_count
+    }
+
+    #[test]
+    fn interrupted_commit_leaves_only_complete_subtries() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state(create_test_pairs);
+        let tries_before = assert_only_complete_subtries(&state);
+
+        let stored_values: HashMap<Key, StoredValue> = (0..50_u8)
+            .map(|i| {
+                let key = Key::Hash([i; 32]);
+                let value = StoredValue::CLValue(CLValue::from_t(i32::from(i)).unwrap());
+                (key, value)
+            })
+            .collect();
+        let scratch_trie = state.get_scratch_store();
+        let new_root = put_stored_values::<_, _, error::Error>(
+            &scratch_trie,
+            &scratch_trie,
+            correlation_id,
+            root_hash,
+            stored_values.clone(),
+        )
+        .unwrap();
+
+        // Replacing the last new leaf in the cache with a different trie makes the commit fail
+        // there, after the tries before it have been committed one per transaction.
+        let last_key = Key::Hash([49; 32]);
+        let last_leaf_hash = Trie::leaf(last_key, stored_values[&last_key].clone())
+            .trie_hash()
+            .unwrap();
+        assert!(scratch_trie.cache.remove(&last_leaf_hash).unwrap());
+        scratch_trie
+            .cache
+            .insert_dirty(
+                last_leaf_hash,
+                Trie::leaf(last_key, StoredValue::CLValue(CLValue::unit())),
+            )
+            .unwrap();
+        assert_eq!(
+            scratch_trie.write_root_to_db(new_root, 1, |_| Ok(())),
+            Err(CommitError::TrieHashMismatch(last_leaf_hash).into())
+        );
+
+        // Some of the new tries were committed, but none of them without its children, and not
+        // the new state root, so the interrupted commit can simply be run again.
+        assert!(assert_only_complete_subtries(&state) > tries_before);
+        assert!(state.checkout(new_root).unwrap().is_none());
+        let (committed_root, _) = state
+            .put_stored_values(correlation_id, root_hash, stored_values.clone())
+            .unwrap();
+        assert_eq!(committed_root, new_root);
+        assert_only_complete_subtries(&state);
+        let checkout = state.checkout(new_root).unwrap().unwrap();
+        for (key, value) in stored_values {
+            assert_eq!(Some(value), checkout.read(correlation_id, &key).unwrap());
+        }
+    }
 }
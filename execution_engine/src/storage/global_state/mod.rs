@@ -1,14 +1,34 @@
 //! Global state.
 
+/// Hot backups of the LMDB global state as tarballs of checkpoints.
+pub mod backup;
+/// Consistent copies of the LMDB global state taken while it is in use.
+pub mod checkpoint;
+
+/// Offline compaction of the LMDB global state, reclaiming its free pages.
+pub mod compaction;
+
 /// In-memory implementation of global state.
 pub mod in_memory;
 
 /// Lmdb implementation of global state.
 pub mod lmdb;
 
+/// Speculative loading of the values a block is about to read into a scratch global state.
+pub mod prefetch;
+
+/// Read-only access to the LMDB global state, for inspecting the data of a running node.
+pub mod read_only;
+
 /// Lmdb implementation of global state with cache.
 pub mod scratch;
 
+/// The blocks which produced the state roots of the LMDB global state.
+pub mod state_root_anchors;
+
+/// Export and import of tries to and from files.
+pub mod trie_file;
+
 use std::{collections::HashMap, hash::BuildHasher};
 
 use tracing::error;
@@ -24,7 +44,7 @@ use crate::{
     },
     storage::{
         transaction_source::{Transaction, TransactionSource},
-        trie::{merkle_proof::TrieMerkleProof, Trie, TrieRaw},
+        trie::{merkle_proof::TrieMerkleProof, path_proof::TriePathProof, Trie, TrieRaw},
         trie_store::{
             operations::{read, write, ReadResult, WriteResult},
             TrieStore,
@@ -47,12 +67,41 @@ pub trait StateReader<K, V> {
         key: &K,
     ) -> Result<Option<TrieMerkleProof<K, V>>, Self::Error>;
 
+    /// Returns the state value from the corresponding key together with the trie nodes leading to
+    /// it, so that the value can be verified against the state root hash.
+    fn read_with_path_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &K,
+    ) -> Result<Option<TriePathProof<K, V>>, Self::Error>;
+
     /// Returns the keys in the trie matching `prefix`.
     fn keys_with_prefix(
         &self,
         correlation_id: CorrelationId,
         prefix: &[u8],
     ) -> Result<Vec<K>, Self::Error>;
+
+    /// Calls `visit` with each key in the trie matching `prefix` and its value, in key order.
+    ///
+    /// Visiting stops early once `visit` returns `false`.  Implementations backed by a trie store
+    /// read the leaves one at a time instead of collecting them, so this is suitable for scanning
+    /// large parts of the state such as all bids or all accounts.
+    fn visit_leaves_with_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        prefix: &[u8],
+        visit: &mut dyn FnMut(K, V) -> bool,
+    ) -> Result<(), Self::Error> {
+        for key in self.keys_with_prefix(correlation_id, prefix)? {
+            if let Some(value) = self.read(correlation_id, &key)? {
+                if !visit(key, value) {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// An error emitted by the execution engine on commit
@@ -76,6 +125,15 @@ pub enum CommitError {
     /// Trie not found while attempting to validate cache write.
     #[error("Trie not found in cache {0}")]
     TrieNotFoundInCache(Digest),
+    /// A cached trie doesn't hash to the digest it is stored under.
+    #[error("Trie in cache doesn't match its hash {0}")]
+    TrieHashMismatch(Digest),
+    /// The effects of a deploy write to a key reserved for system contracts.
+    #[error("Write to protected system key: {0}")]
+    ProtectedKeyWrite(Key),
+    /// The temporary database for spilling new tries couldn't be created.
+    #[error("Failed to create scratch spill database: {0}")]
+    ScratchSpillUnavailable(String),
 }
 
 /// Provides `commit` method.
@@ -114,12 +172,53 @@ pub trait StateProvider {
     /// Insert a trie node into the trie
     fn put_trie(&self, correlation_id: CorrelationId, trie: &[u8]) -> Result<Digest, Self::Error>;
 
+    /// Insert several trie nodes into the trie, in a single transaction where the store supports
+    /// it.
+    fn put_tries(
+        &self,
+        correlation_id: CorrelationId,
+        tries: &[&[u8]],
+    ) -> Result<Vec<Digest>, Self::Error> {
+        tries
+            .iter()
+            .map(|trie| self.put_trie(correlation_id, trie))
+            .collect()
+    }
+
+    /// Reads the values under `keys` at `state_root`, in a single read transaction where the store
+    /// supports it.
+    ///
+    /// Returns `None` if `state_root` isn't found, otherwise the values in the order of `keys`.
+    fn read_many(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        keys: &[Key],
+    ) -> Result<Option<Vec<Option<StoredValue>>>, Self::Error> {
+        let reader = match self.checkout(state_root)? {
+            Some(reader) => reader,
+            None => return Ok(None),
+        };
+        keys.iter()
+            .map(|key| reader.read(correlation_id, key))
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
     /// Finds all the children of `trie_raw` which aren't present in the state.
     fn missing_children(
         &self,
         correlation_id: CorrelationId,
         trie_raw: &[u8],
     ) -> Result<Vec<Digest>, Self::Error>;
+
+    /// Finds the first `limit` tries below `state_root` which are missing from the state.
+    fn missing_descendants(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        limit: usize,
+    ) -> Result<Vec<Digest>, Self::Error>;
 }
 
 /// Write multiple key/stored value pairs to the store in a single rw transaction.
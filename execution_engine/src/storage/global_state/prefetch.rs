@@ -0,0 +1,240 @@
+//! Speculative loading of the values a block is about to read.
+//!
+//! Before the deploys of a block are executed, a [`TriePrefetch`] reads the values they are likely
+//! to need - the accounts of their senders, along with the purses and contracts those refer to -
+//! on worker threads, and caches them in the scratch global state the block executes against.
+//! Execution then finds them without walking global state.  The tries on their paths are read
+//! through the scratch trie store the block is later committed with, so they are held in its
+//! cache, within its size budget, for the commit of the block.
+//!
+//! Values are read at the pre-state root of the block, and are never cached over a value already
+//! in the cache, so a value written by a deploy of the block is never replaced by a stale one.
+//! Prefetching is best-effort: a target which fails to be read is logged and skipped.
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use rayon::prelude::*;
+use tracing::{debug, warn};
+
+use casper_hashing::Digest;
+use casper_types::{account::AccountHash, Key, StoredValue};
+
+use crate::{
+    shared::newtypes::CorrelationId,
+    storage::{
+        error,
+        global_state::scratch::SharedCache,
+        trie_store::{
+            lmdb::ScratchTrieStore,
+            operations::{read, ReadResult},
+        },
+    },
+};
+
+/// The most values read for a single target, including the values it refers to.
+const MAX_VALUES_PER_TARGET: usize = 8;
+
+/// A value to prefetch.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PrefetchTarget {
+    /// The value under a key.
+    Key(Key),
+    /// The value under a named key of an account.
+    NamedKey {
+        /// The account holding the named key.
+        account_hash: AccountHash,
+        /// The name of the key.
+        name: String,
+    },
+}
+
+/// Counts of the values read by a [`TriePrefetch`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PrefetchStats {
+    /// The number of values read and cached.
+    pub values_cached: u64,
+    /// The number of values skipped as they were already cached.
+    pub already_cached: u64,
+    /// The number of keys with no value in global state.
+    pub not_found: u64,
+}
+
+#[derive(Default)]
+struct PrefetchCounters {
+    values_cached: AtomicU64,
+    already_cached: AtomicU64,
+    not_found: AtomicU64,
+}
+
+/// A prefetch running in the background, cancelled when dropped.
+pub struct TriePrefetch {
+    cancelled: Arc<AtomicBool>,
+    worker: Option<JoinHandle<PrefetchStats>>,
+}
+
+impl TriePrefetch {
+    /// Returns a prefetch which does nothing.
+    pub(super) fn inactive() -> Self {
+        TriePrefetch {
+            cancelled: Arc::new(AtomicBool::new(true)),
+            worker: None,
+        }
+    }
+
+    /// Starts reading `targets` at `state_root` into `cache` on the rayon thread pool, through
+    /// `trie_store` so that the tries read are cached in it.
+    pub(super) fn spawn(
+        cache: SharedCache,
+        trie_store: ScratchTrieStore,
+        state_root: Digest,
+        targets: Vec<PrefetchTarget>,
+    ) -> Self {
+        if targets.is_empty() {
+            return TriePrefetch::inactive();
+        }
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let prefetcher = Prefetcher {
+            cache,
+            trie_store,
+            state_root,
+            cancelled: Arc::clone(&cancelled),
+            counters: PrefetchCounters::default(),
+        };
+        let spawned = thread::Builder::new()
+            .name("trie-prefetch".to_string())
+            .spawn(move || prefetcher.run(targets));
+        match spawned {
+            Ok(worker) => TriePrefetch {
+                cancelled,
+                worker: Some(worker),
+            },
+            Err(error) => {
+                warn!(%error, "failed to start prefetching global state");
+                TriePrefetch::inactive()
+            }
+        }
+    }
+
+    /// Stops prefetching, waiting for the reads in progress to finish, and returns what was read.
+    pub fn finish(mut self) -> PrefetchStats {
+        self.cancelled.store(true, Ordering::Relaxed);
+        match self.worker.take() {
+            Some(worker) => worker.join().unwrap_or_else(|_| {
+                warn!("prefetching global state panicked");
+                PrefetchStats::default()
+            }),
+            None => PrefetchStats::default(),
+        }
+    }
+}
+
+impl Drop for TriePrefetch {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Prefetcher {
+    cache: SharedCache,
+    trie_store: ScratchTrieStore,
+    state_root: Digest,
+    cancelled: Arc<AtomicBool>,
+    counters: PrefetchCounters,
+}
+
+impl Prefetcher {
+    fn run(self, targets: Vec<PrefetchTarget>) -> PrefetchStats {
+        targets.into_par_iter().for_each(|target| {
+            if self.cancelled.load(Ordering::Relaxed) {
+                return;
+            }
+            if let Err(error) = self.prefetch(&target) {
+                debug!(?target, %error, "failed to prefetch global state");
+            }
+        });
+        PrefetchStats {
+            values_cached: self.counters.values_cached.load(Ordering::Relaxed),
+            already_cached: self.counters.already_cached.load(Ordering::Relaxed),
+            not_found: self.counters.not_found.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Reads the value of `target`, then the values it refers to.
+    fn prefetch(&self, target: &PrefetchTarget) -> Result<(), error::Error> {
+        let mut to_read = match target {
+            PrefetchTarget::Key(key) => vec![*key],
+            PrefetchTarget::NamedKey { account_hash, name } => {
+                match self.read(Key::Account(*account_hash))? {
+                    Some(StoredValue::Account(account)) => account
+                        .named_keys()
+                        .get(name)
+                        .copied()
+                        .into_iter()
+                        .collect(),
+                    _ => vec![],
+                }
+            }
+        };
+        let mut values_read = 0;
+        while let Some(key) = to_read.pop() {
+            if values_read == MAX_VALUES_PER_TARGET || self.cancelled.load(Ordering::Relaxed) {
+                break;
+            }
+            values_read += 1;
+            if let Some(value) = self.read(key)? {
+                to_read.extend(referenced_keys(&value));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the value under `key`, reading and caching it unless already cached.
+    fn read(&self, key: Key) -> Result<Option<StoredValue>, error::Error> {
+        if let Some(value) = self.cache.read().unwrap().get(&key) {
+            self.counters.already_cached.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some(value.clone()));
+        }
+        // The scratch trie store is its own transaction, reading each trie it misses from LMDB.
+        match read::<Key, StoredValue, ScratchTrieStore, ScratchTrieStore, error::Error>(
+            CorrelationId::new(),
+            &self.trie_store,
+            &self.trie_store,
+            &self.state_root,
+            &key,
+        )? {
+            ReadResult::Found(value) => {
+                self.cache.write().unwrap().insert_read(key, value.clone());
+                self.counters.values_cached.fetch_add(1, Ordering::Relaxed);
+                Ok(Some(value))
+            }
+            ReadResult::NotFound => {
+                self.counters.not_found.fetch_add(1, Ordering::Relaxed);
+                Ok(None)
+            }
+            ReadResult::RootNotFound => {
+                // Nothing else can be read either.
+                self.cancelled.store(true, Ordering::Relaxed);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Returns the keys of the values executing against `value` is likely to read next.
+fn referenced_keys(value: &StoredValue) -> Vec<Key> {
+    match value {
+        StoredValue::Account(account) => vec![Key::Balance(account.main_purse().addr())],
+        StoredValue::ContractPackage(contract_package) => contract_package
+            .current_contract_hash()
+            .map(|contract_hash| Key::Hash(contract_hash.value()))
+            .into_iter()
+            .collect(),
+        StoredValue::Contract(contract) => vec![Key::Hash(contract.contract_wasm_hash().value())],
+        _ => vec![],
+    }
+}
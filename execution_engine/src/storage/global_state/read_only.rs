@@ -0,0 +1,245 @@
+//! Read-only access to the LMDB global state of another process.
+//!
+//! Tools inspecting the data directory of a running node must neither take the LMDB writer lock,
+//! stalling the node's commits, nor write anything the node doesn't expect.  A
+//! [`ReadOnlyLmdbGlobalState`] opens the environment with `MDB_RDONLY`, so any attempt to write
+//! fails instead.
+//!
+//! Neither may they keep a read transaction open for long, as the node can't reuse the pages freed
+//! since a transaction started until it ends, growing the database meanwhile.  Searches over
+//! many tries are therefore split across short read transactions.  When the node grows its memory
+//! map, the read-only environment adopts the new size as it creates its next transaction.
+use std::{path::Path, sync::Arc};
+
+use casper_hashing::Digest;
+use casper_types::{EraId, Key, StoredValue};
+
+use crate::{
+    shared::newtypes::CorrelationId,
+    storage::{
+        error,
+        global_state::{
+            lmdb::{LmdbGlobalState, LmdbGlobalStateView},
+            state_root_anchors::{StateRootAnchor, StateRootAnchors},
+            StateProvider,
+        },
+        transaction_source::lmdb::LmdbEnvironment,
+        trie::{operations::create_hashed_empty_trie, TrieRaw},
+        trie_store::{
+            dedup::TrieDedupReport, integrity::TrieIntegrityReport, lmdb::LmdbTrieStore,
+            stats::TrieStoreStats,
+        },
+    },
+};
+
+/// The most tries looked up per read transaction when searching for missing tries.
+const MISSING_DESCENDANTS_BATCH_LEN: usize = 64 * 1024;
+
+/// Global state backed by an LMDB database opened read-only.
+pub struct ReadOnlyLmdbGlobalState {
+    inner: LmdbGlobalState,
+}
+
+impl ReadOnlyLmdbGlobalState {
+    /// Opens the global state database in `dir` read-only.
+    ///
+    /// See [`LmdbEnvironment::open_read_only`] for the requirements on `map_size`.
+    pub fn open(dir: &Path, map_size: usize, max_readers: u32) -> Result<Self, error::Error> {
+        let environment = Arc::new(LmdbEnvironment::open_read_only(dir, map_size, max_readers)?);
+        let trie_store = Arc::new(LmdbTrieStore::open(&environment, None)?);
+        let (empty_root_hash, _) = create_hashed_empty_trie::<Key, StoredValue>()?;
+        let state_root_anchors = StateRootAnchors::open_existing(&environment)?;
+        let mut inner = LmdbGlobalState::new(environment, trie_store, empty_root_hash);
+        inner.state_root_anchors = state_root_anchors;
+        Ok(ReadOnlyLmdbGlobalState { inner })
+    }
+
+    /// Checks that every trie below `state_root` is present and hashes to its key.
+    ///
+    /// See [`LmdbGlobalState::verify_trie_integrity`].
+    pub fn verify_trie_integrity(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        limit: usize,
+    ) -> Result<TrieIntegrityReport, error::Error> {
+        self.inner
+            .verify_trie_integrity(correlation_id, state_root, limit)
+    }
+
+    /// Gathers statistics about the tries in the trie store.
+    ///
+    /// See [`LmdbGlobalState::trie_store_stats`].
+    pub fn trie_store_stats(
+        &self,
+        state_root: Option<Digest>,
+    ) -> Result<TrieStoreStats, error::Error> {
+        self.inner.trie_store_stats(state_root)
+    }
+
+    /// Computes how the tries below `state_roots` are shared between them.
+    ///
+    /// See [`LmdbGlobalState::trie_dedup_report`].
+    pub fn trie_dedup_report(
+        &self,
+        state_roots: &[Digest],
+        batch_size: usize,
+    ) -> Result<TrieDedupReport, error::Error> {
+        self.inner.trie_dedup_report(state_roots, batch_size)
+    }
+
+    /// Returns the block which produced `state_root`, if it was anchored.
+    ///
+    /// See [`LmdbGlobalState::state_root_anchor`].
+    pub fn state_root_anchor(
+        &self,
+        state_root: &Digest,
+    ) -> Result<Option<StateRootAnchor>, error::Error> {
+        self.inner.state_root_anchor(state_root)
+    }
+
+    /// Returns the state roots produced by blocks of `era_id`, in order of height.
+    ///
+    /// See [`LmdbGlobalState::state_roots_in_era`].
+    pub fn state_roots_in_era(&self, era_id: EraId) -> Result<Vec<(u64, Digest)>, error::Error> {
+        self.inner.state_roots_in_era(era_id)
+    }
+
+    /// Get a reference to the read-only environment.
+    #[must_use]
+    pub fn environment(&self) -> &LmdbEnvironment {
+        self.inner.environment()
+    }
+}
+
+impl StateProvider for ReadOnlyLmdbGlobalState {
+    type Error = error::Error;
+
+    type Reader = LmdbGlobalStateView;
+
+    fn checkout(&self, state_hash: Digest) -> Result<Option<Self::Reader>, Self::Error> {
+        self.inner.checkout(state_hash)
+    }
+
+    fn empty_root(&self) -> Digest {
+        self.inner.empty_root()
+    }
+
+    fn get_trie_full(
+        &self,
+        correlation_id: CorrelationId,
+        trie_key: &Digest,
+    ) -> Result<Option<TrieRaw>, Self::Error> {
+        self.inner.get_trie_full(correlation_id, trie_key)
+    }
+
+    /// Always fails, as the environment is read-only.
+    fn put_trie(&self, correlation_id: CorrelationId, trie: &[u8]) -> Result<Digest, Self::Error> {
+        self.inner.put_trie(correlation_id, trie)
+    }
+
+    /// Always fails, as the environment is read-only.
+    fn put_tries(
+        &self,
+        correlation_id: CorrelationId,
+        tries: &[&[u8]],
+    ) -> Result<Vec<Digest>, Self::Error> {
+        self.inner.put_tries(correlation_id, tries)
+    }
+
+    fn read_many(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        keys: &[Key],
+    ) -> Result<Option<Vec<Option<StoredValue>>>, Self::Error> {
+        self.inner.read_many(correlation_id, state_root, keys)
+    }
+
+    fn missing_children(
+        &self,
+        correlation_id: CorrelationId,
+        trie_raw: &[u8],
+    ) -> Result<Vec<Digest>, Self::Error> {
+        self.inner.missing_children(correlation_id, trie_raw)
+    }
+
+    /// Searches across several short read transactions, see
+    /// [`LmdbGlobalState::missing_descendants_in_batches`].
+    fn missing_descendants(
+        &self,
+        _correlation_id: CorrelationId,
+        state_root: Digest,
+        limit: usize,
+    ) -> Result<Vec<Digest>, Self::Error> {
+        self.inner
+            .missing_descendants_in_batches(state_root, limit, MISSING_DESCENDANTS_BATCH_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lmdb::DatabaseFlags;
+    use tempfile::tempdir;
+
+    use casper_types::{account::AccountHash, CLValue};
+
+    use super::*;
+    use crate::{
+        shared::{additive_map::AdditiveMap, transform::Transform},
+        storage::{
+            global_state::{CommitProvider, StateReader},
+            transaction_source::TransactionSource,
+            DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS,
+        },
+    };
+
+    #[test]
+    fn should_read_state_committed_by_writer_and_refuse_writes() {
+        let correlation_id = CorrelationId::new();
+        let dir = tempdir().unwrap();
+        let environment = Arc::new(
+            LmdbEnvironment::new(
+                dir.path(),
+                DEFAULT_TEST_MAX_DB_SIZE,
+                DEFAULT_TEST_MAX_READERS,
+                false,
+            )
+            .unwrap(),
+        );
+        let trie_store =
+            Arc::new(LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+        let writer = LmdbGlobalState::empty(environment, trie_store).unwrap();
+
+        let key = Key::Account(AccountHash::new([7; 32]));
+        let value = StoredValue::CLValue(CLValue::from_t(7_u64).unwrap());
+        let mut effects = AdditiveMap::new();
+        effects.insert(key, Transform::Write(value.clone()));
+        let empty_root = writer.empty_state_root_hash();
+        let root = writer.commit(correlation_id, empty_root, effects).unwrap();
+        // LMDB doesn't support opening an environment twice within one process.
+        drop(writer);
+
+        let reader = ReadOnlyLmdbGlobalState::open(
+            dir.path(),
+            DEFAULT_TEST_MAX_DB_SIZE,
+            DEFAULT_TEST_MAX_READERS,
+        )
+        .unwrap();
+        assert!(reader.environment().is_read_only());
+        assert_eq!(reader.empty_root(), empty_root);
+        let view = reader.checkout(root).unwrap().expect("should have root");
+        assert_eq!(view.read(correlation_id, &key).unwrap(), Some(value));
+        assert_eq!(
+            reader.read_many(correlation_id, root, &[key]).unwrap(),
+            Some(vec![view.read(correlation_id, &key).unwrap()])
+        );
+
+        let trie = reader
+            .get_trie_full(correlation_id, &root)
+            .unwrap()
+            .expect("should have root trie");
+        assert!(reader.put_trie(correlation_id, trie.inner()).is_err());
+        assert!(reader.environment().create_read_write_txn().is_err());
+    }
+}
@@ -8,28 +8,39 @@ use std::{
 use tracing::error;
 
 use casper_hashing::Digest;
-use casper_types::{Key, StoredValue};
+use casper_types::{
+    bytesrepr::{Bytes, ToBytes},
+    Key, StoredValue,
+};
 
 use crate::{
     shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
     storage::{
         error,
-        global_state::{CommitError, CommitProvider, StateProvider, StateReader},
+        global_state::{
+            prefetch::{PrefetchTarget, TriePrefetch},
+            CommitError, CommitProvider, StateProvider, StateReader,
+        },
         store::Store,
-        transaction_source::{lmdb::LmdbEnvironment, Transaction, TransactionSource},
-        trie::{merkle_proof::TrieMerkleProof, Trie, TrieRaw},
+        transaction_source::{
+            lmdb::{LmdbEnvironment, LmdbReadTransaction, LmdbReadWriteTransaction},
+            Transaction, TransactionSource,
+        },
+        trie::{merkle_proof::TrieMerkleProof, path_proof::TriePathProof, Trie, TrieRaw},
         trie_store::{
-            lmdb::LmdbTrieStore,
+            lmdb::{LmdbTrieStore, ScratchTrieStore},
             operations::{
-                keys_with_prefix, missing_children, put_trie, read, read_with_proof, ReadResult,
+                keys_with_prefix, missing_children, missing_descendants, put_trie, read,
+                read_with_proof, ReadResult,
             },
+            TrieStore,
         },
     },
 };
 
-type SharedCache = Arc<RwLock<Cache>>;
+pub(super) type SharedCache = Arc<RwLock<Cache>>;
 
-struct Cache {
+pub(super) struct Cache {
     cached_values: HashMap<Key, (bool, StoredValue)>,
 }
 
@@ -44,11 +55,11 @@ impl Cache {
         self.cached_values.insert(key, (true, value));
     }
 
-    fn insert_read(&mut self, key: Key, value: StoredValue) {
+    pub(super) fn insert_read(&mut self, key: Key, value: StoredValue) {
         self.cached_values.entry(key).or_insert((false, value));
     }
 
-    fn get(&self, key: &Key) -> Option<&StoredValue> {
+    pub(super) fn get(&self, key: &Key) -> Option<&StoredValue> {
         self.cached_values.get(key).map(|(_dirty, value)| value)
     }
 
@@ -73,6 +84,10 @@ pub struct ScratchGlobalState {
     // TODO: make this a lazy-static
     /// Empty root hash used for a new trie.
     pub(crate) empty_root_hash: Digest,
+    /// Whether `prefetch` reads anything.
+    prefetch_enabled: bool,
+    /// The trie store the state is to be committed with, which prefetching reads tries into.
+    scratch_trie_store: Option<ScratchTrieStore>,
 }
 
 /// Represents a "view" of global state at a particular root hash.
@@ -99,13 +114,61 @@ impl ScratchGlobalState {
             environment,
             trie_store,
             empty_root_hash,
+            prefetch_enabled: false,
+            scratch_trie_store: None,
+        }
+    }
+
+    /// Enables prefetching values via `prefetch`.
+    pub fn with_prefetch(mut self, enabled: bool) -> Self {
+        self.prefetch_enabled = enabled;
+        self
+    }
+
+    /// Sets the trie store the state is to be committed with, so that the tries read by `prefetch`
+    /// are already cached in it for the commit.
+    pub(crate) fn with_scratch_trie_store(mut self, scratch_trie_store: ScratchTrieStore) -> Self {
+        self.scratch_trie_store = Some(scratch_trie_store);
+        self
+    }
+
+    /// Starts reading the values of `targets` at `state_root` into the cache in the background.
+    ///
+    /// Does nothing if prefetching isn't enabled, or if no scratch trie store is set to read the
+    /// tries through.
+    pub fn prefetch(&self, state_root: Digest, targets: Vec<PrefetchTarget>) -> TriePrefetch {
+        match &self.scratch_trie_store {
+            Some(scratch_trie_store) if self.prefetch_enabled => TriePrefetch::spawn(
+                Arc::clone(&self.cache),
+                scratch_trie_store.clone(),
+                state_root,
+                targets,
+            ),
+            _ => TriePrefetch::inactive(),
         }
     }
 
+    /// Returns every key written so far along with the serialized size of the value written to it.
+    pub fn written_value_sizes(&self) -> Vec<(Key, usize)> {
+        self.cache
+            .read()
+            .unwrap()
+            .cached_values
+            .iter()
+            .filter(|(_key, (dirty, _value))| *dirty)
+            .map(|(key, (_dirty, value))| (*key, value.serialized_length()))
+            .collect()
+    }
+
     /// Consume self and return inner cache.
     pub fn into_inner(self) -> HashMap<Key, StoredValue> {
+        self.into_parts().0
+    }
+
+    /// Consumes self and returns the inner cache along with the scratch trie store, if set.
+    pub(crate) fn into_parts(self) -> (HashMap<Key, StoredValue>, Option<ScratchTrieStore>) {
         let cache = mem::replace(&mut *self.cache.write().unwrap(), Cache::new());
-        cache.into_dirty_writes()
+        (cache.into_dirty_writes(), self.scratch_trie_store)
     }
 }
 
@@ -121,7 +184,7 @@ impl StateReader<Key, StoredValue> for ScratchGlobalStateView {
             return Ok(Some(value.clone()));
         }
         let txn = self.environment.create_read_txn()?;
-        let ret = match read::<Key, StoredValue, lmdb::RoTransaction, LmdbTrieStore, Self::Error>(
+        let ret = match read::<Key, StoredValue, LmdbReadTransaction, LmdbTrieStore, Self::Error>(
             correlation_id,
             &txn,
             self.trie_store.deref(),
@@ -148,7 +211,7 @@ impl StateReader<Key, StoredValue> for ScratchGlobalStateView {
         let ret = match read_with_proof::<
             Key,
             StoredValue,
-            lmdb::RoTransaction,
+            LmdbReadTransaction,
             LmdbTrieStore,
             Self::Error,
         >(
@@ -166,6 +229,26 @@ impl StateReader<Key, StoredValue> for ScratchGlobalStateView {
         Ok(ret)
     }
 
+    fn read_with_path_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TriePathProof<Key, StoredValue>>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let ret = match self.trie_store.read_with_path_proof(
+            correlation_id,
+            &txn,
+            &self.root_hash,
+            key,
+        )? {
+            ReadResult::Found(proof) => Some(proof),
+            ReadResult::NotFound => None,
+            ReadResult::RootNotFound => panic!("LmdbWithCacheGlobalState has invalid root"),
+        };
+        txn.commit()?;
+        Ok(ret)
+    }
+
     fn keys_with_prefix(
         &self,
         correlation_id: CorrelationId,
@@ -211,7 +294,7 @@ impl CommitProvider for ScratchGlobalState {
                     let updated_value = match read::<
                         Key,
                         StoredValue,
-                        lmdb::RoTransaction,
+                        LmdbReadTransaction,
                         LmdbTrieStore,
                         Self::Error,
                     >(
@@ -289,24 +372,32 @@ impl StateProvider for ScratchGlobalState {
         trie_key: &Digest,
     ) -> Result<Option<TrieRaw>, Self::Error> {
         let txn = self.environment.create_read_txn()?;
-        let ret: Option<TrieRaw> =
-            Store::<Digest, Trie<Digest, StoredValue>>::get_raw(&*self.trie_store, &txn, trie_key)?
-                .map(TrieRaw::new);
+        // The trie is written straight into the returned buffer, from the cache if it is there.
+        let mut trie_bytes = Vec::new();
+        let ret: Option<TrieRaw> = Store::<Digest, Trie<Digest, StoredValue>>::get_into(
+            &*self.trie_store,
+            &txn,
+            trie_key,
+            &mut trie_bytes,
+        )?
+        .then(|| TrieRaw::new(Bytes::from(trie_bytes)));
         txn.commit()?;
         Ok(ret)
     }
 
     fn put_trie(&self, correlation_id: CorrelationId, trie: &[u8]) -> Result<Digest, Self::Error> {
-        let mut txn = self.environment.create_read_write_txn()?;
-        let trie_hash = put_trie::<
-            Key,
-            StoredValue,
-            lmdb::RwTransaction,
-            LmdbTrieStore,
-            Self::Error,
-        >(correlation_id, &mut txn, &self.trie_store, trie)?;
-        txn.commit()?;
-        Ok(trie_hash)
+        self.environment.write_with_map_growth(|| {
+            let mut txn = self.environment.create_read_write_txn()?;
+            let trie_hash = put_trie::<
+                Key,
+                StoredValue,
+                LmdbReadWriteTransaction,
+                LmdbTrieStore,
+                Self::Error,
+            >(correlation_id, &mut txn, &self.trie_store, trie)?;
+            txn.commit()?;
+            Ok(trie_hash)
+        })
     }
 
     /// Finds all of the keys of missing directly descendant `Trie<K,V>` values
@@ -317,7 +408,7 @@ impl StateProvider for ScratchGlobalState {
     ) -> Result<Vec<Digest>, Self::Error> {
         let txn = self.environment.create_read_txn()?;
         let missing_descendants =
-            missing_children::<Key, StoredValue, lmdb::RoTransaction, LmdbTrieStore, Self::Error>(
+            missing_children::<Key, StoredValue, LmdbReadTransaction, LmdbTrieStore, Self::Error>(
                 correlation_id,
                 &txn,
                 self.trie_store.deref(),
@@ -326,6 +417,31 @@ impl StateProvider for ScratchGlobalState {
         txn.commit()?;
         Ok(missing_descendants)
     }
+
+    /// Finds the first `limit` missing `Trie<K,V>` values below `state_root`.
+    fn missing_descendants(
+        &self,
+        correlation_id: CorrelationId,
+        state_root: Digest,
+        limit: usize,
+    ) -> Result<Vec<Digest>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let missing_hashes = missing_descendants::<
+            Key,
+            StoredValue,
+            LmdbReadTransaction,
+            LmdbTrieStore,
+            Self::Error,
+        >(
+            correlation_id,
+            &txn,
+            self.trie_store.deref(),
+            state_root,
+            limit,
+        )?;
+        txn.commit()?;
+        Ok(missing_hashes)
+    }
 }
 
 #[cfg(test)]
@@ -338,7 +454,7 @@ mod tests {
 
     use super::*;
     use crate::storage::{
-        global_state::{lmdb::LmdbGlobalState, CommitProvider},
+        global_state::{lmdb::LmdbGlobalState, prefetch::PrefetchStats, CommitProvider},
         trie_store::operations::{write, WriteResult},
         DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS,
     };
@@ -609,4 +725,56 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn prefetch_caches_values_without_replacing_writes() {
+        let correlation_id = CorrelationId::new();
+        let [written, read] = create_test_pairs();
+        let missing_key = Key::Account(AccountHash::new([9u8; 32]));
+        let new_value = StoredValue::CLValue(CLValue::from_t("written").unwrap());
+
+        let TestState { state, root_hash } = create_test_state();
+        let targets = vec![
+            PrefetchTarget::Key(written.key),
+            PrefetchTarget::Key(read.key),
+            PrefetchTarget::Key(missing_key),
+        ];
+
+        // Prefetching does nothing unless enabled.
+        let scratch = state.create_scratch();
+        assert_eq!(
+            scratch.prefetch(root_hash, targets.clone()).finish(),
+            PrefetchStats::default()
+        );
+        assert!(scratch.into_inner().is_empty());
+
+        let state = state.with_trie_prefetch(true);
+        let scratch = state.create_scratch();
+        let mut effects = AdditiveMap::new();
+        effects.insert(written.key, Transform::Write(new_value.clone()));
+        scratch.commit(correlation_id, root_hash, effects).unwrap();
+
+        let stats = scratch.prefetch(root_hash, targets).finish();
+        assert_eq!(
+            stats,
+            PrefetchStats {
+                values_cached: 1,
+                already_cached: 1,
+                not_found: 1,
+            }
+        );
+
+        let checkout = scratch.checkout(root_hash).unwrap().unwrap();
+        assert_eq!(
+            checkout.read(correlation_id, &written.key).unwrap(),
+            Some(new_value)
+        );
+        assert_eq!(
+            scratch.cache.read().unwrap().get(&read.key),
+            Some(&read.value)
+        );
+        // The tries read are cached for the commit.
+        let scratch_trie_store = scratch.scratch_trie_store.as_ref().unwrap();
+        assert!(scratch_trie_store.cache.contains(&root_hash).unwrap());
+    }
 }
@@ -0,0 +1,257 @@
+//! The blocks which produced the state roots of the LMDB global state.
+//!
+//! Each state root committed by a block is anchored to the height and era of that block within the
+//! transaction committing the state root, so that pruning and snapshot tools working on the global
+//! state alone can tell which state roots are recent.  State roots committed by genesis or an
+//! upgrade are anchored to the first block executed on top of them, and those synchronized from
+//! peers to the block being synchronized.  A second database indexes the state roots by era, keyed
+//! by the era ID in big-endian followed by the state root, so that the roots of an era are found
+//! with a single cursor scan.
+//!
+//! A state root produced by several blocks, e.g. by consecutive blocks which left global state
+//! unchanged, is anchored to the latest of them.
+use std::convert::TryFrom;
+
+use lmdb::{Cursor, Database, DatabaseFlags, RwTransaction, Transaction, WriteFlags};
+
+use casper_hashing::Digest;
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    EraId,
+};
+
+use crate::storage::{error, transaction_source::lmdb::LmdbEnvironment};
+
+/// The name of the LMDB database mapping state roots to the blocks which produced them.
+const ANCHORS_DB_NAME: &str = "STATE_ROOT_ANCHORS";
+/// The name of the LMDB database indexing state roots by era.
+const ANCHORS_BY_ERA_DB_NAME: &str = "STATE_ROOT_ANCHORS_BY_ERA";
+
+/// The block which produced a state root.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateRootAnchor {
+    /// The height of the block.
+    pub block_height: u64,
+    /// The era of the block.
+    pub era_id: EraId,
+}
+
+impl ToBytes for StateRootAnchor {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.block_height.to_bytes()?);
+        buffer.extend(self.era_id.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.block_height.serialized_length() + self.era_id.serialized_length()
+    }
+}
+
+impl FromBytes for StateRootAnchor {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (block_height, remainder) = u64::from_bytes(bytes)?;
+        let (era_id, remainder) = EraId::from_bytes(remainder)?;
+        Ok((
+            StateRootAnchor {
+                block_height,
+                era_id,
+            },
+            remainder,
+        ))
+    }
+}
+
+/// The LMDB databases anchoring state roots to the blocks which produced them.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct StateRootAnchors {
+    anchors_db: Database,
+    by_era_db: Database,
+}
+
+impl StateRootAnchors {
+    /// Opens the databases in `environment`, creating them if missing.
+    pub(crate) fn open(environment: &LmdbEnvironment) -> Result<Self, error::Error> {
+        let env = environment.env();
+        Ok(StateRootAnchors {
+            anchors_db: env.create_db(Some(ANCHORS_DB_NAME), DatabaseFlags::empty())?,
+            by_era_db: env.create_db(Some(ANCHORS_BY_ERA_DB_NAME), DatabaseFlags::empty())?,
+        })
+    }
+
+    /// Opens the databases in `environment` without creating them, returning `None` if they are
+    /// missing.
+    pub(crate) fn open_existing(
+        environment: &LmdbEnvironment,
+    ) -> Result<Option<Self>, error::Error> {
+        let env = environment.env();
+        let open = |name| match env.open_db(Some(name)) {
+            Ok(db) => Ok(Some(db)),
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(error) => Err(error),
+        };
+        match (open(ANCHORS_DB_NAME)?, open(ANCHORS_BY_ERA_DB_NAME)?) {
+            (Some(anchors_db), Some(by_era_db)) => Ok(Some(StateRootAnchors {
+                anchors_db,
+                by_era_db,
+            })),
+            _ => Ok(None),
+        }
+    }
+
+    /// Anchors `state_root` to the block which produced it, replacing any previous anchor.
+    pub(crate) fn put(
+        &self,
+        txn: &mut RwTransaction,
+        state_root: &Digest,
+        anchor: StateRootAnchor,
+    ) -> Result<(), error::Error> {
+        if let Some(previous) = self.get(txn, state_root)? {
+            match txn.del(
+                self.by_era_db,
+                &by_era_key(previous.era_id, state_root),
+                None,
+            ) {
+                Ok(()) | Err(lmdb::Error::NotFound) => (),
+                Err(error) => return Err(error.into()),
+            }
+        }
+        txn.put(
+            self.anchors_db,
+            state_root,
+            &anchor.to_bytes()?,
+            WriteFlags::empty(),
+        )?;
+        txn.put(
+            self.by_era_db,
+            &by_era_key(anchor.era_id, state_root),
+            &anchor.block_height.to_bytes()?,
+            WriteFlags::empty(),
+        )?;
+        Ok(())
+    }
+
+    /// Returns the block which produced `state_root`, if it was anchored.
+    pub(crate) fn get<T: Transaction>(
+        &self,
+        txn: &T,
+        state_root: &Digest,
+    ) -> Result<Option<StateRootAnchor>, error::Error> {
+        match txn.get(self.anchors_db, state_root) {
+            Ok(bytes) => Ok(Some(bytesrepr::deserialize_from_slice(bytes)?)),
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Returns every anchored state root along with its anchor, in order of block height.
+    pub(crate) fn all<T: Transaction>(
+        &self,
+        txn: &T,
+    ) -> Result<Vec<(Digest, StateRootAnchor)>, error::Error> {
+        let mut anchored = Vec::new();
+        let mut cursor = txn.open_ro_cursor(self.anchors_db)?;
+        for row in cursor.iter_start() {
+            let (key, value) = row?;
+            let state_root = Digest::try_from(key).map_err(|_| bytesrepr::Error::Formatting)?;
+            let anchor: StateRootAnchor = bytesrepr::deserialize_from_slice(value)?;
+            anchored.push((state_root, anchor));
+        }
+        anchored.sort_unstable_by_key(|(state_root, anchor)| (anchor.block_height, *state_root));
+        Ok(anchored)
+    }
+
+    /// Returns the state roots anchored to blocks of `era_id`, along with the heights of those
+    /// blocks, in order of height.
+    pub(crate) fn state_roots_in_era<T: Transaction>(
+        &self,
+        txn: &T,
+        era_id: EraId,
+    ) -> Result<Vec<(u64, Digest)>, error::Error> {
+        let prefix = era_id.value().to_be_bytes();
+        let mut state_roots = Vec::new();
+        let mut cursor = txn.open_ro_cursor(self.by_era_db)?;
+        for row in cursor.iter_from(prefix) {
+            let (key, value) = row?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let state_root =
+                Digest::try_from(&key[prefix.len()..]).map_err(|_| bytesrepr::Error::Formatting)?;
+            let block_height: u64 = bytesrepr::deserialize_from_slice(value)?;
+            state_roots.push((block_height, state_root));
+        }
+        state_roots.sort_unstable();
+        Ok(state_roots)
+    }
+}
+
+/// Returns the key of `state_root` in the index by era.
+fn by_era_key(era_id: EraId, state_root: &Digest) -> Vec<u8> {
+    let mut key = era_id.value().to_be_bytes().to_vec();
+    key.extend_from_slice(state_root.as_ref());
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::storage::{DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS};
+
+    #[test]
+    fn should_anchor_state_roots_and_list_them_by_era() {
+        let tmp_dir = tempdir().unwrap();
+        let environment = LmdbEnvironment::new(
+            tmp_dir.path(),
+            DEFAULT_TEST_MAX_DB_SIZE,
+            DEFAULT_TEST_MAX_READERS,
+            true,
+        )
+        .unwrap();
+        assert!(StateRootAnchors::open_existing(&environment)
+            .unwrap()
+            .is_none());
+        let anchors = StateRootAnchors::open(&environment).unwrap();
+
+        let roots: Vec<Digest> = (0..4u8).map(|i| Digest::hash([i])).collect();
+        let anchor = |block_height, era_id| StateRootAnchor {
+            block_height,
+            era_id: EraId::new(era_id),
+        };
+        let mut txn = environment.env().begin_rw_txn().unwrap();
+        anchors.put(&mut txn, &roots[2], anchor(2, 1)).unwrap();
+        anchors.put(&mut txn, &roots[0], anchor(0, 0)).unwrap();
+        anchors.put(&mut txn, &roots[1], anchor(1, 1)).unwrap();
+        // A later block leaving the state root unchanged moves its anchor.
+        anchors.put(&mut txn, &roots[1], anchor(3, 2)).unwrap();
+        txn.commit().unwrap();
+
+        let txn = environment.env().begin_ro_txn().unwrap();
+        assert_eq!(anchors.get(&txn, &roots[0]).unwrap(), Some(anchor(0, 0)));
+        assert_eq!(anchors.get(&txn, &roots[1]).unwrap(), Some(anchor(3, 2)));
+        assert_eq!(anchors.get(&txn, &roots[3]).unwrap(), None);
+        assert_eq!(
+            anchors.state_roots_in_era(&txn, EraId::new(1)).unwrap(),
+            vec![(2, roots[2])]
+        );
+        assert_eq!(
+            anchors.state_roots_in_era(&txn, EraId::new(2)).unwrap(),
+            vec![(3, roots[1])]
+        );
+        assert!(anchors
+            .state_roots_in_era(&txn, EraId::new(3))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            anchors.all(&txn).unwrap(),
+            vec![
+                (roots[0], anchor(0, 0)),
+                (roots[2], anchor(2, 1)),
+                (roots[1], anchor(3, 2))
+            ]
+        );
+    }
+}
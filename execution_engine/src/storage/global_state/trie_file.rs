@@ -0,0 +1,415 @@
+//! Export and import of the tries under a state root to and from a file.
+//!
+//! This allows operators to move the global state of a specific state root between machines
+//! without running the network's fast-sync protocol.
+//!
+//! An export file starts with a header holding a magic number, the format version and the state
+//! root hash.  It is followed by a sequence of chunks, each consisting of the length of its payload
+//! as a little-endian `u32`, the payload itself and the digest of the payload.  A payload holds
+//! whole serialized tries, each prefixed with its length as a little-endian `u32`.  The file ends
+//! with a chunk of length zero, which has no payload or digest.
+//!
+//! Tries are written in depth-first order starting at the state root, and both export and import
+//! only hold a single chunk of at most [`MAX_CHUNK_LENGTH`] bytes in memory at a time.  On import
+//! every chunk is checked against its digest, and every trie must hash to the state root or to a
+//! child of a trie read before it, so a file can't smuggle in tries foreign to the state root.  The
+//! tries of a chunk are stored in a single transaction, and the state root itself is only stored
+//! once all tries below it are, so an interrupted import never leaves an incomplete state root
+//! behind.
+use std::{
+    collections::HashSet,
+    convert::TryFrom,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use thiserror::Error;
+
+use casper_hashing::Digest;
+use casper_types::{bytesrepr, Key, StoredValue};
+
+use crate::{
+    shared::newtypes::CorrelationId,
+    storage::{error, global_state::StateProvider, trie::Trie},
+};
+
+const MAGIC: &[u8; 8] = b"CSPRTRIE";
+const VERSION: u8 = 1;
+const U32_LENGTH: usize = 4;
+
+/// The maximum length of a chunk's payload.  Larger chunks are rejected on import without
+/// allocating a buffer for them.
+pub const MAX_CHUNK_LENGTH: usize = 64 * 1024 * 1024;
+
+/// Error returned when exporting or importing tries.
+#[derive(Debug, Error)]
+pub enum TrieFileError {
+    /// Reading or writing the file failed.
+    #[error("i/o error: {0}")]
+    Io(#[from] io::Error),
+    /// Reading or writing global state failed.
+    #[error("global state error: {0}")]
+    GlobalState(#[from] error::Error),
+    /// A trie could not be parsed.
+    #[error("failed to parse trie {trie_hash}: {error}")]
+    InvalidTrie {
+        /// The hash of the trie.
+        trie_hash: Digest,
+        /// The parsing error.
+        error: bytesrepr::Error,
+    },
+    /// The state root to export is not in global state.
+    #[error("state root {0} not found")]
+    RootNotFound(Digest),
+    /// A trie is too large for the chunk format, i.e. larger than [`MAX_CHUNK_LENGTH`].
+    #[error("trie {0} is too large to export")]
+    TrieTooLarge(Digest),
+    /// The file doesn't start with a valid header.
+    #[error("not a trie export file, or unsupported version")]
+    InvalidHeader,
+    /// The file holds the tries of a different state root.
+    #[error("file holds state root {found}, expected {expected}")]
+    StateRootMismatch {
+        /// The expected state root.
+        expected: Digest,
+        /// The state root found in the file header.
+        found: Digest,
+    },
+    /// A chunk doesn't match its digest.
+    #[error("chunk {index} doesn't match its digest")]
+    ChunkDigestMismatch {
+        /// The index of the chunk.
+        index: u64,
+    },
+    /// A chunk is longer than [`MAX_CHUNK_LENGTH`].
+    #[error("chunk {index} has length {length}, exceeding the maximum")]
+    ChunkTooLarge {
+        /// The index of the chunk.
+        index: u64,
+        /// The length of the chunk's payload.
+        length: u32,
+    },
+    /// A trie in the file is neither the state root nor a descendant of a trie read before it.
+    #[error("trie {0} is not part of the state root")]
+    UnexpectedTrie(Digest),
+    /// A chunk's payload is malformed.
+    #[error("chunk {index} is malformed")]
+    MalformedChunk {
+        /// The index of the chunk.
+        index: u64,
+    },
+    /// After importing all chunks, tries below the state root are still missing.
+    #[error("import incomplete: trie {0} below the state root is missing")]
+    MissingTrie(Digest),
+}
+
+/// Statistics of an export or import.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrieFileSummary {
+    /// The number of chunks.
+    pub chunks: u64,
+    /// The number of tries.
+    pub tries: u64,
+    /// The total size of the serialized tries in bytes.
+    pub trie_bytes: u64,
+}
+
+/// Writes all tries under `state_root` to the file at `path`, in chunks of roughly `chunk_size`
+/// bytes, but never more than [`MAX_CHUNK_LENGTH`].
+pub fn export_trie_to_file<S>(
+    state: &S,
+    correlation_id: CorrelationId,
+    state_root: Digest,
+    path: &Path,
+    chunk_size: usize,
+) -> Result<TrieFileSummary, TrieFileError>
+where
+    S: StateProvider<Error = error::Error>,
+{
+    if state.get_trie_full(correlation_id, &state_root)?.is_none() {
+        return Err(TrieFileError::RootNotFound(state_root));
+    }
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION])?;
+    writer.write_all(&state_root.value())?;
+
+    let mut summary = TrieFileSummary::default();
+    let mut payload = Vec::new();
+    let mut to_visit = vec![state_root];
+    while let Some(trie_hash) = to_visit.pop() {
+        let trie_raw = match state.get_trie_full(correlation_id, &trie_hash)? {
+            Some(trie_raw) => trie_raw,
+            None => return Err(TrieFileError::MissingTrie(trie_hash)),
+        };
+        let trie_bytes = trie_raw.inner().as_ref();
+        if trie_bytes.first() != Some(&Trie::<Key, StoredValue>::LEAF_TAG) {
+            let trie: Trie<Key, StoredValue> = bytesrepr::deserialize_from_slice(trie_bytes)
+                .map_err(|error| TrieFileError::InvalidTrie { trie_hash, error })?;
+            to_visit.extend(trie.iter_descendants());
+        }
+        if U32_LENGTH + trie_bytes.len() > MAX_CHUNK_LENGTH {
+            return Err(TrieFileError::TrieTooLarge(trie_hash));
+        }
+        if payload.len() + U32_LENGTH + trie_bytes.len() > MAX_CHUNK_LENGTH {
+            write_chunk(&mut writer, &payload)?;
+            summary.chunks += 1;
+            payload.clear();
+        }
+        payload.extend_from_slice(&(trie_bytes.len() as u32).to_le_bytes());
+        payload.extend_from_slice(trie_bytes);
+        summary.tries += 1;
+        summary.trie_bytes += trie_bytes.len() as u64;
+        if payload.len() >= chunk_size {
+            write_chunk(&mut writer, &payload)?;
+            summary.chunks += 1;
+            payload.clear();
+        }
+    }
+    if !payload.is_empty() {
+        write_chunk(&mut writer, &payload)?;
+        summary.chunks += 1;
+    }
+    writer.write_all(&0_u32.to_le_bytes())?;
+    writer.flush()?;
+    Ok(summary)
+}
+
+/// Writes a chunk whose payload is known to be at most `MAX_CHUNK_LENGTH` bytes.
+fn write_chunk(writer: &mut impl Write, payload: &[u8]) -> Result<(), TrieFileError> {
+    debug_assert!(payload.len() <= MAX_CHUNK_LENGTH);
+    writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+    writer.write_all(payload)?;
+    writer.write_all(&Digest::hash(payload).value())?;
+    Ok(())
+}
+
+/// Imports the tries exported to the file at `path` into `state`, and verifies that they form the
+/// complete trie of `expected_state_root`.
+pub fn import_trie_from_file<S>(
+    state: &S,
+    correlation_id: CorrelationId,
+    expected_state_root: Digest,
+    path: &Path,
+) -> Result<TrieFileSummary, TrieFileError>
+where
+    S: StateProvider<Error = error::Error>,
+{
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut header = [0; MAGIC.len() + 1 + Digest::LENGTH];
+    reader.read_exact(&mut header)?;
+    if &header[..MAGIC.len()] != MAGIC || header[MAGIC.len()] != VERSION {
+        return Err(TrieFileError::InvalidHeader);
+    }
+    let found =
+        Digest::try_from(&header[MAGIC.len() + 1..]).map_err(|_| TrieFileError::InvalidHeader)?;
+    if found != expected_state_root {
+        return Err(TrieFileError::StateRootMismatch {
+            expected: expected_state_root,
+            found,
+        });
+    }
+
+    let mut summary = TrieFileSummary::default();
+    // The hashes of tries referenced by tries read so far, but not read themselves yet.
+    let mut expected = HashSet::new();
+    expected.insert(expected_state_root);
+    // The state root is held back until all tries below it are stored.
+    let mut root_bytes = None;
+    let mut payload = Vec::new();
+    loop {
+        let index = summary.chunks;
+        let length = read_u32(&mut reader)?;
+        if length == 0 {
+            break;
+        }
+        if length as usize > MAX_CHUNK_LENGTH {
+            return Err(TrieFileError::ChunkTooLarge { index, length });
+        }
+        payload.clear();
+        reader
+            .by_ref()
+            .take(u64::from(length))
+            .read_to_end(&mut payload)?;
+        if payload.len() != length as usize {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        let mut digest = [0; Digest::LENGTH];
+        reader.read_exact(&mut digest)?;
+        if Digest::hash(&payload) != Digest::from(digest) {
+            return Err(TrieFileError::ChunkDigestMismatch { index });
+        }
+
+        let mut tries = Vec::new();
+        let mut remainder = payload.as_slice();
+        while !remainder.is_empty() {
+            let trie_length = remainder
+                .get(..U32_LENGTH)
+                .and_then(|bytes| <[u8; U32_LENGTH]>::try_from(bytes).ok())
+                .map(|bytes| u32::from_le_bytes(bytes) as usize)
+                .ok_or(TrieFileError::MalformedChunk { index })?;
+            let trie_bytes = remainder
+                .get(U32_LENGTH..U32_LENGTH + trie_length)
+                .ok_or(TrieFileError::MalformedChunk { index })?;
+            let trie_hash = Digest::hash_into_chunks_if_necessary(trie_bytes);
+            if !expected.remove(&trie_hash) {
+                return Err(TrieFileError::UnexpectedTrie(trie_hash));
+            }
+            if trie_bytes.first() != Some(&Trie::<Key, StoredValue>::LEAF_TAG) {
+                let trie: Trie<Key, StoredValue> = bytesrepr::deserialize_from_slice(trie_bytes)
+                    .map_err(|error| TrieFileError::InvalidTrie { trie_hash, error })?;
+                expected.extend(trie.iter_descendants());
+            }
+            if trie_hash == expected_state_root {
+                root_bytes = Some(trie_bytes.to_vec());
+            } else {
+                tries.push(trie_bytes);
+            }
+            summary.tries += 1;
+            summary.trie_bytes += trie_length as u64;
+            remainder = &remainder[U32_LENGTH + trie_length..];
+        }
+        state.put_tries(correlation_id, &tries)?;
+        summary.chunks += 1;
+    }
+
+    if let Some(missing) = expected.into_iter().next() {
+        return Err(TrieFileError::MissingTrie(missing));
+    }
+    if let Some(root_bytes) = root_bytes {
+        state.put_trie(correlation_id, &root_bytes)?;
+    }
+    if let Some(missing) = state
+        .missing_descendants(correlation_id, expected_state_root, 1)?
+        .into_iter()
+        .next()
+    {
+        return Err(TrieFileError::MissingTrie(missing));
+    }
+    Ok(summary)
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, io::Error> {
+    let mut bytes = [0; U32_LENGTH];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use casper_types::{account::AccountHash, CLValue};
+
+    use super::*;
+    use crate::storage::global_state::{in_memory::InMemoryGlobalState, StateReader};
+
+    fn test_pairs() -> Vec<(Key, StoredValue)> {
+        (0..50_u8)
+            .map(|i| {
+                let key = Key::Account(AccountHash::new([i; 32]));
+                let value = StoredValue::CLValue(CLValue::from_t(i32::from(i)).unwrap());
+                (key, value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_export_and_import_trie() {
+        let correlation_id = CorrelationId::new();
+        let pairs = test_pairs();
+        let (source, state_root) = InMemoryGlobalState::from_pairs(correlation_id, &pairs).unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("state.tries");
+
+        let exported =
+            export_trie_to_file(&source, correlation_id, state_root, &path, 256).unwrap();
+        assert!(exported.chunks > 1);
+
+        let target = InMemoryGlobalState::empty().unwrap();
+        assert!(target.checkout(state_root).unwrap().is_none());
+        let imported = import_trie_from_file(&target, correlation_id, state_root, &path).unwrap();
+        assert_eq!(imported, exported);
+
+        let view = target.checkout(state_root).unwrap().unwrap();
+        for (key, value) in pairs {
+            assert_eq!(view.read(correlation_id, &key).unwrap(), Some(value));
+        }
+    }
+
+    #[test]
+    fn should_reject_corrupt_or_foreign_file() {
+        let correlation_id = CorrelationId::new();
+        let (source, state_root) =
+            InMemoryGlobalState::from_pairs(correlation_id, &test_pairs()).unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("state.tries");
+        export_trie_to_file(&source, correlation_id, state_root, &path, 256).unwrap();
+
+        let target = InMemoryGlobalState::empty().unwrap();
+        let other_root = Digest::hash(b"other");
+        assert!(matches!(
+            import_trie_from_file(&target, correlation_id, other_root, &path),
+            Err(TrieFileError::StateRootMismatch { .. })
+        ));
+
+        // Flip a byte in the first chunk's payload.
+        let mut bytes = fs::read(&path).unwrap();
+        let payload_start = MAGIC.len() + 1 + Digest::LENGTH + U32_LENGTH;
+        bytes[payload_start + 10] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            import_trie_from_file(&target, correlation_id, state_root, &path),
+            Err(TrieFileError::ChunkDigestMismatch { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn should_reject_foreign_tries() {
+        let correlation_id = CorrelationId::new();
+        let (_, state_root) =
+            InMemoryGlobalState::from_pairs(correlation_id, &test_pairs()).unwrap();
+        let (other, other_root) =
+            InMemoryGlobalState::from_pairs(correlation_id, &test_pairs()[..10]).unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("state.tries");
+        export_trie_to_file(&other, correlation_id, other_root, &path, 256).unwrap();
+
+        // Claim the other state root's tries are those of `state_root`.
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[MAGIC.len() + 1..MAGIC.len() + 1 + Digest::LENGTH]
+            .copy_from_slice(&state_root.value());
+        fs::write(&path, &bytes).unwrap();
+
+        let target = InMemoryGlobalState::empty().unwrap();
+        assert!(matches!(
+            import_trie_from_file(&target, correlation_id, state_root, &path),
+            Err(TrieFileError::UnexpectedTrie(trie_hash)) if trie_hash == other_root
+        ));
+        assert!(target.checkout(state_root).unwrap().is_none());
+        assert!(target.checkout(other_root).unwrap().is_none());
+    }
+
+    #[test]
+    fn should_reject_oversized_chunk() {
+        let correlation_id = CorrelationId::new();
+        let state_root = Digest::hash(b"root");
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("state.tries");
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&state_root.value());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+
+        let target = InMemoryGlobalState::empty().unwrap();
+        assert!(matches!(
+            import_trie_from_file(&target, correlation_id, state_root, &path),
+            Err(TrieFileError::ChunkTooLarge {
+                index: 0,
+                length: u32::MAX
+            })
+        ));
+    }
+}
@@ -2,13 +2,15 @@ mod store_ext;
 #[cfg(test)]
 pub(crate) mod tests;
 
+use std::io;
+
 use casper_types::bytesrepr::{self, Bytes, FromBytes, ToBytes};
 
 pub use self::store_ext::StoreExt;
 use crate::storage::transaction_source::{Readable, Writable};
 
-/// Store is responsible for abstracting `get` and `put` operations over the underlying store
-/// specified by its associated `Handle` type.
+/// Store is responsible for abstracting `get`, `put` and `delete` operations over the underlying
+/// store specified by its associated `Handle` type.
 pub trait Store<K, V> {
     /// Errors possible from this store.
     type Error: From<bytesrepr::Error>;
@@ -28,14 +30,9 @@ pub trait Store<K, V> {
         V: FromBytes,
         Self::Error: From<T::Error>,
     {
-        let raw = self.get_raw(txn, key)?;
-        match raw {
-            Some(bytes) => {
-                let value = bytesrepr::deserialize_from_slice(bytes)?;
-                Ok(Some(value))
-            }
-            None => Ok(None),
-        }
+        let maybe_value =
+            self.with_raw(txn, key, |bytes| bytesrepr::deserialize_from_slice(bytes))?;
+        Ok(maybe_value.transpose()?)
     }
 
     /// Returns an optional value (may exist or not) as read through a transaction, or an error
@@ -50,6 +47,50 @@ pub trait Store<K, V> {
         Ok(txn.read(handle, key.as_ref())?)
     }
 
+    /// Calls `f` with the raw value at `key`, if any, as read through a transaction.
+    ///
+    /// Unlike [`Store::get_raw`], the value is passed in place where the transaction allows it,
+    /// so that large values which are only deserialized or copied elsewhere aren't first copied
+    /// into an intermediate buffer.
+    fn with_raw<T, F, R>(&self, txn: &T, key: &K, f: F) -> Result<Option<R>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        K: AsRef<[u8]>,
+        F: FnOnce(&[u8]) -> R,
+        Self::Error: From<T::Error>,
+    {
+        let handle = self.handle();
+        Ok(txn.read_with(handle, key.as_ref(), f)?)
+    }
+
+    /// Writes the raw value at `key` to `writer`, returning whether there was one.
+    fn get_into<T, W>(&self, txn: &T, key: &K, writer: &mut W) -> Result<bool, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        K: AsRef<[u8]>,
+        W: io::Write,
+        Self::Error: From<T::Error> + From<io::Error>,
+    {
+        match self.with_raw(txn, key, |bytes| writer.write_all(bytes))? {
+            Some(result) => {
+                result?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Returns `true` if there is a value at `key`, without reading or deserializing it.
+    fn exists<T>(&self, txn: &T, key: &K) -> Result<bool, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        K: AsRef<[u8]>,
+        Self::Error: From<T::Error>,
+    {
+        let handle = self.handle();
+        Ok(txn.exists(handle, key.as_ref())?)
+    }
+
     /// Puts a `value` into the store at `key` within a transaction, potentially returning an
     /// error of type `Self::Error` if that fails.
     fn put<T>(&self, txn: &mut T, key: &K, value: &V) -> Result<(), Self::Error>
@@ -74,4 +115,34 @@ pub trait Store<K, V> {
         txn.write(handle, key.as_ref(), trie_bytes)
             .map_err(Into::into)
     }
+
+    /// Puts multiple key/value pairs into the store in one transaction, potentially returning an
+    /// error of type `Self::Error` if that fails.
+    fn put_many<'a, T>(
+        &self,
+        txn: &mut T,
+        pairs: impl Iterator<Item = (&'a K, &'a V)>,
+    ) -> Result<(), Self::Error>
+    where
+        T: Writable<Handle = Self::Handle>,
+        K: AsRef<[u8]> + 'a,
+        V: ToBytes + 'a,
+        Self::Error: From<T::Error>,
+    {
+        for (key, value) in pairs {
+            self.put(txn, key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the value at `key` within a transaction, returning whether there was one.
+    fn delete<T>(&self, txn: &mut T, key: &K) -> Result<bool, Self::Error>
+    where
+        T: Writable<Handle = Self::Handle>,
+        K: AsRef<[u8]>,
+        Self::Error: From<T::Error>,
+    {
+        let handle = self.handle();
+        Ok(txn.delete(handle, key.as_ref())?)
+    }
 }
@@ -1,11 +1,8 @@
 //! Extension traits for store.
 
-use casper_types::bytesrepr::{FromBytes, ToBytes};
+use casper_types::bytesrepr::FromBytes;
 
-use crate::storage::{
-    store::Store,
-    transaction_source::{Readable, Writable},
-};
+use crate::storage::{store::Store, transaction_source::Readable};
 
 /// Extension trait for Store.
 pub trait StoreExt<K, V>: Store<K, V> {
@@ -28,25 +25,6 @@ pub trait StoreExt<K, V>: Store<K, V> {
         }
         Ok(ret)
     }
-
-    /// Puts multiple key/value pairs into the store in one transaction, potentially returning an
-    /// error of type `Self::Error` if that fails.
-    fn put_many<'a, T>(
-        &self,
-        txn: &mut T,
-        pairs: impl Iterator<Item = (&'a K, &'a V)>,
-    ) -> Result<(), Self::Error>
-    where
-        T: Writable<Handle = Self::Handle>,
-        K: AsRef<[u8]> + 'a,
-        V: ToBytes + 'a,
-        Self::Error: From<T::Error>,
-    {
-        for (key, value) in pairs {
-            self.put(txn, key, value)?;
-        }
-        Ok(())
-    }
 }
 
 impl<K, V, T: Store<K, V>> StoreExt<K, V> for T {}
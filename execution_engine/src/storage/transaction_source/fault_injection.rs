@@ -0,0 +1,223 @@
+//! Failure and latency injection for the in-memory transaction source.
+//!
+//! Faults are enabled per [`InMemoryEnvironment`](super::in_memory::InMemoryEnvironment), letting
+//! tests exercise the error paths of code reading global state, which are otherwise unreachable
+//! with an in-memory store.  Faults are drawn from an RNG seeded by the configuration, so a failing
+//! test can be reproduced by rerunning it with the same seed.
+use std::{thread, time::Duration};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+
+use casper_types::bytesrepr::{self, Bytes};
+
+use crate::storage::error::in_memory::Error;
+
+/// Describes which faults to inject into reads, and how often.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaultConfig {
+    seed: u64,
+    read_error_rate: f64,
+    corruption_rate: f64,
+    max_read_delay: Duration,
+}
+
+impl FaultConfig {
+    /// Creates a configuration injecting no faults, drawing them from an RNG seeded with `seed`
+    /// once enabled.
+    pub fn new(seed: u64) -> Self {
+        FaultConfig {
+            seed,
+            read_error_rate: 0.0,
+            corruption_rate: 0.0,
+            max_read_delay: Duration::ZERO,
+        }
+    }
+
+    /// Sets the probability with which a read fails with a serialization error.
+    ///
+    /// `rate` must be between `0.0` and `1.0`.
+    pub fn with_read_error_rate(mut self, rate: f64) -> Self {
+        self.read_error_rate = rate;
+        self
+    }
+
+    /// Sets the probability with which a single byte of a value read is flipped.
+    ///
+    /// `rate` must be between `0.0` and `1.0`.
+    pub fn with_corruption_rate(mut self, rate: f64) -> Self {
+        self.corruption_rate = rate;
+        self
+    }
+
+    /// Delays each read by a random duration of up to `max_read_delay`.
+    pub fn with_max_read_delay(mut self, max_read_delay: Duration) -> Self {
+        self.max_read_delay = max_read_delay;
+        self
+    }
+}
+
+/// Counts of the reads performed and the faults injected into them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FaultStats {
+    /// The number of reads performed while faults were enabled.
+    pub reads: u64,
+    /// The number of reads which failed with an injected error.
+    pub injected_errors: u64,
+    /// The number of reads which returned corrupted bytes.
+    pub corrupted_reads: u64,
+    /// The number of reads which were delayed.
+    pub delayed_reads: u64,
+}
+
+/// Injects faults into reads according to a [`FaultConfig`].
+#[derive(Debug)]
+pub(crate) struct FaultInjector {
+    config: FaultConfig,
+    rng: ChaChaRng,
+    stats: FaultStats,
+}
+
+impl FaultInjector {
+    pub(crate) fn new(config: FaultConfig) -> Self {
+        let rng = ChaChaRng::seed_from_u64(config.seed);
+        FaultInjector {
+            config,
+            rng,
+            stats: FaultStats::default(),
+        }
+    }
+
+    pub(crate) fn stats(&self) -> FaultStats {
+        self.stats
+    }
+
+    /// Applies the configured faults to the outcome of reading a value.
+    pub(crate) fn on_read(&mut self, value: Option<Bytes>) -> Result<Option<Bytes>, Error> {
+        self.stats.reads += 1;
+
+        if self.config.max_read_delay > Duration::ZERO {
+            let delay = self
+                .rng
+                .gen_range(Duration::ZERO..=self.config.max_read_delay);
+            self.stats.delayed_reads += 1;
+            thread::sleep(delay);
+        }
+
+        if self.rng.gen_bool(self.config.read_error_rate) {
+            self.stats.injected_errors += 1;
+            return Err(Error::BytesRepr(bytesrepr::Error::Formatting));
+        }
+
+        match value {
+            Some(bytes) if !bytes.is_empty() && self.rng.gen_bool(self.config.corruption_rate) => {
+                let mut corrupted = bytes.to_vec();
+                let index = self.rng.gen_range(0..corrupted.len());
+                corrupted[index] ^= self.rng.gen_range(1..=u8::MAX);
+                self.stats.corrupted_reads += 1;
+                Ok(Some(Bytes::from(corrupted)))
+            }
+            value => Ok(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::transaction_source::{
+        in_memory::InMemoryEnvironment, Readable, Transaction, TransactionSource, Writable,
+    };
+
+    const KEY: &[u8] = b"key";
+    const VALUE: &[u8] = &[1, 2, 3];
+
+    fn environment_with_value() -> InMemoryEnvironment {
+        let env = InMemoryEnvironment::default();
+        let mut txn = env.create_read_write_txn().unwrap();
+        txn.write(None, KEY, VALUE).unwrap();
+        txn.commit().unwrap();
+        env
+    }
+
+    #[test]
+    fn should_inject_configured_faults_deterministically() {
+        let value = Some(Bytes::from(vec![1, 2, 3]));
+
+        let mut injector = FaultInjector::new(FaultConfig::new(7));
+        assert_eq!(injector.on_read(value.clone()), Ok(value.clone()));
+
+        let mut injector = FaultInjector::new(FaultConfig::new(7).with_read_error_rate(1.0));
+        assert_eq!(
+            injector.on_read(value.clone()),
+            Err(Error::BytesRepr(bytesrepr::Error::Formatting))
+        );
+
+        let config = FaultConfig::new(7).with_corruption_rate(1.0);
+        let corrupted = FaultInjector::new(config.clone())
+            .on_read(value.clone())
+            .unwrap();
+        assert_ne!(corrupted, value);
+        assert_eq!(
+            FaultInjector::new(config).on_read(value.clone()).unwrap(),
+            corrupted
+        );
+
+        let mut injector = FaultInjector::new(FaultConfig::new(7).with_corruption_rate(1.0));
+        assert_eq!(injector.on_read(None), Ok(None));
+        assert_eq!(
+            injector.stats(),
+            FaultStats {
+                reads: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn should_fail_reads_of_all_transactions_once_enabled() {
+        let env = environment_with_value();
+        let read_txn = env.create_read_txn().unwrap();
+        let expected_error = Err(Error::BytesRepr(bytesrepr::Error::Formatting));
+
+        env.set_fault_config(Some(FaultConfig::new(7).with_read_error_rate(1.0)))
+            .unwrap();
+        // Transactions created before faults were enabled fail too.
+        assert_eq!(read_txn.read(None, KEY), expected_error);
+        assert_eq!(
+            env.create_read_txn().unwrap().read(None, KEY),
+            expected_error
+        );
+
+        // Writes are unaffected, but reading back within the transaction fails.
+        let mut read_write_txn = env.create_read_write_txn().unwrap();
+        read_write_txn.write(None, KEY, &[4, 5]).unwrap();
+        assert_eq!(read_write_txn.read(None, KEY), expected_error);
+        read_write_txn.commit().unwrap();
+
+        assert_eq!(
+            env.fault_stats().unwrap(),
+            FaultStats {
+                reads: 3,
+                injected_errors: 3,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn should_stop_injecting_faults_once_disabled() {
+        let env = environment_with_value();
+        env.set_fault_config(Some(FaultConfig::new(7).with_read_error_rate(1.0)))
+            .unwrap();
+        let read_txn = env.create_read_txn().unwrap();
+        assert!(read_txn.read(None, KEY).is_err());
+
+        env.set_fault_config(None).unwrap();
+        assert_eq!(env.fault_stats().unwrap(), FaultStats::default());
+        assert_eq!(
+            read_txn.read(None, KEY),
+            Ok(Some(Bytes::from(VALUE.to_vec())))
+        );
+    }
+}
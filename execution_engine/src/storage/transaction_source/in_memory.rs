@@ -1,10 +1,13 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
+    io::{Read, Write},
     sync::{Arc, Mutex, MutexGuard},
 };
 
-use casper_types::bytesrepr::Bytes;
+use casper_types::bytesrepr::{self, Bytes, ToBytes};
 
+#[cfg(any(test, feature = "test-support"))]
+use crate::storage::transaction_source::fault_injection::{FaultConfig, FaultInjector, FaultStats};
 use crate::storage::{
     error::in_memory::Error,
     transaction_source::{Readable, Transaction, TransactionSource, Writable},
@@ -18,12 +21,21 @@ type WriteLock<'a> = MutexGuard<'a, WriteCapability>;
 
 type BytesMap = HashMap<Bytes, Bytes>;
 
+/// The contents of all databases of an environment, sorted so that they serialize the same
+/// regardless of the order they were written in.
+type Snapshot = BTreeMap<Option<String>, BTreeMap<Bytes, Bytes>>;
+
+#[cfg(any(test, feature = "test-support"))]
+type SharedFaultInjector = Arc<Mutex<Option<FaultInjector>>>;
+
 #[cfg(test)]
 type PoisonError<'a> = std::sync::PoisonError<MutexGuard<'a, HashMap<Option<String>, BytesMap>>>;
 
 /// A read transaction for the in-memory trie store.
 pub struct InMemoryReadTransaction {
     view: HashMap<Option<String>, BytesMap>,
+    #[cfg(any(test, feature = "test-support"))]
+    faults: SharedFaultInjector,
 }
 
 impl InMemoryReadTransaction {
@@ -33,7 +45,11 @@ impl InMemoryReadTransaction {
             let view_lock = db_ref.lock()?;
             view_lock.to_owned()
         };
-        Ok(InMemoryReadTransaction { view })
+        Ok(InMemoryReadTransaction {
+            view,
+            #[cfg(any(test, feature = "test-support"))]
+            faults: Arc::clone(&store.faults),
+        })
     }
 }
 
@@ -49,11 +65,10 @@ impl Transaction for InMemoryReadTransaction {
 
 impl Readable for InMemoryReadTransaction {
     fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Bytes>, Self::Error> {
-        let sub_view = match self.view.get(&handle) {
-            Some(view) => view,
-            None => return Ok(None),
-        };
-        Ok(sub_view.get(&Bytes::from(key)).cloned())
+        let value = read_from_view(&self.view, handle, key);
+        #[cfg(any(test, feature = "test-support"))]
+        let value = inject_faults(&self.faults, value)?;
+        Ok(value)
     }
 }
 
@@ -62,6 +77,8 @@ pub struct InMemoryReadWriteTransaction<'a> {
     view: HashMap<Option<String>, BytesMap>,
     store_ref: Arc<Mutex<HashMap<Option<String>, BytesMap>>>,
     _write_lock: WriteLock<'a>,
+    #[cfg(any(test, feature = "test-support"))]
+    faults: SharedFaultInjector,
 }
 
 impl<'a> InMemoryReadWriteTransaction<'a> {
@@ -78,6 +95,8 @@ impl<'a> InMemoryReadWriteTransaction<'a> {
             view,
             store_ref,
             _write_lock,
+            #[cfg(any(test, feature = "test-support"))]
+            faults: Arc::clone(&store.faults),
         })
     }
 }
@@ -96,11 +115,10 @@ impl<'a> Transaction for InMemoryReadWriteTransaction<'a> {
 
 impl<'a> Readable for InMemoryReadWriteTransaction<'a> {
     fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Bytes>, Self::Error> {
-        let sub_view = match self.view.get(&handle) {
-            Some(view) => view,
-            None => return Ok(None),
-        };
-        Ok(sub_view.get(&Bytes::from(key)).cloned())
+        let value = read_from_view(&self.view, handle, key);
+        #[cfg(any(test, feature = "test-support"))]
+        let value = inject_faults(&self.faults, value)?;
+        Ok(value)
     }
 }
 
@@ -110,12 +128,43 @@ impl<'a> Writable for InMemoryReadWriteTransaction<'a> {
         sub_view.insert(Bytes::from(key), Bytes::from(value));
         Ok(())
     }
+
+    fn delete(&mut self, handle: Self::Handle, key: &[u8]) -> Result<bool, Self::Error> {
+        // The whole view of each handle replaces the stored one on commit, so removing the key
+        // from the view is enough.
+        let removed = self
+            .view
+            .get_mut(&handle)
+            .and_then(|sub_view| sub_view.remove(&Bytes::from(key)));
+        Ok(removed.is_some())
+    }
+}
+
+fn read_from_view(
+    view: &HashMap<Option<String>, BytesMap>,
+    handle: Option<String>,
+    key: &[u8],
+) -> Option<Bytes> {
+    view.get(&handle)?.get(&Bytes::from(key)).cloned()
+}
+
+#[cfg(any(test, feature = "test-support"))]
+fn inject_faults(
+    faults: &SharedFaultInjector,
+    value: Option<Bytes>,
+) -> Result<Option<Bytes>, Error> {
+    match faults.lock()?.as_mut() {
+        Some(injector) => injector.on_read(value),
+        None => Ok(value),
+    }
 }
 
 /// An environment for the in-memory trie store.
 pub struct InMemoryEnvironment {
     data: Arc<Mutex<HashMap<Option<String>, BytesMap>>>,
     write_mutex: Arc<Mutex<WriteCapability>>,
+    #[cfg(any(test, feature = "test-support"))]
+    faults: SharedFaultInjector,
 }
 
 impl Default for InMemoryEnvironment {
@@ -126,7 +175,12 @@ impl Default for InMemoryEnvironment {
             Arc::new(Mutex::new(initial_map))
         };
         let write_mutex = Arc::new(Mutex::new(WriteCapability));
-        InMemoryEnvironment { data, write_mutex }
+        InMemoryEnvironment {
+            data,
+            write_mutex,
+            #[cfg(any(test, feature = "test-support"))]
+            faults: Default::default(),
+        }
     }
 }
 
@@ -136,6 +190,35 @@ impl InMemoryEnvironment {
         Default::default()
     }
 
+    /// Writes the contents of all databases to `writer`.
+    ///
+    /// Environments holding the same data produce the same snapshot.
+    pub fn write_snapshot<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let snapshot: Snapshot = self
+            .data
+            .lock()?
+            .iter()
+            .map(|(handle, data)| (handle.clone(), data.clone().into_iter().collect()))
+            .collect();
+        writer.write_all(&snapshot.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Creates an environment holding the databases read from a snapshot written by
+    /// [`write_snapshot`](Self::write_snapshot).
+    pub fn read_snapshot<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let snapshot: Snapshot = bytesrepr::deserialize(bytes)?;
+        let environment = InMemoryEnvironment::new();
+        environment.data.lock()?.extend(
+            snapshot
+                .into_iter()
+                .map(|(handle, data)| (handle, data.into_iter().collect())),
+        );
+        Ok(environment)
+    }
+
     #[cfg(test)]
     pub fn data(&self, name: Option<&str>) -> Result<Option<BytesMap>, PoisonError> {
         let data = self.data.lock()?;
@@ -143,6 +226,27 @@ impl InMemoryEnvironment {
         let ret = data.get(&name).cloned();
         Ok(ret)
     }
+
+    /// Starts injecting faults into all subsequent reads as described by `config`, or stops
+    /// injecting them if `config` is `None`.
+    ///
+    /// Transactions created before the call are affected too.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn set_fault_config(&self, config: Option<FaultConfig>) -> Result<(), Error> {
+        *self.faults.lock()? = config.map(FaultInjector::new);
+        Ok(())
+    }
+
+    /// Returns the statistics of faults injected since the last call to `set_fault_config`.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn fault_stats(&self) -> Result<FaultStats, Error> {
+        Ok(self
+            .faults
+            .lock()?
+            .as_ref()
+            .map(FaultInjector::stats)
+            .unwrap_or_default())
+    }
 }
 
 impl<'a> TransactionSource<'a> for InMemoryEnvironment {
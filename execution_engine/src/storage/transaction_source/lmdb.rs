@@ -1,19 +1,53 @@
-use std::path::Path;
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    ffi::CString,
+    fmt::{self, Debug, Formatter},
+    fs::{self, File},
+    io::{self, Seek, SeekFrom},
+    mem,
+    ops::{Deref, DerefMut},
+    os::unix::{ffi::OsStrExt, io::AsRawFd},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Condvar, Mutex, PoisonError,
+    },
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
+};
+
+#[cfg(debug_assertions)]
+use backtrace::Backtrace;
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use casper_types::bytesrepr::Bytes;
+use casper_hashing::Digest;
+use casper_types::bytesrepr::{self, Bytes};
 use lmdb::{
-    self, Database, Environment, EnvironmentFlags, RoTransaction, RwTransaction, WriteFlags,
+    self, Database, Environment, EnvironmentFlags, InactiveTransaction, RoTransaction,
+    RwTransaction, WriteFlags,
 };
+use lmdb_sys::{mdb_env_copy2, mdb_env_copyfd2, MDB_CP_COMPACT};
+use tracing::{info, warn};
 
-use crate::storage::{
-    error,
-    transaction_source::{Readable, Transaction, TransactionSource, Writable},
-    trie_store::lmdb::ScratchTrieStore,
-    MAX_DBS,
+use crate::{
+    shared::utils::OS_PAGE_SIZE,
+    storage::{
+        error,
+        transaction_source::{Readable, Transaction, TransactionSource, Writable},
+        trie_store::lmdb::ScratchTrieStore,
+        MAX_DBS,
+    },
 };
 
 /// Filename for the LMDB database created by the EE.
-const EE_DB_FILENAME: &str = "data.lmdb";
+pub(crate) const EE_DB_FILENAME: &str = "data.lmdb";
+
+/// How long to wait for a pooled read transaction to be released by another thread before opening
+/// one regardless.
+const READ_TXN_POOL_MAX_WAIT: Duration = Duration::from_secs(10);
 
 impl Transaction for ScratchTrieStore {
     type Error = error::Error;
@@ -27,21 +61,75 @@ impl Transaction for ScratchTrieStore {
 impl Readable for ScratchTrieStore {
     fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Bytes>, Self::Error> {
         let txn = self.env.create_read_txn()?;
-        match lmdb::Transaction::get(&txn, handle.store.get_db(), &key) {
-            Ok(bytes) => Ok(Some(Bytes::from(bytes))),
-            Err(lmdb::Error::NotFound) => Ok(None),
-            Err(e) => Err(error::Error::Lmdb(e)),
+        let codec = handle.store.codec();
+        for db in handle.store.databases() {
+            if let Some(stored_bytes) = txn.read(db, key).map_err(error::Error::Lmdb)? {
+                return codec.decode(stored_bytes).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn exists(&self, handle: Self::Handle, key: &[u8]) -> Result<bool, Self::Error> {
+        let txn = self.env.create_read_txn()?;
+        for db in handle.store.databases() {
+            if txn.exists(db, key).map_err(error::Error::Lmdb)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn read_with<F, R>(
+        &self,
+        handle: Self::Handle,
+        key: &[u8],
+        f: F,
+    ) -> Result<Option<R>, Self::Error>
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        let txn = self.env.create_read_txn()?;
+        let codec = handle.store.codec();
+        let mut f = Some(f);
+        for db in handle.store.databases() {
+            let maybe_result = txn
+                .read_with(db, key, |stored_bytes| {
+                    let f = f
+                        .take()
+                        .expect("trie should only be read from one database");
+                    codec
+                        .decode_slice(stored_bytes)
+                        .map(|trie_bytes| f(&trie_bytes))
+                })
+                .map_err(error::Error::Lmdb)?;
+            if let Some(result) = maybe_result {
+                return result.map(Some);
+            }
         }
+        Ok(None)
     }
 }
 
 impl Writable for ScratchTrieStore {
     fn write(&mut self, handle: Self::Handle, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
+        let stored_bytes = handle.store.codec().encode(value)?;
         let mut txn = self.env.create_read_write_txn()?;
-        txn.put(handle.store.get_db(), &key, &value, WriteFlags::empty())
-            .map_err(error::Error::Lmdb)?;
+        txn.put(
+            handle.store.db_for(value),
+            &key,
+            &stored_bytes,
+            WriteFlags::empty(),
+        )
+        .map_err(error::Error::Lmdb)?;
         Ok(())
     }
+
+    /// Drops the trie under `key` from the cache, leaving the underlying store untouched.
+    fn delete(&mut self, handle: Self::Handle, key: &[u8]) -> Result<bool, Self::Error> {
+        let digest = Digest::try_from(key).map_err(|_| bytesrepr::Error::Formatting)?;
+        handle.cache.remove(&digest)
+    }
 }
 
 impl<'a> TransactionSource<'a> for ScratchTrieStore {
@@ -76,6 +164,588 @@ impl<'a> Readable for RoTransaction<'a> {
             Err(e) => Err(e),
         }
     }
+
+    fn read_with<F, R>(
+        &self,
+        handle: Self::Handle,
+        key: &[u8],
+        f: F,
+    ) -> Result<Option<R>, Self::Error>
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        lmdb_read_with(self, handle, key, f)
+    }
+
+    fn exists(&self, handle: Self::Handle, key: &[u8]) -> Result<bool, Self::Error> {
+        lmdb_exists(self, handle, key)
+    }
+}
+
+/// Calls `f` with the value under `key` in `handle`, in place in the memory map.
+fn lmdb_read_with<T, F, R>(
+    txn: &T,
+    handle: Database,
+    key: &[u8],
+    f: F,
+) -> Result<Option<R>, lmdb::Error>
+where
+    T: lmdb::Transaction,
+    F: FnOnce(&[u8]) -> R,
+{
+    match txn.get(handle, &key) {
+        Ok(bytes) => Ok(Some(f(bytes))),
+        Err(lmdb::Error::NotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Looks `key` up in `handle` without copying the value out of the memory map.
+fn lmdb_exists<T: lmdb::Transaction>(
+    txn: &T,
+    handle: Database,
+    key: &[u8],
+) -> Result<bool, lmdb::Error> {
+    match txn.get(handle, &key) {
+        Ok(_) => Ok(true),
+        Err(lmdb::Error::NotFound) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// A read transaction which is still open.
+#[derive(Debug)]
+struct OpenReadTxn {
+    opened_at: Instant,
+    /// Whether a warning was already logged about the transaction being held open for too long.
+    reported: bool,
+    /// Where the transaction was created.  Only captured in debug builds, as it is costly.
+    #[cfg(debug_assertions)]
+    backtrace: Backtrace,
+}
+
+/// The read transactions open on an [`LmdbEnvironment`].
+///
+/// A leaked reader is never released, so it never triggers the warning of its [`ReadTxnGuard`];
+/// tracking open readers allows finding it while it is still open.
+#[derive(Debug, Default)]
+struct OpenReadTxns {
+    next_id: AtomicU64,
+    txns: Mutex<HashMap<u64, OpenReadTxn>>,
+}
+
+impl OpenReadTxns {
+    fn insert(&self, opened_at: Instant) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let txn = OpenReadTxn {
+            opened_at,
+            reported: false,
+            #[cfg(debug_assertions)]
+            backtrace: Backtrace::new_unresolved(),
+        };
+        self.txns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id, txn);
+        id
+    }
+
+    fn remove(&self, id: u64) {
+        self.txns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&id);
+    }
+}
+
+/// Tracks how long a read transaction has been open.
+///
+/// LMDB can't reuse pages freed by later commits while an older read transaction still refers to
+/// them, so a reader which is never closed makes the database file grow without bound.  This logs
+/// a warning when a transaction is released after being held for longer than the configured
+/// duration, which makes such readers easy to spot.
+#[derive(Debug)]
+struct ReadTxnGuard<'a> {
+    opened_at: Instant,
+    max_duration: Option<Duration>,
+    /// The ID under which the transaction is tracked as open, if the environment tracks readers.
+    tracked: Option<(u64, &'a OpenReadTxns)>,
+}
+
+impl<'a> ReadTxnGuard<'a> {
+    fn is_overdue(&self) -> bool {
+        self.max_duration.map_or(false, |max_duration| {
+            self.opened_at.elapsed() > max_duration
+        })
+    }
+}
+
+impl<'a> Drop for ReadTxnGuard<'a> {
+    fn drop(&mut self) {
+        if let Some((id, open_read_txns)) = self.tracked {
+            open_read_txns.remove(id);
+        }
+        if self.is_overdue() {
+            warn!(
+                elapsed = ?self.opened_at.elapsed(),
+                max_duration = ?self.max_duration,
+                "global state read transaction was held open for too long"
+            );
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct OpenTxnsState {
+    /// The number of transactions open on each thread holding any.
+    holders: HashMap<ThreadId, usize>,
+    /// Whether the memory map is waiting to be resized.
+    resize_pending: bool,
+    /// The number of copies of the environment in progress.
+    copies: usize,
+}
+
+/// Counts the transactions open on an [`LmdbEnvironment`].
+///
+/// LMDB only allows the memory map to be resized while no transaction is open in the process.
+/// Once a resize is pending, threads which hold no transaction wait for it before opening one, so
+/// that a steady stream of short transactions can't starve the resize.  A thread which already
+/// holds a transaction can still open another one, as the resize waits for it anyway.
+///
+/// Copies of the environment read the memory map too, but are counted apart from transactions: as
+/// a copy can take a long time, a resize waits for copies in progress before it stops new
+/// transactions from being opened, rather than stalling them for the rest of the copy.
+#[derive(Debug, Default)]
+struct OpenTxns {
+    state: Mutex<OpenTxnsState>,
+    changed: Condvar,
+}
+
+impl OpenTxns {
+    fn open(&self) -> OpenTxnGuard<'_> {
+        let thread_id = thread::current().id();
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        while state.resize_pending && !state.holders.contains_key(&thread_id) {
+            state = self
+                .changed
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        *state.holders.entry(thread_id).or_default() += 1;
+        OpenTxnGuard {
+            open_txns: self,
+            thread_id,
+        }
+    }
+
+    /// Marks a copy of the environment as in progress, once no resize is pending.
+    fn start_copy(&self) -> CopyGuard<'_> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        while state.resize_pending {
+            state = self
+                .changed
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        state.copies += 1;
+        CopyGuard { open_txns: self }
+    }
+
+    /// Returns whether the calling thread holds an open transaction.
+    fn held_by_current_thread(&self) -> bool {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .holders
+            .contains_key(&thread::current().id())
+    }
+
+    /// Runs `f` once no transaction is open and no copy is in progress, preventing new ones from
+    /// being opened from the moment the copies are done until `f` returns.
+    fn when_all_closed<T>(&self, f: impl FnOnce() -> T) -> T {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        while state.resize_pending || state.copies > 0 {
+            state = self
+                .changed
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        state.resize_pending = true;
+        while !state.holders.is_empty() {
+            state = self
+                .changed
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        let result = f();
+        state.resize_pending = false;
+        drop(state);
+        self.changed.notify_all();
+        result
+    }
+}
+
+/// Marks a transaction as open until dropped.
+#[derive(Debug)]
+struct OpenTxnGuard<'a> {
+    open_txns: &'a OpenTxns,
+    thread_id: ThreadId,
+}
+
+impl<'a> Drop for OpenTxnGuard<'a> {
+    fn drop(&mut self) {
+        let mut state = self
+            .open_txns
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(count) = state.holders.get_mut(&self.thread_id) {
+            *count -= 1;
+            if *count == 0 {
+                state.holders.remove(&self.thread_id);
+            }
+        }
+        if state.holders.is_empty() {
+            drop(state);
+            self.open_txns.changed.notify_all();
+        }
+    }
+}
+
+/// Marks a copy of the environment as in progress until dropped.
+#[derive(Debug)]
+struct CopyGuard<'a> {
+    open_txns: &'a OpenTxns,
+}
+
+impl<'a> Drop for CopyGuard<'a> {
+    fn drop(&mut self) {
+        let mut state = self
+            .open_txns
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        state.copies -= 1;
+        drop(state);
+        self.open_txns.changed.notify_all();
+    }
+}
+
+/// A read transaction which was reset, keeping its slot in the reader table.
+struct IdleReadTxn(InactiveTransaction<'static>);
+
+// SAFETY: Environments pooling read transactions are opened with `MDB_NOTLS`, so reader slots
+// belong to transactions rather than to the threads which created them, and a reset transaction
+// may be renewed on any thread.
+unsafe impl Send for IdleReadTxn {}
+
+impl Debug for IdleReadTxn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("IdleReadTxn")
+    }
+}
+
+#[derive(Debug, Default)]
+struct ReadTxnPoolState {
+    /// The number of pooled transactions currently open.
+    in_use: usize,
+    /// The number of pooled transactions currently open on each thread holding any.
+    ///
+    /// Read transactions can't be sent to other threads, so each is released on the thread which
+    /// acquired it.
+    holders: HashMap<ThreadId, usize>,
+    idle: Vec<IdleReadTxn>,
+}
+
+impl ReadTxnPoolState {
+    fn remove_holder(&mut self, thread_id: ThreadId) {
+        if let Some(count) = self.holders.get_mut(&thread_id) {
+            *count -= 1;
+            if *count == 0 {
+                self.holders.remove(&thread_id);
+            }
+        }
+    }
+}
+
+/// A pool of the read transactions of an [`LmdbEnvironment`].
+///
+/// Each open read transaction takes one of the `max_readers` slots of LMDB's reader table, and
+/// opening one more once they are all taken fails.  The pool bounds the number of read transactions
+/// open at once, so that many concurrent queries wait for one another rather than fail.  Released
+/// transactions are reset rather than closed, keeping their reader slot, and renewed for the next
+/// query.
+#[derive(Debug)]
+struct ReadTxnPool {
+    max_open: usize,
+    state: Mutex<ReadTxnPoolState>,
+    released: Condvar,
+}
+
+impl ReadTxnPool {
+    fn new(max_open: usize) -> Self {
+        ReadTxnPool {
+            max_open: max_open.max(1),
+            state: Mutex::new(ReadTxnPoolState::default()),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Returns an idle transaction renewed, or a new one if none is idle.
+    ///
+    /// `env` must be the environment owning the pool.  Waits for a transaction to be released while
+    /// `max_open` are open, unless the calling thread already holds one: a nested read would
+    /// otherwise wait for the outer one, which is only released once the nested read is done.  The
+    /// wait is bounded by [`READ_TXN_POOL_MAX_WAIT`] in case the holders wait on the caller in
+    /// some other way.
+    fn acquire<'a>(&'a self, env: &'a Environment) -> Result<RoTransaction<'a>, lmdb::Error> {
+        let thread_id = thread::current().id();
+        let deadline = Instant::now() + READ_TXN_POOL_MAX_WAIT;
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        while state.in_use >= self.max_open && !state.holders.contains_key(&thread_id) {
+            let now = Instant::now();
+            if now >= deadline {
+                warn!(
+                    max_open = self.max_open,
+                    "timed out waiting for a pooled global state read transaction"
+                );
+                break;
+            }
+            state = self
+                .released
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        state.in_use += 1;
+        *state.holders.entry(thread_id).or_default() += 1;
+        let maybe_idle = state.idle.pop();
+        drop(state);
+
+        let result = match maybe_idle {
+            Some(IdleReadTxn(inactive)) => inactive.renew().or_else(|_| env.begin_ro_txn()),
+            None => env.begin_ro_txn(),
+        };
+        if result.is_err() {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.in_use -= 1;
+            state.remove_holder(thread_id);
+            drop(state);
+            self.released.notify_one();
+        }
+        result
+    }
+
+    /// Resets `txn` and keeps it for reuse, unless enough transactions are already idle.
+    fn release(&self, txn: RoTransaction<'_>) {
+        let inactive = txn.reset();
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.in_use -= 1;
+        state.remove_holder(thread::current().id());
+        if state.in_use + state.idle.len() < self.max_open {
+            // SAFETY: The transaction borrows the environment owning the pool, and extending that
+            // borrow to `'static` only lets the pool hold on to it.  It never outlives the
+            // environment:
+            // * the pool is owned by the `LmdbEnvironment` wrapping the environment and is only
+            //   reachable through it, so it can't be used after that is dropped,
+            // * idle transactions only leave the pool through `acquire`, which renews them bound
+            //   again to a borrow of the environment,
+            // * `LmdbEnvironment`'s `Drop` implementation closes the idle transactions, which are
+            //   reset and so hold no snapshot, before its fields are dropped and the environment is
+            //   closed, and
+            // * LMDB allocates the environment itself on the heap, so moving the `LmdbEnvironment`
+            //   doesn't move what the transactions point to.
+            let inactive = unsafe {
+                mem::transmute::<InactiveTransaction<'_>, InactiveTransaction<'static>>(inactive)
+            };
+            state.idle.push(IdleReadTxn(inactive));
+        }
+        drop(state);
+        self.released.notify_one();
+    }
+
+    /// Closes every idle transaction.
+    fn clear(&self) {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .idle
+            .clear();
+    }
+}
+
+/// An error which may be caused by the memory map of an [`LmdbEnvironment`] being full.
+pub trait MapFullError {
+    /// Returns `true` if this is LMDB's `MDB_MAP_FULL` error.
+    fn is_map_full(&self) -> bool;
+}
+
+impl MapFullError for lmdb::Error {
+    fn is_map_full(&self) -> bool {
+        matches!(self, lmdb::Error::MapFull)
+    }
+}
+
+impl MapFullError for error::Error {
+    fn is_map_full(&self) -> bool {
+        matches!(self, error::Error::Lmdb(lmdb::Error::MapFull))
+    }
+}
+
+/// A read transaction on an [`LmdbEnvironment`].
+///
+/// Wraps [`lmdb::RoTransaction`].  Like every read transaction, it reads from a snapshot of the
+/// database taken when it was created (see [`TransactionSource::create_read_txn`]).
+#[derive(Debug)]
+pub struct LmdbReadTransaction<'a> {
+    /// Only `None` once the transaction was released.
+    txn: Option<RoTransaction<'a>>,
+    /// The pool to return the transaction to once released, if it came from one.
+    pool: Option<&'a ReadTxnPool>,
+    guard: ReadTxnGuard<'a>,
+    // Declared after `txn` so that the transaction is closed before it stops being counted.
+    _open_txn: Option<OpenTxnGuard<'a>>,
+}
+
+impl<'a> LmdbReadTransaction<'a> {
+    /// Returns how long this transaction has been open.
+    pub fn elapsed(&self) -> Duration {
+        self.guard.opened_at.elapsed()
+    }
+
+    /// Returns `true` if this transaction has been open for longer than the maximum duration
+    /// configured for its environment.
+    pub fn is_overdue(&self) -> bool {
+        self.guard.is_overdue()
+    }
+
+    /// Returns the wrapped transaction, e.g. to open cursors.
+    pub(crate) fn lmdb_txn(&self) -> &RoTransaction<'a> {
+        self.txn
+            .as_ref()
+            .expect("transaction should not be released before being dropped")
+    }
+}
+
+impl<'a> Drop for LmdbReadTransaction<'a> {
+    fn drop(&mut self) {
+        if let (Some(txn), Some(pool)) = (self.txn.take(), self.pool) {
+            pool.release(txn);
+        }
+    }
+}
+
+impl<'a> Transaction for LmdbReadTransaction<'a> {
+    type Error = lmdb::Error;
+
+    type Handle = Database;
+
+    fn commit(mut self) -> Result<(), Self::Error> {
+        match (self.txn.take(), self.pool) {
+            (Some(txn), Some(pool)) => {
+                pool.release(txn);
+                Ok(())
+            }
+            (Some(txn), None) => lmdb::Transaction::commit(txn),
+            (None, _) => Ok(()),
+        }
+    }
+}
+
+impl<'a> Readable for LmdbReadTransaction<'a> {
+    fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Bytes>, Self::Error> {
+        self.lmdb_txn().read(handle, key)
+    }
+
+    fn exists(&self, handle: Self::Handle, key: &[u8]) -> Result<bool, Self::Error> {
+        self.lmdb_txn().exists(handle, key)
+    }
+
+    fn read_with<F, R>(
+        &self,
+        handle: Self::Handle,
+        key: &[u8],
+        f: F,
+    ) -> Result<Option<R>, Self::Error>
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        self.lmdb_txn().read_with(handle, key, f)
+    }
+}
+
+/// A read-write transaction on an [`LmdbEnvironment`].
+///
+/// Wraps [`lmdb::RwTransaction`], which it dereferences to, e.g. to open cursors.
+#[derive(Debug)]
+pub struct LmdbReadWriteTransaction<'a> {
+    txn: RwTransaction<'a>,
+    // Declared after `txn` so that the transaction is closed before it stops being counted.
+    _open_txn: Option<OpenTxnGuard<'a>>,
+}
+
+impl<'a> LmdbReadWriteTransaction<'a> {
+    /// Commits the transaction.
+    pub fn commit(self) -> Result<(), lmdb::Error> {
+        let LmdbReadWriteTransaction { txn, _open_txn } = self;
+        lmdb::Transaction::commit(txn)
+    }
+}
+
+impl<'a> Deref for LmdbReadWriteTransaction<'a> {
+    type Target = RwTransaction<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.txn
+    }
+}
+
+impl<'a> DerefMut for LmdbReadWriteTransaction<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.txn
+    }
+}
+
+impl<'a> Transaction for LmdbReadWriteTransaction<'a> {
+    type Error = lmdb::Error;
+
+    type Handle = Database;
+
+    fn commit(self) -> Result<(), Self::Error> {
+        LmdbReadWriteTransaction::commit(self)
+    }
+}
+
+impl<'a> Readable for LmdbReadWriteTransaction<'a> {
+    fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Bytes>, Self::Error> {
+        self.txn.read(handle, key)
+    }
+
+    fn exists(&self, handle: Self::Handle, key: &[u8]) -> Result<bool, Self::Error> {
+        self.txn.exists(handle, key)
+    }
+
+    fn read_with<F, R>(
+        &self,
+        handle: Self::Handle,
+        key: &[u8],
+        f: F,
+    ) -> Result<Option<R>, Self::Error>
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        self.txn.read_with(handle, key, f)
+    }
+}
+
+impl<'a> Writable for LmdbReadWriteTransaction<'a> {
+    fn write(&mut self, handle: Self::Handle, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
+        self.txn.write(handle, key, value)
+    }
+
+    fn delete(&mut self, handle: Self::Handle, key: &[u8]) -> Result<bool, Self::Error> {
+        Writable::delete(&mut self.txn, handle, key)
+    }
 }
 
 impl<'a> Transaction for RwTransaction<'a> {
@@ -96,6 +766,22 @@ impl<'a> Readable for RwTransaction<'a> {
             Err(e) => Err(e),
         }
     }
+
+    fn read_with<F, R>(
+        &self,
+        handle: Self::Handle,
+        key: &[u8],
+        f: F,
+    ) -> Result<Option<R>, Self::Error>
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        lmdb_read_with(self, handle, key, f)
+    }
+
+    fn exists(&self, handle: Self::Handle, key: &[u8]) -> Result<bool, Self::Error> {
+        lmdb_exists(self, handle, key)
+    }
 }
 
 impl<'a> Writable for RwTransaction<'a> {
@@ -103,6 +789,182 @@ impl<'a> Writable for RwTransaction<'a> {
         self.put(handle, &key, &value, WriteFlags::empty())
             .map_err(Into::into)
     }
+
+    fn delete(&mut self, handle: Self::Handle, key: &[u8]) -> Result<bool, Self::Error> {
+        match self.del(handle, &key, None) {
+            Ok(()) => Ok(true),
+            Err(lmdb::Error::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// How an [`LmdbEnvironment`] makes its commits durable, trading durability for write throughput.
+///
+/// None of the modes risks the database when only the process crashes: committed data sits in the
+/// OS page cache either way.  They differ in what a crash of the OS or a power loss can cost.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LmdbSyncMode {
+    /// Every commit is flushed to disk before it returns.
+    ///
+    /// Durable, but the slowest mode, as each commit waits for two flushes.
+    Full,
+    /// Commits are written through a writable memory map and flushed asynchronously, and the
+    /// environment is synced explicitly through [`LmdbEnvironment::sync`], e.g. after each block.
+    ///
+    /// A crash of the OS between syncs can lose the commits since the last sync and, as pages may
+    /// reach the disk in any order, can leave the database corrupted.
+    Manual,
+    /// Data pages are flushed on every commit, but the meta page only with the next commit or
+    /// sync.
+    ///
+    /// A crash of the OS can undo the last commit, but leaves the database intact.
+    NoMetaSync,
+    /// Commits are never flushed, leaving it to the OS to write them back whenever it chooses.
+    ///
+    /// The fastest mode.  A crash of the OS can lose any number of recent commits and can leave
+    /// the database corrupted.
+    NoSync,
+}
+
+impl LmdbSyncMode {
+    /// Returns the flags an LMDB environment is opened with to apply the mode.
+    pub fn flags(self) -> EnvironmentFlags {
+        match self {
+            LmdbSyncMode::Full => EnvironmentFlags::empty(),
+            LmdbSyncMode::Manual => {
+                EnvironmentFlags::MAP_ASYNC
+                    | EnvironmentFlags::WRITE_MAP
+                    | EnvironmentFlags::NO_META_SYNC
+            }
+            LmdbSyncMode::NoMetaSync => EnvironmentFlags::NO_META_SYNC,
+            LmdbSyncMode::NoSync => EnvironmentFlags::NO_SYNC,
+        }
+    }
+}
+
+/// An invalid [`LmdbTuning`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LmdbTuningError {
+    /// The growth step isn't a whole number of pages.
+    #[error(
+        "LMDB growth step of {growth_step} bytes is not a multiple of the OS page size of \
+         {page_size} bytes"
+    )]
+    GrowthStepNotPageAligned {
+        /// The configured growth step.
+        growth_step: usize,
+        /// The OS page size.
+        page_size: usize,
+    },
+}
+
+/// The settings an [`LmdbEnvironment`] is opened with which trade durability or read latency
+/// against throughput.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LmdbTuning {
+    /// How commits are made durable.
+    pub sync_mode: LmdbSyncMode,
+    /// Whether the OS may read ahead of the pages accessed.
+    ///
+    /// Readahead speeds up the mostly sequential reads of a database smaller than RAM on spinning
+    /// disks, but wastes page cache on the random reads which dominate once the database outgrows
+    /// RAM, or on SSDs.
+    pub readahead: bool,
+    /// The number of bytes to grow the memory map by once full, if it should be grown at all.
+    pub map_size_increment: Option<usize>,
+    /// The maximum number of read transactions open at once, if read transactions should be
+    /// pooled at all.
+    ///
+    /// Released read transactions are then reset and renewed for later queries rather than closed,
+    /// and queries wait for a transaction to be released while this many are open, rather than
+    /// failing once the reader table is full.  It should be lower than `max_readers` if other
+    /// processes read the database too.  Pooling opens the environment with `MDB_NOTLS`, as pooled
+    /// transactions may be renewed on other threads than the ones which created them.
+    pub read_txn_pool_size: Option<usize>,
+}
+
+impl LmdbTuning {
+    /// Returns the tuning [`LmdbEnvironment::new`] opens an environment with.
+    pub fn new(manual_sync_enabled: bool) -> Self {
+        LmdbTuning {
+            sync_mode: if manual_sync_enabled {
+                LmdbSyncMode::Manual
+            } else {
+                LmdbSyncMode::Full
+            },
+            readahead: false,
+            map_size_increment: None,
+            read_txn_pool_size: None,
+        }
+    }
+
+    /// Checks that the tuning can be applied.
+    pub fn validate(&self) -> Result<(), LmdbTuningError> {
+        match self.map_size_increment {
+            Some(growth_step) if growth_step % *OS_PAGE_SIZE != 0 => {
+                Err(LmdbTuningError::GrowthStepNotPageAligned {
+                    growth_step,
+                    page_size: *OS_PAGE_SIZE,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn flags(&self) -> EnvironmentFlags {
+        let mut flags = EnvironmentFlags::NO_SUB_DIR;
+        if self.read_txn_pool_size.is_some() {
+            // Without thread-local reader slots, read transactions can be pooled across threads.
+            flags |= EnvironmentFlags::NO_TLS;
+        }
+        if !self.readahead {
+            flags |= EnvironmentFlags::NO_READAHEAD;
+        }
+        flags | self.sync_mode.flags()
+    }
+}
+
+/// Copies the environment `env`, which must have been opened with `NO_SUB_DIR`, to the new file
+/// `target_path`, leaving out free pages if `compact` is set.
+///
+/// The copy includes all named databases and is made from a single read transaction, so it is
+/// consistent even while other threads or processes keep committing.
+pub fn copy_environment(
+    env: &Environment,
+    target_path: &Path,
+    compact: bool,
+) -> Result<(), lmdb::Error> {
+    let c_target_path =
+        CString::new(target_path.as_os_str().as_bytes()).map_err(|_| lmdb::Error::Invalid)?;
+    let flags = if compact { MDB_CP_COMPACT } else { 0 };
+    // SAFETY: `env` is a valid, open environment for the duration of the call, and the path is a
+    // NUL-terminated string which outlives it.
+    let result = unsafe { mdb_env_copy2(env.env(), c_target_path.as_ptr(), flags) };
+    if result != 0 {
+        return Err(lmdb::Error::from_err_code(result));
+    }
+    Ok(())
+}
+
+/// Like [`copy_environment`], but writes the copy to `file` from its current offset.
+///
+/// LMDB writes the copy sequentially without seeking, so it can be streamed into a larger file
+/// such as an archive, leaving the offset of `file` at the end of the copy.
+pub fn copy_environment_to_file(
+    env: &Environment,
+    file: &File,
+    compact: bool,
+) -> Result<(), lmdb::Error> {
+    let flags = if compact { MDB_CP_COMPACT } else { 0 };
+    // SAFETY: `env` is a valid, open environment and `file` an open file for the duration of the
+    // call.
+    let result = unsafe { mdb_env_copyfd2(env.env(), file.as_raw_fd(), flags) };
+    if result != 0 {
+        return Err(lmdb::Error::from_err_code(result));
+    }
+    Ok(())
 }
 
 /// The environment for an LMDB-backed trie store.
@@ -111,7 +973,16 @@ impl<'a> Writable for RwTransaction<'a> {
 #[derive(Debug)]
 pub struct LmdbEnvironment {
     env: Environment,
-    manual_sync_enabled: bool,
+    sync_mode: LmdbSyncMode,
+    read_only: bool,
+    max_read_txn_duration: Option<Duration>,
+    /// The current size of the memory map in bytes.
+    map_size: AtomicUsize,
+    /// The number of bytes to grow the memory map by once full, if it should be grown at all.
+    map_size_increment: Option<usize>,
+    open_txns: OpenTxns,
+    open_read_txns: OpenReadTxns,
+    read_txn_pool: Option<ReadTxnPool>,
 }
 
 impl LmdbEnvironment {
@@ -122,38 +993,295 @@ impl LmdbEnvironment {
         max_readers: u32,
         manual_sync_enabled: bool,
     ) -> Result<Self, error::Error> {
-        let lmdb_flags = if manual_sync_enabled {
-            // These options require that we manually call sync on the environment for the EE.
-            EnvironmentFlags::NO_SUB_DIR
-                | EnvironmentFlags::NO_READAHEAD
-                | EnvironmentFlags::MAP_ASYNC
-                | EnvironmentFlags::WRITE_MAP
-                | EnvironmentFlags::NO_META_SYNC
-        } else {
-            EnvironmentFlags::NO_SUB_DIR | EnvironmentFlags::NO_READAHEAD
-        };
+        Self::new_tuned(
+            path,
+            map_size,
+            max_readers,
+            LmdbTuning::new(manual_sync_enabled),
+        )
+    }
 
+    /// Constructs an `LmdbEnvironment` opened with `tuning`, which should have been validated.
+    pub fn new_tuned<P: AsRef<Path>>(
+        path: P,
+        map_size: usize,
+        max_readers: u32,
+        tuning: LmdbTuning,
+    ) -> Result<Self, error::Error> {
         let env = Environment::new()
             // Set the flag to manage our own directory like in the storage component.
-            .set_flags(lmdb_flags)
+            .set_flags(tuning.flags())
+            .set_max_dbs(MAX_DBS)
+            .set_map_size(map_size)
+            .set_max_readers(max_readers)
+            .open(&path.as_ref().join(EE_DB_FILENAME))?;
+        Ok(LmdbEnvironment {
+            env,
+            sync_mode: tuning.sync_mode,
+            read_only: false,
+            max_read_txn_duration: None,
+            map_size: AtomicUsize::new(map_size),
+            map_size_increment: tuning.map_size_increment,
+            open_txns: OpenTxns::default(),
+            open_read_txns: OpenReadTxns::default(),
+            read_txn_pool: tuning.read_txn_pool_size.map(ReadTxnPool::new),
+        })
+    }
+
+    /// Opens the existing database in `path` read-only, e.g. to inspect the global state of a
+    /// running node.
+    ///
+    /// Read-only transactions never take the writer lock, so they can't block or corrupt the
+    /// writes of another process, and read-write transactions are refused.  If the writing process
+    /// grows its memory map, this environment adopts the new size before creating its next read
+    /// transaction.
+    pub fn open_read_only<P: AsRef<Path>>(
+        path: P,
+        map_size: usize,
+        max_readers: u32,
+    ) -> Result<Self, error::Error> {
+        let env = Environment::new()
+            .set_flags(
+                EnvironmentFlags::NO_SUB_DIR
+                    | EnvironmentFlags::NO_READAHEAD
+                    | EnvironmentFlags::READ_ONLY,
+            )
             .set_max_dbs(MAX_DBS)
             .set_map_size(map_size)
             .set_max_readers(max_readers)
             .open(&path.as_ref().join(EE_DB_FILENAME))?;
         Ok(LmdbEnvironment {
             env,
-            manual_sync_enabled,
+            sync_mode: LmdbSyncMode::Full,
+            read_only: true,
+            max_read_txn_duration: None,
+            map_size: AtomicUsize::new(map_size),
+            map_size_increment: None,
+            open_txns: OpenTxns::default(),
+            open_read_txns: OpenReadTxns::default(),
+            read_txn_pool: None,
         })
     }
 
+    /// Enables growing the memory map by `map_size_increment` bytes whenever a write made through
+    /// [`write_with_map_growth`](Self::write_with_map_growth) finds it full.
+    ///
+    /// The increment should be a multiple of the OS page size.
+    pub fn with_map_size_increment(mut self, map_size_increment: usize) -> Self {
+        self.map_size_increment = Some(map_size_increment);
+        self
+    }
+
+    /// Returns the current size of the memory map in bytes.
+    pub fn map_size(&self) -> usize {
+        self.map_size.load(Ordering::SeqCst)
+    }
+
+    /// Copies the whole environment into the directory `dir`, which must exist and must not hold
+    /// a database yet, so that it can be opened with [`LmdbEnvironment::new`].
+    ///
+    /// The copy is made from a single read transaction, so it is consistent even while other
+    /// threads keep committing, and it leaves out free pages if `compact` is set.  Meanwhile the
+    /// memory map can't be grown, though transactions keep being opened until a writer finds the
+    /// map full.  If the copy fails, the partly written database is removed.
+    pub fn copy_to(&self, dir: &Path, compact: bool) -> Result<(), error::Error> {
+        let target_path = dir.join(EE_DB_FILENAME);
+        let result = {
+            let _copy = self.open_txns.start_copy();
+            copy_environment(&self.env, &target_path, compact)
+        };
+        if let Err(error) = result {
+            // LMDB refuses to copy over an existing file, which is then not ours to remove.
+            if error == lmdb::Error::Other(libc::EEXIST) {
+                return Err(error.into());
+            }
+            if let Err(remove_error) = fs::remove_file(&target_path) {
+                if remove_error.kind() != io::ErrorKind::NotFound {
+                    warn!(
+                        path = %target_path.display(),
+                        %remove_error,
+                        "failed to remove partial copy of global state"
+                    );
+                }
+            }
+            return Err(error.into());
+        }
+        Ok(())
+    }
+
+    /// Copies the whole environment into `file` from its current offset, as
+    /// [`LmdbEnvironment::copy_to`] does into a directory.
+    ///
+    /// If the copy fails, `file` is truncated back to the offset the copy started at.
+    pub fn copy_to_file(&self, file: &File, compact: bool) -> Result<(), error::Error> {
+        let mut seekable = file;
+        let start = seekable.stream_position()?;
+        let result = {
+            let _copy = self.open_txns.start_copy();
+            copy_environment_to_file(&self.env, file, compact)
+        };
+        if let Err(error) = result {
+            if let Err(truncate_error) = file
+                .set_len(start)
+                .and_then(|()| seekable.seek(SeekFrom::Start(start)).map(drop))
+            {
+                warn!(%truncate_error, "failed to truncate partial copy of global state");
+            }
+            return Err(error.into());
+        }
+        Ok(())
+    }
+
+    /// Runs `write`, which is expected to create and commit its own read-write transaction.
+    ///
+    /// If map growth is enabled and `write` fails with `MDB_MAP_FULL`, the aborted transaction is
+    /// discarded, the memory map is grown once every transaction open in this process is closed,
+    /// and `write` is run again.  Meanwhile, no new transactions are opened.  The calling thread
+    /// must not hold a transaction of its own, or growing the map waits forever.
+    pub fn write_with_map_growth<T, E, F>(&self, mut write: F) -> Result<T, E>
+    where
+        F: FnMut() -> Result<T, E>,
+        E: MapFullError + From<lmdb::Error>,
+    {
+        let map_size_increment = match self.map_size_increment {
+            Some(map_size_increment) => map_size_increment,
+            None => return write(),
+        };
+        loop {
+            let map_size = self.map_size();
+            let result = {
+                let _open_txn = self.open_txns.open();
+                write()
+            };
+            match result {
+                Err(error) if error.is_map_full() => {
+                    self.grow_map(map_size, map_size_increment)?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Grows the memory map from `full_map_size` by `map_size_increment` bytes, unless another
+    /// writer already grew it.
+    fn grow_map(&self, full_map_size: usize, map_size_increment: usize) -> Result<(), lmdb::Error> {
+        self.open_txns.when_all_closed(|| {
+            if self.map_size() != full_map_size {
+                return Ok(());
+            }
+            let new_map_size = full_map_size
+                .checked_add(map_size_increment)
+                .filter(|_| map_size_increment > 0)
+                .ok_or(lmdb::Error::MapFull)?;
+            self.env.set_map_size(new_map_size)?;
+            self.map_size.store(new_map_size, Ordering::SeqCst);
+            info!(
+                old_map_size = full_map_size,
+                new_map_size, "grew global state memory map"
+            );
+            Ok(())
+        })
+    }
+
+    /// Adopts the size of the memory map grown by the process writing a read-only environment, once
+    /// every transaction open in this process is closed.
+    fn adopt_resized_map(&self) -> Result<(), lmdb::Error> {
+        self.open_txns.when_all_closed(|| {
+            // A size of 0 makes LMDB take the current size of the database file.
+            self.env.set_map_size(0)?;
+            let old_map_size = self.map_size();
+            let new_map_size = self.env.info()?.map_size();
+            self.map_size.store(new_map_size, Ordering::SeqCst);
+            info!(
+                old_map_size,
+                new_map_size, "adopted global state memory map grown by the writer"
+            );
+            Ok(())
+        })
+    }
+
+    /// Returns whether open transactions are counted, as needed to resize the memory map.
+    fn tracks_open_txns(&self) -> bool {
+        self.map_size_increment.is_some() || self.read_only
+    }
+
+    /// Sets the duration after which a read transaction is considered to be held open for too
+    /// long, causing a warning to be logged once it is released.
+    ///
+    /// This also enables tracking open read transactions, so that ones which are never released
+    /// can be found via [`report_stale_read_txns`](Self::report_stale_read_txns).
+    pub fn with_max_read_txn_duration(mut self, max_read_txn_duration: Duration) -> Self {
+        self.max_read_txn_duration = Some(max_read_txn_duration);
+        self
+    }
+
+    /// Returns the number of read transactions which have been open for longer than the maximum
+    /// read transaction duration.
+    ///
+    /// A warning is logged for each of them the first time it is found, including in debug builds
+    /// the backtrace of where it was created.  Always returns 0 if no maximum duration was set.
+    pub fn report_stale_read_txns(&self) -> usize {
+        let max_duration = match self.max_read_txn_duration {
+            Some(max_duration) => max_duration,
+            None => return 0,
+        };
+        let mut txns = self
+            .open_read_txns
+            .txns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut stale_count = 0;
+        for (id, txn) in txns.iter_mut() {
+            let elapsed = txn.opened_at.elapsed();
+            if elapsed <= max_duration {
+                continue;
+            }
+            stale_count += 1;
+            if txn.reported {
+                continue;
+            }
+            txn.reported = true;
+            #[cfg(debug_assertions)]
+            {
+                let mut backtrace = txn.backtrace.clone();
+                backtrace.resolve();
+                warn!(
+                    id,
+                    ?elapsed,
+                    ?max_duration,
+                    ?backtrace,
+                    "global state read transaction is still open"
+                );
+            }
+            #[cfg(not(debug_assertions))]
+            warn!(
+                id,
+                ?elapsed,
+                ?max_duration,
+                "global state read transaction is still open"
+            );
+        }
+        stale_count
+    }
+
     /// Returns a reference to the wrapped `Environment`.
     pub fn env(&self) -> &Environment {
         &self.env
     }
 
+    /// Returns whether this environment was opened read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     /// Returns if this environment was constructed with manual synchronization enabled.
     pub fn is_manual_sync_enabled(&self) -> bool {
-        self.manual_sync_enabled
+        self.sync_mode == LmdbSyncMode::Manual
+    }
+
+    /// Returns how this environment makes its commits durable.
+    pub fn sync_mode(&self) -> LmdbSyncMode {
+        self.sync_mode
     }
 
     /// Manually synchronize LMDB to disk.
@@ -167,15 +1295,358 @@ impl<'a> TransactionSource<'a> for LmdbEnvironment {
 
     type Handle = Database;
 
-    type ReadTransaction = RoTransaction<'a>;
+    type ReadTransaction = LmdbReadTransaction<'a>;
+
+    type ReadWriteTransaction = LmdbReadWriteTransaction<'a>;
+
+    /// Creates a read transaction.
+    ///
+    /// If the environment is read-only and the writing process grew the memory map beyond this
+    /// one's, LMDB refuses new transactions with `MDB_MAP_RESIZED`.  The new size is then adopted
+    /// and the transaction created again, unless the calling thread holds another transaction,
+    /// which would keep the map from ever being resized.
+    fn create_read_txn(&'a self) -> Result<LmdbReadTransaction<'a>, Self::Error> {
+        let pool = self.read_txn_pool.as_ref();
+        let (txn, open_txn) = loop {
+            let open_txn = self.tracks_open_txns().then(|| self.open_txns.open());
+            let result = match pool {
+                Some(pool) => pool.acquire(&self.env),
+                None => self.env.begin_ro_txn(),
+            };
+            match result {
+                Err(lmdb::Error::MapResized) if self.read_only => {
+                    drop(open_txn);
+                    if self.open_txns.held_by_current_thread() {
+                        return Err(lmdb::Error::MapResized);
+                    }
+                    self.adopt_resized_map()?;
+                }
+                result => break (result?, open_txn),
+            }
+        };
+        let opened_at = Instant::now();
+        let tracked = self
+            .max_read_txn_duration
+            .map(|_| (self.open_read_txns.insert(opened_at), &self.open_read_txns));
+        Ok(LmdbReadTransaction {
+            txn: Some(txn),
+            pool,
+            guard: ReadTxnGuard {
+                opened_at,
+                max_duration: self.max_read_txn_duration,
+                tracked,
+            },
+            _open_txn: open_txn,
+        })
+    }
+
+    fn create_read_write_txn(&'a self) -> Result<LmdbReadWriteTransaction<'a>, Self::Error> {
+        if self.read_only {
+            return Err(lmdb::Error::Other(libc::EACCES));
+        }
+        let open_txn = self.map_size_increment.map(|_| self.open_txns.open());
+        Ok(LmdbReadWriteTransaction {
+            txn: self.env.begin_rw_txn()?,
+            _open_txn: open_txn,
+        })
+    }
+}
+
+impl Drop for LmdbEnvironment {
+    fn drop(&mut self) {
+        // Idle transactions must be closed while the environment is still open.
+        if let Some(pool) = &self.read_txn_pool {
+            pool.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lmdb::DatabaseFlags;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    const VALUE_COUNT: u8 = 64;
+
+    fn write_values(env: &LmdbEnvironment, db: Database) -> Result<(), lmdb::Error> {
+        let mut txn = env.create_read_write_txn()?;
+        let value = vec![0u8; 4 * *OS_PAGE_SIZE];
+        for key in 0..VALUE_COUNT {
+            txn.write(db, &[key], &value)?;
+        }
+        txn.commit()
+    }
+
+    #[test]
+    fn should_grow_full_map_and_retry_write() {
+        let tmp_dir = tempdir().unwrap();
+        let map_size = 16 * *OS_PAGE_SIZE;
+        let env = LmdbEnvironment::new(tmp_dir.path(), map_size, 8, true).unwrap();
+        let db = env.env().create_db(None, DatabaseFlags::empty()).unwrap();
+        assert!(env
+            .write_with_map_growth(|| write_values(&env, db))
+            .unwrap_err()
+            .is_map_full());
+
+        let env = env.with_map_size_increment(map_size);
+        let mut attempts = 0;
+        env.write_with_map_growth(|| {
+            attempts += 1;
+            write_values(&env, db)
+        })
+        .unwrap();
+        assert!(attempts > 1);
+        assert!(env.map_size() > map_size);
+
+        let txn = env.create_read_txn().unwrap();
+        assert!(txn.read(db, &[VALUE_COUNT - 1]).unwrap().is_some());
+    }
+
+    #[test]
+    fn pending_map_growth_should_hold_back_new_txns() {
+        let tmp_dir = tempdir().unwrap();
+        // Pooling opens the environment with `MDB_NOTLS`, letting a thread open several read
+        // transactions at once.
+        let tuning = LmdbTuning {
+            map_size_increment: Some(16 * *OS_PAGE_SIZE),
+            read_txn_pool_size: Some(8),
+            ..LmdbTuning::new(true)
+        };
+        let env =
+            LmdbEnvironment::new_tuned(tmp_dir.path(), 16 * *OS_PAGE_SIZE, 8, tuning).unwrap();
+        let resize_pending = || env.open_txns.state.lock().unwrap().resize_pending;
+
+        let txn = env.create_read_txn().unwrap();
+        std::thread::scope(|scope| {
+            let resizing = scope.spawn(|| env.grow_map(env.map_size(), 16 * *OS_PAGE_SIZE));
+            while !resize_pending() {
+                std::thread::yield_now();
+            }
+            // Other threads wait for the resize before opening a transaction, while a thread
+            // already holding one may open more.
+            let waiting = scope.spawn(|| env.create_read_write_txn().unwrap().commit());
+            let nested_txn = env.create_read_txn().unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!waiting.is_finished());
+            assert!(!resizing.is_finished());
+
+            drop(nested_txn);
+            drop(txn);
+            resizing.join().unwrap().unwrap();
+            waiting.join().unwrap().unwrap();
+        });
+        assert_eq!(env.map_size(), 32 * *OS_PAGE_SIZE);
+        assert!(env.open_txns.state.lock().unwrap().holders.is_empty());
+    }
+
+    #[test]
+    fn copy_in_progress_should_not_hold_back_new_txns() {
+        let tmp_dir = tempdir().unwrap();
+        let env = LmdbEnvironment::new(tmp_dir.path(), 16 * *OS_PAGE_SIZE, 8, true).unwrap();
+        let copies = || env.open_txns.state.lock().unwrap().copies;
+
+        let copy = env.open_txns.start_copy();
+        std::thread::scope(|scope| {
+            let resizing = scope.spawn(|| env.grow_map(env.map_size(), 16 * *OS_PAGE_SIZE));
+            // The resize waits for the copy without stopping other threads from opening
+            // transactions meanwhile.
+            scope
+                .spawn(|| env.create_read_write_txn().unwrap().commit())
+                .join()
+                .unwrap()
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!resizing.is_finished());
+
+            drop(copy);
+            resizing.join().unwrap().unwrap();
+        });
+        assert_eq!(env.map_size(), 32 * *OS_PAGE_SIZE);
+        assert_eq!(copies(), 0);
+    }
+
+    #[test]
+    fn should_copy_environment_without_overwriting_existing_database() {
+        let tmp_dir = tempdir().unwrap();
+        let env = LmdbEnvironment::new(tmp_dir.path(), 16 * *OS_PAGE_SIZE, 8, true).unwrap();
+        let db = env.env().create_db(None, DatabaseFlags::empty()).unwrap();
+        let mut txn = env.create_read_write_txn().unwrap();
+        txn.write(db, &[0], &[1]).unwrap();
+        txn.commit().unwrap();
 
-    type ReadWriteTransaction = RwTransaction<'a>;
+        let copy_dir = tempdir().unwrap();
+        env.copy_to(copy_dir.path(), true).unwrap();
+        let copy = LmdbEnvironment::new(copy_dir.path(), 16 * *OS_PAGE_SIZE, 8, true).unwrap();
+        let copied_db = copy.env().open_db(None).unwrap();
+        let txn = copy.create_read_txn().unwrap();
+        assert_eq!(
+            txn.read(copied_db, &[0]).unwrap(),
+            Some(Bytes::from(vec![1]))
+        );
+        txn.commit().unwrap();
+        drop(copy);
 
-    fn create_read_txn(&'a self) -> Result<RoTransaction<'a>, Self::Error> {
-        self.env.begin_ro_txn()
+        // A failed copy leaves a database which was already there in place.
+        let existing_len = fs::metadata(copy_dir.path().join(EE_DB_FILENAME))
+            .unwrap()
+            .len();
+        assert!(env.copy_to(copy_dir.path(), true).is_err());
+        assert_eq!(
+            fs::metadata(copy_dir.path().join(EE_DB_FILENAME))
+                .unwrap()
+                .len(),
+            existing_len
+        );
+        assert_eq!(env.open_txns.state.lock().unwrap().copies, 0);
     }
 
-    fn create_read_write_txn(&'a self) -> Result<RwTransaction<'a>, Self::Error> {
-        self.env.begin_rw_txn()
+    #[test]
+    fn should_validate_and_apply_tuning() {
+        let mut tuning = LmdbTuning {
+            sync_mode: LmdbSyncMode::NoSync,
+            readahead: true,
+            map_size_increment: Some(*OS_PAGE_SIZE + 1),
+            read_txn_pool_size: None,
+        };
+        assert_eq!(
+            tuning.validate(),
+            Err(LmdbTuningError::GrowthStepNotPageAligned {
+                growth_step: *OS_PAGE_SIZE + 1,
+                page_size: *OS_PAGE_SIZE,
+            })
+        );
+        tuning.map_size_increment = Some(4 * *OS_PAGE_SIZE);
+        assert_eq!(tuning.validate(), Ok(()));
+        assert!(tuning.flags().contains(EnvironmentFlags::NO_SYNC));
+        assert!(!tuning.flags().contains(EnvironmentFlags::NO_READAHEAD));
+        assert!(!tuning.flags().contains(EnvironmentFlags::NO_TLS));
+        assert!(LmdbTuning {
+            read_txn_pool_size: Some(2),
+            ..tuning
+        }
+        .flags()
+        .contains(EnvironmentFlags::NO_TLS));
+
+        let tmp_dir = tempdir().unwrap();
+        let env =
+            LmdbEnvironment::new_tuned(tmp_dir.path(), 16 * *OS_PAGE_SIZE, 8, tuning).unwrap();
+        assert_eq!(env.sync_mode(), LmdbSyncMode::NoSync);
+        assert!(!env.is_manual_sync_enabled());
+        assert_eq!(env.map_size_increment, Some(4 * *OS_PAGE_SIZE));
+    }
+
+    #[test]
+    fn should_report_stale_read_txns() {
+        let tmp_dir = tempdir().unwrap();
+        // Pooling opens the environment with `MDB_NOTLS`, letting a thread open several read
+        // transactions at once.
+        let tuning = LmdbTuning {
+            read_txn_pool_size: Some(8),
+            ..LmdbTuning::new(true)
+        };
+        let env = LmdbEnvironment::new_tuned(tmp_dir.path(), 16 * *OS_PAGE_SIZE, 8, tuning)
+            .unwrap()
+            .with_max_read_txn_duration(Duration::from_millis(10));
+
+        let stale_txn = env.create_read_txn().unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        let fresh_txn = env.create_read_txn().unwrap();
+        assert_eq!(env.report_stale_read_txns(), 1);
+        // Already reported transactions are still counted.
+        assert_eq!(env.report_stale_read_txns(), 1);
+
+        drop(stale_txn);
+        assert_eq!(env.report_stale_read_txns(), 0);
+        drop(fresh_txn);
+        assert!(env.open_read_txns.txns.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_reuse_pooled_read_txns_and_wait_for_one_to_be_released() {
+        let tmp_dir = tempdir().unwrap();
+        let tuning = LmdbTuning {
+            read_txn_pool_size: Some(2),
+            ..LmdbTuning::new(true)
+        };
+        let env =
+            LmdbEnvironment::new_tuned(tmp_dir.path(), 16 * *OS_PAGE_SIZE, 8, tuning).unwrap();
+        let db = env.env().create_db(None, DatabaseFlags::empty()).unwrap();
+        let write_value = |value: u8| {
+            let mut txn = env.create_read_write_txn().unwrap();
+            txn.write(db, &[0], &[value]).unwrap();
+            txn.commit().unwrap();
+        };
+        let idle_count = || {
+            env.read_txn_pool
+                .as_ref()
+                .unwrap()
+                .state
+                .lock()
+                .unwrap()
+                .idle
+                .len()
+        };
+
+        write_value(1);
+        let txn = env.create_read_txn().unwrap();
+        assert_eq!(txn.read(db, &[0]).unwrap(), Some(Bytes::from(vec![1])));
+        txn.commit().unwrap();
+        assert_eq!(idle_count(), 1);
+
+        // A renewed transaction reads from a new snapshot.
+        write_value(2);
+        let first_txn = env.create_read_txn().unwrap();
+        assert_eq!(idle_count(), 0);
+        assert_eq!(
+            first_txn.read(db, &[0]).unwrap(),
+            Some(Bytes::from(vec![2]))
+        );
+        let second_txn = env.create_read_txn().unwrap();
+
+        std::thread::scope(|scope| {
+            let waiting = scope.spawn(|| env.create_read_txn().unwrap().read(db, &[0]).unwrap());
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!waiting.is_finished());
+            drop(second_txn);
+            assert_eq!(waiting.join().unwrap(), Some(Bytes::from(vec![2])));
+        });
+        drop(first_txn);
+        assert_eq!(idle_count(), 2);
+    }
+
+    #[test]
+    fn should_not_make_nested_pooled_read_txns_wait() {
+        let tmp_dir = tempdir().unwrap();
+        let tuning = LmdbTuning {
+            read_txn_pool_size: Some(1),
+            ..LmdbTuning::new(true)
+        };
+        let env =
+            LmdbEnvironment::new_tuned(tmp_dir.path(), 16 * *OS_PAGE_SIZE, 8, tuning).unwrap();
+        let pool = env.read_txn_pool.as_ref().unwrap();
+
+        let outer_txn = env.create_read_txn().unwrap();
+        let started_at = Instant::now();
+        let nested_txn = env.create_read_txn().unwrap();
+        assert!(started_at.elapsed() < READ_TXN_POOL_MAX_WAIT);
+        assert_eq!(pool.state.lock().unwrap().in_use, 2);
+
+        // Other threads still wait for the transactions to be released.
+        std::thread::scope(|scope| {
+            let waiting = scope.spawn(|| env.create_read_txn().map(drop));
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!waiting.is_finished());
+            drop(nested_txn);
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!waiting.is_finished());
+            drop(outer_txn);
+            waiting.join().unwrap().unwrap();
+        });
+        let state = pool.state.lock().unwrap();
+        assert_eq!(state.in_use, 0);
+        assert!(state.holders.is_empty());
+        assert_eq!(state.idle.len(), 1);
     }
 }
@@ -1,5 +1,8 @@
 use casper_types::bytesrepr::Bytes;
 
+/// Failure and latency injection for the in-memory transaction source.
+#[cfg(any(test, feature = "test-support"))]
+pub mod fault_injection;
 /// In-memory implementation of transaction source.
 pub mod in_memory;
 /// LMDB implementation of transaction source.
@@ -29,12 +32,38 @@ pub trait Transaction: Sized {
 pub trait Readable: Transaction {
     /// Returns the value from the corresponding key from a given [`Transaction::Handle`].
     fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Bytes>, Self::Error>;
+
+    /// Returns `true` if the given [`Transaction::Handle`] holds a value at the key.
+    fn exists(&self, handle: Self::Handle, key: &[u8]) -> Result<bool, Self::Error> {
+        Ok(self.read(handle, key)?.is_some())
+    }
+
+    /// Calls `f` with the value from the corresponding key from a given
+    /// [`Transaction::Handle`], if any.
+    ///
+    /// Implementations backed by memory-mapped storage pass the value in place rather than
+    /// copying it out first, which spares a copy of large values which are only deserialized.
+    fn read_with<F, R>(
+        &self,
+        handle: Self::Handle,
+        key: &[u8],
+        f: F,
+    ) -> Result<Option<R>, Self::Error>
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        Ok(self.read(handle, key)?.map(|bytes| f(&bytes)))
+    }
 }
 
 /// A transaction with the capability to write to a given [`Handle`](Transaction::Handle).
 pub trait Writable: Transaction {
     /// Inserts a key-value pair into a given [`Transaction::Handle`].
     fn write(&mut self, handle: Self::Handle, key: &[u8], value: &[u8]) -> Result<(), Self::Error>;
+
+    /// Removes a key-value pair from a given [`Transaction::Handle`], returning whether there was
+    /// one.
+    fn delete(&mut self, handle: Self::Handle, key: &[u8]) -> Result<bool, Self::Error>;
 }
 
 /// A source of transactions e.g. values that implement [`Readable`]
@@ -55,6 +84,11 @@ pub trait TransactionSource<'a> {
         + Writable<Error = Self::Error, Handle = Self::Handle>;
 
     /// Creates a read transaction.
+    ///
+    /// A read transaction observes a consistent snapshot of the data as of the moment it was
+    /// created: writes committed by other transactions while it is open are not visible through
+    /// it, no matter how long it lives.  Implementations may need to retain superseded data for as
+    /// long as such a snapshot is alive, so read transactions should be kept short.
     fn create_read_txn(&'a self) -> Result<Self::ReadTransaction, Self::Error>;
 
     /// Creates a read-write transaction.
@@ -23,6 +23,7 @@ pub mod gens;
 
 /// Merkle proofs.
 pub mod merkle_proof;
+pub mod path_proof;
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,169 @@
+//! Proofs consisting of the full chain of trie nodes leading to a leaf.
+//!
+//! Unlike a [`TrieMerkleProof`](super::merkle_proof::TrieMerkleProof), which only keeps the sibling
+//! pointers needed to recompute the state root hash, a [`TriePathProof`] contains every trie node
+//! read on the way from the state root to the leaf, exactly as stored.  This makes it simple for a
+//! light client to check: each node must hash to the pointer followed in its parent, starting with
+//! the state root hash itself.
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_hashing::Digest;
+use casper_types::bytesrepr::{self, FromBytes, ToBytes};
+
+use crate::storage::trie::Trie;
+
+/// Error returned when a [`TriePathProof`] does not prove its value.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TriePathProofError {
+    /// Serializing the key or a trie node failed.
+    #[error("serialization error: {0}")]
+    Serialization(bytesrepr::Error),
+    /// The trie node at the given index doesn't hash to the pointer followed in its parent, or to
+    /// the state root hash if it is the first node.  The leaf has the index `nodes().len()`.
+    #[error("trie node at index {index} has an unexpected hash")]
+    HashMismatch {
+        /// The index of the offending node.
+        index: usize,
+    },
+    /// The trie node at the given index doesn't lead on towards the key.
+    #[error("trie node at index {index} doesn't lead to the key")]
+    PathMismatch {
+        /// The index of the offending node.
+        index: usize,
+    },
+    /// A leaf is among the nodes leading to the key.
+    #[error("unexpected leaf at index {index}")]
+    UnexpectedLeaf {
+        /// The index of the offending node.
+        index: usize,
+    },
+}
+
+impl From<bytesrepr::Error> for TriePathProofError {
+    fn from(error: bytesrepr::Error) -> Self {
+        TriePathProofError::Serialization(error)
+    }
+}
+
+/// A key and value read from the trie, together with the nodes on the path from the state root to
+/// its leaf.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TriePathProof<K, V> {
+    key: K,
+    value: V,
+    /// The nodes and extensions leading to the leaf, starting with the state root.
+    nodes: Vec<Trie<K, V>>,
+}
+
+impl<K, V> TriePathProof<K, V> {
+    /// Constructs a [`TriePathProof`] from a leaf's key and value and the nodes leading to it.
+    pub fn new(key: K, value: V, nodes: Vec<Trie<K, V>>) -> Self {
+        TriePathProof { key, value, nodes }
+    }
+
+    /// Returns the key that was read.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the value that was read.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Returns the nodes leading to the leaf, starting with the state root.
+    pub fn nodes(&self) -> &[Trie<K, V>] {
+        &self.nodes
+    }
+
+    /// Returns the value that was read.
+    pub fn into_value(self) -> V {
+        self.value
+    }
+}
+
+impl<K, V> TriePathProof<K, V>
+where
+    K: ToBytes + Clone,
+    V: ToBytes + Clone,
+{
+    /// Checks that the proof shows the key to have the value in the trie with root `state_root`.
+    pub fn verify(&self, state_root: &Digest) -> Result<(), TriePathProofError> {
+        let path = self.key.to_bytes()?;
+        let mut expected_hash = *state_root;
+        let mut depth = 0;
+        for (index, node) in self.nodes.iter().enumerate() {
+            if node.trie_hash()? != expected_hash {
+                return Err(TriePathProofError::HashMismatch { index });
+            }
+            let pointer = match node {
+                Trie::Node { pointer_block } => {
+                    let maybe_pointer = path
+                        .get(depth)
+                        .and_then(|path_byte| pointer_block[usize::from(*path_byte)]);
+                    depth += 1;
+                    maybe_pointer
+                }
+                Trie::Extension { affix, pointer } => {
+                    let matches = path
+                        .get(depth..)
+                        .map_or(false, |rest| rest.starts_with(affix.as_slice()));
+                    depth += affix.len();
+                    if matches {
+                        Some(*pointer)
+                    } else {
+                        None
+                    }
+                }
+                Trie::Leaf { .. } => return Err(TriePathProofError::UnexpectedLeaf { index }),
+            };
+            expected_hash = match pointer {
+                Some(pointer) => pointer.into_hash(),
+                None => return Err(TriePathProofError::PathMismatch { index }),
+            };
+        }
+        let leaf = Trie::<K, V>::leaf(self.key.clone(), self.value.clone());
+        if leaf.trie_hash()? != expected_hash {
+            return Err(TriePathProofError::HashMismatch {
+                index: self.nodes.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<K, V> ToBytes for TriePathProof<K, V>
+where
+    K: ToBytes,
+    V: ToBytes,
+{
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut ret: Vec<u8> = bytesrepr::allocate_buffer(self)?;
+        ret.append(&mut self.key.to_bytes()?);
+        ret.append(&mut self.value.to_bytes()?);
+        ret.append(&mut self.nodes.to_bytes()?);
+        Ok(ret)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.key.serialized_length()
+            + self.value.serialized_length()
+            + self.nodes.serialized_length()
+    }
+}
+
+impl<K, V> FromBytes for TriePathProof<K, V>
+where
+    K: FromBytes,
+    V: FromBytes,
+{
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (key, rem): (K, &[u8]) = FromBytes::from_bytes(bytes)?;
+        let (value, rem): (V, &[u8]) = FromBytes::from_bytes(rem)?;
+        let (nodes, rem): (Vec<Trie<K, V>>, &[u8]) = FromBytes::from_bytes(rem)?;
+        Ok((TriePathProof { key, value, nodes }, rem))
+    }
+}
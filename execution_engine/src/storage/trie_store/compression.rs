@@ -0,0 +1,199 @@
+//! Optional zstd compression of the tries held in an LMDB trie store.
+//!
+//! Leaves holding contract Wasm dominate the size of global state and compress 3-4x.  With
+//! compression enabled, serialized tries of at least [`MIN_COMPRESSED_LEN`] bytes are stored as
+//! zstd frames whenever that makes them smaller.  A serialized trie starts with its tag and never
+//! with the zstd magic number, so compressed and uncompressed tries can be told apart and live
+//! side by side in one store.
+//!
+//! Whether a store may hold compressed tries is recorded in a metadata database when compression
+//! is first enabled.  Stores without the record, written before compression existed, are read as
+//! they are, while a store with the record keeps being decompressed even if compression is
+//! disabled again later.
+use std::{borrow::Cow, io};
+
+use lmdb::{DatabaseFlags, Transaction, WriteFlags};
+
+use casper_types::bytesrepr::Bytes;
+
+use crate::storage::{
+    error,
+    transaction_source::{self, lmdb::LmdbEnvironment, TransactionSource},
+};
+
+/// The default zstd compression level.
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 3;
+
+/// The name of the LMDB database holding the metadata of trie stores, keyed by store name.
+pub(super) const METADATA_DB_NAME: &str = "TRIE_STORE_METADATA";
+/// The first bytes of every zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+/// Serialized tries shorter than this are never compressed, as they are mostly nodes and
+/// extensions made of hashes which don't compress.
+const MIN_COMPRESSED_LEN: usize = 256;
+/// The metadata record of a store which may hold zstd-compressed tries.
+const FORMAT_ZSTD: u8 = 1;
+
+/// Encodes tries before writing them to, and decodes them after reading them from, a trie store.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TrieCodec {
+    /// The zstd level tries are compressed with when written, if compression is enabled.
+    compression_level: Option<i32>,
+    /// Whether the store may hold compressed tries.
+    may_hold_compressed: bool,
+}
+
+impl TrieCodec {
+    /// Returns the codec of the store `store_name` as recorded in its metadata.
+    pub(crate) fn read(env: &LmdbEnvironment, store_name: &str) -> Result<Self, error::Error> {
+        let db = match env.env().open_db(Some(METADATA_DB_NAME)) {
+            Ok(db) => db,
+            Err(lmdb::Error::NotFound) => return Ok(TrieCodec::default()),
+            Err(error) => return Err(error.into()),
+        };
+        let txn = env.create_read_txn()?;
+        let may_hold_compressed = match txn.lmdb_txn().get(db, &store_name) {
+            Ok(format) => format == [FORMAT_ZSTD],
+            Err(lmdb::Error::NotFound) => false,
+            Err(error) => return Err(error.into()),
+        };
+        transaction_source::Transaction::commit(txn)?;
+        Ok(TrieCodec {
+            compression_level: None,
+            may_hold_compressed,
+        })
+    }
+
+    /// Enables compressing tries at `level` for the store `store_name`, recording in its metadata
+    /// that it may hold compressed tries from now on.
+    pub(crate) fn enable_compression(
+        env: &LmdbEnvironment,
+        store_name: &str,
+        level: i32,
+    ) -> Result<Self, error::Error> {
+        let db = env
+            .env()
+            .create_db(Some(METADATA_DB_NAME), DatabaseFlags::empty())?;
+        env.write_with_map_growth(|| -> Result<(), error::Error> {
+            let mut txn = env.create_read_write_txn()?;
+            txn.put(db, &store_name, &[FORMAT_ZSTD], WriteFlags::empty())?;
+            txn.commit()?;
+            Ok(())
+        })?;
+        Ok(TrieCodec {
+            compression_level: Some(level),
+            may_hold_compressed: true,
+        })
+    }
+
+    /// Returns whether `stored_bytes` are a compressed trie.
+    pub(crate) fn is_compressed(&self, stored_bytes: &[u8]) -> bool {
+        self.may_hold_compressed && stored_bytes.starts_with(&ZSTD_MAGIC)
+    }
+
+    /// Returns the bytes to store for the serialized trie `trie_bytes`.
+    pub(crate) fn encode<'a>(&self, trie_bytes: &'a [u8]) -> Result<Cow<'a, [u8]>, error::Error> {
+        let level = match self.compression_level {
+            Some(level) if trie_bytes.len() >= MIN_COMPRESSED_LEN => level,
+            _ => return Ok(Cow::Borrowed(trie_bytes)),
+        };
+        let compressed = zstd::bulk::compress(trie_bytes, level)
+            .map_err(|error| error::Error::Compression(error.to_string()))?;
+        if compressed.len() < trie_bytes.len() {
+            Ok(Cow::Owned(compressed))
+        } else {
+            Ok(Cow::Borrowed(trie_bytes))
+        }
+    }
+
+    /// Returns the serialized trie stored as `stored_bytes`.
+    pub(crate) fn decode_slice<'a>(
+        &self,
+        stored_bytes: &'a [u8],
+    ) -> Result<Cow<'a, [u8]>, error::Error> {
+        if !self.is_compressed(stored_bytes) {
+            return Ok(Cow::Borrowed(stored_bytes));
+        }
+        zstd::stream::decode_all(stored_bytes)
+            .map(Cow::Owned)
+            .map_err(|error| error::Error::Compression(error.to_string()))
+    }
+
+    /// Writes the serialized trie stored as `stored_bytes` to `writer`, decompressing it a chunk
+    /// at a time if needed rather than into a buffer holding the whole trie.
+    pub(crate) fn decode_into<W: io::Write>(
+        &self,
+        stored_bytes: &[u8],
+        writer: &mut W,
+    ) -> Result<(), error::Error> {
+        if !self.is_compressed(stored_bytes) {
+            writer.write_all(stored_bytes)?;
+            return Ok(());
+        }
+        zstd::stream::copy_decode(stored_bytes, writer)
+            .map_err(|error| error::Error::Compression(error.to_string()))
+    }
+
+    /// Returns the serialized trie stored as `stored_bytes`, avoiding a copy if it isn't
+    /// compressed.
+    pub(crate) fn decode(&self, stored_bytes: Bytes) -> Result<Bytes, error::Error> {
+        if !self.is_compressed(&stored_bytes) {
+            return Ok(stored_bytes);
+        }
+        Ok(Bytes::from(self.decode_slice(&stored_bytes)?.into_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::storage::{DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS};
+
+    #[test]
+    fn should_only_compress_large_compressible_tries_once_enabled() {
+        let dir = tempdir().unwrap();
+        let env = LmdbEnvironment::new(
+            dir.path(),
+            DEFAULT_TEST_MAX_DB_SIZE,
+            DEFAULT_TEST_MAX_READERS,
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            TrieCodec::read(&env, "TRIE_STORE").unwrap(),
+            TrieCodec::default()
+        );
+
+        let large = vec![7u8; 4 * MIN_COMPRESSED_LEN];
+        let small = vec![7u8; MIN_COMPRESSED_LEN - 1];
+        assert!(matches!(
+            TrieCodec::default().encode(&large).unwrap(),
+            Cow::Borrowed(_)
+        ));
+
+        let codec = TrieCodec::enable_compression(&env, "TRIE_STORE", 3).unwrap();
+        let encoded = codec.encode(&large).unwrap().into_owned();
+        assert!(encoded.len() < large.len());
+        assert!(codec.is_compressed(&encoded));
+        assert_eq!(
+            codec.decode(Bytes::from(encoded)).unwrap(),
+            Bytes::from(large)
+        );
+        assert!(matches!(codec.encode(&small).unwrap(), Cow::Borrowed(_)));
+
+        // Once recorded, the store is decompressed even when compression isn't enabled again.
+        let codec = TrieCodec::read(&env, "TRIE_STORE").unwrap();
+        assert!(codec.may_hold_compressed);
+        assert!(matches!(
+            codec.encode(&[7u8; 1024]).unwrap(),
+            Cow::Borrowed(_)
+        ));
+        assert!(
+            !TrieCodec::read(&env, "TRIE_STORE-other")
+                .unwrap()
+                .may_hold_compressed
+        );
+    }
+}
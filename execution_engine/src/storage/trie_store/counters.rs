@@ -0,0 +1,76 @@
+//! Counters of the operations performed on a trie store.
+//!
+//! An [`LmdbTrieStore`](super::lmdb::LmdbTrieStore) counts the tries it reads and writes, along
+//! with the bytes moved to and from LMDB, and the lookups and inserts of a
+//! [`ScratchTrieStore`](super::lmdb::ScratchTrieStore) built on top of it.  The counts are taken
+//! periodically by the node and exported as metrics.
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counts of the operations performed on a trie store since they were last taken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrieStoreCounts {
+    /// The number of tries looked up in LMDB.
+    pub gets: u64,
+    /// The number of bytes read from LMDB, as stored, i.e. possibly compressed.
+    pub bytes_read: u64,
+    /// The number of tries written to LMDB.
+    pub puts: u64,
+    /// The number of bytes written to LMDB, as stored, i.e. possibly compressed.
+    pub bytes_written: u64,
+    /// The number of tries looked up through a scratch trie store, whether served from its cache
+    /// or from LMDB.
+    pub scratch_gets: u64,
+    /// The number of new tries inserted into a scratch trie store.
+    pub scratch_puts: u64,
+}
+
+/// Thread-safe counters of the operations performed on a trie store.
+#[derive(Debug, Default)]
+pub struct TrieStoreCounters {
+    gets: AtomicU64,
+    bytes_read: AtomicU64,
+    puts: AtomicU64,
+    bytes_written: AtomicU64,
+    scratch_gets: AtomicU64,
+    scratch_puts: AtomicU64,
+}
+
+impl TrieStoreCounters {
+    /// Records a lookup in LMDB which found `stored_len` bytes, if any.
+    pub(crate) fn record_get(&self, stored_len: Option<usize>) {
+        self.gets.fetch_add(1, Ordering::Relaxed);
+        if let Some(stored_len) = stored_len {
+            self.bytes_read
+                .fetch_add(stored_len as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Records a write of `stored_len` bytes to LMDB.
+    pub(crate) fn record_put(&self, stored_len: usize) {
+        self.puts.fetch_add(1, Ordering::Relaxed);
+        self.bytes_written
+            .fetch_add(stored_len as u64, Ordering::Relaxed);
+    }
+
+    /// Records a lookup through a scratch trie store.
+    pub(crate) fn record_scratch_get(&self) {
+        self.scratch_gets.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records an insert into a scratch trie store.
+    pub(crate) fn record_scratch_put(&self) {
+        self.scratch_puts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the counts since the last call, resetting them to zero.
+    pub fn take(&self) -> TrieStoreCounts {
+        TrieStoreCounts {
+            gets: self.gets.swap(0, Ordering::Relaxed),
+            bytes_read: self.bytes_read.swap(0, Ordering::Relaxed),
+            puts: self.puts.swap(0, Ordering::Relaxed),
+            bytes_written: self.bytes_written.swap(0, Ordering::Relaxed),
+            scratch_gets: self.scratch_gets.swap(0, Ordering::Relaxed),
+            scratch_puts: self.scratch_puts.swap(0, Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Accounting of the tries shared between state roots.
+//!
+//! A [`TrieDedupReport`] splits the storage below a set of state roots into the tries reachable
+//! from a single root, which pruning that root would free, and those shared by several roots.
+//! Reports are produced by `LmdbGlobalState::trie_dedup_report` to guide the choice of which state
+//! roots to retain.
+//!
+//! As leaves hold their full keys, a node or extension can only appear at a single path in any
+//! trie: the longest common prefix of the keys below it.  A leaf can appear at different depths,
+//! but is walked as if at the path of its full key.  The walk therefore visits the tries below all
+//! state roots together in order of their path, meeting every copy of a shared trie at once.  Only
+//! the frontier of the walk is held in memory, and it can be advanced a batch of tries at a time.
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fmt::{self, Display, Formatter},
+};
+
+use serde::Serialize;
+
+use casper_hashing::Digest;
+use casper_types::bytesrepr::{self, FromBytes, ToBytes};
+
+use crate::storage::{
+    store::Store, transaction_source::Readable, trie::Trie, trie_store::TrieStore,
+};
+
+/// The tries reachable from a single state root.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct StateRootFootprint {
+    /// The state root.
+    pub state_root: Digest,
+    /// The number of tries reachable from the state root, including itself.
+    pub tries: u64,
+    /// The serialized size of those tries, in bytes.
+    pub bytes: u64,
+    /// The number of those tries not reachable from any other of the state roots.
+    pub unique_tries: u64,
+    /// The serialized size of the unique tries, in bytes.
+    pub unique_bytes: u64,
+}
+
+/// How the tries below a set of state roots are shared between them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TrieDedupReport {
+    /// The tries reachable from each state root, in the order the roots were given.
+    pub state_roots: Vec<StateRootFootprint>,
+    /// The number of distinct tries reachable from any of the state roots.
+    pub total_tries: u64,
+    /// The serialized size of the distinct tries, in bytes.
+    pub total_bytes: u64,
+    /// The number of distinct tries reachable from more than one of the state roots.
+    pub shared_tries: u64,
+    /// The serialized size of the shared tries, in bytes.
+    pub shared_bytes: u64,
+    /// The number of tries referenced but missing from the store.
+    pub missing_tries: u64,
+}
+
+impl TrieDedupReport {
+    fn record(&mut self, root_indices: &[usize], size: u64) {
+        let unique = root_indices.len() == 1;
+        for index in root_indices {
+            let footprint = &mut self.state_roots[*index];
+            footprint.tries += 1;
+            footprint.bytes += size;
+            if unique {
+                footprint.unique_tries += 1;
+                footprint.unique_bytes += size;
+            }
+        }
+        self.total_tries += 1;
+        self.total_bytes += size;
+        if !unique {
+            self.shared_tries += 1;
+            self.shared_bytes += size;
+        }
+    }
+}
+
+impl Display for TrieDedupReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} tries, {} bytes below {} state roots: {} tries, {} bytes shared ({} tries missing)",
+            self.total_tries,
+            self.total_bytes,
+            self.state_roots.len(),
+            self.shared_tries,
+            self.shared_bytes,
+            self.missing_tries
+        )?;
+        for footprint in &self.state_roots {
+            writeln!(
+                f,
+                "  {}: {} tries, {} bytes, of which {} tries, {} bytes unique",
+                footprint.state_root,
+                footprint.tries,
+                footprint.bytes,
+                footprint.unique_tries,
+                footprint.unique_bytes
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A trie yet to be visited: its path, its key, the indices of the state roots reaching it and,
+/// for a leaf already read once and moved to the path of its full key, its size.
+type PendingTrie = Reverse<(Vec<u8>, Digest, Vec<usize>, Option<u64>)>;
+
+/// An incremental walk of the tries below a set of state roots, building a [`TrieDedupReport`].
+pub(crate) struct TrieDedupWalk {
+    to_visit: BinaryHeap<PendingTrie>,
+    report: TrieDedupReport,
+}
+
+impl TrieDedupWalk {
+    pub(crate) fn new(state_roots: &[Digest]) -> Self {
+        let to_visit = state_roots
+            .iter()
+            .enumerate()
+            .map(|(index, state_root)| Reverse((Vec::new(), *state_root, vec![index], None)))
+            .collect();
+        let report = TrieDedupReport {
+            state_roots: state_roots
+                .iter()
+                .map(|state_root| StateRootFootprint {
+                    state_root: *state_root,
+                    ..StateRootFootprint::default()
+                })
+                .collect(),
+            ..TrieDedupReport::default()
+        };
+        TrieDedupWalk { to_visit, report }
+    }
+
+    /// Returns `true` once every trie below the state roots has been visited.
+    pub(crate) fn is_done(&self) -> bool {
+        self.to_visit.is_empty()
+    }
+
+    /// Advances the walk, reading at most `max_tries` tries from the store.
+    pub(crate) fn step<K, V, T, S, E>(
+        &mut self,
+        txn: &T,
+        store: &S,
+        max_tries: usize,
+    ) -> Result<(), E>
+    where
+        K: ToBytes + FromBytes + Eq + std::fmt::Debug,
+        V: ToBytes + FromBytes + std::fmt::Debug,
+        T: Readable<Handle = S::Handle>,
+        S: TrieStore<K, V>,
+        S::Error: From<T::Error>,
+        E: From<S::Error> + From<bytesrepr::Error>,
+    {
+        for _ in 0..max_tries {
+            let Reverse((path, trie_key, mut root_indices, mut maybe_leaf_size)) =
+                match self.to_visit.pop() {
+                    Some(pending) => pending,
+                    None => break,
+                };
+            // Every other copy of the trie has the same path, so is next in the heap.
+            while let Some(Reverse((next_path, next_key, _, _))) = self.to_visit.peek() {
+                if *next_key != trie_key || *next_path != path {
+                    break;
+                }
+                if let Some(Reverse((_, _, more_indices, more_leaf_size))) = self.to_visit.pop() {
+                    root_indices.extend(more_indices);
+                    maybe_leaf_size = maybe_leaf_size.or(more_leaf_size);
+                }
+            }
+            root_indices.sort_unstable();
+            root_indices.dedup();
+
+            // A leaf moved to the path of its full key was read already.
+            if let Some(leaf_size) = maybe_leaf_size {
+                self.report.record(&root_indices, leaf_size);
+                continue;
+            }
+
+            let trie_raw = match store.get_raw(txn, &trie_key)? {
+                Some(trie_raw) => trie_raw,
+                None => {
+                    self.report.missing_tries += 1;
+                    continue;
+                }
+            };
+
+            // Leaves are walked at the path of their full key, sparing the parsing of their values.
+            if let Some(&Trie::<K, V>::LEAF_TAG) = trie_raw.first() {
+                let leaf_bytes: &[u8] = &trie_raw;
+                let leaf_size = leaf_bytes.len() as u64;
+                let (_, remainder) = K::from_bytes(&leaf_bytes[1..])?;
+                let key_bytes = &leaf_bytes[1..leaf_bytes.len() - remainder.len()];
+                if path == key_bytes {
+                    self.report.record(&root_indices, leaf_size);
+                } else {
+                    self.to_visit.push(Reverse((
+                        key_bytes.to_vec(),
+                        trie_key,
+                        root_indices,
+                        Some(leaf_size),
+                    )));
+                }
+                continue;
+            }
+            self.report.record(&root_indices, trie_raw.len() as u64);
+
+            match bytesrepr::deserialize_from_slice(trie_raw)? {
+                Trie::<K, V>::Leaf { .. } => {}
+                Trie::Node { pointer_block } => {
+                    for (index, pointer) in pointer_block.as_indexed_pointers() {
+                        let mut child_path = path.clone();
+                        child_path.push(index);
+                        self.to_visit.push(Reverse((
+                            child_path,
+                            *pointer.hash(),
+                            root_indices.clone(),
+                            None,
+                        )));
+                    }
+                }
+                Trie::Extension { affix, pointer } => {
+                    let mut child_path = path;
+                    child_path.extend_from_slice(&affix);
+                    self.to_visit.push(Reverse((
+                        child_path,
+                        pointer.into_hash(),
+                        root_indices,
+                        None,
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn into_report(self) -> TrieDedupReport {
+        self.report
+    }
+}
@@ -0,0 +1,94 @@
+//! Results of verifying that the trie below a state root is complete and uncorrupted.
+//!
+//! Reports are produced by `LmdbGlobalState::verify_trie_integrity`, for operators assessing the
+//! extent of corruption of a node's global state.
+use std::fmt::{self, Display, Formatter};
+
+use serde::Serialize;
+
+use casper_hashing::Digest;
+
+/// What is wrong with a trie found while verifying the integrity of a state root.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum TrieIntegrityProblemKind {
+    /// The trie is referenced but not present in the store.
+    Missing,
+    /// The trie's bytes don't hash to the key it is stored under.
+    HashMismatch {
+        /// The hash of the stored bytes.
+        actual: Digest,
+    },
+    /// The trie's bytes hash correctly but can't be parsed.
+    Unparsable,
+}
+
+/// A missing or corrupted trie found while verifying the integrity of a state root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TrieIntegrityProblem {
+    /// The key of the trie, as referenced by its parent.
+    pub trie_key: Digest,
+    /// The path from the state root to the trie, i.e. the prefix of the keys stored below it.
+    pub path: Vec<u8>,
+    /// What is wrong with the trie.
+    pub kind: TrieIntegrityProblemKind,
+}
+
+/// The outcome of verifying the integrity of the trie below a state root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TrieIntegrityReport {
+    /// The verified state root.
+    pub state_root: Digest,
+    /// The number of tries read and checked.
+    pub tries_checked: u64,
+    /// The problems found, in depth-first order.
+    pub problems: Vec<TrieIntegrityProblem>,
+    /// Whether the walk stopped early because the limit of problems was reached.
+    pub truncated: bool,
+}
+
+impl TrieIntegrityReport {
+    /// Returns `true` if every trie below the state root was checked and is present and intact.
+    pub fn is_intact(&self) -> bool {
+        self.problems.is_empty() && !self.truncated
+    }
+}
+
+impl Display for TrieIntegrityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_intact() {
+            return writeln!(
+                f,
+                "state root {} is intact ({} tries checked)",
+                self.state_root, self.tries_checked
+            );
+        }
+        writeln!(
+            f,
+            "{} problems below state root {} ({} tries checked{})",
+            self.problems.len(),
+            self.state_root,
+            self.tries_checked,
+            if self.truncated {
+                ", stopped early"
+            } else {
+                ""
+            }
+        )?;
+        for problem in &self.problems {
+            write!(
+                f,
+                "{} at path {}: ",
+                problem.trie_key,
+                base16::encode_lower(&problem.path)
+            )?;
+            match problem.kind {
+                TrieIntegrityProblemKind::Missing => writeln!(f, "missing")?,
+                TrieIntegrityProblemKind::HashMismatch { actual } => {
+                    writeln!(f, "corrupted, hashes to {}", actual)?
+                }
+                TrieIntegrityProblemKind::Unparsable => writeln!(f, "corrupted, unparsable")?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -103,13 +103,14 @@
 //!
 //! tmp_dir.close().unwrap();
 //! ```
-use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
-};
+use std::{borrow::Cow, io, iter, sync::Arc};
 
-use casper_types::{bytesrepr, Key, StoredValue};
-use lmdb::{Database, DatabaseFlags, Transaction};
+use casper_types::{
+    bytesrepr::{self, Bytes, FromBytes, ToBytes},
+    Key, StoredValue,
+};
+use lmdb::{Cursor, Database, DatabaseFlags, RwTransaction, Transaction, WriteFlags};
+use tracing::info;
 
 use casper_hashing::Digest;
 
@@ -119,15 +120,36 @@ use crate::storage::{
     store::Store,
     transaction_source::{lmdb::LmdbEnvironment, Readable, TransactionSource, Writable},
     trie::Trie,
-    trie_store::{self, TrieStore},
+    trie_store::{
+        self,
+        compression::TrieCodec,
+        counters::{TrieStoreCounters, TrieStoreCounts},
+        quarantine::TrieQuarantine,
+        scratch_cache::{ScratchCache, ScratchCacheStats},
+        shards::{TrieShard, TrieShards, SHARD_COUNT},
+        stats::{TrieDepthStats, TrieStoreStatWalk, TrieStoreStats},
+        TrieLocation, TrieStore,
+    },
 };
 
+/// The most tries looked at per read-write transaction when moving leaves into or out of shard
+/// databases.
+const SHARD_MIGRATION_BATCH_LEN: usize = 16 * 1024;
+
 /// An LMDB-backed trie store.
 ///
-/// Wraps [`lmdb::Database`].
+/// Wraps [`lmdb::Database`].  Tries may be stored compressed, see
+/// [`compression`](super::compression), and leaves may be spread over several databases, see
+/// [`shards`](super::shards).  Tries which fail to deserialize are quarantined, see
+/// [`quarantine`](super::quarantine).  Clones share the same [`TrieStoreCounters`] and quarantine.
 #[derive(Debug, Clone)]
 pub struct LmdbTrieStore {
     db: Database,
+    shards: Option<TrieShards>,
+    name: String,
+    codec: TrieCodec,
+    counters: Arc<TrieStoreCounters>,
+    quarantine: Arc<TrieQuarantine>,
 }
 
 impl LmdbTrieStore {
@@ -139,14 +161,163 @@ impl LmdbTrieStore {
     ) -> Result<Self, error::Error> {
         let name = Self::name(maybe_name);
         let db = env.env().create_db(Some(&name), flags)?;
-        Ok(LmdbTrieStore { db })
+        let shards = TrieShards::open_existing(env, &name)?;
+        let codec = TrieCodec::read(env, &name)?;
+        let quarantine = Arc::new(TrieQuarantine::open(env, &name)?);
+        Ok(LmdbTrieStore {
+            db,
+            shards,
+            name,
+            codec,
+            counters: Arc::default(),
+            quarantine,
+        })
     }
 
     /// Constructor for `LmdbTrieStore` which opens an existing lmdb store file.
     pub fn open(env: &LmdbEnvironment, maybe_name: Option<&str>) -> Result<Self, error::Error> {
         let name = Self::name(maybe_name);
         let db = env.env().open_db(Some(&name))?;
-        Ok(LmdbTrieStore { db })
+        let shards = TrieShards::open_existing(env, &name)?;
+        let codec = TrieCodec::read(env, &name)?;
+        let quarantine = Arc::new(TrieQuarantine::open(env, &name)?);
+        Ok(LmdbTrieStore {
+            db,
+            shards,
+            name,
+            codec,
+            counters: Arc::default(),
+            quarantine,
+        })
+    }
+
+    /// Compresses tries written from now on with zstd at `level`.
+    ///
+    /// The store is marked as holding compressed tries in `env`, so it is decompressed when
+    /// opened later even without compression enabled.
+    pub fn with_compression(
+        mut self,
+        env: &LmdbEnvironment,
+        level: i32,
+    ) -> Result<Self, error::Error> {
+        self.codec = TrieCodec::enable_compression(env, &self.name, level)?;
+        Ok(self)
+    }
+
+    /// Writes leaves under account, balance, contract and dictionary keys to a database of their
+    /// own.
+    ///
+    /// The shard databases are created in `env`, and the leaves already in the main database are
+    /// moved into them unless that was done before.  The store is opened sharded later even
+    /// without sharding enabled, until [`LmdbTrieStore::without_sharding`] is called.
+    pub fn with_sharding(mut self, env: &LmdbEnvironment) -> Result<Self, error::Error> {
+        let mut shards = TrieShards::create(env, &self.name)?;
+        if !shards.is_migrated() {
+            let moved = self.move_tries(env, self.db, |stored_bytes| {
+                let trie_bytes = self.codec.decode_slice(stored_bytes)?;
+                Ok(TrieShard::of(&trie_bytes).map(|shard| shards.db(shard)))
+            })?;
+            shards.set_migrated(env, &self.name, true)?;
+            info!(moved, "moved trie leaves into shard databases");
+        }
+        self.shards = Some(shards);
+        Ok(self)
+    }
+
+    /// Moves the leaves held in shard databases back into the main database and drops the shards,
+    /// so that the store is no longer sharded.
+    ///
+    /// Does nothing if the store isn't sharded.
+    pub fn without_sharding(mut self, env: &LmdbEnvironment) -> Result<Self, error::Error> {
+        let mut shards = match self.shards.take() {
+            Some(shards) => shards,
+            None => return Ok(self),
+        };
+        // Cleared first, so that if interrupted, leaves are looked for in the main database again.
+        shards.set_migrated(env, &self.name, false)?;
+        let mut moved = 0;
+        for db in shards.dbs() {
+            moved += self.move_tries(env, db, |_| Ok(Some(self.db)))?;
+        }
+        shards.drop_dbs(env)?;
+        info!(moved, "moved trie leaves out of shard databases");
+        Ok(self)
+    }
+
+    /// Moves the tries in `from` for which `target` returns a database there, returning how many
+    /// were moved.
+    fn move_tries<F>(
+        &self,
+        env: &LmdbEnvironment,
+        from: Database,
+        target: F,
+    ) -> Result<usize, error::Error>
+    where
+        F: Fn(&[u8]) -> Result<Option<Database>, error::Error>,
+    {
+        let mut moved = 0;
+        let mut resume_from = None;
+        loop {
+            let (batch_moved, next_key) = env.write_with_map_growth(|| {
+                Self::move_tries_batch(env, from, &target, resume_from.as_deref())
+            })?;
+            moved += batch_moved;
+            match next_key {
+                Some(next_key) => resume_from = Some(next_key),
+                None => return Ok(moved),
+            }
+        }
+    }
+
+    /// Moves tries as [`LmdbTrieStore::move_tries`] does, in a single read-write transaction
+    /// looking at no more than `SHARD_MIGRATION_BATCH_LEN` tries or
+    /// `DEFAULT_WRITE_BATCH_SIZE_BYTES` bytes, and returns the key to resume from, if any.
+    fn move_tries_batch<F>(
+        env: &LmdbEnvironment,
+        from: Database,
+        target: &F,
+        resume_from: Option<&[u8]>,
+    ) -> Result<(usize, Option<Vec<u8>>), error::Error>
+    where
+        F: Fn(&[u8]) -> Result<Option<Database>, error::Error>,
+    {
+        let mut txn = env.create_read_write_txn()?;
+        let mut to_move = Vec::new();
+        let mut next_key = None;
+        {
+            let mut cursor = txn.open_rw_cursor(from)?;
+            let rows = match resume_from {
+                Some(key) => cursor.iter_from(key),
+                None => cursor.iter(),
+            };
+            let mut looked_at = 0;
+            let mut bytes_to_move = 0;
+            for row in rows {
+                let (raw_key, stored_bytes) = row?;
+                if looked_at == SHARD_MIGRATION_BATCH_LEN
+                    || bytes_to_move >= DEFAULT_WRITE_BATCH_SIZE_BYTES
+                {
+                    next_key = Some(raw_key.to_vec());
+                    break;
+                }
+                looked_at += 1;
+                if let Some(db) = target(stored_bytes)? {
+                    bytes_to_move += stored_bytes.len();
+                    to_move.push((raw_key.to_vec(), stored_bytes.to_vec(), db));
+                    cursor.del(WriteFlags::empty())?;
+                }
+            }
+        }
+        for (raw_key, stored_bytes, db) in &to_move {
+            txn.put(*db, raw_key, stored_bytes, WriteFlags::empty())?;
+        }
+        txn.commit()?;
+        Ok((to_move.len(), next_key))
+    }
+
+    /// Returns `true` if leaves are spread over shard databases.
+    pub fn is_sharded(&self) -> bool {
+        self.shards.is_some()
     }
 
     fn name(maybe_name: Option<&str>) -> String {
@@ -156,9 +327,273 @@ impl LmdbTrieStore {
     }
 
     /// Get a handle to the underlying database.
+    ///
+    /// A sharded store holds some of its leaves in other databases, see
+    /// [`LmdbTrieStore::databases`].
     pub fn get_db(&self) -> Database {
         self.db
     }
+
+    /// Returns every database tries are stored in, starting with the main one.
+    pub fn databases(&self) -> impl Iterator<Item = Database> + '_ {
+        iter::once(self.db).chain(self.shards.iter().flat_map(|shards| shards.dbs()))
+    }
+
+    /// Returns the database the serialized trie `trie_bytes` is written to.
+    pub(crate) fn db_for(&self, trie_bytes: &[u8]) -> Database {
+        match (&self.shards, TrieShard::of(trie_bytes)) {
+            (Some(shards), Some(shard)) => shards.db(shard),
+            _ => self.db,
+        }
+    }
+
+    /// Returns the databases which may hold a trie at `location`, in the order to look in them.
+    ///
+    /// A leaf is only looked for in its shard, unless leaves written before sharding was enabled
+    /// may not all have been moved there yet.  A trie at an unknown location is looked for in
+    /// every database.
+    pub(crate) fn databases_at(&self, location: TrieLocation) -> impl Iterator<Item = Database> {
+        let mut dbs = [None; 1 + SHARD_COUNT];
+        match (&self.shards, location) {
+            (None, _) | (Some(_), TrieLocation::Node) => dbs[0] = Some(self.db),
+            (Some(shards), TrieLocation::Leaf { key_tag }) => {
+                match TrieShard::of_key_tag(key_tag) {
+                    Some(shard) => {
+                        dbs[0] = Some(shards.db(shard));
+                        if !shards.is_migrated() {
+                            dbs[1] = Some(self.db);
+                        }
+                    }
+                    None => dbs[0] = Some(self.db),
+                }
+            }
+            (Some(_), TrieLocation::Unknown) => {
+                for (slot, db) in dbs.iter_mut().zip(self.databases()) {
+                    *slot = Some(db);
+                }
+            }
+        }
+        IntoIterator::into_iter(dbs).flatten()
+    }
+
+    /// Calls `f` with the serialized trie under `digest`, looking for it in the databases which
+    /// may hold it at `location`.
+    fn with_raw_at<T, F, R>(
+        &self,
+        txn: &T,
+        digest: &Digest,
+        location: TrieLocation,
+        f: F,
+    ) -> Result<Option<R>, error::Error>
+    where
+        T: Readable<Handle = Database>,
+        F: FnOnce(&[u8]) -> R,
+        error::Error: From<T::Error>,
+    {
+        let mut f = Some(f);
+        for db in self.databases_at(location) {
+            let maybe_result = txn.read_with(db, digest.as_ref(), |stored_bytes| {
+                self.counters.record_get(Some(stored_bytes.len()));
+                let f = f
+                    .take()
+                    .expect("trie should only be read from one database");
+                self.codec
+                    .decode_slice(stored_bytes)
+                    .map(|trie_bytes| f(&trie_bytes))
+            })?;
+            if let Some(result) = maybe_result {
+                return result.map(Some);
+            }
+        }
+        self.counters.record_get(None);
+        Ok(None)
+    }
+
+    /// Returns the stored bytes of the trie under `key`, along with the database holding them.
+    pub(crate) fn get_stored<'txn>(
+        &self,
+        txn: &'txn impl Transaction,
+        key: &[u8],
+    ) -> Result<Option<(Database, &'txn [u8])>, lmdb::Error> {
+        for db in self.databases() {
+            match txn.get(db, &key) {
+                Ok(stored_bytes) => return Ok(Some((db, stored_bytes))),
+                Err(lmdb::Error::NotFound) => continue,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the counts of the operations performed on the store since they were last taken,
+    /// resetting them.
+    pub fn take_counts(&self) -> TrieStoreCounts {
+        self.counters.take()
+    }
+
+    /// Quarantines the trie under `digest`, whose stored bytes failed to deserialize with `error`,
+    /// returning the error to report.
+    pub(crate) fn quarantine_trie(&self, digest: Digest, error: bytesrepr::Error) -> error::Error {
+        self.quarantine.insert(digest, &error);
+        error::Error::CorruptedTrie(digest, error)
+    }
+
+    /// Returns `true` if the trie under `digest` is quarantined.
+    pub fn is_quarantined(&self, digest: &Digest) -> bool {
+        self.quarantine.contains(digest)
+    }
+
+    /// Returns the digests of the quarantined tries, persisting any newly quarantined ones in
+    /// `env`.
+    pub(crate) fn quarantined_tries(
+        &self,
+        env: &LmdbEnvironment,
+    ) -> Result<Vec<Digest>, error::Error> {
+        self.quarantine.persist(env)
+    }
+
+    /// Returns how tries are encoded in the database.
+    pub(crate) fn codec(&self) -> TrieCodec {
+        self.codec
+    }
+
+    /// Returns the serialized trie held in the database as `stored_bytes`, which are compressed if
+    /// the store compresses tries.
+    ///
+    /// Only needed when reading the database directly rather than through [`Store`].
+    pub fn decode<'a>(&self, stored_bytes: &'a [u8]) -> Result<Cow<'a, [u8]>, error::Error> {
+        self.codec.decode_slice(stored_bytes)
+    }
+
+    /// Advances `walk`, gathering statistics about the tries in the store, by at most `batch_len`
+    /// tries read in `txn`.
+    ///
+    /// The walk reads the entire store, so it is slow on a large global state, and is meant to be
+    /// advanced over several short read transactions.  Tries written in between may be counted or
+    /// not.
+    pub fn stat(
+        &self,
+        txn: &impl Transaction,
+        walk: &mut TrieStoreStatWalk,
+        batch_len: usize,
+    ) -> Result<(), error::Error> {
+        let mut budget = batch_len.max(1);
+        let databases: Vec<Database> = self.databases().collect();
+        while let Some(db) = databases.get(walk.db_index).copied() {
+            let (read, resume_from) = self.stat_db(
+                txn,
+                db,
+                walk.resume_from.as_deref(),
+                budget,
+                &mut walk.stats,
+            )?;
+            budget -= read;
+            match resume_from {
+                Some(key) => {
+                    walk.resume_from = Some(key);
+                    return Ok(());
+                }
+                None => {
+                    walk.db_index += 1;
+                    walk.resume_from = None;
+                }
+            }
+        }
+        if let Some(depths) = walk.stats.depths.as_mut() {
+            self.depth_stats(txn, depths, &mut walk.to_visit, budget)?;
+        }
+        walk.finished = walk.to_visit.is_empty();
+        Ok(())
+    }
+
+    /// Reads at most `budget` entries of `db` from `resume_from` or its start.
+    ///
+    /// Returns the number of entries read and the key to resume from, if any are left.
+    fn stat_db(
+        &self,
+        txn: &impl Transaction,
+        db: Database,
+        resume_from: Option<&[u8]>,
+        budget: usize,
+        stats: &mut TrieStoreStats,
+    ) -> Result<(usize, Option<Vec<u8>>), error::Error> {
+        let mut cursor = txn.open_ro_cursor(db)?;
+        let rows = match resume_from {
+            Some(key) => cursor.iter_from(key),
+            None => cursor.iter_start(),
+        };
+        let mut read = 0;
+        for row in rows {
+            let (key, value) = row?;
+            if read == budget {
+                return Ok((read, Some(key.to_vec())));
+            }
+            read += 1;
+            stats.entries += 1;
+            stats.total_bytes += (key.len() + value.len()) as u64;
+            if self.codec.is_compressed(value) {
+                stats.compressed += 1;
+            }
+            let value = match self.codec.decode_slice(value) {
+                Ok(value) => value,
+                Err(_) => {
+                    stats.unparsable += 1;
+                    continue;
+                }
+            };
+            // Leaves are counted by their tag alone, sparing the parsing of their values.
+            if value.first() == Some(&Trie::<Key, StoredValue>::LEAF_TAG) {
+                stats.leaves += 1;
+                continue;
+            }
+            match bytesrepr::deserialize_from_slice(value) {
+                Ok(Trie::<Key, StoredValue>::Node { pointer_block }) => {
+                    stats.nodes += 1;
+                    *stats
+                        .branching_factors
+                        .entry(pointer_block.child_count())
+                        .or_default() += 1;
+                }
+                Ok(Trie::Extension { .. }) => stats.extensions += 1,
+                Ok(Trie::Leaf { .. }) | Err(_) => stats.unparsable += 1,
+            }
+        }
+        Ok((read, None))
+    }
+
+    /// Visits at most `budget` tries of `to_visit`, along with their depths below the state root.
+    fn depth_stats(
+        &self,
+        txn: &impl Transaction,
+        depths: &mut TrieDepthStats,
+        to_visit: &mut Vec<(Digest, usize)>,
+        budget: usize,
+    ) -> Result<(), error::Error> {
+        for _ in 0..budget {
+            let (trie_key, depth) = match to_visit.pop() {
+                Some(next) => next,
+                None => break,
+            };
+            let trie_bytes = match self.get_stored(txn, trie_key.as_ref())? {
+                Some((_, trie_bytes)) => trie_bytes,
+                None => {
+                    depths.missing_tries += 1;
+                    continue;
+                }
+            };
+            let trie_bytes = self.codec.decode_slice(trie_bytes)?;
+            if trie_bytes.first() == Some(&Trie::<Key, StoredValue>::LEAF_TAG) {
+                *depths.leaf_depths.entry(depth).or_default() += 1;
+                continue;
+            }
+            let trie: Trie<Key, StoredValue> = bytesrepr::deserialize_from_slice(trie_bytes)?;
+            to_visit.extend(
+                trie.iter_descendants()
+                    .map(|child_key| (child_key, depth + 1)),
+            );
+        }
+        Ok(())
+    }
 }
 
 impl<K, V> Store<Digest, Trie<K, V>> for LmdbTrieStore {
@@ -169,68 +604,290 @@ impl<K, V> Store<Digest, Trie<K, V>> for LmdbTrieStore {
     fn handle(&self) -> Self::Handle {
         self.db
     }
+
+    /// Reads the trie under `digest`, quarantining it if it fails to deserialize.
+    fn get<T>(&self, txn: &T, digest: &Digest) -> Result<Option<Trie<K, V>>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Trie<K, V>: FromBytes,
+        Self::Error: From<T::Error>,
+    {
+        let maybe_trie = Store::<Digest, Trie<K, V>>::with_raw(self, txn, digest, |trie_bytes| {
+            bytesrepr::deserialize_from_slice(trie_bytes)
+        })?;
+        maybe_trie
+            .transpose()
+            .map_err(|error| self.quarantine_trie(*digest, error))
+    }
+
+    /// Reads the serialized trie under `digest`, which is taken to be missing if quarantined.
+    fn get_raw<T>(&self, txn: &T, digest: &Digest) -> Result<Option<Bytes>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Self::Error: From<T::Error>,
+    {
+        if self.is_quarantined(digest) {
+            self.counters.record_get(None);
+            return Ok(None);
+        }
+        let mut maybe_stored_bytes = None;
+        for db in self.databases() {
+            maybe_stored_bytes = txn.read(db, digest.as_ref())?;
+            if maybe_stored_bytes.is_some() {
+                break;
+            }
+        }
+        self.counters.record_get(
+            maybe_stored_bytes
+                .as_ref()
+                .map(|stored_bytes| stored_bytes.len()),
+        );
+        match maybe_stored_bytes {
+            Some(stored_bytes) => Ok(Some(self.codec.decode(stored_bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn with_raw<T, F, R>(&self, txn: &T, digest: &Digest, f: F) -> Result<Option<R>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        F: FnOnce(&[u8]) -> R,
+        Self::Error: From<T::Error>,
+    {
+        self.with_raw_at(txn, digest, TrieLocation::Unknown, f)
+    }
+
+    /// Writes the serialized trie under `digest` to `writer`, streaming it out of LMDB and through
+    /// decompression without holding a copy of the whole trie.
+    ///
+    /// A quarantined trie is taken to be missing, so that it isn't served to peers.
+    fn get_into<T, W>(&self, txn: &T, digest: &Digest, writer: &mut W) -> Result<bool, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        W: io::Write,
+        Self::Error: From<T::Error> + From<io::Error>,
+    {
+        if self.is_quarantined(digest) {
+            self.counters.record_get(None);
+            return Ok(false);
+        }
+        for db in self.databases() {
+            let maybe_result = txn.read_with(db, digest.as_ref(), |stored_bytes| {
+                self.counters.record_get(Some(stored_bytes.len()));
+                self.codec.decode_into(stored_bytes, writer)
+            })?;
+            if let Some(result) = maybe_result {
+                result?;
+                return Ok(true);
+            }
+        }
+        self.counters.record_get(None);
+        Ok(false)
+    }
+
+    /// Writes the serialized trie under `digest`, releasing it from quarantine.
+    fn put_raw<T>(&self, txn: &mut T, digest: &Digest, trie_bytes: &[u8]) -> Result<(), Self::Error>
+    where
+        T: Writable<Handle = Self::Handle>,
+        Self::Error: From<T::Error>,
+    {
+        let stored_bytes = self.codec.encode(trie_bytes)?;
+        if self.is_quarantined(digest) {
+            // The corrupted bytes may be held in another database than the trie is written to.
+            for db in self.databases() {
+                txn.delete(db, digest.as_ref())?;
+            }
+            self.quarantine.release(txn, digest)?;
+        }
+        txn.write(self.db_for(trie_bytes), digest.as_ref(), &stored_bytes)?;
+        self.counters.record_put(stored_bytes.len());
+        Ok(())
+    }
+
+    /// Returns `true` if the trie under `digest` is stored and not quarantined.
+    fn exists<T>(&self, txn: &T, digest: &Digest) -> Result<bool, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Self::Error: From<T::Error>,
+    {
+        if self.is_quarantined(digest) {
+            return Ok(false);
+        }
+        for db in self.databases() {
+            if txn.exists(db, digest.as_ref())? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn delete<T>(&self, txn: &mut T, digest: &Digest) -> Result<bool, Self::Error>
+    where
+        T: Writable<Handle = Self::Handle>,
+        Self::Error: From<T::Error>,
+    {
+        for db in self.databases() {
+            if txn.delete(db, digest.as_ref())? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl<K, V> TrieStore<K, V> for LmdbTrieStore {
+    /// Reads the trie under `digest` from the one database `location` tells, quarantining it if it
+    /// fails to deserialize.
+    fn get_at<T>(
+        &self,
+        txn: &T,
+        digest: &Digest,
+        location: TrieLocation,
+    ) -> Result<Option<Trie<K, V>>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Trie<K, V>: FromBytes,
+        Self::Error: From<T::Error>,
+    {
+        let maybe_trie = self.with_raw_at(txn, digest, location, |trie_bytes| {
+            bytesrepr::deserialize_from_slice(trie_bytes)
+        })?;
+        maybe_trie
+            .transpose()
+            .map_err(|error| self.quarantine_trie(*digest, error))
+    }
 }
 
-impl<K, V> TrieStore<K, V> for LmdbTrieStore {}
+/// The default number of bytes of tries written to LMDB in a single transaction when committing a
+/// scratch trie store.
+pub(crate) const DEFAULT_WRITE_BATCH_SIZE_BYTES: usize = 16 * 1024 * 1024;
 
-/// Cache used by the scratch trie.  The keys represent the hash of the trie being cached.  The
-/// values represent:  1) A boolean, where `false` means the trie was _not_ written and `true` means
-/// it was 2) A deserialized trie
-pub(crate) type Cache = Arc<Mutex<HashMap<Digest, (bool, Trie<Key, StoredValue>)>>>;
+/// The number of dirty tries serialized at a time when committing a scratch trie store.
+const SERIALIZE_CHUNK_LEN: usize = 4096;
+
+/// Statistics about the tries written to LMDB when committing a scratch trie store.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ScratchCommitStats {
+    /// The number of tries written.
+    pub(crate) tries_written: usize,
+    /// The total size of the serialized tries written, in bytes.
+    pub(crate) bytes_written: usize,
+    /// The number of LMDB transactions committed.
+    pub(crate) batches: usize,
+}
 
 /// Cached version of the trie store.
 #[derive(Clone)]
 pub(crate) struct ScratchTrieStore {
-    pub(crate) cache: Cache,
+    pub(crate) cache: Arc<ScratchCache>,
     pub(crate) store: Arc<LmdbTrieStore>,
     pub(crate) env: Arc<LmdbEnvironment>,
 }
 
 impl ScratchTrieStore {
-    /// Creates a new ScratchTrieStore.
-    pub fn new(store: Arc<LmdbTrieStore>, env: Arc<LmdbEnvironment>) -> Self {
+    /// Creates a new ScratchTrieStore, caching tries in `cache`.
+    pub fn new(store: Arc<LmdbTrieStore>, env: Arc<LmdbEnvironment>, cache: ScratchCache) -> Self {
         Self {
             store,
             env,
-            cache: Default::default(),
+            cache: Arc::new(cache),
         }
     }
 
+    /// Returns the hit and miss counts of lookups in the cache and the number of spilled tries so
+    /// far.
+    pub(crate) fn cache_stats(&self) -> Result<ScratchCacheStats, error::Error> {
+        self.cache.stats()
+    }
+
     /// Writes only tries which are both under the given `state_root` and dirty to the underlying db
     /// while maintaining the invariant that children must be written before parent nodes.
-    pub fn write_root_to_db(self, state_root: Digest) -> Result<(), error::Error> {
+    ///
+    /// The dirty tries are serialized and checked against their hashes in parallel chunks, then
+    /// written in order across as many transactions as needed to keep each one under roughly
+    /// `batch_size_bytes`.  Since children are always committed no later than their parents, a
+    /// failure part way through leaves only complete subtries in the store, and no state root
+    /// until the last batch is committed.
+    ///
+    /// `on_root_commit` is run within the transaction committing `state_root`, or within a
+    /// transaction of its own if `state_root` is already in the store, so that data describing
+    /// the state root is committed along with it.
+    pub fn write_root_to_db<F>(
+        self,
+        state_root: Digest,
+        batch_size_bytes: usize,
+        on_root_commit: F,
+    ) -> Result<ScratchCommitStats, error::Error>
+    where
+        F: Fn(&mut RwTransaction) -> Result<(), error::Error>,
+    {
         let env = self.env;
         let store = self.store;
-        let cache = &mut *self.cache.lock().map_err(|_| error::Error::Poison)?;
+        let cache = self.cache;
 
-        let (is_root_dirty, root_trie) = cache
-            .get(&state_root)
-            .ok_or(CommitError::TrieNotFoundInCache(state_root))?;
-
-        // Early exit if there is no work to do.
-        if !is_root_dirty {
-            return Ok(());
-        }
+        let root_descendants = match cache.dirty_descendants(&state_root)? {
+            Some(root_descendants) => root_descendants,
+            None => {
+                // Early exit if there is no work to do.
+                let txn = env.create_read_txn()?;
+                let root_exists =
+                    Store::<Digest, Trie<Key, StoredValue>>::exists(&*store, &txn, &state_root)?;
+                txn.commit()?;
+                if !root_exists {
+                    return Err(CommitError::TrieNotFoundInCache(state_root).into());
+                }
+                let mut txn = env.create_read_write_txn()?;
+                on_root_commit(&mut txn)?;
+                txn.commit()?;
+                return Ok(ScratchCommitStats::default());
+            }
+        };
 
-        let mut txn = env.create_read_write_txn()?;
-        let mut tries_to_visit = vec![(state_root, root_trie, root_trie.iter_descendants())];
+        let mut dirty_tries = Vec::new();
+        let mut tries_to_visit = vec![(state_root, root_descendants.into_iter())];
 
-        while let Some((digest, current_trie, mut descendants_iterator)) = tries_to_visit.pop() {
+        while let Some((digest, mut descendants_iterator)) = tries_to_visit.pop() {
             if let Some(descendant) = descendants_iterator.next() {
-                tries_to_visit.push((digest, current_trie, descendants_iterator));
-                // Only if a node is marked as dirty in the cache do we want to visit it's
-                // descendants
-                if let Some((true, child_trie)) = cache.get(&descendant) {
-                    tries_to_visit.push((descendant, child_trie, child_trie.iter_descendants()));
+                tries_to_visit.push((digest, descendants_iterator));
+                // Only if a node is dirty do we want to visit it's descendants
+                if let Some(child_descendants) = cache.dirty_descendants(&descendant)? {
+                    tries_to_visit.push((descendant, child_descendants.into_iter()));
                 }
             } else {
-                // We can write this node since it has no children, or they were already written.
-                store.put(&mut txn, &digest, current_trie)?;
+                // This node has no dirty children left to visit, so it is queued after them.
+                dirty_tries.push(digest);
             }
         }
 
+        let mut stats = ScratchCommitStats::default();
+        let mut batch_bytes = 0;
+        let mut txn = env.create_read_write_txn()?;
+        // Serializing in chunks bounds the memory used, as dirty tries may have been spilled.
+        for chunk in dirty_tries.chunks(SERIALIZE_CHUNK_LEN) {
+            for (digest, trie_bytes) in cache.serialize_dirty(chunk)? {
+                // A full batch is only committed once there is another trie to write, so that the
+                // last batch always holds the state root.
+                if batch_bytes >= batch_size_bytes {
+                    txn.commit()?;
+                    stats.batches += 1;
+                    batch_bytes = 0;
+                    txn = env.create_read_write_txn()?;
+                }
+                Store::<Digest, Trie<Key, StoredValue>>::put_raw(
+                    &*store,
+                    &mut txn,
+                    &digest,
+                    &trie_bytes,
+                )?;
+                stats.tries_written += 1;
+                stats.bytes_written += trie_bytes.len();
+                batch_bytes += trie_bytes.len();
+            }
+        }
+        on_root_commit(&mut txn)?;
         txn.commit()?;
-        Ok(())
+        stats.batches += 1;
+        Ok(stats)
     }
 }
 
@@ -255,11 +912,8 @@ impl Store<Digest, Trie<Key, StoredValue>> for ScratchTrieStore {
         T: Writable<Handle = Self::Handle>,
         Self::Error: From<T::Error>,
     {
-        self.cache
-            .lock()
-            .map_err(|_| error::Error::Poison)?
-            .insert(*digest, (true, trie.clone()));
-        Ok(())
+        self.store.counters.record_scratch_put();
+        self.cache.insert_dirty(*digest, trie.clone())
     }
 
     /// Returns an optional value (may exist or not) as read through a transaction, or an error
@@ -273,34 +927,92 @@ impl Store<Digest, Trie<Key, StoredValue>> for ScratchTrieStore {
         T: Readable<Handle = Self::Handle>,
         Self::Error: From<T::Error>,
     {
-        let maybe_trie = {
-            self.cache
-                .lock()
-                .map_err(|_| error::Error::Poison)?
-                .get(digest)
-                .cloned()
-        };
-        match maybe_trie {
-            Some((_, cached)) => Ok(Some(cached)),
-            None => {
-                let raw = self.get_raw(txn, digest)?;
-                match raw {
-                    Some(bytes) => {
-                        let value: Trie<Key, StoredValue> = bytesrepr::deserialize(bytes.into())?;
-                        {
-                            let store =
-                                &mut *self.cache.lock().map_err(|_| error::Error::Poison)?;
-                            if !store.contains_key(digest) {
-                                store.insert(*digest, (false, value.clone()));
-                            }
-                        }
-                        Ok(Some(value))
-                    }
-                    None => Ok(None),
-                }
+        self.store.counters.record_scratch_get();
+        if let Some(cached) = self.cache.get(digest)? {
+            return Ok(Some(cached));
+        }
+        let maybe_read = self.with_raw(txn, digest, |trie_bytes| {
+            bytesrepr::deserialize_from_slice::<_, Trie<Key, StoredValue>>(trie_bytes)
+        })?;
+        match maybe_read {
+            Some(value) => {
+                let value = value.map_err(|error| self.store.quarantine_trie(*digest, error))?;
+                self.cache.insert_clean(*digest, value.clone())?;
+                Ok(Some(value))
             }
+            None => Ok(None),
         }
     }
+
+    /// Writes the serialized trie under `digest` to `writer`, from the cache if it is there.
+    fn get_into<T, W>(&self, txn: &T, digest: &Digest, writer: &mut W) -> Result<bool, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        W: io::Write,
+        Self::Error: From<T::Error> + From<io::Error>,
+    {
+        if let Some(cached) = self.cache.get(digest)? {
+            writer.write_all(&cached.to_bytes()?)?;
+            return Ok(true);
+        }
+        match self.with_raw(txn, digest, |trie_bytes| writer.write_all(trie_bytes))? {
+            Some(result) => {
+                result?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Returns `true` if the trie under `digest` is cached or already in the underlying store.
+    fn exists<T>(&self, txn: &T, digest: &Digest) -> Result<bool, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Self::Error: From<T::Error>,
+    {
+        if self.cache.contains(digest)? {
+            return Ok(true);
+        }
+        Ok(txn.exists(self.handle(), digest.as_ref())?)
+    }
+
+    /// Drops the trie under `digest` from the cache, so that it isn't written by a later commit.
+    ///
+    /// Tries already in the underlying store are left untouched: they may be shared by other state
+    /// roots, so removing them is left to pruning.
+    fn delete<T>(&self, txn: &mut T, digest: &Digest) -> Result<bool, Self::Error>
+    where
+        T: Writable<Handle = Self::Handle>,
+        Self::Error: From<T::Error>,
+    {
+        Ok(txn.delete(self.handle(), digest.as_ref())?)
+    }
 }
 
-impl TrieStore<Key, StoredValue> for ScratchTrieStore {}
+impl TrieStore<Key, StoredValue> for ScratchTrieStore {
+    /// Returns the trie under `digest` from the cache, or else reads it from the database
+    /// `location` tells and caches it.
+    fn get_at<T>(
+        &self,
+        _txn: &T,
+        digest: &Digest,
+        location: TrieLocation,
+    ) -> Result<Option<Trie<Key, StoredValue>>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Trie<Key, StoredValue>: FromBytes,
+        Self::Error: From<T::Error>,
+    {
+        self.store.counters.record_scratch_get();
+        if let Some(cached) = self.cache.get(digest)? {
+            return Ok(Some(cached));
+        }
+        let txn = self.env.create_read_txn()?;
+        let maybe_trie =
+            TrieStore::<Key, StoredValue>::get_at(&*self.store, &txn, digest, location)?;
+        if let Some(trie) = &maybe_trie {
+            self.cache.insert_clean(*digest, trie.clone())?;
+        }
+        Ok(maybe_trie)
+    }
+}
@@ -2,17 +2,111 @@
 //!
 //! See the [in_memory](in_memory/index.html#usage) and
 //! [lmdb](lmdb/index.html#usage) modules for usage examples.
+pub mod compression;
+pub mod counters;
+pub mod dedup;
 pub mod in_memory;
+pub mod integrity;
 pub mod lmdb;
 pub(crate) mod operations;
+pub(crate) mod quarantine;
+pub(crate) mod scratch_cache;
+pub mod shards;
+pub mod stats;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
 #[cfg(test)]
 mod tests;
 
+use std::fmt::Debug;
+
 use casper_hashing::Digest;
+use casper_types::bytesrepr::{FromBytes, ToBytes};
+
+use crate::{
+    shared::newtypes::CorrelationId,
+    storage::{
+        store::Store,
+        transaction_source::Readable,
+        trie::{path_proof::TriePathProof, Pointer, Trie},
+    },
+};
 
-use crate::storage::{store::Store, trie::Trie};
+use self::operations::ReadResult;
 
 const NAME: &str = "TRIE_STORE";
 
+/// Where a trie is to be found, as told by the pointer leading to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrieLocation {
+    /// Not known, as for a state root read by its digest alone.
+    Unknown,
+    /// A node or an extension.
+    Node,
+    /// A leaf, whose serialized key starts with `key_tag`.
+    Leaf {
+        /// The first byte of the serialized key of the leaf.
+        key_tag: u8,
+    },
+}
+
+impl TrieLocation {
+    /// Returns the location of the trie `pointer` leads to, below the path `path` starts.
+    ///
+    /// Every leaf below a path starts with the same first byte as the path, so that byte is the
+    /// key tag of the leaf.
+    pub(crate) fn of_child(pointer: &Pointer, path: &[u8]) -> Self {
+        match (pointer, path.first()) {
+            (Pointer::NodePointer(_), _) => TrieLocation::Node,
+            (Pointer::LeafPointer(_), Some(key_tag)) => TrieLocation::Leaf { key_tag: *key_tag },
+            (Pointer::LeafPointer(_), None) => TrieLocation::Unknown,
+        }
+    }
+}
+
 /// An entity which persists [`Trie`] values at their hashes.
-pub trait TrieStore<K, V>: Store<Digest, Trie<K, V>> {}
+pub trait TrieStore<K, V>: Store<Digest, Trie<K, V>> {
+    /// Reads the trie under `digest`, which `location` tells where to look for.
+    ///
+    /// Stores which keep tries apart by location look in one place only; others read the trie by
+    /// its digest alone.
+    fn get_at<T>(
+        &self,
+        txn: &T,
+        digest: &Digest,
+        location: TrieLocation,
+    ) -> Result<Option<Trie<K, V>>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Trie<K, V>: FromBytes,
+        Self::Error: From<T::Error>,
+    {
+        let _ = location;
+        self.get(txn, digest)
+    }
+
+    /// Reads the value under `key` in the trie with root `root`, together with the trie nodes on
+    /// the path from the root to its leaf.
+    fn read_with_path_proof<T>(
+        &self,
+        correlation_id: CorrelationId,
+        txn: &T,
+        root: &Digest,
+        key: &K,
+    ) -> Result<ReadResult<TriePathProof<K, V>>, Self::Error>
+    where
+        Self: Sized,
+        K: ToBytes + FromBytes + Eq + Debug,
+        V: ToBytes + FromBytes,
+        T: Readable<Handle = Self::Handle>,
+        Self::Error: From<T::Error>,
+    {
+        operations::read_with_path_proof::<K, V, T, Self, Self::Error>(
+            correlation_id,
+            txn,
+            self,
+            root,
+            key,
+        )
+    }
+}
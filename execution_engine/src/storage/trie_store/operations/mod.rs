@@ -1,7 +1,13 @@
 #[cfg(test)]
 mod tests;
 
-use std::{cmp, collections::VecDeque, convert::TryInto, mem};
+use std::{
+    cmp,
+    collections::{BTreeMap, HashSet, VecDeque},
+    convert::TryInto,
+    mem,
+    ops::RangeInclusive,
+};
 
 use tracing::{error, warn};
 
@@ -14,9 +20,13 @@ use crate::{
         transaction_source::{Readable, Writable},
         trie::{
             merkle_proof::{TrieMerkleProof, TrieMerkleProofStep},
+            path_proof::TriePathProof,
             Parents, Pointer, PointerBlock, Trie, RADIX, USIZE_EXCEEDS_U8,
         },
-        trie_store::TrieStore,
+        trie_store::{
+            integrity::{TrieIntegrityProblem, TrieIntegrityProblemKind, TrieIntegrityReport},
+            TrieLocation, TrieStore,
+        },
     },
 };
 
@@ -84,7 +94,11 @@ where
                     pointer_block[index]
                 };
                 match maybe_pointer {
-                    Some(pointer) => match store.get(txn, pointer.hash())? {
+                    Some(pointer) => match store.get_at(
+                        txn,
+                        pointer.hash(),
+                        TrieLocation::of_child(&pointer, &path),
+                    )? {
                         Some(next) => {
                             depth += 1;
                             current = next;
@@ -106,7 +120,11 @@ where
             Trie::Extension { affix, pointer } => {
                 let sub_path = &path[depth..depth + affix.len()];
                 if sub_path == affix.as_slice() {
-                    match store.get(txn, pointer.hash())? {
+                    match store.get_at(
+                        txn,
+                        pointer.hash(),
+                        TrieLocation::of_child(&pointer, &path),
+                    )? {
                         Some(next) => {
                             depth += affix.len();
                             current = next;
@@ -128,6 +146,93 @@ where
     }
 }
 
+/// Returns the values under `keys` at a given root in a given store, in the order of `keys`.
+///
+/// The paths to all keys are walked together, so a trie shared by several of them is only fetched
+/// once.  Returns `None` if the root isn't found.
+pub fn read_many<K, V, T, S, E>(
+    _correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    root: &Digest,
+    keys: &[K],
+) -> Result<Option<Vec<Option<V>>>, E>
+where
+    K: ToBytes + FromBytes + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + Clone,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+{
+    let root_trie: Trie<K, V> = match store.get(txn, root)? {
+        Some(root_trie) => root_trie,
+        None => return Ok(None),
+    };
+    let paths = keys
+        .iter()
+        .map(ToBytes::to_bytes)
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut values: Vec<Option<V>> = vec![None; keys.len()];
+
+    // Each entry holds a trie, the depth of its path and the indices of the keys which may be below
+    // it.
+    let mut tries_to_visit = vec![(root_trie, 0, (0..keys.len()).collect::<Vec<usize>>())];
+    while let Some((trie, depth, indices)) = tries_to_visit.pop() {
+        let (children, child_depth) = match trie {
+            Trie::Leaf {
+                key: leaf_key,
+                value: leaf_value,
+            } => {
+                // Keys may not match in the case of a compressed path from a Node directly to a
+                // Leaf.
+                for index in indices {
+                    if keys[index] == leaf_key {
+                        values[index] = Some(leaf_value.clone());
+                    }
+                }
+                continue;
+            }
+            Trie::Node { pointer_block } => {
+                let mut indices_by_child: BTreeMap<u8, Vec<usize>> = BTreeMap::new();
+                for index in indices {
+                    if let Some(byte) = paths[index].get(depth) {
+                        indices_by_child.entry(*byte).or_default().push(index);
+                    }
+                }
+                let children = indices_by_child
+                    .into_iter()
+                    .filter_map(|(byte, child_indices)| {
+                        pointer_block[usize::from(byte)].map(|pointer| (pointer, child_indices))
+                    })
+                    .collect::<Vec<_>>();
+                (children, depth + 1)
+            }
+            Trie::Extension { affix, pointer } => {
+                let child_indices = indices
+                    .into_iter()
+                    .filter(|index| {
+                        paths[*index].get(depth..depth + affix.len()) == Some(affix.as_slice())
+                    })
+                    .collect::<Vec<_>>();
+                if child_indices.is_empty() {
+                    continue;
+                }
+                (vec![(pointer, child_indices)], depth + affix.len())
+            }
+        };
+        for (pointer, child_indices) in children {
+            // Every key below the child shares its path up to the child, so any of them will do.
+            let location = TrieLocation::of_child(&pointer, &paths[child_indices[0]]);
+            match store.get_at(txn, pointer.hash(), location)? {
+                Some(child) => tries_to_visit.push((child, child_depth, child_indices)),
+                None => warn!("No trie value at key: {:?}", pointer.hash()),
+            }
+        }
+    }
+    Ok(Some(values))
+}
+
 /// Same as [`read`], except that a [`TrieMerkleProof`] is generated and returned along with the key
 /// and the value given the root and store.
 pub fn read_with_proof<K, V, T, S, E>(
@@ -185,7 +290,11 @@ where
                     .as_indexed_pointers()
                     .filter(|(index, _)| *index as usize != hole_index)
                     .collect();
-                let next = match store.get(txn, pointer.hash())? {
+                let next = match store.get_at(
+                    txn,
+                    pointer.hash(),
+                    TrieLocation::of_child(&pointer, &path),
+                )? {
                     Some(next) => next,
                     None => {
                         warn!(
@@ -210,7 +319,11 @@ where
                     return Ok(ReadResult::NotFound);
                 };
 
-                let next = match store.get(txn, pointer.hash())? {
+                let next = match store.get_at(
+                    txn,
+                    pointer.hash(),
+                    TrieLocation::of_child(&pointer, &path),
+                )? {
                     Some(next) => next,
                     None => {
                         warn!(
@@ -229,6 +342,80 @@ where
     }
 }
 
+/// Same as [`read`], except that the trie nodes leading to the leaf are returned along with the key
+/// and the value, as a [`TriePathProof`].
+pub fn read_with_path_proof<K, V, T, S, E>(
+    _correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    root: &Digest,
+    key: &K,
+) -> Result<ReadResult<TriePathProof<K, V>>, E>
+where
+    K: ToBytes + FromBytes + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+{
+    let mut nodes = Vec::new();
+    let path: Vec<u8> = key.to_bytes()?;
+
+    let mut depth: usize = 0;
+    let mut current: Trie<K, V> = match store.get(txn, root)? {
+        Some(root) => root,
+        None => return Ok(ReadResult::RootNotFound),
+    };
+    loop {
+        let pointer = match current {
+            Trie::Leaf {
+                key: leaf_key,
+                value,
+            } => {
+                if *key != leaf_key {
+                    return Ok(ReadResult::NotFound);
+                }
+                return Ok(ReadResult::Found(TriePathProof::new(
+                    leaf_key, value, nodes,
+                )));
+            }
+            Trie::Node { ref pointer_block } => {
+                let index: usize = {
+                    assert!(depth < path.len(), "depth must be < {}", path.len());
+                    path[depth].into()
+                };
+                depth += 1;
+                match pointer_block[index] {
+                    Some(pointer) => pointer,
+                    None => return Ok(ReadResult::NotFound),
+                }
+            }
+            Trie::Extension { ref affix, pointer } => {
+                let sub_path = &path[depth..depth + affix.len()];
+                if sub_path != affix.as_slice() {
+                    return Ok(ReadResult::NotFound);
+                }
+                depth += affix.len();
+                pointer
+            }
+        };
+        nodes.push(current);
+        current =
+            match store.get_at(txn, pointer.hash(), TrieLocation::of_child(&pointer, &path))? {
+                Some(next) => next,
+                None => {
+                    warn!(
+                        "No trie value at key: {:?} (reading from path: {:?})",
+                        pointer.hash(),
+                        path
+                    );
+                    return Ok(ReadResult::NotFound);
+                }
+            };
+    }
+}
+
 /// Given a serialized trie, find any children that are referenced but not present in the database.
 pub fn missing_children<K, V, T, S, E>(
     _correlation_id: CorrelationId,
@@ -258,7 +445,7 @@ where
         }
     };
 
-    let is_present = |trie_key| matches!(store.get_raw(txn, &trie_key), Ok(Some(_)));
+    let is_present = |trie_key| matches!(store.exists(txn, &trie_key), Ok(true));
 
     Ok(match trie {
         // Should be unreachable due to checking the first byte as a shortcut above.
@@ -284,6 +471,308 @@ where
     })
 }
 
+/// Walks the trie below `state_root` depth-first, returning the hashes of the first `limit` tries
+/// which are referenced but not present in the database.
+///
+/// An empty result means the state root and everything below it is present.  If the state root
+/// itself is missing, it is the only hash returned.
+pub fn missing_descendants<K, V, T, S, E>(
+    _correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    state_root: Digest,
+    limit: usize,
+) -> Result<Vec<Digest>, E>
+where
+    K: ToBytes + FromBytes + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+{
+    let mut walk = MissingDescendantsWalk::new(state_root, limit);
+    walk.step::<K, V, T, S, E>(txn, store, usize::MAX)?;
+    Ok(walk.into_missing())
+}
+
+/// A search for the tries below a state root which are missing from the store, which can be
+/// advanced across several read transactions so that none of them is held open for long.
+#[derive(Debug)]
+pub struct MissingDescendantsWalk {
+    to_visit: Vec<Digest>,
+    missing: Vec<Digest>,
+    limit: usize,
+}
+
+impl MissingDescendantsWalk {
+    /// Starts a search for at most `limit` missing tries below `state_root`.
+    pub fn new(state_root: Digest, limit: usize) -> Self {
+        MissingDescendantsWalk {
+            to_visit: vec![state_root],
+            missing: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Returns whether the search is finished.
+    pub fn is_finished(&self) -> bool {
+        self.missing.len() >= self.limit || self.to_visit.is_empty()
+    }
+
+    /// Returns the missing tries found.
+    pub fn into_missing(self) -> Vec<Digest> {
+        self.missing
+    }
+
+    /// Visits at most `batch_len` tries within `txn`.
+    pub fn step<K, V, T, S, E>(&mut self, txn: &T, store: &S, batch_len: usize) -> Result<(), E>
+    where
+        K: ToBytes + FromBytes + Eq + std::fmt::Debug,
+        V: ToBytes + FromBytes + std::fmt::Debug,
+        T: Readable<Handle = S::Handle>,
+        S: TrieStore<K, V>,
+        S::Error: From<T::Error>,
+        E: From<S::Error> + From<bytesrepr::Error>,
+    {
+        let mut visited = 0;
+        while visited < batch_len && self.missing.len() < self.limit {
+            let trie_key = match self.to_visit.pop() {
+                Some(trie_key) => trie_key,
+                None => break,
+            };
+            visited += 1;
+            let trie_raw = match store.get_raw(txn, &trie_key)? {
+                Some(trie_raw) => trie_raw,
+                None => {
+                    self.missing.push(trie_key);
+                    continue;
+                }
+            };
+
+            // Optimization: Don't deserialize leaves as they have no descendants.
+            if let Some(&Trie::<K, V>::LEAF_TAG) = trie_raw.first() {
+                continue;
+            }
+
+            match bytesrepr::deserialize_from_slice(trie_raw)? {
+                Trie::<K, V>::Leaf { .. } => {}
+                Trie::Node { pointer_block } => self.to_visit.extend(
+                    pointer_block
+                        .as_indexed_pointers()
+                        .map(|(_, pointer)| *pointer.hash()),
+                ),
+                Trie::Extension { pointer, .. } => self.to_visit.push(pointer.into_hash()),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of collecting the tries reachable from a set of state roots.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReachableTries {
+    /// The hashes of the reachable tries within the requested hash range.
+    Found(HashSet<Digest>),
+    /// The given state root is missing from the store.
+    RootNotFound(Digest),
+    /// More reachable tries than the given maximum are within the requested hash range.
+    TooMany,
+}
+
+/// Returns the hashes of the tries reachable from any of `state_roots` whose first byte lies
+/// within `hash_range`.
+///
+/// Only the tries within the range are recorded, and the walk gives up once more than `max_tries`
+/// of them are found, bounding the memory used.  Subtries within the range which are shared
+/// between several state roots are only visited once, whereas subtries outside of it are walked
+/// again each time they are reached.
+///
+/// Tries missing from the store below the state roots are not included, nor are their
+/// descendants.
+pub fn reachable_tries<K, V, T, S, E>(
+    _correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    state_roots: &[Digest],
+    hash_range: RangeInclusive<u8>,
+    max_tries: usize,
+) -> Result<ReachableTries, E>
+where
+    K: ToBytes + FromBytes + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+{
+    for state_root in state_roots {
+        if !store.exists(txn, state_root)? {
+            return Ok(ReachableTries::RootNotFound(*state_root));
+        }
+    }
+
+    let mut reachable = HashSet::new();
+    let mut to_visit = state_roots.to_vec();
+    while let Some(trie_key) = to_visit.pop() {
+        let in_range = hash_range.contains(&trie_key.value()[0]);
+        if in_range && reachable.contains(&trie_key) {
+            continue;
+        }
+        let trie_raw = match store.get_raw(txn, &trie_key)? {
+            Some(trie_raw) => trie_raw,
+            None => continue,
+        };
+        if in_range {
+            if reachable.len() == max_tries {
+                return Ok(ReachableTries::TooMany);
+            }
+            reachable.insert(trie_key);
+        }
+
+        // Optimization: Don't deserialize leaves as they have no descendants.
+        if let Some(&Trie::<K, V>::LEAF_TAG) = trie_raw.first() {
+            continue;
+        }
+
+        match bytesrepr::deserialize_from_slice(trie_raw)? {
+            Trie::<K, V>::Leaf { .. } => {}
+            Trie::Node { pointer_block } => to_visit.extend(
+                pointer_block
+                    .as_indexed_pointers()
+                    .map(|(_, pointer)| *pointer.hash()),
+            ),
+            Trie::Extension { pointer, .. } => to_visit.push(pointer.into_hash()),
+        }
+    }
+    Ok(ReachableTries::Found(reachable))
+}
+
+/// A walk verifying the integrity of the trie below a state root, which can be carried on across
+/// transactions.
+pub(crate) struct TrieIntegrityWalk {
+    report: TrieIntegrityReport,
+    /// The tries left to check, along with their paths.
+    to_visit: Vec<(Digest, Vec<u8>)>,
+    /// The number of problems after which the walk stops.
+    limit: usize,
+}
+
+impl TrieIntegrityWalk {
+    /// Starts a walk from `state_root`, stopping once `limit` problems have been found.
+    pub(crate) fn new(state_root: Digest, limit: usize) -> Self {
+        TrieIntegrityWalk {
+            report: TrieIntegrityReport {
+                state_root,
+                tries_checked: 0,
+                problems: Vec::new(),
+                truncated: false,
+            },
+            to_visit: vec![(state_root, Vec::new())],
+            limit,
+        }
+    }
+
+    /// Returns `true` once every trie has been checked, or the walk stopped early.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.to_visit.is_empty() || self.report.truncated
+    }
+
+    /// Returns the report of the problems found so far.
+    pub(crate) fn into_report(self) -> TrieIntegrityReport {
+        self.report
+    }
+}
+
+/// Carries on `walk`, checking that each trie referenced below its state root is present, hashes
+/// to the key it is stored under and can be parsed, until `max_tries` tries have been looked up or
+/// the walk is finished.
+///
+/// Descendants of missing or corrupted tries can't be reached and are not checked.  The walk stops
+/// once its limit of problems has been found.
+pub(crate) fn verify_trie_integrity<K, V, T, S, E>(
+    _correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    walk: &mut TrieIntegrityWalk,
+    max_tries: usize,
+) -> Result<(), E>
+where
+    K: ToBytes + FromBytes + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+{
+    let TrieIntegrityWalk {
+        report,
+        to_visit,
+        limit,
+    } = walk;
+    for _ in 0..max_tries {
+        let (trie_key, path) = match to_visit.pop() {
+            Some(next) => next,
+            None => break,
+        };
+        if report.problems.len() >= *limit {
+            report.truncated = true;
+            break;
+        }
+        let trie_raw = match store.get_raw(txn, &trie_key)? {
+            Some(trie_raw) => trie_raw,
+            None => {
+                report.problems.push(TrieIntegrityProblem {
+                    trie_key,
+                    path,
+                    kind: TrieIntegrityProblemKind::Missing,
+                });
+                continue;
+            }
+        };
+        report.tries_checked += 1;
+
+        let actual = Digest::hash_into_chunks_if_necessary(&trie_raw);
+        if actual != trie_key {
+            report.problems.push(TrieIntegrityProblem {
+                trie_key,
+                path,
+                kind: TrieIntegrityProblemKind::HashMismatch { actual },
+            });
+            continue;
+        }
+
+        let trie: Trie<K, V> = match bytesrepr::deserialize_from_slice(trie_raw) {
+            Ok(trie) => trie,
+            Err(error) => {
+                warn!(%trie_key, ?error, "unable to parse trie");
+                report.problems.push(TrieIntegrityProblem {
+                    trie_key,
+                    path,
+                    kind: TrieIntegrityProblemKind::Unparsable,
+                });
+                continue;
+            }
+        };
+        match trie {
+            Trie::Leaf { .. } => {}
+            Trie::Node { pointer_block } => {
+                to_visit.extend(pointer_block.as_indexed_pointers().map(|(index, pointer)| {
+                    let mut child_path = path.clone();
+                    child_path.push(index);
+                    (*pointer.hash(), child_path)
+                }))
+            }
+            Trie::Extension { affix, pointer } => {
+                let mut child_path = path;
+                child_path.extend_from_slice(&affix);
+                to_visit.push((pointer.into_hash(), child_path));
+            }
+        }
+    }
+    Ok(())
+}
+
 struct TrieScan<K, V> {
     tip: Trie<K, V>,
     parents: Parents<K, V>,
@@ -394,7 +883,7 @@ pub enum DeleteResult {
 }
 
 #[allow(unused)]
-fn delete<K, V, T, S, E>(
+pub(crate) fn delete<K, V, T, S, E>(
     correlation_id: CorrelationId,
     txn: &mut T,
     store: &S,
@@ -974,7 +1463,10 @@ struct VisitedTrieNode<K, V> {
     path: Vec<u8>,
 }
 
-pub struct KeysIterator<'a, 'b, K, V, T, S: TrieStore<K, V>> {
+/// An iterator over the keys and values of the leaves in a subtrie, in key order.
+///
+/// Tries are read from the store one at a time as the iteration proceeds.
+pub struct LeavesIterator<'a, 'b, K, V, T, S: TrieStore<K, V>> {
     initial_descend: VecDeque<u8>,
     visited: Vec<VisitedTrieNode<K, V>>,
     store: &'a S,
@@ -982,7 +1474,7 @@ pub struct KeysIterator<'a, 'b, K, V, T, S: TrieStore<K, V>> {
     state: KeysIteratorState<K, V, S>,
 }
 
-impl<'a, 'b, K, V, T, S> Iterator for KeysIterator<'a, 'b, K, V, T, S>
+impl<'a, 'b, K, V, T, S> Iterator for LeavesIterator<'a, 'b, K, V, T, S>
 where
     K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
     V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
@@ -990,7 +1482,7 @@ where
     S: TrieStore<K, V>,
     S::Error: From<T::Error> + From<bytesrepr::Error>,
 {
-    type Item = Result<K, S::Error>;
+    type Item = Result<(K, V), S::Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match mem::replace(&mut self.state, KeysIteratorState::Ok) {
@@ -1012,7 +1504,7 @@ where
             let mut maybe_next_trie: Option<Trie<K, V>> = None;
 
             match trie {
-                Trie::Leaf { key, .. } => {
+                Trie::Leaf { key, value } => {
                     let key_bytes = match key.to_bytes() {
                         Ok(bytes) => bytes,
                         Err(e) => {
@@ -1024,7 +1516,7 @@ where
                     // only return the leaf if it matches the initial descend path
                     path.extend(&self.initial_descend);
                     if key_bytes.starts_with(&path) {
-                        return Some(Ok(key));
+                        return Some(Ok((key, value)));
                     }
                 }
                 Trie::Node { ref pointer_block } => {
@@ -1102,6 +1594,24 @@ where
     }
 }
 
+/// An iterator over the keys of the leaves in a subtrie, in key order.
+pub struct KeysIterator<'a, 'b, K, V, T, S: TrieStore<K, V>>(LeavesIterator<'a, 'b, K, V, T, S>);
+
+impl<'a, 'b, K, V, T, S> Iterator for KeysIterator<'a, 'b, K, V, T, S>
+where
+    K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error> + From<bytesrepr::Error>,
+{
+    type Item = Result<K, S::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|result| result.map(|(key, _)| key))
+    }
+}
+
 /// Returns the iterator over the keys at a given root hash.
 ///
 /// The root should be the apex of the trie.
@@ -1126,12 +1636,32 @@ where
 ///
 /// The root should be the apex of the trie.
 pub fn keys_with_prefix<'a, 'b, K, V, T, S>(
-    _correlation_id: CorrelationId,
+    correlation_id: CorrelationId,
     txn: &'b T,
     store: &'a S,
     root: &Digest,
     prefix: &[u8],
 ) -> KeysIterator<'a, 'b, K, V, T, S>
+where
+    K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+{
+    KeysIterator(leaves_with_prefix(correlation_id, txn, store, root, prefix))
+}
+
+/// Returns the iterator over the keys and values of the leaves in the subtrie matching `prefix`.
+///
+/// The root should be the apex of the trie.
+pub fn leaves_with_prefix<'a, 'b, K, V, T, S>(
+    _correlation_id: CorrelationId,
+    txn: &'b T,
+    store: &'a S,
+    root: &Digest,
+    prefix: &[u8],
+) -> LeavesIterator<'a, 'b, K, V, T, S>
 where
     K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
     V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
@@ -1152,7 +1682,7 @@ where
         ),
     };
 
-    KeysIterator {
+    LeavesIterator {
         initial_descend: prefix.iter().cloned().collect(),
         visited,
         store,
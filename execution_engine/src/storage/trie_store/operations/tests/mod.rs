@@ -23,7 +23,7 @@ use crate::{
             in_memory::InMemoryEnvironment, lmdb::LmdbEnvironment, Readable, Transaction,
             TransactionSource,
         },
-        trie::{merkle_proof::TrieMerkleProof, Pointer, Trie},
+        trie::{merkle_proof::TrieMerkleProof, path_proof::TriePathProof, Pointer, Trie},
         trie_store::{
             self,
             in_memory::InMemoryTrieStore,
@@ -604,7 +604,7 @@ where
     Ok(ret)
 }
 
-/// For a given vector of leaves check the merkle proofs exist and are correct
+/// For a given vector of leaves check the merkle proofs and path proofs exist and are correct
 fn check_merkle_proofs<K, V, T, S, E>(
     correlation_id: CorrelationId,
     txn: &T,
@@ -626,16 +626,23 @@ where
         if let Trie::Leaf { key, value } = leaf {
             let maybe_proof: ReadResult<TrieMerkleProof<K, V>> =
                 read_with_proof::<_, _, _, _, E>(correlation_id, txn, store, root, key)?;
-            match maybe_proof {
+            let merkle_proof_valid = match maybe_proof {
                 ReadResult::Found(proof) => {
                     let hash = proof.compute_state_hash()?;
-                    ret.push(hash == *root && proof.value() == value);
-                }
-                ReadResult::NotFound => {
-                    ret.push(false);
+                    hash == *root && proof.value() == value
                 }
+                ReadResult::NotFound => false,
+                ReadResult::RootNotFound => panic!("Root not found!"),
+            };
+            let maybe_path_proof: ReadResult<TriePathProof<K, V>> =
+                store.read_with_path_proof(correlation_id, txn, root, key)?;
+            let path_proof_valid = match maybe_path_proof {
+                ReadResult::Found(proof) => proof.verify(root).is_ok() && proof.value() == value,
+                ReadResult::NotFound => false,
                 ReadResult::RootNotFound => panic!("Root not found!"),
             };
+            assert_eq!(merkle_proof_valid, path_proof_valid);
+            ret.push(merkle_proof_valid);
         } else {
             panic!("leaves should only contain leaves")
         }
@@ -9,7 +9,10 @@
 //! [`full_tries`] modules for more info.
 
 use super::*;
-use crate::storage::error::{self, in_memory};
+use crate::storage::{
+    error::{self, in_memory},
+    trie::path_proof::TriePathProofError,
+};
 
 mod partial_tries {
     //! Here we construct 6 separate "partial" tries, increasing in size
@@ -126,3 +129,40 @@ mod full_tries {
         }
     }
 }
+
+#[test]
+fn path_proofs_should_not_verify_when_tampered() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[5]().unwrap();
+    let (other_root_hash, _) = TEST_TRIE_GENERATORS[4]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+    let txn = context.environment.create_read_txn().unwrap();
+    let key = TEST_LEAVES[2].key().unwrap();
+    let proof = match context
+        .store
+        .read_with_path_proof(correlation_id, &txn, &root_hash, key)
+        .unwrap()
+    {
+        ReadResult::Found(proof) => proof,
+        _ => panic!("should find leaf"),
+    };
+    txn.commit().unwrap();
+
+    assert!(!proof.nodes().is_empty());
+    assert_eq!(proof.verify(&root_hash), Ok(()));
+    assert!(matches!(
+        proof.verify(&other_root_hash),
+        Err(TriePathProofError::HashMismatch { index: 0 })
+    ));
+
+    let tampered = TriePathProof::new(*key, TestValue(*b"value9"), proof.nodes().to_vec());
+    assert!(matches!(
+        tampered.verify(&root_hash),
+        Err(TriePathProofError::HashMismatch { .. })
+    ));
+
+    let truncated = TriePathProof::new(*key, *proof.value(), proof.nodes()[1..].to_vec());
+    assert!(truncated.verify(&root_hash).is_err());
+
+    bytesrepr::test_serialization_roundtrip(&proof);
+}
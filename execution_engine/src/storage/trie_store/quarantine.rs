@@ -0,0 +1,154 @@
+//! Quarantining of tries whose stored bytes can't be deserialized.
+//!
+//! Only the digest of a corrupted trie can be trusted, so it can't be repaired from the local
+//! store.  When deserializing a trie read from the store fails, its digest is quarantined: reading
+//! the trie still fails, but the raw reads and existence checks used to sync global state report it
+//! missing, so that syncing fetches it from peers again like any other missing trie.  Writing the
+//! trie again releases it from quarantine.
+//!
+//! Corruption is found within read transactions, so digests are quarantined in memory at first.
+//! [`TrieQuarantine::persist`] writes them to a database of the store's own, created on first use,
+//! from which they are loaded when the store is opened again.
+use std::{
+    collections::BTreeSet,
+    convert::TryFrom,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use lmdb::{Cursor, Database, DatabaseFlags, Transaction, WriteFlags};
+use tracing::warn;
+
+use casper_hashing::Digest;
+use casper_types::bytesrepr;
+
+use crate::storage::{
+    error,
+    transaction_source::{self, lmdb::LmdbEnvironment, TransactionSource, Writable},
+};
+
+/// The quarantined digests of a trie store.
+#[derive(Debug)]
+pub(crate) struct TrieQuarantine {
+    db_name: String,
+    /// Whether any digest is quarantined, sparing reads the lock while none is.
+    any: AtomicBool,
+    state: Mutex<QuarantineState>,
+}
+
+#[derive(Debug, Default)]
+struct QuarantineState {
+    /// The database persisting the quarantined digests, once created.
+    db: Option<Database>,
+    digests: BTreeSet<Digest>,
+    /// The quarantined digests not yet written to the database.
+    unpersisted: BTreeSet<Digest>,
+}
+
+impl TrieQuarantine {
+    /// Loads the digests quarantined in the store `store_name`, if any.
+    pub(crate) fn open(env: &LmdbEnvironment, store_name: &str) -> Result<Self, error::Error> {
+        let db_name = format!("{}_QUARANTINE", store_name);
+        let mut state = QuarantineState::default();
+        match env.env().open_db(Some(&db_name)) {
+            Ok(db) => {
+                let txn = env.create_read_txn()?;
+                {
+                    let mut cursor = txn.lmdb_txn().open_ro_cursor(db)?;
+                    for row in cursor.iter_start() {
+                        let (key, _) = row?;
+                        let digest =
+                            Digest::try_from(key).map_err(|_| bytesrepr::Error::Formatting)?;
+                        state.digests.insert(digest);
+                    }
+                }
+                transaction_source::Transaction::commit(txn)?;
+                state.db = Some(db);
+            }
+            Err(lmdb::Error::NotFound) => (),
+            Err(error) => return Err(error.into()),
+        }
+        Ok(TrieQuarantine {
+            db_name,
+            any: AtomicBool::new(!state.digests.is_empty()),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Returns `true` if `digest` is quarantined.
+    pub(crate) fn contains(&self, digest: &Digest) -> bool {
+        if !self.any.load(Ordering::Acquire) {
+            return false;
+        }
+        self.state
+            .lock()
+            .map_or(false, |state| state.digests.contains(digest))
+    }
+
+    /// Quarantines `digest`, whose stored trie failed to deserialize with `error`.
+    pub(crate) fn insert(&self, digest: Digest, error: &bytesrepr::Error) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+        if state.digests.insert(digest) {
+            warn!(%digest, %error, "quarantined corrupted trie");
+            state.unpersisted.insert(digest);
+            self.any.store(true, Ordering::Release);
+        }
+    }
+
+    /// Releases `digest` from quarantine as its trie is written again, deleting it from the
+    /// database within `txn`.
+    ///
+    /// The digest is released in memory straight away.  Should `txn` not be committed, the trie is
+    /// quarantined again when next read.
+    pub(crate) fn release<T>(&self, txn: &mut T, digest: &Digest) -> Result<(), T::Error>
+    where
+        T: Writable<Handle = Database>,
+    {
+        if !self.contains(digest) {
+            return Ok(());
+        }
+        let db = match self.state.lock() {
+            Ok(mut state) => {
+                state.digests.remove(digest);
+                state.unpersisted.remove(digest);
+                self.any.store(!state.digests.is_empty(), Ordering::Release);
+                state.db
+            }
+            Err(_) => return Ok(()),
+        };
+        if let Some(db) = db {
+            txn.delete(db, digest.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Writes the digests quarantined since last persisted to the database, creating it if
+    /// needed, and returns all the quarantined digests.
+    pub(crate) fn persist(&self, env: &LmdbEnvironment) -> Result<Vec<Digest>, error::Error> {
+        let mut state = self.state.lock()?;
+        if !state.unpersisted.is_empty() {
+            let db = match state.db {
+                Some(db) => db,
+                None => env
+                    .env()
+                    .create_db(Some(&self.db_name), DatabaseFlags::empty())?,
+            };
+            state.db = Some(db);
+            env.write_with_map_growth(|| -> Result<(), error::Error> {
+                let mut txn = env.create_read_write_txn()?;
+                for digest in &state.unpersisted {
+                    txn.put(db, digest, &[], WriteFlags::empty())?;
+                }
+                txn.commit()?;
+                Ok(())
+            })?;
+            state.unpersisted.clear();
+        }
+        Ok(state.digests.iter().copied().collect())
+    }
+}
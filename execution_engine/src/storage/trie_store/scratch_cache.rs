@@ -0,0 +1,723 @@
+//! The trie cache backing a [`ScratchTrieStore`](super::lmdb::ScratchTrieStore).
+//!
+//! Tries written to the scratch store ("dirty" tries) only exist in the cache until they are
+//! committed to LMDB, so they are held separately and never evicted.  Tries read from LMDB
+//! ("clean" tries) are cached to avoid deserializing them again, but can always be re-read, so
+//! they are held in a size-bounded LRU which is split into shards to let concurrent readers avoid
+//! contending on a single lock.
+//!
+//! Sizes are estimates of the memory held by the deserialized tries and their cache entries, not
+//! their serialized lengths, which understate it several times over for small leaves and nodes.
+//!
+//! Optionally, once the dirty tries held in memory exceed a byte budget, the oldest of them are
+//! spilled to a temporary LMDB database until those left in memory take half of the budget.  The
+//! database lives until the cache is dropped; databases left behind by a crash are removed on
+//! startup by [`remove_stale_spill_dirs`].  Spilled tries are still dirty: they are served by
+//! lookups and written to global state on commit like the rest.
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io, mem,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, TryLockError,
+    },
+};
+
+use datasize::DataSize;
+use lmdb::{Database, DatabaseFlags, Environment, EnvironmentFlags, Transaction, WriteFlags};
+use rayon::prelude::*;
+use tempfile::TempDir;
+
+use casper_hashing::Digest;
+use casper_types::{
+    bytesrepr::{self, ToBytes},
+    Key, StoredValue,
+};
+
+use crate::storage::{
+    error,
+    global_state::CommitError,
+    transaction_source::lmdb::EE_DB_FILENAME,
+    trie::{PointerBlock, Trie},
+};
+
+/// The number of shards the clean tries are split into.
+const SHARD_COUNT: usize = 16;
+
+/// The prefix of the temporary directories holding spilled dirty tries.
+const SPILL_DIR_PREFIX: &str = "scratch-spill-";
+
+/// The maximum size of a spill database.  LMDB only reserves address space for it.
+const SPILL_MAP_SIZE: usize = 1 << 40; // 1 TiB
+
+/// The maximum number of concurrent readers of a spill database.
+const SPILL_MAX_READERS: u32 = 512;
+
+/// The memory taken by a cache entry besides the trie itself: its map entry and its place in the
+/// use or insertion order.
+const ENTRY_OVERHEAD: usize =
+    mem::size_of::<(Digest, (u64, u64))>() + mem::size_of::<(u64, Digest)>();
+
+/// Returns an estimate of the memory held by a cached trie, including its cache entry.
+fn in_memory_size(trie: &Trie<Key, StoredValue>) -> u64 {
+    let heap_size = match trie {
+        Trie::Leaf { key, value } => key.estimate_heap_size() + value.estimate_heap_size(),
+        Trie::Node { .. } => mem::size_of::<PointerBlock>(),
+        Trie::Extension { affix, .. } => affix.len(),
+    };
+    (mem::size_of::<Trie<Key, StoredValue>>() + heap_size + ENTRY_OVERHEAD) as u64
+}
+
+/// Clean tries cached in one shard, in least recently used order.
+#[derive(Default)]
+struct LruShard {
+    /// Cached tries with their in-memory size and last use by the shard's logical clock.
+    entries: HashMap<Digest, (Trie<Key, StoredValue>, u64, u64)>,
+    /// Cached tries by last use.
+    order: BTreeMap<u64, Digest>,
+    /// Total in-memory size of the cached tries.
+    size: u64,
+    /// Logical clock used to order uses of tries.
+    clock: u64,
+}
+
+impl LruShard {
+    fn get(&mut self, digest: &Digest) -> Option<Trie<Key, StoredValue>> {
+        self.clock += 1;
+        let clock = self.clock;
+        let (trie, _, last_used) = self.entries.get_mut(digest)?;
+        self.order.remove(last_used);
+        self.order.insert(clock, *digest);
+        *last_used = clock;
+        Some(trie.clone())
+    }
+
+    fn insert(&mut self, digest: Digest, trie: Trie<Key, StoredValue>, size: u64, max_size: u64) {
+        if size > max_size || self.entries.contains_key(&digest) {
+            return;
+        }
+        self.clock += 1;
+        self.order.insert(self.clock, digest);
+        self.entries.insert(digest, (trie, size, self.clock));
+        self.size += size;
+
+        while self.size > max_size {
+            let oldest = match self.order.keys().next() {
+                Some(oldest) => *oldest,
+                None => break,
+            };
+            if let Some(evicted) = self.order.remove(&oldest) {
+                self.remove(&evicted);
+            }
+        }
+    }
+
+    fn remove(&mut self, digest: &Digest) {
+        if let Some((_, size, last_used)) = self.entries.remove(digest) {
+            self.order.remove(&last_used);
+            self.size -= size;
+        }
+    }
+}
+
+/// Dirty tries moved out of memory into a temporary LMDB database.
+///
+/// LMDB lets the database be read while a spill is being written, so the store is shared between
+/// the threads using the cache without a lock.
+struct SpillStore {
+    // Declared before the directory so the environment is closed before the directory is removed.
+    env: Environment,
+    db: Database,
+    _dir: TempDir,
+}
+
+impl SpillStore {
+    /// Creates an empty spill database in a new temporary directory under `parent`.
+    fn open(parent: &Path) -> Result<Self, error::Error> {
+        let dir = fs::create_dir_all(parent)
+            .and_then(|_| {
+                tempfile::Builder::new()
+                    .prefix(SPILL_DIR_PREFIX)
+                    .tempdir_in(parent)
+            })
+            .map_err(|error| CommitError::ScratchSpillUnavailable(error.to_string()))?;
+        // Spilled tries don't need to survive a crash, so writes are never synced.
+        let env = Environment::new()
+            .set_flags(
+                EnvironmentFlags::NO_SUB_DIR
+                    | EnvironmentFlags::NO_READAHEAD
+                    | EnvironmentFlags::NO_SYNC
+                    | EnvironmentFlags::NO_META_SYNC,
+            )
+            .set_map_size(SPILL_MAP_SIZE)
+            .set_max_readers(SPILL_MAX_READERS)
+            .open(&dir.path().join(EE_DB_FILENAME))?;
+        let db = env.create_db(None, DatabaseFlags::empty())?;
+        Ok(SpillStore { env, db, _dir: dir })
+    }
+
+    fn put(&self, tries: &[(Digest, Vec<u8>)]) -> Result<(), error::Error> {
+        let mut txn = self.env.begin_rw_txn()?;
+        for (digest, trie_bytes) in tries {
+            txn.put(self.db, digest, trie_bytes, WriteFlags::empty())?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn get_raw(&self, digest: &Digest) -> Result<Option<Vec<u8>>, error::Error> {
+        let txn = self.env.begin_ro_txn()?;
+        let maybe_bytes = match txn.get(self.db, digest) {
+            Ok(trie_bytes) => Some(trie_bytes.to_vec()),
+            Err(lmdb::Error::NotFound) => None,
+            Err(error) => return Err(error.into()),
+        };
+        txn.commit()?;
+        Ok(maybe_bytes)
+    }
+}
+
+/// Removes the spill directories left under `dir` by scratch caches which were never dropped,
+/// e.g. because the process crashed, returning how many were removed.
+///
+/// Must not be called while any scratch cache spilling under `dir` is in use.
+pub(crate) fn remove_stale_spill_dirs(dir: &Path) -> Result<usize, error::Error> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(error) => return Err(error.into()),
+    };
+    let mut removed = 0;
+    for entry in entries {
+        let entry = entry?;
+        let is_spill_dir = entry
+            .file_name()
+            .to_str()
+            .map_or(false, |name| name.starts_with(SPILL_DIR_PREFIX));
+        if is_spill_dir && entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Where and when to spill dirty tries, and the spill database once created.
+struct Spill {
+    dir: PathBuf,
+    max_dirty_size: u64,
+    /// The spill database, created by the first spill.
+    store: Mutex<Option<Arc<SpillStore>>>,
+    /// Held while spilling, so that only one thread spills at a time.
+    spilling: Mutex<()>,
+}
+
+impl Spill {
+    /// The size the tries held in memory are brought down to by a spill: half of the budget, so
+    /// that the next few insertions don't trigger another spill right away.
+    fn low_water_size(&self) -> u64 {
+        self.max_dirty_size / 2
+    }
+
+    fn store(&self) -> Result<Option<Arc<SpillStore>>, error::Error> {
+        Ok(self.store.lock().map_err(|_| error::Error::Poison)?.clone())
+    }
+
+    /// Returns the spill database, creating it if needed.
+    fn open_store(&self) -> Result<Arc<SpillStore>, error::Error> {
+        let mut maybe_store = self.store.lock().map_err(|_| error::Error::Poison)?;
+        match &*maybe_store {
+            Some(store) => Ok(Arc::clone(store)),
+            None => {
+                let store = Arc::new(SpillStore::open(&self.dir)?);
+                *maybe_store = Some(Arc::clone(&store));
+                Ok(store)
+            }
+        }
+    }
+}
+
+/// The dirty tries, in memory in the order they were inserted, and possibly spilled.
+#[derive(Default)]
+struct DirtyTries {
+    /// Tries held in memory, with their in-memory size and insertion sequence number.  The size
+    /// is only computed when spilling is enabled.
+    in_memory: HashMap<Digest, (Trie<Key, StoredValue>, u64, u64)>,
+    /// Tries held in memory by insertion sequence number.
+    order: BTreeMap<u64, Digest>,
+    /// Total in-memory size of the tries held in memory.
+    size: u64,
+    /// The next insertion sequence number.
+    next_seq: u64,
+    /// Tries moved to the spill database.
+    spilled: HashSet<Digest>,
+}
+
+impl DirtyTries {
+    fn contains(&self, digest: &Digest) -> bool {
+        self.in_memory.contains_key(digest) || self.spilled.contains(digest)
+    }
+
+    /// Forgets the trie under `digest`, returning whether it was dirty.  A spilled trie is left in
+    /// the spill database, but can no longer be read from it.
+    fn remove(&mut self, digest: &Digest) -> bool {
+        if let Some((_, size, seq)) = self.in_memory.remove(digest) {
+            self.order.remove(&seq);
+            self.size -= size;
+            return true;
+        }
+        self.spilled.remove(digest)
+    }
+
+    /// Returns copies of the oldest tries held in memory, such that those remaining fit in
+    /// `low_water_size`.
+    fn oldest_above(&self, low_water_size: u64) -> Vec<(Digest, Trie<Key, StoredValue>)> {
+        let mut remaining_size = self.size;
+        let mut oldest = Vec::new();
+        for digest in self.order.values() {
+            if remaining_size <= low_water_size {
+                break;
+            }
+            if let Some((trie, size, _)) = self.in_memory.get(digest) {
+                remaining_size -= size;
+                oldest.push((*digest, trie.clone()));
+            }
+        }
+        oldest
+    }
+
+    /// Drops the tries under `digests` from memory, now that they are in the spill database.
+    /// Tries removed from the cache in the meantime are not marked as spilled.
+    fn mark_spilled(&mut self, digests: impl Iterator<Item = Digest>) {
+        for digest in digests {
+            if let Some((_, size, seq)) = self.in_memory.remove(&digest) {
+                self.order.remove(&seq);
+                self.size -= size;
+                self.spilled.insert(digest);
+            }
+        }
+    }
+}
+
+/// Hit and miss counts of lookups in a scratch cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ScratchCacheStats {
+    /// The number of lookups served from the cache.
+    pub(crate) hits: u64,
+    /// The number of lookups which had to read LMDB.
+    pub(crate) misses: u64,
+    /// The number of dirty tries spilled out of memory.
+    pub(crate) spilled: u64,
+}
+
+/// Cache of dirty and clean tries used by the scratch trie store.
+pub(crate) struct ScratchCache {
+    dirty: Mutex<DirtyTries>,
+    spill: Option<Spill>,
+    shards: Vec<Mutex<LruShard>>,
+    max_shard_size: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ScratchCache {
+    /// Creates an empty cache holding at most about `max_size` bytes of clean tries.
+    pub(crate) fn new(max_size: u64) -> Self {
+        ScratchCache {
+            dirty: Mutex::new(DirtyTries::default()),
+            spill: None,
+            shards: (0..SHARD_COUNT).map(|_| Mutex::default()).collect(),
+            max_shard_size: max_size / SHARD_COUNT as u64,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn shard(&self, digest: &Digest) -> Result<MutexGuard<LruShard>, error::Error> {
+        let index = digest.value()[0] as usize % SHARD_COUNT;
+        self.shards[index].lock().map_err(|_| error::Error::Poison)
+    }
+
+    /// Enables spilling dirty tries to a temporary database under `dir` once those held in memory
+    /// exceed `max_dirty_size` bytes.
+    pub(crate) fn with_spill(mut self, dir: PathBuf, max_dirty_size: u64) -> Self {
+        self.spill = Some(Spill {
+            dir,
+            max_dirty_size,
+            store: Mutex::new(None),
+            spilling: Mutex::new(()),
+        });
+        self
+    }
+
+    fn dirty(&self) -> Result<MutexGuard<DirtyTries>, error::Error> {
+        self.dirty.lock().map_err(|_| error::Error::Poison)
+    }
+
+    /// Returns the spill database, if the trie under `digest` has been spilled to it.
+    fn spill_store_holding(
+        &self,
+        digest: &Digest,
+    ) -> Result<Option<Arc<SpillStore>>, error::Error> {
+        match &self.spill {
+            Some(spill) if self.dirty()?.spilled.contains(digest) => spill.store(),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the dirty trie under `digest`, reading it from the spill database if needed.
+    fn get_dirty(&self, digest: &Digest) -> Result<Option<Trie<Key, StoredValue>>, error::Error> {
+        if let Some((trie, _, _)) = self.dirty()?.in_memory.get(digest) {
+            return Ok(Some(trie.clone()));
+        }
+        // The spill database is read without holding the lock on the dirty tries.
+        match self.spill_store_holding(digest)? {
+            Some(store) => match store.get_raw(digest)? {
+                Some(trie_bytes) => Ok(Some(bytesrepr::deserialize(trie_bytes)?)),
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the hashes of the children of the dirty trie under `digest`, or `None` if the trie
+    /// isn't dirty.
+    pub(crate) fn dirty_descendants(
+        &self,
+        digest: &Digest,
+    ) -> Result<Option<Vec<Digest>>, error::Error> {
+        Ok(self
+            .get_dirty(digest)?
+            .map(|trie| trie.iter_descendants().collect()))
+    }
+
+    /// Serializes the dirty tries under `digests` in parallel, checking that each one hashes to
+    /// its digest.
+    pub(crate) fn serialize_dirty(
+        &self,
+        digests: &[Digest],
+    ) -> Result<Vec<(Digest, Vec<u8>)>, error::Error> {
+        // Tries held in memory are copied out, so that neither serializing them nor reading the
+        // spilled ones happens while holding the lock on the dirty tries.
+        let in_memory: HashMap<Digest, Trie<Key, StoredValue>> = {
+            let dirty = self.dirty()?;
+            digests
+                .iter()
+                .filter_map(|digest| {
+                    dirty
+                        .in_memory
+                        .get(digest)
+                        .map(|(trie, _, _)| (*digest, trie.clone()))
+                })
+                .collect()
+        };
+        let spill_store = match &self.spill {
+            Some(spill) => spill.store()?,
+            None => None,
+        };
+        digests
+            .par_iter()
+            .map(|digest| -> Result<_, error::Error> {
+                let trie_bytes = match in_memory.get(digest) {
+                    Some(trie) => trie.to_bytes()?,
+                    None => spill_store
+                        .as_ref()
+                        .map(|store| store.get_raw(digest))
+                        .transpose()?
+                        .flatten()
+                        .ok_or(CommitError::TrieNotFoundInCache(*digest))?,
+                };
+                if Digest::hash_into_chunks_if_necessary(&trie_bytes) != *digest {
+                    return Err(CommitError::TrieHashMismatch(*digest).into());
+                }
+                Ok((*digest, trie_bytes))
+            })
+            .collect()
+    }
+
+    /// Returns the cached trie under `digest`, whether dirty or clean.
+    pub(crate) fn get(
+        &self,
+        digest: &Digest,
+    ) -> Result<Option<Trie<Key, StoredValue>>, error::Error> {
+        let mut maybe_trie = self.get_dirty(digest)?;
+        if maybe_trie.is_none() {
+            maybe_trie = self.shard(digest)?.get(digest);
+        }
+        let counter = if maybe_trie.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        Ok(maybe_trie)
+    }
+
+    /// Returns `true` if the trie under `digest` is cached, whether dirty or clean, without
+    /// counting a lookup or marking it as used.
+    pub(crate) fn contains(&self, digest: &Digest) -> Result<bool, error::Error> {
+        if self.dirty()?.contains(digest) {
+            return Ok(true);
+        }
+        Ok(self.shard(digest)?.entries.contains_key(digest))
+    }
+
+    /// Drops the trie under `digest` from the cache, returning whether it was cached.
+    pub(crate) fn remove(&self, digest: &Digest) -> Result<bool, error::Error> {
+        let was_dirty = self.dirty()?.remove(digest);
+        let mut shard = self.shard(digest)?;
+        let was_clean = shard.entries.contains_key(digest);
+        shard.remove(digest);
+        Ok(was_dirty || was_clean)
+    }
+
+    /// Caches a trie written to the scratch store, spilling the oldest dirty tries if those held
+    /// in memory exceed the budget.
+    pub(crate) fn insert_dirty(
+        &self,
+        digest: Digest,
+        trie: Trie<Key, StoredValue>,
+    ) -> Result<(), error::Error> {
+        let over_budget = {
+            let mut dirty = self.dirty()?;
+            if !dirty.contains(&digest) {
+                let size = if self.spill.is_some() {
+                    in_memory_size(&trie)
+                } else {
+                    0
+                };
+                let seq = dirty.next_seq;
+                dirty.next_seq += 1;
+                dirty.order.insert(seq, digest);
+                dirty.in_memory.insert(digest, (trie, size, seq));
+                dirty.size += size;
+            }
+            self.spill
+                .as_ref()
+                .map_or(false, |spill| dirty.size > spill.max_dirty_size)
+        };
+        self.shard(&digest)?.remove(&digest);
+        if over_budget {
+            self.spill_oldest()?;
+        }
+        Ok(())
+    }
+
+    /// Moves the oldest dirty tries held in memory to the spill database until those remaining
+    /// fit in the low-water mark.
+    ///
+    /// The lock on the dirty tries is only held to pick the tries to spill and to drop them from
+    /// memory once written, so lookups aren't blocked while spilling.  Until then, the tries being
+    /// spilled are still served from memory.
+    fn spill_oldest(&self) -> Result<(), error::Error> {
+        let spill = match &self.spill {
+            Some(spill) => spill,
+            None => return Ok(()),
+        };
+        // A spill in progress on another thread brings the tries in memory down anyway.
+        let _spilling = match spill.spilling.try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) => return Ok(()),
+            Err(TryLockError::Poisoned(_)) => return Err(error::Error::Poison),
+        };
+        let to_spill = self.dirty()?.oldest_above(spill.low_water_size());
+        if to_spill.is_empty() {
+            return Ok(());
+        }
+        let serialized = to_spill
+            .into_par_iter()
+            .map(|(digest, trie)| -> Result<_, error::Error> { Ok((digest, trie.into_bytes()?)) })
+            .collect::<Result<Vec<_>, _>>()?;
+        spill.open_store()?.put(&serialized)?;
+        self.dirty()?
+            .mark_spilled(serialized.into_iter().map(|(digest, _)| digest));
+        Ok(())
+    }
+
+    /// Caches a trie read from LMDB, evicting the least recently used clean tries of its shard if
+    /// needed.
+    pub(crate) fn insert_clean(
+        &self,
+        digest: Digest,
+        trie: Trie<Key, StoredValue>,
+    ) -> Result<(), error::Error> {
+        if self.dirty()?.contains(&digest) {
+            return Ok(());
+        }
+        let size = in_memory_size(&trie);
+        self.shard(&digest)?
+            .insert(digest, trie, size, self.max_shard_size);
+        Ok(())
+    }
+
+    /// Returns the hit and miss counts of lookups and the number of spilled tries so far.
+    pub(crate) fn stats(&self) -> Result<ScratchCacheStats, error::Error> {
+        Ok(ScratchCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            spilled: self.dirty()?.spilled.len() as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{bytesrepr::ToBytes, CLValue};
+
+    use super::*;
+
+    fn leaf(i: u8) -> (Digest, Trie<Key, StoredValue>, u64) {
+        let trie = Trie::leaf(
+            Key::Hash([i; 32]),
+            StoredValue::CLValue(CLValue::from_t(i32::from(i)).unwrap()),
+        );
+        let size = in_memory_size(&trie);
+        (trie.trie_hash().unwrap(), trie, size)
+    }
+
+    #[test]
+    fn should_evict_least_recently_used_clean_tries() {
+        let mut shard = LruShard::default();
+        let (digest_1, trie_1, size) = leaf(1);
+        let (digest_2, trie_2, _) = leaf(2);
+        let (digest_3, trie_3, _) = leaf(3);
+        shard.insert(digest_1, trie_1, size, 2 * size);
+        shard.insert(digest_2, trie_2, size, 2 * size);
+
+        // Using the first trie makes the second the least recently used.
+        assert!(shard.get(&digest_1).is_some());
+        shard.insert(digest_3, trie_3, size, 2 * size);
+        assert!(shard.get(&digest_2).is_none());
+        assert!(shard.get(&digest_1).is_some());
+        assert!(shard.get(&digest_3).is_some());
+        assert_eq!(shard.size, 2 * size);
+    }
+
+    #[test]
+    fn should_never_evict_dirty_tries() {
+        let cache = ScratchCache::new(0);
+        let tries: Vec<_> = (0..10).map(leaf).collect();
+        for (digest, trie, _) in tries.iter().cloned() {
+            cache.insert_dirty(digest, trie).unwrap();
+        }
+        let (clean_digest, clean_trie, _) = leaf(10);
+        cache.insert_clean(clean_digest, clean_trie).unwrap();
+
+        for (digest, trie, _) in tries {
+            assert_eq!(cache.get(&digest).unwrap(), Some(trie));
+        }
+        assert_eq!(cache.get(&clean_digest).unwrap(), None);
+        assert_eq!(
+            cache.stats().unwrap(),
+            ScratchCacheStats {
+                hits: 10,
+                misses: 1,
+                spilled: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn should_spill_oldest_dirty_tries() {
+        let spill_dir = tempfile::tempdir().unwrap();
+        let (_, _, size) = leaf(0);
+        let cache = ScratchCache::new(0).with_spill(spill_dir.path().to_path_buf(), 4 * size);
+        let tries: Vec<_> = (0..10).map(leaf).collect();
+        for (digest, trie, _) in tries.iter().cloned() {
+            cache.insert_dirty(digest, trie).unwrap();
+        }
+        // The fifth and the eighth insertion each exceed the budget, and spill the oldest tries
+        // until only two are left in memory.
+        assert_eq!(cache.stats().unwrap().spilled, 6);
+        {
+            let dirty = cache.dirty().unwrap();
+            assert_eq!(dirty.size, 4 * size);
+            assert!(!dirty.in_memory.contains_key(&tries[0].0));
+            assert!(dirty.in_memory.contains_key(&tries[9].0));
+        }
+
+        // Spilled tries are still served, and serialized for commit.
+        let digests: Vec<Digest> = tries.iter().map(|(digest, _, _)| *digest).collect();
+        for (digest, trie, _) in &tries {
+            assert_eq!(cache.get(digest).unwrap().as_ref(), Some(trie));
+        }
+        let serialized = cache.serialize_dirty(&digests).unwrap();
+        for ((digest, trie, _), (serialized_digest, trie_bytes)) in tries.iter().zip(serialized) {
+            assert_eq!(*digest, serialized_digest);
+            assert_eq!(trie.to_bytes().unwrap(), trie_bytes);
+        }
+    }
+
+    #[test]
+    fn should_remove_dirty_spilled_and_clean_tries() {
+        let spill_dir = tempfile::tempdir().unwrap();
+        let (_, _, size) = leaf(0);
+        let cache = ScratchCache::new(SHARD_COUNT as u64 * size)
+            .with_spill(spill_dir.path().to_path_buf(), 2 * size);
+        let (spilled_digest, spilled_trie, _) = leaf(1);
+        let (other_spilled_digest, other_spilled_trie, _) = leaf(4);
+        let (dirty_digest, dirty_trie, _) = leaf(2);
+        let (clean_digest, clean_trie, _) = leaf(3);
+        cache.insert_dirty(spilled_digest, spilled_trie).unwrap();
+        cache
+            .insert_dirty(other_spilled_digest, other_spilled_trie)
+            .unwrap();
+        cache.insert_dirty(dirty_digest, dirty_trie).unwrap();
+        cache.insert_clean(clean_digest, clean_trie).unwrap();
+        assert_eq!(cache.stats().unwrap().spilled, 2);
+
+        for digest in &[spilled_digest, dirty_digest, clean_digest] {
+            assert!(cache.contains(digest).unwrap());
+            assert!(cache.remove(digest).unwrap());
+            assert!(!cache.contains(digest).unwrap());
+            assert!(!cache.remove(digest).unwrap());
+            assert_eq!(cache.get(digest).unwrap(), None);
+        }
+        let dirty = cache.dirty().unwrap();
+        assert!(dirty.order.is_empty());
+        assert_eq!(dirty.size, 0);
+    }
+
+    #[test]
+    fn should_remove_stale_spill_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_, _, size) = leaf(0);
+        let cache = ScratchCache::new(0).with_spill(dir.path().to_path_buf(), size);
+        for (digest, trie, _) in (0..3).map(leaf) {
+            cache.insert_dirty(digest, trie).unwrap();
+        }
+        assert!(cache.stats().unwrap().spilled > 0);
+        // Leaking the cache leaves its spill directory behind, as a crash would.
+        mem::forget(cache);
+        let other_dir = dir.path().join("other");
+        fs::create_dir(&other_dir).unwrap();
+
+        assert_eq!(remove_stale_spill_dirs(dir.path()).unwrap(), 1);
+        let remaining: Vec<PathBuf> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(remaining, vec![other_dir]);
+        assert_eq!(
+            remove_stale_spill_dirs(&dir.path().join("missing")).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn should_budget_by_in_memory_size() {
+        let (_, trie, size) = leaf(0);
+        assert!(size > trie.serialized_length() as u64);
+
+        // A budget of one trie's serialized length per shard doesn't fit any trie.
+        let cache = ScratchCache::new(SHARD_COUNT as u64 * trie.serialized_length() as u64);
+        let tries: Vec<_> = (0..10).map(leaf).collect();
+        for (digest, trie, _) in tries.iter().cloned() {
+            cache.insert_clean(digest, trie).unwrap();
+        }
+        for (digest, _, _) in &tries {
+            assert!(!cache.contains(digest).unwrap());
+        }
+    }
+}
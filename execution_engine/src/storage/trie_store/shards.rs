@@ -0,0 +1,261 @@
+//! Optional sharding of the leaves held in an LMDB trie store by the variant of their key.
+//!
+//! Balances are rewritten by nearly every deploy, while contract Wasm leaves are large and
+//! rarely touched.  Kept in one database, both share a single B-tree, so writing a balance leaf
+//! pages through a tree made deep and sparse by the Wasm.  With sharding enabled, leaves under
+//! account, balance, contract (`Key::Hash`) and dictionary keys are written to a database of their
+//! own, while nodes, extensions and the leaves of other keys stay in the main database.
+//!
+//! A trie's digest doesn't tell which database it is in, but the pointer to it does: a node
+//! pointer leads to the main database, and a leaf pointer to the shard of the key tag the path to
+//! it starts with.  Reads walking down from a state root therefore look in a single database, see
+//! [`TrieLocation`](super::TrieLocation), and only a trie read by its digest alone is looked for
+//! in every database.
+//!
+//! Whether a store is sharded is told by the presence of its shard databases, which are created
+//! when sharding is enabled and opened with the store from then on.  Enabling sharding moves the
+//! leaves already written into their shards, and disabling it moves them back and drops the
+//! shards.  Leaves are moved in batches, and the move is only recorded as done once all of them
+//! are, so an interrupted move carries on when sharding is next enabled; until then, leaves are
+//! also looked for in the main database.
+use lmdb::{Database, DatabaseFlags, Transaction, WriteFlags};
+
+use casper_types::{Key, KeyTag, StoredValue};
+
+use crate::storage::{
+    error,
+    transaction_source::{self, lmdb::LmdbEnvironment, TransactionSource},
+    trie::Trie,
+    trie_store::compression::METADATA_DB_NAME,
+};
+
+/// The number of shard databases of a sharded store.
+pub(crate) const SHARD_COUNT: usize = 4;
+
+/// A database holding the leaves under one kind of key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TrieShard {
+    /// Leaves under `Key::Account`.
+    Accounts = 0,
+    /// Leaves under `Key::Balance`.
+    Balances = 1,
+    /// Leaves under `Key::Hash`, holding contracts, contract packages and Wasm.
+    Contracts = 2,
+    /// Leaves under `Key::Dictionary`.
+    Dictionaries = 3,
+}
+
+impl TrieShard {
+    const ALL: [TrieShard; SHARD_COUNT] = [
+        TrieShard::Accounts,
+        TrieShard::Balances,
+        TrieShard::Contracts,
+        TrieShard::Dictionaries,
+    ];
+
+    /// Returns the shard the serialized trie `trie_bytes` belongs in, or `None` if it belongs in
+    /// the main database.
+    pub(crate) fn of(trie_bytes: &[u8]) -> Option<TrieShard> {
+        match trie_bytes {
+            [tag, key_tag, ..] if *tag == Trie::<Key, StoredValue>::LEAF_TAG => {
+                TrieShard::of_key_tag(*key_tag)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the shard a leaf whose serialized key starts with `key_tag` belongs in, or `None`
+    /// if it belongs in the main database.
+    pub(crate) fn of_key_tag(key_tag: u8) -> Option<TrieShard> {
+        if key_tag == KeyTag::Account as u8 {
+            Some(TrieShard::Accounts)
+        } else if key_tag == KeyTag::Balance as u8 {
+            Some(TrieShard::Balances)
+        } else if key_tag == KeyTag::Hash as u8 {
+            Some(TrieShard::Contracts)
+        } else if key_tag == KeyTag::Dictionary as u8 {
+            Some(TrieShard::Dictionaries)
+        } else {
+            None
+        }
+    }
+
+    fn db_name(self, store_name: &str) -> String {
+        let suffix = match self {
+            TrieShard::Accounts => "ACCOUNTS",
+            TrieShard::Balances => "BALANCES",
+            TrieShard::Contracts => "CONTRACTS",
+            TrieShard::Dictionaries => "DICTIONARIES",
+        };
+        format!("{}_{}", store_name, suffix)
+    }
+}
+
+/// The key of the metadata record marking the leaves of the store `store_name` as all moved into
+/// their shards.
+fn migrated_marker(store_name: &str) -> String {
+    format!("{}_SHARDED", store_name)
+}
+
+/// The shard databases of a sharded trie store.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TrieShards {
+    dbs: [Database; SHARD_COUNT],
+    /// Whether every leaf has been moved out of the main database into its shard.
+    migrated: bool,
+}
+
+impl TrieShards {
+    /// Opens the shard databases of the store `store_name`, creating them if missing.
+    pub(crate) fn create(env: &LmdbEnvironment, store_name: &str) -> Result<Self, error::Error> {
+        let create = |shard: TrieShard| {
+            env.env()
+                .create_db(Some(&shard.db_name(store_name)), DatabaseFlags::empty())
+        };
+        Ok(TrieShards {
+            dbs: [
+                create(TrieShard::Accounts)?,
+                create(TrieShard::Balances)?,
+                create(TrieShard::Contracts)?,
+                create(TrieShard::Dictionaries)?,
+            ],
+            migrated: Self::read_migrated(env, store_name)?,
+        })
+    }
+
+    /// Opens the shard databases of the store `store_name` without creating them, returning
+    /// `None` if the store isn't sharded.
+    pub(crate) fn open_existing(
+        env: &LmdbEnvironment,
+        store_name: &str,
+    ) -> Result<Option<Self>, error::Error> {
+        let mut dbs = Vec::with_capacity(SHARD_COUNT);
+        for shard in TrieShard::ALL {
+            match env.env().open_db(Some(&shard.db_name(store_name))) {
+                Ok(db) => dbs.push(db),
+                Err(lmdb::Error::NotFound) => return Ok(None),
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Ok(Some(TrieShards {
+            dbs: [dbs[0], dbs[1], dbs[2], dbs[3]],
+            migrated: Self::read_migrated(env, store_name)?,
+        }))
+    }
+
+    fn read_migrated(env: &LmdbEnvironment, store_name: &str) -> Result<bool, error::Error> {
+        let db = match env.env().open_db(Some(METADATA_DB_NAME)) {
+            Ok(db) => db,
+            Err(lmdb::Error::NotFound) => return Ok(false),
+            Err(error) => return Err(error.into()),
+        };
+        let txn = env.create_read_txn()?;
+        let migrated = match txn.lmdb_txn().get(db, &migrated_marker(store_name)) {
+            Ok(_) => true,
+            Err(lmdb::Error::NotFound) => false,
+            Err(error) => return Err(error.into()),
+        };
+        transaction_source::Transaction::commit(txn)?;
+        Ok(migrated)
+    }
+
+    /// Returns `true` if every leaf has been moved into its shard, so that none is left in the
+    /// main database.
+    pub(crate) fn is_migrated(&self) -> bool {
+        self.migrated
+    }
+
+    /// Records whether every leaf of the store `store_name` has been moved into its shard.
+    pub(crate) fn set_migrated(
+        &mut self,
+        env: &LmdbEnvironment,
+        store_name: &str,
+        migrated: bool,
+    ) -> Result<(), error::Error> {
+        let db = env
+            .env()
+            .create_db(Some(METADATA_DB_NAME), DatabaseFlags::empty())?;
+        env.write_with_map_growth(|| -> Result<(), error::Error> {
+            let mut txn = env.create_read_write_txn()?;
+            let marker = migrated_marker(store_name);
+            if migrated {
+                txn.put(db, &marker, &[1u8], WriteFlags::empty())?;
+            } else {
+                match txn.del(db, &marker, None) {
+                    Ok(()) | Err(lmdb::Error::NotFound) => (),
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            txn.commit()?;
+            Ok(())
+        })?;
+        self.migrated = migrated;
+        Ok(())
+    }
+
+    /// Drops the shard databases, which must have been emptied.
+    pub(crate) fn drop_dbs(self, env: &LmdbEnvironment) -> Result<(), error::Error> {
+        let mut txn = env.create_read_write_txn()?;
+        for db in self.dbs {
+            // Safe as the handles are dropped along with `self`, and the store holding them stops
+            // being sharded.
+            unsafe { txn.drop_db(db)? };
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Returns the database of `shard`.
+    pub(crate) fn db(&self, shard: TrieShard) -> Database {
+        self.dbs[shard as usize]
+    }
+
+    /// Returns the shard databases.
+    pub(crate) fn dbs(&self) -> [Database; SHARD_COUNT] {
+        self.dbs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_hashing::Digest;
+    use casper_types::{account::AccountHash, bytesrepr::ToBytes, CLValue, EraId, URefAddr};
+
+    use super::*;
+    use crate::storage::trie::Pointer;
+
+    fn leaf_bytes(key: Key) -> Vec<u8> {
+        let value = StoredValue::CLValue(CLValue::from_t(()).unwrap());
+        Trie::Leaf { key, value }.to_bytes().unwrap()
+    }
+
+    #[test]
+    fn should_shard_leaves_by_key_tag() {
+        assert_eq!(
+            TrieShard::of(&leaf_bytes(Key::Account(AccountHash::new([1; 32])))),
+            Some(TrieShard::Accounts)
+        );
+        assert_eq!(
+            TrieShard::of(&leaf_bytes(Key::Balance(URefAddr::default()))),
+            Some(TrieShard::Balances)
+        );
+        assert_eq!(
+            TrieShard::of(&leaf_bytes(Key::Hash([2; 32]))),
+            Some(TrieShard::Contracts)
+        );
+        assert_eq!(
+            TrieShard::of(&leaf_bytes(Key::Dictionary([3; 32]))),
+            Some(TrieShard::Dictionaries)
+        );
+        assert_eq!(
+            TrieShard::of(&leaf_bytes(Key::EraInfo(EraId::new(1)))),
+            None
+        );
+        // Nodes and extensions stay in the main database.
+        let extension: Trie<Key, StoredValue> = Trie::Extension {
+            affix: vec![0u8].into(),
+            pointer: Pointer::NodePointer(Digest::hash([0])),
+        };
+        assert_eq!(TrieShard::of(&extension.to_bytes().unwrap()), None);
+    }
+}
@@ -0,0 +1,144 @@
+//! Statistics about the tries held in a trie store.
+//!
+//! Statistics are gathered by `LmdbTrieStore::stat`, for operators sizing a node's global state or
+//! judging whether compacting the database would be worthwhile.
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+use serde::Serialize;
+
+use casper_hashing::Digest;
+
+/// The shape of the trie below a single state root.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TrieDepthStats {
+    /// The state root the trie was walked from.
+    pub state_root: Digest,
+    /// The number of leaves at each depth, the state root being at depth 0.
+    pub leaf_depths: BTreeMap<usize, u64>,
+    /// The number of tries referenced below the state root but missing from the store.
+    pub missing_tries: u64,
+}
+
+/// Statistics about the tries held in a trie store.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TrieStoreStats {
+    /// The number of entries in the store.
+    pub entries: u64,
+    /// The total size of the keys and values of all entries as stored, in bytes.
+    pub total_bytes: u64,
+    /// The number of entries stored compressed.
+    pub compressed: u64,
+    /// The number of leaves.
+    pub leaves: u64,
+    /// The number of nodes.
+    pub nodes: u64,
+    /// The number of extensions.
+    pub extensions: u64,
+    /// The number of entries which couldn't be parsed as tries.
+    pub unparsable: u64,
+    /// The number of nodes with each number of children.
+    pub branching_factors: BTreeMap<usize, u64>,
+    /// The shape of the trie below the requested state root, if any.
+    pub depths: Option<TrieDepthStats>,
+}
+
+/// The progress of gathering [`TrieStoreStats`], which is advanced over several read transactions
+/// by `LmdbTrieStore::stat`.
+#[derive(Debug)]
+pub struct TrieStoreStatWalk {
+    pub(crate) stats: TrieStoreStats,
+    /// The index of the database being scanned.
+    pub(crate) db_index: usize,
+    /// The key to resume scanning the database from.
+    pub(crate) resume_from: Option<Vec<u8>>,
+    /// The tries left to visit below the state root, along with their depths.
+    pub(crate) to_visit: Vec<(Digest, usize)>,
+    pub(crate) finished: bool,
+}
+
+impl TrieStoreStatWalk {
+    /// Starts gathering statistics, walking the trie below `state_root` too if given.
+    pub fn new(state_root: Option<Digest>) -> Self {
+        let stats = TrieStoreStats {
+            depths: state_root.map(|state_root| TrieDepthStats {
+                state_root,
+                ..TrieDepthStats::default()
+            }),
+            ..TrieStoreStats::default()
+        };
+        TrieStoreStatWalk {
+            stats,
+            db_index: 0,
+            resume_from: None,
+            to_visit: state_root
+                .map(|state_root| (state_root, 0))
+                .into_iter()
+                .collect(),
+            finished: false,
+        }
+    }
+
+    /// Returns `true` once every trie has been read.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the statistics gathered.
+    pub fn into_stats(self) -> TrieStoreStats {
+        self.stats
+    }
+}
+
+impl TrieStoreStats {
+    /// Returns the mean number of children of the nodes in the store.
+    pub fn mean_branching_factor(&self) -> f64 {
+        if self.nodes == 0 {
+            return 0.0;
+        }
+        let children: u64 = self
+            .branching_factors
+            .iter()
+            .map(|(children, count)| *children as u64 * count)
+            .sum();
+        children as f64 / self.nodes as f64
+    }
+}
+
+impl Display for TrieStoreStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} entries ({} compressed), {} bytes: {} leaves, {} nodes, {} extensions, {} \
+            unparsable",
+            self.entries,
+            self.compressed,
+            self.total_bytes,
+            self.leaves,
+            self.nodes,
+            self.extensions,
+            self.unparsable
+        )?;
+        writeln!(
+            f,
+            "children per node (mean {:.2}):",
+            self.mean_branching_factor()
+        )?;
+        for (children, count) in &self.branching_factors {
+            writeln!(f, "  {:>3}: {}", children, count)?;
+        }
+        if let Some(depths) = &self.depths {
+            writeln!(
+                f,
+                "leaves by depth below state root {} ({} tries missing):",
+                depths.state_root, depths.missing_tries
+            )?;
+            for (depth, count) in &depths.leaf_depths {
+                writeln!(f, "  {:>3}: {}", depth, count)?;
+            }
+        }
+        Ok(())
+    }
+}
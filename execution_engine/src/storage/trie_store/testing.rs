@@ -0,0 +1,627 @@
+//! A deterministic property suite for trie stores.
+//!
+//! Whatever its backend, a [`TrieStore`] must give the same answers and produce the same root
+//! hashes for the same sequence of writes.  This module generates sequences of inserts, deletes and
+//! lookups from a seed and runs them against a store, checking every outcome against a
+//! [`TrieOracle`]: an ordered map holding what the trie should hold.  Keys are drawn from a narrow
+//! alphabet so that they share long prefixes, exercising the splitting and collapsing of extensions
+//! and nodes rather than only leaves hung off the root.
+//!
+//! A failing sequence is reproduced from its seed alone.  [`check_store`] validates a single store,
+//! while [`check_against_in_memory`] also requires a store to produce the same root after every
+//! operation as the [`InMemoryTrieStore`].  This is how the LMDB store is validated in each of its
+//! [`LmdbVariant`]s, and how any other backend can be.
+use std::{collections::BTreeMap, fmt::Display, ops::RangeInclusive};
+
+use lmdb::DatabaseFlags;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use tempfile::tempdir;
+use thiserror::Error;
+
+use casper_hashing::Digest;
+use casper_types::bytesrepr::{self, FromBytes, ToBytes};
+
+use crate::{
+    shared::newtypes::CorrelationId,
+    storage::{
+        error::{self, in_memory},
+        transaction_source::{
+            in_memory::InMemoryEnvironment, lmdb::LmdbEnvironment, Transaction, TransactionSource,
+        },
+        trie::Trie,
+        trie_store::{
+            compression::DEFAULT_COMPRESSION_LEVEL,
+            in_memory::InMemoryTrieStore,
+            lmdb::LmdbTrieStore,
+            operations::{self, DeleteResult, ReadResult, WriteResult},
+            TrieStore,
+        },
+        DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS,
+    },
+};
+
+/// The length of a [`TestKey`].
+pub const TEST_KEY_LENGTH: usize = 8;
+/// The bytes keys are drawn from, including both ends of the range of a byte.
+const KEY_ALPHABET: [u8; 4] = [0, 1, 2, u8::MAX];
+/// The maximum length of a [`TestValue`], except for the occasional large one.
+const MAX_VALUE_LENGTH: usize = 16;
+/// The range of lengths of a large [`TestValue`], whose leaves are long enough to be compressed.
+const LARGE_VALUE_LENGTHS: RangeInclusive<usize> = 256..=512;
+/// The probability of a [`TestValue`] being large.
+const LARGE_VALUE_PROBABILITY: f64 = 0.05;
+
+/// A key of the tries under test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TestKey(pub [u8; TEST_KEY_LENGTH]);
+
+impl ToBytes for TestKey {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        Ok(self.0.to_vec())
+    }
+
+    fn serialized_length(&self) -> usize {
+        TEST_KEY_LENGTH
+    }
+}
+
+impl FromBytes for TestKey {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        if bytes.len() < TEST_KEY_LENGTH {
+            return Err(bytesrepr::Error::EarlyEndOfStream);
+        }
+        let (key, remainder) = bytes.split_at(TEST_KEY_LENGTH);
+        let mut array = [0; TEST_KEY_LENGTH];
+        array.copy_from_slice(key);
+        Ok((TestKey(array), remainder))
+    }
+}
+
+/// A value of the tries under test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestValue(pub Vec<u8>);
+
+impl ToBytes for TestValue {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+impl FromBytes for TestValue {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (value, remainder) = Vec::<u8>::from_bytes(bytes)?;
+        Ok((TestValue(value), remainder))
+    }
+}
+
+/// An operation on a trie.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrieOp {
+    /// Writes the value under the key.
+    Insert(TestKey, TestValue),
+    /// Deletes the key.
+    Delete(TestKey),
+    /// Reads the value under the key.
+    Lookup(TestKey),
+}
+
+/// The outcome of a [`TrieOp`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrieOpOutcome {
+    /// The value was written, giving a new root.
+    Written,
+    /// The key already held the value, leaving the root unchanged.
+    AlreadyExists,
+    /// The key was deleted, giving a new root.
+    Deleted,
+    /// The key to delete wasn't in the trie.
+    DoesNotExist,
+    /// The key held the value.
+    Found(TestValue),
+    /// The key wasn't in the trie.
+    NotFound,
+    /// The root the operation applied to wasn't in the store.
+    RootNotFound,
+}
+
+/// Generates operations on a trie from a seed.
+#[derive(Debug)]
+pub struct TrieOpGenerator {
+    rng: ChaChaRng,
+    /// The pairs inserted so far, which later operations mostly target.
+    inserted: Vec<(TestKey, TestValue)>,
+}
+
+impl TrieOpGenerator {
+    /// Creates a generator drawing operations from an RNG seeded with `seed`.
+    pub fn new(seed: u64) -> Self {
+        TrieOpGenerator {
+            rng: ChaChaRng::seed_from_u64(seed),
+            inserted: Vec::new(),
+        }
+    }
+
+    /// Generates a key.
+    pub fn key(&mut self) -> TestKey {
+        let mut key = [0; TEST_KEY_LENGTH];
+        for byte in key.iter_mut() {
+            *byte = KEY_ALPHABET[self.rng.gen_range(0..KEY_ALPHABET.len())];
+        }
+        TestKey(key)
+    }
+
+    /// Generates a value of up to 16 bytes, or now and then of 256 to 512 bytes.
+    pub fn value(&mut self) -> TestValue {
+        let length = if self.rng.gen_bool(LARGE_VALUE_PROBABILITY) {
+            self.rng.gen_range(LARGE_VALUE_LENGTHS)
+        } else {
+            self.rng.gen_range(0..=MAX_VALUE_LENGTH)
+        };
+        TestValue((0..length).map(|_| self.rng.gen()).collect())
+    }
+
+    /// Generates `count` pairs to populate a trie with, some of which share their key.
+    pub fn pairs(&mut self, count: usize) -> Vec<(TestKey, TestValue)> {
+        (0..count).map(|_| (self.key(), self.value())).collect()
+    }
+
+    /// Generates the next operation.
+    ///
+    /// Half the operations are inserts, a quarter of which target a key inserted earlier, either
+    /// with a new value or with the value it was inserted with.  The rest are split evenly between
+    /// deletes and lookups, three quarters of which target a key inserted earlier.
+    pub fn next_op(&mut self) -> TrieOp {
+        let roll = self.rng.gen_range(0..4);
+        let reuse_probability = if roll < 2 { 0.25 } else { 0.75 };
+        let earlier = if !self.inserted.is_empty() && self.rng.gen_bool(reuse_probability) {
+            Some(self.inserted[self.rng.gen_range(0..self.inserted.len())].clone())
+        } else {
+            None
+        };
+        match (roll, earlier) {
+            (0 | 1, Some(pair)) if self.rng.gen_bool(0.5) => TrieOp::Insert(pair.0, pair.1),
+            (0 | 1, earlier) => {
+                let key = earlier.map_or_else(|| self.key(), |(key, _)| key);
+                let value = self.value();
+                self.inserted.push((key, value.clone()));
+                TrieOp::Insert(key, value)
+            }
+            (2, earlier) => TrieOp::Delete(earlier.map_or_else(|| self.key(), |(key, _)| key)),
+            (_, earlier) => TrieOp::Lookup(earlier.map_or_else(|| self.key(), |(key, _)| key)),
+        }
+    }
+
+    /// Generates the next `count` operations.
+    pub fn ops(&mut self, count: usize) -> Vec<TrieOp> {
+        (0..count).map(|_| self.next_op()).collect()
+    }
+}
+
+/// What a trie should hold after a sequence of operations.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrieOracle(BTreeMap<TestKey, TestValue>);
+
+impl TrieOracle {
+    /// Applies `op`, returning the outcome a trie store should report for it.
+    pub fn apply(&mut self, op: &TrieOp) -> TrieOpOutcome {
+        match op {
+            TrieOp::Insert(key, value) => {
+                if self.0.get(key) == Some(value) {
+                    return TrieOpOutcome::AlreadyExists;
+                }
+                self.0.insert(*key, value.clone());
+                TrieOpOutcome::Written
+            }
+            TrieOp::Delete(key) => match self.0.remove(key) {
+                Some(_) => TrieOpOutcome::Deleted,
+                None => TrieOpOutcome::DoesNotExist,
+            },
+            TrieOp::Lookup(key) => match self.0.get(key) {
+                Some(value) => TrieOpOutcome::Found(value.clone()),
+                None => TrieOpOutcome::NotFound,
+            },
+        }
+    }
+
+    /// Returns the keys the trie should hold, in order.
+    pub fn keys(&self) -> Vec<TestKey> {
+        self.0.keys().copied().collect()
+    }
+}
+
+/// An error found while checking a trie store.
+#[derive(Debug, Error)]
+pub enum TrieCheckError {
+    /// The store failed.
+    #[error("store error: {0}")]
+    Store(String),
+    /// An operation had an outcome other than the oracle's.
+    #[error("operation {step} ({op:?}) returned {actual:?} instead of {expected:?}")]
+    OutcomeMismatch {
+        /// The index of the operation.
+        step: usize,
+        /// The operation.
+        op: TrieOp,
+        /// The outcome reported by the oracle.
+        expected: TrieOpOutcome,
+        /// The outcome reported by the store.
+        actual: TrieOpOutcome,
+    },
+    /// The keys of the final trie aren't the oracle's.
+    #[error(
+        "trie {root} holds key {actual:?} instead of {expected:?} at position {position} of its \
+         {actual_count} keys, against the oracle's {expected_count}"
+    )]
+    KeysMismatch {
+        /// The root of the final trie.
+        root: Digest,
+        /// The position of the first key which differs.
+        position: usize,
+        /// The key held by the oracle at `position`, if any.
+        expected: Option<TestKey>,
+        /// The key held by the trie at `position`, if any.
+        actual: Option<TestKey>,
+        /// The number of keys held by the oracle.
+        expected_count: usize,
+        /// The number of keys held by the trie.
+        actual_count: usize,
+    },
+    /// An operation produced a root other than the reference store's.
+    #[error("operation {step} produced root {actual} instead of {expected}")]
+    RootMismatch {
+        /// The index of the operation.
+        step: usize,
+        /// The root produced by the reference store.
+        expected: Digest,
+        /// The root produced by the store under test.
+        actual: Digest,
+    },
+}
+
+impl TrieCheckError {
+    fn store(error: impl Display) -> Self {
+        TrieCheckError::Store(error.to_string())
+    }
+}
+
+/// Puts the empty trie into `store`, returning its root.
+pub fn put_empty_root<'a, R, S, E>(environment: &'a R, store: &S) -> Result<Digest, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
+{
+    let empty_root: Trie<TestKey, TestValue> = Trie::node(&[]);
+    let root = empty_root.trie_hash()?;
+    let mut txn = environment.create_read_write_txn()?;
+    store.put(&mut txn, &root, &empty_root)?;
+    txn.commit()?;
+    Ok(root)
+}
+
+/// Applies `op` to the trie under `root` in `store`, moving `root` to the new root if the trie
+/// changes.
+pub fn apply_op<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    root: &mut Digest,
+    op: &TrieOp,
+) -> Result<TrieOpOutcome, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
+{
+    let outcome = match op {
+        TrieOp::Insert(key, value) => {
+            let mut txn = environment.create_read_write_txn()?;
+            let outcome = match operations::write::<_, _, _, _, E>(
+                correlation_id,
+                &mut txn,
+                store,
+                root,
+                key,
+                value,
+            )? {
+                WriteResult::Written(new_root) => {
+                    *root = new_root;
+                    TrieOpOutcome::Written
+                }
+                WriteResult::AlreadyExists => TrieOpOutcome::AlreadyExists,
+                WriteResult::RootNotFound => TrieOpOutcome::RootNotFound,
+            };
+            txn.commit()?;
+            outcome
+        }
+        TrieOp::Delete(key) => {
+            let mut txn = environment.create_read_write_txn()?;
+            let outcome = match operations::delete::<TestKey, TestValue, _, _, E>(
+                correlation_id,
+                &mut txn,
+                store,
+                root,
+                key,
+            )? {
+                DeleteResult::Deleted(new_root) => {
+                    *root = new_root;
+                    TrieOpOutcome::Deleted
+                }
+                DeleteResult::DoesNotExist => TrieOpOutcome::DoesNotExist,
+                DeleteResult::RootNotFound => TrieOpOutcome::RootNotFound,
+            };
+            txn.commit()?;
+            outcome
+        }
+        TrieOp::Lookup(key) => {
+            let txn = environment.create_read_txn()?;
+            match operations::read::<TestKey, TestValue, _, _, E>(
+                correlation_id,
+                &txn,
+                store,
+                root,
+                key,
+            )? {
+                ReadResult::Found(value) => TrieOpOutcome::Found(value),
+                ReadResult::NotFound => TrieOpOutcome::NotFound,
+                ReadResult::RootNotFound => TrieOpOutcome::RootNotFound,
+            }
+        }
+    };
+    Ok(outcome)
+}
+
+/// Returns the keys of the trie under `root` in `store`, in order.
+pub fn trie_keys<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    root: &Digest,
+) -> Result<Vec<TestKey>, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error> + From<bytesrepr::Error>,
+    E: From<R::Error> + From<S::Error>,
+{
+    let txn = environment.create_read_txn()?;
+    let keys = operations::keys_with_prefix::<TestKey, TestValue, _, _>(
+        correlation_id,
+        &txn,
+        store,
+        root,
+        &[],
+    )
+    .collect::<Result<Vec<_>, _>>()?;
+    Ok(keys)
+}
+
+/// Runs `ops` against `store` starting from the empty trie, checking the outcome of every
+/// operation and the keys of the final trie against a [`TrieOracle`].
+///
+/// Returns the root after each operation.
+pub fn check_store<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    ops: &[TrieOp],
+) -> Result<Vec<Digest>, TrieCheckError>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error> + From<bytesrepr::Error>,
+    E: From<R::Error> + From<S::Error> + From<bytesrepr::Error> + Display,
+{
+    let mut root = put_empty_root::<_, _, E>(environment, store).map_err(TrieCheckError::store)?;
+    let mut oracle = TrieOracle::default();
+    let mut roots = Vec::with_capacity(ops.len());
+    for (step, op) in ops.iter().enumerate() {
+        let expected = oracle.apply(op);
+        let actual = apply_op::<_, _, E>(correlation_id, environment, store, &mut root, op)
+            .map_err(TrieCheckError::store)?;
+        if actual != expected {
+            return Err(TrieCheckError::OutcomeMismatch {
+                step,
+                op: op.clone(),
+                expected,
+                actual,
+            });
+        }
+        roots.push(root);
+    }
+
+    let keys = trie_keys::<_, _, E>(correlation_id, environment, store, &root)
+        .map_err(TrieCheckError::store)?;
+    let expected_keys = oracle.keys();
+    if keys != expected_keys {
+        let position = keys
+            .iter()
+            .zip(&expected_keys)
+            .position(|(key, expected_key)| key != expected_key)
+            .unwrap_or_else(|| keys.len().min(expected_keys.len()));
+        return Err(TrieCheckError::KeysMismatch {
+            root,
+            position,
+            expected: expected_keys.get(position).copied(),
+            actual: keys.get(position).copied(),
+            expected_count: expected_keys.len(),
+            actual_count: keys.len(),
+        });
+    }
+    Ok(roots)
+}
+
+/// Runs `count` operations generated from `seed` against `store` as [`check_store`] does, and
+/// checks that every operation produces the same root as it does in an [`InMemoryTrieStore`].
+pub fn check_against_in_memory<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    seed: u64,
+    count: usize,
+) -> Result<(), TrieCheckError>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error> + From<bytesrepr::Error>,
+    E: From<R::Error> + From<S::Error> + From<bytesrepr::Error> + Display,
+{
+    let ops = TrieOpGenerator::new(seed).ops(count);
+    let roots = check_store::<_, _, E>(correlation_id, environment, store, &ops)?;
+
+    let reference_environment = InMemoryEnvironment::new();
+    let reference_store = InMemoryTrieStore::new(&reference_environment, None);
+    let reference_roots = check_store::<_, _, in_memory::Error>(
+        correlation_id,
+        &reference_environment,
+        &reference_store,
+        &ops,
+    )?;
+
+    match roots
+        .iter()
+        .zip(&reference_roots)
+        .position(|(root, reference_root)| root != reference_root)
+    {
+        Some(step) => Err(TrieCheckError::RootMismatch {
+            step,
+            expected: reference_roots[step],
+            actual: roots[step],
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The ways an [`LmdbTrieStore`] can be set up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LmdbVariant {
+    /// Every trie in one database, uncompressed.
+    Plain,
+    /// Large tries compressed with zstd.
+    Compressed,
+    /// Leaves spread over shard databases by the tag of their key.
+    Sharded,
+    /// Both compressed and sharded.
+    CompressedAndSharded,
+}
+
+impl LmdbVariant {
+    /// Every variant.
+    pub const ALL: [LmdbVariant; 4] = [
+        LmdbVariant::Plain,
+        LmdbVariant::Compressed,
+        LmdbVariant::Sharded,
+        LmdbVariant::CompressedAndSharded,
+    ];
+
+    fn open(self, environment: &LmdbEnvironment) -> Result<LmdbTrieStore, error::Error> {
+        let store = LmdbTrieStore::new(environment, None, DatabaseFlags::empty())?;
+        let store = match self {
+            LmdbVariant::Compressed | LmdbVariant::CompressedAndSharded => {
+                store.with_compression(environment, DEFAULT_COMPRESSION_LEVEL)?
+            }
+            LmdbVariant::Plain | LmdbVariant::Sharded => store,
+        };
+        match self {
+            LmdbVariant::Sharded | LmdbVariant::CompressedAndSharded => {
+                store.with_sharding(environment)
+            }
+            LmdbVariant::Plain | LmdbVariant::Compressed => Ok(store),
+        }
+    }
+}
+
+/// Runs `count` operations generated from `seed` against a fresh [`LmdbTrieStore`] set up as
+/// `variant`, as [`check_against_in_memory`] does.
+pub fn check_lmdb_against_in_memory(
+    variant: LmdbVariant,
+    seed: u64,
+    count: usize,
+) -> Result<(), TrieCheckError> {
+    let temp_dir = tempdir().map_err(TrieCheckError::store)?;
+    let environment = LmdbEnvironment::new(
+        temp_dir.path(),
+        DEFAULT_TEST_MAX_DB_SIZE,
+        DEFAULT_TEST_MAX_READERS,
+        true,
+    )
+    .map_err(TrieCheckError::store)?;
+    let store = variant.open(&environment).map_err(TrieCheckError::store)?;
+    check_against_in_memory::<_, _, error::Error>(
+        CorrelationId::new(),
+        &environment,
+        &store,
+        seed,
+        count,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_generate_the_same_ops_from_a_seed() {
+        let ops = TrieOpGenerator::new(7).ops(100);
+        assert_eq!(ops, TrieOpGenerator::new(7).ops(100));
+        assert_ne!(ops, TrieOpGenerator::new(8).ops(100));
+        assert!(ops.iter().any(|op| matches!(op, TrieOp::Delete(_))));
+        assert!(ops.iter().any(|op| matches!(op, TrieOp::Lookup(_))));
+    }
+
+    #[test]
+    fn should_apply_ops_to_oracle() {
+        let key = TestKey([1; TEST_KEY_LENGTH]);
+        let value = TestValue(vec![1, 2, 3]);
+        let mut oracle = TrieOracle::default();
+        assert_eq!(oracle.apply(&TrieOp::Lookup(key)), TrieOpOutcome::NotFound);
+        assert_eq!(
+            oracle.apply(&TrieOp::Insert(key, value.clone())),
+            TrieOpOutcome::Written
+        );
+        assert_eq!(
+            oracle.apply(&TrieOp::Insert(key, value.clone())),
+            TrieOpOutcome::AlreadyExists
+        );
+        assert_eq!(
+            oracle.apply(&TrieOp::Lookup(key)),
+            TrieOpOutcome::Found(value)
+        );
+        assert_eq!(oracle.keys(), vec![key]);
+        assert_eq!(oracle.apply(&TrieOp::Delete(key)), TrieOpOutcome::Deleted);
+        assert_eq!(
+            oracle.apply(&TrieOp::Delete(key)),
+            TrieOpOutcome::DoesNotExist
+        );
+        assert!(oracle.keys().is_empty());
+    }
+
+    #[test]
+    fn lmdb_should_match_in_memory() {
+        for variant in LmdbVariant::ALL {
+            for seed in 0..8 {
+                if let Err(error) = check_lmdb_against_in_memory(variant, seed, 500) {
+                    panic!("{:?} store, seed {}: {}", variant, seed, error);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_generate_values_large_enough_to_compress() {
+        let mut generator = TrieOpGenerator::new(7);
+        let values: Vec<TestValue> = (0..200).map(|_| generator.value()).collect();
+        assert!(values
+            .iter()
+            .any(|value| LARGE_VALUE_LENGTHS.contains(&value.0.len())));
+        assert!(values.iter().all(|value| value.0.len() <= MAX_VALUE_LENGTH
+            || LARGE_VALUE_LENGTHS.contains(&value.0.len())));
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     sync::{Arc, Barrier},
     thread,
+    time::Duration,
 };
 
 use casper_types::bytesrepr::Bytes;
@@ -127,3 +128,158 @@ fn in_memory_writer_mutex_does_not_collide_with_readers() {
 
     assert!(handles.into_iter().all(|b| b.join().unwrap()))
 }
+
+#[test]
+fn lmdb_reader_observes_snapshot_while_writer_commits() {
+    let dir = tempdir().unwrap();
+    let env = Arc::new(
+        LmdbEnvironment::new(
+            &dir.path(),
+            DEFAULT_TEST_MAX_DB_SIZE,
+            DEFAULT_TEST_MAX_READERS,
+            true,
+        )
+        .unwrap(),
+    );
+    let store = Arc::new(LmdbTrieStore::new(&env, None, Default::default()).unwrap());
+    let data = super::create_data();
+    let TestData(leaf_1_hash, leaf_1) = data[0].clone();
+
+    let long_lived_txn = env.create_read_txn().unwrap();
+
+    let writer = {
+        let env = env.clone();
+        let store = store.clone();
+        let leaf_1 = leaf_1.clone();
+        thread::spawn(move || {
+            let mut txn = env.create_read_write_txn().unwrap();
+            store.put(&mut txn, &leaf_1_hash, &leaf_1).unwrap();
+            txn.commit().unwrap();
+        })
+    };
+    writer.join().unwrap();
+
+    // The commit happened after the snapshot was taken, so it must not be visible through it.
+    let result: Option<Trie<Bytes, Bytes>> = store.get(&long_lived_txn, &leaf_1_hash).unwrap();
+    assert_eq!(result, None);
+    long_lived_txn.commit().unwrap();
+
+    let txn = env.create_read_txn().unwrap();
+    let result: Option<Trie<Bytes, Bytes>> = store.get(&txn, &leaf_1_hash).unwrap();
+    assert_eq!(result, Some(leaf_1));
+    txn.commit().unwrap();
+}
+
+#[test]
+fn in_memory_reader_observes_snapshot_while_writer_commits() {
+    let env = Arc::new(InMemoryEnvironment::new());
+    let store = Arc::new(InMemoryTrieStore::new(&env, None));
+    let data = super::create_data();
+    let TestData(leaf_1_hash, leaf_1) = data[0].clone();
+
+    let long_lived_txn = env.create_read_txn().unwrap();
+
+    let writer = {
+        let env = env.clone();
+        let store = store.clone();
+        let leaf_1 = leaf_1.clone();
+        thread::spawn(move || {
+            let mut txn = env.create_read_write_txn().unwrap();
+            store.put(&mut txn, &leaf_1_hash, &leaf_1).unwrap();
+            txn.commit().unwrap();
+        })
+    };
+    writer.join().unwrap();
+
+    let result: Option<Trie<Bytes, Bytes>> = store.get(&long_lived_txn, &leaf_1_hash).unwrap();
+    assert_eq!(result, None);
+    long_lived_txn.commit().unwrap();
+
+    let txn = env.create_read_txn().unwrap();
+    let result: Option<Trie<Bytes, Bytes>> = store.get(&txn, &leaf_1_hash).unwrap();
+    assert_eq!(result, Some(leaf_1));
+    txn.commit().unwrap();
+}
+
+#[test]
+fn lmdb_readers_never_observe_partial_commits() {
+    let dir = tempdir().unwrap();
+    let env = Arc::new(
+        LmdbEnvironment::new(
+            &dir.path(),
+            DEFAULT_TEST_MAX_DB_SIZE,
+            DEFAULT_TEST_MAX_READERS,
+            true,
+        )
+        .unwrap(),
+    );
+    let store = Arc::new(LmdbTrieStore::new(&env, None, Default::default()).unwrap());
+    let data = Arc::new(super::create_data());
+    let num_threads = 10;
+    let barrier = Arc::new(Barrier::new(num_threads + 1));
+    let mut handles = Vec::new();
+
+    for _ in 0..num_threads {
+        let reader_env = env.clone();
+        let reader_store = store.clone();
+        let reader_barrier = barrier.clone();
+        let data = data.clone();
+
+        handles.push(thread::spawn(move || {
+            reader_barrier.wait();
+            for _ in 0..100 {
+                let txn = reader_env.create_read_txn().unwrap();
+                let found = data
+                    .iter()
+                    .map(|TestData(hash, _)| {
+                        let result: Option<Trie<Bytes, Bytes>> =
+                            reader_store.get(&txn, hash).unwrap();
+                        result.is_some()
+                    })
+                    .collect::<Vec<_>>();
+                txn.commit().unwrap();
+                // All the tries were written in a single transaction, so a reader sees either none
+                // or all of them.
+                if found.iter().any(|is_found| *is_found) != found.iter().all(|is_found| *is_found)
+                {
+                    return false;
+                }
+            }
+            true
+        }));
+    }
+
+    barrier.wait();
+    let mut txn = env.create_read_write_txn().unwrap();
+    for TestData(hash, trie) in data.iter() {
+        store.put(&mut txn, hash, trie).unwrap();
+    }
+    txn.commit().unwrap();
+
+    assert!(handles.into_iter().all(|b| b.join().unwrap()))
+}
+
+#[test]
+fn lmdb_read_txn_should_report_exceeding_max_duration() {
+    let dir = tempdir().unwrap();
+    let env = LmdbEnvironment::new(
+        &dir.path(),
+        DEFAULT_TEST_MAX_DB_SIZE,
+        DEFAULT_TEST_MAX_READERS,
+        true,
+    )
+    .unwrap();
+
+    // Without a configured maximum, no transaction is ever considered overdue.
+    let txn = env.create_read_txn().unwrap();
+    thread::sleep(Duration::from_millis(5));
+    assert!(!txn.is_overdue());
+    txn.commit().unwrap();
+
+    let env = env.with_max_read_txn_duration(Duration::from_millis(1));
+    let txn = env.create_read_txn().unwrap();
+    thread::sleep(Duration::from_millis(5));
+    assert!(txn.elapsed() >= Duration::from_millis(5));
+    assert!(txn.is_overdue());
+    txn.commit().unwrap();
+}
@@ -1,17 +1,24 @@
 use lmdb::DatabaseFlags;
 use tempfile::tempdir;
 
-use casper_types::bytesrepr::{self, Bytes, FromBytes, ToBytes};
+use casper_hashing::Digest;
+use casper_types::{
+    bytesrepr::{self, Bytes, FromBytes, ToBytes},
+    CLValue, EraId, Key, StoredValue, U512,
+};
 
 use super::TestData;
 use crate::storage::{
     error::{self, in_memory},
-    store::StoreExt,
+    store::{Store, StoreExt},
     transaction_source::{
         in_memory::InMemoryEnvironment, lmdb::LmdbEnvironment, Transaction, TransactionSource,
     },
     trie::Trie,
-    trie_store::{in_memory::InMemoryTrieStore, lmdb::LmdbTrieStore, TrieStore},
+    trie_store::{
+        compression::DEFAULT_COMPRESSION_LEVEL, in_memory::InMemoryTrieStore, lmdb::LmdbTrieStore,
+        TrieLocation, TrieStore,
+    },
     DEFAULT_TEST_MAX_DB_SIZE, DEFAULT_TEST_MAX_READERS,
 };
 
@@ -606,3 +613,276 @@ fn lmdb_uncommitted_transactions_across_sub_databases_do_not_persist() {
         .is_ok()
     )
 }
+
+fn put_exists_delete_succeeds<'a, S, X, E>(store: &S, env: &'a X) -> Result<(), E>
+where
+    S: TrieStore<Bytes, Bytes>,
+    X: TransactionSource<'a, Handle = S::Handle>,
+    S::Error: From<X::Error>,
+    E: From<S::Error> + From<X::Error>,
+{
+    let data = super::create_data();
+    let TestData(ref leaf_1_hash, _) = data[0];
+    let TestData(ref leaf_2_hash, _) = data[1];
+
+    {
+        let mut write_txn = env.create_read_write_txn()?;
+        store.put_many(&mut write_txn, data.iter().map(Into::into))?;
+        assert!(store.exists(&write_txn, leaf_1_hash)?);
+        assert!(store.delete(&mut write_txn, leaf_1_hash)?);
+        assert!(!store.exists(&write_txn, leaf_1_hash)?);
+        assert!(!store.delete(&mut write_txn, leaf_1_hash)?);
+        write_txn.commit()?;
+    }
+
+    let read_txn = env.create_read_txn()?;
+    assert!(!store.exists(&read_txn, leaf_1_hash)?);
+    assert_eq!(store.get(&read_txn, leaf_1_hash)?, None);
+    assert!(store.exists(&read_txn, leaf_2_hash)?);
+    read_txn.commit()?;
+
+    Ok(())
+}
+
+#[test]
+fn in_memory_put_exists_delete_succeeds() {
+    let env = InMemoryEnvironment::new();
+    let store = InMemoryTrieStore::new(&env, None);
+
+    put_exists_delete_succeeds::<_, _, in_memory::Error>(&store, &env).unwrap()
+}
+
+#[test]
+fn lmdb_put_exists_delete_succeeds() {
+    let dir = tempdir().unwrap();
+    let env = LmdbEnvironment::new(
+        &dir.path(),
+        DEFAULT_TEST_MAX_DB_SIZE,
+        DEFAULT_TEST_MAX_READERS,
+        true,
+    )
+    .unwrap();
+    let store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty()).unwrap();
+
+    put_exists_delete_succeeds::<_, _, error::Error>(&store, &env).unwrap()
+}
+
+fn get_into_succeeds<'a, S, X, E>(store: &S, env: &'a X) -> Result<(), E>
+where
+    S: TrieStore<Bytes, Bytes>,
+    X: TransactionSource<'a, Handle = S::Handle>,
+    S::Error: From<X::Error> + From<std::io::Error>,
+    E: From<S::Error> + From<X::Error>,
+{
+    // A leaf large and repetitive enough to be compressed by a store compressing tries.
+    let large_leaf = Trie::Leaf {
+        key: Bytes::from(vec![2u8, 0, 0]),
+        value: Bytes::from(vec![7u8; 64 * 1024]),
+    };
+    let large_leaf_hash = Digest::hash(&large_leaf.to_bytes().unwrap());
+    let mut data = super::create_data();
+    data.push(TestData(large_leaf_hash, large_leaf));
+    let missing_hash = Digest::hash(b"missing");
+
+    {
+        let mut write_txn = env.create_read_write_txn()?;
+        store.put_many(&mut write_txn, data.iter().map(Into::into))?;
+        write_txn.commit()?;
+    }
+
+    let read_txn = env.create_read_txn()?;
+    for TestData(hash, trie) in &data {
+        let mut buffer = Vec::new();
+        assert!(store.get_into(&read_txn, hash, &mut buffer)?);
+        assert_eq!(buffer, trie.to_bytes().unwrap());
+        assert_eq!(store.get(&read_txn, hash)?.as_ref(), Some(trie));
+    }
+    let mut buffer = Vec::new();
+    assert!(!store.get_into(&read_txn, &missing_hash, &mut buffer)?);
+    assert!(buffer.is_empty());
+    read_txn.commit()?;
+
+    Ok(())
+}
+
+#[test]
+fn in_memory_get_into_succeeds() {
+    let env = InMemoryEnvironment::new();
+    let store = InMemoryTrieStore::new(&env, None);
+
+    get_into_succeeds::<_, _, in_memory::Error>(&store, &env).unwrap()
+}
+
+#[test]
+fn lmdb_get_into_succeeds() {
+    let dir = tempdir().unwrap();
+    let env = LmdbEnvironment::new(
+        &dir.path(),
+        DEFAULT_TEST_MAX_DB_SIZE,
+        DEFAULT_TEST_MAX_READERS,
+        true,
+    )
+    .unwrap();
+    let store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty()).unwrap();
+
+    get_into_succeeds::<_, _, error::Error>(&store, &env).unwrap()
+}
+
+#[test]
+fn lmdb_compressed_get_into_succeeds() {
+    let dir = tempdir().unwrap();
+    let env = LmdbEnvironment::new(
+        &dir.path(),
+        DEFAULT_TEST_MAX_DB_SIZE,
+        DEFAULT_TEST_MAX_READERS,
+        true,
+    )
+    .unwrap();
+    let store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty())
+        .unwrap()
+        .with_compression(&env, DEFAULT_COMPRESSION_LEVEL)
+        .unwrap();
+
+    get_into_succeeds::<_, _, error::Error>(&store, &env).unwrap()
+}
+
+#[test]
+fn lmdb_sharded_put_exists_delete_succeeds() {
+    let dir = tempdir().unwrap();
+    let env = LmdbEnvironment::new(
+        &dir.path(),
+        DEFAULT_TEST_MAX_DB_SIZE,
+        DEFAULT_TEST_MAX_READERS,
+        true,
+    )
+    .unwrap();
+    let store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty())
+        .unwrap()
+        .with_sharding(&env)
+        .unwrap();
+
+    put_exists_delete_succeeds::<_, _, error::Error>(&store, &env).unwrap()
+}
+
+#[test]
+fn lmdb_sharded_store_keeps_leaves_apart_from_nodes() {
+    let dir = tempdir().unwrap();
+    let env = LmdbEnvironment::new(
+        &dir.path(),
+        DEFAULT_TEST_MAX_DB_SIZE,
+        DEFAULT_TEST_MAX_READERS,
+        true,
+    )
+    .unwrap();
+    let store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty())
+        .unwrap()
+        .with_sharding(&env)
+        .unwrap();
+    assert_eq!(store.databases().count(), 5);
+
+    let balance_leaf: Trie<Key, StoredValue> = Trie::Leaf {
+        key: Key::Balance([1; 32]),
+        value: StoredValue::CLValue(CLValue::from_t(U512::one()).unwrap()),
+    };
+    let era_info_leaf: Trie<Key, StoredValue> = Trie::Leaf {
+        key: Key::EraInfo(EraId::new(1)),
+        value: StoredValue::CLValue(CLValue::from_t(()).unwrap()),
+    };
+    let balance_leaf_hash = Digest::hash(&balance_leaf.to_bytes().unwrap());
+    let era_info_leaf_hash = Digest::hash(&era_info_leaf.to_bytes().unwrap());
+
+    let mut txn = env.create_read_write_txn().unwrap();
+    store
+        .put(&mut txn, &balance_leaf_hash, &balance_leaf)
+        .unwrap();
+    store
+        .put(&mut txn, &era_info_leaf_hash, &era_info_leaf)
+        .unwrap();
+    txn.commit().unwrap();
+
+    // Reopening the store finds it sharded.
+    let store = LmdbTrieStore::open(&env, None).unwrap();
+    assert!(store.is_sharded());
+    let txn = env.create_read_txn().unwrap();
+    let (balance_db, _) = store
+        .get_stored(txn.lmdb_txn(), balance_leaf_hash.as_ref())
+        .unwrap()
+        .unwrap();
+    assert_ne!(balance_db, store.get_db());
+    let (era_info_db, _) = store
+        .get_stored(txn.lmdb_txn(), era_info_leaf_hash.as_ref())
+        .unwrap()
+        .unwrap();
+    assert_eq!(era_info_db, store.get_db());
+    assert_eq!(
+        store.get(&txn, &balance_leaf_hash).unwrap(),
+        Some(balance_leaf)
+    );
+    assert_eq!(
+        store.get(&txn, &era_info_leaf_hash).unwrap(),
+        Some(era_info_leaf)
+    );
+    txn.commit().unwrap();
+}
+
+#[test]
+fn lmdb_sharding_moves_leaves_already_written() {
+    let dir = tempdir().unwrap();
+    let env = LmdbEnvironment::new(
+        &dir.path(),
+        DEFAULT_TEST_MAX_DB_SIZE,
+        DEFAULT_TEST_MAX_READERS,
+        true,
+    )
+    .unwrap();
+    let store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty()).unwrap();
+
+    let balance_leaf: Trie<Key, StoredValue> = Trie::Leaf {
+        key: Key::Balance([1; 32]),
+        value: StoredValue::CLValue(CLValue::from_t(U512::one()).unwrap()),
+    };
+    let balance_leaf_hash = Digest::hash(&balance_leaf.to_bytes().unwrap());
+    let location = TrieLocation::Leaf {
+        key_tag: balance_leaf.to_bytes().unwrap()[1],
+    };
+    let mut txn = env.create_read_write_txn().unwrap();
+    store
+        .put(&mut txn, &balance_leaf_hash, &balance_leaf)
+        .unwrap();
+    txn.commit().unwrap();
+
+    // Enabling sharding moves the leaf into its shard, where it's read from by its location.
+    let store = store.with_sharding(&env).unwrap();
+    let txn = env.create_read_txn().unwrap();
+    let (balance_db, _) = store
+        .get_stored(txn.lmdb_txn(), balance_leaf_hash.as_ref())
+        .unwrap()
+        .unwrap();
+    assert_ne!(balance_db, store.get_db());
+    assert_eq!(
+        store.databases_at(location).collect::<Vec<_>>(),
+        vec![balance_db]
+    );
+    assert_eq!(
+        store.get_at(&txn, &balance_leaf_hash, location).unwrap(),
+        Some(balance_leaf.clone())
+    );
+    txn.commit().unwrap();
+
+    // Disabling it moves the leaf back, and the store is no longer opened sharded.
+    let store = store.without_sharding(&env).unwrap();
+    assert!(!store.is_sharded());
+    let store = LmdbTrieStore::open(&env, None).unwrap();
+    assert!(!store.is_sharded());
+    let txn = env.create_read_txn().unwrap();
+    let (balance_db, _) = store
+        .get_stored(txn.lmdb_txn(), balance_leaf_hash.as_ref())
+        .unwrap()
+        .unwrap();
+    assert_eq!(balance_db, store.get_db());
+    assert_eq!(
+        store.get_at(&txn, &balance_leaf_hash, location).unwrap(),
+        Some(balance_leaf)
+    );
+    txn.commit().unwrap();
+}
@@ -303,6 +303,10 @@ pub trait Auction:
     /// The arguments are the delegator's key, the validator's key, the amount,
     /// and the new validator's key.
     ///
+    /// If direct redelegation is enabled, the amount moves to the new validator at the end of the
+    /// current era instead of after the unbonding delay, and the delegator can't redelegate again
+    /// until the redelegation cooldown has passed.
+    ///
     /// Returns the remaining bid amount if the new validator is inactive.
     fn redelegate(
         &mut self,
@@ -323,6 +327,20 @@ pub trait Auction:
             return Err(Error::DelegationAmountTooSmall);
         }
 
+        if let Some(cooldown_eras) = self.direct_redelegation_cooldown_eras() {
+            let current_era_id = self.read_era_id()?;
+            let mut redelegation_eras = detail::get_redelegation_eras(self)?;
+            if let Some(era_id) = redelegation_eras.get(&provided_account_hash) {
+                if current_era_id < *era_id + cooldown_eras {
+                    return Err(Error::RedelegationCooldown);
+                }
+            }
+            if cooldown_eras > 0 {
+                redelegation_eras.insert(provided_account_hash, current_era_id);
+                detail::set_redelegation_eras(self, redelegation_eras)?;
+            }
+        }
+
         let validator_account_hash = AccountHash::from(&validator_public_key);
 
         let mut bid = match self.read_bid(&validator_account_hash)? {
@@ -629,6 +647,10 @@ pub trait Auction:
 
     /// Activates a given validator's bid.  To be used when a validator has been marked as inactive
     /// by consensus (aka "evicted").
+    ///
+    /// Once strict bid activation is enabled, fails if the bid is already active, or if it has no
+    /// stake left, since such a bid couldn't win a validator slot anyway.  Before that, such bids
+    /// are activated as they always were, so that blocks executed earlier replay unchanged.
     fn activate_bid(&mut self, validator_public_key: PublicKey) -> Result<(), Error> {
         let provided_account_hash =
             AccountHash::from_public_key(&validator_public_key, |x| self.blake2b(x));
@@ -642,6 +664,16 @@ pub trait Auction:
             None => return Err(Error::ValidatorNotFound),
         };
 
+        if self.strict_bid_activation() {
+            if !bid.inactive() {
+                return Err(Error::BidNotInactive);
+            }
+
+            if bid.staked_amount().is_zero() {
+                return Err(Error::BondTooSmall);
+            }
+        }
+
         bid.activate();
 
         self.write_bid(provided_account_hash, bid)?;
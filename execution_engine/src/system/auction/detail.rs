@@ -8,7 +8,8 @@ use casper_types::{
     system::auction::{
         Bids, Delegator, Error, SeigniorageAllocation, SeigniorageRecipientsSnapshot,
         UnbondingPurse, UnbondingPurses, AUCTION_DELAY_KEY, ERA_END_TIMESTAMP_MILLIS_KEY,
-        ERA_ID_KEY, SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY, UNBONDING_DELAY_KEY, VALIDATOR_SLOTS_KEY,
+        ERA_ID_KEY, REDELEGATION_ERAS_KEY, SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY,
+        UNBONDING_DELAY_KEY, VALIDATOR_SLOTS_KEY,
     },
     ApiError, CLTyped, EraId, Key, KeyTag, PublicKey, URef, U512,
 };
@@ -185,6 +186,35 @@ where
     read_from(provider, UNBONDING_DELAY_KEY)
 }
 
+/// Returns the eras in which delegators last redelegated directly, by their account hash.
+pub(crate) fn get_redelegation_eras<P>(
+    provider: &mut P,
+) -> Result<BTreeMap<AccountHash, EraId>, Error>
+where
+    P: StorageProvider + RuntimeProvider + ?Sized,
+{
+    if provider.named_keys_get(REDELEGATION_ERAS_KEY).is_none() {
+        return Ok(BTreeMap::new());
+    }
+    read_from(provider, REDELEGATION_ERAS_KEY)
+}
+
+/// Writes the eras in which delegators last redelegated directly, creating the named key holding
+/// them if this is the first direct redelegation.
+pub(crate) fn set_redelegation_eras<P>(
+    provider: &mut P,
+    redelegation_eras: BTreeMap<AccountHash, EraId>,
+) -> Result<(), Error>
+where
+    P: StorageProvider + RuntimeProvider + ?Sized,
+{
+    if provider.named_keys_get(REDELEGATION_ERAS_KEY).is_none() {
+        let uref = provider.new_uref(redelegation_eras)?;
+        return provider.put_key(REDELEGATION_ERAS_KEY, uref.into());
+    }
+    write_to(provider, REDELEGATION_ERAS_KEY, redelegation_eras)
+}
+
 /// Iterates over unbonding entries and checks if a locked amount can be paid already if
 /// a specific era is reached.
 ///
@@ -202,14 +232,20 @@ pub(crate) fn process_unbond_requests<P: Auction + ?Sized>(
     let current_era_id = provider.read_era_id()?;
 
     let unbonding_delay = get_unbonding_delay(provider)?;
+    let redelegation_cooldown_eras = provider.direct_redelegation_cooldown_eras();
 
     for unbonding_list in unbonding_purses.values_mut() {
         let mut new_unbonding_list = Vec::new();
         for unbonding_purse in unbonding_list.iter() {
+            // Redelegations move at the end of the era they were requested in if enabled.
+            let delay = match (unbonding_purse.new_validator(), redelegation_cooldown_eras) {
+                (Some(_), Some(_)) => 0,
+                _ => unbonding_delay,
+            };
             // Since `process_unbond_requests` is run before `run_auction`, we should check if
             // current era id + unbonding delay is equal or greater than the `era_of_creation` that
             // was calculated on `unbond` attempt.
-            if current_era_id >= unbonding_purse.era_of_creation() + unbonding_delay {
+            if current_era_id >= unbonding_purse.era_of_creation() + delay {
                 match unbonding_purse.new_validator() {
                     Some(new_validator) => {
                         match provider.read_bid(&new_validator.to_account_hash()) {
@@ -256,6 +292,16 @@ pub(crate) fn process_unbond_requests<P: Auction + ?Sized>(
     }
 
     set_unbonding_purses(provider, unbonding_purses)?;
+
+    // Forget the redelegations whose cooldown has passed.
+    if let Some(cooldown_eras) = redelegation_cooldown_eras {
+        let mut redelegation_eras = get_redelegation_eras(provider)?;
+        let count = redelegation_eras.len();
+        redelegation_eras.retain(|_, era_id| current_era_id < *era_id + cooldown_eras);
+        if redelegation_eras.len() != count {
+            set_redelegation_eras(provider, redelegation_eras)?;
+        }
+    }
     Ok(())
 }
 
@@ -21,6 +21,9 @@ pub trait RuntimeProvider {
     /// Gets named key under a `name`.
     fn named_keys_get(&self, name: &str) -> Option<Key>;
 
+    /// Stores given [`Key`] under `name`.
+    fn put_key(&mut self, name: &str, key: Key) -> Result<(), Error>;
+
     /// Gets keys in a given keyspace
     fn get_keys(&mut self, key_tag: &KeyTag) -> Result<BTreeSet<Key>, Error>;
 
@@ -29,10 +32,20 @@ pub trait RuntimeProvider {
 
     /// Returns vesting schedule period.
     fn vesting_schedule_period_millis(&self) -> u64;
+
+    /// Returns the number of eras a delegator has to wait between redelegations if redelegations
+    /// move at the next era end, or `None` if they wait out the unbonding delay.
+    fn direct_redelegation_cooldown_eras(&self) -> Option<u64>;
+
+    /// Returns `true` if only inactive bids with a stake left may be activated.
+    fn strict_bid_activation(&self) -> bool;
 }
 
 /// Provides functionality of a contract storage.
 pub trait StorageProvider {
+    /// Creates new [`URef`] holding `init`.
+    fn new_uref<T: ToBytes + CLTyped>(&mut self, init: T) -> Result<URef, Error>;
+
     /// Reads data from [`URef`].
     fn read<T: FromBytes + CLTyped>(&mut self, uref: URef) -> Result<Option<T>, Error>;
 
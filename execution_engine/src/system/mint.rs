@@ -140,6 +140,60 @@ pub trait Mint: RuntimeProvider + StorageProvider + SystemProvider {
         Ok(())
     }
 
+    /// Transfers tokens from `source` purse to each of the `(account, target purse, amount, id)`
+    /// entries of `transfers`.
+    ///
+    /// Unlike a sequence of [`transfer`](Self::transfer) calls, the balance of `source` is read,
+    /// checked against the total amount and written once for the whole batch, while each target
+    /// balance is only added to.  Nothing is transferred unless every transfer can be made.
+    fn transfer_batch(
+        &mut self,
+        source: URef,
+        transfers: Vec<(AccountHash, URef, U512, Option<u64>)>,
+    ) -> Result<(), Error> {
+        if let (Phase::Session, Some(&CallStackElement::StoredSession { .. })) =
+            (self.get_phase(), self.get_immediate_caller())
+        {
+            // stored session code is not allowed to call this method in the session phase
+            return Err(Error::InvalidContext);
+        }
+
+        if !source.is_readable() || !source.is_writeable() {
+            return Err(Error::InvalidAccessRights);
+        }
+        let mut total_amount = U512::zero();
+        for (_, target, amount, _) in &transfers {
+            if !target.is_addable() {
+                return Err(Error::InvalidAccessRights);
+            }
+            total_amount = total_amount
+                .checked_add(*amount)
+                .ok_or(Error::InsufficientFunds)?;
+        }
+
+        let source_balance: U512 = match self.read_balance(source)? {
+            Some(source_balance) => source_balance,
+            None => return Err(Error::SourceNotFound),
+        };
+        if total_amount > source_balance {
+            return Err(Error::InsufficientFunds);
+        }
+
+        if self.get_main_purse().addr() == source.addr() {
+            if total_amount > self.get_approved_spending_limit() {
+                return Err(Error::UnapprovedSpendingAmount);
+            }
+            self.sub_approved_spending_limit(total_amount);
+        }
+
+        self.write_balance(source, source_balance - total_amount)?;
+        for (account_hash, target, amount, id) in transfers {
+            self.add_balance(target, amount)?;
+            self.record_transfer(Some(account_hash), source, target, amount, id)?;
+        }
+        Ok(())
+    }
+
     /// Retrieves the base round reward.
     fn read_base_round_reward(&mut self) -> Result<U512, Error> {
         let total_supply_uref = match self.get_key(TOTAL_SUPPLY_KEY) {
@@ -8,7 +8,7 @@ use casper_execution_engine::core::engine_state::{
 use casper_hashing::Digest;
 use casper_types::{
     account::AccountHash, ContractHash, ContractPackageHash, ContractVersion, DeployHash, HashAddr,
-    RuntimeArgs,
+    RuntimeArgs, U512,
 };
 
 use crate::{utils, DEFAULT_GAS_PRICE};
@@ -21,6 +21,7 @@ struct DeployItemData {
     pub gas_price: u64,
     pub authorization_keys: BTreeSet<AccountHash>,
     pub deploy_hash: Option<DeployHash>,
+    pub priority_fee: U512,
 }
 
 /// Builds a [`DeployItem`].
@@ -261,6 +262,12 @@ impl DeployItemBuilder {
         self
     }
 
+    /// Sets the priority fee offered to the block proposer.
+    pub fn with_priority_fee(mut self, priority_fee: U512) -> Self {
+        self.deploy_item.priority_fee = priority_fee;
+        self
+    }
+
     /// Consumes self and returns a [`DeployItem`].
     pub fn build(self) -> DeployItem {
         DeployItem {
@@ -282,6 +289,7 @@ impl DeployItemBuilder {
                 .deploy_item
                 .deploy_hash
                 .unwrap_or_else(|| rand::thread_rng().gen()),
+            priority_fee: self.deploy_item.priority_fee,
         }
     }
 }
@@ -39,7 +39,9 @@ pub use deploy_item_builder::DeployItemBuilder;
 pub use execute_request_builder::ExecuteRequestBuilder;
 pub use step_request_builder::StepRequestBuilder;
 pub use upgrade_request_builder::UpgradeRequestBuilder;
-pub use wasm_test_builder::{InMemoryWasmTestBuilder, LmdbWasmTestBuilder, WasmTestBuilder};
+pub use wasm_test_builder::{
+    BalanceChange, InMemoryWasmTestBuilder, LmdbWasmTestBuilder, WasmTestBuilder,
+};
 
 const DAY_MILLIS: u64 = 24 * 60 * 60 * 1000;
 
@@ -19,6 +19,7 @@ pub struct UpgradeRequestBuilder {
     new_unbonding_delay: Option<u64>,
     global_state_update: BTreeMap<Key, StoredValue>,
     chainspec_registry: ChainspecRegistry,
+    migrate_legacy_withdraws: bool,
 }
 
 impl UpgradeRequestBuilder {
@@ -99,6 +100,12 @@ impl UpgradeRequestBuilder {
         self
     }
 
+    /// Sets whether legacy withdraw purses should be migrated into unbonding purses.
+    pub fn with_migrate_legacy_withdraws(mut self, migrate_legacy_withdraws: bool) -> Self {
+        self.migrate_legacy_withdraws = migrate_legacy_withdraws;
+        self
+    }
+
     /// Consumes the `UpgradeRequestBuilder` and returns an [`UpgradeConfig`].
     pub fn build(self) -> UpgradeConfig {
         UpgradeConfig::new(
@@ -113,6 +120,7 @@ impl UpgradeRequestBuilder {
             self.new_unbonding_delay,
             self.global_state_update,
             self.chainspec_registry,
+            self.migrate_legacy_withdraws,
         )
     }
 }
@@ -131,6 +139,7 @@ impl Default for UpgradeRequestBuilder {
             new_unbonding_delay: None,
             global_state_update: Default::default(),
             chainspec_registry: ChainspecRegistry::new_with_optional_global_state(&[], None),
+            migrate_legacy_withdraws: false,
         }
     }
 }
@@ -25,8 +25,9 @@ use casper_execution_engine::{
             run_genesis_request::RunGenesisRequest,
             step::{StepRequest, StepSuccess},
             BalanceResult, EngineConfig, EngineState, Error, GenesisSuccess, GetBidsRequest,
-            QueryRequest, QueryResult, RewardItem, StepError, SystemContractRegistry,
-            UpgradeConfig, UpgradeSuccess, DEFAULT_MAX_QUERY_DEPTH,
+            HostFunctionDeprecations, QueryRequest, QueryResult, RewardItem, StepError,
+            SystemContractRegistry, TotalStake, TotalStakeRequest, UpgradeConfig, UpgradeSuccess,
+            DEFAULT_MAX_QUERY_DEPTH,
         },
         execution,
     },
@@ -47,7 +48,10 @@ use casper_execution_engine::{
             in_memory::InMemoryGlobalState, lmdb::LmdbGlobalState, scratch::ScratchGlobalState,
             CommitProvider, StateProvider, StateReader,
         },
-        transaction_source::lmdb::LmdbEnvironment,
+        transaction_source::{
+            fault_injection::{FaultConfig, FaultStats},
+            lmdb::LmdbEnvironment,
+        },
         trie::{merkle_proof::TrieMerkleProof, Trie},
         trie_store::lmdb::LmdbTrieStore,
     },
@@ -68,7 +72,7 @@ use casper_types::{
     },
     CLTyped, CLValue, Contract, ContractHash, ContractPackage, ContractPackageHash, ContractWasm,
     DeployHash, DeployInfo, EraId, Gas, Key, KeyTag, ProtocolVersion, PublicKey, RuntimeArgs,
-    StoredValue, Transfer, TransferAddr, URef, U512,
+    StoredValue, Tagged, Transfer, TransferAddr, URef, U512,
 };
 
 use crate::{
@@ -93,6 +97,15 @@ pub type InMemoryWasmTestBuilder = WasmTestBuilder<InMemoryGlobalState>;
 /// Wasm test builder where state is held in LMDB.
 pub type LmdbWasmTestBuilder = WasmTestBuilder<LmdbGlobalState>;
 
+/// The expected change of a purse's balance, see [`WasmTestBuilder::expect_balance_change`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BalanceChange {
+    /// The balance grew by the given amount.
+    Increase(U512),
+    /// The balance shrank by the given amount.
+    Decrease(U512),
+}
+
 /// Builder for simple WASM test
 pub struct WasmTestBuilder<S> {
     /// [`EngineState`] is wrapped in [`Rc`] to work around a missing [`Clone`] implementation
@@ -104,6 +117,8 @@ pub struct WasmTestBuilder<S> {
     genesis_hash: Option<Digest>,
     /// Post state hash.
     post_state_hash: Option<Digest>,
+    /// The state hash the last exec was run on.
+    last_exec_pre_state_hash: Option<Digest>,
     /// Cached transform maps after subsequent successful runs i.e. `transforms[0]` is for first
     /// exec call etc.
     transforms: Vec<ExecutionJournal>,
@@ -142,6 +157,7 @@ impl<S> Clone for WasmTestBuilder<S> {
             upgrade_results: self.upgrade_results.clone(),
             genesis_hash: self.genesis_hash,
             post_state_hash: self.post_state_hash,
+            last_exec_pre_state_hash: self.last_exec_pre_state_hash,
             transforms: self.transforms.clone(),
             genesis_account: self.genesis_account.clone(),
             genesis_transforms: self.genesis_transforms.clone(),
@@ -169,6 +185,7 @@ impl InMemoryWasmTestBuilder {
             engine_state: Rc::new(engine_state),
             genesis_hash: Some(genesis_hash),
             post_state_hash: Some(genesis_hash),
+            last_exec_pre_state_hash: None,
             transforms: Vec::new(),
             genesis_account: None,
             genesis_transforms: None,
@@ -192,6 +209,7 @@ impl InMemoryWasmTestBuilder {
             engine_state: Rc::new(engine_state),
             genesis_hash: maybe_post_state_hash,
             post_state_hash: maybe_post_state_hash,
+            last_exec_pre_state_hash: None,
             transforms: Vec::new(),
             genesis_account: None,
             genesis_transforms: None,
@@ -229,6 +247,53 @@ impl InMemoryWasmTestBuilder {
 
         Self::new(global_state, engine_config, post_state_hash)
     }
+
+    /// Returns an [`InMemoryWasmTestBuilder`] resuming from the global state and post-state hash
+    /// saved to the snapshot file at `path` by [`write_snapshot`](Self::write_snapshot).
+    pub fn from_snapshot<P: AsRef<Path>>(path: P, engine_config: EngineConfig) -> Self {
+        let (global_state, post_state_hash) = InMemoryGlobalState::read_snapshot(path.as_ref())
+            .unwrap_or_else(|error| {
+                panic!(
+                    "should read snapshot {}: {}",
+                    path.as_ref().display(),
+                    error
+                )
+            });
+        Self::new(global_state, engine_config, Some(post_state_hash))
+    }
+
+    /// Saves the whole global state, along with the current post-state hash, to a single snapshot
+    /// file at `path`.
+    ///
+    /// Unlike the directory of an [`LmdbWasmTestBuilder`], the file only depends on the content of
+    /// global state, which makes it suitable as a fixture committed to a repository.
+    pub fn write_snapshot<P: AsRef<Path>>(&self, path: P) -> &Self {
+        self.engine_state
+            .get_state()
+            .write_snapshot(self.get_post_state_hash(), path.as_ref())
+            .expect("should write snapshot");
+        self
+    }
+
+    /// Starts injecting faults into all subsequent reads of global state as described by
+    /// `config`, or stops injecting them if `config` is `None`.
+    ///
+    /// Intended for exercising error paths; see [`FaultConfig`] for the faults available.
+    pub fn set_fault_config(&mut self, config: Option<FaultConfig>) -> &mut Self {
+        self.engine_state
+            .get_state()
+            .set_fault_config(config)
+            .expect("should set fault config");
+        self
+    }
+
+    /// Returns the statistics of faults injected since they were last configured.
+    pub fn get_fault_stats(&self) -> FaultStats {
+        self.engine_state
+            .get_state()
+            .fault_stats()
+            .expect("should get fault stats")
+    }
 }
 
 impl LmdbWasmTestBuilder {
@@ -264,6 +329,7 @@ impl LmdbWasmTestBuilder {
             upgrade_results: Vec::new(),
             genesis_hash: None,
             post_state_hash: None,
+            last_exec_pre_state_hash: None,
             transforms: Vec::new(),
             genesis_account: None,
             genesis_transforms: None,
@@ -360,6 +426,7 @@ impl LmdbWasmTestBuilder {
             upgrade_results: Vec::new(),
             genesis_hash: None,
             post_state_hash: Some(post_state_hash),
+            last_exec_pre_state_hash: None,
             transforms: Vec::new(),
             genesis_account: None,
             genesis_transforms: None,
@@ -412,6 +479,7 @@ impl LmdbWasmTestBuilder {
             exec_request.parent_state_hash = hash;
             exec_request
         };
+        self.last_exec_pre_state_hash = Some(exec_request.parent_state_hash);
 
         let mut exec_results = Vec::new();
         // First execute the request against our scratch global state.
@@ -439,9 +507,9 @@ impl LmdbWasmTestBuilder {
     pub fn write_scratch_to_db(&mut self) -> &mut Self {
         let prestate_hash = self.post_state_hash.expect("Should have genesis hash");
         if let Some(scratch) = self.scratch_engine_state.take() {
-            let new_state_root = self
+            let (new_state_root, _timings) = self
                 .engine_state
-                .write_scratch_to_db(prestate_hash, scratch.into_inner())
+                .write_scratch_to_db(prestate_hash, scratch.into_inner(), None)
                 .unwrap();
             self.post_state_hash = Some(new_state_root);
         }
@@ -472,6 +540,17 @@ where
     engine_state::Error: From<S::Error>,
     S::Error: Into<execution::Error>,
 {
+    /// Sets the host functions deprecated from given protocol versions onwards for all subsequent
+    /// executions.
+    pub fn set_host_function_deprecations(
+        &mut self,
+        host_function_deprecations: HostFunctionDeprecations,
+    ) -> &mut Self {
+        let engine_state = Rc::get_mut(&mut self.engine_state).unwrap();
+        engine_state.set_host_function_deprecations(host_function_deprecations);
+        self
+    }
+
     /// Takes a [`RunGenesisRequest`], executes the request and returns Self.
     pub fn run_genesis(&mut self, run_genesis_request: &RunGenesisRequest) -> &mut Self {
         let system_account = Key::Account(PublicKey::System.to_account_hash());
@@ -658,6 +737,7 @@ where
             exec_request.parent_state_hash = hash;
             exec_request
         };
+        self.last_exec_pre_state_hash = Some(exec_request.parent_state_hash);
 
         let maybe_exec_results = self
             .engine_state
@@ -834,6 +914,97 @@ where
             .cloned()
     }
 
+    /// Returns the effects of all deploys of the last exec, merged into a single map.
+    pub fn get_last_exec_effects(&self) -> AdditiveMap<Key, Transform> {
+        let mut effects = AdditiveMap::new();
+        for exec_result in self
+            .exec_results
+            .last()
+            .expect("Expected to be called after run()")
+        {
+            for (key, transform) in exec_result.execution_journal().clone() {
+                effects.insert_add(key, transform);
+            }
+        }
+        effects
+    }
+
+    /// Expects the last exec to have written `value` under `key`.
+    pub fn expect_write(&mut self, key: Key, value: StoredValue) -> &mut Self {
+        let effects = self.get_last_exec_effects();
+        match effects.get(&key) {
+            Some(Transform::Write(written)) if *written == value => {}
+            other => panic!(
+                "Expected write of {:?} under {}, but instead got: {:?}",
+                value, key, other
+            ),
+        }
+        self
+    }
+
+    /// Expects the last exec to have changed the balance of `purse` by `change`.
+    ///
+    /// The balance before the exec is read from the state the exec was run on, so this still works
+    /// after its effects were committed.
+    pub fn expect_balance_change(&mut self, purse: URef, change: BalanceChange) -> &mut Self {
+        let pre_state_hash = self
+            .last_exec_pre_state_hash
+            .expect("Expected to be called after run()");
+        let balance_key = Key::Balance(purse.addr());
+        let pre_balance = self
+            .query(Some(pre_state_hash), balance_key, &[])
+            .unwrap_or_else(|_| {
+                StoredValue::CLValue(CLValue::from_t(U512::zero()).expect("should create CLValue"))
+            });
+        let post_balance = match self.get_last_exec_effects().get(&balance_key) {
+            Some(transform) => transform
+                .clone()
+                .apply(pre_balance.clone())
+                .expect("should apply balance transform"),
+            None => pre_balance.clone(),
+        };
+        let as_u512 = |value: StoredValue| -> U512 {
+            CLValue::try_from(value)
+                .ok()
+                .and_then(|cl_value| cl_value.into_t().ok())
+                .expect("should parse balance into a U512")
+        };
+        let pre_balance = as_u512(pre_balance);
+        let post_balance = as_u512(post_balance);
+        let expected_post_balance = match change {
+            BalanceChange::Increase(amount) => pre_balance.checked_add(amount),
+            BalanceChange::Decrease(amount) => pre_balance.checked_sub(amount),
+        };
+        if expected_post_balance != Some(post_balance) {
+            panic!(
+                "Expected balance of {} to change by {:?} from {}, but it is {}",
+                purse, change, pre_balance, post_balance
+            );
+        }
+        self
+    }
+
+    /// Expects the last exec to have modified no keys other than those in the key spaces
+    /// `allowed`.  Reads are not taken into account.
+    pub fn expect_no_writes_outside(&mut self, allowed: &[KeyTag]) -> &mut Self {
+        let unexpected: Vec<_> = self
+            .get_last_exec_effects()
+            .iter()
+            .filter(|(key, transform)| {
+                let key_tag: KeyTag = key.tag();
+                !matches!(transform, Transform::Identity) && !allowed.contains(&key_tag)
+            })
+            .map(|(key, transform)| format!("{}: {}", key, transform))
+            .collect();
+        if !unexpected.is_empty() {
+            panic!(
+                "Expected no writes outside {:?}, but instead got: {:#?}",
+                allowed, unexpected
+            );
+        }
+        self
+    }
+
     /// Gets the transform map that's cached between runs
     #[deprecated(
         since = "2.1.0",
@@ -1176,6 +1347,18 @@ where
         get_bids_result.into_success().unwrap()
     }
 
+    /// Gets the [`TotalStake`] of the account identified by the given public key.
+    pub fn get_total_stake(&mut self, public_key: PublicKey) -> TotalStake {
+        let total_stake_request = TotalStakeRequest::new(self.get_post_state_hash(), public_key);
+
+        let total_stake_result = self
+            .engine_state
+            .get_total_stake(CorrelationId::new(), total_stake_request)
+            .unwrap();
+
+        total_stake_result.into_success().unwrap()
+    }
+
     /// Gets [`UnbondingPurses`].
     pub fn get_unbonds(&mut self) -> UnbondingPurses {
         let correlation_id = CorrelationId::new();
@@ -0,0 +1,137 @@
+use assert_matches::assert_matches;
+use once_cell::sync::Lazy;
+
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    MINIMUM_ACCOUNT_CREATION_BALANCE, PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::core::{engine_state::Error, execution};
+use casper_types::{
+    account::AccountHash, contracts::AllowedCaller, runtime_args, ContractHash, RuntimeArgs, U512,
+};
+
+const ALLOWED_CALLERS: &str = "allowed_callers.wasm";
+const CONTRACT_TRANSFER_TO_ACCOUNT: &str = "transfer_to_account_u512.wasm";
+const HASH_KEY_NAME: &str = "restricted_hash";
+const ENTRY_POINT_PING: &str = "ping";
+const ARG_ACTION: &str = "action";
+const ARG_CONTRACT_HASH: &str = "contract_hash";
+const ARG_ACCOUNT: &str = "account";
+const ARG_TARGET: &str = "target";
+const ARG_AMOUNT: &str = "amount";
+const ACTION_INSTALL: &str = "install";
+const ACTION_ALLOW: &str = "allow";
+const ACTION_DISALLOW: &str = "disallow";
+
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1; 32]);
+
+static TRANSFER_1_AMOUNT: Lazy<U512> =
+    Lazy::new(|| U512::from(MINIMUM_ACCOUNT_CREATION_BALANCE) + 1000);
+
+/// Installs the contract whose `ping` entry point is restricted to allowed callers, and funds
+/// `ACCOUNT_1_ADDR`.
+fn setup() -> (InMemoryWasmTestBuilder, ContractHash) {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let exec_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        ALLOWED_CALLERS,
+        runtime_args! { ARG_ACTION => ACTION_INSTALL },
+    )
+    .build();
+    builder.exec(exec_request).expect_success().commit();
+
+    let exec_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_TRANSFER_TO_ACCOUNT,
+        runtime_args! { ARG_TARGET => ACCOUNT_1_ADDR, ARG_AMOUNT => *TRANSFER_1_AMOUNT },
+    )
+    .build();
+    builder.exec(exec_request).expect_success().commit();
+
+    let contract_hash = builder
+        .get_expected_account(*DEFAULT_ACCOUNT_ADDR)
+        .named_keys()
+        .get(HASH_KEY_NAME)
+        .and_then(|key| key.into_hash())
+        .map(ContractHash::new)
+        .expect("should have contract hash");
+    (builder, contract_hash)
+}
+
+/// Allows `account` to call `ping` if `allow` is `true`, or stops allowing it otherwise.
+fn set_allowed(
+    builder: &mut InMemoryWasmTestBuilder,
+    contract_hash: ContractHash,
+    account: AccountHash,
+    allow: bool,
+) {
+    let action = if allow { ACTION_ALLOW } else { ACTION_DISALLOW };
+    let exec_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        ALLOWED_CALLERS,
+        runtime_args! {
+            ARG_ACTION => action,
+            ARG_CONTRACT_HASH => contract_hash,
+            ARG_ACCOUNT => account,
+        },
+    )
+    .build();
+    builder.exec(exec_request).expect_success().commit();
+}
+
+/// Calls `ping` as `sender`, returning the error it failed with, if any.
+fn ping(
+    builder: &mut InMemoryWasmTestBuilder,
+    sender: AccountHash,
+    contract_hash: ContractHash,
+) -> Option<Error> {
+    let exec_request = ExecuteRequestBuilder::contract_call_by_hash(
+        sender,
+        contract_hash,
+        ENTRY_POINT_PING,
+        RuntimeArgs::default(),
+    )
+    .build();
+    builder.exec(exec_request).commit();
+    builder.get_error()
+}
+
+#[ignore]
+#[test]
+fn should_only_allow_listed_callers() {
+    let (mut builder, contract_hash) = setup();
+    assert_matches!(
+        ping(&mut builder, *DEFAULT_ACCOUNT_ADDR, contract_hash),
+        Some(Error::Exec(execution::Error::CallerNotAllowed { entry_point, caller }))
+            if entry_point == ENTRY_POINT_PING
+                && caller == AllowedCaller::Account(*DEFAULT_ACCOUNT_ADDR)
+    );
+
+    set_allowed(&mut builder, contract_hash, *DEFAULT_ACCOUNT_ADDR, true);
+    let error = ping(&mut builder, *DEFAULT_ACCOUNT_ADDR, contract_hash);
+    assert!(error.is_none(), "{:?}", error);
+    // Allowing one account doesn't allow any other.
+    assert_matches!(
+        ping(&mut builder, ACCOUNT_1_ADDR, contract_hash),
+        Some(Error::Exec(execution::Error::CallerNotAllowed { caller, .. }))
+            if caller == AllowedCaller::Account(ACCOUNT_1_ADDR)
+    );
+}
+
+#[ignore]
+#[test]
+fn should_reject_caller_no_longer_allowed() {
+    let (mut builder, contract_hash) = setup();
+    set_allowed(&mut builder, contract_hash, ACCOUNT_1_ADDR, true);
+    let error = ping(&mut builder, ACCOUNT_1_ADDR, contract_hash);
+    assert!(error.is_none(), "{:?}", error);
+
+    set_allowed(&mut builder, contract_hash, ACCOUNT_1_ADDR, false);
+    assert_matches!(
+        ping(&mut builder, ACCOUNT_1_ADDR, contract_hash),
+        Some(Error::Exec(execution::Error::CallerNotAllowed { caller, .. }))
+            if caller == AllowedCaller::Account(ACCOUNT_1_ADDR)
+    );
+}
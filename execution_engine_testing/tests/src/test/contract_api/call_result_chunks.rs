@@ -0,0 +1,160 @@
+use assert_matches::assert_matches;
+
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::{
+    core::{
+        engine_state::{Error, ExecuteRequest},
+        execution::Error as ExecError,
+    },
+    shared::wasm_config::DEFAULT_MAX_CALL_RESULT_SIZE,
+};
+use casper_types::{
+    bytesrepr::{Bytes, FromBytes, ToBytes},
+    runtime_args, ApiError, CLTyped, CLValue, Key, RuntimeArgs, StoredValue,
+};
+
+const CONTRACT_CALL_RESULT_CHUNKS: &str = "call_result_chunks.wasm";
+const CONTRACT_CALL_RESULT_CHUNKS_CALL: &str = "call_result_chunks_call.wasm";
+const ARG_SIZE: &str = "size";
+const ARG_CHUNK_SIZE: &str = "chunk_size";
+const RESULT_VALUE: &str = "result_value";
+const RESULT_CHUNK_SIZES: &str = "result_chunk_sizes";
+const RESULT_PAST_END_ERROR: &str = "result_past_end_error";
+const RESULT_AFTER_LAST_CHUNK_ERROR: &str = "result_after_last_chunk_error";
+
+fn setup() -> InMemoryWasmTestBuilder {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let exec_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_CALL_RESULT_CHUNKS,
+        RuntimeArgs::default(),
+    )
+    .build();
+    builder.exec(exec_request).expect_success().commit();
+    builder
+}
+
+/// Reads the value returned for `size` in chunks of `chunk_size` bytes.
+fn call_request(size: u32, chunk_size: u32) -> ExecuteRequest {
+    ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_CALL_RESULT_CHUNKS_CALL,
+        runtime_args! {
+            ARG_SIZE => size,
+            ARG_CHUNK_SIZE => chunk_size,
+        },
+    )
+    .build()
+}
+
+fn query_result<T: CLTyped + FromBytes>(builder: &InMemoryWasmTestBuilder, name: &str) -> T {
+    let stored_value = builder
+        .query(
+            None,
+            Key::Account(*DEFAULT_ACCOUNT_ADDR),
+            &[name.to_string()],
+        )
+        .expect("should have result");
+    match stored_value {
+        StoredValue::CLValue(cl_value) => CLValue::into_t(cl_value).expect("should have type"),
+        other => panic!("unexpected stored value {:?}", other),
+    }
+}
+
+/// The value returned by the `return_bytes` entry point for `size`.
+fn expected_value(size: u32) -> Bytes {
+    let mut value = vec![0; size as usize];
+    for (index, byte) in value.iter_mut().take(256).enumerate() {
+        *byte = index as u8;
+    }
+    Bytes::from(value)
+}
+
+#[ignore]
+#[test]
+fn should_read_call_result_in_chunks() {
+    let mut builder = setup();
+    let size = 300;
+    builder
+        .exec(call_request(size, 64))
+        .expect_success()
+        .commit();
+
+    // The serialized value has a 4 byte length prefix.
+    let expected = expected_value(size);
+    assert_eq!(expected.serialized_length(), 304);
+    let value: Bytes = query_result(&builder, RESULT_VALUE);
+    assert_eq!(value, expected);
+    let chunk_sizes: Vec<u32> = query_result(&builder, RESULT_CHUNK_SIZES);
+    assert_eq!(chunk_sizes, vec![64, 64, 64, 64, 48]);
+}
+
+#[ignore]
+#[test]
+fn should_read_call_result_in_a_single_oversized_chunk() {
+    let mut builder = setup();
+    let size = 10;
+    builder
+        .exec(call_request(size, 1024))
+        .expect_success()
+        .commit();
+
+    let value: Bytes = query_result(&builder, RESULT_VALUE);
+    assert_eq!(value, expected_value(size));
+    let chunk_sizes: Vec<u32> = query_result(&builder, RESULT_CHUNK_SIZES);
+    assert_eq!(chunk_sizes, vec![14]);
+}
+
+#[ignore]
+#[test]
+fn should_fail_to_read_call_result_past_the_end() {
+    let mut builder = setup();
+    builder
+        .exec(call_request(100, 32))
+        .expect_success()
+        .commit();
+
+    // Reading past the end fails without discarding the value, which is then read in full.
+    let past_end_error: Option<u32> = query_result(&builder, RESULT_PAST_END_ERROR);
+    assert_eq!(past_end_error, Some(u32::from(ApiError::InvalidArgument)));
+    let value: Bytes = query_result(&builder, RESULT_VALUE);
+    assert_eq!(value, expected_value(100));
+}
+
+#[ignore]
+#[test]
+fn should_clear_host_buffer_after_last_chunk() {
+    let mut builder = setup();
+    builder
+        .exec(call_request(100, 32))
+        .expect_success()
+        .commit();
+
+    let after_last_chunk_error: Option<u32> = query_result(&builder, RESULT_AFTER_LAST_CHUNK_ERROR);
+    assert_eq!(
+        after_last_chunk_error,
+        Some(u32::from(ApiError::HostBufferEmpty))
+    );
+}
+
+#[ignore]
+#[test]
+fn should_fail_to_return_value_larger_than_max_call_result_size() {
+    let mut builder = setup();
+    builder
+        .exec(call_request(DEFAULT_MAX_CALL_RESULT_SIZE, 1024))
+        .expect_failure()
+        .commit();
+
+    let error = builder.get_error().expect("should have error");
+    assert_matches!(
+        error,
+        Error::Exec(ExecError::ReturnValueTooLarge { size, max })
+            if max == DEFAULT_MAX_CALL_RESULT_SIZE && size > max as usize
+    );
+}
@@ -0,0 +1,232 @@
+use assert_matches::assert_matches;
+use once_cell::sync::Lazy;
+
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR, DEFAULT_PROTOCOL_VERSION,
+    MINIMUM_ACCOUNT_CREATION_BALANCE, PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::core::{
+    engine_state::{EngineConfig, Error},
+    execution,
+};
+use casper_types::{
+    account::AccountHash, runtime_args, ContractHash, ContractPackageHash, RuntimeArgs, U512,
+};
+
+const CONTRACT_PACKAGE_PAUSE: &str = "contract_package_pause.wasm";
+const CONTRACT_TRANSFER_TO_ACCOUNT: &str = "transfer_to_account_u512.wasm";
+const HASH_KEY_NAME: &str = "pausable_hash";
+const PACKAGE_HASH_KEY_NAME: &str = "pausable_package_hash";
+const ENTRY_POINT_PING: &str = "ping";
+const ENTRY_POINT_PAUSE_AND_PING: &str = "pause_and_ping";
+const ARG_ACTION: &str = "action";
+const ARG_CONTRACT_PACKAGE_HASH: &str = "contract_package_hash";
+const ARG_TARGET: &str = "target";
+const ARG_AMOUNT: &str = "amount";
+const ACTION_INSTALL: &str = "install";
+const ACTION_INSTALL_LOCKED: &str = "install_locked";
+const ACTION_PAUSE: &str = "pause";
+const ACTION_RESUME: &str = "resume";
+
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1; 32]);
+
+static TRANSFER_1_AMOUNT: Lazy<U512> =
+    Lazy::new(|| U512::from(MINIMUM_ACCOUNT_CREATION_BALANCE) + 1000);
+
+/// Installs the pausable contract, returning its package hash and contract hash.
+fn setup(
+    engine_config: EngineConfig,
+    install_action: &str,
+) -> (InMemoryWasmTestBuilder, ContractPackageHash, ContractHash) {
+    let mut builder = InMemoryWasmTestBuilder::new_with_config(engine_config);
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let exec_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_PACKAGE_PAUSE,
+        runtime_args! { ARG_ACTION => install_action },
+    )
+    .build();
+    builder.exec(exec_request).expect_success().commit();
+
+    let account = builder.get_expected_account(*DEFAULT_ACCOUNT_ADDR);
+    let contract_package_hash = account
+        .named_keys()
+        .get(PACKAGE_HASH_KEY_NAME)
+        .and_then(|key| key.into_hash())
+        .map(ContractPackageHash::new)
+        .expect("should have contract package hash");
+    let contract_hash = account
+        .named_keys()
+        .get(HASH_KEY_NAME)
+        .and_then(|key| key.into_hash())
+        .map(ContractHash::new)
+        .expect("should have contract hash");
+    (builder, contract_package_hash, contract_hash)
+}
+
+fn pause_enabled_config() -> EngineConfig {
+    EngineConfig::default().with_contract_package_pause_version(*DEFAULT_PROTOCOL_VERSION)
+}
+
+/// Runs the pause or resume action as `sender`, returning the error it failed with, if any.
+fn set_paused(
+    builder: &mut InMemoryWasmTestBuilder,
+    sender: AccountHash,
+    contract_package_hash: ContractPackageHash,
+    paused: bool,
+) -> Option<Error> {
+    let action = if paused { ACTION_PAUSE } else { ACTION_RESUME };
+    let exec_request = ExecuteRequestBuilder::standard(
+        sender,
+        CONTRACT_PACKAGE_PAUSE,
+        runtime_args! {
+            ARG_ACTION => action,
+            ARG_CONTRACT_PACKAGE_HASH => contract_package_hash,
+        },
+    )
+    .build();
+    builder.exec(exec_request).commit();
+    builder.get_error()
+}
+
+/// Calls `ping` through the package, returning the error it failed with, if any.
+fn ping_package(
+    builder: &mut InMemoryWasmTestBuilder,
+    contract_package_hash: ContractPackageHash,
+) -> Option<Error> {
+    let exec_request = ExecuteRequestBuilder::versioned_contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        contract_package_hash,
+        None,
+        ENTRY_POINT_PING,
+        RuntimeArgs::default(),
+    )
+    .build();
+    builder.exec(exec_request).commit();
+    builder.get_error()
+}
+
+/// Calls `ping` through the contract hash, returning the error it failed with, if any.
+fn ping_contract(
+    builder: &mut InMemoryWasmTestBuilder,
+    contract_hash: ContractHash,
+) -> Option<Error> {
+    let exec_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        contract_hash,
+        ENTRY_POINT_PING,
+        RuntimeArgs::default(),
+    )
+    .build();
+    builder.exec(exec_request).commit();
+    builder.get_error()
+}
+
+#[ignore]
+#[test]
+fn should_pause_and_resume_calls_into_package() {
+    let (mut builder, contract_package_hash, contract_hash) =
+        setup(pause_enabled_config(), ACTION_INSTALL);
+    assert!(ping_package(&mut builder, contract_package_hash).is_none());
+
+    let error = set_paused(
+        &mut builder,
+        *DEFAULT_ACCOUNT_ADDR,
+        contract_package_hash,
+        true,
+    );
+    assert!(error.is_none(), "{:?}", error);
+    assert_matches!(
+        ping_package(&mut builder, contract_package_hash),
+        Some(Error::Exec(execution::Error::ContractPackagePaused(hash)))
+            if hash == contract_package_hash
+    );
+    // Naming a contract hash directly doesn't get around the pause.
+    assert_matches!(
+        ping_contract(&mut builder, contract_hash),
+        Some(Error::Exec(execution::Error::ContractPackagePaused(hash)))
+            if hash == contract_package_hash
+    );
+
+    let error = set_paused(
+        &mut builder,
+        *DEFAULT_ACCOUNT_ADDR,
+        contract_package_hash,
+        false,
+    );
+    assert!(error.is_none(), "{:?}", error);
+    assert!(ping_package(&mut builder, contract_package_hash).is_none());
+    assert!(ping_contract(&mut builder, contract_hash).is_none());
+}
+
+#[ignore]
+#[test]
+fn should_not_pause_package_of_another_account() {
+    let (mut builder, contract_package_hash, _) = setup(pause_enabled_config(), ACTION_INSTALL);
+    let exec_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_TRANSFER_TO_ACCOUNT,
+        runtime_args! { ARG_TARGET => ACCOUNT_1_ADDR, ARG_AMOUNT => *TRANSFER_1_AMOUNT },
+    )
+    .build();
+    builder.exec(exec_request).expect_success().commit();
+
+    assert_matches!(
+        set_paused(&mut builder, ACCOUNT_1_ADDR, contract_package_hash, true),
+        Some(Error::Exec(execution::Error::ForgedReference(_)))
+    );
+    assert!(ping_package(&mut builder, contract_package_hash).is_none());
+}
+
+#[ignore]
+#[test]
+fn should_not_pause_locked_package() {
+    let (mut builder, contract_package_hash, _) =
+        setup(pause_enabled_config(), ACTION_INSTALL_LOCKED);
+
+    assert_matches!(
+        set_paused(&mut builder, *DEFAULT_ACCOUNT_ADDR, contract_package_hash, true),
+        Some(Error::Exec(execution::Error::LockedContract(hash))) if hash == contract_package_hash
+    );
+    assert!(ping_package(&mut builder, contract_package_hash).is_none());
+}
+
+#[ignore]
+#[test]
+fn should_allow_calls_from_within_paused_package() {
+    let (mut builder, contract_package_hash, _) = setup(pause_enabled_config(), ACTION_INSTALL);
+
+    // The stored session pauses its own package, then calls into it.
+    let exec_request = ExecuteRequestBuilder::versioned_contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        contract_package_hash,
+        None,
+        ENTRY_POINT_PAUSE_AND_PING,
+        RuntimeArgs::default(),
+    )
+    .build();
+    builder.exec(exec_request).expect_success().commit();
+
+    assert_matches!(
+        ping_package(&mut builder, contract_package_hash),
+        Some(Error::Exec(execution::Error::ContractPackagePaused(_)))
+    );
+}
+
+#[ignore]
+#[test]
+fn should_not_pause_package_before_activation() {
+    let (mut builder, contract_package_hash, _) = setup(EngineConfig::default(), ACTION_INSTALL);
+
+    assert_matches!(
+        set_paused(
+            &mut builder,
+            *DEFAULT_ACCOUNT_ADDR,
+            contract_package_hash,
+            true
+        ),
+        Some(Error::Exec(execution::Error::InactiveHostFunction(_)))
+    );
+    assert!(ping_package(&mut builder, contract_package_hash).is_none());
+}
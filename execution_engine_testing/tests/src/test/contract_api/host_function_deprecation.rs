@@ -0,0 +1,85 @@
+use assert_matches::assert_matches;
+
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR, DEFAULT_PROTOCOL_VERSION,
+    PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::core::{
+    engine_state::{DeprecatedHostFunction, DeprecationAction, Error, HostFunctionDeprecations},
+    execution,
+};
+use casper_types::{runtime_args, ProtocolVersion, RuntimeArgs};
+
+const CONTRACT_GET_BLOCKTIME: &str = "get_blocktime.wasm";
+const ARG_KNOWN_BLOCK_TIME: &str = "known_block_time";
+const GET_BLOCKTIME: &str = "casper_get_blocktime";
+const BLOCK_TIME: u64 = 42;
+
+fn deprecation(since: ProtocolVersion, action: DeprecationAction) -> DeprecatedHostFunction {
+    DeprecatedHostFunction {
+        name: GET_BLOCKTIME.to_string(),
+        since,
+        action,
+    }
+}
+
+/// Runs the contract calling `casper_get_blocktime` with `deprecation` in place.
+fn run_get_blocktime(deprecation: DeprecatedHostFunction) -> InMemoryWasmTestBuilder {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder
+        .run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST)
+        .set_host_function_deprecations(HostFunctionDeprecations::new(vec![deprecation]));
+
+    let exec_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_GET_BLOCKTIME,
+        runtime_args! { ARG_KNOWN_BLOCK_TIME => BLOCK_TIME },
+    )
+    .with_block_time(BLOCK_TIME)
+    .build();
+    builder.exec(exec_request).commit();
+    builder
+}
+
+#[ignore]
+#[test]
+fn should_fail_call_of_rejected_host_function() {
+    let deprecation = deprecation(*DEFAULT_PROTOCOL_VERSION, DeprecationAction::Reject);
+    let builder = run_get_blocktime(deprecation.clone());
+
+    assert_matches!(
+        builder.get_error(),
+        Some(Error::Exec(execution::Error::DeprecatedHostFunction(name))) if name == GET_BLOCKTIME
+    );
+    let response = builder
+        .get_exec_result_owned(0)
+        .expect("there should be a response");
+    assert_eq!(response[0].deprecation_warnings(), &[deprecation]);
+}
+
+#[ignore]
+#[test]
+fn should_record_warned_host_function_in_execution_result() {
+    let deprecation = deprecation(*DEFAULT_PROTOCOL_VERSION, DeprecationAction::Warn);
+    let mut builder = run_get_blocktime(deprecation.clone());
+
+    builder.expect_success();
+    let response = builder
+        .get_exec_result_owned(0)
+        .expect("there should be a response");
+    assert_eq!(response[0].deprecation_warnings(), &[deprecation]);
+}
+
+#[ignore]
+#[test]
+fn should_not_apply_deprecation_before_its_protocol_version() {
+    let since = ProtocolVersion::from_parts(1, 1, 0);
+    assert!(since > *DEFAULT_PROTOCOL_VERSION);
+    let mut builder = run_get_blocktime(deprecation(since, DeprecationAction::Reject));
+
+    builder.expect_success();
+    let response = builder
+        .get_exec_result_owned(0)
+        .expect("there should be a response");
+    assert!(response[0].deprecation_warnings().is_empty());
+}
@@ -7,6 +7,8 @@ use casper_types::{account::AccountHash, contracts::NamedKeys, runtime_args, Key
 const CONTRACT_LIST_NAMED_KEYS: &str = "list_named_keys.wasm";
 const NEW_NAME_ACCOUNT: &str = "Account";
 const NEW_NAME_HASH: &str = "Hash";
+/// Names added after the others, out of order and sorting differently by byte than by letter.
+const NEW_NAMES_UNORDERED: [&str; 5] = ["b", "\u{e9}", "a", "B", "ab"];
 const ARG_INITIAL_NAMED_KEYS: &str = "initial_named_args";
 const ARG_NEW_NAMED_KEYS: &str = "new_named_keys";
 
@@ -18,15 +20,19 @@ fn should_list_named_keys() {
 
     let initial_named_keys: NamedKeys = NamedKeys::new();
 
-    let new_named_keys = {
+    // Passed as a list rather than as `NamedKeys`, so that the contract adds them in this order.
+    let new_named_keys: Vec<(String, Key)> = {
         let account_hash = AccountHash::new([1; 32]);
-        let mut named_keys = NamedKeys::new();
-        assert!(named_keys
-            .insert(NEW_NAME_ACCOUNT.to_string(), Key::Account(account_hash))
-            .is_none());
-        assert!(named_keys
-            .insert(NEW_NAME_HASH.to_string(), Key::Hash([2; 32]))
-            .is_none());
+        let mut named_keys = vec![
+            (NEW_NAME_HASH.to_string(), Key::Hash([2; 32])),
+            (NEW_NAME_ACCOUNT.to_string(), Key::Account(account_hash)),
+        ];
+        named_keys.extend(
+            NEW_NAMES_UNORDERED
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (name.to_string(), Key::Hash([3 + index as u8; 32]))),
+        );
         named_keys
     };
 
@@ -1,4 +1,7 @@
 mod account;
+mod allowed_callers;
+mod call_result_chunks;
+mod contract_package_pause;
 mod create_purse;
 mod dictionary;
 mod get_arg;
@@ -6,6 +9,7 @@ mod get_blocktime;
 mod get_call_stack;
 mod get_caller;
 mod get_phase;
+mod host_function_deprecation;
 mod list_authorization_keys;
 mod list_named_keys;
 mod main_purse;
@@ -16,6 +20,8 @@ mod revert;
 mod runtime;
 mod subcall;
 mod transfer;
+mod transfer_batch;
 mod transfer_cached;
 mod transfer_purse_to_account;
 mod transfer_purse_to_purse;
+mod view;
@@ -0,0 +1,158 @@
+use assert_matches::assert_matches;
+
+use casper_engine_test_support::{
+    utils, DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    DEFAULT_PAYMENT, PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::core::engine_state::{
+    account_sequence::{account_sequence_key, ARG_ACCOUNT_SEQUENCE},
+    EngineConfig, Error, ExecuteRequest,
+};
+use casper_types::{runtime_args, CLValue, RuntimeArgs, StoredValue};
+
+const ARG_AMOUNT: &str = "amount";
+const DO_NOTHING_WASM: &str = "do_nothing.wasm";
+
+fn do_nothing_request(deploy_hash: [u8; 32], maybe_sequence: Option<u64>) -> ExecuteRequest {
+    let mut payment_args = runtime_args! { ARG_AMOUNT => *DEFAULT_PAYMENT };
+    if let Some(sequence) = maybe_sequence {
+        payment_args
+            .insert(ARG_ACCOUNT_SEQUENCE, sequence)
+            .expect("should insert sequence");
+    }
+
+    let deploy = DeployItemBuilder::new()
+        .with_address(*DEFAULT_ACCOUNT_ADDR)
+        .with_session_code(DO_NOTHING_WASM, RuntimeArgs::default())
+        .with_empty_payment_bytes(payment_args)
+        .with_authorization_keys(&[*DEFAULT_ACCOUNT_ADDR])
+        .with_deploy_hash(deploy_hash)
+        .build();
+
+    ExecuteRequestBuilder::new().push_deploy(deploy).build()
+}
+
+fn setup() -> InMemoryWasmTestBuilder {
+    let engine_config = EngineConfig::default().with_account_sequence_enforced(true);
+    let mut builder = InMemoryWasmTestBuilder::new_with_config(engine_config);
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+    builder
+}
+
+fn last_account_sequence(builder: &InMemoryWasmTestBuilder) -> Option<u64> {
+    match builder.query(None, account_sequence_key(*DEFAULT_ACCOUNT_ADDR), &[]) {
+        Ok(StoredValue::CLValue(cl_value)) => {
+            Some(CLValue::into_t(cl_value).expect("should be u64"))
+        }
+        Ok(other) => panic!("unexpected stored value {:?}", other),
+        Err(_) => None,
+    }
+}
+
+#[ignore]
+#[test]
+fn should_execute_deploys_with_consecutive_sequence_numbers() {
+    let mut builder = setup();
+    assert_eq!(last_account_sequence(&builder), None);
+
+    builder
+        .exec(do_nothing_request([1; 32], Some(1)))
+        .expect_success()
+        .commit();
+    assert_eq!(last_account_sequence(&builder), Some(1));
+
+    builder
+        .exec(do_nothing_request([2; 32], Some(2)))
+        .expect_success()
+        .commit();
+    assert_eq!(last_account_sequence(&builder), Some(2));
+}
+
+#[ignore]
+#[test]
+fn should_raise_precondition_failure_for_missing_sequence_number() {
+    let mut builder = setup();
+
+    builder.exec(do_nothing_request([1; 32], None)).commit();
+
+    let response = builder
+        .get_exec_result_owned(0)
+        .expect("there should be a response");
+    let precondition_failure = utils::get_precondition_failure(&response);
+    assert_matches!(
+        precondition_failure,
+        Error::InvalidAccountSequence {
+            last: 0,
+            actual: None
+        }
+    );
+}
+
+#[ignore]
+#[test]
+fn should_raise_precondition_failure_for_replayed_sequence_number() {
+    let mut builder = setup();
+
+    builder
+        .exec(do_nothing_request([1; 32], Some(1)))
+        .expect_success()
+        .commit();
+
+    // A different deploy hash doesn't help if the sequence number was already used.
+    builder.exec(do_nothing_request([2; 32], Some(1))).commit();
+
+    let response = builder
+        .get_exec_result_owned(1)
+        .expect("there should be a response");
+    let precondition_failure = utils::get_precondition_failure(&response);
+    assert_matches!(
+        precondition_failure,
+        Error::InvalidAccountSequence {
+            last: 1,
+            actual: Some(1)
+        }
+    );
+    assert_eq!(last_account_sequence(&builder), Some(1));
+}
+
+#[ignore]
+#[test]
+fn should_raise_precondition_failure_for_skipped_sequence_number() {
+    let mut builder = setup();
+
+    builder.exec(do_nothing_request([1; 32], Some(2))).commit();
+
+    let response = builder
+        .get_exec_result_owned(0)
+        .expect("there should be a response");
+    let precondition_failure = utils::get_precondition_failure(&response);
+    assert_matches!(
+        precondition_failure,
+        Error::InvalidAccountSequence {
+            last: 0,
+            actual: Some(2)
+        }
+    );
+}
+
+#[ignore]
+#[test]
+fn should_consume_sequence_number_when_session_fails() {
+    let mut builder = setup();
+
+    let deploy = DeployItemBuilder::new()
+        .with_address(*DEFAULT_ACCOUNT_ADDR)
+        .with_session_bytes(vec![0; 16], RuntimeArgs::default())
+        .with_empty_payment_bytes(runtime_args! {
+            ARG_AMOUNT => *DEFAULT_PAYMENT,
+            ARG_ACCOUNT_SEQUENCE => 1u64,
+        })
+        .with_authorization_keys(&[*DEFAULT_ACCOUNT_ADDR])
+        .with_deploy_hash([1; 32])
+        .build();
+    let exec_request = ExecuteRequestBuilder::new().push_deploy(deploy).build();
+
+    // Invalid session code is charged for, so the sequence number must be used up as well.
+    builder.exec(exec_request).expect_failure().commit();
+    assert_eq!(last_account_sequence(&builder), Some(1));
+}
@@ -0,0 +1,140 @@
+use assert_matches::assert_matches;
+
+use casper_engine_test_support::{
+    utils, DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    DEFAULT_PAYMENT, PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::{
+    core::engine_state::{EngineConfig, Error, ExecuteRequest},
+    shared::newtypes::CorrelationId,
+};
+use casper_types::{runtime_args, Gas, RuntimeArgs, U512};
+
+const ARG_AMOUNT: &str = "amount";
+const DO_NOTHING_WASM: &str = "do_nothing.wasm";
+
+fn do_nothing_request(deploy_hash: [u8; 32], payment_args: RuntimeArgs) -> ExecuteRequest {
+    let deploy = DeployItemBuilder::new()
+        .with_address(*DEFAULT_ACCOUNT_ADDR)
+        .with_session_code(DO_NOTHING_WASM, RuntimeArgs::default())
+        .with_empty_payment_bytes(payment_args)
+        .with_authorization_keys(&[*DEFAULT_ACCOUNT_ADDR])
+        .with_deploy_hash(deploy_hash)
+        .build();
+
+    ExecuteRequestBuilder::new().push_deploy(deploy).build()
+}
+
+/// Returns a request declaring the default payment amount as its gas limit.
+fn default_gas_limit_request(deploy_hash: [u8; 32]) -> ExecuteRequest {
+    do_nothing_request(
+        deploy_hash,
+        runtime_args! { ARG_AMOUNT => *DEFAULT_PAYMENT },
+    )
+}
+
+fn setup(computation_quota_per_era: U512) -> InMemoryWasmTestBuilder {
+    let engine_config =
+        EngineConfig::default().with_computation_quota_per_era(computation_quota_per_era.as_u64());
+    let mut builder = InMemoryWasmTestBuilder::new_with_config(engine_config);
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+    builder
+}
+
+fn remaining_quota(builder: &InMemoryWasmTestBuilder) -> Gas {
+    builder
+        .get_engine_state()
+        .get_remaining_computation_quota(
+            CorrelationId::new(),
+            builder.get_post_state_hash(),
+            *DEFAULT_ACCOUNT_ADDR,
+        )
+        .expect("should read remaining quota")
+        .expect("should have computation quota")
+}
+
+#[ignore]
+#[test]
+fn should_deduct_gas_used_from_quota_without_charging() {
+    let quota = *DEFAULT_PAYMENT * 3;
+    let mut builder = setup(quota);
+    let main_purse = builder
+        .get_expected_account(*DEFAULT_ACCOUNT_ADDR)
+        .main_purse();
+    let balance_before = builder.get_purse_balance(main_purse);
+
+    builder
+        .exec(default_gas_limit_request([1; 32]))
+        .expect_success()
+        .commit();
+
+    let response = builder
+        .get_exec_result_owned(0)
+        .expect("there should be a response");
+    let cost = response[0].cost();
+    assert!(cost.value() > U512::zero());
+    assert_eq!(remaining_quota(&builder), Gas::new(quota - cost.value()));
+    assert_eq!(builder.get_purse_balance(main_purse), balance_before);
+}
+
+#[ignore]
+#[test]
+fn should_reject_deploy_exceeding_remaining_quota() {
+    let mut builder = setup(*DEFAULT_PAYMENT + 1);
+
+    // The first deploy fits into the whole quota, but uses up more than a single mote of gas.
+    builder
+        .exec(default_gas_limit_request([1; 32]))
+        .expect_success()
+        .commit();
+    let remaining = remaining_quota(&builder);
+    assert!(remaining < Gas::new(*DEFAULT_PAYMENT));
+
+    builder.exec(default_gas_limit_request([2; 32])).commit();
+    let response = builder
+        .get_exec_result_owned(1)
+        .expect("there should be a response");
+    let precondition_failure = utils::get_precondition_failure(&response);
+    assert_matches!(
+        precondition_failure,
+        Error::ComputationQuotaExceeded { requested, remaining: actual_remaining }
+            if *requested == Gas::new(*DEFAULT_PAYMENT) && *actual_remaining == remaining
+    );
+    // A rejected deploy doesn't use up any of the quota.
+    assert_eq!(remaining_quota(&builder), remaining);
+}
+
+#[ignore]
+#[test]
+fn should_replenish_quota_in_next_era() {
+    let quota = *DEFAULT_PAYMENT + 1;
+    let mut builder = setup(quota);
+    builder
+        .exec(default_gas_limit_request([1; 32]))
+        .expect_success()
+        .commit();
+    assert!(remaining_quota(&builder) < Gas::new(*DEFAULT_PAYMENT));
+
+    builder.advance_era(vec![]);
+    assert_eq!(remaining_quota(&builder), Gas::new(quota));
+
+    builder
+        .exec(default_gas_limit_request([2; 32]))
+        .expect_success()
+        .commit();
+}
+
+#[ignore]
+#[test]
+fn should_reject_deploy_without_gas_limit() {
+    let mut builder = setup(*DEFAULT_PAYMENT);
+
+    builder
+        .exec(do_nothing_request([1; 32], RuntimeArgs::new()))
+        .commit();
+    let response = builder
+        .get_exec_result_owned(0)
+        .expect("there should be a response");
+    let precondition_failure = utils::get_precondition_failure(&response);
+    assert_matches!(precondition_failure, Error::MissingGasLimit);
+}
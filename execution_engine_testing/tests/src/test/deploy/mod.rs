@@ -1,5 +1,8 @@
+mod account_sequence;
+mod computation_quota;
 mod context_association;
 mod non_standard_payment;
 mod preconditions;
+mod priority_fee;
 mod receipts;
 mod stored_contracts;
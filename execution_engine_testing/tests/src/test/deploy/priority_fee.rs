@@ -0,0 +1,79 @@
+use assert_matches::assert_matches;
+
+use casper_engine_test_support::{
+    utils, DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    DEFAULT_PAYMENT, DEFAULT_PROTOCOL_VERSION, PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::core::{
+    engine_state::{EngineConfig, Error, ExecuteRequest},
+    execution,
+};
+use casper_types::{runtime_args, RuntimeArgs, U512};
+
+const ARG_AMOUNT: &str = "amount";
+const DO_NOTHING_WASM: &str = "do_nothing.wasm";
+
+fn do_nothing_request(priority_fee: U512) -> ExecuteRequest {
+    let payment_args = runtime_args! {
+        ARG_AMOUNT => *DEFAULT_PAYMENT,
+    };
+
+    let deploy = DeployItemBuilder::new()
+        .with_address(*DEFAULT_ACCOUNT_ADDR)
+        .with_session_code(DO_NOTHING_WASM, RuntimeArgs::default())
+        .with_empty_payment_bytes(payment_args)
+        .with_authorization_keys(&[*DEFAULT_ACCOUNT_ADDR])
+        .with_deploy_hash([42; 32])
+        .with_priority_fee(priority_fee)
+        .build();
+
+    ExecuteRequestBuilder::new().push_deploy(deploy).build()
+}
+
+fn setup(engine_config: EngineConfig) -> InMemoryWasmTestBuilder {
+    let mut builder = InMemoryWasmTestBuilder::new_with_config(engine_config);
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+    builder
+}
+
+#[ignore]
+#[test]
+fn should_reserve_priority_fee_from_session_gas_limit() {
+    let engine_config =
+        EngineConfig::default().with_priority_fees_version(*DEFAULT_PROTOCOL_VERSION);
+    let mut builder = setup(engine_config);
+
+    // Offering the whole payment amount as a priority fee leaves no gas for the session.
+    builder.exec(do_nothing_request(*DEFAULT_PAYMENT)).commit();
+
+    let response = builder
+        .get_exec_result_owned(0)
+        .expect("there should be a response");
+    let execution_result = utils::get_success_result(&response);
+    let error = execution_result.as_error().expect("should have error");
+    assert_matches!(error, Error::Exec(execution::Error::GasLimit));
+}
+
+#[ignore]
+#[test]
+fn should_execute_deploy_with_small_priority_fee() {
+    let engine_config =
+        EngineConfig::default().with_priority_fees_version(*DEFAULT_PROTOCOL_VERSION);
+    let mut builder = setup(engine_config);
+
+    builder
+        .exec(do_nothing_request(U512::from(1_000)))
+        .expect_success()
+        .commit();
+}
+
+#[ignore]
+#[test]
+fn should_ignore_priority_fee_before_activation() {
+    let mut builder = setup(EngineConfig::default());
+
+    builder
+        .exec(do_nothing_request(*DEFAULT_PAYMENT))
+        .expect_success()
+        .commit();
+}
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use assert_matches::assert_matches;
+
+use casper_engine_test_support::{
+    utils, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::{
+    core::engine_state::{Error, ExecuteRequest, QueryRequest},
+    shared::newtypes::CorrelationId,
+    storage::{self, transaction_source::fault_injection::FaultConfig},
+};
+use casper_types::{bytesrepr, Key, RuntimeArgs};
+
+const DO_NOTHING_WASM: &str = "do_nothing.wasm";
+const SEED: u64 = 42;
+
+fn do_nothing_request() -> ExecuteRequest {
+    ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, DO_NOTHING_WASM, RuntimeArgs::new())
+        .build()
+}
+
+fn setup() -> InMemoryWasmTestBuilder {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+    builder
+}
+
+#[ignore]
+#[test]
+fn should_raise_precondition_failure_when_global_state_reads_fail() {
+    let mut builder = setup();
+
+    builder.set_fault_config(Some(FaultConfig::new(SEED).with_read_error_rate(1.0)));
+    builder.exec(do_nothing_request());
+
+    let response = builder
+        .get_exec_result_owned(0)
+        .expect("there should be a response");
+    let precondition_failure = utils::get_precondition_failure(&response);
+    assert_matches!(
+        precondition_failure,
+        Error::Storage(storage::error::Error::BytesRepr(
+            bytesrepr::Error::Formatting
+        ))
+    );
+    assert!(builder.get_fault_stats().injected_errors > 0);
+}
+
+#[ignore]
+#[test]
+fn should_execute_deploy_with_delayed_reads() {
+    let mut builder = setup();
+
+    builder.set_fault_config(Some(
+        FaultConfig::new(SEED).with_max_read_delay(Duration::from_millis(1)),
+    ));
+    builder.exec(do_nothing_request()).expect_success().commit();
+
+    let stats = builder.get_fault_stats();
+    assert!(stats.reads > 0);
+    assert_eq!(stats.delayed_reads, stats.reads);
+    assert_eq!(stats.injected_errors, 0);
+}
+
+#[ignore]
+#[test]
+fn should_fail_query_when_global_state_reads_fail() {
+    let mut builder = setup();
+    let query_request = QueryRequest::new(
+        builder.get_post_state_hash(),
+        Key::Account(*DEFAULT_ACCOUNT_ADDR),
+        vec![],
+    );
+
+    builder.set_fault_config(Some(FaultConfig::new(SEED).with_read_error_rate(1.0)));
+    let result = builder
+        .get_engine_state()
+        .run_query(CorrelationId::new(), query_request);
+    assert_matches!(
+        result,
+        Err(Error::Storage(storage::error::Error::BytesRepr(
+            bytesrepr::Error::Formatting
+        )))
+    );
+    assert!(builder.get_fault_stats().injected_errors > 0);
+}
+
+#[ignore]
+#[test]
+fn should_fail_commit_when_global_state_reads_fail() {
+    let mut builder = setup();
+    let pre_state_hash = builder.get_post_state_hash();
+    builder.exec(do_nothing_request()).expect_success();
+    let effects = builder
+        .get_transforms()
+        .pop()
+        .expect("there should be effects");
+
+    builder.set_fault_config(Some(FaultConfig::new(SEED).with_read_error_rate(1.0)));
+    let result = builder.get_engine_state().apply_effect(
+        CorrelationId::new(),
+        pre_state_hash,
+        effects.clone(),
+    );
+    assert!(result.is_err());
+    assert!(builder.get_fault_stats().injected_errors > 0);
+
+    // The failed commit left nothing behind which stops the effects from being committed later.
+    builder.set_fault_config(None);
+    builder.commit_transforms(pre_state_hash, effects);
+    assert_ne!(builder.get_post_state_hash(), pre_state_hash);
+}
+
+#[ignore]
+#[test]
+fn should_recover_once_faults_are_disabled() {
+    let mut builder = setup();
+
+    builder.set_fault_config(Some(FaultConfig::new(SEED).with_read_error_rate(1.0)));
+    builder.exec(do_nothing_request());
+    assert!(builder.is_error());
+
+    builder.set_fault_config(None);
+    builder.exec(do_nothing_request()).expect_success().commit();
+}
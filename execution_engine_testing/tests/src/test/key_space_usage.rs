@@ -0,0 +1,81 @@
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    MINIMUM_ACCOUNT_CREATION_BALANCE, PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::{
+    core::engine_state::{KeySpaceUsage, StorageFootprint, StorageOwner, StorageUsage},
+    shared::newtypes::CorrelationId,
+};
+use casper_types::{
+    account::AccountHash, bytesrepr::ToBytes, runtime_args, Key, RuntimeArgs, U512,
+};
+
+const CONTRACT_TRANSFER_TO_ACCOUNT: &str = "transfer_to_account_u512.wasm";
+const ARG_TARGET: &str = "target";
+const ARG_AMOUNT: &str = "amount";
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1; 32]);
+
+fn key_space_usage(builder: &InMemoryWasmTestBuilder, limit: usize) -> KeySpaceUsage {
+    builder
+        .get_engine_state()
+        .key_space_usage(CorrelationId::new(), builder.get_post_state_hash(), limit)
+        .expect("should account for key space")
+}
+
+#[ignore]
+#[test]
+fn should_attribute_global_state_to_accounts_and_contracts() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+    let before = key_space_usage(&builder, usize::MAX);
+    assert!(before.contracts > 0);
+    assert_eq!(
+        before.largest.len() as u64,
+        before.accounts + before.contracts
+    );
+
+    let exec_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_TRANSFER_TO_ACCOUNT,
+        runtime_args! {
+            ARG_TARGET => ACCOUNT_1_ADDR,
+            ARG_AMOUNT => U512::from(MINIMUM_ACCOUNT_CREATION_BALANCE),
+        },
+    )
+    .build();
+    builder.exec(exec_request).expect_success().commit();
+
+    let usage = key_space_usage(&builder, usize::MAX);
+    assert_eq!(usage.accounts, before.accounts + 1);
+    assert_eq!(usage.contracts, before.contracts);
+    assert!(usage
+        .largest
+        .windows(2)
+        .all(|pair| pair[0].footprint.stored_bytes >= pair[1].footprint.stored_bytes));
+
+    // The new account owns its record, its main purse and the purse's balance.
+    let account = builder.get_expected_account(ACCOUNT_1_ADDR);
+    let main_purse = account.main_purse().remove_access_rights();
+    let purse_bytes = builder
+        .query(None, Key::URef(main_purse), &[])
+        .expect("should have main purse")
+        .serialized_length();
+    let balance_bytes = builder
+        .query(None, Key::Balance(main_purse.addr()), &[])
+        .expect("should have balance")
+        .serialized_length();
+    let expected = StorageUsage {
+        owner: StorageOwner::Account(ACCOUNT_1_ADDR),
+        footprint: StorageFootprint {
+            stored_bytes: (account.serialized_length() + purse_bytes + balance_bytes) as u64,
+            stored_keys: 3,
+        },
+    };
+    assert!(usage.largest.contains(&expected), "{:?}", usage.largest);
+
+    // Listing fewer owners keeps the largest ones, and still accounts for all of them.
+    let limited = key_space_usage(&builder, 1);
+    assert_eq!(limited.largest, &usage.largest[..1]);
+    assert_eq!(limited.attributed, usage.attributed);
+    assert_eq!(limited.accounts, usage.accounts);
+}
@@ -5,12 +5,15 @@ mod contract_api;
 mod contract_context;
 mod deploy;
 mod explorer;
+mod fault_injection;
 mod gas_counter;
 mod get_balance;
 mod groups;
 mod host_function_costs;
+mod key_space_usage;
 mod manage_groups;
 mod regression;
+mod snapshot;
 mod step;
 mod storage_costs;
 mod system_contracts;
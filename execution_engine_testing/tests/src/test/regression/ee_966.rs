@@ -24,7 +24,10 @@ use casper_execution_engine::{
         opcode_costs::OpcodeCosts,
         storage_costs::StorageCosts,
         system_config::SystemConfig,
-        wasm_config::{WasmConfig, DEFAULT_MAX_STACK_HEIGHT, DEFAULT_WASM_MAX_MEMORY},
+        wasm_config::{
+            WasmConfig, DEFAULT_MAX_CALL_RESULT_SIZE, DEFAULT_MAX_STACK_HEIGHT,
+            DEFAULT_WASM_MAX_MEMORY,
+        },
     },
 };
 use casper_types::{
@@ -40,6 +43,7 @@ static DOUBLED_WASM_MEMORY_LIMIT: Lazy<WasmConfig> = Lazy::new(|| {
     WasmConfig::new(
         DEFAULT_WASM_MAX_MEMORY * 2,
         DEFAULT_MAX_STACK_HEIGHT,
+        DEFAULT_MAX_CALL_RESULT_SIZE,
         OpcodeCosts::default(),
         StorageCosts::default(),
         HostFunctionCosts::default(),
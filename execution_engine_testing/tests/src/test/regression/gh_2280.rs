@@ -17,7 +17,10 @@ use casper_execution_engine::{
     shared::{
         host_function_costs::{Cost, HostFunction, HostFunctionCosts},
         system_config::{mint_costs::MintCosts, SystemConfig},
-        wasm_config::{WasmConfig, DEFAULT_MAX_STACK_HEIGHT, DEFAULT_WASM_MAX_MEMORY},
+        wasm_config::{
+            WasmConfig, DEFAULT_MAX_CALL_RESULT_SIZE, DEFAULT_MAX_STACK_HEIGHT,
+            DEFAULT_WASM_MAX_MEMORY,
+        },
     },
 };
 use casper_types::{
@@ -773,6 +776,7 @@ fn make_wasm_config(
     WasmConfig::new(
         DEFAULT_WASM_MAX_MEMORY,
         DEFAULT_MAX_STACK_HEIGHT,
+        DEFAULT_MAX_CALL_RESULT_SIZE,
         old_wasm_config.opcode_costs(),
         old_wasm_config.storage_costs(),
         new_host_function_costs,
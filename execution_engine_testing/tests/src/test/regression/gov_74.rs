@@ -16,7 +16,7 @@ use casper_execution_engine::{
         execution::Error as ExecError,
     },
     shared::{
-        wasm_config::{WasmConfig, DEFAULT_WASM_MAX_MEMORY},
+        wasm_config::{WasmConfig, DEFAULT_MAX_CALL_RESULT_SIZE, DEFAULT_WASM_MAX_MEMORY},
         wasm_prep::DEFAULT_MAX_PARAMETER_COUNT,
     },
 };
@@ -114,6 +114,7 @@ fn should_observe_stack_height_limit() {
             WasmConfig::new(
                 DEFAULT_WASM_MAX_MEMORY,
                 NEW_WASM_STACK_HEIGHT,
+                DEFAULT_MAX_CALL_RESULT_SIZE,
                 Default::default(),
                 Default::default(),
                 Default::default(),
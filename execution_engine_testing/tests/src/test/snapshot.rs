@@ -0,0 +1,38 @@
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::core::engine_state::EngineConfig;
+use casper_types::RuntimeArgs;
+
+const DO_NOTHING_WASM: &str = "do_nothing.wasm";
+
+#[ignore]
+#[test]
+fn should_resume_from_snapshot() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+    let do_nothing_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, DO_NOTHING_WASM, RuntimeArgs::new())
+            .build();
+    builder.exec(do_nothing_request).expect_success().commit();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("global_state.snapshot");
+    builder.write_snapshot(&path);
+
+    let mut restored = InMemoryWasmTestBuilder::from_snapshot(&path, EngineConfig::default());
+    assert_eq!(
+        restored.get_post_state_hash(),
+        builder.get_post_state_hash()
+    );
+    assert_eq!(
+        restored.get_account(*DEFAULT_ACCOUNT_ADDR),
+        builder.get_account(*DEFAULT_ACCOUNT_ADDR)
+    );
+
+    let do_nothing_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, DO_NOTHING_WASM, RuntimeArgs::new())
+            .build();
+    restored.exec(do_nothing_request).expect_success().commit();
+}
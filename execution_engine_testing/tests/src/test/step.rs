@@ -9,7 +9,7 @@ use casper_engine_test_support::{
 use casper_execution_engine::{
     core::engine_state::{
         genesis::{GenesisAccount, GenesisValidator},
-        RewardItem, SlashItem,
+        RewardItem, SlashItem, StepSuccess,
     },
     storage::global_state::in_memory::InMemoryGlobalState,
 };
@@ -192,3 +192,49 @@ fn should_adjust_total_supply() {
         "total supply should be reduced due to slashing"
     );
 }
+
+#[ignore]
+#[test]
+fn should_report_rewards_and_burned_amount() {
+    let mut builder = initialize_builder();
+
+    let mint_hash = builder.get_mint_contract_hash();
+    let total_supply_key = get_named_key(&mut builder, mint_hash, TOTAL_SUPPLY_KEY);
+    let get_total_supply = |builder: &InMemoryWasmTestBuilder| {
+        CLValue::try_from(
+            builder
+                .query(None, total_supply_key, &[])
+                .expect("should have total supply"),
+        )
+        .expect("should be a CLValue")
+        .into_t::<U512>()
+        .expect("should be U512")
+    };
+    let starting_total_supply = get_total_supply(&builder);
+
+    let step_request = StepRequestBuilder::new()
+        .with_parent_state_hash(builder.get_post_state_hash())
+        .with_protocol_version(ProtocolVersion::V1_0_0)
+        .with_slash_item(SlashItem::new(ACCOUNT_1_PK.clone()))
+        .with_reward_item(RewardItem::new(ACCOUNT_1_PK.clone(), BLOCK_REWARD / 2))
+        .with_reward_item(RewardItem::new(ACCOUNT_2_PK.clone(), BLOCK_REWARD / 2))
+        .with_next_era_id(EraId::from(1))
+        .build();
+
+    let StepSuccess { reward_report, .. } = builder.step(step_request).unwrap();
+
+    assert_eq!(reward_report.era_id(), EraId::from(0));
+    let rewarded_validators: Vec<_> = reward_report
+        .validators()
+        .iter()
+        .map(|rewards| rewards.validator_public_key.clone())
+        .collect();
+    assert!(rewarded_validators.contains(&ACCOUNT_1_PK));
+    assert!(rewarded_validators.contains(&ACCOUNT_2_PK));
+    assert!(!reward_report.total_validator_rewards().is_zero());
+    assert!(reward_report.total_burned() >= U512::from(ACCOUNT_1_BOND));
+    assert_eq!(
+        get_total_supply(&builder),
+        starting_total_supply + reward_report.total_accumulated() - reward_report.total_burned()
+    );
+}
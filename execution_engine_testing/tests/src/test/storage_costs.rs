@@ -22,7 +22,10 @@ use casper_execution_engine::{
         opcode_costs::OpcodeCosts,
         storage_costs::StorageCosts,
         system_config::SystemConfig,
-        wasm_config::{WasmConfig, DEFAULT_MAX_STACK_HEIGHT, DEFAULT_WASM_MAX_MEMORY},
+        wasm_config::{
+            WasmConfig, DEFAULT_MAX_CALL_RESULT_SIZE, DEFAULT_MAX_STACK_HEIGHT,
+            DEFAULT_WASM_MAX_MEMORY,
+        },
     },
 };
 use casper_types::{
@@ -129,11 +132,17 @@ static NEW_HOST_FUNCTION_COSTS: Lazy<HostFunctionCosts> = Lazy::new(|| HostFunct
     print: HostFunction::fixed(0),
     blake2b: HostFunction::fixed(0),
     random_bytes: HostFunction::fixed(0),
+    read_host_buffer_chunk: HostFunction::fixed(0),
+    add_allowed_caller: HostFunction::fixed(0),
+    remove_allowed_caller: HostFunction::fixed(0),
+    transfer_batch: HostFunction::fixed(0),
+    set_contract_package_paused: HostFunction::fixed(0),
 });
 static STORAGE_COSTS_ONLY: Lazy<WasmConfig> = Lazy::new(|| {
     WasmConfig::new(
         DEFAULT_WASM_MAX_MEMORY,
         DEFAULT_MAX_STACK_HEIGHT,
+        DEFAULT_MAX_CALL_RESULT_SIZE,
         NEW_OPCODE_COSTS,
         StorageCosts::default(),
         *NEW_HOST_FUNCTION_COSTS,
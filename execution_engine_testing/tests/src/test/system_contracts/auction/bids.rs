@@ -6,9 +6,10 @@ use once_cell::sync::Lazy;
 
 use casper_engine_test_support::{
     utils, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNTS, DEFAULT_ACCOUNT_ADDR,
-    DEFAULT_ACCOUNT_INITIAL_BALANCE, DEFAULT_EXEC_CONFIG, DEFAULT_GENESIS_TIMESTAMP_MILLIS,
-    DEFAULT_LOCKED_FUNDS_PERIOD_MILLIS, DEFAULT_UNBONDING_DELAY, MINIMUM_ACCOUNT_CREATION_BALANCE,
-    PRODUCTION_RUN_GENESIS_REQUEST, SYSTEM_ADDR, TIMESTAMP_MILLIS_INCREMENT,
+    DEFAULT_ACCOUNT_INITIAL_BALANCE, DEFAULT_ACCOUNT_PUBLIC_KEY, DEFAULT_EXEC_CONFIG,
+    DEFAULT_GENESIS_TIMESTAMP_MILLIS, DEFAULT_LOCKED_FUNDS_PERIOD_MILLIS, DEFAULT_PROTOCOL_VERSION,
+    DEFAULT_UNBONDING_DELAY, MINIMUM_ACCOUNT_CREATION_BALANCE, PRODUCTION_RUN_GENESIS_REQUEST,
+    SYSTEM_ADDR, TIMESTAMP_MILLIS_INCREMENT,
 };
 use casper_execution_engine::{
     core::{
@@ -487,6 +488,102 @@ fn should_run_delegate_and_undelegate() {
     assert_eq!(unbond_list[0].era_of_creation(), INITIAL_ERA_ID);
 }
 
+#[ignore]
+#[test]
+fn should_get_total_stake_of_validator_and_delegator() {
+    let accounts = {
+        let mut tmp: Vec<GenesisAccount> = DEFAULT_ACCOUNTS.clone();
+        let account_1 = GenesisAccount::account(
+            BID_ACCOUNT_1_PK.clone(),
+            Motes::new(BID_ACCOUNT_1_BALANCE.into()),
+            None,
+        );
+        tmp.push(account_1);
+        tmp
+    };
+
+    let run_genesis_request = utils::create_run_genesis_request(accounts);
+
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&run_genesis_request);
+
+    let transfer_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_TRANSFER_TO_ACCOUNT,
+        runtime_args! {
+            ARG_TARGET => *NON_FOUNDER_VALIDATOR_1_ADDR,
+            ARG_AMOUNT => U512::from(TRANSFER_AMOUNT)
+        },
+    )
+    .build();
+
+    let add_bid_request = ExecuteRequestBuilder::standard(
+        *NON_FOUNDER_VALIDATOR_1_ADDR,
+        CONTRACT_ADD_BID,
+        runtime_args! {
+            ARG_PUBLIC_KEY => NON_FOUNDER_VALIDATOR_1_PK.clone(),
+            ARG_AMOUNT => U512::from(ADD_BID_AMOUNT_1),
+            ARG_DELEGATION_RATE => ADD_BID_DELEGATION_RATE_1,
+        },
+    )
+    .build();
+
+    let delegate_request = ExecuteRequestBuilder::standard(
+        *BID_ACCOUNT_1_ADDR,
+        CONTRACT_DELEGATE,
+        runtime_args! {
+            ARG_AMOUNT => U512::from(DELEGATE_AMOUNT_1),
+            ARG_VALIDATOR => NON_FOUNDER_VALIDATOR_1_PK.clone(),
+            ARG_DELEGATOR => BID_ACCOUNT_1_PK.clone(),
+        },
+    )
+    .build();
+
+    let undelegate_request = ExecuteRequestBuilder::standard(
+        *BID_ACCOUNT_1_ADDR,
+        CONTRACT_UNDELEGATE,
+        runtime_args! {
+            ARG_AMOUNT => U512::from(UNDELEGATE_AMOUNT_1),
+            ARG_VALIDATOR => NON_FOUNDER_VALIDATOR_1_PK.clone(),
+            ARG_DELEGATOR => BID_ACCOUNT_1_PK.clone(),
+        },
+    )
+    .build();
+
+    builder.exec(transfer_request).commit().expect_success();
+    builder.exec(add_bid_request).commit().expect_success();
+    builder.exec(delegate_request).commit().expect_success();
+    builder.exec(undelegate_request).commit().expect_success();
+
+    let validator_stake = builder.get_total_stake(NON_FOUNDER_VALIDATOR_1_PK.clone());
+    assert_eq!(
+        validator_stake.validator_stake,
+        U512::from(ADD_BID_AMOUNT_1)
+    );
+    assert!(validator_stake.delegations.is_empty());
+    assert_eq!(validator_stake.unbonding, U512::zero());
+    assert_eq!(validator_stake.total(), U512::from(ADD_BID_AMOUNT_1));
+    assert_eq!(validator_stake.proofs.len(), 1);
+
+    let delegator_stake = builder.get_total_stake(BID_ACCOUNT_1_PK.clone());
+    assert_eq!(delegator_stake.validator_stake, U512::zero());
+    assert_eq!(
+        delegator_stake
+            .delegations
+            .get(&*NON_FOUNDER_VALIDATOR_1_PK),
+        Some(&U512::from(DELEGATE_AMOUNT_1 - UNDELEGATE_AMOUNT_1))
+    );
+    assert_eq!(delegator_stake.unbonding, U512::from(UNDELEGATE_AMOUNT_1));
+    assert_eq!(delegator_stake.total(), U512::from(DELEGATE_AMOUNT_1));
+    // One proof for the validator's bid holding the delegation, one for the unbonds.
+    assert_eq!(delegator_stake.proofs.len(), 2);
+
+    let unrelated_stake = builder.get_total_stake(DEFAULT_ACCOUNT_PUBLIC_KEY.clone());
+    assert_eq!(unrelated_stake.total(), U512::zero());
+    assert!(unrelated_stake.proofs.is_empty());
+}
+
 #[ignore]
 #[test]
 fn should_calculate_era_validators() {
@@ -3270,6 +3367,130 @@ fn should_delegate_and_redelegate() {
     );
 }
 
+#[ignore]
+#[test]
+fn should_redelegate_at_era_end_once_cooldown_passed() {
+    const COOLDOWN_ERAS: u64 = 2;
+
+    let fund_request = |target: AccountHash| {
+        ExecuteRequestBuilder::standard(
+            *DEFAULT_ACCOUNT_ADDR,
+            CONTRACT_TRANSFER_TO_ACCOUNT,
+            runtime_args! {
+                ARG_TARGET => target,
+                ARG_AMOUNT => U512::from(TRANSFER_AMOUNT)
+            },
+        )
+        .build()
+    };
+    let add_bid_request = |account_hash: AccountHash, public_key: &PublicKey| {
+        ExecuteRequestBuilder::standard(
+            account_hash,
+            CONTRACT_ADD_BID,
+            runtime_args! {
+                ARG_PUBLIC_KEY => public_key.clone(),
+                ARG_AMOUNT => U512::from(ADD_BID_AMOUNT_1),
+                ARG_DELEGATION_RATE => ADD_BID_DELEGATION_RATE_1,
+            },
+        )
+        .build()
+    };
+    let redelegate_request = |validator: &PublicKey, new_validator: &PublicKey| {
+        ExecuteRequestBuilder::standard(
+            *BID_ACCOUNT_1_ADDR,
+            CONTRACT_REDELEGATE,
+            runtime_args! {
+                ARG_AMOUNT => U512::from(DEFAULT_MINIMUM_DELEGATION_AMOUNT),
+                ARG_VALIDATOR => validator.clone(),
+                ARG_DELEGATOR => BID_ACCOUNT_1_PK.clone(),
+                ARG_NEW_VALIDATOR => new_validator.clone()
+            },
+        )
+        .build()
+    };
+    let delegate_request = ExecuteRequestBuilder::standard(
+        *BID_ACCOUNT_1_ADDR,
+        CONTRACT_DELEGATE,
+        runtime_args! {
+            ARG_AMOUNT => U512::from(DELEGATE_AMOUNT_1),
+            ARG_VALIDATOR => NON_FOUNDER_VALIDATOR_1_PK.clone(),
+            ARG_DELEGATOR => BID_ACCOUNT_1_PK.clone(),
+        },
+    )
+    .build();
+
+    let post_genesis_requests = vec![
+        fund_request(*SYSTEM_ADDR),
+        fund_request(*BID_ACCOUNT_1_ADDR),
+        fund_request(*NON_FOUNDER_VALIDATOR_1_ADDR),
+        fund_request(*NON_FOUNDER_VALIDATOR_2_ADDR),
+        add_bid_request(*NON_FOUNDER_VALIDATOR_1_ADDR, &NON_FOUNDER_VALIDATOR_1_PK),
+        add_bid_request(*NON_FOUNDER_VALIDATOR_2_ADDR, &NON_FOUNDER_VALIDATOR_2_PK),
+        delegate_request,
+    ];
+
+    let engine_config =
+        EngineConfig::default().with_direct_redelegation(*DEFAULT_PROTOCOL_VERSION, COOLDOWN_ERAS);
+    let mut builder = InMemoryWasmTestBuilder::new_with_config(engine_config);
+
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    for request in post_genesis_requests {
+        builder.exec(request).commit().expect_success();
+    }
+
+    builder.advance_eras_by_default_auction_delay(vec![]);
+
+    let assert_in_cooldown = |builder: &mut InMemoryWasmTestBuilder| {
+        builder
+            .exec(redelegate_request(
+                &NON_FOUNDER_VALIDATOR_2_PK,
+                &NON_FOUNDER_VALIDATOR_1_PK,
+            ))
+            .commit();
+        let error = builder.get_error().expect("should have error");
+        assert!(matches!(
+            error,
+            engine_state::Error::Exec(execution::Error::Revert(ApiError::AuctionError(auction_error)))
+            if auction_error == system::auction::Error::RedelegationCooldown as u8
+        ));
+    };
+
+    builder
+        .exec(redelegate_request(
+            &NON_FOUNDER_VALIDATOR_1_PK,
+            &NON_FOUNDER_VALIDATOR_2_PK,
+        ))
+        .commit()
+        .expect_success();
+    assert_in_cooldown(&mut builder);
+
+    // The redelegated amount moves to the new validator at the end of the era.
+    builder.advance_era(vec![]);
+
+    let unbonds = builder.get_unbonds();
+    assert!(unbonds
+        .get(&NON_FOUNDER_VALIDATOR_1_ADDR)
+        .map_or(true, |unbonding_purses| unbonding_purses.is_empty()));
+    let bids = builder.get_bids();
+    let delegators = bids[&NON_FOUNDER_VALIDATOR_2_PK].delegators();
+    assert_eq!(
+        *delegators[&BID_ACCOUNT_1_PK].staked_amount(),
+        U512::from(DEFAULT_MINIMUM_DELEGATION_AMOUNT)
+    );
+
+    // The delegator can only redelegate again once the cooldown has passed.
+    assert_in_cooldown(&mut builder);
+    builder.advance_era(vec![]);
+    builder
+        .exec(redelegate_request(
+            &NON_FOUNDER_VALIDATOR_2_PK,
+            &NON_FOUNDER_VALIDATOR_1_PK,
+        ))
+        .commit()
+        .expect_success();
+}
+
 #[ignore]
 #[test]
 fn should_handle_redelegation_to_inactive_validator() {
@@ -3458,3 +3679,96 @@ fn should_handle_redelegation_to_inactive_validator() {
         delegator_2_purse_balance_after
     );
 }
+
+/// Activates the default account's bid first while it's still active, then once its whole stake
+/// has been withdrawn, returning the error each attempt failed with, if any.
+fn activate_bid_errors(
+    engine_config: EngineConfig,
+) -> (Option<engine_state::Error>, Option<engine_state::Error>) {
+    let mut builder = InMemoryWasmTestBuilder::new_with_config(engine_config);
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let auction_hash = builder.get_auction_contract_hash();
+
+    let activate_bid_request = || {
+        ExecuteRequestBuilder::contract_call_by_hash(
+            *DEFAULT_ACCOUNT_ADDR,
+            auction_hash,
+            auction::METHOD_ACTIVATE_BID,
+            runtime_args! {
+                auction::ARG_VALIDATOR_PUBLIC_KEY => DEFAULT_ACCOUNT_PUBLIC_KEY.clone(),
+            },
+        )
+        .build()
+    };
+
+    let add_bid_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        auction_hash,
+        auction::METHOD_ADD_BID,
+        runtime_args! {
+            ARG_PUBLIC_KEY => DEFAULT_ACCOUNT_PUBLIC_KEY.clone(),
+            ARG_AMOUNT => U512::from(ADD_BID_AMOUNT_1),
+            ARG_DELEGATION_RATE => ADD_BID_DELEGATION_RATE_1,
+        },
+    )
+    .build();
+    builder.exec(add_bid_request).expect_success().commit();
+
+    builder.exec(activate_bid_request()).commit();
+    let active_bid_error = builder.get_error();
+
+    // Withdrawing the whole stake deactivates the bid, but leaves nothing to activate.
+    let withdraw_bid_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        auction_hash,
+        auction::METHOD_WITHDRAW_BID,
+        runtime_args! {
+            ARG_PUBLIC_KEY => DEFAULT_ACCOUNT_PUBLIC_KEY.clone(),
+            ARG_AMOUNT => U512::from(ADD_BID_AMOUNT_1),
+        },
+    )
+    .build();
+    builder.exec(withdraw_bid_request).expect_success().commit();
+
+    let bids = builder.get_bids();
+    let bid = bids
+        .get(&*DEFAULT_ACCOUNT_PUBLIC_KEY)
+        .expect("should have bid");
+    assert!(bid.inactive());
+
+    builder.exec(activate_bid_request()).commit();
+    let unstaked_bid_error = builder.get_error();
+
+    (active_bid_error, unstaked_bid_error)
+}
+
+#[ignore]
+#[test]
+fn should_only_activate_inactive_bid_with_stake() {
+    let engine_config =
+        EngineConfig::default().with_strict_bid_activation_version(*DEFAULT_PROTOCOL_VERSION);
+    let (active_bid_error, unstaked_bid_error) = activate_bid_errors(engine_config);
+
+    assert!(matches!(
+        active_bid_error,
+        Some(engine_state::Error::Exec(execution::Error::Revert(
+            ApiError::AuctionError(auction_error)
+        ))) if auction_error == system::auction::Error::BidNotInactive as u8
+    ));
+    assert!(matches!(
+        unstaked_bid_error,
+        Some(engine_state::Error::Exec(execution::Error::Revert(
+            ApiError::AuctionError(auction_error)
+        ))) if auction_error == system::auction::Error::BondTooSmall as u8
+    ));
+}
+
+#[ignore]
+#[test]
+fn should_activate_any_bid_before_strict_bid_activation() {
+    let (active_bid_error, unstaked_bid_error) = activate_bid_errors(EngineConfig::default());
+
+    assert!(active_bid_error.is_none(), "{:?}", active_bid_error);
+    assert!(unstaked_bid_error.is_none(), "{:?}", unstaked_bid_error);
+}
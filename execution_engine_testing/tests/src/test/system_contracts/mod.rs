@@ -2,5 +2,7 @@ mod auction;
 mod auction_bidding;
 mod genesis;
 mod handle_payment;
+mod protected_keys;
 mod standard_payment;
+mod storage_rent;
 mod upgrade;
@@ -0,0 +1,133 @@
+use std::collections::BTreeSet;
+
+use assert_matches::assert_matches;
+
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    MINIMUM_ACCOUNT_CREATION_BALANCE, PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::{
+    core::engine_state::{EngineConfig, Error},
+    shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
+    storage::global_state::CommitError,
+};
+use casper_types::{
+    account::AccountHash,
+    runtime_args,
+    system::{auction::SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY, mint},
+    CLValue, ContractHash, Key, RuntimeArgs, StoredValue, U512,
+};
+
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1u8; 32]);
+
+fn setup(protect_system_keys: bool) -> InMemoryWasmTestBuilder {
+    let engine_config = EngineConfig::default().with_protect_system_keys(protect_system_keys);
+    let mut builder = InMemoryWasmTestBuilder::new_with_config(engine_config);
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+    builder
+}
+
+fn named_key(builder: &InMemoryWasmTestBuilder, contract_hash: ContractHash, name: &str) -> Key {
+    *builder
+        .get_contract(contract_hash)
+        .expect("should have system contract")
+        .named_keys()
+        .get(name)
+        .expect("should have named key")
+}
+
+fn protected_system_keys(builder: &InMemoryWasmTestBuilder) -> BTreeSet<Key> {
+    builder
+        .get_engine_state()
+        .protected_system_keys(CorrelationId::new(), builder.get_post_state_hash())
+        .expect("should read protected system keys")
+}
+
+fn write_effects(key: Key) -> AdditiveMap<Key, Transform> {
+    let mut effects = AdditiveMap::new();
+    let value = StoredValue::CLValue(CLValue::from_t(U512::one()).unwrap());
+    effects.insert(key.normalize(), Transform::Write(value));
+    effects
+}
+
+#[ignore]
+#[test]
+fn should_refuse_deploy_effects_writing_to_protected_system_keys() {
+    let builder = setup(true);
+    let total_supply_key = named_key(
+        &builder,
+        builder.get_mint_contract_hash(),
+        mint::TOTAL_SUPPLY_KEY,
+    );
+    let snapshot_key = named_key(
+        &builder,
+        builder.get_auction_contract_hash(),
+        SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY,
+    );
+
+    let protected_keys = protected_system_keys(&builder);
+    for key in [total_supply_key, snapshot_key] {
+        let result = builder.get_engine_state().apply_deploy_effect(
+            CorrelationId::new(),
+            builder.get_post_state_hash(),
+            &protected_keys,
+            write_effects(key),
+        );
+        assert_matches!(
+            result,
+            Err(Error::CommitError(CommitError::ProtectedKeyWrite(protected_key)))
+                if protected_key == key.normalize()
+        );
+    }
+}
+
+#[ignore]
+#[test]
+fn should_apply_deploy_effects_not_touching_protected_system_keys() {
+    let mut builder = setup(true);
+    let transfer_request = ExecuteRequestBuilder::transfer(
+        *DEFAULT_ACCOUNT_ADDR,
+        runtime_args! {
+            mint::ARG_TARGET => ACCOUNT_1_ADDR,
+            mint::ARG_AMOUNT => U512::from(MINIMUM_ACCOUNT_CREATION_BALANCE),
+            mint::ARG_ID => <Option<u64>>::None,
+        },
+    )
+    .build();
+    builder.exec(transfer_request).expect_success();
+
+    let protected_keys = protected_system_keys(&builder);
+    assert_eq!(protected_keys.len(), 2);
+    builder
+        .get_engine_state()
+        .apply_deploy_effect(
+            CorrelationId::new(),
+            builder.get_post_state_hash(),
+            &protected_keys,
+            builder.get_last_exec_effects(),
+        )
+        .expect("should apply transfer effects");
+}
+
+#[ignore]
+#[test]
+fn should_apply_writes_to_system_keys_if_protection_is_disabled() {
+    let builder = setup(false);
+    let total_supply_key = named_key(
+        &builder,
+        builder.get_mint_contract_hash(),
+        mint::TOTAL_SUPPLY_KEY,
+    );
+
+    let protected_keys = protected_system_keys(&builder);
+    assert!(protected_keys.is_empty());
+    builder
+        .get_engine_state()
+        .apply_deploy_effect(
+            CorrelationId::new(),
+            builder.get_post_state_hash(),
+            &protected_keys,
+            write_effects(total_supply_key),
+        )
+        .expect("should apply effects");
+}
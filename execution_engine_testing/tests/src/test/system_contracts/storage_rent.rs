@@ -0,0 +1,163 @@
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, InMemoryWasmTestBuilder, StepRequestBuilder, DEFAULT_ACCOUNT_ADDR,
+    DEFAULT_PROTOCOL_VERSION, PRODUCTION_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::core::engine_state::{
+    storage_rent::{storage_rent_key, StorageRentRecord, STORAGE_RENT_PURSE_NAMED_KEY},
+    EngineConfig,
+};
+use casper_types::{runtime_args, system::mint, ContractHash, Key, RuntimeArgs, StoredValue, U512};
+
+const CONTRACT_DO_NOTHING_STORED: &str = "do_nothing_stored.wasm";
+const DO_NOTHING_HASH_KEY_NAME: &str = "do_nothing_hash";
+const CONTRACT_PURSE_HOLDER_STORED: &str = "purse_holder_stored.wasm";
+const PURSE_HOLDER_HASH_KEY_NAME: &str = "purse_holder_stored";
+const ENTRY_POINT_ADD_NAMED_PURSE: &str = "add_named_purse";
+const ARG_PURSE_NAME: &str = "purse_name";
+const ARG_IS_LOCKED: &str = "is_locked";
+const RENT_PURSE_FUNDS: u64 = 1_000_000_000_000;
+const MOTES_PER_BYTE: u64 = 10;
+const GRACE_ERAS: u64 = 1;
+
+fn setup(engine_config: EngineConfig) -> (InMemoryWasmTestBuilder, ContractHash) {
+    let mut builder = InMemoryWasmTestBuilder::new_with_config(engine_config);
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_DO_NOTHING_STORED,
+        RuntimeArgs::new(),
+    )
+    .build();
+    builder.exec(install_request).expect_success().commit();
+
+    let contract_hash = builder
+        .get_expected_account(*DEFAULT_ACCOUNT_ADDR)
+        .named_keys()
+        .get(DO_NOTHING_HASH_KEY_NAME)
+        .and_then(|key| key.into_hash())
+        .map(ContractHash::new)
+        .expect("should have contract hash");
+    (builder, contract_hash)
+}
+
+fn step(builder: &mut InMemoryWasmTestBuilder) {
+    let step_request = StepRequestBuilder::new()
+        .with_parent_state_hash(builder.get_post_state_hash())
+        .with_protocol_version(*DEFAULT_PROTOCOL_VERSION)
+        .with_next_era_id(builder.get_era().successor())
+        .with_run_auction(true)
+        .build();
+    builder.step(step_request).expect("should execute step");
+}
+
+fn rent_record(
+    builder: &InMemoryWasmTestBuilder,
+    contract_hash: ContractHash,
+) -> Option<StorageRentRecord> {
+    match builder.query(None, storage_rent_key(contract_hash), &[]) {
+        Ok(StoredValue::CLValue(cl_value)) => {
+            Some(StorageRentRecord::from_cl_value(cl_value).expect("should parse rent record"))
+        }
+        Ok(other) => panic!("unexpected stored value {:?}", other),
+        Err(_) => None,
+    }
+}
+
+#[ignore]
+#[test]
+fn should_mark_contract_without_rent_purse_as_evicted_after_grace_eras() {
+    let engine_config = EngineConfig::default().with_storage_rent(MOTES_PER_BYTE, GRACE_ERAS);
+    let (mut builder, contract_hash) = setup(engine_config);
+    let total_supply_before = builder.total_supply(None);
+
+    step(&mut builder);
+    let record = rent_record(&builder, contract_hash).expect("should have rent record");
+    assert!(record.stored_bytes > 0);
+    assert_eq!(record.unpaid_eras, 1);
+    assert!(!record.evicted);
+
+    step(&mut builder);
+    let record = rent_record(&builder, contract_hash).expect("should have rent record");
+    assert_eq!(record.unpaid_eras, 2);
+    assert!(record.evicted);
+
+    // Eviction is only recorded, and nothing was burned as nothing was paid.
+    assert!(builder.get_contract(contract_hash).is_some());
+    assert_eq!(builder.total_supply(None), total_supply_before);
+}
+
+#[ignore]
+#[test]
+fn should_not_charge_storage_rent_by_default() {
+    let (mut builder, contract_hash) = setup(EngineConfig::default());
+    step(&mut builder);
+    assert_eq!(rent_record(&builder, contract_hash), None);
+}
+
+#[ignore]
+#[test]
+fn should_burn_storage_rent_paid_from_rent_purse() {
+    let engine_config = EngineConfig::default().with_storage_rent(MOTES_PER_BYTE, GRACE_ERAS);
+    let mut builder = InMemoryWasmTestBuilder::new_with_config(engine_config);
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_PURSE_HOLDER_STORED,
+        runtime_args! { ARG_IS_LOCKED => false },
+    )
+    .build();
+    builder.exec(install_request).expect_success().commit();
+    let contract_hash = builder
+        .get_expected_account(*DEFAULT_ACCOUNT_ADDR)
+        .named_keys()
+        .get(PURSE_HOLDER_HASH_KEY_NAME)
+        .and_then(|key| key.into_hash())
+        .map(ContractHash::new)
+        .expect("should have contract hash");
+
+    // The contract creates its rent purse itself, and the account funds it.
+    let add_purse_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        contract_hash,
+        ENTRY_POINT_ADD_NAMED_PURSE,
+        runtime_args! { ARG_PURSE_NAME => STORAGE_RENT_PURSE_NAMED_KEY.to_string() },
+    )
+    .build();
+    builder.exec(add_purse_request).expect_success().commit();
+    let rent_purse = builder
+        .get_contract(contract_hash)
+        .expect("should have contract")
+        .named_keys()
+        .get(STORAGE_RENT_PURSE_NAMED_KEY)
+        .and_then(Key::as_uref)
+        .copied()
+        .expect("should have rent purse");
+    let fund_request = ExecuteRequestBuilder::transfer(
+        *DEFAULT_ACCOUNT_ADDR,
+        runtime_args! {
+            mint::ARG_TARGET => rent_purse,
+            mint::ARG_AMOUNT => U512::from(RENT_PURSE_FUNDS),
+            mint::ARG_ID => <Option<u64>>::None
+        },
+    )
+    .build();
+    builder.exec(fund_request).expect_success().commit();
+    let total_supply_before = builder.total_supply(None);
+
+    step(&mut builder);
+
+    let record = rent_record(&builder, contract_hash).expect("should have rent record");
+    assert!(record.stored_bytes > 0);
+    assert_eq!(record.unpaid_eras, 0);
+    assert!(!record.evicted);
+
+    // The rent is taken from the rent purse and burned.
+    let rent = U512::from(record.stored_bytes) * U512::from(MOTES_PER_BYTE);
+    assert_eq!(
+        builder.get_purse_balance(rent_purse),
+        U512::from(RENT_PURSE_FUNDS) - rent
+    );
+    assert_eq!(builder.total_supply(None), total_supply_before - rent);
+}
@@ -4,8 +4,8 @@ use num_rational::Ratio;
 
 use casper_engine_test_support::{
     ExecuteRequestBuilder, InMemoryWasmTestBuilder, UpgradeRequestBuilder, DEFAULT_ACCOUNT_ADDR,
-    DEFAULT_MAX_ASSOCIATED_KEYS, DEFAULT_UNBONDING_DELAY, DEFAULT_WASM_CONFIG,
-    PRODUCTION_RUN_GENESIS_REQUEST,
+    DEFAULT_ACCOUNT_PUBLIC_KEY, DEFAULT_MAX_ASSOCIATED_KEYS, DEFAULT_UNBONDING_DELAY,
+    DEFAULT_WASM_CONFIG, PRODUCTION_RUN_GENESIS_REQUEST,
 };
 
 use casper_execution_engine::{
@@ -32,7 +32,10 @@ use casper_execution_engine::{
             mint_costs::MintCosts, standard_payment_costs::StandardPaymentCosts, SystemConfig,
             DEFAULT_WASMLESS_TRANSFER_COST,
         },
-        wasm_config::{WasmConfig, DEFAULT_MAX_STACK_HEIGHT, DEFAULT_WASM_MAX_MEMORY},
+        wasm_config::{
+            WasmConfig, DEFAULT_MAX_CALL_RESULT_SIZE, DEFAULT_MAX_STACK_HEIGHT,
+            DEFAULT_WASM_MAX_MEMORY,
+        },
     },
 };
 use casper_types::{
@@ -40,11 +43,12 @@ use casper_types::{
     runtime_args,
     system::{
         auction::{
-            AUCTION_DELAY_KEY, LOCKED_FUNDS_PERIOD_KEY, UNBONDING_DELAY_KEY, VALIDATOR_SLOTS_KEY,
+            UnbondingPurse, WithdrawPurse, AUCTION_DELAY_KEY, LOCKED_FUNDS_PERIOD_KEY,
+            UNBONDING_DELAY_KEY, VALIDATOR_SLOTS_KEY,
         },
         mint::ROUND_SEIGNIORAGE_RATE_KEY,
     },
-    CLValue, EraId, ProtocolVersion, RuntimeArgs, StoredValue, U256, U512,
+    CLValue, EraId, Key, ProtocolVersion, RuntimeArgs, StoredValue, U256, U512,
 };
 
 const PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::V1_0_0;
@@ -76,6 +80,7 @@ fn get_upgraded_wasm_config() -> WasmConfig {
     WasmConfig::new(
         DEFAULT_WASM_MAX_MEMORY,
         DEFAULT_MAX_STACK_HEIGHT * 2,
+        DEFAULT_MAX_CALL_RESULT_SIZE,
         opcode_cost,
         storage_costs,
         host_function_costs,
@@ -734,3 +739,75 @@ fn should_increase_max_associated_keys_after_upgrade() {
         new_engine_config.max_associated_keys() as usize
     );
 }
+
+#[ignore]
+#[test]
+fn should_migrate_legacy_withdraws_into_unbonds() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
+
+    let account = builder
+        .get_account(*DEFAULT_ACCOUNT_ADDR)
+        .expect("should get account");
+
+    let withdraw_purse = WithdrawPurse::new(
+        account.main_purse(),
+        DEFAULT_ACCOUNT_PUBLIC_KEY.clone(),
+        DEFAULT_ACCOUNT_PUBLIC_KEY.clone(),
+        EraId::new(1),
+        U512::from(1_000_000u64),
+    );
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let first_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 1);
+    let second_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 2);
+
+    // Plant a legacy withdraw entry, as left behind by pre-1.4 networks.
+    let mut global_state_update = BTreeMap::new();
+    global_state_update.insert(
+        Key::Withdraw(*DEFAULT_ACCOUNT_ADDR),
+        StoredValue::Withdraw(vec![withdraw_purse.clone()]),
+    );
+
+    let mut upgrade_request = UpgradeRequestBuilder::new()
+        .with_current_protocol_version(PROTOCOL_VERSION)
+        .with_new_protocol_version(first_protocol_version)
+        .with_activation_point(DEFAULT_ACTIVATION_POINT)
+        .with_global_state_update(global_state_update)
+        .build();
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .expect_upgrade_success();
+
+    assert!(!builder.get_unbonds().contains_key(&*DEFAULT_ACCOUNT_ADDR));
+
+    let mut upgrade_request = UpgradeRequestBuilder::new()
+        .with_current_protocol_version(first_protocol_version)
+        .with_new_protocol_version(second_protocol_version)
+        .with_activation_point(DEFAULT_ACTIVATION_POINT)
+        .with_migrate_legacy_withdraws(true)
+        .build();
+
+    builder
+        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .expect_upgrade_success();
+
+    let unbonds = builder.get_unbonds();
+    let unbonding_purses = unbonds
+        .get(&*DEFAULT_ACCOUNT_ADDR)
+        .expect("should have migrated unbonds");
+    assert_eq!(
+        unbonding_purses,
+        &vec![UnbondingPurse::from(withdraw_purse)]
+    );
+
+    let withdraws = builder.get_withdraw_purses();
+    assert!(withdraws
+        .get(&*DEFAULT_ACCOUNT_ADDR)
+        .map(Vec::is_empty)
+        .unwrap_or(true));
+}
@@ -31,7 +31,10 @@ use casper_execution_engine::{
             standard_payment_costs::StandardPaymentCosts,
             SystemConfig, DEFAULT_WASMLESS_TRANSFER_COST,
         },
-        wasm_config::{WasmConfig, DEFAULT_MAX_STACK_HEIGHT, DEFAULT_WASM_MAX_MEMORY},
+        wasm_config::{
+            WasmConfig, DEFAULT_MAX_CALL_RESULT_SIZE, DEFAULT_MAX_STACK_HEIGHT,
+            DEFAULT_WASM_MAX_MEMORY,
+        },
     },
 };
 use casper_types::{
@@ -982,11 +985,17 @@ fn should_verify_wasm_add_bid_wasm_cost_is_not_recursive() {
         print: HostFunction::fixed(0),
         blake2b: HostFunction::fixed(0),
         random_bytes: HostFunction::fixed(0),
+        read_host_buffer_chunk: HostFunction::fixed(0),
+        add_allowed_caller: HostFunction::fixed(0),
+        remove_allowed_caller: HostFunction::fixed(0),
+        transfer_batch: HostFunction::fixed(0),
+        set_contract_package_paused: HostFunction::fixed(0),
     };
 
     let new_wasm_config = WasmConfig::new(
         DEFAULT_WASM_MAX_MEMORY,
         DEFAULT_MAX_STACK_HEIGHT,
+        DEFAULT_MAX_CALL_RESULT_SIZE,
         new_opcode_costs,
         new_storage_costs,
         new_host_function_costs,
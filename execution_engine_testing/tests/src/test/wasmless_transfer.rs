@@ -1,9 +1,9 @@
 use once_cell::sync::Lazy;
 
 use casper_engine_test_support::{
-    DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, UpgradeRequestBuilder,
-    DEFAULT_ACCOUNT_ADDR, DEFAULT_MAX_ASSOCIATED_KEYS, DEFAULT_PAYMENT, DEFAULT_PROTOCOL_VERSION,
-    PRODUCTION_RUN_GENESIS_REQUEST,
+    BalanceChange, DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder,
+    UpgradeRequestBuilder, DEFAULT_ACCOUNT_ADDR, DEFAULT_MAX_ASSOCIATED_KEYS, DEFAULT_PAYMENT,
+    DEFAULT_PROTOCOL_VERSION, PRODUCTION_RUN_GENESIS_REQUEST,
 };
 use casper_execution_engine::{
     core::{
@@ -30,8 +30,8 @@ use casper_types::{
     account::AccountHash,
     runtime_args,
     system::{handle_payment, mint},
-    AccessRights, ApiError, EraId, Gas, Key, Motes, ProtocolVersion, PublicKey, RuntimeArgs,
-    SecretKey, URef, U512,
+    AccessRights, ApiError, EraId, Gas, Key, KeyTag, Motes, ProtocolVersion, PublicKey,
+    RuntimeArgs, SecretKey, URef, U512,
 };
 
 const CONTRACT_TRANSFER_PURSE_TO_ACCOUNT: &str = "transfer_purse_to_account.wasm";
@@ -642,6 +642,51 @@ fn transfer_wasmless_should_create_target_if_it_doesnt_exist() {
     );
 }
 
+#[ignore]
+#[test]
+fn transfer_wasmless_should_only_touch_balances_and_records() {
+    let wasmless_transfer_cost = Motes::from_gas(
+        Gas::from(DEFAULT_WASMLESS_TRANSFER_COST),
+        WASMLESS_TRANSFER_FIXED_GAS_PRICE,
+    )
+    .expect("gas overflow");
+
+    let create_account_2: bool = true;
+    let mut builder = init_wasmless_transform_builder(create_account_2);
+    let transfer_amount: U512 = U512::from(1000);
+
+    let account_1_purse = builder
+        .get_account(*ACCOUNT_1_ADDR)
+        .expect("should get account 1")
+        .main_purse();
+    let account_2_purse = builder
+        .get_account(*ACCOUNT_2_ADDR)
+        .expect("should get account 2")
+        .main_purse();
+
+    let no_wasm_transfer_request = ExecuteRequestBuilder::transfer(
+        *ACCOUNT_1_ADDR,
+        runtime_args! {
+            mint::ARG_TARGET => account_2_purse,
+            mint::ARG_AMOUNT => transfer_amount,
+            mint::ARG_ID => <Option<u64>>::None
+        },
+    )
+    .build();
+
+    builder
+        .exec(no_wasm_transfer_request)
+        .expect_success()
+        .expect_balance_change(
+            account_1_purse,
+            BalanceChange::Decrease(transfer_amount + wasmless_transfer_cost.value()),
+        )
+        .expect_balance_change(account_2_purse, BalanceChange::Increase(transfer_amount))
+        .expect_no_writes_outside(&[KeyTag::Balance, KeyTag::Transfer, KeyTag::DeployInfo])
+        .commit()
+        .expect_balance_change(account_2_purse, BalanceChange::Increase(transfer_amount));
+}
+
 fn get_default_account_named_uref(builder: &mut InMemoryWasmTestBuilder, name: &str) -> URef {
     let default_account = builder
         .get_account(*DEFAULT_ACCOUNT_ADDR)
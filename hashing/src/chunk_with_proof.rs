@@ -85,6 +85,10 @@ impl ChunkWithProof {
         })
     }
 
+    pub(crate) fn from_parts(proof: IndexedMerkleProof, chunk: Bytes) -> Self {
+        ChunkWithProof { proof, chunk }
+    }
+
     /// Get a reference to the `ChunkWithProof`'s chunk.
     pub fn chunk(&self) -> &[u8] {
         self.chunk.as_slice()
@@ -120,6 +124,25 @@ impl ChunkWithProof {
         }
         Ok(())
     }
+
+    /// Verify the integrity of this chunk and that it is part of the data hashing to `digest`, as
+    /// computed by [`Digest::hash_into_chunks_if_necessary`] or
+    /// [`ChunkedHasher`](crate::ChunkedHasher).
+    ///
+    /// Data which fits in a single chunk is hashed directly rather than as a Merkle tree, so in
+    /// that case `digest` is compared with the hash of the chunk.
+    pub fn verify_against(&self, digest: &Digest) -> Result<(), ChunkWithProofVerificationError> {
+        self.verify()?;
+        let actual = if self.proof().count() == 1 {
+            Digest::hash(self.chunk())
+        } else {
+            self.proof().root_hash()
+        };
+        if actual != *digest {
+            return Err(ChunkWithProofVerificationError::UnexpectedRootHash);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,221 @@
+//! Incremental hashing of byte streams of arbitrary size, split into chunks.
+use std::io::{self, Read, Write};
+
+use crate::{error::MerkleConstructionError, ChunkWithProof, Digest, IndexedMerkleProof};
+
+/// Computes the same digest as [`Digest::hash_into_chunks_if_necessary`], but over a stream of
+/// bytes which is fed in incrementally and never needs to be held in memory as a whole.
+///
+/// The stream is split into chunks of [`ChunkWithProof::CHUNK_SIZE_BYTES`] bytes.  Only the
+/// current chunk and the digests of the completed ones are retained, so hashing a stream requires
+/// memory proportional to the chunk size plus 32 bytes per chunk.
+///
+/// ```
+/// use std::io::Write;
+///
+/// use casper_hashing::{ChunkWithProof, ChunkedHasher, Digest};
+///
+/// let data = vec![7; 2 * ChunkWithProof::CHUNK_SIZE_BYTES + 1];
+/// let mut hasher = ChunkedHasher::new();
+/// for part in data.chunks(1024) {
+///     hasher.write_all(part).unwrap();
+/// }
+/// let chunked_digest = hasher.finalize();
+/// assert_eq!(chunked_digest.chunk_count(), 3);
+/// assert_eq!(
+///     chunked_digest.digest(),
+///     Digest::hash_into_chunks_if_necessary(&data)
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ChunkedHasher {
+    current_chunk: Vec<u8>,
+    chunk_digests: Vec<Digest>,
+}
+
+impl ChunkedHasher {
+    /// Constructs a new, empty `ChunkedHasher`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hashes everything read from `reader` until it is exhausted.
+    pub fn hash_reader<R: Read>(mut reader: R) -> io::Result<ChunkedDigest> {
+        let mut hasher = ChunkedHasher::new();
+        let _ = io::copy(&mut reader, &mut hasher)?;
+        Ok(hasher.finalize())
+    }
+
+    /// Feeds `data` into the hasher.
+    pub fn update<T: AsRef<[u8]>>(&mut self, data: T) {
+        let mut remainder = data.as_ref();
+        while !remainder.is_empty() {
+            let available = ChunkWithProof::CHUNK_SIZE_BYTES - self.current_chunk.len();
+            let (head, tail) = remainder.split_at(available.min(remainder.len()));
+            self.current_chunk.extend_from_slice(head);
+            if self.current_chunk.len() == ChunkWithProof::CHUNK_SIZE_BYTES {
+                self.chunk_digests
+                    .push(Digest::blake2b_hash(&self.current_chunk));
+                self.current_chunk.clear();
+            }
+            remainder = tail;
+        }
+    }
+
+    /// Consumes the hasher, returning the digest of all the data fed in along with the digests of
+    /// the individual chunks.
+    pub fn finalize(self) -> ChunkedDigest {
+        let ChunkedHasher {
+            current_chunk,
+            mut chunk_digests,
+        } = self;
+        // Empty data is represented as a single, empty chunk.
+        if !current_chunk.is_empty() || chunk_digests.is_empty() {
+            chunk_digests.push(Digest::blake2b_hash(&current_chunk));
+        }
+        let digest = if chunk_digests.len() == 1 {
+            chunk_digests[0]
+        } else {
+            Digest::hash_merkle_tree(chunk_digests.iter().copied())
+        };
+        ChunkedDigest {
+            chunk_digests,
+            digest,
+        }
+    }
+}
+
+impl Write for ChunkedHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The outcome of hashing a byte stream with a [`ChunkedHasher`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkedDigest {
+    chunk_digests: Vec<Digest>,
+    digest: Digest,
+}
+
+impl ChunkedDigest {
+    /// Returns the digest of the whole stream, as computed by
+    /// [`Digest::hash_into_chunks_if_necessary`].
+    pub fn digest(&self) -> Digest {
+        self.digest
+    }
+
+    /// Returns the number of chunks the stream was split into.
+    ///
+    /// This is never zero: empty data is represented as a single, empty chunk.
+    pub fn chunk_count(&self) -> u64 {
+        self.chunk_digests.len() as u64
+    }
+
+    /// Returns the digests of the individual chunks, i.e. the leaves of the Merkle tree.
+    pub fn chunk_digests(&self) -> &[Digest] {
+        &self.chunk_digests
+    }
+
+    /// Constructs the [`ChunkWithProof`] for the chunk at `index`, whose contents are `chunk`.
+    ///
+    /// Since the stream itself isn't retained, the caller needs to provide the chunk again, e.g.
+    /// by re-reading it from its source.  It is checked against the digest recorded while hashing.
+    pub fn chunk_with_proof(
+        &self,
+        index: u64,
+        chunk: &[u8],
+    ) -> Result<ChunkWithProof, MerkleConstructionError> {
+        let proof = IndexedMerkleProof::new(self.chunk_digests.iter().copied(), index)?;
+        if proof.merkle_proof().first() != Some(&Digest::blake2b_hash(chunk)) {
+            return Err(MerkleConstructionError::ChunkDigestMismatch { index });
+        }
+        Ok(ChunkWithProof::from_parts(proof, chunk.to_vec().into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_CHUNK_COUNT: usize = 8;
+
+    fn hash_in_parts(data: &[u8], part_size: usize) -> ChunkedDigest {
+        let mut hasher = ChunkedHasher::new();
+        for part in data.chunks(part_size) {
+            hasher.update(part);
+        }
+        hasher.finalize()
+    }
+
+    #[test]
+    fn should_hash_empty_data_as_single_chunk() {
+        let chunked_digest = ChunkedHasher::new().finalize();
+        assert_eq!(chunked_digest.chunk_count(), 1);
+        assert_eq!(
+            chunked_digest.digest(),
+            Digest::hash_into_chunks_if_necessary(&[])
+        );
+    }
+
+    #[test]
+    fn should_match_hash_into_chunks_if_necessary() {
+        let max_len = MAX_CHUNK_COUNT * ChunkWithProof::CHUNK_SIZE_BYTES;
+        let data: Vec<u8> = (0..max_len).map(|value| value as u8).collect();
+        for len in 0..=max_len {
+            let data = &data[..len];
+            let expected = Digest::hash_into_chunks_if_necessary(data);
+            for part_size in 1..=2 * ChunkWithProof::CHUNK_SIZE_BYTES {
+                assert_eq!(hash_in_parts(data, part_size).digest(), expected);
+            }
+            assert_eq!(ChunkedHasher::hash_reader(data).unwrap().digest(), expected);
+        }
+    }
+
+    #[test]
+    fn should_construct_verifiable_proofs() {
+        let data: Vec<u8> = (0..=255).cycle().take(55).collect();
+        let chunked_digest = hash_in_parts(&data, 7);
+        assert_eq!(chunked_digest.chunk_count(), 6);
+
+        for (index, chunk) in data.chunks(ChunkWithProof::CHUNK_SIZE_BYTES).enumerate() {
+            let chunk_with_proof = chunked_digest
+                .chunk_with_proof(index as u64, chunk)
+                .unwrap();
+            assert_eq!(
+                chunk_with_proof,
+                ChunkWithProof::new(&data, index as u64).unwrap()
+            );
+            chunk_with_proof
+                .verify_against(&chunked_digest.digest())
+                .unwrap();
+            assert!(chunk_with_proof
+                .verify_against(&Digest::hash(b"other"))
+                .is_err());
+        }
+
+        assert_eq!(
+            chunked_digest.chunk_with_proof(1, &data[..ChunkWithProof::CHUNK_SIZE_BYTES]),
+            Err(MerkleConstructionError::ChunkDigestMismatch { index: 1 })
+        );
+        assert_eq!(
+            chunked_digest.chunk_with_proof(6, &[]),
+            Err(MerkleConstructionError::IndexOutOfBounds { count: 6, index: 6 })
+        );
+    }
+
+    #[test]
+    fn should_verify_single_chunk_against_plain_digest() {
+        let data = b"short";
+        let chunked_digest = ChunkedHasher::hash_reader(&data[..]).unwrap();
+        let chunk_with_proof = chunked_digest.chunk_with_proof(0, data).unwrap();
+        chunk_with_proof
+            .verify_against(&Digest::hash(data))
+            .unwrap();
+    }
+}
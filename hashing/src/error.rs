@@ -103,4 +103,10 @@ pub enum MerkleConstructionError {
         /// Total chunks count.
         count: String,
     },
+    /// The provided chunk does not match the digest of the chunk at the given index.
+    #[error("Could not construct Merkle proof. Chunk does not match digest at index {index}")]
+    ChunkDigestMismatch {
+        /// Requested index.
+        index: u64,
+    },
 }
@@ -60,10 +60,9 @@ impl FromBytes for IndexedMerkleProof {
 }
 
 impl IndexedMerkleProof {
-    pub(crate) fn new<I>(
-        leaves: I,
-        index: u64,
-    ) -> Result<IndexedMerkleProof, MerkleConstructionError>
+    /// Creates a proof of the leaf at `index` in the Merkle tree over `leaves`, as hashed by
+    /// [`Digest::hash_merkle_tree`].
+    pub fn new<I>(leaves: I, index: u64) -> Result<IndexedMerkleProof, MerkleConstructionError>
     where
         I: IntoIterator<Item = Digest>,
         I::IntoIter: ExactSizeIterator,
@@ -198,7 +197,8 @@ impl IndexedMerkleProof {
         l
     }
 
-    pub(crate) fn verify(&self) -> Result<(), MerkleVerificationError> {
+    /// Checks that the index lies within the tree and that the proof has the expected length.
+    pub fn verify(&self) -> Result<(), MerkleVerificationError> {
         if self.index >= self.count {
             return Err(MerkleVerificationError::IndexOutOfBounds {
                 count: self.count,
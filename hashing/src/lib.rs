@@ -8,6 +8,7 @@
 #![warn(missing_docs)]
 
 mod chunk_with_proof;
+mod chunked_hasher;
 mod error;
 mod indexed_merkle_proof;
 
@@ -36,6 +37,7 @@ use casper_types::{
     checksummed_hex, CLType, CLTyped,
 };
 pub use chunk_with_proof::ChunkWithProof;
+pub use chunked_hasher::{ChunkedDigest, ChunkedHasher};
 pub use error::{
     ChunkWithProofVerificationError, Error, MerkleConstructionError, MerkleVerificationError,
 };
@@ -225,6 +227,10 @@ impl Digest {
     }
 
     /// Hash data into chunks if necessary.
+    ///
+    /// Data longer than [`ChunkWithProof::CHUNK_SIZE_BYTES`] is split into chunks which are hashed
+    /// as the leaves of a Merkle tree; shorter data is hashed directly.  Use a [`ChunkedHasher`]
+    /// to compute the same digest over a stream of bytes.
     pub fn hash_into_chunks_if_necessary(bytes: &[u8]) -> Digest {
         if bytes.len() <= ChunkWithProof::CHUNK_SIZE_BYTES {
             Digest::blake2b_hash(bytes)
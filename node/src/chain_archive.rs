@@ -0,0 +1,774 @@
+//! Portable archives of chain history.
+//!
+//! An archive holds a contiguous range of blocks, each together with its finality signatures,
+//! deploys, approvals hashes and the execution results of its deploys, in a single file independent
+//! of the layout of the node's databases. It starts with the
+//! [`ARCHIVE_MAGIC`] bytes, followed by frames each consisting of a little-endian `u64` length and
+//! that many bytes of a bincode-encoded value. The first frame is an [`ArchiveHeader`], every
+//! further frame an [`ArchivedBlock`], in order of height.
+//!
+//! The archived blocks are hash-linked through their parent hashes, and the header carries an
+//! anchor: the switch block concluding the era before that of the first archived block. Given a
+//! trusted anchor, the validator weights it records are enough to check the finality signatures of
+//! the first era's blocks, and every archived switch block in turn vouches for the validators of
+//! the following era. An archive can therefore be verified in full by a node holding nothing but
+//! the anchor, or holding nothing at all if the archive starts at genesis and the genesis block
+//! hash is configured as the trusted hash.
+//!
+//! The approvals hashes of a block carry a Merkle proof of the checksum registry under the block's
+//! state root, which in turn vouches for the approvals of the block's deploys and for their
+//! execution results. Blocks stored without approvals hashes, such as those executed before the
+//! checksum registry was introduced, are archived without them, and their execution results can
+//! only be checked against the block's deploys.
+//!
+//! Imported blocks are marked complete. Their global state is not part of the archive; the node
+//! syncs the global state of the highest complete block as usual once it starts.
+//!
+//! The node must not be running while chain history is exported or imported.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{File, OpenOptions},
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Write},
+    path::PathBuf,
+};
+
+use num_rational::Ratio;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+
+use casper_types::{crypto, EraId, ExecutionResult, PublicKey, U512};
+
+use crate::{
+    components::{
+        fetcher::FetchItem,
+        storage::{FatalStorageError, Storage},
+    },
+    reactor::main_reactor::Config,
+    types::{
+        chainspec, ApprovalsHashes, ApprovalsHashesValidationError, Block, BlockHash, BlockHeader,
+        BlockSignatures, BlockValidationError, Chainspec, ChainspecRawBytes, Chunkable, Deploy,
+        DeployConfigurationFailure, DeployHash,
+    },
+    utils::{self, BlockSignatureError, Loadable, WithDir},
+};
+
+/// The bytes every archive starts with.
+const ARCHIVE_MAGIC: &[u8; 8] = b"CSPRARCH";
+
+/// The version of the archive format written by this version of the node.
+const ARCHIVE_FORMAT_VERSION: u32 = 2;
+
+/// The largest frame accepted when reading an archive, guarding against allocating for a corrupt
+/// length prefix.
+const MAX_FRAME_SIZE: u64 = 1 << 30;
+
+/// Error returned as a result of exporting or importing chain history.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// Error loading the chainspec.
+    #[error("error loading chainspec: {0}")]
+    LoadChainspec(chainspec::Error),
+
+    /// Error accessing storage.
+    #[error("error accessing storage: {0}")]
+    Storage(#[from] FatalStorageError),
+
+    /// Error reading or writing the archive file.
+    #[error("error accessing archive: {0}")]
+    Io(#[from] io::Error),
+
+    /// Error encoding or decoding an archive frame.
+    #[error("error encoding or decoding archive frame: {0}")]
+    Bincode(#[from] bincode::Error),
+
+    /// The requested range of blocks is empty.
+    #[error("invalid block range: {from} is above {to}")]
+    InvalidRange {
+        /// The lowest height requested.
+        from: u64,
+        /// The highest height requested.
+        to: u64,
+    },
+
+    /// The archive file already exists.
+    #[error("refusing to overwrite existing archive {}", .0.display())]
+    OutputExists(PathBuf),
+
+    /// A block to be exported, or its signatures or deploys, is not stored.
+    #[error("cannot export block at height {height}: {missing} not stored")]
+    MissingData {
+        /// The height of the block.
+        height: u64,
+        /// What is missing.
+        missing: String,
+    },
+
+    /// The file doesn't start with the archive magic bytes.
+    #[error("not a chain archive")]
+    NotAnArchive,
+
+    /// The archive was written in a format version this node doesn't understand.
+    #[error("unsupported archive format version {0}")]
+    UnsupportedVersion(u32),
+
+    /// A frame's length prefix exceeds the maximum frame size.
+    #[error("archive frame of {0} bytes exceeds maximum size")]
+    FrameTooLarge(u64),
+
+    /// The archive ended before all blocks of its range were read.
+    #[error("archive is truncated: expected block at height {0}")]
+    Truncated(u64),
+
+    /// The archive holds more blocks than its range.
+    #[error("archive holds data beyond its range")]
+    TrailingData,
+
+    /// The archive is for a different network.
+    #[error("archive is for network {archive}, but this node is on {ours}")]
+    WrongNetwork {
+        /// The network named in the archive.
+        archive: String,
+        /// Our network.
+        ours: String,
+    },
+
+    /// The archive's anchor is neither stored locally nor the configured trusted hash.
+    #[error("archive anchor {0} is not stored locally and is not the configured trusted hash")]
+    UntrustedAnchor(BlockHash),
+
+    /// The archive doesn't start at genesis, but has no anchor.
+    #[error("archive not starting at genesis has no anchor")]
+    MissingAnchor,
+
+    /// A block failed verification.
+    #[error("invalid block at height {height}: {reason}")]
+    InvalidBlock {
+        /// The height of the block.
+        height: u64,
+        /// Why the block was rejected.
+        reason: InvalidBlock,
+    },
+}
+
+/// The reasons for rejecting an archived block.
+#[derive(Debug, Error)]
+pub(crate) enum InvalidBlock {
+    /// The block's hashes don't match its contents.
+    #[error(transparent)]
+    Validation(#[from] BlockValidationError),
+
+    /// The block is not at the expected height.
+    #[error("expected height {expected}, got {actual}")]
+    UnexpectedHeight {
+        /// The expected height.
+        expected: u64,
+        /// The actual height.
+        actual: u64,
+    },
+
+    /// The block is not a child of the preceding archived block.
+    #[error("parent hash {actual} doesn't match preceding block {expected}")]
+    UnexpectedParent {
+        /// The hash of the preceding block.
+        expected: BlockHash,
+        /// The block's parent hash.
+        actual: BlockHash,
+    },
+
+    /// The block's era doesn't follow the era of the latest trusted switch block.
+    #[error("expected era {expected}, got {actual}")]
+    UnexpectedEra {
+        /// The era following that of the latest trusted switch block.
+        expected: EraId,
+        /// The block's era.
+        actual: EraId,
+    },
+
+    /// The archived signatures are for another block or era.
+    #[error("signatures are for block {block_hash} in era {era_id}")]
+    MismatchedSignatures {
+        /// The block hash the signatures are for.
+        block_hash: BlockHash,
+        /// The era the signatures are for.
+        era_id: EraId,
+    },
+
+    /// A finality signature is invalid.
+    #[error("invalid finality signature: {0}")]
+    Signature(crypto::Error),
+
+    /// The signatures don't carry enough weight to finalize the block.
+    #[error(transparent)]
+    InsufficientSignatures(#[from] BlockSignatureError),
+
+    /// The archived deploys are not exactly those of the block.
+    #[error("archived deploys don't match the block's deploy hashes")]
+    MismatchedDeploys,
+
+    /// An archived deploy is invalid.
+    #[error("invalid deploy {deploy_hash}: {error}")]
+    InvalidDeploy {
+        /// The hash of the deploy.
+        deploy_hash: DeployHash,
+        /// The validation error.
+        error: DeployConfigurationFailure,
+    },
+
+    /// The archived approvals hashes are invalid.
+    #[error("invalid approvals hashes: {0}")]
+    ApprovalsHashes(#[from] ApprovalsHashesValidationError),
+
+    /// The approvals of an archived deploy don't match the block's approvals hashes.
+    #[error("approvals of deploy {0} don't match the block's approvals hashes")]
+    MismatchedApprovals(DeployHash),
+
+    /// The archived execution results are not exactly those of the block's deploys.
+    #[error("archived execution results don't match the block's deploy hashes")]
+    MismatchedExecutionResults,
+
+    /// The archived execution results don't match the checksum in the block's global state.
+    #[error("archived execution results don't match the block's execution results checksum")]
+    ExecutionResultsChecksum,
+}
+
+/// The first frame of an archive.
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveHeader {
+    /// The version of the archive format.
+    format_version: u32,
+    /// The name of the network the blocks belong to.
+    network_name: String,
+    /// The height of the first archived block.
+    from: u64,
+    /// The height of the last archived block.
+    to: u64,
+    /// The header of the switch block concluding the era before that of the first archived block,
+    /// or `None` if the first archived block is the genesis block.
+    anchor: Option<BlockHeader>,
+}
+
+/// A frame holding a single block and its associated data.
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedBlock {
+    block: Block,
+    signatures: BlockSignatures,
+    /// The block's deploys, with their finalized approvals.
+    deploys: Vec<Deploy>,
+    /// The approvals hashes of the block, or `None` if none are stored for it.
+    approvals_hashes: Option<ApprovalsHashes>,
+    /// The execution results of the block's deploys, in the order of its deploys and transfers.
+    execution_results: Vec<(DeployHash, ExecutionResult)>,
+}
+
+/// Writes the blocks with heights `from` to `to` inclusive, with their signatures, deploys,
+/// approvals hashes and execution results, to a new archive at `output`.
+pub(crate) fn export_chain(
+    config: WithDir<Config>,
+    from: u64,
+    to: u64,
+    output: PathBuf,
+) -> Result<(), Error> {
+    if from > to {
+        return Err(Error::InvalidRange { from, to });
+    }
+    let (storage, chainspec) = open_storage(config)?;
+
+    let missing = |height: u64, missing: String| Error::MissingData { height, missing };
+    let first = storage
+        .read_block_header_by_height(from)?
+        .ok_or_else(|| missing(from, "block".to_string()))?;
+    let anchor = if first.is_genesis() {
+        None
+    } else {
+        let era_id = first.era_id().predecessor().ok_or(Error::MissingAnchor)?;
+        let anchor = storage
+            .read_switch_block_header_by_era_id(era_id)?
+            .ok_or_else(|| missing(from, format!("switch block of era {}", era_id)))?;
+        Some(anchor)
+    };
+
+    let file = match OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&output)
+    {
+        Err(error) if error.kind() == ErrorKind::AlreadyExists => {
+            return Err(Error::OutputExists(output))
+        }
+        result => result?,
+    };
+    let mut writer = BufWriter::new(file);
+    writer.write_all(ARCHIVE_MAGIC)?;
+    write_frame(
+        &mut writer,
+        &ArchiveHeader {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            network_name: chainspec.network_config.name,
+            from,
+            to,
+            anchor,
+        },
+    )?;
+
+    for height in from..=to {
+        let block_with_metadata = storage
+            .read_block_and_metadata_by_height(height)?
+            .ok_or_else(|| missing(height, "block or signatures".to_string()))?;
+        let block = block_with_metadata.block;
+        let deploys = block
+            .deploy_and_transfer_hashes()
+            .map(|deploy_hash| {
+                storage
+                    .get_deploy_with_finalized_approvals_by_hash(deploy_hash)
+                    .map(|deploy| deploy.into_naive())
+                    .ok_or_else(|| missing(height, format!("deploy {}", deploy_hash)))
+            })
+            .collect::<Result<_, Error>>()?;
+        let approvals_hashes = storage.read_approvals_hashes(block.hash())?;
+        let execution_results = storage
+            .read_block_execution_results(block.hash())?
+            .ok_or_else(|| missing(height, "execution results".to_string()))?;
+        write_frame(
+            &mut writer,
+            &ArchivedBlock {
+                block,
+                signatures: block_with_metadata.block_signatures,
+                deploys,
+                approvals_hashes,
+                execution_results,
+            },
+        )?;
+    }
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+
+    info!(from, to, output = %output.display(), "exported chain history");
+    Ok(())
+}
+
+/// Verifies the archive at `input` and stores the blocks it holds along with all their data,
+/// marking them complete.
+///
+/// The archive's anchor must either be stored locally or be the configured trusted hash. Each block
+/// is stored in a single transaction as soon as it is verified, so if verification fails part-way
+/// through, the blocks before the invalid one remain stored and complete.
+pub(crate) fn import_chain(config: WithDir<Config>, input: PathBuf) -> Result<(), Error> {
+    let trusted_hash = config.value().node.trusted_hash;
+    let (mut storage, chainspec) = open_storage(config)?;
+
+    let mut reader = BufReader::new(File::open(&input)?);
+    let mut magic = [0; ARCHIVE_MAGIC.len()];
+    match reader.read_exact(&mut magic) {
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Err(Error::NotAnArchive),
+        result => result?,
+    }
+    if &magic != ARCHIVE_MAGIC {
+        return Err(Error::NotAnArchive);
+    }
+    let header: ArchiveHeader = read_frame(&mut reader)?.ok_or(Error::NotAnArchive)?;
+    if header.format_version != ARCHIVE_FORMAT_VERSION {
+        return Err(Error::UnsupportedVersion(header.format_version));
+    }
+    if header.network_name != chainspec.network_config.name {
+        return Err(Error::WrongNetwork {
+            archive: header.network_name,
+            ours: chainspec.network_config.name,
+        });
+    }
+
+    let mut verifier = match &header.anchor {
+        Some(anchor) => {
+            if !is_trusted(&storage, trusted_hash, &anchor.block_hash())? {
+                return Err(Error::UntrustedAnchor(anchor.block_hash()));
+            }
+            Verifier::new(
+                chainspec.core_config.finality_threshold_fraction,
+                header.from,
+                Some(anchor),
+            )
+        }
+        None if header.from == 0 => Verifier::new(
+            chainspec.core_config.finality_threshold_fraction,
+            header.from,
+            None,
+        ),
+        None => return Err(Error::MissingAnchor),
+    };
+
+    for height in header.from..=header.to {
+        let archived: ArchivedBlock = read_frame(&mut reader)?.ok_or(Error::Truncated(height))?;
+        if archived.block.header().is_genesis()
+            && !is_trusted(&storage, trusted_hash, archived.block.hash())?
+        {
+            return Err(Error::UntrustedAnchor(*archived.block.hash()));
+        }
+        verifier
+            .verify(&archived)
+            .map_err(|reason| Error::InvalidBlock { height, reason })?;
+
+        let ArchivedBlock {
+            block,
+            signatures,
+            deploys,
+            approvals_hashes,
+            execution_results,
+        } = archived;
+        storage.put_archived_block(
+            &block,
+            signatures,
+            &deploys,
+            approvals_hashes.as_ref(),
+            execution_results.into_iter().collect::<HashMap<_, _>>(),
+        )?;
+    }
+    if read_frame::<ArchivedBlock>(&mut reader)?.is_some() {
+        return Err(Error::TrailingData);
+    }
+
+    info!(
+        from = header.from,
+        to = header.to,
+        input = %input.display(),
+        "imported chain history"
+    );
+    Ok(())
+}
+
+/// Returns whether `block_hash` is the configured trusted hash or the hash of a stored block.
+fn is_trusted(
+    storage: &Storage,
+    trusted_hash: Option<BlockHash>,
+    block_hash: &BlockHash,
+) -> Result<bool, Error> {
+    Ok(trusted_hash == Some(*block_hash) || storage.read_block_header(block_hash)?.is_some())
+}
+
+/// Checks archived blocks one by one, tracking the validators of the era they belong to.
+struct Verifier {
+    fault_tolerance_fraction: Ratio<u64>,
+    /// The height of the next block expected.
+    next_height: u64,
+    /// The hash of the preceding archived block.
+    parent_hash: Option<BlockHash>,
+    /// The era of the blocks being verified and its validator weights, known from the latest
+    /// trusted switch block, or `None` before the genesis block.
+    era: Option<(EraId, BTreeMap<PublicKey, U512>)>,
+}
+
+impl Verifier {
+    fn new(fault_tolerance_fraction: Ratio<u64>, from: u64, anchor: Option<&BlockHeader>) -> Self {
+        Verifier {
+            fault_tolerance_fraction,
+            next_height: from,
+            parent_hash: None,
+            era: anchor.and_then(Self::next_era),
+        }
+    }
+
+    /// Checks the approvals hashes against the block's state root, and the approvals of its
+    /// deploys and their execution results against the checksums the approvals hashes prove.
+    fn verify_approvals_hashes(
+        block: &Block,
+        deploys: &[Deploy],
+        approvals_hashes: &ApprovalsHashes,
+        execution_results: &[(DeployHash, ExecutionResult)],
+    ) -> Result<(), InvalidBlock> {
+        approvals_hashes.validate(block)?;
+
+        let deploys: HashMap<&DeployHash, &Deploy> = deploys
+            .iter()
+            .map(|deploy| (deploy.hash(), deploy))
+            .collect();
+        for deploy_id in approvals_hashes.deploy_ids(block) {
+            let deploy_hash = deploy_id.deploy_hash();
+            let matches = deploys
+                .get(deploy_hash)
+                .and_then(|deploy| deploy.approvals_hash().ok())
+                .map_or(false, |approvals_hash| {
+                    approvals_hash == *deploy_id.approvals_hash()
+                });
+            if !matches {
+                return Err(InvalidBlock::MismatchedApprovals(*deploy_hash));
+            }
+        }
+
+        if let Some(checksum) = approvals_hashes.execution_results_checksum() {
+            let execution_results: Vec<ExecutionResult> = execution_results
+                .iter()
+                .map(|(_, execution_result)| execution_result.clone())
+                .collect();
+            // `Chunkable` is implemented for a reference to the results.
+            if (&execution_results).hash().ok() != Some(checksum) {
+                return Err(InvalidBlock::ExecutionResultsChecksum);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the era following the given switch block, and its validator weights.
+    fn next_era(switch_block: &BlockHeader) -> Option<(EraId, BTreeMap<PublicKey, U512>)> {
+        switch_block
+            .next_era_validator_weights()
+            .map(|weights| (switch_block.era_id().successor(), weights.clone()))
+    }
+
+    fn verify(&mut self, archived: &ArchivedBlock) -> Result<(), InvalidBlock> {
+        let ArchivedBlock {
+            block,
+            signatures,
+            deploys,
+            approvals_hashes,
+            execution_results,
+        } = archived;
+        let header = block.header();
+        block.verify()?;
+
+        if header.height() != self.next_height {
+            return Err(InvalidBlock::UnexpectedHeight {
+                expected: self.next_height,
+                actual: header.height(),
+            });
+        }
+        if let Some(parent_hash) = self.parent_hash {
+            if *header.parent_hash() != parent_hash {
+                return Err(InvalidBlock::UnexpectedParent {
+                    expected: parent_hash,
+                    actual: *header.parent_hash(),
+                });
+            }
+        }
+
+        if signatures.block_hash != *block.hash() || signatures.era_id != header.era_id() {
+            return Err(InvalidBlock::MismatchedSignatures {
+                block_hash: signatures.block_hash,
+                era_id: signatures.era_id,
+            });
+        }
+        // The genesis block is trusted by its hash rather than by its signatures.
+        if !header.is_genesis() {
+            let validator_weights = match &self.era {
+                Some((era_id, validator_weights)) if *era_id == header.era_id() => {
+                    validator_weights
+                }
+                era => {
+                    return Err(InvalidBlock::UnexpectedEra {
+                        expected: era.as_ref().map_or(EraId::new(0), |(era_id, _)| *era_id),
+                        actual: header.era_id(),
+                    })
+                }
+            };
+            signatures.verify().map_err(InvalidBlock::Signature)?;
+            utils::check_sufficient_block_signatures(
+                validator_weights,
+                self.fault_tolerance_fraction,
+                Some(signatures),
+            )?;
+        }
+
+        let mut expected_hashes: Vec<&DeployHash> = block.deploy_and_transfer_hashes().collect();
+        let mut actual_hashes: Vec<&DeployHash> = deploys.iter().map(Deploy::hash).collect();
+        expected_hashes.sort();
+        actual_hashes.sort();
+        if expected_hashes != actual_hashes {
+            return Err(InvalidBlock::MismatchedDeploys);
+        }
+        for deploy in deploys {
+            deploy
+                .is_valid()
+                .map_err(|error| InvalidBlock::InvalidDeploy {
+                    deploy_hash: *deploy.hash(),
+                    error,
+                })?;
+        }
+
+        let execution_results_hashes: Vec<&DeployHash> = execution_results
+            .iter()
+            .map(|(deploy_hash, _)| deploy_hash)
+            .collect();
+        if !execution_results_hashes
+            .into_iter()
+            .eq(block.deploy_and_transfer_hashes())
+        {
+            return Err(InvalidBlock::MismatchedExecutionResults);
+        }
+        if let Some(approvals_hashes) = approvals_hashes {
+            Self::verify_approvals_hashes(block, deploys, approvals_hashes, execution_results)?;
+        }
+
+        self.next_height += 1;
+        self.parent_hash = Some(*block.hash());
+        if let Some(next_era) = Self::next_era(header) {
+            self.era = Some(next_era);
+        }
+        Ok(())
+    }
+}
+
+/// Opens the block store and loads the chainspec.
+fn open_storage(config: WithDir<Config>) -> Result<(Storage, Chainspec), Error> {
+    let (root, config) = config.into_parts();
+    let (chainspec, _) =
+        <(Chainspec, ChainspecRawBytes)>::from_path(&root).map_err(Error::LoadChainspec)?;
+    let storage = Storage::new(
+        &WithDir::new(&root, config.storage),
+        chainspec.core_config.finality_threshold_fraction,
+        None,
+        chainspec.protocol_version(),
+        &chainspec.network_config.name,
+        chainspec.deploy_config.max_ttl,
+        chainspec.core_config.recent_era_count(),
+        None,
+        false,
+    )?;
+    Ok((storage, chainspec))
+}
+
+/// Writes `value` as a length-prefixed frame.
+fn write_frame<W: Write, T: Serialize>(writer: &mut W, value: &T) -> Result<(), Error> {
+    let bytes = bincode::serialize(value)?;
+    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Reads a length-prefixed frame, returning `None` if the reader is exhausted.
+fn read_frame<R: Read, T: DeserializeOwned>(reader: &mut R) -> Result<Option<T>, Error> {
+    let mut length = [0; 8];
+    match reader.read_exact(&mut length) {
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        result => result?,
+    }
+    let length = u64::from_le_bytes(length);
+    if length > MAX_FRAME_SIZE {
+        return Err(Error::FrameTooLarge(length));
+    }
+    let mut bytes = vec![0; length as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(Some(bincode::deserialize(&bytes)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use casper_types::{
+        crypto::generate_ed25519_keypair, testing::TestRng, ProtocolVersion, SecretKey,
+    };
+
+    use super::*;
+    use crate::types::FinalitySignature;
+
+    fn sign(block: &Block, secret_key: &SecretKey, public_key: &PublicKey) -> BlockSignatures {
+        let mut signatures = BlockSignatures::new(*block.hash(), block.header().era_id());
+        let signature = FinalitySignature::create(
+            *block.hash(),
+            block.header().era_id(),
+            secret_key,
+            public_key.clone(),
+        );
+        signatures.insert_proof(public_key.clone(), signature.signature);
+        signatures
+    }
+
+    #[test]
+    fn should_roundtrip_frames() {
+        let mut rng = TestRng::new();
+        let block = Block::random(&mut rng);
+        let signatures = BlockSignatures::new(*block.hash(), block.header().era_id());
+
+        let mut bytes = vec![];
+        write_frame(
+            &mut bytes,
+            &ArchivedBlock {
+                block: block.clone(),
+                signatures,
+                deploys: vec![],
+                approvals_hashes: None,
+                execution_results: vec![],
+            },
+        )
+        .unwrap();
+
+        let mut reader = bytes.as_slice();
+        let archived: ArchivedBlock = read_frame(&mut reader).unwrap().unwrap();
+        assert_eq!(archived.block, block);
+        assert!(read_frame::<ArchivedBlock>(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn should_verify_blocks_signed_by_anchored_validators() {
+        let mut rng = TestRng::new();
+        let (secret_key, public_key) = generate_ed25519_keypair();
+        let (other_secret_key, other_public_key) = generate_ed25519_keypair();
+        let weights: BTreeMap<_, _> = [
+            (public_key.clone(), U512::from(100)),
+            (other_public_key.clone(), U512::from(1)),
+        ]
+        .into_iter()
+        .collect();
+
+        let block_at = |rng: &mut TestRng, height: u64, parent_hash: Option<BlockHash>| {
+            Block::random_with_specifics_and_parent_and_validator_weights(
+                rng,
+                EraId::new(3),
+                height,
+                ProtocolVersion::V1_0_0,
+                false,
+                None,
+                parent_hash,
+                BTreeMap::new(),
+            )
+        };
+        let anchor = Block::random_with_specifics_and_parent_and_validator_weights(
+            &mut rng,
+            EraId::new(2),
+            9,
+            ProtocolVersion::V1_0_0,
+            true,
+            None,
+            None,
+            weights,
+        );
+        let first = block_at(&mut rng, 10, Some(*anchor.hash()));
+        let second = block_at(&mut rng, 11, Some(*first.hash()));
+        let unlinked = block_at(&mut rng, 11, None);
+
+        let archived = |block: &Block, signatures: BlockSignatures| ArchivedBlock {
+            block: block.clone(),
+            signatures,
+            deploys: vec![],
+            approvals_hashes: None,
+            execution_results: vec![],
+        };
+        let mut verifier = Verifier::new(Ratio::new(1, 3), 10, Some(anchor.header()));
+
+        let weakly_signed = archived(&first, sign(&first, &other_secret_key, &other_public_key));
+        assert!(matches!(
+            verifier.verify(&weakly_signed),
+            Err(InvalidBlock::InsufficientSignatures(_))
+        ));
+        verifier
+            .verify(&archived(&first, sign(&first, &secret_key, &public_key)))
+            .unwrap();
+
+        let unlinked = archived(&unlinked, sign(&unlinked, &secret_key, &public_key));
+        assert!(matches!(
+            verifier.verify(&unlinked),
+            Err(InvalidBlock::UnexpectedParent { .. })
+        ));
+        // The execution results must be exactly those of the block's deploys.
+        let mut stray_result = archived(&second, sign(&second, &secret_key, &public_key));
+        stray_result
+            .execution_results
+            .push((DeployHash::random(&mut rng), rng.gen()));
+        assert!(matches!(
+            verifier.verify(&stray_result),
+            Err(InvalidBlock::MismatchedExecutionResults)
+        ));
+        verifier
+            .verify(&archived(&second, sign(&second, &secret_key, &public_key)))
+            .unwrap();
+    }
+}
@@ -3,10 +3,11 @@
 //! Most configuration is done via config files (see [`config`](../config/index.html) for details).
 
 pub mod arglang;
+mod layering;
 
 use std::{
     alloc::System,
-    fs,
+    env, fs,
     path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
@@ -48,6 +49,10 @@ pub enum Cli {
         /// Path to configuration file.
         config: PathBuf,
 
+        #[structopt(long, env = "NODE_CONFIG_OVERRIDE")]
+        /// Path to a configuration file whose entries override those of the main one.
+        config_override: Option<PathBuf>,
+
         #[structopt(
             short = "C",
             long,
@@ -59,6 +64,8 @@ pub enum Cli {
         /// <SECTION>.<KEY>=<VALUE>.  For example, '-C=node.chainspec_config_path=chainspec.toml'
         config_ext: Vec<ConfigExt>,
     },
+    /// Inspect the node's configuration.
+    Config(ConfigCommand),
     /// Migrate modified values from the old config as required after an upgrade.
     MigrateConfig {
         /// Path to configuration file of previous version of node.
@@ -77,6 +84,91 @@ pub enum Cli {
         #[structopt(long)]
         new_config: PathBuf,
     },
+    /// Write compacted copies of the block store and global state to a separate directory.
+    ///
+    /// Only global state reachable from stored blocks is retained. The node must not be running
+    /// while compacting.
+    CompactStorage {
+        /// Path to configuration file.
+        config: PathBuf,
+        /// Directory to write the compacted databases to.
+        #[structopt(long)]
+        output_dir: PathBuf,
+    },
+    /// Export a range of blocks with their finality signatures and deploys to a portable archive.
+    ///
+    /// The node must not be running while exporting.
+    ExportChain {
+        /// Path to configuration file.
+        config: PathBuf,
+        /// Height of the first block to export.
+        #[structopt(long)]
+        from: u64,
+        /// Height of the last block to export.
+        #[structopt(long)]
+        to: u64,
+        /// Path of the archive file to write.
+        #[structopt(long)]
+        output: PathBuf,
+    },
+    /// Verify a chain archive and import its blocks, finality signatures and deploys.
+    ///
+    /// The switch block preceding the archived blocks must be stored already or be configured as
+    /// `node.trusted_hash`. The node must not be running while importing.
+    ImportChain {
+        /// Path to configuration file.
+        config: PathBuf,
+        /// Path of the archive file to import.
+        #[structopt(long)]
+        input: PathBuf,
+    },
+    /// List the era snapshots tagged while `storage.enable_era_snapshots` was set.
+    ListSnapshots {
+        /// Path to configuration file.
+        config: PathBuf,
+    },
+    /// Reset the node to a tagged era snapshot, deleting all later blocks.
+    ///
+    /// Intended for development and private networks only. The node must not be running while
+    /// resetting.
+    ResetToSnapshot {
+        /// Path to configuration file.
+        config: PathBuf,
+        /// Name of the snapshot to reset to, e.g. `era-12`.
+        #[structopt(long)]
+        snapshot: String,
+    },
+}
+
+/// Subcommands of the `config` command.
+#[derive(Debug, StructOpt)]
+pub enum ConfigCommand {
+    /// Print the configuration resulting from merging the configuration file, the override file,
+    /// the `CASPER_NODE__<SECTION>__<KEY>` environment variables and the command-line overrides.
+    Dump {
+        /// Path to configuration file.
+        config: PathBuf,
+
+        #[structopt(long, env = "NODE_CONFIG_OVERRIDE")]
+        /// Path to a configuration file whose entries override those of the main one.
+        config_override: Option<PathBuf>,
+
+        #[structopt(
+            short = "C",
+            long,
+            env = "NODE_CONFIG",
+            use_delimiter(true),
+            value_delimiter(";")
+        )]
+        /// Overrides and extensions for configuration file entries in the form
+        /// <SECTION>.<KEY>=<VALUE>.
+        config_ext: Vec<ConfigExt>,
+
+        /// Validate the merged configuration as the node would on startup, failing if any entry
+        /// is unknown or invalid.
+        #[structopt(long)]
+        effective: bool,
+    },
 }
 
 #[derive(Debug)]
@@ -144,11 +236,15 @@ impl Cli {
     /// Executes selected CLI command.
     pub async fn run(self) -> anyhow::Result<i32> {
         match self {
-            Cli::Standard { config, config_ext } => {
+            Cli::Standard {
+                config,
+                config_override,
+                config_ext,
+            } => {
                 // Setup UNIX signal hooks.
                 setup_signal_hooks();
 
-                let validator_config = Self::init(&config, config_ext)?;
+                let validator_config = Self::init(&config, config_override.as_deref(), config_ext)?;
 
                 // We use a `ChaCha20Rng` for the production node. For one, we want to completely
                 // eliminate any chance of runtime failures, regardless of how small (these
@@ -177,6 +273,8 @@ impl Cli {
                 ))
                 .context("failed to create a network identity")?;
 
+                let shutdown_drain_timeout = validator_config.value().node.shutdown_drain_timeout;
+
                 let mut main_runner = Runner::<main_reactor::MainReactor>::with_metrics(
                     validator_config,
                     Arc::new(chainspec),
@@ -188,13 +286,32 @@ impl Cli {
                 .await?;
 
                 let exit_code = main_runner.run(&mut rng).await;
+                main_runner
+                    .drain(&mut rng, shutdown_drain_timeout.into())
+                    .await;
                 Ok(exit_code as i32)
             }
+            Cli::Config(ConfigCommand::Dump {
+                config,
+                config_override,
+                config_ext,
+                effective,
+            }) => {
+                // Logging is left uninitialized, so that only the configuration is written to
+                // stdout.
+                let (_root, config_table) =
+                    Self::load_config_table(&config, config_override.as_deref(), config_ext)?;
+                if effective {
+                    let _: main_reactor::Config = config_table.clone().try_into()?;
+                }
+                print!("{}", toml::to_string_pretty(&config_table)?);
+                Ok(ExitCode::Success as i32)
+            }
             Cli::MigrateConfig {
                 old_config,
                 new_config,
             } => {
-                let new_config = Self::init(&new_config, vec![])?;
+                let new_config = Self::init(&new_config, None, vec![])?;
 
                 let old_root = old_config
                     .parent()
@@ -216,7 +333,7 @@ impl Cli {
                 old_config,
                 new_config,
             } => {
-                let new_config = Self::init(&new_config, vec![])?;
+                let new_config = Self::init(&new_config, None, vec![])?;
 
                 let old_root = old_config
                     .parent()
@@ -234,14 +351,73 @@ impl Cli {
                 )?;
                 Ok(ExitCode::Success as i32)
             }
+            Cli::CompactStorage { config, output_dir } => {
+                let config = Self::init(&config, None, vec![])?;
+
+                info!(build_version = %crate::VERSION_STRING.as_str(), "compacting storage");
+                crate::storage_compaction::compact_storage(config, output_dir)?;
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::ExportChain {
+                config,
+                from,
+                to,
+                output,
+            } => {
+                let config = Self::init(&config, None, vec![])?;
+
+                info!(build_version = %crate::VERSION_STRING.as_str(), "exporting chain");
+                crate::chain_archive::export_chain(config, from, to, output)?;
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::ImportChain { config, input } => {
+                let config = Self::init(&config, None, vec![])?;
+
+                info!(build_version = %crate::VERSION_STRING.as_str(), "importing chain");
+                crate::chain_archive::import_chain(config, input)?;
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::ListSnapshots { config } => {
+                let config = Self::init(&config, None, vec![])?;
+
+                crate::era_snapshot_reset::list_snapshots(config)?;
+                Ok(ExitCode::Success as i32)
+            }
+            Cli::ResetToSnapshot { config, snapshot } => {
+                let config = Self::init(&config, None, vec![])?;
+
+                info!(build_version = %crate::VERSION_STRING.as_str(), "resetting to era snapshot");
+                crate::era_snapshot_reset::reset_to_snapshot(config, snapshot)?;
+                Ok(ExitCode::Success as i32)
+            }
         }
     }
 
     /// Parses the config file for the current version of casper-node, and initializes logging.
     fn init(
         config: &Path,
+        config_override: Option<&Path>,
         config_ext: Vec<ConfigExt>,
     ) -> anyhow::Result<WithDir<main_reactor::Config>> {
+        let (root, config_table) = Self::load_config_table(config, config_override, config_ext)?;
+
+        // Create main config, including any overridden values.
+        let main_config: main_reactor::Config = config_table.try_into()?;
+        logging::init_with_config(&main_config.logging)?;
+
+        Ok(WithDir::new(root, main_config))
+    }
+
+    /// Reads the config file and applies the override file, the environment variables and the
+    /// command-line overrides to it, in that order (see [`layering`]).
+    ///
+    /// Returns the directory relative paths in the config are resolved against, which is that of
+    /// the main config file even for paths set in the override file.
+    fn load_config_table(
+        config: &Path,
+        config_override: Option<&Path>,
+        config_ext: Vec<ConfigExt>,
+    ) -> anyhow::Result<(PathBuf, Value)> {
         // Determine the parent directory of the configuration file, if any.
         // Otherwise, we default to `/`.
         let root = config
@@ -258,15 +434,23 @@ impl Cli {
         // defaulted config instance if one is not provided.
         let mut config_table: Value = toml::from_str(&encoded_config)?;
 
+        if let Some(config_override) = config_override {
+            let encoded_override = fs::read_to_string(config_override)
+                .context("could not read configuration override file")
+                .with_context(|| config_override.display().to_string())?;
+            let override_table: Value = toml::from_str(&encoded_override)
+                .context("could not parse configuration override file")
+                .with_context(|| config_override.display().to_string())?;
+            layering::merge(&mut config_table, override_table);
+        }
+
+        layering::merge(&mut config_table, layering::env_overlay(env::vars_os())?);
+
         // If any command line overrides to the config values are passed, apply them.
         for item in config_ext {
             item.update_toml_table(&mut config_table)?;
         }
 
-        // Create main config, including any overridden values.
-        let main_config: main_reactor::Config = config_table.try_into()?;
-        logging::init_with_config(&main_config.logging)?;
-
-        Ok(WithDir::new(root, main_config))
+        Ok((root, config_table))
     }
 }
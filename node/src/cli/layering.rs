@@ -0,0 +1,203 @@
+//! Layering of configuration sources.
+//!
+//! The effective configuration is assembled from the following layers, each taking precedence over
+//! the ones before it:
+//!
+//! 1. the base configuration file,
+//! 2. an optional override file, containing any subset of the base file's sections and keys,
+//! 3. environment variables named `CASPER_NODE__<SECTION>__<KEY>`, where nested tables add further
+//!    `__`-separated segments, e.g. `CASPER_NODE__CONSENSUS__HIGHWAY__PENDING_VERTEX_TIMEOUT`. The
+//!    segments are lowercased to form the keys and values use the [`arglang`](super::arglang)
+//!    syntax,
+//! 4. the `-C`/`NODE_CONFIG` command-line overrides.
+
+use std::{collections::BTreeMap, ffi::OsString};
+
+use anyhow::{bail, Context};
+use toml::{value::Table, Value};
+
+use super::arglang;
+
+/// Prefix of environment variables overriding configuration values.
+pub(super) const ENV_PREFIX: &str = "CASPER_NODE__";
+
+/// Separator between the path segments of an environment variable name.
+const ENV_SEPARATOR: &str = "__";
+
+/// Recursively merges `overlay` into `base`.
+///
+/// Tables are merged key by key; any other value in `overlay`, including arrays, replaces the one
+/// in `base`.
+pub(super) fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Table(base_table), Value::Table(overlay_table)) => {
+            for (key, overlay_value) in overlay_table {
+                match base_table.get_mut(&key) {
+                    Some(base_value) => merge(base_value, overlay_value),
+                    None => {
+                        let _ = base_table.insert(key, overlay_value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Builds a table from all variables in `vars` named with [`ENV_PREFIX`].
+///
+/// Variables are applied in order of their names, so the outcome doesn't depend on the order in
+/// which the environment lists them.  Variables whose name or value isn't valid UTF-8 are skipped.
+pub(super) fn env_overlay<I>(vars: I) -> anyhow::Result<Value>
+where
+    I: IntoIterator<Item = (OsString, OsString)>,
+{
+    let overrides: BTreeMap<String, String> = vars
+        .into_iter()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+        .collect();
+
+    let mut overlay = Value::Table(Table::new());
+    for (name, raw_value) in overrides {
+        let path: Vec<String> = name[ENV_PREFIX.len()..]
+            .split(ENV_SEPARATOR)
+            .map(str::to_lowercase)
+            .collect();
+        if path.len() < 2 || path.iter().any(String::is_empty) {
+            bail!(
+                "environment variable {} should be named {}<SECTION>{}<KEY>",
+                name,
+                ENV_PREFIX,
+                ENV_SEPARATOR
+            );
+        }
+        let value = arglang::parse(&raw_value)
+            .with_context(|| format!("could not parse value of environment variable {}", name))?;
+
+        let mut nested = value;
+        for segment in path.into_iter().rev() {
+            let mut table = Table::new();
+            let _ = table.insert(segment, nested);
+            nested = Value::Table(table);
+        }
+        merge(&mut overlay, nested);
+    }
+    Ok(overlay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(items: &[(&str, &str)]) -> Vec<(OsString, OsString)> {
+        items
+            .iter()
+            .map(|(name, value)| (OsString::from(name), OsString::from(value)))
+            .collect()
+    }
+
+    #[test]
+    fn should_merge_tables_recursively() {
+        let mut base: Value = toml::from_str(
+            r#"
+            [network]
+            bind_address = "0.0.0.0:34553"
+            known_addresses = ["1.2.3.4:34553"]
+
+            [consensus.highway]
+            request_state_interval = "20sec"
+            "#,
+        )
+        .unwrap();
+        let overlay: Value = toml::from_str(
+            r#"
+            [network]
+            known_addresses = ["5.6.7.8:34553"]
+
+            [consensus.highway]
+            pending_vertex_timeout = "1min"
+
+            [storage]
+            path = "/var/lib/casper"
+            "#,
+        )
+        .unwrap();
+
+        merge(&mut base, overlay);
+
+        let expected: Value = toml::from_str(
+            r#"
+            [network]
+            bind_address = "0.0.0.0:34553"
+            known_addresses = ["5.6.7.8:34553"]
+
+            [consensus.highway]
+            request_state_interval = "20sec"
+            pending_vertex_timeout = "1min"
+
+            [storage]
+            path = "/var/lib/casper"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(base, expected);
+    }
+
+    #[test]
+    fn should_build_overlay_from_prefixed_env_vars() {
+        let overlay = env_overlay(vars(&[
+            ("CASPER_NODE__NETWORK__KNOWN_ADDRESSES", "[1.2.3.4:34553]"),
+            (
+                "CASPER_NODE__CONSENSUS__HIGHWAY__PENDING_VERTEX_TIMEOUT",
+                "33sec",
+            ),
+            ("casper_node__storage__max_block_store_size", "1024"),
+            ("NODE_CONFIG", "node.sync_to_genesis=true"),
+            ("PATH", "/usr/bin"),
+        ]))
+        .unwrap();
+
+        let expected: Value = toml::from_str(
+            r#"
+            [network]
+            known_addresses = ["1.2.3.4:34553"]
+
+            [consensus.highway]
+            pending_vertex_timeout = "33sec"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(overlay, expected);
+    }
+
+    #[test]
+    fn should_reject_malformed_env_vars() {
+        assert!(env_overlay(vars(&[("CASPER_NODE__NETWORK", "x")])).is_err());
+        assert!(env_overlay(vars(&[("CASPER_NODE__NETWORK____KEY", "x")])).is_err());
+        assert!(env_overlay(vars(&[("CASPER_NODE__NETWORK__KEY", "\"x")])).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_skip_non_utf8_env_vars() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let non_utf8 = || OsString::from_vec(vec![b'x', 0xff]);
+        let mut env = vars(&[("CASPER_NODE__STORAGE__MAX_BLOCK_STORE_SIZE", "1024")]);
+        env.push((non_utf8(), OsString::from("1")));
+        env.push((OsString::from("CASPER_NODE__NETWORK__NAME"), non_utf8()));
+        env.push((OsString::from("LC_CTYPE"), non_utf8()));
+
+        let overlay = env_overlay(env).unwrap();
+
+        let expected: Value = toml::from_str(
+            r#"
+            [storage]
+            max_block_store_size = 1024
+            "#,
+        )
+        .unwrap();
+        assert_eq!(overlay, expected);
+    }
+}
@@ -65,9 +65,12 @@ pub mod rpc_server;
 pub(crate) mod shutdown_trigger;
 pub mod storage;
 pub(crate) mod sync_leaper;
+pub(crate) mod upgrade_downloader;
 pub(crate) mod upgrade_watcher;
+pub(crate) mod webhook_notifier;
 
 use datasize::DataSize;
+use futures::future::BoxFuture;
 use serde::Deserialize;
 use std::fmt::{Debug, Display};
 use tracing::info;
@@ -193,6 +196,26 @@ pub(crate) trait PortBoundComponent<REv>: InitializedComponent<REv> {
     ) -> Result<Effects<Self::ComponentEvent>, Self::Error>;
 }
 
+/// A non-critical component which can be torn down and initialized anew after failing, without
+/// affecting the rest of the node.
+pub(crate) trait RestartableComponent<REv>: InitializedComponent<REv> {
+    /// Returns why the component failed, or `None` if it is healthy.
+    ///
+    /// By default, only a fatal state counts as a failure.
+    fn failure(&self) -> Option<String> {
+        match self.state() {
+            ComponentState::Fatal(msg) => Some(msg.clone()),
+            _ => None,
+        }
+    }
+
+    /// Releases everything the component still holds and puts it back into the uninitialized
+    /// state.
+    ///
+    /// The returned future resolves once the component's background tasks have exited.
+    fn reset(&mut self) -> BoxFuture<'static, ()>;
+}
+
 pub(crate) trait ValidatorBoundComponent<REv>: Component<REv> {
     fn handle_validators(
         &mut self,
@@ -16,7 +16,6 @@ use std::{
 };
 
 use datasize::DataSize;
-use futures::FutureExt;
 use itertools::Itertools;
 use prometheus::Registry;
 use tracing::{debug, error, info, warn};
@@ -38,6 +37,7 @@ use crate::{
         BlockHash, BlockSignatures, FinalitySignature, MetaBlock, MetaBlockState, NodeId,
         ValidatorMatrix,
     },
+    utils::signature_verification::SignatureVerifier,
     NodeRng,
 };
 
@@ -125,6 +125,8 @@ pub(crate) struct BlockAccumulator {
     /// The minimum time between a block and its child.
     min_block_time: TimeDiff,
     #[data_size(skip)]
+    signature_verifier: SignatureVerifier,
+    #[data_size(skip)]
     metrics: Metrics,
 }
 
@@ -134,6 +136,7 @@ impl BlockAccumulator {
         validator_matrix: ValidatorMatrix,
         recent_era_interval: u64,
         min_block_time: TimeDiff,
+        signature_verifier: SignatureVerifier,
         registry: &Registry,
     ) -> Result<Self, prometheus::Error> {
         Ok(Self {
@@ -148,6 +151,7 @@ impl BlockAccumulator {
             recent_era_interval,
             peer_block_timestamps: Default::default(),
             min_block_time,
+            signature_verifier,
             metrics: Metrics::new(registry)?,
         })
     }
@@ -337,18 +341,31 @@ impl BlockAccumulator {
             Some(acceptor) => acceptor,
         };
 
+        // Only the first copy of a block received from a peer says anything about propagation.  The
+        // latency is measured from when we first heard of the block rather than from its timestamp,
+        // which includes the time taken to finalize it and is subject to the proposer's clock.
+        let first_received_from_peer = sender.is_some() && acceptor.block_height().is_none();
+        let first_seen = acceptor.first_seen();
+
         match acceptor.register_block(meta_block, sender) {
-            Ok(_) => match self.validator_matrix.validator_weights(era_id) {
-                Some(evw) => {
-                    let (should_store, faulty_senders) = acceptor.should_store_block(&evw);
-                    self.store_block_and_finality_signatures(
-                        effect_builder,
-                        should_store,
-                        faulty_senders,
-                    )
+            Ok(_) => {
+                if first_received_from_peer {
+                    self.metrics
+                        .block_propagation_latency
+                        .observe(first_seen.elapsed().millis() as f64 / 1000.0);
                 }
-                None => Effects::new(),
-            },
+                match self.validator_matrix.validator_weights(era_id) {
+                    Some(evw) => {
+                        let (should_store, faulty_senders) = acceptor.should_store_block(&evw);
+                        self.store_block_and_finality_signatures(
+                            effect_builder,
+                            should_store,
+                            faulty_senders,
+                        )
+                    }
+                    None => Effects::new(),
+                }
+            }
             Err(error) => match error {
                 Error::InvalidGossip(ref gossip_error) => {
                     warn!(%gossip_error, "received invalid block");
@@ -528,7 +545,7 @@ impl BlockAccumulator {
         effects
     }
 
-    fn highest_usable_block_height(&self) -> Option<u64> {
+    pub(crate) fn highest_usable_block_height(&self) -> Option<u64> {
         let mut ret = self.local_tip.map(|local_tip| local_tip.height);
         for block_acceptor in self.block_acceptors.values() {
             if false == block_acceptor.has_sufficient_finality() {
@@ -674,10 +691,12 @@ impl BlockAccumulator {
                 self.update_block_children(&meta_block);
                 // The block wasn't executed yet, so we just put it to storage. An `ExecutedBlock`
                 // event will then re-trigger this flow and eventually mark it complete.
-                let cloned_signatures = block_signatures.clone();
                 effect_builder
-                    .put_block_to_storage(Arc::clone(&meta_block.block))
-                    .then(move |_| effect_builder.put_signatures_to_storage(cloned_signatures))
+                    .put_signed_block_to_storage(
+                        Arc::clone(&meta_block.block),
+                        block_signatures.clone(),
+                        false,
+                    )
                     .event(move |_| Event::Stored {
                         maybe_meta_block: Some(meta_block),
                         maybe_block_signatures: Some(block_signatures),
@@ -693,10 +712,12 @@ impl BlockAccumulator {
                 // The block was already executed, which means it is stored and we have the global
                 // state for it. As on this code path we also know it is sufficiently signed,
                 // we mark it as complete.
-                let block_height = meta_block.block.height();
                 effect_builder
-                    .put_signatures_to_storage(block_signatures.clone())
-                    .then(move |_| effect_builder.mark_block_completed(block_height))
+                    .put_signed_block_to_storage(
+                        Arc::clone(&meta_block.block),
+                        block_signatures.clone(),
+                        true,
+                    )
                     .event(move |_| Event::Stored {
                         maybe_meta_block: Some(meta_block),
                         maybe_block_signatures: Some(block_signatures),
@@ -795,6 +816,17 @@ impl<REv: ReactorEvent> Component<REv> for BlockAccumulator {
             Event::CreatedFinalitySignature { finality_signature } => {
                 self.register_finality_signature(effect_builder, *finality_signature, None)
             }
+            Event::ReceivedFinalitySignature {
+                finality_signature,
+                sender,
+            } if !finality_signature.is_verification_cached() => self
+                .signature_verifier
+                .clone()
+                .verify_finality_signature(finality_signature)
+                .event(move |finality_signature| Event::ReceivedFinalitySignature {
+                    finality_signature,
+                    sender,
+                }),
             Event::ReceivedFinalitySignature {
                 finality_signature,
                 sender,
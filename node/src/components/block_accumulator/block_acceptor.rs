@@ -24,6 +24,8 @@ pub(super) struct BlockAcceptor {
     signatures: BTreeMap<PublicKey, (FinalitySignature, BTreeSet<NodeId>)>,
     peers: BTreeSet<NodeId>,
     last_progress: Timestamp,
+    /// When we first heard of the block, be it via the block itself or a finality signature.
+    first_seen: Timestamp,
 }
 
 #[derive(Debug, PartialEq)]
@@ -53,6 +55,7 @@ impl BlockAcceptor {
             signatures: BTreeMap::new(),
             peers: peers.into_iter().collect(),
             last_progress: Timestamp::now(),
+            first_seen: Timestamp::now(),
         }
     }
 
@@ -346,6 +349,10 @@ impl BlockAcceptor {
         self.last_progress
     }
 
+    pub(super) fn first_seen(&self) -> Timestamp {
+        self.first_seen
+    }
+
     /// Removes finality signatures that have the wrong era ID or are signed by non-validators.
     /// Returns the set of peers that sent us these signatures.
     fn remove_bogus_validators(
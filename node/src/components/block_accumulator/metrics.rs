@@ -1,6 +1,16 @@
-use prometheus::{IntGauge, Registry};
+use prometheus::{Histogram, IntGauge, Registry};
 
-use crate::unregister_metric;
+use crate::{unregister_metric, utils};
+
+const BLOCK_PROPAGATION_LATENCY_NAME: &str = "block_accumulator_block_propagation_latency_seconds";
+const BLOCK_PROPAGATION_LATENCY_HELP: &str =
+    "time (in sec) from first hearing of a block until it was first received from a peer";
+
+// Blocks usually follow their finality signatures closely, but slow propagation can take much
+// longer.  Coverage is ~82s with higher resolution in the first buckets.
+const EXPONENTIAL_BUCKET_START: f64 = 0.01;
+const EXPONENTIAL_BUCKET_FACTOR: f64 = 2.0;
+const EXPONENTIAL_BUCKET_COUNT: usize = 14;
 
 /// Metrics for the block accumulator component.
 #[derive(Debug)]
@@ -10,6 +20,10 @@ pub(super) struct Metrics {
     /// Number of child block hashes that we know of and that will be used in order to request next
     /// blocks.
     pub(super) known_child_blocks: IntGauge,
+    /// Time from first hearing of a block, e.g. via a finality signature, until the block itself
+    /// was first received from a peer.  Unlike the block's timestamp, the time we first heard of
+    /// it is taken from our own clock and excludes the time taken to finalize the block.
+    pub(super) block_propagation_latency: Histogram,
     registry: Registry,
 }
 
@@ -25,12 +39,24 @@ impl Metrics {
             "number of blocks received by the Block Accumulator for which we know the hash of the child block".to_string(),
         )?;
 
+        let block_propagation_latency = utils::register_histogram_metric(
+            registry,
+            BLOCK_PROPAGATION_LATENCY_NAME,
+            BLOCK_PROPAGATION_LATENCY_HELP,
+            prometheus::exponential_buckets(
+                EXPONENTIAL_BUCKET_START,
+                EXPONENTIAL_BUCKET_FACTOR,
+                EXPONENTIAL_BUCKET_COUNT,
+            )?,
+        )?;
+
         registry.register(Box::new(block_acceptors.clone()))?;
         registry.register(Box::new(known_child_blocks.clone()))?;
 
         Ok(Metrics {
             block_acceptors,
             known_child_blocks,
+            block_propagation_latency,
             registry: registry.clone(),
         })
     }
@@ -40,5 +66,6 @@ impl Drop for Metrics {
     fn drop(&mut self) {
         unregister_metric!(self.registry, self.block_acceptors);
         unregister_metric!(self.registry, self.known_child_blocks);
+        unregister_metric!(self.registry, self.block_propagation_latency);
     }
 }
@@ -165,6 +165,7 @@ impl Reactor for MockReactor {
             validator_matrix.clone(),
             RECENT_ERA_INTERVAL,
             block_time,
+            SignatureVerifier::new().unwrap(),
             registry,
         )
         .unwrap();
@@ -279,6 +280,7 @@ fn upsert_acceptor() {
         validator_matrix,
         recent_era_interval,
         block_time,
+        SignatureVerifier::new().unwrap(),
         &metrics_registry,
     )
     .unwrap();
@@ -752,6 +754,7 @@ fn accumulator_highest_usable_block_height() {
         validator_matrix.clone(),
         recent_era_interval,
         block_time,
+        SignatureVerifier::new().unwrap(),
         &Registry::default(),
     )
     .unwrap();
@@ -877,6 +880,7 @@ fn accumulator_should_leap() {
         validator_matrix.clone(),
         recent_era_interval,
         block_time,
+        SignatureVerifier::new().unwrap(),
         &Registry::default(),
     )
     .unwrap();
@@ -953,6 +957,7 @@ fn accumulator_purge() {
         validator_matrix.clone(),
         recent_era_interval,
         block_time,
+        SignatureVerifier::new().unwrap(),
         &Registry::default(),
     )
     .unwrap();
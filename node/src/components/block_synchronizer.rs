@@ -12,16 +12,17 @@ mod metrics;
 mod need_next;
 mod peer_list;
 mod signature_acquisition;
+mod sync_estimate;
 mod trie_accumulator;
 
 #[cfg(test)]
 mod tests;
 
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 use datasize::DataSize;
 use either::Either;
-use futures::FutureExt;
+use futures::{future, FutureExt};
 use once_cell::sync::Lazy;
 use prometheus::Registry;
 use schemars::JsonSchema;
@@ -30,7 +31,7 @@ use tracing::{debug, error, info, trace, warn};
 
 use casper_execution_engine::core::engine_state;
 use casper_hashing::Digest;
-use casper_types::Timestamp;
+use casper_types::{EraId, TimeDiff, Timestamp};
 
 use super::network::blocklist::BlocklistJustification;
 use crate::{
@@ -73,6 +74,7 @@ pub(crate) use global_state_synchronizer::{
 };
 use metrics::Metrics;
 pub(crate) use need_next::NeedNext;
+use sync_estimate::{SyncEstimate, SyncEstimator, ESTIMATE_LOG_INTERVAL};
 use trie_accumulator::TrieAccumulator;
 pub(crate) use trie_accumulator::{
     Error as TrieAccumulatorError, Event as TrieAccumulatorEvent,
@@ -90,6 +92,14 @@ static BLOCK_SYNCHRONIZER_STATUS: Lazy<BlockSynchronizerStatus> = Lazy::new(|| {
             ),
             block_height: Some(40),
             acquisition_state: "have strict finality(40) for: block hash 16dd..c55e".to_string(),
+            estimate: SyncEstimate {
+                headers_per_minute: Some(45),
+                blocks_per_minute: Some(12),
+                tries_per_minute: Some(5400),
+                remaining_blocks: Some(41),
+                trie_frontier: 1250,
+                eta: Some(TimeDiff::from_seconds(205)),
+            },
         }),
         Some(BlockSyncStatus {
             block_hash: BlockHash::new(
@@ -100,6 +110,14 @@ static BLOCK_SYNCHRONIZER_STATUS: Lazy<BlockSynchronizerStatus> = Lazy::new(|| {
             ),
             block_height: Some(6701),
             acquisition_state: "have block body(6701) for: block hash 5990..4983".to_string(),
+            estimate: SyncEstimate {
+                headers_per_minute: Some(30),
+                blocks_per_minute: Some(30),
+                tries_per_minute: None,
+                remaining_blocks: Some(3),
+                trie_frontier: 0,
+                eta: Some(TimeDiff::from_seconds(6)),
+            },
         }),
     )
 });
@@ -164,6 +182,8 @@ pub(crate) struct BlockSyncStatus {
     block_height: Option<u64>,
     /// The state of acquisition of the data associated with the block.
     acquisition_state: String,
+    /// An estimate of the work left to sync and of how long it will take.
+    estimate: SyncEstimate,
 }
 
 /// The status of the block synchronizer.
@@ -207,6 +227,9 @@ pub(crate) struct BlockSynchronizer {
     historical: Option<BlockBuilder>,
     // deals with global state acquisition for historical blocks
     global_sync: GlobalStateSynchronizer,
+    forward_estimator: SyncEstimator,
+    historical_estimator: SyncEstimator,
+    last_estimate_log: Timestamp,
     #[data_size(skip)]
     metrics: Metrics,
 }
@@ -226,6 +249,9 @@ impl BlockSynchronizer {
             forward: None,
             historical: None,
             global_sync: GlobalStateSynchronizer::new(config.max_parallel_trie_fetches() as usize),
+            forward_estimator: SyncEstimator::default(),
+            historical_estimator: SyncEstimator::default(),
+            last_estimate_log: Timestamp::now(),
             metrics: Metrics::new(registry)?,
         })
     }
@@ -296,6 +322,22 @@ impl BlockSynchronizer {
         true
     }
 
+    /// Registers the height syncing is heading for, used to estimate how long it will take.
+    ///
+    /// Historical syncs head back to the lowest block needed, while catch-up and forward syncs
+    /// head up to the highest block known to the network.
+    pub(crate) fn register_target_height(
+        &mut self,
+        should_fetch_execution_state: bool,
+        target_height: Option<u64>,
+    ) {
+        if should_fetch_execution_state {
+            self.historical_estimator.set_target_height(target_height);
+        } else {
+            self.forward_estimator.set_target_height(target_height);
+        }
+    }
+
     /// Registers a sync leap result, if able.
     pub(crate) fn register_sync_leap(
         &mut self,
@@ -412,6 +454,7 @@ impl BlockSynchronizer {
         match &mut self.forward {
             Some(builder) if builder.block_hash() == *block_hash => {
                 builder.register_block_executed();
+                self.forward_estimator.record_block(Timestamp::now());
                 self.metrics
                     .forward_block_sync_duration
                     .observe(builder.sync_start_time().elapsed().as_secs_f64());
@@ -481,6 +524,7 @@ impl BlockSynchronizer {
                 self.metrics
                     .historical_block_sync_duration
                     .observe(builder.sync_start_time().elapsed().as_secs_f64());
+                self.historical_estimator.record_block(Timestamp::now());
             }
             _ => {
                 trace!(%block_hash, "BlockSynchronizer: not currently synchronizing historical block");
@@ -575,14 +619,15 @@ impl BlockSynchronizer {
                 }
                 NeedNext::GlobalState(block_hash, global_state_root_hash) => {
                     builder.set_in_flight_latch();
+                    let sync = sync_and_anchor_global_state(
+                        effect_builder,
+                        block_hash,
+                        global_state_root_hash,
+                        builder.block_height_and_era(),
+                        peers.into_iter().collect(),
+                    );
                     results.extend(
-                        effect_builder
-                            .sync_global_state(
-                                block_hash,
-                                global_state_root_hash,
-                                peers.into_iter().collect(),
-                            )
-                            .event(move |result| Event::GlobalStateSynced { block_hash, result }),
+                        sync.event(move |result| Event::GlobalStateSynced { block_hash, result }),
                     );
                 }
                 NeedNext::ExecutionResultsChecksum(block_hash, global_state_root_hash) => {
@@ -736,6 +781,8 @@ impl BlockSynchronizer {
             }
         };
 
+        let is_forward =
+            matches!(&self.forward, Some(builder) if builder.block_hash() == block_hash);
         match (&mut self.forward, &mut self.historical) {
             (Some(builder), _) | (_, Some(builder)) if builder.block_hash() == block_hash => {
                 match maybe_block_header {
@@ -749,6 +796,12 @@ impl BlockSynchronizer {
                             error!(%error, "BlockSynchronizer: failed to apply block header");
                         } else {
                             builder.register_era_validator_weights(&self.validator_matrix);
+                            let estimator = if is_forward {
+                                &mut self.forward_estimator
+                            } else {
+                                &mut self.historical_estimator
+                            };
+                            estimator.record_header(Timestamp::now());
                         }
                     }
                 }
@@ -1118,19 +1171,58 @@ impl BlockSynchronizer {
     }
 
     fn status(&self) -> BlockSynchronizerStatus {
+        let now = Timestamp::now();
         BlockSynchronizerStatus::new(
             self.historical.as_ref().map(|builder| BlockSyncStatus {
                 block_hash: builder.block_hash(),
                 block_height: builder.block_height(),
                 acquisition_state: builder.block_acquisition_state().to_string(),
+                estimate: self.historical_estimate(builder, now),
             }),
             self.forward.as_ref().map(|builder| BlockSyncStatus {
                 block_hash: builder.block_hash(),
                 block_height: builder.block_height(),
                 acquisition_state: builder.block_acquisition_state().to_string(),
+                estimate: self
+                    .forward_estimator
+                    .estimate(builder.block_height(), None, now),
             }),
         )
     }
+
+    fn historical_estimate(&self, builder: &BlockBuilder, now: Timestamp) -> SyncEstimate {
+        let tries = (
+            self.global_sync.tries_written(),
+            self.global_sync.trie_frontier(),
+        );
+        self.historical_estimator
+            .estimate(builder.block_height(), Some(tries), now)
+    }
+
+    /// Logs a summary of the sync estimates, at most once per `ESTIMATE_LOG_INTERVAL`.
+    fn log_estimates(&mut self) {
+        let now = Timestamp::now();
+        if now.saturating_diff(self.last_estimate_log) < ESTIMATE_LOG_INTERVAL {
+            return;
+        }
+        self.last_estimate_log = now;
+        if let Some(builder) = &self.historical {
+            let estimate = self.historical_estimate(builder, now);
+            info!(
+                block_height = ?builder.block_height(),
+                "BlockSynchronizer: historical sync: {}", estimate
+            );
+        }
+        if let Some(builder) = &self.forward {
+            let estimate = self
+                .forward_estimator
+                .estimate(builder.block_height(), None, now);
+            info!(
+                block_height = ?builder.block_height(),
+                "BlockSynchronizer: forward sync: {}", estimate
+            );
+        }
+    }
 }
 
 impl<REv> InitializedComponent<REv> for BlockSynchronizer
@@ -1152,6 +1244,32 @@ where
     }
 }
 
+/// Synchronizes global state under the given root hash, then anchors it to the block at the given
+/// height and era, just as the state roots of executed blocks are anchored.
+///
+/// Failing to anchor the state root is only logged, as anchors are informational.
+async fn sync_and_anchor_global_state<REv: ReactorEvent>(
+    effect_builder: EffectBuilder<REv>,
+    block_hash: BlockHash,
+    state_root_hash: Digest,
+    maybe_height_and_era: Option<(u64, EraId)>,
+    peers: HashSet<NodeId>,
+) -> Result<GlobalStateSynchronizerResponse, GlobalStateSynchronizerError> {
+    let result = effect_builder
+        .sync_global_state(block_hash, state_root_hash, peers)
+        .await;
+    if let (Ok(response), Some((block_height, era_id))) = (&result, maybe_height_and_era) {
+        let root_hash = *response.hash();
+        if let Err(error) = effect_builder
+            .anchor_state_root(root_hash, block_height, era_id)
+            .await
+        {
+            warn!(%block_hash, %error, "BlockSynchronizer: failed to anchor synced state root");
+        }
+    }
+    result
+}
+
 impl<REv: ReactorEvent> Component<REv> for BlockSynchronizer {
     type Event = Event;
 
@@ -1236,7 +1354,10 @@ impl<REv: ReactorEvent> Component<REv> for BlockSynchronizer {
                     }
                     // prompts for what data (if any) is needed next to acquire block(s) being
                     // sync'd
-                    BlockSynchronizerRequest::NeedNext => self.need_next(effect_builder, rng),
+                    BlockSynchronizerRequest::NeedNext => {
+                        self.log_estimates();
+                        self.need_next(effect_builder, rng)
+                    }
                     // this component is periodically asked for any peers that have provided false
                     // data (if any) which are then disconnected from
                     BlockSynchronizerRequest::DishonestPeers => {
@@ -1291,6 +1412,44 @@ impl<REv: ReactorEvent> Component<REv> for BlockSynchronizer {
                             })
                             .collect()
                     }
+
+                    // sent when executing a block failed on tries quarantined as corrupted; each is
+                    // synced like the root of a global state, which writes it over the corrupted
+                    // one
+                    BlockSynchronizerRequest::RefetchTries {
+                        block_hash,
+                        trie_hashes,
+                    } => {
+                        let max_simultaneous_peers = self.max_simultaneous_peers as usize;
+                        async move {
+                            let peers: HashSet<NodeId> = effect_builder
+                                .get_fully_connected_peers(max_simultaneous_peers)
+                                .await
+                                .into_iter()
+                                .collect();
+                            let results =
+                                future::join_all(trie_hashes.into_iter().map(|trie_hash| {
+                                    effect_builder.sync_global_state(
+                                        block_hash,
+                                        trie_hash,
+                                        peers.clone(),
+                                    )
+                                }))
+                                .await;
+                            for result in results {
+                                match result {
+                                    Ok(response) => info!(
+                                        trie_hash = %response.hash(),
+                                        "re-fetched quarantined trie"
+                                    ),
+                                    Err(error) => {
+                                        warn!(%error, "failed to re-fetch quarantined trie")
+                                    }
+                                }
+                            }
+                        }
+                        .ignore()
+                    }
                 },
                 // tunnel event to global state synchronizer
                 // global_state_sync is a black box; we do not hook need next here
@@ -328,7 +328,7 @@ impl BlockBuilder {
     }
 
     pub(super) fn disqualify_peer(&mut self, peer: Option<NodeId>) {
-        debug!(?peer, "disqualify_peer");
+        debug!(peer_id = ?peer, "disqualify_peer");
         self.peer_list.disqualify_peer(peer);
     }
 
@@ -13,7 +13,10 @@ use casper_execution_engine::{core::engine_state, storage::trie::TrieRaw};
 use casper_hashing::Digest;
 use casper_types::Timestamp;
 
-use super::{TrieAccumulator, TrieAccumulatorError, TrieAccumulatorEvent, TrieAccumulatorResponse};
+use super::{
+    sync_estimate::Throughput, TrieAccumulator, TrieAccumulatorError, TrieAccumulatorEvent,
+    TrieAccumulatorResponse,
+};
 use crate::{
     components::Component,
     effect::{
@@ -68,6 +71,11 @@ impl Response {
 pub(crate) enum Event {
     #[from]
     Request(SyncGlobalStateRequest),
+    LocalStateProbed {
+        request: SyncGlobalStateRequest,
+        #[serde(skip)]
+        missing_tries: Result<Vec<Digest>, engine_state::Error>,
+    },
     FetchedTrie {
         trie_hash: Digest,
         trie_accumulator_result: Result<TrieAccumulatorResponse, TrieAccumulatorError>,
@@ -211,6 +219,9 @@ pub(super) struct GlobalStateSynchronizer {
     /// were requested for syncing and have those tries as descendants.
     in_flight: BTreeMap<Digest, HashSet<Digest>>,
     last_progress: Option<Timestamp>,
+    /// The rate at which tries are written, measured afresh for each sync which starts while no
+    /// other state root is being synced.
+    tries_written: Throughput,
 }
 
 impl GlobalStateSynchronizer {
@@ -223,6 +234,7 @@ impl GlobalStateSynchronizer {
             fetch_queue: Default::default(),
             in_flight: Default::default(),
             last_progress: None,
+            tries_written: Throughput::default(),
         }
     }
 
@@ -234,11 +246,78 @@ impl GlobalStateSynchronizer {
         self.last_progress
     }
 
+    /// Returns the rate at which tries are written.
+    pub(super) fn tries_written(&self) -> &Throughput {
+        &self.tries_written
+    }
+
+    /// Returns the number of tries known to be needed which aren't written yet: those queued or
+    /// being fetched, and those fetched but waiting for their children.
+    pub(super) fn trie_frontier(&self) -> u64 {
+        (self.fetch_queue.queue.len() + self.in_flight.len() + self.tries_awaiting_children.len())
+            as u64
+    }
+
     /// Returns whether we are already processing a request for the given hash.
     pub(super) fn has_global_state_request(&self, global_state_hash: &Digest) -> bool {
         self.request_states.contains_key(global_state_hash)
     }
 
+    /// Checks whether the requested state root is already complete in the local trie store before
+    /// fetching anything from peers.
+    ///
+    /// Only the state root and its children are looked up, since tries are never written before
+    /// their children.
+    fn probe_local_state<REv>(
+        &mut self,
+        request: SyncGlobalStateRequest,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event>
+    where
+        REv: From<TrieAccumulatorRequest> + From<ContractRuntimeRequest> + Send,
+    {
+        if self.has_global_state_request(&request.state_root_hash) {
+            // Already being synced, so known to be incomplete.
+            return self.handle_request(request, effect_builder);
+        }
+        effect_builder
+            .get_missing_state_root_tries(request.state_root_hash)
+            .event(move |missing_tries| Event::LocalStateProbed {
+                request,
+                missing_tries,
+            })
+    }
+
+    fn handle_local_state_probed<REv>(
+        &mut self,
+        request: SyncGlobalStateRequest,
+        missing_tries: Result<Vec<Digest>, engine_state::Error>,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event>
+    where
+        REv: From<TrieAccumulatorRequest> + Send,
+    {
+        match missing_tries {
+            Ok(missing_tries) if missing_tries.is_empty() => {
+                debug!(
+                    state_root_hash = %request.state_root_hash,
+                    "global state already present locally"
+                );
+                let response = Response::new(request.state_root_hash, vec![]);
+                request.responder.respond(Ok(response)).ignore()
+            }
+            Ok(_) => self.handle_request(request, effect_builder),
+            Err(error) => {
+                warn!(
+                    state_root_hash = %request.state_root_hash,
+                    %error,
+                    "failed to probe local global state"
+                );
+                self.handle_request(request, effect_builder)
+            }
+        }
+    }
+
     fn handle_request<REv>(
         &mut self,
         request: SyncGlobalStateRequest,
@@ -248,6 +327,11 @@ impl GlobalStateSynchronizer {
         REv: From<TrieAccumulatorRequest> + Send,
     {
         let state_root_hash = request.state_root_hash;
+        if self.request_states.is_empty() {
+            // The rate of an earlier sync, possibly of a much smaller or remote global state,
+            // says little about this one.
+            self.tries_written = Throughput::default();
+        }
         match self.request_states.entry(state_root_hash) {
             Entry::Vacant(entry) => {
                 let mut root_hashes = HashSet::new();
@@ -460,6 +544,7 @@ impl GlobalStateSynchronizer {
     where
         REv: From<TrieAccumulatorRequest> + From<ContractRuntimeRequest> + Send,
     {
+        self.tries_written.record(1, Timestamp::now());
         // Remove the written trie from dependencies of the tries that are waiting.
         for trie_awaiting in self.tries_awaiting_children.values_mut() {
             trie_awaiting.trie_written(written_trie);
@@ -546,7 +631,11 @@ where
         event: Self::Event,
     ) -> Effects<Self::Event> {
         match event {
-            Event::Request(request) => self.handle_request(request, effect_builder),
+            Event::Request(request) => self.probe_local_state(request, effect_builder),
+            Event::LocalStateProbed {
+                request,
+                missing_tries,
+            } => self.handle_local_state_probed(request, missing_tries, effect_builder),
             Event::FetchedTrie {
                 trie_hash,
                 trie_accumulator_result,
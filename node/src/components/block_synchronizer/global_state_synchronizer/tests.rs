@@ -565,3 +565,46 @@ async fn stored_trie_finalizes_request() {
     let result = receiver.await.unwrap();
     assert!(result.is_ok());
 }
+
+#[tokio::test]
+async fn only_incomplete_local_state_is_fetched() {
+    let mut rng = TestRng::new();
+    let reactor = MockReactor::new();
+    let mut global_state_synchronizer = GlobalStateSynchronizer::new(rng.gen_range(2..10));
+
+    // A state root which is complete locally is reported as synced right away.
+    let (sender, receiver) = oneshot::channel();
+    let (request, _) =
+        random_sync_global_state_request(&mut rng, 2, Responder::without_shutdown(sender));
+    let state_root_hash = request.state_root_hash;
+    let mut effects = global_state_synchronizer.handle_local_state_probed(
+        request,
+        Ok(vec![]),
+        reactor.effect_builder(),
+    );
+    assert_eq!(effects.len(), 1);
+    assert_eq!(global_state_synchronizer.request_states.len(), 0);
+    assert_eq!(global_state_synchronizer.in_flight.len(), 0);
+    tokio::spawn(async move { effects.remove(0).await });
+    let response = receiver.await.unwrap().unwrap();
+    assert_eq!(*response.hash(), state_root_hash);
+
+    // An incomplete one is fetched from peers.
+    let (sender, _receiver) = oneshot::channel();
+    let (request, _) =
+        random_sync_global_state_request(&mut rng, 2, Responder::without_shutdown(sender));
+    let state_root_hash = request.state_root_hash;
+    let peers = request.peers.clone();
+    let mut effects = global_state_synchronizer.handle_local_state_probed(
+        request,
+        Ok(vec![state_root_hash]),
+        reactor.effect_builder(),
+    );
+    assert_eq!(effects.len(), 1);
+    assert_eq!(global_state_synchronizer.request_states.len(), 1);
+    assert_eq!(global_state_synchronizer.in_flight.len(), 1);
+    tokio::spawn(async move { effects.remove(0).await });
+    reactor
+        .expect_trie_accumulator_request(&state_root_hash, &peers)
+        .await;
+}
@@ -0,0 +1,278 @@
+//! Estimates of how long syncing will take.
+//!
+//! The rates at which block headers are fetched, blocks are synced and tries are written are each
+//! tracked as an exponentially weighted moving average of the rates seen over successive sampling
+//! periods, so that a single slow block or a burst of tries shifts an estimate only a little.
+//!
+//! A block is synced no faster than its header is fetched, so the slower of the two rates is taken
+//! as the rate blocks are synced at, and the header rate stands in until blocks have been timed.
+//! The work left is the gap between the height being synced and the height syncing is heading
+//! for, where the reactor knows it, and the trie frontier: the tries of the global state being
+//! synced which are known to be missing but aren't written yet.  The frontier grows as the
+//! children of fetched tries are discovered, so an estimate based on it alone is a lower bound.
+use std::fmt::{self, Display, Formatter};
+
+use datasize::DataSize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use casper_types::{TimeDiff, Timestamp};
+
+/// The shortest period over which a rate is sampled before it is folded into the average.
+const SAMPLE_PERIOD: TimeDiff = TimeDiff::from_seconds(10);
+/// The weight of the latest sample in the moving average.
+const SMOOTHING: f64 = 0.3;
+/// How often a summary of the estimates is logged while syncing.
+pub(super) const ESTIMATE_LOG_INTERVAL: TimeDiff = TimeDiff::from_seconds(60);
+
+/// The moving average of the rate at which some kind of work is done.
+#[derive(Clone, Copy, DataSize, Debug, Default)]
+pub(super) struct Throughput {
+    /// The amount of work done so far.
+    total: u64,
+    /// The amount of work done and the time at the start of the current sampling period.
+    sample_start: Option<(u64, Timestamp)>,
+    /// The moving average in units of work per second, once a period was sampled.
+    per_second: Option<f64>,
+}
+
+impl Throughput {
+    /// Records `count` units of work done at `now`.
+    pub(super) fn record(&mut self, count: u64, now: Timestamp) {
+        self.total += count;
+        // The first piece of work only marks the start of the first period.
+        let (start_total, start_time) = *self.sample_start.get_or_insert((self.total, now));
+        let elapsed = now.saturating_diff(start_time);
+        if elapsed >= SAMPLE_PERIOD {
+            let rate = rate_per_second(self.total - start_total, elapsed);
+            self.per_second = Some(self.blend(rate));
+            self.sample_start = Some((self.total, now));
+        }
+    }
+
+    /// Returns the average rate per minute at `now`.
+    ///
+    /// A sampling period which has run for longer than usual is blended in as it stands, so that
+    /// the rate falls while work stalls rather than staying at its last value.
+    fn per_minute(&self, now: Timestamp) -> Option<f64> {
+        let per_second = match self.sample_start {
+            Some((start_total, start_time))
+                if now.saturating_diff(start_time) >= SAMPLE_PERIOD.saturating_mul(2) =>
+            {
+                let elapsed = now.saturating_diff(start_time);
+                Some(self.blend(rate_per_second(self.total - start_total, elapsed)))
+            }
+            _ => self.per_second,
+        };
+        per_second.map(|per_second| per_second * 60.0)
+    }
+
+    fn blend(&self, rate: f64) -> f64 {
+        match self.per_second {
+            Some(average) => SMOOTHING * rate + (1.0 - SMOOTHING) * average,
+            None => rate,
+        }
+    }
+}
+
+fn rate_per_second(count: u64, elapsed: TimeDiff) -> f64 {
+    count as f64 * 1000.0 / elapsed.millis() as f64
+}
+
+/// Tracks the progress of one of the block synchronizer's builders.
+#[derive(Clone, Copy, DataSize, Debug, Default)]
+pub(super) struct SyncEstimator {
+    headers: Throughput,
+    blocks: Throughput,
+    /// The height syncing is heading for, if known.
+    target_height: Option<u64>,
+}
+
+impl SyncEstimator {
+    pub(super) fn record_header(&mut self, now: Timestamp) {
+        self.headers.record(1, now);
+    }
+
+    pub(super) fn record_block(&mut self, now: Timestamp) {
+        self.blocks.record(1, now);
+    }
+
+    pub(super) fn set_target_height(&mut self, target_height: Option<u64>) {
+        self.target_height = target_height;
+    }
+
+    /// Estimates the work left while syncing the block at `block_height`, along with the tries
+    /// being written and the size of their frontier, if the builder syncs global state.
+    pub(super) fn estimate(
+        &self,
+        block_height: Option<u64>,
+        tries: Option<(&Throughput, u64)>,
+        now: Timestamp,
+    ) -> SyncEstimate {
+        let headers_per_minute = self.headers.per_minute(now);
+        let blocks_per_minute = self.blocks.per_minute(now);
+        let block_rate = match (blocks_per_minute, headers_per_minute) {
+            (Some(blocks_per_minute), Some(headers_per_minute)) => {
+                Some(blocks_per_minute.min(headers_per_minute))
+            }
+            (blocks_per_minute, headers_per_minute) => blocks_per_minute.or(headers_per_minute),
+        };
+        let tries_per_minute = tries.and_then(|(tries, _)| tries.per_minute(now));
+        let trie_frontier = tries.map_or(0, |(_, trie_frontier)| trie_frontier);
+        // The block being synced is part of the work left, whichever way syncing heads.
+        let remaining_blocks = block_height
+            .zip(self.target_height)
+            .map(|(block_height, target_height)| block_height.abs_diff(target_height) + 1);
+
+        let minutes_for = |amount: u64, per_minute: Option<f64>| {
+            per_minute
+                .filter(|per_minute| *per_minute > 0.0)
+                .map(|per_minute| amount as f64 / per_minute)
+        };
+        let eta_minutes = remaining_blocks
+            .and_then(|remaining_blocks| minutes_for(remaining_blocks, block_rate))
+            .or_else(|| {
+                if trie_frontier == 0 {
+                    return None;
+                }
+                minutes_for(trie_frontier, tries_per_minute)
+            });
+        SyncEstimate {
+            headers_per_minute: headers_per_minute.map(round),
+            blocks_per_minute: blocks_per_minute.map(round),
+            tries_per_minute: tries_per_minute.map(round),
+            remaining_blocks,
+            trie_frontier,
+            eta: eta_minutes.map(|minutes| {
+                TimeDiff::from_seconds((minutes * 60.0).round().min(u32::MAX as f64) as u32)
+            }),
+        }
+    }
+}
+
+fn round(rate: f64) -> u64 {
+    rate.round() as u64
+}
+
+/// An estimate of the work left to sync and of how long it will take.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SyncEstimate {
+    /// The moving average of block headers fetched per minute, once measured.
+    pub(super) headers_per_minute: Option<u64>,
+    /// The moving average of blocks synced per minute, once measured.
+    pub(super) blocks_per_minute: Option<u64>,
+    /// The moving average of global state tries written per minute, once measured.
+    pub(super) tries_per_minute: Option<u64>,
+    /// The number of blocks left to sync, including the current one, if the target height is
+    /// known.
+    pub(super) remaining_blocks: Option<u64>,
+    /// The number of tries known to be missing from the global state being synced, which grows as
+    /// more of the global state is discovered.
+    pub(super) trie_frontier: u64,
+    /// The estimated time left until syncing completes, if it can be estimated yet.
+    pub(super) eta: Option<TimeDiff>,
+}
+
+impl Display for SyncEstimate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.remaining_blocks {
+            Some(remaining_blocks) => write!(f, "{} blocks left", remaining_blocks)?,
+            None => write!(f, "unknown number of blocks left")?,
+        }
+        if let Some(blocks_per_minute) = self.blocks_per_minute {
+            write!(f, " at {} blocks/min", blocks_per_minute)?;
+        }
+        if self.trie_frontier > 0 {
+            write!(f, ", {} tries in frontier", self.trie_frontier)?;
+            if let Some(tries_per_minute) = self.tries_per_minute {
+                write!(f, " at {} tries/min", tries_per_minute)?;
+            }
+        }
+        match self.eta {
+            Some(eta) => write!(f, ", eta {}", eta),
+            None => write!(f, ", eta unknown"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at_seconds(seconds: u64) -> Timestamp {
+        Timestamp::from(seconds * 1000)
+    }
+
+    #[test]
+    fn should_average_rates_over_sampling_periods() {
+        let mut throughput = Throughput::default();
+        assert_eq!(throughput.per_minute(at_seconds(0)), None);
+
+        // 2 units per second for the first period.
+        for second in 0..=10 {
+            throughput.record(2, at_seconds(second));
+        }
+        assert_eq!(throughput.per_minute(at_seconds(10)), Some(120.0));
+
+        // Nothing for a while: the rate decays instead of sticking.
+        let stalled = throughput.per_minute(at_seconds(40)).unwrap();
+        assert!(stalled < 120.0);
+        assert!(stalled > 0.0);
+    }
+
+    #[test]
+    fn should_estimate_time_left_from_blocks_then_tries() {
+        let mut estimator = SyncEstimator::default();
+        let mut tries = Throughput::default();
+        for second in 0..=10 {
+            tries.record(10, at_seconds(second));
+        }
+
+        // No block rate yet: the trie frontier gives a lower bound.
+        let estimate = estimator.estimate(Some(100), Some((&tries, 6000)), at_seconds(10));
+        assert_eq!(estimate.remaining_blocks, None);
+        assert_eq!(estimate.tries_per_minute, Some(600));
+        assert_eq!(estimate.eta, Some(TimeDiff::from_seconds(600)));
+
+        // Syncing back to genesis from height 99 at a block every 5 seconds.
+        estimator.set_target_height(Some(0));
+        estimator.record_block(at_seconds(0));
+        estimator.record_block(at_seconds(5));
+        estimator.record_block(at_seconds(10));
+        let estimate = estimator.estimate(Some(99), Some((&tries, 6000)), at_seconds(10));
+        assert_eq!(estimate.remaining_blocks, Some(100));
+        assert_eq!(estimate.blocks_per_minute, Some(12));
+        assert_eq!(estimate.eta, Some(TimeDiff::from_seconds(500)));
+
+        // Forward syncs head up to the target.
+        estimator.set_target_height(Some(120));
+        let estimate = estimator.estimate(Some(100), None, at_seconds(10));
+        assert_eq!(estimate.remaining_blocks, Some(21));
+        assert_eq!(estimate.trie_frontier, 0);
+        assert_eq!(estimate.tries_per_minute, None);
+    }
+
+    #[test]
+    fn should_estimate_time_left_from_headers() {
+        let mut estimator = SyncEstimator::default();
+        estimator.set_target_height(Some(0));
+
+        // Headers are timed before any block: a header every 2 seconds.
+        for second in (0..=10).step_by(2) {
+            estimator.record_header(at_seconds(second));
+        }
+        let estimate = estimator.estimate(Some(59), None, at_seconds(10));
+        assert_eq!(estimate.headers_per_minute, Some(30));
+        assert_eq!(estimate.blocks_per_minute, None);
+        assert_eq!(estimate.eta, Some(TimeDiff::from_seconds(120)));
+
+        // Blocks faster than their headers are held back by the headers.
+        for second in 0..=10 {
+            estimator.record_block(at_seconds(second));
+        }
+        let estimate = estimator.estimate(Some(59), None, at_seconds(10));
+        assert_eq!(estimate.blocks_per_minute, Some(60));
+        assert_eq!(estimate.eta, Some(TimeDiff::from_seconds(120)));
+    }
+}
@@ -259,8 +259,19 @@ where
                         ));
                         let block_timestamp = entry.key().timestamp();
                         let deploy_config = self.chainspec.deploy_config;
+                        let payment_lanes = self
+                            .chainspec
+                            .core_config
+                            .active_payment_lanes(self.chainspec.protocol_version());
+                        let computation_quota_per_era =
+                            self.chainspec.core_config.computation_quota_per_era;
                         entry.insert(BlockValidationState {
-                            appendable_block: AppendableBlock::new(deploy_config, block_timestamp),
+                            appendable_block: AppendableBlock::new(
+                                deploy_config,
+                                payment_lanes,
+                                computation_quota_per_era,
+                                block_timestamp,
+                            ),
                             missing_deploys: block_deploys,
                             responders: smallvec![responder],
                             sources: VecDeque::new(), /* This is empty b/c we create the first
@@ -300,7 +311,13 @@ where
                             }
                         };
                         if let Err(err) = add_result {
-                            info!(block = ?key, %dt_hash, ?deploy_info, ?err, "block invalid");
+                            info!(
+                                proposed_block = ?key,
+                                %dt_hash,
+                                ?deploy_info,
+                                ?err,
+                                "block invalid"
+                            );
                             invalid.push(key.clone());
                         }
                     }
@@ -341,7 +358,7 @@ where
                     }
                     match state.source() {
                         Some(peer) => {
-                            info!(%dt_hash, ?peer, "trying the next peer");
+                            info!(%dt_hash, peer_id = ?peer, "trying the next peer");
                             // There's still hope to download the deploy.
                             effects.extend(fetch_deploy(effect_builder, dt_hash, peer));
                             retried = true;
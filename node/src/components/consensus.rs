@@ -12,6 +12,7 @@ pub(crate) mod error;
 mod leader_sequence;
 mod metrics;
 mod protocols;
+mod standstill;
 #[cfg(test)]
 pub(crate) mod tests;
 mod traits;
@@ -59,7 +60,7 @@ use traits::Context;
 pub(crate) use cl_context::ClContext;
 pub(crate) use config::{ChainspecConsensusExt, Config};
 pub(crate) use consensus_protocol::{BlockContext, EraReport, ProposedBlock};
-pub(crate) use era_supervisor::{debug::EraDump, EraSupervisor};
+pub(crate) use era_supervisor::{debug::EraDump, remove_unit_files_after, EraSupervisor};
 #[cfg(test)]
 pub(crate) use highway_core::highway::Vertex as HighwayVertex;
 pub(crate) use leader_sequence::LeaderSequence;
@@ -208,6 +209,8 @@ pub(crate) enum Event {
     /// Dump state for debugging purposes.
     #[from]
     DumpState(DumpConsensusStateRequest),
+    /// Check whether consensus is at a standstill.
+    CheckStandstill,
 }
 
 impl Debug for ConsensusMessage {
@@ -320,6 +323,7 @@ impl Display for Event {
                 era_id, faulty_num
             ),
             Event::DumpState(req) => Display::fmt(req, f),
+            Event::CheckStandstill => write!(f, "check for standstill"),
         }
     }
 }
@@ -409,6 +413,7 @@ where
                 faulty_num,
                 delay,
             } => self.handle_deactivate_era(effect_builder, era_id, faulty_num, delay),
+            Event::CheckStandstill => self.handle_check_standstill(effect_builder),
             Event::ConsensusRequest(ConsensusRequest::Status(responder)) => self.status(responder),
             Event::ConsensusRequest(ConsensusRequest::ValidatorChanges(responder)) => {
                 let validator_changes = self.get_validator_changes();
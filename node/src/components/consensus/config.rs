@@ -33,6 +33,14 @@ pub(crate) struct Config {
     /// Zug-specific node configuration.
     #[serde(default)]
     pub(crate) zug: ZugConfig,
+    /// The number of past eras for which unit files are kept on disk. Files of older eras are
+    /// deleted whenever a new era begins. Defaults to the chainspec's unbonding delay.
+    #[serde(default)]
+    pub(crate) unit_file_retention_eras: Option<u64>,
+    /// The number of rounds without a finalized block after which consensus is considered to be at
+    /// a standstill. Standstill detection is disabled if not set.
+    #[serde(default)]
+    pub(crate) standstill_timeout_rounds: Option<u64>,
 }
 
 impl Default for Config {
@@ -42,6 +50,8 @@ impl Default for Config {
             max_execution_delay: DEFAULT_MAX_EXECUTION_DELAY,
             highway: HighwayConfig::default(),
             zug: ZugConfig::default(),
+            unit_file_retention_eras: None,
+            standstill_timeout_rounds: None,
         }
     }
 }
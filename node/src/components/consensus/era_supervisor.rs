@@ -12,6 +12,7 @@ use std::{
     cmp,
     collections::{BTreeMap, BTreeSet, HashMap},
     convert::TryInto,
+    ffi::OsStr,
     fmt::{self, Debug, Formatter},
     fs, io,
     path::{Path, PathBuf},
@@ -39,6 +40,7 @@ use crate::{
                 ProtocolOutcome,
             },
             metrics::Metrics,
+            standstill::StandstillDetector,
             validator_change::{ValidatorChange, ValidatorChanges},
             ActionId, ChainspecConsensusExt, Config, ConsensusMessage, ConsensusRequestMessage,
             Event, HighwayProtocol, NewBlockPayload, ReactorEventT, ResolveValidity, TimerId, Zug,
@@ -75,6 +77,12 @@ pub(super) const PAST_EVIDENCE_ERAS: u64 = 1;
 /// The more recent half of these is active: it contains units and can still accept further units.
 /// The older half is in evidence-only state, and only used to validate cited evidence.
 pub(super) const PAST_OPEN_ERAS: u64 = 2 * PAST_EVIDENCE_ERAS;
+/// The prefix of the names of unit files, which is followed by the era ID.
+const UNIT_FILE_PREFIX: &str = "unit_";
+/// The extension of the names of unit files.
+const UNIT_FILE_EXTENSION: &str = ".dat";
+/// The folder within the storage directory holding the unit files.
+const UNIT_FILES_FOLDER: &str = "unit_files";
 
 #[derive(DataSize)]
 pub struct EraSupervisor {
@@ -108,6 +116,10 @@ pub struct EraSupervisor {
     /// The path to the folder where unit files will be stored.
     unit_files_folder: PathBuf,
     last_progress: Timestamp,
+    /// Tracks the time since the last block was finalized or the current era began.
+    standstill: StandstillDetector,
+    /// Whether the periodic standstill check has been scheduled.
+    standstill_check_scheduled: bool,
 }
 
 impl Debug for EraSupervisor {
@@ -128,7 +140,7 @@ impl EraSupervisor {
         chainspec: Arc<Chainspec>,
         registry: &Registry,
     ) -> Result<Self, Error> {
-        let unit_files_folder = storage_dir.join("unit_files");
+        let unit_files_folder = storage_dir.join(UNIT_FILES_FOLDER);
         std::fs::create_dir_all(&unit_files_folder)?;
         info!(our_id = %public_signing_key, "EraSupervisor pubkey",);
         let metrics = Metrics::new(registry)?;
@@ -144,6 +156,8 @@ impl EraSupervisor {
             unit_files_folder,
             next_executed_height: 0,
             last_progress: Timestamp::now(),
+            standstill: StandstillDetector::new(Timestamp::now()),
+            standstill_check_scheduled: false,
         };
 
         Ok(era_supervisor)
@@ -284,6 +298,13 @@ impl EraSupervisor {
         (era_id.value()..=era_id.value().saturating_add(num_eras)).map(EraId::from)
     }
 
+    /// Returns whether the last executed block is too far behind the last finalized block.
+    fn is_execution_lagging(&self) -> bool {
+        self.next_block_height
+            .saturating_sub(self.next_executed_height)
+            > self.config.max_execution_delay
+    }
+
     /// Pauses or unpauses consensus: Whenever the last executed block is too far behind the last
     /// finalized block, we suspend consensus.
     fn update_consensus_pause<REv: ReactorEventT>(
@@ -292,10 +313,7 @@ impl EraSupervisor {
         rng: &mut NodeRng,
         era_id: EraId,
     ) -> Effects<Event> {
-        let paused = self
-            .next_block_height
-            .saturating_sub(self.next_executed_height)
-            > self.config.max_execution_delay;
+        let paused = self.is_execution_lagging();
         self.delegate_to_era(effect_builder, rng, era_id, |consensus, _| {
             consensus.set_paused(paused, Timestamp::now())
         })
@@ -311,7 +329,19 @@ impl EraSupervisor {
     ) -> Effects<Event> {
         match self.create_new_era(switch_blocks) {
             Ok((era_id, outcomes)) => {
-                self.handle_consensus_outcomes(effect_builder, rng, era_id, outcomes)
+                let mut effects =
+                    self.handle_consensus_outcomes(effect_builder, rng, era_id, outcomes);
+                if self.config.standstill_timeout_rounds.is_some()
+                    && !self.standstill_check_scheduled
+                {
+                    self.standstill_check_scheduled = true;
+                    effects.extend(
+                        effect_builder
+                            .set_timeout(self.round_length().into())
+                            .event(|_| Event::CheckStandstill),
+                    );
+                }
+                effects
             }
             Err(err) => fatal!(
                 effect_builder,
@@ -456,7 +486,7 @@ impl EraSupervisor {
                 start_time,
                 seed,
                 now,
-                self.unit_file(&instance_id),
+                self.unit_file(era_id, &instance_id),
             ),
         };
 
@@ -492,13 +522,14 @@ impl EraSupervisor {
                 .consensus_current_era
                 .set(era_id.value() as i64);
             self.next_block_height = self.next_block_height.max(start_height);
+            self.standstill.record_progress(now);
             outcomes.extend(self.era_mut(era_id).consensus.handle_is_current(now));
             if !self.era(era_id).validators().contains_key(&our_id) {
                 info!(era = era_id.value(), %our_id, "not voting; not a validator");
             } else {
                 info!(era = era_id.value(), %our_id, "start voting");
                 let secret = Keypair::new(self.secret_signing_key.clone(), our_id.clone());
-                let unit_hash_file = self.unit_file(&instance_id);
+                let unit_hash_file = self.unit_file(era_id, &instance_id);
                 outcomes.extend(self.era_mut(era_id).consensus.activate_validator(
                     our_id,
                     secret,
@@ -526,13 +557,11 @@ impl EraSupervisor {
         // information necessary to validate evidence that units in the two most recent eras may
         // refer to for cross-era fault tracking.
         if let Some(current_era) = self.current_era() {
-            let mut removed_instance_ids = vec![];
             let earliest_open_era = current_era.saturating_sub(PAST_OPEN_ERAS);
             let earliest_active_era = current_era.saturating_sub(PAST_EVIDENCE_ERAS);
             self.open_eras.retain(|era_id, era| {
                 if earliest_open_era > *era_id {
                     trace!(era = era_id.value(), "removing obsolete era");
-                    removed_instance_ids.push(*era.consensus.instance_id());
                     false
                 } else if earliest_active_era > *era_id {
                     trace!(era = era_id.value(), "setting old era to evidence only");
@@ -542,26 +571,72 @@ impl EraSupervisor {
                     true
                 }
             });
-            for instance_id in removed_instance_ids {
-                if let Err(err) = fs::remove_file(self.unit_file(&instance_id)) {
-                    match err.kind() {
-                        io::ErrorKind::NotFound => {}
-                        err => warn!(?err, "could not delete unit hash file"),
-                    }
-                }
-            }
+            self.prune_unit_files(current_era);
         }
 
         Ok((era_id, outcomes))
     }
 
+    /// Returns the length of the current era's next round, or the minimum block time if unknown.
+    fn round_length(&self) -> TimeDiff {
+        self.open_eras
+            .values()
+            .last()
+            .and_then(|era| era.consensus.next_round_length())
+            .unwrap_or(self.chainspec.core_config.minimum_block_time)
+    }
+
     /// Returns the path to the era's unit file.
-    fn unit_file(&self, instance_id: &Digest) -> PathBuf {
-        self.unit_files_folder.join(format!(
-            "unit_{:?}_{}.dat",
+    ///
+    /// Unit files written by earlier versions don't contain the era ID in their name. If such a
+    /// file exists for this era, it is renamed so that it is subject to pruning.
+    fn unit_file(&self, era_id: EraId, instance_id: &Digest) -> PathBuf {
+        let our_key_hex = self.public_signing_key.to_hex();
+        let unit_file = self.unit_files_folder.join(format!(
+            "{}{}_{:?}_{}{}",
+            UNIT_FILE_PREFIX,
+            era_id.value(),
             instance_id,
-            self.public_signing_key.to_hex()
-        ))
+            our_key_hex,
+            UNIT_FILE_EXTENSION
+        ));
+        let legacy_unit_file = self.unit_files_folder.join(format!(
+            "{}{:?}_{}{}",
+            UNIT_FILE_PREFIX, instance_id, our_key_hex, UNIT_FILE_EXTENSION
+        ));
+        if !unit_file.exists() && legacy_unit_file.exists() {
+            if let Err(error) = fs::rename(&legacy_unit_file, &unit_file) {
+                warn!(%error, ?legacy_unit_file, "could not rename legacy unit file");
+                return legacy_unit_file;
+            }
+        }
+        unit_file
+    }
+
+    /// Deletes the unit files of eras which ended more than the configured number of eras before
+    /// `current_era`. The files of eras which are still open are never deleted.
+    fn prune_unit_files(&mut self, current_era: EraId) {
+        let retention_eras = self
+            .config
+            .unit_file_retention_eras
+            .unwrap_or(self.chainspec.core_config.unbonding_delay)
+            .max(PAST_OPEN_ERAS);
+        let earliest_retained_era = current_era.saturating_sub(retention_eras);
+        match prune_unit_files(&self.unit_files_folder, earliest_retained_era) {
+            Ok((pruned, retained)) => {
+                if pruned > 0 {
+                    debug!(
+                        pruned,
+                        retained,
+                        earliest_retained_era = earliest_retained_era.value(),
+                        "pruned consensus unit files"
+                    );
+                }
+                self.metrics.unit_files_pruned.inc_by(pruned as u64);
+                self.metrics.unit_files.set(retained as i64);
+            }
+            Err(error) => warn!(%error, "could not prune consensus unit files"),
+        }
     }
 
     /// Applies `f` to the consensus protocol of the specified era.
@@ -671,6 +746,49 @@ impl EraSupervisor {
         }
     }
 
+    /// Checks whether no block was finalized for the configured number of rounds, and if so,
+    /// reports the standstill in the logs and metrics.
+    ///
+    /// Recovering from a standstill is left to the operators: restarting the era's consensus
+    /// instance is only safe if all validators agree on it, e.g. via an emergency upgrade.
+    pub(super) fn handle_check_standstill<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        let timeout_rounds = match self.config.standstill_timeout_rounds {
+            Some(timeout_rounds) => timeout_rounds,
+            None => return Effects::new(),
+        };
+        let round_length = self.round_length();
+        let mut effects = effect_builder
+            .set_timeout(round_length.into())
+            .event(|_| Event::CheckStandstill);
+        let era_id = match self.current_era() {
+            Some(era_id) => era_id,
+            None => return effects,
+        };
+        // If consensus is paused while execution catches up, the lack of finalized blocks is not
+        // the validators' fault.
+        let idle = match self
+            .standstill
+            .check(Timestamp::now(), round_length, timeout_rounds)
+        {
+            Some(idle) if !self.is_execution_lagging() => idle,
+            _ => {
+                self.metrics.standstill.set(0);
+                return effects;
+            }
+        };
+        self.metrics.standstill.set(1);
+        warn!(
+            era = era_id.value(),
+            next_block_height = self.next_block_height,
+            %idle,
+            "no block finalized for too long; consensus is at a standstill"
+        );
+        effects
+    }
+
     pub(super) fn handle_demand<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -895,7 +1013,7 @@ impl EraSupervisor {
         match consensus_result {
             ProtocolOutcome::Disconnect(sender) => {
                 warn!(
-                    %sender,
+                    peer_id = %sender,
                     "disconnecting from the sender of invalid data"
                 );
                 {
@@ -1035,6 +1153,7 @@ impl EraSupervisor {
                     "finalized block"
                 );
                 self.metrics.finalized_block(&finalized_block);
+                self.standstill.record_progress(Timestamp::now());
                 // Announce the finalized block.
                 let mut effects = effect_builder
                     .announce_finalized_block(finalized_block.clone())
@@ -1069,7 +1188,7 @@ impl EraSupervisor {
                 self.era_mut(era_id)
                     .add_block(proposed_block.clone(), missing_evidence.clone());
                 if let Some(deploy_hash) = proposed_block.contains_replay() {
-                    info!(%sender, %deploy_hash, "block contains a replayed deploy");
+                    info!(peer_id = %sender, %deploy_hash, "block contains a replayed deploy");
                     return self.resolve_validity(
                         effect_builder,
                         rng,
@@ -1137,10 +1256,16 @@ impl EraSupervisor {
                 .collect(),
             ProtocolOutcome::WeAreFaulty => Default::default(),
             ProtocolOutcome::DoppelgangerDetected => Default::default(),
-            ProtocolOutcome::FttExceeded => effect_builder
-                .set_timeout(Duration::from_millis(FTT_EXCEEDED_SHUTDOWN_DELAY_MILLIS))
-                .then(move |_| fatal!(effect_builder, "too many faulty validators"))
-                .ignore(),
+            ProtocolOutcome::FttExceeded => {
+                let mut effects = effect_builder.announce_ftt_exceeded(era_id).ignore();
+                effects.extend(
+                    effect_builder
+                        .set_timeout(Duration::from_millis(FTT_EXCEEDED_SHUTDOWN_DELAY_MILLIS))
+                        .then(move |_| fatal!(effect_builder, "too many faulty validators"))
+                        .ignore(),
+                );
+                effects
+            }
         }
     }
 
@@ -1280,6 +1405,86 @@ where
     })
 }
 
+/// Deletes the unit files in `folder` belonging to eras before `earliest_retained_era`.
+///
+/// Unit files whose name doesn't start with the era ID are left in place, since it's unknown which
+/// era they belong to, and other files are ignored. A unit file which can't be deleted is logged
+/// and left in place without stopping the rest from being pruned. Returns the number of files
+/// deleted and the number of unit files left.
+fn prune_unit_files(folder: &Path, earliest_retained_era: EraId) -> io::Result<(usize, usize)> {
+    let mut pruned = 0;
+    let mut retained = 0;
+    for entry in fs::read_dir(folder)? {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                warn!(%error, ?folder, "could not read consensus unit files folder entry");
+                continue;
+            }
+        };
+        if !is_unit_file(&entry) {
+            continue;
+        }
+        match unit_file_era(&entry.file_name()) {
+            Some(era_id) if era_id < earliest_retained_era => match fs::remove_file(entry.path()) {
+                Ok(()) => pruned += 1,
+                Err(error) => {
+                    let unit_file = entry.path();
+                    warn!(%error, ?unit_file, "could not delete consensus unit file");
+                    retained += 1;
+                }
+            },
+            _ => retained += 1,
+        }
+    }
+    Ok((pruned, retained))
+}
+
+/// Returns whether `entry` is a file named like a unit file.
+fn is_unit_file(entry: &fs::DirEntry) -> bool {
+    let is_file = entry
+        .file_type()
+        .map_or(false, |file_type| file_type.is_file());
+    is_file
+        && entry.file_name().to_str().map_or(false, |name| {
+            name.starts_with(UNIT_FILE_PREFIX) && name.ends_with(UNIT_FILE_EXTENSION)
+        })
+}
+
+/// Deletes the unit files kept in `storage_dir` belonging to eras after `last_retained_era`,
+/// returning the number of files deleted.
+///
+/// Used when the chain is reset to the end of `last_retained_era`: the later eras are run again
+/// from scratch, so the units we created in them before must be forgotten.
+pub(crate) fn remove_unit_files_after(
+    storage_dir: &Path,
+    last_retained_era: EraId,
+) -> io::Result<usize> {
+    let folder = storage_dir.join(UNIT_FILES_FOLDER);
+    if !folder.exists() {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    for entry in fs::read_dir(folder)? {
+        let entry = entry?;
+        if matches!(unit_file_era(&entry.file_name()), Some(era_id) if era_id > last_retained_era) {
+            fs::remove_file(entry.path())?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Returns the era a unit file belongs to, if its name starts with the era ID.
+fn unit_file_era(file_name: &OsStr) -> Option<EraId> {
+    file_name
+        .to_str()
+        .and_then(|name| name.strip_prefix(UNIT_FILE_PREFIX))
+        .and_then(|name| name.split('_').next())
+        .and_then(|era_id| era_id.parse().ok())
+        .map(EraId::new)
+}
+
 impl ProposedBlock<ClContext> {
     /// If this block contains a deploy that's also present in an ancestor, this returns the deploy
     /// hash, otherwise `None`.
@@ -1294,3 +1499,59 @@ impl ProposedBlock<ClContext> {
             .map(DeployOrTransferHash::into)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_prune_unit_files_of_old_eras() {
+        let folder = tempfile::tempdir().unwrap();
+        let file_names = [
+            "unit_3_aa_01.dat",
+            "unit_4_bb_01.dat",
+            "unit_5_cc_01.dat",
+            "unit_dd_01.dat",
+        ];
+        for file_name in &file_names {
+            fs::write(folder.path().join(file_name), []).unwrap();
+        }
+        // Neither is a unit file, so neither is counted nor deleted.
+        fs::write(folder.path().join("unit_1_ee_01.dat.tmp"), []).unwrap();
+        fs::create_dir(folder.path().join("unit_1_ff_01.dat")).unwrap();
+
+        assert_eq!(
+            prune_unit_files(folder.path(), EraId::new(4)).unwrap(),
+            (1, 3)
+        );
+        let mut remaining: Vec<_> = fs::read_dir(folder.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            vec![
+                "unit_1_ee_01.dat.tmp",
+                "unit_1_ff_01.dat",
+                "unit_4_bb_01.dat",
+                "unit_5_cc_01.dat",
+                "unit_dd_01.dat"
+            ]
+        );
+
+        let storage_dir = folder.path();
+        fs::create_dir(storage_dir.join(UNIT_FILES_FOLDER)).unwrap();
+        for file_name in &file_names {
+            fs::write(storage_dir.join(UNIT_FILES_FOLDER).join(file_name), []).unwrap();
+        }
+        assert_eq!(
+            remove_unit_files_after(storage_dir, EraId::new(3)).unwrap(),
+            2
+        );
+        assert_eq!(
+            remove_unit_files_after(storage_dir, EraId::new(3)).unwrap(),
+            0
+        );
+    }
+}
@@ -410,7 +410,7 @@ impl<C: Context + 'static> Synchronizer<C> {
                     .find(|(vv, _)| vv.inner().id() == transitive_dependency)
                 {
                     debug!(
-                        dependency = ?transitive_dependency, %sender,
+                        dependency = ?transitive_dependency, peer_id = %sender,
                         "adding sender as a source for proposal"
                     );
                     let dep_pv = PendingVertex::new(sender, vv.clone().into(), time_received);
@@ -432,7 +432,12 @@ impl<C: Context + 'static> Synchronizer<C> {
                 }
                 // Otherwise request the missing dependency from the sender.
                 let uuid = thread_rng().next_u64();
-                debug!(?uuid, dependency = ?transitive_dependency, %sender, "requesting dependency");
+                debug!(
+                    ?uuid,
+                    dependency = ?transitive_dependency,
+                    peer_id = %sender,
+                    "requesting dependency"
+                );
                 let msg = HighwayMessage::RequestDependency(uuid, transitive_dependency);
                 outcomes.push(ProtocolOutcome::CreatedTargetedMessage(msg.into(), sender));
                 continue;
@@ -1,4 +1,4 @@
-use prometheus::{Gauge, IntGauge, Registry};
+use prometheus::{Gauge, IntCounter, IntGauge, Registry};
 
 use casper_types::Timestamp;
 
@@ -17,6 +17,12 @@ pub(super) struct Metrics {
     time_of_last_finalized_block: IntGauge,
     /// The current era.
     pub(super) consensus_current_era: IntGauge,
+    /// The number of unit files kept on disk.
+    pub(super) unit_files: IntGauge,
+    /// The total number of unit files of past eras deleted.
+    pub(super) unit_files_pruned: IntCounter,
+    /// 1 if consensus is at a standstill, 0 otherwise.
+    pub(super) standstill: IntGauge,
     /// Registry component.
     registry: Registry,
 }
@@ -39,17 +45,35 @@ impl Metrics {
         )?;
         let consensus_current_era =
             IntGauge::new("consensus_current_era", "the current era in consensus")?;
+        let unit_files = IntGauge::new(
+            "consensus_unit_files",
+            "the number of consensus unit files kept on disk",
+        )?;
+        let unit_files_pruned = IntCounter::new(
+            "consensus_unit_files_pruned",
+            "the total number of consensus unit files of past eras deleted",
+        )?;
+        let standstill = IntGauge::new(
+            "consensus_standstill",
+            "1 if no block was finalized for the configured number of rounds, 0 otherwise",
+        )?;
         registry.register(Box::new(finalization_time.clone()))?;
         registry.register(Box::new(finalized_block_count.clone()))?;
         registry.register(Box::new(consensus_current_era.clone()))?;
         registry.register(Box::new(time_of_last_proposed_block.clone()))?;
         registry.register(Box::new(time_of_last_finalized_block.clone()))?;
+        registry.register(Box::new(unit_files.clone()))?;
+        registry.register(Box::new(unit_files_pruned.clone()))?;
+        registry.register(Box::new(standstill.clone()))?;
         Ok(Metrics {
             finalization_time,
             finalized_block_count,
             time_of_last_proposed_block,
             time_of_last_finalized_block,
             consensus_current_era,
+            unit_files,
+            unit_files_pruned,
+            standstill,
             registry: registry.clone(),
         })
     }
@@ -78,5 +102,8 @@ impl Drop for Metrics {
         unregister_metric!(self.registry, self.consensus_current_era);
         unregister_metric!(self.registry, self.time_of_last_finalized_block);
         unregister_metric!(self.registry, self.time_of_last_proposed_block);
+        unregister_metric!(self.registry, self.unit_files);
+        unregister_metric!(self.registry, self.unit_files_pruned);
+        unregister_metric!(self.registry, self.standstill);
     }
 }
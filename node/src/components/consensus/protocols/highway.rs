@@ -711,7 +711,7 @@ where
                     Err((_, err)) => {
                         // drop the vertices that might have depended on this one
                         let faulty_senders = self.synchronizer.invalid_vertices(vec![v_id]);
-                        warn!(?err, ?sender, ?faulty_senders, "invalid incoming message");
+                        warn!(?err, peer_id = ?sender, ?faulty_senders, "invalid incoming message");
                         return iter::once(ProtocolOutcome::Disconnect(sender))
                             .chain(faulty_senders.into_iter().map(ProtocolOutcome::Disconnect))
                             .collect();
@@ -753,7 +753,7 @@ where
                 trace!(?uuid, dependency=?dep, "received a request for a dependency");
                 match self.highway.get_dependency(&dep) {
                     GetDepOutcome::None => {
-                        info!(?dep, peer_id=?sender, "requested dependency doesn't exist");
+                        info!(?dep, peer_id = ?sender, "requested dependency doesn't exist");
                         vec![]
                     }
                     GetDepOutcome::Evidence(vid) => {
@@ -781,7 +781,7 @@ where
                         info!(
                             ?vid,
                             ?unit_seq_number,
-                            ?sender,
+                            peer_id = ?sender,
                             "requested dependency doesn't exist"
                         );
                         vec![]
@@ -850,7 +850,7 @@ where
         _msg: EraRequest<C>,
         _now: Timestamp,
     ) -> (ProtocolOutcomes<C>, Option<EraMessage<C>>) {
-        info!(?sender, "invalid incoming request");
+        info!(peer_id = ?sender, "invalid incoming request");
         (vec![ProtocolOutcome::Disconnect(sender)], None)
     }
 
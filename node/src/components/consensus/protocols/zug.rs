@@ -732,7 +732,7 @@ impl<C: Context + 'static> Zug<C> {
         if first_validator_idx.0 >= self.validators.len() as u32 {
             info!(
                 first_validator_idx = first_validator_idx.0,
-                %sender,
+                peer_id = %sender,
                 "invalid SyncRequest message"
             );
             return (vec![ProtocolOutcome::Disconnect(sender)], None);
@@ -821,7 +821,7 @@ impl<C: Context + 'static> Zug<C> {
                 Fault::Banned => {
                     info!(
                         validator_index = v_idx.0,
-                        %sender,
+                        peer_id = %sender,
                         "peer disagrees about banned validator; disconnecting"
                     );
                     return (vec![ProtocolOutcome::Disconnect(sender)], None);
@@ -945,7 +945,7 @@ impl<C: Context + 'static> Zug<C> {
         } else {
             warn!(
                 ?signed_msg,
-                %sender,
+                peer_id = %sender,
                 "invalid incoming message: validator index out of range",
             );
             return vec![ProtocolOutcome::Disconnect(sender)];
@@ -968,13 +968,13 @@ impl<C: Context + 'static> Zug<C> {
 
         if let Some(round) = self.round(signed_msg.round_id) {
             if round.contains(&signed_msg.content, validator_idx) {
-                debug!(?signed_msg, %sender, "received a duplicated message");
+                debug!(?signed_msg, peer_id = %sender, "received a duplicated message");
                 return vec![];
             }
         }
 
         if !signed_msg.verify_signature(&validator_id) {
-            warn!(?signed_msg, %sender, "invalid signature",);
+            warn!(?signed_msg, peer_id = %sender, "invalid signature",);
             return vec![ProtocolOutcome::Disconnect(sender)];
         }
 
@@ -1017,7 +1017,7 @@ impl<C: Context + 'static> Zug<C> {
         } else {
             warn!(
                 ?signed_msg,
-                %sender,
+                peer_id = %sender,
                 "invalid incoming evidence: validator index out of range",
             );
             return vec![ProtocolOutcome::Disconnect(sender)];
@@ -1026,7 +1026,7 @@ impl<C: Context + 'static> Zug<C> {
             warn!(
                 ?signed_msg,
                 ?content2,
-                %sender,
+                peer_id = %sender,
                 "invalid evidence: contents don't conflict",
             );
             return vec![ProtocolOutcome::Disconnect(sender)];
@@ -1039,7 +1039,7 @@ impl<C: Context + 'static> Zug<C> {
             warn!(
                 ?signed_msg,
                 ?content2,
-                %sender,
+                peer_id = %sender,
                 "invalid signature in evidence",
             );
             return vec![ProtocolOutcome::Disconnect(sender)];
@@ -1066,7 +1066,7 @@ impl<C: Context + 'static> Zug<C> {
                     parent = $prop.maybe_parent_round_id,
                     timestamp = %$prop.timestamp,
                     leader_idx = leader_idx.0,
-                    ?sender,
+                    peer_id = ?sender,
                     "{}",
                     $msg
                 );
@@ -1967,12 +1967,12 @@ where
     ) -> ProtocolOutcomes<C> {
         match msg.try_into_zug() {
             Err(_msg) => {
-                warn!(%sender, "received a message for the wrong consensus protocol");
+                warn!(peer_id = %sender, "received a message for the wrong consensus protocol");
                 vec![ProtocolOutcome::Disconnect(sender)]
             }
             Ok(zug_msg) if zug_msg.instance_id() != self.instance_id() => {
                 let instance_id = zug_msg.instance_id();
-                warn!(?instance_id, %sender, "wrong instance ID; disconnecting");
+                warn!(?instance_id, peer_id = %sender, "wrong instance ID; disconnecting");
                 vec![ProtocolOutcome::Disconnect(sender)]
             }
             Ok(Message::SyncResponse(sync_response)) => {
@@ -2001,14 +2001,14 @@ where
         match msg.try_into_zug() {
             Err(_msg) => {
                 warn!(
-                    %sender,
+                    peer_id = %sender,
                     "received a request for the wrong consensus protocol"
                 );
                 (vec![ProtocolOutcome::Disconnect(sender)], None)
             }
             Ok(sync_request) if sync_request.instance_id != *self.instance_id() => {
                 let instance_id = sync_request.instance_id;
-                warn!(?instance_id, %sender, "wrong instance ID; disconnecting");
+                warn!(?instance_id, peer_id = %sender, "wrong instance ID; disconnecting");
                 (vec![ProtocolOutcome::Disconnect(sender)], None)
             }
             Ok(sync_request) => self.handle_sync_request(sync_request, sender),
@@ -2129,7 +2129,13 @@ where
                 // the value "invalid" even if it just couldn't download the deploys, which could
                 // just be because the original sender went offline.
                 let validator_index = self.leader(round_id).0;
-                info!(%validator_index, %round_id, %sender, %proposal, "dropping invalid proposal");
+                info!(
+                    %validator_index,
+                    %round_id,
+                    peer_id = %sender,
+                    %proposal,
+                    "dropping invalid proposal"
+                );
             }
         }
         outcomes
@@ -0,0 +1,80 @@
+//! Detection of consensus standstills.
+//!
+//! If no block is finalized for a configurable number of rounds, the network is considered to be
+//! at a standstill. This is reported in the logs and metrics, so that operators can coordinate a
+//! recovery. Standstills are deliberately not acted upon automatically: restarting an era's
+//! consensus instance is only safe if every validator agrees to abandon the original instance,
+//! which requires an emergency upgrade.
+
+use datasize::DataSize;
+
+use casper_types::{TimeDiff, Timestamp};
+
+/// Keeps track of the time since consensus last made progress.
+#[derive(DataSize, Debug)]
+pub(super) struct StandstillDetector {
+    /// The time the last block was finalized or the current era began, whichever is later.
+    last_progress: Timestamp,
+}
+
+impl StandstillDetector {
+    /// Creates a detector that considers `now` the time of the last progress.
+    pub(super) fn new(now: Timestamp) -> Self {
+        StandstillDetector { last_progress: now }
+    }
+
+    /// Records that a block was finalized or a new era began.
+    pub(super) fn record_progress(&mut self, now: Timestamp) {
+        self.last_progress = self.last_progress.max(now);
+    }
+
+    /// Returns the time since the last progress if it exceeds `timeout_rounds` rounds of
+    /// `round_length`, i.e. if consensus is at a standstill.
+    pub(super) fn check(
+        &self,
+        now: Timestamp,
+        round_length: TimeDiff,
+        timeout_rounds: u64,
+    ) -> Option<TimeDiff> {
+        let idle = now.saturating_diff(self.last_progress);
+        (idle > round_length.saturating_mul(timeout_rounds)).then(|| idle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_standstill_after_timeout_rounds() {
+        let start = Timestamp::from(1_000);
+        let round_length = TimeDiff::from_millis(100);
+        let detector = StandstillDetector::new(start);
+
+        assert_eq!(detector.check(start, round_length, 3), None);
+        assert_eq!(
+            detector.check(start + round_length * 3, round_length, 3),
+            None
+        );
+        assert_eq!(
+            detector.check(start + TimeDiff::from_millis(301), round_length, 3),
+            Some(TimeDiff::from_millis(301))
+        );
+    }
+
+    #[test]
+    fn should_reset_on_progress() {
+        let start = Timestamp::from(1_000);
+        let round_length = TimeDiff::from_millis(100);
+        let mut detector = StandstillDetector::new(start);
+        let later = start + TimeDiff::from_millis(500);
+        assert!(detector.check(later, round_length, 3).is_some());
+
+        detector.record_progress(later);
+        assert_eq!(detector.check(later, round_length, 3), None);
+
+        // Progress recorded with an earlier timestamp doesn't move the last progress backwards.
+        detector.record_progress(start);
+        assert_eq!(detector.check(later, round_length, 3), None);
+    }
+}
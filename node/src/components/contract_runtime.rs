@@ -7,13 +7,13 @@ mod operations;
 mod types;
 
 use std::{
-    cmp::Ordering,
+    cmp::{self, Ordering},
     collections::{BTreeMap, HashMap},
     convert::TryInto,
     fmt::{self, Debug, Display, Formatter},
-    path::Path,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use datasize::DataSize;
@@ -23,21 +23,26 @@ use once_cell::sync::Lazy;
 use prometheus::Registry;
 use serde::Serialize;
 use thiserror::Error;
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, trace, warn};
 
 use casper_execution_engine::{
     core::engine_state::{
         self, genesis::GenesisError, ChainspecRegistry, EngineConfig, EngineState, GenesisSuccess,
+        HostFunctionDeprecations, PaymentLanes, QueryCache, SessionCodePolicy,
         SystemContractRegistry, UpgradeConfig, UpgradeSuccess,
     },
-    shared::{newtypes::CorrelationId, system_config::SystemConfig, wasm_config::WasmConfig},
+    shared::{
+        newtypes::CorrelationId, system_config::SystemConfig, wasm_config::WasmConfig,
+        wasm_prep_cache::PreprocessedModuleCache,
+    },
     storage::{
-        global_state::lmdb::LmdbGlobalState, transaction_source::lmdb::LmdbEnvironment,
+        global_state::{compaction, lmdb::LmdbGlobalState},
+        transaction_source::lmdb::LmdbEnvironment,
         trie_store::lmdb::LmdbTrieStore,
     },
 };
 use casper_hashing::Digest;
-use casper_types::{bytesrepr::Bytes, EraId, ProtocolVersion, Timestamp};
+use casper_types::{bytesrepr::Bytes, EraId, Gas, ProtocolVersion, Timestamp};
 
 use crate::{
     components::{fetcher::FetchResponse, Component, ComponentState},
@@ -66,6 +71,9 @@ pub(crate) use types::{
 
 const COMPONENT_NAME: &str = "contract_runtime";
 
+/// Name of the directory under the storage directory holding preprocessed Wasm modules.
+const PREPROCESSED_MODULE_CACHE_DIR: &str = "wasm_cache";
+
 /// An enum that represents all possible error conditions of a `contract_runtime` component.
 #[derive(Debug, Error, From)]
 pub(crate) enum ContractRuntimeError {
@@ -85,6 +93,13 @@ pub(crate) enum ContractRuntimeError {
 /// TODO: Fine tune this constant to the machine executing the node.
 const MAX_PARALLEL_INTENSIVE_TASKS: usize = 4;
 
+/// How long to wait before executing a block again after it failed on quarantined tries, giving
+/// the block synchronizer time to re-fetch them.
+const QUARANTINED_TRIES_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// How many times a block which failed on quarantined tries is executed again before giving up.
+const MAX_QUARANTINED_TRIES_RETRIES: u32 = 12;
+
 pub(crate) const APPROVALS_CHECKSUM_NAME: &str = "approvals_checksum";
 pub(crate) const EXECUTION_RESULTS_CHECKSUM_NAME: &str = "execution_results_checksum";
 
@@ -109,6 +124,20 @@ where
         .expect("task panicked")
 }
 
+/// Asynchronously runs a task reading global state.
+///
+/// Opening a read transaction may wait for a pooled one to be released, so the task is run on the
+/// blocking thread pool rather than on an async worker.
+async fn run_read_task<T, V>(task: T) -> V
+where
+    T: 'static + Send + FnOnce() -> V,
+    V: 'static + Send,
+{
+    tokio::task::spawn_blocking(task)
+        .await
+        .expect("task panicked")
+}
+
 #[derive(DataSize, Debug, Clone, Serialize)]
 /// Wrapper for speculative execution prestate.
 pub struct SpeculativeExecutionState {
@@ -202,6 +231,10 @@ pub(crate) struct ContractRuntime {
     exec_queue: ExecQueue,
     /// Cached instance of a [`SystemContractRegistry`].
     system_contract_registry: Option<SystemContractRegistry>,
+    /// The maximum gas a read-only view call may use.
+    max_view_call_gas: Gas,
+    /// The directory holding global state.
+    storage_dir: PathBuf,
 }
 
 impl Debug for ContractRuntime {
@@ -255,6 +288,24 @@ impl ContractRuntime {
             .len()
     }
 
+    /// Updates the count of global state read transactions held open for too long, logging
+    /// those not reported before.
+    pub(crate) fn update_stale_read_txns_metric(&self) {
+        let stale_count = self
+            .engine_state
+            .get_state()
+            .environment()
+            .report_stale_read_txns();
+        self.metrics.stale_read_transactions.set(stale_count as i64);
+    }
+
+    /// Adds the operations performed on the trie store since the last update to the trie store
+    /// metrics.
+    pub(crate) fn update_trie_store_metrics(&self) {
+        let counts = self.engine_state.take_trie_store_counts();
+        self.metrics.observe_trie_store_counts(&counts);
+    }
+
     /// Handles an incoming request to get a trie.
     fn handle_trie_request<REv>(
         &self,
@@ -336,10 +387,14 @@ impl ContractRuntime {
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
                 async move {
-                    let correlation_id = CorrelationId::new();
-                    let start = Instant::now();
-                    let result = engine_state.run_query(correlation_id, query_request);
-                    metrics.run_query.observe(start.elapsed().as_secs_f64());
+                    let result = run_read_task(move || {
+                        let correlation_id = CorrelationId::new();
+                        let start = Instant::now();
+                        let result = engine_state.run_query(correlation_id, query_request);
+                        metrics.run_query.observe(start.elapsed().as_secs_f64());
+                        result
+                    })
+                    .await;
                     trace!(?result, "query result");
                     responder.respond(result).await
                 }
@@ -353,14 +408,18 @@ impl ContractRuntime {
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
                 async move {
-                    let correlation_id = CorrelationId::new();
-                    let start = Instant::now();
-                    let result = engine_state.get_purse_balance(
-                        correlation_id,
-                        balance_request.state_hash(),
-                        balance_request.purse_uref(),
-                    );
-                    metrics.get_balance.observe(start.elapsed().as_secs_f64());
+                    let result = run_read_task(move || {
+                        let correlation_id = CorrelationId::new();
+                        let start = Instant::now();
+                        let result = engine_state.get_purse_balance(
+                            correlation_id,
+                            balance_request.state_hash(),
+                            balance_request.purse_uref(),
+                        );
+                        metrics.get_balance.observe(start.elapsed().as_secs_f64());
+                        result
+                    })
+                    .await;
                     trace!(?result, "balance result");
                     responder.respond(result).await
                 }
@@ -374,16 +433,20 @@ impl ContractRuntime {
                 // Increment the counter to track the amount of times GetEraValidators was
                 // requested.
                 async move {
-                    let correlation_id = CorrelationId::new();
-                    let start = Instant::now();
-                    let era_validators = engine_state.get_era_validators(
-                        correlation_id,
-                        system_contract_registry,
-                        request.into(),
-                    );
-                    metrics
-                        .get_era_validators
-                        .observe(start.elapsed().as_secs_f64());
+                    let era_validators = run_read_task(move || {
+                        let correlation_id = CorrelationId::new();
+                        let start = Instant::now();
+                        let era_validators = engine_state.get_era_validators(
+                            correlation_id,
+                            system_contract_registry,
+                            request.into(),
+                        );
+                        metrics
+                            .get_era_validators
+                            .observe(start.elapsed().as_secs_f64());
+                        era_validators
+                    })
+                    .await;
                     trace!(?era_validators, "get era validators response");
                     responder.respond(era_validators).await
                 }
@@ -397,7 +460,10 @@ impl ContractRuntime {
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
                 async move {
-                    let result = Self::do_get_trie(&engine_state, &metrics, trie_or_chunk_id);
+                    let result = run_read_task(move || {
+                        Self::do_get_trie(&engine_state, &metrics, trie_or_chunk_id)
+                    })
+                    .await;
                     trace!(?result, "get_trie response");
                     responder.respond(result).await
                 }
@@ -411,7 +477,10 @@ impl ContractRuntime {
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
                 async move {
-                    let result = Self::get_trie_full(&engine_state, &metrics, trie_key);
+                    let result = run_read_task(move || {
+                        Self::get_trie_full(&engine_state, &metrics, trie_key)
+                    })
+                    .await;
                     trace!(?result, "get_trie_full response");
                     responder.respond(result).await
                 }
@@ -445,6 +514,158 @@ impl ContractRuntime {
                 }
                 .ignore()
             }
+            ContractRuntimeRequest::GetMissingStateRootTries {
+                state_root_hash,
+                responder,
+            } => {
+                trace!(%state_root_hash, "get_missing_state_root_tries request");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result = run_read_task(move || {
+                        engine_state.missing_state_root_tries(CorrelationId::new(), state_root_hash)
+                    })
+                    .await;
+                    trace!(?result, "get_missing_state_root_tries response");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::GetMissingTrieDescendants {
+                state_root_hash,
+                limit,
+                responder,
+            } => {
+                trace!(%state_root_hash, limit, "get_missing_trie_descendants request");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result = run_read_task(move || {
+                        engine_state.missing_descendants(
+                            CorrelationId::new(),
+                            state_root_hash,
+                            limit,
+                        )
+                    })
+                    .await;
+                    trace!(?result, "get_missing_trie_descendants response");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::GetQuarantinedTries { responder } => {
+                trace!("get_quarantined_tries request");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    // Newly quarantined tries are written to LMDB, which may wait on a commit.
+                    let result = run_intensive_task(move || engine_state.quarantined_tries()).await;
+                    trace!(?result, "get_quarantined_tries response");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::VerifyTrieIntegrity {
+                state_root_hash,
+                limit,
+                responder,
+            } => {
+                debug!(%state_root_hash, limit, "verify_trie_integrity request");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = run_intensive_task(move || {
+                        engine_state.verify_trie_integrity(correlation_id, state_root_hash, limit)
+                    })
+                    .await;
+                    debug!(?result, "verify_trie_integrity response");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::GetTrieStoreStats {
+                state_root_hash,
+                responder,
+            } => {
+                debug!(?state_root_hash, "get_trie_store_stats request");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result =
+                        run_intensive_task(move || engine_state.trie_store_stats(state_root_hash))
+                            .await;
+                    debug!(?result, "get_trie_store_stats response");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::GetKeySpaceUsage {
+                state_root_hash,
+                limit,
+                responder,
+            } => {
+                let state_root_hash = state_root_hash.unwrap_or_else(|| {
+                    self.execution_pre_state.lock().unwrap().pre_state_root_hash
+                });
+                debug!(%state_root_hash, limit, "get_key_space_usage request");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = run_intensive_task(move || {
+                        engine_state.key_space_usage(correlation_id, state_root_hash, limit)
+                    })
+                    .await;
+                    debug!(?result, "get_key_space_usage response");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::BackupGlobalState { path, responder } => {
+                // Back up the state root blocks are currently executed on top of.
+                let state_root_hash = self.execution_pre_state.lock().unwrap().pre_state_root_hash;
+                info!(%state_root_hash, path = %path.display(), "backing up global state");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result = run_intensive_task(move || {
+                        engine_state.backup_global_state(state_root_hash, &path)
+                    })
+                    .await;
+                    match &result {
+                        Ok(manifest) => info!(
+                            state_roots = manifest.state_roots.len(),
+                            "backed up global state"
+                        ),
+                        Err(error) => warn!(%error, "failed to back up global state"),
+                    }
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::CheckpointGlobalState { dir, responder } => {
+                let state_root_hash = self.execution_pre_state.lock().unwrap().pre_state_root_hash;
+                info!(%state_root_hash, dir = %dir.display(), "checkpointing global state");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result = run_intensive_task(move || {
+                        engine_state.create_checkpoint(state_root_hash, &dir)
+                    })
+                    .await;
+                    match &result {
+                        Ok(checkpoint) => info!(
+                            size_bytes = checkpoint.size_bytes,
+                            duration = ?checkpoint.duration,
+                            "checkpointed global state"
+                        ),
+                        Err(error) => warn!(%error, "failed to checkpoint global state"),
+                    }
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::RequestGlobalStateCompaction { responder } => {
+                let result = compaction::request_compaction(&self.storage_dir);
+                match &result {
+                    Ok(()) => info!("global state will be compacted on the next restart"),
+                    Err(error) => warn!(%error, "failed to request global state compaction"),
+                }
+                responder.respond(result).ignore()
+            }
             ContractRuntimeRequest::EnqueueBlockForExecution {
                 finalized_block,
                 deploys,
@@ -515,29 +736,129 @@ impl ContractRuntime {
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
                 async move {
-                    let correlation_id = CorrelationId::new();
-                    let start = Instant::now();
-                    let result = engine_state.get_bids(correlation_id, get_bids_request);
-                    metrics.get_bids.observe(start.elapsed().as_secs_f64());
+                    let result = run_read_task(move || {
+                        let correlation_id = CorrelationId::new();
+                        let start = Instant::now();
+                        let result = engine_state.get_bids(correlation_id, get_bids_request);
+                        metrics.get_bids.observe(start.elapsed().as_secs_f64());
+                        result
+                    })
+                    .await;
                     trace!(?result, "get bids result");
                     responder.respond(result).await
                 }
                 .ignore()
             }
+            ContractRuntimeRequest::GetUnbonds {
+                get_unbonds_request,
+                responder,
+            } => {
+                trace!(?get_unbonds_request, "get unbonds request");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result = run_read_task(move || {
+                        engine_state.get_unbonds(CorrelationId::new(), get_unbonds_request)
+                    })
+                    .await;
+                    trace!(?result, "get unbonds result");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
             ContractRuntimeRequest::GetExecutionResultsChecksum {
                 state_root_hash,
                 responder,
             } => {
-                let correlation_id = CorrelationId::new();
-                let result = self
-                    .engine_state
-                    .get_checksum_registry(correlation_id, state_root_hash)
-                    .map(|maybe_registry| {
-                        maybe_registry.and_then(|registry| {
-                            registry.get(EXECUTION_RESULTS_CHECKSUM_NAME).copied()
-                        })
-                    });
-                responder.respond(result).ignore()
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result = run_read_task(move || {
+                        engine_state
+                            .get_checksum_registry(CorrelationId::new(), state_root_hash)
+                            .map(|maybe_registry| {
+                                maybe_registry.and_then(|registry| {
+                                    registry.get(EXECUTION_RESULTS_CHECKSUM_NAME).copied()
+                                })
+                            })
+                    })
+                    .await;
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::AnchorStateRoot {
+                state_root_hash,
+                block_height,
+                era_id,
+                responder,
+            } => {
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result =
+                        engine_state.anchor_state_root(state_root_hash, block_height, era_id);
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::GetRemainingComputationQuota {
+                state_root_hash,
+                account_hash,
+                responder,
+            } => {
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let result = run_read_task(move || {
+                        engine_state.get_remaining_computation_quota(
+                            CorrelationId::new(),
+                            state_root_hash,
+                            account_hash,
+                        )
+                    })
+                    .await;
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::GetExecutionResultProof {
+                state_root_hash,
+                execution_results,
+                index,
+                responder,
+            } => {
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    // Hashing the execution results of a large block can take a while.
+                    let result = run_intensive_task(move || {
+                        engine_state.get_execution_result_proof(
+                            correlation_id,
+                            state_root_hash,
+                            &execution_results,
+                            index,
+                        )
+                    })
+                    .await;
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            ContractRuntimeRequest::CallView {
+                view_request,
+                responder,
+            } => {
+                trace!(?view_request, "call view request");
+                let gas_limit = cmp::min(view_request.gas_limit(), self.max_view_call_gas);
+                let view_request = (*view_request).with_gas_limit(gas_limit);
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = run_intensive_task(move || {
+                        engine_state.call_view(correlation_id, view_request)
+                    })
+                    .await;
+                    trace!(?result, "call view result");
+                    responder.respond(result).await
+                }
+                .ignore()
             }
             ContractRuntimeRequest::SpeculativeDeployExecution {
                 execution_prestate,
@@ -571,6 +892,21 @@ impl ContractRuntime {
         minimum_delegation_amount: u64,
         strict_argument_checking: bool,
         vesting_schedule_period_millis: u64,
+        account_sequence_enforced: bool,
+        execution_results_merkle_tree_version: ProtocolVersion,
+        priority_fees_version: ProtocolVersion,
+        session_code_policy: SessionCodePolicy,
+        payment_lanes: PaymentLanes,
+        payment_lanes_version: ProtocolVersion,
+        computation_quota_per_era: u64,
+        protect_system_keys: bool,
+        storage_rent_per_byte: u64,
+        storage_rent_grace_eras: u64,
+        direct_redelegation_version: ProtocolVersion,
+        redelegation_cooldown_eras: u64,
+        deprecated_host_functions: HostFunctionDeprecations,
+        contract_package_pause_version: ProtocolVersion,
+        strict_bid_activation_version: ProtocolVersion,
         registry: &Registry,
     ) -> Result<Self, ConfigError> {
         // TODO: This is bogus, get rid of this
@@ -581,20 +917,71 @@ impl ContractRuntime {
             parent_seed: Default::default(),
         }));
 
-        let environment = Arc::new(LmdbEnvironment::new(
+        let lmdb_tuning = contract_runtime_config.lmdb_tuning()?;
+        match compaction::compact_if_requested(
             storage_dir,
             contract_runtime_config.max_global_state_size(),
             contract_runtime_config.max_readers(),
-            contract_runtime_config.manual_sync_enabled(),
-        )?);
+        ) {
+            Ok(Some(compaction)) => info!(
+                size_before = compaction.size_before,
+                size_after = compaction.size_after,
+                duration = ?compaction.duration,
+                "compacted global state"
+            ),
+            Ok(None) => {}
+            Err(error) => {
+                // The original database is left in place, so the node starts uncompacted rather
+                // than failing on every restart.
+                warn!(%error, "failed to compact global state, withdrawing the request");
+                if let Err(error) = compaction::clear_compaction_request(storage_dir) {
+                    warn!(%error, "failed to withdraw global state compaction request");
+                }
+            }
+        }
 
-        let trie_store = Arc::new(LmdbTrieStore::new(
-            &environment,
-            None,
-            DatabaseFlags::empty(),
-        )?);
+        let environment = Arc::new(
+            LmdbEnvironment::new_tuned(
+                storage_dir,
+                contract_runtime_config.max_global_state_size(),
+                contract_runtime_config.max_readers(),
+                lmdb_tuning,
+            )?
+            .with_max_read_txn_duration(
+                contract_runtime_config
+                    .max_read_transaction_duration()
+                    .into(),
+            ),
+        );
 
-        let global_state = LmdbGlobalState::empty(environment, trie_store)?;
+        let mut trie_store = LmdbTrieStore::new(&environment, None, DatabaseFlags::empty())?;
+        if let Some(level) = contract_runtime_config.trie_compression_level() {
+            trie_store = trie_store.with_compression(&environment, level)?;
+        }
+        trie_store = if contract_runtime_config.shard_tries() {
+            trie_store.with_sharding(&environment)?
+        } else {
+            trie_store.without_sharding(&environment)?
+        };
+        let trie_store = Arc::new(trie_store);
+
+        let mut global_state = LmdbGlobalState::empty(environment, trie_store)?
+            .with_scratch_cache_size(contract_runtime_config.max_scratch_cache_size())
+            .with_trie_prefetch(contract_runtime_config.prefetch_tries())
+            .with_state_root_anchors()?;
+        // Spilled tries are only of use to the process which spilled them.
+        let stale_spills = LmdbGlobalState::remove_stale_scratch_spills(storage_dir)?;
+        if stale_spills > 0 {
+            info!(
+                stale_spills,
+                "removed scratch spill databases left by an unclean shutdown"
+            );
+        }
+        let max_scratch_dirty_size = contract_runtime_config.max_scratch_dirty_size();
+        if max_scratch_dirty_size > 0 {
+            global_state =
+                global_state.with_scratch_spill(storage_dir.to_path_buf(), max_scratch_dirty_size);
+        }
         let engine_config = EngineConfig::new(
             contract_runtime_config.max_query_depth(),
             max_associated_keys,
@@ -604,9 +991,37 @@ impl ContractRuntime {
             vesting_schedule_period_millis,
             wasm_config,
             system_config,
-        );
-
-        let engine_state = Arc::new(EngineState::new(global_state, engine_config));
+        )
+        .with_account_sequence_enforced(account_sequence_enforced)
+        .with_execution_results_merkle_tree_version(execution_results_merkle_tree_version)
+        .with_priority_fees_version(priority_fees_version)
+        .with_payment_lanes_version(payment_lanes_version)
+        .with_computation_quota_per_era(computation_quota_per_era)
+        .with_protect_system_keys(protect_system_keys)
+        .with_storage_rent(storage_rent_per_byte, storage_rent_grace_eras)
+        .with_direct_redelegation(direct_redelegation_version, redelegation_cooldown_eras)
+        .with_contract_package_pause_version(contract_package_pause_version)
+        .with_strict_bid_activation_version(strict_bid_activation_version);
+
+        let mut engine_state = EngineState::new(global_state, engine_config)
+            .with_session_code_policy(session_code_policy)
+            .with_payment_lanes(payment_lanes)
+            .with_host_function_deprecations(deprecated_host_functions);
+        let max_preprocessed_module_cache_size =
+            contract_runtime_config.max_preprocessed_module_cache_size();
+        if max_preprocessed_module_cache_size > 0 {
+            let cache = PreprocessedModuleCache::open(
+                storage_dir.join(PREPROCESSED_MODULE_CACHE_DIR),
+                max_preprocessed_module_cache_size,
+            )
+            .map_err(ConfigError::PreprocessedModuleCache)?;
+            engine_state = engine_state.with_preprocessed_module_cache(cache);
+        }
+        let max_query_cache_size = contract_runtime_config.max_query_cache_size();
+        if max_query_cache_size > 0 {
+            engine_state = engine_state.with_query_cache(QueryCache::new(max_query_cache_size));
+        }
+        let engine_state = Arc::new(engine_state);
 
         let metrics = Arc::new(Metrics::new(registry)?);
 
@@ -618,10 +1033,14 @@ impl ContractRuntime {
             protocol_version,
             exec_queue: Arc::new(Mutex::new(BTreeMap::new())),
             system_contract_registry: None,
+            max_view_call_gas: Gas::from(contract_runtime_config.max_view_call_gas()),
+            storage_dir: storage_dir.to_path_buf(),
         })
     }
 
     /// Commits a genesis request.
+    ///
+    /// The genesis state root is anchored to the genesis block, which is executed on top of it.
     pub(crate) fn commit_genesis(
         &self,
         chainspec: &Chainspec,
@@ -652,19 +1071,36 @@ impl ContractRuntime {
             &ee_config,
             chainspec_registry,
         );
+        if let Ok(success) = &result {
+            self.engine_state
+                .anchor_state_root(success.post_state_hash, 0, EraId::default())?;
+        }
         self.engine_state.flush_environment()?;
         result
     }
 
+    /// Commits an upgrade.
+    ///
+    /// The upgraded state root is anchored to the block at `next_block_height` in `next_era_id`,
+    /// which is executed on top of it.
     pub(crate) fn commit_upgrade(
         &self,
         upgrade_config: UpgradeConfig,
+        next_block_height: u64,
+        next_era_id: EraId,
     ) -> Result<UpgradeSuccess, engine_state::Error> {
         debug!(?upgrade_config, "upgrade");
         let start = Instant::now();
         let result = self
             .engine_state
             .commit_upgrade(CorrelationId::new(), upgrade_config);
+        if let Ok(success) = &result {
+            self.engine_state.anchor_state_root(
+                success.post_state_hash,
+                next_block_height,
+                next_era_id,
+            )?;
+        }
         self.engine_state.flush_environment()?;
         self.metrics
             .commit_upgrade
@@ -736,28 +1172,67 @@ impl ContractRuntime {
     {
         debug!("ContractRuntime: execute_finalized_block_or_requeue");
         let current_execution_pre_state = execution_pre_state.lock().unwrap().clone();
-        let contract_runtime_metrics = metrics.clone();
+        let parent_hash = current_execution_pre_state.parent_hash;
+        let mut retries = 0;
         let BlockAndExecutionResults {
             block,
             approvals_hashes,
             execution_results,
             maybe_step_effect_and_upcoming_era_validators,
-        } = match run_intensive_task(move || {
-            debug!("ContractRuntime: execute_finalized_block");
-            execute_finalized_block(
-                engine_state.as_ref(),
-                Some(contract_runtime_metrics),
-                protocol_version,
-                current_execution_pre_state,
-                finalized_block,
-                deploys,
-            )
-        })
-        .await
-        {
-            Ok(block_and_execution_results) => block_and_execution_results,
-            Err(error) => return fatal!(effect_builder, "{}", error).await,
+            write_stats,
+            written_keys,
+        } = loop {
+            let task_engine_state = Arc::clone(&engine_state);
+            let contract_runtime_metrics = metrics.clone();
+            let current_execution_pre_state = current_execution_pre_state.clone();
+            let finalized_block = finalized_block.clone();
+            let deploys = deploys.clone();
+            let error = match run_intensive_task(move || {
+                debug!("ContractRuntime: execute_finalized_block");
+                execute_finalized_block(
+                    task_engine_state.as_ref(),
+                    Some(contract_runtime_metrics),
+                    protocol_version,
+                    current_execution_pre_state,
+                    finalized_block,
+                    deploys,
+                )
+            })
+            .await
+            {
+                Ok(block_and_execution_results) => break block_and_execution_results,
+                Err(error) => error,
+            };
+
+            // Tries which failed to deserialize are quarantined as they are read.  Rather than
+            // giving up, have them re-fetched from peers and execute the block again, a bounded
+            // number of times.
+            if !error.is_corrupted_trie() || retries >= MAX_QUARANTINED_TRIES_RETRIES {
+                return fatal!(effect_builder, "{}", error).await;
+            }
+            retries += 1;
+            let quarantine_engine_state = Arc::clone(&engine_state);
+            let trie_hashes =
+                match run_intensive_task(move || quarantine_engine_state.quarantined_tries()).await
+                {
+                    Ok(trie_hashes) if !trie_hashes.is_empty() => trie_hashes,
+                    _ => return fatal!(effect_builder, "{}", error).await,
+                };
+            error!(
+                %error,
+                block_height = finalized_block.height(),
+                quarantined_tries = trie_hashes.len(),
+                retries,
+                "failed to execute block on corrupted global state; retrying once re-fetched"
+            );
+            effect_builder
+                .announce_tries_quarantined(parent_hash, trie_hashes)
+                .await;
+            effect_builder
+                .set_timeout(QUARANTINED_TRIES_RETRY_DELAY)
+                .await;
         };
+        let announcement_start = Instant::now();
 
         let new_execution_pre_state = ExecutionPreState::from_block_header(block.header());
         debug!(
@@ -771,12 +1246,16 @@ impl ContractRuntime {
 
         if let Some(StepEffectAndUpcomingEraValidators {
             step_execution_journal,
+            reward_report,
             mut upcoming_era_validators,
         }) = maybe_step_effect_and_upcoming_era_validators
         {
             effect_builder
                 .announce_commit_step_success(current_era_id, step_execution_journal)
                 .await;
+            effect_builder
+                .announce_era_rewards_distributed(reward_report)
+                .await;
 
             if current_era_id.is_genesis() {
                 match upcoming_era_validators
@@ -805,28 +1284,26 @@ impl ContractRuntime {
             "executed block"
         );
 
+        metrics.observe_block_write_stats(&write_stats);
+        effect_builder.announce_block_write_stats(write_stats).await;
+
         let execution_results_map: HashMap<_, _> = execution_results
             .iter()
             .cloned()
             .map(|(deploy_hash, _, execution_result)| (deploy_hash, execution_result))
             .collect();
 
-        if meta_block_state.register_as_stored().was_updated() {
-            effect_builder
-                .put_executed_block_to_storage(
-                    Arc::clone(&block),
-                    approvals_hashes,
-                    execution_results_map,
-                )
-                .await;
-        } else {
-            effect_builder
-                .put_approvals_hashes_to_storage(approvals_hashes)
-                .await;
-            effect_builder
-                .put_execution_results_to_storage(*block.hash(), execution_results_map)
-                .await;
-        }
+        // The block is written even if it was already stored, as it shares a single transaction
+        // with everything else written for it.
+        let _ = meta_block_state.register_as_stored();
+        effect_builder
+            .put_executed_block_to_storage(
+                Arc::clone(&block),
+                approvals_hashes,
+                execution_results_map,
+                written_keys,
+            )
+            .await;
         if meta_block_state
             .register_as_executed()
             .was_already_registered()
@@ -841,6 +1318,9 @@ impl ContractRuntime {
 
         let meta_block = MetaBlock::new(block, execution_results, meta_block_state);
         effect_builder.announce_meta_block(meta_block).await;
+        metrics
+            .block_commit_announcement
+            .observe(announcement_start.elapsed().as_secs_f64());
 
         // If the child is already finalized, start execution.
         let next_block = {
@@ -992,6 +1472,21 @@ mod tests {
             10,
             true,
             1,
+            false,
+            ProtocolVersion::default(),
+            ProtocolVersion::default(),
+            SessionCodePolicy::Unrestricted,
+            PaymentLanes::default(),
+            ProtocolVersion::default(),
+            0,
+            false,
+            0,
+            0,
+            ProtocolVersion::default(),
+            0,
+            HostFunctionDeprecations::default(),
+            ProtocolVersion::default(),
+            ProtocolVersion::default(),
             &Registry::default(),
         )
         .unwrap();
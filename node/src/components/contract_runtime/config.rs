@@ -1,12 +1,35 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
-use casper_execution_engine::shared::utils;
+use casper_execution_engine::{
+    shared::utils,
+    storage::{
+        global_state::lmdb::DEFAULT_SCRATCH_CACHE_SIZE,
+        transaction_source::lmdb::{LmdbSyncMode, LmdbTuning},
+    },
+};
+use casper_types::TimeDiff;
+
+use super::ConfigError;
 
 const DEFAULT_MAX_GLOBAL_STATE_SIZE: usize = 805_306_368_000; // 750 GiB
 const DEFAULT_MAX_READERS: u32 = 512;
+/// The most reader slots left out of the read transaction pool, for read transactions opened
+/// outside it and for other processes reading the global state store.
+const RESERVED_READER_SLOTS: u32 = 32;
 const DEFAULT_MAX_QUERY_DEPTH: u64 = 5;
 const DEFAULT_MANUAL_SYNC_ENABLED: bool = true;
+const DEFAULT_MAX_READ_TRANSACTION_DURATION: TimeDiff = TimeDiff::from_seconds(60);
+const DEFAULT_MAX_PREPROCESSED_MODULE_CACHE_SIZE: u64 = 268_435_456; // 256 MiB
+const DEFAULT_MAX_QUERY_CACHE_SIZE: u64 = 0;
+const DEFAULT_MAX_SCRATCH_DIRTY_SIZE: u64 = 0;
+const DEFAULT_GLOBAL_STATE_SIZE_INCREMENT: usize = 0;
+const DEFAULT_MAX_VIEW_CALL_GAS: u64 = 100_000_000_000;
+const DEFAULT_COMPRESS_TRIES: bool = false;
+const DEFAULT_TRIE_COMPRESSION_LEVEL: i32 = 3;
+const DEFAULT_PREFETCH_TRIES: bool = false;
+const DEFAULT_SHARD_TRIES: bool = false;
+const DEFAULT_LMDB_READAHEAD: bool = false;
 
 /// Contract runtime configuration.
 #[derive(Clone, Copy, DataSize, Debug, Deserialize, Serialize)]
@@ -19,6 +42,15 @@ pub struct Config {
     ///
     /// The size should be a multiple of the OS page size.
     max_global_state_size: Option<usize>,
+    /// The number of bytes to grow the global state store by whenever it becomes full.
+    ///
+    /// A value of 0 disables growing, in which case the node stops once the store reaches
+    /// `max_global_state_size`.
+    ///
+    /// Defaults to 0.
+    ///
+    /// The size should be a multiple of the OS page size.
+    global_state_size_increment: Option<usize>,
     /// The maximum number of readers to use for the global state store.
     ///
     /// Defaults to 512.
@@ -29,8 +61,109 @@ pub struct Config {
     max_query_depth: Option<u64>,
     /// Enable synchronizing to disk only after each block is written.
     ///
-    /// Defaults to `false`.
+    /// Superseded by `lmdb.sync_mode`, which it must agree with if both are set.
+    ///
+    /// Defaults to `true`.
     enable_manual_sync: Option<bool>,
+    /// The duration after which an open global state read transaction is reported in the logs.
+    ///
+    /// Readers held open for long periods prevent LMDB from reclaiming pages, causing the database
+    /// to grow.
+    ///
+    /// Defaults to 60 seconds.
+    max_read_transaction_duration: Option<TimeDiff>,
+    /// The maximum total size in bytes of preprocessed Wasm modules cached on disk.
+    ///
+    /// Caching lets repeated executions of the same module bytes, including ones after a restart,
+    /// skip preprocessing. A value of 0 disables the cache.
+    ///
+    /// Defaults to 268,435,456 == 256 MiB.
+    max_preprocessed_module_cache_size: Option<u64>,
+    /// The maximum total size in bytes of global state query results cached in memory.
+    ///
+    /// Results under a given state root never change, so caching them lets repeated queries of the
+    /// same keys skip reading global state. A value of 0 disables the cache.
+    ///
+    /// Defaults to 0.
+    max_query_cache_size: Option<u64>,
+    /// The maximum total size in bytes of tries read from global state which are cached in memory
+    /// while committing a block.
+    ///
+    /// Sizes are estimates of the memory the deserialized tries take.  Tries written by the block
+    /// are always kept until committed and don't count towards this limit.
+    ///
+    /// Defaults to 536,870,912 == 512 MiB.
+    max_scratch_cache_size: Option<u64>,
+    /// The maximum total size in bytes of tries written by a block which are held in memory until
+    /// the block is committed.
+    ///
+    /// Beyond this, the oldest tries are spilled to a temporary database in the global state
+    /// directory, bounding the memory used by blocks which touch very many keys.  A value of 0
+    /// disables spilling.
+    ///
+    /// Defaults to 0.
+    max_scratch_dirty_size: Option<u64>,
+    /// The maximum gas a read-only contract entry point call made via the `query_contract_view`
+    /// RPC may use.  The RPC is only served by the speculative execution server.
+    ///
+    /// Defaults to 100,000,000,000.
+    max_view_call_gas: Option<u64>,
+    /// Enable compressing large tries, such as leaves holding contract Wasm, with zstd when
+    /// writing them to the global state store.
+    ///
+    /// Once enabled, the store may hold compressed tries and is always decompressed, even if this
+    /// is disabled again.  Tries already written are left as they are.
+    ///
+    /// Defaults to `false`.
+    compress_tries: Option<bool>,
+    /// The zstd level to compress tries at, if `compress_tries` is enabled.
+    ///
+    /// Defaults to 3.
+    trie_compression_level: Option<i32>,
+    /// Enable reading the accounts, purses and contracts the deploys of a block refer to on
+    /// background threads while the block is executed, so that execution finds them in memory.
+    ///
+    /// Defaults to `false`.
+    prefetch_tries: Option<bool>,
+    /// Enable writing the leaves under account, balance, contract and dictionary keys to LMDB
+    /// databases of their own, apart from the rest of the tries.
+    ///
+    /// Leaves already written are moved into their databases at startup when this is enabled, and
+    /// moved back, dropping the databases, when it is disabled again.
+    ///
+    /// Defaults to `false`.
+    shard_tries: Option<bool>,
+    /// Tuning of the LMDB environment holding the global state store.
+    lmdb: Option<LmdbConfig>,
+}
+
+/// Tuning of an LMDB environment, trading durability or read latency against throughput.
+#[derive(Clone, Copy, DataSize, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LmdbConfig {
+    /// How commits are made durable: `full`, `manual`, `no_meta_sync` or `no_sync`.
+    ///
+    /// `full` flushes every commit and survives any crash.  `manual` syncs after each block; a
+    /// crash of the OS between syncs can lose the block and corrupt the store.  `no_meta_sync` can
+    /// lose the last commit on a crash of the OS, but never corrupts the store.  `no_sync` leaves
+    /// flushing to the OS; a crash of the OS can lose recent blocks and corrupt the store.
+    ///
+    /// Defaults to `manual`, unless `enable_manual_sync` is `false`, in which case to `full`.
+    sync_mode: Option<LmdbSyncMode>,
+    /// The number of bytes to grow the store by whenever it becomes full.
+    ///
+    /// Supersedes `global_state_size_increment`, which it must agree with unless that is 0.  A
+    /// value of 0 disables growing.  Must be a multiple of the OS page size.
+    ///
+    /// Defaults to `global_state_size_increment`.
+    growth_step: Option<usize>,
+    /// Enable the OS reading ahead of the pages accessed.
+    ///
+    /// Readahead can speed up a store smaller than RAM on spinning disks, but wastes the page
+    /// cache on SSDs and on stores larger than RAM.
+    ///
+    /// Defaults to `false`.
+    readahead: Option<bool>,
 }
 
 impl Config {
@@ -50,9 +183,106 @@ impl Config {
         self.max_query_depth.unwrap_or(DEFAULT_MAX_QUERY_DEPTH)
     }
 
-    pub(crate) fn manual_sync_enabled(&self) -> bool {
-        self.enable_manual_sync
-            .unwrap_or(DEFAULT_MANUAL_SYNC_ENABLED)
+    pub(crate) fn max_read_transaction_duration(&self) -> TimeDiff {
+        self.max_read_transaction_duration
+            .unwrap_or(DEFAULT_MAX_READ_TRANSACTION_DURATION)
+    }
+
+    pub(crate) fn max_preprocessed_module_cache_size(&self) -> u64 {
+        self.max_preprocessed_module_cache_size
+            .unwrap_or(DEFAULT_MAX_PREPROCESSED_MODULE_CACHE_SIZE)
+    }
+
+    pub(crate) fn max_query_cache_size(&self) -> u64 {
+        self.max_query_cache_size
+            .unwrap_or(DEFAULT_MAX_QUERY_CACHE_SIZE)
+    }
+
+    pub(crate) fn max_scratch_cache_size(&self) -> u64 {
+        self.max_scratch_cache_size
+            .unwrap_or(DEFAULT_SCRATCH_CACHE_SIZE)
+    }
+
+    pub(crate) fn max_scratch_dirty_size(&self) -> u64 {
+        self.max_scratch_dirty_size
+            .unwrap_or(DEFAULT_MAX_SCRATCH_DIRTY_SIZE)
+    }
+
+    pub(crate) fn max_view_call_gas(&self) -> u64 {
+        self.max_view_call_gas.unwrap_or(DEFAULT_MAX_VIEW_CALL_GAS)
+    }
+
+    /// Returns the zstd level to compress tries at, or `None` if compression is disabled.
+    pub(crate) fn trie_compression_level(&self) -> Option<i32> {
+        if self.compress_tries.unwrap_or(DEFAULT_COMPRESS_TRIES) {
+            Some(
+                self.trie_compression_level
+                    .unwrap_or(DEFAULT_TRIE_COMPRESSION_LEVEL),
+            )
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn prefetch_tries(&self) -> bool {
+        self.prefetch_tries.unwrap_or(DEFAULT_PREFETCH_TRIES)
+    }
+
+    pub(crate) fn shard_tries(&self) -> bool {
+        self.shard_tries.unwrap_or(DEFAULT_SHARD_TRIES)
+    }
+
+    /// Returns the number of read transactions pooled, leaving some of the `max_readers` reader
+    /// slots free for transactions opened outside the pool.
+    fn read_txn_pool_size(&self) -> usize {
+        let max_readers = self.max_readers();
+        let reserved = RESERVED_READER_SLOTS.min(max_readers / 2);
+        (max_readers - reserved).max(1) as usize
+    }
+
+    /// Returns the validated tuning of the global state store's LMDB environment, reconciling
+    /// the `lmdb` table with the older settings it supersedes.
+    pub(crate) fn lmdb_tuning(&self) -> Result<LmdbTuning, ConfigError> {
+        let lmdb = self.lmdb.unwrap_or_default();
+        let sync_mode = match (lmdb.sync_mode, self.enable_manual_sync) {
+            (Some(sync_mode), Some(manual_sync_enabled))
+                if (sync_mode == LmdbSyncMode::Manual) != manual_sync_enabled =>
+            {
+                return Err(ConfigError::ConflictingLmdbSyncMode {
+                    sync_mode,
+                    manual_sync_enabled,
+                });
+            }
+            (Some(sync_mode), _) => sync_mode,
+            (None, manual_sync_enabled) => {
+                if manual_sync_enabled.unwrap_or(DEFAULT_MANUAL_SYNC_ENABLED) {
+                    LmdbSyncMode::Manual
+                } else {
+                    LmdbSyncMode::Full
+                }
+            }
+        };
+        let size_increment = self
+            .global_state_size_increment
+            .unwrap_or(DEFAULT_GLOBAL_STATE_SIZE_INCREMENT);
+        let growth_step = match lmdb.growth_step {
+            Some(growth_step) if size_increment != 0 && growth_step != size_increment => {
+                return Err(ConfigError::ConflictingLmdbGrowthStep {
+                    growth_step,
+                    size_increment,
+                });
+            }
+            Some(growth_step) => growth_step,
+            None => size_increment,
+        };
+        let tuning = LmdbTuning {
+            sync_mode,
+            readahead: lmdb.readahead.unwrap_or(DEFAULT_LMDB_READAHEAD),
+            map_size_increment: (growth_step > 0).then(|| growth_step),
+            read_txn_pool_size: Some(self.read_txn_pool_size()),
+        };
+        tuning.validate()?;
+        Ok(tuning)
     }
 }
 
@@ -60,9 +290,73 @@ impl Default for Config {
     fn default() -> Self {
         Config {
             max_global_state_size: Some(DEFAULT_MAX_GLOBAL_STATE_SIZE),
+            global_state_size_increment: Some(DEFAULT_GLOBAL_STATE_SIZE_INCREMENT),
             max_readers: Some(DEFAULT_MAX_READERS),
             max_query_depth: Some(DEFAULT_MAX_QUERY_DEPTH),
             enable_manual_sync: Some(DEFAULT_MANUAL_SYNC_ENABLED),
+            max_read_transaction_duration: Some(DEFAULT_MAX_READ_TRANSACTION_DURATION),
+            max_preprocessed_module_cache_size: Some(DEFAULT_MAX_PREPROCESSED_MODULE_CACHE_SIZE),
+            max_query_cache_size: Some(DEFAULT_MAX_QUERY_CACHE_SIZE),
+            max_scratch_cache_size: Some(DEFAULT_SCRATCH_CACHE_SIZE),
+            max_scratch_dirty_size: Some(DEFAULT_MAX_SCRATCH_DIRTY_SIZE),
+            max_view_call_gas: Some(DEFAULT_MAX_VIEW_CALL_GAS),
+            compress_tries: Some(DEFAULT_COMPRESS_TRIES),
+            trie_compression_level: Some(DEFAULT_TRIE_COMPRESSION_LEVEL),
+            prefetch_tries: Some(DEFAULT_PREFETCH_TRIES),
+            shard_tries: Some(DEFAULT_SHARD_TRIES),
+            lmdb: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use casper_execution_engine::shared::utils::OS_PAGE_SIZE;
+
+    use super::*;
+
+    #[test]
+    fn should_reconcile_lmdb_tuning_with_superseded_settings() {
+        let mut config = Config::default();
+        let tuning = config.lmdb_tuning().unwrap();
+        assert_eq!(tuning.sync_mode, LmdbSyncMode::Manual);
+        assert_eq!(tuning.map_size_increment, None);
+        assert!(!tuning.readahead);
+        assert_eq!(
+            tuning.read_txn_pool_size,
+            Some((DEFAULT_MAX_READERS - RESERVED_READER_SLOTS) as usize)
+        );
+
+        config.lmdb = Some(LmdbConfig {
+            sync_mode: Some(LmdbSyncMode::NoMetaSync),
+            growth_step: Some(*OS_PAGE_SIZE),
+            readahead: Some(true),
+        });
+        assert!(matches!(
+            config.lmdb_tuning(),
+            Err(ConfigError::ConflictingLmdbSyncMode { .. })
+        ));
+
+        config.enable_manual_sync = None;
+        let tuning = config.lmdb_tuning().unwrap();
+        assert_eq!(tuning.sync_mode, LmdbSyncMode::NoMetaSync);
+        assert_eq!(tuning.map_size_increment, Some(*OS_PAGE_SIZE));
+        assert!(tuning.readahead);
+
+        config.global_state_size_increment = Some(2 * *OS_PAGE_SIZE);
+        assert!(matches!(
+            config.lmdb_tuning(),
+            Err(ConfigError::ConflictingLmdbGrowthStep { .. })
+        ));
+
+        config.global_state_size_increment = None;
+        config.lmdb = Some(LmdbConfig {
+            growth_step: Some(*OS_PAGE_SIZE + 1),
+            ..LmdbConfig::default()
+        });
+        assert!(matches!(
+            config.lmdb_tuning(),
+            Err(ConfigError::LmdbTuning(_))
+        ));
+    }
+}
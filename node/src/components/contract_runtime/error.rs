@@ -1,11 +1,19 @@
 //! Errors that the contract runtime component may raise.
 
+use std::io;
+
 use serde::Serialize;
 use thiserror::Error;
 
 use casper_execution_engine::{
-    core::engine_state::{Error as EngineStateError, StepError},
-    storage::error::lmdb::Error as StorageLmdbError,
+    core::{
+        engine_state::{Error as EngineStateError, StepError},
+        execution::Error as ExecutionError,
+    },
+    storage::{
+        error::lmdb::Error as StorageLmdbError,
+        transaction_source::lmdb::{LmdbSyncMode, LmdbTuningError},
+    },
 };
 
 use crate::{
@@ -26,6 +34,34 @@ pub(crate) enum ConfigError {
     /// Error initializing execution engine.
     #[error("failed to initialize execution engine: {0}")]
     EngineState(#[from] EngineStateError),
+    /// Error opening the preprocessed Wasm module cache.
+    #[error("failed to open preprocessed module cache: {0}")]
+    PreprocessedModuleCache(io::Error),
+    /// The LMDB sync mode contradicts `enable_manual_sync`.
+    #[error(
+        "contract_runtime.lmdb.sync_mode {sync_mode:?} conflicts with enable_manual_sync = \
+         {manual_sync_enabled}; remove enable_manual_sync"
+    )]
+    ConflictingLmdbSyncMode {
+        /// The configured sync mode.
+        sync_mode: LmdbSyncMode,
+        /// The configured `enable_manual_sync`.
+        manual_sync_enabled: bool,
+    },
+    /// The LMDB growth step contradicts `global_state_size_increment`.
+    #[error(
+        "contract_runtime.lmdb.growth_step {growth_step} conflicts with \
+         global_state_size_increment = {size_increment}; remove global_state_size_increment"
+    )]
+    ConflictingLmdbGrowthStep {
+        /// The configured growth step.
+        growth_step: usize,
+        /// The configured `global_state_size_increment`.
+        size_increment: usize,
+    },
+    /// The LMDB tuning is invalid.
+    #[error("invalid LMDB tuning for contract runtime: {0}")]
+    LmdbTuning(#[from] LmdbTuningError),
 }
 
 /// An error during block execution.
@@ -80,3 +116,34 @@ pub enum BlockExecutionError {
         GetEraValidatorsError,
     ),
 }
+
+impl BlockExecutionError {
+    /// Returns `true` if the block failed to execute because a stored trie couldn't be parsed.
+    pub(crate) fn is_corrupted_trie(&self) -> bool {
+        match self {
+            BlockExecutionError::EngineState(error) => is_corrupted_trie(error),
+            BlockExecutionError::Step(
+                StepError::TrackingCopyError(error)
+                | StepError::GetContractError(error)
+                | StepError::GetSystemModuleError(error)
+                | StepError::SlashingError(error)
+                | StepError::AuctionError(error)
+                | StepError::DistributeError(error)
+                | StepError::StorageRentError(error)
+                | StepError::OtherEngineStateError(error),
+            ) => is_corrupted_trie(error),
+            BlockExecutionError::Step(StepError::ExecutionError(ExecutionError::Storage(
+                StorageLmdbError::CorruptedTrie(..),
+            ))) => true,
+            _ => false,
+        }
+    }
+}
+
+fn is_corrupted_trie(error: &EngineStateError) -> bool {
+    matches!(
+        error,
+        EngineStateError::Storage(StorageLmdbError::CorruptedTrie(..))
+            | EngineStateError::Exec(ExecutionError::Storage(StorageLmdbError::CorruptedTrie(..)))
+    )
+}
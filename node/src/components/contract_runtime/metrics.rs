@@ -1,6 +1,8 @@
-use prometheus::{self, Gauge, Histogram, IntGauge, Registry};
+use prometheus::{self, Gauge, Histogram, IntCounter, IntCounterVec, IntGauge, Opts, Registry};
 
-use crate::{unregister_metric, utils};
+use casper_execution_engine::storage::trie_store::counters::TrieStoreCounts;
+
+use crate::{types::BlockWriteStats, unregister_metric, utils};
 
 /// Value of upper bound of histogram.
 const EXPONENTIAL_BUCKET_START: f64 = 0.01;
@@ -48,6 +50,56 @@ const GET_TRIE_HELP: &str = "time in seconds to get a trie";
 const EXEC_BLOCK_NAME: &str = "contract_runtime_execute_block";
 const EXEC_BLOCK_HELP: &str = "time in seconds to execute all deploys in a block";
 
+const BLOCK_COMMIT_EXECUTION_NAME: &str = "contract_runtime_block_commit_execution";
+const BLOCK_COMMIT_EXECUTION_HELP: &str = "time in seconds spent executing the deploys of a block";
+
+const BLOCK_COMMIT_APPLY_EFFECTS_NAME: &str = "contract_runtime_block_commit_apply_effects";
+const BLOCK_COMMIT_APPLY_EFFECTS_HELP: &str =
+    "time in seconds spent applying the execution effects of a block to the scratch state";
+
+const BLOCK_COMMIT_STEP_NAME: &str = "contract_runtime_block_commit_step";
+const BLOCK_COMMIT_STEP_HELP: &str =
+    "time in seconds spent committing the era end step of a switch block to the scratch state";
+
+const BLOCK_COMMIT_SCRATCH_NAME: &str = "contract_runtime_block_commit_scratch_validation";
+const BLOCK_COMMIT_SCRATCH_HELP: &str =
+    "time in seconds spent building and validating the tries of a block in memory";
+
+const BLOCK_COMMIT_LMDB_WRITE_NAME: &str = "contract_runtime_block_commit_lmdb_write";
+const BLOCK_COMMIT_LMDB_WRITE_HELP: &str =
+    "time in seconds spent writing the tries of a block to LMDB and flushing";
+
+const BLOCK_COMMIT_TRIES_WRITTEN_NAME: &str = "contract_runtime_block_commit_tries_written";
+const BLOCK_COMMIT_TRIES_WRITTEN_HELP: &str = "number of tries written to LMDB per block commit";
+
+const BLOCK_COMMIT_BYTES_WRITTEN_NAME: &str = "contract_runtime_block_commit_bytes_written";
+const BLOCK_COMMIT_BYTES_WRITTEN_HELP: &str =
+    "total size in bytes of the tries written to LMDB per block commit";
+
+const BLOCK_COMMIT_WRITE_BATCHES_NAME: &str = "contract_runtime_block_commit_write_batches";
+const BLOCK_COMMIT_WRITE_BATCHES_HELP: &str =
+    "number of LMDB transactions the tries of a block were written in";
+
+const SCRATCH_CACHE_HITS_NAME: &str = "contract_runtime_scratch_cache_hits";
+const SCRATCH_CACHE_HITS_HELP: &str =
+    "number of trie lookups served from the scratch cache while committing blocks";
+
+const SCRATCH_CACHE_MISSES_NAME: &str = "contract_runtime_scratch_cache_misses";
+const SCRATCH_CACHE_MISSES_HELP: &str =
+    "number of trie lookups which missed the scratch cache while committing blocks";
+
+const SCRATCH_TRIES_SPILLED_NAME: &str = "contract_runtime_scratch_tries_spilled";
+const SCRATCH_TRIES_SPILLED_HELP: &str =
+    "number of new tries spilled out of memory to a temporary database while committing blocks";
+
+const PREFETCHED_VALUES_NAME: &str = "contract_runtime_prefetched_values";
+const PREFETCHED_VALUES_HELP: &str =
+    "number of global state values read ahead of executing the deploys of blocks";
+
+const BLOCK_COMMIT_ANNOUNCEMENT_NAME: &str = "contract_runtime_block_commit_announcement";
+const BLOCK_COMMIT_ANNOUNCEMENT_HELP: &str =
+    "time in seconds spent storing and announcing an executed block";
+
 const LATEST_COMMIT_STEP_NAME: &str = "contract_runtime_latest_commit_step";
 const LATEST_COMMIT_STEP_HELP: &str = "duration in seconds of latest commit step at era end";
 
@@ -55,6 +107,43 @@ const EXEC_QUEUE_SIZE_NAME: &str = "execution_queue_size";
 const EXEC_QUEUE_SIZE_HELP: &str =
     "number of blocks that are currently enqueued and waiting for execution";
 
+const STALE_READ_TRANSACTIONS_NAME: &str = "contract_runtime_stale_read_transactions";
+const STALE_READ_TRANSACTIONS_HELP: &str =
+    "number of global state read transactions open for longer than the maximum duration";
+
+const VALUES_WRITTEN_NAME: &str = "contract_runtime_values_written_by_key_space";
+const VALUES_WRITTEN_HELP: &str =
+    "number of values written to global state by executed blocks, by kind of key";
+
+const BYTES_WRITTEN_NAME: &str = "contract_runtime_bytes_written_by_key_space";
+const BYTES_WRITTEN_HELP: &str =
+    "serialized size in bytes of the values written to global state by executed blocks, by kind \
+    of key";
+
+const TRIE_STORE_GETS_NAME: &str = "contract_runtime_trie_store_gets";
+const TRIE_STORE_GETS_HELP: &str = "number of tries looked up in the LMDB trie store";
+
+const TRIE_STORE_BYTES_READ_NAME: &str = "contract_runtime_trie_store_bytes_read";
+const TRIE_STORE_BYTES_READ_HELP: &str =
+    "number of bytes of tries read from the LMDB trie store, as stored";
+
+const TRIE_STORE_PUTS_NAME: &str = "contract_runtime_trie_store_puts";
+const TRIE_STORE_PUTS_HELP: &str = "number of tries written to the LMDB trie store";
+
+const TRIE_STORE_BYTES_WRITTEN_NAME: &str = "contract_runtime_trie_store_bytes_written";
+const TRIE_STORE_BYTES_WRITTEN_HELP: &str =
+    "number of bytes of tries written to the LMDB trie store, as stored";
+
+const SCRATCH_TRIE_STORE_GETS_NAME: &str = "contract_runtime_scratch_trie_store_gets";
+const SCRATCH_TRIE_STORE_GETS_HELP: &str =
+    "number of tries looked up through scratch trie stores, whether cached or not";
+
+const SCRATCH_TRIE_STORE_PUTS_NAME: &str = "contract_runtime_scratch_trie_store_puts";
+const SCRATCH_TRIE_STORE_PUTS_HELP: &str = "number of new tries inserted into scratch trie stores";
+
+/// The label of the key space metrics.
+const KEY_SPACE_LABEL: &str = "key_space";
+
 /// Metrics for the contract runtime component.
 #[derive(Debug)]
 pub struct Metrics {
@@ -69,8 +158,30 @@ pub struct Metrics {
     pub(super) put_trie: Histogram,
     pub(super) get_trie: Histogram,
     pub(super) exec_block: Histogram,
+    pub(super) block_commit_execution: Histogram,
+    pub(super) block_commit_apply_effects: Histogram,
+    pub(super) block_commit_step: Histogram,
+    pub(super) block_commit_scratch_validation: Histogram,
+    pub(super) block_commit_lmdb_write: Histogram,
+    pub(super) block_commit_tries_written: Histogram,
+    pub(super) block_commit_bytes_written: Histogram,
+    pub(super) block_commit_write_batches: Histogram,
+    pub(super) scratch_cache_hits: IntCounter,
+    pub(super) scratch_cache_misses: IntCounter,
+    pub(super) scratch_tries_spilled: IntCounter,
+    pub(super) prefetched_values: IntCounter,
+    trie_store_gets: IntCounter,
+    trie_store_bytes_read: IntCounter,
+    trie_store_puts: IntCounter,
+    trie_store_bytes_written: IntCounter,
+    scratch_trie_store_gets: IntCounter,
+    scratch_trie_store_puts: IntCounter,
+    pub(super) block_commit_announcement: Histogram,
     pub(super) latest_commit_step: Gauge,
     pub(super) exec_queue_size: IntGauge,
+    pub(super) stale_read_transactions: IntGauge,
+    pub(super) values_written: IntCounterVec,
+    pub(super) bytes_written: IntCounterVec,
     registry: Registry,
 }
 
@@ -89,12 +200,70 @@ impl Metrics {
         // Anything above that should be a warning signal.
         let tiny_buckets = prometheus::exponential_buckets(0.001, 2.0, 10)?;
 
+        // Counts of tries and batches, from 1 up to ~500k.
+        let count_buckets = prometheus::exponential_buckets(1.0, 4.0, 10)?;
+
+        // Sizes from 1 KiB up to 1 GiB.
+        let size_buckets = prometheus::exponential_buckets(1024.0, 4.0, 11)?;
+
         let latest_commit_step = Gauge::new(LATEST_COMMIT_STEP_NAME, LATEST_COMMIT_STEP_HELP)?;
         registry.register(Box::new(latest_commit_step.clone()))?;
 
         let exec_queue_size = IntGauge::new(EXEC_QUEUE_SIZE_NAME, EXEC_QUEUE_SIZE_HELP)?;
         registry.register(Box::new(exec_queue_size.clone()))?;
 
+        let stale_read_transactions =
+            IntGauge::new(STALE_READ_TRANSACTIONS_NAME, STALE_READ_TRANSACTIONS_HELP)?;
+        registry.register(Box::new(stale_read_transactions.clone()))?;
+
+        let scratch_cache_hits = IntCounter::new(SCRATCH_CACHE_HITS_NAME, SCRATCH_CACHE_HITS_HELP)?;
+        registry.register(Box::new(scratch_cache_hits.clone()))?;
+
+        let scratch_cache_misses =
+            IntCounter::new(SCRATCH_CACHE_MISSES_NAME, SCRATCH_CACHE_MISSES_HELP)?;
+        registry.register(Box::new(scratch_cache_misses.clone()))?;
+
+        let scratch_tries_spilled =
+            IntCounter::new(SCRATCH_TRIES_SPILLED_NAME, SCRATCH_TRIES_SPILLED_HELP)?;
+        registry.register(Box::new(scratch_tries_spilled.clone()))?;
+
+        let prefetched_values = IntCounter::new(PREFETCHED_VALUES_NAME, PREFETCHED_VALUES_HELP)?;
+        registry.register(Box::new(prefetched_values.clone()))?;
+
+        let trie_store_gets = IntCounter::new(TRIE_STORE_GETS_NAME, TRIE_STORE_GETS_HELP)?;
+        registry.register(Box::new(trie_store_gets.clone()))?;
+
+        let trie_store_bytes_read =
+            IntCounter::new(TRIE_STORE_BYTES_READ_NAME, TRIE_STORE_BYTES_READ_HELP)?;
+        registry.register(Box::new(trie_store_bytes_read.clone()))?;
+
+        let trie_store_puts = IntCounter::new(TRIE_STORE_PUTS_NAME, TRIE_STORE_PUTS_HELP)?;
+        registry.register(Box::new(trie_store_puts.clone()))?;
+
+        let trie_store_bytes_written =
+            IntCounter::new(TRIE_STORE_BYTES_WRITTEN_NAME, TRIE_STORE_BYTES_WRITTEN_HELP)?;
+        registry.register(Box::new(trie_store_bytes_written.clone()))?;
+
+        let scratch_trie_store_gets =
+            IntCounter::new(SCRATCH_TRIE_STORE_GETS_NAME, SCRATCH_TRIE_STORE_GETS_HELP)?;
+        registry.register(Box::new(scratch_trie_store_gets.clone()))?;
+
+        let scratch_trie_store_puts =
+            IntCounter::new(SCRATCH_TRIE_STORE_PUTS_NAME, SCRATCH_TRIE_STORE_PUTS_HELP)?;
+        registry.register(Box::new(scratch_trie_store_puts.clone()))?;
+
+        let values_written = IntCounterVec::new(
+            Opts::new(VALUES_WRITTEN_NAME, VALUES_WRITTEN_HELP),
+            &[KEY_SPACE_LABEL],
+        )?;
+        registry.register(Box::new(values_written.clone()))?;
+
+        let bytes_written = IntCounterVec::new(
+            Opts::new(BYTES_WRITTEN_NAME, BYTES_WRITTEN_HELP),
+            &[KEY_SPACE_LABEL],
+        )?;
+        registry.register(Box::new(bytes_written.clone()))?;
+
         Ok(Metrics {
             run_execute: utils::register_histogram_metric(
                 registry,
@@ -156,6 +325,60 @@ impl Metrics {
                 PUT_TRIE_HELP,
                 tiny_buckets,
             )?,
+            block_commit_execution: utils::register_histogram_metric(
+                registry,
+                BLOCK_COMMIT_EXECUTION_NAME,
+                BLOCK_COMMIT_EXECUTION_HELP,
+                common_buckets.clone(),
+            )?,
+            block_commit_apply_effects: utils::register_histogram_metric(
+                registry,
+                BLOCK_COMMIT_APPLY_EFFECTS_NAME,
+                BLOCK_COMMIT_APPLY_EFFECTS_HELP,
+                common_buckets.clone(),
+            )?,
+            block_commit_step: utils::register_histogram_metric(
+                registry,
+                BLOCK_COMMIT_STEP_NAME,
+                BLOCK_COMMIT_STEP_HELP,
+                common_buckets.clone(),
+            )?,
+            block_commit_scratch_validation: utils::register_histogram_metric(
+                registry,
+                BLOCK_COMMIT_SCRATCH_NAME,
+                BLOCK_COMMIT_SCRATCH_HELP,
+                common_buckets.clone(),
+            )?,
+            block_commit_lmdb_write: utils::register_histogram_metric(
+                registry,
+                BLOCK_COMMIT_LMDB_WRITE_NAME,
+                BLOCK_COMMIT_LMDB_WRITE_HELP,
+                common_buckets.clone(),
+            )?,
+            block_commit_tries_written: utils::register_histogram_metric(
+                registry,
+                BLOCK_COMMIT_TRIES_WRITTEN_NAME,
+                BLOCK_COMMIT_TRIES_WRITTEN_HELP,
+                count_buckets.clone(),
+            )?,
+            block_commit_bytes_written: utils::register_histogram_metric(
+                registry,
+                BLOCK_COMMIT_BYTES_WRITTEN_NAME,
+                BLOCK_COMMIT_BYTES_WRITTEN_HELP,
+                size_buckets,
+            )?,
+            block_commit_write_batches: utils::register_histogram_metric(
+                registry,
+                BLOCK_COMMIT_WRITE_BATCHES_NAME,
+                BLOCK_COMMIT_WRITE_BATCHES_HELP,
+                count_buckets,
+            )?,
+            block_commit_announcement: utils::register_histogram_metric(
+                registry,
+                BLOCK_COMMIT_ANNOUNCEMENT_NAME,
+                BLOCK_COMMIT_ANNOUNCEMENT_HELP,
+                common_buckets.clone(),
+            )?,
             exec_block: utils::register_histogram_metric(
                 registry,
                 EXEC_BLOCK_NAME,
@@ -164,9 +387,44 @@ impl Metrics {
             )?,
             latest_commit_step,
             exec_queue_size,
+            stale_read_transactions,
+            scratch_cache_hits,
+            scratch_cache_misses,
+            scratch_tries_spilled,
+            prefetched_values,
+            trie_store_gets,
+            trie_store_bytes_read,
+            trie_store_puts,
+            trie_store_bytes_written,
+            scratch_trie_store_gets,
+            scratch_trie_store_puts,
+            values_written,
+            bytes_written,
             registry: registry.clone(),
         })
     }
+
+    /// Adds the values written by an executed block to the per key space counters.
+    pub(super) fn observe_block_write_stats(&self, write_stats: &BlockWriteStats) {
+        for (key_space, writes) in &write_stats.key_spaces {
+            self.values_written
+                .with_label_values(&[key_space])
+                .inc_by(writes.values);
+            self.bytes_written
+                .with_label_values(&[key_space])
+                .inc_by(writes.bytes);
+        }
+    }
+
+    /// Adds the operations performed on the trie store to the trie store counters.
+    pub(super) fn observe_trie_store_counts(&self, counts: &TrieStoreCounts) {
+        self.trie_store_gets.inc_by(counts.gets);
+        self.trie_store_bytes_read.inc_by(counts.bytes_read);
+        self.trie_store_puts.inc_by(counts.puts);
+        self.trie_store_bytes_written.inc_by(counts.bytes_written);
+        self.scratch_trie_store_gets.inc_by(counts.scratch_gets);
+        self.scratch_trie_store_puts.inc_by(counts.scratch_puts);
+    }
 }
 
 impl Drop for Metrics {
@@ -182,7 +440,29 @@ impl Drop for Metrics {
         unregister_metric!(self.registry, self.put_trie);
         unregister_metric!(self.registry, self.get_trie);
         unregister_metric!(self.registry, self.exec_block);
+        unregister_metric!(self.registry, self.block_commit_execution);
+        unregister_metric!(self.registry, self.block_commit_apply_effects);
+        unregister_metric!(self.registry, self.block_commit_step);
+        unregister_metric!(self.registry, self.block_commit_scratch_validation);
+        unregister_metric!(self.registry, self.block_commit_lmdb_write);
+        unregister_metric!(self.registry, self.block_commit_tries_written);
+        unregister_metric!(self.registry, self.block_commit_bytes_written);
+        unregister_metric!(self.registry, self.block_commit_write_batches);
+        unregister_metric!(self.registry, self.block_commit_announcement);
         unregister_metric!(self.registry, self.latest_commit_step);
         unregister_metric!(self.registry, self.exec_queue_size);
+        unregister_metric!(self.registry, self.stale_read_transactions);
+        unregister_metric!(self.registry, self.scratch_cache_hits);
+        unregister_metric!(self.registry, self.scratch_cache_misses);
+        unregister_metric!(self.registry, self.scratch_tries_spilled);
+        unregister_metric!(self.registry, self.prefetched_values);
+        unregister_metric!(self.registry, self.trie_store_gets);
+        unregister_metric!(self.registry, self.trie_store_bytes_read);
+        unregister_metric!(self.registry, self.trie_store_puts);
+        unregister_metric!(self.registry, self.trie_store_bytes_written);
+        unregister_metric!(self.registry, self.scratch_trie_store_gets);
+        unregister_metric!(self.registry, self.scratch_trie_store_puts);
+        unregister_metric!(self.registry, self.values_written);
+        unregister_metric!(self.registry, self.bytes_written);
     }
 }
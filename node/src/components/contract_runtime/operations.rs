@@ -1,4 +1,8 @@
-use std::{collections::BTreeMap, sync::Arc, time::Instant};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use itertools::Itertools;
 use tracing::{debug, trace, warn};
@@ -6,18 +10,23 @@ use tracing::{debug, trace, warn};
 use casper_execution_engine::{
     core::{
         engine_state::{
-            self, execution_result::ExecutionResults, step::EvictItem, ChecksumRegistry,
-            DeployItem, EngineState, ExecuteRequest, ExecutionResult as EngineExecutionResult,
-            GetEraValidatorsRequest, RewardItem, StepError, StepRequest, StepSuccess,
+            self, execution_result::ExecutionResults,
+            execution_results_proof::execution_results_merkle_root, step::EvictItem,
+            ChecksumRegistry, DeployItem, EngineState, ExecuteRequest,
+            ExecutionResult as EngineExecutionResult, GetEraValidatorsRequest, RewardItem,
+            StepError, StepRequest, StepSuccess, EXECUTION_RESULTS_MERKLE_ROOT_NAME,
         },
         execution,
     },
     shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
-    storage::global_state::{lmdb::LmdbGlobalState, CommitProvider, StateProvider},
+    storage::global_state::{
+        lmdb::LmdbGlobalState, state_root_anchors::StateRootAnchor, CommitProvider, StateProvider,
+    },
 };
 use casper_hashing::Digest;
 use casper_types::{
-    CLValue, DeployHash, EraId, ExecutionResult, Key, ProtocolVersion, PublicKey, U512,
+    CLValue, DeployHash, EraId, ExecutionResult, Key, KeyTag, ProtocolVersion, PublicKey, Tagged,
+    U512,
 };
 
 use crate::{
@@ -31,8 +40,8 @@ use crate::{
         fetcher::FetchItem,
     },
     types::{
-        self, error::BlockCreationError, ApprovalsHashes, Block, Chunkable, Deploy, DeployHeader,
-        FinalizedBlock,
+        self, error::BlockCreationError, ApprovalsHashes, Block, BlockWriteStats, Chunkable,
+        Deploy, DeployHeader, FinalizedBlock,
     },
 };
 
@@ -70,15 +79,31 @@ pub fn execute_finalized_block(
 
     // Create a new EngineState that reads from LMDB but only caches changes in memory.
     let scratch_state = engine_state.get_scratch_engine_state();
+    let deploys = deploys
+        .into_iter()
+        .map(|deploy| {
+            let deploy_hash = *deploy.hash();
+            let deploy_header = deploy.header().clone();
+            (deploy_hash, deploy_header, DeployItem::from(deploy))
+        })
+        .collect_vec();
+    // Read what the deploys refer to in the background, while the first ones execute.
+    let prefetch = scratch_state.prefetch_deploys(
+        pre_state_root_hash,
+        deploys.iter().map(|(_, _, deploy_item)| deploy_item),
+    );
+    let protected_keys =
+        scratch_state.protected_system_keys(CorrelationId::new(), pre_state_root_hash)?;
+    // Time spent in each phase of the block commit, exported once the block is written.
+    let mut execution_time = Duration::ZERO;
+    let mut apply_effects_time = Duration::ZERO;
 
     // WARNING: Do not change the order of `deploys` as it will result in a different root hash.
-    for deploy in deploys {
-        let deploy_hash = *deploy.hash();
-        let deploy_header = deploy.header().clone();
+    for (deploy_hash, deploy_header, deploy_item) in deploys {
         let execute_request = ExecuteRequest::new(
             state_root_hash,
             block_time,
-            vec![DeployItem::from(deploy)],
+            vec![deploy_item],
             protocol_version,
             *finalized_block.proposer(),
         );
@@ -88,20 +113,32 @@ pub fn execute_finalized_block(
         // mapping between deploy_hash and execution result, and this outer logic is
         // enriching it with the deploy hash. If we were passing multiple deploys per exec
         // the relation between the deploy and the execution results would be lost.
+        let phase_start = Instant::now();
         let result = execute(&scratch_state, metrics.clone(), execute_request)?;
+        execution_time += phase_start.elapsed();
 
         trace!(?deploy_hash, ?result, "deploy execution result");
         // As for now a given state is expected to exist.
+        let phase_start = Instant::now();
         let (state_hash, execution_result) = commit_execution_results(
             &scratch_state,
             metrics.clone(),
             state_root_hash,
+            &protected_keys,
             deploy_hash.into(),
             result,
         )?;
+        apply_effects_time += phase_start.elapsed();
         execution_results.push((deploy_hash, deploy_header, execution_result));
         state_root_hash = state_hash;
     }
+    let prefetch_stats = prefetch.finish();
+    debug!(?prefetch_stats, "prefetched global state for block");
+    if let Some(metrics) = metrics.as_ref() {
+        metrics
+            .prefetched_values
+            .inc_by(prefetch_stats.values_cached);
+    }
 
     // Write the deploy approvals and execution results Merkle root hashes to global state if there
     // were any deploys.
@@ -117,6 +154,24 @@ pub fn execute_finalized_block(
     let mut checksum_registry = ChecksumRegistry::new();
     checksum_registry.insert(APPROVALS_CHECKSUM_NAME, approvals_checksum);
     checksum_registry.insert(EXECUTION_RESULTS_CHECKSUM_NAME, execution_results_checksum);
+    if engine_state
+        .config()
+        .execution_results_merkle_tree_enabled(protocol_version)
+    {
+        // Unlike the checksum above, which hashes the serialized results as a whole, this root
+        // allows proving a single deploy's execution result.
+        let execution_results_merkle_root = execution_results_merkle_root(
+            &execution_results
+                .iter()
+                .map(|(_, _, result)| result.clone())
+                .collect_vec(),
+        )
+        .map_err(BlockCreationError::BytesRepr)?;
+        checksum_registry.insert(
+            EXECUTION_RESULTS_MERKLE_ROOT_NAME,
+            execution_results_merkle_root,
+        );
+    }
     let _ = effects.insert(
         Key::ChecksumRegistry,
         Transform::Write(
@@ -125,31 +180,51 @@ pub fn execute_finalized_block(
                 .into(),
         ),
     );
+    let phase_start = Instant::now();
     scratch_state.apply_effect(CorrelationId::new(), state_root_hash, effects)?;
+    apply_effects_time += phase_start.elapsed();
 
     if let Some(metrics) = metrics.as_ref() {
         metrics.exec_block.observe(start.elapsed().as_secs_f64());
     }
 
+    // The new state root is anchored to this block as it's committed.
+    let anchor = Some(StateRootAnchor {
+        block_height: finalized_block.height(),
+        era_id: finalized_block.era_id(),
+    });
+
     // If the finalized block has an era report, run the auction contract and get the upcoming era
     // validators.
+    let write_timings;
+    let step_time;
+    let written_value_sizes;
     let maybe_step_effect_and_upcoming_era_validators =
         if let Some(era_report) = finalized_block.era_report() {
+            let phase_start = Instant::now();
             let StepSuccess {
                 post_state_hash: _, // ignore the post-state-hash returned from scratch
                 execution_journal: step_execution_journal,
+                reward_report,
             } = commit_step(
                 &scratch_state, // engine_state
-                metrics,
+                metrics.clone(),
                 protocol_version,
                 state_root_hash,
                 era_report,
                 finalized_block.timestamp().millis(),
                 finalized_block.era_id().successor(),
             )?;
+            step_time = Some(phase_start.elapsed());
 
-            state_root_hash =
-                engine_state.write_scratch_to_db(state_root_hash, scratch_state.into_inner())?;
+            written_value_sizes = scratch_state.written_value_sizes();
+            let (post_state_hash, timings) = engine_state.write_scratch_to_db(
+                state_root_hash,
+                scratch_state.into_inner(),
+                anchor,
+            )?;
+            state_root_hash = post_state_hash;
+            write_timings = timings;
 
             // In this flow we execute using a recent state root hash where the system contract
             // registry is guaranteed to exist.
@@ -162,18 +237,60 @@ pub fn execute_finalized_block(
             )?;
             Some(StepEffectAndUpcomingEraValidators {
                 step_execution_journal,
+                reward_report,
                 upcoming_era_validators,
             })
         } else {
             // Finally, the new state-root-hash from the cumulative changes to global state is
             // returned when they are written to LMDB.
-            state_root_hash =
-                engine_state.write_scratch_to_db(state_root_hash, scratch_state.into_inner())?;
+            written_value_sizes = scratch_state.written_value_sizes();
+            let (post_state_hash, timings) = engine_state.write_scratch_to_db(
+                state_root_hash,
+                scratch_state.into_inner(),
+                anchor,
+            )?;
+            state_root_hash = post_state_hash;
+            write_timings = timings;
+            step_time = None;
             None
         };
 
     // Flush once, after all deploys have been executed.
+    let phase_start = Instant::now();
     engine_state.flush_environment()?;
+    if let Some(metrics) = metrics.as_ref() {
+        metrics
+            .block_commit_execution
+            .observe(execution_time.as_secs_f64());
+        metrics
+            .block_commit_apply_effects
+            .observe(apply_effects_time.as_secs_f64());
+        if let Some(step_time) = step_time {
+            metrics.block_commit_step.observe(step_time.as_secs_f64());
+        }
+        metrics
+            .block_commit_scratch_validation
+            .observe(write_timings.scratch_validation.as_secs_f64());
+        metrics
+            .block_commit_lmdb_write
+            .observe((write_timings.lmdb_write + phase_start.elapsed()).as_secs_f64());
+        metrics
+            .block_commit_tries_written
+            .observe(write_timings.tries_written as f64);
+        metrics
+            .block_commit_bytes_written
+            .observe(write_timings.bytes_written as f64);
+        metrics
+            .block_commit_write_batches
+            .observe(write_timings.write_batches as f64);
+        metrics.scratch_cache_hits.inc_by(write_timings.cache_hits);
+        metrics
+            .scratch_cache_misses
+            .inc_by(write_timings.cache_misses);
+        metrics
+            .scratch_tries_spilled
+            .inc_by(write_timings.tries_spilled);
+    }
 
     let proof_of_checksum_registry =
         engine_state.get_checksum_registry_proof(CorrelationId::new(), state_root_hash)?;
@@ -210,11 +327,25 @@ pub fn execute_finalized_block(
         proof_of_checksum_registry,
     ));
 
+    let write_stats = BlockWriteStats::new(
+        *block.hash(),
+        block.height(),
+        written_value_sizes
+            .iter()
+            .map(|(key, size)| (Tagged::<KeyTag>::tag(key), *size)),
+    );
+    let written_keys = written_value_sizes
+        .into_iter()
+        .map(|(key, _size)| key)
+        .collect();
+
     Ok(BlockAndExecutionResults {
         block,
         approvals_hashes,
         execution_results,
         maybe_step_effect_and_upcoming_era_validators,
+        write_stats,
+        written_keys,
     })
 }
 
@@ -223,6 +354,7 @@ fn commit_execution_results<S>(
     engine_state: &EngineState<S>,
     metrics: Option<Arc<Metrics>>,
     state_root_hash: Digest,
+    protected_keys: &BTreeSet<Key>,
     deploy_hash: DeployHash,
     execution_results: ExecutionResults,
 ) -> Result<(Digest, ExecutionResult), BlockExecutionError>
@@ -261,8 +393,13 @@ where
         }
     }
     .into();
-    let new_state_root =
-        commit_transforms(engine_state, metrics, state_root_hash, execution_effect)?;
+    let new_state_root = commit_transforms(
+        engine_state,
+        metrics,
+        state_root_hash,
+        protected_keys,
+        execution_effect,
+    )?;
     Ok((new_state_root, json_execution_result))
 }
 
@@ -270,6 +407,7 @@ fn commit_transforms<S>(
     engine_state: &EngineState<S>,
     metrics: Option<Arc<Metrics>>,
     state_root_hash: Digest,
+    protected_keys: &BTreeSet<Key>,
     effects: AdditiveMap<Key, Transform>,
 ) -> Result<Digest, engine_state::Error>
 where
@@ -279,7 +417,8 @@ where
     trace!(?state_root_hash, ?effects, "commit");
     let correlation_id = CorrelationId::new();
     let start = Instant::now();
-    let result = engine_state.apply_effect(correlation_id, state_root_hash, effects);
+    let result =
+        engine_state.apply_deploy_effect(correlation_id, state_root_hash, protected_keys, effects);
     if let Some(metrics) = metrics {
         metrics.apply_effect.observe(start.elapsed().as_secs_f64());
     }
@@ -6,9 +6,11 @@ use casper_execution_engine::{
     core::engine_state::GetEraValidatorsRequest, shared::execution_journal::ExecutionJournal,
 };
 use casper_hashing::Digest;
-use casper_types::{EraId, ExecutionResult, ProtocolVersion, PublicKey, U512};
+use casper_types::{
+    system::auction::EraRewardReport, EraId, ExecutionResult, Key, ProtocolVersion, PublicKey, U512,
+};
 
-use crate::types::{ApprovalsHashes, Block, DeployHash, DeployHeader};
+use crate::types::{ApprovalsHashes, Block, BlockWriteStats, DeployHash, DeployHeader};
 
 /// Request for validator weights for a specific era.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -90,6 +92,8 @@ pub(crate) struct StepEffectAndUpcomingEraValidators {
     pub(crate) upcoming_era_validators: BTreeMap<EraId, BTreeMap<PublicKey, U512>>,
     /// An [`ExecutionJournal`] created by an era ending.
     pub(crate) step_execution_journal: ExecutionJournal,
+    /// The rewards distributed and the tokens burned by the step.
+    pub(crate) reward_report: EraRewardReport,
 }
 
 #[doc(hidden)]
@@ -106,4 +110,8 @@ pub struct BlockAndExecutionResults {
     /// The [`ExecutionJournal`] and the upcoming validator sets determined by the `step`
     pub(crate) maybe_step_effect_and_upcoming_era_validators:
         Option<StepEffectAndUpcomingEraValidators>,
+    /// The values the block wrote to global state, by kind of key.
+    pub(crate) write_stats: BlockWriteStats,
+    /// The keys the block wrote to in global state.
+    pub(crate) written_keys: Vec<Key>,
 }
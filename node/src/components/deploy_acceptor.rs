@@ -2,41 +2,46 @@ mod event;
 mod metrics;
 mod tests;
 
-use std::fmt::Debug;
+use std::{collections::BTreeSet, fmt::Debug};
 
 use datasize::DataSize;
+use futures::future::join_all;
 use prometheus::Registry;
 use serde::Serialize;
 use thiserror::Error;
 use tracing::{debug, error, trace};
 
 use casper_execution_engine::core::engine_state::{
+    account_sequence::ARG_ACCOUNT_SEQUENCE,
     executable_deploy_item::{
         ContractIdentifier, ContractPackageIdentifier, ExecutableDeployItemIdentifier,
     },
-    ExecutableDeployItem, MAX_PAYMENT,
+    ExecutableDeployItem, PaymentLanes, SessionCodePolicy, MAX_PAYMENT,
 };
 use casper_hashing::Digest;
 use casper_types::{
     account::{Account, AccountHash},
     system::auction::ARG_AMOUNT,
-    Contract, ContractHash, ContractPackage, ContractPackageHash, ContractVersion,
-    ContractVersionKey, Key, ProtocolVersion, Timestamp, U512,
+    CLType, Contract, ContractHash, ContractPackage, ContractPackageHash, ContractVersion,
+    ContractVersionKey, EntryPoint, Gas, Key, Phase, ProtocolVersion, RuntimeArgs, Timestamp, U512,
 };
 
 use crate::{
-    components::Component,
+    components::{
+        fetcher::{EmptyValidationMetadata, FetchedData},
+        Component,
+    },
     effect::{
         announcements::{DeployAcceptorAnnouncement, FatalAnnouncement},
-        requests::{ContractRuntimeRequest, StorageRequest},
+        requests::{ContractRuntimeRequest, FetcherRequest, NetworkInfoRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects, Responder,
     },
     fatal,
     types::{
-        chainspec::DeployConfig, BlockHeader, Chainspec, Deploy, DeployConfigurationFailure,
-        FinalizedApprovals,
+        chainspec::DeployConfig, Approval, BlockHeader, Chainspec, Deploy, DeployApprovals,
+        DeployConfigurationFailure, FinalizedApprovals,
     },
-    utils::Source,
+    utils::{signature_verification::SignatureVerifier, Source},
     NodeRng,
 };
 
@@ -46,6 +51,10 @@ const COMPONENT_NAME: &str = "deploy_acceptor";
 
 const ARG_TARGET: &str = "target";
 
+/// The number of peers asked for the approvals of a deploy whose own approvals lack the weight to
+/// authorize it.
+const MISSING_APPROVALS_PEER_COUNT: usize = 5;
+
 #[derive(Debug, Error, Serialize)]
 pub(crate) enum Error {
     /// The block chain has no blocks.
@@ -71,6 +80,15 @@ pub(crate) enum Error {
         /// The timestamp when the node validated the expiry timestamp.
         current_node_timestamp: Timestamp,
     },
+
+    /// The deploy's session or payment code is not permitted by the chainspec's session code
+    /// policy.
+    #[error("session or payment code not permitted by the session code policy")]
+    DisallowedSessionCode,
+
+    /// The chainspec configures payment lanes, but none admits the deploy.
+    #[error("deploy is not admitted by any payment lane")]
+    NoPaymentLane,
 }
 
 /// A representation of the way in which a deploy failed validation checks.
@@ -120,6 +138,62 @@ pub(crate) enum DeployParameterFailure {
     /// Module bytes for session code cannot be empty.
     #[error("module bytes for session code cannot be empty")]
     MissingModuleBytes,
+    /// Missing payment "account_sequence" runtime argument.
+    #[error("missing payment 'account_sequence' runtime argument")]
+    MissingAccountSequence,
+    /// Failed to parse payment "account_sequence" runtime argument.
+    #[error("failed to parse payment 'account_sequence' runtime argument as u64")]
+    FailedToParseAccountSequence,
+    /// The deploy's account has an unknown sequence number.
+    #[error("unable to determine sequence number for {account_hash}")]
+    UnknownAccountSequence { account_hash: AccountHash },
+    /// The deploy's sequence number has already been used by its account.
+    #[error(
+        "account sequence {provided} already used by {account_hash}, last used sequence is {last}"
+    )]
+    StaleAccountSequence {
+        account_hash: AccountHash,
+        last: u64,
+        provided: u64,
+    },
+    /// Priority fees are not enabled under the current protocol version.
+    #[error("priority fees are not enabled")]
+    PriorityFeesNotEnabled,
+    /// Native transfers can't carry a priority fee.
+    #[error("native transfers can't carry a priority fee")]
+    PriorityFeeOnTransfer,
+    /// User tags are not enabled under the current protocol version.
+    #[error("user tags are not enabled")]
+    UserTagsNotEnabled,
+    /// The deploy's gas limit exceeds what is left of its account's computation quota.
+    #[error(
+        "gas limit {gas_limit} exceeds remaining computation quota {remaining} of {account_hash}"
+    )]
+    ExceededComputationQuota {
+        account_hash: AccountHash,
+        gas_limit: U512,
+        remaining: U512,
+    },
+    /// The deploy's account has an unknown computation quota.
+    #[error("unable to determine remaining computation quota of {account_hash}")]
+    UnknownComputationQuota { account_hash: AccountHash },
+    /// A non-optional runtime argument of the called entry point is missing.
+    #[error("missing runtime argument '{arg_name}' of entry point {entry_point}")]
+    MissingEntryPointArgument {
+        entry_point: String,
+        arg_name: String,
+    },
+    /// A runtime argument doesn't have the type declared by the called entry point.
+    #[error(
+        "runtime argument '{arg_name}' of entry point {entry_point} should be {expected:?}, \
+        found {found:?}"
+    )]
+    EntryPointArgumentTypeMismatch {
+        entry_point: String,
+        arg_name: String,
+        expected: CLType,
+        found: CLType,
+    },
 }
 
 /// A helper trait constraining `DeployAcceptor` compatible reactor events.
@@ -128,6 +202,8 @@ pub(crate) trait ReactorEventT:
     + From<DeployAcceptorAnnouncement>
     + From<StorageRequest>
     + From<ContractRuntimeRequest>
+    + From<FetcherRequest<DeployApprovals>>
+    + From<NetworkInfoRequest>
     + From<FatalAnnouncement>
     + Send
 {
@@ -138,6 +214,8 @@ impl<REv> ReactorEventT for REv where
         + From<DeployAcceptorAnnouncement>
         + From<StorageRequest>
         + From<ContractRuntimeRequest>
+        + From<FetcherRequest<DeployApprovals>>
+        + From<NetworkInfoRequest>
         + From<FatalAnnouncement>
         + Send
 {
@@ -185,6 +263,16 @@ pub struct DeployAcceptor {
     protocol_version: ProtocolVersion,
     deploy_config: DeployConfig,
     max_associated_keys: u32,
+    account_sequence_enforced: bool,
+    priority_fees_enabled: bool,
+    user_tags_enabled: bool,
+    session_code_policy: SessionCodePolicy,
+    payment_lanes: PaymentLanes,
+    computation_quota_per_era: u64,
+    wasmless_transfer_cost: u32,
+    strict_argument_checking: bool,
+    #[data_size(skip)]
+    signature_verifier: SignatureVerifier,
     #[data_size(skip)]
     metrics: metrics::Metrics,
 }
@@ -192,6 +280,7 @@ pub struct DeployAcceptor {
 impl DeployAcceptor {
     pub(crate) fn new(
         chainspec: &Chainspec,
+        signature_verifier: SignatureVerifier,
         registry: &Registry,
     ) -> Result<Self, prometheus::Error> {
         Ok(DeployAcceptor {
@@ -199,6 +288,22 @@ impl DeployAcceptor {
             protocol_version: chainspec.protocol_version(),
             deploy_config: chainspec.deploy_config,
             max_associated_keys: chainspec.core_config.max_associated_keys,
+            account_sequence_enforced: chainspec
+                .core_config
+                .replay_protection
+                .account_sequence_enforced(),
+            priority_fees_enabled: chainspec.protocol_version()
+                >= chainspec.core_config.priority_fees_version,
+            user_tags_enabled: chainspec.protocol_version()
+                >= chainspec.core_config.user_tags_version,
+            session_code_policy: chainspec.core_config.session_code_policy.clone(),
+            payment_lanes: chainspec
+                .core_config
+                .active_payment_lanes(chainspec.protocol_version()),
+            computation_quota_per_era: chainspec.core_config.computation_quota_per_era,
+            wasmless_transfer_cost: chainspec.system_costs_config.wasmless_transfer_cost(),
+            strict_argument_checking: chainspec.core_config.strict_argument_checking,
+            signature_verifier,
             metrics: metrics::Metrics::new(registry)?,
         })
     }
@@ -231,6 +336,37 @@ impl DeployAcceptor {
             );
         }
 
+        if !self
+            .session_code_policy
+            .is_allowed(deploy.payment(), Phase::Payment)
+            || !self
+                .session_code_policy
+                .is_allowed(deploy.session(), Phase::Session)
+        {
+            debug!(%deploy, "deploy carries code not permitted by the session code policy");
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                EventMetadata::new(deploy, source, maybe_responder),
+                Error::DisallowedSessionCode,
+                verification_start_timestamp,
+            );
+        }
+
+        if self.payment_lanes.is_enabled()
+            && self
+                .payment_lanes
+                .lane_for_session(deploy.session())
+                .is_none()
+        {
+            debug!(%deploy, "deploy is not admitted by any payment lane");
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                EventMetadata::new(deploy, source, maybe_responder),
+                Error::NoPaymentLane,
+                verification_start_timestamp,
+            );
+        }
+
         // We only perform expiry checks on deploys received from the client.
         if source.is_client() {
             let current_node_timestamp = Timestamp::now();
@@ -282,7 +418,25 @@ impl DeployAcceptor {
         let account_hash = event_metadata.deploy.header().account().to_account_hash();
         let account_key = account_hash.into();
 
-        if event_metadata.source.is_client() {
+        // Deploys fetched from peers may already be included in a block, whereas new ones from
+        // clients or gossiped by peers must fit into what is left of their account's quota.
+        let is_new_deploy = matches!(
+            event_metadata.source,
+            Source::Client | Source::PeerGossiped(_)
+        );
+        if is_new_deploy && self.computation_quota_per_era > 0 {
+            effect_builder
+                .get_remaining_computation_quota(prestate_hash, account_hash)
+                .event(
+                    move |maybe_remaining_quota| Event::GetRemainingComputationQuotaResult {
+                        event_metadata,
+                        prestate_hash,
+                        maybe_remaining_quota,
+                        account_hash,
+                        verification_start_timestamp,
+                    },
+                )
+        } else if event_metadata.source.is_client() {
             effect_builder
                 .get_account_from_global_state(prestate_hash, account_key)
                 .event(move |maybe_account| Event::GetAccountResult {
@@ -301,6 +455,73 @@ impl DeployAcceptor {
         }
     }
 
+    /// Checks that the gas limit of a deploy received from a client or gossiped by a peer doesn't
+    /// exceed what is left of its account's computation quota.
+    ///
+    /// The quota may yet be used up by other deploys of the account before this one is executed;
+    /// the engine enforces the quota at execution time.
+    fn handle_get_remaining_computation_quota_result<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        event_metadata: EventMetadata,
+        prestate_hash: Digest,
+        maybe_remaining_quota: Option<Gas>,
+        account_hash: AccountHash,
+        verification_start_timestamp: Timestamp,
+    ) -> Effects<Event> {
+        let deploy = &event_metadata.deploy;
+        let maybe_gas_limit = if deploy.session().is_transfer() {
+            Some(Gas::from(self.wasmless_transfer_cost))
+        } else {
+            deploy.payment().payment_amount(deploy.header().gas_price())
+        };
+        let failure = match (maybe_remaining_quota, maybe_gas_limit) {
+            (_, None) => Some(DeployParameterFailure::MissingPaymentAmount),
+            (None, Some(_)) => {
+                Some(DeployParameterFailure::UnknownComputationQuota { account_hash })
+            }
+            (Some(remaining), Some(gas_limit)) if gas_limit > remaining => {
+                Some(DeployParameterFailure::ExceededComputationQuota {
+                    account_hash,
+                    gas_limit: gas_limit.value(),
+                    remaining: remaining.value(),
+                })
+            }
+            (Some(_), Some(_)) => None,
+        };
+
+        if let Some(failure) = failure {
+            debug!(?account_hash, %failure, "computation quota exceeded");
+            let error = Error::InvalidDeployParameters {
+                prestate_hash,
+                failure,
+            };
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                event_metadata,
+                error,
+                verification_start_timestamp,
+            );
+        }
+
+        if !event_metadata.source.is_client() {
+            return self.verify_payment_logic(
+                effect_builder,
+                event_metadata,
+                prestate_hash,
+                verification_start_timestamp,
+            );
+        }
+        effect_builder
+            .get_account_from_global_state(prestate_hash, account_hash.into())
+            .event(move |maybe_account| Event::GetAccountResult {
+                event_metadata,
+                maybe_account,
+                prestate_hash,
+                verification_start_timestamp,
+            })
+    }
+
     fn handle_get_account_result<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -328,49 +549,202 @@ impl DeployAcceptor {
                 )
             }
             Some(account) => {
-                let authorization_keys = event_metadata
-                    .deploy
-                    .approvals()
-                    .iter()
-                    .map(|approval| approval.signer().to_account_hash())
-                    .collect();
-                if !account.can_authorize(&authorization_keys) {
-                    let error = Error::InvalidDeployParameters {
-                        prestate_hash,
-                        failure: DeployParameterFailure::InvalidAssociatedKeys,
-                    };
-                    debug!(?authorization_keys, "account authorization invalid");
-                    return self.handle_invalid_deploy_result(
-                        effect_builder,
-                        event_metadata,
-                        error,
-                        verification_start_timestamp,
-                    );
-                }
-                if !account.can_deploy_with(&authorization_keys) {
-                    let error = Error::InvalidDeployParameters {
-                        prestate_hash,
-                        failure: DeployParameterFailure::InsufficientDeploySignatureWeight,
-                    };
-                    debug!(?authorization_keys, "insufficient deploy signature weight");
-                    return self.handle_invalid_deploy_result(
-                        effect_builder,
-                        event_metadata,
-                        error,
-                        verification_start_timestamp,
-                    );
+                let collect_missing_approvals = event_metadata.source.is_client();
+                self.verify_account_authorization(
+                    effect_builder,
+                    event_metadata,
+                    prestate_hash,
+                    account,
+                    collect_missing_approvals,
+                    verification_start_timestamp,
+                )
+            }
+        }
+    }
+
+    /// Checks that the deploy's approvals authorize it on behalf of `account`.
+    ///
+    /// If the signers are associated keys of the account but lack the weight to deploy, and
+    /// `collect_missing_approvals` is set, approvals of the same deploy submitted to other nodes
+    /// are collected from peers before checking again.  This is only done for deploys received
+    /// from clients: deploys gossiped by peers must carry sufficient approvals already.
+    fn verify_account_authorization<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        event_metadata: EventMetadata,
+        prestate_hash: Digest,
+        account: Account,
+        collect_missing_approvals: bool,
+        verification_start_timestamp: Timestamp,
+    ) -> Effects<Event> {
+        let authorization_keys = event_metadata
+            .deploy
+            .approvals()
+            .iter()
+            .map(|approval| approval.signer().to_account_hash())
+            .collect();
+        if !account.can_authorize(&authorization_keys) {
+            let error = Error::InvalidDeployParameters {
+                prestate_hash,
+                failure: DeployParameterFailure::InvalidAssociatedKeys,
+            };
+            debug!(?authorization_keys, "account authorization invalid");
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                event_metadata,
+                error,
+                verification_start_timestamp,
+            );
+        }
+        if !account.can_deploy_with(&authorization_keys) {
+            if collect_missing_approvals {
+                debug!(?authorization_keys, "collecting missing deploy approvals");
+                return self.collect_missing_approvals(
+                    effect_builder,
+                    event_metadata,
+                    prestate_hash,
+                    account,
+                    verification_start_timestamp,
+                );
+            }
+            let error = Error::InvalidDeployParameters {
+                prestate_hash,
+                failure: DeployParameterFailure::InsufficientDeploySignatureWeight,
+            };
+            debug!(?authorization_keys, "insufficient deploy signature weight");
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                event_metadata,
+                error,
+                verification_start_timestamp,
+            );
+        }
+        effect_builder
+            .check_purse_balance(prestate_hash, account.main_purse())
+            .event(move |maybe_balance_value| Event::GetBalanceResult {
+                event_metadata,
+                prestate_hash,
+                maybe_balance_value,
+                account_hash: account.account_hash(),
+                verification_start_timestamp,
+            })
+    }
+
+    /// Records the deploy's approvals, so that peers collecting for the same deploy can fetch
+    /// them, then fetches the approvals held by up to `MISSING_APPROVALS_PEER_COUNT` peers.
+    ///
+    /// The client isn't kept waiting on peers: it is told right away that the deploy's approvals
+    /// lack the weight to authorize it, and the deploy is accepted and gossiped if the collected
+    /// approvals make up the missing weight.  Only approvals by associated keys of the account are
+    /// collected, at most one per key, and they are discarded once the deploy expires.
+    fn collect_missing_approvals<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        mut event_metadata: EventMetadata,
+        prestate_hash: Digest,
+        account: Account,
+        verification_start_timestamp: Timestamp,
+    ) -> Effects<Event> {
+        let mut effects = Effects::new();
+        if let Some(responder) = event_metadata.maybe_responder.take() {
+            let error = Error::InvalidDeployParameters {
+                prestate_hash,
+                failure: DeployParameterFailure::InsufficientDeploySignatureWeight,
+            };
+            effects.extend(responder.respond(Err(error)).ignore());
+        }
+
+        let deploy_hash = *event_metadata.deploy.hash();
+        let expires = event_metadata.deploy.header().expires();
+        let associated_keys = account.associated_keys().clone();
+        let approvals = event_metadata.deploy.approvals().clone();
+        let collect = async move {
+            let mut all_approvals = effect_builder
+                .put_deploy_approvals_to_storage(
+                    deploy_hash,
+                    expires,
+                    associated_keys.len(),
+                    approvals,
+                )
+                .await;
+            let peers = effect_builder
+                .get_fully_connected_peers(MISSING_APPROVALS_PEER_COUNT)
+                .await;
+            let fetch_results = join_all(peers.into_iter().map(|peer| {
+                effect_builder.fetch::<DeployApprovals>(deploy_hash, peer, EmptyValidationMetadata)
+            }))
+            .await;
+            let mut fetched_approvals = BTreeSet::new();
+            for fetch_result in fetch_results {
+                match fetch_result {
+                    Ok(FetchedData::FromStorage { item })
+                    | Ok(FetchedData::FromPeer { item, .. }) => fetched_approvals.extend(
+                        item.into_approvals().into_iter().filter(|approval| {
+                            associated_keys.contains_key(&approval.signer().to_account_hash())
+                        }),
+                    ),
+                    Err(error) => debug!(%error, "failed to fetch deploy approvals"),
                 }
-                effect_builder
-                    .check_purse_balance(prestate_hash, account.main_purse())
-                    .event(move |maybe_balance_value| Event::GetBalanceResult {
-                        event_metadata,
-                        prestate_hash,
-                        maybe_balance_value,
-                        account_hash: account.account_hash(),
-                        verification_start_timestamp,
-                    })
             }
+            if !fetched_approvals.is_subset(&all_approvals) {
+                all_approvals = effect_builder
+                    .put_deploy_approvals_to_storage(
+                        deploy_hash,
+                        expires,
+                        associated_keys.len(),
+                        fetched_approvals,
+                    )
+                    .await;
+            }
+            all_approvals
+        };
+        effects.extend(collect.event(move |approvals| Event::GotMissingApprovals {
+            event_metadata,
+            prestate_hash,
+            account,
+            approvals,
+            verification_start_timestamp,
+        }));
+        effects
+    }
+
+    /// Adds the collected approvals by associated keys of the account to the deploy, and checks
+    /// its authorization once more.
+    fn handle_got_missing_approvals<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        mut event_metadata: EventMetadata,
+        prestate_hash: Digest,
+        account: Account,
+        approvals: BTreeSet<Approval>,
+        verification_start_timestamp: Timestamp,
+    ) -> Effects<Event> {
+        let mut all_approvals: BTreeSet<Approval> = approvals
+            .into_iter()
+            .filter(|approval| {
+                account
+                    .associated_keys()
+                    .contains_key(&approval.signer().to_account_hash())
+            })
+            .collect();
+        all_approvals.extend(event_metadata.deploy.approvals().iter().cloned());
+        if all_approvals.len() > event_metadata.deploy.approvals().len() {
+            debug!(
+                deploy_hash = %event_metadata.deploy.hash(),
+                approval_count = all_approvals.len(),
+                "collected missing deploy approvals"
+            );
+            event_metadata.deploy =
+                Box::new((*event_metadata.deploy).with_approvals(all_approvals));
         }
+        self.verify_account_authorization(
+            effect_builder,
+            event_metadata,
+            prestate_hash,
+            account,
+            false,
+            verification_start_timestamp,
+        )
     }
 
     fn handle_get_balance_result<REv: ReactorEventT>(
@@ -407,7 +781,9 @@ impl DeployAcceptor {
                 )
             }
             Some(balance) => {
-                let has_minimum_balance = balance >= *MAX_PAYMENT;
+                // Nothing is charged on gas-free chains, so there is no minimum balance.
+                let has_minimum_balance =
+                    self.computation_quota_per_era > 0 || balance >= *MAX_PAYMENT;
                 if !has_minimum_balance {
                     let error = Error::InvalidDeployParameters {
                         prestate_hash,
@@ -420,6 +796,16 @@ impl DeployAcceptor {
                         error,
                         verification_start_timestamp,
                     )
+                } else if self.account_sequence_enforced {
+                    effect_builder
+                        .get_account_sequence_from_global_state(prestate_hash, account_hash)
+                        .event(move |maybe_last_sequence| Event::GetAccountSequenceResult {
+                            event_metadata,
+                            prestate_hash,
+                            maybe_last_sequence,
+                            account_hash,
+                            verification_start_timestamp,
+                        })
                 } else {
                     self.verify_payment_logic(
                         effect_builder,
@@ -432,6 +818,89 @@ impl DeployAcceptor {
         }
     }
 
+    /// Checks that a deploy received from a client carries a sequence number which hasn't been
+    /// used by its account yet.
+    ///
+    /// Sequence numbers further ahead than the next one are accepted, since the account may have
+    /// other deploys which are not yet executed; the engine enforces the strict ordering.
+    fn handle_get_account_sequence_result<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        event_metadata: EventMetadata,
+        prestate_hash: Digest,
+        maybe_last_sequence: Option<u64>,
+        account_hash: AccountHash,
+        verification_start_timestamp: Timestamp,
+    ) -> Effects<Event> {
+        let failure = match (
+            maybe_last_sequence,
+            event_metadata
+                .deploy
+                .payment()
+                .args()
+                .get(ARG_ACCOUNT_SEQUENCE),
+        ) {
+            (_, None) => Some(DeployParameterFailure::MissingAccountSequence),
+            (None, Some(_)) => {
+                Some(DeployParameterFailure::UnknownAccountSequence { account_hash })
+            }
+            (Some(last), Some(value)) => match value.clone().into_t::<u64>() {
+                Ok(provided) if provided > last => None,
+                Ok(provided) => Some(DeployParameterFailure::StaleAccountSequence {
+                    account_hash,
+                    last,
+                    provided,
+                }),
+                Err(_) => Some(DeployParameterFailure::FailedToParseAccountSequence),
+            },
+        };
+
+        if let Some(failure) = failure {
+            debug!(?account_hash, %failure, "invalid account sequence");
+            let error = Error::InvalidDeployParameters {
+                prestate_hash,
+                failure,
+            };
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                event_metadata,
+                error,
+                verification_start_timestamp,
+            );
+        }
+
+        self.verify_payment_logic(
+            effect_builder,
+            event_metadata,
+            prestate_hash,
+            verification_start_timestamp,
+        )
+    }
+
+    /// Checks the priority fee offered by `deploy`, if any.
+    fn check_priority_fee(&self, deploy: &Deploy) -> Option<DeployParameterFailure> {
+        if deploy.priority_fee().is_zero() {
+            None
+        } else if !self.priority_fees_enabled {
+            Some(DeployParameterFailure::PriorityFeesNotEnabled)
+        } else if deploy.session().is_transfer() {
+            Some(DeployParameterFailure::PriorityFeeOnTransfer)
+        } else {
+            None
+        }
+    }
+
+    /// Checks the user tag of `deploy`, if any.
+    ///
+    /// Its length is checked along with the rest of the deploy's configuration.
+    fn check_user_tag(&self, deploy: &Deploy) -> Option<DeployParameterFailure> {
+        if deploy.user_tag().is_some() && !self.user_tags_enabled {
+            Some(DeployParameterFailure::UserTagsNotEnabled)
+        } else {
+            None
+        }
+    }
+
     fn verify_payment_logic<REv: ReactorEventT>(
         &self,
         effect_builder: EffectBuilder<REv>,
@@ -446,6 +915,26 @@ impl DeployAcceptor {
             failure,
         };
 
+        if let Some(failure) = self.check_priority_fee(&event_metadata.deploy) {
+            debug!(%failure, "invalid priority fee in payment logic");
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                event_metadata,
+                make_error(failure),
+                verification_start_timestamp,
+            );
+        }
+
+        if let Some(failure) = self.check_user_tag(&event_metadata.deploy) {
+            debug!(%failure, "invalid user tag in payment logic");
+            return self.handle_invalid_deploy_result(
+                effect_builder,
+                event_metadata,
+                make_error(failure),
+                verification_start_timestamp,
+            );
+        }
+
         match payment {
             ExecutableDeployItem::Transfer { .. } => {
                 debug!("invalid payment variant in payment logic");
@@ -656,23 +1145,55 @@ impl DeployAcceptor {
         verification_start_timestamp: Timestamp,
     ) -> Effects<Event> {
         if let Some(contract) = maybe_contract {
-            if !contract.entry_points().has_entry_point(&entry_point) {
-                debug!(
-                    ?entry_point,
-                    ?contract_hash,
-                    ?prestate_hash,
-                    "missing entry point in contract"
-                );
-                let error = Error::InvalidDeployParameters {
-                    prestate_hash,
-                    failure: DeployParameterFailure::NonexistentContractEntryPoint { entry_point },
+            let entry_point = match contract.entry_point(&entry_point) {
+                Some(entry_point) => entry_point,
+                None => {
+                    debug!(
+                        ?entry_point,
+                        ?contract_hash,
+                        ?prestate_hash,
+                        "missing entry point in contract"
+                    );
+                    let error = Error::InvalidDeployParameters {
+                        prestate_hash,
+                        failure: DeployParameterFailure::NonexistentContractEntryPoint {
+                            entry_point,
+                        },
+                    };
+                    return self.handle_invalid_deploy_result(
+                        effect_builder,
+                        event_metadata,
+                        error,
+                        verification_start_timestamp,
+                    );
+                }
+            };
+            // Execution would fail on these arguments in the same way, after charging payment.
+            if self.strict_argument_checking {
+                let deploy = &event_metadata.deploy;
+                let args = if is_payment {
+                    deploy.payment().args()
+                } else {
+                    deploy.session().args()
                 };
-                return self.handle_invalid_deploy_result(
-                    effect_builder,
-                    event_metadata,
-                    error,
-                    verification_start_timestamp,
-                );
+                if let Some(failure) = check_entry_point_args(entry_point, args) {
+                    debug!(
+                        %failure,
+                        ?contract_hash,
+                        ?prestate_hash,
+                        "runtime args don't match entry point"
+                    );
+                    let error = Error::InvalidDeployParameters {
+                        prestate_hash,
+                        failure,
+                    };
+                    return self.handle_invalid_deploy_result(
+                        effect_builder,
+                        event_metadata,
+                        error,
+                        verification_start_timestamp,
+                    );
+                }
             }
             if is_payment {
                 return self.verify_session_logic(
@@ -922,6 +1443,34 @@ impl DeployAcceptor {
     }
 }
 
+/// Checks `args` against the parameters declared by `entry_point` as strict argument checking does
+/// on execution: every non-optional parameter must be provided, and provided parameters must have
+/// the declared type.  Arguments not declared by the entry point are ignored.
+fn check_entry_point_args(
+    entry_point: &EntryPoint,
+    args: &RuntimeArgs,
+) -> Option<DeployParameterFailure> {
+    entry_point
+        .args()
+        .iter()
+        .find_map(|param| match args.get(param.name()) {
+            Some(value) if value.cl_type() != param.cl_type() => {
+                Some(DeployParameterFailure::EntryPointArgumentTypeMismatch {
+                    entry_point: entry_point.name().to_string(),
+                    arg_name: param.name().to_string(),
+                    expected: param.cl_type().clone(),
+                    found: value.cl_type().clone(),
+                })
+            }
+            Some(_) => None,
+            None if param.cl_type().is_option() => None,
+            None => Some(DeployParameterFailure::MissingEntryPointArgument {
+                entry_point: entry_point.name().to_string(),
+                arg_name: param.name().to_string(),
+            }),
+        })
+}
+
 impl<REv: ReactorEventT> Component<REv> for DeployAcceptor {
     type Event = Event;
 
@@ -933,6 +1482,19 @@ impl<REv: ReactorEventT> Component<REv> for DeployAcceptor {
     ) -> Effects<Self::Event> {
         trace!(?event, "DeployAcceptor: handling event");
         match event {
+            Event::Accept {
+                deploy,
+                source,
+                maybe_responder,
+            } if !deploy.is_verification_cached() => self
+                .signature_verifier
+                .clone()
+                .verify_deploy(deploy)
+                .event(move |deploy| Event::Accept {
+                    deploy,
+                    source,
+                    maybe_responder,
+                }),
             Event::Accept {
                 deploy,
                 source,
@@ -948,6 +1510,20 @@ impl<REv: ReactorEventT> Component<REv> for DeployAcceptor {
                 *maybe_block_header,
                 verification_start_timestamp,
             ),
+            Event::GetRemainingComputationQuotaResult {
+                event_metadata,
+                prestate_hash,
+                maybe_remaining_quota,
+                account_hash,
+                verification_start_timestamp,
+            } => self.handle_get_remaining_computation_quota_result(
+                effect_builder,
+                event_metadata,
+                prestate_hash,
+                maybe_remaining_quota,
+                account_hash,
+                verification_start_timestamp,
+            ),
             Event::GetAccountResult {
                 event_metadata,
                 prestate_hash,
@@ -960,6 +1536,20 @@ impl<REv: ReactorEventT> Component<REv> for DeployAcceptor {
                 maybe_account,
                 verification_start_timestamp,
             ),
+            Event::GotMissingApprovals {
+                event_metadata,
+                prestate_hash,
+                account,
+                approvals,
+                verification_start_timestamp,
+            } => self.handle_got_missing_approvals(
+                effect_builder,
+                event_metadata,
+                prestate_hash,
+                account,
+                approvals,
+                verification_start_timestamp,
+            ),
             Event::GetBalanceResult {
                 event_metadata,
                 prestate_hash,
@@ -974,6 +1564,20 @@ impl<REv: ReactorEventT> Component<REv> for DeployAcceptor {
                 account_hash,
                 verification_start_timestamp,
             ),
+            Event::GetAccountSequenceResult {
+                event_metadata,
+                prestate_hash,
+                maybe_last_sequence,
+                account_hash,
+                verification_start_timestamp,
+            } => self.handle_get_account_sequence_result(
+                effect_builder,
+                event_metadata,
+                prestate_hash,
+                maybe_last_sequence,
+                account_hash,
+                verification_start_timestamp,
+            ),
             Event::GetContractResult {
                 event_metadata,
                 prestate_hash,
@@ -1,4 +1,7 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+};
 
 use serde::Serialize;
 
@@ -6,13 +9,14 @@ use super::Source;
 use crate::{
     components::deploy_acceptor::Error,
     effect::{announcements::RpcServerAnnouncement, Responder},
-    types::{BlockHeader, Deploy},
+    types::{Approval, BlockHeader, Deploy},
 };
 
 use casper_hashing::Digest;
 use casper_types::{
     account::{Account, AccountHash},
-    Contract, ContractHash, ContractPackage, ContractPackageHash, ContractVersion, Timestamp, U512,
+    Contract, ContractHash, ContractPackage, ContractPackageHash, ContractVersion, Gas, Timestamp,
+    U512,
 };
 
 /// A utility struct to hold duplicated information across events.
@@ -65,6 +69,15 @@ pub(crate) enum Event {
         maybe_block_header: Box<Option<BlockHeader>>,
         verification_start_timestamp: Timestamp,
     },
+    /// The result of querying the gas left of the computation quota of the `Account` associated
+    /// with the `Deploy`.
+    GetRemainingComputationQuotaResult {
+        event_metadata: EventMetadata,
+        prestate_hash: Digest,
+        maybe_remaining_quota: Option<Gas>,
+        account_hash: AccountHash,
+        verification_start_timestamp: Timestamp,
+    },
     /// The result of querying global state for the `Account` associated with the `Deploy`.
     GetAccountResult {
         event_metadata: EventMetadata,
@@ -72,6 +85,15 @@ pub(crate) enum Event {
         maybe_account: Option<Account>,
         verification_start_timestamp: Timestamp,
     },
+    /// The approvals of the `Deploy` collected from storage and peers, as those it was received
+    /// with lack the weight to authorize it on behalf of the `Account`.
+    GotMissingApprovals {
+        event_metadata: EventMetadata,
+        prestate_hash: Digest,
+        account: Account,
+        approvals: BTreeSet<Approval>,
+        verification_start_timestamp: Timestamp,
+    },
     /// The result of querying the balance of the `Account` associated with the `Deploy`.
     GetBalanceResult {
         event_metadata: EventMetadata,
@@ -80,6 +102,15 @@ pub(crate) enum Event {
         account_hash: AccountHash,
         verification_start_timestamp: Timestamp,
     },
+    /// The result of querying the last sequence number used by the `Account` associated with the
+    /// `Deploy`.
+    GetAccountSequenceResult {
+        event_metadata: EventMetadata,
+        prestate_hash: Digest,
+        maybe_last_sequence: Option<u64>,
+        account_hash: AccountHash,
+        verification_start_timestamp: Timestamp,
+    },
     /// The result of querying global state for a `Contract` to verify the executable logic.
     GetContractResult {
         event_metadata: EventMetadata,
@@ -164,6 +195,13 @@ impl Display for Event {
                     event_metadata.deploy.hash()
                 )
             }
+            Event::GetRemainingComputationQuotaResult { event_metadata, .. } => {
+                write!(
+                    formatter,
+                    "verifying computation quota to validate deploy with hash {}.",
+                    event_metadata.deploy.hash()
+                )
+            }
             Event::GetAccountResult { event_metadata, .. } => {
                 write!(
                     formatter,
@@ -171,6 +209,18 @@ impl Display for Event {
                     event_metadata.deploy.hash()
                 )
             }
+            Event::GotMissingApprovals {
+                event_metadata,
+                approvals,
+                ..
+            } => {
+                write!(
+                    formatter,
+                    "collected {} approvals to validate deploy with hash {}.",
+                    approvals.len(),
+                    event_metadata.deploy.hash()
+                )
+            }
             Event::GetBalanceResult { event_metadata, .. } => {
                 write!(
                     formatter,
@@ -178,6 +228,13 @@ impl Display for Event {
                     event_metadata.deploy.hash()
                 )
             }
+            Event::GetAccountSequenceResult { event_metadata, .. } => {
+                write!(
+                    formatter,
+                    "verifying account sequence to validate deploy with hash {}.",
+                    event_metadata.deploy.hash()
+                )
+            }
             Event::GetContractResult {
                 event_metadata,
                 prestate_hash,
@@ -3,6 +3,7 @@
 use std::{
     collections::{BTreeMap, VecDeque},
     fmt::{self, Debug, Display, Formatter},
+    iter,
     sync::Arc,
     time::Duration,
 };
@@ -26,7 +27,8 @@ use casper_execution_engine::{
 };
 use casper_types::{
     account::{Account, ActionThresholds, AssociatedKeys, Weight},
-    CLValue, StoredValue, URef, U512,
+    CLValue, EntryPointAccess, EntryPointType, Parameter, PublicKey, SecretKey, StoredValue, URef,
+    U512,
 };
 
 use super::*;
@@ -39,7 +41,7 @@ use crate::{
         announcements::{ControlAnnouncement, DeployAcceptorAnnouncement},
         requests::{
             BlockCompleteConfirmationRequest, ContractRuntimeRequest, MakeBlockExecutableRequest,
-            NetworkRequest,
+            NetworkInfoRequest, NetworkRequest,
         },
         Responder,
     },
@@ -76,6 +78,10 @@ enum Event {
     StorageRequest(StorageRequest),
     #[from]
     NetworkRequest(NetworkRequest<Message>),
+    #[from]
+    NetworkInfoRequest(NetworkInfoRequest),
+    #[from]
+    DeployApprovalsFetcherRequest(FetcherRequest<DeployApprovals>),
 }
 
 impl From<MakeBlockExecutableRequest> for Event {
@@ -122,6 +128,12 @@ impl Display for Event {
             }
             Event::StorageRequest(request) => write!(formatter, "storage request: {:?}", request),
             Event::NetworkRequest(request) => write!(formatter, "network request: {:?}", request),
+            Event::NetworkInfoRequest(request) => {
+                write!(formatter, "network info request: {}", request)
+            }
+            Event::DeployApprovalsFetcherRequest(request) => {
+                write!(formatter, "deploy approvals fetcher request: {}", request)
+            }
         }
     }
 }
@@ -167,6 +179,7 @@ enum TestScenario {
     FromClientValidDeploy,
     FromClientRepeatedValidDeploy,
     FromClientAccountWithInsufficientWeight,
+    FromClientAccountWithApprovalsHeldByPeer,
     FromClientAccountWithInvalidAssociatedKeys,
     AccountWithUnknownBalance,
     FromClientCustomPaymentContract(ContractScenario),
@@ -208,6 +221,7 @@ impl TestScenario {
             | TestScenario::FromClientValidDeploy
             | TestScenario::FromClientRepeatedValidDeploy
             | TestScenario::FromClientAccountWithInsufficientWeight
+            | TestScenario::FromClientAccountWithApprovalsHeldByPeer
             | TestScenario::FromClientAccountWithInvalidAssociatedKeys
             | TestScenario::AccountWithUnknownBalance
             | TestScenario::DeployWithoutPaymentAmount
@@ -243,6 +257,7 @@ impl TestScenario {
             | TestScenario::FromClientRepeatedValidDeploy
             | TestScenario::FromClientAccountWithInvalidAssociatedKeys
             | TestScenario::FromClientAccountWithInsufficientWeight
+            | TestScenario::FromClientAccountWithApprovalsHeldByPeer
             | TestScenario::AccountWithUnknownBalance
             | TestScenario::BalanceCheckForDeploySentByPeer => {
                 Deploy::random_valid_native_transfer(rng)
@@ -340,6 +355,7 @@ impl TestScenario {
             | TestScenario::FromPeerAccountWithInvalidAssociatedKeys // account check skipped if from peer
             | TestScenario::FromClientRepeatedValidDeploy
             | TestScenario::FromClientValidDeploy
+            | TestScenario::FromClientAccountWithApprovalsHeldByPeer
             | TestScenario::ShouldAcceptExpiredDeploySentByPeer=> true,
             TestScenario::FromPeerInvalidDeploy
             | TestScenario::FromClientInsufficientBalance
@@ -408,10 +424,33 @@ fn create_account(account_hash: AccountHash, test_scenario: TestScenario) -> Acc
                 invalid_action_threshold,
             )
         }
+        TestScenario::FromClientAccountWithApprovalsHeldByPeer => {
+            let action_thresholds = ActionThresholds::new(Weight::new(100u8), Weight::new(100u8))
+                .expect("should create action threshold");
+            let mut associated_keys = AssociatedKeys::new(account_hash, Weight::new(1));
+            associated_keys
+                .add_key(
+                    PublicKey::from(&peer_held_approval_secret_key()).to_account_hash(),
+                    Weight::new(99),
+                )
+                .expect("should add associated key");
+            Account::new(
+                account_hash,
+                BTreeMap::new(),
+                URef::default(),
+                associated_keys,
+                action_thresholds,
+            )
+        }
         _ => Account::create(account_hash, BTreeMap::new(), URef::default()),
     }
 }
 
+/// The key of the approval a peer holds in `FromClientAccountWithApprovalsHeldByPeer`.
+fn peer_held_approval_secret_key() -> SecretKey {
+    SecretKey::ed25519_from_bytes([7; SecretKey::ED25519_LENGTH]).expect("should create key")
+}
+
 struct Reactor {
     storage: Storage,
     deploy_acceptor: DeployAcceptor,
@@ -436,7 +475,12 @@ impl reactor::Reactor for Reactor {
         let (storage_config, storage_tempdir) = storage::Config::default_for_tests();
         let storage_withdir = WithDir::new(storage_tempdir.path(), storage_config);
 
-        let deploy_acceptor = DeployAcceptor::new(chainspec.as_ref(), registry).unwrap();
+        let deploy_acceptor = DeployAcceptor::new(
+            chainspec.as_ref(),
+            SignatureVerifier::new().unwrap(),
+            registry,
+        )
+        .unwrap();
 
         let storage = Storage::new(
             &storage_withdir,
@@ -631,6 +675,32 @@ impl reactor::Reactor for Reactor {
                 _ => panic!("should not receive {:?}", event),
             },
             Event::NetworkRequest(_) => panic!("test does not handle network requests"),
+            Event::NetworkInfoRequest(NetworkInfoRequest::FullyConnectedPeers {
+                responder,
+                ..
+            }) => {
+                let peers = if self.test_scenario
+                    == TestScenario::FromClientAccountWithApprovalsHeldByPeer
+                {
+                    vec![NodeId::random(rng)]
+                } else {
+                    vec![]
+                };
+                responder.respond(peers).ignore()
+            }
+            Event::NetworkInfoRequest(request) => panic!("should not receive {}", request),
+            Event::DeployApprovalsFetcherRequest(FetcherRequest {
+                id,
+                peer,
+                responder,
+                ..
+            }) => {
+                let approval = Approval::create(&id, &peer_held_approval_secret_key());
+                let item = DeployApprovals::new(id, iter::once(approval).collect());
+                responder
+                    .respond(Ok(FetchedData::from_peer(item, peer)))
+                    .ignore()
+            }
         }
     }
 }
@@ -907,7 +977,8 @@ async fn run_deploy_acceptor_without_timeout(
             }
             // Check that a, new and valid, deploy sent by a client raises an `AcceptedNewDeploy`
             // announcement with the appropriate source.
-            TestScenario::FromClientValidDeploy => {
+            TestScenario::FromClientValidDeploy
+            | TestScenario::FromClientAccountWithApprovalsHeldByPeer => {
                 matches!(
                     event,
                     Event::DeployAcceptorAnnouncement(
@@ -1060,6 +1131,20 @@ async fn should_reject_valid_deploy_from_client_for_account_with_insufficient_we
     ))
 }
 
+#[tokio::test]
+async fn should_accept_valid_deploy_from_client_with_missing_approvals_held_by_peer() {
+    // The client isn't kept waiting while the approvals are collected, but the deploy is accepted
+    // once they are.
+    let result = run_deploy_acceptor(TestScenario::FromClientAccountWithApprovalsHeldByPeer).await;
+    assert!(matches!(
+        result,
+        Err(super::Error::InvalidDeployParameters {
+            failure: DeployParameterFailure::InsufficientDeploySignatureWeight,
+            ..
+        })
+    ))
+}
+
 #[tokio::test]
 async fn should_reject_valid_deploy_from_client_for_insufficient_balance() {
     let result = run_deploy_acceptor(TestScenario::FromClientInsufficientBalance).await;
@@ -1563,3 +1648,43 @@ async fn should_panic_when_balance_checking_for_deploy_sent_by_peer() {
     let result = run_deploy_acceptor(test_scenario).await;
     assert!(result.is_ok())
 }
+
+#[test]
+fn should_check_runtime_args_against_entry_point() {
+    let entry_point = EntryPoint::new(
+        "transfer",
+        vec![
+            Parameter::new("amount", CLType::U512),
+            Parameter::new("memo", CLType::Option(Box::new(CLType::String))),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    );
+
+    let mut args = RuntimeArgs::new();
+    assert_eq!(
+        check_entry_point_args(&entry_point, &args),
+        Some(DeployParameterFailure::MissingEntryPointArgument {
+            entry_point: "transfer".to_string(),
+            arg_name: "amount".to_string(),
+        })
+    );
+
+    args.insert("amount", 10_u64).unwrap();
+    assert_eq!(
+        check_entry_point_args(&entry_point, &args),
+        Some(DeployParameterFailure::EntryPointArgumentTypeMismatch {
+            entry_point: "transfer".to_string(),
+            arg_name: "amount".to_string(),
+            expected: CLType::U512,
+            found: CLType::U64,
+        })
+    );
+
+    // The optional "memo" may be omitted, and undeclared args are ignored.
+    let mut args = RuntimeArgs::new();
+    args.insert("amount", U512::from(10)).unwrap();
+    args.insert("unused", true).unwrap();
+    assert_eq!(check_entry_point_args(&entry_point, &args), None);
+}
@@ -1,5 +1,6 @@
 mod config;
 mod event;
+mod lookback;
 mod metrics;
 #[cfg(test)]
 mod tests;
@@ -9,6 +10,7 @@ use std::{
     convert::TryInto,
     iter::FromIterator,
     mem,
+    path::{Path, PathBuf},
 };
 
 use datasize::DataSize;
@@ -16,9 +18,11 @@ use futures::FutureExt;
 use itertools::Itertools;
 use prometheus::Registry;
 use smallvec::smallvec;
+use tokio::task;
 use tracing::{debug, error, info, warn};
 
-use casper_types::Timestamp;
+use casper_execution_engine::core::engine_state::PaymentLanes;
+use casper_types::{EraId, Gas, PublicKey, Timestamp};
 
 use crate::{
     components::{
@@ -44,6 +48,7 @@ use crate::{
 pub(crate) use config::Config;
 pub(crate) use event::Event;
 
+use lookback::{Lookback, LOOKBACK_FILENAME, LOOKBACK_PERSIST_DELAY};
 use metrics::Metrics;
 
 const COMPONENT_NAME: &str = "deploy_buffer";
@@ -55,6 +60,8 @@ pub(crate) struct DeployBuffer {
     state: ComponentState,
     cfg: Config,
     deploy_config: DeployConfig,
+    payment_lanes: PaymentLanes,
+    computation_quota_per_era: u64,
     // Keeps track of all deploys the buffer is currently aware of.
     //
     // `hold` and `dead` are used to filter it on demand as necessary.
@@ -78,6 +85,18 @@ pub(crate) struct DeployBuffer {
     // if we have no block_height gaps but our earliest block_time is less than now - ttl,
     // we do NOT have full TTL awareness.
     chain_index: BTreeMap<u64, Timestamp>,
+    // file the held and dead deploys are persisted to, so that they survive a restart
+    lookback_path: Option<PathBuf>,
+    // whether the held or dead deploys changed since they were last persisted
+    lookback_dirty: bool,
+    // whether persisting the held and dead deploys is scheduled or in progress
+    lookback_persisting: bool,
+    // the era the next block is expected in, if execution is limited by computation quotas
+    quota_era: Option<EraId>,
+    // the account and gas limit of each deploy included in a block of `quota_era`, which count
+    // towards the computation quotas of the accounts in that era
+    #[data_size(skip)]
+    quota_usage: HashMap<DeployHash, (PublicKey, Gas)>,
     // deploy buffer metrics
     #[data_size(skip)]
     metrics: Metrics,
@@ -87,6 +106,8 @@ impl DeployBuffer {
     /// Create a deploy buffer for fun and profit.
     pub(crate) fn new(
         deploy_config: DeployConfig,
+        payment_lanes: PaymentLanes,
+        computation_quota_per_era: u64,
         cfg: Config,
         registry: &Registry,
     ) -> Result<Self, prometheus::Error> {
@@ -94,14 +115,28 @@ impl DeployBuffer {
             state: ComponentState::Uninitialized,
             cfg,
             deploy_config,
+            payment_lanes,
+            computation_quota_per_era,
             buffer: HashMap::new(),
             hold: BTreeMap::new(),
             dead: HashSet::new(),
             chain_index: BTreeMap::new(),
+            lookback_path: None,
+            lookback_dirty: false,
+            lookback_persisting: false,
+            quota_era: None,
+            quota_usage: HashMap::new(),
             metrics: Metrics::new(registry)?,
         })
     }
 
+    /// Persists the deploys held for proposed blocks or included in blocks to a file in
+    /// `storage_dir`, restoring them when initializing after a restart.
+    pub(crate) fn with_lookback(mut self, storage_dir: &Path) -> Self {
+        self.lookback_path = Some(storage_dir.join(LOOKBACK_FILENAME));
+        self
+    }
+
     pub(crate) fn initialize_component(
         &mut self,
         effect_builder: EffectBuilder<MainEvent>,
@@ -133,6 +168,7 @@ impl DeployBuffer {
                 blocks = ?blocks.iter().map(|b| b.height()).collect_vec(),
                 "DeployBuffer: initialization"
             );
+            self.restore_lookback();
             info!("initialized {}", <Self as Component<MainEvent>>::name(self));
             let event = Event::Initialize(blocks);
             return Some(smallvec![async {
@@ -153,6 +189,101 @@ impl DeployBuffer {
         None
     }
 
+    /// Restores the held and dead deploys persisted before a restart which haven't expired yet.
+    fn restore_lookback(&mut self) {
+        let path = match &self.lookback_path {
+            Some(path) => path,
+            None => return,
+        };
+        let mut lookback = match Lookback::read(path) {
+            Ok(Some(lookback)) => lookback,
+            Ok(None) => return,
+            Err(error) => {
+                warn!(%error, path = %path.display(), "DeployBuffer: could not restore lookback");
+                return;
+            }
+        };
+        lookback.retain_unexpired(Timestamp::now(), self.deploy_config.max_ttl);
+        info!(
+            held = lookback.held.values().map(HashSet::len).sum::<usize>(),
+            included = lookback.included.len(),
+            "DeployBuffer: restored lookback"
+        );
+        for (timestamp, deploy_hashes) in lookback.held {
+            self.hold
+                .entry(timestamp)
+                .or_default()
+                .extend(deploy_hashes);
+        }
+        for (deploy_hash, expiry_time) in lookback.included {
+            self.buffer
+                .entry(deploy_hash)
+                .or_insert((expiry_time, None));
+            self.dead.insert(deploy_hash);
+        }
+    }
+
+    /// Marks the held and dead deploys as changed, scheduling them to be persisted if enabled.
+    ///
+    /// Changes are collected for `LOOKBACK_PERSIST_DELAY`, and only one write is in progress at a
+    /// time.
+    fn schedule_persist_lookback<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event>
+    where
+        REv: Send,
+    {
+        if self.lookback_path.is_none() {
+            return Effects::new();
+        }
+        self.lookback_dirty = true;
+        if self.lookback_persisting {
+            return Effects::new();
+        }
+        self.lookback_persisting = true;
+        effect_builder
+            .set_timeout(LOOKBACK_PERSIST_DELAY)
+            .event(|_| Event::PersistLookback)
+    }
+
+    /// Persists the held and dead deploys on the blocking thread pool.
+    fn persist_lookback(&mut self) -> Effects<Event> {
+        let path = match &self.lookback_path {
+            Some(path) => path.clone(),
+            None => return Effects::new(),
+        };
+        self.lookback_dirty = false;
+        let included = self
+            .dead
+            .iter()
+            .filter_map(|deploy_hash| {
+                self.buffer
+                    .get(deploy_hash)
+                    .map(|(expiry_time, _)| (*deploy_hash, *expiry_time))
+            })
+            .collect();
+        let lookback = Lookback {
+            held: self.hold.clone(),
+            included,
+        };
+        async move {
+            let write_path = path.clone();
+            match task::spawn_blocking(move || lookback.write(&write_path)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(error)) => {
+                    warn!(
+                        %error,
+                        path = %path.display(),
+                        "DeployBuffer: could not persist lookback"
+                    )
+                }
+                Err(error) => warn!(%error, "failed to join tokio task"),
+            }
+        }
+        .event(|()| Event::LookbackPersisted)
+    }
+
     /// Returns `true` if we have enough information to participate in consensus in the era after
     /// the `switch block`.
     ///
@@ -231,13 +362,21 @@ impl DeployBuffer {
         });
         self.hold.retain(|_, remaining| !remaining.is_empty());
 
+        // only deploys we accepted ourselves and which never made it into a block were dropped;
+        // the rest were either included or only ever known to us via a block
+        let dropped = freed
+            .iter()
+            .filter(|(deploy_hash, (_, maybe_data))| {
+                maybe_data.is_some() && !self.dead.contains(*deploy_hash)
+            })
+            .map(|(deploy_hash, _)| *deploy_hash)
+            .collect();
+
         self.dead
             .retain(|deploy_hash| !freed.contains_key(deploy_hash));
         self.buffer = buffer;
 
-        let mut effects = effect_builder
-            .announce_expired_deploys(freed.keys().cloned().collect())
-            .ignore();
+        let mut effects = effect_builder.announce_expired_deploys(dropped).ignore();
         effects.extend(
             effect_builder
                 .set_timeout(self.cfg.expiry_check_interval().into())
@@ -350,6 +489,11 @@ impl DeployBuffer {
         let block_height = block.header().height();
         let timestamp = block.timestamp();
         debug!(%timestamp, "DeployBuffer: register_block({}) timestamp finalized", block_height);
+        self.register_quota_usage(
+            block.header().era_id(),
+            block.header().is_switch_block(),
+            block.deploy_and_transfer_hashes(),
+        );
         self.register_deploys(block_height, timestamp, block.deploy_and_transfer_hashes());
     }
 
@@ -358,6 +502,11 @@ impl DeployBuffer {
         let block_height = finalized_block.height();
         let timestamp = finalized_block.timestamp();
         debug!(%timestamp, "DeployBuffer: register_block_finalized({}) timestamp finalized", block_height);
+        self.register_quota_usage(
+            finalized_block.era_id(),
+            finalized_block.era_report().is_some(),
+            finalized_block.deploy_and_transfer_hashes(),
+        );
         self.register_deploys(
             block_height,
             timestamp,
@@ -365,10 +514,60 @@ impl DeployBuffer {
         );
     }
 
-    /// Returns eligible deploys that are buffered and not held or dead.
+    /// Records the gas limits of a block's deploys against their accounts' computation quotas.
+    ///
+    /// Quotas replenish at era boundaries, so the usage is dropped once the block is a switch
+    /// block or a block of a later era arrives; blocks of earlier eras are ignored. The same block
+    /// may be registered both when finalized and when executed, hence usage is keyed by deploy.
+    fn register_quota_usage<'a>(
+        &mut self,
+        era_id: EraId,
+        is_switch_block: bool,
+        deploy_hashes: impl Iterator<Item = &'a DeployHash>,
+    ) {
+        if self.computation_quota_per_era == 0 {
+            return;
+        }
+        if self.quota_era.map_or(false, |quota_era| era_id < quota_era) {
+            return;
+        }
+        if is_switch_block {
+            self.quota_era = Some(era_id.successor());
+            self.quota_usage.clear();
+            return;
+        }
+        if self.quota_era != Some(era_id) {
+            self.quota_era = Some(era_id);
+            self.quota_usage.clear();
+        }
+        for deploy_hash in deploy_hashes {
+            if let Some((_, Some((footprint, _)))) = self.buffer.get(deploy_hash) {
+                if !footprint.is_transfer {
+                    let account = footprint.header.account().clone();
+                    self.quota_usage
+                        .insert(*deploy_hash, (account, footprint.gas_estimate));
+                }
+            }
+        }
+    }
+
+    /// Returns the gas each account has used of its computation quota in the current era, bounded
+    /// by the gas limits of the included deploys the buffer knows of.
+    fn account_quota_usage(&self) -> HashMap<PublicKey, Gas> {
+        let mut usage: HashMap<PublicKey, Gas> = HashMap::new();
+        for (account, gas) in self.quota_usage.values() {
+            let used = usage.entry(account.clone()).or_default();
+            *used = Gas::new(used.value().saturating_add(gas.value()));
+        }
+        usage
+    }
+
+    /// Returns eligible deploys that are buffered and not held or dead, those offering the highest
+    /// priority fees first.
     fn proposable(&self) -> Vec<(DeployHashWithApprovals, DeployFootprint)> {
         debug!("DeployBuffer: getting proposable deploys");
-        self.buffer
+        let mut proposable: Vec<_> = self
+            .buffer
             .iter()
             .filter(|(dh, _)| !self.hold.values().any(|hs| hs.contains(dh)))
             .filter(|(dh, _)| !self.dead.contains(dh))
@@ -380,12 +579,22 @@ impl DeployBuffer {
                     )
                 })
             })
-            .collect()
+            .collect();
+        proposable.sort_by(|(_, footprint_a), (_, footprint_b)| {
+            footprint_b.priority_fee.cmp(&footprint_a.priority_fee)
+        });
+        proposable
     }
 
     /// Returns a right-sized payload of deploys that can be proposed.
     fn appendable_block(&mut self, timestamp: Timestamp) -> AppendableBlock {
-        let mut ret = AppendableBlock::new(self.deploy_config, timestamp);
+        let mut ret = AppendableBlock::new(
+            self.deploy_config,
+            self.payment_lanes.clone(),
+            self.computation_quota_per_era,
+            timestamp,
+        )
+        .with_quota_usage(self.account_quota_usage());
         let mut holds = HashSet::new();
         let mut have_hit_transfer_limit = false;
         let mut have_hit_deploy_limit = false;
@@ -414,15 +623,22 @@ impl DeployBuffer {
                             );
                             self.dead.insert(deploy_hash);
                         }
-                        AddError::InvalidDeploy => {
+                        AddError::InvalidDeploy | AddError::NoPaymentLane => {
                             // it should not be possible for an invalid deploy to get buffered
                             // in the first place, thus this should be unreachable
                             error!(
                                 ?deploy_hash,
+                                %error,
                                 "DeployBuffer: invalid deploy in deploy buffer"
                             );
                             self.dead.insert(deploy_hash);
                         }
+                        AddError::LaneCount(_) => {
+                            // keep iterating, we can maybe fit in a deploy from another lane
+                        }
+                        AddError::ComputationQuota => {
+                            // keep iterating, we can maybe fit in a deploy from another account
+                        }
                         AddError::TransferCount => {
                             if have_hit_deploy_limit {
                                 info!(
@@ -565,10 +781,14 @@ where
                             self,
                             ComponentState::Initialized,
                         );
+                        let mut effects = self.schedule_persist_lookback(effect_builder);
                         // start self-expiry management on initialization
-                        effect_builder
-                            .set_timeout(self.cfg.expiry_check_interval().into())
-                            .event(move |_| Event::Expire)
+                        effects.extend(
+                            effect_builder
+                                .set_timeout(self.cfg.expiry_check_interval().into())
+                                .event(move |_| Event::Expire),
+                        );
+                        effects
                     }
                     Event::Request(_)
                     | Event::ReceiveDeployGossiped(_)
@@ -576,7 +796,9 @@ where
                     | Event::BlockProposed(_)
                     | Event::Block(_)
                     | Event::BlockFinalized(_)
-                    | Event::Expire => {
+                    | Event::Expire
+                    | Event::PersistLookback
+                    | Event::LookbackPersisted => {
                         warn!(
                             ?event,
                             name = <Self as Component<MainEvent>>::name(self),
@@ -598,18 +820,23 @@ where
                 Event::Request(DeployBufferRequest::GetAppendableBlock {
                     timestamp,
                     responder,
-                }) => responder.respond(self.appendable_block(timestamp)).ignore(),
+                }) => {
+                    let appendable_block = self.appendable_block(timestamp);
+                    let mut effects = self.schedule_persist_lookback(effect_builder);
+                    effects.extend(responder.respond(appendable_block).ignore());
+                    effects
+                }
                 Event::BlockFinalized(finalized_block) => {
                     self.register_block_finalized(&*finalized_block);
-                    Effects::new()
+                    self.schedule_persist_lookback(effect_builder)
                 }
                 Event::Block(block) => {
                     self.register_block(&block);
-                    Effects::new()
+                    self.schedule_persist_lookback(effect_builder)
                 }
                 Event::BlockProposed(proposed) => {
                     self.register_block_proposed(*proposed);
-                    Effects::new()
+                    self.schedule_persist_lookback(effect_builder)
                 }
                 Event::ReceiveDeployGossiped(deploy_id) => {
                     self.register_deploy_gossiped(deploy_id, effect_builder)
@@ -625,7 +852,20 @@ where
                     }
                     Effects::new()
                 }
-                Event::Expire => self.expire(effect_builder),
+                Event::Expire => {
+                    let mut effects = self.expire(effect_builder);
+                    effects.extend(self.schedule_persist_lookback(effect_builder));
+                    effects
+                }
+                Event::PersistLookback => self.persist_lookback(),
+                Event::LookbackPersisted => {
+                    self.lookback_persisting = false;
+                    if self.lookback_dirty {
+                        self.schedule_persist_lookback(effect_builder)
+                    } else {
+                        Effects::new()
+                    }
+                }
             },
         }
     }
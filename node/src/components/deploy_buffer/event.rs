@@ -23,6 +23,8 @@ pub(crate) enum Event {
     Block(Arc<Block>),
     BlockFinalized(Box<FinalizedBlock>),
     Expire,
+    PersistLookback,
+    LookbackPersisted,
 }
 
 impl Display for Event {
@@ -61,6 +63,12 @@ impl Display for Event {
             Event::Expire => {
                 write!(formatter, "expire deploys")
             }
+            Event::PersistLookback => {
+                write!(formatter, "persist lookback")
+            }
+            Event::LookbackPersisted => {
+                write!(formatter, "lookback persisted")
+            }
         }
     }
 }
@@ -0,0 +1,125 @@
+//! Persistence of the deploys recently proposed or included in blocks.
+//!
+//! After a restart, the deploy buffer learns about included deploys from the blocks in storage
+//! only.  Deploys held for blocks proposed shortly before the restart, and those in blocks which
+//! were finalized but not yet stored, would be eligible for proposal again, so the buffer keeps a
+//! record of them on disk which it restores when initializing.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_types::{TimeDiff, Timestamp};
+
+use crate::types::DeployHash;
+
+/// Name of the file holding the record, in the storage directory.
+pub(super) const LOOKBACK_FILENAME: &str = "deploy_buffer_lookback.bin";
+
+/// How long changes are collected before the record is written, so that a burst of events results
+/// in a single write.
+pub(super) const LOOKBACK_PERSIST_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Error)]
+pub(super) enum LookbackError {
+    #[error("could not access lookback file: {0}")]
+    Io(#[from] io::Error),
+    #[error("could not (de)serialize lookback: {0}")]
+    Serialization(#[from] bincode::Error),
+}
+
+/// The deploys recently proposed or included in blocks.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct Lookback {
+    /// Deploys held for proposed blocks, by block timestamp.
+    pub(super) held: BTreeMap<Timestamp, HashSet<DeployHash>>,
+    /// Deploys included in blocks, along with the time they expire.
+    pub(super) included: Vec<(DeployHash, Timestamp)>,
+}
+
+impl Lookback {
+    /// Reads the record from `path`, returning `None` if there is none.
+    pub(super) fn read(path: &Path) -> Result<Option<Self>, LookbackError> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        Ok(Some(bincode::deserialize(&bytes)?))
+    }
+
+    /// Writes the record to `path`, replacing any previous one.
+    ///
+    /// The record is written and synced to a temporary file first, so a crash never leaves a
+    /// partial record.
+    pub(super) fn write(&self, path: &Path) -> Result<(), LookbackError> {
+        let bytes = bincode::serialize(self)?;
+        let temp_path = path.with_extension("tmp");
+        let mut file = File::create(&temp_path)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// Drops the deploys which can't be included in a block anymore as of `now`.
+    pub(super) fn retain_unexpired(&mut self, now: Timestamp, max_ttl: TimeDiff) {
+        self.held
+            .retain(|timestamp, _| timestamp.saturating_add(max_ttl) >= now);
+        self.included.retain(|(_, expiry)| *expiry >= now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_and_drop_expired() {
+        let mut rng = TestRng::new();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOOKBACK_FILENAME);
+        assert_eq!(Lookback::read(&path).unwrap(), None);
+
+        let now = Timestamp::from(1_000_000);
+        let max_ttl = TimeDiff::from_millis(1_000);
+        let held_deploy = DeployHash::random(&mut rng);
+        let expired_held_deploy = DeployHash::random(&mut rng);
+        let included_deploy = DeployHash::random(&mut rng);
+        let expired_included_deploy = DeployHash::random(&mut rng);
+        let mut lookback = Lookback::default();
+        lookback.held.insert(now, HashSet::from([held_deploy]));
+        lookback.held.insert(
+            now.saturating_sub(TimeDiff::from_millis(2_000)),
+            HashSet::from([expired_held_deploy]),
+        );
+        lookback.included = vec![
+            (included_deploy, now.saturating_add(max_ttl)),
+            (
+                expired_included_deploy,
+                now.saturating_sub(TimeDiff::from_millis(1)),
+            ),
+        ];
+
+        lookback.write(&path).unwrap();
+        let mut restored = Lookback::read(&path).unwrap().unwrap();
+        assert_eq!(restored, lookback);
+
+        restored.retain_unexpired(now, max_ttl);
+        assert_eq!(restored.held.len(), 1);
+        assert!(restored.held[&now].contains(&held_deploy));
+        assert_eq!(
+            restored.included,
+            vec![(included_deploy, now.saturating_add(max_ttl))]
+        );
+    }
+}
@@ -2,10 +2,16 @@ use super::*;
 use crate::{
     effect::announcements::DeployBufferAnnouncement::{self, DeploysExpired},
     reactor::{EventQueueHandle, QueueKind, Scheduler},
-    types::{Block, FinalizedBlock},
+    types::{Block, DeployBuilder, FinalizedBlock},
     utils,
 };
-use casper_types::{testing::TestRng, EraId, ProtocolVersion, TimeDiff};
+use casper_execution_engine::core::engine_state::{
+    executable_deploy_item::ExecutableDeployItem, LaneKind, PaymentLane, MAX_PAYMENT_AMOUNT,
+};
+use casper_types::{
+    runtime_args, system::standard_payment::ARG_AMOUNT, testing::TestRng, EraId, ProtocolVersion,
+    RuntimeArgs, SecretKey, TimeDiff, U512,
+};
 use prometheus::Registry;
 use rand::Rng;
 
@@ -107,8 +113,14 @@ fn assert_container_sizes(
 #[test]
 fn register_deploy_and_check_size() {
     let mut rng = TestRng::new();
-    let mut deploy_buffer =
-        DeployBuffer::new(DeployConfig::default(), Config::default(), &Registry::new()).unwrap();
+    let mut deploy_buffer = DeployBuffer::new(
+        DeployConfig::default(),
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
 
     // Try to register valid deploys
     let num_valid_deploys: usize = rng.gen_range(50..500);
@@ -144,8 +156,14 @@ fn register_deploy_and_check_size() {
 #[test]
 fn register_block_with_valid_deploys() {
     let mut rng = TestRng::new();
-    let mut deploy_buffer =
-        DeployBuffer::new(DeployConfig::default(), Config::default(), &Registry::new()).unwrap();
+    let mut deploy_buffer = DeployBuffer::new(
+        DeployConfig::default(),
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
 
     let deploys = create_valid_deploys(&mut rng, 10, DeployType::Random, None, None);
     let block = Block::random_with_deploys(&mut rng, deploys.iter());
@@ -157,8 +175,14 @@ fn register_block_with_valid_deploys() {
 #[test]
 fn register_finalized_block_with_valid_deploys() {
     let mut rng = TestRng::new();
-    let mut deploy_buffer =
-        DeployBuffer::new(DeployConfig::default(), Config::default(), &Registry::new()).unwrap();
+    let mut deploy_buffer = DeployBuffer::new(
+        DeployConfig::default(),
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
 
     let deploys = create_valid_deploys(&mut rng, 10, DeployType::Random, None, None);
     let block = FinalizedBlock::random_with_deploys(&mut rng, deploys.iter());
@@ -170,8 +194,14 @@ fn register_finalized_block_with_valid_deploys() {
 #[test]
 fn get_proposable_deploys() {
     let mut rng = TestRng::new();
-    let mut deploy_buffer =
-        DeployBuffer::new(DeployConfig::default(), Config::default(), &Registry::new()).unwrap();
+    let mut deploy_buffer = DeployBuffer::new(
+        DeployConfig::default(),
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
 
     // populate deploy buffer with some deploys
     let deploys = create_valid_deploys(&mut rng, 50, DeployType::Random, None, None);
@@ -233,8 +263,14 @@ fn get_appendable_block_with_native_transfers() {
         block_max_approval_count: 210,
         ..Default::default()
     };
-    let mut deploy_buffer =
-        DeployBuffer::new(deploy_config, Config::default(), &Registry::new()).unwrap();
+    let mut deploy_buffer = DeployBuffer::new(
+        deploy_config,
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
     get_appendable_block(
         &mut rng,
         &mut deploy_buffer,
@@ -252,8 +288,14 @@ fn get_appendable_block_with_standard_deploys() {
         block_max_approval_count: 210,
         ..Default::default()
     };
-    let mut deploy_buffer =
-        DeployBuffer::new(deploy_config, Config::default(), &Registry::new()).unwrap();
+    let mut deploy_buffer = DeployBuffer::new(
+        deploy_config,
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
     get_appendable_block(
         &mut rng,
         &mut deploy_buffer,
@@ -271,8 +313,14 @@ fn get_appendable_block_with_random_deploys() {
         block_max_approval_count: 210,
         ..Default::default()
     };
-    let mut deploy_buffer =
-        DeployBuffer::new(deploy_config, Config::default(), &Registry::new()).unwrap();
+    let mut deploy_buffer = DeployBuffer::new(
+        deploy_config,
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
     get_appendable_block(
         &mut rng,
         &mut deploy_buffer,
@@ -306,12 +354,59 @@ fn get_appendable_block(
     );
 }
 
+#[test]
+fn get_appendable_block_within_payment_lane_limits() {
+    let mut rng = TestRng::new();
+    let lane = |name: &str, kind: LaneKind, max_per_block: u32| PaymentLane {
+        name: name.to_string(),
+        kind,
+        max_session_size: 0,
+        fixed_cost: 1,
+        max_per_block,
+    };
+    let payment_lanes = PaymentLanes::new(vec![
+        lane("transfer", LaneKind::NativeTransfer, 3),
+        lane("stored_contract", LaneKind::Session, 2),
+    ]);
+    let mut deploy_buffer = DeployBuffer::new(
+        DeployConfig::default(),
+        payment_lanes,
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
+
+    let mut deploys = create_valid_deploys(&mut rng, 5, DeployType::Transfer, None, None);
+    deploys.extend(create_valid_deploys(
+        &mut rng,
+        5,
+        DeployType::Standard,
+        None,
+        None,
+    ));
+    deploys
+        .iter()
+        .for_each(|deploy| deploy_buffer.register_deploy(deploy.clone()));
+
+    // Filling one lane doesn't stop deploys from the other from being added.
+    let appendable_block = deploy_buffer.appendable_block(Timestamp::now());
+    assert_eq!(appendable_block.deploy_and_transfer_set().len(), 5);
+    assert_container_sizes(&deploy_buffer, deploys.len(), 0, 5);
+}
+
 #[test]
 fn have_full_ttl_worth_of_deploys() {
     let mut rng = TestRng::new();
     let deploy_config = DeployConfig::default();
-    let mut deploy_buffer =
-        DeployBuffer::new(deploy_config, Config::default(), &Registry::new()).unwrap();
+    let mut deploy_buffer = DeployBuffer::new(
+        deploy_config,
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
 
     // register some blocks that have deploys
     for i in 2..10 {
@@ -402,8 +497,14 @@ fn have_full_ttl_worth_of_deploys() {
 #[test]
 fn register_deploys_and_blocks() {
     let mut rng = TestRng::new();
-    let mut deploy_buffer =
-        DeployBuffer::new(DeployConfig::default(), Config::default(), &Registry::new()).unwrap();
+    let mut deploy_buffer = DeployBuffer::new(
+        DeployConfig::default(),
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
 
     // try to register valid deploys
     let num_valid_deploys: usize = rng.gen_range(50..500);
@@ -535,8 +636,14 @@ impl MockReactor {
 #[tokio::test]
 async fn expire_deploys_and_check_announcement() {
     let mut rng = TestRng::new();
-    let mut deploy_buffer =
-        DeployBuffer::new(DeployConfig::default(), Config::default(), &Registry::new()).unwrap();
+    let mut deploy_buffer = DeployBuffer::new(
+        DeployConfig::default(),
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
 
     let reactor = MockReactor::new();
     let event_queue_handle = EventQueueHandle::without_shutdown(reactor.scheduler);
@@ -582,3 +689,271 @@ async fn expire_deploys_and_check_announcement() {
     // the valid deploys should still be in the buffer
     assert_container_sizes(&deploy_buffer, deploys.len(), 0, 0);
 }
+
+#[tokio::test]
+async fn expire_deploys_and_check_announcement_excludes_included_deploys() {
+    let mut rng = TestRng::new();
+    let mut deploy_buffer = DeployBuffer::new(
+        DeployConfig::default(),
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
+
+    let reactor = MockReactor::new();
+    let event_queue_handle = EventQueueHandle::without_shutdown(reactor.scheduler);
+    let effect_builder = EffectBuilder::new(event_queue_handle);
+
+    // generate and register some already expired deploys
+    let ttl = TimeDiff::from_seconds(rng.gen_range(30..300));
+    let past_timestamp = Timestamp::now()
+        .saturating_sub(ttl)
+        .saturating_sub(TimeDiff::from_seconds(5));
+
+    let num_deploys: usize = rng.gen_range(5..50);
+    let expired_deploys = create_valid_deploys(
+        &mut rng,
+        num_deploys * 2,
+        DeployType::Transfer,
+        Some(past_timestamp),
+        Some(ttl),
+    );
+    expired_deploys
+        .iter()
+        .for_each(|deploy| deploy_buffer.register_deploy(deploy.clone()));
+
+    // include half of them in a block
+    let (included_deploys, dropped_deploys) = expired_deploys.split_at(num_deploys);
+    let block = Block::random_with_deploys(&mut rng, included_deploys.iter());
+    deploy_buffer.register_block(&block);
+    assert_container_sizes(
+        &deploy_buffer,
+        expired_deploys.len(),
+        included_deploys.len(),
+        0,
+    );
+
+    // only the deploys which were never included should be announced as expired
+    let dropped_deploy_hashes: HashSet<_> = dropped_deploys
+        .iter()
+        .map(|deploy| *deploy.hash())
+        .collect();
+    let mut effects = deploy_buffer.expire(effect_builder);
+    tokio::spawn(effects.remove(0)).await.unwrap();
+    reactor
+        .expect_deploy_buffer_expire_announcement(&dropped_deploy_hashes)
+        .await;
+
+    assert_container_sizes(&deploy_buffer, 0, 0, 0);
+}
+
+#[test]
+fn should_propose_deploys_offering_higher_priority_fees_first() {
+    let mut rng = TestRng::new();
+    let mut deploy_buffer = DeployBuffer::new(
+        DeployConfig::default(),
+        PaymentLanes::default(),
+        0,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
+
+    let secret_key = SecretKey::random(&mut rng);
+    let create_deploy = |priority_fee: u64| {
+        let session = ExecutableDeployItem::ModuleBytes {
+            module_bytes: vec![1].into(),
+            args: RuntimeArgs::new(),
+        };
+        DeployBuilder::new("casper-example", session)
+            .with_standard_payment(MAX_PAYMENT_AMOUNT)
+            .with_ttl(TimeDiff::from_seconds(60))
+            .with_priority_fee(priority_fee)
+            .with_secret_key(&secret_key)
+            .build()
+            .unwrap()
+    };
+    let deploys = vec![create_deploy(5), create_deploy(0), create_deploy(20)];
+    deploys
+        .iter()
+        .for_each(|deploy| deploy_buffer.register_deploy(deploy.clone()));
+
+    let proposed: Vec<_> = deploy_buffer
+        .proposable()
+        .into_iter()
+        .map(|(with_approvals, _)| *with_approvals.deploy_hash())
+        .collect();
+    assert_eq!(
+        proposed,
+        vec![*deploys[2].hash(), *deploys[0].hash(), *deploys[1].hash()]
+    );
+}
+
+#[test]
+fn get_appendable_block_within_computation_quotas() {
+    let mut rng = TestRng::new();
+    let mut deploy_buffer = DeployBuffer::new(
+        DeployConfig::default(),
+        PaymentLanes::default(),
+        2 * MAX_PAYMENT_AMOUNT,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
+
+    let create_deploy = |secret_key: &SecretKey, index: u8| {
+        Deploy::new(
+            Timestamp::now(),
+            TimeDiff::from_seconds(60),
+            1,
+            vec![],
+            "casper-example".to_string(),
+            ExecutableDeployItem::ModuleBytes {
+                module_bytes: Default::default(),
+                args: runtime_args! { ARG_AMOUNT => U512::from(MAX_PAYMENT_AMOUNT) },
+            },
+            ExecutableDeployItem::ModuleBytes {
+                module_bytes: vec![index].into(),
+                args: RuntimeArgs::new(),
+            },
+            secret_key,
+            None,
+        )
+    };
+    let busy_account = SecretKey::random(&mut rng);
+    let other_account = SecretKey::random(&mut rng);
+    let mut deploys: Vec<_> = (1..=3)
+        .map(|index| create_deploy(&busy_account, index))
+        .collect();
+    deploys.push(create_deploy(&other_account, 1));
+    deploys
+        .iter()
+        .for_each(|deploy| deploy_buffer.register_deploy(deploy.clone()));
+
+    // Only two of the busy account's deploys fit into its quota, but that doesn't stop the other
+    // account's deploy from being added.
+    let appendable_block = deploy_buffer.appendable_block(Timestamp::now());
+    assert_eq!(appendable_block.deploy_and_transfer_set().len(), 3);
+    assert!(appendable_block
+        .deploy_and_transfer_set()
+        .contains(deploys[3].hash()));
+    assert_container_sizes(&deploy_buffer, deploys.len(), 0, 3);
+}
+
+#[test]
+fn get_appendable_block_within_remaining_computation_quotas() {
+    let mut rng = TestRng::new();
+    let mut deploy_buffer = DeployBuffer::new(
+        DeployConfig::default(),
+        PaymentLanes::default(),
+        2 * MAX_PAYMENT_AMOUNT,
+        Config::default(),
+        &Registry::new(),
+    )
+    .unwrap();
+
+    let secret_key = SecretKey::random(&mut rng);
+    let deploys: Vec<_> = (1..=3)
+        .map(|index| {
+            Deploy::new(
+                Timestamp::now(),
+                TimeDiff::from_seconds(60),
+                1,
+                vec![],
+                "casper-example".to_string(),
+                ExecutableDeployItem::ModuleBytes {
+                    module_bytes: Default::default(),
+                    args: runtime_args! { ARG_AMOUNT => U512::from(MAX_PAYMENT_AMOUNT) },
+                },
+                ExecutableDeployItem::ModuleBytes {
+                    module_bytes: vec![index].into(),
+                    args: RuntimeArgs::new(),
+                },
+                &secret_key,
+                None,
+            )
+        })
+        .collect();
+    deploys
+        .iter()
+        .for_each(|deploy| deploy_buffer.register_deploy(deploy.clone()));
+
+    // Half of the account's quota is used up by a deploy in an earlier block of the era, even if
+    // that block is registered both when finalized and when executed.
+    let block = Block::random_with_specifics(
+        &mut rng,
+        EraId::from(2),
+        1,
+        ProtocolVersion::V1_0_0,
+        false,
+        deploys.iter().take(1),
+    );
+    deploy_buffer.register_block_finalized(&FinalizedBlock::from(block.clone()));
+    deploy_buffer.register_block(&block);
+    let appendable_block = deploy_buffer.appendable_block(Timestamp::now());
+    assert_eq!(appendable_block.deploy_and_transfer_set().len(), 1);
+
+    // The quota is replenished once the era ends.
+    deploy_buffer.hold.clear();
+    let switch_block = Block::random_with_specifics(
+        &mut rng,
+        EraId::from(2),
+        2,
+        ProtocolVersion::V1_0_0,
+        true,
+        None,
+    );
+    deploy_buffer.register_block(&switch_block);
+    assert!(deploy_buffer.quota_usage.is_empty());
+    let appendable_block = deploy_buffer.appendable_block(Timestamp::now());
+    assert_eq!(appendable_block.deploy_and_transfer_set().len(), 2);
+}
+
+#[tokio::test]
+async fn should_persist_lookback_once_per_burst_of_changes() {
+    let mut rng = TestRng::new();
+    let storage_dir = tempfile::tempdir().unwrap();
+    let new_deploy_buffer = || {
+        DeployBuffer::new(
+            DeployConfig::default(),
+            PaymentLanes::default(),
+            0,
+            Config::default(),
+            &Registry::new(),
+        )
+        .unwrap()
+        .with_lookback(storage_dir.path())
+    };
+    let mut deploy_buffer = new_deploy_buffer();
+
+    let reactor = MockReactor::new();
+    let event_queue_handle = EventQueueHandle::without_shutdown(reactor.scheduler);
+    let effect_builder = EffectBuilder::new(event_queue_handle);
+
+    let deploys = create_valid_deploys(&mut rng, 5, DeployType::Transfer, None, None);
+    let block = Block::random_with_deploys(&mut rng, deploys.iter());
+    deploy_buffer.register_block(&block);
+
+    // Only the first change schedules a write.
+    assert_eq!(
+        deploy_buffer
+            .schedule_persist_lookback(effect_builder)
+            .len(),
+        1
+    );
+    assert!(deploy_buffer
+        .schedule_persist_lookback(effect_builder)
+        .is_empty());
+    assert!(!storage_dir.path().join(LOOKBACK_FILENAME).exists());
+
+    let mut effects = deploy_buffer.persist_lookback();
+    let events = tokio::spawn(effects.remove(0)).await.unwrap();
+    assert!(matches!(events.as_slice(), [Event::LookbackPersisted]));
+
+    let mut restored = new_deploy_buffer();
+    restored.restore_lookback();
+    assert_eq!(restored.dead, deploy_buffer.dead);
+    assert_eq!(restored.dead.len(), deploys.len());
+}
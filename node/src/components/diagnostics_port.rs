@@ -25,7 +25,10 @@ use crate::{
     effect::{
         announcements::ControlAnnouncement,
         diagnostics_port::DumpConsensusStateRequest,
-        requests::{NetworkInfoRequest, SetNodeStopRequest},
+        requests::{
+            ComponentRestartsRequest, ContractRuntimeRequest, NetworkInfoRequest,
+            SetNodeStopRequest,
+        },
         EffectBuilder, EffectExt, Effects,
     },
     reactor::main_reactor::MainEvent,
@@ -108,6 +111,8 @@ where
         + From<ControlAnnouncement>
         + From<NetworkInfoRequest>
         + From<SetNodeStopRequest>
+        + From<ContractRuntimeRequest>
+        + From<ComponentRestartsRequest>
         + Send,
 {
     type Event = Event;
@@ -162,6 +167,8 @@ where
         + From<ControlAnnouncement>
         + From<NetworkInfoRequest>
         + From<SetNodeStopRequest>
+        + From<ContractRuntimeRequest>
+        + From<ComponentRestartsRequest>
         + Send,
 {
     fn state(&self) -> &ComponentState {
@@ -186,6 +193,8 @@ where
         + From<ControlAnnouncement>
         + From<NetworkInfoRequest>
         + From<SetNodeStopRequest>
+        + From<ContractRuntimeRequest>
+        + From<ComponentRestartsRequest>
         + Send,
 {
     type Error = Error;
@@ -1,5 +1,8 @@
 use std::{
     fmt::{self, Display, Formatter},
+    net::IpAddr,
+    num::NonZeroUsize,
+    path::PathBuf,
     str::FromStr,
 };
 
@@ -7,7 +10,10 @@ use serde::Serialize;
 use structopt::StructOpt;
 use thiserror::Error;
 
+use casper_hashing::Digest;
+
 use super::StopAtSpec;
+use crate::{components::network::ConnectionId, types::NodeId, utils::short_id::IdOrShortId};
 
 /// Command processing error.
 ///
@@ -84,6 +90,13 @@ pub(super) enum Action {
     GetLogFilter,
     /// Change the current log filter configuration.
     SetLogFilter { directive: String },
+    /// Change the log level of a single target, keeping the levels of all other targets.
+    SetLogLevel {
+        /// The target, e.g. `casper_node::components::network`.
+        target: String,
+        /// The new level, e.g. `debug`.
+        level: String,
+    },
     /// Dump the state of the consensus component.
     ///
     /// It is recommended to set the output format to `bincode` if the data is to be visualized
@@ -94,8 +107,110 @@ pub(super) enum Action {
     },
     /// Dump the event queues.
     DumpQueues,
+    /// List how often each non-critical component supervised by the node has been restarted after
+    /// failing, along with its most recent failure.
+    ComponentRestarts,
     /// Get detailed networking insights.
     NetInfo,
+    /// Diagnose whether the node is partitioned from the network.
+    ///
+    /// Reports on the connections to a random sample of known peers, i.e. their reachability,
+    /// protocol versions and clock skew, along with a verdict. No new connections are opened.
+    NetDiagnose {
+        /// Maximum number of peers to probe.
+        #[structopt(short, long, default_value = "10")]
+        sample: usize,
+    },
+    /// Look up the peer a connection belongs to by the connection ID found in logs.
+    ///
+    /// Both ends of a connection log the same ID. Connections are remembered for a while after
+    /// being closed.
+    NetConnection {
+        /// The base16-encoded connection ID, or its short form as logged.
+        connection_id: IdOrShortId<ConnectionId>,
+    },
+    /// Resolve a node ID found in logs to the full ID and addresses of the peer.
+    ///
+    /// Only peers with open or recently closed connections are found.
+    NetLookup {
+        /// The short form of the node ID as logged, e.g. `tls:0a1b2c3d4e-91f0`, or the full
+        /// base16-encoded node ID.
+        node_id: IdOrShortId<NodeId>,
+    },
+    /// List failed connection attempts by remote address, classified by the kind of failure.
+    ///
+    /// Failures of incoming connections are listed under the remote IP with port 0.
+    NetHandshakeFailures {
+        /// Only list failures involving this IP address.
+        #[structopt(short, long)]
+        ip: Option<IpAddr>,
+    },
+    /// List tries missing below a state root, e.g. to see whether a partially synced global state
+    /// is complete.
+    MissingTries {
+        /// The base16-encoded state root hash.
+        #[structopt(parse(try_from_str = Digest::from_hex))]
+        state_root_hash: Digest,
+        /// Maximum number of missing tries to list.
+        #[structopt(short, long, default_value = "100")]
+        limit: NonZeroUsize,
+    },
+    /// Check that every trie below a state root is present and hashes to its key, listing the
+    /// missing or corrupted tries along with their paths.
+    ///
+    /// This reads the entire trie and can take a long time on a large global state.
+    VerifyTrie {
+        /// The base16-encoded state root hash.
+        #[structopt(parse(try_from_str = Digest::from_hex))]
+        state_root_hash: Digest,
+        /// Maximum number of missing or corrupted tries to list.
+        #[structopt(short, long, default_value = "100")]
+        limit: NonZeroUsize,
+    },
+    /// Show statistics about the tries in global state: their number and size, and how many
+    /// children nodes have.
+    ///
+    /// This reads the entire trie store and can take a long time on a large global state.
+    TrieStats {
+        /// Also count the leaves at each depth below this base16-encoded state root hash.
+        #[structopt(short, long, parse(try_from_str = Digest::from_hex))]
+        state_root_hash: Option<Digest>,
+    },
+    /// Attribute the bytes stored in global state to the accounts and contracts owning them,
+    /// listing the owners with the largest footprints.
+    ///
+    /// This reads all accounts, contracts and dictionaries in global state and can take a long
+    /// time on a large global state.
+    KeySpaceUsage {
+        /// The base16-encoded state root hash to account for, rather than the last committed one.
+        #[structopt(short, long, parse(try_from_str = Digest::from_hex))]
+        state_root_hash: Option<Digest>,
+        /// Maximum number of owners to list.
+        #[structopt(short, long, default_value = "100")]
+        limit: usize,
+    },
+    /// Write a hot backup of global state to a tarball on the node's host.
+    ///
+    /// Global state is copied straight into the tarball while blocks keep executing, followed by
+    /// a manifest of the state roots it includes.
+    BackupGlobalState {
+        /// The path of the tarball to create, which must not exist yet.
+        path: PathBuf,
+    },
+    /// Copy global state into a checkpoint directory on the node's host.
+    ///
+    /// The checkpoint is a compacted copy of the database, taken while blocks keep executing,
+    /// which opens like any other global state directory.
+    CheckpointGlobalState {
+        /// The directory to hold the checkpoint, which is created if missing and must not hold a
+        /// database yet.
+        dir: PathBuf,
+    },
+    /// Compact global state on the next restart, shrinking the database file by the space of its
+    /// free pages.
+    ///
+    /// The node rewrites the whole database while starting, so the restart takes longer.
+    CompactGlobalState,
     /// Stop the node at a certain condition.
     Stop {
         /// When to stop the node.
@@ -142,5 +257,90 @@ mod tests {
 
         let cmd = Command::from_line("dump-queues").expect("command parsing failed");
         assert!(matches!(cmd.action, Action::DumpQueues));
+
+        let cmd = Command::from_line("component-restarts").expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::ComponentRestarts));
+
+        let cmd = Command::from_line("net-diagnose -s 25").expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::NetDiagnose { sample } if sample == 25));
+
+        let cmd = Command::from_line("net-handshake-failures -i 10.0.0.1")
+            .expect("command parsing failed");
+        assert!(matches!(
+            cmd.action,
+            Action::NetHandshakeFailures { ip: Some(_) }
+        ));
+
+        let cmd = Command::from_line(&format!("net-connection {}", "ab".repeat(32)))
+            .expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::NetConnection { .. }));
+        assert!(Command::from_line("net-connection abcd").is_err());
+
+        let cmd =
+            Command::from_line("net-connection 0101010101-eb42").expect("command parsing failed");
+        assert!(matches!(
+            cmd.action,
+            Action::NetConnection {
+                connection_id: IdOrShortId::Short(_)
+            }
+        ));
+
+        let cmd =
+            Command::from_line("net-lookup tls:0101010101-eb42").expect("command parsing failed");
+        assert!(matches!(
+            cmd.action,
+            Action::NetLookup {
+                node_id: IdOrShortId::Short(_)
+            }
+        ));
+        assert!(Command::from_line("net-lookup tls:0101010101-eb43").is_err());
+
+        let cmd = Command::from_line(&format!("missing-tries {} -l 5", "cd".repeat(32)))
+            .expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::MissingTries { limit, .. } if limit.get() == 5));
+        assert!(Command::from_line(&format!("missing-tries {} -l 0", "cd".repeat(32))).is_err());
+
+        let cmd = Command::from_line(&format!("verify-trie {}", "cd".repeat(32)))
+            .expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::VerifyTrie { limit, .. } if limit.get() == 100));
+        assert!(Command::from_line(&format!("verify-trie {} -l 0", "cd".repeat(32))).is_err());
+
+        let cmd = Command::from_line("trie-stats").expect("command parsing failed");
+        assert!(matches!(
+            cmd.action,
+            Action::TrieStats {
+                state_root_hash: None
+            }
+        ));
+        let cmd = Command::from_line(&format!("trie-stats -s {}", "cd".repeat(32)))
+            .expect("command parsing failed");
+        assert!(matches!(
+            cmd.action,
+            Action::TrieStats {
+                state_root_hash: Some(_)
+            }
+        ));
+
+        let cmd = Command::from_line("key-space-usage -l 10").expect("command parsing failed");
+        assert!(matches!(
+            cmd.action,
+            Action::KeySpaceUsage {
+                state_root_hash: None,
+                limit: 10
+            }
+        ));
+
+        let cmd = Command::from_line("backup-global-state /tmp/backup.tar")
+            .expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::BackupGlobalState { .. }));
+        assert!(Command::from_line("backup-global-state").is_err());
+
+        let cmd = Command::from_line("checkpoint-global-state /tmp/checkpoint")
+            .expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::CheckpointGlobalState { .. }));
+        assert!(Command::from_line("checkpoint-global-state").is_err());
+
+        let cmd = Command::from_line("compact-global-state").expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::CompactGlobalState));
     }
 }
@@ -21,6 +21,7 @@ use tokio::{
 };
 use tracing::{debug, info, info_span, warn, Instrument};
 
+use casper_hashing::Digest;
 use casper_types::EraId;
 use tracing_subscriber::{filter::ParseError, EnvFilter};
 
@@ -33,7 +34,10 @@ use crate::{
     effect::{
         announcements::{ControlAnnouncement, QueueDumpFormat},
         diagnostics_port::DumpConsensusStateRequest,
-        requests::{NetworkInfoRequest, SetNodeStopRequest},
+        requests::{
+            ComponentRestartsRequest, ContractRuntimeRequest, NetworkInfoRequest,
+            SetNodeStopRequest,
+        },
         EffectBuilder,
     },
     logging,
@@ -87,6 +91,36 @@ impl Display for Outcome {
     }
 }
 
+/// Tries missing below a state root.
+#[derive(Debug, Serialize)]
+struct MissingTries {
+    /// The probed state root.
+    state_root_hash: Digest,
+    /// The maximum number of missing tries listed.
+    limit: usize,
+    /// The missing tries found, in no particular order.
+    missing: Vec<Digest>,
+}
+
+impl Display for MissingTries {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.missing.is_empty() {
+            return writeln!(f, "state root {} is complete", self.state_root_hash);
+        }
+        writeln!(
+            f,
+            "{} tries missing below state root {} (listing at most {})",
+            self.missing.len(),
+            self.state_root_hash,
+            self.limit
+        )?;
+        for digest in &self.missing {
+            writeln!(f, "{}", digest)?;
+        }
+        Ok(())
+    }
+}
+
 /// Configuration for a connection diagnostics port session.
 #[derive(Copy, Clone, Debug, Default, Serialize)]
 struct Session {
@@ -202,6 +236,8 @@ impl Session {
             + From<ControlAnnouncement>
             + From<NetworkInfoRequest>
             + From<SetNodeStopRequest>
+            + From<ContractRuntimeRequest>
+            + From<ComponentRestartsRequest>
             + Send,
     {
         debug!(%line, "line received");
@@ -268,6 +304,22 @@ impl Session {
                             .await?;
                         }
                     },
+                    Action::SetLogLevel {
+                        ref target,
+                        ref level,
+                    } => match logging::set_global_target_level(target, level) {
+                        Ok(()) => {
+                            self.send_outcome(writer, &Outcome::success("new log level set"))
+                                .await?;
+                        }
+                        Err(err) => {
+                            self.send_outcome(
+                                writer,
+                                &Outcome::failed(format!("failed to set log level: {}", err)),
+                            )
+                            .await?;
+                        }
+                    },
                     Action::DumpConsensus { era } => {
                         let output = effect_builder
                             .diagnostics_port_dump_consensus_state(
@@ -316,12 +368,205 @@ impl Session {
                             }
                         };
                     }
+                    Action::ComponentRestarts => {
+                        self.send_outcome(writer, &Outcome::success("listing restarts"))
+                            .await?;
+                        let restarts = effect_builder.get_component_restarts().await;
+                        self.send_to_client(writer, &restarts).await?;
+                    }
                     Action::NetInfo => {
                         self.send_outcome(writer, &Outcome::success("collecting insights"))
                             .await?;
                         let insights = effect_builder.get_network_insights().await;
                         self.send_to_client(writer, &insights).await?;
                     }
+                    Action::NetDiagnose { sample } => {
+                        self.send_outcome(writer, &Outcome::success("probing peers"))
+                            .await?;
+                        let report = effect_builder.diagnose_network_partition(sample).await;
+                        self.send_to_client(writer, &report).await?;
+                    }
+                    Action::NetConnection { connection_id } => {
+                        match effect_builder.get_network_connection(connection_id).await {
+                            Some(info) => {
+                                self.send_outcome(writer, &Outcome::success("found connection"))
+                                    .await?;
+                                self.send_to_client(writer, &info).await?;
+                            }
+                            None => {
+                                self.send_outcome(
+                                    writer,
+                                    &Outcome::failed(format!(
+                                        "no open or recently closed connection {}",
+                                        connection_id
+                                    )),
+                                )
+                                .await?;
+                            }
+                        }
+                    }
+                    Action::NetLookup { node_id } => {
+                        self.send_outcome(writer, &Outcome::success("looking up peer"))
+                            .await?;
+                        let report = effect_builder.lookup_network_peer(node_id).await;
+                        self.send_to_client(writer, &report).await?;
+                    }
+                    Action::NetHandshakeFailures { ip } => {
+                        self.send_outcome(writer, &Outcome::success("collecting failures"))
+                            .await?;
+                        let report = effect_builder.get_handshake_failures(ip).await;
+                        self.send_to_client(writer, &report).await?;
+                    }
+                    Action::MissingTries {
+                        state_root_hash,
+                        limit,
+                    } => {
+                        match effect_builder
+                            .get_missing_trie_descendants(state_root_hash, limit.get())
+                            .await
+                        {
+                            Ok(missing) => {
+                                self.send_outcome(writer, &Outcome::success("probed state root"))
+                                    .await?;
+                                let report = MissingTries {
+                                    state_root_hash,
+                                    limit: limit.get(),
+                                    missing,
+                                };
+                                self.send_to_client(writer, &report).await?;
+                            }
+                            Err(err) => {
+                                self.send_outcome(
+                                    writer,
+                                    &Outcome::failed(format!(
+                                        "failed to probe state root: {}",
+                                        display_error(&err)
+                                    )),
+                                )
+                                .await?;
+                            }
+                        }
+                    }
+                    Action::VerifyTrie {
+                        state_root_hash,
+                        limit,
+                    } => {
+                        self.send_outcome(writer, &Outcome::success("verifying trie"))
+                            .await?;
+                        match effect_builder
+                            .verify_trie_integrity(state_root_hash, limit.get())
+                            .await
+                        {
+                            Ok(report) => self.send_to_client(writer, &report).await?,
+                            Err(err) => {
+                                self.send_outcome(
+                                    writer,
+                                    &Outcome::failed(format!(
+                                        "failed to verify trie: {}",
+                                        display_error(&err)
+                                    )),
+                                )
+                                .await?;
+                            }
+                        }
+                    }
+                    Action::TrieStats { state_root_hash } => {
+                        self.send_outcome(writer, &Outcome::success("gathering trie statistics"))
+                            .await?;
+                        match effect_builder.get_trie_store_stats(state_root_hash).await {
+                            Ok(stats) => self.send_to_client(writer, &stats).await?,
+                            Err(err) => {
+                                self.send_outcome(
+                                    writer,
+                                    &Outcome::failed(format!(
+                                        "failed to gather trie statistics: {}",
+                                        display_error(&err)
+                                    )),
+                                )
+                                .await?;
+                            }
+                        }
+                    }
+                    Action::KeySpaceUsage {
+                        state_root_hash,
+                        limit,
+                    } => {
+                        self.send_outcome(writer, &Outcome::success("accounting for key space"))
+                            .await?;
+                        match effect_builder
+                            .get_key_space_usage(state_root_hash, limit)
+                            .await
+                        {
+                            Ok(usage) => self.send_to_client(writer, &usage).await?,
+                            Err(err) => {
+                                self.send_outcome(
+                                    writer,
+                                    &Outcome::failed(format!(
+                                        "failed to account for key space: {}",
+                                        display_error(&err)
+                                    )),
+                                )
+                                .await?;
+                            }
+                        }
+                    }
+                    Action::BackupGlobalState { path } => {
+                        self.send_outcome(writer, &Outcome::success("backing up global state"))
+                            .await?;
+                        match effect_builder.backup_global_state(path).await {
+                            Ok(manifest) => self.send_to_client(writer, &manifest).await?,
+                            Err(err) => {
+                                self.send_outcome(
+                                    writer,
+                                    &Outcome::failed(format!(
+                                        "failed to back up global state: {}",
+                                        display_error(&err)
+                                    )),
+                                )
+                                .await?;
+                            }
+                        }
+                    }
+                    Action::CheckpointGlobalState { dir } => {
+                        self.send_outcome(writer, &Outcome::success("checkpointing global state"))
+                            .await?;
+                        match effect_builder.checkpoint_global_state(dir).await {
+                            Ok(checkpoint) => self.send_to_client(writer, &checkpoint).await?,
+                            Err(err) => {
+                                self.send_outcome(
+                                    writer,
+                                    &Outcome::failed(format!(
+                                        "failed to checkpoint global state: {}",
+                                        display_error(&err)
+                                    )),
+                                )
+                                .await?;
+                            }
+                        }
+                    }
+                    Action::CompactGlobalState => {
+                        match effect_builder.request_global_state_compaction().await {
+                            Ok(()) => {
+                                self.send_outcome(
+                                    writer,
+                                    &Outcome::success(
+                                        "global state will be compacted on the next restart",
+                                    ),
+                                )
+                                .await?;
+                            }
+                            Err(err) => {
+                                self.send_outcome(
+                                    writer,
+                                    &Outcome::failed(format!(
+                                        "failed to request compaction: {}",
+                                        display_error(&err)
+                                    )),
+                                )
+                                .await?;
+                            }
+                        }
+                    }
                     Action::Stop { at, clear } => {
                         let (msg, stop_at) = if clear {
                             ("clearing stopping point", None)
@@ -478,6 +723,8 @@ where
         + From<ControlAnnouncement>
         + From<NetworkInfoRequest>
         + From<SetNodeStopRequest>
+        + From<ContractRuntimeRequest>
+        + From<ComponentRestartsRequest>
         + Send,
 {
     debug!("accepted new connection on diagnostics port");
@@ -522,6 +769,8 @@ pub(super) async fn server<REv>(
         + From<ControlAnnouncement>
         + From<NetworkInfoRequest>
         + From<SetNodeStopRequest>
+        + From<ContractRuntimeRequest>
+        + From<ComponentRestartsRequest>
         + Send,
 {
     let handling_shutdown_receiver = shutdown_receiver.clone();
@@ -601,7 +850,10 @@ mod tests {
         effect::{
             announcements::ControlAnnouncement,
             diagnostics_port::DumpConsensusStateRequest,
-            requests::{NetworkInfoRequest, SetNodeStopRequest},
+            requests::{
+                ComponentRestartsRequest, ContractRuntimeRequest, NetworkInfoRequest,
+                SetNodeStopRequest,
+            },
             EffectBuilder, EffectExt, Effects,
         },
         reactor::{
@@ -661,6 +913,10 @@ mod tests {
         NetworkInfoRequest(NetworkInfoRequest),
         #[from]
         SetNodeStopRequest(SetNodeStopRequest),
+        #[from]
+        ContractRuntimeRequest(ContractRuntimeRequest),
+        #[from]
+        ComponentRestartsRequest(ComponentRestartsRequest),
     }
 
     impl Display for Event {
@@ -707,7 +963,9 @@ mod tests {
                 Event::DumpConsensusStateRequest(_)
                 | Event::SetNodeStopRequest(_)
                 | Event::ControlAnnouncement(_)
-                | Event::NetworkInfoRequest(_) => {
+                | Event::NetworkInfoRequest(_)
+                | Event::ContractRuntimeRequest(_)
+                | Event::ComponentRestartsRequest(_) => {
                     panic!("unexpected: {}", event)
                 }
             }
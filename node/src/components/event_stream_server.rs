@@ -31,9 +31,13 @@ mod tests;
 use std::{fmt::Debug, net::SocketAddr, path::PathBuf, sync::Arc};
 
 use datasize::DataSize;
-use tokio::sync::{
-    mpsc::{self, UnboundedSender},
-    oneshot,
+use futures::{future::BoxFuture, FutureExt};
+use tokio::{
+    sync::{
+        mpsc::{self, UnboundedSender},
+        oneshot,
+    },
+    task::JoinHandle,
 };
 use tracing::{error, info, warn};
 use warp::Filter;
@@ -42,7 +46,7 @@ use casper_types::ProtocolVersion;
 
 use super::Component;
 use crate::{
-    components::{ComponentState, InitializedComponent, PortBoundComponent},
+    components::{ComponentState, InitializedComponent, PortBoundComponent, RestartableComponent},
     effect::{EffectBuilder, Effects},
     reactor::main_reactor::MainEvent,
     types::JsonBlock,
@@ -81,6 +85,10 @@ struct InnerServer {
     sse_data_sender: UnboundedSender<(EventIndex, SseData)>,
     event_indexer: EventIndexer,
     listening_address: SocketAddr,
+    /// The task handle which will only join once the server has sent the final events to its
+    /// clients and exited.
+    #[data_size(skip)]
+    server_join_handle: JoinHandle<()>,
 }
 
 #[derive(DataSize, Debug)]
@@ -143,7 +151,7 @@ impl EventStreamServer {
 
         let (sse_data_sender, sse_data_receiver) = mpsc::unbounded_channel();
 
-        tokio::spawn(http_server::run(
+        let server_join_handle = tokio::spawn(http_server::run(
             self.config.clone(),
             self.api_version,
             server_with_shutdown,
@@ -159,10 +167,34 @@ impl EventStreamServer {
             sse_data_sender,
             event_indexer,
             listening_address,
+            server_join_handle,
         });
         Ok(())
     }
 
+    /// Sends the final `Shutdown` event to all clients and stops the server from accepting new
+    /// subscribers.
+    ///
+    /// The returned future resolves once the server has exited.
+    pub(crate) fn begin_shutdown(&mut self) -> BoxFuture<'static, ()> {
+        let _ = self.broadcast(SseData::Shutdown);
+        match self.sse_server.take() {
+            Some(server) => {
+                // Dropping the data sender causes the server loop to exit.
+                let InnerServer {
+                    server_join_handle, ..
+                } = server;
+                async move {
+                    if let Err(error) = server_join_handle.await {
+                        error!(%error, "could not join event stream server task cleanly");
+                    }
+                }
+                .boxed()
+            }
+            None => async {}.boxed(),
+        }
+    }
+
     /// Broadcasts the SSE data to all clients connected to the event stream.
     fn broadcast(&mut self, sse_data: SseData) -> Effects<Event> {
         if let Some(server) = self.sse_server.as_mut() {
@@ -221,7 +253,9 @@ where
                 | Event::DeploysExpired(_)
                 | Event::Fault { .. }
                 | Event::FinalitySignature(_)
-                | Event::Step { .. } => {
+                | Event::Step { .. }
+                | Event::EraRewards(_)
+                | Event::BlockWriteStats(_) => {
                     warn!(
                         ?event,
                         name = <Self as Component<MainEvent>>::name(self),
@@ -260,6 +294,9 @@ where
                     block_hash: Box::new(block_hash),
                     execution_result,
                 }),
+                Event::DeploysExpired(_) if !self.config.emit_deploy_expired_events => {
+                    Effects::new()
+                }
                 Event::DeploysExpired(deploy_hashes) => deploy_hashes
                     .into_iter()
                     .flat_map(|deploy_hash| self.broadcast(SseData::DeployExpired { deploy_hash }))
@@ -281,6 +318,15 @@ where
                     era_id,
                     execution_effect,
                 }),
+                Event::EraRewards(reward_report) => {
+                    self.broadcast(SseData::EraRewards(reward_report))
+                }
+                Event::BlockWriteStats(_) if !self.config.emit_block_write_stats_events => {
+                    Effects::new()
+                }
+                Event::BlockWriteStats(write_stats) => {
+                    self.broadcast(SseData::BlockWriteStats(write_stats))
+                }
             },
         }
     }
@@ -324,3 +370,25 @@ where
         Ok(Effects::new())
     }
 }
+
+impl<REv> RestartableComponent<REv> for EventStreamServer
+where
+    REv: ReactorEventT,
+{
+    fn failure(&self) -> Option<String> {
+        if let ComponentState::Fatal(msg) = &self.state {
+            return Some(msg.clone());
+        }
+        let server_exited = self
+            .sse_server
+            .as_ref()
+            .map_or(false, |server| server.server_join_handle.is_finished());
+        server_exited.then(|| "event stream server task exited".to_string())
+    }
+
+    fn reset(&mut self) -> BoxFuture<'static, ()> {
+        let shutdown = self.begin_shutdown();
+        <Self as InitializedComponent<MainEvent>>::set_state(self, ComponentState::Uninitialized);
+        shutdown
+    }
+}
@@ -28,6 +28,14 @@ pub struct Config {
 
     /// Default maximum number of subscribers across all event streams permitted at any one time.
     pub max_concurrent_subscribers: u32,
+
+    /// Whether to emit `DeployExpired` events for accepted deploys which expired without being
+    /// included in a block.
+    pub emit_deploy_expired_events: bool,
+
+    /// Whether to emit `BlockWriteStats` events, reporting the values each executed block wrote
+    /// to global state by kind of key.
+    pub emit_block_write_stats_events: bool,
 }
 
 impl Config {
@@ -38,6 +46,8 @@ impl Config {
             address: DEFAULT_ADDRESS.to_string(),
             event_stream_buffer_length: DEFAULT_EVENT_STREAM_BUFFER_LENGTH,
             max_concurrent_subscribers: DEFAULT_MAX_CONCURRENT_SUBSCRIBERS,
+            emit_deploy_expired_events: true,
+            emit_block_write_stats_events: false,
         }
     }
 }
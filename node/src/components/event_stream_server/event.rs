@@ -3,10 +3,14 @@ use std::{
     sync::Arc,
 };
 
-use casper_types::{EraId, ExecutionEffect, ExecutionResult, PublicKey, Timestamp};
+use casper_types::{
+    system::auction::EraRewardReport, EraId, ExecutionEffect, ExecutionResult, PublicKey, Timestamp,
+};
 use itertools::Itertools;
 
-use crate::types::{Block, BlockHash, Deploy, DeployHash, DeployHeader, FinalitySignature};
+use crate::types::{
+    Block, BlockHash, BlockWriteStats, Deploy, DeployHash, DeployHeader, FinalitySignature,
+};
 
 #[derive(Debug)]
 pub enum Event {
@@ -30,6 +34,8 @@ pub enum Event {
         era_id: EraId,
         execution_effect: ExecutionEffect,
     },
+    EraRewards(Box<EraRewardReport>),
+    BlockWriteStats(Box<BlockWriteStats>),
 }
 
 impl Display for Event {
@@ -61,6 +67,16 @@ impl Display for Event {
             ),
             Event::FinalitySignature(fs) => write!(formatter, "finality signature {}", fs),
             Event::Step { era_id, .. } => write!(formatter, "step committed for {}", era_id),
+            Event::EraRewards(reward_report) => {
+                write!(
+                    formatter,
+                    "rewards distributed for {}",
+                    reward_report.era_id()
+                )
+            }
+            Event::BlockWriteStats(write_stats) => {
+                write!(formatter, "write stats of block {}", write_stats.block_hash)
+            }
         }
     }
 }
@@ -1,4 +1,7 @@
-use futures::{future, Future, FutureExt};
+use futures::{
+    future::{self, Either},
+    Future, FutureExt,
+};
 use tokio::{
     select,
     sync::{broadcast, mpsc, oneshot},
@@ -113,11 +116,19 @@ pub(super) async fn run(
 
     // Wait for the event stream future to exit, which will only happen if the last `data_sender`
     // paired with `data_receiver` is dropped.  `server_joiner` will never return here.
-    let _ = future::select(server_joiner, event_stream_fut.boxed()).await;
+    let maybe_server_joiner = match future::select(server_joiner, event_stream_fut.boxed()).await {
+        Either::Left(_) => None,
+        Either::Right((_, server_joiner)) => Some(server_joiner),
+    };
 
     // Kill the event-stream handlers, and shut down the server.
     let _ = broadcaster.send(BroadcastChannelMessage::Shutdown);
     let _ = server_shutdown_sender.send(());
 
+    // Let the server finish writing the final events to the connected clients before returning.
+    if let Some(server_joiner) = maybe_server_joiner {
+        let _ = server_joiner.await;
+    }
+
     trace!("Event stream server stopped");
 }
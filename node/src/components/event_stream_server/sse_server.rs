@@ -32,13 +32,16 @@ use warp::{
     Filter, Reply,
 };
 
+#[cfg(test)]
+use casper_types::system::auction::{EraInfo, SeigniorageAllocation};
 #[cfg(test)]
 use casper_types::testing::TestRng;
 use casper_types::{
-    EraId, ExecutionEffect, ExecutionResult, ProtocolVersion, PublicKey, TimeDiff, Timestamp,
+    system::auction::EraRewardReport, EraId, ExecutionEffect, ExecutionResult, ProtocolVersion,
+    PublicKey, TimeDiff, Timestamp,
 };
 
-use crate::types::{BlockHash, Deploy, DeployHash, FinalitySignature, JsonBlock};
+use crate::types::{BlockHash, BlockWriteStats, Deploy, DeployHash, FinalitySignature, JsonBlock};
 #[cfg(test)]
 use crate::{testing, types::Block};
 
@@ -55,12 +58,14 @@ pub const SSE_API_SIGNATURES_PATH: &str = "sigs";
 pub const QUERY_FIELD: &str = "start_from";
 
 /// The filter associated with `/events/main` path.
-const MAIN_FILTER: [EventFilter; 5] = [
+const MAIN_FILTER: [EventFilter; 7] = [
     EventFilter::BlockAdded,
     EventFilter::DeployProcessed,
     EventFilter::DeployExpired,
     EventFilter::Fault,
     EventFilter::Step,
+    EventFilter::EraRewards,
+    EventFilter::BlockWriteStats,
 ];
 /// The filter associated with `/events/deploys` path.
 const DEPLOYS_FILTER: [EventFilter; 1] = [EventFilter::DeployAccepted];
@@ -115,6 +120,10 @@ pub enum SseData {
         #[data_size(skip)]
         execution_effect: ExecutionEffect,
     },
+    /// The rewards distributed at the end of an era.
+    EraRewards(Box<EraRewardReport>),
+    /// The values the given block wrote to global state, by kind of key.
+    BlockWriteStats(Box<BlockWriteStats>),
     /// The node is about to shut down.
     Shutdown,
 }
@@ -130,6 +139,8 @@ impl SseData {
             SseData::Fault { .. } => filter.contains(&EventFilter::Fault),
             SseData::FinalitySignature(_) => filter.contains(&EventFilter::FinalitySignature),
             SseData::Step { .. } => filter.contains(&EventFilter::Step),
+            SseData::EraRewards(_) => filter.contains(&EventFilter::EraRewards),
+            SseData::BlockWriteStats(_) => filter.contains(&EventFilter::BlockWriteStats),
         }
     }
 }
@@ -215,6 +226,37 @@ impl SseData {
             execution_effect,
         }
     }
+
+    /// Returns a random `SseData::EraRewards`.
+    pub(super) fn random_era_rewards(rng: &mut TestRng) -> Self {
+        let mut era_info = EraInfo::new();
+        for _ in 0..rng.gen_range(1..4) {
+            let validator_public_key = PublicKey::random(rng);
+            era_info
+                .seigniorage_allocations_mut()
+                .push(SeigniorageAllocation::validator(
+                    validator_public_key.clone(),
+                    rng.gen::<u64>().into(),
+                ));
+            era_info
+                .seigniorage_allocations_mut()
+                .push(SeigniorageAllocation::delegator(
+                    PublicKey::random(rng),
+                    validator_public_key,
+                    rng.gen::<u64>().into(),
+                ));
+        }
+        SseData::EraRewards(Box::new(EraRewardReport::new(
+            EraId::new(rng.gen()),
+            &era_info,
+            rng.gen::<u64>().into(),
+        )))
+    }
+
+    /// Returns a random `SseData::BlockWriteStats`.
+    pub(super) fn random_block_write_stats(rng: &mut TestRng) -> Self {
+        SseData::BlockWriteStats(Box::new(BlockWriteStats::random(rng)))
+    }
 }
 
 #[derive(Serialize)]
@@ -274,6 +316,8 @@ pub(super) enum EventFilter {
     Fault,
     FinalitySignature,
     Step,
+    EraRewards,
+    BlockWriteStats,
 }
 
 /// Filters the `event`, mapping it to a warp event, or `None` if it should be filtered out.
@@ -315,6 +359,8 @@ async fn filter_map_server_sent_event(
         | &SseData::DeployExpired { .. }
         | &SseData::Fault { .. }
         | &SseData::Step { .. }
+        | &SseData::EraRewards(_)
+        | &SseData::BlockWriteStats(_)
         | &SseData::FinalitySignature(_)
         | &SseData::Shutdown => Some(Ok(WarpServerSentEvent::default()
             .json_data(&event.data)
@@ -198,7 +198,7 @@ struct TestFixture {
 impl TestFixture {
     /// Constructs a new `TestFixture` including `EVENT_COUNT` random events ready to be served.
     fn new(rng: &mut TestRng) -> Self {
-        const DISTINCT_EVENTS_COUNT: u32 = 7;
+        const DISTINCT_EVENTS_COUNT: u32 = 9;
 
         let _ = logging::init();
         let storage_dir = tempfile::tempdir().unwrap();
@@ -219,6 +219,8 @@ impl TestFixture {
                 4 => SseData::random_fault(rng),
                 5 => SseData::random_step(rng),
                 6 => SseData::random_finality_signature(rng),
+                7 => SseData::random_era_rewards(rng),
+                8 => SseData::random_block_write_stats(rng),
                 _ => unreachable!(),
             })
             .collect();
@@ -795,6 +797,59 @@ async fn server_exit_should_gracefully_shut_down_stream() {
     );
 }
 
+/// Checks that beginning the shutdown of the server (e.g. when the node drains ahead of exiting)
+/// sends connected clients a final `Shutdown` event, and that the returned future resolves once the
+/// server has stopped serving.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn begin_shutdown_should_send_shutdown_event_and_stop_server() {
+    let mut rng = crate::new_rng();
+    let fixture = TestFixture::new(&mut rng);
+    let mut server = EventStreamServer::new(
+        Config::default(),
+        fixture.storage_dir.path().to_path_buf(),
+        fixture.protocol_version,
+    );
+    server.listen().unwrap();
+    let url = url(
+        server.sse_server.as_ref().unwrap().listening_address,
+        MAIN_PATH,
+        None,
+    );
+
+    // Wait for the client to receive the initial `ApiVersion` event, so it is known to be
+    // subscribed before shutdown begins.
+    let response = reqwest::get(&url).await.unwrap();
+    let mut stream = response.bytes_stream();
+    let mut response_text = String::new();
+    while !response_text.contains("ApiVersion") {
+        let bytes = stream.next().await.unwrap().unwrap();
+        response_text.push_str(str::from_utf8(bytes.as_ref()).unwrap());
+    }
+
+    let shutdown = server.begin_shutdown();
+    let read_to_end = async {
+        while let Some(item) = stream.next().await {
+            let bytes = item.expect("stream should end without error");
+            response_text.push_str(str::from_utf8(bytes.as_ref()).unwrap());
+        }
+    };
+    let (shutdown_result, read_result) = join!(
+        time::timeout(MAX_TEST_TIME, shutdown),
+        time::timeout(MAX_TEST_TIME, read_to_end)
+    );
+    shutdown_result.expect("server should stop once clients have received the final events");
+    read_result.expect("client stream should end once the server stops");
+
+    let received_events = parse_response(response_text, "client");
+    assert_eq!(
+        received_events.last().unwrap().data,
+        serde_json::to_string(&SseData::Shutdown).unwrap()
+    );
+
+    // The server no longer accepts new clients.
+    assert!(reqwest::get(&url).await.is_err());
+}
+
 /// Checks that clients which don't consume the events in a timely manner are forcibly disconnected
 /// by the server.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -127,11 +127,11 @@ where
             },
             Event::GotInvalidRemotely { .. } => Effects::new(),
             Event::AbsentRemotely { id, peer } => {
-                trace!(TAG=%T::TAG, %id, %peer, "item absent on the remote node");
+                trace!(TAG=%T::TAG, %id, peer_id = %peer, "item absent on the remote node");
                 self.signal(id.clone(), Err(Error::Absent { id, peer }), peer)
             }
             Event::RejectedRemotely { id, peer } => {
-                trace!(TAG=%T::TAG, %id, %peer, "peer rejected fetch request");
+                trace!(TAG=%T::TAG, %id, peer_id = %peer, "peer rejected fetch request");
                 self.signal(id.clone(), Err(Error::Rejected { id, peer }), peer)
             }
             Event::TimeoutPeer { id, peer } => {
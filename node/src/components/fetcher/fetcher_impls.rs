@@ -2,6 +2,8 @@ mod approvals_hashes_fetcher;
 mod block_execution_results_or_chunk_fetcher;
 mod block_fetcher;
 mod block_header_fetcher;
+mod deploy_approvals_fetcher;
+mod deploy_execution_result_fetcher;
 mod deploy_fetcher;
 mod finality_signature_fetcher;
 mod legacy_deploy_fetcher;
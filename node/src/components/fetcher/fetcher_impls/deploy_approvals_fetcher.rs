@@ -0,0 +1,44 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::{
+    components::fetcher::{metrics::Metrics, Fetcher, ItemFetcher, ItemHandle, StoringState},
+    effect::{requests::StorageRequest, EffectBuilder},
+    types::{DeployApprovals, DeployHash, NodeId},
+};
+
+#[async_trait]
+impl ItemFetcher<DeployApprovals> for Fetcher<DeployApprovals> {
+    const SAFE_TO_RESPOND_TO_ALL: bool = false;
+
+    fn item_handles(
+        &mut self,
+    ) -> &mut HashMap<DeployHash, HashMap<NodeId, ItemHandle<DeployApprovals>>> {
+        &mut self.item_handles
+    }
+
+    fn metrics(&mut self) -> &Metrics {
+        &self.metrics
+    }
+
+    fn peer_timeout(&self) -> Duration {
+        self.get_from_peer_timeout
+    }
+
+    /// The approvals held locally are never what is sought, so they're always fetched from peers.
+    async fn get_from_storage<REv: From<StorageRequest> + Send>(
+        _effect_builder: EffectBuilder<REv>,
+        _id: DeployHash,
+    ) -> Option<DeployApprovals> {
+        None
+    }
+
+    /// Fetched approvals are merged into storage by the requester.
+    fn put_to_storage<'a, REv: From<StorageRequest> + Send>(
+        _effect_builder: EffectBuilder<REv>,
+        item: DeployApprovals,
+    ) -> StoringState<'a, DeployApprovals> {
+        StoringState::WontStore(item)
+    }
+}
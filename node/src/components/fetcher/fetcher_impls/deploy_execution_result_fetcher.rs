@@ -0,0 +1,51 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use futures::FutureExt;
+
+use crate::{
+    components::fetcher::{metrics::Metrics, Fetcher, ItemFetcher, ItemHandle, StoringState},
+    effect::{requests::StorageRequest, EffectBuilder},
+    types::{DeployExecutionResult, DeployExecutionResultId, NodeId},
+};
+
+#[async_trait]
+impl ItemFetcher<DeployExecutionResult> for Fetcher<DeployExecutionResult> {
+    const SAFE_TO_RESPOND_TO_ALL: bool = true;
+
+    fn item_handles(
+        &mut self,
+    ) -> &mut HashMap<DeployExecutionResultId, HashMap<NodeId, ItemHandle<DeployExecutionResult>>>
+    {
+        &mut self.item_handles
+    }
+
+    fn metrics(&mut self) -> &Metrics {
+        &self.metrics
+    }
+
+    fn peer_timeout(&self) -> Duration {
+        self.get_from_peer_timeout
+    }
+
+    async fn get_from_storage<REv: From<StorageRequest> + Send>(
+        effect_builder: EffectBuilder<REv>,
+        id: DeployExecutionResultId,
+    ) -> Option<DeployExecutionResult> {
+        effect_builder
+            .get_deploy_execution_result_from_storage(id)
+            .await
+    }
+
+    fn put_to_storage<'a, REv: From<StorageRequest> + Send>(
+        effect_builder: EffectBuilder<REv>,
+        item: DeployExecutionResult,
+    ) -> StoringState<'a, DeployExecutionResult> {
+        StoringState::Enqueued(
+            effect_builder
+                .put_deploy_execution_result_to_storage(Box::new(item))
+                .map(|_| ())
+                .boxed(),
+        )
+    }
+}
@@ -114,7 +114,7 @@ pub(super) trait ItemFetcher<T: FetchItem + 'static> {
             }
             .event(move |_| Event::TimeoutPeer { id, peer }),
             Err(error) => {
-                error!(%peer, %error, "failed to construct get request");
+                error!(peer_id = %peer, %error, "failed to construct get request");
 
                 self.signal(
                     id.clone(),
@@ -143,13 +143,13 @@ pub(super) trait ItemFetcher<T: FetchItem + 'static> {
         {
             Some(item_handle) => item_handle.validation_metadata(),
             None => {
-                debug!(item_id = %item.fetch_id(), tag = ?T::TAG, %peer, "got unexpected item from peer");
+                debug!(item_id = %item.fetch_id(), tag = ?T::TAG, peer_id = %peer, "got unexpected item from peer");
                 return Effects::new();
             }
         };
 
         if let Err(err) = item.validate(validation_metadata) {
-            debug!(%peer, %err, ?item, "peer sent invalid item");
+            debug!(peer_id = %peer, %err, ?item, "peer sent invalid item");
             effect_builder
                 .announce_block_peer_with_justification(
                     peer,
@@ -216,7 +216,7 @@ pub(super) trait ItemFetcher<T: FetchItem + 'static> {
                             // Only if there's still a responder waiting for the item we increment
                             // the metric. Otherwise we will count every request as timed out, even
                             // if the item had been fetched.
-                            trace!(TAG=%T::TAG, %id, %peer, "request timed out");
+                            trace!(TAG=%T::TAG, %id, peer_id = %peer, "request timed out");
                             self.metrics().timeouts.inc();
                         }
                         item_handle.has_no_responders()
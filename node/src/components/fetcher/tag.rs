@@ -51,4 +51,10 @@ pub enum Tag {
     /// The execution results for a single block.
     #[display(fmt = "block execution results")]
     BlockExecutionResults,
+    /// The approvals a peer holds for a deploy, identified by the deploy's hash.
+    #[display(fmt = "deploy approvals")]
+    DeployApprovals,
+    /// The execution result of a deploy in a block, with its proof.
+    #[display(fmt = "deploy execution result")]
+    DeployExecutionResult,
 }
@@ -56,17 +56,15 @@ where
 impl<const ID_IS_COMPLETE_ITEM: bool, T: GossipItem + 'static> Gossiper<ID_IS_COMPLETE_ITEM, T> {
     /// Constructs a new gossiper component.
     ///
-    /// Must be supplied with a name, which should be a snake-case identifier to disambiguate the
-    /// specific gossiper from other potentially present gossipers.
-    pub(crate) fn new(
-        name: &'static str,
-        config: Config,
-        registry: &Registry,
-    ) -> Result<Self, prometheus::Error> {
+    /// The gossiper is named after `T::GOSSIPER_NAME`, and any overrides configured for that name
+    /// are applied on top of the general gossip `config`.
+    pub(crate) fn new(config: &Config, registry: &Registry) -> Result<Self, prometheus::Error> {
+        let name = T::GOSSIPER_NAME;
+        let config = config.for_item(name);
         Ok(Gossiper {
-            table: GossipTable::new(config),
             gossip_timeout: config.gossip_request_timeout().into(),
             get_from_peer_timeout: config.get_remainder_timeout().into(),
+            table: GossipTable::new(config),
             name,
             metrics: Metrics::new(name, registry)?,
         })
@@ -354,6 +352,33 @@ impl<const ID_IS_COMPLETE_ITEM: bool, T: GossipItem + 'static> Gossiper<ID_IS_CO
         effects
     }
 
+    /// Handles an incoming gossip request for an item which needs to be verified before we gossip
+    /// it onwards.
+    ///
+    /// The item is not added to the gossip table; it will be once whoever verifies it requests we
+    /// begin gossiping it.
+    fn handle_unverified_gossip<REv>(
+        effect_builder: EffectBuilder<REv>,
+        item_id: T::Id,
+        sender: NodeId,
+    ) -> Effects<Event<T>>
+    where
+        REv: From<NetworkRequest<Message<T>>> + From<GossiperAnnouncement<T>> + Send,
+    {
+        debug!(item=%item_id, %sender, "received gossip request for unverified item");
+        let reply = Message::GossipResponse {
+            item_id: item_id.clone(),
+            is_already_held: false,
+        };
+        let mut effects = effect_builder.send_message(sender, reply).ignore();
+        effects.extend(
+            effect_builder
+                .announce_gossip_received(item_id, sender)
+                .ignore(),
+        );
+        effects
+    }
+
     /// Handles an incoming gossip response from a peer on the network.
     fn handle_gossip_response<REv>(
         &mut self,
@@ -609,9 +634,13 @@ where
             }
             Event::Incoming(GossiperIncoming::<T> { sender, message }) => match message {
                 Message::Gossip(item_id) => {
-                    let target = <T as SmallGossipItem>::id_as_item(&item_id).gossip_target();
-                    let action = self.table.new_complete_data(&item_id, Some(sender), target);
-                    self.handle_gossip(effect_builder, item_id, sender, action)
+                    if T::REQUIRES_VERIFICATION_BEFORE_GOSSIP && !self.table.contains(&item_id) {
+                        Self::handle_unverified_gossip(effect_builder, item_id, sender)
+                    } else {
+                        let target = <T as SmallGossipItem>::id_as_item(&item_id).gossip_target();
+                        let action = self.table.new_complete_data(&item_id, Some(sender), target);
+                        self.handle_gossip(effect_builder, item_id, sender, action)
+                    }
                 }
                 Message::GossipResponse {
                     item_id,
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
 use datasize::DataSize;
 use serde::{
@@ -11,6 +11,11 @@ use casper_types::TimeDiff;
 
 #[cfg(test)]
 use super::error::Error;
+use super::GossipItem;
+use crate::{
+    components::network::GossipedAddress,
+    types::{Block, Deploy, FinalitySignature},
+};
 
 const DEFAULT_INFECTION_TARGET: u8 = 3;
 const DEFAULT_SATURATION_LIMIT_PERCENT: u8 = 80;
@@ -25,8 +30,16 @@ const SMALL_TIMEOUTS_GOSSIP_REQUEST_TIMEOUT: &str = "1sec";
 #[cfg(test)]
 const SMALL_TIMEOUTS_GET_REMAINDER_TIMEOUT: &str = "1sec";
 
+/// The names of all gossipers for which overrides can be configured.
+const GOSSIPER_NAMES: [&str; 4] = [
+    GossipedAddress::GOSSIPER_NAME,
+    Block::GOSSIPER_NAME,
+    Deploy::GOSSIPER_NAME,
+    FinalitySignature::GOSSIPER_NAME,
+];
+
 /// Configuration options for gossiping.
-#[derive(Copy, Clone, DataSize, Debug, Deserialize, Serialize)]
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
 pub struct Config {
     /// Target number of peers to infect with a given piece of data.
     infection_target: u8,
@@ -50,6 +63,31 @@ pub struct Config {
     /// The timeout duration in seconds for retrieving the remaining part(s) of newly-discovered
     /// data from a peer which gossiped information about that data to this node.
     get_remainder_timeout: TimeDiff,
+    /// Per-gossiper overrides of the options above, keyed by the gossiper's name (e.g.
+    /// "deploy_gossiper").
+    #[serde(
+        default,
+        deserialize_with = "deserialize_overrides",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    overrides: BTreeMap<String, ItemConfig>,
+}
+
+/// Overrides of the gossip configuration options for a single gossiper.
+///
+/// Options which are not set fall back to the values of the general gossip config.
+#[derive(Copy, Clone, Default, DataSize, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemConfig {
+    infection_target: Option<u8>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_saturation_limit_percent"
+    )]
+    saturation_limit_percent: Option<u8>,
+    finished_entry_duration: Option<TimeDiff>,
+    gossip_request_timeout: Option<TimeDiff>,
+    get_remainder_timeout: Option<TimeDiff>,
 }
 
 impl Config {
@@ -70,6 +108,7 @@ impl Config {
             finished_entry_duration,
             gossip_request_timeout,
             get_remainder_timeout,
+            overrides: BTreeMap::new(),
         })
     }
 
@@ -105,6 +144,34 @@ impl Config {
     pub(crate) fn get_remainder_timeout(&self) -> TimeDiff {
         self.get_remainder_timeout
     }
+
+    /// Returns the config to be used by the gossiper named `gossiper_name`, i.e. this config with
+    /// any overrides for that gossiper applied.
+    pub(crate) fn for_item(&self, gossiper_name: &str) -> Config {
+        let item_config = self
+            .overrides
+            .get(gossiper_name)
+            .copied()
+            .unwrap_or_default();
+        Config {
+            infection_target: item_config
+                .infection_target
+                .unwrap_or(self.infection_target),
+            saturation_limit_percent: item_config
+                .saturation_limit_percent
+                .unwrap_or(self.saturation_limit_percent),
+            finished_entry_duration: item_config
+                .finished_entry_duration
+                .unwrap_or(self.finished_entry_duration),
+            gossip_request_timeout: item_config
+                .gossip_request_timeout
+                .unwrap_or(self.gossip_request_timeout),
+            get_remainder_timeout: item_config
+                .get_remainder_timeout
+                .unwrap_or(self.get_remainder_timeout),
+            overrides: BTreeMap::new(),
+        }
+    }
 }
 
 impl Default for Config {
@@ -115,6 +182,7 @@ impl Default for Config {
             finished_entry_duration: TimeDiff::from_str(DEFAULT_FINISHED_ENTRY_DURATION).unwrap(),
             gossip_request_timeout: TimeDiff::from_str(DEFAULT_GOSSIP_REQUEST_TIMEOUT).unwrap(),
             get_remainder_timeout: TimeDiff::from_str(DEFAULT_GET_REMAINDER_TIMEOUT).unwrap(),
+            overrides: BTreeMap::new(),
         }
     }
 }
@@ -139,6 +207,39 @@ where
     Ok(saturation_limit_percent)
 }
 
+/// Deserializes the per-gossiper overrides but fails if any of them is keyed by an unknown gossiper
+/// name, so that a misspelled name doesn't silently leave the gossiper unconfigured.
+fn deserialize_overrides<'de, D>(deserializer: D) -> Result<BTreeMap<String, ItemConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let overrides = BTreeMap::<String, ItemConfig>::deserialize(deserializer)?;
+    if let Some(unknown_name) = overrides
+        .keys()
+        .find(|name| !GOSSIPER_NAMES.contains(&name.as_str()))
+    {
+        error!(
+            "gossip overrides given for unknown gossiper {}, expected one of {:?}",
+            unknown_name, GOSSIPER_NAMES
+        );
+        return Err(SerdeError::invalid_value(
+            Unexpected::Str(unknown_name),
+            &"the name of a gossiper",
+        ));
+    }
+
+    Ok(overrides)
+}
+
+fn deserialize_optional_saturation_limit_percent<'de, D>(
+    deserializer: D,
+) -> Result<Option<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_saturation_limit_percent(deserializer).map(Some)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,6 +253,7 @@ mod tests {
             finished_entry_duration: TimeDiff::from_str(DEFAULT_FINISHED_ENTRY_DURATION).unwrap(),
             gossip_request_timeout: TimeDiff::from_str(DEFAULT_GOSSIP_REQUEST_TIMEOUT).unwrap(),
             get_remainder_timeout: TimeDiff::from_str(DEFAULT_GET_REMAINDER_TIMEOUT).unwrap(),
+            overrides: BTreeMap::new(),
         };
 
         // Parsing should fail.
@@ -168,4 +270,75 @@ mod tests {
         )
         .is_err())
     }
+
+    #[test]
+    fn should_apply_overrides_for_named_gossiper() {
+        let config: Config = toml::from_str(
+            r#"
+            infection_target = 3
+            saturation_limit_percent = 80
+            finished_entry_duration = '60sec'
+            gossip_request_timeout = '10sec'
+            get_remainder_timeout = '5sec'
+
+            [overrides.finality_signature_gossiper]
+            infection_target = 6
+            gossip_request_timeout = '2sec'
+            "#,
+        )
+        .unwrap();
+
+        let overridden = config.for_item("finality_signature_gossiper");
+        assert_eq!(overridden.infection_target(), 6);
+        assert_eq!(
+            overridden.gossip_request_timeout(),
+            TimeDiff::from_str("2sec").unwrap()
+        );
+        assert_eq!(overridden.saturation_limit_percent(), 80);
+        assert_eq!(
+            overridden.get_remainder_timeout(),
+            TimeDiff::from_str("5sec").unwrap()
+        );
+
+        let not_overridden = config.for_item("deploy_gossiper");
+        assert_eq!(not_overridden.infection_target(), 3);
+        assert_eq!(
+            not_overridden.gossip_request_timeout(),
+            TimeDiff::from_str("10sec").unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_override_should_fail() {
+        let result = toml::from_str::<Config>(
+            r#"
+            infection_target = 3
+            saturation_limit_percent = 80
+            finished_entry_duration = '60sec'
+            gossip_request_timeout = '10sec'
+            get_remainder_timeout = '5sec'
+
+            [overrides.deploy_gossiper]
+            saturation_limit_percent = 100
+            "#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn override_for_unknown_gossiper_should_fail() {
+        let result = toml::from_str::<Config>(
+            r#"
+            infection_target = 3
+            saturation_limit_percent = 80
+            finished_entry_duration = '60sec'
+            gossip_request_timeout = '10sec'
+            get_remainder_timeout = '5sec'
+
+            [overrides.finality_signatures_gossiper]
+            infection_target = 6
+            "#,
+        );
+        assert!(result.is_err());
+    }
 }
@@ -18,6 +18,16 @@ pub(crate) trait GossipItem:
     const ID_IS_COMPLETE_ITEM: bool;
     /// Whether the arrival of a new gossip message should be announced or not.
     const REQUIRES_GOSSIP_RECEIVED_ANNOUNCEMENT: bool;
+    /// Whether an item first received via gossip must be verified before we gossip it onwards.
+    ///
+    /// If `true`, the arrival of a gossip message for an item we don't yet hold is announced as
+    /// `GossipReceived`, but the item is only gossiped once a `BeginGossipRequest` for it is made.
+    /// Only supported for items whose ID is the complete item.
+    const REQUIRES_VERIFICATION_BEFORE_GOSSIP: bool;
+    /// The snake-case name of the gossiper handling this type of item.
+    ///
+    /// Used to disambiguate the gossiper's metrics and to select its configuration overrides.
+    const GOSSIPER_NAME: &'static str;
 
     /// The ID of the specific item.
     fn gossip_id(&self) -> Self::Id;
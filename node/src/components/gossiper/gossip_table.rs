@@ -227,6 +227,11 @@ impl<T: Clone + Eq + Hash + Display> GossipTable<T> {
         }
     }
 
+    /// Returns whether the given data is currently being gossiped or has recently finished.
+    pub(super) fn contains(&self, data_id: &T) -> bool {
+        self.current.contains_key(data_id) || self.finished.contains(data_id)
+    }
+
     /// We received knowledge about potentially new data with given ID from the given peer.  This
     /// should only be called where we don't already hold everything locally we need to be able to
     /// gossip it onwards.  If we are able to gossip the data already, call `new_complete_data`
@@ -435,7 +440,7 @@ impl<T: Clone + Eq + Hash + Display> GossipTable<T> {
         if let Some(mut state) = self.current.remove(data_id) {
             if !state.held_by_us() {
                 let _ = state.holders.remove(&peer);
-                trace!(item=%data_id, %peer, "removed peer as a holder of the item");
+                trace!(item=%data_id, peer_id = %peer, "removed peer as a holder of the item");
                 if state.holders.is_empty() {
                     // We don't hold the full data, and we don't know any holders - remove the entry
                     trace!(item=%data_id, "no further action: item now removed as no holders");
@@ -173,11 +173,8 @@ impl reactor::Reactor for Reactor {
         .unwrap();
 
         let fake_deploy_acceptor = FakeDeployAcceptor::new();
-        let deploy_gossiper = Gossiper::<{ Deploy::ID_IS_COMPLETE_ITEM }, _>::new(
-            "deploy_gossiper",
-            config,
-            registry,
-        )?;
+        let deploy_gossiper =
+            Gossiper::<{ Deploy::ID_IS_COMPLETE_ITEM }, _>::new(&config, registry)?;
 
         let reactor = Reactor {
             network,
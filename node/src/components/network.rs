@@ -23,16 +23,21 @@
 //! Nodes gossip their public listening addresses periodically, and will try to establish and
 //! maintain an outgoing connection to any new address learned.
 
+mod address_verification;
 mod bincode_format;
 pub(crate) mod blocklist;
 mod chain_info;
 mod config;
+mod connections;
 mod counting_format;
+mod diagnosis;
 mod error;
 mod event;
 mod gossiped_address;
+mod handshake_failures;
 mod health;
 mod identity;
+mod incoming_queue;
 mod insights;
 mod limiter;
 mod message;
@@ -43,6 +48,7 @@ mod symmetry;
 pub(crate) mod tasks;
 #[cfg(test)]
 mod tests;
+mod validator_endpoints;
 
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
@@ -76,41 +82,53 @@ use tracing::{debug, error, info, trace, warn, Instrument, Span};
 
 use casper_types::{EraId, PublicKey, SecretKey};
 
-pub(crate) use self::{
-    bincode_format::BincodeFormat,
-    config::{Config, IdentityConfig},
-    error::Error,
-    event::Event,
-    gossiped_address::GossipedAddress,
-    identity::Identity,
-    insights::NetworkInsights,
-    message::{EstimatorWeights, FromIncoming, Message, MessageKind, Payload},
-};
 use self::{
+    address_verification::AddressVerifier,
     blocklist::BlocklistJustification,
     chain_info::ChainInfo,
-    counting_format::{ConnectionId, CountingFormat, Role},
+    connections::{ConnectionStats, Connections},
+    counting_format::{CountingFormat, Role},
     error::{ConnectionError, Result},
     event::{IncomingConnection, OutgoingConnection},
+    handshake_failures::HandshakeFailures,
     health::{HealthConfig, TaggedTimestamp},
+    incoming_queue::{IncomingJob, IncomingQueue},
     limiter::Limiter,
     message::NodeKeyPair,
     metrics::Metrics,
     outgoing::{DialOutcome, DialRequest, OutgoingConfig, OutgoingManager},
     symmetry::ConnectionSymmetry,
     tasks::{MessageQueueItem, NetworkContext},
+    validator_endpoints::{ValidatorAddressBook, ValidatorEndpoint},
+};
+pub(crate) use self::{
+    bincode_format::BincodeFormat,
+    config::{Config, IdentityConfig},
+    connections::{ConnectionInfo, PeerLookupReport},
+    counting_format::ConnectionId,
+    diagnosis::{PartitionReport, PeerProbe, ProbeOutcome},
+    error::Error,
+    event::Event,
+    gossiped_address::GossipedAddress,
+    handshake_failures::HandshakeFailureReport,
+    identity::Identity,
+    incoming_queue::IncomingClass,
+    insights::NetworkInsights,
+    message::{EstimatorWeights, FromIncoming, Message, MessageKind, Payload},
 };
 use crate::{
     components::{gossiper::GossipItem, Component, ComponentState, InitializedComponent},
     effect::{
         announcements::PeerBehaviorAnnouncement,
         requests::{BeginGossipRequest, NetworkInfoRequest, NetworkRequest, StorageRequest},
-        AutoClosingResponder, EffectBuilder, EffectExt, Effects, GossipTarget,
+        AutoClosingResponder, EffectBuilder, EffectExt, Effects, GossipTarget, Responder,
     },
     reactor::{Finalize, ReactorEvent},
     tls,
     types::{NodeId, ValidatorMatrix},
-    utils::{self, display_error, Source},
+    utils::{
+        self, display_error, short_id::IdOrShortId, work_queue::BoundedWorkQueueMetrics, Source,
+    },
     NodeRng,
 };
 
@@ -145,11 +163,19 @@ const PING_TIMEOUT: Duration = Duration::from_secs(6);
 /// How many pings to send before giving up and dropping the connection.
 const PING_RETRIES: u16 = 5;
 
+/// How long a gossiped address may await verification before its claims are dropped.
+const ADDRESS_VERIFICATION_TIMEOUT: Duration = Duration::from_secs(60);
+
 #[derive(Clone, DataSize, Debug)]
 pub(crate) struct OutgoingHandle<P> {
     #[data_size(skip)] // Unfortunately, there is no way to inspect an `UnboundedSender`.
     sender: UnboundedSender<MessageQueueItem<P>>,
     peer_addr: SocketAddr,
+    /// The time it took to connect and complete the handshake.
+    handshake_duration: Duration,
+    /// How far the peer's clock was ahead of ours when it sent its handshake, in milliseconds,
+    /// if it reported its time.
+    clock_skew_ms: Option<i64>,
 }
 
 impl<P> Display for OutgoingHandle<P> {
@@ -173,10 +199,25 @@ where
     outgoing_manager: OutgoingManager<OutgoingHandle<P>, ConnectionError>,
     /// Tracks whether a connection is symmetric or not.
     connection_symmetries: HashMap<NodeId, ConnectionSymmetry>,
+    /// Established connections by their ID, along with recently closed ones.
+    #[data_size(skip)]
+    connections: Connections,
+    /// Failed connection attempts by remote address.
+    #[data_size(skip)]
+    handshake_failures: HandshakeFailures,
 
     /// Tracks nodes that have announced themselves as nodes that are syncing.
     syncing_nodes: HashSet<NodeId>,
 
+    /// Gossiped addresses which must be verified before we gossip them onwards.
+    address_verifier: AddressVerifier,
+
+    /// The validators of recent and upcoming eras.
+    validator_matrix: ValidatorMatrix,
+    /// Endpoints of the validators of recent and upcoming eras, used to connect to them directly.
+    #[data_size(skip)]
+    validator_address_book: ValidatorAddressBook,
+
     channel_management: Option<ChannelManagement>,
 
     /// Networking metrics.
@@ -193,6 +234,13 @@ where
     #[data_size(skip)]
     incoming_limiter: Limiter,
 
+    /// Incoming fetched items and gossip waiting to be handled.
+    #[data_size(skip)]
+    incoming_queue: Arc<IncomingQueue<P>>,
+    /// The metrics of the incoming queue, unregistered when dropped.
+    #[data_size(skip)]
+    _incoming_queue_metrics: BoundedWorkQueueMetrics,
+
     /// The era that is considered the active era by the network component.
     active_era: EraId,
 
@@ -255,9 +303,12 @@ where
         let incoming_limiter = Limiter::new(
             cfg.max_incoming_message_rate_non_validators,
             net_metrics.accumulated_incoming_limiter_delay.clone(),
-            validator_matrix,
+            validator_matrix.clone(),
         );
 
+        let incoming_queue = Arc::new(incoming_queue::new_incoming_queue()?);
+        let incoming_queue_metrics = incoming_queue.register_metrics(registry)?;
+
         let outgoing_manager = OutgoingManager::with_metrics(
             OutgoingConfig {
                 retry_attempts: RECONNECTION_ATTEMPTS,
@@ -287,11 +338,18 @@ where
             context,
             outgoing_manager,
             connection_symmetries: HashMap::new(),
+            connections: Connections::default(),
+            handshake_failures: HandshakeFailures::default(),
             syncing_nodes: HashSet::new(),
+            address_verifier: AddressVerifier::default(),
+            validator_matrix,
+            validator_address_book: ValidatorAddressBook::default(),
             channel_management: None,
             net_metrics,
             outgoing_limiter,
             incoming_limiter,
+            incoming_queue,
+            _incoming_queue_metrics: incoming_queue_metrics,
             // We start with an empty set of validators for era 0 and expect to be updated.
             active_era: EraId::new(0),
             state: ComponentState::Uninitialized,
@@ -389,6 +447,13 @@ where
                 .event(|_| Event::GossipOurAddress),
         );
 
+        // Start advertising our validator endpoints and connecting to other validators.
+        effects.extend(
+            effect_builder
+                .set_timeout(self.cfg.initial_gossip_delay.into())
+                .event(|_| Event::GossipOurValidatorEndpoints),
+        );
+
         // Start regular housekeeping of the outgoing connections.
         effects.extend(
             effect_builder
@@ -493,18 +558,18 @@ where
                 // We should never attempt to send an unsafe message to a peer that we know is still
                 // syncing. Since "unsafe" does usually not mean immediately catastrophic, we
                 // attempt to carry on, but warn loudly.
-                error!(kind=%msg.classify(), node_id=%dest, "sending unsafe message to syncing node");
+                error!(kind=%msg.classify(), peer_id=%dest, "sending unsafe message to syncing node");
             }
 
             if let Err(msg) = connection.sender.send((msg, opt_responder)) {
                 // We lost the connection, but that fact has not reached us yet.
-                warn!(our_id=%self.context.our_id(), %dest, ?msg, "dropped outgoing message, lost connection");
+                warn!(our_id=%self.context.our_id(), peer_id=%dest, ?msg, "dropped outgoing message, lost connection");
             } else {
                 self.net_metrics.queued_messages.inc();
             }
         } else {
             // We are not connected, so the reconnection is likely already in progress.
-            debug!(our_id=%self.context.our_id(), %dest, ?msg, "dropped outgoing message, no connection");
+            debug!(our_id=%self.context.our_id(), peer_id=%dest, ?msg, "dropped outgoing message, no connection");
         }
     }
 
@@ -515,15 +580,16 @@ where
     ) -> Effects<Event<P>> {
         span.clone().in_scope(|| match *incoming {
             IncomingConnection::FailedEarly {
-                peer_addr: _,
+                peer_addr,
                 ref error,
             } => {
                 // Failed without much info, there is little we can do about this.
                 debug!(err=%display_error(error), "incoming connection failed early");
+                self.record_handshake_failure(peer_addr, true, error, false);
                 Effects::new()
             }
             IncomingConnection::Failed {
-                peer_addr: _,
+                peer_addr,
                 peer_id: _,
                 ref error,
             } => {
@@ -533,6 +599,7 @@ where
                     err = display_error(error),
                     "incoming connection failed after TLS setup"
                 );
+                self.record_handshake_failure(peer_addr, true, error, false);
                 Effects::new()
             }
             IncomingConnection::Loopback => {
@@ -546,6 +613,8 @@ where
                 peer_addr,
                 public_addr,
                 peer_id,
+                connection_id,
+                stats,
                 peer_consensus_public_key,
                 stream,
             } => {
@@ -569,6 +638,8 @@ where
                 }
 
                 info!(%public_addr, "new incoming connection established");
+                self.connections
+                    .insert(connection_id, Role::Listener, peer_addr, peer_id, stats);
 
                 // Learn the address the peer gave us.
                 let dial_requests =
@@ -601,6 +672,7 @@ where
                 effects.extend(
                     tasks::message_reader(
                         self.context.clone(),
+                        Arc::clone(&self.incoming_queue),
                         stream,
                         self.incoming_limiter
                             .create_handle(peer_id, peer_consensus_public_key),
@@ -612,7 +684,8 @@ where
                     .event(move |result| Event::IncomingClosed {
                         result,
                         peer_id: Box::new(peer_id),
-                        peer_addr,
+                        peer_addr: Box::new(peer_addr),
+                        connection_id: Box::new(connection_id),
                         span: boxed_span,
                     }),
                 );
@@ -627,6 +700,7 @@ where
         result: io::Result<()>,
         peer_id: Box<NodeId>,
         peer_addr: SocketAddr,
+        connection_id: ConnectionId,
         span: Span,
     ) -> Effects<Event<P>> {
         span.in_scope(|| {
@@ -640,6 +714,8 @@ where
                 }
             }
 
+            self.connections.close(connection_id);
+
             // Update the connection symmetries.
             self.connection_symmetries
                 .entry(*peer_id)
@@ -697,17 +773,49 @@ where
         }
     }
 
+    /// Records a failed connection attempt in the per-address bookkeeping and the metrics.
+    fn record_handshake_failure(
+        &mut self,
+        peer_addr: SocketAddr,
+        incoming: bool,
+        error: &ConnectionError,
+        banned: bool,
+    ) {
+        let kind = self
+            .handshake_failures
+            .record(peer_addr, incoming, error, banned);
+        self.net_metrics.record_handshake_failure(kind, banned);
+    }
+
     /// Sets up an established outgoing connection.
     ///
     /// Initiates sending of the handshake as soon as the connection is established.
     #[allow(clippy::redundant_clone)]
     fn handle_outgoing_connection(
         &mut self,
+        effect_builder: EffectBuilder<REv>,
         outgoing: OutgoingConnection<P>,
         span: Span,
     ) -> Effects<Event<P>> {
         let now = Instant::now();
-        span.clone().in_scope(|| match outgoing {
+
+        // Resolve any gossiped claims about the dialed address.
+        let verified = match &outgoing {
+            OutgoingConnection::FailedEarly { peer_addr, .. }
+            | OutgoingConnection::Failed { peer_addr, .. } => {
+                self.address_verifier.unreachable(*peer_addr);
+                None
+            }
+            OutgoingConnection::Loopback { peer_addr } => self
+                .address_verifier
+                .connected(*peer_addr, self.context.our_id()),
+            OutgoingConnection::Established {
+                peer_addr, peer_id, ..
+            } => self.address_verifier.connected(*peer_addr, *peer_id),
+        };
+        let mut effects = Self::gossip_verified_address(effect_builder, verified);
+
+        effects.extend(span.clone().in_scope(|| match outgoing {
             OutgoingConnection::FailedEarly { peer_addr, error }
             | OutgoingConnection::Failed {
                 peer_addr,
@@ -718,7 +826,14 @@ where
                 // We perform blocking first, to not trigger a reconnection before blocking.
                 let mut requests = Vec::new();
 
-                if let Some(justification) = self.is_blockable_offense_for_outgoing(&error) {
+                let maybe_justification = self.is_blockable_offense_for_outgoing(&error);
+                self.record_handshake_failure(
+                    peer_addr,
+                    false,
+                    &error,
+                    maybe_justification.is_some(),
+                );
+                if let Some(justification) = maybe_justification {
                     requests.extend(
                         self.outgoing_manager
                             .block_addr(peer_addr, now, justification)
@@ -750,14 +865,25 @@ where
             OutgoingConnection::Established {
                 peer_addr,
                 peer_id,
+                connection_id,
+                stats,
                 peer_consensus_public_key,
                 sink,
                 is_syncing,
+                handshake_duration,
+                clock_skew_ms,
             } => {
                 info!("new outgoing connection established");
+                self.connections
+                    .insert(connection_id, Role::Dialer, peer_addr, peer_id, stats);
 
                 let (sender, receiver) = mpsc::unbounded_channel();
-                let handle = OutgoingHandle { sender, peer_addr };
+                let handle = OutgoingHandle {
+                    sender,
+                    peer_addr,
+                    handshake_duration,
+                    clock_skew_ms,
+                };
 
                 let request = self
                     .outgoing_manager
@@ -793,12 +919,179 @@ where
                     .event(move |_| Event::OutgoingDropped {
                         peer_id: Box::new(peer_id),
                         peer_addr,
+                        connection_id: Box::new(connection_id),
                     }),
                 );
 
                 effects
             }
-        })
+        }));
+        effects
+    }
+
+    /// Handles a peer address received via gossip, which needs to be verified before it is
+    /// gossiped onwards.
+    fn handle_peer_address_received(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        gossiped_address: GossipedAddress,
+        advertiser: NodeId,
+    ) -> Effects<Event<P>> {
+        let addr = SocketAddr::from(gossiped_address);
+        if self.outgoing_manager.is_blocked(addr) {
+            debug!(%gossiped_address, %advertiser, "ignoring gossiped blocked address");
+            return Effects::new();
+        }
+
+        let now = Instant::now();
+        let is_new = self
+            .address_verifier
+            .add_claim(gossiped_address, advertiser, now);
+
+        // If we are already connected to the address, the claim can be checked right away.
+        let connected_peer = self
+            .outgoing_manager
+            .connected_peers()
+            .find(|peer_id| self.outgoing_manager.get_addr(*peer_id) == Some(addr));
+        if let Some(peer_id) = connected_peer {
+            let verified = self.address_verifier.connected(addr, peer_id);
+            return Self::gossip_verified_address(effect_builder, verified);
+        }
+
+        if !is_new {
+            return Effects::new();
+        }
+        let request = self.outgoing_manager.learn_addr(addr, false, now);
+        self.process_dial_requests(request)
+    }
+
+    /// Gossips a verified address onwards.
+    fn gossip_verified_address(
+        effect_builder: EffectBuilder<REv>,
+        verified: Option<GossipedAddress>,
+    ) -> Effects<Event<P>> {
+        match verified {
+            Some(verified) => {
+                debug!(%verified, "verified gossiped address");
+                effect_builder
+                    .begin_gossip(verified, Source::Ourself, verified.gossip_target())
+                    .ignore()
+            }
+            None => Effects::new(),
+        }
+    }
+
+    /// Floods our endpoint to the validators of every era we are bonded in, and connects to the
+    /// other validators of those eras.
+    fn gossip_our_validator_endpoints(&mut self) -> Effects<Event<P>> {
+        let eras = self.validator_matrix.eras();
+        self.validator_address_book.retain_eras(&eras);
+
+        let key_pair = match self.context.node_key_pair() {
+            Some(key_pair) => key_pair,
+            None => return Effects::new(),
+        };
+        let public_addr = self
+            .context
+            .public_addr()
+            .expect("component not initialized properly");
+        let our_eras: Vec<_> = eras
+            .into_iter()
+            .filter(|era_id| {
+                self.validator_matrix
+                    .is_validator_in_era(*era_id, key_pair.public_key())
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        for era_id in &our_eras {
+            let endpoint = ValidatorEndpoint::create(
+                &self.context.chain_info().network_name,
+                *era_id,
+                self.context.our_id(),
+                public_addr,
+                key_pair,
+            );
+            let _ = self.validator_address_book.insert(endpoint.clone());
+            self.flood_validator_endpoint(endpoint, None);
+        }
+
+        // Redial any validators we lost the connection to since learning their endpoints.
+        let now = Instant::now();
+        let requests: Vec<_> = our_eras
+            .iter()
+            .flat_map(|era_id| self.validator_address_book.endpoints(*era_id))
+            .filter(|endpoint| self.needs_validator_connection(endpoint))
+            .map(ValidatorEndpoint::address)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|addr| self.outgoing_manager.learn_addr(addr, false, now))
+            .collect();
+        self.process_dial_requests(requests)
+    }
+
+    /// Sends a validator endpoint to all connected peers except `sender`.
+    fn flood_validator_endpoint(&self, endpoint: ValidatorEndpoint, sender: Option<NodeId>) {
+        let msg = Arc::new(Message::ValidatorEndpoint(Box::new(endpoint)));
+        for peer_id in self.outgoing_manager.connected_peers() {
+            if Some(peer_id) != sender {
+                self.send_message(peer_id, msg.clone(), None);
+            }
+        }
+    }
+
+    /// Returns `true` if we are a validator in the endpoint's era too, but not yet connected to the
+    /// endpoint's node.
+    fn needs_validator_connection(&self, endpoint: &ValidatorEndpoint) -> bool {
+        let is_fellow_validator = self.context.node_key_pair().map_or(false, |key_pair| {
+            self.validator_matrix
+                .is_validator_in_era(endpoint.era_id(), key_pair.public_key())
+                .unwrap_or(false)
+        });
+        is_fellow_validator
+            && endpoint.node_id() != self.context.our_id()
+            && self
+                .outgoing_manager
+                .get_route(endpoint.node_id())
+                .is_none()
+            && !self.outgoing_manager.is_blocked(endpoint.address())
+    }
+
+    /// Handles a validator endpoint received from a peer, flooding it onwards if it is new and
+    /// connecting to it if the validator is one of our fellow validators.
+    fn handle_validator_endpoint_received(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        endpoint: ValidatorEndpoint,
+        sender: NodeId,
+    ) -> Effects<Event<P>> {
+        if let Err(error) = endpoint.verify(&self.context.chain_info().network_name) {
+            warn!(%endpoint, %error, "received validator endpoint with invalid signature");
+            return effect_builder
+                .announce_block_peer_with_justification(
+                    sender,
+                    BlocklistJustification::SentInvalidValidatorEndpoint,
+                )
+                .ignore();
+        }
+        // Endpoints of eras we know nothing about yet are dropped; they will be advertised again.
+        let is_validator = self
+            .validator_matrix
+            .is_validator_in_era(endpoint.era_id(), endpoint.public_key())
+            .unwrap_or(false);
+        if !is_validator || !self.validator_address_book.insert(endpoint.clone()) {
+            return Effects::new();
+        }
+
+        debug!(%endpoint, "learned validator endpoint");
+        let request = if self.needs_validator_connection(&endpoint) {
+            self.outgoing_manager
+                .learn_addr(endpoint.address(), false, Instant::now())
+        } else {
+            None
+        };
+        self.flood_validator_endpoint(endpoint, Some(sender));
+        self.process_dial_requests(request)
     }
 
     fn handle_network_request(
@@ -862,7 +1155,10 @@ where
         &mut self,
         peer_id: NodeId,
         peer_addr: SocketAddr,
+        connection_id: ConnectionId,
     ) -> Effects<Event<P>> {
+        self.connections.close(connection_id);
+
         let requests = self
             .outgoing_manager
             .handle_connection_drop(peer_addr, Instant::now());
@@ -954,6 +1250,9 @@ where
                     Effects::new()
                 }
             }
+            Message::ValidatorEndpoint(endpoint) => {
+                self.handle_validator_endpoint_received(effect_builder, *endpoint, peer_id)
+            }
             Message::Payload(payload) => {
                 effect_builder.announce_incoming(peer_id, payload).ignore()
             }
@@ -1002,6 +1301,15 @@ where
         ret
     }
 
+    /// Resolves a full or short node ID to the matching peers and their addresses.
+    fn lookup_peers(&self, node_id: &IdOrShortId<NodeId>) -> PeerLookupReport {
+        let mut peers = self.connections.lookup_peers(node_id);
+        for peer in &mut peers {
+            peer.set_public_addr(self.outgoing_manager.get_addr(peer.node_id()));
+        }
+        PeerLookupReport { peers }
+    }
+
     pub(crate) fn fully_connected_peers_random(
         &self,
         rng: &mut NodeRng,
@@ -1015,6 +1323,43 @@ where
             .choose_multiple(rng, count)
     }
 
+    /// Probes a random sample of up to `sample_size` known addresses and responds with a report on
+    /// the outcomes.
+    ///
+    /// Probing inspects the outgoing connections to the addresses as they stand, so no connections
+    /// are opened for it. Addresses which are blocked or known to be our own are not probed.
+    fn diagnose_partition(
+        &self,
+        rng: &mut NodeRng,
+        sample_size: usize,
+        responder: Responder<PartitionReport>,
+    ) -> Effects<Event<P>> {
+        let known_addresses = self.outgoing_manager.outgoing.len();
+        let probes = self
+            .outgoing_manager
+            .outgoing
+            .iter()
+            .filter_map(|(addr, outgoing)| {
+                ProbeOutcome::from_outgoing_state(&outgoing.state).map(|outcome| PeerProbe {
+                    addr: *addr,
+                    outcome,
+                })
+            })
+            .choose_multiple(rng, sample_size);
+        let fully_connected_peers = self
+            .connection_symmetries
+            .values()
+            .filter(|sym| matches!(sym, ConnectionSymmetry::Symmetric { .. }))
+            .count();
+        let report = PartitionReport::new(
+            self.context.our_id(),
+            known_addresses,
+            fully_connected_peers,
+            probes,
+        );
+        responder.respond(report).ignore()
+    }
+
     pub(crate) fn has_sufficient_fully_connected_peers(&self) -> bool {
         self.connection_symmetries
             .iter()
@@ -1157,13 +1502,15 @@ where
                 },
                 Event::IncomingConnection { .. }
                 | Event::IncomingMessage { .. }
+                | Event::IncomingMessageQueued
                 | Event::IncomingClosed { .. }
                 | Event::OutgoingConnection { .. }
                 | Event::OutgoingDropped { .. }
                 | Event::NetworkRequest { .. }
                 | Event::NetworkInfoRequest { .. }
                 | Event::GossipOurAddress
-                | Event::PeerAddressReceived(_)
+                | Event::GossipOurValidatorEndpoints
+                | Event::PeerAddressReceived { .. }
                 | Event::SweepOutgoing
                 | Event::BlocklistAnnouncement(_) => {
                     warn!(
@@ -1189,18 +1536,29 @@ where
                 Event::IncomingMessage { peer_id, msg, span } => {
                     self.handle_incoming_message(effect_builder, *peer_id, *msg, span)
                 }
+                Event::IncomingMessageQueued => match self.incoming_queue.pop_job() {
+                    Some((_, IncomingJob { peer_id, msg, span })) => {
+                        self.handle_incoming_message(effect_builder, peer_id, msg, span)
+                    }
+                    None => Effects::new(),
+                },
                 Event::IncomingClosed {
                     result,
                     peer_id,
                     peer_addr,
+                    connection_id,
                     span,
-                } => self.handle_incoming_closed(result, peer_id, peer_addr, *span),
-                Event::OutgoingConnection { outgoing, span } => {
-                    self.handle_outgoing_connection(*outgoing, span)
+                } => {
+                    self.handle_incoming_closed(result, peer_id, *peer_addr, *connection_id, *span)
                 }
-                Event::OutgoingDropped { peer_id, peer_addr } => {
-                    self.handle_outgoing_dropped(*peer_id, peer_addr)
+                Event::OutgoingConnection { outgoing, span } => {
+                    self.handle_outgoing_connection(effect_builder, *outgoing, span)
                 }
+                Event::OutgoingDropped {
+                    peer_id,
+                    peer_addr,
+                    connection_id,
+                } => self.handle_outgoing_dropped(*peer_id, peer_addr, *connection_id),
                 Event::NetworkRequest { req: request } => {
                     self.handle_network_request(*request, rng)
                 }
@@ -1215,6 +1573,22 @@ where
                         NetworkInfoRequest::Insight { responder } => responder
                             .respond(NetworkInsights::collect_from_component(self))
                             .ignore(),
+                        NetworkInfoRequest::DiagnosePartition {
+                            sample_size,
+                            responder,
+                        } => self.diagnose_partition(rng, sample_size, responder),
+                        NetworkInfoRequest::Connection {
+                            connection_id,
+                            responder,
+                        } => responder
+                            .respond(self.connections.get(&connection_id))
+                            .ignore(),
+                        NetworkInfoRequest::LookupPeer { node_id, responder } => {
+                            responder.respond(self.lookup_peers(&node_id)).ignore()
+                        }
+                        NetworkInfoRequest::HandshakeFailures { ip, responder } => responder
+                            .respond(self.handshake_failures.report(ip))
+                            .ignore(),
                     }
                 }
                 Event::GossipOurAddress => {
@@ -1222,6 +1596,7 @@ where
                         self.context
                             .public_addr()
                             .expect("component not initialized properly"),
+                        self.context.our_id(),
                     );
 
                     let mut effects = effect_builder
@@ -1234,16 +1609,27 @@ where
                     );
                     effects
                 }
-                Event::PeerAddressReceived(gossiped_address) => {
-                    let requests = self.outgoing_manager.learn_addr(
-                        gossiped_address.into(),
-                        false,
-                        Instant::now(),
+                Event::GossipOurValidatorEndpoints => {
+                    let mut effects = self.gossip_our_validator_endpoints();
+                    effects.extend(
+                        effect_builder
+                            .set_timeout(self.cfg.gossip_interval.into())
+                            .event(|_| Event::GossipOurValidatorEndpoints),
                     );
-                    self.process_dial_requests(requests)
+                    effects
                 }
+                Event::PeerAddressReceived {
+                    gossiped_address,
+                    advertiser,
+                } => self.handle_peer_address_received(
+                    effect_builder,
+                    *gossiped_address,
+                    *advertiser,
+                ),
                 Event::SweepOutgoing => {
                     let now = Instant::now();
+                    self.address_verifier
+                        .expire(now, ADDRESS_VERIFICATION_TIMEOUT);
                     let requests = self.outgoing_manager.perform_housekeeping(rng, now);
 
                     let mut effects = self.process_dial_requests(requests);
@@ -1331,6 +1717,7 @@ pub(crate) type FramedTransport = tokio_util::codec::Framed<Transport, LengthDel
 /// A full transport contains the framing as well as the encoding scheme used to send messages.
 fn full_transport<P>(
     metrics: Weak<Metrics>,
+    stats: Arc<ConnectionStats>,
     connection_id: ConnectionId,
     framed: FramedTransport,
     role: Role,
@@ -1341,7 +1728,13 @@ where
 {
     tokio_serde::Framed::new(
         framed,
-        CountingFormat::new(metrics, connection_id, role, BincodeFormat::default()),
+        CountingFormat::new(
+            metrics,
+            stats,
+            connection_id,
+            role,
+            BincodeFormat::default(),
+        ),
     )
 }
 
@@ -0,0 +1,196 @@
+//! Verification of peer addresses learned via gossip.
+//!
+//! A gossiped address claims that a specific node is listening on it. Before such an address is
+//! gossiped onwards, we connect to it ourselves and check that the node completing the handshake
+//! is the one claimed. This prevents nodes from poisoning address books across the network with
+//! addresses that are unreachable or belong to someone else.
+//!
+//! Claims that turn out to be inaccurate are dropped, but their advertisers are not punished: node
+//! IDs are regenerated whenever a node restarts, so honest peers regularly gossip addresses whose
+//! node ID has since changed.
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use datasize::DataSize;
+use tracing::debug;
+
+use super::GossipedAddress;
+use crate::types::NodeId;
+
+/// Claims about a single address which have not been verified yet.
+#[derive(DataSize, Debug)]
+struct PendingClaims {
+    /// The advertisers of each node ID claimed to be listening on the address.
+    claims: HashMap<NodeId, HashSet<NodeId>>,
+    /// The time the first claim was received.
+    #[data_size(skip)]
+    since: Instant,
+}
+
+/// Tracks gossiped addresses awaiting verification.
+#[derive(DataSize, Debug, Default)]
+pub(super) struct AddressVerifier {
+    pending: HashMap<SocketAddr, PendingClaims>,
+}
+
+impl AddressVerifier {
+    /// Records that `advertiser` claimed the node `gossiped_address.node_id()` is listening on
+    /// `addr`.
+    ///
+    /// Returns `true` if no claims about `addr` were pending before.
+    pub(super) fn add_claim(
+        &mut self,
+        gossiped_address: GossipedAddress,
+        advertiser: NodeId,
+        now: Instant,
+    ) -> bool {
+        let addr = SocketAddr::from(gossiped_address);
+        let is_new = !self.pending.contains_key(&addr);
+        let _ = self
+            .pending
+            .entry(addr)
+            .or_insert_with(|| PendingClaims {
+                claims: HashMap::new(),
+                since: now,
+            })
+            .claims
+            .entry(gossiped_address.node_id())
+            .or_default()
+            .insert(advertiser);
+        is_new
+    }
+
+    /// Resolves the claims about `addr` after finding `node_id` listening on it.
+    ///
+    /// Returns the verified address if any claim named `node_id`. All other claims are dropped.
+    pub(super) fn connected(
+        &mut self,
+        addr: SocketAddr,
+        node_id: NodeId,
+    ) -> Option<GossipedAddress> {
+        let pending = self.pending.remove(&addr)?;
+        let mut verified = None;
+        for (claimed_node_id, advertisers) in pending.claims {
+            if claimed_node_id == node_id {
+                verified = Some(GossipedAddress::new(addr, node_id));
+            } else {
+                debug!(
+                    %addr,
+                    %claimed_node_id,
+                    %node_id,
+                    ?advertisers,
+                    "dropping outdated or false gossiped address claim"
+                );
+            }
+        }
+        verified
+    }
+
+    /// Drops the claims about `addr` after failing to connect or to complete a handshake with it.
+    pub(super) fn unreachable(&mut self, addr: SocketAddr) {
+        if self.pending.remove(&addr).is_some() {
+            debug!(%addr, "dropping claims about unreachable gossiped address");
+        }
+    }
+
+    /// Drops all claims which have been pending for longer than `timeout`.
+    pub(super) fn expire(&mut self, now: Instant, timeout: Duration) {
+        self.pending
+            .retain(|_, pending| now.saturating_duration_since(pending.since) < timeout);
+    }
+
+    /// Returns the number of addresses awaiting verification.
+    #[cfg(test)]
+    fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn should_verify_accurate_claims_and_drop_false_ones() {
+        let mut rng = TestRng::new();
+        let now = Instant::now();
+        let mut verifier = AddressVerifier::default();
+
+        let listener = NodeId::random(&mut rng);
+        let impostor = NodeId::random(&mut rng);
+        let honest_advertiser = NodeId::random(&mut rng);
+        let dishonest_advertiser = NodeId::random(&mut rng);
+
+        assert!(verifier.add_claim(
+            GossipedAddress::new(addr(1), listener),
+            honest_advertiser,
+            now
+        ));
+        assert!(!verifier.add_claim(
+            GossipedAddress::new(addr(1), impostor),
+            dishonest_advertiser,
+            now
+        ));
+
+        assert_eq!(
+            verifier.connected(addr(1), listener),
+            Some(GossipedAddress::new(addr(1), listener))
+        );
+        assert_eq!(verifier.pending_count(), 0);
+    }
+
+    #[test]
+    fn should_not_verify_claims_naming_a_previous_node_id() {
+        let mut rng = TestRng::new();
+        let now = Instant::now();
+        let mut verifier = AddressVerifier::default();
+
+        // The node restarted with a new ID after the advertiser learned its address.
+        let previous_id = NodeId::random(&mut rng);
+        let current_id = NodeId::random(&mut rng);
+        let _ = verifier.add_claim(
+            GossipedAddress::new(addr(1), previous_id),
+            NodeId::random(&mut rng),
+            now,
+        );
+
+        assert_eq!(verifier.connected(addr(1), current_id), None);
+        assert_eq!(verifier.pending_count(), 0);
+    }
+
+    #[test]
+    fn should_drop_unreachable_and_expired_claims() {
+        let mut rng = TestRng::new();
+        let now = Instant::now();
+        let mut verifier = AddressVerifier::default();
+
+        let _ = verifier.add_claim(
+            GossipedAddress::new(addr(1), NodeId::random(&mut rng)),
+            NodeId::random(&mut rng),
+            now,
+        );
+        let _ = verifier.add_claim(
+            GossipedAddress::new(addr(2), NodeId::random(&mut rng)),
+            NodeId::random(&mut rng),
+            now + Duration::from_secs(10),
+        );
+
+        verifier.unreachable(addr(1));
+        assert_eq!(verifier.pending_count(), 1);
+
+        verifier.expire(now + Duration::from_secs(15), Duration::from_secs(10));
+        assert_eq!(verifier.pending_count(), 1);
+        verifier.expire(now + Duration::from_secs(20), Duration::from_secs(10));
+        assert_eq!(verifier.pending_count(), 0);
+    }
+}
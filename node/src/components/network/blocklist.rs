@@ -55,6 +55,13 @@ pub(crate) enum BlocklistJustification {
     MissingChainspecHash,
     /// Peer is considered dishonest.
     DishonestPeer,
+    /// Peer sent a validator endpoint with an invalid signature.
+    SentInvalidValidatorEndpoint,
+    /// Peer sent a sync leap whose switch block was outvoted by other peers.
+    SentConflictingSwitchBlock {
+        /// The era of the switch block.
+        era: EraId,
+    },
 }
 
 impl Display for BlocklistJustification {
@@ -99,6 +106,14 @@ impl Display for BlocklistJustification {
                 write!(f, "sent a block that is invalid or unexpected ({})", error)
             }
             BlocklistJustification::DishonestPeer => f.write_str("dishonest peer"),
+            BlocklistJustification::SentInvalidValidatorEndpoint => {
+                f.write_str("sent a validator endpoint with an invalid signature")
+            }
+            BlocklistJustification::SentConflictingSwitchBlock { era } => write!(
+                f,
+                "sent a sync leap with a switch block of {} outvoted by other peers",
+                era
+            ),
         }
     }
 }
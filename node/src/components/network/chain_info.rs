@@ -6,7 +6,7 @@
 use std::net::SocketAddr;
 
 use casper_hashing::Digest;
-use casper_types::ProtocolVersion;
+use casper_types::{ProtocolVersion, Timestamp};
 use datasize::DataSize;
 
 use super::{
@@ -61,6 +61,7 @@ impl ChainInfo {
                 .map(|key_pair| ConsensusCertificate::create(connection_id, key_pair)),
             is_syncing,
             chainspec_hash: Some(self.chainspec_hash),
+            timestamp: Some(Timestamp::now()),
         }
     }
 }
@@ -0,0 +1,332 @@
+//! Bookkeeping of established connections by their [`ConnectionId`].
+//!
+//! Both ends of a connection derive the same ID, which is attached to every log line about the
+//! connection. Tracking connections by ID lets an operator handed an ID from a peer's logs look up
+//! which peer and address it belongs to, along with per-connection traffic counters, even for a
+//! while after the connection was closed.  The same bookkeeping resolves the short node IDs found
+//! in logs to the full IDs and addresses of the peers.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    fmt::{self, Display, Formatter},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use serde::Serialize;
+
+use casper_types::Timestamp;
+
+use super::counting_format::{ConnectionId, Role};
+use crate::{
+    types::NodeId,
+    utils::{
+        opt_display::OptDisplay,
+        short_id::{IdOrShortId, ShortId},
+    },
+};
+
+/// The number of closed connections remembered for lookups.
+const MAX_CLOSED_CONNECTIONS: usize = 256;
+
+/// Traffic counters of a single connection, updated by its transport.
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionStats {
+    messages_in: AtomicU64,
+    bytes_in: AtomicU64,
+    messages_out: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+impl ConnectionStats {
+    /// Records a received message of `msg_size` bytes.
+    pub(super) fn record_in(&self, msg_size: u64) {
+        self.messages_in.fetch_add(1, Ordering::Relaxed);
+        self.bytes_in.fetch_add(msg_size, Ordering::Relaxed);
+    }
+
+    /// Records a sent message of `msg_size` bytes.
+    pub(super) fn record_out(&self, msg_size: u64) {
+        self.messages_out.fetch_add(1, Ordering::Relaxed);
+        self.bytes_out.fetch_add(msg_size, Ordering::Relaxed);
+    }
+}
+
+/// Information about a connection.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct ConnectionInfo {
+    /// The ID of the connection, shared by both ends.
+    connection_id: ConnectionId,
+    /// Our role in the connection.
+    role: Role,
+    /// The address of the peer.
+    ///
+    /// For incoming connections this is the port the peer dialed us from, not its public address.
+    peer_addr: SocketAddr,
+    /// The peer's [`NodeId`].
+    peer_id: NodeId,
+    /// When the connection was established.
+    established: Timestamp,
+    /// When the connection was closed, if it was.
+    closed: Option<Timestamp>,
+    /// The number of messages received.
+    messages_in: u64,
+    /// The number of bytes received, excluding framing.
+    bytes_in: u64,
+    /// The number of messages sent.
+    messages_out: u64,
+    /// The number of bytes sent, excluding framing.
+    bytes_out: u64,
+}
+
+impl Display for ConnectionInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "connection {} ({})",
+            self.connection_id,
+            base16::encode_lower(self.connection_id.as_bytes())
+        )?;
+        writeln!(f, "role: {:?}", self.role)?;
+        writeln!(f, "peer: {} {}", self.peer_addr, self.peer_id)?;
+        writeln!(f, "established: {}", self.established)?;
+        writeln!(f, "closed: {}", OptDisplay::new(self.closed, "still open"))?;
+        writeln!(
+            f,
+            "received: {} messages, {} bytes",
+            self.messages_in, self.bytes_in
+        )?;
+        writeln!(
+            f,
+            "sent: {} messages, {} bytes",
+            self.messages_out, self.bytes_out
+        )
+    }
+}
+
+/// A peer whose node ID matched a lookup.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct PeerLookup {
+    /// The peer's full [`NodeId`].
+    node_id: NodeId,
+    /// The checksummed short form of the peer's node ID, as found in logs.
+    short_id: ShortId,
+    /// The public address we dial the peer at, if we are connected to it.
+    public_addr: Option<SocketAddr>,
+    /// The addresses of the peer's open or recently closed connections.
+    connection_addrs: BTreeSet<SocketAddr>,
+    /// Whether any connection to the peer is still open.
+    connected: bool,
+}
+
+impl PeerLookup {
+    /// Sets the public address of the peer.
+    pub(super) fn set_public_addr(&mut self, public_addr: Option<SocketAddr>) {
+        self.public_addr = public_addr;
+    }
+
+    /// Returns the peer's node ID.
+    pub(super) fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+}
+
+/// The peers whose node IDs matched a lookup.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct PeerLookupReport {
+    pub(super) peers: Vec<PeerLookup>,
+}
+
+impl Display for PeerLookupReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.peers.is_empty() {
+            return writeln!(f, "no matching peer");
+        }
+        for peer in &self.peers {
+            writeln!(
+                f,
+                "{} {}{}",
+                peer.short_id,
+                base16::encode_lower(peer.node_id.hash_bytes()),
+                if peer.connected {
+                    ""
+                } else {
+                    " (disconnected)"
+                }
+            )?;
+            writeln!(
+                f,
+                "  public address: {}",
+                OptDisplay::new(peer.public_addr, "unknown")
+            )?;
+            for addr in &peer.connection_addrs {
+                writeln!(f, "  connected from/to: {}", addr)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An established connection which hasn't been closed yet.
+#[derive(Debug)]
+struct OpenConnection {
+    role: Role,
+    peer_addr: SocketAddr,
+    peer_id: NodeId,
+    established: Timestamp,
+    stats: Arc<ConnectionStats>,
+}
+
+impl OpenConnection {
+    fn info(&self, connection_id: ConnectionId, closed: Option<Timestamp>) -> ConnectionInfo {
+        ConnectionInfo {
+            connection_id,
+            role: self.role,
+            peer_addr: self.peer_addr,
+            peer_id: self.peer_id,
+            established: self.established,
+            closed,
+            messages_in: self.stats.messages_in.load(Ordering::Relaxed),
+            bytes_in: self.stats.bytes_in.load(Ordering::Relaxed),
+            messages_out: self.stats.messages_out.load(Ordering::Relaxed),
+            bytes_out: self.stats.bytes_out.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Open connections, along with the most recently closed ones.
+#[derive(Debug, Default)]
+pub(super) struct Connections {
+    open: HashMap<ConnectionId, OpenConnection>,
+    closed: VecDeque<ConnectionInfo>,
+}
+
+impl Connections {
+    /// Registers a newly established connection.
+    pub(super) fn insert(
+        &mut self,
+        connection_id: ConnectionId,
+        role: Role,
+        peer_addr: SocketAddr,
+        peer_id: NodeId,
+        stats: Arc<ConnectionStats>,
+    ) {
+        let connection = OpenConnection {
+            role,
+            peer_addr,
+            peer_id,
+            established: Timestamp::now(),
+            stats,
+        };
+        self.open.insert(connection_id, connection);
+    }
+
+    /// Marks a connection as closed, remembering it for later lookups.
+    pub(super) fn close(&mut self, connection_id: ConnectionId) {
+        if let Some(connection) = self.open.remove(&connection_id) {
+            if self.closed.len() >= MAX_CLOSED_CONNECTIONS {
+                self.closed.pop_front();
+            }
+            self.closed
+                .push_back(connection.info(connection_id, Some(Timestamp::now())));
+        }
+    }
+
+    /// Returns information about an open or recently closed connection.
+    pub(super) fn get(&self, connection_id: &IdOrShortId<ConnectionId>) -> Option<ConnectionInfo> {
+        if let Some((id, connection)) = self.open.iter().find(|(id, _)| connection_id.matches(id)) {
+            return Some(connection.info(*id, None));
+        }
+        // Search from the back, as the same ID may be reused in rare cases.
+        self.closed
+            .iter()
+            .rev()
+            .find(|info| connection_id.matches(&info.connection_id))
+            .cloned()
+    }
+
+    /// Returns the peers of open or recently closed connections whose node ID matches `node_id`.
+    pub(super) fn lookup_peers(&self, node_id: &IdOrShortId<NodeId>) -> Vec<PeerLookup> {
+        let mut peers: BTreeMap<NodeId, PeerLookup> = BTreeMap::new();
+        let open = self
+            .open
+            .values()
+            .map(|connection| (connection.peer_id, connection.peer_addr, true));
+        let closed = self
+            .closed
+            .iter()
+            .map(|info| (info.peer_id, info.peer_addr, false));
+        for (peer_id, peer_addr, is_open) in open.chain(closed) {
+            if !node_id.matches(&peer_id) {
+                continue;
+            }
+            let peer = peers.entry(peer_id).or_insert_with(|| PeerLookup {
+                node_id: peer_id,
+                short_id: peer_id.short_id(),
+                public_addr: None,
+                connection_addrs: BTreeSet::new(),
+                connected: false,
+            });
+            peer.connection_addrs.insert(peer_addr);
+            peer.connected |= is_open;
+        }
+        peers.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    #[test]
+    fn should_remember_recently_closed_connections() {
+        let mut rng = TestRng::new();
+        let mut connections = Connections::default();
+        let peer_addr = ([127, 0, 0, 1], 34553).into();
+        let peer_id = NodeId::random(&mut rng);
+        let stats = Arc::new(ConnectionStats::default());
+
+        let first = ConnectionId::random(&mut rng);
+        connections.insert(first, Role::Dialer, peer_addr, peer_id, stats.clone());
+        stats.record_out(100);
+        let info = connections
+            .get(&IdOrShortId::Full(first))
+            .expect("should be open");
+        assert_eq!(info.peer_id, peer_id);
+        assert_eq!((info.messages_out, info.bytes_out), (1, 100));
+        assert!(info.closed.is_none());
+
+        connections.close(first);
+        let closed = connections
+            .get(&IdOrShortId::Short(first.short_id()))
+            .expect("should find closed connection by its short ID");
+        assert!(closed.closed.is_some());
+
+        let peers = connections.lookup_peers(&IdOrShortId::Short(peer_id.short_id()));
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].node_id, peer_id);
+        assert!(!peers[0].connected);
+        assert!(peers[0].connection_addrs.contains(&peer_addr));
+
+        for _ in 0..MAX_CLOSED_CONNECTIONS {
+            let connection_id = ConnectionId::random(&mut rng);
+            connections.insert(
+                connection_id,
+                Role::Listener,
+                peer_addr,
+                peer_id,
+                stats.clone(),
+            );
+            connections.close(connection_id);
+        }
+        assert!(connections.get(&IdOrShortId::Full(first)).is_none());
+        assert!(connections
+            .get(&IdOrShortId::Full(ConnectionId::random(&mut rng)))
+            .is_none());
+    }
+}
@@ -9,6 +9,7 @@ use std::{
     convert::TryFrom,
     fmt::{self, Display, Formatter},
     pin::Pin,
+    str::FromStr,
     sync::{Arc, Weak},
 };
 
@@ -19,14 +20,18 @@ use openssl::ssl::SslRef;
 use pin_project::pin_project;
 #[cfg(test)]
 use rand::RngCore;
+use serde::Serialize;
 use static_assertions::const_assert;
 use tokio_serde::{Deserializer, Serializer};
 use tracing::{trace, warn};
 
 use casper_hashing::Digest;
 
-use super::{tls::KeyFingerprint, Message, Metrics, Payload};
-use crate::{types::NodeId, utils};
+use super::{connections::ConnectionStats, tls::KeyFingerprint, Message, Metrics, Payload};
+use crate::{
+    types::NodeId,
+    utils::{self, short_id::ShortId},
+};
 
 /// Lazily-evaluated network message ID generator.
 ///
@@ -42,9 +47,9 @@ impl Display for TraceId {
 
 /// A metric-updating serializer/deserializer wrapper for network messages.
 ///
-/// Classifies each message given and updates the `NetworkingMetrics` accordingly. Also emits a
-/// TRACE-level message to the `net_out` and `net_in` target with a per-message unique hash when
-/// a message is sent or received.
+/// Classifies each message given and updates the `NetworkingMetrics` and the traffic counters of
+/// the connection accordingly. Also emits a TRACE-level message to the `net_out` and `net_in`
+/// target with a per-message unique hash when a message is sent or received.
 #[pin_project]
 #[derive(Debug)]
 pub struct CountingFormat<F> {
@@ -61,6 +66,8 @@ pub struct CountingFormat<F> {
     role: Role,
     /// Metrics to update.
     metrics: Weak<Metrics>,
+    /// Traffic counters of the connection to update.
+    stats: Arc<ConnectionStats>,
 }
 
 impl<F> CountingFormat<F> {
@@ -68,12 +75,14 @@ impl<F> CountingFormat<F> {
     #[inline]
     pub(super) fn new(
         metrics: Weak<Metrics>,
+        stats: Arc<ConnectionStats>,
         connection_id: ConnectionId,
         role: Role,
         inner: F,
     ) -> Self {
         Self {
             metrics,
+            stats,
             connection_id,
             out_count: 0,
             in_count: 0,
@@ -99,6 +108,7 @@ where
         let msg_size = serialized.len() as u64;
         let msg_kind = item.classify();
         Metrics::record_payload_out(this.metrics, msg_kind, msg_size);
+        this.stats.record_out(msg_size);
 
         let trace_id = this
             .connection_id
@@ -131,6 +141,7 @@ where
         let deserialized = F::deserialize(projection, src)?;
         let msg_kind = deserialized.classify();
         Metrics::record_payload_in(this.metrics, msg_kind, msg_size);
+        this.stats.record_in(msg_size);
 
         let trace_id = this
             .connection_id
@@ -150,8 +161,45 @@ where
 ///
 /// The ID is guaranteed to be the same on both ends of the connection, but not guaranteed to be
 /// unique or sufficiently random. Do not use it for any cryptographic/security related purposes.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub(super) struct ConnectionId([u8; Digest::LENGTH]);
+///
+/// It is displayed and logged in its checksummed short form, serialized base16-encoded and parsed
+/// from the latter.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub(crate) struct ConnectionId([u8; Digest::LENGTH]);
+
+impl Display for ConnectionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.short_id(), f)
+    }
+}
+
+impl Serialize for ConnectionId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base16::encode_lower(&self.0))
+    }
+}
+
+impl AsRef<[u8]> for ConnectionId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl FromStr for ConnectionId {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let bytes = base16::decode(input).map_err(|error| error.to_string())?;
+        let id = <[u8; Digest::LENGTH]>::try_from(bytes.as_slice()).map_err(|_| {
+            format!(
+                "connection ID must be {} bytes, got {}",
+                Digest::LENGTH,
+                bytes.len()
+            )
+        })?;
+        Ok(ConnectionId(id))
+    }
+}
 
 // Invariant assumed by `ConnectionId`, `Digest` must be <= than `KeyFingerprint`.
 const_assert!(KeyFingerprint::LENGTH >= Digest::LENGTH);
@@ -265,6 +313,11 @@ impl ConnectionId {
         &self.0
     }
 
+    /// Returns the checksummed short form of the connection ID, which is how it is displayed.
+    pub(crate) fn short_id(&self) -> ShortId {
+        ShortId::new(&self.0)
+    }
+
     /// Creates a new connection ID from an existing SSL connection.
     #[inline]
     pub(crate) fn from_connection(ssl: &SslRef, our_id: NodeId, their_id: NodeId) -> Self {
@@ -283,7 +336,7 @@ impl ConnectionId {
 }
 
 /// Message sending direction.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize)]
 #[repr(u8)]
 pub(super) enum Role {
     /// Dialer, i.e. initiator of the connection.
@@ -314,6 +367,8 @@ impl Role {
 
 #[cfg(test)]
 mod tests {
+    use casper_types::testing::TestRng;
+
     use crate::types::NodeId;
 
     use super::{ConnectionId, Role, TlsRandomData, TraceId};
@@ -327,6 +382,17 @@ mod tests {
         assert_eq!(output.len(), 16);
     }
 
+    #[test]
+    fn connection_id_roundtrips_through_display() {
+        let connection_id = ConnectionId::random(&mut TestRng::new());
+
+        let displayed = connection_id.to_string();
+        assert_eq!(displayed.len(), 64);
+        assert_eq!(displayed.parse::<ConnectionId>(), Ok(connection_id));
+        assert!(displayed[..62].parse::<ConnectionId>().is_err());
+        assert!("not hex".parse::<ConnectionId>().is_err());
+    }
+
     #[test]
     fn can_create_deterministic_trace_id() {
         let mut rng = crate::new_rng();
@@ -0,0 +1,375 @@
+//! Network partition self-diagnosis.
+//!
+//! On request, the networking component samples the addresses it knows about and reports on its
+//! outgoing connections to them as they stand, without dialing anyone: the handshake of established
+//! connections, or the error of the last failed attempt. The outcomes are summarized in a
+//! [`PartitionReport`] along with a verdict on whether the node appears to be cut off from the
+//! network, intended to be attached to support tickets as is.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    net::SocketAddr,
+    time::Duration,
+};
+
+use casper_types::ProtocolVersion;
+use serde::Serialize;
+
+use super::{error::ConnectionError, outgoing::OutgoingState, OutgoingHandle};
+use crate::{
+    types::NodeId,
+    utils::{display_error, opt_display::OptDisplay},
+};
+
+/// The clock skew beyond which a peer's clock is reported as deviating from ours.
+const MAX_TOLERABLE_CLOCK_SKEW: Duration = Duration::from_secs(2);
+
+/// The outcome of probing a single peer, i.e. the state of our outgoing connection to it.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) enum ProbeOutcome {
+    /// A connection attempt is in progress.
+    Connecting,
+    /// The last connection attempt failed.
+    Unreachable { error: String },
+    /// The last connection attempt did not complete in time.
+    TimedOut,
+    /// The peer is running a different protocol version.
+    IncompatibleVersion { protocol_version: ProtocolVersion },
+    /// We are connected to the peer.
+    Healthy {
+        peer_id: NodeId,
+        /// The time it took to connect and complete the handshake.
+        handshake_duration: Duration,
+        /// How far the peer's clock is ahead of ours, in milliseconds, if it reported its time.
+        ///
+        /// Since the peer's time is taken when it sends its handshake, the estimate is skewed
+        /// towards the peer being behind by up to `handshake_duration`.
+        clock_skew_ms: Option<i64>,
+    },
+}
+
+impl ProbeOutcome {
+    /// Returns the outcome for an address whose outgoing connection is in `state`, or `None` if
+    /// the address is not probed as it is blocked or our own.
+    pub(super) fn from_outgoing_state<P>(
+        state: &OutgoingState<OutgoingHandle<P>, ConnectionError>,
+    ) -> Option<Self> {
+        let outcome = match state {
+            OutgoingState::Connecting { .. } => ProbeOutcome::Connecting,
+            OutgoingState::Waiting {
+                error: Some(ConnectionError::IncompatibleVersion(protocol_version)),
+                ..
+            } => ProbeOutcome::IncompatibleVersion {
+                protocol_version: *protocol_version,
+            },
+            OutgoingState::Waiting {
+                error: Some(error), ..
+            } => ProbeOutcome::Unreachable {
+                error: display_error(error).to_string(),
+            },
+            // Connections are only put into waiting without an error if they timed out.
+            OutgoingState::Waiting { error: None, .. } => ProbeOutcome::TimedOut,
+            OutgoingState::Connected {
+                peer_id, handle, ..
+            } => ProbeOutcome::Healthy {
+                peer_id: *peer_id,
+                handshake_duration: handle.handshake_duration,
+                clock_skew_ms: handle.clock_skew_ms,
+            },
+            OutgoingState::Blocked { .. } | OutgoingState::Loopback => return None,
+        };
+        Some(outcome)
+    }
+
+    /// Returns whether the peer's clock deviates from ours by more than we tolerate.
+    fn has_excessive_clock_skew(&self) -> bool {
+        match self {
+            ProbeOutcome::Healthy {
+                clock_skew_ms: Some(skew),
+                ..
+            } => skew.unsigned_abs() > MAX_TOLERABLE_CLOCK_SKEW.as_millis() as u64,
+            _ => false,
+        }
+    }
+}
+
+impl Display for ProbeOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ProbeOutcome::Connecting => f.write_str("connecting"),
+            ProbeOutcome::Unreachable { error } => write!(f, "unreachable: {}", error),
+            ProbeOutcome::TimedOut => f.write_str("timed out"),
+            ProbeOutcome::IncompatibleVersion { protocol_version } => {
+                write!(f, "runs incompatible protocol version {}", protocol_version)
+            }
+            ProbeOutcome::Healthy {
+                peer_id,
+                handshake_duration,
+                clock_skew_ms,
+            } => write!(
+                f,
+                "healthy {} (handshake {} ms, clock skew {} ms)",
+                peer_id,
+                handshake_duration.as_millis(),
+                OptDisplay::new(*clock_skew_ms, "?")
+            ),
+        }
+    }
+}
+
+/// The outcome of probing the peer at a given address.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct PeerProbe {
+    /// The probed address.
+    pub(super) addr: SocketAddr,
+    /// What happened.
+    pub(super) outcome: ProbeOutcome,
+}
+
+/// The overall assessment of a node's connectivity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub(crate) enum PartitionVerdict {
+    /// None of the probed peers could be handshaken with.
+    Isolated,
+    /// Less than half of the probed peers could be handshaken with.
+    PartialPartition,
+    /// At least half of the probed peers could be handshaken with.
+    Healthy,
+}
+
+impl Display for PartitionVerdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PartitionVerdict::Isolated => f.write_str("isolated"),
+            PartitionVerdict::PartialPartition => f.write_str("partial partition"),
+            PartitionVerdict::Healthy => f.write_str("healthy"),
+        }
+    }
+}
+
+/// A summary of probing a sample of known peers.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct PartitionReport {
+    /// Our own node ID.
+    our_id: NodeId,
+    /// The number of addresses known to the networking component.
+    known_addresses: usize,
+    /// The number of peers we are currently connected to in both directions.
+    fully_connected_peers: usize,
+    /// The individual probes.
+    probes: Vec<PeerProbe>,
+    /// The number of probed peers that completed a handshake.
+    healthy: usize,
+    /// The number of probed peers that could not be connected to at all or timed out.
+    unreachable: usize,
+    /// The number of probed peers running a different protocol version.
+    incompatible: usize,
+    /// The number of probed peers with a clock skew exceeding what is tolerated.
+    clock_skewed: usize,
+    /// The overall verdict.
+    verdict: PartitionVerdict,
+}
+
+impl PartitionReport {
+    /// Creates a new report from the outcomes of `probes`.
+    pub(super) fn new(
+        our_id: NodeId,
+        known_addresses: usize,
+        fully_connected_peers: usize,
+        probes: Vec<PeerProbe>,
+    ) -> Self {
+        let count = |predicate: fn(&ProbeOutcome) -> bool| {
+            probes
+                .iter()
+                .filter(|probe| predicate(&probe.outcome))
+                .count()
+        };
+        let probed = count(|outcome| !matches!(outcome, ProbeOutcome::Connecting));
+        let healthy = count(|outcome| matches!(outcome, ProbeOutcome::Healthy { .. }));
+        let unreachable = count(|outcome| {
+            matches!(
+                outcome,
+                ProbeOutcome::Unreachable { .. } | ProbeOutcome::TimedOut
+            )
+        });
+        let incompatible =
+            count(|outcome| matches!(outcome, ProbeOutcome::IncompatibleVersion { .. }));
+        let clock_skewed = count(ProbeOutcome::has_excessive_clock_skew);
+
+        let verdict = if healthy == 0 {
+            PartitionVerdict::Isolated
+        } else if healthy * 2 < probed {
+            PartitionVerdict::PartialPartition
+        } else {
+            PartitionVerdict::Healthy
+        };
+
+        PartitionReport {
+            our_id,
+            known_addresses,
+            fully_connected_peers,
+            probes,
+            healthy,
+            unreachable,
+            incompatible,
+            clock_skewed,
+            verdict,
+        }
+    }
+
+    /// Returns the overall verdict.
+    #[cfg(test)]
+    pub(crate) fn verdict(&self) -> PartitionVerdict {
+        self.verdict
+    }
+}
+
+impl Display for PartitionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "verdict: {}", self.verdict)?;
+        writeln!(
+            f,
+            "node {}: {} known addresses, {} fully connected peers",
+            self.our_id, self.known_addresses, self.fully_connected_peers
+        )?;
+        writeln!(
+            f,
+            "probed {}: {} healthy, {} unreachable, {} incompatible, {} with clock skew over {} ms",
+            self.probes.len(),
+            self.healthy,
+            self.unreachable,
+            self.incompatible,
+            self.clock_skewed,
+            MAX_TOLERABLE_CLOCK_SKEW.as_millis()
+        )?;
+        for probe in &self.probes {
+            writeln!(f, "{:23}  {}", probe.addr, probe.outcome)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use casper_types::testing::TestRng;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::{components::network::health::ConnectionHealth, protocol::Message};
+
+    fn probe(outcome: ProbeOutcome) -> PeerProbe {
+        PeerProbe {
+            addr: ([127, 0, 0, 1], 34553).into(),
+            outcome,
+        }
+    }
+
+    fn healthy(clock_skew_ms: i64) -> PeerProbe {
+        probe(ProbeOutcome::Healthy {
+            peer_id: NodeId::random(&mut TestRng::new()),
+            handshake_duration: Duration::from_millis(50),
+            clock_skew_ms: Some(clock_skew_ms),
+        })
+    }
+
+    fn unreachable() -> PeerProbe {
+        probe(ProbeOutcome::Unreachable {
+            error: "connection refused".to_string(),
+        })
+    }
+
+    fn report(probes: Vec<PeerProbe>) -> PartitionReport {
+        PartitionReport::new(NodeId::random(&mut TestRng::new()), 10, 1, probes)
+    }
+
+    #[test]
+    fn should_judge_node_without_healthy_peers_isolated() {
+        assert_eq!(report(vec![]).verdict(), PartitionVerdict::Isolated);
+        assert_eq!(
+            report(vec![unreachable(), probe(ProbeOutcome::TimedOut)]).verdict(),
+            PartitionVerdict::Isolated
+        );
+    }
+
+    #[test]
+    fn should_judge_partial_partition_by_share_of_healthy_peers() {
+        assert_eq!(
+            report(vec![healthy(0), unreachable(), unreachable()]).verdict(),
+            PartitionVerdict::PartialPartition
+        );
+        // Addresses still being connected to don't count against the share.
+        assert_eq!(
+            report(vec![
+                healthy(0),
+                unreachable(),
+                probe(ProbeOutcome::Connecting)
+            ])
+            .verdict(),
+            PartitionVerdict::Healthy
+        );
+    }
+
+    #[test]
+    fn should_take_outcomes_from_outgoing_connections() {
+        let mut rng = TestRng::new();
+        let peer_id = NodeId::random(&mut rng);
+        let (sender, _receiver) = mpsc::unbounded_channel();
+        let connected: OutgoingState<OutgoingHandle<Message>, ConnectionError> =
+            OutgoingState::Connected {
+                peer_id,
+                handle: OutgoingHandle {
+                    sender,
+                    peer_addr: ([127, 0, 0, 1], 34553).into(),
+                    handshake_duration: Duration::from_millis(30),
+                    clock_skew_ms: Some(-4),
+                },
+                health: ConnectionHealth::new(Instant::now()),
+            };
+        assert!(matches!(
+            ProbeOutcome::from_outgoing_state(&connected),
+            Some(ProbeOutcome::Healthy {
+                peer_id: id,
+                handshake_duration,
+                clock_skew_ms: Some(-4),
+            }) if id == peer_id && handshake_duration == Duration::from_millis(30)
+        ));
+
+        let incompatible: OutgoingState<OutgoingHandle<Message>, ConnectionError> =
+            OutgoingState::Waiting {
+                failures_so_far: 1,
+                error: Some(ConnectionError::IncompatibleVersion(
+                    ProtocolVersion::V1_0_0,
+                )),
+                last_failure: Instant::now(),
+            };
+        assert!(matches!(
+            ProbeOutcome::from_outgoing_state(&incompatible),
+            Some(ProbeOutcome::IncompatibleVersion { protocol_version })
+                if protocol_version == ProtocolVersion::V1_0_0
+        ));
+
+        let timed_out: OutgoingState<OutgoingHandle<Message>, ConnectionError> =
+            OutgoingState::Waiting {
+                failures_so_far: 1,
+                error: None,
+                last_failure: Instant::now(),
+            };
+        assert!(matches!(
+            ProbeOutcome::from_outgoing_state(&timed_out),
+            Some(ProbeOutcome::TimedOut)
+        ));
+
+        let loopback: OutgoingState<OutgoingHandle<Message>, ConnectionError> =
+            OutgoingState::Loopback;
+        assert!(ProbeOutcome::from_outgoing_state(&loopback).is_none());
+    }
+
+    #[test]
+    fn should_count_peers_with_excessive_clock_skew() {
+        let report = report(vec![healthy(100), healthy(-5_000), healthy(3_000)]);
+        assert_eq!(report.verdict(), PartitionVerdict::Healthy);
+        assert_eq!(report.clock_skewed, 2);
+    }
+}
@@ -3,6 +3,7 @@ use std::{
     io, mem,
     net::SocketAddr,
     sync::Arc,
+    time::Duration,
 };
 
 use derive_more::From;
@@ -13,7 +14,10 @@ use tracing::Span;
 
 use casper_types::PublicKey;
 
-use super::{error::ConnectionError, FullTransport, GossipedAddress, Message, NodeId};
+use super::{
+    connections::ConnectionStats, counting_format::ConnectionId, error::ConnectionError,
+    FullTransport, GossipedAddress, Message, NodeId,
+};
 use crate::{
     effect::{
         announcements::PeerBehaviorAnnouncement,
@@ -45,12 +49,16 @@ pub(crate) enum Event<P> {
         span: Span,
     },
 
+    /// A received message was pushed onto the bounded incoming queue, and is ready to be handled.
+    IncomingMessageQueued,
+
     /// Incoming connection closed.
     IncomingClosed {
         #[serde(skip_serializing)]
         result: io::Result<()>,
         peer_id: Box<NodeId>,
-        peer_addr: SocketAddr,
+        peer_addr: Box<SocketAddr>,
+        connection_id: Box<ConnectionId>,
         #[serde(skip_serializing)]
         span: Box<Span>,
     },
@@ -66,6 +74,7 @@ pub(crate) enum Event<P> {
     OutgoingDropped {
         peer_id: Box<NodeId>,
         peer_addr: SocketAddr,
+        connection_id: Box<ConnectionId>,
     },
 
     /// Incoming network request.
@@ -85,8 +94,15 @@ pub(crate) enum Event<P> {
     /// The node should gossip its own public listening address.
     GossipOurAddress,
 
+    /// The node should advertise its endpoint to the validators of the eras it is bonded in, and
+    /// connect to the endpoints of the other validators.
+    GossipOurValidatorEndpoints,
+
     /// We received a peer's public listening address via gossip.
-    PeerAddressReceived(GossipedAddress),
+    PeerAddressReceived {
+        gossiped_address: Box<GossipedAddress>,
+        advertiser: Box<NodeId>,
+    },
 
     /// Housekeeping for the outgoing manager.
     SweepOutgoing,
@@ -120,21 +136,36 @@ impl<P: Display> Display for Event<P> {
                 msg,
                 span: _,
             } => write!(f, "msg from {}: {}", node_id, msg),
+            Event::IncomingMessageQueued => write!(f, "incoming message queued"),
             Event::IncomingClosed { peer_addr, .. } => {
                 write!(f, "closed connection from {}", peer_addr)
             }
             Event::OutgoingConnection { outgoing, span: _ } => {
                 write!(f, "outgoing connection: {}", outgoing)
             }
-            Event::OutgoingDropped { peer_id, peer_addr } => {
-                write!(f, "dropped outgoing {} {}", peer_id, peer_addr)
+            Event::OutgoingDropped {
+                peer_id,
+                peer_addr,
+                connection_id,
+            } => {
+                write!(
+                    f,
+                    "dropped outgoing {} {} (connection {})",
+                    peer_id, peer_addr, connection_id
+                )
             }
             Event::NetworkRequest { req } => write!(f, "request: {}", req),
             Event::NetworkInfoRequest { req } => write!(f, "request: {}", req),
             Event::GossipOurAddress => write!(f, "gossip our address"),
-            Event::PeerAddressReceived(gossiped_address) => {
-                write!(f, "received gossiped peer address {}", gossiped_address)
-            }
+            Event::GossipOurValidatorEndpoints => write!(f, "gossip our validator endpoints"),
+            Event::PeerAddressReceived {
+                gossiped_address,
+                advertiser,
+            } => write!(
+                f,
+                "received gossiped peer address {} from {}",
+                gossiped_address, advertiser
+            ),
             Event::BlocklistAnnouncement(ann) => {
                 write!(f, "handling blocklist announcement: {}", ann)
             }
@@ -174,6 +205,11 @@ pub(crate) enum IncomingConnection<P> {
         public_addr: SocketAddr,
         /// Peer's [`NodeId`].
         peer_id: NodeId,
+        /// The ID of the connection, shared with the peer.
+        connection_id: ConnectionId,
+        /// Traffic counters of the connection.
+        #[serde(skip_serializing)]
+        stats: Arc<ConnectionStats>,
         /// The public key the peer is validating with, if any.
         peer_consensus_public_key: Option<PublicKey>,
         /// Stream of incoming messages. for incoming connections.
@@ -198,13 +234,15 @@ impl<P> Display for IncomingConnection<P> {
                 peer_addr,
                 public_addr,
                 peer_id,
+                connection_id,
+                stats: _,
                 peer_consensus_public_key,
                 stream: _,
             } => {
                 write!(
                     f,
-                    "connection established from {}/{}; public: {}",
-                    peer_addr, peer_id, public_addr
+                    "connection {} established from {}/{}; public: {}",
+                    connection_id, peer_addr, peer_id, public_addr
                 )?;
 
                 if let Some(public_key) = peer_consensus_public_key {
@@ -244,6 +282,11 @@ pub(crate) enum OutgoingConnection<P> {
         peer_addr: SocketAddr,
         /// Peer's [`NodeId`].
         peer_id: NodeId,
+        /// The ID of the connection, shared with the peer.
+        connection_id: ConnectionId,
+        /// Traffic counters of the connection.
+        #[serde(skip_serializing)]
+        stats: Arc<ConnectionStats>,
         /// The public key the peer is validating with, if any.
         peer_consensus_public_key: Option<PublicKey>,
         /// Sink for outgoing messages.
@@ -251,6 +294,11 @@ pub(crate) enum OutgoingConnection<P> {
         sink: SplitSink<FullTransport<P>, Arc<Message<P>>>,
         /// Holds the information whether the remote node is syncing.
         is_syncing: bool,
+        /// The time it took to connect and complete the handshake.
+        handshake_duration: Duration,
+        /// How far the peer's clock was ahead of ours when it sent its handshake, in milliseconds,
+        /// if it reported its time.
+        clock_skew_ms: Option<i64>,
     },
 }
 
@@ -269,14 +317,18 @@ impl<P> Display for OutgoingConnection<P> {
             OutgoingConnection::Established {
                 peer_addr,
                 peer_id,
+                connection_id,
+                stats: _,
                 peer_consensus_public_key,
                 sink: _,
                 is_syncing,
+                handshake_duration: _,
+                clock_skew_ms: _,
             } => {
                 write!(
                     f,
-                    "connection established to {}/{}, is_syncing: {}",
-                    peer_addr, peer_id, is_syncing
+                    "connection {} established to {}/{}, is_syncing: {}",
+                    connection_id, peer_addr, peer_id, is_syncing
                 )?;
 
                 if let Some(public_key) = peer_consensus_public_key {
@@ -9,29 +9,47 @@ use serde::{Deserialize, Serialize};
 use crate::{
     components::gossiper::{GossipItem, SmallGossipItem},
     effect::GossipTarget,
+    types::NodeId,
 };
 
-/// Used to gossip our public listening address to peers.
+/// Used to gossip a node's public listening address to peers.
+///
+/// The address is accompanied by the ID of the node listening on it, which allows recipients to
+/// verify the advertisement by connecting to the address before gossiping it onwards.
 #[derive(
     Copy, Clone, DataSize, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug,
 )]
-pub struct GossipedAddress(SocketAddr);
+pub struct GossipedAddress {
+    address: SocketAddr,
+    node_id: NodeId,
+}
 
 impl GossipedAddress {
-    pub(super) fn new(address: SocketAddr) -> Self {
-        GossipedAddress(address)
+    pub(super) fn new(address: SocketAddr, node_id: NodeId) -> Self {
+        GossipedAddress { address, node_id }
+    }
+
+    /// Returns the ID of the node claimed to be listening on the address.
+    pub(super) fn node_id(&self) -> NodeId {
+        self.node_id
     }
 }
 
 impl Display for GossipedAddress {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
-        write!(formatter, "gossiped-address {}", self.0)
+        write!(
+            formatter,
+            "gossiped-address {} of {}",
+            self.address, self.node_id
+        )
     }
 }
 
 impl GossipItem for GossipedAddress {
     const ID_IS_COMPLETE_ITEM: bool = true;
     const REQUIRES_GOSSIP_RECEIVED_ANNOUNCEMENT: bool = false;
+    const REQUIRES_VERIFICATION_BEFORE_GOSSIP: bool = true;
+    const GOSSIPER_NAME: &'static str = "address_gossiper";
 
     type Id = GossipedAddress;
 
@@ -52,6 +70,6 @@ impl SmallGossipItem for GossipedAddress {
 
 impl From<GossipedAddress> for SocketAddr {
     fn from(gossiped_address: GossipedAddress) -> Self {
-        gossiped_address.0
+        gossiped_address.address
     }
 }
@@ -0,0 +1,291 @@
+//! Bookkeeping of failed connection attempts by remote address.
+//!
+//! Every connection which fails before or during the handshake is classified by the kind of
+//! failure and counted per remote address, so that an operator wondering why the node won't
+//! connect to a given peer can ask the node itself through the diagnostics port instead of
+//! searching the logs.
+//!
+//! Incoming connections originate from ephemeral ports, so their failures are recorded under the
+//! remote IP with port 0, while failures of outgoing connections are recorded under the dialed
+//! address.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+    net::{IpAddr, SocketAddr},
+};
+
+use serde::Serialize;
+
+use casper_types::Timestamp;
+
+use super::error::{ConnectionError, IoError};
+use crate::utils::display_error;
+
+/// The number of remote addresses failures are tracked for.
+const MAX_TRACKED_ADDRS: usize = 1024;
+
+/// The kind of a failed connection attempt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub(crate) enum HandshakeFailureKind {
+    /// No TCP connection could be established.
+    Unreachable,
+    /// The TLS handshake failed or the peer's certificate was missing or invalid.
+    Tls,
+    /// The peer is on a different network or uses a different chainspec.
+    WrongNetwork,
+    /// The peer runs an incompatible protocol version.
+    IncompatibleVersion,
+    /// The peer did not complete the handshake in time.
+    Timeout,
+    /// The peer sent something other than a valid handshake.
+    Protocol,
+    /// The handshake failed due to an error on our side.
+    Internal,
+}
+
+impl HandshakeFailureKind {
+    /// Classifies a connection error.
+    pub(super) fn classify(error: &ConnectionError) -> Self {
+        match error {
+            ConnectionError::TcpConnection(_) | ConnectionError::TcpNoDelay(_) => {
+                HandshakeFailureKind::Unreachable
+            }
+            ConnectionError::TlsHandshake(_)
+            | ConnectionError::NoPeerCertificate
+            | ConnectionError::PeerCertificateInvalid(_) => HandshakeFailureKind::Tls,
+            ConnectionError::WrongNetwork(_)
+            | ConnectionError::WrongChainspecHash(_)
+            | ConnectionError::MissingChainspecHash => HandshakeFailureKind::WrongNetwork,
+            ConnectionError::IncompatibleVersion(_) => HandshakeFailureKind::IncompatibleVersion,
+            ConnectionError::HandshakeSend(IoError::Timeout)
+            | ConnectionError::HandshakeRecv(IoError::Timeout) => HandshakeFailureKind::Timeout,
+            ConnectionError::HandshakeSend(_)
+            | ConnectionError::HandshakeRecv(_)
+            | ConnectionError::DidNotSendHandshake
+            | ConnectionError::InvalidRemoteHandshakeMessage(_)
+            | ConnectionError::InvalidConsensusCertificate(_) => HandshakeFailureKind::Protocol,
+            ConnectionError::TlsInitialization(_)
+            | ConnectionError::CouldNotEncodeOurHandshake(_)
+            | ConnectionError::HandshakeSenderCrashed(_)
+            | ConnectionError::FailedToReuniteHandshakeSinkAndStream => {
+                HandshakeFailureKind::Internal
+            }
+        }
+    }
+}
+
+impl Display for HandshakeFailureKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HandshakeFailureKind::Unreachable => f.write_str("unreachable"),
+            HandshakeFailureKind::Tls => f.write_str("TLS error"),
+            HandshakeFailureKind::WrongNetwork => f.write_str("wrong network"),
+            HandshakeFailureKind::IncompatibleVersion => f.write_str("incompatible version"),
+            HandshakeFailureKind::Timeout => f.write_str("timeout"),
+            HandshakeFailureKind::Protocol => f.write_str("protocol violation"),
+            HandshakeFailureKind::Internal => f.write_str("internal error"),
+        }
+    }
+}
+
+/// The failed connection attempts involving a single remote address.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct AddrHandshakeFailures {
+    /// The remote address, with port 0 for incoming connections.
+    addr: SocketAddr,
+    /// The number of failures of each kind.
+    counts: BTreeMap<HandshakeFailureKind, u64>,
+    /// The number of failures that got the address blocked.
+    bans: u64,
+    /// The kind of the most recent failure.
+    last_kind: HandshakeFailureKind,
+    /// The error of the most recent failure.
+    last_error: String,
+    /// When the most recent failure happened.
+    last_failure: Timestamp,
+}
+
+impl Display for AddrHandshakeFailures {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.addr)?;
+        for (kind, count) in &self.counts {
+            write!(f, " {} x{},", kind, count)?;
+        }
+        write!(
+            f,
+            " banned {} times, last at {}: {}",
+            self.bans, self.last_failure, self.last_error
+        )
+    }
+}
+
+/// Failed connection attempts of the most recently failing remote addresses.
+#[derive(Debug, Default)]
+pub(super) struct HandshakeFailures {
+    by_addr: HashMap<SocketAddr, AddrHandshakeFailures>,
+}
+
+impl HandshakeFailures {
+    /// Records a failed connection attempt and returns its kind.
+    ///
+    /// `banned` indicates whether the failure got the address blocked.
+    pub(super) fn record(
+        &mut self,
+        addr: SocketAddr,
+        incoming: bool,
+        error: &ConnectionError,
+        banned: bool,
+    ) -> HandshakeFailureKind {
+        let kind = HandshakeFailureKind::classify(error);
+        let addr = if incoming {
+            SocketAddr::new(addr.ip(), 0)
+        } else {
+            addr
+        };
+        if !self.by_addr.contains_key(&addr) && self.by_addr.len() >= MAX_TRACKED_ADDRS {
+            self.evict_oldest();
+        }
+        let now = Timestamp::now();
+        let last_error = display_error(error).to_string();
+        let entry = self
+            .by_addr
+            .entry(addr)
+            .or_insert_with(|| AddrHandshakeFailures {
+                addr,
+                counts: BTreeMap::new(),
+                bans: 0,
+                last_kind: kind,
+                last_error: String::new(),
+                last_failure: now,
+            });
+        *entry.counts.entry(kind).or_default() += 1;
+        if banned {
+            entry.bans += 1;
+        }
+        entry.last_kind = kind;
+        entry.last_error = last_error;
+        entry.last_failure = now;
+        kind
+    }
+
+    /// Forgets the address whose most recent failure is the oldest.
+    fn evict_oldest(&mut self) {
+        let maybe_oldest = self
+            .by_addr
+            .values()
+            .min_by_key(|failures| failures.last_failure)
+            .map(|failures| failures.addr);
+        if let Some(oldest) = maybe_oldest {
+            self.by_addr.remove(&oldest);
+        }
+    }
+
+    /// Creates a report of the failures involving `ip`, or all addresses if `None`.
+    pub(super) fn report(&self, ip: Option<IpAddr>) -> HandshakeFailureReport {
+        let mut addrs: Vec<_> = self
+            .by_addr
+            .values()
+            .filter(|failures| ip.map_or(true, |ip| failures.addr.ip() == ip))
+            .cloned()
+            .collect();
+        addrs.sort_by(|a, b| b.last_failure.cmp(&a.last_failure));
+        let mut totals = BTreeMap::new();
+        for failures in &addrs {
+            for (kind, count) in &failures.counts {
+                *totals.entry(*kind).or_default() += count;
+            }
+        }
+        HandshakeFailureReport { totals, addrs }
+    }
+}
+
+/// A summary of failed connection attempts, most recent first.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct HandshakeFailureReport {
+    /// The number of failures of each kind across all listed addresses.
+    totals: BTreeMap<HandshakeFailureKind, u64>,
+    /// The failures per address, most recently failing first.
+    addrs: Vec<AddrHandshakeFailures>,
+}
+
+impl Display for HandshakeFailureReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.addrs.is_empty() {
+            return writeln!(f, "no failed connection attempts recorded");
+        }
+        write!(f, "totals:")?;
+        for (kind, count) in &self.totals {
+            write!(f, " {} x{},", kind, count)?;
+        }
+        writeln!(f)?;
+        for failures in &self.addrs {
+            writeln!(f, "{}", failures)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::ProtocolVersion;
+
+    use super::*;
+
+    #[test]
+    fn should_classify_and_count_failures_per_addr() {
+        let mut failures = HandshakeFailures::default();
+        let peer: SocketAddr = ([10, 0, 0, 1], 34553).into();
+        let other: SocketAddr = ([10, 0, 0, 2], 34553).into();
+
+        let timeout = ConnectionError::HandshakeRecv(IoError::Timeout);
+        let version = ConnectionError::IncompatibleVersion(ProtocolVersion::V1_0_0);
+        let network = ConnectionError::WrongNetwork("other-chain".to_string());
+        assert_eq!(
+            failures.record(peer, false, &timeout, false),
+            HandshakeFailureKind::Timeout
+        );
+        assert_eq!(
+            failures.record(peer, false, &timeout, false),
+            HandshakeFailureKind::Timeout
+        );
+        assert_eq!(
+            failures.record(peer, false, &network, true),
+            HandshakeFailureKind::WrongNetwork
+        );
+        // Incoming failures are recorded under port 0, as the source port is ephemeral.
+        failures.record(([10, 0, 0, 1], 50123).into(), true, &version, false);
+        failures.record(other, false, &version, false);
+
+        let report = failures.report(Some(peer.ip()));
+        assert_eq!(report.addrs.len(), 2);
+        let dialed = report
+            .addrs
+            .iter()
+            .find(|entry| entry.addr == peer)
+            .unwrap();
+        assert_eq!(dialed.counts[&HandshakeFailureKind::Timeout], 2);
+        assert_eq!(dialed.bans, 1);
+        assert_eq!(dialed.last_kind, HandshakeFailureKind::WrongNetwork);
+        assert!(report
+            .addrs
+            .iter()
+            .any(|entry| entry.addr == SocketAddr::new(peer.ip(), 0)));
+        assert_eq!(report.totals[&HandshakeFailureKind::IncompatibleVersion], 1);
+
+        assert_eq!(
+            failures.report(None).totals[&HandshakeFailureKind::IncompatibleVersion],
+            2
+        );
+    }
+
+    #[test]
+    fn should_bound_tracked_addrs() {
+        let mut failures = HandshakeFailures::default();
+        let error = ConnectionError::DidNotSendHandshake;
+        for port in 0..=MAX_TRACKED_ADDRS as u16 {
+            failures.record(([10, 0, 0, 1], port + 1).into(), false, &error, false);
+        }
+        assert_eq!(failures.by_addr.len(), MAX_TRACKED_ADDRS);
+    }
+}
@@ -0,0 +1,68 @@
+//! Bounded queue of incoming fetched items and gossip awaiting the reactor.
+//!
+//! Responses carrying fetched items and gossip messages make up the bulk of what peers send, e.g.
+//! while a node is syncing. Scheduled straight onto the reactor, a burst of them piles up in its
+//! unbounded queue, hiding the backlog from the peers sending them. Instead, the message reader of
+//! each connection pushes them onto this queue, waiting while their class is full, so that the
+//! backpressure reaches the peers through their connections. Only a notification is scheduled onto
+//! the reactor, upon which the network component pops whichever queued message is due next.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    num::NonZeroUsize,
+};
+
+use tracing::Span;
+
+use super::Message;
+use crate::{
+    types::NodeId,
+    utils::work_queue::{BoundedWorkQueue, JobClassLimits},
+};
+
+/// The most messages of each class waiting for the reactor.
+const QUEUE_CAPACITY: usize = 1024;
+
+/// The classes of incoming messages held in the queue, in order of priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum IncomingClass {
+    /// Responses to our requests for items.
+    FetchedItems,
+    /// Gossip messages.
+    Gossip,
+}
+
+impl Display for IncomingClass {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            IncomingClass::FetchedItems => write!(formatter, "fetched_items"),
+            IncomingClass::Gossip => write!(formatter, "gossip"),
+        }
+    }
+}
+
+/// An incoming message waiting for the reactor.
+pub(super) struct IncomingJob<P> {
+    pub(super) peer_id: NodeId,
+    pub(super) msg: Message<P>,
+    pub(super) span: Span,
+}
+
+/// The queue of incoming messages of a network component.
+pub(super) type IncomingQueue<P> = BoundedWorkQueue<IncomingClass, IncomingJob<P>>;
+
+/// Creates an empty queue, whose metrics are not registered yet.
+pub(super) fn new_incoming_queue<P>() -> Result<IncomingQueue<P>, prometheus::Error> {
+    let limits = |weight| JobClassLimits {
+        weight: NonZeroUsize::new(weight).expect("weight should be non-zero"),
+        capacity: NonZeroUsize::new(QUEUE_CAPACITY).expect("capacity should be non-zero"),
+    };
+    // Fetched items were asked for, so are favored over gossip, which may well be redundant.
+    BoundedWorkQueue::new(
+        "net_incoming",
+        vec![
+            (IncomingClass::FetchedItems, limits(2)),
+            (IncomingClass::Gossip, limits(1)),
+        ],
+    )
+}
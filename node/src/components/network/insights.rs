@@ -45,6 +45,8 @@ pub(crate) struct NetworkInsights {
     privileged_upcoming_outgoing_nodes: Option<HashSet<PublicKey>>,
     /// The amount of bandwidth allowance currently buffered, ready to be spent.
     unspent_bandwidth_allowance_bytes: Option<i64>,
+    /// The number of known endpoints of validators of recent and upcoming eras.
+    validator_endpoints: usize,
     /// Map of outgoing connections, along with their current state.
     outgoing_connections: Vec<(SocketAddr, OutgoingInsight)>,
     /// Map of incoming connections.
@@ -317,6 +319,7 @@ impl NetworkInsights {
             unspent_bandwidth_allowance_bytes: net
                 .outgoing_limiter
                 .debug_inspect_unspent_allowance(),
+            validator_endpoints: net.validator_address_book.len(),
             outgoing_connections,
             connection_symmetries,
         }
@@ -363,6 +366,7 @@ impl Display for NetworkInsights {
             "privileged upcoming: {}",
             OptDisplay::new(upcoming, "inactive")
         )?;
+        writeln!(f, "known validator endpoints: {}", self.validator_endpoints)?;
 
         f.write_str("outgoing connections:\n")?;
         writeln!(f, "address                  uf     state")?;
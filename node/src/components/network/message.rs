@@ -7,7 +7,9 @@ use std::{
 use casper_hashing::Digest;
 #[cfg(test)]
 use casper_types::testing::TestRng;
-use casper_types::{crypto, AsymmetricType, ProtocolVersion, PublicKey, SecretKey, Signature};
+use casper_types::{
+    crypto, AsymmetricType, ProtocolVersion, PublicKey, SecretKey, Signature, Timestamp,
+};
 use datasize::DataSize;
 use futures::future::BoxFuture;
 use serde::{
@@ -17,7 +19,10 @@ use serde::{
 
 use crate::{effect::EffectBuilder, types::NodeId, utils::opt_display::OptDisplay};
 
-use super::{counting_format::ConnectionId, health::Nonce};
+use super::{
+    counting_format::ConnectionId, health::Nonce, incoming_queue::IncomingClass,
+    validator_endpoints::ValidatorEndpoint,
+};
 
 /// The default protocol version to use in absence of one in the protocol version field.
 #[inline]
@@ -45,6 +50,9 @@ pub(crate) enum Message<P> {
         /// Hash of the chainspec the node is running.
         #[serde(default)]
         chainspec_hash: Option<Digest>,
+        /// The time at which the handshake was created, used to estimate clock skew.
+        #[serde(default)]
+        timestamp: Option<Timestamp>,
     },
     /// A ping request.
     Ping {
@@ -57,6 +65,10 @@ pub(crate) enum Message<P> {
         nonce: Nonce,
     },
     Payload(P),
+    /// A validator's endpoint, flooded to all nodes.
+    ///
+    /// Kept after `Payload`, so the encoding of the existing variants doesn't change.
+    ValidatorEndpoint(Box<ValidatorEndpoint>),
 }
 
 impl<P: Payload> Message<P> {
@@ -67,6 +79,7 @@ impl<P: Payload> Message<P> {
             Message::Handshake { .. } | Message::Ping { .. } | Message::Pong { .. } => {
                 MessageKind::Protocol
             }
+            Message::ValidatorEndpoint(_) => MessageKind::AddressGossip,
             Message::Payload(payload) => payload.message_kind(),
         }
     }
@@ -75,7 +88,10 @@ impl<P: Payload> Message<P> {
     #[inline]
     pub(super) fn is_low_priority(&self) -> bool {
         match self {
-            Message::Handshake { .. } | Message::Ping { .. } | Message::Pong { .. } => false,
+            Message::Handshake { .. }
+            | Message::Ping { .. }
+            | Message::Pong { .. }
+            | Message::ValidatorEndpoint(_) => false,
             Message::Payload(payload) => payload.is_low_priority(),
         }
     }
@@ -89,15 +105,31 @@ impl<P: Payload> Message<P> {
             // sent as a reply, it has a higher weight.
             Message::Ping { .. } => 2,
             Message::Pong { .. } => 1,
+            Message::ValidatorEndpoint(_) => weights.gossip,
             Message::Payload(payload) => payload.incoming_resource_estimate(weights),
         }
     }
 
+    /// Returns the class of the bounded incoming queue the message waits in, if any.
+    #[inline]
+    pub(super) fn incoming_class(&self) -> Option<IncomingClass> {
+        match self {
+            Message::Handshake { .. }
+            | Message::Ping { .. }
+            | Message::Pong { .. }
+            | Message::ValidatorEndpoint(_) => None,
+            Message::Payload(payload) => payload.incoming_class(),
+        }
+    }
+
     /// Returns whether or not the payload is unsafe for syncing node consumption.
     #[inline]
     pub(super) fn payload_is_unsafe_for_syncing_nodes(&self) -> bool {
         match self {
-            Message::Handshake { .. } | Message::Ping { .. } | Message::Pong { .. } => false,
+            Message::Handshake { .. }
+            | Message::Ping { .. }
+            | Message::Pong { .. }
+            | Message::ValidatorEndpoint(_) => false,
             Message::Payload(payload) => payload.is_unsafe_for_syncing_peers(),
         }
     }
@@ -114,7 +146,10 @@ impl<P: Payload> Message<P> {
         REv: FromIncoming<P> + Send,
     {
         match self {
-            Message::Handshake { .. } | Message::Ping { .. } | Message::Pong { .. } => Err(self),
+            Message::Handshake { .. }
+            | Message::Ping { .. }
+            | Message::Pong { .. }
+            | Message::ValidatorEndpoint(_) => Err(self),
             Message::Payload(payload) => {
                 // Note: For now, the wrapping/unwrap of the payload is a bit unfortunate here.
                 REv::try_demand_from_incoming(effect_builder, sender, payload)
@@ -139,8 +174,13 @@ impl NodeKeyPair {
         }
     }
 
+    /// Returns the public key of this keypair.
+    pub(super) fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
     /// Sign a value using this keypair.
-    fn sign<T: AsRef<[u8]>>(&self, value: T) -> Signature {
+    pub(super) fn sign<T: AsRef<[u8]>>(&self, value: T) -> Signature {
         crypto::sign(value, &self.secret_key, &self.public_key)
     }
 }
@@ -281,20 +321,23 @@ impl<P: Display> Display for Message<P> {
                 consensus_certificate,
                 is_syncing,
                 chainspec_hash,
+                timestamp,
             } => {
                 write!(
                     f,
-                    "handshake: {}, public addr: {}, protocol_version: {}, consensus_certificate: {}, is_syncing: {}, chainspec_hash: {}",
+                    "handshake: {}, public addr: {}, protocol_version: {}, consensus_certificate: {}, is_syncing: {}, chainspec_hash: {}, timestamp: {}",
                     network_name,
                     public_addr,
                     protocol_version,
                     OptDisplay::new(consensus_certificate.as_ref(), "none"),
                     is_syncing,
-                    OptDisplay::new(chainspec_hash.as_ref(), "none")
+                    OptDisplay::new(chainspec_hash.as_ref(), "none"),
+                    OptDisplay::new(timestamp.as_ref(), "none")
                 )
             }
             Message::Ping { nonce } => write!(f, "ping({})", nonce),
             Message::Pong { nonce } => write!(f, "pong({})", nonce),
+            Message::ValidatorEndpoint(endpoint) => write!(f, "{}", endpoint),
             Message::Payload(payload) => write!(f, "payload: {}", payload),
         }
     }
@@ -360,6 +403,12 @@ pub(crate) trait Payload:
         false
     }
 
+    /// The class of the bounded incoming queue the payload waits in before reaching the reactor,
+    /// if it is queued there rather than scheduled on the reactor right away.
+    fn incoming_class(&self) -> Option<IncomingClass> {
+        None
+    }
+
     /// Indicates a message is not safe to send to a syncing node.
     ///
     /// This functionality should be removed once multiplexed networking lands.
@@ -579,6 +628,7 @@ mod tests {
             consensus_certificate: Some(ConsensusCertificate::random(&mut rng)),
             is_syncing: false,
             chainspec_hash: Some(Digest::hash("example-chainspec")),
+            timestamp: Some(Timestamp::from(1_234_567)),
         };
 
         let legacy_handshake: V1_0_0_Message = roundtrip_message(&modern_handshake);
@@ -613,6 +663,7 @@ mod tests {
             consensus_certificate,
             is_syncing,
             chainspec_hash,
+            timestamp,
         } = modern_handshake
         {
             assert_eq!(network_name, "example-handshake");
@@ -620,7 +671,8 @@ mod tests {
             assert_eq!(protocol_version, ProtocolVersion::V1_0_0);
             assert!(consensus_certificate.is_none());
             assert!(!is_syncing);
-            assert!(chainspec_hash.is_none())
+            assert!(chainspec_hash.is_none());
+            assert!(timestamp.is_none())
         } else {
             panic!("did not expect modern handshake to deserialize to anything but")
         }
@@ -637,6 +689,7 @@ mod tests {
             consensus_certificate,
             is_syncing,
             chainspec_hash,
+            timestamp,
         } = modern_handshake
         {
             assert!(!is_syncing);
@@ -645,7 +698,8 @@ mod tests {
             assert_eq!(protocol_version, ProtocolVersion::V1_0_0);
             assert!(consensus_certificate.is_none());
             assert!(!is_syncing);
-            assert!(chainspec_hash.is_none())
+            assert!(chainspec_hash.is_none());
+            assert!(timestamp.is_none())
         } else {
             panic!("did not expect modern handshake to deserialize to anything but")
         }
@@ -662,6 +716,7 @@ mod tests {
             consensus_certificate,
             is_syncing,
             chainspec_hash,
+            timestamp,
         } = modern_handshake
         {
             assert_eq!(network_name, "example-handshake");
@@ -689,7 +744,8 @@ mod tests {
                 .unwrap()
             );
             assert!(!is_syncing);
-            assert!(chainspec_hash.is_none())
+            assert!(chainspec_hash.is_none());
+            assert!(timestamp.is_none())
         } else {
             panic!("did not expect modern handshake to deserialize to anything but")
         }
@@ -706,6 +762,7 @@ mod tests {
             consensus_certificate,
             is_syncing,
             chainspec_hash,
+            timestamp,
         } = modern_handshake
         {
             assert!(!is_syncing);
@@ -733,7 +790,8 @@ mod tests {
                 .unwrap()
             );
             assert!(!is_syncing);
-            assert!(chainspec_hash.is_none())
+            assert!(chainspec_hash.is_none());
+            assert!(timestamp.is_none())
         } else {
             panic!("did not expect modern handshake to deserialize to anything but")
         }
@@ -3,7 +3,7 @@ use std::sync::Weak;
 use prometheus::{Counter, IntCounter, IntGauge, Registry};
 use tracing::debug;
 
-use super::{outgoing::OutgoingMetrics, MessageKind};
+use super::{handshake_failures::HandshakeFailureKind, outgoing::OutgoingMetrics, MessageKind};
 use crate::unregister_metric;
 
 /// Network-type agnostic networking metrics.
@@ -115,6 +115,23 @@ pub(super) struct Metrics {
     /// Total time spent delaying incoming traffic from non-validators due to limiter, in seconds.
     pub(super) accumulated_incoming_limiter_delay: Counter,
 
+    /// Number of failed connection attempts to unreachable peers.
+    pub(super) handshake_failures_unreachable: IntCounter,
+    /// Number of failed connection attempts due to TLS errors.
+    pub(super) handshake_failures_tls: IntCounter,
+    /// Number of failed connection attempts to peers on a different network.
+    pub(super) handshake_failures_wrong_network: IntCounter,
+    /// Number of failed connection attempts to peers with an incompatible version.
+    pub(super) handshake_failures_incompatible_version: IntCounter,
+    /// Number of failed connection attempts due to handshake timeouts.
+    pub(super) handshake_failures_timeout: IntCounter,
+    /// Number of failed connection attempts due to invalid handshakes.
+    pub(super) handshake_failures_protocol: IntCounter,
+    /// Number of failed connection attempts due to errors on our side.
+    pub(super) handshake_failures_internal: IntCounter,
+    /// Number of failed connection attempts that got the peer's address blocked.
+    pub(super) handshake_failures_banned: IntCounter,
+
     /// Registry instance.
     registry: Registry,
 }
@@ -337,6 +354,39 @@ impl Metrics {
             "seconds spent delaying incoming traffic from non-validators due to limiter, in seconds."
         )?;
 
+        let handshake_failures_unreachable = IntCounter::new(
+            "net_handshake_failures_unreachable",
+            "number of failed connection attempts to unreachable peers",
+        )?;
+        let handshake_failures_tls = IntCounter::new(
+            "net_handshake_failures_tls",
+            "number of failed connection attempts due to TLS errors",
+        )?;
+        let handshake_failures_wrong_network = IntCounter::new(
+            "net_handshake_failures_wrong_network",
+            "number of failed connection attempts to peers on a different network",
+        )?;
+        let handshake_failures_incompatible_version = IntCounter::new(
+            "net_handshake_failures_incompatible_version",
+            "number of failed connection attempts to peers with an incompatible version",
+        )?;
+        let handshake_failures_timeout = IntCounter::new(
+            "net_handshake_failures_timeout",
+            "number of failed connection attempts due to handshake timeouts",
+        )?;
+        let handshake_failures_protocol = IntCounter::new(
+            "net_handshake_failures_protocol",
+            "number of failed connection attempts due to invalid handshakes",
+        )?;
+        let handshake_failures_internal = IntCounter::new(
+            "net_handshake_failures_internal",
+            "number of failed connection attempts due to errors on our side",
+        )?;
+        let handshake_failures_banned = IntCounter::new(
+            "net_handshake_failures_banned",
+            "number of failed connection attempts that got the peer's address blocked",
+        )?;
+
         registry.register(Box::new(broadcast_requests.clone()))?;
         registry.register(Box::new(direct_message_requests.clone()))?;
         registry.register(Box::new(queued_messages.clone()))?;
@@ -398,6 +448,15 @@ impl Metrics {
         registry.register(Box::new(accumulated_outgoing_limiter_delay.clone()))?;
         registry.register(Box::new(accumulated_incoming_limiter_delay.clone()))?;
 
+        registry.register(Box::new(handshake_failures_unreachable.clone()))?;
+        registry.register(Box::new(handshake_failures_tls.clone()))?;
+        registry.register(Box::new(handshake_failures_wrong_network.clone()))?;
+        registry.register(Box::new(handshake_failures_incompatible_version.clone()))?;
+        registry.register(Box::new(handshake_failures_timeout.clone()))?;
+        registry.register(Box::new(handshake_failures_protocol.clone()))?;
+        registry.register(Box::new(handshake_failures_internal.clone()))?;
+        registry.register(Box::new(handshake_failures_banned.clone()))?;
+
         Ok(Metrics {
             broadcast_requests,
             direct_message_requests,
@@ -452,6 +511,14 @@ impl Metrics {
             requests_for_trie_finished,
             accumulated_outgoing_limiter_delay,
             accumulated_incoming_limiter_delay,
+            handshake_failures_unreachable,
+            handshake_failures_tls,
+            handshake_failures_wrong_network,
+            handshake_failures_incompatible_version,
+            handshake_failures_timeout,
+            handshake_failures_protocol,
+            handshake_failures_internal,
+            handshake_failures_banned,
             registry: registry.clone(),
         })
     }
@@ -556,6 +623,24 @@ impl Metrics {
         }
     }
 
+    /// Records a failed connection attempt.
+    pub(super) fn record_handshake_failure(&self, kind: HandshakeFailureKind, banned: bool) {
+        match kind {
+            HandshakeFailureKind::Unreachable => self.handshake_failures_unreachable.inc(),
+            HandshakeFailureKind::Tls => self.handshake_failures_tls.inc(),
+            HandshakeFailureKind::WrongNetwork => self.handshake_failures_wrong_network.inc(),
+            HandshakeFailureKind::IncompatibleVersion => {
+                self.handshake_failures_incompatible_version.inc()
+            }
+            HandshakeFailureKind::Timeout => self.handshake_failures_timeout.inc(),
+            HandshakeFailureKind::Protocol => self.handshake_failures_protocol.inc(),
+            HandshakeFailureKind::Internal => self.handshake_failures_internal.inc(),
+        }
+        if banned {
+            self.handshake_failures_banned.inc();
+        }
+    }
+
     /// Creates a set of outgoing metrics that is connected to this set of metrics.
     pub(super) fn create_outgoing_metrics(&self) -> OutgoingMetrics {
         OutgoingMetrics {
@@ -648,5 +733,14 @@ impl Drop for Metrics {
 
         unregister_metric!(self.registry, self.accumulated_outgoing_limiter_delay);
         unregister_metric!(self.registry, self.accumulated_incoming_limiter_delay);
+
+        unregister_metric!(self.registry, self.handshake_failures_unreachable);
+        unregister_metric!(self.registry, self.handshake_failures_tls);
+        unregister_metric!(self.registry, self.handshake_failures_wrong_network);
+        unregister_metric!(self.registry, self.handshake_failures_incompatible_version);
+        unregister_metric!(self.registry, self.handshake_failures_timeout);
+        unregister_metric!(self.registry, self.handshake_failures_protocol);
+        unregister_metric!(self.registry, self.handshake_failures_internal);
+        unregister_metric!(self.registry, self.handshake_failures_banned);
     }
 }
@@ -416,10 +416,10 @@ where
     if let Some(outgoing) = outgoing {
         match outgoing.state {
             OutgoingState::Connected { peer_id, .. } => {
-                error_span!("outgoing", %addr, state=%outgoing.state, %peer_id, consensus_key=Empty)
+                error_span!("outgoing", %addr, state=%outgoing.state, %peer_id, connection_id=Empty, consensus_key=Empty)
             }
             _ => {
-                error_span!("outgoing", %addr, state=%outgoing.state, peer_id=Empty, consensus_key=Empty)
+                error_span!("outgoing", %addr, state=%outgoing.state, peer_id=Empty, connection_id=Empty, consensus_key=Empty)
             }
         }
     } else {
@@ -10,7 +10,7 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc, Weak,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use bincode::Options;
@@ -39,14 +39,16 @@ use tracing::{
     info, trace, warn, Instrument, Span,
 };
 
-use casper_types::{ProtocolVersion, PublicKey, TimeDiff};
+use casper_types::{ProtocolVersion, PublicKey, TimeDiff, Timestamp};
 
 use super::{
     chain_info::ChainInfo,
+    connections::ConnectionStats,
     counting_format::{ConnectionId, Role},
     error::{ConnectionError, IoError},
     event::{IncomingConnection, OutgoingConnection},
     full_transport,
+    incoming_queue::{IncomingJob, IncomingQueue},
     limiter::LimiterHandle,
     message::NodeKeyPair,
     message_pack_format::MessagePackFormat,
@@ -81,6 +83,8 @@ struct HandshakeOutcome {
     peer_consensus_public_key: Option<PublicKey>,
     /// Holds the information whether the remote node is syncing.
     is_peer_syncing: bool,
+    /// The time at which the peer created its handshake, if it reported it.
+    peer_timestamp: Option<Timestamp>,
 }
 
 /// Low-level TLS connection function.
@@ -137,6 +141,7 @@ where
     REv: 'static,
     P: Payload,
 {
+    let started = Instant::now();
     let (peer_id, transport) = match tls_connect(&context, peer_addr).await {
         Ok(value) => value,
         Err(error) => return OutgoingConnection::FailedEarly { peer_addr, error },
@@ -152,8 +157,10 @@ where
 
     debug!("Outgoing TLS connection established");
 
-    // Setup connection id and framed transport.
+    // Setup connection id and framed transport, registering the id on the [`Span`] so both ends of
+    // the connection can correlate their logs.
     let connection_id = ConnectionId::from_connection(transport.ssl(), context.our_id, peer_id);
+    Span::current().record("connection_id", &field::display(connection_id));
     let framed_transport = framed_transport(transport, context.chain_info.maximum_net_message_size);
 
     // Negotiate the handshake, concluding the incoming connection process.
@@ -163,7 +170,13 @@ where
             public_addr,
             peer_consensus_public_key,
             is_peer_syncing: is_syncing,
+            peer_timestamp,
         }) => {
+            let handshake_duration = started.elapsed();
+            let now = Timestamp::now();
+            let clock_skew_ms =
+                peer_timestamp.map(|timestamp| timestamp.millis() as i64 - now.millis() as i64);
+
             if let Some(ref public_key) = peer_consensus_public_key {
                 Span::current().record("consensus_key", &field::display(public_key));
             }
@@ -174,8 +187,10 @@ where
             }
 
             // Setup full framed transport, then close down receiving end of the transport.
+            let stats = Arc::new(ConnectionStats::default());
             let full_transport = full_transport::<P>(
                 context.net_metrics.clone(),
+                stats.clone(),
                 connection_id,
                 framed_transport,
                 Role::Dialer,
@@ -185,9 +200,13 @@ where
             OutgoingConnection::Established {
                 peer_addr,
                 peer_id,
+                connection_id,
+                stats,
                 peer_consensus_public_key,
                 sink,
                 is_syncing,
+                handshake_duration,
+                clock_skew_ms,
             }
         }
         Err(error) => OutgoingConnection::Failed {
@@ -299,6 +318,11 @@ impl<REv> NetworkContext<REv> {
         self.public_addr
     }
 
+    /// Our consensus key pair, if we have one.
+    pub(super) fn node_key_pair(&self) -> Option<&NodeKeyPair> {
+        self.node_key_pair.as_ref()
+    }
+
     /// Chain info extract from chainspec.
     pub(super) fn chain_info(&self) -> &ChainInfo {
         &self.chain_info
@@ -351,8 +375,10 @@ where
 
     debug!("Incoming TLS connection established");
 
-    // Setup connection id and framed transport.
+    // Setup connection id and framed transport, registering the id on the [`Span`] so both ends of
+    // the connection can correlate their logs.
     let connection_id = ConnectionId::from_connection(transport.ssl(), context.our_id, peer_id);
+    Span::current().record("connection_id", &field::display(connection_id));
     let framed_transport = framed_transport(transport, context.chain_info.maximum_net_message_size);
 
     // Negotiate the handshake, concluding the incoming connection process.
@@ -362,14 +388,17 @@ where
             public_addr,
             peer_consensus_public_key,
             is_peer_syncing: _,
+            peer_timestamp: _,
         }) => {
             if let Some(ref public_key) = peer_consensus_public_key {
                 Span::current().record("consensus_key", &field::display(public_key));
             }
 
             // Establish full transport and close the receiving end.
+            let stats = Arc::new(ConnectionStats::default());
             let full_transport = full_transport::<P>(
                 context.net_metrics.clone(),
+                stats.clone(),
                 connection_id,
                 framed_transport,
                 Role::Listener,
@@ -381,6 +410,8 @@ where
                 peer_addr,
                 public_addr,
                 peer_id,
+                connection_id,
+                stats,
                 peer_consensus_public_key,
                 stream,
             }
@@ -513,6 +544,7 @@ where
         consensus_certificate,
         is_syncing,
         chainspec_hash,
+        timestamp,
     } = remote_message
     {
         debug!(%protocol_version, "handshake received");
@@ -571,6 +603,7 @@ where
             public_addr,
             peer_consensus_public_key,
             is_peer_syncing: is_syncing,
+            peer_timestamp: timestamp,
         })
     } else {
         // Received a non-handshake, this is an error.
@@ -601,8 +634,13 @@ pub(super) async fn server<P, REv>(
             match listener.accept().await {
                 Ok((stream, peer_addr)) => {
                     // The span setup here is used throughout the entire lifetime of the connection.
-                    let span =
-                        error_span!("incoming", %peer_addr, peer_id=Empty, consensus_key=Empty);
+                    let span = error_span!(
+                        "incoming",
+                        %peer_addr,
+                        peer_id = Empty,
+                        connection_id = Empty,
+                        consensus_key = Empty
+                    );
 
                     let context = context.clone();
                     let handler_span = span.clone();
@@ -656,6 +694,7 @@ pub(super) async fn server<P, REv>(
 /// Schedules all received messages until the stream is closed or an error occurs.
 pub(super) async fn message_reader<REv, P>(
     context: Arc<NetworkContext<REv>>,
+    incoming_queue: Arc<IncomingQueue<P>>,
     mut stream: SplitStream<FullTransport<P>>,
     limiter: LimiterHandle,
     mut close_incoming_receiver: watch::Receiver<()>,
@@ -754,16 +793,34 @@ where
                                 QueueKind::NetworkIncoming
                             };
 
-                            event_queue
-                                .schedule(
-                                    Event::IncomingMessage {
-                                        peer_id: Box::new(peer_id),
-                                        msg: Box::new(msg),
-                                        span: span.clone(),
-                                    },
-                                    queue_kind,
-                                )
-                                .await;
+                            if let Some(class) = msg.incoming_class() {
+                                // Fetched items and gossip wait in the bounded queue, holding up
+                                // this connection while their class is full.
+                                incoming_queue
+                                    .push_job(
+                                        class,
+                                        IncomingJob {
+                                            peer_id,
+                                            msg,
+                                            span: span.clone(),
+                                        },
+                                    )
+                                    .await;
+                                event_queue
+                                    .schedule(Event::IncomingMessageQueued, queue_kind)
+                                    .await;
+                            } else {
+                                event_queue
+                                    .schedule(
+                                        Event::IncomingMessage {
+                                            peer_id: Box::new(peer_id),
+                                            msg: Box::new(msg),
+                                            span: span.clone(),
+                                        },
+                                        queue_kind,
+                                    )
+                                    .await;
+                            }
                         }
                     }
                 }
@@ -201,8 +201,7 @@ impl Reactor for TestReactor {
         )?;
         let gossiper_config = gossiper::Config::new_with_small_timeouts();
         let address_gossiper = Gossiper::<{ GossipedAddress::ID_IS_COMPLETE_ITEM }, _>::new(
-            "address_gossiper",
-            gossiper_config,
+            &gossiper_config,
             registry,
         )?;
 
@@ -240,19 +239,23 @@ impl Reactor for TestReactor {
             Event::ControlAnnouncement(ctrl_ann) => {
                 unreachable!("unhandled control announcement: {}", ctrl_ann)
             }
-            Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(
-                gossiped_address,
-            )) => reactor::wrap_effects(
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::GossipReceived {
+                item_id,
+                sender,
+            }) => reactor::wrap_effects(
                 Event::Net,
                 self.net.handle_event(
                     effect_builder,
                     rng,
-                    NetworkEvent::PeerAddressReceived(gossiped_address),
+                    NetworkEvent::PeerAddressReceived {
+                        gossiped_address: Box::new(item_id),
+                        advertiser: Box::new(sender),
+                    },
                 ),
             ),
 
-            Event::AddressGossiperAnnouncement(GossiperAnnouncement::GossipReceived { .. }) => {
-                // We do not care about the announcement of a new gossiped item in this test.
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(_)) => {
+                // Gossiped addresses are verified before being gossiped onwards.
                 Effects::new()
             }
             Event::AddressGossiperAnnouncement(GossiperAnnouncement::FinishedGossiping(_)) => {
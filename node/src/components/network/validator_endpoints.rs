@@ -0,0 +1,244 @@
+//! Direct connectivity between the validators of an era.
+//!
+//! Consensus messages are only broadcast to validators we are directly connected to, so a
+//! validator which happens not to be among our peers only learns about them later and indirectly.
+//! To avoid this, every validator periodically signs a [`ValidatorEndpoint`] for each era it is
+//! bonded in, binding its consensus key to its node ID and public listening address, and floods it
+//! across the network. Validators of the same era use the endpoints collected in their
+//! [`ValidatorAddressBook`] to dial each other directly.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+    net::SocketAddr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use casper_types::{crypto, EraId, PublicKey, Signature, Timestamp};
+
+use super::message::NodeKeyPair;
+use crate::types::NodeId;
+
+/// The domain tag prefixed to the bytes covered by an endpoint's signature, so that the signature
+/// can't be passed off as one made by the same consensus key for any other purpose.
+const SIGNATURE_DOMAIN_TAG: &[u8] = b"casper-node/validator-endpoint/v1";
+
+/// A validator's self-signed claim that it is listening on an address during an era.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ValidatorEndpoint {
+    /// The era the validator is bonded in.
+    era_id: EraId,
+    /// The validator's consensus key.
+    public_key: PublicKey,
+    /// The ID of the validator's node.
+    node_id: NodeId,
+    /// The public listening address of the validator's node.
+    address: SocketAddr,
+    /// The time the endpoint was created, used to prefer the latest of several endpoints.
+    timestamp: Timestamp,
+    /// The signature over all of the above, made with the validator's consensus key.
+    signature: Signature,
+}
+
+impl ValidatorEndpoint {
+    /// Creates an endpoint for our node on the network named `network_name`, signed with our
+    /// consensus key.
+    pub(super) fn create(
+        network_name: &str,
+        era_id: EraId,
+        node_id: NodeId,
+        address: SocketAddr,
+        key_pair: &NodeKeyPair,
+    ) -> Self {
+        let timestamp = Timestamp::now();
+        let signature = key_pair.sign(signed_bytes(
+            network_name,
+            era_id,
+            node_id,
+            address,
+            timestamp,
+        ));
+        ValidatorEndpoint {
+            era_id,
+            public_key: key_pair.public_key().clone(),
+            node_id,
+            address,
+            timestamp,
+            signature,
+        }
+    }
+
+    /// Verifies that the endpoint was signed by the validator it names, for the network named
+    /// `network_name`.
+    pub(super) fn verify(&self, network_name: &str) -> Result<(), crypto::Error> {
+        let bytes = signed_bytes(
+            network_name,
+            self.era_id,
+            self.node_id,
+            self.address,
+            self.timestamp,
+        );
+        crypto::verify(bytes, &self.signature, &self.public_key)
+    }
+
+    /// Returns the era the validator is bonded in.
+    pub(super) fn era_id(&self) -> EraId {
+        self.era_id
+    }
+
+    /// Returns the validator's consensus key.
+    pub(super) fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Returns the ID of the validator's node.
+    pub(super) fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    /// Returns the public listening address of the validator's node.
+    pub(super) fn address(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+impl Display for ValidatorEndpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "endpoint of validator {} in {}: {} at {}",
+            self.public_key, self.era_id, self.node_id, self.address
+        )
+    }
+}
+
+/// Returns the bytes covered by an endpoint's signature.
+///
+/// The network name is included so that an endpoint signed for one network can't be replayed on
+/// another one sharing the same validators.
+fn signed_bytes(
+    network_name: &str,
+    era_id: EraId,
+    node_id: NodeId,
+    address: SocketAddr,
+    timestamp: Timestamp,
+) -> Vec<u8> {
+    let mut bytes = SIGNATURE_DOMAIN_TAG.to_vec();
+    bytes.extend((network_name.len() as u64).to_le_bytes());
+    bytes.extend(network_name.as_bytes());
+    bytes.extend(era_id.value().to_le_bytes());
+    bytes.extend(node_id.hash_bytes());
+    bytes.extend(address.to_string().as_bytes());
+    bytes.extend(timestamp.millis().to_le_bytes());
+    bytes
+}
+
+/// The latest known endpoints of the validators of each era.
+#[derive(Debug, Default)]
+pub(super) struct ValidatorAddressBook {
+    eras: BTreeMap<EraId, HashMap<PublicKey, ValidatorEndpoint>>,
+}
+
+impl ValidatorAddressBook {
+    /// Records a verified endpoint.
+    ///
+    /// Returns `true` if the endpoint is newer than the one known for the same validator and era,
+    /// in which case it should be flooded onwards.
+    pub(super) fn insert(&mut self, endpoint: ValidatorEndpoint) -> bool {
+        let endpoints = self.eras.entry(endpoint.era_id).or_default();
+        if let Some(known) = endpoints.get(&endpoint.public_key) {
+            if known.timestamp >= endpoint.timestamp {
+                return false;
+            }
+        }
+        endpoints.insert(endpoint.public_key.clone(), endpoint);
+        true
+    }
+
+    /// Returns the known endpoints of the validators of `era_id`.
+    pub(super) fn endpoints(&self, era_id: EraId) -> impl Iterator<Item = &ValidatorEndpoint> {
+        self.eras.get(&era_id).into_iter().flat_map(HashMap::values)
+    }
+
+    /// Forgets the endpoints of all eras not in `era_ids`.
+    pub(super) fn retain_eras(&mut self, era_ids: &[EraId]) {
+        self.eras.retain(|era_id, _| era_ids.contains(era_id));
+    }
+
+    /// Returns the total number of known endpoints.
+    pub(super) fn len(&self) -> usize {
+        self.eras.values().map(HashMap::len).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use casper_types::{testing::TestRng, SecretKey};
+
+    use super::*;
+
+    fn key_pair(rng: &mut TestRng) -> NodeKeyPair {
+        let secret_key = SecretKey::random(rng);
+        let public_key = PublicKey::from(&secret_key);
+        NodeKeyPair::new((Arc::new(secret_key), public_key))
+    }
+
+    const NETWORK_NAME: &str = "casper-test";
+
+    fn endpoint(rng: &mut TestRng, key_pair: &NodeKeyPair, era: u64) -> ValidatorEndpoint {
+        let address = SocketAddr::from(([127, 0, 0, 1], 34553));
+        ValidatorEndpoint::create(
+            NETWORK_NAME,
+            EraId::new(era),
+            NodeId::random(rng),
+            address,
+            key_pair,
+        )
+    }
+
+    #[test]
+    fn should_verify_signature() {
+        let mut rng = TestRng::new();
+        let key_pair = key_pair(&mut rng);
+        let valid = endpoint(&mut rng, &key_pair, 1);
+        assert!(valid.verify(NETWORK_NAME).is_ok());
+
+        // An endpoint signed for one network isn't valid on another.
+        assert!(valid.verify("casper").is_err());
+
+        let mut moved = valid.clone();
+        moved.address = SocketAddr::from(([10, 0, 0, 1], 34553));
+        assert!(moved.verify(NETWORK_NAME).is_err());
+
+        let mut hijacked = valid;
+        hijacked.node_id = NodeId::random(&mut rng);
+        assert!(hijacked.verify(NETWORK_NAME).is_err());
+    }
+
+    #[test]
+    fn should_keep_latest_endpoint_per_validator_and_era() {
+        let mut rng = TestRng::new();
+        let key_pair = key_pair(&mut rng);
+        let mut address_book = ValidatorAddressBook::default();
+
+        let mut older = endpoint(&mut rng, &key_pair, 1);
+        older.timestamp = Timestamp::zero();
+        let newer = endpoint(&mut rng, &key_pair, 1);
+        assert!(address_book.insert(newer.clone()));
+        assert!(!address_book.insert(newer.clone()));
+        assert!(!address_book.insert(older));
+        assert!(address_book.insert(endpoint(&mut rng, &key_pair, 2)));
+        assert_eq!(address_book.len(), 2);
+        assert_eq!(
+            address_book.endpoints(EraId::new(1)).collect::<Vec<_>>(),
+            vec![&newer]
+        );
+
+        address_book.retain_eras(&[EraId::new(2), EraId::new(3)]);
+        assert_eq!(address_book.endpoints(EraId::new(1)).count(), 0);
+        assert_eq!(address_book.len(), 1);
+    }
+}
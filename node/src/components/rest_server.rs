@@ -17,6 +17,9 @@
 //!     example: curl -X GET 'http://<ip>:8888/status'
 //! /metrics : time series data collected from the internals of the node being queried.
 //!     example: curl -X GET 'http://<ip>:8888/metrics'
+//! /chainspec-info : the parsed chainspec the node is running and that of any staged upgrade,
+//!     along with values derived from them such as the least number of blocks in an era.
+//!     example: curl -X GET 'http://<ip>:8888/chainspec-info'
 
 mod config;
 mod event;
@@ -36,7 +39,7 @@ use super::Component;
 use crate::{
     components::{
         rpc_server::rpcs::docs::OPEN_RPC_SCHEMA, ComponentState, InitializedComponent,
-        PortBoundComponent,
+        PortBoundComponent, RestartableComponent,
     },
     effect::{
         requests::{
@@ -130,6 +133,31 @@ impl RestServer {
             inner_rest: None,
         }
     }
+
+    /// Stops the REST server from accepting new connections.
+    ///
+    /// The returned future resolves once the requests already in flight have been served.
+    pub(crate) fn begin_shutdown(&mut self) -> BoxFuture<'static, ()> {
+        let maybe_rest_server = self.inner_rest.take();
+        async {
+            if let Some(mut rest_server) = maybe_rest_server {
+                let _ = rest_server.shutdown_sender.send(());
+
+                // Wait for the server to exit cleanly.
+                if let Some(join_handle) = rest_server.server_join_handle.take() {
+                    match join_handle.await {
+                        Ok(_) => debug!("rest server exited cleanly"),
+                        Err(error) => error!(%error, "could not join rest server task cleanly"),
+                    }
+                } else {
+                    warn!("rest server shutdown while already shut down")
+                }
+            } else {
+                info!("rest server was disabled in config, no shutdown performed")
+            }
+        }
+        .boxed()
+    }
 }
 
 impl<REv> Component<REv> for RestServer
@@ -308,26 +336,32 @@ where
     }
 }
 
-impl Finalize for RestServer {
-    fn finalize(self) -> BoxFuture<'static, ()> {
-        async {
-            if let Some(mut rest_server) = self.inner_rest {
-                let _ = rest_server.shutdown_sender.send(());
-
-                // Wait for the server to exit cleanly.
-                if let Some(join_handle) = rest_server.server_join_handle.take() {
-                    match join_handle.await {
-                        Ok(_) => debug!("rest server exited cleanly"),
-                        Err(error) => error!(%error, "could not join rest server task cleanly"),
-                    }
-                } else {
-                    warn!("rest server shutdown while already shut down")
-                }
-            } else {
-                info!("rest server was disabled in config, no shutdown performed")
-            }
+impl<REv> RestartableComponent<REv> for RestServer
+where
+    REv: ReactorEventT,
+{
+    fn failure(&self) -> Option<String> {
+        if let ComponentState::Fatal(msg) = &self.state {
+            return Some(msg.clone());
         }
-        .boxed()
+        let server_exited = self
+            .inner_rest
+            .as_ref()
+            .and_then(|rest_server| rest_server.server_join_handle.as_ref())
+            .map_or(false, JoinHandle::is_finished);
+        server_exited.then(|| "rest server task exited".to_string())
+    }
+
+    fn reset(&mut self) -> BoxFuture<'static, ()> {
+        let shutdown = self.begin_shutdown();
+        <Self as InitializedComponent<MainEvent>>::set_state(self, ComponentState::Uninitialized);
+        shutdown
+    }
+}
+
+impl Finalize for RestServer {
+    fn finalize(mut self) -> BoxFuture<'static, ()> {
+        self.begin_shutdown()
     }
 }
 
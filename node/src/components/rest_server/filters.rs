@@ -1,4 +1,4 @@
-use futures::FutureExt;
+use futures::{join, FutureExt};
 use http::Response;
 use hyper::Body;
 use tracing::warn;
@@ -17,7 +17,7 @@ use crate::{
     effect::{requests::RestRequest, EffectBuilder},
     reactor::QueueKind,
     rpcs::info::{GetChainspecResult, GetValidatorChangesResult},
-    types::GetStatusResult,
+    types::{chainspec::GetChainspecIntrospectionResult, GetStatusResult},
 };
 
 /// The status URL path.
@@ -35,6 +35,9 @@ pub const VALIDATOR_CHANGES_API_PATH: &str = "validator-changes";
 /// The chainspec file URL path.
 pub const CHAINSPEC_API_PATH: &str = "chainspec";
 
+/// The parsed chainspec URL path.
+pub const CHAINSPEC_INTROSPECTION_API_PATH: &str = "chainspec-info";
+
 pub(super) fn create_status_filter<REv: ReactorEventT>(
     effect_builder: EffectBuilder<REv>,
     api_version: ProtocolVersion,
@@ -134,3 +137,20 @@ pub(super) fn create_chainspec_filter<REv: ReactorEventT>(
         })
         .boxed()
 }
+
+pub(super) fn create_chainspec_introspection_filter<REv: ReactorEventT>(
+    effect_builder: EffectBuilder<REv>,
+    api_version: ProtocolVersion,
+) -> BoxedFilter<(Response<Body>,)> {
+    warp::get()
+        .and(warp::path(CHAINSPEC_INTROSPECTION_API_PATH))
+        .and_then(move || async move {
+            let (active, staged_upgrade) = join!(
+                effect_builder.get_chainspec(),
+                effect_builder.get_staged_chainspec()
+            );
+            let result = GetChainspecIntrospectionResult::new(api_version, active, staged_upgrade);
+            Ok::<_, Rejection>(reply::json(&result).into_response())
+        })
+        .boxed()
+}
@@ -29,6 +29,8 @@ pub(super) async fn run<REv: ReactorEventT>(
     let rest_validator_changes =
         filters::create_validator_changes_filter(effect_builder, api_version);
     let rest_chainspec_filter = filters::create_chainspec_filter(effect_builder, api_version);
+    let rest_chainspec_introspection_filter =
+        filters::create_chainspec_introspection_filter(effect_builder, api_version);
 
     let service = warp::service(
         rest_status
@@ -36,6 +38,7 @@ pub(super) async fn run<REv: ReactorEventT>(
             .or(rest_open_rpc)
             .or(rest_validator_changes)
             .or(rest_chainspec_filter)
+            .or(rest_chainspec_introspection_filter)
             .with(warp::cors().allow_any_origin()),
     );
 
@@ -24,7 +24,8 @@ mod speculative_exec_server;
 use std::{fmt::Debug, time::Instant};
 
 use datasize::DataSize;
-use futures::join;
+use futures::{future::BoxFuture, join, FutureExt};
+use tokio::{sync::oneshot, task::JoinHandle};
 use tracing::{error, info, warn};
 
 use casper_execution_engine::core::engine_state::{
@@ -46,13 +47,13 @@ use crate::{
         announcements::RpcServerAnnouncement,
         requests::{
             BlockSynchronizerRequest, ChainspecRawBytesRequest, ConsensusRequest,
-            ContractRuntimeRequest, MetricsRequest, NetworkInfoRequest, ReactorStatusRequest,
-            RpcRequest, StorageRequest, UpgradeWatcherRequest,
+            ContractRuntimeRequest, FetcherRequest, MetricsRequest, NetworkInfoRequest,
+            ReactorStatusRequest, RpcRequest, StorageRequest, UpgradeWatcherRequest,
         },
         EffectBuilder, EffectExt, Effects, Responder,
     },
     reactor::main_reactor::MainEvent,
-    types::{BlockHeader, ChainspecInfo, Deploy, StatusFeed},
+    types::{BlockHeader, ChainspecInfo, Deploy, DeployExecutionResult, StatusFeed},
     utils::{self, ListeningError},
     NodeRng,
 };
@@ -76,7 +77,9 @@ pub(crate) trait ReactorEventT:
     + From<StorageRequest>
     + From<ReactorStatusRequest>
     + From<BlockSynchronizerRequest>
+    + From<FetcherRequest<DeployExecutionResult>>
     + Send
+    + 'static
 {
 }
 
@@ -93,6 +96,7 @@ impl<REv> ReactorEventT for REv where
         + From<StorageRequest>
         + From<ReactorStatusRequest>
         + From<BlockSynchronizerRequest>
+        + From<FetcherRequest<DeployExecutionResult>>
         + Send
         + 'static
 {
@@ -112,12 +116,41 @@ pub(crate) struct RpcServer {
     network_name: String,
     /// The uptime start.
     node_startup_instant: Instant,
+    /// The JSON-RPC server, present once listening.
+    rpc: Option<InnerServer>,
     /// Inner speculative execution JSON-RPC server is present only when enabled
     /// in the speculative execution JSON-RPC server config.
-    /// The inner speculative execution JSON-RPC server as a struct would have
-    /// no fields and no methods because all that is needed to operate it is the
-    /// spawned tokio task, so a unit struct will suffice here.
-    speculative_exec: Option<()>,
+    speculative_exec: Option<InnerServer>,
+}
+
+/// A JSON-RPC server running in a background task.
+#[derive(DataSize, Debug)]
+struct InnerServer {
+    /// When the message is sent, the server stops accepting new connections and exits once the
+    /// requests it is handling have been served.
+    #[data_size(skip)]
+    shutdown_sender: Option<oneshot::Sender<()>>,
+    /// The task handle which will only join once the server has exited.
+    #[data_size(skip)]
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl InnerServer {
+    fn new(shutdown_sender: oneshot::Sender<()>, join_handle: JoinHandle<()>) -> Self {
+        InnerServer {
+            shutdown_sender: Some(shutdown_sender),
+            join_handle: Some(join_handle),
+        }
+    }
+
+    /// Signals the server to shut down, returning the handle of its task if it wasn't already
+    /// signalled.
+    fn shut_down(&mut self) -> Option<JoinHandle<()>> {
+        if let Some(shutdown_sender) = self.shutdown_sender.take() {
+            let _ = shutdown_sender.send(());
+        }
+        self.join_handle.take()
+    }
 }
 
 impl RpcServer {
@@ -135,9 +168,31 @@ impl RpcServer {
             api_version,
             network_name,
             node_startup_instant,
+            rpc: None,
             speculative_exec: None,
         }
     }
+
+    /// Stops the JSON-RPC servers from accepting new connections.
+    ///
+    /// The returned future resolves once the servers have finished handling the requests already
+    /// in flight.  Those requests still need the reactor to be running in order to complete.
+    pub(crate) fn begin_shutdown(&mut self) -> BoxFuture<'static, ()> {
+        let join_handles: Vec<_> = self
+            .rpc
+            .iter_mut()
+            .chain(self.speculative_exec.iter_mut())
+            .filter_map(InnerServer::shut_down)
+            .collect();
+        async move {
+            for join_handle in join_handles {
+                if let Err(error) = join_handle.await {
+                    error!(%error, "could not join JSON-RPC server task cleanly");
+                }
+            }
+        }
+        .boxed()
+    }
 }
 
 impl RpcServer {
@@ -323,6 +378,80 @@ where
                         result: Box::new(result),
                         main_responder: responder,
                     }),
+                Event::RpcRequest(RpcRequest::GetBlocksByProposer {
+                    proposer,
+                    range,
+                    responder,
+                }) => async move {
+                    responder
+                        .respond(
+                            effect_builder
+                                .get_blocks_by_proposer_from_storage(proposer, range)
+                                .await,
+                        )
+                        .await
+                }
+                .ignore(),
+                Event::RpcRequest(RpcRequest::GetSwitchBlock {
+                    era_id,
+                    only_from_available_block_range,
+                    responder,
+                }) => async move {
+                    responder
+                        .respond(
+                            effect_builder
+                                .get_switch_block_with_metadata_from_storage(
+                                    era_id,
+                                    only_from_available_block_range,
+                                )
+                                .await,
+                        )
+                        .await
+                }
+                .ignore(),
+                Event::RpcRequest(RpcRequest::GetStateRootHashRange {
+                    low,
+                    high,
+                    responder,
+                }) => async move {
+                    responder
+                        .respond(
+                            effect_builder
+                                .get_state_root_hash_range_from_storage(low, high)
+                                .await,
+                        )
+                        .await
+                }
+                .ignore(),
+                Event::RpcRequest(RpcRequest::GetDeploysByTag {
+                    user_tag,
+                    after,
+                    limit,
+                    responder,
+                }) => async move {
+                    responder
+                        .respond(
+                            effect_builder
+                                .get_deploys_by_tag_from_storage(user_tag, after, limit)
+                                .await,
+                        )
+                        .await
+                }
+                .ignore(),
+                Event::RpcRequest(RpcRequest::GetKeyHistory {
+                    key,
+                    before_height,
+                    responder,
+                }) => async move {
+                    responder
+                        .respond(
+                            effect_builder
+                                .get_key_history_from_storage(key, before_height)
+                                .await,
+                        )
+                        .await
+                }
+                .ignore(),
                 Event::RpcRequest(RpcRequest::QueryGlobalState {
                     state_root_hash,
                     base_key,
@@ -536,27 +665,32 @@ where
         self.speculative_exec = if self.speculative_exec_config.enable_server {
             let cfg = &self.speculative_exec_config;
             let builder = utils::start_listening(&cfg.address)?;
-            tokio::spawn(speculative_exec_server::run(
+            let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+            let join_handle = tokio::spawn(speculative_exec_server::run(
                 builder,
                 effect_builder,
                 self.api_version,
                 cfg.qps_limit,
                 cfg.max_body_bytes,
+                shutdown_receiver,
             ));
-            Some(())
+            Some(InnerServer::new(shutdown_sender, join_handle))
         } else {
             None
         };
 
         let cfg = &self.config;
         let builder = utils::start_listening(&cfg.address)?;
-        tokio::spawn(http_server::run(
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+        let join_handle = tokio::spawn(http_server::run(
             builder,
             effect_builder,
             self.api_version,
             cfg.qps_limit,
             cfg.max_body_bytes,
+            shutdown_receiver,
         ));
+        self.rpc = Some(InnerServer::new(shutdown_sender, join_handle));
 
         Ok(Effects::new())
     }
@@ -564,8 +698,12 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::{sync::Arc, time::Duration};
+
     use schemars::schema_for_value;
+    use tokio::{sync::Notify, time};
 
+    use super::*;
     use crate::{rpcs::docs::OPEN_RPC_SCHEMA, testing::assert_schema};
 
     #[test]
@@ -576,4 +714,48 @@ mod tests {
         );
         assert_schema(schema_path, schema_for_value!(OPEN_RPC_SCHEMA.clone()));
     }
+
+    /// Returns a stand-in for a running JSON-RPC server, which exits once told to shut down and
+    /// once its in-flight request is released.
+    fn inner_server(release_request: Arc<Notify>) -> InnerServer {
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel();
+        let join_handle = tokio::spawn(async move {
+            let _ = shutdown_receiver.await;
+            release_request.notified().await;
+        });
+        InnerServer::new(shutdown_sender, join_handle)
+    }
+
+    #[tokio::test]
+    async fn begin_shutdown_should_wait_for_in_flight_requests() {
+        let mut rpc_server = RpcServer::new(
+            Config::default(),
+            SpeculativeExecConfig::default(),
+            ProtocolVersion::V1_0_0,
+            "test".to_string(),
+            Instant::now(),
+        );
+        let release_rpc_request = Arc::new(Notify::new());
+        let release_speculative_exec_request = Arc::new(Notify::new());
+        rpc_server.rpc = Some(inner_server(Arc::clone(&release_rpc_request)));
+        rpc_server.speculative_exec =
+            Some(inner_server(Arc::clone(&release_speculative_exec_request)));
+
+        // Both servers are signalled, but shutting down lasts until both in-flight requests have
+        // been served.
+        let mut shutdown = rpc_server.begin_shutdown();
+        release_rpc_request.notify_one();
+        assert!(time::timeout(Duration::from_millis(100), &mut shutdown)
+            .await
+            .is_err());
+        release_speculative_exec_request.notify_one();
+        time::timeout(Duration::from_secs(5), shutdown)
+            .await
+            .expect("should finish shutting down once requests are served");
+
+        // The servers are only shut down once.
+        time::timeout(Duration::from_secs(5), rpc_server.begin_shutdown())
+            .await
+            .expect("should not wait for servers already shut down");
+    }
 }
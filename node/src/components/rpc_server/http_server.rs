@@ -1,4 +1,5 @@
 use hyper::server::{conn::AddrIncoming, Builder};
+use tokio::sync::oneshot;
 
 use casper_json_rpc::RequestHandlersBuilder;
 use casper_types::ProtocolVersion;
@@ -6,12 +7,18 @@ use casper_types::ProtocolVersion;
 use super::{
     rpcs::{
         account::PutDeploy,
-        chain::{GetBlock, GetBlockTransfers, GetEraInfoBySwitchBlock, GetStateRootHash},
+        chain::{
+            GetBlock, GetBlockTransfers, GetBlocksByProposer, GetEraInfoBySwitchBlock,
+            GetEraValidators, GetStateRootHash, GetStateRootHashRange,
+        },
         docs::ListRpcs,
-        info::{GetChainspec, GetDeploy, GetPeers, GetStatus, GetValidatorChanges},
+        info::{
+            GetChainspec, GetDeploy, GetDeploysByTag, GetExecutionResultProof, GetPeers, GetStatus,
+            GetValidatorChanges,
+        },
         state::{
-            GetAccountInfo, GetAuctionInfo, GetBalance, GetDictionaryItem, GetItem, GetTrie,
-            QueryBalance, QueryGlobalState,
+            GetAccountInfo, GetAuctionInfo, GetBalance, GetBidStatus, GetDictionaryItem, GetItem,
+            GetKeyHistory, GetTrie, GetUnbonds, QueryBalance, QueryGlobalState,
         },
         RpcWithOptionalParams, RpcWithParams, RpcWithoutParams,
     },
@@ -31,27 +38,36 @@ pub(super) async fn run<REv: ReactorEventT>(
     api_version: ProtocolVersion,
     qps_limit: u64,
     max_body_bytes: u32,
+    shutdown_receiver: oneshot::Receiver<()>,
 ) {
     let mut handlers = RequestHandlersBuilder::new();
     PutDeploy::register_as_handler(effect_builder, api_version, &mut handlers);
     GetBlock::register_as_handler(effect_builder, api_version, &mut handlers);
     GetBlockTransfers::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetBlocksByProposer::register_as_handler(effect_builder, api_version, &mut handlers);
     GetStateRootHash::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetStateRootHashRange::register_as_handler(effect_builder, api_version, &mut handlers);
     GetItem::register_as_handler(effect_builder, api_version, &mut handlers);
     QueryGlobalState::register_as_handler(effect_builder, api_version, &mut handlers);
     GetBalance::register_as_handler(effect_builder, api_version, &mut handlers);
     GetAccountInfo::register_as_handler(effect_builder, api_version, &mut handlers);
     GetDeploy::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetDeploysByTag::register_as_handler(effect_builder, api_version, &mut handlers);
     GetPeers::register_as_handler(effect_builder, api_version, &mut handlers);
     GetStatus::register_as_handler(effect_builder, api_version, &mut handlers);
     GetEraInfoBySwitchBlock::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetEraValidators::register_as_handler(effect_builder, api_version, &mut handlers);
     GetAuctionInfo::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetBidStatus::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetUnbonds::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetExecutionResultProof::register_as_handler(effect_builder, api_version, &mut handlers);
     GetTrie::register_as_handler(effect_builder, api_version, &mut handlers);
     GetValidatorChanges::register_as_handler(effect_builder, api_version, &mut handlers);
     ListRpcs::register_as_handler(effect_builder, api_version, &mut handlers);
     GetDictionaryItem::register_as_handler(effect_builder, api_version, &mut handlers);
     GetChainspec::register_as_handler(effect_builder, api_version, &mut handlers);
     QueryBalance::register_as_handler(effect_builder, api_version, &mut handlers);
+    GetKeyHistory::register_as_handler(effect_builder, api_version, &mut handlers);
     let handlers = handlers.build();
 
     super::rpcs::run(
@@ -61,6 +77,7 @@ pub(super) async fn run<REv: ReactorEventT>(
         max_body_bytes,
         RPC_API_PATH,
         RPC_API_SERVER_NAME,
+        shutdown_receiver,
     )
     .await;
 }
@@ -12,6 +12,8 @@ mod error_code;
 pub mod info;
 pub mod speculative_exec;
 pub mod state;
+#[cfg(test)]
+mod test_utils;
 
 use std::{str, sync::Arc, time::Duration};
 
@@ -23,7 +25,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::sync::oneshot;
 use tower::ServiceBuilder;
-use tracing::info;
+use tracing::{info, warn};
 use warp::Filter;
 
 use casper_json_rpc::{Error, Params, RequestHandlers, RequestHandlersBuilder, ReservedErrorCode};
@@ -254,7 +256,10 @@ pub(super) trait RpcWithOptionalParams {
     ) -> Result<Self::ResponseResult, Error>;
 }
 
-/// Start JSON RPC server in a background.
+/// Run a JSON-RPC server.
+///
+/// A message received on `shutdown_receiver` causes the server to stop accepting new connections
+/// and to exit once the requests it is already handling have been served.
 pub(super) async fn run(
     builder: Builder<AddrIncoming>,
     handlers: RequestHandlers,
@@ -262,6 +267,7 @@ pub(super) async fn run(
     max_body_bytes: u32,
     api_path: &'static str,
     server_name: &'static str,
+    shutdown_receiver: oneshot::Receiver<()>,
 ) {
     let make_svc = hyper::service::make_service_fn(move |_| {
         let service_routes = casper_json_rpc::route(
@@ -288,13 +294,13 @@ pub(super) async fn run(
     let server = builder.serve(make_svc);
     info!(address = %server.local_addr(), "started {} server", server_name);
 
-    let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
     let server_with_shutdown = server.with_graceful_shutdown(async {
         shutdown_receiver.await.ok();
     });
 
-    let _ = tokio::spawn(server_with_shutdown).await;
-    let _ = shutdown_sender.send(());
+    if let Err(error) = server_with_shutdown.await {
+        warn!(%error, "error running {} server", server_name);
+    }
     info!("{} server shut down", server_name);
 }
 
@@ -527,4 +533,82 @@ mod tests {
             );
         }
     }
+
+    mod graceful_shutdown {
+        use std::net::TcpListener;
+
+        use http::header::CONTENT_TYPE;
+        use hyper::Server;
+        use tokio::{sync::Notify, time};
+
+        use super::*;
+
+        const METHOD: &str = "slow_method";
+        const API_PATH: &str = "rpc";
+
+        #[tokio::test]
+        async fn should_serve_in_flight_requests_before_exiting() {
+            // The handler signals once it is handling a request, then waits until released.
+            let request_started = Arc::new(Notify::new());
+            let release_request = Arc::new(Notify::new());
+            let started = Arc::clone(&request_started);
+            let release = Arc::clone(&release_request);
+            let handler = move |_maybe_params: Option<Params>| {
+                let started = Arc::clone(&started);
+                let release = Arc::clone(&release);
+                async move {
+                    started.notify_one();
+                    release.notified().await;
+                    Ok::<_, Error>(1_u8)
+                }
+            };
+            let mut handlers = RequestHandlersBuilder::new();
+            handlers.register_handler(METHOD, Arc::new(handler));
+
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/{}", listener.local_addr().unwrap(), API_PATH);
+            let builder = Server::from_tcp(listener).unwrap();
+            let (shutdown_sender, shutdown_receiver) = oneshot::channel();
+            let server = tokio::spawn(run(
+                builder,
+                handlers.build(),
+                100,
+                1024,
+                API_PATH,
+                "test JSON-RPC",
+                shutdown_receiver,
+            ));
+
+            let send_request = |url: String| async move {
+                let body = format!(r#"{{"jsonrpc":"2.0","id":"a","method":"{}"}}"#, METHOD);
+                reqwest::Client::new()
+                    .post(url)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(body)
+                    .send()
+                    .await
+            };
+            let in_flight_request = tokio::spawn(send_request(url.clone()));
+            request_started.notified().await;
+
+            // Once told to shut down, the server refuses new connections, but keeps running until
+            // the in-flight request has been served.
+            shutdown_sender.send(()).unwrap();
+            time::sleep(Duration::from_millis(100)).await;
+            assert!(send_request(url).await.is_err());
+            assert!(!server.is_finished());
+
+            release_request.notify_one();
+            let http_response = in_flight_request.await.unwrap().unwrap();
+            assert_eq!(http_response.status(), StatusCode::OK);
+            let rpc_response: Response =
+                serde_json::from_slice(&http_response.bytes().await.unwrap()).unwrap();
+            assert_eq!(rpc_response.result(), Some(1_u8));
+
+            time::timeout(Duration::from_secs(5), server)
+                .await
+                .expect("server should exit once the in-flight request is served")
+                .unwrap();
+        }
+    }
 }
@@ -12,18 +12,30 @@ use once_cell::sync::Lazy;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use casper_execution_engine::core::engine_state::SystemContractRegistry;
 use casper_hashing::Digest;
-use casper_types::{Key, ProtocolVersion, Transfer};
+use casper_types::{
+    system::{
+        auction::{SeigniorageRecipientsSnapshot, SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY},
+        AUCTION,
+    },
+    CLValue, EraId, Key, ProtocolVersion, PublicKey, Transfer,
+};
 
 use super::{
     docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
-    Error, ErrorCode, ReactorEventT, ReservedErrorCode, RpcRequest, RpcWithOptionalParams,
+    state, Error, ErrorCode, ReactorEventT, ReservedErrorCode, RpcRequest, RpcWithOptionalParams,
+    RpcWithParams,
 };
 use crate::{
     effect::EffectBuilder,
     reactor::QueueKind,
-    rpcs::common,
-    types::{Block, BlockHash, BlockWithMetadata, JsonBlock},
+    rpcs::common::{self, MERKLE_PROOF},
+    types::{
+        json_compatibility::{JsonValidatorWeights, StoredValue},
+        Block, BlockHash, BlockHashAndHeight, BlockWithMetadata, JsonBlock, JsonBlockHeader,
+        JsonProof,
+    },
 };
 pub use era_summary::EraSummary;
 use era_summary::ERA_SUMMARY;
@@ -54,6 +66,19 @@ static GET_STATE_ROOT_HASH_RESULT: Lazy<GetStateRootHashResult> =
         api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
         state_root_hash: Some(*Block::doc_example().header().state_root_hash()),
     });
+static GET_STATE_ROOT_HASH_RANGE_PARAMS: Lazy<GetStateRootHashRangeParams> =
+    Lazy::new(|| GetStateRootHashRangeParams {
+        low: Block::doc_example().header().height(),
+        high: Block::doc_example().header().height(),
+    });
+static GET_STATE_ROOT_HASH_RANGE_RESULT: Lazy<GetStateRootHashRangeResult> =
+    Lazy::new(|| GetStateRootHashRangeResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        state_root_hashes: vec![BlockHeightAndStateRootHash {
+            block_height: Block::doc_example().header().height(),
+            state_root_hash: *Block::doc_example().header().state_root_hash(),
+        }],
+    });
 static GET_ERA_INFO_PARAMS: Lazy<GetEraInfoParams> = Lazy::new(|| GetEraInfoParams {
     block_identifier: BlockIdentifier::Hash(*Block::doc_example().hash()),
 });
@@ -61,6 +86,49 @@ static GET_ERA_INFO_RESULT: Lazy<GetEraInfoResult> = Lazy::new(|| GetEraInfoResu
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     era_summary: Some(ERA_SUMMARY.clone()),
 });
+static GET_ERA_VALIDATORS_PARAMS: Lazy<GetEraValidatorsParams> =
+    Lazy::new(|| GetEraValidatorsParams {
+        era_id: Block::doc_example().header().era_id().successor(),
+    });
+static GET_ERA_VALIDATORS_RESULT: Lazy<GetEraValidatorsResult> = Lazy::new(|| {
+    let block = Block::doc_example();
+    let validator_weights = block
+        .header()
+        .next_era_validator_weights()
+        .into_iter()
+        .flatten()
+        .map(|(public_key, weight)| JsonValidatorWeights::new(public_key.clone(), *weight))
+        .collect();
+    let snapshot = CLValue::from_t(SeigniorageRecipientsSnapshot::new()).unwrap();
+    GetEraValidatorsResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        era_validators: EraValidatorsWithProof {
+            era_id: block.header().era_id().successor(),
+            validator_weights,
+            switch_block_hash: *block.hash(),
+            switch_block_header: JsonBlockHeader::from(block.header().clone()),
+            finality_signatures: JsonBlock::doc_example().proofs.clone(),
+            seigniorage_recipients_snapshot: StoredValue::CLValue(snapshot),
+            merkle_proof: MERKLE_PROOF.clone(),
+        },
+    }
+});
+static GET_BLOCKS_BY_PROPOSER_PARAMS: Lazy<GetBlocksByProposerParams> =
+    Lazy::new(|| GetBlocksByProposerParams {
+        public_key: Block::doc_example().body().proposer().clone(),
+        range: ProposerBlockRange::Era(Block::doc_example().header().era_id()),
+    });
+static GET_BLOCKS_BY_PROPOSER_RESULT: Lazy<GetBlocksByProposerResult> =
+    Lazy::new(|| GetBlocksByProposerResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        blocks: vec![BlockHashAndHeight::new(
+            *Block::doc_example().hash(),
+            Block::doc_example().header().height(),
+        )],
+    });
+
+/// The maximum number of heights a single "chain_get_state_root_hash_range" request may span.
+pub const MAX_STATE_ROOT_HASH_RANGE: u64 = 1_000;
 
 /// Identifier for possible ways to retrieve a block.
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, JsonSchema)]
@@ -93,6 +161,31 @@ impl str::FromStr for BlockIdentifier {
     }
 }
 
+/// The range of blocks searched for those created by a given proposer.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub enum ProposerBlockRange {
+    /// All blocks of the given era.
+    Era(EraId),
+    /// All blocks with heights between `low` and `high`, inclusive.
+    Heights {
+        /// The lowest block height in the range.
+        low: u64,
+        /// The highest block height in the range.
+        high: u64,
+    },
+}
+
+impl ProposerBlockRange {
+    /// Returns `true` if a block at `height` in era `era_id` falls within the range.
+    pub(crate) fn contains(&self, era_id: EraId, height: u64) -> bool {
+        match *self {
+            ProposerBlockRange::Era(range_era_id) => era_id == range_era_id,
+            ProposerBlockRange::Heights { low, high } => low <= height && height <= high,
+        }
+    }
+}
+
 /// Represents errors that can arise when parsing a [`BlockIdentifier`].
 #[derive(thiserror::Error, Debug)]
 pub enum ParseBlockIdentifierError {
@@ -334,6 +427,99 @@ impl RpcWithOptionalParams for GetStateRootHash {
     }
 }
 
+/// Params for "chain_get_state_root_hash_range" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetStateRootHashRangeParams {
+    /// The lowest block height in the range.
+    pub low: u64,
+    /// The highest block height in the range, inclusive.
+    pub high: u64,
+}
+
+impl DocExample for GetStateRootHashRangeParams {
+    fn doc_example() -> &'static Self {
+        &*GET_STATE_ROOT_HASH_RANGE_PARAMS
+    }
+}
+
+/// A block's height and the hash of its state root.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BlockHeightAndStateRootHash {
+    /// The height of the block.
+    pub block_height: u64,
+    /// Hex-encoded hash of the state root after executing the block.
+    pub state_root_hash: Digest,
+}
+
+/// Result for "chain_get_state_root_hash_range" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetStateRootHashRangeResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The state root hashes of the blocks in the range, ordered by height.  Heights outside the
+    /// available block range are omitted.
+    pub state_root_hashes: Vec<BlockHeightAndStateRootHash>,
+}
+
+impl DocExample for GetStateRootHashRangeResult {
+    fn doc_example() -> &'static Self {
+        &*GET_STATE_ROOT_HASH_RANGE_RESULT
+    }
+}
+
+/// "chain_get_state_root_hash_range" RPC.
+pub struct GetStateRootHashRange {}
+
+#[async_trait]
+impl RpcWithParams for GetStateRootHashRange {
+    const METHOD: &'static str = "chain_get_state_root_hash_range";
+    type RequestParams = GetStateRootHashRangeParams;
+    type ResponseResult = GetStateRootHashRangeResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let GetStateRootHashRangeParams { low, high } = params;
+        if high < low || high - low >= MAX_STATE_ROOT_HASH_RANGE {
+            let message = format!(
+                "invalid range {}..={}: must not be empty or span more than {} heights",
+                low, high, MAX_STATE_ROOT_HASH_RANGE
+            );
+            return Err(Error::new(ReservedErrorCode::InvalidParams, message));
+        }
+
+        let state_root_hashes = effect_builder
+            .make_request(
+                |responder| RpcRequest::GetStateRootHashRange {
+                    low,
+                    high,
+                    responder,
+                },
+                QueueKind::Api,
+            )
+            .await
+            .into_iter()
+            .map(
+                |(block_height, state_root_hash)| BlockHeightAndStateRootHash {
+                    block_height,
+                    state_root_hash,
+                },
+            )
+            .collect();
+
+        Ok(Self::ResponseResult {
+            api_version,
+            state_root_hashes,
+        })
+    }
+}
+
 /// Params for "chain_get_era_info" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -420,6 +606,231 @@ impl RpcWithOptionalParams for GetEraInfoBySwitchBlock {
     }
 }
 
+/// Params for "chain_get_era_validators" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetEraValidatorsParams {
+    /// The era whose validators to retrieve.
+    pub era_id: EraId,
+}
+
+impl DocExample for GetEraValidatorsParams {
+    fn doc_example() -> &'static Self {
+        &*GET_ERA_VALIDATORS_PARAMS
+    }
+}
+
+/// The validators of an era together with everything needed to verify them.
+///
+/// The weights are listed in the header of the previous era's switch block, which is signed by
+/// the given finality signatures.  The weights are derived from the auction's seigniorage
+/// recipients snapshot, whose Merkle proof is rooted in the switch block's state root hash.  The
+/// proof starts at the auction contract.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EraValidatorsWithProof {
+    /// The era the weights apply to.
+    pub era_id: EraId,
+    /// The validators of the era and their weights.
+    pub validator_weights: Vec<JsonValidatorWeights>,
+    /// The hash of the switch block of the previous era.
+    pub switch_block_hash: BlockHash,
+    /// The header of the switch block of the previous era.
+    pub switch_block_header: JsonBlockHeader,
+    /// The finality signatures of the switch block.
+    pub finality_signatures: Vec<JsonProof>,
+    /// The seigniorage recipients snapshot of the auction contract after the switch block.
+    pub seigniorage_recipients_snapshot: StoredValue,
+    /// The Merkle proof of the snapshot.
+    pub merkle_proof: String,
+}
+
+/// Result for "chain_get_era_validators" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetEraValidatorsResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The era's validators.
+    pub era_validators: EraValidatorsWithProof,
+}
+
+impl DocExample for GetEraValidatorsResult {
+    fn doc_example() -> &'static Self {
+        &*GET_ERA_VALIDATORS_RESULT
+    }
+}
+
+/// "chain_get_era_validators" RPC.
+pub struct GetEraValidators {}
+
+#[async_trait]
+impl RpcWithParams for GetEraValidators {
+    const METHOD: &'static str = "chain_get_era_validators";
+    type RequestParams = GetEraValidatorsParams;
+    type ResponseResult = GetEraValidatorsResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let era_id = params.era_id;
+        // The validators of the first era are not listed in any switch block.
+        let maybe_switch_block = match era_id.predecessor() {
+            Some(previous_era_id) => {
+                effect_builder
+                    .make_request(
+                        |responder| RpcRequest::GetSwitchBlock {
+                            era_id: previous_era_id,
+                            only_from_available_block_range: true,
+                            responder,
+                        },
+                        QueueKind::Api,
+                    )
+                    .await
+            }
+            None => None,
+        };
+        let BlockWithMetadata {
+            block,
+            block_signatures,
+        } = match maybe_switch_block {
+            Some(block_with_metadata) => block_with_metadata,
+            None => {
+                return Err(common::missing_block_or_state_root_error(
+                    effect_builder,
+                    ErrorCode::NoSuchBlock,
+                    format!("switch block preceding {} not stored on this node", era_id),
+                )
+                .await)
+            }
+        };
+
+        let validator_weights = block
+            .header()
+            .next_era_validator_weights()
+            .ok_or_else(|| {
+                Error::new(
+                    ReservedErrorCode::InternalError,
+                    format!("block {} is not a switch block", block.hash()),
+                )
+            })?
+            .iter()
+            .map(|(public_key, weight)| JsonValidatorWeights::new(public_key.clone(), *weight))
+            .collect();
+
+        let state_root_hash = *block.state_root_hash();
+        let (registry_value, _) = state::run_query(
+            effect_builder,
+            state_root_hash,
+            Key::SystemContractRegistry,
+            vec![],
+        )
+        .await?;
+        let auction_hash = registry_value
+            .as_cl_value()
+            .and_then(|cl_value| cl_value.clone().into_t::<SystemContractRegistry>().ok())
+            .and_then(|registry| registry.get(AUCTION).copied())
+            .ok_or_else(|| {
+                Error::new(
+                    ReservedErrorCode::InternalError,
+                    "failed to read the auction contract hash",
+                )
+            })?;
+        let (seigniorage_recipients_snapshot, merkle_proof) = common::run_query_and_encode(
+            effect_builder,
+            state_root_hash,
+            Key::from(auction_hash),
+            vec![SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY.to_string()],
+        )
+        .await?;
+
+        Ok(Self::ResponseResult {
+            api_version,
+            era_validators: EraValidatorsWithProof {
+                era_id,
+                validator_weights,
+                switch_block_hash: *block.hash(),
+                switch_block_header: JsonBlockHeader::from(block.header().clone()),
+                finality_signatures: block_signatures
+                    .proofs
+                    .into_iter()
+                    .map(JsonProof::from)
+                    .collect(),
+                seigniorage_recipients_snapshot,
+                merkle_proof,
+            },
+        })
+    }
+}
+
+/// Params for "chain_get_blocks_by_proposer" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlocksByProposerParams {
+    /// The public key of the proposer.
+    pub public_key: PublicKey,
+    /// The range of blocks to search.
+    pub range: ProposerBlockRange,
+}
+
+impl DocExample for GetBlocksByProposerParams {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCKS_BY_PROPOSER_PARAMS
+    }
+}
+
+/// Result for "chain_get_blocks_by_proposer" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlocksByProposerResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The hashes and heights of the blocks created by the proposer, ordered by height.
+    pub blocks: Vec<BlockHashAndHeight>,
+}
+
+impl DocExample for GetBlocksByProposerResult {
+    fn doc_example() -> &'static Self {
+        &*GET_BLOCKS_BY_PROPOSER_RESULT
+    }
+}
+
+/// "chain_get_blocks_by_proposer" RPC.
+pub struct GetBlocksByProposer {}
+
+#[async_trait]
+impl RpcWithParams for GetBlocksByProposer {
+    const METHOD: &'static str = "chain_get_blocks_by_proposer";
+    type RequestParams = GetBlocksByProposerParams;
+    type ResponseResult = GetBlocksByProposerResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let blocks = effect_builder
+            .make_request(
+                |responder| RpcRequest::GetBlocksByProposer {
+                    proposer: Box::new(params.public_key),
+                    range: params.range,
+                    responder,
+                },
+                QueueKind::Api,
+            )
+            .await;
+
+        Ok(Self::ResponseResult {
+            api_version,
+            blocks,
+        })
+    }
+}
+
 pub(super) async fn get_block_with_metadata<REv: ReactorEventT>(
     maybe_id: Option<BlockIdentifier>,
     only_from_available_block_range: bool,
@@ -473,3 +884,229 @@ pub(super) async fn get_block_with_metadata<REv: ReactorEventT>(
 
     Err(error)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeMap, VecDeque},
+        convert::TryFrom,
+    };
+
+    use rand::Rng;
+    use tokio::task::JoinHandle;
+
+    use casper_execution_engine::{
+        core::engine_state::QueryResult, storage::trie::merkle_proof::TrieMerkleProof,
+    };
+    use casper_types::{
+        bytesrepr::ToBytes, testing::TestRng, ContractHash, SecretKey, Signature, U512,
+    };
+
+    use super::*;
+    use crate::{
+        components::rpc_server::rpcs::{test_utils::MockReactor, ErrorData},
+        types::{AvailableBlockRange, BlockSignatures, FinalitySignature},
+    };
+
+    const SWITCH_BLOCK_ERA: EraId = EraId::new(4);
+
+    fn get_era_validators(
+        reactor: &MockReactor,
+        era_id: EraId,
+    ) -> JoinHandle<Result<GetEraValidatorsResult, Error>> {
+        tokio::spawn(GetEraValidators::do_handle_request(
+            reactor.effect_builder(),
+            ProtocolVersion::V1_0_0,
+            GetEraValidatorsParams { era_id },
+        ))
+    }
+
+    async fn expect_switch_block_request(
+        reactor: &MockReactor,
+        maybe_switch_block: Option<BlockWithMetadata>,
+    ) {
+        match reactor.expect_rpc_request().await {
+            RpcRequest::GetSwitchBlock {
+                era_id,
+                only_from_available_block_range,
+                responder,
+            } => {
+                assert_eq!(era_id, SWITCH_BLOCK_ERA);
+                assert!(only_from_available_block_range);
+                responder.respond(maybe_switch_block).await;
+            }
+            other => panic!("unexpected request: {}", other),
+        }
+    }
+
+    async fn expect_query(
+        reactor: &MockReactor,
+        expected_state_root_hash: Digest,
+        expected_base_key: Key,
+        expected_path: Vec<String>,
+        value: casper_types::StoredValue,
+        proofs: Vec<TrieMerkleProof<Key, casper_types::StoredValue>>,
+    ) {
+        match reactor.expect_rpc_request().await {
+            RpcRequest::QueryGlobalState {
+                state_root_hash,
+                base_key,
+                path,
+                responder,
+            } => {
+                assert_eq!(state_root_hash, expected_state_root_hash);
+                assert_eq!(base_key, expected_base_key);
+                assert_eq!(path, expected_path);
+                let value = Box::new(value);
+                responder
+                    .respond(Ok(QueryResult::Success { value, proofs }))
+                    .await;
+            }
+            other => panic!("unexpected request: {}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_get_era_validators_with_proof_and_signatures() {
+        let mut rng = TestRng::new();
+        let reactor = MockReactor::new();
+
+        let validator_keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random(&mut rng)).collect();
+        let validator_weights: BTreeMap<PublicKey, U512> = validator_keys
+            .iter()
+            .map(|secret_key| (PublicKey::from(secret_key), U512::from(rng.gen::<u64>())))
+            .collect();
+        let switch_block = Block::random_with_specifics_and_parent_and_validator_weights(
+            &mut rng,
+            SWITCH_BLOCK_ERA,
+            40,
+            ProtocolVersion::V1_0_0,
+            true,
+            None,
+            None,
+            validator_weights.clone(),
+        );
+        let mut block_signatures = BlockSignatures::new(*switch_block.hash(), SWITCH_BLOCK_ERA);
+        for secret_key in &validator_keys {
+            let signature = FinalitySignature::create(
+                *switch_block.hash(),
+                SWITCH_BLOCK_ERA,
+                secret_key,
+                PublicKey::from(secret_key),
+            );
+            block_signatures.insert_proof(signature.public_key, signature.signature);
+        }
+
+        let auction_hash = ContractHash::new(rng.gen());
+        let mut registry = SystemContractRegistry::new();
+        registry.insert(AUCTION.to_string(), auction_hash);
+        let registry = casper_types::StoredValue::CLValue(CLValue::from_t(registry).unwrap());
+        let snapshot = casper_types::StoredValue::CLValue(
+            CLValue::from_t(SeigniorageRecipientsSnapshot::new()).unwrap(),
+        );
+        let snapshot_proofs = vec![TrieMerkleProof::new(
+            Key::from(auction_hash),
+            snapshot.clone(),
+            VecDeque::new(),
+        )];
+
+        let handler = get_era_validators(&reactor, SWITCH_BLOCK_ERA.successor());
+        expect_switch_block_request(
+            &reactor,
+            Some(BlockWithMetadata {
+                block: switch_block.clone(),
+                block_signatures,
+            }),
+        )
+        .await;
+        // The snapshot is proven from the switch block's state root, starting at the auction.
+        let state_root_hash = *switch_block.state_root_hash();
+        expect_query(
+            &reactor,
+            state_root_hash,
+            Key::SystemContractRegistry,
+            vec![],
+            registry,
+            vec![],
+        )
+        .await;
+        expect_query(
+            &reactor,
+            state_root_hash,
+            Key::from(auction_hash),
+            vec![SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY.to_string()],
+            snapshot.clone(),
+            snapshot_proofs.clone(),
+        )
+        .await;
+
+        let era_validators = handler
+            .await
+            .unwrap()
+            .expect("should get era validators")
+            .era_validators;
+        assert_eq!(era_validators.era_id, SWITCH_BLOCK_ERA.successor());
+        let expected_weights: Vec<_> = validator_weights
+            .iter()
+            .map(|(public_key, weight)| JsonValidatorWeights::new(public_key.clone(), *weight))
+            .collect();
+        assert_eq!(era_validators.validator_weights, expected_weights);
+        assert_eq!(era_validators.switch_block_hash, *switch_block.hash());
+        assert_eq!(
+            era_validators.switch_block_header,
+            JsonBlockHeader::from(switch_block.header().clone())
+        );
+        assert_eq!(
+            era_validators.seigniorage_recipients_snapshot,
+            StoredValue::try_from(snapshot).unwrap()
+        );
+        assert_eq!(
+            era_validators.merkle_proof,
+            base16::encode_lower(&snapshot_proofs.to_bytes().unwrap())
+        );
+
+        // Every validator's signature of the switch block is included.
+        assert_eq!(
+            era_validators.finality_signatures.len(),
+            validator_keys.len()
+        );
+        for proof in era_validators.finality_signatures {
+            let (public_key, signature): (PublicKey, Signature) = proof.into();
+            assert!(validator_weights.contains_key(&public_key));
+            FinalitySignature::new(
+                *switch_block.hash(),
+                SWITCH_BLOCK_ERA,
+                signature,
+                public_key,
+            )
+            .is_verified()
+            .expect("should be a valid signature of the switch block");
+        }
+    }
+
+    #[tokio::test]
+    async fn should_fail_to_get_era_validators_without_switch_block() {
+        let reactor = MockReactor::new();
+        let era_id = SWITCH_BLOCK_ERA.successor();
+        let available_block_range = AvailableBlockRange::new(50, 100);
+
+        let handler = get_era_validators(&reactor, era_id);
+        expect_switch_block_request(&reactor, None).await;
+        match reactor.expect_rpc_request().await {
+            RpcRequest::GetAvailableBlockRange { responder } => {
+                responder.respond(available_block_range).await
+            }
+            other => panic!("unexpected request: {}", other),
+        }
+
+        let error = handler
+            .await
+            .unwrap()
+            .expect_err("should fail without switch block");
+        let error_data = ErrorData::MissingBlockOrStateRoot {
+            message: format!("switch block preceding {} not stored on this node", era_id),
+            available_block_range,
+        };
+        assert_eq!(error, Error::new(ErrorCode::NoSuchBlock, error_data));
+    }
+}
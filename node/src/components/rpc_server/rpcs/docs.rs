@@ -17,11 +17,17 @@ use casper_types::ProtocolVersion;
 
 use super::{
     account::PutDeploy,
-    chain::{GetBlock, GetBlockTransfers, GetEraInfoBySwitchBlock, GetStateRootHash},
-    info::{GetChainspec, GetDeploy, GetPeers, GetStatus, GetValidatorChanges},
+    chain::{
+        GetBlock, GetBlockTransfers, GetBlocksByProposer, GetEraInfoBySwitchBlock,
+        GetEraValidators, GetStateRootHash, GetStateRootHashRange,
+    },
+    info::{
+        GetChainspec, GetDeploy, GetDeploysByTag, GetExecutionResultProof, GetPeers, GetStatus,
+        GetValidatorChanges,
+    },
     state::{
-        GetAccountInfo, GetAuctionInfo, GetBalance, GetDictionaryItem, GetItem, QueryBalance,
-        QueryGlobalState,
+        GetAccountInfo, GetAuctionInfo, GetBalance, GetBidStatus, GetDictionaryItem, GetItem,
+        GetKeyHistory, GetUnbonds, QueryBalance, QueryGlobalState,
     },
     Error, ReactorEventT, RpcWithOptionalParams, RpcWithParams, RpcWithoutParams,
 };
@@ -68,6 +74,9 @@ pub(crate) static OPEN_RPC_SCHEMA: Lazy<OpenRpcSchema> = Lazy::new(|| {
 
     schema.push_with_params::<PutDeploy>("receives a Deploy to be executed by the network");
     schema.push_with_params::<GetDeploy>("returns a Deploy from the network");
+    schema.push_with_params::<GetDeploysByTag>(
+        "returns the hashes of the Deploys stored by the node which carry a given user tag",
+    );
     schema.push_with_params::<GetAccountInfo>("returns an Account from the network");
     schema.push_with_params::<GetDictionaryItem>("returns an item from a Dictionary");
     schema.push_with_params::<QueryGlobalState>(
@@ -91,6 +100,13 @@ pub(crate) static OPEN_RPC_SCHEMA: Lazy<OpenRpcSchema> = Lazy::new(|| {
     schema.push_with_optional_params::<GetStateRootHash>(
         "returns a state root hash at a given Block",
     );
+    schema.push_with_params::<GetBlocksByProposer>(
+        "returns the hashes and heights of the blocks created by a validator in an era or a range \
+        of heights",
+    );
+    schema.push_with_params::<GetStateRootHashRange>(
+        "returns the state root hashes of the blocks in a range of heights",
+    );
     schema.push_with_params::<GetItem>(
         "returns a stored value from the network. This RPC is deprecated, use \
         `query_global_state` instead.",
@@ -99,10 +115,27 @@ pub(crate) static OPEN_RPC_SCHEMA: Lazy<OpenRpcSchema> = Lazy::new(|| {
     schema.push_with_optional_params::<GetEraInfoBySwitchBlock>(
         "returns an EraInfo from the network",
     );
+    schema.push_with_params::<GetEraValidators>(
+        "returns the validators of an era with the switch block and Merkle proof attesting to them",
+    );
     schema.push_with_optional_params::<GetAuctionInfo>(
         "returns the bids and validators as of either a specific block (by height or hash), or \
         the most recently added block",
     );
+    schema.push_with_params::<GetBidStatus>(
+        "returns whether a validator's bid is inactive and whether it can be reactivated",
+    );
+    schema.push_with_params::<GetUnbonds>(
+        "returns the pending unbonds, optionally of a single validator or delegator, along with \
+        the eras in which their funds are released",
+    );
+    schema.push_with_params::<GetExecutionResultProof>(
+        "returns a deploy's execution result along with a proof that it is part of the block it \
+        was executed in",
+    );
+    schema.push_with_params::<GetKeyHistory>(
+        "returns the most recent blocks which wrote to a key in global state",
+    );
 
     schema
 });
@@ -35,6 +35,14 @@ pub enum ErrorCode {
     FailedToGetTrie = -32011,
     /// The requested state root hash was not found.
     NoSuchStateRoot = -32012,
+    /// The requested execution result was not found.
+    NoSuchExecutionResult = -32013,
+    /// Failed to get a proof of the requested execution result.
+    FailedToGetExecutionResultProof = -32014,
+    /// The read-only contract entry point call failed.
+    ViewCallFailed = -32015,
+    /// The node doesn't maintain the key history index.
+    KeyHistoryUnavailable = -32016,
 }
 
 impl From<ErrorCode> for (i64, &'static str) {
@@ -59,6 +67,12 @@ impl From<ErrorCode> for (i64, &'static str) {
             }
             ErrorCode::FailedToGetTrie => (error_code as i64, "Failed to get trie"),
             ErrorCode::NoSuchStateRoot => (error_code as i64, "No such state root"),
+            ErrorCode::NoSuchExecutionResult => (error_code as i64, "No such execution result"),
+            ErrorCode::FailedToGetExecutionResultProof => {
+                (error_code as i64, "Failed to get execution result proof")
+            }
+            ErrorCode::ViewCallFailed => (error_code as i64, "View call failed"),
+            ErrorCode::KeyHistoryUnavailable => (error_code as i64, "Key history unavailable"),
         }
     }
 }
@@ -11,14 +11,21 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
-use casper_types::{EraId, ExecutionResult, ProtocolVersion, PublicKey};
+use casper_execution_engine::core::engine_state::Error as EngineStateError;
+use casper_json_rpc::ReservedErrorCode;
+use casper_types::{
+    bytesrepr::{Bytes, ToBytes},
+    EraId, ExecutionResult, ProtocolVersion, PublicKey,
+};
 
 use super::{
+    chain::BlockIdentifier,
+    common::{self, MERKLE_PROOF},
     docs::{DocExample, DOCS_EXAMPLE_PROTOCOL_VERSION},
     Error, ErrorCode, ReactorEventT, RpcRequest, RpcWithParams, RpcWithoutParams,
 };
 use crate::{
-    components::consensus::ValidatorChange,
+    components::{consensus::ValidatorChange, storage::MAX_DEPLOYS_BY_TAG},
     effect::EffectBuilder,
     reactor::QueueKind,
     types::{
@@ -27,6 +34,9 @@ use crate::{
     },
 };
 
+/// The number of peers asked in turn for the execution result of a deploy which isn't held.
+const BACKFILL_EXECUTION_RESULT_PEER_COUNT: usize = 3;
+
 static GET_DEPLOY_PARAMS: Lazy<GetDeployParams> = Lazy::new(|| GetDeployParams {
     deploy_hash: *Deploy::doc_example().hash(),
     finalized_approvals: true,
@@ -40,6 +50,28 @@ static GET_DEPLOY_RESULT: Lazy<GetDeployResult> = Lazy::new(|| GetDeployResult {
     }],
     block_hash_and_height: None,
 });
+static GET_DEPLOYS_BY_TAG_PARAMS: Lazy<GetDeploysByTagParams> =
+    Lazy::new(|| GetDeploysByTagParams {
+        user_tag: Bytes::from(b"deposit-0042".to_vec()),
+        after: None,
+        limit: Some(100),
+    });
+static GET_DEPLOYS_BY_TAG_RESULT: Lazy<GetDeploysByTagResult> =
+    Lazy::new(|| GetDeploysByTagResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        deploy_hashes: vec![*Deploy::doc_example().hash()],
+    });
+static GET_EXECUTION_RESULT_PROOF_PARAMS: Lazy<GetExecutionResultProofParams> =
+    Lazy::new(|| GetExecutionResultProofParams {
+        deploy_hash: *Deploy::doc_example().hash(),
+    });
+static GET_EXECUTION_RESULT_PROOF_RESULT: Lazy<GetExecutionResultProofResult> =
+    Lazy::new(|| GetExecutionResultProofResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        block_hash: *Block::doc_example().hash(),
+        execution_result: ExecutionResult::example().clone(),
+        merkle_proof: MERKLE_PROOF.clone(),
+    });
 static GET_PEERS_RESULT: Lazy<GetPeersResult> = Lazy::new(|| GetPeersResult {
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     peers: GetStatusResult::doc_example().peers.clone(),
@@ -162,6 +194,13 @@ impl RpcWithParams for GetDeploy {
                 None,
             ),
             DeployMetadataExt::BlockInfo(block_hash_and_height) => {
+                // The execution result was pruned or skipped while syncing.  Rather than holding
+                // up the response, backfill it in the background for later requests to return.
+                tokio::spawn(effect_builder.backfill_deploy_execution_result(
+                    block_hash_and_height.block_hash,
+                    params.deploy_hash,
+                    BACKFILL_EXECUTION_RESULT_PEER_COUNT,
+                ));
                 (Vec::new(), Some(block_hash_and_height))
             }
             DeployMetadataExt::Empty => (Vec::new(), None),
@@ -177,6 +216,247 @@ impl RpcWithParams for GetDeploy {
     }
 }
 
+/// Params for "info_get_deploys_by_tag" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDeploysByTagParams {
+    /// The hex-encoded user tag carried in the headers of the deploys.
+    #[schemars(with = "String")]
+    pub user_tag: Bytes,
+    /// Only deploys with hashes greater than this one are returned, if given.  Pass the last hash
+    /// of the previous response to get the next page.
+    pub after: Option<DeployHash>,
+    /// The maximum number of deploy hashes to return.  Defaults to, and is capped at, 1000.
+    pub limit: Option<u32>,
+}
+
+impl DocExample for GetDeploysByTagParams {
+    fn doc_example() -> &'static Self {
+        &*GET_DEPLOYS_BY_TAG_PARAMS
+    }
+}
+
+/// Result for "info_get_deploys_by_tag" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetDeploysByTagResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The hashes of the deploys carrying the tag, ordered by deploy hash.
+    pub deploy_hashes: Vec<DeployHash>,
+}
+
+impl DocExample for GetDeploysByTagResult {
+    fn doc_example() -> &'static Self {
+        &*GET_DEPLOYS_BY_TAG_RESULT
+    }
+}
+
+/// "info_get_deploys_by_tag" RPC.
+pub struct GetDeploysByTag {}
+
+#[async_trait]
+impl RpcWithParams for GetDeploysByTag {
+    const METHOD: &'static str = "info_get_deploys_by_tag";
+    type RequestParams = GetDeploysByTagParams;
+    type ResponseResult = GetDeploysByTagResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let deploy_hashes = effect_builder
+            .make_request(
+                |responder| RpcRequest::GetDeploysByTag {
+                    user_tag: params.user_tag,
+                    after: params.after,
+                    limit: params.limit.map_or(MAX_DEPLOYS_BY_TAG, |limit| {
+                        MAX_DEPLOYS_BY_TAG.min(limit as usize)
+                    }),
+                    responder,
+                },
+                QueueKind::Api,
+            )
+            .await;
+
+        Ok(Self::ResponseResult {
+            api_version,
+            deploy_hashes,
+        })
+    }
+}
+
+/// Params for "info_get_execution_result_proof" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetExecutionResultProofParams {
+    /// The deploy hash.
+    pub deploy_hash: DeployHash,
+}
+
+impl DocExample for GetExecutionResultProofParams {
+    fn doc_example() -> &'static Self {
+        &*GET_EXECUTION_RESULT_PROOF_PARAMS
+    }
+}
+
+/// Result for "info_get_execution_result_proof" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetExecutionResultProofResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The hash of the block in which the deploy was executed.
+    pub block_hash: BlockHash,
+    /// The execution result of the deploy.
+    pub execution_result: ExecutionResult,
+    /// The proof that the execution result is part of the block, checkable against the block's
+    /// state root hash.
+    pub merkle_proof: String,
+}
+
+impl DocExample for GetExecutionResultProofResult {
+    fn doc_example() -> &'static Self {
+        &*GET_EXECUTION_RESULT_PROOF_RESULT
+    }
+}
+
+/// "info_get_execution_result_proof" RPC.
+pub struct GetExecutionResultProof {}
+
+#[async_trait]
+impl RpcWithParams for GetExecutionResultProof {
+    const METHOD: &'static str = "info_get_execution_result_proof";
+    type RequestParams = GetExecutionResultProofParams;
+    type ResponseResult = GetExecutionResultProofResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let deploy_hash = params.deploy_hash;
+        let maybe_deploy_and_metadata = effect_builder
+            .make_request(
+                |responder| RpcRequest::GetDeploy {
+                    hash: deploy_hash,
+                    finalized_approvals: false,
+                    responder,
+                },
+                QueueKind::Api,
+            )
+            .await;
+
+        let block_hash = match maybe_deploy_and_metadata {
+            Some((_, DeployMetadataExt::Metadata(metadata))) => {
+                match metadata.execution_results.into_keys().next() {
+                    Some(block_hash) => block_hash,
+                    None => {
+                        let message = format!("{} has not been executed", deploy_hash);
+                        info!("{}", message);
+                        return Err(Error::new(ErrorCode::NoSuchExecutionResult, message));
+                    }
+                }
+            }
+            Some(_) => {
+                let message = format!("execution results of {} are not held", deploy_hash);
+                info!("{}", message);
+                return Err(Error::new(ErrorCode::NoSuchExecutionResult, message));
+            }
+            None => {
+                let message = format!("failed to get {} and metadata from storage", deploy_hash);
+                info!("{}", message);
+                return Err(Error::new(ErrorCode::NoSuchDeploy, message));
+            }
+        };
+
+        // This RPC request is restricted by the block availability index.
+        let only_from_available_block_range = true;
+        let block = common::get_block(
+            Some(BlockIdentifier::Hash(block_hash)),
+            only_from_available_block_range,
+            effect_builder,
+        )
+        .await?;
+
+        let execution_results: Vec<_> = match effect_builder
+            .get_execution_results_from_storage(block_hash)
+            .await
+        {
+            Some(execution_results) => execution_results
+                .into_iter()
+                .map(|(hash, _, execution_result)| (hash, execution_result))
+                .collect(),
+            None => {
+                let message = format!("execution results of {} are not held", block_hash);
+                info!("{}", message);
+                return Err(Error::new(ErrorCode::NoSuchExecutionResult, message));
+            }
+        };
+        let index = match execution_results
+            .iter()
+            .position(|(hash, _)| *hash == deploy_hash)
+        {
+            Some(index) => index,
+            None => {
+                let message = format!("{} is not part of {}", deploy_hash, block_hash);
+                info!("{}", message);
+                return Err(Error::new(ErrorCode::NoSuchExecutionResult, message));
+            }
+        };
+        let execution_result = execution_results[index].1.clone();
+
+        let proof = effect_builder
+            .get_execution_result_proof(
+                *block.header().state_root_hash(),
+                execution_results
+                    .into_iter()
+                    .map(|(_, execution_result)| execution_result)
+                    .collect(),
+                index as u64,
+            )
+            .await
+            .map_err(|error| match error {
+                EngineStateError::RootNotFound(_) => {
+                    Error::new(ErrorCode::NoSuchStateRoot, error.to_string())
+                }
+                EngineStateError::MissingExecutionResultsMerkleRoot => Error::new(
+                    ErrorCode::FailedToGetExecutionResultProof,
+                    format!(
+                        "{} was executed before execution result proofs were enabled",
+                        block_hash
+                    ),
+                ),
+                _ => Error::new(
+                    ErrorCode::FailedToGetExecutionResultProof,
+                    error.to_string(),
+                ),
+            })?;
+
+        let merkle_proof = match proof.to_bytes() {
+            Ok(bytes) => base16::encode_lower(&bytes),
+            Err(error) => {
+                info!(?error, "failed to encode execution result proof");
+                return Err(Error::new(
+                    ReservedErrorCode::InternalError,
+                    format!("failed to encode execution result proof: {}", error),
+                ));
+            }
+        };
+
+        let result = Self::ResponseResult {
+            api_version,
+            block_hash,
+            execution_result,
+            merkle_proof,
+        };
+        Ok(result)
+    }
+}
+
 /// Result for "info_get_peers" RPC response.
 #[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
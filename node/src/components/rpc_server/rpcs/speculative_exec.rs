@@ -156,6 +156,8 @@ impl RpcWithParams for SpeculativeExec {
                     | EngineStateError::FailedToGetWithdrawKeys
                     | EngineStateError::FailedToGetStoredWithdraws
                     | EngineStateError::FailedToGetWithdrawPurses
+                    | EngineStateError::FailedToGetUnbondingPurses
+                    | EngineStateError::WithdrawMigrationMismatch { .. }
                     | EngineStateError::FailedToRetrieveUnbondingDelay
                     | EngineStateError::FailedToRetrieveEraId => {
                         Error::new(ReservedErrorCode::InternalError, &format!("{}", error))
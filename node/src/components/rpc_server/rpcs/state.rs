@@ -12,7 +12,9 @@ use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
 
 use casper_execution_engine::{
-    core::engine_state::{BalanceResult, GetBidsResult, QueryResult},
+    core::engine_state::{
+        self, BalanceResult, GetBidsResult, GetUnbondsRequest, QueryResult, ViewRequest, ViewResult,
+    },
     storage::trie::merkle_proof::TrieMerkleProof,
 };
 use casper_hashing::Digest;
@@ -20,8 +22,9 @@ use casper_json_rpc::ReservedErrorCode;
 use casper_types::{
     account::AccountHash,
     bytesrepr::{Bytes, ToBytes},
-    CLValue, Key, ProtocolVersion, PublicKey, SecretKey, StoredValue as DomainStoredValue, URef,
-    U512,
+    system::auction::UnbondingPurse,
+    AccessRights, BlockTime, CLValue, ContractHash, EraId, Gas, Key, ProtocolVersion, PublicKey,
+    RuntimeArgs, SecretKey, StoredValue as DomainStoredValue, URef, U512,
 };
 
 use crate::{
@@ -35,7 +38,7 @@ use crate::{
     },
     types::{
         json_compatibility::{Account as JsonAccount, AuctionState, StoredValue},
-        Block, BlockHash, JsonBlockHeader,
+        Block, BlockHash, JsonBlockHeader, KeyWrite,
     },
 };
 
@@ -79,6 +82,45 @@ static GET_ACCOUNT_INFO_RESULT: Lazy<GetAccountInfoResult> = Lazy::new(|| GetAcc
     account: JsonAccount::doc_example().clone(),
     merkle_proof: MERKLE_PROOF.clone(),
 });
+static GET_BID_STATUS_PARAMS: Lazy<GetBidStatusParams> = Lazy::new(|| {
+    let secret_key = SecretKey::ed25519_from_bytes([0; 32]).unwrap();
+    let public_key = PublicKey::from(&secret_key);
+    GetBidStatusParams {
+        public_key,
+        block_identifier: Some(BlockIdentifier::Hash(*Block::doc_example().hash())),
+    }
+});
+static GET_BID_STATUS_RESULT: Lazy<GetBidStatusResult> = Lazy::new(|| GetBidStatusResult {
+    api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+    inactive: true,
+    staked_amount: U512::from(123_456),
+    can_be_activated: true,
+    merkle_proof: MERKLE_PROOF.clone(),
+});
+static GET_UNBONDS_PARAMS: Lazy<GetUnbondsParams> = Lazy::new(|| {
+    let secret_key = SecretKey::ed25519_from_bytes([0; 32]).unwrap();
+    let public_key = PublicKey::from(&secret_key);
+    GetUnbondsParams {
+        block_identifier: Some(BlockIdentifier::Hash(*Block::doc_example().hash())),
+        public_key: Some(public_key),
+    }
+});
+static GET_UNBONDS_RESULT: Lazy<GetUnbondsResult> = Lazy::new(|| {
+    let secret_key = SecretKey::ed25519_from_bytes([0; 32]).unwrap();
+    let public_key = PublicKey::from(&secret_key);
+    let unbonding_purse = UnbondingPurse::new(
+        URef::new([250; 32], AccessRights::READ_ADD_WRITE),
+        public_key.clone(),
+        public_key,
+        EraId::new(10),
+        U512::from(123_456),
+        None,
+    );
+    GetUnbondsResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        unbonds: vec![PendingUnbond::new(unbonding_purse, 7, 7)],
+    }
+});
 static GET_DICTIONARY_ITEM_PARAMS: Lazy<GetDictionaryItemParams> =
     Lazy::new(|| GetDictionaryItemParams {
         state_root_hash: *Block::doc_example().header().state_root_hash(),
@@ -127,6 +169,37 @@ static QUERY_BALANCE_RESULT: Lazy<QueryBalanceResult> = Lazy::new(|| QueryBalanc
     api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
     balance: U512::from(123_456),
 });
+static QUERY_CONTRACT_VIEW_PARAMS: Lazy<QueryContractViewParams> =
+    Lazy::new(|| QueryContractViewParams {
+        block_identifier: Some(BlockIdentifier::Hash(*Block::doc_example().hash())),
+        caller: AccountHash::new([9u8; 32]),
+        contract_hash: ContractHash::new([12u8; 32]),
+        entry_point: "counter_get".to_string(),
+        args: RuntimeArgs::new(),
+        gas_limit: Some(1_000_000_000),
+    });
+static QUERY_CONTRACT_VIEW_RESULT: Lazy<QueryContractViewResult> =
+    Lazy::new(|| QueryContractViewResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        block_hash: *Block::doc_example().hash(),
+        value: CLValue::from_t(1i32).unwrap(),
+        cost: U512::from(123_456),
+    });
+static GET_KEY_HISTORY_PARAMS: Lazy<GetKeyHistoryParams> = Lazy::new(|| GetKeyHistoryParams {
+    key: "deploy-af684263911154d26fa05be9963171802801a0b6aff8f199b7391eacb8edc9e1".to_string(),
+    before_height: Some(11),
+});
+static GET_KEY_HISTORY_RESULT: Lazy<GetKeyHistoryResult> = Lazy::new(|| {
+    let block = Block::doc_example();
+    GetKeyHistoryResult {
+        api_version: DOCS_EXAMPLE_PROTOCOL_VERSION,
+        writes: vec![KeyWrite {
+            block_hash: *block.hash(),
+            block_height: block.height(),
+            state_root_hash: *block.state_root_hash(),
+        }],
+    }
+});
 
 /// Params for "state_get_item" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -552,6 +625,262 @@ impl RpcWithParams for GetAccountInfo {
     }
 }
 
+/// Params for "state_get_bid_status" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBidStatusParams {
+    /// The public key of the validator.
+    pub public_key: PublicKey,
+    /// The block identifier.
+    pub block_identifier: Option<BlockIdentifier>,
+}
+
+impl DocExample for GetBidStatusParams {
+    fn doc_example() -> &'static Self {
+        &*GET_BID_STATUS_PARAMS
+    }
+}
+
+/// Result for "state_get_bid_status" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetBidStatusResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// Whether the bid is inactive, e.g. after a liveness fault of the validator.
+    pub inactive: bool,
+    /// The amount staked by the validator, excluding delegations.
+    pub staked_amount: U512,
+    /// Whether the bid can be reactivated by an `activate_bid` call to the auction contract.
+    pub can_be_activated: bool,
+    /// The Merkle proof.
+    pub merkle_proof: String,
+}
+
+impl DocExample for GetBidStatusResult {
+    fn doc_example() -> &'static Self {
+        &*GET_BID_STATUS_RESULT
+    }
+}
+
+/// "state_get_bid_status" RPC.
+pub struct GetBidStatus {}
+
+#[async_trait]
+impl RpcWithParams for GetBidStatus {
+    const METHOD: &'static str = "state_get_bid_status";
+    type RequestParams = GetBidStatusParams;
+    type ResponseResult = GetBidStatusResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        // This RPC request is restricted by the block availability index.
+        let only_from_available_block_range = true;
+
+        let block = common::get_block(
+            params.block_identifier,
+            only_from_available_block_range,
+            effect_builder,
+        )
+        .await?;
+
+        let state_root_hash = *block.header().state_root_hash();
+        let base_key = Key::Bid(params.public_key.to_account_hash());
+        let (stored_value, merkle_proof) =
+            common::run_query_and_encode(effect_builder, state_root_hash, base_key, vec![]).await?;
+
+        let bid = if let StoredValue::Bid(bid) = stored_value {
+            bid
+        } else {
+            let error_msg = format!("stored value is not a bid for {}", base_key);
+            info!(?stored_value, "{}", error_msg);
+            return Err(Error::new(ErrorCode::QueryFailed, error_msg));
+        };
+
+        let inactive = bid.inactive();
+        let staked_amount = *bid.staked_amount();
+        let result = Self::ResponseResult {
+            api_version,
+            inactive,
+            staked_amount,
+            can_be_activated: inactive && !staked_amount.is_zero(),
+            merkle_proof,
+        };
+
+        Ok(result)
+    }
+}
+
+/// Params for "state_get_unbonds" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetUnbondsParams {
+    /// The block identifier.
+    pub block_identifier: Option<BlockIdentifier>,
+    /// If given, only unbonds from the validator or by the delegator with this public key are
+    /// returned.
+    pub public_key: Option<PublicKey>,
+}
+
+impl DocExample for GetUnbondsParams {
+    fn doc_example() -> &'static Self {
+        &*GET_UNBONDS_PARAMS
+    }
+}
+
+/// An unbond awaiting payout.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PendingUnbond {
+    /// The validator the unbonded funds were staked with.
+    pub validator_public_key: PublicKey,
+    /// The unbonder, i.e. the validator itself or one of its delegators.
+    pub unbonder_public_key: PublicKey,
+    /// The purse the funds are released to.
+    pub bonding_purse: URef,
+    /// The amount being unbonded.
+    pub amount: U512,
+    /// The era in which the unbond was requested.
+    pub era_of_creation: EraId,
+    /// The era at the end of which the funds are released.
+    pub release_era: EraId,
+    /// The validator the funds are redelegated to instead of being released, if any.
+    pub new_validator: Option<PublicKey>,
+}
+
+impl PendingUnbond {
+    /// Creates the pending unbond of `unbonding_purse`, released `unbonding_delay` eras after its
+    /// creation, or `redelegation_delay` eras if it is redelegated.
+    fn new(unbonding_purse: UnbondingPurse, unbonding_delay: u64, redelegation_delay: u64) -> Self {
+        let delay = if unbonding_purse.new_validator().is_some() {
+            redelegation_delay
+        } else {
+            unbonding_delay
+        };
+        PendingUnbond {
+            validator_public_key: unbonding_purse.validator_public_key().clone(),
+            unbonder_public_key: unbonding_purse.unbonder_public_key().clone(),
+            bonding_purse: *unbonding_purse.bonding_purse(),
+            amount: *unbonding_purse.amount(),
+            era_of_creation: unbonding_purse.era_of_creation(),
+            release_era: unbonding_purse.era_of_creation().saturating_add(delay),
+            new_validator: unbonding_purse.new_validator().clone(),
+        }
+    }
+}
+
+/// Result for "state_get_unbonds" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetUnbondsResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The pending unbonds, in order of the era their funds are released.
+    pub unbonds: Vec<PendingUnbond>,
+}
+
+impl DocExample for GetUnbondsResult {
+    fn doc_example() -> &'static Self {
+        &*GET_UNBONDS_RESULT
+    }
+}
+
+/// "state_get_unbonds" RPC.
+pub struct GetUnbonds {}
+
+#[async_trait]
+impl RpcWithParams for GetUnbonds {
+    const METHOD: &'static str = "state_get_unbonds";
+    type RequestParams = GetUnbondsParams;
+    type ResponseResult = GetUnbondsResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        // This RPC request is restricted by the block availability index.
+        let only_from_available_block_range = true;
+
+        let block = common::get_block(
+            params.block_identifier,
+            only_from_available_block_range,
+            effect_builder,
+        )
+        .await?;
+
+        let state_root_hash = *block.header().state_root_hash();
+        let get_unbonds_result = effect_builder
+            .get_unbonds(GetUnbondsRequest::new(
+                state_root_hash,
+                block.header().protocol_version(),
+            ))
+            .await;
+
+        let (unbonding_purses, unbonding_delay, redelegation_delay) = match get_unbonds_result {
+            Ok(engine_state::GetUnbondsResult::Success {
+                unbonding_purses,
+                unbonding_delay,
+                redelegation_delay,
+            }) => (unbonding_purses, unbonding_delay, redelegation_delay),
+            Ok(engine_state::GetUnbondsResult::RootNotFound) => {
+                error!(
+                    block_hash=?block.hash(),
+                    ?state_root_hash,
+                    "root not found while trying to get unbonds"
+                );
+                return Err(Error::new(
+                    ReservedErrorCode::InternalError,
+                    format!(
+                        "root not found when getting unbonds at block {:?}",
+                        block.hash().inner()
+                    ),
+                ));
+            }
+            Err(error) => {
+                error!(
+                    block_hash=?block.hash(),
+                    ?state_root_hash,
+                    ?error,
+                    "failed to get unbonds"
+                );
+                return Err(Error::new(
+                    ReservedErrorCode::InternalError,
+                    format!(
+                        "error getting unbonds at block {:?}: {}",
+                        block.hash().inner(),
+                        error
+                    ),
+                ));
+            }
+        };
+
+        let mut unbonds: Vec<PendingUnbond> = unbonding_purses
+            .into_iter()
+            .flat_map(|(_, purses)| purses)
+            .filter(|purse| match &params.public_key {
+                Some(public_key) => {
+                    purse.validator_public_key() == public_key
+                        || purse.unbonder_public_key() == public_key
+                }
+                None => true,
+            })
+            .map(|purse| PendingUnbond::new(purse, unbonding_delay, redelegation_delay))
+            .collect();
+        unbonds.sort_by_key(|unbond| unbond.release_era);
+
+        Ok(Self::ResponseResult {
+            api_version,
+            unbonds,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, JsonSchema, Clone)]
 /// Options for dictionary item lookups.
 pub enum DictionaryIdentifier {
@@ -992,6 +1321,124 @@ impl RpcWithParams for QueryBalance {
     }
 }
 
+/// Params for "query_contract_view" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QueryContractViewParams {
+    /// The block on top of whose global state to call the entry point.  Defaults to the highest
+    /// complete block.
+    pub block_identifier: Option<BlockIdentifier>,
+    /// The account on whose behalf the entry point is called.
+    pub caller: AccountHash,
+    /// The contract to call.
+    pub contract_hash: ContractHash,
+    /// The name of the entry point to call.
+    pub entry_point: String,
+    /// The arguments to pass to the entry point.
+    #[serde(default)]
+    pub args: RuntimeArgs,
+    /// The gas the call may use.  Defaults to, and is capped at, the node's configured maximum.
+    pub gas_limit: Option<u64>,
+}
+
+impl DocExample for QueryContractViewParams {
+    fn doc_example() -> &'static Self {
+        &*QUERY_CONTRACT_VIEW_PARAMS
+    }
+}
+
+/// Result for "query_contract_view" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QueryContractViewResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// Hash of the block on top of which the entry point was called.
+    pub block_hash: BlockHash,
+    /// The value returned by the entry point.
+    pub value: CLValue,
+    /// The gas used by the call.
+    pub cost: U512,
+}
+
+impl DocExample for QueryContractViewResult {
+    fn doc_example() -> &'static Self {
+        &*QUERY_CONTRACT_VIEW_RESULT
+    }
+}
+
+/// "query_contract_view" RPC.
+///
+/// Calls a contract's entry point in read-only mode, without a deploy or payment.  The call fails
+/// if the entry point writes to global state.  As it executes Wasm, it is only served by the
+/// speculative execution server.
+pub struct QueryContractView {}
+
+#[async_trait]
+impl RpcWithParams for QueryContractView {
+    const METHOD: &'static str = "query_contract_view";
+    type RequestParams = QueryContractViewParams;
+    type ResponseResult = QueryContractViewResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        // This RPC request is restricted by the block availability index.
+        let only_from_available_block_range = true;
+        let block = common::get_block(
+            params.block_identifier,
+            only_from_available_block_range,
+            effect_builder,
+        )
+        .await?;
+        let block_hash = *block.hash();
+        let header = block.header();
+
+        // The contract runtime lowers the gas limit to its configured maximum.
+        let gas_limit = Gas::from(params.gas_limit.unwrap_or(u64::MAX));
+        let view_request = ViewRequest::new(
+            *header.state_root_hash(),
+            header.protocol_version(),
+            BlockTime::new(header.timestamp().millis()),
+            params.caller,
+            params.contract_hash,
+            params.entry_point,
+            params.args,
+            gas_limit,
+        );
+
+        let (value, cost) = match effect_builder.call_contract_view(view_request).await {
+            Ok(ViewResult::Success { value, cost }) => (value, cost),
+            Ok(ViewResult::RootNotFound) => {
+                let message = format!("state root of {} not found", block_hash);
+                info!("{}", message);
+                return Err(Error::new(ErrorCode::NoSuchStateRoot, message));
+            }
+            Ok(ViewResult::Failure { error, cost }) => {
+                let message = format!("view call failed after using {} gas: {}", cost, error);
+                info!("{}", message);
+                return Err(Error::new(ErrorCode::ViewCallFailed, message));
+            }
+            Err(error) => {
+                let message = format!("view call failed to execute: {}", error);
+                info!("{}", message);
+                return Err(Error::new(ReservedErrorCode::InternalError, message));
+            }
+        };
+
+        let result = Self::ResponseResult {
+            api_version,
+            block_hash,
+            value,
+            cost: cost.value(),
+        };
+        Ok(result)
+    }
+}
+
 /// Parameters for "state_get_trie" RPC request.
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 pub struct GetTrieParams {
@@ -1061,6 +1508,85 @@ impl RpcWithParams for GetTrie {
     }
 }
 
+/// Params for "state_get_key_history" RPC request.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetKeyHistoryParams {
+    /// `casper_types::Key` as formatted string.
+    pub key: String,
+    /// If given, only blocks below this height are returned, allowing to page through the
+    /// history.
+    pub before_height: Option<u64>,
+}
+
+impl DocExample for GetKeyHistoryParams {
+    fn doc_example() -> &'static Self {
+        &*GET_KEY_HISTORY_PARAMS
+    }
+}
+
+/// Result for "state_get_key_history" RPC response.
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetKeyHistoryResult {
+    /// The RPC API version.
+    #[schemars(with = "String")]
+    pub api_version: ProtocolVersion,
+    /// The most recent blocks which wrote to the key, newest first.  Only blocks executed by this
+    /// node while its key history index was enabled are included.
+    pub writes: Vec<KeyWrite>,
+}
+
+impl DocExample for GetKeyHistoryResult {
+    fn doc_example() -> &'static Self {
+        &*GET_KEY_HISTORY_RESULT
+    }
+}
+
+/// "state_get_key_history" RPC.
+pub struct GetKeyHistory {}
+
+#[async_trait]
+impl RpcWithParams for GetKeyHistory {
+    const METHOD: &'static str = "state_get_key_history";
+    type RequestParams = GetKeyHistoryParams;
+    type ResponseResult = GetKeyHistoryResult;
+
+    async fn do_handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        api_version: ProtocolVersion,
+        params: Self::RequestParams,
+    ) -> Result<Self::ResponseResult, Error> {
+        let key = Key::from_formatted_str(&params.key).map_err(|error| {
+            let error_msg = format!("failed to parse key: {}", error);
+            info!("{}", error_msg);
+            Error::new(ErrorCode::FailedToParseQueryKey, error_msg)
+        })?;
+
+        let maybe_writes = effect_builder
+            .make_request(
+                |responder| RpcRequest::GetKeyHistory {
+                    key,
+                    before_height: params.before_height,
+                    responder,
+                },
+                QueueKind::Api,
+            )
+            .await;
+
+        match maybe_writes {
+            Some(writes) => Ok(Self::ResponseResult {
+                api_version,
+                writes,
+            }),
+            None => Err(Error::new(
+                ErrorCode::KeyHistoryUnavailable,
+                "the key history index is not enabled on this node",
+            )),
+        }
+    }
+}
+
 type QuerySuccess = (
     DomainStoredValue,
     Vec<TrieMerkleProof<Key, DomainStoredValue>>,
@@ -1184,3 +1710,337 @@ pub(super) async fn get_state_root_hash_and_optional_header<REv: ReactorEventT>(
         GlobalStateIdentifier::StateRootHash(state_root_hash) => Ok((state_root_hash, None)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use casper_types::{system::auction::UnbondingPurses, testing::TestRng};
+
+    use super::*;
+    use crate::{
+        components::rpc_server::rpcs::test_utils::MockReactor,
+        effect::requests::ContractRuntimeRequest,
+        types::{BlockSignatures, BlockWithMetadata},
+    };
+
+    const UNBONDING_DELAY: u64 = 7;
+    /// Redelegations move at the end of the era they're requested in.
+    const REDELEGATION_DELAY: u64 = 0;
+
+    struct UnbondsFixture {
+        validator_1: PublicKey,
+        validator_2: PublicKey,
+        delegator: PublicKey,
+        unbonding_purses: UnbondingPurses,
+    }
+
+    impl UnbondsFixture {
+        fn new(rng: &mut TestRng) -> Self {
+            let validator_1 = PublicKey::random(rng);
+            let validator_2 = PublicKey::random(rng);
+            let delegator = PublicKey::random(rng);
+            let unbonding_purse = |validator: &PublicKey, unbonder: &PublicKey, era_id: u64| {
+                UnbondingPurse::new(
+                    URef::new([era_id as u8; 32], AccessRights::READ_ADD_WRITE),
+                    validator.clone(),
+                    unbonder.clone(),
+                    EraId::new(era_id),
+                    U512::from(era_id * 1_000),
+                    None,
+                )
+            };
+            let mut unbonding_purses = BTreeMap::new();
+            unbonding_purses.insert(
+                validator_1.to_account_hash(),
+                vec![
+                    unbonding_purse(&validator_1, &validator_1, 5),
+                    unbonding_purse(&validator_1, &delegator, 3),
+                ],
+            );
+            unbonding_purses.insert(
+                validator_2.to_account_hash(),
+                vec![unbonding_purse(&validator_2, &validator_2, 4)],
+            );
+            UnbondsFixture {
+                validator_1,
+                validator_2,
+                delegator,
+                unbonding_purses,
+            }
+        }
+
+        /// Runs "state_get_unbonds" against the fixture's unbonding purses.
+        async fn get_unbonds(
+            &self,
+            rng: &mut TestRng,
+            public_key: Option<PublicKey>,
+        ) -> Vec<PendingUnbond> {
+            let reactor = MockReactor::new();
+            let block = Block::random(rng);
+            let params = GetUnbondsParams {
+                block_identifier: Some(BlockIdentifier::Hash(*block.hash())),
+                public_key,
+            };
+            let handler = tokio::spawn(GetUnbonds::do_handle_request(
+                reactor.effect_builder(),
+                ProtocolVersion::V1_0_0,
+                params,
+            ));
+
+            match reactor.expect_rpc_request().await {
+                RpcRequest::GetBlock {
+                    maybe_id,
+                    responder,
+                    ..
+                } => {
+                    assert!(matches!(
+                        maybe_id,
+                        Some(BlockIdentifier::Hash(block_hash)) if block_hash == *block.hash()
+                    ));
+                    let block_signatures =
+                        BlockSignatures::new(*block.hash(), block.header().era_id());
+                    responder
+                        .respond(Some(BlockWithMetadata {
+                            block: block.clone(),
+                            block_signatures,
+                        }))
+                        .await;
+                }
+                other => panic!("unexpected request: {}", other),
+            }
+            match reactor.expect_contract_runtime_request().await {
+                ContractRuntimeRequest::GetUnbonds {
+                    get_unbonds_request,
+                    responder,
+                } => {
+                    assert_eq!(
+                        get_unbonds_request.state_hash(),
+                        *block.header().state_root_hash()
+                    );
+                    assert_eq!(
+                        get_unbonds_request.protocol_version(),
+                        block.header().protocol_version()
+                    );
+                    responder
+                        .respond(Ok(engine_state::GetUnbondsResult::Success {
+                            unbonding_purses: self.unbonding_purses.clone(),
+                            unbonding_delay: UNBONDING_DELAY,
+                            redelegation_delay: REDELEGATION_DELAY,
+                        }))
+                        .await;
+                }
+                other => panic!("unexpected request: {}", other),
+            }
+
+            handler.await.unwrap().expect("should get unbonds").unbonds
+        }
+    }
+
+    #[tokio::test]
+    async fn should_get_unbonds_with_release_eras() {
+        let mut rng = TestRng::new();
+        let fixture = UnbondsFixture::new(&mut rng);
+
+        let unbonds = fixture.get_unbonds(&mut rng, None).await;
+
+        // Each unbond is released `UNBONDING_DELAY` eras after its creation, and they are listed in
+        // order of their release.
+        let unbonds: Vec<_> = unbonds
+            .iter()
+            .map(|unbond| {
+                (
+                    unbond.validator_public_key.clone(),
+                    unbond.unbonder_public_key.clone(),
+                    unbond.era_of_creation,
+                    unbond.release_era,
+                    unbond.amount,
+                )
+            })
+            .collect();
+        let expected_unbonds = vec![
+            (
+                fixture.validator_1.clone(),
+                fixture.delegator.clone(),
+                EraId::new(3),
+                EraId::new(10),
+                U512::from(3_000),
+            ),
+            (
+                fixture.validator_2.clone(),
+                fixture.validator_2.clone(),
+                EraId::new(4),
+                EraId::new(11),
+                U512::from(4_000),
+            ),
+            (
+                fixture.validator_1.clone(),
+                fixture.validator_1.clone(),
+                EraId::new(5),
+                EraId::new(12),
+                U512::from(5_000),
+            ),
+        ];
+        assert_eq!(unbonds, expected_unbonds);
+    }
+
+    #[tokio::test]
+    async fn should_filter_unbonds_by_public_key() {
+        let mut rng = TestRng::new();
+        let fixture = UnbondsFixture::new(&mut rng);
+
+        // A delegator's filter matches its own unbonds only.
+        let unbonds = fixture
+            .get_unbonds(&mut rng, Some(fixture.delegator.clone()))
+            .await;
+        assert_eq!(unbonds.len(), 1);
+        assert_eq!(unbonds[0].unbonder_public_key, fixture.delegator);
+        assert_eq!(unbonds[0].release_era, EraId::new(10));
+
+        // A validator's filter also matches its delegators' unbonds.
+        let unbonds = fixture
+            .get_unbonds(&mut rng, Some(fixture.validator_1.clone()))
+            .await;
+        let unbonders: Vec<_> = unbonds
+            .iter()
+            .map(|unbond| unbond.unbonder_public_key.clone())
+            .collect();
+        assert_eq!(
+            unbonders,
+            vec![fixture.delegator.clone(), fixture.validator_1.clone()]
+        );
+        assert!(unbonds
+            .iter()
+            .all(|unbond| unbond.validator_public_key == fixture.validator_1));
+
+        // A key unbonding nothing matches nothing.
+        let other_public_key = PublicKey::random(&mut rng);
+        let unbonds = fixture.get_unbonds(&mut rng, Some(other_public_key)).await;
+        assert!(unbonds.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_release_redelegations_after_redelegation_delay() {
+        let mut rng = TestRng::new();
+        let mut fixture = UnbondsFixture::new(&mut rng);
+        let redelegation = UnbondingPurse::new(
+            URef::new([6; 32], AccessRights::READ_ADD_WRITE),
+            fixture.validator_2.clone(),
+            fixture.delegator.clone(),
+            EraId::new(6),
+            U512::from(6_000),
+            Some(fixture.validator_1.clone()),
+        );
+        fixture
+            .unbonding_purses
+            .get_mut(&fixture.validator_2.to_account_hash())
+            .expect("should have unbonds from validator 2")
+            .push(redelegation);
+
+        let unbonds = fixture
+            .get_unbonds(&mut rng, Some(fixture.delegator.clone()))
+            .await;
+        let release_eras: Vec<_> = unbonds
+            .iter()
+            .map(|unbond| (unbond.new_validator.clone(), unbond.release_era))
+            .collect();
+        assert_eq!(
+            release_eras,
+            vec![
+                (
+                    Some(fixture.validator_1.clone()),
+                    EraId::new(6 + REDELEGATION_DELAY)
+                ),
+                (None, EraId::new(3 + UNBONDING_DELAY)),
+            ]
+        );
+    }
+
+    /// Runs "query_contract_view" on top of a random block, answering the view call with `result`.
+    async fn query_contract_view(
+        rng: &mut TestRng,
+        result: ViewResult,
+    ) -> (Block, Result<QueryContractViewResult, Error>) {
+        let reactor = MockReactor::new();
+        let block = Block::random(rng);
+        let params = QueryContractViewParams {
+            block_identifier: Some(BlockIdentifier::Hash(*block.hash())),
+            caller: AccountHash::new([9; 32]),
+            contract_hash: ContractHash::new([12; 32]),
+            entry_point: "counter_get".to_string(),
+            args: RuntimeArgs::new(),
+            gas_limit: Some(1_000),
+        };
+        let handler = tokio::spawn(QueryContractView::do_handle_request(
+            reactor.effect_builder(),
+            ProtocolVersion::V1_0_0,
+            params,
+        ));
+
+        match reactor.expect_rpc_request().await {
+            RpcRequest::GetBlock { responder, .. } => {
+                let block_signatures = BlockSignatures::new(*block.hash(), block.header().era_id());
+                responder
+                    .respond(Some(BlockWithMetadata {
+                        block: block.clone(),
+                        block_signatures,
+                    }))
+                    .await;
+            }
+            other => panic!("unexpected request: {}", other),
+        }
+        match reactor.expect_contract_runtime_request().await {
+            ContractRuntimeRequest::CallView {
+                view_request,
+                responder,
+            } => {
+                assert_eq!(view_request.state_hash(), *block.header().state_root_hash());
+                assert_eq!(view_request.contract_hash(), ContractHash::new([12; 32]));
+                assert_eq!(view_request.entry_point(), "counter_get");
+                assert_eq!(view_request.gas_limit(), Gas::from(1_000u64));
+                responder.respond(Ok(result)).await;
+            }
+            other => panic!("unexpected request: {}", other),
+        }
+
+        (block, handler.await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn should_return_view_call_value() {
+        let mut rng = TestRng::new();
+        let value = CLValue::from_t(7i32).unwrap();
+        let result = ViewResult::Success {
+            value: value.clone(),
+            cost: Gas::from(500u64),
+        };
+
+        let (block, result) = query_contract_view(&mut rng, result).await;
+
+        let expected = QueryContractViewResult {
+            api_version: ProtocolVersion::V1_0_0,
+            block_hash: *block.hash(),
+            value,
+            cost: U512::from(500),
+        };
+        assert_eq!(result.expect("should call view"), expected);
+    }
+
+    #[tokio::test]
+    async fn should_fail_view_call_writing_to_global_state() {
+        let mut rng = TestRng::new();
+        let error = engine_state::Error::ViewWrite(Key::Hash([1; 32]));
+        let message = format!("view call failed after using 500 gas: {}", error);
+        let result = ViewResult::Failure {
+            error,
+            cost: Gas::from(500u64),
+        };
+
+        let (_block, result) = query_contract_view(&mut rng, result).await;
+
+        assert_eq!(
+            result.expect_err("should fail view call"),
+            Error::new(ErrorCode::ViewCallFailed, message)
+        );
+    }
+}
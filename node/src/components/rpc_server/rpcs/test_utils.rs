@@ -0,0 +1,50 @@
+//! Utilities for testing RPC handlers against a mocked reactor.
+
+use crate::{
+    components::rpc_server::Event,
+    effect::{
+        requests::{ContractRuntimeRequest, RpcRequest},
+        EffectBuilder,
+    },
+    reactor::{main_reactor::MainEvent, EventQueueHandle, QueueKind, Scheduler},
+    utils,
+};
+
+/// Stands in for the reactor, letting a test answer the requests an RPC handler makes.
+pub(super) struct MockReactor {
+    scheduler: &'static Scheduler<MainEvent>,
+}
+
+impl MockReactor {
+    pub(super) fn new() -> Self {
+        MockReactor {
+            scheduler: utils::leak(Scheduler::new(QueueKind::weights())),
+        }
+    }
+
+    pub(super) fn effect_builder(&self) -> EffectBuilder<MainEvent> {
+        EffectBuilder::new(EventQueueHandle::without_shutdown(self.scheduler))
+    }
+
+    /// Returns the next request made by the handler under test, which should be an RPC request.
+    pub(super) async fn expect_rpc_request(&self) -> RpcRequest {
+        match self.next_event().await {
+            MainEvent::RpcServer(Event::RpcRequest(request)) => request,
+            other => panic!("unexpected event: {}", other),
+        }
+    }
+
+    /// Returns the next request made by the handler under test, which should be a contract runtime
+    /// request.
+    pub(super) async fn expect_contract_runtime_request(&self) -> ContractRuntimeRequest {
+        match self.next_event().await {
+            MainEvent::ContractRuntimeRequest(request) => request,
+            other => panic!("unexpected event: {}", other),
+        }
+    }
+
+    async fn next_event(&self) -> MainEvent {
+        let ((_ancestor, reactor_event), _) = self.scheduler.pop().await;
+        reactor_event
+    }
+}
@@ -1,4 +1,5 @@
 use hyper::server::{conn::AddrIncoming, Builder};
+use tokio::sync::oneshot;
 
 use casper_json_rpc::RequestHandlersBuilder;
 use casper_types::ProtocolVersion;
@@ -6,7 +7,7 @@ use casper_types::ProtocolVersion;
 use super::ReactorEventT;
 use crate::{
     effect::EffectBuilder,
-    rpcs::{speculative_exec::SpeculativeExec, RpcWithParams},
+    rpcs::{speculative_exec::SpeculativeExec, state::QueryContractView, RpcWithParams},
 };
 
 /// The URL path for all JSON-RPC requests.
@@ -21,9 +22,11 @@ pub(super) async fn run<REv: ReactorEventT>(
     api_version: ProtocolVersion,
     qps_limit: u64,
     max_body_bytes: u32,
+    shutdown_receiver: oneshot::Receiver<()>,
 ) {
     let mut handlers = RequestHandlersBuilder::new();
     SpeculativeExec::register_as_handler(effect_builder, api_version, &mut handlers);
+    QueryContractView::register_as_handler(effect_builder, api_version, &mut handlers);
     let handlers = handlers.build();
 
     super::rpcs::run(
@@ -33,6 +36,7 @@ pub(super) async fn run<REv: ReactorEventT>(
         max_body_bytes,
         SPECULATIVE_EXEC_API_PATH,
         SPECULATIVE_EXEC_SERVER_NAME,
+        shutdown_receiver,
     )
     .await;
 }
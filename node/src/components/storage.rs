@@ -30,11 +30,14 @@
 //! The storage component itself is panic free and in general reports three classes of errors:
 //! Corruption, temporary resource exhaustion and potential bugs.
 
+mod compaction;
 pub(crate) mod disjoint_sequences;
+mod era_snapshots;
 mod error;
 mod lmdb_ext;
 mod metrics;
 mod object_pool;
+mod signature_aggregates;
 #[cfg(test)]
 mod tests;
 
@@ -42,6 +45,7 @@ mod tests;
 use std::collections::BTreeSet;
 use std::{
     borrow::Cow,
+    cell::RefCell,
     collections::{btree_map::Entry, BTreeMap, HashMap, HashSet},
     convert::{TryFrom, TryInto},
     fmt::{self, Display, Formatter},
@@ -60,6 +64,7 @@ use lmdb::{
     Cursor, Database, DatabaseFlags, Environment, EnvironmentFlags, RwTransaction, Transaction,
     WriteFlags,
 };
+use lmdb_sys::{MDB_LAST, MDB_NEXT, MDB_PREV, MDB_SET_RANGE};
 use num_rational::Ratio;
 use prometheus::Registry;
 use serde::{Deserialize, Serialize};
@@ -69,10 +74,12 @@ use static_assertions::const_assert;
 use tempfile::TempDir;
 use tracing::{debug, error, info, trace, warn};
 
+use casper_execution_engine::storage::transaction_source::lmdb::LmdbSyncMode;
 use casper_hashing::Digest;
 use casper_types::{
-    bytesrepr::{FromBytes, ToBytes},
-    EraId, ExecutionResult, ProtocolVersion, PublicKey, TimeDiff, Timestamp, Transfer, Transform,
+    bytesrepr::{Bytes, FromBytes, ToBytes},
+    EraId, ExecutionResult, Key, ProtocolVersion, PublicKey, TimeDiff, Timestamp, Transfer,
+    Transform, U512,
 };
 
 use crate::{
@@ -91,23 +98,29 @@ use crate::{
     },
     fatal,
     protocol::Message,
+    rpcs::chain::ProposerBlockRange,
     types::{
-        ApprovalsHash, ApprovalsHashes, AvailableBlockRange, Block, BlockAndDeploys, BlockBody,
-        BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash,
+        Approval, ApprovalsHash, ApprovalsHashes, AvailableBlockRange, Block, BlockAndDeploys,
+        BlockBody, BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash,
         BlockHashAndHeight, BlockHeader, BlockHeaderWithMetadata, BlockSignatures,
-        BlockWithMetadata, Deploy, DeployHash, DeployHeader, DeployId, DeployMetadata,
-        DeployMetadataExt, DeployWithFinalizedApprovals, FinalitySignature, FinalizedApprovals,
-        FinalizedBlock, LegacyDeploy, NodeId, SyncLeap, SyncLeapIdentifier, ValueOrChunk,
+        BlockWithMetadata, Deploy, DeployApprovals, DeployExecutionResult, DeployExecutionResultId,
+        DeployHash, DeployHeader, DeployId, DeployMetadata, DeployMetadataExt,
+        DeployWithFinalizedApprovals, FinalitySignature, FinalizedApprovals, FinalizedBlock,
+        KeyWrite, LegacyDeploy, NodeId, SyncLeap, SyncLeapIdentifier, ValueOrChunk,
+        MAX_USER_TAG_LENGTH,
     },
     utils::{self, display_error, WithDir},
     NodeRng,
 };
+pub(crate) use compaction::compact_block_store;
 use disjoint_sequences::{DisjointSequences, Sequence};
+pub(crate) use era_snapshots::EraSnapshot;
 pub use error::FatalStorageError;
 use error::GetRequestError;
 use lmdb_ext::{BytesreprError, LmdbExtError, TransactionExt, WriteTransactionExt};
 use metrics::Metrics;
 use object_pool::ObjectPool;
+use signature_aggregates::BlockSignaturesAggregate;
 
 const COMPONENT_NAME: &str = "storage";
 
@@ -129,12 +142,25 @@ const DEFAULT_MAX_DEPLOY_STORE_SIZE: usize = 300 * GIB;
 const DEFAULT_MAX_DEPLOY_METADATA_STORE_SIZE: usize = 300 * GIB;
 /// Default max state store size.
 const DEFAULT_MAX_STATE_STORE_SIZE: usize = 10 * GIB;
+/// Default sync mode of the LMDB environment.
+const DEFAULT_LMDB_SYNC_MODE: LmdbSyncMode = LmdbSyncMode::Full;
+/// Default readahead setting of the LMDB environment.
+const DEFAULT_LMDB_READAHEAD: bool = false;
 /// Maximum number of allowed dbs.
-const MAX_DB_COUNT: u32 = 9;
+const MAX_DB_COUNT: u32 = 13;
+/// Maximum number of writes to a single key returned by one key history query.
+const MAX_KEY_HISTORY_WRITES: usize = 100;
+/// Maximum number of deploy hashes returned by one query for the deploys carrying a user tag.
+pub(crate) const MAX_DEPLOYS_BY_TAG: usize = 1_000;
 /// Key under which completed blocks are to be stored.
 const COMPLETED_BLOCKS_STORAGE_KEY: &[u8] = b"completed_blocks_disjoint_sequences";
+/// Key under which era snapshots are to be stored.
+const ERA_SNAPSHOTS_STORAGE_KEY: &[u8] = b"era_snapshots";
 /// Name of the file created when initializing a force resync.
 const FORCE_RESYNC_FILE_NAME: &str = "force_resync";
+/// The number of eras whose validator weights are cached for expanding and aggregating block
+/// signatures.
+const ERA_VALIDATOR_WEIGHTS_CACHE_SIZE: usize = 4;
 
 /// OS-specific lmdb flags.
 #[cfg(not(target_os = "macos"))]
@@ -150,6 +176,9 @@ const_assert!(_STORAGE_EVENT_SIZE <= 32);
 
 type FinalizedBlockAndDeploys = (FinalizedBlock, Vec<Deploy>);
 
+/// The validator weights of an era.
+type ValidatorWeights = Rc<BTreeMap<PublicKey, U512>>;
+
 const STORAGE_FILES: [&str; 5] = [
     "data.lmdb",
     "data.lmdb-lock",
@@ -166,6 +195,8 @@ pub struct Storage {
     /// Environment holding LMDB databases.
     #[data_size(skip)]
     env: Rc<Environment>,
+    /// How commits to `env` are made durable.
+    lmdb_sync_mode: LmdbSyncMode,
     /// The block header database.
     #[data_size(skip)]
     block_header_db: Database,
@@ -178,6 +209,9 @@ pub struct Storage {
     /// The block metadata db.
     #[data_size(skip)]
     block_metadata_db: Database,
+    /// The database of the signatures of final blocks, compacted into aggregates.
+    #[data_size(skip)]
+    block_signatures_aggregate_db: Database,
     /// The deploy database.
     #[data_size(skip)]
     deploy_db: Database,
@@ -193,16 +227,36 @@ pub struct Storage {
     /// The finalized approvals database.
     #[data_size(skip)]
     finalized_approvals_db: Database,
+    /// The database of approvals collected from clients and peers for deploys not yet stored.
+    #[data_size(skip)]
+    deploy_approvals_db: Database,
+    /// The database of deploy hashes by user tag.
+    #[data_size(skip)]
+    deploy_tag_db: Database,
+    /// The database of the blocks which wrote to each global state key, keyed by the serialized
+    /// key followed by the big-endian block height.
+    #[data_size(skip)]
+    key_history_db: Database,
     /// A map of block height to block ID.
     block_height_index: BTreeMap<u64, BlockHash>,
     /// A map of era ID to switch block ID.
     switch_block_era_id_index: BTreeMap<EraId, BlockHash>,
+    /// The validator weights of recent eras, along with the hash of the switch block they were
+    /// read from, which is checked against `switch_block_era_id_index` on every use.
+    #[data_size(skip)]
+    era_validator_weights_cache: RefCell<BTreeMap<EraId, (BlockHash, ValidatorWeights)>>,
     /// A map of deploy hashes to hashes and heights of blocks containing them.
     deploy_hash_index: BTreeMap<DeployHash, BlockHashAndHeight>,
+    /// A map of proposer public keys to the heights and era IDs of the blocks they created.
+    proposer_index: BTreeMap<PublicKey, BTreeMap<u64, EraId>>,
     /// Runs of completed blocks known in storage.
     completed_blocks: DisjointSequences,
     /// Whether or not memory deduplication is enabled.
     enable_mem_deduplication: bool,
+    /// Whether or not a snapshot is tagged for every completed switch block.
+    enable_era_snapshots: bool,
+    /// Whether or not the blocks writing to each global state key are indexed.
+    enable_key_history_index: bool,
     /// An in-memory pool of already loaded serialized items.
     ///
     /// Keyed by serialized item ID, contains the serialized item.
@@ -218,6 +272,15 @@ pub struct Storage {
     max_ttl: TimeDiff,
 }
 
+/// Approvals collected from clients and peers for a deploy that isn't stored yet.
+#[derive(Deserialize, Serialize)]
+struct CollectedApprovals {
+    /// The time the deploy expires, after which the approvals are discarded.
+    expires: Timestamp,
+    /// At most one approval per signer.
+    approvals: BTreeSet<Approval>,
+}
+
 /// A storage component event.
 #[derive(Debug, From, Serialize)]
 #[repr(u8)]
@@ -360,17 +423,22 @@ impl Storage {
             .saturating_add(config.max_deploy_store_size)
             .saturating_add(config.max_deploy_metadata_store_size);
 
+        let lmdb = config.lmdb.unwrap_or_default();
+        let lmdb_sync_mode = lmdb.sync_mode.unwrap_or(DEFAULT_LMDB_SYNC_MODE);
+        let mut flags = OS_FLAGS
+            // We manage our own directory.
+            | EnvironmentFlags::NO_SUB_DIR
+            // Disable thread local storage, strongly suggested for operation with tokio.
+            | EnvironmentFlags::NO_TLS
+            | lmdb_sync_mode.flags();
+        if !lmdb.readahead.unwrap_or(DEFAULT_LMDB_READAHEAD) {
+            // Our data is not stored/read in sequence that would benefit from the read-ahead.
+            flags |= EnvironmentFlags::NO_READAHEAD;
+        }
+
         // Creates the environment and databases.
         let env = Environment::new()
-            .set_flags(
-                OS_FLAGS
-                // We manage our own directory.
-                | EnvironmentFlags::NO_SUB_DIR
-                // Disable thread local storage, strongly suggested for operation with tokio.
-                | EnvironmentFlags::NO_TLS
-                // Disable read-ahead. Our data is not stored/read in sequence that would benefit from the read-ahead.
-                | EnvironmentFlags::NO_READAHEAD,
-            )
+            .set_flags(flags)
             .set_max_readers(MAX_TRANSACTIONS)
             .set_max_dbs(MAX_DB_COUNT)
             .set_map_size(total_size)
@@ -378,27 +446,37 @@ impl Storage {
 
         let block_header_db = env.create_db(Some("block_header"), DatabaseFlags::empty())?;
         let block_metadata_db = env.create_db(Some("block_metadata"), DatabaseFlags::empty())?;
+        let block_signatures_aggregate_db =
+            env.create_db(Some("block_signatures_aggregates"), DatabaseFlags::empty())?;
         let deploy_db = env.create_db(Some("deploys"), DatabaseFlags::empty())?;
         let deploy_metadata_db = env.create_db(Some("deploy_metadata"), DatabaseFlags::empty())?;
         let transfer_db = env.create_db(Some("transfer"), DatabaseFlags::empty())?;
         let state_store_db = env.create_db(Some("state_store"), DatabaseFlags::empty())?;
         let finalized_approvals_db =
             env.create_db(Some("finalized_approvals"), DatabaseFlags::empty())?;
+        let deploy_approvals_db =
+            env.create_db(Some("deploy_approvals"), DatabaseFlags::empty())?;
         let block_body_db = env.create_db(Some("block_body"), DatabaseFlags::empty())?;
         let approvals_hashes_db =
             env.create_db(Some("approvals_hashes"), DatabaseFlags::empty())?;
+        let deploy_tag_db = env.create_db(Some("deploy_tags"), DatabaseFlags::empty())?;
+        let key_history_db = env.create_db(Some("key_history"), DatabaseFlags::empty())?;
 
         // We now need to restore the block-height index. Log messages allow timing here.
         info!("indexing block store");
         let mut block_height_index = BTreeMap::new();
         let mut switch_block_era_id_index = BTreeMap::new();
         let mut deploy_hash_index = BTreeMap::new();
+        let mut proposer_index = BTreeMap::new();
         let mut block_txn = env.begin_rw_txn()?;
         let mut cursor = block_txn.open_rw_cursor(block_header_db)?;
 
         let mut deleted_block_hashes = HashSet::new();
         let mut deleted_block_body_hashes = HashSet::new();
         let mut deleted_deploy_hashes = HashSet::<DeployHash>::new();
+        // The validators of the eras following deleted switch blocks, which signature aggregates
+        // of surviving blocks may still depend on.
+        let mut orphaned_validator_weights = BTreeMap::new();
 
         // Note: `iter_start` has an undocumented panic if called on an empty database. We rely on
         //       the iterator being at the start when created.
@@ -424,6 +502,11 @@ impl Storage {
 
                     let _ = deleted_block_body_hashes.insert(*block_header.body_hash());
 
+                    if let Some(validator_weights) = block_header.next_era_validator_weights() {
+                        let _ = orphaned_validator_weights
+                            .insert(block_header.era_id().successor(), validator_weights.clone());
+                    }
+
                     cursor.del(WriteFlags::empty())?;
                     continue;
                 }
@@ -442,6 +525,7 @@ impl Storage {
                     &block_body,
                     block_header.height(),
                 )?;
+                insert_to_proposer_index(&mut proposer_index, &block_header, &block_body);
             }
         }
         info!("block store reindexing complete");
@@ -461,6 +545,13 @@ impl Storage {
         )?;
 
         initialize_block_metadata_db(&env, &block_metadata_db, &deleted_block_hashes_raw)?;
+        initialize_block_signatures_aggregate_db(
+            &env,
+            &block_signatures_aggregate_db,
+            &block_metadata_db,
+            &deleted_block_hashes_raw,
+            &orphaned_validator_weights,
+        )?;
         initialize_deploy_metadata_db(&env, &deploy_metadata_db, &deleted_deploy_hashes)?;
 
         let metrics = registry.map(Metrics::new).transpose()?;
@@ -468,20 +559,29 @@ impl Storage {
         let mut component = Self {
             root,
             env: Rc::new(env),
+            lmdb_sync_mode,
             block_header_db,
             block_body_db,
             block_metadata_db,
+            block_signatures_aggregate_db,
             approvals_hashes_db,
             deploy_db,
             deploy_metadata_db,
             transfer_db,
             state_store_db,
             finalized_approvals_db,
+            deploy_approvals_db,
+            deploy_tag_db,
+            key_history_db,
             block_height_index,
             switch_block_era_id_index,
+            era_validator_weights_cache: RefCell::new(BTreeMap::new()),
             deploy_hash_index,
+            proposer_index,
             completed_blocks: Default::default(),
             enable_mem_deduplication: config.enable_mem_deduplication,
+            enable_era_snapshots: config.enable_era_snapshots,
+            enable_key_history_index: config.enable_key_history_index,
             serialized_item_pool: ObjectPool::new(config.mem_pool_prune_interval),
             recent_era_count,
             fault_tolerance_fraction,
@@ -586,21 +686,18 @@ impl Storage {
         Ok(bytes)
     }
 
-    /// Writes a key to the state storage database.
+    /// Writes a key to the state storage database within the given transaction.
     // See note below why `key` and `data` are not `&[u8]`s.
-    fn write_state_store(
+    fn put_state_store(
         &self,
+        txn: &mut RwTransaction,
         key: Cow<'static, [u8]>,
         data: &Vec<u8>,
     ) -> Result<(), FatalStorageError> {
-        let mut txn = self.env.begin_rw_txn()?;
-
         // Note: The interface of `lmdb` seems suboptimal: `&K` and `&V` could simply be `&[u8]` for
         //       simplicity. At the very least it seems to be missing a `?Sized` trait bound. For
         //       this reason, we need to use actual sized types in the function signature above.
         txn.put(self.state_store_db, &key, data, WriteFlags::default())?;
-        txn.commit()?;
-
         Ok(())
     }
 
@@ -748,6 +845,37 @@ impl Storage {
                     fetch_response,
                 )?)
             }
+            NetRequest::DeployExecutionResult(ref serialized_id) => {
+                let item_id = decode_item_id::<DeployExecutionResult>(serialized_id)?;
+                let opt_item = self.read_deploy_execution_result(&item_id)?;
+                let fetch_response = FetchResponse::from_opt(item_id, opt_item);
+
+                Ok(self.update_pool_and_send(
+                    effect_builder,
+                    incoming.sender,
+                    serialized_id,
+                    fetch_response,
+                )?)
+            }
+            NetRequest::DeployApprovals(ref serialized_id) => {
+                let deploy_hash = decode_item_id::<DeployApprovals>(serialized_id)?;
+                let opt_item = self
+                    .get_deploy_approvals(&deploy_hash)
+                    .map_err(FatalStorageError::from)?;
+                let fetch_response = FetchResponse::from_opt(deploy_hash, opt_item);
+
+                // The approvals held for a deploy grow over time, so the response isn't pooled.
+                let serialized = fetch_response
+                    .to_serialized()
+                    .map_err(FatalStorageError::StoredItemSerializationFailure)?;
+                let message = Message::new_get_response_from_serialized(
+                    <DeployApprovals as FetchItem>::TAG,
+                    serialized.into(),
+                );
+                Ok(effect_builder
+                    .send_message(incoming.sender, message)
+                    .ignore())
+            }
         }
     }
 
@@ -824,9 +952,45 @@ impl Storage {
                     .respond(self.get_transfers(&mut txn, &block_hash)?)
                     .ignore()
             }
+            StorageRequest::GetBlocksByProposer {
+                proposer,
+                range,
+                responder,
+            } => responder
+                .respond(self.read_blocks_by_proposer(&proposer, range))
+                .ignore(),
+            StorageRequest::GetStateRootHashRange {
+                low,
+                high,
+                responder,
+            } => responder
+                .respond(self.read_state_root_hash_range(low, high)?)
+                .ignore(),
+            StorageRequest::GetDeploysByTag {
+                user_tag,
+                after,
+                limit,
+                responder,
+            } => responder
+                .respond(self.read_deploys_by_tag(&user_tag, after, limit)?)
+                .ignore(),
             StorageRequest::PutDeploy { deploy, responder } => {
                 responder.respond(self.put_deploy(&*deploy)?).ignore()
             }
+            StorageRequest::PutDeployApprovals {
+                deploy_hash,
+                expires,
+                max_approvals,
+                approvals,
+                responder,
+            } => responder
+                .respond(self.put_deploy_approvals(
+                    &deploy_hash,
+                    expires,
+                    max_approvals,
+                    approvals,
+                )?)
+                .ignore(),
             StorageRequest::GetDeploys {
                 deploy_hashes,
                 responder,
@@ -878,6 +1042,16 @@ impl Storage {
             StorageRequest::GetBlockExecutionResultsOrChunk { id, responder } => responder
                 .respond(self.read_block_execution_results_or_chunk(&id)?)
                 .ignore(),
+            StorageRequest::GetDeployExecutionResult { id, responder } => responder
+                .respond(self.read_deploy_execution_result(&id)?)
+                .ignore(),
+            StorageRequest::PutDeployExecutionResult { item, responder } => {
+                let env = Rc::clone(&self.env);
+                let mut txn = env.begin_rw_txn()?;
+                let was_written = self.write_deploy_execution_result(&mut txn, &item)?;
+                txn.commit()?;
+                responder.respond(was_written).ignore()
+            }
             StorageRequest::PutExecutionResults {
                 block_hash,
                 execution_results,
@@ -925,45 +1099,28 @@ impl Storage {
                 only_from_available_block_range,
                 responder,
             } => {
-                let mut txn = self.env.begin_ro_txn()?;
-
-                let block: Block =
-                    if let Some(block) = self.get_single_block(&mut txn, &block_hash)? {
-                        block
-                    } else {
-                        return Ok(responder.respond(None).ignore());
-                    };
-
-                if !(self.should_return_block(block.height(), only_from_available_block_range)?) {
-                    return Ok(responder.respond(None).ignore());
-                }
-
-                // Check that the hash of the block retrieved is correct.
-                if block_hash != *block.hash() {
-                    error!(
-                        queried_block_hash = ?block_hash,
-                        actual_block_hash = ?block.hash(),
-                        "block not stored under hash"
-                    );
-                    debug_assert_eq!(&block_hash, block.hash());
-                    return Ok(responder.respond(None).ignore());
-                }
-                let block_signatures = match self.get_block_signatures(&mut txn, &block_hash)? {
-                    Some(signatures) => signatures,
-                    None => BlockSignatures::new(block_hash, block.header().era_id()),
-                };
-                if block_signatures.verify().is_err() {
-                    error!(?block, "invalid block signatures for block");
-                    debug_assert!(block_signatures.verify().is_ok());
-                    return Ok(responder.respond(None).ignore());
-                }
                 responder
-                    .respond(Some(BlockWithMetadata {
-                        block,
-                        block_signatures,
-                    }))
+                    .respond(self.read_block_and_metadata_by_hash(
+                        block_hash,
+                        only_from_available_block_range,
+                    )?)
                     .ignore()
             }
+            StorageRequest::GetSwitchBlockAndMetadataByEraId {
+                era_id,
+                only_from_available_block_range,
+                responder,
+            } => {
+                let maybe_block_with_metadata =
+                    match self.switch_block_era_id_index.get(&era_id).copied() {
+                        Some(block_hash) => self.read_block_and_metadata_by_hash(
+                            block_hash,
+                            only_from_available_block_range,
+                        )?,
+                        None => None,
+                    };
+                responder.respond(maybe_block_with_metadata).ignore()
+            }
             StorageRequest::GetFinalitySignature { id, responder } => {
                 let mut txn = self.env.begin_ro_txn()?;
                 let maybe_sig = self
@@ -1042,25 +1199,7 @@ impl Storage {
                     );
                     return Ok(responder.respond(false).ignore());
                 }
-                let mut txn = self.env.begin_rw_txn()?;
-                let old_data: Option<BlockSignatures> =
-                    txn.get_value(self.block_metadata_db, &signatures.block_hash)?;
-                let new_data = match old_data {
-                    None => signatures,
-                    Some(mut data) => {
-                        for (public_key, sig) in signatures.proofs {
-                            data.insert_proof(public_key, sig);
-                        }
-                        data
-                    }
-                };
-                let outcome = txn.put_value(
-                    self.block_metadata_db,
-                    &new_data.block_hash,
-                    &new_data,
-                    true,
-                )?;
-                txn.commit()?;
+                let outcome = self.put_block_signatures(signatures)?;
                 responder.respond(outcome).ignore()
             }
             StorageRequest::PutFinalitySignature {
@@ -1068,18 +1207,13 @@ impl Storage {
                 responder,
             } => {
                 let mut txn = self.env.begin_rw_txn()?;
-                let mut block_signatures = txn
-                    .get_value(self.block_metadata_db, &signature.block_hash)?
+                let mut block_signatures = self
+                    .get_block_signatures(&mut txn, &signature.block_hash)?
                     .unwrap_or_else(|| {
                         BlockSignatures::new(signature.block_hash, signature.era_id)
                     });
                 block_signatures.insert_proof(signature.public_key, signature.signature);
-                let outcome = txn.put_value(
-                    self.block_metadata_db,
-                    &block_signatures.block_hash,
-                    &block_signatures,
-                    true,
-                )?;
+                let outcome = self.write_block_signatures(&mut txn, &block_signatures)?;
                 txn.commit()?;
                 responder.respond(outcome).ignore()
             }
@@ -1133,9 +1267,30 @@ impl Storage {
                 block,
                 approvals_hashes,
                 execution_results,
+                written_keys,
                 responder,
             } => responder
-                .respond(self.put_executed_block(&block, &approvals_hashes, execution_results)?)
+                .respond(self.put_executed_block(
+                    &block,
+                    &approvals_hashes,
+                    execution_results,
+                    &written_keys,
+                )?)
+                .ignore(),
+            StorageRequest::PutSignedBlock {
+                block,
+                block_signatures,
+                mark_complete,
+                responder,
+            } => responder
+                .respond(self.put_signed_block(&block, *block_signatures, mark_complete)?)
+                .ignore(),
+            StorageRequest::GetKeyHistory {
+                key,
+                before_height,
+                responder,
+            } => responder
+                .respond(self.read_key_history(&key, before_height)?)
                 .ignore(),
         })
     }
@@ -1148,22 +1303,33 @@ impl Storage {
             responder,
         }: BlockCompleteConfirmationRequest,
     ) -> Result<Effects<Event>, FatalStorageError> {
-        let is_new = self.mark_block_complete(block_height)?;
+        let env = Rc::clone(&self.env);
+        let mut txn = env.begin_rw_txn()?;
+        let is_new = self.mark_block_complete(&mut txn, block_height)?;
+        txn.commit()?;
+        self.sync_completed_block()?;
         Ok(responder.respond(is_new).ignore())
     }
 
-    /// Marks the block at height `block_height` as complete by inserting it
-    /// into the `completed_blocks` index and storing it to disk.
-    fn mark_block_complete(&mut self, block_height: u64) -> Result<bool, FatalStorageError> {
+    /// Marks the block at height `block_height` as complete by inserting it into the
+    /// `completed_blocks` index and storing the index within the given transaction.
+    fn mark_block_complete(
+        &mut self,
+        txn: &mut RwTransaction,
+        block_height: u64,
+    ) -> Result<bool, FatalStorageError> {
         let is_new = self.completed_blocks.insert(block_height);
         if is_new {
-            self.persist_completed_blocks()?;
+            self.put_completed_blocks(txn, &self.completed_blocks)?;
             info!(
                 "Storage: marked block {} complete: {}",
                 block_height,
                 self.get_available_block_range()
             );
             self.update_chain_height_metrics();
+            if self.enable_era_snapshots {
+                self.tag_era_snapshot(txn, block_height)?;
+            }
         } else {
             debug!(
                 "Storage: tried to mark already-complete block {} complete",
@@ -1173,13 +1339,183 @@ impl Storage {
         Ok(is_new)
     }
 
+    /// Flushes the environment to disk if it is synced manually, once a block has been committed
+    /// as complete.
+    fn sync_completed_block(&self) -> Result<(), FatalStorageError> {
+        if self.lmdb_sync_mode == LmdbSyncMode::Manual {
+            self.env.sync(true)?;
+        }
+        Ok(())
+    }
+
     /// Persists the completed blocks disjoint sequences state to the database.
     fn persist_completed_blocks(&mut self) -> Result<(), FatalStorageError> {
-        let serialized = self
-            .completed_blocks
+        let mut txn = self.env.begin_rw_txn()?;
+        self.put_completed_blocks(&mut txn, &self.completed_blocks)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Writes the completed blocks disjoint sequences state within the given transaction.
+    fn put_completed_blocks(
+        &self,
+        txn: &mut RwTransaction,
+        completed_blocks: &DisjointSequences,
+    ) -> Result<(), FatalStorageError> {
+        let serialized = completed_blocks
             .to_bytes()
             .map_err(FatalStorageError::UnexpectedSerializationFailure)?;
-        self.write_state_store(Cow::Borrowed(COMPLETED_BLOCKS_STORAGE_KEY), &serialized)
+        self.put_state_store(
+            txn,
+            Cow::Borrowed(COMPLETED_BLOCKS_STORAGE_KEY),
+            &serialized,
+        )
+    }
+
+    /// Tags a snapshot of the chain if the block at `block_height` is a switch block.
+    ///
+    /// The block is read through `txn`, as it may have been written within it.
+    fn tag_era_snapshot(
+        &mut self,
+        txn: &mut RwTransaction,
+        block_height: u64,
+    ) -> Result<(), FatalStorageError> {
+        let header = match self.get_block_header_by_height_restricted(txn, block_height, false)? {
+            Some(header) if header.is_switch_block() => header,
+            _ => return Ok(()),
+        };
+        let snapshot = EraSnapshot::new(&header);
+        info!(%snapshot, "Storage: tagged era snapshot");
+        let mut snapshots = self.read_era_snapshots()?;
+        snapshots.insert(snapshot.era_id, snapshot);
+        self.put_era_snapshots(txn, &snapshots)
+    }
+
+    /// Reads the tagged era snapshots.
+    pub(crate) fn read_era_snapshots(
+        &self,
+    ) -> Result<BTreeMap<EraId, EraSnapshot>, FatalStorageError> {
+        match self.read_state_store(&Cow::Borrowed(ERA_SNAPSHOTS_STORAGE_KEY))? {
+            Some(raw) => Ok(lmdb_ext::deserialize(&raw)?),
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    /// Writes the tagged era snapshots within the given transaction.
+    fn put_era_snapshots(
+        &self,
+        txn: &mut RwTransaction,
+        snapshots: &BTreeMap<EraId, EraSnapshot>,
+    ) -> Result<(), FatalStorageError> {
+        let serialized = lmdb_ext::serialize(snapshots)?;
+        self.put_state_store(txn, Cow::Borrowed(ERA_SNAPSHOTS_STORAGE_KEY), &serialized)
+    }
+
+    /// Deletes all blocks above `height` along with their finality signatures, approvals hashes,
+    /// transfers and the execution results of their deploys, returning the number of deleted
+    /// blocks.
+    ///
+    /// Deploys themselves and global state are retained. Era snapshots above `height` are
+    /// deleted as well.
+    pub(crate) fn truncate_blocks_above(
+        &mut self,
+        height: u64,
+    ) -> Result<usize, FatalStorageError> {
+        // The indices are only updated once the transaction has been committed, so that they still
+        // match the database if it fails.
+        let deleted: BTreeMap<u64, BlockHash> = self
+            .block_height_index
+            .range(height + 1..)
+            .map(|(block_height, block_hash)| (*block_height, *block_hash))
+            .collect();
+        let deleted_block_hashes: HashSet<BlockHash> = deleted.values().copied().collect();
+        let mut deleted_block_body_hashes = HashSet::new();
+
+        let env = Rc::clone(&self.env);
+        let mut txn = env.begin_rw_txn()?;
+        for block_hash in deleted.values() {
+            if let Some(header) = self.get_single_block_header(&mut txn, block_hash)? {
+                let maybe_body =
+                    get_body_for_block_header(&mut txn, header.body_hash(), self.block_body_db)?;
+                if let Some(body) = maybe_body {
+                    for deploy_hash in body.deploy_and_transfer_hashes() {
+                        delete_if_present(&mut txn, self.deploy_metadata_db, deploy_hash)?;
+                    }
+                }
+                deleted_block_body_hashes.insert(*header.body_hash());
+            }
+            for db in [
+                self.block_header_db,
+                self.block_metadata_db,
+                self.block_signatures_aggregate_db,
+                self.approvals_hashes_db,
+                self.transfer_db,
+            ] {
+                delete_if_present(&mut txn, db, block_hash)?;
+            }
+        }
+        let mut completed_blocks = self.completed_blocks.clone();
+        completed_blocks.truncate(height);
+        self.put_completed_blocks(&mut txn, &completed_blocks)?;
+        let mut snapshots = self.read_era_snapshots()?;
+        snapshots.retain(|_, snapshot| snapshot.block_height <= height);
+        self.put_era_snapshots(&mut txn, &snapshots)?;
+        txn.commit()?;
+
+        let _ = self.block_height_index.split_off(&(height + 1));
+        self.completed_blocks = completed_blocks;
+        self.switch_block_era_id_index
+            .retain(|_, block_hash| !deleted_block_hashes.contains(block_hash));
+        self.deploy_hash_index
+            .retain(|_, block_hash_and_height| block_hash_and_height.block_height <= height);
+        self.proposer_index.retain(|_, blocks| {
+            let _ = blocks.split_off(&(height + 1));
+            !blocks.is_empty()
+        });
+
+        // Block bodies may be shared by several blocks, so only unreferenced ones are purged.
+        initialize_block_body_db(
+            &env,
+            &self.block_header_db,
+            &self.block_body_db,
+            &deleted_block_body_hashes
+                .iter()
+                .map(Digest::as_ref)
+                .collect(),
+        )?;
+
+        Ok(deleted.len())
+    }
+
+    /// Stores block signatures, merging them with any already stored for the same block.
+    pub(crate) fn put_block_signatures(
+        &self,
+        signatures: BlockSignatures,
+    ) -> Result<bool, FatalStorageError> {
+        let mut txn = self.env.begin_rw_txn()?;
+        let outcome = self.merge_block_signatures(&mut txn, signatures)?;
+        txn.commit()?;
+        Ok(outcome)
+    }
+
+    /// Writes block signatures within the given transaction, merging them with any already stored
+    /// for the same block.
+    fn merge_block_signatures(
+        &self,
+        txn: &mut RwTransaction,
+        signatures: BlockSignatures,
+    ) -> Result<bool, FatalStorageError> {
+        let old_data = self.get_block_signatures(txn, &signatures.block_hash)?;
+        let new_data = match old_data {
+            None => signatures,
+            Some(mut data) => {
+                for (public_key, sig) in signatures.proofs {
+                    data.insert_proof(public_key, sig);
+                }
+                data
+            }
+        };
+        self.write_block_signatures(txn, &new_data)
     }
 
     /// Put a single deploy into storage.
@@ -1189,6 +1525,10 @@ impl Storage {
         let outcome = txn.put_value(self.deploy_db, deploy_hash, deploy, false)?;
         if outcome {
             debug!(%deploy_hash, "Storage: new deploy stored");
+            if let Some(user_tag) = deploy.user_tag() {
+                self.insert_to_deploy_tag_index(&mut txn, user_tag, *deploy_hash)?;
+            }
+            delete_if_present(&mut txn, self.deploy_approvals_db, deploy_hash)?;
         } else {
             debug!(%deploy_hash, "Storage: attempt to store existing deploy");
         }
@@ -1196,11 +1536,14 @@ impl Storage {
         Ok(outcome)
     }
 
+    /// Stores an executed block along with its approvals hashes, the execution results of its
+    /// deploys and the global state keys it wrote, all in a single transaction.
     fn put_executed_block(
         &mut self,
         block: &Block,
         approvals_hashes: &ApprovalsHashes,
         execution_results: HashMap<DeployHash, ExecutionResult>,
+        written_keys: &[Key],
     ) -> Result<bool, FatalStorageError> {
         let env = Rc::clone(&self.env);
         let mut txn = env.begin_rw_txn()?;
@@ -1211,18 +1554,84 @@ impl Storage {
 
         let _ = self.write_approvals_hashes(&mut txn, approvals_hashes)?;
         let _ = self.write_execution_results(&mut txn, block.hash(), execution_results)?;
+        self.put_key_history(&mut txn, &KeyWrite::from(block), written_keys)?;
         txn.commit()?;
 
         Ok(true)
     }
 
+    /// Stores a block imported from a chain archive along with its finality signatures, deploys,
+    /// approvals hashes and the execution results of its deploys, and marks it complete, all in a
+    /// single transaction.
+    ///
+    /// The block and all its data must have been verified by the caller.
+    pub(crate) fn put_archived_block(
+        &mut self,
+        block: &Block,
+        block_signatures: BlockSignatures,
+        deploys: &[Deploy],
+        approvals_hashes: Option<&ApprovalsHashes>,
+        execution_results: HashMap<DeployHash, ExecutionResult>,
+    ) -> Result<(), FatalStorageError> {
+        let env = Rc::clone(&self.env);
+        let mut txn = env.begin_rw_txn()?;
+        for deploy in deploys {
+            let deploy_hash = deploy.hash();
+            if txn.put_value(self.deploy_db, deploy_hash, deploy, false)? {
+                if let Some(user_tag) = deploy.user_tag() {
+                    self.insert_to_deploy_tag_index(&mut txn, user_tag, *deploy_hash)?;
+                }
+            }
+        }
+        if !self.write_validated_block(&mut txn, block)? {
+            return Err(FatalStorageError::FailedToOverwriteBlock);
+        }
+        let _ = self.merge_block_signatures(&mut txn, block_signatures)?;
+        if let Some(approvals_hashes) = approvals_hashes {
+            let _ = self.write_approvals_hashes(&mut txn, approvals_hashes)?;
+        }
+        let _ = self.write_execution_results(&mut txn, block.hash(), execution_results)?;
+        let _ = self.mark_block_complete(&mut txn, block.height())?;
+        txn.commit()?;
+        self.sync_completed_block()
+    }
+
+    /// Stores a block along with its finality signatures, merged with any already stored, and
+    /// marks it complete if `mark_complete` is set, all in a single transaction.
+    ///
+    /// Returns `Ok(false)` if the block couldn't be written, in which case nothing is stored.
+    fn put_signed_block(
+        &mut self,
+        block: &Block,
+        block_signatures: BlockSignatures,
+        mark_complete: bool,
+    ) -> Result<bool, FatalStorageError> {
+        block.verify()?;
+        let env = Rc::clone(&self.env);
+        let mut txn = env.begin_rw_txn()?;
+        if !self.write_validated_block(&mut txn, block)? {
+            return Ok(false);
+        }
+        if !block_signatures.proofs.is_empty() {
+            let _ = self.merge_block_signatures(&mut txn, block_signatures)?;
+        }
+        if mark_complete {
+            let _ = self.mark_block_complete(&mut txn, block.height())?;
+        }
+        txn.commit()?;
+        if mark_complete {
+            self.sync_completed_block()?;
+        }
+        Ok(true)
+    }
+
     /// Retrieves a block by hash.
     pub fn read_block(&self, block_hash: &BlockHash) -> Result<Option<Block>, FatalStorageError> {
         self.get_single_block(&mut self.env.begin_ro_txn()?, block_hash)
     }
 
     /// Retrieves a approvals hashes by block hash.
-    fn read_approvals_hashes(
+    pub(crate) fn read_approvals_hashes(
         &self,
         block_hash: &BlockHash,
     ) -> Result<Option<ApprovalsHashes>, FatalStorageError> {
@@ -1376,8 +1785,9 @@ impl Storage {
         let wrote = self.write_validated_block(&mut txn, block)?;
         if wrote {
             // Update the `completed_blocks` index only if the block was actually stored.
-            let _ = self.mark_block_complete(block.height())?;
+            let _ = self.mark_block_complete(&mut txn, block.height())?;
             txn.commit()?;
+            self.sync_completed_block()?;
         }
         Ok(wrote)
     }
@@ -1437,6 +1847,28 @@ impl Storage {
         Ok(was_written)
     }
 
+    /// Writes the execution result of a single deploy in a block, backfilled from a peer.
+    ///
+    /// Unlike [`Self::write_execution_results`], this leaves the transfers of the block untouched,
+    /// as they can only be written once the execution results of all its deploys are held.
+    fn write_deploy_execution_result(
+        &mut self,
+        txn: &mut RwTransaction,
+        item: &DeployExecutionResult,
+    ) -> Result<bool, FatalStorageError> {
+        let id = item.id();
+        let mut metadata = self
+            .get_deploy_metadata(txn, id.deploy_hash())?
+            .unwrap_or_default();
+        if metadata.execution_results.get(id.block_hash()) == Some(item.execution_result()) {
+            return Ok(true);
+        }
+        metadata
+            .execution_results
+            .insert(*id.block_hash(), item.execution_result().clone());
+        Ok(txn.put_value(self.deploy_metadata_db, id.deploy_hash(), &metadata, true)?)
+    }
+
     /// Writes approvals hashes to storage.
     fn write_approvals_hashes(
         &mut self,
@@ -1462,11 +1894,7 @@ impl Storage {
         signatures: &BlockSignatures,
     ) -> Result<(), FatalStorageError> {
         let mut txn = self.env.begin_rw_txn()?;
-        let block_hash = signatures.block_hash;
-        if txn
-            .put_value(self.block_metadata_db, &block_hash, signatures, true)
-            .is_err()
-        {
+        if self.write_block_signatures(&mut txn, signatures).is_err() {
             panic!("write_finality_signatures() failed");
         }
         txn.commit()?;
@@ -1515,6 +1943,7 @@ impl Storage {
                 block.body(),
                 block.header().height(),
             )?;
+            insert_to_proposer_index(&mut self.proposer_index, block.header(), block.body());
         }
         Ok(true)
     }
@@ -1551,6 +1980,243 @@ impl Storage {
         Ok(result)
     }
 
+    /// Adds `deploy_hash` to the deploys tagged with `user_tag`.
+    ///
+    /// Every tagged deploy has its own entry, keyed on the tag followed by the deploy hash, so
+    /// indexing a deploy doesn't rewrite the entries of the other deploys carrying the same tag.
+    fn insert_to_deploy_tag_index(
+        &self,
+        txn: &mut RwTransaction,
+        user_tag: &Bytes,
+        deploy_hash: DeployHash,
+    ) -> Result<(), FatalStorageError> {
+        // Deploy validation rejects empty and oversized tags anyway.
+        if user_tag.is_empty() || user_tag.len() > MAX_USER_TAG_LENGTH {
+            return Ok(());
+        }
+        let mut db_key = deploy_tag_prefix(user_tag);
+        db_key.extend_from_slice(deploy_hash.inner().as_ref());
+        let _ = txn.put_value(self.deploy_tag_db, &db_key, &(), true)?;
+        Ok(())
+    }
+
+    /// Retrieves the hashes of up to `limit` stored deploys tagged with `user_tag`, ordered by
+    /// deploy hash and starting after `after`, if given.
+    pub(crate) fn read_deploys_by_tag(
+        &self,
+        user_tag: &Bytes,
+        after: Option<DeployHash>,
+        limit: usize,
+    ) -> Result<Vec<DeployHash>, FatalStorageError> {
+        if user_tag.is_empty() || user_tag.len() > MAX_USER_TAG_LENGTH {
+            return Ok(Vec::new());
+        }
+        let prefix = deploy_tag_prefix(user_tag);
+        let mut seek_key = prefix.clone();
+        if let Some(after) = after {
+            seek_key.extend_from_slice(after.inner().as_ref());
+        }
+        let limit = limit.min(MAX_DEPLOYS_BY_TAG);
+        let txn = self.env.begin_ro_txn()?;
+        let mut deploy_hashes = Vec::new();
+        {
+            let cursor = txn.open_ro_cursor(self.deploy_tag_db)?;
+            let mut row = cursor.get(Some(seek_key.as_slice()), None, MDB_SET_RANGE);
+            while deploy_hashes.len() < limit {
+                let db_key = match row {
+                    Ok((Some(db_key), _)) => db_key,
+                    Ok((None, _)) | Err(lmdb::Error::NotFound) => break,
+                    Err(error) => return Err(error.into()),
+                };
+                if !db_key.starts_with(&prefix) {
+                    break;
+                }
+                if db_key != seek_key.as_slice() {
+                    let deploy_hash = Digest::try_from(&db_key[prefix.len()..])
+                        .map(DeployHash::new)
+                        .map_err(|_| FatalStorageError::CorruptedDeployTagIndex {
+                            db_key: db_key.to_vec(),
+                        })?;
+                    deploy_hashes.push(deploy_hash);
+                }
+                row = cursor.get(None, None, MDB_NEXT);
+            }
+        }
+        txn.commit()?;
+        Ok(deploy_hashes)
+    }
+
+    /// Records that the block described by `key_write` wrote to each of `written_keys`, if the key
+    /// history index is enabled.
+    #[cfg(test)]
+    fn write_key_history(
+        &self,
+        key_write: &KeyWrite,
+        written_keys: &[Key],
+    ) -> Result<(), FatalStorageError> {
+        let mut txn = self.env.begin_rw_txn()?;
+        self.put_key_history(&mut txn, key_write, written_keys)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Records the keys written by the block described by `key_write` within the given
+    /// transaction, if the key history index is enabled.
+    fn put_key_history(
+        &self,
+        txn: &mut RwTransaction,
+        key_write: &KeyWrite,
+        written_keys: &[Key],
+    ) -> Result<(), FatalStorageError> {
+        if !self.enable_key_history_index {
+            return Ok(());
+        }
+        for key in written_keys {
+            let mut db_key = key
+                .to_bytes()
+                .map_err(FatalStorageError::UnexpectedSerializationFailure)?;
+            db_key.extend_from_slice(&key_write.block_height.to_be_bytes());
+            let _ = txn.put_value(self.key_history_db, &db_key, key_write, true)?;
+        }
+        Ok(())
+    }
+
+    /// Retrieves the most recent blocks below `before_height` which wrote to `key`, newest first,
+    /// or `None` if the key history index is disabled.
+    ///
+    /// Only blocks executed by this node while the index was enabled are known.  Writes by blocks
+    /// which were since removed from storage, e.g. by a hard reset, are omitted.
+    pub(crate) fn read_key_history(
+        &self,
+        key: &Key,
+        before_height: Option<u64>,
+    ) -> Result<Option<Vec<KeyWrite>>, FatalStorageError> {
+        if !self.enable_key_history_index {
+            return Ok(None);
+        }
+        let prefix = key
+            .to_bytes()
+            .map_err(FatalStorageError::UnexpectedSerializationFailure)?;
+        let mut seek_key = prefix.clone();
+        seek_key.extend_from_slice(&before_height.unwrap_or(u64::MAX).to_be_bytes());
+        let txn = self.env.begin_ro_txn()?;
+        let mut writes = Vec::new();
+        {
+            let cursor = txn.open_ro_cursor(self.key_history_db)?;
+            // Entries are ordered by height under each key, so the newest write below
+            // `before_height` is the last entry before the seek key.
+            let mut row = match cursor.get(Some(seek_key.as_slice()), None, MDB_SET_RANGE) {
+                Ok(_) => cursor.get(None, None, MDB_PREV),
+                Err(lmdb::Error::NotFound) => cursor.get(None, None, MDB_LAST),
+                Err(error) => Err(error),
+            };
+            while writes.len() < MAX_KEY_HISTORY_WRITES {
+                let (db_key, raw_val) = match row {
+                    Ok((Some(db_key), raw_val)) => (db_key, raw_val),
+                    Ok((None, _)) | Err(lmdb::Error::NotFound) => break,
+                    Err(error) => return Err(error.into()),
+                };
+                if !db_key.starts_with(&prefix) {
+                    break;
+                }
+                let key_write: KeyWrite = lmdb_ext::deserialize(raw_val)?;
+                if self.block_height_index.get(&key_write.block_height)
+                    == Some(&key_write.block_hash)
+                {
+                    writes.push(key_write);
+                }
+                row = cursor.get(None, None, MDB_PREV);
+            }
+        }
+        txn.commit()?;
+        Ok(Some(writes))
+    }
+
+    /// Retrieves the hashes and heights of the blocks in `range` created by `proposer`, ordered
+    /// by height.
+    pub(crate) fn read_blocks_by_proposer(
+        &self,
+        proposer: &PublicKey,
+        range: ProposerBlockRange,
+    ) -> Vec<BlockHashAndHeight> {
+        self.proposer_index
+            .get(proposer)
+            .into_iter()
+            .flatten()
+            .filter(|(height, era_id)| range.contains(**era_id, **height))
+            .filter_map(|(height, _)| {
+                self.block_height_index
+                    .get(height)
+                    .map(|block_hash| BlockHashAndHeight::new(*block_hash, *height))
+            })
+            .collect()
+    }
+
+    /// Retrieves the block with the given hash together with its finality signatures.
+    fn read_block_and_metadata_by_hash(
+        &self,
+        block_hash: BlockHash,
+        only_from_available_block_range: bool,
+    ) -> Result<Option<BlockWithMetadata>, FatalStorageError> {
+        let mut txn = self.env.begin_ro_txn()?;
+
+        let block: Block = if let Some(block) = self.get_single_block(&mut txn, &block_hash)? {
+            block
+        } else {
+            return Ok(None);
+        };
+
+        if !(self.should_return_block(block.height(), only_from_available_block_range)?) {
+            return Ok(None);
+        }
+
+        // Check that the hash of the block retrieved is correct.
+        if block_hash != *block.hash() {
+            error!(
+                queried_block_hash = ?block_hash,
+                actual_block_hash = ?block.hash(),
+                "block not stored under hash"
+            );
+            debug_assert_eq!(&block_hash, block.hash());
+            return Ok(None);
+        }
+        let block_signatures = match self.get_block_signatures(&mut txn, &block_hash)? {
+            Some(signatures) => signatures,
+            None => BlockSignatures::new(block_hash, block.header().era_id()),
+        };
+        if block_signatures.verify().is_err() {
+            error!(?block, "invalid block signatures for block");
+            debug_assert!(block_signatures.verify().is_ok());
+            return Ok(None);
+        }
+        Ok(Some(BlockWithMetadata {
+            block,
+            block_signatures,
+        }))
+    }
+
+    /// Retrieves the heights and state root hashes of the blocks with heights between `low` and
+    /// `high`, inclusive, ordered by height.  Heights outside the available block range are
+    /// skipped.
+    pub(crate) fn read_state_root_hash_range(
+        &self,
+        low: u64,
+        high: u64,
+    ) -> Result<Vec<(u64, Digest)>, FatalStorageError> {
+        let available_block_range = self.get_available_block_range();
+        let mut txn = self.env.begin_ro_txn()?;
+        let mut state_root_hashes = Vec::new();
+        for (height, block_hash) in self.block_height_index.range(low..=high) {
+            if !available_block_range.contains(*height) {
+                continue;
+            }
+            if let Some(block_header) = self.get_single_block_header(&mut txn, block_hash)? {
+                state_root_hashes.push((*height, *block_header.state_root_hash()));
+            }
+        }
+        Ok(state_root_hashes)
+    }
+
     /// Retrieves the highest block header from the storage, if one exists.
     pub fn read_highest_block_height(&self) -> Option<u64> {
         self.block_height_index.keys().last().copied()
@@ -1580,6 +2246,15 @@ impl Storage {
         self.get_single_block_header(&mut txn, block_hash)
     }
 
+    /// Retrieves the header of the switch block concluding the given era.
+    pub(crate) fn read_switch_block_header_by_era_id(
+        &self,
+        era_id: EraId,
+    ) -> Result<Option<BlockHeader>, FatalStorageError> {
+        let mut txn = self.env.begin_ro_txn()?;
+        self.get_switch_block_header_by_era_id(&mut txn, era_id)
+    }
+
     /// Retrieves single block by height by looking it up in the index and returning it.
     pub fn read_block_by_height(&self, height: u64) -> Result<Option<Block>, FatalStorageError> {
         self.get_block_by_height(&mut self.env.begin_ro_txn()?, height)
@@ -2152,7 +2827,109 @@ impl Storage {
         txn: &mut Tx,
         block_hash: &BlockHash,
     ) -> Result<Option<BlockSignatures>, FatalStorageError> {
-        Ok(txn.get_value(self.block_metadata_db, block_hash)?)
+        if let Some(block_signatures) = txn.get_value(self.block_metadata_db, block_hash)? {
+            return Ok(Some(block_signatures));
+        }
+        let aggregate: BlockSignaturesAggregate =
+            match txn.get_value(self.block_signatures_aggregate_db, block_hash)? {
+                Some(aggregate) => aggregate,
+                None => return Ok(None),
+            };
+        let era_id = aggregate.era_id();
+        let maybe_block_signatures = self
+            .get_era_validator_weights(txn, era_id)?
+            .and_then(|validator_weights| aggregate.expand(&validator_weights));
+        if maybe_block_signatures.is_none() {
+            warn!(
+                %block_hash,
+                %era_id,
+                "validators of era unknown or changed, can't expand aggregated block signatures"
+            );
+        }
+        Ok(maybe_block_signatures)
+    }
+
+    /// Stores block signatures, replacing any already stored for the same block.
+    ///
+    /// Signatures sufficient to make the block final are stored as an aggregate, which requires
+    /// the switch block of the preceding era to be stored.
+    fn write_block_signatures(
+        &self,
+        txn: &mut RwTransaction,
+        block_signatures: &BlockSignatures,
+    ) -> Result<bool, FatalStorageError> {
+        let block_hash = &block_signatures.block_hash;
+        let maybe_aggregate = self
+            .get_era_validator_weights(txn, block_signatures.era_id)?
+            .filter(|validator_weights| {
+                utils::check_sufficient_block_signatures(
+                    validator_weights,
+                    self.fault_tolerance_fraction,
+                    Some(block_signatures),
+                )
+                .is_ok()
+            })
+            .and_then(|validator_weights| {
+                BlockSignaturesAggregate::new(block_signatures, &validator_weights)
+            });
+        match maybe_aggregate {
+            Some(aggregate) => {
+                delete_if_present(txn, self.block_metadata_db, block_hash)?;
+                Ok(txn.put_value(
+                    self.block_signatures_aggregate_db,
+                    block_hash,
+                    &aggregate,
+                    true,
+                )?)
+            }
+            None => {
+                // An aggregate stored before would otherwise outlive the signatures replacing it.
+                delete_if_present(txn, self.block_signatures_aggregate_db, block_hash)?;
+                Ok(txn.put_value(self.block_metadata_db, block_hash, block_signatures, true)?)
+            }
+        }
+    }
+
+    /// Retrieves the validator weights of the given era from the switch block of the era before.
+    ///
+    /// Returns `None` for the genesis era, whose validators aren't recorded in any block.  The
+    /// weights of the most recent eras are cached, as expanding or aggregating the signatures of
+    /// every block would otherwise read and parse its era's switch block.
+    fn get_era_validator_weights<Tx: Transaction>(
+        &self,
+        txn: &mut Tx,
+        era_id: EraId,
+    ) -> Result<Option<ValidatorWeights>, FatalStorageError> {
+        let previous_era_id = match era_id.predecessor() {
+            Some(previous_era_id) => previous_era_id,
+            None => return Ok(None),
+        };
+        let switch_block_hash = match self.switch_block_era_id_index.get(&previous_era_id) {
+            Some(switch_block_hash) => *switch_block_hash,
+            None => return Ok(None),
+        };
+        if let Some((cached_hash, validator_weights)) =
+            self.era_validator_weights_cache.borrow().get(&era_id)
+        {
+            if *cached_hash == switch_block_hash {
+                return Ok(Some(Rc::clone(validator_weights)));
+            }
+        }
+        let maybe_validator_weights = self
+            .get_single_block_header(txn, &switch_block_hash)?
+            .and_then(|switch_block_header| {
+                switch_block_header.next_era_validator_weights().cloned()
+            })
+            .map(Rc::new);
+        if let Some(validator_weights) = &maybe_validator_weights {
+            let mut cache = self.era_validator_weights_cache.borrow_mut();
+            cache.insert(era_id, (switch_block_hash, Rc::clone(validator_weights)));
+            while cache.len() > ERA_VALIDATOR_WEIGHTS_CACHE_SIZE {
+                let oldest_era_id = *cache.keys().next().expect("cache can't be empty");
+                cache.remove(&oldest_era_id);
+            }
+        }
+        Ok(maybe_validator_weights)
     }
 
     /// Retrieves a finality signature for a block with a given block hash.
@@ -2162,8 +2939,7 @@ impl Storage {
         block_hash: &BlockHash,
         public_key: &PublicKey,
     ) -> Result<Option<FinalitySignature>, FatalStorageError> {
-        let maybe_signatures: Option<BlockSignatures> =
-            txn.get_value(self.block_metadata_db, block_hash)?;
+        let maybe_signatures = self.get_block_signatures(txn, block_hash)?;
         Ok(maybe_signatures.and_then(|signatures| signatures.get_finality_signature(public_key)))
     }
 
@@ -2231,6 +3007,98 @@ impl Storage {
             .and_then(|mut txn| txn.get_value(self.deploy_db, &deploy_hash))
     }
 
+    /// Retrieves all the approvals known for a deploy: those of the stored deploy, if any, and
+    /// those collected for it, unless it has expired.
+    fn get_deploy_approvals(
+        &self,
+        deploy_hash: &DeployHash,
+    ) -> Result<Option<DeployApprovals>, LmdbExtError> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let mut approvals =
+            self.read_collected_approvals(&mut txn, deploy_hash, Timestamp::now())?;
+        if let Some(deploy) = txn.get_value::<_, Deploy>(self.deploy_db, deploy_hash)? {
+            approvals.extend(deploy.approvals().iter().cloned());
+        }
+        if approvals.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(DeployApprovals::new(*deploy_hash, approvals)))
+    }
+
+    /// Reads the approvals collected for a deploy, treating them as absent once it has expired.
+    fn read_collected_approvals<Tx: Transaction>(
+        &self,
+        txn: &mut Tx,
+        deploy_hash: &DeployHash,
+        now: Timestamp,
+    ) -> Result<BTreeSet<Approval>, LmdbExtError> {
+        Ok(txn
+            .get_value::<_, CollectedApprovals>(self.deploy_approvals_db, deploy_hash)?
+            .filter(|collected| collected.expires >= now)
+            .map(|collected| collected.approvals)
+            .unwrap_or_default())
+    }
+
+    /// Adds `approvals` to those collected for a deploy expiring at `expires`, returning all the
+    /// approvals known for it.
+    ///
+    /// At most one approval per signer is kept, and no more than `max_approvals` in total.  Once
+    /// the deploy is stored, its approvals can no longer change, so none are collected.  The
+    /// approvals collected for deploys which have expired in the meantime are discarded.
+    fn put_deploy_approvals(
+        &self,
+        deploy_hash: &DeployHash,
+        expires: Timestamp,
+        max_approvals: usize,
+        approvals: BTreeSet<Approval>,
+    ) -> Result<BTreeSet<Approval>, FatalStorageError> {
+        let now = Timestamp::now();
+        let mut txn = self.env.begin_rw_txn()?;
+        self.delete_expired_approvals(&mut txn, now)?;
+        if let Some(deploy) = txn.get_value::<_, Deploy>(self.deploy_db, deploy_hash)? {
+            txn.commit()?;
+            return Ok(deploy.approvals().clone());
+        }
+        let mut collected = CollectedApprovals {
+            expires,
+            approvals: self.read_collected_approvals(&mut txn, deploy_hash, now)?,
+        };
+        for approval in approvals {
+            if collected.approvals.len() >= max_approvals {
+                break;
+            }
+            if !collected
+                .approvals
+                .iter()
+                .any(|known| known.signer() == approval.signer())
+            {
+                collected.approvals.insert(approval);
+            }
+        }
+        if expires >= now {
+            let _ = txn.put_value(self.deploy_approvals_db, deploy_hash, &collected, true)?;
+        }
+        txn.commit()?;
+        Ok(collected.approvals)
+    }
+
+    /// Deletes the approvals collected for deploys which have expired.
+    fn delete_expired_approvals(
+        &self,
+        txn: &mut RwTransaction,
+        now: Timestamp,
+    ) -> Result<(), LmdbExtError> {
+        let mut cursor = txn.open_rw_cursor(self.deploy_approvals_db)?;
+        for row in cursor.iter() {
+            let (_raw_key, raw_val) = row?;
+            let collected: CollectedApprovals = lmdb_ext::deserialize(raw_val)?;
+            if collected.expires < now {
+                cursor.del(WriteFlags::empty())?;
+            }
+        }
+        Ok(())
+    }
+
     /// Retrieves a deploy from the deploy store by deploy ID.
     fn get_deploy(&self, deploy_id: DeployId) -> Result<Option<Deploy>, LmdbExtError> {
         let mut txn = self.env.begin_ro_txn()?;
@@ -2466,6 +3334,18 @@ impl Storage {
         Ok(Some(execution_results))
     }
 
+    /// Retrieves the execution results of the deploys of a block, in the order of the block's
+    /// deploys and transfers.
+    ///
+    /// Returns `None` if the block or the execution result of any of its deploys is not stored.
+    pub(crate) fn read_block_execution_results(
+        &self,
+        block_hash: &BlockHash,
+    ) -> Result<Option<Vec<(DeployHash, ExecutionResult)>>, FatalStorageError> {
+        let mut txn = self.env.begin_ro_txn()?;
+        self.get_execution_results(&mut txn, block_hash)
+    }
+
     #[allow(clippy::type_complexity)]
     fn read_execution_results(
         &self,
@@ -2523,6 +3403,34 @@ impl Storage {
         Ok(Some(request.response(value_or_chunk)))
     }
 
+    /// Reads the execution result of a deploy in a block, proving it against the execution results
+    /// of all the deploys of the block.
+    fn read_deploy_execution_result(
+        &self,
+        id: &DeployExecutionResultId,
+    ) -> Result<Option<DeployExecutionResult>, FatalStorageError> {
+        let mut txn = self.env.begin_ro_txn()?;
+        let execution_results = match self.get_execution_results(&mut txn, id.block_hash())? {
+            Some(execution_results) => execution_results,
+            None => return Ok(None),
+        };
+        let index = match execution_results
+            .iter()
+            .position(|(deploy_hash, _)| deploy_hash == id.deploy_hash())
+        {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        match DeployExecutionResult::new(*id.block_hash(), &execution_results, index) {
+            Ok(item) => Ok(Some(item)),
+            Err(error) => {
+                // As with chunked execution results, this only prevents answering this query.
+                error!(%id, %error, "failed to construct `DeployExecutionResult`");
+                Ok(None)
+            }
+        }
+    }
+
     fn update_chain_height_metrics(&self) {
         if let Some(metrics) = self.metrics.as_ref() {
             if let Some(sequence) = self.completed_blocks.highest_sequence() {
@@ -2614,6 +3522,29 @@ fn insert_to_deploy_index(
     Ok(())
 }
 
+/// Returns the prefix shared by the deploy tag index keys of all deploys tagged with `user_tag`.
+///
+/// The tag is length-prefixed, so that no tag's keys share a prefix with a longer tag's keys.
+fn deploy_tag_prefix(user_tag: &Bytes) -> Vec<u8> {
+    let mut prefix = Vec::with_capacity(1 + user_tag.len() + Digest::LENGTH);
+    // Tags are at most `MAX_USER_TAG_LENGTH` bytes long, so their length fits into a byte.
+    prefix.push(user_tag.len() as u8);
+    prefix.extend_from_slice(user_tag.as_ref());
+    prefix
+}
+
+/// Inserts the block's height and era ID into the index of blocks by proposer.
+fn insert_to_proposer_index(
+    proposer_index: &mut BTreeMap<PublicKey, BTreeMap<u64, EraId>>,
+    block_header: &BlockHeader,
+    block_body: &BlockBody,
+) {
+    let _ = proposer_index
+        .entry(block_body.proposer().clone())
+        .or_default()
+        .insert(block_header.height(), block_header.era_id());
+}
+
 fn should_move_storage_files_to_network_subdir(
     root: &Path,
     file_names: &[&str],
@@ -2699,8 +3630,37 @@ pub struct Config {
     max_state_store_size: usize,
     /// Whether or not memory deduplication is enabled.
     enable_mem_deduplication: bool,
+    /// Whether or not to tag a snapshot of the chain at every switch block, which it can later be
+    /// reset to.
+    enable_era_snapshots: bool,
+    /// Whether or not to index the blocks writing to each global state key.
+    enable_key_history_index: bool,
     /// How many loads before memory duplication checks for dead references.
     mem_pool_prune_interval: u16,
+    /// Tuning of the LMDB environment holding the storage component's databases.
+    lmdb: Option<LmdbConfig>,
+}
+
+/// Tuning of the storage component's LMDB environment, trading durability or read latency
+/// against throughput.
+///
+/// Unlike the global state store, the storage component's memory map is sized up front by the
+/// `max_*_store_size` settings, so it has no growth step.
+#[derive(Clone, Copy, DataSize, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LmdbConfig {
+    /// How commits are made durable: `full`, `manual`, `no_meta_sync` or `no_sync`.
+    ///
+    /// `manual` syncs whenever a block is stored as complete; a crash of the OS between syncs can
+    /// lose the blocks since and corrupt the store.  The other modes behave as for the global
+    /// state store.
+    ///
+    /// Defaults to `full`.
+    sync_mode: Option<LmdbSyncMode>,
+    /// Enable the OS reading ahead of the pages accessed.
+    ///
+    /// Defaults to `false`.
+    readahead: Option<bool>,
 }
 
 impl Default for Config {
@@ -2713,7 +3673,10 @@ impl Default for Config {
             max_deploy_metadata_store_size: DEFAULT_MAX_DEPLOY_METADATA_STORE_SIZE,
             max_state_store_size: DEFAULT_MAX_STATE_STORE_SIZE,
             enable_mem_deduplication: true,
+            enable_era_snapshots: false,
+            enable_key_history_index: false,
             mem_pool_prune_interval: 4096,
+            lmdb: None,
         }
     }
 }
@@ -2855,8 +3818,8 @@ impl Storage {
             .env
             .begin_ro_txn()
             .expect("could not create RO transaction");
-        let res = txn
-            .get_value(self.block_metadata_db, &block_hash)
+        let res = self
+            .get_block_signatures(&mut txn, &block_hash)
             .expect("could not retrieve value from storage");
         txn.commit().expect("Could not commit transaction");
         res
@@ -2922,6 +3885,18 @@ fn get_body_for_block_header<Tx: Transaction>(
     txn.get_value(block_body_db, block_body_hash)
 }
 
+/// Deletes the entry under `key`, if any.
+fn delete_if_present<K: AsRef<[u8]>>(
+    txn: &mut RwTransaction,
+    db: Database,
+    key: &K,
+) -> Result<(), lmdb::Error> {
+    match txn.del(db, key, None) {
+        Ok(()) | Err(lmdb::Error::NotFound) => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
 /// Purges stale entries from the block metadata database.
 fn initialize_block_metadata_db(
     env: &Environment,
@@ -2947,6 +3922,66 @@ fn initialize_block_metadata_db(
     Ok(())
 }
 
+/// Purges stale entries from the block signatures aggregate database.
+///
+/// Aggregates of blocks in the eras of `orphaned_validator_weights`, whose preceding switch block
+/// has been deleted, can't be expanded once the node is running, so they are moved back to the
+/// block metadata database as individual signatures.
+fn initialize_block_signatures_aggregate_db(
+    env: &Environment,
+    block_signatures_aggregate_db: &Database,
+    block_metadata_db: &Database,
+    deleted_block_hashes: &HashSet<&[u8]>,
+    orphaned_validator_weights: &BTreeMap<EraId, BTreeMap<PublicKey, U512>>,
+) -> Result<(), FatalStorageError> {
+    info!("initializing block signatures aggregate database");
+    let mut txn = env.begin_rw_txn()?;
+    let mut expanded = Vec::new();
+    let mut cursor = txn.open_rw_cursor(*block_signatures_aggregate_db)?;
+
+    for row in cursor.iter() {
+        let (raw_key, raw_val) = row?;
+        if deleted_block_hashes.contains(raw_key) {
+            cursor.del(WriteFlags::empty())?;
+            continue;
+        }
+        if orphaned_validator_weights.is_empty() {
+            continue;
+        }
+        let aggregate: BlockSignaturesAggregate = lmdb_ext::deserialize(raw_val)?;
+        let era_id = aggregate.era_id();
+        if let Some(validator_weights) = orphaned_validator_weights.get(&era_id) {
+            match aggregate.expand(validator_weights) {
+                Some(block_signatures) => {
+                    expanded.push(block_signatures);
+                    cursor.del(WriteFlags::empty())?;
+                }
+                None => warn!(
+                    %era_id,
+                    "validators of era changed, can't expand aggregated block signatures"
+                ),
+            }
+        }
+    }
+
+    drop(cursor);
+    for block_signatures in &expanded {
+        let _ = txn.put_value(
+            *block_metadata_db,
+            &block_signatures.block_hash,
+            block_signatures,
+            true,
+        )?;
+    }
+    txn.commit()?;
+
+    info!(
+        expanded_aggregates = expanded.len(),
+        "block signatures aggregate database initialized"
+    );
+    Ok(())
+}
+
 /// Purges stale entries from the deploy metadata database.
 fn initialize_deploy_metadata_db(
     env: &Environment,
@@ -0,0 +1,78 @@
+//! Offline compaction of the block store.
+//!
+//! Copies the storage database into a fresh LMDB environment, leaving behind the free pages
+//! accumulated by deletions and overwrites.
+
+use std::{fs, path::Path};
+
+use lmdb::{Cursor, Environment, EnvironmentFlags, Transaction};
+use tracing::info;
+
+use casper_execution_engine::storage::transaction_source::lmdb::copy_environment;
+use casper_hashing::Digest;
+
+use super::{lmdb_ext, Config, FatalStorageError, MAX_DB_COUNT, STORAGE_DB_FILENAME};
+use crate::{types::BlockHeader, utils::WithDir};
+
+/// Writes a compacted copy of the block store to `output_dir`.
+///
+/// The source database is opened read-only, so the node must not be running while this is
+/// called. Returns the state root hashes of all stored block headers, i.e. the global state
+/// roots which need to be retained by a compacted copy of the trie store.
+pub(crate) fn compact_block_store(
+    cfg: &WithDir<Config>,
+    network_name: &str,
+    output_dir: &Path,
+) -> Result<Vec<Digest>, FatalStorageError> {
+    let config = cfg.value();
+    let source_path = cfg
+        .with_dir(config.path.clone())
+        .join(network_name)
+        .join(STORAGE_DB_FILENAME);
+    let target_path = output_dir.join(STORAGE_DB_FILENAME);
+
+    fs::create_dir_all(output_dir)
+        .map_err(|err| FatalStorageError::CreateDatabaseDirectory(output_dir.to_path_buf(), err))?;
+
+    let total_size = config
+        .max_block_store_size
+        .saturating_add(config.max_deploy_store_size)
+        .saturating_add(config.max_deploy_metadata_store_size);
+
+    let env = Environment::new()
+        .set_flags(
+            EnvironmentFlags::NO_SUB_DIR
+                | EnvironmentFlags::NO_TLS
+                | EnvironmentFlags::NO_READAHEAD
+                | EnvironmentFlags::READ_ONLY,
+        )
+        .set_max_dbs(MAX_DB_COUNT)
+        .set_map_size(total_size)
+        .open(&source_path)?;
+
+    info!(source = %source_path.display(), target = %target_path.display(), "compacting block store");
+    copy_environment(&env, &target_path, true)?;
+
+    let block_header_db = env.open_db(Some("block_header"))?;
+    let txn = env.begin_ro_txn()?;
+    let mut state_root_hashes = Vec::new();
+    {
+        let mut cursor = txn.open_ro_cursor(block_header_db)?;
+        // Note: `iter_start` has an undocumented panic if called on an empty database. We rely on
+        //       the iterator being at the start when created.
+        for row in cursor.iter() {
+            let (_, raw_val) = row?;
+            let block_header: BlockHeader = lmdb_ext::deserialize(raw_val)?;
+            state_root_hashes.push(*block_header.state_root_hash());
+        }
+    }
+    txn.commit()?;
+
+    state_root_hashes.sort_unstable();
+    state_root_hashes.dedup();
+    info!(
+        state_root_count = state_root_hashes.len(),
+        "finished compacting block store"
+    );
+    Ok(state_root_hashes)
+}
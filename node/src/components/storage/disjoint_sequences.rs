@@ -89,8 +89,7 @@ impl Sequence {
 ///
 /// For example, if `sequences` contains `[9,9], [7,3]` and `8` is inserted, then `sequences` will
 /// be reduced to `[9,3]`.
-#[derive(Default, Debug, DataSize)]
-#[cfg_attr(test, derive(Clone))]
+#[derive(Clone, Default, Debug, DataSize)]
 pub(super) struct DisjointSequences {
     sequences: Vec<Sequence>,
 }
@@ -0,0 +1,63 @@
+//! Snapshots of the chain at era boundaries.
+//!
+//! If enabled in the storage config, a snapshot is tagged whenever a switch block is marked
+//! complete, i.e. once its global state is available. A snapshot records nothing but the switch
+//! block and its state root: global state is never pruned, so the chain can be rolled back to a
+//! snapshot by deleting the blocks above it. This is meant for development networks and consortium
+//! chains recovering from a bad upgrade, never for public networks.
+
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use casper_hashing::Digest;
+use casper_types::{EraId, Timestamp};
+
+use crate::types::{BlockHash, BlockHeader};
+
+/// The chain as of the end of an era.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct EraSnapshot {
+    /// The era concluded by the switch block.
+    pub(crate) era_id: EraId,
+    /// The height of the switch block.
+    pub(crate) block_height: u64,
+    /// The hash of the switch block.
+    pub(crate) block_hash: BlockHash,
+    /// The global state root hash after executing the switch block.
+    pub(crate) state_root_hash: Digest,
+    /// The timestamp of the switch block.
+    pub(crate) timestamp: Timestamp,
+}
+
+impl EraSnapshot {
+    /// Creates a snapshot of the chain as of the given switch block.
+    pub(super) fn new(switch_block_header: &BlockHeader) -> Self {
+        EraSnapshot {
+            era_id: switch_block_header.era_id(),
+            block_height: switch_block_header.height(),
+            block_hash: switch_block_header.block_hash(),
+            state_root_hash: *switch_block_header.state_root_hash(),
+            timestamp: switch_block_header.timestamp(),
+        }
+    }
+
+    /// Returns the name the snapshot is tagged with, e.g. `era-12` for the end of era 12.
+    pub(crate) fn name(&self) -> String {
+        format!("era-{}", self.era_id.value())
+    }
+}
+
+impl Display for EraSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: block {} at height {}, state root {}, {}",
+            self.name(),
+            self.block_hash,
+            self.block_height,
+            self.state_root_hash,
+            self.timestamp
+        )
+    }
+}
@@ -118,6 +118,12 @@ pub enum FatalStorageError {
         /// The block hash of the signatures found in the index.
         block_hash_bytes: Vec<u8>,
     },
+    /// Corrupted deploy tag index.
+    #[error("deploy tag index key doesn't end in a deploy hash. Key bytes in LMDB: {db_key:x?}")]
+    CorruptedDeployTagIndex {
+        /// The key in the deploy tag index.
+        db_key: Vec<u8>,
+    },
     /// Switch block does not contain era end.
     #[error("switch block does not contain era end: {0:?}")]
     InvalidSwitchBlock(Box<BlockHeader>),
@@ -0,0 +1,139 @@
+//! Compact storage of the finality signatures of finalized blocks.
+//!
+//! Until a block's signatures carry enough weight to make it final, they are stored as a
+//! [`BlockSignatures`], keyed by the signers' public keys.  Once they do, the validator set of the
+//! block's era is known from the preceding switch block, so storage replaces them with a
+//! [`BlockSignaturesAggregate`]: a bitmap of which validators of the era signed, along with their
+//! signatures.  Signatures can't be combined into a single one, but every public key, i.e. most of
+//! the size of each proof, is dropped from the record.
+//!
+//! An aggregate can only be expanded while the switch block it depends on is stored.  Truncating
+//! the chain deletes that switch block only along with every later block, whereas a hard reset may
+//! keep blocks of the following era, so their aggregates are expanded again when the switch block
+//! is deleted.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use casper_types::{crypto::Signature, EraId, PublicKey, U512};
+
+use crate::types::{BlockHash, BlockSignatures};
+
+/// The signatures of a final block, with the signers given by their position in the era's
+/// validator set.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct BlockSignaturesAggregate {
+    block_hash: BlockHash,
+    era_id: EraId,
+    /// Bit `i % 8` of byte `i / 8` is set if the `i`-th validator of the era, in public key
+    /// order, signed.
+    signers: Vec<u8>,
+    /// The signatures of the signers, in public key order.
+    signatures: Vec<Signature>,
+}
+
+impl BlockSignaturesAggregate {
+    /// Aggregates `block_signatures`, or returns `None` if any of the signers isn't one of
+    /// `validator_weights`.
+    pub(super) fn new(
+        block_signatures: &BlockSignatures,
+        validator_weights: &BTreeMap<PublicKey, U512>,
+    ) -> Option<Self> {
+        let mut signers = vec![0; (validator_weights.len() + 7) / 8];
+        let mut signatures = Vec::with_capacity(block_signatures.proofs.len());
+        for (index, public_key) in validator_weights.keys().enumerate() {
+            if let Some(signature) = block_signatures.proofs.get(public_key) {
+                signers[index / 8] |= 1 << (index % 8);
+                signatures.push(*signature);
+            }
+        }
+        if signatures.len() != block_signatures.proofs.len() {
+            return None;
+        }
+        Some(BlockSignaturesAggregate {
+            block_hash: block_signatures.block_hash,
+            era_id: block_signatures.era_id,
+            signers,
+            signatures,
+        })
+    }
+
+    /// Returns the era of the signed block.
+    pub(super) fn era_id(&self) -> EraId {
+        self.era_id
+    }
+
+    /// Restores the individual signatures, or returns `None` if `validator_weights` isn't the
+    /// validator set the signatures were aggregated with.
+    pub(super) fn expand(
+        self,
+        validator_weights: &BTreeMap<PublicKey, U512>,
+    ) -> Option<BlockSignatures> {
+        if self.signers.len() != (validator_weights.len() + 7) / 8 {
+            return None;
+        }
+        let signer_keys: Vec<&PublicKey> = validator_weights
+            .keys()
+            .enumerate()
+            .filter(|(index, _)| self.signers[index / 8] & (1 << (index % 8)) != 0)
+            .map(|(_, public_key)| public_key)
+            .collect();
+        if signer_keys.len() != self.signatures.len() {
+            return None;
+        }
+        let mut block_signatures = BlockSignatures::new(self.block_hash, self.era_id);
+        for (public_key, signature) in signer_keys.into_iter().zip(self.signatures) {
+            block_signatures.insert_proof(public_key.clone(), signature);
+        }
+        Some(block_signatures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{crypto, testing::TestRng, SecretKey};
+
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_and_reject_other_validator_sets() {
+        let mut rng = TestRng::new();
+        let keys: BTreeMap<PublicKey, SecretKey> = (0..10)
+            .map(|_| {
+                let secret_key = SecretKey::random(&mut rng);
+                (PublicKey::from(&secret_key), secret_key)
+            })
+            .collect();
+        let validator_weights: BTreeMap<PublicKey, U512> = keys
+            .keys()
+            .map(|public_key| (public_key.clone(), U512::from(100)))
+            .collect();
+
+        let mut block_signatures = BlockSignatures::new(BlockHash::random(&mut rng), EraId::new(3));
+        for (public_key, secret_key) in keys.iter().step_by(3) {
+            let signature = crypto::sign(b"block", secret_key, public_key);
+            block_signatures.insert_proof(public_key.clone(), signature);
+        }
+
+        let aggregate = BlockSignaturesAggregate::new(&block_signatures, &validator_weights)
+            .expect("should aggregate");
+        assert_eq!(aggregate.era_id(), EraId::new(3));
+        assert_eq!(
+            aggregate.clone().expand(&validator_weights),
+            Some(block_signatures.clone())
+        );
+
+        // The first and last validators both signed.
+        let mut fewer_validators = validator_weights.clone();
+        fewer_validators.remove(keys.keys().next().unwrap());
+        fewer_validators.remove(keys.keys().last().unwrap());
+        assert_eq!(aggregate.expand(&fewer_validators), None);
+
+        // A signature by a non-validator can't be aggregated.
+        assert_eq!(
+            BlockSignaturesAggregate::new(&block_signatures, &fewer_validators),
+            None
+        );
+    }
+}
@@ -4,23 +4,32 @@ use std::{
     collections::{BTreeMap, HashMap},
     fs::{self, File},
     iter,
+    ops::Range,
     rc::Rc,
     sync::Arc,
 };
 
+use lmdb::Transaction;
 use num_rational::Ratio;
 use rand::{prelude::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
 use smallvec::smallvec;
 
+use casper_execution_engine::{
+    core::engine_state::execution_results_proof::execution_results_merkle_root,
+    storage::transaction_source::lmdb::LmdbSyncMode,
+};
+use casper_hashing::Digest;
 use casper_types::{
-    generate_ed25519_keypair, system::auction::UnbondingPurse, testing::TestRng, AccessRights,
-    EraId, ExecutionResult, ProtocolVersion, PublicKey, SecretKey, TimeDiff, URef, U512,
+    bytesrepr::Bytes, generate_ed25519_keypair, system::auction::UnbondingPurse, testing::TestRng,
+    AccessRights, EraId, ExecutionResult, Key, ProtocolVersion, PublicKey, SecretKey, TimeDiff,
+    Timestamp, URef, U512,
 };
 
 use super::{
-    move_storage_files_to_network_subdir, should_move_storage_files_to_network_subdir, Config,
-    Storage,
+    compact_block_store, move_storage_files_to_network_subdir,
+    should_move_storage_files_to_network_subdir, Config, EraSnapshot, LmdbConfig, Storage,
+    ERA_VALIDATOR_WEIGHTS_CACHE_SIZE, MAX_DEPLOYS_BY_TAG,
 };
 use crate::{
     components::fetcher::{FetchItem, FetchResponse},
@@ -28,16 +37,19 @@ use crate::{
         requests::{BlockCompleteConfirmationRequest, StorageRequest},
         Multiple,
     },
+    rpcs::chain::ProposerBlockRange,
     storage::{
         lmdb_ext::{deserialize_internal, serialize_internal},
         FORCE_RESYNC_FILE_NAME,
     },
     testing::{ComponentHarness, UnitTestEvent},
     types::{
-        AvailableBlockRange, Block, BlockHash, BlockHashAndHeight, BlockHeader,
-        BlockHeaderWithMetadata, BlockSignatures, Chainspec, ChainspecRawBytes, Deploy, DeployHash,
-        DeployMetadata, DeployMetadataExt, DeployWithFinalizedApprovals, FinalitySignature,
-        LegacyDeploy, SyncLeapIdentifier,
+        Approval, AvailableBlockRange, Block, BlockHash, BlockHashAndHeight, BlockHeader,
+        BlockHeaderWithMetadata, BlockSignatures, Chainspec, ChainspecRawBytes, Deploy,
+        DeployBuilder, DeployExecutionResult, DeployExecutionResultId,
+        DeployExecutionResultValidationMetadata, DeployHash, DeployMetadata, DeployMetadataExt,
+        DeployWithFinalizedApprovals, FinalitySignature, KeyWrite, LegacyDeploy,
+        SyncLeapIdentifier,
     },
     utils::{Loadable, WithDir},
 };
@@ -56,7 +68,10 @@ fn new_config(harness: &ComponentHarness<UnitTestEvent>) -> Config {
         max_deploy_metadata_store_size: 50 * MIB,
         max_state_store_size: 50 * MIB,
         enable_mem_deduplication: true,
+        enable_era_snapshots: true,
+        enable_key_history_index: true,
         mem_pool_prune_interval: 4,
+        lmdb: None,
     }
 }
 
@@ -1101,6 +1116,374 @@ fn persist_blocks_deploys_and_deploy_metadata_across_instantiations() {
     );
 }
 
+/// Creates a deploy carrying `user_tag`.
+fn tagged_deploy(rng: &mut TestRng, user_tag: &Bytes) -> Deploy {
+    let secret_key = SecretKey::random(rng);
+    DeployBuilder::new("casper-example", rng.gen())
+        .with_standard_payment(10)
+        .with_secret_key(&secret_key)
+        .with_user_tag(user_tag.clone())
+        .build()
+        .expect("should build deploy")
+}
+
+#[test]
+fn should_index_deploys_by_tag() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let user_tag = Bytes::from(b"deposit-0042".to_vec());
+    let first = tagged_deploy(&mut harness.rng, &user_tag);
+    let second = tagged_deploy(&mut harness.rng, &user_tag);
+    let other = tagged_deploy(&mut harness.rng, &Bytes::from(b"other".to_vec()));
+    // A tag which is a prefix of the queried tag.
+    let shorter = tagged_deploy(&mut harness.rng, &Bytes::from(b"deposit-004".to_vec()));
+    assert!(put_deploy(
+        &mut harness,
+        &mut storage,
+        Box::new(shorter.clone())
+    ));
+    assert!(put_deploy(
+        &mut harness,
+        &mut storage,
+        Box::new(first.clone())
+    ));
+    assert!(put_deploy(
+        &mut harness,
+        &mut storage,
+        Box::new(other.clone())
+    ));
+    assert!(put_deploy(
+        &mut harness,
+        &mut storage,
+        Box::new(Deploy::random(&mut harness.rng))
+    ));
+    assert!(put_deploy(
+        &mut harness,
+        &mut storage,
+        Box::new(second.clone())
+    ));
+    // Storing a deploy again must not index it twice.
+    assert!(!put_deploy(
+        &mut harness,
+        &mut storage,
+        Box::new(first.clone())
+    ));
+
+    let mut expected = vec![*first.hash(), *second.hash()];
+    expected.sort();
+    assert_eq!(
+        storage
+            .read_deploys_by_tag(&user_tag, None, MAX_DEPLOYS_BY_TAG)
+            .unwrap(),
+        expected
+    );
+    // Paging through the deploys with a limit of one.
+    assert_eq!(
+        storage.read_deploys_by_tag(&user_tag, None, 1).unwrap(),
+        vec![expected[0]]
+    );
+    assert_eq!(
+        storage
+            .read_deploys_by_tag(&user_tag, Some(expected[0]), 1)
+            .unwrap(),
+        vec![expected[1]]
+    );
+    assert!(storage
+        .read_deploys_by_tag(&user_tag, Some(expected[1]), 1)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        storage
+            .read_deploys_by_tag(&Bytes::from(b"other".to_vec()), None, MAX_DEPLOYS_BY_TAG)
+            .unwrap(),
+        vec![*other.hash()]
+    );
+    assert_eq!(
+        storage
+            .read_deploys_by_tag(
+                &Bytes::from(b"deposit-004".to_vec()),
+                None,
+                MAX_DEPLOYS_BY_TAG
+            )
+            .unwrap(),
+        vec![*shorter.hash()]
+    );
+    assert!(storage
+        .read_deploys_by_tag(&Bytes::new(), None, MAX_DEPLOYS_BY_TAG)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn should_collect_deploy_approvals_until_expiry() {
+    let mut harness = ComponentHarness::default();
+    let storage = storage_fixture(&harness);
+
+    let deploy_hash = DeployHash::random(&mut harness.rng);
+    let approvals: Vec<Approval> = (0..3)
+        .map(|_| Approval::create(&deploy_hash, &SecretKey::random(&mut harness.rng)))
+        .collect();
+    let expires = Timestamp::now() + TimeDiff::from_seconds(60);
+
+    let collected = storage
+        .put_deploy_approvals(
+            &deploy_hash,
+            expires,
+            2,
+            iter::once(approvals[0].clone()).collect(),
+        )
+        .unwrap();
+    assert_eq!(collected, iter::once(approvals[0].clone()).collect());
+
+    // No more than `max_approvals` are collected.
+    let collected = storage
+        .put_deploy_approvals(
+            &deploy_hash,
+            expires,
+            2,
+            approvals[1..].iter().cloned().collect(),
+        )
+        .unwrap();
+    assert_eq!(collected.len(), 2);
+    assert!(collected.contains(&approvals[0]));
+    let stored = storage.get_deploy_approvals(&deploy_hash).unwrap().unwrap();
+    assert_eq!(stored.approvals(), &collected);
+
+    // The approvals of an expired deploy are discarded.
+    let expired_deploy_hash = DeployHash::random(&mut harness.rng);
+    let expired_approval =
+        Approval::create(&expired_deploy_hash, &SecretKey::random(&mut harness.rng));
+    let _ = storage
+        .put_deploy_approvals(
+            &expired_deploy_hash,
+            Timestamp::now() - TimeDiff::from_seconds(1),
+            2,
+            iter::once(expired_approval).collect(),
+        )
+        .unwrap();
+    assert!(storage
+        .get_deploy_approvals(&expired_deploy_hash)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn should_index_blocks_by_proposer() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let blocks: Vec<Block> = (0..4)
+        .map(|height| {
+            Block::random_with_specifics(
+                &mut harness.rng,
+                EraId::new(height / 2),
+                height,
+                ProtocolVersion::V1_0_0,
+                false,
+                None,
+            )
+        })
+        .collect();
+    for block in &blocks {
+        put_complete_block(&mut harness, &mut storage, Arc::new(block.clone()));
+    }
+
+    let check_index = |storage: &Storage| {
+        for block in &blocks {
+            let proposer = block.body().proposer();
+            let expected = vec![BlockHashAndHeight::new(*block.hash(), block.height())];
+            assert_eq!(
+                storage.read_blocks_by_proposer(
+                    proposer,
+                    ProposerBlockRange::Era(block.header().era_id())
+                ),
+                expected
+            );
+            assert_eq!(
+                storage.read_blocks_by_proposer(
+                    proposer,
+                    ProposerBlockRange::Heights {
+                        low: block.height(),
+                        high: block.height(),
+                    }
+                ),
+                expected
+            );
+            assert!(storage
+                .read_blocks_by_proposer(
+                    proposer,
+                    ProposerBlockRange::Era(block.header().era_id().successor())
+                )
+                .is_empty());
+        }
+    };
+    check_index(&storage);
+
+    // The index should be rebuilt when the storage is reopened.
+    let (on_disk, rng) = harness.into_parts();
+    let harness = ComponentHarness::builder()
+        .on_disk(on_disk)
+        .rng(rng)
+        .build();
+    let storage = storage_fixture(&harness);
+    check_index(&storage);
+}
+
+#[test]
+fn should_read_state_root_hash_range() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let blocks: Vec<Block> = (0..4)
+        .map(|height| {
+            Block::random_with_specifics(
+                &mut harness.rng,
+                EraId::new(0),
+                height,
+                ProtocolVersion::V1_0_0,
+                false,
+                None,
+            )
+        })
+        .collect();
+    for block in &blocks {
+        put_complete_block(&mut harness, &mut storage, Arc::new(block.clone()));
+    }
+    let expected = |heights: Range<usize>| -> Vec<(u64, Digest)> {
+        blocks[heights]
+            .iter()
+            .map(|block| (block.height(), *block.header().state_root_hash()))
+            .collect()
+    };
+
+    assert_eq!(
+        storage.read_state_root_hash_range(1, 2).unwrap(),
+        expected(1..3)
+    );
+    // Heights above the highest block are skipped.
+    assert_eq!(
+        storage.read_state_root_hash_range(2, 10).unwrap(),
+        expected(2..4)
+    );
+    assert!(storage
+        .read_state_root_hash_range(5, 10)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn should_index_key_history() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let key_write = |block: &Block| KeyWrite {
+        block_hash: *block.hash(),
+        block_height: block.height(),
+        state_root_hash: *block.state_root_hash(),
+    };
+    let blocks: Vec<Block> = (0..4)
+        .map(|height| {
+            Block::random_with_specifics(
+                &mut harness.rng,
+                EraId::new(0),
+                height,
+                ProtocolVersion::V1_0_0,
+                false,
+                None,
+            )
+        })
+        .collect();
+    let key = Key::Hash([1; 32]);
+    let other_key = Key::Hash([2; 32]);
+    for block in &blocks {
+        put_complete_block(&mut harness, &mut storage, Arc::new(block.clone()));
+        let written_keys = if block.height() == 2 {
+            vec![other_key]
+        } else {
+            vec![key, other_key]
+        };
+        storage
+            .write_key_history(&key_write(block), &written_keys)
+            .unwrap();
+    }
+    // A write by a block which isn't in storage, e.g. one removed by a hard reset, is omitted.
+    let orphan = Block::random_with_specifics(
+        &mut harness.rng,
+        EraId::new(0),
+        4,
+        ProtocolVersion::V1_0_0,
+        false,
+        None,
+    );
+    storage
+        .write_key_history(&key_write(&orphan), &[key])
+        .unwrap();
+
+    let expected = |heights: &[usize]| -> Option<Vec<KeyWrite>> {
+        Some(
+            heights
+                .iter()
+                .map(|height| key_write(&blocks[*height]))
+                .collect(),
+        )
+    };
+    assert_eq!(
+        storage.read_key_history(&key, None).unwrap(),
+        expected(&[3, 1, 0])
+    );
+    assert_eq!(
+        storage.read_key_history(&key, Some(3)).unwrap(),
+        expected(&[1, 0])
+    );
+    assert_eq!(
+        storage.read_key_history(&other_key, Some(3)).unwrap(),
+        expected(&[2, 1, 0])
+    );
+    assert_eq!(
+        storage.read_key_history(&Key::Hash([3; 32]), None).unwrap(),
+        expected(&[])
+    );
+}
+
+#[test]
+fn should_put_signed_block_and_mark_it_complete_in_one_request() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let block = Arc::new(Block::random(&mut harness.rng));
+    let block_signatures = random_signatures(&mut harness.rng, &block);
+    let block_hash = *block.hash();
+    let block_height = block.height();
+
+    let cloned_block = Arc::clone(&block);
+    let cloned_signatures = block_signatures.clone();
+    let stored = harness.send_request(&mut storage, move |responder| {
+        StorageRequest::PutSignedBlock {
+            block: cloned_block,
+            block_signatures: Box::new(cloned_signatures),
+            mark_complete: true,
+            responder,
+        }
+        .into()
+    });
+    assert!(harness.is_idle());
+    assert!(stored);
+
+    assert_eq!(
+        get_block(&mut harness, &mut storage, block_hash).as_ref(),
+        Some(&*block)
+    );
+    assert_eq!(
+        get_block_signatures(&mut storage, block_hash),
+        Some(block_signatures)
+    );
+    assert_eq!(
+        storage.get_available_block_range(),
+        AvailableBlockRange::new(block_height, block_height)
+    );
+}
+
 #[test]
 fn should_hard_reset() {
     let blocks_count = 8_usize;
@@ -1225,6 +1608,68 @@ fn should_hard_reset() {
     check(0);
 }
 
+#[test]
+fn should_tag_era_snapshots_and_truncate_to_them() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    // Create and store 8 blocks, 0-2 in era 0, 3-5 in era 1, and 6,7 in era 2.
+    let blocks: Vec<Block> = (0..8)
+        .map(|height| {
+            let deploy = Deploy::random(&mut harness.rng);
+            Block::random_with_specifics(
+                &mut harness.rng,
+                EraId::from(height / 3),
+                height,
+                ProtocolVersion::V1_0_0,
+                height % 3 == 2,
+                iter::once(&deploy),
+            )
+        })
+        .collect();
+    for block in &blocks {
+        assert!(put_complete_block(
+            &mut harness,
+            &mut storage,
+            Arc::new(block.clone())
+        ));
+        let block_signatures = random_signatures(&mut harness.rng, block);
+        put_block_signatures(&mut harness, &mut storage, block_signatures);
+    }
+
+    let snapshots = storage.read_era_snapshots().unwrap();
+    let names: Vec<_> = snapshots.values().map(EraSnapshot::name).collect();
+    assert_eq!(names, vec!["era-0", "era-1"]);
+    let snapshot = snapshots[&EraId::from(0)].clone();
+    assert_eq!(snapshot.block_hash, *blocks[2].hash());
+    assert_eq!(snapshot.state_root_hash, *blocks[2].state_root_hash());
+
+    assert_eq!(
+        storage
+            .truncate_blocks_above(snapshot.block_height)
+            .unwrap(),
+        5
+    );
+    assert_eq!(
+        Some(blocks[2].clone()),
+        get_highest_complete_block(&mut harness, &mut storage)
+    );
+    for (index, block) in blocks.iter().enumerate() {
+        let should_get_block = index <= 2;
+        let result = get_block(&mut harness, &mut storage, *block.hash());
+        assert_eq!(should_get_block, result.is_some());
+        let result = get_block_signatures(&mut storage, *block.hash());
+        assert_eq!(should_get_block, result.is_some());
+    }
+    let names: Vec<_> = storage
+        .read_era_snapshots()
+        .unwrap()
+        .values()
+        .map(EraSnapshot::name)
+        .collect();
+    assert_eq!(names, vec!["era-0"]);
+}
+
 #[test]
 fn should_create_subdir_named_after_network() {
     let harness = ComponentHarness::default();
@@ -1525,6 +1970,134 @@ fn should_get_sync_leap() {
     sync_leap.validate(&Arc::new(chainspec)).unwrap();
 }
 
+#[test]
+fn should_aggregate_signatures_of_final_blocks() {
+    let (storage, _chainspec, blocks) = create_sync_leap_test_chain(&[], false, None);
+    let is_aggregated = |block: &Block| {
+        let txn = storage.env.begin_ro_txn().unwrap();
+        let in_metadata_db = txn.get(storage.block_metadata_db, block.hash()).is_ok();
+        let in_aggregate_db = txn
+            .get(storage.block_signatures_aggregate_db, block.hash())
+            .is_ok();
+        assert_ne!(in_metadata_db, in_aggregate_db);
+        in_aggregate_db
+    };
+
+    // The validators of era 0 aren't recorded in any switch block.
+    assert!(!is_aggregated(&blocks[0]));
+    for block in &blocks[1..] {
+        assert!(is_aggregated(block));
+        let block_signatures = storage
+            .read_block_signatures(block.hash())
+            .unwrap()
+            .expect("should have signatures");
+        assert_eq!(block_signatures.block_hash, *block.hash());
+        assert_eq!(block_signatures.proofs.len(), 1);
+        block_signatures.verify().unwrap();
+    }
+    // The validator weights of the most recent eras are kept for later reads.
+    let cache = storage.era_validator_weights_cache.borrow();
+    assert_eq!(cache.len(), ERA_VALIDATOR_WEIGHTS_CACHE_SIZE);
+    let highest_era_id = blocks.last().unwrap().header().era_id();
+    assert!(cache.contains_key(&highest_era_id));
+}
+
+#[test]
+fn should_replace_aggregate_with_insufficient_signatures() {
+    let (mut storage, _chainspec, blocks) = create_sync_leap_test_chain(&[], false, None);
+    let block = blocks.last().unwrap();
+    let insufficient_signatures = BlockSignatures::new(*block.hash(), block.header().era_id());
+    storage
+        .write_finality_signatures(&insufficient_signatures)
+        .unwrap();
+
+    let txn = storage.env.begin_ro_txn().unwrap();
+    assert!(txn
+        .get(storage.block_signatures_aggregate_db, block.hash())
+        .is_err());
+    drop(txn);
+    let block_signatures = storage
+        .read_block_signatures(block.hash())
+        .unwrap()
+        .expect("should have signatures");
+    assert!(block_signatures.proofs.is_empty());
+}
+
+#[test]
+fn should_expand_aggregated_signatures_when_switch_block_is_deleted() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+    let (validator_secret_key, validator_public_key) = generate_ed25519_keypair();
+    let mut validator_weights = BTreeMap::new();
+    validator_weights.insert(validator_public_key.clone(), U512::from(100));
+
+    // The switch blocks of eras 0 and 1 were created before the upgrade to 1.1.0, the block in
+    // era 2 after it.
+    let mut blocks: Vec<Block> = vec![];
+    for (height, protocol_version) in [
+        ProtocolVersion::V1_0_0,
+        ProtocolVersion::V1_0_0,
+        ProtocolVersion::from_parts(1, 1, 0),
+    ]
+    .iter()
+    .enumerate()
+    {
+        let is_switch = height < 2;
+        let block = Block::random_with_specifics_and_parent_and_validator_weights(
+            &mut harness.rng,
+            EraId::from(height as u64),
+            height as u64,
+            *protocol_version,
+            is_switch,
+            None,
+            blocks.last().map(|parent| *parent.hash()),
+            if is_switch {
+                validator_weights.clone()
+            } else {
+                BTreeMap::new()
+            },
+        );
+        storage.write_block(&block).unwrap();
+        let finality_signature = FinalitySignature::create(
+            *block.hash(),
+            block.header().era_id(),
+            &Rc::new(&validator_secret_key),
+            validator_public_key.clone(),
+        );
+        let mut block_signatures = BlockSignatures::new(*block.hash(), block.header().era_id());
+        block_signatures.insert_proof(validator_public_key.clone(), finality_signature.signature);
+        storage
+            .write_finality_signatures(&block_signatures)
+            .unwrap();
+        blocks.push(block);
+    }
+    let block = &blocks[2];
+    {
+        let txn = storage.env.begin_ro_txn().unwrap();
+        assert!(txn
+            .get(storage.block_signatures_aggregate_db, block.hash())
+            .is_ok());
+    }
+    drop(storage);
+
+    // A hard reset to era 1 deletes the switch block of era 1, but keeps the block in era 2.
+    let mut storage = storage_fixture_with_hard_reset(&harness, EraId::from(1));
+    assert!(get_block(&mut harness, &mut storage, *blocks[1].hash()).is_none());
+    assert!(get_block(&mut harness, &mut storage, *block.hash()).is_some());
+
+    let block_signatures = storage
+        .read_block_signatures(block.hash())
+        .unwrap()
+        .expect("should have signatures");
+    assert_eq!(block_signatures.block_hash, *block.hash());
+    assert_eq!(block_signatures.proofs.len(), 1);
+    block_signatures.verify().unwrap();
+    let txn = storage.env.begin_ro_txn().unwrap();
+    assert!(txn
+        .get(storage.block_signatures_aggregate_db, block.hash())
+        .is_err());
+}
+
 #[test]
 fn sync_leap_signed_block_headers_should_be_empty_when_asked_for_a_tip() {
     let (storage, chainspec, blocks) = create_sync_leap_test_chain(&[], false, None);
@@ -1675,6 +2248,53 @@ fn should_get_block_header_by_height() {
     assert_eq!(expected_header, maybe_block_header.unwrap());
 }
 
+#[test]
+fn should_store_complete_blocks_with_manual_sync() {
+    let mut harness = ComponentHarness::default();
+    let cfg = WithDir::new(
+        harness.tmp.path(),
+        Config {
+            lmdb: Some(LmdbConfig {
+                sync_mode: Some(LmdbSyncMode::Manual),
+                readahead: Some(true),
+            }),
+            ..new_config(&harness)
+        },
+    );
+    let open_storage = || {
+        Storage::new(
+            &cfg,
+            Ratio::new(1, 3),
+            None,
+            ProtocolVersion::from_parts(1, 0, 0),
+            "test",
+            MAX_TTL,
+            RECENT_ERA_COUNT,
+            None,
+            false,
+        )
+        .expect("could not create storage component fixture")
+    };
+    let mut storage = open_storage();
+
+    let block = Block::random(&mut harness.rng);
+    assert!(put_complete_block(
+        &mut harness,
+        &mut storage,
+        Arc::new(block.clone())
+    ));
+    drop(storage);
+
+    // The block was synced along with the completed blocks index.
+    let mut storage = open_storage();
+    assert_eq!(
+        storage.get_available_block_range(),
+        AvailableBlockRange::new(block.height(), block.height())
+    );
+    let stored_block = get_block(&mut harness, &mut storage, *block.hash());
+    assert_eq!(stored_block.as_ref(), Some(&block));
+}
+
 #[test]
 fn check_force_resync_with_marker_file() {
     let mut harness = ComponentHarness::default();
@@ -1802,3 +2422,170 @@ fn unbonding_purse_serialization_roundtrip() {
     // Explicitly assert that the `new_validator` is not `None`
     assert!(deserialized.new_validator().is_some())
 }
+
+#[test]
+fn compacted_block_store_retains_blocks_and_reports_state_roots() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let blocks: Vec<_> = (0..3)
+        .map(|height| {
+            Arc::new(Block::random_with_specifics(
+                &mut harness.rng,
+                EraId::new(0),
+                height,
+                ProtocolVersion::V1_0_0,
+                false,
+                None,
+            ))
+        })
+        .collect();
+    for block in &blocks {
+        assert!(put_complete_block(
+            &mut harness,
+            &mut storage,
+            block.clone()
+        ));
+    }
+    drop(storage);
+
+    let cfg = WithDir::new(harness.tmp.path(), new_config(&harness));
+    let compacted_path = harness.tmp.path().join("compacted");
+    let state_root_hashes = compact_block_store(&cfg, "test", &compacted_path.join("test"))
+        .expect("should compact block store");
+
+    let mut expected: Vec<_> = blocks
+        .iter()
+        .map(|block| *block.header().state_root_hash())
+        .collect();
+    expected.sort_unstable();
+    expected.dedup();
+    assert_eq!(state_root_hashes, expected);
+
+    // Point a fresh storage component at the compacted copy and check the blocks are all there.
+    let mut compacted_cfg = new_config(&harness);
+    compacted_cfg.path = compacted_path;
+    let mut compacted_storage = Storage::new(
+        &WithDir::new(harness.tmp.path(), compacted_cfg),
+        Ratio::new(1, 3),
+        None,
+        ProtocolVersion::V1_0_0,
+        "test",
+        MAX_TTL,
+        RECENT_ERA_COUNT,
+        None,
+        false,
+    )
+    .expect("could not open compacted storage");
+    for block in &blocks {
+        assert_eq!(
+            get_block_at_height(&mut compacted_storage, block.height()).as_ref(),
+            Some(&**block)
+        );
+    }
+}
+
+#[test]
+fn should_prove_execution_results_of_single_deploys() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let deploys: Vec<Deploy> = (0..3).map(|_| Deploy::random(&mut harness.rng)).collect();
+    let block = Block::random_with_specifics(
+        &mut harness.rng,
+        EraId::new(1),
+        1,
+        ProtocolVersion::V1_0_0,
+        false,
+        &deploys,
+    );
+    put_complete_block(&mut harness, &mut storage, Arc::new(block.clone()));
+
+    let execution_results: Vec<(DeployHash, ExecutionResult)> = block
+        .deploy_and_transfer_hashes()
+        .map(|deploy_hash| (*deploy_hash, harness.rng.gen()))
+        .collect();
+    let execution_results_root = execution_results_merkle_root(
+        &execution_results
+            .iter()
+            .map(|(_, execution_result)| execution_result.clone())
+            .collect::<Vec<_>>(),
+    )
+    .unwrap();
+
+    // Nothing can be proven until the results of every deploy of the block are stored.
+    let first_id = DeployExecutionResultId::new(*block.hash(), execution_results[0].0);
+    assert!(storage
+        .read_deploy_execution_result(&first_id)
+        .unwrap()
+        .is_none());
+
+    put_execution_results(
+        &mut harness,
+        &mut storage,
+        *block.hash(),
+        execution_results.iter().cloned().collect(),
+    );
+
+    for (index, (deploy_hash, execution_result)) in execution_results.iter().enumerate() {
+        let id = DeployExecutionResultId::new(*block.hash(), *deploy_hash);
+        let item = storage
+            .read_deploy_execution_result(&id)
+            .unwrap()
+            .expect("should hold execution result");
+        assert_eq!(item.fetch_id(), id);
+        assert_eq!(item.clone().into_execution_result(), *execution_result);
+        let metadata = DeployExecutionResultValidationMetadata {
+            execution_results_root,
+            index: index as u64,
+        };
+        item.validate(&metadata).unwrap();
+    }
+
+    let other_id =
+        DeployExecutionResultId::new(*block.hash(), DeployHash::random(&mut harness.rng));
+    assert!(storage
+        .read_deploy_execution_result(&other_id)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn should_store_backfilled_execution_result_of_single_deploy() {
+    let mut harness = ComponentHarness::default();
+    let mut storage = storage_fixture(&harness);
+
+    let deploys: Vec<Deploy> = (0..3).map(|_| Deploy::random(&mut harness.rng)).collect();
+    let block = Block::random_with_specifics(
+        &mut harness.rng,
+        EraId::new(1),
+        1,
+        ProtocolVersion::V1_0_0,
+        false,
+        &deploys,
+    );
+    put_complete_block(&mut harness, &mut storage, Arc::new(block.clone()));
+    let execution_results: Vec<(DeployHash, ExecutionResult)> = block
+        .deploy_and_transfer_hashes()
+        .map(|deploy_hash| (*deploy_hash, harness.rng.gen()))
+        .collect();
+    let item = DeployExecutionResult::new(*block.hash(), &execution_results, 1).unwrap();
+    let deploy_hash = execution_results[1].0;
+
+    let was_written = harness.send_request(&mut storage, |responder| {
+        StorageRequest::PutDeployExecutionResult {
+            item: Box::new(item),
+            responder,
+        }
+        .into()
+    });
+    assert!(was_written);
+
+    let metadata = storage
+        .get_deploy_metadata_by_hash(&deploy_hash)
+        .expect("should hold deploy metadata");
+    assert_eq!(
+        metadata.execution_results.get(block.hash()),
+        Some(&execution_results[1].1)
+    );
+}
@@ -17,9 +17,13 @@ use tracing::{error, info, warn};
 use crate::{
     components::{
         fetcher::{self, FetchResult, FetchedData},
+        network::blocklist::BlocklistJustification,
         Component,
     },
-    effect::{requests::FetcherRequest, EffectBuilder, EffectExt, Effects},
+    effect::{
+        announcements::PeerBehaviorAnnouncement, requests::FetcherRequest, EffectBuilder,
+        EffectExt, Effects,
+    },
     types::{Chainspec, NodeId, SyncLeap, SyncLeapIdentifier},
     NodeRng,
 };
@@ -39,6 +43,8 @@ pub(crate) enum PeerState {
     Rejected,
     CouldntFetch,
     Fetched(Box<SyncLeap>),
+    /// The peer's sync leap was outvoted by other peers on a switch block.
+    Conflicting,
 }
 
 #[derive(Debug)]
@@ -71,6 +77,9 @@ enum Error {
 pub(crate) struct SyncLeaper {
     leap_activity: Option<LeapActivity>,
     chainspec: Arc<Chainspec>,
+    /// The number of peers which must provide mutually consistent sync leaps before one is
+    /// accepted.
+    cross_check_peers: usize,
     #[data_size(skip)]
     metrics: Metrics,
 }
@@ -78,11 +87,24 @@ pub(crate) struct SyncLeaper {
 impl SyncLeaper {
     pub(crate) fn new(
         chainspec: Arc<Chainspec>,
+        cross_check_peers: usize,
         registry: &Registry,
     ) -> Result<Self, prometheus::Error> {
+        // We never ask more than `simultaneous_peer_requests` peers at once, so requiring more
+        // responses than that would stall every leap.
+        let max_cross_check_peers =
+            (chainspec.core_config.simultaneous_peer_requests as usize).max(1);
+        if cross_check_peers > max_cross_check_peers {
+            warn!(
+                cross_check_peers,
+                max_cross_check_peers,
+                "sync leap cross-check peer count exceeds simultaneous peer requests; capping it"
+            );
+        }
         Ok(SyncLeaper {
             leap_activity: None,
             chainspec,
+            cross_check_peers: cross_check_peers.clamp(1, max_cross_check_peers),
             metrics: Metrics::new(registry)?,
         })
     }
@@ -92,7 +114,7 @@ impl SyncLeaper {
         match &self.leap_activity {
             None => LeapState::Idle,
             Some(activity) => {
-                let result = activity.status();
+                let result = activity.status(self.cross_check_peers);
                 if result.active() == false {
                     match result {
                         LeapState::Received { .. } | LeapState::Failed { .. } => {
@@ -225,14 +247,14 @@ impl SyncLeaper {
                         });
                     }
                 };
-                info!(%peer, %sync_leap_identifier, "peer rejected our request for a sync leap");
+                info!(peer_id = %peer, %sync_leap_identifier, "peer rejected our request for a sync leap");
                 *peer_state = PeerState::Rejected;
                 self.metrics.sync_leap_rejected_by_peer.inc();
                 Ok(())
             }
             Err(error) => {
                 let peer = error.peer();
-                info!(?error, %peer, %sync_leap_identifier, "failed to fetch a sync leap from peer");
+                info!(?error, peer_id = %peer, %sync_leap_identifier, "failed to fetch a sync leap from peer");
                 let peer_state = match leap_activity.peers_mut().get_mut(peer) {
                     Some(state) => state,
                     None => {
@@ -252,7 +274,7 @@ impl SyncLeaper {
 
 impl<REv> Component<REv> for SyncLeaper
 where
-    REv: From<FetcherRequest<SyncLeap>> + Send,
+    REv: From<FetcherRequest<SyncLeap>> + From<PeerBehaviorAnnouncement> + Send,
 {
     type Event = Event;
 
@@ -300,7 +322,24 @@ where
                         | Error::ResponseFromUnknownPeer { .. } => warn!(%error),
                     }
                 }
-                Effects::new()
+                let outvoted_peers = match self.leap_activity.as_mut() {
+                    Some(leap_activity) => {
+                        leap_activity.mark_conflicting_peers(self.cross_check_peers)
+                    }
+                    None => vec![],
+                };
+                let mut effects = Effects::new();
+                for (peer, era) in outvoted_peers {
+                    effects.extend(
+                        effect_builder
+                            .announce_block_peer_with_justification(
+                                peer,
+                                BlocklistJustification::SentConflictingSwitchBlock { era },
+                            )
+                            .ignore(),
+                    );
+                }
+                effects
             }
         }
     }
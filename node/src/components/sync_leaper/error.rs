@@ -4,13 +4,23 @@ use std::{
     fmt::{Display, Formatter},
 };
 
-use crate::types::{NodeId, SyncLeapIdentifier};
+use casper_types::EraId;
+
+use crate::{
+    types::{NodeId, SyncLeapIdentifier},
+    utils::DisplayIter,
+};
 
 #[derive(Debug, Clone, DataSize)]
 pub(crate) enum LeapActivityError {
     TooOld(SyncLeapIdentifier, Vec<NodeId>),
     Unobtainable(SyncLeapIdentifier, Vec<NodeId>),
     NoPeers(SyncLeapIdentifier),
+    /// Fewer peers than required for cross-checking returned a sync leap.
+    InsufficientAgreement(SyncLeapIdentifier, Vec<NodeId>),
+    /// The listed peers returned different switch block headers, and hence possibly different
+    /// validator weights, for the given era.
+    DivergentSwitchBlocks(SyncLeapIdentifier, EraId, Vec<NodeId>),
 }
 
 impl Display for LeapActivityError {
@@ -33,6 +43,23 @@ impl Display for LeapActivityError {
                     sync_leap_identifier
                 )
             }
+            LeapActivityError::InsufficientAgreement(sync_leap_identifier, peers) => {
+                write!(
+                    formatter,
+                    "only {} peers provided data for: {}",
+                    peers.len(),
+                    sync_leap_identifier
+                )
+            }
+            LeapActivityError::DivergentSwitchBlocks(sync_leap_identifier, era_id, peers) => {
+                write!(
+                    formatter,
+                    "peers {} provided divergent switch blocks of era {} for: {}",
+                    DisplayIter::new(peers),
+                    era_id,
+                    sync_leap_identifier
+                )
+            }
         }
     }
 }
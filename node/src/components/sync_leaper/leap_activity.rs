@@ -1,8 +1,14 @@
-use std::{cmp::Ordering, collections::HashMap, time::Instant};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet},
+    time::Instant,
+};
 
 use datasize::DataSize;
 
-use crate::types::{NodeId, SyncLeap, SyncLeapIdentifier};
+use casper_types::EraId;
+
+use crate::types::{BlockHash, NodeId, SyncLeap, SyncLeapIdentifier};
 
 use super::{leap_state::LeapState, LeapActivityError, PeerState};
 
@@ -26,7 +32,12 @@ impl LeapActivity {
         }
     }
 
-    pub(super) fn status(&self) -> LeapState {
+    /// Returns the current state of the leap.
+    ///
+    /// If `cross_check_peers` is more than one, the leap is only reported as received once every
+    /// peer asked has responded, and at least `cross_check_peers` of them agree on every switch
+    /// block.  Peers outvoted on a switch block are left out of the result.
+    pub(super) fn status(&self, cross_check_peers: usize) -> LeapState {
         let sync_leap_identifier = self.sync_leap_identifier;
         let in_flight = self
             .peers
@@ -43,13 +54,16 @@ impl LeapActivity {
                 from_peers: vec![],
             };
         }
-        if in_flight > 0 && responsed == 0 {
+        if in_flight > 0 && (responsed == 0 || cross_check_peers > 1) {
             return LeapState::Awaiting {
                 sync_leap_identifier,
                 in_flight,
             };
         }
-        match self.best_response() {
+        match self
+            .cross_check(cross_check_peers)
+            .and_then(|conflicting_peers| self.best_response(&conflicting_peers))
+        {
             Ok((best_available, from_peers)) => LeapState::Received {
                 in_flight,
                 best_available: Box::new(best_available),
@@ -57,10 +71,15 @@ impl LeapActivity {
             },
             // `Unobtainable` means we couldn't download it from any peer so far - don't treat it
             // as a failure if there are still requests in flight
-            Err(LeapActivityError::Unobtainable(_, _)) if in_flight > 0 => LeapState::Awaiting {
-                sync_leap_identifier,
-                in_flight,
-            },
+            Err(LeapActivityError::Unobtainable(_, _))
+            | Err(LeapActivityError::InsufficientAgreement(_, _))
+                if in_flight > 0 =>
+            {
+                LeapState::Awaiting {
+                    sync_leap_identifier,
+                    in_flight,
+                }
+            }
             Err(error) => LeapState::Failed {
                 sync_leap_identifier,
                 from_peers: vec![],
@@ -70,7 +89,12 @@ impl LeapActivity {
         }
     }
 
-    fn best_response(&self) -> Result<(SyncLeap, Vec<NodeId>), LeapActivityError> {
+    /// Returns the sync leap reaching highest among those fetched from peers other than
+    /// `excluded_peers`, along with the peers which provided it.
+    fn best_response(
+        &self,
+        excluded_peers: &HashSet<NodeId>,
+    ) -> Result<(SyncLeap, Vec<NodeId>), LeapActivityError> {
         let reject_count = self
             .peers
             .values()
@@ -80,6 +104,9 @@ impl LeapActivity {
         let mut peers = vec![];
         let mut maybe_ret = None;
         for (peer, peer_state) in &self.peers {
+            if excluded_peers.contains(peer) {
+                continue;
+            }
             match peer_state {
                 PeerState::Fetched(sync_leap) => match &maybe_ret {
                     None => {
@@ -102,7 +129,10 @@ impl LeapActivity {
                         }
                     }
                 },
-                PeerState::RequestSent | PeerState::Rejected | PeerState::CouldntFetch => {}
+                PeerState::RequestSent
+                | PeerState::Rejected
+                | PeerState::CouldntFetch
+                | PeerState::Conflicting => {}
             }
         }
 
@@ -121,6 +151,111 @@ impl LeapActivity {
         }
     }
 
+    /// Cross-checks the switch blocks of the fetched sync leaps, which carry the validator weights
+    /// of the following eras.
+    ///
+    /// Where peers disagree on the switch block of an era, the one provided by at least
+    /// `cross_check_peers` peers, and by more peers than any other, wins.  Returns the peers
+    /// outvoted on any era, once at least `cross_check_peers` peers agree on every switch block.
+    fn cross_check(&self, cross_check_peers: usize) -> Result<HashSet<NodeId>, LeapActivityError> {
+        let mut conflicting_peers = HashSet::new();
+        for (era_id, votes) in self.switch_block_votes() {
+            if votes.len() < 2 {
+                continue;
+            }
+            let mut votes: Vec<Vec<NodeId>> = votes.into_values().collect();
+            votes.sort_by_key(|peers| peers.len());
+            let winning_peers = votes.pop().unwrap_or_default();
+            let runner_up_count = votes.last().map_or(0, Vec::len);
+            if winning_peers.len() < cross_check_peers || winning_peers.len() == runner_up_count {
+                return Err(LeapActivityError::DivergentSwitchBlocks(
+                    self.sync_leap_identifier,
+                    era_id,
+                    winning_peers
+                        .into_iter()
+                        .chain(votes.into_iter().flatten())
+                        .collect(),
+                ));
+            }
+            conflicting_peers.extend(votes.into_iter().flatten());
+        }
+
+        let agreeing_peers: Vec<NodeId> = self
+            .peers
+            .iter()
+            .filter(|(peer, peer_state)| {
+                matches!(peer_state, PeerState::Fetched(_)) && !conflicting_peers.contains(peer)
+            })
+            .map(|(peer, _)| *peer)
+            .collect();
+        if agreeing_peers.len() < cross_check_peers {
+            return Err(LeapActivityError::InsufficientAgreement(
+                self.sync_leap_identifier,
+                agreeing_peers,
+            ));
+        }
+        Ok(conflicting_peers)
+    }
+
+    /// Marks the peers outvoted on a switch block as conflicting, once every peer asked has
+    /// responded, returning them along with the era they were outvoted on.
+    pub(super) fn mark_conflicting_peers(
+        &mut self,
+        cross_check_peers: usize,
+    ) -> Vec<(NodeId, EraId)> {
+        if cross_check_peers < 2
+            || self
+                .peers
+                .values()
+                .any(|peer_state| matches!(peer_state, PeerState::RequestSent))
+        {
+            return vec![];
+        }
+        let conflicting_peers = match self.cross_check(cross_check_peers) {
+            Ok(conflicting_peers) => conflicting_peers,
+            Err(_) => return vec![],
+        };
+        let mut outvoted = vec![];
+        for (era_id, votes) in self.switch_block_votes() {
+            for peer in votes.into_values().flatten() {
+                if conflicting_peers.contains(&peer)
+                    && !outvoted
+                        .iter()
+                        .any(|(outvoted_peer, _)| *outvoted_peer == peer)
+                {
+                    outvoted.push((peer, era_id));
+                }
+            }
+        }
+        for (peer, _) in &outvoted {
+            self.peers.insert(*peer, PeerState::Conflicting);
+        }
+        outvoted
+    }
+
+    /// Returns the peers which provided each switch block in their fetched sync leaps, by era.
+    fn switch_block_votes(&self) -> BTreeMap<EraId, HashMap<BlockHash, Vec<NodeId>>> {
+        let mut votes: BTreeMap<EraId, HashMap<BlockHash, Vec<NodeId>>> = BTreeMap::new();
+        for (peer, peer_state) in &self.peers {
+            let sync_leap = match peer_state {
+                PeerState::Fetched(sync_leap) => sync_leap,
+                PeerState::RequestSent
+                | PeerState::Rejected
+                | PeerState::CouldntFetch
+                | PeerState::Conflicting => continue,
+            };
+            for switch_block in sync_leap.switch_blocks() {
+                votes
+                    .entry(switch_block.era_id())
+                    .or_default()
+                    .entry(switch_block.block_hash())
+                    .or_default()
+                    .push(*peer);
+            }
+        }
+        votes
+    }
+
     pub(crate) fn leap_start(&self) -> Instant {
         self.leap_start
     }
@@ -149,11 +284,11 @@ impl LeapActivity {
 #[cfg(test)]
 mod tests {
     use std::{
-        collections::{BTreeSet, HashMap},
+        collections::{BTreeSet, HashMap, HashSet},
         time::Instant,
     };
 
-    use casper_types::{testing::TestRng, ProtocolVersion};
+    use casper_types::{testing::TestRng, EraId, ProtocolVersion};
     use rand::seq::SliceRandom;
 
     use crate::{
@@ -215,7 +350,8 @@ mod tests {
             leap_start: Instant::now(),
         };
 
-        let (actual_sync_leap, actual_peers) = leap_activity.best_response().unwrap();
+        let (actual_sync_leap, actual_peers) =
+            leap_activity.best_response(&HashSet::new()).unwrap();
 
         assert!(!actual_peers.is_empty());
         assert_eq!(actual_peers.get(0).unwrap(), &peer_1.0);
@@ -231,7 +367,8 @@ mod tests {
                 .cloned(),
         );
 
-        let (actual_sync_leap, actual_peers) = leap_activity.best_response().unwrap();
+        let (actual_sync_leap, actual_peers) =
+            leap_activity.best_response(&HashSet::new()).unwrap();
 
         assert_eq!(actual_peers.len(), 1);
         assert_eq!(actual_peers.get(0).unwrap(), &peer_1.0);
@@ -272,7 +409,8 @@ mod tests {
             leap_start: Instant::now(),
         };
 
-        let (actual_sync_leap, actual_peers) = leap_activity.best_response().unwrap();
+        let (actual_sync_leap, actual_peers) =
+            leap_activity.best_response(&HashSet::new()).unwrap();
 
         // Expect only a single peer with the best sync leap.
         assert_eq!(actual_peers.len(), 1);
@@ -299,7 +437,8 @@ mod tests {
             .cloned(),
         );
 
-        let (actual_sync_leap, mut actual_peers) = leap_activity.best_response().unwrap();
+        let (actual_sync_leap, mut actual_peers) =
+            leap_activity.best_response(&HashSet::new()).unwrap();
 
         // Expect two recently added peers with best sync leap to be reported.
         let mut expected_peers = vec![peer_2_best_node_id, peer_3_best_node_id];
@@ -330,7 +469,8 @@ mod tests {
             .cloned(),
         );
 
-        let (actual_sync_leap, mut actual_peers) = leap_activity.best_response().unwrap();
+        let (actual_sync_leap, mut actual_peers) =
+            leap_activity.best_response(&HashSet::new()).unwrap();
 
         // Expect two previously added best peers with best sync leap to be reported.
         let mut expected_peers = vec![peer_2_best_node_id, peer_3_best_node_id];
@@ -351,7 +491,8 @@ mod tests {
                 .cloned(),
         );
 
-        let (actual_sync_leap, mut actual_peers) = leap_activity.best_response().unwrap();
+        let (actual_sync_leap, mut actual_peers) =
+            leap_activity.best_response(&HashSet::new()).unwrap();
         let mut expected_peers = vec![peer_2_best_node_id, peer_3_best_node_id];
         actual_peers.sort_unstable();
         expected_peers.sort_unstable();
@@ -378,7 +519,7 @@ mod tests {
             leap_start: Instant::now(),
         };
 
-        let best_response_error = leap_activity.best_response().unwrap_err();
+        let best_response_error = leap_activity.best_response(&HashSet::new()).unwrap_err();
         assert!(matches!(
             best_response_error,
             LeapActivityError::Unobtainable(_, _)
@@ -387,7 +528,7 @@ mod tests {
         leap_activity
             .peers
             .insert(NodeId::random(&mut rng), PeerState::Rejected);
-        let best_response_error = leap_activity.best_response().unwrap_err();
+        let best_response_error = leap_activity.best_response(&HashSet::new()).unwrap_err();
         assert!(matches!(
             best_response_error,
             LeapActivityError::TooOld(_, _)
@@ -406,7 +547,7 @@ mod tests {
             leap_start: Instant::now(),
         };
         assert!(matches!(
-            leap_activity.status(),
+            leap_activity.status(1),
             LeapState::Failed { error, .. } if matches!(error, LeapActivityError::NoPeers(_))
         ));
 
@@ -417,7 +558,7 @@ mod tests {
             leap_start: Instant::now(),
         };
         assert!(matches!(
-            leap_activity.status(),
+            leap_activity.status(1),
             LeapState::Failed { error, .. } if matches!(error, LeapActivityError::Unobtainable(_, _))
         ));
     }
@@ -435,12 +576,18 @@ mod tests {
             peers: [peer_1, peer_2].iter().cloned().collect(),
             leap_start: Instant::now(),
         };
-        assert!(matches!(leap_activity.status(), LeapState::Awaiting { .. }));
+        assert!(matches!(
+            leap_activity.status(1),
+            LeapState::Awaiting { .. }
+        ));
 
         leap_activity
             .peers
             .insert(NodeId::random(&mut rng), PeerState::CouldntFetch);
-        assert!(matches!(leap_activity.status(), LeapState::Awaiting { .. }));
+        assert!(matches!(
+            leap_activity.status(1),
+            LeapState::Awaiting { .. }
+        ));
     }
 
     #[test]
@@ -459,7 +606,10 @@ mod tests {
             peers: [peer_1].iter().cloned().collect(),
             leap_start: Instant::now(),
         };
-        assert!(matches!(leap_activity.status(), LeapState::Received { .. }));
+        assert!(matches!(
+            leap_activity.status(1),
+            LeapState::Received { .. }
+        ));
 
         // Adding peers in other states does not change the result.
         let peer_request_sent = (NodeId::random(&mut rng), PeerState::RequestSent);
@@ -471,7 +621,176 @@ mod tests {
                 .cloned(),
         );
 
-        assert!(matches!(leap_activity.status(), LeapState::Received { .. }));
+        assert!(matches!(
+            leap_activity.status(1),
+            LeapState::Received { .. }
+        ));
+    }
+
+    #[test]
+    fn leap_activity_status_requires_cross_check_peers() {
+        let mut rng = TestRng::new();
+
+        let sync_leap_identifier = SyncLeapIdentifier::sync_to_tip(BlockHash::random(&mut rng));
+
+        let sync_leap = make_test_sync_leap(&mut rng);
+        let peer_1 = (
+            NodeId::random(&mut rng),
+            PeerState::Fetched(Box::new(sync_leap.clone())),
+        );
+        let peer_2 = (NodeId::random(&mut rng), PeerState::RequestSent);
+        let mut leap_activity = LeapActivity {
+            sync_leap_identifier,
+            peers: [peer_1, peer_2.clone()].iter().cloned().collect(),
+            leap_start: Instant::now(),
+        };
+
+        // A single response is not enough while waiting for a second one.
+        assert!(matches!(
+            leap_activity.status(2),
+            LeapState::Awaiting { .. }
+        ));
+
+        leap_activity
+            .peers
+            .insert(peer_2.0, PeerState::Fetched(Box::new(sync_leap.clone())));
+        assert!(matches!(
+            leap_activity.status(2),
+            LeapState::Received { .. }
+        ));
+
+        // Once nothing is in flight, too few responses is a failure.
+        leap_activity
+            .peers
+            .insert(peer_2.0, PeerState::CouldntFetch);
+        assert!(matches!(
+            leap_activity.status(2),
+            LeapState::Failed { error, .. }
+                if matches!(error, LeapActivityError::InsufficientAgreement(_, _))
+        ));
+    }
+
+    #[test]
+    fn leap_activity_status_divergent_switch_blocks() {
+        let mut rng = TestRng::new();
+
+        let sync_leap_identifier = SyncLeapIdentifier::sync_to_tip(BlockHash::random(&mut rng));
+
+        let era_id = EraId::new(5);
+        let make_switch_block_sync_leap = |rng: &mut TestRng| {
+            let block = Block::random_with_specifics(
+                rng,
+                era_id,
+                10,
+                ProtocolVersion::default(),
+                true,
+                None,
+            );
+            make_sync_leap_with_trusted_block_header(block.header().clone())
+        };
+        let sync_leap_1 = make_switch_block_sync_leap(&mut rng);
+        let sync_leap_2 = make_switch_block_sync_leap(&mut rng);
+        let peer_1 = NodeId::random(&mut rng);
+        let peer_2 = NodeId::random(&mut rng);
+
+        let leap_activity = LeapActivity {
+            sync_leap_identifier,
+            peers: [
+                (peer_1, PeerState::Fetched(Box::new(sync_leap_1))),
+                (peer_2, PeerState::Fetched(Box::new(sync_leap_2))),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+            leap_start: Instant::now(),
+        };
+
+        match leap_activity.status(1) {
+            LeapState::Failed {
+                error: LeapActivityError::DivergentSwitchBlocks(_, actual_era_id, peers),
+                ..
+            } => {
+                assert_eq!(actual_era_id, era_id);
+                let peers: BTreeSet<_> = peers.into_iter().collect();
+                assert_eq!(peers, [peer_1, peer_2].iter().copied().collect());
+            }
+            other => panic!("unexpected leap state: {}", other),
+        }
+    }
+
+    #[test]
+    fn leap_activity_status_outvotes_conflicting_switch_block() {
+        let mut rng = TestRng::new();
+
+        let sync_leap_identifier = SyncLeapIdentifier::sync_to_tip(BlockHash::random(&mut rng));
+
+        let era_id = EraId::new(5);
+        let make_switch_block_sync_leap = |rng: &mut TestRng| {
+            let block = Block::random_with_specifics(
+                rng,
+                era_id,
+                10,
+                ProtocolVersion::default(),
+                true,
+                None,
+            );
+            make_sync_leap_with_trusted_block_header(block.header().clone())
+        };
+        let agreed_sync_leap = make_switch_block_sync_leap(&mut rng);
+        let conflicting_sync_leap = make_switch_block_sync_leap(&mut rng);
+        let peer_1 = NodeId::random(&mut rng);
+        let peer_2 = NodeId::random(&mut rng);
+        let peer_3 = NodeId::random(&mut rng);
+
+        let mut leap_activity = LeapActivity {
+            sync_leap_identifier,
+            peers: [
+                (
+                    peer_1,
+                    PeerState::Fetched(Box::new(agreed_sync_leap.clone())),
+                ),
+                (peer_2, PeerState::RequestSent),
+                (peer_3, PeerState::Fetched(Box::new(conflicting_sync_leap))),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+            leap_start: Instant::now(),
+        };
+
+        // Nothing is decided or marked while a response is still in flight.
+        assert!(matches!(
+            leap_activity.status(2),
+            LeapState::Awaiting { .. }
+        ));
+        assert!(leap_activity.mark_conflicting_peers(2).is_empty());
+
+        leap_activity.peers.insert(
+            peer_2,
+            PeerState::Fetched(Box::new(agreed_sync_leap.clone())),
+        );
+        match leap_activity.status(2) {
+            LeapState::Received {
+                best_available,
+                from_peers,
+                ..
+            } => {
+                assert_eq!(*best_available, agreed_sync_leap);
+                let from_peers: BTreeSet<_> = from_peers.into_iter().collect();
+                assert_eq!(from_peers, [peer_1, peer_2].iter().copied().collect());
+            }
+            other => panic!("unexpected leap state: {}", other),
+        }
+
+        assert_eq!(
+            leap_activity.mark_conflicting_peers(2),
+            vec![(peer_3, era_id)]
+        );
+        assert_eq!(leap_activity.peers[&peer_3], PeerState::Conflicting);
+        assert!(matches!(
+            leap_activity.status(2),
+            LeapState::Received { .. }
+        ));
     }
 
     #[test]
@@ -0,0 +1,689 @@
+//! Upgrade downloader component.
+//!
+//! Periodically fetches the manifest of the next staged upgrade for this network from a set of
+//! configured HTTPS mirrors. The manifest lists the protocol version of the upgrade and the
+//! Blake2b hash of every file in the upgrade package (the chainspec along with any accompanying
+//! files, such as metadata about the node binaries). It is accepted only if enough of the
+//! operator-configured trusted keys have signed it, in a detached signature file published next
+//! to it.
+//!
+//! Once accepted, every listed file is downloaded and checked against its hash, and the package
+//! is then staged into the subdirectory of the config root which the
+//! [upgrade watcher](super::upgrade_watcher) scans for upgrades, e.g. `1_5_0/chainspec.toml`.
+//! Staging writes to a temporary directory first and moves it into place, so the upgrade watcher
+//! never sees a partially written upgrade.
+//!
+//! For a mirror `https://mirror.example` and network `casper`, the following are fetched:
+//!
+//! * `https://mirror.example/casper/manifest.json`
+//! * `https://mirror.example/casper/manifest.json.sig`
+//! * `https://mirror.example/casper/<version dir>/<file name>` for every file in the manifest
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::task;
+use tracing::{debug, info, warn};
+
+use casper_hashing::Digest;
+use casper_types::{crypto, AsymmetricType, ProtocolVersion, PublicKey, Signature, TimeDiff};
+
+use crate::{
+    components::Component,
+    effect::{EffectBuilder, EffectExt, Effects},
+    types::{chainspec::CHAINSPEC_FILENAME, NodeRng},
+};
+
+const COMPONENT_NAME: &str = "upgrade_downloader";
+
+const MANIFEST_FILENAME: &str = "manifest.json";
+const SIGNATURES_FILENAME: &str = "manifest.json.sig";
+
+const DEFAULT_CHECK_INTERVAL: &str = "5min";
+const DEFAULT_REQUEST_TIMEOUT: &str = "1min";
+
+/// Configuration options for downloading staged upgrades.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Whether or not staged upgrades are downloaded.
+    enabled: bool,
+    /// Base URLs of the mirrors to download from, tried in order. Must use HTTPS.
+    mirrors: Vec<String>,
+    /// Hex-encoded public keys trusted to sign upgrade manifests.
+    trusted_keys: Vec<String>,
+    /// Number of distinct trusted keys which must have signed a manifest for it to be accepted.
+    required_signatures: usize,
+    /// How often to check the mirrors for a new upgrade.
+    check_interval: TimeDiff,
+    /// Timeout for a single download.
+    request_timeout: TimeDiff,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enabled: false,
+            mirrors: vec![],
+            trusted_keys: vec![],
+            required_signatures: 1,
+            check_interval: DEFAULT_CHECK_INTERVAL.parse().unwrap(),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT.parse().unwrap(),
+        }
+    }
+}
+
+/// The manifest of an upgrade package, as published on the mirrors.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Manifest {
+    /// The protocol version the package upgrades to.
+    protocol_version: ProtocolVersion,
+    /// The files making up the package.
+    files: Vec<ManifestFile>,
+}
+
+/// A single file of an upgrade package.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ManifestFile {
+    /// The name of the file within the upgrade's directory.
+    name: String,
+    /// The Blake2b hash of the file's contents.
+    hash: Digest,
+}
+
+/// A detached signature over the manifest, as published on the mirrors.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ManifestSignature {
+    public_key: PublicKey,
+    signature: Signature,
+}
+
+/// Error configuring or running the upgrade downloader.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// A mirror does not use HTTPS.
+    #[error("upgrade mirror {0} does not use https")]
+    InsecureMirror(String),
+
+    /// A trusted key could not be parsed.
+    #[error("invalid trusted upgrade key {key}: {error}")]
+    InvalidTrustedKey {
+        /// The key as configured.
+        key: String,
+        /// The parse error.
+        error: crypto::Error,
+    },
+
+    /// More signatures are required than there are trusted keys.
+    #[error("{required} upgrade manifest signatures required, but only {trusted} keys trusted")]
+    UnsatisfiableSignatureThreshold {
+        /// The number of required signatures.
+        required: usize,
+        /// The number of trusted keys.
+        trusted: usize,
+    },
+
+    /// Error building the HTTP client.
+    #[error("failed to build http client: {0}")]
+    Client(reqwest::Error),
+
+    /// Error downloading from a mirror.
+    #[error("failed to download {url}: {error}")]
+    Download {
+        /// The URL being downloaded.
+        url: String,
+        /// The underlying error.
+        error: reqwest::Error,
+    },
+
+    /// The manifest or its signatures could not be decoded.
+    #[error("failed to decode {name}: {error}")]
+    Decode {
+        /// The name of the file being decoded.
+        name: &'static str,
+        /// The underlying error.
+        error: serde_json::Error,
+    },
+
+    /// Too few trusted keys signed the manifest.
+    #[error("manifest signed by {valid} trusted keys, {required} required")]
+    InsufficientSignatures {
+        /// The number of distinct trusted keys with a valid signature.
+        valid: usize,
+        /// The number of required signatures.
+        required: usize,
+    },
+
+    /// The manifest lists a file name which is not a plain file name.
+    #[error("invalid file name in manifest: {0:?}")]
+    InvalidFileName(String),
+
+    /// The manifest does not include a chainspec.
+    #[error("manifest does not include {}", CHAINSPEC_FILENAME)]
+    MissingChainspec,
+
+    /// A downloaded file does not match the hash in the manifest.
+    #[error("hash of {name} is {actual}, manifest lists {expected}")]
+    HashMismatch {
+        /// The name of the file.
+        name: String,
+        /// The hash listed in the manifest.
+        expected: Digest,
+        /// The hash of the downloaded file.
+        actual: Digest,
+    },
+
+    /// The downloaded chainspec is for a different protocol version than the manifest.
+    #[error("chainspec is for protocol version {chainspec:?}, manifest is for {manifest}")]
+    ChainspecVersionMismatch {
+        /// The protocol version in the chainspec, if it could be read.
+        chainspec: Option<String>,
+        /// The protocol version in the manifest.
+        manifest: ProtocolVersion,
+    },
+
+    /// Error writing the staged upgrade.
+    #[error("failed to stage upgrade in {}: {error}", dir.display())]
+    Stage {
+        /// The directory being written.
+        dir: PathBuf,
+        /// The underlying error.
+        error: io::Error,
+    },
+
+    /// The staging task panicked.
+    #[error("staging task failed: {0}")]
+    Join(#[from] task::JoinError),
+}
+
+/// Upgrade downloader events.
+#[derive(Debug, Serialize)]
+pub(crate) enum Event {
+    /// Check the mirrors for a new upgrade.
+    CheckMirrors,
+    /// The outcome of checking the mirrors: the staged protocol version, if any.
+    Checked(Option<ProtocolVersion>),
+}
+
+impl Display for Event {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::CheckMirrors => write!(formatter, "check upgrade mirrors"),
+            Event::Checked(Some(version)) => {
+                write!(formatter, "staged upgrade to {}", version)
+            }
+            Event::Checked(None) => write!(formatter, "no upgrade staged"),
+        }
+    }
+}
+
+/// Upgrade downloader component.
+#[derive(DataSize, Debug)]
+pub(crate) struct UpgradeDownloader {
+    config: Config,
+    trusted_keys: Vec<PublicKey>,
+    network_name: String,
+    /// The highest protocol version installed or staged so far.
+    #[data_size(skip)]
+    latest_version: ProtocolVersion,
+    /// The directory holding a subdirectory per installed protocol version.
+    root_dir: PathBuf,
+    #[data_size(skip)]
+    client: reqwest::Client,
+}
+
+impl UpgradeDownloader {
+    /// Creates a new upgrade downloader, along with the effects starting its periodic checks.
+    ///
+    /// `chainspec_dir` is the directory of the running protocol version's chainspec; upgrades are
+    /// staged next to it.
+    pub(crate) fn new<REv: Send>(
+        config: Config,
+        network_name: String,
+        current_version: ProtocolVersion,
+        chainspec_dir: &Path,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Result<(Self, Effects<Event>), Error> {
+        if let Some(mirror) = config
+            .mirrors
+            .iter()
+            .find(|mirror| !mirror.starts_with("https://"))
+        {
+            return Err(Error::InsecureMirror(mirror.clone()));
+        }
+        let trusted_keys = config
+            .trusted_keys
+            .iter()
+            .map(|key| {
+                PublicKey::from_hex(key).map_err(|error| Error::InvalidTrustedKey {
+                    key: key.clone(),
+                    error,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if config.enabled && config.required_signatures > trusted_keys.len() {
+            return Err(Error::UnsatisfiableSignatureThreshold {
+                required: config.required_signatures,
+                trusted: trusted_keys.len(),
+            });
+        }
+        let client = reqwest::Client::builder()
+            .https_only(true)
+            .timeout(config.request_timeout.into())
+            .build()
+            .map_err(Error::Client)?;
+
+        let effects = if config.enabled {
+            effect_builder.immediately().event(|()| Event::CheckMirrors)
+        } else {
+            Effects::new()
+        };
+        let downloader = UpgradeDownloader {
+            config,
+            trusted_keys,
+            network_name,
+            latest_version: current_version,
+            root_dir: chainspec_dir
+                .parent()
+                .unwrap_or(chainspec_dir)
+                .to_path_buf(),
+            client,
+        };
+        Ok((downloader, effects))
+    }
+
+    fn check_mirrors<REv: Send>(&self, effect_builder: EffectBuilder<REv>) -> Effects<Event> {
+        let client = self.client.clone();
+        let mirrors = self.config.mirrors.clone();
+        let network_name = self.network_name.clone();
+        let latest_version = self.latest_version;
+        let trusted_keys = self.trusted_keys.clone();
+        let required_signatures = self.config.required_signatures;
+        let root_dir = self.root_dir.clone();
+        let mut effects = async move {
+            for mirror in mirrors {
+                let base_url = format!("{}/{}", mirror.trim_end_matches('/'), network_name);
+                match download_and_stage(
+                    &client,
+                    &base_url,
+                    latest_version,
+                    &trusted_keys,
+                    required_signatures,
+                    &root_dir,
+                )
+                .await
+                {
+                    Ok(staged) => return staged,
+                    Err(error) => warn!(%mirror, %error, "failed to get upgrade from mirror"),
+                }
+            }
+            None
+        }
+        .event(Event::Checked);
+
+        effects.extend(
+            effect_builder
+                .set_timeout(self.config.check_interval.into())
+                .event(|_| Event::CheckMirrors),
+        );
+        effects
+    }
+}
+
+/// Fetches the manifest from `base_url` and, if it is for a version newer than `latest_version`,
+/// downloads, verifies and stages the upgrade package it lists.
+///
+/// Returns the version staged, if any.
+async fn download_and_stage(
+    client: &reqwest::Client,
+    base_url: &str,
+    latest_version: ProtocolVersion,
+    trusted_keys: &[PublicKey],
+    required_signatures: usize,
+    root_dir: &Path,
+) -> Result<Option<ProtocolVersion>, Error> {
+    let manifest_bytes = download(client, &format!("{}/{}", base_url, MANIFEST_FILENAME)).await?;
+    let signatures_bytes =
+        download(client, &format!("{}/{}", base_url, SIGNATURES_FILENAME)).await?;
+    let manifest = verify_manifest(
+        &manifest_bytes,
+        &signatures_bytes,
+        trusted_keys,
+        required_signatures,
+    )?;
+    if manifest.protocol_version <= latest_version {
+        debug!(
+            protocol_version = %manifest.protocol_version,
+            "upgrade from mirror is not newer than latest version"
+        );
+        return Ok(None);
+    }
+    let version_dir = version_dir_name(&manifest.protocol_version);
+    if root_dir.join(&version_dir).exists() {
+        debug!(protocol_version = %manifest.protocol_version, "upgrade already installed");
+        return Ok(Some(manifest.protocol_version));
+    }
+
+    let mut files = Vec::with_capacity(manifest.files.len());
+    for file in &manifest.files {
+        let url = format!("{}/{}/{}", base_url, version_dir, file.name);
+        let contents = download(client, &url).await?;
+        let actual = Digest::hash(&contents);
+        if actual != file.hash {
+            return Err(Error::HashMismatch {
+                name: file.name.clone(),
+                expected: file.hash,
+                actual,
+            });
+        }
+        files.push((file.name.clone(), contents));
+    }
+    check_chainspec_version(&manifest, &files)?;
+
+    let root_dir = root_dir.to_path_buf();
+    let protocol_version = manifest.protocol_version;
+    task::spawn_blocking(move || stage(&root_dir, &protocol_version, &files)).await??;
+    info!(%protocol_version, "staged upgrade");
+    Ok(Some(protocol_version))
+}
+
+async fn download(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, Error> {
+    let to_error = |error| Error::Download {
+        url: url.to_string(),
+        error,
+    };
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(to_error)?;
+    Ok(response.bytes().await.map_err(to_error)?.to_vec())
+}
+
+/// Decodes the manifest, checking it was signed by at least `required_signatures` distinct keys
+/// from `trusted_keys` and that it lists a valid set of files.
+fn verify_manifest(
+    manifest_bytes: &[u8],
+    signatures_bytes: &[u8],
+    trusted_keys: &[PublicKey],
+    required_signatures: usize,
+) -> Result<Manifest, Error> {
+    let signatures: Vec<ManifestSignature> =
+        serde_json::from_slice(signatures_bytes).map_err(|error| Error::Decode {
+            name: SIGNATURES_FILENAME,
+            error,
+        })?;
+    let valid = signatures
+        .iter()
+        .filter(|signature| trusted_keys.contains(&signature.public_key))
+        .filter(|signature| {
+            crypto::verify(manifest_bytes, &signature.signature, &signature.public_key).is_ok()
+        })
+        .map(|signature| &signature.public_key)
+        .collect::<BTreeSet<_>>()
+        .len();
+    if valid < required_signatures {
+        return Err(Error::InsufficientSignatures {
+            valid,
+            required: required_signatures,
+        });
+    }
+
+    let manifest: Manifest =
+        serde_json::from_slice(manifest_bytes).map_err(|error| Error::Decode {
+            name: MANIFEST_FILENAME,
+            error,
+        })?;
+    for file in &manifest.files {
+        let is_plain_name = Path::new(&file.name)
+            .file_name()
+            .map_or(false, |name| name == file.name.as_str());
+        if !is_plain_name {
+            return Err(Error::InvalidFileName(file.name.clone()));
+        }
+    }
+    if !manifest
+        .files
+        .iter()
+        .any(|file| file.name == CHAINSPEC_FILENAME)
+    {
+        return Err(Error::MissingChainspec);
+    }
+    Ok(manifest)
+}
+
+/// Checks that the downloaded chainspec is for the protocol version listed in the manifest.
+fn check_chainspec_version(manifest: &Manifest, files: &[(String, Vec<u8>)]) -> Result<(), Error> {
+    let chainspec_version = files
+        .iter()
+        .find(|(name, _)| name == CHAINSPEC_FILENAME)
+        .and_then(|(_, contents)| toml::from_slice::<toml::Value>(contents).ok())
+        .and_then(|chainspec| {
+            chainspec
+                .get("protocol")?
+                .get("version")?
+                .as_str()
+                .map(str::to_string)
+        });
+    let matches = chainspec_version
+        .as_deref()
+        .and_then(|version| ProtocolVersion::from_str(version).ok())
+        == Some(manifest.protocol_version);
+    if !matches {
+        return Err(Error::ChainspecVersionMismatch {
+            chainspec: chainspec_version,
+            manifest: manifest.protocol_version,
+        });
+    }
+    Ok(())
+}
+
+/// Writes `files` into the directory for `protocol_version` under `root_dir`.
+///
+/// The files are written to a temporary directory which is then renamed, so the upgrade only
+/// becomes visible once complete.
+fn stage(
+    root_dir: &Path,
+    protocol_version: &ProtocolVersion,
+    files: &[(String, Vec<u8>)],
+) -> Result<(), Error> {
+    let version_dir = version_dir_name(protocol_version);
+    let staging_dir = root_dir.join(format!(".{}.staging", version_dir));
+    let to_error = |dir: &Path| {
+        let dir = dir.to_path_buf();
+        move |error| Error::Stage { dir, error }
+    };
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir).map_err(to_error(&staging_dir))?;
+    }
+    fs::create_dir_all(&staging_dir).map_err(to_error(&staging_dir))?;
+    for (name, contents) in files {
+        fs::write(staging_dir.join(name), contents).map_err(to_error(&staging_dir))?;
+    }
+    let target_dir = root_dir.join(version_dir);
+    fs::rename(&staging_dir, &target_dir).map_err(to_error(&target_dir))
+}
+
+/// Returns the name of the directory the upgrade watcher expects for `protocol_version`.
+fn version_dir_name(protocol_version: &ProtocolVersion) -> String {
+    protocol_version.to_string().replace('.', "_")
+}
+
+impl<REv> Component<REv> for UpgradeDownloader
+where
+    REv: Send,
+{
+    type Event = Event;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::CheckMirrors => self.check_mirrors(effect_builder),
+            Event::Checked(Some(version)) => {
+                if version > self.latest_version {
+                    self.latest_version = version;
+                }
+                Effects::new()
+            }
+            Event::Checked(None) => Effects::new(),
+        }
+    }
+
+    fn name(&self) -> &str {
+        COMPONENT_NAME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{testing::TestRng, SecretKey};
+
+    use super::*;
+
+    fn manifest(version: ProtocolVersion, chainspec: &[u8]) -> Manifest {
+        Manifest {
+            protocol_version: version,
+            files: vec![ManifestFile {
+                name: CHAINSPEC_FILENAME.to_string(),
+                hash: Digest::hash(chainspec),
+            }],
+        }
+    }
+
+    fn sign(manifest_bytes: &[u8], secret_key: &SecretKey) -> ManifestSignature {
+        let public_key = PublicKey::from(secret_key);
+        ManifestSignature {
+            signature: crypto::sign(manifest_bytes, secret_key, &public_key),
+            public_key,
+        }
+    }
+
+    #[test]
+    fn should_reject_unknown_config_fields() {
+        let config: Config = toml::from_str(
+            r#"
+            enabled = true
+            mirrors = ['https://mirror.example']
+            trusted_keys = []
+            required_signatures = 1
+            check_interval = '5min'
+            request_timeout = '1min'
+            "#,
+        )
+        .unwrap();
+        assert!(config.enabled);
+
+        // A misspelt setting mustn't silently leave the default in place.
+        assert!(toml::from_str::<Config>(
+            r#"
+            enabled = true
+            mirrors = ['https://mirror.example']
+            trusted_keys = []
+            required_signatures = 1
+            require_signatures = 2
+            check_interval = '5min'
+            request_timeout = '1min'
+            "#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn should_require_enough_distinct_trusted_signatures() {
+        let mut rng = TestRng::new();
+        let trusted: Vec<_> = (0..2).map(|_| SecretKey::random(&mut rng)).collect();
+        let untrusted = SecretKey::random(&mut rng);
+        let trusted_keys: Vec<_> = trusted.iter().map(PublicKey::from).collect();
+
+        let manifest_bytes =
+            serde_json::to_vec(&manifest(ProtocolVersion::from_parts(1, 5, 0), b"")).unwrap();
+        let signatures = |keys: &[&SecretKey]| {
+            let signatures: Vec<_> = keys.iter().map(|key| sign(&manifest_bytes, key)).collect();
+            serde_json::to_vec(&signatures).unwrap()
+        };
+
+        let both = signatures(&[&trusted[0], &trusted[1]]);
+        assert!(verify_manifest(&manifest_bytes, &both, &trusted_keys, 2).is_ok());
+
+        let duplicated = signatures(&[&trusted[0], &trusted[0], &untrusted]);
+        assert!(matches!(
+            verify_manifest(&manifest_bytes, &duplicated, &trusted_keys, 2),
+            Err(Error::InsufficientSignatures {
+                valid: 1,
+                required: 2
+            })
+        ));
+
+        let mut tampered = manifest_bytes.clone();
+        tampered.push(b' ');
+        assert!(matches!(
+            verify_manifest(&tampered, &both, &trusted_keys, 1),
+            Err(Error::InsufficientSignatures { valid: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn should_reject_file_names_escaping_the_upgrade_dir() {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random(&mut rng);
+        let mut manifest = manifest(ProtocolVersion::from_parts(1, 5, 0), b"");
+        manifest.files.push(ManifestFile {
+            name: "../config.toml".to_string(),
+            hash: Digest::hash(b""),
+        });
+        let manifest_bytes = serde_json::to_vec(&manifest).unwrap();
+        let signatures = serde_json::to_vec(&[sign(&manifest_bytes, &secret_key)]).unwrap();
+
+        assert!(matches!(
+            verify_manifest(
+                &manifest_bytes,
+                &signatures,
+                &[PublicKey::from(&secret_key)],
+                1
+            ),
+            Err(Error::InvalidFileName(_))
+        ));
+    }
+
+    #[test]
+    fn should_stage_upgrade_for_upgrade_watcher() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let version = ProtocolVersion::from_parts(1, 5, 0);
+        let chainspec = b"[protocol]\nversion = '1.5.0'\n".to_vec();
+        let files = vec![
+            (CHAINSPEC_FILENAME.to_string(), chainspec.clone()),
+            ("bin_metadata.json".to_string(), b"{}".to_vec()),
+        ];
+
+        check_chainspec_version(&manifest(version, &chainspec), &files).unwrap();
+        assert!(matches!(
+            check_chainspec_version(
+                &manifest(ProtocolVersion::from_parts(1, 6, 0), &chainspec),
+                &files
+            ),
+            Err(Error::ChainspecVersionMismatch { .. })
+        ));
+
+        stage(tempdir.path(), &version, &files).unwrap();
+        let staged = tempdir.path().join("1_5_0");
+        assert_eq!(
+            fs::read(staged.join(CHAINSPEC_FILENAME)).unwrap(),
+            chainspec
+        );
+        assert!(staged.join("bin_metadata.json").exists());
+        assert!(!tempdir.path().join(".1_5_0.staging").exists());
+    }
+}
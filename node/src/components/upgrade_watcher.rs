@@ -12,6 +12,7 @@ use std::{
     fs, io,
     path::{Path, PathBuf},
     str::FromStr,
+    sync::Arc,
 };
 
 use datasize::DataSize;
@@ -36,8 +37,9 @@ use crate::{
     reactor::main_reactor::MainEvent,
     types::{
         chainspec::{ProtocolConfig, CHAINSPEC_FILENAME},
-        ActivationPoint, Chainspec,
+        ActivationPoint, Chainspec, ChainspecRawBytes,
     },
+    utils::Loadable,
     NodeRng,
 };
 
@@ -70,6 +72,11 @@ pub(crate) enum Event {
     CheckForNextUpgrade,
     /// If the result of checking for an upgrade is successful, it is passed here.
     GotNextUpgrade(NextUpgrade),
+    /// The result of loading the full chainspec staged for the given version.
+    GotStagedChainspec {
+        version: ProtocolVersion,
+        maybe_chainspec: Option<Arc<Chainspec>>,
+    },
 }
 
 impl Display for Event {
@@ -87,6 +94,9 @@ impl Display for Event {
             Event::GotNextUpgrade(next_upgrade) => {
                 write!(formatter, "got {}", next_upgrade)
             }
+            Event::GotStagedChainspec { version, .. } => {
+                write!(formatter, "got staged chainspec for {}", version)
+            }
         }
     }
 }
@@ -171,6 +181,9 @@ pub(crate) struct UpgradeWatcher {
     root_dir: PathBuf,
     state: ComponentState,
     next_upgrade: Option<NextUpgrade>,
+    /// The full chainspec staged for the next upgrade, reloaded whenever the next upgrade is read.
+    #[data_size(skip)]
+    staged_chainspec: Option<Arc<Chainspec>>,
 }
 
 impl UpgradeWatcher {
@@ -194,6 +207,7 @@ impl UpgradeWatcher {
             root_dir,
             state: ComponentState::Uninitialized,
             next_upgrade,
+            staged_chainspec: None,
         };
 
         Ok(upgrade_watcher)
@@ -262,9 +276,55 @@ impl UpgradeWatcher {
                 );
             }
         }
+        let version = next_upgrade.protocol_version;
+        if self.staged_chainspec.as_ref().map_or(false, |chainspec| {
+            chainspec.protocol_config.version != version
+        }) {
+            self.staged_chainspec = None;
+        }
         self.next_upgrade = Some(next_upgrade);
+
+        // The staged chainspec is re-read on every check, so that edits to it are picked up even
+        // if the next upgrade itself remains unchanged.
+        let root_dir = self.root_dir.clone();
+        async move {
+            task::spawn_blocking(move || staged_chainspec(&root_dir, &version))
+                .await
+                .unwrap_or_else(|error| {
+                    warn!(%error, "failed to join tokio task");
+                    None
+                })
+        }
+        .event(move |maybe_chainspec| Event::GotStagedChainspec {
+            version,
+            maybe_chainspec: maybe_chainspec.map(Arc::new),
+        })
+    }
+
+    fn handle_got_staged_chainspec(
+        &mut self,
+        version: ProtocolVersion,
+        maybe_chainspec: Option<Arc<Chainspec>>,
+    ) -> Effects<Event> {
+        // Ignore results for a version which is no longer the next one.
+        if self.next_upgrade.as_ref().map_or(false, |next_upgrade| {
+            next_upgrade.protocol_version == version
+        }) {
+            self.staged_chainspec = maybe_chainspec;
+        }
         Effects::new()
     }
+
+    fn handle_request(&self, request: UpgradeWatcherRequest) -> Effects<Event> {
+        match request {
+            UpgradeWatcherRequest::GetNextUpgrade(responder) => {
+                responder.respond(self.next_upgrade.clone()).ignore()
+            }
+            UpgradeWatcherRequest::GetStagedChainspec(responder) => {
+                responder.respond(self.staged_chainspec.clone()).ignore()
+            }
+        }
+    }
 }
 
 impl<REv> Component<REv> for UpgradeWatcher
@@ -299,7 +359,10 @@ where
             }
             ComponentState::Initializing => match event {
                 Event::Initialize => self.start_checking_for_upgrades(effect_builder),
-                Event::Request(_) | Event::CheckForNextUpgrade | Event::GotNextUpgrade(_) => {
+                Event::Request(_)
+                | Event::CheckForNextUpgrade
+                | Event::GotNextUpgrade(_)
+                | Event::GotStagedChainspec { .. } => {
                     warn!(
                         ?event,
                         name = <Self as Component<MainEvent>>::name(self),
@@ -317,9 +380,13 @@ where
                     );
                     Effects::new()
                 }
-                Event::Request(request) => request.0.respond(self.next_upgrade.clone()).ignore(),
+                Event::Request(request) => self.handle_request(request),
                 Event::CheckForNextUpgrade => self.check_for_next_upgrade(effect_builder),
                 Event::GotNextUpgrade(next_upgrade) => self.handle_got_next_upgrade(next_upgrade),
+                Event::GotStagedChainspec {
+                    version,
+                    maybe_chainspec,
+                } => self.handle_got_staged_chainspec(version, maybe_chainspec),
             },
         }
     }
@@ -464,6 +531,21 @@ fn next_upgrade(dir: PathBuf, current_version: ProtocolVersion) -> Option<NextUp
     Some(NextUpgrade::from(upgrade_point.protocol_config))
 }
 
+/// Parses the full chainspec installed for `version`, returning `None` if it can't be loaded.
+///
+/// Unlike an `UpgradePoint`, this requires the staged chainspec to be in the format of the current
+/// version.
+fn staged_chainspec(dir: &Path, version: &ProtocolVersion) -> Option<Chainspec> {
+    let subdir = dir.join(dir_name_from_version(version));
+    match <(Chainspec, ChainspecRawBytes)>::from_path(&subdir) {
+        Ok((chainspec, _)) => Some(chainspec),
+        Err(error) => {
+            warn!(subdir=%subdir.display(), %error, "failed to load staged chainspec");
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use casper_types::testing::TestRng;
@@ -0,0 +1,453 @@
+//! Webhook notifications for node operators.
+//!
+//! Sends a JSON notification to every configured URL whenever the node hits a critical event:
+//! an equivocation being detected, the node shutting down for an upgrade, the faulty validators
+//! exceeding the fault tolerance threshold, or the storage volume running low on free space.
+//!
+//! Every notification is signed with the node's secret key; the signature over the exact request
+//! body is sent in the `X-Casper-Signature` header alongside the node's public key in
+//! `X-Casper-Public-Key`. Failed deliveries are retried with exponential backoff. Notifications
+//! which could not be delivered after the configured number of attempts are appended to a
+//! dead-letter file, so no alert is silently lost.
+
+mod config;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use datasize::DataSize;
+use derive_more::From;
+use serde::Serialize;
+use tokio::task;
+use tracing::{debug, error, info, warn};
+
+use casper_types::{crypto, AsymmetricType, EraId, PublicKey, SecretKey, Timestamp};
+
+use crate::{
+    components::Component,
+    effect::{EffectBuilder, EffectExt, Effects},
+    types::NodeRng,
+};
+pub use config::Config;
+
+const COMPONENT_NAME: &str = "webhook_notifier";
+
+/// Header carrying the hex-encoded public key of the notifying node.
+const PUBLIC_KEY_HEADER: &str = "X-Casper-Public-Key";
+/// Header carrying the hex-encoded signature of the request body.
+const SIGNATURE_HEADER: &str = "X-Casper-Signature";
+
+/// A critical event operators are notified about.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum CriticalEvent {
+    /// A validator was found to be equivocating.
+    EquivocationDetected {
+        /// The era in which the equivocation was detected.
+        era_id: EraId,
+        /// The public key of the equivocator.
+        public_key: Box<PublicKey>,
+        /// The time at which the equivocation was detected.
+        timestamp: Timestamp,
+    },
+    /// The node stopped processing blocks in order to shut down for an upgrade.
+    ShutdownForUpgrade,
+    /// The weight of faulty validators exceeded the fault tolerance threshold.
+    FttExceeded {
+        /// The era in which the threshold was exceeded.
+        era_id: EraId,
+    },
+    /// The free space on the storage volume fell below the configured minimum.
+    DiskSpaceLow {
+        /// The storage directory whose volume was checked.
+        path: PathBuf,
+        /// The free space available, in bytes.
+        available_bytes: u64,
+        /// The configured minimum free space, in bytes.
+        threshold_bytes: u64,
+    },
+}
+
+impl Display for CriticalEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CriticalEvent::EquivocationDetected {
+                era_id, public_key, ..
+            } => write!(f, "equivocation by {} in {}", public_key, era_id),
+            CriticalEvent::ShutdownForUpgrade => write!(f, "shutdown for upgrade"),
+            CriticalEvent::FttExceeded { era_id } => {
+                write!(f, "fault tolerance threshold exceeded in {}", era_id)
+            }
+            CriticalEvent::DiskSpaceLow {
+                available_bytes,
+                threshold_bytes,
+                ..
+            } => write!(
+                f,
+                "disk space low: {} bytes available, minimum is {}",
+                available_bytes, threshold_bytes
+            ),
+        }
+    }
+}
+
+/// The body of a notification.
+#[derive(Debug, Serialize)]
+struct Notification<'a> {
+    network_name: &'a str,
+    node_public_key: &'a PublicKey,
+    timestamp: Timestamp,
+    event: &'a CriticalEvent,
+}
+
+/// A notification which could not be delivered, as written to the dead-letter file.
+#[derive(Debug, Serialize)]
+struct DeadLetter<'a> {
+    url: &'a str,
+    attempts: u32,
+    error: String,
+    notification: serde_json::Value,
+}
+
+/// The webhook notifier component's event.
+#[derive(Debug, From, Serialize)]
+pub(crate) enum Event {
+    /// A critical event operators should be notified about.
+    #[from]
+    Notify(CriticalEvent),
+    /// Time to check the free space on the storage volume.
+    CheckDiskSpace,
+    /// The result of checking the free space on the storage volume.
+    GotAvailableDiskSpace(Option<u64>),
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Notify(critical_event) => write!(f, "notify: {}", critical_event),
+            Event::CheckDiskSpace => write!(f, "check disk space"),
+            Event::GotAvailableDiskSpace(Some(available)) => {
+                write!(f, "got available disk space: {} bytes", available)
+            }
+            Event::GotAvailableDiskSpace(None) => write!(f, "failed to get available disk space"),
+        }
+    }
+}
+
+/// Webhook notifier component.
+#[derive(DataSize, Debug)]
+pub(crate) struct WebhookNotifier {
+    config: Config,
+    /// The resolved path of the dead-letter file.
+    dead_letter_path: PathBuf,
+    /// The storage directory whose volume is checked for free space.
+    storage_path: PathBuf,
+    network_name: String,
+    secret_key: Arc<SecretKey>,
+    public_key: PublicKey,
+    #[data_size(skip)]
+    client: reqwest::Client,
+    /// Whether the last disk space check found the free space below the minimum.
+    disk_space_low: bool,
+}
+
+impl WebhookNotifier {
+    /// Creates a new webhook notifier, along with the effects starting its disk space checks.
+    pub(crate) fn new<REv: Send>(
+        config: Config,
+        dead_letter_path: PathBuf,
+        storage_path: PathBuf,
+        network_name: String,
+        secret_key: Arc<SecretKey>,
+        public_key: PublicKey,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Result<(Self, Effects<Event>), reqwest::Error> {
+        let client = reqwest::Client::builder()
+            .timeout(config.request_timeout.into())
+            .build()?;
+        let effects = if config.enabled && config.min_free_disk_space > 0 {
+            effect_builder
+                .immediately()
+                .event(|()| Event::CheckDiskSpace)
+        } else {
+            Effects::new()
+        };
+        let notifier = WebhookNotifier {
+            config,
+            dead_letter_path,
+            storage_path,
+            network_name,
+            secret_key,
+            public_key,
+            client,
+            disk_space_low: false,
+        };
+        Ok((notifier, effects))
+    }
+
+    /// Serializes and signs the notification for `critical_event`.
+    ///
+    /// Returns the request body and the hex-encoded signature over it.
+    fn sign_notification(
+        &self,
+        critical_event: &CriticalEvent,
+    ) -> Result<(Vec<u8>, String), serde_json::Error> {
+        let notification = Notification {
+            network_name: &self.network_name,
+            node_public_key: &self.public_key,
+            timestamp: Timestamp::now(),
+            event: critical_event,
+        };
+        let body = serde_json::to_vec(&notification)?;
+        let signature = crypto::sign(&body, &self.secret_key, &self.public_key);
+        Ok((body, signature.to_hex()))
+    }
+
+    fn notify(&self, critical_event: CriticalEvent) -> Effects<Event> {
+        if !self.config.enabled {
+            return Effects::new();
+        }
+        info!(%critical_event, "notifying operator webhooks");
+        let (body, signature) = match self.sign_notification(&critical_event) {
+            Ok(signed) => signed,
+            Err(error) => {
+                error!(%error, %critical_event, "failed to serialize webhook notification");
+                return Effects::new();
+            }
+        };
+        let body = Arc::new(body);
+        let public_key = self.public_key.to_hex();
+        self.config
+            .urls
+            .iter()
+            .flat_map(|url| {
+                deliver(
+                    self.client.clone(),
+                    url.clone(),
+                    Arc::clone(&body),
+                    public_key.clone(),
+                    signature.clone(),
+                    self.config.max_attempts.max(1),
+                    self.config.retry_delay.into(),
+                    self.dead_letter_path.clone(),
+                )
+                .ignore()
+            })
+            .collect()
+    }
+
+    fn check_disk_space<REv: Send>(&self, effect_builder: EffectBuilder<REv>) -> Effects<Event> {
+        let path = self.storage_path.clone();
+        let mut effects = async move {
+            match task::spawn_blocking(move || fs2::available_space(path)).await {
+                Ok(Ok(available)) => Some(available),
+                Ok(Err(error)) => {
+                    warn!(%error, "failed to get available disk space");
+                    None
+                }
+                Err(error) => {
+                    warn!(%error, "disk space check panicked");
+                    None
+                }
+            }
+        }
+        .event(Event::GotAvailableDiskSpace);
+        effects.extend(
+            effect_builder
+                .set_timeout(self.config.disk_check_interval.into())
+                .event(|_| Event::CheckDiskSpace),
+        );
+        effects
+    }
+
+    fn handle_available_disk_space(&mut self, available_bytes: u64) -> Effects<Event> {
+        let threshold_bytes = self.config.min_free_disk_space;
+        let was_low = self.disk_space_low;
+        self.disk_space_low = available_bytes < threshold_bytes;
+        if !self.disk_space_low {
+            if was_low {
+                info!(available_bytes, threshold_bytes, "disk space recovered");
+            }
+            return Effects::new();
+        }
+        if was_low {
+            // Only the transition to low disk space is notified, not every check finding it so.
+            return Effects::new();
+        }
+        warn!(available_bytes, threshold_bytes, "disk space low");
+        self.notify(CriticalEvent::DiskSpaceLow {
+            path: self.storage_path.clone(),
+            available_bytes,
+            threshold_bytes,
+        })
+    }
+}
+
+/// POSTs `body` to `url`, retrying up to `max_attempts` times in total.
+///
+/// If all attempts fail, the notification is appended to the dead-letter file.
+#[allow(clippy::too_many_arguments)]
+async fn deliver(
+    client: reqwest::Client,
+    url: String,
+    body: Arc<Vec<u8>>,
+    public_key: String,
+    signature: String,
+    max_attempts: u32,
+    retry_delay: Duration,
+    dead_letter_path: PathBuf,
+) {
+    let mut delay = retry_delay;
+    let mut attempt = 1;
+    let error = loop {
+        let result = client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(PUBLIC_KEY_HEADER, &public_key)
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body.as_ref().clone())
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        match result {
+            Ok(_) => {
+                debug!(%url, attempt, "delivered webhook notification");
+                return;
+            }
+            Err(error) if attempt >= max_attempts => break error.to_string(),
+            Err(error) => {
+                debug!(%url, attempt, %error, "failed to deliver webhook notification, retrying");
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+        }
+    };
+
+    warn!(%url, attempts = attempt, %error, "giving up on webhook notification");
+    let written = task::spawn_blocking(move || {
+        write_dead_letter(&dead_letter_path, &url, attempt, error, &body)
+    })
+    .await;
+    match written {
+        Ok(Ok(())) => {}
+        Ok(Err(error)) => error!(%error, "failed to write webhook dead letter"),
+        Err(error) => error!(%error, "writing webhook dead letter panicked"),
+    }
+}
+
+/// Appends an undeliverable notification to the dead-letter file as a single JSON line.
+fn write_dead_letter(
+    path: &Path,
+    url: &str,
+    attempts: u32,
+    error: String,
+    body: &[u8],
+) -> Result<(), std::io::Error> {
+    let notification = serde_json::from_slice(body)?;
+    let mut line = serde_json::to_vec(&DeadLetter {
+        url,
+        attempts,
+        error,
+        notification,
+    })?;
+    line.push(b'\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(&line)
+}
+
+impl<REv> Component<REv> for WebhookNotifier
+where
+    REv: Send,
+{
+    type Event = Event;
+
+    fn handle_event(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        _rng: &mut NodeRng,
+        event: Self::Event,
+    ) -> Effects<Self::Event> {
+        match event {
+            Event::Notify(critical_event) => self.notify(critical_event),
+            Event::CheckDiskSpace => self.check_disk_space(effect_builder),
+            Event::GotAvailableDiskSpace(Some(available_bytes)) => {
+                self.handle_available_disk_space(available_bytes)
+            }
+            Event::GotAvailableDiskSpace(None) => Effects::new(),
+        }
+    }
+
+    fn name(&self) -> &str {
+        COMPONENT_NAME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{testing::TestRng, Signature};
+
+    use super::*;
+
+    #[test]
+    fn should_sign_notification_body() {
+        let mut rng = TestRng::new();
+        let secret_key = Arc::new(SecretKey::random(&mut rng));
+        let public_key = PublicKey::from(&*secret_key);
+        let notifier = WebhookNotifier {
+            config: Config::default(),
+            dead_letter_path: PathBuf::new(),
+            storage_path: PathBuf::new(),
+            network_name: "test-network".to_string(),
+            secret_key,
+            public_key: public_key.clone(),
+            client: reqwest::Client::new(),
+            disk_space_low: false,
+        };
+
+        let critical_event = CriticalEvent::FttExceeded {
+            era_id: EraId::new(3),
+        };
+        let (body, signature) = notifier.sign_notification(&critical_event).unwrap();
+
+        let signature = Signature::from_hex(signature).unwrap();
+        crypto::verify(&body, &signature, &public_key).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["network_name"], "test-network");
+        assert_eq!(json["event"]["type"], "ftt_exceeded");
+        assert_eq!(json["event"]["era_id"], 3);
+    }
+
+    #[test]
+    fn should_append_dead_letters_as_json_lines() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("dead_letters.log");
+        let body = br#"{"event":{"type":"shutdown_for_upgrade"}}"#;
+
+        for url in ["http://a.example", "http://b.example"] {
+            write_dead_letter(&path, url, 5, "connection refused".to_string(), body).unwrap();
+        }
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["url"], "http://b.example");
+        assert_eq!(lines[1]["attempts"], 5);
+        assert_eq!(
+            lines[1]["notification"]["event"]["type"],
+            "shutdown_for_upgrade"
+        );
+    }
+}
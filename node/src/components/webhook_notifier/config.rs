@@ -0,0 +1,53 @@
+use std::{path::PathBuf, str::FromStr};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use casper_types::TimeDiff;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_RETRY_DELAY: &str = "2sec";
+const DEFAULT_REQUEST_TIMEOUT: &str = "10sec";
+const DEFAULT_DEAD_LETTER_PATH: &str = "webhook_dead_letters.log";
+const DEFAULT_DISK_CHECK_INTERVAL: &str = "1min";
+
+/// Configuration options for operator webhook notifications.
+#[derive(Clone, DataSize, Debug, Deserialize, Serialize)]
+// Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Whether or not notifications are sent.
+    pub enabled: bool,
+    /// The URLs every notification is POSTed to.
+    pub urls: Vec<String>,
+    /// Maximum number of delivery attempts per URL before a notification is dead-lettered.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled after every further failed attempt.
+    pub retry_delay: TimeDiff,
+    /// Timeout for a single delivery attempt.
+    pub request_timeout: TimeDiff,
+    /// File to which undeliverable notifications are appended, one JSON object per line.
+    ///
+    /// If relative, it is relative to the directory of the config file.
+    pub dead_letter_path: PathBuf,
+    /// Free space in bytes below which the storage volume triggers a notification. 0 disables
+    /// the check.
+    pub min_free_disk_space: u64,
+    /// Interval at which the free space of the storage volume is checked.
+    pub disk_check_interval: TimeDiff,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enabled: false,
+            urls: vec![],
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            retry_delay: TimeDiff::from_str(DEFAULT_RETRY_DELAY).unwrap(),
+            request_timeout: TimeDiff::from_str(DEFAULT_REQUEST_TIMEOUT).unwrap(),
+            dead_letter_path: DEFAULT_DEAD_LETTER_PATH.into(),
+            min_free_disk_space: 0,
+            disk_check_interval: TimeDiff::from_str(DEFAULT_DISK_CHECK_INTERVAL).unwrap(),
+        }
+    }
+}
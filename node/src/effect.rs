@@ -98,10 +98,12 @@ pub(crate) mod requests;
 use std::{
     any::type_name,
     borrow::Cow,
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     future::Future,
     mem,
+    net::IpAddr,
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -116,17 +118,29 @@ use tracing::{debug, error, warn};
 
 use casper_execution_engine::{
     core::engine_state::{
-        self, era_validators::GetEraValidatorsError, BalanceRequest, BalanceResult, GetBidsRequest,
-        GetBidsResult, QueryRequest, QueryResult,
+        self, account_sequence::account_sequence_key, era_validators::GetEraValidatorsError,
+        BalanceRequest, BalanceResult, ExecutionResultProof, GetBidsRequest, GetBidsResult,
+        GetUnbondsRequest, GetUnbondsResult, KeySpaceUsage, QueryRequest, QueryResult, ViewRequest,
+        ViewResult,
     },
     shared::execution_journal::ExecutionJournal,
-    storage::trie::TrieRaw,
+    storage::{
+        global_state::{
+            backup::{BackupError, BackupManifest},
+            checkpoint::{Checkpoint, CheckpointError},
+            compaction::CompactionError,
+        },
+        trie::TrieRaw,
+        trie_store::{integrity::TrieIntegrityReport, stats::TrieStoreStats},
+    },
 };
 use casper_hashing::Digest;
 use casper_types::{
-    account::Account, bytesrepr::Bytes, system::auction::EraValidators, Contract, ContractPackage,
-    EraId, ExecutionEffect, ExecutionResult, Key, PublicKey, TimeDiff, Timestamp, Transfer, URef,
-    U512,
+    account::{Account, AccountHash},
+    bytesrepr::Bytes,
+    system::auction::{EraRewardReport, EraValidators},
+    Contract, ContractPackage, EraId, ExecutionEffect, ExecutionResult, Gas, Key, PublicKey,
+    TimeDiff, Timestamp, Transfer, URef, U512,
 };
 
 use crate::{
@@ -139,22 +153,31 @@ use crate::{
         contract_runtime::{ContractRuntimeError, EraValidatorsRequest},
         deploy_acceptor,
         diagnostics_port::StopAtSpec,
-        fetcher::{FetchItem, FetchResult},
+        fetcher::{FetchItem, FetchResult, FetchedData},
         gossiper::GossipItem,
-        network::{blocklist::BlocklistJustification, FromIncoming, NetworkInsights},
+        network::{
+            blocklist::BlocklistJustification, ConnectionId, ConnectionInfo, FromIncoming,
+            HandshakeFailureReport, NetworkInsights, PartitionReport, PeerLookupReport,
+        },
         upgrade_watcher::NextUpgrade,
     },
     contract_runtime::SpeculativeExecutionState,
-    reactor::{main_reactor::ReactorState, EventQueueHandle, QueueKind},
+    reactor::{
+        main_reactor::{ComponentRestarts, ReactorState},
+        EventQueueHandle, QueueKind,
+    },
+    rpcs::chain::ProposerBlockRange,
     types::{
-        appendable_block::AppendableBlock, ApprovalsHashes, AvailableBlockRange, Block,
-        BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash, BlockHeader,
-        BlockSignatures, BlockWithMetadata, ChainspecRawBytes, Deploy, DeployHash, DeployHeader,
-        DeployId, DeployMetadataExt, DeployWithFinalizedApprovals, FinalitySignature,
-        FinalitySignatureId, FinalizedApprovals, FinalizedBlock, LegacyDeploy, MetaBlock,
-        MetaBlockState, NodeId, TrieOrChunk, TrieOrChunkId,
+        appendable_block::AppendableBlock, Approval, ApprovalsHashes, AvailableBlockRange, Block,
+        BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash,
+        BlockHashAndHeight, BlockHeader, BlockSignatures, BlockWithMetadata, BlockWriteStats,
+        Chainspec, ChainspecRawBytes, Deploy, DeployExecutionResult, DeployExecutionResultId,
+        DeployExecutionResultValidationMetadata, DeployHash, DeployHeader, DeployId,
+        DeployMetadataExt, DeployWithFinalizedApprovals, FinalitySignature, FinalitySignatureId,
+        FinalizedApprovals, FinalizedBlock, KeyWrite, LegacyDeploy, MetaBlock, MetaBlockState,
+        NodeId, TrieOrChunk, TrieOrChunkId,
     },
-    utils::{fmt_limit::FmtLimit, SharedFlag, Source},
+    utils::{fmt_limit::FmtLimit, short_id::IdOrShortId, SharedFlag, Source},
 };
 use announcements::{
     BlockAccumulatorAnnouncement, ConsensusAnnouncement, ContractRuntimeAnnouncement,
@@ -165,10 +188,10 @@ use announcements::{
 use diagnostics_port::DumpConsensusStateRequest;
 use requests::{
     BeginGossipRequest, BlockAccumulatorRequest, BlockCompleteConfirmationRequest,
-    BlockSynchronizerRequest, BlockValidationRequest, ChainspecRawBytesRequest, ConsensusRequest,
-    FetcherRequest, MakeBlockExecutableRequest, NetworkInfoRequest, NetworkRequest,
-    ReactorStatusRequest, StorageRequest, SyncGlobalStateRequest, TrieAccumulatorRequest,
-    UpgradeWatcherRequest,
+    BlockSynchronizerRequest, BlockValidationRequest, ChainspecRawBytesRequest,
+    ComponentRestartsRequest, ConsensusRequest, FetcherRequest, MakeBlockExecutableRequest,
+    NetworkInfoRequest, NetworkRequest, ReactorStatusRequest, StorageRequest,
+    SyncGlobalStateRequest, TrieAccumulatorRequest, UpgradeWatcherRequest,
 };
 
 use self::requests::{
@@ -776,6 +799,63 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Looks up an open or recently closed connection by its full or short ID.
+    pub(crate) async fn get_network_connection(
+        self,
+        connection_id: IdOrShortId<ConnectionId>,
+    ) -> Option<ConnectionInfo>
+    where
+        REv: From<NetworkInfoRequest>,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::Connection {
+                connection_id,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Resolves a full or short node ID to the matching peers and their addresses.
+    pub(crate) async fn lookup_network_peer(self, node_id: IdOrShortId<NodeId>) -> PeerLookupReport
+    where
+        REv: From<NetworkInfoRequest>,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::LookupPeer { node_id, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the recorded failed connection attempts involving `ip`, or all addresses if `None`.
+    pub(crate) async fn get_handshake_failures(self, ip: Option<IpAddr>) -> HandshakeFailureReport
+    where
+        REv: From<NetworkInfoRequest>,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::HandshakeFailures { ip, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Probes up to `sample_size` known peers and diagnoses whether the node is partitioned.
+    pub(crate) async fn diagnose_network_partition(self, sample_size: usize) -> PartitionReport
+    where
+        REv: From<NetworkInfoRequest>,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::DiagnosePartition {
+                sample_size,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets a map of the current network peers to their socket addresses.
     pub(crate) async fn network_peers(self) -> BTreeMap<NodeId, String>
     where
@@ -800,7 +880,7 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
-    /// Announces which deploys have expired.
+    /// Announces which deploys have expired without being included in a block.
     pub(crate) async fn announce_expired_deploys(self, hashes: Vec<DeployHash>)
     where
         REv: From<DeployBufferAnnouncement>,
@@ -1024,6 +1104,56 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces the rewards distributed at the end of an era.
+    pub(crate) async fn announce_era_rewards_distributed(self, reward_report: EraRewardReport)
+    where
+        REv: From<ContractRuntimeAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                ContractRuntimeAnnouncement::EraRewardsDistributed {
+                    reward_report: Box::new(reward_report),
+                },
+                QueueKind::ContractRuntime,
+            )
+            .await
+    }
+
+    /// Announces the values an executed block wrote to global state.
+    pub(crate) async fn announce_block_write_stats(self, write_stats: BlockWriteStats)
+    where
+        REv: From<ContractRuntimeAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                ContractRuntimeAnnouncement::BlockWriteStats {
+                    write_stats: Box::new(write_stats),
+                },
+                QueueKind::ContractRuntime,
+            )
+            .await
+    }
+
+    /// Announces that tries in the global state of the block `block_hash` were quarantined as
+    /// corrupted while executing its child.
+    pub(crate) async fn announce_tries_quarantined(
+        self,
+        block_hash: BlockHash,
+        trie_hashes: Vec<Digest>,
+    ) where
+        REv: From<ContractRuntimeAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                ContractRuntimeAnnouncement::TriesQuarantined {
+                    block_hash,
+                    trie_hashes,
+                },
+                QueueKind::ContractRuntime,
+            )
+            .await
+    }
+
     /// Announces validators for upcoming era.
     pub(crate) async fn announce_upcoming_era_validators(
         self,
@@ -1091,12 +1221,14 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
-    /// Puts the given block and approvals hashes into the linear block store.
+    /// Puts the given block, approvals hashes and execution results into the linear block store,
+    /// recording the keys written by the block in the key history index.
     pub(crate) async fn put_executed_block_to_storage(
         self,
         block: Arc<Block>,
         approvals_hashes: Box<ApprovalsHashes>,
         execution_results: HashMap<DeployHash, ExecutionResult>,
+        written_keys: Vec<Key>,
     ) -> bool
     where
         REv: From<StorageRequest>,
@@ -1106,6 +1238,7 @@ impl<REv> EffectBuilder<REv> {
                 block,
                 approvals_hashes,
                 execution_results,
+                written_keys,
                 responder,
             },
             QueueKind::ToStorage,
@@ -1255,6 +1388,29 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Puts the given block and its finality signatures into the linear block store, marking the
+    /// block complete if `mark_complete` is set.  Either all of them are stored or none are.
+    pub(crate) async fn put_signed_block_to_storage(
+        self,
+        block: Arc<Block>,
+        block_signatures: BlockSignatures,
+        mark_complete: bool,
+    ) -> bool
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutSignedBlock {
+                block,
+                block_signatures: Box::new(block_signatures),
+                mark_complete,
+                responder,
+            },
+            QueueKind::ToStorage,
+        )
+        .await
+    }
+
     pub(crate) async fn put_finality_signature_to_storage(
         self,
         signature: FinalitySignature,
@@ -1290,6 +1446,92 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the hashes and heights of the blocks in `range` created by `proposer` from storage.
+    pub(crate) async fn get_blocks_by_proposer_from_storage(
+        self,
+        proposer: Box<PublicKey>,
+        range: ProposerBlockRange,
+    ) -> Vec<BlockHashAndHeight>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetBlocksByProposer {
+                proposer,
+                range,
+                responder,
+            },
+            QueueKind::FromStorage,
+        )
+        .await
+    }
+
+    /// Gets the heights and state root hashes of the blocks with heights between `low` and `high`,
+    /// inclusive, from storage.
+    pub(crate) async fn get_state_root_hash_range_from_storage(
+        self,
+        low: u64,
+        high: u64,
+    ) -> Vec<(u64, Digest)>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetStateRootHashRange {
+                low,
+                high,
+                responder,
+            },
+            QueueKind::FromStorage,
+        )
+        .await
+    }
+
+    /// Gets the hashes of up to `limit` stored deploys carrying `user_tag` from storage, ordered by
+    /// deploy hash and starting after `after`, if given.
+    pub(crate) async fn get_deploys_by_tag_from_storage(
+        self,
+        user_tag: Bytes,
+        after: Option<DeployHash>,
+        limit: usize,
+    ) -> Vec<DeployHash>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetDeploysByTag {
+                user_tag,
+                after,
+                limit,
+                responder,
+            },
+            QueueKind::FromStorage,
+        )
+        .await
+    }
+
+    /// Gets the most recent blocks below `before_height` which wrote to `key`, newest first.
+    ///
+    /// Returns `None` if the key history index is disabled.
+    pub(crate) async fn get_key_history_from_storage(
+        self,
+        key: Key,
+        before_height: Option<u64>,
+    ) -> Option<Vec<KeyWrite>>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetKeyHistory {
+                key,
+                before_height,
+                responder,
+            },
+            QueueKind::FromStorage,
+        )
+        .await
+    }
+
     /// Requests the header of the block containing the given deploy.
     pub(crate) async fn get_block_header_for_deploy_from_storage(
         self,
@@ -1392,6 +1634,15 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Gets the restart history of the components supervised by the reactor.
+    pub(crate) async fn get_component_restarts(self) -> Vec<ComponentRestarts>
+    where
+        REv: From<ComponentRestartsRequest>,
+    {
+        self.make_request(ComponentRestartsRequest, QueueKind::Regular)
+            .await
+    }
+
     pub(crate) async fn get_block_synchronizer_status(self) -> BlockSynchronizerStatus
     where
         REv: From<BlockSynchronizerRequest>,
@@ -1441,6 +1692,165 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Returns the tries missing from the trie store among `state_root_hash` and its children; an
+    /// empty result means the state root is complete.
+    pub(crate) async fn get_missing_state_root_tries(
+        self,
+        state_root_hash: Digest,
+    ) -> Result<Vec<Digest>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetMissingStateRootTries {
+                state_root_hash,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Returns the first `limit` tries below `state_root_hash` which are missing from the trie
+    /// store; an empty result means the state root is complete.
+    pub(crate) async fn get_missing_trie_descendants(
+        self,
+        state_root_hash: Digest,
+        limit: usize,
+    ) -> Result<Vec<Digest>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetMissingTrieDescendants {
+                state_root_hash,
+                limit,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Returns the hashes of the tries quarantined because their stored bytes failed to
+    /// deserialize, which are to be fetched from peers again.
+    pub(crate) async fn get_quarantined_tries(self) -> Result<Vec<Digest>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetQuarantinedTries { responder },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Checks that every trie below `state_root_hash` is present and intact, reporting at most
+    /// `limit` missing or corrupted tries.
+    pub(crate) async fn verify_trie_integrity(
+        self,
+        state_root_hash: Digest,
+        limit: usize,
+    ) -> Result<TrieIntegrityReport, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::VerifyTrieIntegrity {
+                state_root_hash,
+                limit,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Gathers statistics about the tries in global state, along with the shape of the trie below
+    /// `state_root_hash` if given.
+    pub(crate) async fn get_trie_store_stats(
+        self,
+        state_root_hash: Option<Digest>,
+    ) -> Result<TrieStoreStats, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetTrieStoreStats {
+                state_root_hash,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Attributes the global state at `state_root_hash`, or at the last committed state root if
+    /// `None`, to the accounts and contracts owning it, listing the `limit` largest owners.
+    pub(crate) async fn get_key_space_usage(
+        self,
+        state_root_hash: Option<Digest>,
+        limit: usize,
+    ) -> Result<KeySpaceUsage, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetKeySpaceUsage {
+                state_root_hash,
+                limit,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Writes a hot backup of global state at the last committed state root to a new tarball at
+    /// `path`.
+    pub(crate) async fn backup_global_state(
+        self,
+        path: PathBuf,
+    ) -> Result<BackupManifest, BackupError>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::BackupGlobalState { path, responder },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Copies global state at the last committed state root into a checkpoint in the directory
+    /// `dir`.
+    pub(crate) async fn checkpoint_global_state(
+        self,
+        dir: PathBuf,
+    ) -> Result<Checkpoint, CheckpointError>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::CheckpointGlobalState { dir, responder },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Requests global state to be compacted, reclaiming the space of its free pages, the next
+    /// time the node starts.
+    pub(crate) async fn request_global_state_compaction(self) -> Result<(), CompactionError>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::RequestGlobalStateCompaction { responder },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
     /// Puts the given deploy into the deploy store.
     pub(crate) async fn put_deploy_to_storage(self, deploy: Box<Deploy>) -> bool
     where
@@ -1453,6 +1863,31 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Adds the given approvals to those collected for a deploy, returning all the approvals known
+    /// for it.
+    pub(crate) async fn put_deploy_approvals_to_storage(
+        self,
+        deploy_hash: DeployHash,
+        expires: Timestamp,
+        max_approvals: usize,
+        approvals: BTreeSet<Approval>,
+    ) -> BTreeSet<Approval>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutDeployApprovals {
+                deploy_hash,
+                expires,
+                max_approvals,
+                approvals,
+                responder,
+            },
+            QueueKind::ToStorage,
+        )
+        .await
+    }
+
     /// Gets the requested deploys from the deploy store.
     ///
     /// Returns the "original" deploys, which are the first received by the node, along with a
@@ -1609,6 +2044,26 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the switch block of `era_id` with its associated metadata.
+    pub(crate) async fn get_switch_block_with_metadata_from_storage(
+        self,
+        era_id: EraId,
+        only_from_available_block_range: bool,
+    ) -> Option<BlockWithMetadata>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetSwitchBlockAndMetadataByEraId {
+                era_id,
+                only_from_available_block_range,
+                responder,
+            },
+            QueueKind::FromStorage,
+        )
+        .await
+    }
+
     /// Gets the highest block with its associated metadata.
     pub(crate) async fn get_highest_block_with_metadata_from_storage(
         self,
@@ -1646,6 +2101,52 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Backfills from up to `peer_count` peers the execution result of the given deploy in the
+    /// given block, which the fetcher stores once validated.
+    ///
+    /// The result is proven against the execution results Merkle root held in the block's approvals
+    /// hashes, so neither the block's global state nor the results of its other deploys are needed.
+    ///
+    /// Returns `None` if the block or its approvals hashes aren't held, if the block was executed
+    /// before execution results Merkle trees were enabled, or if no peer provided a valid result.
+    pub(crate) async fn backfill_deploy_execution_result(
+        self,
+        block_hash: BlockHash,
+        deploy_hash: DeployHash,
+        peer_count: usize,
+    ) -> Option<ExecutionResult>
+    where
+        REv: From<StorageRequest>
+            + From<NetworkInfoRequest>
+            + From<FetcherRequest<DeployExecutionResult>>,
+    {
+        let block = self.get_block_from_storage(block_hash).await?;
+        let index = block
+            .deploy_and_transfer_hashes()
+            .position(|hash| *hash == deploy_hash)?;
+        let execution_results_root = self
+            .get_approvals_hashes_from_storage(block_hash)
+            .await?
+            .execution_results_merkle_root()?;
+        let id = DeployExecutionResultId::new(block_hash, deploy_hash);
+        let validation_metadata = DeployExecutionResultValidationMetadata {
+            execution_results_root,
+            index: index as u64,
+        };
+        for peer in self.get_fully_connected_peers(peer_count).await {
+            match self
+                .fetch::<DeployExecutionResult>(id, peer, validation_metadata)
+                .await
+            {
+                Ok(FetchedData::FromStorage { item }) | Ok(FetchedData::FromPeer { item, .. }) => {
+                    return Some(item.into_execution_result());
+                }
+                Err(error) => debug!(%error, "failed to backfill deploy execution result"),
+            }
+        }
+        None
+    }
+
     pub(crate) async fn fetch_trie(
         self,
         hash: Digest,
@@ -1779,6 +2280,19 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// The faulty validators in an era exceeded the fault tolerance threshold.
+    pub(crate) async fn announce_ftt_exceeded(self, era_id: EraId)
+    where
+        REv: From<ConsensusAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                ConsensusAnnouncement::FttExceeded { era_id },
+                QueueKind::Consensus,
+            )
+            .await
+    }
+
     /// Blocks a specific peer due to a transgression.
     ///
     /// This function will also emit a log message for the block.
@@ -1806,10 +2320,22 @@ impl<REv> EffectBuilder<REv> {
     where
         REv: From<UpgradeWatcherRequest> + Send,
     {
-        self.make_request(UpgradeWatcherRequest, QueueKind::Control)
+        self.make_request(UpgradeWatcherRequest::GetNextUpgrade, QueueKind::Control)
             .await
     }
 
+    /// Gets the parsed chainspec of the next scheduled upgrade, if any.
+    pub(crate) async fn get_staged_chainspec(self) -> Option<Arc<Chainspec>>
+    where
+        REv: From<UpgradeWatcherRequest> + Send,
+    {
+        self.make_request(
+            UpgradeWatcherRequest::GetStagedChainspec,
+            QueueKind::Control,
+        )
+        .await
+    }
+
     /// Requests a query be executed on the Contract Runtime component.
     pub(crate) async fn query_global_state(
         self,
@@ -1865,6 +2391,51 @@ impl<REv> EffectBuilder<REv> {
         }
     }
 
+    /// Retrieves the last sequence number used by an account, returns `None` if it can't be
+    /// determined.
+    pub(crate) async fn get_account_sequence_from_global_state(
+        self,
+        prestate_hash: Digest,
+        account_hash: AccountHash,
+    ) -> Option<u64>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        let query_request =
+            QueryRequest::new(prestate_hash, account_sequence_key(account_hash), vec![]);
+        match self.query_global_state(query_request).await {
+            Ok(QueryResult::Success { value, .. }) => value
+                .as_cl_value()
+                .and_then(|cl_value| cl_value.clone().into_t().ok()),
+            // Accounts which never used a sequence number have no entry yet.
+            Ok(QueryResult::ValueNotFound(_)) => Some(0),
+            Ok(_) | Err(_) => None,
+        }
+    }
+
+    /// Retrieves the gas left of an account's computation quota in the current era, returns `None`
+    /// if it can't be determined.
+    pub(crate) async fn get_remaining_computation_quota(
+        self,
+        prestate_hash: Digest,
+        account_hash: AccountHash,
+    ) -> Option<Gas>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetRemainingComputationQuota {
+                state_root_hash: prestate_hash,
+                account_hash,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+        .ok()
+        .flatten()
+    }
+
     /// Retrieves an `Contract` from global state if present.
     pub(crate) async fn get_contract_for_validation(
         self,
@@ -1952,6 +2523,24 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Requests the pending unbonds from the Contract Runtime component.
+    pub(crate) async fn get_unbonds(
+        self,
+        get_unbonds_request: GetUnbondsRequest,
+    ) -> Result<GetUnbondsResult, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetUnbonds {
+                get_unbonds_request,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
     /// Returns the value of the execution results checksum stored in the ChecksumRegistry for the
     /// given state root hash.
     pub(crate) async fn get_execution_results_checksum(
@@ -1971,6 +2560,69 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Anchors a state root synchronized from peers to the block at `block_height` in `era_id`.
+    pub(crate) async fn anchor_state_root(
+        self,
+        state_root_hash: Digest,
+        block_height: u64,
+        era_id: EraId,
+    ) -> Result<(), engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::AnchorStateRoot {
+                state_root_hash,
+                block_height,
+                era_id,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Returns a proof that the execution result at `index` of `execution_results` is part of the
+    /// block with the given state root hash.
+    pub(crate) async fn get_execution_result_proof(
+        self,
+        state_root_hash: Digest,
+        execution_results: Vec<ExecutionResult>,
+        index: u64,
+    ) -> Result<ExecutionResultProof, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetExecutionResultProof {
+                state_root_hash,
+                execution_results,
+                index,
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
+    /// Calls a contract's entry point in read-only mode, without a deploy.
+    pub(crate) async fn call_contract_view(
+        self,
+        view_request: ViewRequest,
+    ) -> Result<ViewResult, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::CallView {
+                view_request: Box::new(view_request),
+                responder,
+            },
+            QueueKind::ContractRuntime,
+        )
+        .await
+    }
+
     /// Get our public key from consensus, and if we're a validator, the next round length.
     pub(crate) async fn consensus_status(self) -> Option<(PublicKey, Option<TimeDiff>)>
     where
@@ -2053,6 +2705,18 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the parsed chainspec the node is running.
+    pub(crate) async fn get_chainspec(self) -> Arc<Chainspec>
+    where
+        REv: From<ChainspecRawBytesRequest> + Send,
+    {
+        self.make_request(
+            ChainspecRawBytesRequest::GetChainspec,
+            QueueKind::NetworkInfo,
+        )
+        .await
+    }
+
     /// Stores a set of given finalized approvals in storage.
     ///
     /// Any previously stored finalized approvals for the given hash are quietly overwritten
@@ -2111,6 +2775,36 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the execution result of a deploy in a block, with its proof, from storage.
+    pub(crate) async fn get_deploy_execution_result_from_storage(
+        self,
+        id: DeployExecutionResultId,
+    ) -> Option<DeployExecutionResult>
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetDeployExecutionResult { id, responder },
+            QueueKind::FromStorage,
+        )
+        .await
+    }
+
+    /// Stores the execution result of a single deploy in a block, backfilled from a peer.
+    pub(crate) async fn put_deploy_execution_result_to_storage(
+        self,
+        item: Box<DeployExecutionResult>,
+    ) -> bool
+    where
+        REv: From<StorageRequest>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutDeployExecutionResult { item, responder },
+            QueueKind::ToStorage,
+        )
+        .await
+    }
+
     /// Gets peers for a given block from the block accumulator.
     pub(crate) async fn get_block_accumulated_peers(
         self,
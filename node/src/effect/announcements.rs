@@ -13,7 +13,10 @@ use datasize::DataSize;
 use itertools::Itertools;
 use serde::Serialize;
 
-use casper_types::{EraId, ExecutionEffect, PublicKey, Timestamp, U512};
+use casper_hashing::Digest;
+use casper_types::{
+    system::auction::EraRewardReport, EraId, ExecutionEffect, PublicKey, Timestamp, U512,
+};
 
 use crate::{
     components::{
@@ -25,7 +28,10 @@ use crate::{
         upgrade_watcher::NextUpgrade,
     },
     effect::Responder,
-    types::{Deploy, DeployHash, FinalitySignature, FinalizedBlock, MetaBlock, NodeId},
+    types::{
+        BlockHash, BlockWriteStats, Deploy, DeployHash, FinalitySignature, FinalizedBlock,
+        MetaBlock, NodeId,
+    },
     utils::Source,
 };
 
@@ -217,7 +223,7 @@ impl Display for DeployAcceptorAnnouncement {
 
 #[derive(Debug, Serialize)]
 pub(crate) enum DeployBufferAnnouncement {
-    /// Hashes of the deploys that expired.
+    /// Hashes of the deploys that expired without being included in a block.
     DeploysExpired(Vec<DeployHash>),
 }
 
@@ -247,6 +253,11 @@ pub(crate) enum ConsensusAnnouncement {
         /// The timestamp when the evidence of the equivocation was detected.
         timestamp: Timestamp,
     },
+    /// The weight of faulty validators exceeded the fault tolerance threshold.
+    FttExceeded {
+        /// The Id of the era in which the threshold was exceeded.
+        era_id: EraId,
+    },
 }
 
 impl Display for ConsensusAnnouncement {
@@ -267,6 +278,11 @@ impl Display for ConsensusAnnouncement {
                 "Validator fault with public key: {} has been identified at time: {} in {}",
                 public_key, timestamp, era_id,
             ),
+            ConsensusAnnouncement::FttExceeded { era_id } => write!(
+                formatter,
+                "fault tolerance threshold exceeded in {}",
+                era_id
+            ),
         }
     }
 }
@@ -356,6 +372,16 @@ pub(crate) enum ContractRuntimeAnnouncement {
         /// The operations and transforms committed to global state.
         execution_effect: ExecutionEffect,
     },
+    /// Rewards were distributed at the end of an era.
+    EraRewardsDistributed {
+        /// The per-validator and per-delegator breakdown of the rewards.
+        reward_report: Box<EraRewardReport>,
+    },
+    /// A block was executed, writing to global state.
+    BlockWriteStats {
+        /// The values written by the block, by kind of key.
+        write_stats: Box<BlockWriteStats>,
+    },
     /// New era validators.
     UpcomingEraValidators {
         /// The era id in which the step was committed to global state.
@@ -363,6 +389,14 @@ pub(crate) enum ContractRuntimeAnnouncement {
         /// The validators for the eras after the `era_that_is_ending` era.
         upcoming_era_validators: BTreeMap<EraId, BTreeMap<PublicKey, U512>>,
     },
+    /// Executing the child of a block failed on tries of its global state which were corrupted,
+    /// and have been quarantined.
+    TriesQuarantined {
+        /// The block whose global state holds the quarantined tries.
+        block_hash: BlockHash,
+        /// The hashes of all the tries currently quarantined.
+        trie_hashes: Vec<Digest>,
+    },
 }
 
 impl Display for ContractRuntimeAnnouncement {
@@ -371,6 +405,12 @@ impl Display for ContractRuntimeAnnouncement {
             ContractRuntimeAnnouncement::CommitStepSuccess { era_id, .. } => {
                 write!(f, "commit step completed for {}", era_id)
             }
+            ContractRuntimeAnnouncement::EraRewardsDistributed { reward_report } => {
+                write!(f, "rewards distributed for {}", reward_report.era_id())
+            }
+            ContractRuntimeAnnouncement::BlockWriteStats { write_stats } => {
+                write!(f, "write stats of block {}", write_stats.block_hash)
+            }
             ContractRuntimeAnnouncement::UpcomingEraValidators {
                 era_that_is_ending, ..
             } => {
@@ -380,6 +420,17 @@ impl Display for ContractRuntimeAnnouncement {
                     era_that_is_ending,
                 )
             }
+            ContractRuntimeAnnouncement::TriesQuarantined {
+                block_hash,
+                trie_hashes,
+            } => {
+                write!(
+                    f,
+                    "{} tries quarantined in global state of {}",
+                    trie_hashes.len(),
+                    block_hash
+                )
+            }
         }
     }
 }
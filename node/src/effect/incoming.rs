@@ -97,6 +97,8 @@ pub(crate) enum NetRequest {
     SyncLeap(Vec<u8>),
     ApprovalsHashes(Vec<u8>),
     BlockExecutionResults(Vec<u8>),
+    DeployApprovals(Vec<u8>),
+    DeployExecutionResult(Vec<u8>),
 }
 
 impl Display for NetRequest {
@@ -114,6 +116,10 @@ impl Display for NetRequest {
             NetRequest::BlockExecutionResults(_) => {
                 f.write_str("request for block execution results")
             }
+            NetRequest::DeployApprovals(_) => f.write_str("request for deploy approvals"),
+            NetRequest::DeployExecutionResult(_) => {
+                f.write_str("request for deploy execution result")
+            }
         }
     }
 }
@@ -129,7 +135,9 @@ impl NetRequest {
             | NetRequest::FinalitySignature(ref id)
             | NetRequest::SyncLeap(ref id)
             | NetRequest::ApprovalsHashes(ref id)
-            | NetRequest::BlockExecutionResults(ref id) => id,
+            | NetRequest::BlockExecutionResults(ref id)
+            | NetRequest::DeployApprovals(ref id)
+            | NetRequest::DeployExecutionResult(ref id) => id,
         };
         let mut unique_id = Vec::with_capacity(id.len() + 1);
         unique_id.push(self.tag() as u8);
@@ -149,6 +157,8 @@ impl NetRequest {
             NetRequest::SyncLeap(_) => Tag::SyncLeap,
             NetRequest::ApprovalsHashes(_) => Tag::ApprovalsHashes,
             NetRequest::BlockExecutionResults(_) => Tag::BlockExecutionResults,
+            NetRequest::DeployApprovals(_) => Tag::DeployApprovals,
+            NetRequest::DeployExecutionResult(_) => Tag::DeployExecutionResult,
         }
     }
 }
@@ -166,6 +176,8 @@ pub(crate) enum NetResponse {
     SyncLeap(Arc<[u8]>),
     ApprovalsHashes(Arc<[u8]>),
     BlockExecutionResults(Arc<[u8]>),
+    DeployApprovals(Arc<[u8]>),
+    DeployExecutionResult(Arc<[u8]>),
 }
 
 // `NetResponse` uses `Arcs`, so we count all data as 0.
@@ -192,6 +204,10 @@ impl Display for NetResponse {
             NetResponse::BlockExecutionResults(_) => {
                 f.write_str("response for block execution results")
             }
+            NetResponse::DeployApprovals(_) => f.write_str("response for deploy approvals"),
+            NetResponse::DeployExecutionResult(_) => {
+                f.write_str("response for deploy execution result")
+            }
         }
     }
 }
@@ -4,9 +4,11 @@
 //! top-level module documentation for details.
 
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     mem,
+    net::IpAddr,
+    path::PathBuf,
     sync::Arc,
 };
 
@@ -21,14 +23,26 @@ use casper_execution_engine::{
         balance::{BalanceRequest, BalanceResult},
         era_validators::GetEraValidatorsError,
         get_bids::{GetBidsRequest, GetBidsResult},
+        get_unbonds::{GetUnbondsRequest, GetUnbondsResult},
+        key_space_usage::KeySpaceUsage,
         query::{QueryRequest, QueryResult},
+        view::{ViewRequest, ViewResult},
+        ExecutionResultProof,
+    },
+    storage::{
+        global_state::{
+            backup::{BackupError, BackupManifest},
+            checkpoint::{Checkpoint, CheckpointError},
+            compaction::CompactionError,
+        },
+        trie::TrieRaw,
+        trie_store::{integrity::TrieIntegrityReport, stats::TrieStoreStats},
     },
-    storage::trie::TrieRaw,
 };
 use casper_hashing::Digest;
 use casper_types::{
-    bytesrepr::Bytes, system::auction::EraValidators, EraId, ExecutionResult, Key, ProtocolVersion,
-    PublicKey, TimeDiff, Timestamp, Transfer, URef,
+    account::AccountHash, bytesrepr::Bytes, system::auction::EraValidators, EraId, ExecutionResult,
+    Gas, Key, ProtocolVersion, PublicKey, TimeDiff, Timestamp, Transfer, URef,
 };
 
 use crate::{
@@ -43,22 +57,29 @@ use crate::{
         diagnostics_port::StopAtSpec,
         fetcher::{FetchItem, FetchResult},
         gossiper::GossipItem,
-        network::NetworkInsights,
+        network::{
+            ConnectionId, ConnectionInfo, HandshakeFailureReport, NetworkInsights, PartitionReport,
+            PeerLookupReport,
+        },
         upgrade_watcher::NextUpgrade,
     },
     contract_runtime::{ContractRuntimeError, SpeculativeExecutionState},
     effect::{AutoClosingResponder, Responder},
-    reactor::main_reactor::ReactorState,
-    rpcs::{chain::BlockIdentifier, docs::OpenRpcSchema},
+    reactor::main_reactor::{ComponentRestarts, ReactorState},
+    rpcs::{
+        chain::{BlockIdentifier, ProposerBlockRange},
+        docs::OpenRpcSchema,
+    },
     types::{
-        appendable_block::AppendableBlock, ApprovalsHashes, AvailableBlockRange, Block,
-        BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash, BlockHeader,
-        BlockSignatures, BlockWithMetadata, ChainspecRawBytes, Deploy, DeployHash, DeployHeader,
-        DeployId, DeployMetadataExt, DeployWithFinalizedApprovals, FinalitySignature,
-        FinalitySignatureId, FinalizedApprovals, FinalizedBlock, LegacyDeploy, MetaBlockState,
-        NodeId, StatusFeed, TrieOrChunk, TrieOrChunkId,
-    },
-    utils::{DisplayIter, Source},
+        appendable_block::AppendableBlock, Approval, ApprovalsHashes, AvailableBlockRange, Block,
+        BlockExecutionResultsOrChunk, BlockExecutionResultsOrChunkId, BlockHash,
+        BlockHashAndHeight, BlockHeader, BlockSignatures, BlockWithMetadata, Chainspec,
+        ChainspecRawBytes, Deploy, DeployExecutionResult, DeployExecutionResultId, DeployHash,
+        DeployHeader, DeployId, DeployMetadataExt, DeployWithFinalizedApprovals, FinalitySignature,
+        FinalitySignatureId, FinalizedApprovals, FinalizedBlock, KeyWrite, LegacyDeploy,
+        MetaBlockState, NodeId, StatusFeed, TrieOrChunk, TrieOrChunkId,
+    },
+    utils::{opt_display::OptDisplay, short_id::IdOrShortId, DisplayIter, Source},
 };
 
 use super::GossipTarget;
@@ -213,6 +234,30 @@ pub(crate) enum NetworkInfoRequest {
     Insight {
         responder: Responder<NetworkInsights>,
     },
+    /// Probe up to `sample_size` known peers and diagnose whether the node is partitioned.
+    DiagnosePartition {
+        sample_size: usize,
+        /// Responder to be called with the report once all probes have completed.
+        responder: Responder<PartitionReport>,
+    },
+    /// Look up an open or recently closed connection by its full or short ID.
+    Connection {
+        connection_id: IdOrShortId<ConnectionId>,
+        /// Responder to be called with the connection, if found.
+        responder: Responder<Option<ConnectionInfo>>,
+    },
+    /// Resolve a full or short node ID to the matching peers and their addresses.
+    LookupPeer {
+        node_id: IdOrShortId<NodeId>,
+        /// Responder to be called with the matching peers.
+        responder: Responder<PeerLookupReport>,
+    },
+    /// Get the recorded failed connection attempts involving `ip`, or all addresses if `None`.
+    HandshakeFailures {
+        ip: Option<IpAddr>,
+        /// Responder to be called with the report.
+        responder: Responder<HandshakeFailureReport>,
+    },
 }
 
 impl Display for NetworkInfoRequest {
@@ -230,6 +275,27 @@ impl Display for NetworkInfoRequest {
             NetworkInfoRequest::Insight { responder: _ } => {
                 formatter.write_str("get networking insights")
             }
+            NetworkInfoRequest::DiagnosePartition {
+                sample_size,
+                responder: _,
+            } => write!(
+                formatter,
+                "diagnose network partition probing up to {} peers",
+                sample_size
+            ),
+            NetworkInfoRequest::Connection {
+                connection_id,
+                responder: _,
+            } => write!(formatter, "look up connection {}", connection_id),
+            NetworkInfoRequest::LookupPeer {
+                node_id,
+                responder: _,
+            } => write!(formatter, "look up peer {}", node_id),
+            NetworkInfoRequest::HandshakeFailures { ip, responder: _ } => write!(
+                formatter,
+                "get failed connection attempts involving {}",
+                OptDisplay::new(*ip, "any address")
+            ),
         }
     }
 }
@@ -279,15 +345,28 @@ pub(crate) enum StorageRequest {
         approvals_hashes: Box<ApprovalsHashes>,
         responder: Responder<bool>,
     },
-    /// Store the block and approvals hashes.
+    /// Store the block, approvals hashes, execution results and written keys in a single
+    /// transaction.
     PutExecutedBlock {
         /// Block to be stored.
         block: Arc<Block>,
         /// Approvals hashes to store.
         approvals_hashes: Box<ApprovalsHashes>,
         execution_results: HashMap<DeployHash, ExecutionResult>,
+        /// The global state keys written by the block, recorded in the key history index.
+        written_keys: Vec<Key>,
         responder: Responder<bool>,
     },
+    /// Retrieve the most recent blocks which wrote to the given global state key.
+    GetKeyHistory {
+        /// The global state key.
+        key: Key,
+        /// Only blocks below this height are returned, if given.
+        before_height: Option<u64>,
+        /// Responder to call with the result, newest first.  Returns `None` if the key history
+        /// index is disabled.
+        responder: Responder<Option<Vec<KeyWrite>>>,
+    },
     /// Retrieve block with given hash.
     GetBlock {
         /// Hash of block to be retrieved.
@@ -350,6 +429,36 @@ pub(crate) enum StorageRequest {
         /// local storage under the block_hash provided.
         responder: Responder<Option<Vec<Transfer>>>,
     },
+    /// Retrieve the hashes and heights of the blocks in the given range created by a proposer.
+    GetBlocksByProposer {
+        /// Public key of the proposer.
+        proposer: Box<PublicKey>,
+        /// Range of blocks to search.
+        range: ProposerBlockRange,
+        /// Responder to call with the result, ordered by block height.
+        responder: Responder<Vec<BlockHashAndHeight>>,
+    },
+    /// Retrieve the state root hashes of the blocks with heights between `low` and `high`,
+    /// inclusive, restricted to the available block range.
+    GetStateRootHashRange {
+        /// The lowest block height in the range.
+        low: u64,
+        /// The highest block height in the range.
+        high: u64,
+        /// Responder to call with the heights and state root hashes, ordered by height.
+        responder: Responder<Vec<(u64, Digest)>>,
+    },
+    /// Retrieve the hashes of the stored deploys carrying the given user tag.
+    GetDeploysByTag {
+        /// The user tag.
+        user_tag: Bytes,
+        /// Only deploys with hashes greater than this one are returned, if given.
+        after: Option<DeployHash>,
+        /// The maximum number of deploy hashes to return.
+        limit: usize,
+        /// Responder to call with the result, ordered by deploy hash.
+        responder: Responder<Vec<DeployHash>>,
+    },
     /// Store given deploy.
     PutDeploy {
         /// Deploy to store.
@@ -358,6 +467,19 @@ pub(crate) enum StorageRequest {
         /// attempt or false if it was previously stored.
         responder: Responder<bool>,
     },
+    /// Add approvals to those collected for a deploy not yet stored.
+    PutDeployApprovals {
+        /// Hash of the approved deploy.
+        deploy_hash: DeployHash,
+        /// The time the deploy expires, after which its collected approvals are discarded.
+        expires: Timestamp,
+        /// The maximum number of approvals to collect for the deploy.
+        max_approvals: usize,
+        /// Approvals to add.
+        approvals: BTreeSet<Approval>,
+        /// Responder to call with all the approvals known for the deploy.
+        responder: Responder<BTreeSet<Approval>>,
+    },
     /// Retrieve deploys with given hashes.
     GetDeploys {
         /// Hashes of deploys to be retrieved.
@@ -401,6 +523,21 @@ pub(crate) enum StorageRequest {
         /// None is returned when we don't have the block in the storage.
         responder: Responder<Option<BlockExecutionResultsOrChunk>>,
     },
+    /// Retrieves the execution result of a deploy in a block, with its proof.
+    GetDeployExecutionResult {
+        /// Request ID.
+        id: DeployExecutionResultId,
+        /// Responder to call with the execution result.  None is returned unless the execution
+        /// results of every deploy of the block are held.
+        responder: Responder<Option<DeployExecutionResult>>,
+    },
+    /// Stores the execution result of a single deploy in a block, backfilled from a peer.
+    PutDeployExecutionResult {
+        /// The validated execution result.
+        item: Box<DeployExecutionResult>,
+        /// Responder to call with the result.  Returns true if the execution result was stored.
+        responder: Responder<bool>,
+    },
     /// Retrieve deploy and its metadata.
     GetDeployAndMetadata {
         /// Hash of deploy to be retrieved.
@@ -418,6 +555,16 @@ pub(crate) enum StorageRequest {
         /// The responder to call with the results.
         responder: Responder<Option<BlockWithMetadata>>,
     },
+    /// Retrieve the switch block of an era together with its finality signatures.
+    GetSwitchBlockAndMetadataByEraId {
+        /// The era whose switch block to retrieve.
+        era_id: EraId,
+        /// Flag indicating whether storage should check the block availability before trying to
+        /// retrieve it.
+        only_from_available_block_range: bool,
+        /// The responder to call with the results.
+        responder: Responder<Option<BlockWithMetadata>>,
+    },
     /// Retrieve a finality signature by block hash and public key.
     GetFinalitySignature {
         id: Box<FinalitySignatureId>,
@@ -459,6 +606,18 @@ pub(crate) enum StorageRequest {
         signature: Box<FinalitySignature>,
         responder: Responder<bool>,
     },
+    /// Store a block along with its finality signatures in a single transaction.
+    PutSignedBlock {
+        /// Block to be stored.
+        block: Arc<Block>,
+        /// Signatures to be merged with any already stored for the block.
+        block_signatures: Box<BlockSignatures>,
+        /// Whether to also mark the block complete.
+        mark_complete: bool,
+        /// Responder to call with the result.  Returns false if the block couldn't be stored, in
+        /// which case neither were the signatures.
+        responder: Responder<bool>,
+    },
     /// Store a block header.
     PutBlockHeader {
         /// Block header that is to be stored.
@@ -518,7 +677,36 @@ impl Display for StorageRequest {
             StorageRequest::GetBlockTransfers { block_hash, .. } => {
                 write!(formatter, "get transfers for {}", block_hash)
             }
+            StorageRequest::GetBlocksByProposer {
+                proposer, range, ..
+            } => {
+                write!(formatter, "get blocks by {} in {:?}", proposer, range)
+            }
+            StorageRequest::GetStateRootHashRange { low, high, .. } => {
+                write!(
+                    formatter,
+                    "get state root hashes for heights {}..={}",
+                    low, high
+                )
+            }
+            StorageRequest::GetDeploysByTag { user_tag, .. } => {
+                write!(
+                    formatter,
+                    "get deploys tagged {}",
+                    base16::encode_lower(user_tag)
+                )
+            }
             StorageRequest::PutDeploy { deploy, .. } => write!(formatter, "put {}", deploy),
+            StorageRequest::PutDeployApprovals {
+                deploy_hash,
+                approvals,
+                ..
+            } => write!(
+                formatter,
+                "put {} approvals for {}",
+                approvals.len(),
+                deploy_hash
+            ),
             StorageRequest::GetDeploys { deploy_hashes, .. } => {
                 write!(formatter, "get {}", DisplayIter::new(deploy_hashes.iter()))
             }
@@ -537,6 +725,12 @@ impl Display for StorageRequest {
             StorageRequest::GetBlockExecutionResultsOrChunk { id, .. } => {
                 write!(formatter, "get block execution results or chunk for {}", id)
             }
+            StorageRequest::GetDeployExecutionResult { id, .. } => {
+                write!(formatter, "get {}", id)
+            }
+            StorageRequest::PutDeployExecutionResult { item, .. } => {
+                write!(formatter, "put {}", item)
+            }
 
             StorageRequest::GetDeployAndMetadata { deploy_hash, .. } => {
                 write!(formatter, "get deploy and metadata for {}", deploy_hash)
@@ -551,6 +745,9 @@ impl Display for StorageRequest {
                     block_hash
                 )
             }
+            StorageRequest::GetSwitchBlockAndMetadataByEraId { era_id, .. } => {
+                write!(formatter, "get switch block and metadata for {}", era_id)
+            }
             StorageRequest::GetBlockAndMetadataByHeight { block_height, .. } => {
                 write!(
                     formatter,
@@ -578,6 +775,18 @@ impl Display for StorageRequest {
             StorageRequest::PutFinalitySignature { .. } => {
                 write!(formatter, "put finality signature")
             }
+            StorageRequest::PutSignedBlock {
+                block,
+                mark_complete,
+                ..
+            } => {
+                write!(
+                    formatter,
+                    "put signed block {}, marking complete: {}",
+                    block.hash(),
+                    mark_complete
+                )
+            }
             StorageRequest::PutBlockHeader { block_header, .. } => {
                 write!(formatter, "put block header: {}", block_header)
             }
@@ -590,6 +799,9 @@ impl Display for StorageRequest {
             StorageRequest::PutExecutedBlock { block, .. } => {
                 write!(formatter, "put executed block {}", block.hash(),)
             }
+            StorageRequest::GetKeyHistory { key, .. } => {
+                write!(formatter, "get history of key {}", key)
+            }
         }
     }
 }
@@ -686,6 +898,54 @@ pub(crate) enum RpcRequest {
         /// Responder to call with the result.
         responder: Responder<Option<Vec<Transfer>>>,
     },
+    /// Return the hashes and heights of the blocks in the given range created by a proposer.
+    GetBlocksByProposer {
+        /// Public key of the proposer.
+        proposer: Box<PublicKey>,
+        /// Range of blocks to search.
+        range: ProposerBlockRange,
+        /// Responder to call with the result.
+        responder: Responder<Vec<BlockHashAndHeight>>,
+    },
+    /// Return the switch block of an era with its finality signatures.
+    GetSwitchBlock {
+        /// The era whose switch block to retrieve.
+        era_id: EraId,
+        /// Flag indicating whether storage should check the block availability before trying to
+        /// retrieve it.
+        only_from_available_block_range: bool,
+        /// Responder to call with the result.
+        responder: Responder<Option<BlockWithMetadata>>,
+    },
+    /// Return the state root hashes of the blocks with heights in the given range.
+    GetStateRootHashRange {
+        /// The lowest block height in the range.
+        low: u64,
+        /// The highest block height in the range.
+        high: u64,
+        /// Responder to call with the result.
+        responder: Responder<Vec<(u64, Digest)>>,
+    },
+    /// Return the hashes of the stored deploys carrying the given user tag.
+    GetDeploysByTag {
+        /// The user tag.
+        user_tag: Bytes,
+        /// Only deploys with hashes greater than this one are returned, if given.
+        after: Option<DeployHash>,
+        /// The maximum number of deploy hashes to return.
+        limit: usize,
+        /// Responder to call with the result.
+        responder: Responder<Vec<DeployHash>>,
+    },
+    /// Return the most recent blocks which wrote to the given global state key.
+    GetKeyHistory {
+        /// The global state key.
+        key: Key,
+        /// Only blocks below this height are returned, if given.
+        before_height: Option<u64>,
+        /// Responder to call with the result.
+        responder: Responder<Option<Vec<KeyWrite>>>,
+    },
     /// Query the global state at the given root hash.
     QueryGlobalState {
         /// The state root hash.
@@ -775,6 +1035,29 @@ impl Display for RpcRequest {
             RpcRequest::GetBlockTransfers { block_hash, .. } => {
                 write!(formatter, "get transfers {}", block_hash)
             }
+            RpcRequest::GetBlocksByProposer {
+                proposer, range, ..
+            } => write!(formatter, "get blocks by {} in {:?}", proposer, range),
+            RpcRequest::GetSwitchBlock { era_id, .. } => {
+                write!(formatter, "get switch block of {}", era_id)
+            }
+            RpcRequest::GetStateRootHashRange { low, high, .. } => {
+                write!(
+                    formatter,
+                    "get state root hashes for heights {}..={}",
+                    low, high
+                )
+            }
+            RpcRequest::GetDeploysByTag { user_tag, .. } => {
+                write!(
+                    formatter,
+                    "get deploys tagged {}",
+                    base16::encode_lower(user_tag)
+                )
+            }
+            RpcRequest::GetKeyHistory { key, .. } => {
+                write!(formatter, "get history of key {}", key)
+            }
 
             RpcRequest::QueryGlobalState {
                 state_root_hash,
@@ -900,12 +1183,59 @@ pub(crate) enum ContractRuntimeRequest {
         /// Responder to call with the result.
         responder: Responder<Result<GetBidsResult, engine_state::Error>>,
     },
+    /// Return pending unbonds at a given state root hash
+    GetUnbonds {
+        /// Get unbonds request.
+        #[serde(skip_serializing)]
+        get_unbonds_request: GetUnbondsRequest,
+        /// Responder to call with the result.
+        responder: Responder<Result<GetUnbondsResult, engine_state::Error>>,
+    },
     /// Returns the value of the execution results checksum stored in the ChecksumRegistry for the
     /// given state root hash.
     GetExecutionResultsChecksum {
         state_root_hash: Digest,
         responder: Responder<Result<Option<Digest>, engine_state::Error>>,
     },
+    /// Anchors a state root synchronized from peers to the block which produced it.
+    AnchorStateRoot {
+        /// The state root hash of the block.
+        state_root_hash: Digest,
+        /// The height of the block.
+        block_height: u64,
+        /// The era of the block.
+        era_id: EraId,
+        /// Responder to call with the result.
+        responder: Responder<Result<(), engine_state::Error>>,
+    },
+    /// Returns the gas left of an account's computation quota in the current era, or `None` if
+    /// execution isn't limited by computation quotas.
+    GetRemainingComputationQuota {
+        /// The state root hash to read the quota usage under.
+        state_root_hash: Digest,
+        /// The account whose quota to read.
+        account_hash: AccountHash,
+        /// Responder to call with the result.
+        responder: Responder<Result<Option<Gas>, engine_state::Error>>,
+    },
+    /// Returns a proof that one of a block's execution results is part of the block.
+    GetExecutionResultProof {
+        /// The state root hash of the block.
+        state_root_hash: Digest,
+        /// All execution results of the block, in execution order.
+        execution_results: Vec<ExecutionResult>,
+        /// The position of the execution result to be proven.
+        index: u64,
+        /// Responder to call with the result.
+        responder: Responder<Result<ExecutionResultProof, engine_state::Error>>,
+    },
+    /// Call a contract's entry point in read-only mode, without a deploy.
+    CallView {
+        /// The view call to make.
+        view_request: Box<ViewRequest>,
+        /// Responder to call with the result.
+        responder: Responder<Result<ViewResult, engine_state::Error>>,
+    },
     /// Get a trie or chunk by its ID.
     GetTrie {
         /// The ID of the trie (or chunk of a trie) to be read.
@@ -927,6 +1257,71 @@ pub(crate) enum ContractRuntimeRequest {
         /// Responder to call with the result. Contains the hash of the stored trie.
         responder: Responder<Result<Digest, engine_state::Error>>,
     },
+    /// Get the tries missing from the `TrieStore` among a state root and its children.
+    GetMissingStateRootTries {
+        /// The state root to probe.
+        state_root_hash: Digest,
+        /// Responder to call with the result. Empty if the state root is complete.
+        responder: Responder<Result<Vec<Digest>, engine_state::Error>>,
+    },
+    /// Get the first tries below a state root which are missing from the `TrieStore`.
+    GetMissingTrieDescendants {
+        /// The state root to search below.
+        state_root_hash: Digest,
+        /// The maximum number of missing tries to return.
+        limit: usize,
+        /// Responder to call with the result. Empty if the state root is complete.
+        responder: Responder<Result<Vec<Digest>, engine_state::Error>>,
+    },
+    /// Get the tries quarantined because their stored bytes failed to deserialize.
+    GetQuarantinedTries {
+        /// Responder to call with the hashes of the quarantined tries.
+        responder: Responder<Result<Vec<Digest>, engine_state::Error>>,
+    },
+    /// Check that every trie below a state root is present and intact.
+    VerifyTrieIntegrity {
+        /// The state root to verify.
+        state_root_hash: Digest,
+        /// The maximum number of missing or corrupted tries to report.
+        limit: usize,
+        /// Responder to call with the result.
+        responder: Responder<Result<TrieIntegrityReport, engine_state::Error>>,
+    },
+    /// Gather statistics about the tries in the `TrieStore`.
+    GetTrieStoreStats {
+        /// The state root below which to also gather the shape of the trie, if any.
+        state_root_hash: Option<Digest>,
+        /// Responder to call with the result.
+        responder: Responder<Result<TrieStoreStats, engine_state::Error>>,
+    },
+    /// Attribute the global state to the accounts and contracts owning it.
+    GetKeySpaceUsage {
+        /// The state root to account for, or the last committed one if `None`.
+        state_root_hash: Option<Digest>,
+        /// The maximum number of owners with the largest footprints to list.
+        limit: usize,
+        /// Responder to call with the result.
+        responder: Responder<Result<KeySpaceUsage, engine_state::Error>>,
+    },
+    /// Write a hot backup of global state at the last committed state root.
+    BackupGlobalState {
+        /// The path of the tarball to create.
+        path: PathBuf,
+        /// Responder to call with the manifest of the backup.
+        responder: Responder<Result<BackupManifest, BackupError>>,
+    },
+    /// Copy global state at the last committed state root into a checkpoint directory.
+    CheckpointGlobalState {
+        /// The directory to hold the checkpoint.
+        dir: PathBuf,
+        /// Responder to call with the checkpoint taken.
+        responder: Responder<Result<Checkpoint, CheckpointError>>,
+    },
+    /// Request global state to be compacted the next time the node starts.
+    RequestGlobalStateCompaction {
+        /// Responder to call once the request is recorded.
+        responder: Responder<Result<(), CompactionError>>,
+    },
     /// Execute deploys without commiting results
     SpeculativeDeployExecution {
         /// Hash of a block on top of which to execute the deploy.
@@ -960,6 +1355,12 @@ impl Display for ContractRuntimeRequest {
             } => {
                 write!(formatter, "get bids request: {:?}", get_bids_request)
             }
+            ContractRuntimeRequest::GetUnbonds {
+                get_unbonds_request,
+                ..
+            } => {
+                write!(formatter, "get unbonds request: {:?}", get_unbonds_request)
+            }
             ContractRuntimeRequest::GetExecutionResultsChecksum {
                 state_root_hash, ..
             } => write!(
@@ -967,6 +1368,40 @@ impl Display for ContractRuntimeRequest {
                 "get execution results checksum under {}",
                 state_root_hash
             ),
+            ContractRuntimeRequest::AnchorStateRoot {
+                state_root_hash,
+                block_height,
+                ..
+            } => write!(
+                formatter,
+                "anchor {} to block at height {}",
+                state_root_hash, block_height
+            ),
+            ContractRuntimeRequest::GetRemainingComputationQuota {
+                state_root_hash,
+                account_hash,
+                ..
+            } => write!(
+                formatter,
+                "get remaining computation quota of {} under {}",
+                account_hash, state_root_hash
+            ),
+            ContractRuntimeRequest::GetExecutionResultProof {
+                state_root_hash,
+                index,
+                ..
+            } => write!(
+                formatter,
+                "get proof of execution result {} under {}",
+                index, state_root_hash
+            ),
+            ContractRuntimeRequest::CallView { view_request, .. } => write!(
+                formatter,
+                "call view {} of {} under {}",
+                view_request.entry_point(),
+                view_request.contract_hash(),
+                view_request.state_hash()
+            ),
             ContractRuntimeRequest::GetTrie {
                 trie_or_chunk_id, ..
             } => {
@@ -978,6 +1413,53 @@ impl Display for ContractRuntimeRequest {
             ContractRuntimeRequest::PutTrie { trie_bytes, .. } => {
                 write!(formatter, "trie: {:?}", trie_bytes)
             }
+            ContractRuntimeRequest::GetMissingStateRootTries {
+                state_root_hash, ..
+            } => write!(
+                formatter,
+                "get missing tries among {} and its children",
+                state_root_hash
+            ),
+            ContractRuntimeRequest::GetMissingTrieDescendants {
+                state_root_hash,
+                limit,
+                ..
+            } => write!(
+                formatter,
+                "get up to {} missing tries below {}",
+                limit, state_root_hash
+            ),
+            ContractRuntimeRequest::GetQuarantinedTries { .. } => {
+                write!(formatter, "get quarantined tries")
+            }
+            ContractRuntimeRequest::VerifyTrieIntegrity {
+                state_root_hash, ..
+            } => write!(formatter, "verify integrity of trie {}", state_root_hash),
+            ContractRuntimeRequest::GetTrieStoreStats {
+                state_root_hash: Some(state_root_hash),
+                ..
+            } => write!(formatter, "get trie store stats below {}", state_root_hash),
+            ContractRuntimeRequest::GetTrieStoreStats {
+                state_root_hash: None,
+                ..
+            } => write!(formatter, "get trie store stats"),
+            ContractRuntimeRequest::GetKeySpaceUsage {
+                state_root_hash: Some(state_root_hash),
+                ..
+            } => write!(formatter, "get key space usage at {}", state_root_hash),
+            ContractRuntimeRequest::GetKeySpaceUsage {
+                state_root_hash: None,
+                ..
+            } => write!(formatter, "get key space usage"),
+            ContractRuntimeRequest::BackupGlobalState { path, .. } => {
+                write!(formatter, "back up global state to {}", path.display())
+            }
+            ContractRuntimeRequest::CheckpointGlobalState { dir, .. } => {
+                write!(formatter, "checkpoint global state in {}", dir.display())
+            }
+            ContractRuntimeRequest::RequestGlobalStateCompaction { .. } => {
+                write!(formatter, "request global state compaction")
+            }
             ContractRuntimeRequest::SpeculativeDeployExecution {
                 execution_prestate,
                 deploy,
@@ -1091,6 +1573,8 @@ pub(crate) enum ChainspecRawBytesRequest {
     /// Request for the chainspec file bytes with the genesis_accounts and global_state bytes, if
     /// they are present.
     GetChainspecRawBytes(Responder<Arc<ChainspecRawBytes>>),
+    /// Request for the parsed chainspec the node is running.
+    GetChainspec(Responder<Arc<Chainspec>>),
 }
 
 impl Display for ChainspecRawBytesRequest {
@@ -1099,17 +1583,26 @@ impl Display for ChainspecRawBytesRequest {
             ChainspecRawBytesRequest::GetChainspecRawBytes(_) => {
                 write!(f, "get chainspec raw bytes")
             }
+            ChainspecRawBytesRequest::GetChainspec(_) => write!(f, "get chainspec"),
         }
     }
 }
 
-/// UpgradeWatcher component request to get the next scheduled upgrade, if any.
+/// UpgradeWatcher component requests.
 #[derive(Debug, Serialize)]
-pub(crate) struct UpgradeWatcherRequest(pub(crate) Responder<Option<NextUpgrade>>);
+pub(crate) enum UpgradeWatcherRequest {
+    /// Request for the next scheduled upgrade, if any.
+    GetNextUpgrade(Responder<Option<NextUpgrade>>),
+    /// Request for the parsed chainspec of the next scheduled upgrade, if any.
+    GetStagedChainspec(Responder<Option<Arc<Chainspec>>>),
+}
 
 impl Display for UpgradeWatcherRequest {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "get next upgrade")
+        match self {
+            UpgradeWatcherRequest::GetNextUpgrade(_) => write!(f, "get next upgrade"),
+            UpgradeWatcherRequest::GetStagedChainspec(_) => write!(f, "get staged chainspec"),
+        }
     }
 }
 
@@ -1122,6 +1615,16 @@ impl Display for ReactorStatusRequest {
     }
 }
 
+/// Requests the restart history of the components supervised by the reactor.
+#[derive(Debug, Serialize)]
+pub(crate) struct ComponentRestartsRequest(pub(crate) Responder<Vec<ComponentRestarts>>);
+
+impl Display for ComponentRestartsRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "get component restarts")
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub(crate) enum BlockAccumulatorRequest {
     GetPeersForBlock {
@@ -1141,6 +1644,12 @@ pub(crate) enum BlockSynchronizerRequest {
     NeedNext,
     DishonestPeers,
     SyncGlobalStates(Vec<(BlockHash, Digest)>, Vec<NodeId>),
+    /// Fetch tries which were quarantined as corrupted from peers again, writing them over the
+    /// corrupted ones.
+    RefetchTries {
+        block_hash: BlockHash,
+        trie_hashes: Vec<Digest>,
+    },
     Status {
         responder: Responder<BlockSynchronizerStatus>,
     },
@@ -1161,6 +1670,9 @@ impl Display for BlockSynchronizerRequest {
             BlockSynchronizerRequest::SyncGlobalStates(_, _) => {
                 write!(f, "request to sync global states")
             }
+            BlockSynchronizerRequest::RefetchTries { trie_hashes, .. } => {
+                write!(f, "request to re-fetch {} tries", trie_hashes.len())
+            }
         }
     }
 }
@@ -0,0 +1,110 @@
+//! Resetting a stopped node to a tagged era snapshot.
+//!
+//! Resetting deletes every block above the snapshot's switch block, so that the node resumes from
+//! the end of the snapshot's era when restarted. The consensus unit files of later eras are deleted
+//! too, as the node would otherwise refuse to sign units in eras it believes it has already
+//! participated in. To roll back a private network, all of its nodes need to be reset to the same
+//! snapshot.
+
+use std::io;
+
+use thiserror::Error;
+use tracing::info;
+
+use crate::{
+    components::{
+        consensus,
+        storage::{EraSnapshot, FatalStorageError, Storage},
+    },
+    reactor::main_reactor::Config,
+    types::{chainspec, Chainspec, ChainspecRawBytes},
+    utils::{Loadable, WithDir},
+};
+
+/// Error returned as a result of listing or resetting to era snapshots.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// Error loading the chainspec.
+    #[error("error loading chainspec: {0}")]
+    LoadChainspec(chainspec::Error),
+
+    /// Error accessing storage.
+    #[error("error accessing storage: {0}")]
+    Storage(#[from] FatalStorageError),
+
+    /// Error deleting consensus unit files.
+    #[error("error deleting consensus unit files: {0}")]
+    UnitFiles(io::Error),
+
+    /// No snapshot with the given name was tagged.
+    #[error("no era snapshot named {name}, available: [{available}]")]
+    UnknownSnapshot {
+        /// The name of the requested snapshot.
+        name: String,
+        /// The names of the tagged snapshots.
+        available: String,
+    },
+}
+
+/// Prints the tagged era snapshots.
+pub(crate) fn list_snapshots(config: WithDir<Config>) -> Result<(), Error> {
+    let storage = open_storage(config)?;
+    let snapshots = storage.read_era_snapshots()?;
+    if snapshots.is_empty() {
+        println!("no era snapshots tagged");
+    }
+    for snapshot in snapshots.values() {
+        println!("{}", snapshot);
+    }
+    Ok(())
+}
+
+/// Resets the node to the era snapshot named `name`, deleting all later blocks.
+pub(crate) fn reset_to_snapshot(config: WithDir<Config>, name: String) -> Result<(), Error> {
+    let mut storage = open_storage(config)?;
+    let snapshots = storage.read_era_snapshots()?;
+    let snapshot = match snapshots.values().find(|snapshot| snapshot.name() == name) {
+        Some(snapshot) => snapshot.clone(),
+        None => {
+            let available = snapshots
+                .values()
+                .map(EraSnapshot::name)
+                .collect::<Vec<_>>();
+            return Err(Error::UnknownSnapshot {
+                name,
+                available: available.join(", "),
+            });
+        }
+    };
+
+    info!(%snapshot, "resetting to era snapshot");
+    let deleted_blocks = storage.truncate_blocks_above(snapshot.block_height)?;
+    let deleted_unit_files =
+        consensus::remove_unit_files_after(storage.root_path(), snapshot.era_id)
+            .map_err(Error::UnitFiles)?;
+    info!(
+        deleted_blocks,
+        deleted_unit_files,
+        highest_block = snapshot.block_height,
+        "reset to era snapshot complete"
+    );
+    Ok(())
+}
+
+fn open_storage(config: WithDir<Config>) -> Result<Storage, Error> {
+    let (root, config) = config.into_parts();
+    let (chainspec, _) =
+        <(Chainspec, ChainspecRawBytes)>::from_path(&root).map_err(Error::LoadChainspec)?;
+    let storage = Storage::new(
+        &WithDir::new(&root, config.storage),
+        chainspec.core_config.finality_threshold_fraction,
+        None,
+        chainspec.protocol_version(),
+        &chainspec.network_config.name,
+        chainspec.deploy_config.max_ttl,
+        chainspec.core_config.recent_era_count(),
+        None,
+        false,
+    )?;
+    Ok(storage)
+}
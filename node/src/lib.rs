@@ -22,14 +22,17 @@
 )]
 #![allow(clippy::bool_comparison)]
 
+mod chain_archive;
 pub mod cli;
 pub(crate) mod components;
 mod config_migration;
 mod data_migration;
 pub(crate) mod effect;
+mod era_snapshot_reset;
 pub(crate) mod logging;
 pub(crate) mod protocol;
 pub(crate) mod reactor;
+mod storage_compaction;
 #[cfg(test)]
 pub(crate) mod testing;
 pub(crate) mod tls;
@@ -63,7 +66,9 @@ pub(crate) use components::{
     network::Config as NetworkConfig,
     rest_server::Config as RestServerConfig,
     rpc_server::{Config as RpcServerConfig, SpeculativeExecConfig},
+    upgrade_downloader::Config as UpgradeDownloaderConfig,
     upgrade_watcher::Config as UpgradeWatcherConfig,
+    webhook_notifier::Config as WebhookNotifierConfig,
 };
 pub(crate) use types::NodeRng;
 
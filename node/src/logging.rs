@@ -1,6 +1,6 @@
 //! Logging via the tracing crate.
 
-use std::{env, fmt, io};
+use std::{collections::BTreeMap, env, fmt, io};
 
 use ansi_term::{Color, Style};
 use anyhow::anyhow;
@@ -32,6 +32,8 @@ const LOG_FIELD_MODULE: &str = "log.module_path";
 const LOG_FIELD_FILE: &str = "log.file";
 const LOG_FIELD_LINE: &str = "log.line";
 
+const DEFAULT_LOG_FILTER: &str = "warn,casper_node=info";
+
 /// Global reload handle.
 ///
 /// We use a static variable for the reload handle since our logger instance is also global.
@@ -44,17 +46,23 @@ pub struct LoggingConfig {
     /// Output format for log.
     format: LoggingFormat,
 
-    /// Colored output (has no effect if JSON format is enabled).
+    /// Colored output (only has an effect if text format is enabled).
     ///
     /// If set, the logger will inject ANSI color codes into log messages.  This is useful if
     /// writing out to stdout or stderr on an ANSI terminal, but not so if writing to a logfile.
     color: bool,
 
-    /// Abbreviate module names (has no effect if JSON format is enabled).
+    /// Abbreviate module names (only has an effect if text format is enabled).
     ///
     /// If set, human-readable formats will abbreviate module names, `foo::bar::baz::bizz` will
     /// turn into `f:b:b:bizz`.
     abbreviate_modules: bool,
+
+    /// Log levels of individual targets, e.g. `"casper_node::components::network" = "debug"`.
+    ///
+    /// These override the levels given for the same targets by `RUST_LOG` or the default filter.
+    #[serde(default)]
+    target_levels: BTreeMap<String, String>,
 }
 
 impl LoggingConfig {
@@ -65,6 +73,7 @@ impl LoggingConfig {
             format,
             color,
             abbreviate_modules,
+            target_levels: BTreeMap::new(),
         }
     }
 }
@@ -77,8 +86,13 @@ impl LoggingConfig {
 pub enum LoggingFormat {
     /// Text format.
     Text,
-    /// JSON format.
+    /// JSON format, one event per line.
     Json,
+    /// logfmt format, i.e. space-separated `key=value` pairs, one event per line.
+    Logfmt,
+    /// logfmt format with each line prefixed by the event's syslog priority, e.g. `<4>` for
+    /// warnings, which journald records as the `PRIORITY` field when capturing standard output.
+    Journald,
 }
 
 impl Default for LoggingFormat {
@@ -260,6 +274,65 @@ where
     }
 }
 
+/// Formats events as logfmt lines, for consumption by log aggregators.
+///
+/// The timestamp, level, module and message are written as the `ts`, `level`, `module` and `msg`
+/// keys, followed by the fields of the current spans and of the event itself.
+pub struct LogfmtEvent {
+    /// Whether to prefix each line with the syslog priority of the event.
+    journald_priority: bool,
+}
+
+impl<S, N> FormatEvent<S, N> for LogfmtEvent
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+        if self.journald_priority {
+            write!(writer, "<{}>", syslog_priority(meta.level()))?;
+        }
+        writer.write_str("ts=")?;
+        SystemTime.format_time(&mut writer)?;
+        write!(writer, " level={}", meta.level().as_str().to_lowercase())?;
+
+        let mut field_visitor = FieldVisitor::default();
+        event.record(&mut field_visitor);
+        let module = meta
+            .module_path()
+            .or(field_visitor.module.as_deref())
+            .unwrap_or_else(|| meta.target());
+        write!(writer, " module={}", module)?;
+
+        ctx.visit_spans(|span| {
+            let ext = span.extensions();
+            let fields = &ext
+                .get::<FormattedFields<N>>()
+                .expect("Unable to find FormattedFields in extensions; this is a bug");
+            write!(writer, " span={}{}", span.metadata().name(), fields)
+        })?;
+
+        ctx.format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// Returns the syslog priority corresponding to `level`.
+fn syslog_priority(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        Level::DEBUG | Level::TRACE => 7,
+    }
+}
+
 /// Initializes the logging system with the default parameters.
 ///
 /// See `init_params` for details.
@@ -275,6 +348,10 @@ pub enum ReloadHandle {
     Text(Handle<EnvFilter, Layered<Layer<Registry, FieldFn<FormatDebugFn>, FmtEvent>, Registry>>),
     /// JSON-logger reload handle.
     Json(Handle<EnvFilter, Layered<Layer<Registry, JsonFields, Format<Json>>, Registry>>),
+    /// logfmt-logger reload handle, also used for the journald format.
+    Logfmt(
+        Handle<EnvFilter, Layered<Layer<Registry, FieldFn<FormatDebugFn>, LogfmtEvent>, Registry>>,
+    ),
 }
 
 impl ReloadHandle {
@@ -283,6 +360,7 @@ impl ReloadHandle {
         match self {
             ReloadHandle::Text(handle) => handle.reload(new_filter),
             ReloadHandle::Json(handle) => handle.reload(new_filter),
+            ReloadHandle::Logfmt(handle) => handle.reload(new_filter),
         }
     }
 
@@ -291,6 +369,9 @@ impl ReloadHandle {
         match self {
             ReloadHandle::Text(handle) => handle.with_current(|env_filter| env_filter.to_string()),
             ReloadHandle::Json(handle) => handle.with_current(|env_filter| env_filter.to_string()),
+            ReloadHandle::Logfmt(handle) => {
+                handle.with_current(|env_filter| env_filter.to_string())
+            }
         }
     }
 }
@@ -315,6 +396,28 @@ pub fn display_global_env_filter() -> anyhow::Result<String> {
     Ok(formatted)
 }
 
+/// Sets the log level of a single target in the global [`EnvFilter`], keeping all directives for
+/// other targets.
+pub fn set_global_target_level(target: &str, level: &str) -> anyhow::Result<()> {
+    let current = display_global_env_filter()?;
+    let new_filter = EnvFilter::try_new(with_target_level(current, target, level))?;
+    reload_global_env_filter(new_filter)
+}
+
+/// Appends a directive setting the level of `target` to the filter directives in `filter`.
+///
+/// An [`EnvFilter`] keeps only the last directive given for each target, so this supersedes any
+/// earlier directive for `target`.
+fn with_target_level(mut filter: String, target: &str, level: &str) -> String {
+    if !filter.is_empty() {
+        filter.push(',');
+    }
+    filter.push_str(target);
+    filter.push('=');
+    filter.push_str(level);
+    filter
+}
+
 /// Type alias for the formatting function used.
 pub type FormatDebugFn = fn(&mut Writer, &Field, &dyn std::fmt::Debug) -> fmt::Result;
 
@@ -330,6 +433,31 @@ fn format_into_debug_writer(
     }
 }
 
+fn format_into_logfmt_writer(
+    writer: &mut Writer,
+    field: &Field,
+    value: &dyn std::fmt::Debug,
+) -> fmt::Result {
+    match field.name() {
+        LOG_FIELD_MESSAGE => write!(writer, " msg={}", logfmt_value(format!("{:?}", value))),
+        LOG_FIELD_TARGET | LOG_FIELD_MODULE | LOG_FIELD_FILE | LOG_FIELD_LINE => Ok(()),
+        name => write!(writer, " {}={}", name, logfmt_value(format!("{:?}", value))),
+    }
+}
+
+/// Quotes a logfmt value if it is empty or contains whitespace, quotes or equals signs.
+///
+/// String fields are already quoted and escaped by their `Debug` representation.
+fn logfmt_value(value: String) -> String {
+    let is_quoted = value.len() >= 2 && value.starts_with('"') && value.ends_with('"');
+    let needs_quoting =
+        value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"' || c == '=');
+    if is_quoted || !needs_quoting {
+        return value;
+    }
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 /// Initializes the logging system.
 ///
 /// This function should only be called once during the lifetime of the application. Do not call
@@ -341,11 +469,13 @@ fn format_into_debug_writer(
 pub fn init_with_config(config: &LoggingConfig) -> anyhow::Result<()> {
     let formatter = format::debug_fn(format_into_debug_writer as FormatDebugFn);
 
-    let filter = EnvFilter::new(
-        env::var(LOG_CONFIGURATION_ENVVAR)
-            .as_deref()
-            .unwrap_or("warn,casper_node=info"),
-    );
+    let mut directives =
+        env::var(LOG_CONFIGURATION_ENVVAR).unwrap_or_else(|_| DEFAULT_LOG_FILTER.to_string());
+    for (target, level) in &config.target_levels {
+        directives = with_target_level(directives, target, level);
+    }
+    let filter = EnvFilter::try_new(&directives)
+        .map_err(|error| anyhow!("invalid log filter {:?}: {}", directives, error))?;
 
     match config.format {
         // Setup a new tracing-subscriber writing to `stdout` for logging.
@@ -374,5 +504,57 @@ pub fn init_with_config(config: &LoggingConfig) -> anyhow::Result<()> {
             drop(RELOAD_HANDLE.set(handle));
             Ok(())
         }
+
+        // logfmt and journald logging write to `stdout` too, journald capturing it when the node
+        // runs as a systemd service.
+        LoggingFormat::Logfmt | LoggingFormat::Journald => {
+            let builder = tracing_subscriber::fmt()
+                .with_writer(io::stdout as fn() -> std::io::Stdout)
+                .with_env_filter(filter)
+                .fmt_fields(format::debug_fn(format_into_logfmt_writer as FormatDebugFn))
+                .event_format(LogfmtEvent {
+                    journald_priority: matches!(config.format, LoggingFormat::Journald),
+                })
+                .with_filter_reloading();
+            let handle = ReloadHandle::Logfmt(builder.reload_handle());
+            builder.try_init().map_err(|error| anyhow!(error))?;
+            drop(RELOAD_HANDLE.set(handle));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_quote_logfmt_values_only_when_needed() {
+        assert_eq!(logfmt_value("42".to_string()), "42");
+        assert_eq!(
+            logfmt_value("\"already quoted\"".to_string()),
+            "\"already quoted\""
+        );
+        assert_eq!(logfmt_value("two words".to_string()), "\"two words\"");
+        assert_eq!(logfmt_value("a=b".to_string()), "\"a=b\"");
+        assert_eq!(logfmt_value(String::new()), "\"\"");
+    }
+
+    #[test]
+    fn later_target_level_should_supersede_earlier_one() {
+        let directives = with_target_level(
+            DEFAULT_LOG_FILTER.to_string(),
+            "casper_node::components::network",
+            "debug",
+        );
+        assert_eq!(
+            directives,
+            "warn,casper_node=info,casper_node::components::network=debug"
+        );
+
+        let filter = EnvFilter::new(with_target_level(directives, "casper_node", "error"));
+        let filter = filter.to_string().to_lowercase();
+        assert!(filter.contains("casper_node=error"));
+        assert!(!filter.contains("casper_node=info"));
     }
 }
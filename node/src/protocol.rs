@@ -16,7 +16,9 @@ use crate::{
         consensus,
         fetcher::{FetchItem, FetchResponse, Tag},
         gossiper,
-        network::{EstimatorWeights, FromIncoming, GossipedAddress, MessageKind, Payload},
+        network::{
+            EstimatorWeights, FromIncoming, GossipedAddress, IncomingClass, MessageKind, Payload,
+        },
     },
     effect::{
         incoming::{
@@ -86,6 +88,8 @@ impl Payload for Message {
                 Tag::SyncLeap => MessageKind::BlockTransfer,
                 Tag::ApprovalsHashes => MessageKind::BlockTransfer,
                 Tag::BlockExecutionResults => MessageKind::BlockTransfer,
+                Tag::DeployApprovals => MessageKind::DeployTransfer,
+                Tag::DeployExecutionResult => MessageKind::BlockTransfer,
             },
             Message::FinalitySignature(_) => MessageKind::Consensus,
             Message::FinalitySignatureGossiper(_) => MessageKind::FinalitySignatureGossip,
@@ -109,6 +113,22 @@ impl Payload for Message {
         }
     }
 
+    fn incoming_class(&self) -> Option<IncomingClass> {
+        // Responses and gossip wait in the bounded incoming queue, so that a flood of them holds
+        // back the peers sending them rather than piling up on the reactor.
+        match self {
+            Message::Consensus(_) => None,
+            Message::ConsensusRequest(_) => None,
+            Message::DeployGossiper(_) => Some(IncomingClass::Gossip),
+            Message::BlockGossiper(_) => Some(IncomingClass::Gossip),
+            Message::FinalitySignatureGossiper(_) => Some(IncomingClass::Gossip),
+            Message::AddressGossiper(_) => Some(IncomingClass::Gossip),
+            Message::GetRequest { .. } => None,
+            Message::GetResponse { .. } => Some(IncomingClass::FetchedItems),
+            Message::FinalitySignature(_) => None,
+        }
+    }
+
     #[inline]
     fn incoming_resource_estimate(&self, weights: &EstimatorWeights) -> u32 {
         match self {
@@ -127,6 +147,8 @@ impl Payload for Message {
                 Tag::SyncLeap => weights.block_requests,
                 Tag::ApprovalsHashes => weights.block_requests,
                 Tag::BlockExecutionResults => weights.block_requests,
+                Tag::DeployApprovals => weights.deploy_requests,
+                Tag::DeployExecutionResult => weights.block_requests,
             },
             Message::GetResponse { tag, .. } => match tag {
                 Tag::Deploy | Tag::LegacyDeploy => weights.deploy_responses,
@@ -137,6 +159,8 @@ impl Payload for Message {
                 Tag::SyncLeap => weights.block_responses,
                 Tag::ApprovalsHashes => weights.block_responses,
                 Tag::BlockExecutionResults => weights.block_responses,
+                Tag::DeployApprovals => weights.deploy_responses,
+                Tag::DeployExecutionResult => weights.block_responses,
             },
             Message::FinalitySignature(_) => weights.finality_signatures,
         }
@@ -318,6 +342,16 @@ where
                     message: NetRequest::BlockExecutionResults(serialized_id),
                 }
                 .into(),
+                Tag::DeployApprovals => NetRequestIncoming {
+                    sender,
+                    message: NetRequest::DeployApprovals(serialized_id),
+                }
+                .into(),
+                Tag::DeployExecutionResult => NetRequestIncoming {
+                    sender,
+                    message: NetRequest::DeployExecutionResult(serialized_id),
+                }
+                .into(),
             },
             Message::GetResponse {
                 tag,
@@ -368,6 +402,16 @@ where
                     message: NetResponse::BlockExecutionResults(serialized_item),
                 }
                 .into(),
+                Tag::DeployApprovals => NetResponseIncoming {
+                    sender,
+                    message: NetResponse::DeployApprovals(serialized_item),
+                }
+                .into(),
+                Tag::DeployExecutionResult => NetResponseIncoming {
+                    sender,
+                    message: NetResponse::DeployExecutionResult(serialized_item),
+                }
+                .into(),
             },
             Message::FinalitySignature(message) => {
                 FinalitySignatureIncoming { sender, message }.into()
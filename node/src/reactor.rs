@@ -69,8 +69,8 @@ use crate::{
     },
     types::{
         ApprovalsHashes, Block, BlockExecutionResultsOrChunk, BlockHeader, Chainspec,
-        ChainspecRawBytes, Deploy, ExitCode, FinalitySignature, LegacyDeploy, NodeId, SyncLeap,
-        TrieOrChunk,
+        ChainspecRawBytes, Deploy, DeployApprovals, DeployExecutionResult, ExitCode,
+        FinalitySignature, LegacyDeploy, NodeId, SyncLeap, TrieOrChunk,
     },
     unregister_metric,
     utils::{
@@ -279,6 +279,15 @@ pub(crate) trait Reactor: Sized {
 
     /// Instructs the reactor to update performance metrics, if any.
     fn update_metrics(&mut self, _event_queue_handle: EventQueueHandle<Self::Event>) {}
+
+    /// Instructs the reactor to stop accepting new external requests ahead of shutting down.
+    ///
+    /// The returned future resolves once all requests which were already in flight have completed.
+    /// Those may need further events to be processed, so the reactor keeps being cranked until
+    /// the future resolves.
+    fn begin_shutdown(&mut self) -> BoxFuture<'static, ()> {
+        async {}.boxed()
+    }
 }
 
 /// A reactor event type.
@@ -730,6 +739,30 @@ where
             }
         }
     }
+
+    /// Gives the reactor up to `timeout` to finish handling in-flight external requests after
+    /// `run` has returned.
+    ///
+    /// Events keep being processed while draining so that pending requests can still be answered.
+    pub(crate) async fn drain(&mut self, rng: &mut NodeRng, timeout: Duration) {
+        let mut drained = self.reactor.begin_shutdown();
+        let drain_all = async {
+            loop {
+                tokio::select! {
+                    _ = &mut drained => break,
+                    _ = self.crank(rng) => {}
+                }
+            }
+        };
+
+        match tokio::time::timeout(timeout, drain_all).await {
+            Ok(()) => info!("finished draining in-flight requests"),
+            Err(_) => warn!(
+                ?timeout,
+                "timed out draining in-flight requests, remaining connections will be dropped"
+            ),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -928,6 +961,8 @@ where
         + From<fetcher::Event<SyncLeap>>
         + From<fetcher::Event<TrieOrChunk>>
         + From<fetcher::Event<ApprovalsHashes>>
+        + From<fetcher::Event<DeployApprovals>>
+        + From<fetcher::Event<DeployExecutionResult>>
         + From<block_accumulator::Event>
         + From<PeerBehaviorAnnouncement>,
 {
@@ -990,5 +1025,168 @@ where
                 serialized_item,
             )
         }
+        NetResponse::DeployApprovals(ref serialized_item) => {
+            handle_fetch_response::<R, DeployApprovals>(
+                reactor,
+                effect_builder,
+                rng,
+                sender,
+                serialized_item,
+            )
+        }
+        NetResponse::DeployExecutionResult(ref serialized_item) => {
+            handle_fetch_response::<R, DeployExecutionResult>(
+                reactor,
+                effect_builder,
+                rng,
+                sender,
+                serialized_item,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{self, Formatter};
+
+    use derive_more::From;
+    use thiserror::Error;
+    use tokio::sync::oneshot;
+
+    use casper_types::testing::TestRng;
+
+    use super::*;
+    use crate::utils::Loadable;
+
+    #[derive(Debug, From, Serialize)]
+    enum Event {
+        /// Lets the request which is in flight complete.
+        CompleteRequest,
+        #[from]
+        ControlAnnouncement(ControlAnnouncement),
+    }
+
+    impl ReactorEvent for Event {
+        fn is_control(&self) -> bool {
+            matches!(self, Event::ControlAnnouncement(_))
+        }
+
+        fn try_into_control(self) -> Option<ControlAnnouncement> {
+            match self {
+                Event::ControlAnnouncement(ctrl_ann) => Some(ctrl_ann),
+                Event::CompleteRequest => None,
+            }
+        }
+    }
+
+    impl Display for Event {
+        fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+            Debug::fmt(self, formatter)
+        }
+    }
+
+    #[derive(Debug, Error)]
+    #[error("prometheus (metrics) error: {0}")]
+    struct ReactorError(#[from] prometheus::Error);
+
+    /// A reactor with a single external request in flight, which only completes once the reactor
+    /// has processed `Event::CompleteRequest`.
+    struct DrainingReactor {
+        request_completed: Option<oneshot::Sender<()>>,
+        request_completion: Option<oneshot::Receiver<()>>,
+    }
+
+    impl Reactor for DrainingReactor {
+        type Event = Event;
+        type Config = ();
+        type Error = ReactorError;
+
+        fn dispatch_event(
+            &mut self,
+            _effect_builder: EffectBuilder<Event>,
+            _rng: &mut NodeRng,
+            event: Event,
+        ) -> Effects<Event> {
+            match event {
+                Event::CompleteRequest => {
+                    if let Some(request_completed) = self.request_completed.take() {
+                        let _ = request_completed.send(());
+                    }
+                }
+                Event::ControlAnnouncement(ctrl_ann) => panic!("unexpected: {}", ctrl_ann),
+            }
+            Effects::new()
+        }
+
+        fn new(
+            _cfg: (),
+            _chainspec: Arc<Chainspec>,
+            _chainspec_raw_bytes: Arc<ChainspecRawBytes>,
+            _network_identity: NetworkIdentity,
+            _registry: &Registry,
+            _event_queue: EventQueueHandle<Event>,
+            _rng: &mut NodeRng,
+        ) -> Result<(Self, Effects<Event>), ReactorError> {
+            let (request_completed, request_completion) = oneshot::channel();
+            let reactor = DrainingReactor {
+                request_completed: Some(request_completed),
+                request_completion: Some(request_completion),
+            };
+            Ok((reactor, Effects::new()))
+        }
+
+        fn begin_shutdown(&mut self) -> BoxFuture<'static, ()> {
+            let request_completion = self
+                .request_completion
+                .take()
+                .expect("should only begin shutdown once");
+            async move {
+                let _ = request_completion.await;
+            }
+            .boxed()
+        }
+    }
+
+    async fn new_runner(rng: &mut TestRng) -> Runner<DrainingReactor> {
+        let (chainspec, chainspec_raw_bytes) =
+            <(Chainspec, ChainspecRawBytes)>::from_resources("local");
+        Runner::new((), Arc::new(chainspec), Arc::new(chainspec_raw_bytes), rng)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn drain_should_process_events_until_in_flight_requests_complete() {
+        let mut rng = TestRng::new();
+        let mut runner = new_runner(&mut rng).await;
+        runner
+            .process_injected_effects(|effect_builder| {
+                effect_builder
+                    .into_inner()
+                    .schedule(Event::CompleteRequest, QueueKind::Regular)
+                    .ignore()
+            })
+            .await;
+
+        // The request can only complete if events keep being processed while draining.
+        let timeout = Duration::from_secs(10);
+        let start = Instant::now();
+        runner.drain(&mut rng, timeout).await;
+        assert!(start.elapsed() < timeout);
+        assert!(runner.reactor().request_completed.is_none());
+    }
+
+    #[tokio::test]
+    async fn drain_should_give_up_after_timeout() {
+        let mut rng = TestRng::new();
+        let mut runner = new_runner(&mut rng).await;
+
+        // Nothing completes the request, so draining lasts exactly until the timeout.
+        let timeout = Duration::from_millis(100);
+        let start = Instant::now();
+        runner.drain(&mut rng, timeout).await;
+        assert!(start.elapsed() >= timeout);
+        assert!(runner.reactor().request_completed.is_some());
     }
 }
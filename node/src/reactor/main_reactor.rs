@@ -6,6 +6,7 @@ mod error;
 mod event;
 mod fetchers;
 mod memory_metrics;
+mod supervision;
 mod utils;
 
 mod catch_up;
@@ -20,8 +21,10 @@ mod validate;
 use std::{collections::BTreeMap, sync::Arc, time::Instant};
 
 use datasize::DataSize;
+use futures::{future::BoxFuture, FutureExt};
 use memory_metrics::MemoryMetrics;
 use prometheus::Registry;
+use supervision::Supervisor;
 use tracing::{debug, error, info, warn};
 
 use casper_types::{EraId, PublicKey, TimeDiff, Timestamp, U512};
@@ -45,7 +48,9 @@ use crate::{
         shutdown_trigger::{self, ShutdownTrigger},
         storage::Storage,
         sync_leaper::SyncLeaper,
+        upgrade_downloader::UpgradeDownloader,
         upgrade_watcher::{self, UpgradeWatcher},
+        webhook_notifier::{self, CriticalEvent, WebhookNotifier},
         Component, ValidatorBoundComponent,
     },
     effect::{
@@ -56,7 +61,7 @@ use crate::{
             RpcServerAnnouncement, UpgradeWatcherAnnouncement,
         },
         incoming::{NetResponseIncoming, TrieResponseIncoming},
-        requests::ChainspecRawBytesRequest,
+        requests::{BlockSynchronizerRequest, ChainspecRawBytesRequest},
         EffectBuilder, EffectExt, Effects, GossipTarget,
     },
     fatal,
@@ -71,7 +76,10 @@ use crate::{
         Block, BlockHash, BlockHeader, Chainspec, ChainspecRawBytes, Deploy, FinalitySignature,
         MetaBlock, MetaBlockState, TrieOrChunk, ValidatorMatrix,
     },
-    utils::{Source, WithDir},
+    utils::{
+        signature_verification::SignatureVerifier, work_queue::BoundedWorkQueueMetrics, Source,
+        WithDir,
+    },
     NodeRng,
 };
 #[cfg(test)]
@@ -80,6 +88,7 @@ pub(crate) use config::Config;
 pub(crate) use error::Error;
 pub(crate) use event::MainEvent;
 pub(crate) use reactor_state::ReactorState;
+pub(crate) use supervision::ComponentRestarts;
 
 /// Main node reactor.
 ///
@@ -133,11 +142,13 @@ pub(crate) struct MainReactor {
     storage: Storage,
     contract_runtime: ContractRuntime,
     upgrade_watcher: UpgradeWatcher,
+    upgrade_downloader: UpgradeDownloader,
     rpc_server: RpcServer,
     rest_server: RestServer,
     event_stream_server: EventStreamServer,
     diagnostics_port: DiagnosticsPort,
     shutdown_trigger: ShutdownTrigger,
+    webhook_notifier: WebhookNotifier,
     net: Network<MainEvent, Message>,
     consensus: EraSupervisor,
 
@@ -168,6 +179,11 @@ pub(crate) struct MainReactor {
     memory_metrics: MemoryMetrics,
     #[data_size(skip)]
     event_queue_metrics: EventQueueMetrics,
+    #[data_size(skip)]
+    signature_verification_metrics: BoundedWorkQueueMetrics,
+    //   restarts of failed non-critical components
+    #[data_size(skip)]
+    supervisor: Supervisor,
 
     //   ambient settings / data / load-bearing config
     validator_matrix: ValidatorMatrix,
@@ -209,6 +225,8 @@ impl reactor::Reactor for MainReactor {
         let metrics = Metrics::new(registry.clone());
         let memory_metrics = MemoryMetrics::new(registry.clone())?;
         let event_queue_metrics = EventQueueMetrics::new(registry.clone(), event_queue)?;
+        let signature_verifier = SignatureVerifier::new()?;
+        let signature_verification_metrics = signature_verifier.register_metrics(registry)?;
 
         let protocol_version = chainspec.protocol_config.version;
 
@@ -254,8 +272,27 @@ impl reactor::Reactor for MainReactor {
             chainspec.core_config.minimum_delegation_amount,
             chainspec.core_config.strict_argument_checking,
             chainspec.core_config.vesting_schedule_period.millis(),
+            chainspec
+                .core_config
+                .replay_protection
+                .account_sequence_enforced(),
+            chainspec.core_config.execution_results_merkle_tree_version,
+            chainspec.core_config.priority_fees_version,
+            chainspec.core_config.session_code_policy.clone(),
+            chainspec.core_config.payment_lanes.clone(),
+            chainspec.core_config.payment_lanes_version,
+            chainspec.core_config.computation_quota_per_era,
+            chainspec.core_config.protect_system_keys,
+            chainspec.core_config.storage_rent_per_byte,
+            chainspec.core_config.storage_rent_grace_eras,
+            chainspec.core_config.direct_redelegation_version,
+            chainspec.core_config.redelegation_cooldown_eras,
+            chainspec.core_config.deprecated_host_functions.clone(),
+            chainspec.core_config.contract_package_pause_version,
+            chainspec.core_config.strict_bid_activation_version,
             registry,
         )?;
+        utils::backfill_state_root_anchors(&storage, &contract_runtime)?;
 
         let network = Network::new(
             config.network.clone(),
@@ -266,11 +303,8 @@ impl reactor::Reactor for MainReactor {
             validator_matrix.clone(),
         )?;
 
-        let address_gossiper = Gossiper::<{ GossipedAddress::ID_IS_COMPLETE_ITEM }, _>::new(
-            "address_gossiper",
-            config.gossip,
-            registry,
-        )?;
+        let address_gossiper =
+            Gossiper::<{ GossipedAddress::ID_IS_COMPLETE_ITEM }, _>::new(&config.gossip, registry)?;
 
         let rpc_server = RpcServer::new(
             config.rpc_server.clone(),
@@ -293,26 +327,34 @@ impl reactor::Reactor for MainReactor {
         let diagnostics_port =
             DiagnosticsPort::new(WithDir::new(&root_dir, config.diagnostics_port));
         let shutdown_trigger = ShutdownTrigger::new();
+        let dead_letter_path =
+            storage_config.with_dir(config.webhook_notifier.dead_letter_path.clone());
+        let (webhook_notifier, webhook_notifier_effects) = WebhookNotifier::new(
+            config.webhook_notifier,
+            dead_letter_path,
+            storage.root_path().to_path_buf(),
+            chainspec.network_config.name.clone(),
+            our_secret_key.clone(),
+            our_public_key.clone(),
+            effect_builder,
+        )?;
 
         // local / remote data management
-        let sync_leaper = SyncLeaper::new(chainspec.clone(), registry)?;
+        let sync_leaper = SyncLeaper::new(
+            chainspec.clone(),
+            config.node.sync_leap_cross_check_peers,
+            registry,
+        )?;
         let fetchers = Fetchers::new(&config.fetcher, registry)?;
 
         // gossipers
-        let block_gossiper = Gossiper::<{ Block::ID_IS_COMPLETE_ITEM }, _>::new(
-            "block_gossiper",
-            config.gossip,
-            registry,
-        )?;
-        let deploy_gossiper = Gossiper::<{ Deploy::ID_IS_COMPLETE_ITEM }, _>::new(
-            "deploy_gossiper",
-            config.gossip,
-            registry,
-        )?;
+        let block_gossiper =
+            Gossiper::<{ Block::ID_IS_COMPLETE_ITEM }, _>::new(&config.gossip, registry)?;
+        let deploy_gossiper =
+            Gossiper::<{ Deploy::ID_IS_COMPLETE_ITEM }, _>::new(&config.gossip, registry)?;
         let finality_signature_gossiper =
             Gossiper::<{ FinalitySignature::ID_IS_COMPLETE_ITEM }, _>::new(
-                "finality_signature_gossiper",
-                config.gossip,
+                &config.gossip,
                 registry,
             )?;
 
@@ -333,6 +375,7 @@ impl reactor::Reactor for MainReactor {
             validator_matrix.clone(),
             chainspec.core_config.unbonding_delay,
             chainspec.core_config.minimum_block_time,
+            signature_verifier.clone(),
             registry,
         )?;
         let block_synchronizer = BlockSynchronizer::new(
@@ -344,9 +387,26 @@ impl reactor::Reactor for MainReactor {
         let block_validator = BlockValidator::new(Arc::clone(&chainspec));
         let upgrade_watcher =
             UpgradeWatcher::new(chainspec.as_ref(), config.upgrade_watcher, &root_dir)?;
-        let deploy_acceptor = DeployAcceptor::new(chainspec.as_ref(), registry)?;
-        let deploy_buffer =
-            DeployBuffer::new(chainspec.deploy_config, config.deploy_buffer, registry)?;
+        let (upgrade_downloader, upgrade_downloader_effects) = UpgradeDownloader::new(
+            config.upgrade_downloader,
+            chainspec.network_config.name.clone(),
+            protocol_version,
+            &root_dir,
+            effect_builder,
+        )?;
+        let deploy_acceptor =
+            DeployAcceptor::new(chainspec.as_ref(), signature_verifier, registry)?;
+        let deploy_buffer = DeployBuffer::new(
+            chainspec.deploy_config,
+            chainspec
+                .core_config
+                .active_payment_lanes(chainspec.protocol_version()),
+            chainspec.core_config.computation_quota_per_era,
+            config.deploy_buffer,
+            registry,
+        )?
+        .with_lookback(storage.root_path());
+        let supervisor = Supervisor::new(&config.node, registry)?;
 
         let reactor = MainReactor {
             chainspec,
@@ -354,6 +414,7 @@ impl reactor::Reactor for MainReactor {
             storage,
             contract_runtime,
             upgrade_watcher,
+            upgrade_downloader,
             net: network,
             address_gossiper,
 
@@ -374,10 +435,13 @@ impl reactor::Reactor for MainReactor {
             block_synchronizer,
             diagnostics_port,
             shutdown_trigger,
+            webhook_notifier,
 
             metrics,
             memory_metrics,
             event_queue_metrics,
+            signature_verification_metrics,
+            supervisor,
 
             state: ReactorState::Initialize {},
             attempts: 0,
@@ -392,16 +456,36 @@ impl reactor::Reactor for MainReactor {
             signature_gossip_tracker: SignatureGossipTracker::new(),
         };
         info!("MainReactor: instantiated");
-        let effects = effect_builder
+        let mut effects = effect_builder
             .immediately()
             .event(|()| MainEvent::ReactorCrank);
+        effects.extend(reactor::wrap_effects(
+            MainEvent::WebhookNotifier,
+            webhook_notifier_effects,
+        ));
+        effects.extend(reactor::wrap_effects(
+            MainEvent::UpgradeDownloader,
+            upgrade_downloader_effects,
+        ));
         Ok((reactor, effects))
     }
 
     fn update_metrics(&mut self, event_queue_handle: EventQueueHandle<Self::Event>) {
         self.memory_metrics.estimate(self);
         self.event_queue_metrics
-            .record_event_queue_counts(&event_queue_handle)
+            .record_event_queue_counts(&event_queue_handle);
+        self.contract_runtime.update_stale_read_txns_metric();
+        self.contract_runtime.update_trie_store_metrics();
+    }
+
+    fn begin_shutdown(&mut self) -> BoxFuture<'static, ()> {
+        let rpc_server = self.rpc_server.begin_shutdown();
+        let rest_server = self.rest_server.begin_shutdown();
+        let event_stream_server = self.event_stream_server.begin_shutdown();
+        async move {
+            futures::join!(rpc_server, rest_server, event_stream_server);
+        }
+        .boxed()
     }
 
     fn dispatch_event(
@@ -445,6 +529,9 @@ impl reactor::Reactor for MainReactor {
             MainEvent::MainReactorRequest(req) => {
                 req.0.respond((self.state, self.last_progress)).ignore()
             }
+            MainEvent::ComponentRestartsRequest(req) => {
+                req.0.respond(self.supervisor.restarts()).ignore()
+            }
             MainEvent::MetaBlockAnnouncement(MetaBlockAnnouncement(meta_block)) => {
                 self.handle_meta_block(effect_builder, rng, meta_block)
             }
@@ -460,6 +547,11 @@ impl reactor::Reactor for MainReactor {
                 self.upgrade_watcher
                     .handle_event(effect_builder, rng, req.into()),
             ),
+            MainEvent::UpgradeDownloader(event) => reactor::wrap_effects(
+                MainEvent::UpgradeDownloader,
+                self.upgrade_downloader
+                    .handle_event(effect_builder, rng, event),
+            ),
             MainEvent::UpgradeWatcherAnnouncement(
                 UpgradeWatcherAnnouncement::UpgradeActivationPointRead(next_upgrade),
             ) => reactor::wrap_effects(
@@ -496,6 +588,9 @@ impl reactor::Reactor for MainReactor {
             MainEvent::ChainspecRawBytesRequest(
                 ChainspecRawBytesRequest::GetChainspecRawBytes(responder),
             ) => responder.respond(self.chainspec_raw_bytes.clone()).ignore(),
+            MainEvent::ChainspecRawBytesRequest(ChainspecRawBytesRequest::GetChainspec(
+                responder,
+            )) => responder.respond(self.chainspec.clone()).ignore(),
             MainEvent::EventStreamServer(event) => reactor::wrap_effects(
                 MainEvent::EventStreamServer,
                 self.event_stream_server
@@ -511,6 +606,11 @@ impl reactor::Reactor for MainReactor {
                 self.diagnostics_port
                     .handle_event(effect_builder, rng, event),
             ),
+            MainEvent::WebhookNotifier(event) => reactor::wrap_effects(
+                MainEvent::WebhookNotifier,
+                self.webhook_notifier
+                    .handle_event(effect_builder, rng, event),
+            ),
             MainEvent::DumpConsensusStateRequest(req) => reactor::wrap_effects(
                 MainEvent::Consensus,
                 self.consensus.handle_event(effect_builder, rng, req.into()),
@@ -573,14 +673,18 @@ impl reactor::Reactor for MainReactor {
                     .handle_event(effect_builder, rng, req.into()),
             ),
             MainEvent::AddressGossiperAnnouncement(gossiper_ann) => match gossiper_ann {
-                GossiperAnnouncement::GossipReceived { .. }
-                | GossiperAnnouncement::NewItemBody { .. }
-                | GossiperAnnouncement::FinishedGossiping(_) => Effects::new(),
-                GossiperAnnouncement::NewCompleteItem(gossiped_address) => {
-                    let reactor_event =
-                        MainEvent::Network(network::Event::PeerAddressReceived(gossiped_address));
+                GossiperAnnouncement::GossipReceived { item_id, sender } => {
+                    let reactor_event = MainEvent::Network(network::Event::PeerAddressReceived {
+                        gossiped_address: Box::new(item_id),
+                        advertiser: Box::new(sender),
+                    });
                     self.dispatch_event(effect_builder, rng, reactor_event)
                 }
+                // Addresses are only announced as received before being verified by the network
+                // component, which then begins gossiping them itself.
+                GossiperAnnouncement::NewCompleteItem(_)
+                | GossiperAnnouncement::NewItemBody { .. }
+                | GossiperAnnouncement::FinishedGossiping(_) => Effects::new(),
             },
             MainEvent::SyncLeaper(event) => reactor::wrap_effects(
                 MainEvent::SyncLeaper,
@@ -617,14 +721,33 @@ impl reactor::Reactor for MainReactor {
                         public_key,
                         timestamp,
                     } => {
+                        let mut effects = self.dispatch_event(
+                            effect_builder,
+                            rng,
+                            MainEvent::WebhookNotifier(webhook_notifier::Event::Notify(
+                                CriticalEvent::EquivocationDetected {
+                                    era_id,
+                                    public_key: public_key.clone(),
+                                    timestamp,
+                                },
+                            )),
+                        );
                         let reactor_event =
                             MainEvent::EventStreamServer(event_stream_server::Event::Fault {
                                 era_id,
                                 public_key: *public_key,
                                 timestamp,
                             });
-                        self.dispatch_event(effect_builder, rng, reactor_event)
+                        effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                        effects
                     }
+                    ConsensusAnnouncement::FttExceeded { era_id } => self.dispatch_event(
+                        effect_builder,
+                        rng,
+                        MainEvent::WebhookNotifier(webhook_notifier::Event::Notify(
+                            CriticalEvent::FttExceeded { era_id },
+                        )),
+                    ),
                 }
             }
 
@@ -952,6 +1075,22 @@ impl reactor::Reactor for MainReactor {
                     });
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
+            MainEvent::ContractRuntimeAnnouncement(
+                ContractRuntimeAnnouncement::EraRewardsDistributed { reward_report },
+            ) => {
+                let reactor_event = MainEvent::EventStreamServer(
+                    event_stream_server::Event::EraRewards(reward_report),
+                );
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            MainEvent::ContractRuntimeAnnouncement(
+                ContractRuntimeAnnouncement::BlockWriteStats { write_stats },
+            ) => {
+                let reactor_event = MainEvent::EventStreamServer(
+                    event_stream_server::Event::BlockWriteStats(write_stats),
+                );
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
             MainEvent::ContractRuntimeAnnouncement(
                 ContractRuntimeAnnouncement::UpcomingEraValidators {
                     era_that_is_ending,
@@ -965,6 +1104,19 @@ impl reactor::Reactor for MainReactor {
                 self.validator_matrix.register_eras(upcoming_era_validators);
                 Effects::new()
             }
+            MainEvent::ContractRuntimeAnnouncement(
+                ContractRuntimeAnnouncement::TriesQuarantined {
+                    block_hash,
+                    trie_hashes,
+                },
+            ) => {
+                let reactor_event =
+                    MainEvent::BlockSynchronizerRequest(BlockSynchronizerRequest::RefetchTries {
+                        block_hash,
+                        trie_hashes,
+                    });
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
 
             MainEvent::TrieRequestIncoming(req) => reactor::wrap_effects(
                 MainEvent::ContractRuntime,
@@ -1086,6 +1238,10 @@ impl reactor::Reactor for MainReactor {
             | MainEvent::BlockFetcherRequest(..)
             | MainEvent::DeployFetcher(..)
             | MainEvent::DeployFetcherRequest(..)
+            | MainEvent::DeployApprovalsFetcher(..)
+            | MainEvent::DeployApprovalsFetcherRequest(..)
+            | MainEvent::DeployExecutionResultFetcher(..)
+            | MainEvent::DeployExecutionResultFetcherRequest(..)
             | MainEvent::BlockHeaderFetcher(..)
             | MainEvent::BlockHeaderFetcherRequest(..)
             | MainEvent::TrieOrChunkFetcher(..)
@@ -394,6 +394,8 @@ impl MainReactor {
         effect_builder: EffectBuilder<MainEvent>,
         block_hash: BlockHash,
     ) -> CatchUpInstruction {
+        self.block_synchronizer
+            .register_target_height(true, self.block_accumulator.highest_usable_block_height());
         if self
             .block_synchronizer
             .register_block_by_hash(block_hash, true, true)
@@ -5,7 +5,8 @@ use crate::{
     logging::LoggingConfig, types::NodeConfig, BlockAccumulatorConfig, BlockSynchronizerConfig,
     ConsensusConfig, ContractRuntimeConfig, DeployBufferConfig, DiagnosticsPortConfig,
     EventStreamServerConfig, FetcherConfig, GossipConfig, NetworkConfig, RestServerConfig,
-    RpcServerConfig, SpeculativeExecConfig, StorageConfig, UpgradeWatcherConfig,
+    RpcServerConfig, SpeculativeExecConfig, StorageConfig, UpgradeDownloaderConfig,
+    UpgradeWatcherConfig, WebhookNotifierConfig,
 };
 
 /// Root configuration.
@@ -30,4 +31,6 @@ pub(crate) struct Config {
     pub(crate) block_accumulator: BlockAccumulatorConfig,
     pub(crate) block_synchronizer: BlockSynchronizerConfig,
     pub(crate) upgrade_watcher: UpgradeWatcherConfig,
+    pub(crate) upgrade_downloader: UpgradeDownloaderConfig,
+    pub(crate) webhook_notifier: WebhookNotifierConfig,
 }
@@ -6,14 +6,17 @@ use casper_types::{EraId, PublicKey};
 
 use crate::{
     components::{
-        block_synchronizer, block_synchronizer::BlockSynchronizerProgress, consensus::EraReport,
-        contract_runtime::ExecutionPreState, diagnostics_port, event_stream_server, network,
-        rest_server, rpc_server, upgrade_watcher,
+        block_synchronizer,
+        block_synchronizer::BlockSynchronizerProgress,
+        consensus::EraReport,
+        contract_runtime::ExecutionPreState,
+        diagnostics_port, event_stream_server, network, rest_server, rpc_server, upgrade_watcher,
+        webhook_notifier::{self, CriticalEvent},
     },
     effect::{EffectBuilder, EffectExt, Effects},
     fatal,
     reactor::main_reactor::{
-        catch_up::CatchUpInstruction, keep_up::KeepUpInstruction,
+        catch_up::CatchUpInstruction, keep_up::KeepUpInstruction, supervision::SupervisedComponent,
         upgrade_shutdown::UpgradeShutdownInstruction, upgrading_instruction::UpgradingInstruction,
         utils, validate::ValidateInstruction, MainEvent, MainReactor, ReactorState,
     },
@@ -33,7 +36,9 @@ impl MainReactor {
         if self.attempts > self.max_attempts {
             return fatal!(effect_builder, "exceeded reattempt tolerance").ignore();
         }
-        let (delay, mut effects) = self.do_crank(effect_builder, rng);
+        let mut effects = self.supervise_components(effect_builder);
+        let (delay, crank_effects) = self.do_crank(effect_builder, rng);
+        effects.extend(crank_effects);
         effects.extend(
             async move {
                 if !delay.is_zero() {
@@ -62,7 +67,7 @@ impl MainReactor {
                         return (Duration::ZERO, fatal!(effect_builder, "{}", msg).ignore());
                     }
                     info!("Initialize: switch to CatchUp");
-                    self.state = ReactorState::CatchUp;
+                    self.set_state(ReactorState::CatchUp);
                     (Duration::ZERO, Effects::new())
                 }
             },
@@ -73,7 +78,17 @@ impl MainReactor {
                 }
                 UpgradingInstruction::CatchUp => {
                     info!("Upgrading: switch to CatchUp");
-                    self.state = ReactorState::CatchUp;
+                    self.set_state(ReactorState::CatchUp);
+                    (Duration::ZERO, Effects::new())
+                }
+                UpgradingInstruction::KeepUp => {
+                    if let Err(msg) = self.refresh_contract_runtime() {
+                        return (Duration::ZERO, fatal!(effect_builder, "{}", msg).ignore());
+                    }
+                    // purge to avoid polluting the status endpoints w/ stale state
+                    self.block_synchronizer.purge();
+                    info!("Upgrading: switch to KeepUp");
+                    self.set_state(ReactorState::KeepUp);
                     (Duration::ZERO, Effects::new())
                 }
             },
@@ -83,13 +98,13 @@ impl MainReactor {
                 }
                 CatchUpInstruction::ShutdownForUpgrade => {
                     info!("CatchUp: shutting down for upgrade");
-                    self.state = ReactorState::ShutdownForUpgrade;
-                    (Duration::ZERO, Effects::new())
+                    self.set_state(ReactorState::ShutdownForUpgrade);
+                    (Duration::ZERO, notify_shutdown_for_upgrade(effect_builder))
                 }
                 CatchUpInstruction::CommitGenesis => match self.commit_genesis(effect_builder) {
                     Ok(effects) => {
                         info!("CatchUp: switch to Validate at genesis");
-                        self.state = ReactorState::Validate;
+                        self.set_state(ReactorState::Validate);
                         (Duration::ZERO, effects)
                     }
                     Err(msg) => (
@@ -100,7 +115,7 @@ impl MainReactor {
                 CatchUpInstruction::CommitUpgrade => match self.commit_upgrade(effect_builder) {
                     Ok(effects) => {
                         info!("CatchUp: switch to Upgrading");
-                        self.state = ReactorState::Upgrading;
+                        self.set_state(ReactorState::Upgrading);
                         (Duration::ZERO, effects)
                     }
                     Err(msg) => (
@@ -123,7 +138,7 @@ impl MainReactor {
                     // purge to avoid polluting the status endpoints w/ stale state
                     self.block_synchronizer.purge();
                     info!("CatchUp: switch to KeepUp");
-                    self.state = ReactorState::KeepUp;
+                    self.set_state(ReactorState::KeepUp);
                     (Duration::ZERO, Effects::new())
                 }
             },
@@ -133,8 +148,8 @@ impl MainReactor {
                 }
                 KeepUpInstruction::ShutdownForUpgrade => {
                     info!("KeepUp: switch to ShutdownForUpgrade");
-                    self.state = ReactorState::ShutdownForUpgrade;
-                    (Duration::ZERO, Effects::new())
+                    self.set_state(ReactorState::ShutdownForUpgrade);
+                    (Duration::ZERO, notify_shutdown_for_upgrade(effect_builder))
                 }
                 KeepUpInstruction::CheckLater(msg, wait) => {
                     debug!("KeepUp: {}", msg);
@@ -147,14 +162,14 @@ impl MainReactor {
                 KeepUpInstruction::CatchUp => {
                     self.block_synchronizer.purge();
                     info!("KeepUp: switch to CatchUp");
-                    self.state = ReactorState::CatchUp;
+                    self.set_state(ReactorState::CatchUp);
                     (Duration::ZERO, Effects::new())
                 }
                 KeepUpInstruction::Validate(effects) => {
                     // purge to avoid polluting the status endpoints w/ stale state
                     self.block_synchronizer.purge();
                     info!("KeepUp: switch to Validate");
-                    self.state = ReactorState::Validate;
+                    self.set_state(ReactorState::Validate);
                     (Duration::ZERO, effects)
                 }
             },
@@ -164,8 +179,8 @@ impl MainReactor {
                 }
                 ValidateInstruction::ShutdownForUpgrade => {
                     info!("Validate: switch to ShutdownForUpgrade");
-                    self.state = ReactorState::ShutdownForUpgrade;
-                    (Duration::ZERO, Effects::new())
+                    self.set_state(ReactorState::ShutdownForUpgrade);
+                    (Duration::ZERO, notify_shutdown_for_upgrade(effect_builder))
                 }
                 ValidateInstruction::NonSwitchBlock => {
                     (VALIDATION_STATUS_DELAY_FOR_NON_SWITCH_BLOCK, Effects::new())
@@ -180,7 +195,7 @@ impl MainReactor {
                 }
                 ValidateInstruction::KeepUp => {
                     info!("Validate: switch to KeepUp");
-                    self.state = ReactorState::KeepUp;
+                    self.set_state(ReactorState::KeepUp);
                     (Duration::ZERO, Effects::new())
                 }
             },
@@ -205,6 +220,45 @@ impl MainReactor {
 
     // NOTE: the order in which components are initialized is purposeful,
     // so don't alter the order without understanding the semantics
+    /// Moves the reactor to `next`.
+    ///
+    /// Transitions not declared as allowed (see [`ReactorState::may_transition_to`]) are logged,
+    /// and panic in debug builds.
+    fn set_state(&mut self, next: ReactorState) {
+        let current = self.state;
+        if !current.may_transition_to(next) {
+            error!(%current, %next, "undeclared reactor state transition");
+            debug_assert!(
+                false,
+                "undeclared reactor state transition from {} to {}",
+                current, next
+            );
+        }
+        #[cfg(test)]
+        super::reactor_state::transition_coverage::record(current, next);
+        self.state = next;
+    }
+
+    /// Restarts the non-critical components which have failed, once their backoff has elapsed.
+    fn supervise_components(
+        &mut self,
+        effect_builder: EffectBuilder<MainEvent>,
+    ) -> Effects<MainEvent> {
+        let mut effects = self.supervisor.supervise(
+            effect_builder,
+            SupervisedComponent::EventStreamServer,
+            &mut self.event_stream_server,
+            MainEvent::EventStreamServer(event_stream_server::Event::Initialize),
+        );
+        effects.extend(self.supervisor.supervise(
+            effect_builder,
+            SupervisedComponent::RestServer,
+            &mut self.rest_server,
+            MainEvent::RestServer(rest_server::Event::Initialize),
+        ));
+        effects
+    }
+
     fn initialize_next_component(
         &mut self,
         effect_builder: EffectBuilder<MainEvent>,
@@ -218,8 +272,7 @@ impl MainReactor {
             return Some(effects);
         }
         // init event stream to make sure it can bind & allow early client connection
-        if let Some(effects) = utils::initialize_component(
-            effect_builder,
+        if let Some(effects) = utils::initialize_supervised_component(
             &mut self.event_stream_server,
             MainEvent::EventStreamServer(event_stream_server::Event::Initialize),
         ) {
@@ -275,8 +328,7 @@ impl MainReactor {
         ) {
             return Some(effects);
         }
-        if let Some(effects) = utils::initialize_component(
-            effect_builder,
+        if let Some(effects) = utils::initialize_supervised_component(
             &mut self.rest_server,
             MainEvent::RestServer(rest_server::Event::Initialize),
         ) {
@@ -345,13 +397,6 @@ impl MainReactor {
             .ignore())
     }
 
-    fn upgrading_instruction(&self) -> UpgradingInstruction {
-        UpgradingInstruction::should_commit_upgrade(
-            self.should_commit_upgrade(),
-            self.control_logic_default_delay.into(),
-        )
-    }
-
     fn commit_upgrade(
         &mut self,
         effect_builder: EffectBuilder<MainEvent>,
@@ -370,7 +415,11 @@ impl MainReactor {
             self.chainspec.protocol_config.activation_point.era_id(),
             self.chainspec_raw_bytes.clone(),
         ) {
-            Ok(cfg) => match self.contract_runtime.commit_upgrade(cfg) {
+            Ok(cfg) => match self.contract_runtime.commit_upgrade(
+                cfg,
+                previous_block_header.height() + 1,
+                previous_block_header.next_block_era_id(),
+            ) {
                 Ok(success) => {
                     let post_state_hash = success.post_state_hash;
                     info!(
@@ -526,3 +575,12 @@ impl MainReactor {
         Ok(())
     }
 }
+
+/// Notifies the operator webhooks that the node is shutting down for an upgrade.
+fn notify_shutdown_for_upgrade(effect_builder: EffectBuilder<MainEvent>) -> Effects<MainEvent> {
+    effect_builder.immediately().event(|()| {
+        MainEvent::WebhookNotifier(webhook_notifier::Event::Notify(
+            CriticalEvent::ShutdownForUpgrade,
+        ))
+    })
+}
@@ -6,7 +6,7 @@ use casper_types::{bytesrepr, crypto::ErrorExt as CryptoError};
 use crate::{
     components::{
         contract_runtime, contract_runtime::BlockExecutionError, diagnostics_port, network,
-        storage, upgrade_watcher,
+        storage, upgrade_downloader, upgrade_watcher,
     },
     utils::{ListeningError, LoadError},
 };
@@ -58,6 +58,14 @@ pub(crate) enum Error {
     #[error("diagnostics port: {0}")]
     DiagnosticsPort(#[from] diagnostics_port::Error),
 
+    /// `UpgradeDownloader` component error.
+    #[error("upgrade downloader error: {0}")]
+    UpgradeDownloader(#[from] upgrade_downloader::Error),
+
+    /// `WebhookNotifier` component error.
+    #[error("webhook notifier error: {0}")]
+    WebhookNotifier(#[from] reqwest::Error),
+
     /// Error while loading the signing key pair.
     #[error("signing key pair load error: {0}")]
     LoadSigningKeyPair(#[from] LoadError<CryptoError>),
@@ -12,7 +12,8 @@ use crate::{
         block_validator, consensus, contract_runtime, deploy_acceptor, deploy_buffer,
         diagnostics_port, event_stream_server, fetcher, gossiper,
         network::{self, GossipedAddress},
-        rest_server, rpc_server, shutdown_trigger, storage, sync_leaper, upgrade_watcher,
+        rest_server, rpc_server, shutdown_trigger, storage, sync_leaper, upgrade_downloader,
+        upgrade_watcher, webhook_notifier,
     },
     effect::{
         announcements::{
@@ -30,17 +31,18 @@ use crate::{
         requests::{
             BeginGossipRequest, BlockAccumulatorRequest, BlockCompleteConfirmationRequest,
             BlockSynchronizerRequest, BlockValidationRequest, ChainspecRawBytesRequest,
-            ConsensusRequest, ContractRuntimeRequest, DeployBufferRequest, FetcherRequest,
-            MakeBlockExecutableRequest, MetricsRequest, NetworkInfoRequest, NetworkRequest,
-            ReactorStatusRequest, RestRequest, RpcRequest, SetNodeStopRequest, StorageRequest,
-            SyncGlobalStateRequest, TrieAccumulatorRequest, UpgradeWatcherRequest,
+            ComponentRestartsRequest, ConsensusRequest, ContractRuntimeRequest,
+            DeployBufferRequest, FetcherRequest, MakeBlockExecutableRequest, MetricsRequest,
+            NetworkInfoRequest, NetworkRequest, ReactorStatusRequest, RestRequest, RpcRequest,
+            SetNodeStopRequest, StorageRequest, SyncGlobalStateRequest, TrieAccumulatorRequest,
+            UpgradeWatcherRequest,
         },
     },
     protocol::Message,
     reactor::ReactorEvent,
     types::{
-        ApprovalsHashes, Block, BlockExecutionResultsOrChunk, BlockHeader, Deploy,
-        FinalitySignature, LegacyDeploy, SyncLeap, TrieOrChunk,
+        ApprovalsHashes, Block, BlockExecutionResultsOrChunk, BlockHeader, Deploy, DeployApprovals,
+        DeployExecutionResult, FinalitySignature, LegacyDeploy, SyncLeap, TrieOrChunk,
     },
 };
 
@@ -65,6 +67,8 @@ pub(crate) enum MainEvent {
     #[from]
     UpgradeWatcherAnnouncement(#[serde(skip_serializing)] UpgradeWatcherAnnouncement),
     #[from]
+    UpgradeDownloader(#[serde(skip_serializing)] upgrade_downloader::Event),
+    #[from]
     RpcServer(#[serde(skip_serializing)] rpc_server::Event),
     #[from]
     RpcServerAnnouncement(#[serde(skip_serializing)] RpcServerAnnouncement),
@@ -81,6 +85,8 @@ pub(crate) enum MainEvent {
     #[from]
     DiagnosticsPort(diagnostics_port::Event),
     #[from]
+    WebhookNotifier(#[serde(skip_serializing)] webhook_notifier::Event),
+    #[from]
     DumpConsensusStateRequest(DumpConsensusStateRequest),
     #[from]
     Network(network::Event<Message>),
@@ -191,6 +197,16 @@ pub(crate) enum MainEvent {
     #[from]
     DeployFetcherRequest(#[serde(skip_serializing)] FetcherRequest<Deploy>),
     #[from]
+    DeployApprovalsFetcher(#[serde(skip_serializing)] fetcher::Event<DeployApprovals>),
+    #[from]
+    DeployApprovalsFetcherRequest(#[serde(skip_serializing)] FetcherRequest<DeployApprovals>),
+    #[from]
+    DeployExecutionResultFetcher(#[serde(skip_serializing)] fetcher::Event<DeployExecutionResult>),
+    #[from]
+    DeployExecutionResultFetcherRequest(
+        #[serde(skip_serializing)] FetcherRequest<DeployExecutionResult>,
+    ),
+    #[from]
     DeployBufferRequest(DeployBufferRequest),
     #[from]
     ContractRuntime(contract_runtime::Event),
@@ -225,6 +241,8 @@ pub(crate) enum MainEvent {
     #[from]
     MainReactorRequest(ReactorStatusRequest),
     #[from]
+    ComponentRestartsRequest(ComponentRestartsRequest),
+    #[from]
     MetaBlockAnnouncement(MetaBlockAnnouncement),
 
     // Event related to figuring out validators for immediate switch blocks.
@@ -260,6 +278,8 @@ impl ReactorEvent for MainEvent {
             MainEvent::DeployAcceptor(_) => "DeployAcceptor",
             MainEvent::LegacyDeployFetcher(_) => "LegacyDeployFetcher",
             MainEvent::DeployFetcher(_) => "DeployFetcher",
+            MainEvent::DeployApprovalsFetcher(_) => "DeployApprovalsFetcher",
+            MainEvent::DeployExecutionResultFetcher(_) => "DeployExecutionResultFetcher",
             MainEvent::DeployGossiper(_) => "DeployGossiper",
             MainEvent::FinalitySignatureGossiper(_) => "FinalitySignatureGossiper",
             MainEvent::AddressGossiper(_) => "AddressGossiper",
@@ -275,6 +295,7 @@ impl ReactorEvent for MainEvent {
             MainEvent::ApprovalsHashesFetcher(_) => "ApprovalsHashesFetcher",
             MainEvent::ShutdownTrigger(_) => "ShutdownTrigger",
             MainEvent::DiagnosticsPort(_) => "DiagnosticsPort",
+            MainEvent::WebhookNotifier(_) => "WebhookNotifier",
             MainEvent::NetworkRequest(_) => "NetworkRequest",
             MainEvent::NetworkInfoRequest(_) => "NetworkInfoRequest",
             MainEvent::BlockHeaderFetcherRequest(_) => "BlockHeaderFetcherRequest",
@@ -284,6 +305,10 @@ impl ReactorEvent for MainEvent {
             }
             MainEvent::LegacyDeployFetcherRequest(_) => "LegacyDeployFetcherRequest",
             MainEvent::DeployFetcherRequest(_) => "DeployFetcherRequest",
+            MainEvent::DeployApprovalsFetcherRequest(_) => "DeployApprovalsFetcherRequest",
+            MainEvent::DeployExecutionResultFetcherRequest(_) => {
+                "DeployExecutionResultFetcherRequest"
+            }
             MainEvent::FinalitySignatureFetcherRequest(_) => "FinalitySignatureFetcherRequest",
             MainEvent::SyncLeapFetcherRequest(_) => "SyncLeapFetcherRequest",
             MainEvent::ApprovalsHashesFetcherRequest(_) => "ApprovalsHashesFetcherRequest",
@@ -304,6 +329,7 @@ impl ReactorEvent for MainEvent {
             MainEvent::DeployGossiperAnnouncement(_) => "DeployGossiperAnnouncement",
             MainEvent::AddressGossiperAnnouncement(_) => "AddressGossiperAnnouncement",
             MainEvent::UpgradeWatcherAnnouncement(_) => "UpgradeWatcherAnnouncement",
+            MainEvent::UpgradeDownloader(_) => "UpgradeDownloader",
             MainEvent::NetworkPeerBehaviorAnnouncement(_) => "BlocklistAnnouncement",
             MainEvent::DeployBufferAnnouncement(_) => "DeployBufferAnnouncement",
             MainEvent::AddressGossiperCrank(_) => "BeginAddressGossipRequest",
@@ -334,6 +360,7 @@ impl ReactorEvent for MainEvent {
             MainEvent::BlockFetcherRequest(_) => "BlockFetcherRequest",
             MainEvent::SetNodeStopRequest(_) => "SetNodeStopRequest",
             MainEvent::MainReactorRequest(_) => "MainReactorRequest",
+            MainEvent::ComponentRestartsRequest(_) => "ComponentRestartsRequest",
             MainEvent::MakeBlockExecutableRequest(_) => "MakeBlockExecutableRequest",
             MainEvent::MetaBlockAnnouncement(_) => "MetaBlockAnnouncement",
             MainEvent::GotImmediateSwitchBlockEraValidators(_, _, _) => {
@@ -361,6 +388,12 @@ impl Display for MainEvent {
             MainEvent::DeployAcceptor(event) => write!(f, "deploy acceptor: {}", event),
             MainEvent::LegacyDeployFetcher(event) => write!(f, "legacy deploy fetcher: {}", event),
             MainEvent::DeployFetcher(event) => write!(f, "deploy fetcher: {}", event),
+            MainEvent::DeployApprovalsFetcher(event) => {
+                write!(f, "deploy approvals fetcher: {}", event)
+            }
+            MainEvent::DeployExecutionResultFetcher(event) => {
+                write!(f, "deploy execution result fetcher: {}", event)
+            }
             MainEvent::DeployGossiper(event) => write!(f, "deploy gossiper: {}", event),
             MainEvent::FinalitySignatureGossiper(event) => {
                 write!(f, "block signature gossiper: {}", event)
@@ -405,6 +438,7 @@ impl Display for MainEvent {
             }
             MainEvent::ShutdownTrigger(event) => write!(f, "shutdown trigger: {}", event),
             MainEvent::DiagnosticsPort(event) => write!(f, "diagnostics port: {}", event),
+            MainEvent::WebhookNotifier(event) => write!(f, "webhook notifier: {}", event),
             MainEvent::NetworkRequest(req) => write!(f, "network request: {}", req),
             MainEvent::NetworkInfoRequest(req) => {
                 write!(f, "network info request: {}", req)
@@ -438,6 +472,12 @@ impl Display for MainEvent {
             MainEvent::DeployFetcherRequest(request) => {
                 write!(f, "deploy fetcher request: {}", request)
             }
+            MainEvent::DeployApprovalsFetcherRequest(request) => {
+                write!(f, "deploy approvals fetcher request: {}", request)
+            }
+            MainEvent::DeployExecutionResultFetcherRequest(request) => {
+                write!(f, "deploy execution result fetcher request: {}", request)
+            }
             MainEvent::FinalitySignatureFetcherRequest(request) => {
                 write!(f, "finality signature fetcher request: {}", request)
             }
@@ -486,6 +526,7 @@ impl Display for MainEvent {
             MainEvent::DeployBufferAnnouncement(ann) => {
                 write!(f, "deploy buffer announcement: {}", ann)
             }
+            MainEvent::UpgradeDownloader(event) => write!(f, "upgrade downloader: {}", event),
             MainEvent::UpgradeWatcherAnnouncement(ann) => {
                 write!(f, "chainspec loader announcement: {}", ann)
             }
@@ -511,6 +552,7 @@ impl Display for MainEvent {
             MainEvent::BlockFetcherRequest(inner) => Display::fmt(inner, f),
             MainEvent::SetNodeStopRequest(inner) => Display::fmt(inner, f),
             MainEvent::MainReactorRequest(inner) => Display::fmt(inner, f),
+            MainEvent::ComponentRestartsRequest(inner) => Display::fmt(inner, f),
             MainEvent::MakeBlockExecutableRequest(inner) => Display::fmt(inner, f),
             MainEvent::MetaBlockAnnouncement(inner) => Display::fmt(inner, f),
             MainEvent::GotImmediateSwitchBlockEraValidators(era_id, _, _) => {
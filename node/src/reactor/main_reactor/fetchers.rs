@@ -7,8 +7,8 @@ use crate::{
     reactor,
     reactor::main_reactor::MainEvent,
     types::{
-        ApprovalsHashes, Block, BlockExecutionResultsOrChunk, BlockHeader, Deploy,
-        FinalitySignature, LegacyDeploy, SyncLeap, TrieOrChunk,
+        ApprovalsHashes, Block, BlockExecutionResultsOrChunk, BlockHeader, Deploy, DeployApprovals,
+        DeployExecutionResult, FinalitySignature, LegacyDeploy, SyncLeap, TrieOrChunk,
     },
     utils::Source,
     FetcherConfig, NodeRng,
@@ -23,6 +23,8 @@ pub(super) struct Fetchers {
     finality_signature_fetcher: Fetcher<FinalitySignature>,
     legacy_deploy_fetcher: Fetcher<LegacyDeploy>,
     deploy_fetcher: Fetcher<Deploy>,
+    deploy_approvals_fetcher: Fetcher<DeployApprovals>,
+    deploy_execution_result_fetcher: Fetcher<DeployExecutionResult>,
     trie_or_chunk_fetcher: Fetcher<TrieOrChunk>,
     block_execution_results_or_chunk_fetcher: Fetcher<BlockExecutionResultsOrChunk>,
 }
@@ -44,6 +46,12 @@ impl Fetchers {
             legacy_deploy_fetcher: Fetcher::new("legacy_deploy", config, metrics_registry)?,
             block_fetcher: Fetcher::new("block", config, metrics_registry)?,
             deploy_fetcher: Fetcher::new("deploy", config, metrics_registry)?,
+            deploy_approvals_fetcher: Fetcher::new("deploy_approvals", config, metrics_registry)?,
+            deploy_execution_result_fetcher: Fetcher::new(
+                "deploy_execution_result",
+                config,
+                metrics_registry,
+            )?,
             trie_or_chunk_fetcher: Fetcher::new("trie_or_chunk", config, metrics_registry)?,
             block_execution_results_or_chunk_fetcher: Fetcher::new(
                 "block_execution_results_or_chunk_fetcher",
@@ -128,6 +136,29 @@ impl Fetchers {
                 self.deploy_fetcher
                     .handle_event(effect_builder, rng, request.into()),
             ),
+            MainEvent::DeployApprovalsFetcher(event) => reactor::wrap_effects(
+                MainEvent::DeployApprovalsFetcher,
+                self.deploy_approvals_fetcher
+                    .handle_event(effect_builder, rng, event),
+            ),
+            MainEvent::DeployApprovalsFetcherRequest(request) => reactor::wrap_effects(
+                MainEvent::DeployApprovalsFetcher,
+                self.deploy_approvals_fetcher
+                    .handle_event(effect_builder, rng, request.into()),
+            ),
+            MainEvent::DeployExecutionResultFetcher(event) => reactor::wrap_effects(
+                MainEvent::DeployExecutionResultFetcher,
+                self.deploy_execution_result_fetcher
+                    .handle_event(effect_builder, rng, event),
+            ),
+            MainEvent::DeployExecutionResultFetcherRequest(request) => reactor::wrap_effects(
+                MainEvent::DeployExecutionResultFetcher,
+                self.deploy_execution_result_fetcher.handle_event(
+                    effect_builder,
+                    rng,
+                    request.into(),
+                ),
+            ),
             MainEvent::TrieOrChunkFetcher(event) => reactor::wrap_effects(
                 MainEvent::TrieOrChunkFetcher,
                 self.trie_or_chunk_fetcher
@@ -251,6 +251,10 @@ impl MainReactor {
             }
             SyncInstruction::BlockSync { block_hash } => {
                 debug!("KeepUp: BlockSync: {:?}", block_hash);
+                self.block_synchronizer.register_target_height(
+                    false,
+                    self.block_accumulator.highest_usable_block_height(),
+                );
                 if self
                     .block_synchronizer
                     .register_block_by_hash(block_hash, false, true)
@@ -568,6 +572,10 @@ impl MainReactor {
         rng: &mut NodeRng,
         parent_hash: BlockHash,
     ) -> KeepUpInstruction {
+        // syncing to ttl stops at a height only known once reached
+        let target_height = self.sync_to_genesis.then(|| 0);
+        self.block_synchronizer
+            .register_target_height(true, target_height);
         if self
             .block_synchronizer
             .register_block_by_hash(parent_hash, true, true)
@@ -22,6 +22,7 @@ use serde::{Deserialize, Serialize};
 ///     CatchUp --> Upgrading
 ///     CatchUp -->|at genesis| Validate
 ///     Upgrading --> CatchUp
+///     Upgrading -->|immediate switch block validated| KeepUp
 ///     ShutdownForUpgrade --> End
 /// ```
 /// ```mermaid
@@ -61,7 +62,8 @@ pub enum ReactorState {
     Initialize,
     /// Orient to the network and attempt to catch up to tip.
     CatchUp,
-    /// Running commit upgrade and creating immediate switch block.
+    /// Running commit upgrade and creating immediate switch block, then validating it to skip
+    /// straight to `KeepUp` where possible.
     Upgrading,
     /// Stay caught up with tip.
     KeepUp,
@@ -70,3 +72,140 @@ pub enum ReactorState {
     /// Node should be shut down for upgrade.
     ShutdownForUpgrade,
 }
+
+/// The transitions between reactor states the control logic is allowed to perform.
+///
+/// Keep in sync with the state diagram on [`ReactorState`].
+pub(crate) const ALLOWED_TRANSITIONS: &[(ReactorState, ReactorState)] = &[
+    (ReactorState::Initialize, ReactorState::CatchUp),
+    (ReactorState::CatchUp, ReactorState::KeepUp),
+    (ReactorState::CatchUp, ReactorState::Upgrading),
+    (ReactorState::CatchUp, ReactorState::Validate),
+    (ReactorState::CatchUp, ReactorState::ShutdownForUpgrade),
+    (ReactorState::Upgrading, ReactorState::CatchUp),
+    (ReactorState::Upgrading, ReactorState::KeepUp),
+    (ReactorState::KeepUp, ReactorState::CatchUp),
+    (ReactorState::KeepUp, ReactorState::Validate),
+    (ReactorState::KeepUp, ReactorState::ShutdownForUpgrade),
+    (ReactorState::Validate, ReactorState::KeepUp),
+    (ReactorState::Validate, ReactorState::ShutdownForUpgrade),
+];
+
+impl ReactorState {
+    /// Returns `true` if moving from `self` to `next` is one of the [`ALLOWED_TRANSITIONS`].
+    pub(crate) fn may_transition_to(self, next: ReactorState) -> bool {
+        ALLOWED_TRANSITIONS.contains(&(self, next))
+    }
+}
+
+/// Records the reactor state transitions performed across all reactors in the test process.
+#[cfg(test)]
+pub(crate) mod transition_coverage {
+    use std::{env, fmt::Write, fs, sync::Mutex};
+
+    use once_cell::sync::Lazy;
+    use tracing::{info, warn};
+
+    use super::{ReactorState, ALLOWED_TRANSITIONS};
+
+    /// Environment variable naming a file to which the coverage report is written.
+    pub(crate) const REPORT_FILE_ENV_VAR: &str = "CASPER_TRANSITION_COVERAGE_REPORT";
+
+    static OBSERVED: Lazy<Mutex<Vec<(ReactorState, ReactorState)>>> =
+        Lazy::new(|| Mutex::new(Vec::new()));
+
+    /// Records that a reactor moved from `from` to `to`.
+    pub(crate) fn record(from: ReactorState, to: ReactorState) {
+        let mut observed = OBSERVED.lock().expect("lock poisoned");
+        if !observed.contains(&(from, to)) {
+            observed.push((from, to));
+        }
+    }
+
+    /// Returns all transitions recorded so far.
+    pub(crate) fn observed() -> Vec<(ReactorState, ReactorState)> {
+        OBSERVED.lock().expect("lock poisoned").clone()
+    }
+
+    /// Logs the coverage report and, if `CASPER_TRANSITION_COVERAGE_REPORT` is set, writes it to
+    /// the file it names.
+    ///
+    /// Tests run concurrently and each publishes the report for all transitions recorded so far,
+    /// so the lock is held while writing to leave the most complete report in the file.
+    pub(crate) fn publish() {
+        let observed = OBSERVED.lock().expect("lock poisoned");
+        let report = report_for(&observed);
+        info!("{}", report);
+        if let Some(path) = env::var_os(REPORT_FILE_ENV_VAR) {
+            if let Err(error) = fs::write(&path, format!("{}\n", report)) {
+                warn!(?path, %error, "failed to write reactor state transition coverage report");
+            }
+        }
+    }
+
+    /// Renders a report of which allowed transitions are in `observed`, listing the untested ones.
+    pub(super) fn report_for(observed: &[(ReactorState, ReactorState)]) -> String {
+        let untested: Vec<_> = ALLOWED_TRANSITIONS
+            .iter()
+            .filter(|transition| !observed.contains(transition))
+            .collect();
+        let mut report = format!(
+            "reactor state transitions covered: {}/{}",
+            ALLOWED_TRANSITIONS.len() - untested.len(),
+            ALLOWED_TRANSITIONS.len()
+        );
+        for (from, to) in untested {
+            let _ = write!(report, "\n  untested: {} -> {}", from, to);
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_STATES: [ReactorState; 6] = [
+        ReactorState::Initialize,
+        ReactorState::CatchUp,
+        ReactorState::Upgrading,
+        ReactorState::KeepUp,
+        ReactorState::Validate,
+        ReactorState::ShutdownForUpgrade,
+    ];
+
+    #[test]
+    fn every_state_should_be_reachable_and_only_shutdown_should_be_terminal() {
+        let mut reachable = vec![ReactorState::Initialize];
+        let mut index = 0;
+        while let Some(&state) = reachable.get(index) {
+            for next in ALL_STATES {
+                if state.may_transition_to(next) && !reachable.contains(&next) {
+                    reachable.push(next);
+                }
+            }
+            index += 1;
+        }
+        assert_eq!(reachable.len(), ALL_STATES.len());
+
+        for state in ALL_STATES {
+            let is_terminal = !ALL_STATES.iter().any(|next| state.may_transition_to(*next));
+            assert_eq!(is_terminal, state == ReactorState::ShutdownForUpgrade);
+            assert!(!state.may_transition_to(ReactorState::Initialize));
+        }
+    }
+
+    #[test]
+    fn coverage_report_should_list_untested_transitions() {
+        assert_eq!(
+            transition_coverage::report_for(ALLOWED_TRANSITIONS),
+            "reactor state transitions covered: 12/12"
+        );
+
+        let report = transition_coverage::report_for(&ALLOWED_TRANSITIONS[1..]);
+        assert!(report.starts_with("reactor state transitions covered: 11/12"));
+        assert!(report.contains("untested: Initialize -> CatchUp"));
+        assert!(!report.contains("KeepUp -> Validate"));
+        assert!(!report.contains("Upgrading -> KeepUp"));
+    }
+}
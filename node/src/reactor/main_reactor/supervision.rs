@@ -0,0 +1,371 @@
+//! Supervision of non-critical components.
+//!
+//! A supervised component which fails, either because it could not initialize or because its
+//! server task exited, is restarted in isolation instead of shutting down the node.  Restarts are
+//! spaced out by an exponential backoff.  Once a component has failed more than `max_restarts`
+//! times in a row, its failure is treated as fatal.  A component which stays up for at least the
+//! maximum backoff after a restart is considered recovered, resetting its backoff and failure
+//! streak.
+
+use std::{
+    cmp,
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    time::{Duration, Instant},
+};
+
+use datasize::DataSize;
+use futures::FutureExt;
+use prometheus::{IntCounter, Registry};
+use serde::Serialize;
+use smallvec::smallvec;
+use tracing::{info, warn};
+
+use crate::{
+    components::RestartableComponent,
+    effect::{EffectBuilder, EffectExt, Effects},
+    fatal,
+    reactor::main_reactor::MainEvent,
+    types::NodeConfig,
+    unregister_metric,
+};
+
+/// A component whose failures are handled by restarting it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, DataSize)]
+pub(crate) enum SupervisedComponent {
+    EventStreamServer,
+    RestServer,
+}
+
+impl SupervisedComponent {
+    const ALL: [SupervisedComponent; 2] = [
+        SupervisedComponent::EventStreamServer,
+        SupervisedComponent::RestServer,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            SupervisedComponent::EventStreamServer => "event_stream_server",
+            SupervisedComponent::RestServer => "rest_server",
+        }
+    }
+}
+
+impl Display for SupervisedComponent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The restart history of a supervised component.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct ComponentRestarts {
+    /// The supervised component.
+    pub(crate) component: SupervisedComponent,
+    /// The number of times the component has been restarted since the node started.
+    pub(crate) restarts: u64,
+    /// The number of failures since the component last recovered.
+    pub(crate) consecutive_failures: u32,
+    /// Whether the component is currently failed and waiting to be restarted.
+    pub(crate) awaiting_restart: bool,
+    /// The most recent failure of the component, if any.
+    pub(crate) last_failure: Option<String>,
+}
+
+/// How a failure of a supervised component is handled.
+#[derive(Debug, PartialEq, Eq)]
+enum FailureHandling {
+    /// The component is to be restarted now.
+    Restart,
+    /// The component is to be restarted once its backoff has elapsed.
+    AwaitBackoff,
+    /// The component failed too many times in a row, making its failure fatal.
+    Fatal {
+        consecutive_failures: u32,
+        failure: String,
+    },
+}
+
+#[derive(Debug)]
+struct Supervised {
+    restarts: u64,
+    consecutive_failures: u32,
+    backoff: Duration,
+    /// When the current failure is due to be handled by a restart, if the component is failed.
+    restart_at: Option<Instant>,
+    last_restart: Option<Instant>,
+    last_failure: Option<String>,
+    restart_counter: IntCounter,
+}
+
+/// Restarts failed non-critical components and keeps track of their restarts.
+#[derive(Debug)]
+pub(super) struct Supervisor {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_restarts: u32,
+    components: BTreeMap<SupervisedComponent, Supervised>,
+    registry: Registry,
+}
+
+impl Supervisor {
+    pub(super) fn new(config: &NodeConfig, registry: &Registry) -> Result<Self, prometheus::Error> {
+        let mut components = BTreeMap::new();
+        for component in SupervisedComponent::ALL {
+            let restart_counter = IntCounter::new(
+                format!("{}_restarts", component),
+                format!("number of times the {} has been restarted", component),
+            )?;
+            registry.register(Box::new(restart_counter.clone()))?;
+            let supervised = Supervised {
+                restarts: 0,
+                consecutive_failures: 0,
+                backoff: config.component_restart_initial_backoff.into(),
+                restart_at: None,
+                last_restart: None,
+                last_failure: None,
+                restart_counter,
+            };
+            components.insert(component, supervised);
+        }
+        Ok(Supervisor {
+            initial_backoff: config.component_restart_initial_backoff.into(),
+            max_backoff: config.component_restart_max_backoff.into(),
+            max_restarts: config.max_component_restarts,
+            components,
+            registry: registry.clone(),
+        })
+    }
+
+    /// Checks whether `component` has failed and, once its backoff has elapsed, restarts it by
+    /// resetting it and scheduling `initiating_event`.
+    ///
+    /// Returns a fatal effect if the component has failed too many times in a row.
+    pub(super) fn supervise(
+        &mut self,
+        effect_builder: EffectBuilder<MainEvent>,
+        supervised_component: SupervisedComponent,
+        component: &mut impl RestartableComponent<MainEvent>,
+        initiating_event: MainEvent,
+    ) -> Effects<MainEvent> {
+        let failure = match component.failure() {
+            Some(failure) => failure,
+            None => return Effects::new(),
+        };
+        match self.handle_failure(supervised_component, failure, Instant::now()) {
+            FailureHandling::Restart => {}
+            FailureHandling::AwaitBackoff => return Effects::new(),
+            FailureHandling::Fatal {
+                consecutive_failures,
+                failure,
+            } => {
+                return fatal!(
+                    effect_builder,
+                    "{} failed {} times in a row, last failure: {}",
+                    supervised_component,
+                    consecutive_failures,
+                    failure
+                )
+                .ignore();
+            }
+        }
+
+        let shutdown = component.reset();
+        component.start_initialization();
+        smallvec![async move {
+            shutdown.await;
+            smallvec![initiating_event]
+        }
+        .boxed()]
+    }
+
+    /// Records that `supervised_component` is failed with `failure` at `now`, and returns how the
+    /// failure is to be handled.
+    ///
+    /// Only the first call for a given failure counts it; later calls merely check whether its
+    /// backoff has elapsed.
+    fn handle_failure(
+        &mut self,
+        supervised_component: SupervisedComponent,
+        failure: String,
+        now: Instant,
+    ) -> FailureHandling {
+        let max_backoff = self.max_backoff;
+        let supervised = self
+            .components
+            .get_mut(&supervised_component)
+            .expect("all supervised components should be tracked");
+
+        let restart_at = match supervised.restart_at {
+            Some(restart_at) => restart_at,
+            None => {
+                let recovered = supervised
+                    .last_restart
+                    .map_or(false, |last_restart| now - last_restart >= max_backoff);
+                if recovered {
+                    supervised.consecutive_failures = 0;
+                    supervised.backoff = self.initial_backoff;
+                }
+                supervised.consecutive_failures += 1;
+                if supervised.consecutive_failures > self.max_restarts {
+                    return FailureHandling::Fatal {
+                        consecutive_failures: supervised.consecutive_failures,
+                        failure,
+                    };
+                }
+                warn!(
+                    component = %supervised_component,
+                    %failure,
+                    backoff = ?supervised.backoff,
+                    "supervised component failed, restarting it after backoff"
+                );
+                let restart_at = now + supervised.backoff;
+                supervised.restart_at = Some(restart_at);
+                supervised.last_failure = Some(failure);
+                restart_at
+            }
+        };
+        if now < restart_at {
+            return FailureHandling::AwaitBackoff;
+        }
+
+        supervised.restart_at = None;
+        supervised.last_restart = Some(now);
+        supervised.restarts += 1;
+        supervised.restart_counter.inc();
+        supervised.backoff = cmp::min(supervised.backoff * 2, max_backoff);
+        info!(
+            component = %supervised_component,
+            restarts = supervised.restarts,
+            "restarting supervised component"
+        );
+        FailureHandling::Restart
+    }
+
+    /// Returns the restart history of every supervised component.
+    pub(super) fn restarts(&self) -> Vec<ComponentRestarts> {
+        self.components
+            .iter()
+            .map(|(component, supervised)| ComponentRestarts {
+                component: *component,
+                restarts: supervised.restarts,
+                consecutive_failures: supervised.consecutive_failures,
+                awaiting_restart: supervised.restart_at.is_some(),
+                last_failure: supervised.last_failure.clone(),
+            })
+            .collect()
+    }
+}
+
+impl Drop for Supervisor {
+    fn drop(&mut self) {
+        for supervised in self.components.values() {
+            unregister_metric!(self.registry, supervised.restart_counter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPONENT: SupervisedComponent = SupervisedComponent::RestServer;
+
+    fn new_supervisor(max_component_restarts: u32) -> Supervisor {
+        let config = NodeConfig {
+            component_restart_initial_backoff: "1s".parse().unwrap(),
+            component_restart_max_backoff: "4s".parse().unwrap(),
+            max_component_restarts,
+            ..Default::default()
+        };
+        Supervisor::new(&config, &Registry::new()).expect("should create supervisor")
+    }
+
+    /// Fails the component at `failed_at` and checks it is restarted exactly `backoff` later.
+    ///
+    /// Returns the time of the restart.
+    fn fail_and_restart(
+        supervisor: &mut Supervisor,
+        failed_at: Instant,
+        backoff: Duration,
+    ) -> Instant {
+        let millisecond = Duration::from_millis(1);
+        for now in [failed_at, failed_at + backoff - millisecond] {
+            assert_eq!(
+                supervisor.handle_failure(COMPONENT, "failure".to_string(), now),
+                FailureHandling::AwaitBackoff
+            );
+        }
+        let restarted_at = failed_at + backoff;
+        assert_eq!(
+            supervisor.handle_failure(COMPONENT, "failure".to_string(), restarted_at),
+            FailureHandling::Restart
+        );
+        restarted_at
+    }
+
+    fn restarts(supervisor: &Supervisor) -> ComponentRestarts {
+        supervisor
+            .restarts()
+            .into_iter()
+            .find(|restarts| restarts.component == COMPONENT)
+            .expect("should track component")
+    }
+
+    #[test]
+    fn should_double_backoff_up_to_max() {
+        let mut supervisor = new_supervisor(10);
+        let mut now = Instant::now();
+        for (index, backoff_secs) in [1, 2, 4, 4].iter().enumerate() {
+            // The component fails again right after each restart, so never recovers.
+            now = fail_and_restart(&mut supervisor, now, Duration::from_secs(*backoff_secs));
+            let restarts = restarts(&supervisor);
+            assert_eq!(restarts.restarts, index as u64 + 1);
+            assert_eq!(restarts.consecutive_failures, index as u32 + 1);
+            assert!(!restarts.awaiting_restart);
+            assert_eq!(restarts.last_failure.as_deref(), Some("failure"));
+        }
+        assert_eq!(supervisor.components[&COMPONENT].restart_counter.get(), 4);
+        // The other component is unaffected.
+        assert_eq!(
+            supervisor.components[&SupervisedComponent::EventStreamServer].restarts,
+            0
+        );
+    }
+
+    #[test]
+    fn should_reset_after_recovery() {
+        let mut supervisor = new_supervisor(2);
+        let mut now = Instant::now();
+        now = fail_and_restart(&mut supervisor, now, Duration::from_secs(1));
+        now = fail_and_restart(&mut supervisor, now, Duration::from_secs(2));
+        assert_eq!(restarts(&supervisor).consecutive_failures, 2);
+
+        // Staying up for the max backoff after a restart counts as recovery: the next failure
+        // starts a new streak, with the initial backoff, rather than escalating.
+        now += Duration::from_secs(4);
+        fail_and_restart(&mut supervisor, now, Duration::from_secs(1));
+        let restarts = restarts(&supervisor);
+        assert_eq!(restarts.restarts, 3);
+        assert_eq!(restarts.consecutive_failures, 1);
+    }
+
+    #[test]
+    fn should_escalate_to_fatal_once_restarts_are_exhausted() {
+        let mut supervisor = new_supervisor(2);
+        let mut now = Instant::now();
+        now = fail_and_restart(&mut supervisor, now, Duration::from_secs(1));
+        now = fail_and_restart(&mut supervisor, now, Duration::from_secs(2));
+
+        // Failing again just short of recovery exceeds `max_component_restarts`.
+        now += Duration::from_secs(4) - Duration::from_millis(1);
+        assert_eq!(
+            supervisor.handle_failure(COMPONENT, "last failure".to_string(), now),
+            FailureHandling::Fatal {
+                consecutive_failures: 3,
+                failure: "last failure".to_string(),
+            }
+        );
+        assert_eq!(restarts(&supervisor).restarts, 2);
+    }
+}
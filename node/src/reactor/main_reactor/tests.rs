@@ -30,7 +30,9 @@ use crate::{
     },
     protocol::Message,
     reactor::{
-        main_reactor::{Config, MainEvent, MainReactor, ReactorState},
+        main_reactor::{
+            reactor_state::transition_coverage, Config, MainEvent, MainReactor, ReactorState,
+        },
         Runner,
     },
     testing::{
@@ -146,6 +148,9 @@ impl TestChain {
     }
 
     /// Creates an initializer/validator configuration for the `idx`th validator.
+    ///
+    /// The storage and secret key of the validator are created once, and reused by the
+    /// configurations of the validator's later instances.
     fn create_node_config(&mut self, idx: usize, first_node_port: u16) -> Config {
         // Set the network configuration.
         let mut cfg = Config {
@@ -158,18 +163,21 @@ impl TestChain {
             ..Default::default()
         };
 
-        // Additionally set up storage in a temporary directory.
-        let (storage_cfg, temp_dir) = storage::Config::default_for_tests();
-        // ...and the secret key for our validator.
-        {
-            let secret_key_path = temp_dir.path().join("secret_key");
+        if idx == self.storages.len() {
+            // Additionally set up storage in a temporary directory.
+            let (_, temp_dir) = storage::Config::default_for_tests();
+            // ...and the secret key for our validator.
             self.keys[idx]
-                .to_file(secret_key_path.clone())
+                .to_file(temp_dir.path().join("secret_key"))
                 .expect("could not write secret key");
-            cfg.consensus.secret_key_path = External::Path(secret_key_path);
+            self.storages.push(temp_dir);
         }
-        self.storages.push(temp_dir);
-        cfg.storage = storage_cfg;
+        let storage_dir = self.storages[idx].path();
+        cfg.consensus.secret_key_path = External::Path(storage_dir.join("secret_key"));
+        cfg.storage = storage::Config {
+            path: storage_dir.join("lmdb"),
+            ..Default::default()
+        };
         cfg
     }
 
@@ -198,6 +206,23 @@ impl TestChain {
 
         Ok(network)
     }
+
+    /// Restarts the nodes of `network`, which must all have exited, on their existing storage,
+    /// with the chainspec upgraded to `protocol_version` from `activation_point` on.
+    async fn restart_upgraded_network(
+        &mut self,
+        network: TestingNetwork<FilterReactor<MainReactor>>,
+        protocol_version: ProtocolVersion,
+        activation_point: EraId,
+        rng: &mut NodeRng,
+    ) -> anyhow::Result<TestingNetwork<FilterReactor<MainReactor>>> {
+        // The storage of a node can only be opened again once its previous instance is gone.
+        drop(network);
+        let protocol_config = &mut self.chainspec_mut().protocol_config;
+        protocol_config.version = protocol_version;
+        protocol_config.activation_point = ActivationPoint::EraId(activation_point);
+        self.create_initialized_network(rng).await
+    }
 }
 
 /// Given an era number, returns a predicate to check if all of the nodes are in the specified era.
@@ -341,6 +366,12 @@ async fn run_network() {
         Duration::from_secs(1001),
     )
     .await;
+
+    // The nodes must have caught up and started validating.
+    let observed = transition_coverage::observed();
+    assert!(observed.contains(&(ReactorState::Initialize, ReactorState::CatchUp)));
+    assert!(observed.iter().any(|(_, to)| *to == ReactorState::Validate));
+    transition_coverage::publish();
 }
 
 #[tokio::test]
@@ -540,6 +571,11 @@ async fn assert_network_shutdown_for_upgrade_with_stakes(rng: &mut TestRng, stak
     // Run until the nodes shut down for the upgrade.
     let timeout = Duration::from_secs(90);
     net.settle_on_exit(rng, ExitCode::Success, timeout).await;
+
+    assert!(transition_coverage::observed()
+        .iter()
+        .any(|(_, to)| *to == ReactorState::ShutdownForUpgrade));
+    transition_coverage::publish();
 }
 
 #[tokio::test]
@@ -653,6 +689,94 @@ async fn dont_upgrade_without_switch_block() {
     }
 }
 
+#[tokio::test]
+async fn should_keep_up_right_after_creating_immediate_switch_block() {
+    testing::init_logging();
+
+    let mut rng = crate::new_rng();
+
+    const NETWORK_SIZE: usize = 2;
+    const INITIALIZATION_TIMEOUT: Duration = Duration::from_secs(20);
+
+    let mut chain = TestChain::new(&mut rng, NETWORK_SIZE, None);
+    chain.chainspec_mut().core_config.minimum_era_height = 2;
+    chain.chainspec_mut().core_config.era_duration = TimeDiff::from_millis(0);
+    chain.chainspec_mut().core_config.minimum_block_time = "1second".parse().unwrap();
+
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+
+    // Wait until initialization is finished, so upgrade watcher won't reject test requests.
+    net.settle_on(
+        &mut rng,
+        move |nodes: &Nodes| {
+            nodes
+                .values()
+                .all(|runner| !matches!(runner.main_reactor().state, ReactorState::Initialize))
+        },
+        INITIALIZATION_TIMEOUT,
+    )
+    .await;
+
+    // An upgrade is scheduled for era 2, after the switch block in era 1 (height 2).
+    let activation_point = EraId::from(2);
+    let upgraded_version = ProtocolVersion::from_parts(999, 0, 0);
+    for runner in net.runners_mut() {
+        runner
+            .process_injected_effects(|effect_builder| {
+                let upgrade =
+                    NextUpgrade::new(ActivationPoint::EraId(activation_point), upgraded_version);
+                effect_builder
+                    .announce_upgrade_activation_point_read(upgrade)
+                    .ignore()
+            })
+            .await;
+    }
+
+    // Run until the nodes shut down for the upgrade.
+    let timeout = Duration::from_secs(90);
+    net.settle_on_exit(&mut rng, ExitCode::Success, timeout)
+        .await;
+
+    // Restart the nodes running the upgraded protocol.  Each commits the upgrade and creates the
+    // immediate switch block itself, so it should keep up right away rather than catch up.
+    let mut net = chain
+        .restart_upgraded_network(net, upgraded_version, activation_point, &mut rng)
+        .await
+        .expect("network restart failed");
+    net.settle_on(
+        &mut rng,
+        move |nodes: &Nodes| {
+            nodes.values().all(|runner| {
+                matches!(
+                    runner.main_reactor().state,
+                    ReactorState::KeepUp | ReactorState::Validate
+                )
+            })
+        },
+        timeout,
+    )
+    .await;
+
+    for runner in net.nodes().values() {
+        let header = runner
+            .main_reactor()
+            .storage()
+            .transactional_get_switch_block_by_era_id(activation_point.value())
+            .expect("failed to read from storage")
+            .expect("missing immediate switch block")
+            .take_header();
+        assert_eq!(header.protocol_version(), upgraded_version);
+        assert_eq!(header.height(), 3);
+    }
+    assert!(
+        transition_coverage::observed().contains(&(ReactorState::Upgrading, ReactorState::KeepUp))
+    );
+    transition_coverage::publish();
+}
+
 #[tokio::test]
 async fn should_store_finalized_approvals() {
     testing::init_logging();
@@ -1,19 +1,92 @@
 use std::time::Duration;
 
+use tracing::info;
+
+use crate::{
+    reactor::main_reactor::MainReactor,
+    types::{ActivationPoint, BlockHeader},
+};
+
 pub(super) enum UpgradingInstruction {
     CheckLater(String, Duration),
     CatchUp,
+    KeepUp,
 }
 
-impl UpgradingInstruction {
-    pub(super) fn should_commit_upgrade(
-        should_commit_upgrade: bool,
-        wait: Duration,
-    ) -> UpgradingInstruction {
-        if should_commit_upgrade {
-            UpgradingInstruction::CheckLater("awaiting upgrade".to_string(), wait)
-        } else {
-            UpgradingInstruction::CatchUp
+impl MainReactor {
+    pub(super) fn upgrading_instruction(&self) -> UpgradingInstruction {
+        if self.should_commit_upgrade() {
+            return UpgradingInstruction::CheckLater(
+                "awaiting upgrade".to_string(),
+                self.control_logic_default_delay.into(),
+            );
+        }
+        // the immediate switch block was created locally, so if it checks out there is nothing
+        // the generic catch up flow could teach us; go straight to KeepUp
+        match self.validate_immediate_switch_block() {
+            Ok(block_header) => {
+                info!(
+                    block_hash = %block_header.block_hash(),
+                    era_id = %block_header.era_id(),
+                    "Upgrading: immediate switch block validated"
+                );
+                UpgradingInstruction::KeepUp
+            }
+            Err(msg) => {
+                info!(%msg, "Upgrading: cannot skip CatchUp");
+                UpgradingInstruction::CatchUp
+            }
+        }
+    }
+
+    /// Checks that the highest complete block is the immediate switch block of the current
+    /// upgrade, that it directly follows the last block before the activation point and that the
+    /// validators of the following era are known.
+    fn validate_immediate_switch_block(&self) -> Result<&BlockHeader, String> {
+        let block_header = self
+            .switch_block
+            .as_ref()
+            .ok_or_else(|| "no switch block".to_string())?;
+        if self.storage.highest_complete_block_height() != Some(block_header.height()) {
+            return Err(format!(
+                "switch block at height {} is not the highest complete block",
+                block_header.height()
+            ));
+        }
+        if block_header.protocol_version() != self.chainspec.protocol_version() {
+            return Err(format!(
+                "switch block has protocol version {}, expected {}",
+                block_header.protocol_version(),
+                self.chainspec.protocol_version()
+            ));
+        }
+        if ActivationPoint::EraId(block_header.era_id())
+            != self.chainspec.protocol_config.activation_point
+        {
+            return Err(format!(
+                "switch block is in {}, not at the activation point",
+                block_header.era_id()
+            ));
+        }
+        let parent_header = self
+            .storage
+            .read_block_header(block_header.parent_hash())
+            .map_err(|error| format!("failed to read parent of switch block: {}", error))?
+            .ok_or_else(|| "parent of switch block not stored".to_string())?;
+        if !self
+            .chainspec
+            .protocol_config
+            .is_last_block_before_activation(&parent_header)
+        {
+            return Err(format!(
+                "parent {} is not the last block before activation",
+                parent_header.block_hash()
+            ));
+        }
+        let next_era_id = block_header.next_block_era_id();
+        if !self.validator_matrix.has_era(&next_era_id) {
+            return Err(format!("validators of {} not known", next_era_id));
         }
+        Ok(block_header)
     }
 }
@@ -2,13 +2,21 @@ use futures::FutureExt;
 use smallvec::smallvec;
 use tracing::info;
 
+use casper_execution_engine::storage::global_state::state_root_anchors::StateRootAnchor;
+
 use crate::{
-    components::InitializedComponent,
+    components::{
+        contract_runtime::ContractRuntime, storage::Storage, InitializedComponent,
+        RestartableComponent,
+    },
     effect::{EffectBuilder, EffectExt, Effects},
     fatal,
-    reactor::main_reactor::MainEvent,
+    reactor::main_reactor::{Error, MainEvent},
 };
 
+/// The most state roots anchored per transaction while backfilling state root anchors.
+const BACKFILL_ANCHORS_BATCH_LEN: usize = 1024;
+
 pub(super) fn initialize_component(
     effect_builder: EffectBuilder<MainEvent>,
     component: &mut impl InitializedComponent<MainEvent>,
@@ -24,3 +32,60 @@ pub(super) fn initialize_component(
     }
     None
 }
+
+/// Like `initialize_component`, but a component failing to initialize is left to be restarted by
+/// the supervisor rather than shutting down the node.
+pub(super) fn initialize_supervised_component(
+    component: &mut impl RestartableComponent<MainEvent>,
+    initiating_event: MainEvent,
+) -> Option<Effects<MainEvent>> {
+    if component.is_uninitialized() {
+        component.start_initialization();
+        info!("pending initialization of {}", component.name());
+        return Some(smallvec![async { smallvec![initiating_event] }.boxed()]);
+    }
+    None
+}
+
+/// Anchors the state roots of the available blocks which were stored before their state roots were
+/// anchored as they were committed.
+///
+/// The blocks are anchored from the highest down, so the lowest available block's state root being
+/// anchored means there is nothing left to backfill, even if a previous backfill was interrupted.
+pub(super) fn backfill_state_root_anchors(
+    storage: &Storage,
+    contract_runtime: &ContractRuntime,
+) -> Result<(), Error> {
+    let (lowest_header, highest_height) = match (
+        storage.get_highest_orphaned_block_header(),
+        storage.highest_complete_block_height(),
+    ) {
+        (Some(lowest_header), Some(highest_height)) => (lowest_header, highest_height),
+        _ => return Ok(()),
+    };
+    let engine_state = contract_runtime.engine_state();
+    if engine_state
+        .state_root_anchor(lowest_header.state_root_hash())?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    let mut anchored = 0;
+    let mut batch = Vec::with_capacity(BACKFILL_ANCHORS_BATCH_LEN);
+    for height in (lowest_header.height()..=highest_height).rev() {
+        if let Some(header) = storage.read_block_header_by_height(height)? {
+            let anchor = StateRootAnchor {
+                block_height: height,
+                era_id: header.era_id(),
+            };
+            batch.push((*header.state_root_hash(), anchor));
+        }
+        if batch.len() == BACKFILL_ANCHORS_BATCH_LEN || height == lowest_header.height() {
+            anchored += engine_state.backfill_state_root_anchors(&batch)?;
+            batch.clear();
+        }
+    }
+    info!(anchored, "backfilled state root anchors");
+    Ok(())
+}
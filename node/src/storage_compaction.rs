@@ -0,0 +1,240 @@
+//! Offline compaction of the node's databases.
+//!
+//! Writes minimal copies of the block store and the global state to a separate directory. Both are
+//! copied using LMDB's compacting copy, which omits free pages. The global state is copied with all
+//! of its databases to a staging directory first, where tries not reachable from the state root
+//! hashes of stored blocks are pruned, so tries left behind by abandoned executions are not carried
+//! over.  The pruned copy is then copied once more to the output directory.
+//!
+//! The node must not be running while the databases are compacted.
+
+use std::{collections::HashSet, fs, io, path::PathBuf, sync::Arc};
+
+use lmdb::DatabaseFlags;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use casper_execution_engine::{
+    shared::newtypes::CorrelationId,
+    storage::{
+        error::Error as GlobalStateError,
+        global_state::{lmdb::LmdbGlobalState, read_only::ReadOnlyLmdbGlobalState, StateProvider},
+        transaction_source::lmdb::LmdbEnvironment,
+        trie::{Trie, TrieRaw},
+        trie_store::lmdb::LmdbTrieStore,
+    },
+};
+use casper_hashing::Digest;
+use casper_types::{bytesrepr, Key, StoredValue};
+
+use crate::{
+    components::{
+        contract_runtime::Config as ContractRuntimeConfig,
+        storage::{self, FatalStorageError},
+    },
+    reactor::main_reactor::Config,
+    types::{chainspec, Chainspec, ChainspecRawBytes},
+    utils::{Loadable, WithDir},
+};
+
+/// The files which must not already exist in the output directory.
+const OUTPUT_FILES: [&str; 2] = ["storage.lmdb", "data.lmdb"];
+
+/// The subdirectory of the output directory the global state is pruned in.
+const STAGING_DIR: &str = "global_state_staging";
+
+/// The number of unreachable tries deleted per write transaction while pruning.
+const PRUNE_BATCH_SIZE: usize = 10_000;
+
+/// Error returned as a result of compacting storage.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// Error loading the chainspec.
+    #[error("error loading chainspec: {0}")]
+    LoadChainspec(chainspec::Error),
+
+    /// The output directory already holds a database.
+    #[error("refusing to overwrite existing database {}", .0.display())]
+    OutputExists(PathBuf),
+
+    /// Error compacting the block store.
+    #[error("error compacting block store: {0}")]
+    BlockStore(#[from] FatalStorageError),
+
+    /// Error accessing global state.
+    #[error("error accessing global state: {0}")]
+    GlobalState(#[from] GlobalStateError),
+
+    /// Error deserializing a trie.
+    #[error("error deserializing trie {trie_key}: {error}")]
+    DeserializeTrie {
+        /// The hash of the trie.
+        trie_key: Digest,
+        /// The deserialization error.
+        error: bytesrepr::Error,
+    },
+
+    /// Error creating or removing the staging directory.
+    #[error("error accessing staging directory {}: {1}", .0.display())]
+    StagingDirectory(PathBuf, io::Error),
+
+    /// A trie reachable from a state root is missing from the compacted trie store.
+    #[error("global state under {state_root_hash} is not reachable in the compacted copy: missing trie {trie_key}")]
+    Unreachable {
+        /// The state root hash being verified.
+        state_root_hash: Digest,
+        /// The hash of the missing trie.
+        trie_key: Digest,
+    },
+}
+
+/// Writes compacted copies of the block store and the global state to `output_dir`.
+///
+/// Once the copy has been written, every retained state root is checked to be fully reachable in
+/// it. The compacted files can then be swapped in for the originals while the node is stopped.
+pub(crate) fn compact_storage(config: WithDir<Config>, output_dir: PathBuf) -> Result<(), Error> {
+    let (root, config) = config.into_parts();
+    let network_name = <(Chainspec, ChainspecRawBytes)>::from_path(&root)
+        .map_err(Error::LoadChainspec)?
+        .0
+        .network_config
+        .name;
+
+    for file in OUTPUT_FILES {
+        let path = output_dir.join(file);
+        if path.exists() {
+            return Err(Error::OutputExists(path));
+        }
+    }
+    let staging_dir = output_dir.join(STAGING_DIR);
+    if staging_dir.exists() {
+        return Err(Error::OutputExists(staging_dir));
+    }
+
+    let storage_config = WithDir::new(&root, config.storage.clone());
+    let state_root_hashes =
+        storage::compact_block_store(&storage_config, &network_name, &output_dir)?;
+
+    let source_dir = storage_config
+        .with_dir(config.storage.path.clone())
+        .join(&network_name);
+    let contract_runtime_config = &config.contract_runtime;
+    let source = ReadOnlyLmdbGlobalState::open(
+        &source_dir,
+        contract_runtime_config.max_global_state_size(),
+        contract_runtime_config.max_readers(),
+    )?;
+
+    let correlation_id = CorrelationId::new();
+    let mut retained_roots = Vec::with_capacity(state_root_hashes.len());
+    for state_root_hash in state_root_hashes {
+        if source
+            .get_trie_full(correlation_id, &state_root_hash)?
+            .is_none()
+        {
+            // Nodes which joined the network after genesis don't hold global state for blocks
+            // below the one they synced to.
+            warn!(%state_root_hash, "global state not held by node, not retaining");
+            continue;
+        }
+        retained_roots.push(state_root_hash);
+    }
+
+    // The copy carries over every database in the environment, i.e. the trie store along with its
+    // shards and compression metadata, the state root anchors, the key index and the quarantine.
+    fs::create_dir_all(&staging_dir)
+        .map_err(|error| Error::StagingDirectory(staging_dir.clone(), error))?;
+    source.environment().copy_to(&staging_dir, true)?;
+    drop(source);
+
+    // Syncing after every pruned batch would make pruning needlessly slow; we sync once at the end
+    // instead.
+    let staging_environment = Arc::new(LmdbEnvironment::new(
+        &staging_dir,
+        contract_runtime_config.max_global_state_size(),
+        contract_runtime_config.max_readers(),
+        true,
+    )?);
+    let staging = open_global_state(contract_runtime_config, Arc::clone(&staging_environment))?;
+    let prune_result = staging.prune(correlation_id, &retained_roots, PRUNE_BATCH_SIZE)?;
+    staging_environment.sync().map_err(GlobalStateError::from)?;
+    info!(
+        retained_roots = retained_roots.len(),
+        reachable_tries = prune_result.reachable_tries,
+        deleted_tries = prune_result.deleted_tries,
+        "pruned global state"
+    );
+
+    // Pruning only frees pages, so a second compacting copy is needed to leave them behind.
+    staging_environment.copy_to(&output_dir, true)?;
+    drop(staging);
+    drop(staging_environment);
+    fs::remove_dir_all(&staging_dir)
+        .map_err(|error| Error::StagingDirectory(staging_dir.clone(), error))?;
+    info!("finished compacting global state");
+
+    let target = ReadOnlyLmdbGlobalState::open(
+        &output_dir,
+        contract_runtime_config.max_global_state_size(),
+        contract_runtime_config.max_readers(),
+    )?;
+    let mut verified = HashSet::new();
+    for state_root_hash in retained_roots {
+        verify_reachable(correlation_id, &target, state_root_hash, &mut verified)?;
+    }
+    info!(output_dir = %output_dir.display(), "verified compacted storage");
+
+    Ok(())
+}
+
+/// Opens the global state in `environment` for writing, with the trie store layout configured for
+/// the node.
+fn open_global_state(
+    config: &ContractRuntimeConfig,
+    environment: Arc<LmdbEnvironment>,
+) -> Result<LmdbGlobalState, GlobalStateError> {
+    let mut trie_store = LmdbTrieStore::new(&environment, None, DatabaseFlags::empty())?;
+    if let Some(level) = config.trie_compression_level() {
+        trie_store = trie_store.with_compression(&environment, level)?;
+    }
+    if config.shard_tries() {
+        trie_store = trie_store.with_sharding(&environment)?;
+    }
+    LmdbGlobalState::empty(environment, Arc::new(trie_store))
+}
+
+/// Checks that every trie reachable from `state_root_hash` is present in `global_state`.
+///
+/// Subtrees rooted at the non-leaf tries in `verified` are skipped, and the non-leaf tries visited
+/// are added to it, so shared subtrees are only checked once across state roots.
+fn verify_reachable(
+    correlation_id: CorrelationId,
+    global_state: &ReadOnlyLmdbGlobalState,
+    state_root_hash: Digest,
+    verified: &mut HashSet<Digest>,
+) -> Result<(), Error> {
+    let mut pending = vec![state_root_hash];
+    while let Some(trie_key) = pending.pop() {
+        if verified.contains(&trie_key) {
+            continue;
+        }
+        let trie_raw = global_state
+            .get_trie_full(correlation_id, &trie_key)?
+            .ok_or(Error::Unreachable {
+                state_root_hash,
+                trie_key,
+            })?;
+        let trie = deserialize_trie(trie_key, &trie_raw)?;
+        if let Trie::Leaf { .. } = trie {
+            continue;
+        }
+        pending.extend(trie.iter_descendants());
+        let _ = verified.insert(trie_key);
+    }
+    Ok(())
+}
+
+fn deserialize_trie(trie_key: Digest, trie_raw: &TrieRaw) -> Result<Trie<Key, StoredValue>, Error> {
+    bytesrepr::deserialize_from_slice(trie_raw.inner())
+        .map_err(|error| Error::DeserializeTrie { trie_key, error })
+}
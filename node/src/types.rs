@@ -3,6 +3,7 @@
 pub(crate) mod appendable_block;
 mod available_block_range;
 mod block;
+mod block_write_stats;
 pub mod chainspec;
 mod chunkable;
 mod deploy;
@@ -24,27 +25,31 @@ use rand_chacha::ChaCha20Rng;
 
 pub use available_block_range::AvailableBlockRange;
 pub(crate) use block::{
-    compute_approvals_checksum, ApprovalsHashes, BlockHashAndHeight, BlockHeaderWithMetadata,
-    BlockPayload, BlockWithMetadata, FinalitySignatureId, MetaBlock, MetaBlockMergeError,
-    MetaBlockState,
+    compute_approvals_checksum, ApprovalsHashes, ApprovalsHashesValidationError,
+    BlockHashAndHeight, BlockHeaderWithMetadata, BlockPayload, BlockWithMetadata,
+    FinalitySignatureId, MetaBlock, MetaBlockMergeError, MetaBlockState,
 };
 pub use block::{
-    json_compatibility::{JsonBlock, JsonBlockHeader},
+    json_compatibility::{JsonBlock, JsonBlockHeader, JsonProof},
     Block, BlockAndDeploys, BlockBody, BlockExecutionResultsOrChunk,
     BlockExecutionResultsOrChunkId, BlockExecutionResultsOrChunkIdDisplay, BlockHash, BlockHeader,
-    BlockSignatures, FinalitySignature, FinalizedBlock,
+    BlockSignatures, FinalitySignature, FinalizedBlock, KeyWrite,
 };
+pub use block_write_stats::{BlockWriteStats, KeySpaceWrites};
 pub use chainspec::Chainspec;
 pub(crate) use chainspec::{ActivationPoint, ChainspecRawBytes};
 pub use chunkable::Chunkable;
 pub use datasize::DataSize;
 pub use deploy::{
-    Approval, ApprovalsHash, Deploy, DeployConfigurationFailure, DeployError, DeployHash,
-    DeployHeader, DeployOrTransferHash, ExcessiveSizeError as ExcessiveSizeDeployError,
+    Approval, ApprovalsHash, Deploy, DeployBuilder, DeployBuilderError, DeployConfigurationFailure,
+    DeployError, DeployHash, DeployHeader, DeployOrTransferHash,
+    ExcessiveSizeError as ExcessiveSizeDeployError, TransferTarget,
 };
 pub(crate) use deploy::{
-    DeployFootprint, DeployHashWithApprovals, DeployId, DeployMetadata, DeployMetadataExt,
-    DeployWithFinalizedApprovals, FinalizedApprovals, LegacyDeploy,
+    DeployApprovals, DeployExecutionResult, DeployExecutionResultId,
+    DeployExecutionResultValidationMetadata, DeployFootprint, DeployHashWithApprovals, DeployId,
+    DeployMetadata, DeployMetadataExt, DeployWithFinalizedApprovals, FinalizedApprovals,
+    LegacyDeploy, MAX_USER_TAG_LENGTH,
 };
 pub use error::BlockValidationError;
 pub use exit_code::ExitCode;
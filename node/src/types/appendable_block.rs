@@ -1,8 +1,9 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt::{self, Display, Formatter},
 };
 
+use casper_execution_engine::core::engine_state::PaymentLanes;
 use casper_types::{Gas, PublicKey, Timestamp};
 use datasize::DataSize;
 use num_traits::Zero;
@@ -29,12 +30,28 @@ pub(crate) enum AddError {
     Duplicate,
     #[error("deploy is not valid in this context")]
     InvalidDeploy,
+    #[error("deploy is not admitted by any payment lane")]
+    NoPaymentLane,
+    #[error("would exceed maximum deploy count of payment lane {0} per block")]
+    LaneCount(String),
+    #[error("would exceed the computation quota of the deploy's account")]
+    ComputationQuota,
 }
 
 /// A block that is still being added to. It keeps track of and enforces block limits.
 #[derive(Clone, DataSize, Debug)]
 pub(crate) struct AppendableBlock {
     deploy_config: DeployConfig,
+    payment_lanes: PaymentLanes,
+    /// The number of deploys added from each payment lane, by lane index.
+    lane_counts: Vec<u32>,
+    /// The gas each account may use per era, if execution is gas-free.
+    #[data_size(skip)]
+    computation_quota: Option<Gas>,
+    /// The gas each account has already used of its quota in the current era, plus the total gas
+    /// limit of the deploys added from it, if execution is gas-free.
+    #[data_size(skip)]
+    account_gas: HashMap<PublicKey, Gas>,
     deploys: Vec<DeployHashWithApprovals>,
     transfers: Vec<DeployHashWithApprovals>,
     deploy_and_transfer_set: HashSet<DeployHash>,
@@ -47,9 +64,21 @@ pub(crate) struct AppendableBlock {
 
 impl AppendableBlock {
     /// Creates an empty `AppendableBlock`.
-    pub(crate) fn new(deploy_config: DeployConfig, timestamp: Timestamp) -> Self {
+    pub(crate) fn new(
+        deploy_config: DeployConfig,
+        payment_lanes: PaymentLanes,
+        computation_quota_per_era: u64,
+        timestamp: Timestamp,
+    ) -> Self {
+        let lane_counts = vec![0; payment_lanes.lanes().len()];
+        let computation_quota =
+            (computation_quota_per_era > 0).then(|| Gas::from(computation_quota_per_era));
         AppendableBlock {
             deploy_config,
+            payment_lanes,
+            lane_counts,
+            computation_quota,
+            account_gas: HashMap::new(),
             deploys: Vec::new(),
             transfers: Vec::new(),
             timestamp,
@@ -60,6 +89,17 @@ impl AppendableBlock {
         }
     }
 
+    /// Sets the gas each account has already used of its computation quota in the current era.
+    ///
+    /// Deploys are then only added as long as they fit into what is left of their account's
+    /// quota. Has no effect if execution isn't limited by computation quotas.
+    pub(crate) fn with_quota_usage(mut self, quota_usage: HashMap<PublicKey, Gas>) -> Self {
+        if self.computation_quota.is_some() {
+            self.account_gas = quota_usage;
+        }
+        self
+    }
+
     /// Attempts to add any kind of deploy (transfer or other kind).
     pub(crate) fn add(
         &mut self,
@@ -98,9 +138,13 @@ impl AppendableBlock {
         if self.has_max_transfer_count() {
             return Err(AddError::TransferCount);
         }
+        let maybe_lane = self.payment_lane(footprint)?;
         if self.would_exceed_approval_limits(transfer.approvals().len()) {
             return Err(AddError::ApprovalCount);
         }
+        if let Some(lane_index) = maybe_lane {
+            self.lane_counts[lane_index] += 1;
+        }
         self.deploy_and_transfer_set.insert(*transfer.deploy_hash());
         self.total_approvals += transfer.approvals().len();
         self.transfers.push(transfer);
@@ -129,6 +173,7 @@ impl AppendableBlock {
         if self.has_max_deploy_count() {
             return Err(AddError::DeployCount);
         }
+        let maybe_lane = self.payment_lane(footprint)?;
         if self.would_exceed_approval_limits(deploy.approvals().len()) {
             return Err(AddError::ApprovalCount);
         }
@@ -146,6 +191,14 @@ impl AppendableBlock {
         if new_total_gas > Gas::from(self.deploy_config.block_gas_limit) {
             return Err(AddError::GasLimit);
         }
+        let maybe_account_gas = self.account_gas(footprint)?;
+        if let Some(lane_index) = maybe_lane {
+            self.lane_counts[lane_index] += 1;
+        }
+        if let Some(account_gas) = maybe_account_gas {
+            self.account_gas
+                .insert(footprint.header.account().clone(), account_gas);
+        }
         self.total_gas = new_total_gas;
         self.total_size = new_total_size;
         self.total_approvals += deploy.approvals().len();
@@ -167,6 +220,45 @@ impl AppendableBlock {
         BlockPayload::new(deploys, transfers, accusations, random_bit)
     }
 
+    /// Returns the index of the payment lane the deploy belongs in, or `None` if payment lanes are
+    /// disabled.
+    ///
+    /// Returns an error if no lane admits the deploy, or if its lane is already full.
+    fn payment_lane(&self, footprint: &DeployFootprint) -> Result<Option<usize>, AddError> {
+        if !self.payment_lanes.is_enabled() {
+            return Ok(None);
+        }
+        let (lane_index, lane) = self
+            .payment_lanes
+            .lane_for(footprint.is_transfer, footprint.session_size)
+            .ok_or(AddError::NoPaymentLane)?;
+        if self.lane_counts[lane_index] >= lane.max_per_block {
+            return Err(AddError::LaneCount(lane.name.clone()));
+        }
+        Ok(Some(lane_index))
+    }
+
+    /// Returns the gas the deploy's account would have used of its quota if the deploy were
+    /// added, or `None` if execution isn't limited by computation quotas.
+    ///
+    /// Returns an error if the deploy's gas limit exceeds what is left of the account's quota.
+    /// Unless seeded by [`with_quota_usage`](Self::with_quota_usage) this only bounds the deploys
+    /// in a single block; the engine rejects those exceeding what is left of the quota in the era.
+    fn account_gas(&self, footprint: &DeployFootprint) -> Result<Option<Gas>, AddError> {
+        let quota = match self.computation_quota {
+            Some(quota) => quota,
+            None => return Ok(None),
+        };
+        self.account_gas
+            .get(footprint.header.account())
+            .copied()
+            .unwrap_or_default()
+            .checked_add(footprint.gas_estimate)
+            .filter(|account_gas| *account_gas <= quota)
+            .map(Some)
+            .ok_or(AddError::ComputationQuota)
+    }
+
     /// Returns `true` if the number of transfers is already the maximum allowed count, i.e. no
     /// more transfers can be added to this block.
     fn has_max_transfer_count(&self) -> bool {
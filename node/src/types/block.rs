@@ -50,7 +50,7 @@ use crate::{
     },
     utils::{ds, DisplayIter},
 };
-pub(crate) use approvals_hashes::ApprovalsHashes;
+pub(crate) use approvals_hashes::{ApprovalsHashes, ApprovalsHashesValidationError};
 pub(crate) use meta_block::{
     MergeMismatchError as MetaBlockMergeError, MetaBlock, State as MetaBlockState,
 };
@@ -735,6 +735,28 @@ impl Display for BlockHashAndHeight {
     }
 }
 
+/// A block which wrote to a key in global state.
+#[derive(Clone, Copy, Eq, JsonSchema, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct KeyWrite {
+    /// The hash of the block.
+    pub block_hash: BlockHash,
+    /// The height of the block.
+    pub block_height: u64,
+    /// Hex-encoded hash of the state root after executing the block.
+    pub state_root_hash: Digest,
+}
+
+impl From<&Block> for KeyWrite {
+    fn from(block: &Block) -> Self {
+        KeyWrite {
+            block_hash: *block.hash(),
+            block_height: block.height(),
+            state_root_hash: *block.state_root_hash(),
+        }
+    }
+}
+
 #[derive(Clone, DataSize, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
 /// A struct to contain information related to the end of an era and validator weights for the
 /// following era.
@@ -1636,6 +1658,8 @@ impl GossipItem for Block {
 
     const ID_IS_COMPLETE_ITEM: bool = false;
     const REQUIRES_GOSSIP_RECEIVED_ANNOUNCEMENT: bool = true;
+    const REQUIRES_VERIFICATION_BEFORE_GOSSIP: bool = false;
+    const GOSSIPER_NAME: &'static str = "block_gossiper";
 
     fn gossip_id(&self) -> Self::Id {
         *self.hash()
@@ -2234,6 +2258,11 @@ impl FinalitySignature {
             .clone()
     }
 
+    /// Returns `true` if the outcome of verifying the signature is already known.
+    pub(crate) fn is_verification_cached(&self) -> bool {
+        self.is_verified.get().is_some()
+    }
+
     /// Returns a random `FinalitySignature` for the provided `block_hash` and `era_id`.
     #[cfg(any(feature = "testing", test))]
     pub fn random_for_block(block_hash: BlockHash, era_id: u64) -> Self {
@@ -2344,6 +2373,8 @@ impl GossipItem for FinalitySignature {
 
     const ID_IS_COMPLETE_ITEM: bool = false;
     const REQUIRES_GOSSIP_RECEIVED_ANNOUNCEMENT: bool = true;
+    const REQUIRES_VERIFICATION_BEFORE_GOSSIP: bool = false;
+    const GOSSIPER_NAME: &'static str = "finality_signature_gossiper";
 
     fn gossip_id(&self) -> Self::Id {
         FinalitySignatureId {
@@ -8,14 +8,17 @@ use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use casper_execution_engine::storage::trie::merkle_proof::TrieMerkleProof;
+use casper_execution_engine::{
+    core::engine_state::EXECUTION_RESULTS_MERKLE_ROOT_NAME,
+    storage::trie::merkle_proof::TrieMerkleProof,
+};
 use casper_hashing::Digest;
 use casper_types::{bytesrepr, Key, StoredValue};
 
 use super::{Block, BlockHash};
 use crate::{
     components::{
-        contract_runtime::APPROVALS_CHECKSUM_NAME,
+        contract_runtime::{APPROVALS_CHECKSUM_NAME, EXECUTION_RESULTS_CHECKSUM_NAME},
         fetcher::{FetchItem, Tag},
     },
     types::{self, ApprovalsHash, DeployId},
@@ -70,13 +73,7 @@ impl ApprovalsHashes {
         }
 
         let value_in_proof = self
-            .merkle_proof_approvals
-            .value()
-            .as_cl_value()
-            .and_then(|cl_value| cl_value.clone().into_t().ok())
-            .and_then(|registry: BTreeMap<String, Digest>| {
-                registry.get(APPROVALS_CHECKSUM_NAME).copied()
-            })
+            .checksum(APPROVALS_CHECKSUM_NAME)
             .ok_or(ApprovalsHashesValidationError::InvalidChecksumRegistry)?;
 
         let computed_approvals_checksum =
@@ -103,6 +100,33 @@ impl ApprovalsHashes {
             .map(|(deploy_hash, approvals_hash)| DeployId::new(*deploy_hash, *approvals_hash))
     }
 
+    /// Returns the checksum of the block's execution results recorded in the proven checksum
+    /// registry, if any.
+    ///
+    /// The checksum can only be relied on once `self` has been validated against the block.
+    pub(crate) fn execution_results_checksum(&self) -> Option<Digest> {
+        self.checksum(EXECUTION_RESULTS_CHECKSUM_NAME)
+    }
+
+    /// Returns the Merkle root of the block's execution results recorded in the proven checksum
+    /// registry, if the block was executed once execution results Merkle trees were enabled.
+    ///
+    /// Unlike the checksum, the root allows proving the execution result of a single deploy.  As
+    /// with the checksum, it can only be relied on once `self` has been validated against the
+    /// block.
+    pub(crate) fn execution_results_merkle_root(&self) -> Option<Digest> {
+        self.checksum(EXECUTION_RESULTS_MERKLE_ROOT_NAME)
+    }
+
+    /// Returns the checksum named `name` in the proven checksum registry, if any.
+    fn checksum(&self, name: &str) -> Option<Digest> {
+        self.merkle_proof_approvals
+            .value()
+            .as_cl_value()
+            .and_then(|cl_value| cl_value.clone().into_t().ok())
+            .and_then(|registry: BTreeMap<String, Digest>| registry.get(name).copied())
+    }
+
     pub(crate) fn approvals_hashes(&self) -> &[ApprovalsHash] {
         self.approvals_hashes.as_ref()
     }
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use datasize::DataSize;
+#[cfg(test)]
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use casper_types::testing::TestRng;
+use casper_types::KeyTag;
+
+use super::BlockHash;
+
+/// The number and total serialized size of the values written under one kind of key.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, DataSize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct KeySpaceWrites {
+    /// The number of values written.
+    pub values: u64,
+    /// The total serialized size of the values written, in bytes.
+    pub bytes: u64,
+}
+
+/// The values a block wrote to global state, by kind of key.
+///
+/// A key written several times by the block is counted once, with the size of the value it holds
+/// after the block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DataSize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BlockWriteStats {
+    /// The hash of the block.
+    pub block_hash: BlockHash,
+    /// The height of the block.
+    pub height: u64,
+    /// The values written, by kind of key, e.g. `Dictionary` or `Account`.
+    pub key_spaces: BTreeMap<String, KeySpaceWrites>,
+}
+
+impl BlockWriteStats {
+    /// Aggregates the kind of key and serialized size of every value written by a block.
+    pub(crate) fn new(
+        block_hash: BlockHash,
+        height: u64,
+        written_value_sizes: impl IntoIterator<Item = (KeyTag, usize)>,
+    ) -> Self {
+        let mut key_spaces: BTreeMap<String, KeySpaceWrites> = BTreeMap::new();
+        for (key_tag, size) in written_value_sizes {
+            let writes = key_spaces.entry(format!("{:?}", key_tag)).or_default();
+            writes.values += 1;
+            writes.bytes += size as u64;
+        }
+        BlockWriteStats {
+            block_hash,
+            height,
+            key_spaces,
+        }
+    }
+
+    /// Returns random stats.
+    #[cfg(test)]
+    pub(crate) fn random(rng: &mut TestRng) -> Self {
+        let written_value_sizes = (0..rng.gen_range(1..20))
+            .map(|_| {
+                let key_tag = if rng.gen() {
+                    KeyTag::Dictionary
+                } else {
+                    KeyTag::Account
+                };
+                (key_tag, rng.gen_range(1..1_000))
+            })
+            .collect::<Vec<_>>();
+        BlockWriteStats::new(BlockHash::random(rng), rng.gen(), written_value_sizes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_aggregate_by_kind_of_key() {
+        let mut rng = TestRng::new();
+        let block_hash = BlockHash::random(&mut rng);
+        let stats = BlockWriteStats::new(
+            block_hash,
+            7,
+            vec![
+                (KeyTag::Dictionary, 100),
+                (KeyTag::Account, 40),
+                (KeyTag::Dictionary, 25),
+            ],
+        );
+        assert_eq!(stats.block_hash, block_hash);
+        assert_eq!(stats.height, 7);
+        assert_eq!(
+            stats.key_spaces.into_iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    "Account".to_string(),
+                    KeySpaceWrites {
+                        values: 1,
+                        bytes: 40
+                    }
+                ),
+                (
+                    "Dictionary".to_string(),
+                    KeySpaceWrites {
+                        values: 2,
+                        bytes: 125
+                    }
+                ),
+            ]
+        );
+    }
+}
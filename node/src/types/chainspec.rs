@@ -3,6 +3,7 @@
 
 mod accounts_config;
 mod activation_point;
+mod chainspec_introspection;
 mod chainspec_raw_bytes;
 mod core_config;
 mod deploy_config;
@@ -39,6 +40,7 @@ pub use self::error::Error;
 pub(crate) use self::{
     accounts_config::AccountsConfig,
     activation_point::ActivationPoint,
+    chainspec_introspection::GetChainspecIntrospectionResult,
     chainspec_raw_bytes::ChainspecRawBytes,
     core_config::{ConsensusProtocolName, CoreConfig},
     deploy_config::DeployConfig,
@@ -172,6 +174,7 @@ impl Chainspec {
             Some(self.core_config.unbonding_delay),
             global_state_update,
             chainspec_registry,
+            self.protocol_config.migrate_legacy_withdraws,
         ))
     }
 }
@@ -337,11 +340,17 @@ mod tests {
             print: HostFunction::new(123, [0, 1]),
             blake2b: HostFunction::new(133, [0, 1, 2, 3]),
             random_bytes: HostFunction::new(123, [0, 1]),
+            read_host_buffer_chunk: HostFunction::new(134, [0, 1, 2, 3]),
+            add_allowed_caller: HostFunction::new(135, [0, 1, 2, 3, 4, 5]),
+            remove_allowed_caller: HostFunction::new(136, [0, 1, 2, 3, 4, 5]),
+            transfer_batch: HostFunction::new(139, [0, 1, 2, 3]),
+            set_contract_package_paused: HostFunction::new(140, [0, 1, 2]),
         });
     static EXPECTED_GENESIS_WASM_COSTS: Lazy<WasmConfig> = Lazy::new(|| {
         WasmConfig::new(
             17, // initial_memory
             19, // max_stack_height
+            23, // max_call_result_size
             EXPECTED_GENESIS_COSTS,
             EXPECTED_GENESIS_STORAGE_COSTS,
             *EXPECTED_GENESIS_HOST_FUNCTION_COSTS,
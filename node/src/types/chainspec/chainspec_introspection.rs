@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use casper_types::ProtocolVersion;
+
+use super::Chainspec;
+
+/// A parsed chainspec along with values derived from it, so that tooling doesn't need to parse
+/// the chainspec TOML itself.
+///
+/// The Wasm opcode, storage and host function cost tables are under `chainspec.wasm`, and the
+/// costs of calls into the system contracts under `chainspec.system_costs`.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct ChainspecIntrospection {
+    /// The chainspec.
+    chainspec: Arc<Chainspec>,
+    /// The least number of blocks an era can contain.
+    era_duration_in_blocks: u64,
+    /// The number of eras that have already started and whose validators are still bonded.
+    recent_era_count: u64,
+}
+
+impl ChainspecIntrospection {
+    pub(crate) fn new(chainspec: Arc<Chainspec>) -> Self {
+        ChainspecIntrospection {
+            era_duration_in_blocks: chainspec.core_config.era_duration_in_blocks(),
+            recent_era_count: chainspec.core_config.recent_era_count(),
+            chainspec,
+        }
+    }
+}
+
+/// Result of the chainspec introspection REST request.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct GetChainspecIntrospectionResult {
+    /// The RPC API version.
+    api_version: ProtocolVersion,
+    /// The chainspec the node is running.
+    active: ChainspecIntrospection,
+    /// The chainspec of the next upgrade, if one is staged.
+    staged_upgrade: Option<ChainspecIntrospection>,
+}
+
+impl GetChainspecIntrospectionResult {
+    pub(crate) fn new(
+        api_version: ProtocolVersion,
+        active: Arc<Chainspec>,
+        staged_upgrade: Option<Arc<Chainspec>>,
+    ) -> Self {
+        GetChainspecIntrospectionResult {
+            api_version,
+            active: ChainspecIntrospection::new(active),
+            staged_upgrade: staged_upgrade.map(ChainspecIntrospection::new),
+        }
+    }
+}
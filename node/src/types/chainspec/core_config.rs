@@ -10,15 +10,24 @@ use serde::{
     Deserialize, Serialize, Serializer,
 };
 
+#[cfg(test)]
+use casper_execution_engine::core::engine_state::{
+    DeprecatedHostFunction, DeprecationAction, LaneKind, PaymentLane,
+};
+use casper_execution_engine::core::engine_state::{
+    HostFunctionDeprecations, PaymentLanes, SessionCodePolicy,
+};
+#[cfg(test)]
+use casper_hashing::Digest;
 #[cfg(test)]
 use casper_types::testing::TestRng;
 use casper_types::{
     bytesrepr::{self, FromBytes, ToBytes},
-    TimeDiff,
+    ProtocolVersion, TimeDiff,
 };
 use tracing::{error, warn};
 
-#[derive(Copy, Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[derive(Clone, DataSize, PartialEq, Eq, Serialize, Deserialize, Debug)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
 #[serde(deny_unknown_fields)]
 pub struct CoreConfig {
@@ -53,6 +62,43 @@ pub struct CoreConfig {
     pub(crate) simultaneous_peer_requests: u32,
     /// Which consensus protocol to use.
     pub(crate) consensus_protocol: ConsensusProtocolName,
+    /// How deploys are protected against being replayed.
+    pub(crate) replay_protection: ReplayProtectionMode,
+    /// The protocol version from which blocks commit to a Merkle tree of their execution results,
+    /// allowing the execution result of a single deploy to be proven.
+    pub(crate) execution_results_merkle_tree_version: ProtocolVersion,
+    /// The protocol version from which deploys may pay a priority fee to the block proposer.
+    pub(crate) priority_fees_version: ProtocolVersion,
+    /// The protocol version from which deploys may carry a user tag.
+    pub(crate) user_tags_version: ProtocolVersion,
+    /// Which session and payment Wasm deploys may carry.
+    pub(crate) session_code_policy: SessionCodePolicy,
+    /// The lanes charging deploys fixed prices instead of the gas they use, if any.
+    pub(crate) payment_lanes: PaymentLanes,
+    /// The protocol version from which deploys are charged the fixed prices of payment lanes.
+    pub(crate) payment_lanes_version: ProtocolVersion,
+    /// The gas each account may use per era if execution is gas-free, or `0` if deploys pay for
+    /// their gas.
+    pub(crate) computation_quota_per_era: u64,
+    /// Whether deploys are refused writes to system keys such as the mint's total supply.
+    pub(crate) protect_system_keys: bool,
+    /// The storage rent contracts are charged per stored byte per era, or `0` if they pay no rent.
+    pub(crate) storage_rent_per_byte: u64,
+    /// The number of eras a contract may leave its storage rent unpaid before being marked as
+    /// evicted.
+    pub(crate) storage_rent_grace_eras: u64,
+    /// The protocol version from which redelegations move at the end of the era instead of waiting
+    /// out the unbonding delay.
+    pub(crate) direct_redelegation_version: ProtocolVersion,
+    /// The number of eras a delegator has to wait between direct redelegations.
+    pub(crate) redelegation_cooldown_eras: u64,
+    /// The host functions deprecated from given protocol versions onwards.
+    pub(crate) deprecated_host_functions: HostFunctionDeprecations,
+    /// The protocol version from which contract package owners may pause calls into their
+    /// packages.
+    pub(crate) contract_package_pause_version: ProtocolVersion,
+    /// The protocol version from which only inactive bids with a stake left may be activated.
+    pub(crate) strict_bid_activation_version: ProtocolVersion,
 }
 
 impl CoreConfig {
@@ -62,6 +108,30 @@ impl CoreConfig {
         self.unbonding_delay - self.auction_delay
     }
 
+    /// The least number of blocks an era can contain.
+    ///
+    /// An era ends with the first block at which it has lasted for `era_duration` and contains at
+    /// least `minimum_era_height` blocks, and blocks are at least `minimum_block_time` apart.
+    pub(crate) fn era_duration_in_blocks(&self) -> u64 {
+        let minimum_block_time = self.minimum_block_time.millis();
+        let blocks_in_era_duration = if minimum_block_time == 0 {
+            0
+        } else {
+            (self.era_duration.millis() + minimum_block_time - 1) / minimum_block_time
+        };
+        self.minimum_era_height.max(blocks_in_era_duration)
+    }
+
+    /// Returns the payment lanes deploys are charged under at `protocol_version`, which are none
+    /// before `payment_lanes_version`.
+    pub(crate) fn active_payment_lanes(&self, protocol_version: ProtocolVersion) -> PaymentLanes {
+        if protocol_version >= self.payment_lanes_version {
+            self.payment_lanes.clone()
+        } else {
+            PaymentLanes::default()
+        }
+    }
+
     /// Returns `false` if unbonding delay is not greater than auction delay to ensure
     /// that `recent_era_count()` yields a value of at least 1.
     pub(super) fn is_valid(&self) -> bool {
@@ -92,6 +162,15 @@ impl CoreConfig {
             );
             return false;
         }
+
+        let unknown_host_functions = self.deprecated_host_functions.unknown_names();
+        if !unknown_host_functions.is_empty() {
+            error!(
+                ?unknown_host_functions,
+                "deprecated host functions contain names which aren't host functions",
+            );
+            return false;
+        }
         true
     }
 }
@@ -119,6 +198,69 @@ impl CoreConfig {
         let strict_argument_checking = rng.gen();
         let simultaneous_peer_requests = rng.gen_range(3..100);
         let consensus_protocol = rng.gen();
+        let replay_protection = rng.gen();
+        let execution_results_merkle_tree_version =
+            ProtocolVersion::from_parts(rng.gen_range(0..10), rng.gen(), rng.gen());
+        let priority_fees_version =
+            ProtocolVersion::from_parts(rng.gen_range(0..10), rng.gen(), rng.gen());
+        let user_tags_version =
+            ProtocolVersion::from_parts(rng.gen_range(0..10), rng.gen(), rng.gen());
+        let session_code_policy = match rng.gen_range(0..3) {
+            0 => SessionCodePolicy::Unrestricted,
+            1 => SessionCodePolicy::AllowList(
+                (0..rng.gen_range(0..5))
+                    .map(|_| Digest::hash(rng.gen::<[u8; 32]>()))
+                    .collect(),
+            ),
+            _ => SessionCodePolicy::StoredContractsOnly,
+        };
+        let payment_lanes = PaymentLanes::new(
+            (0..rng.gen_range(0..3))
+                .map(|index| PaymentLane {
+                    name: format!("lane-{}", index),
+                    kind: if rng.gen() {
+                        LaneKind::NativeTransfer
+                    } else {
+                        LaneKind::Session
+                    },
+                    max_session_size: rng.gen(),
+                    fixed_cost: rng.gen(),
+                    max_per_block: rng.gen(),
+                })
+                .collect(),
+        );
+        let payment_lanes_version =
+            ProtocolVersion::from_parts(rng.gen_range(0..10), rng.gen(), rng.gen());
+        let computation_quota_per_era = if rng.gen() { 0 } else { rng.gen() };
+        let protect_system_keys = rng.gen();
+        let storage_rent_per_byte = if rng.gen() {
+            0
+        } else {
+            rng.gen_range(1..1_000)
+        };
+        let storage_rent_grace_eras = rng.gen_range(0..10);
+        let direct_redelegation_version =
+            ProtocolVersion::from_parts(rng.gen_range(0..10), rng.gen(), rng.gen());
+        let redelegation_cooldown_eras = rng.gen_range(0..10);
+        let deprecated_host_functions = HostFunctionDeprecations::new(
+            ["casper_load_named_keys", "casper_get_blocktime"]
+                .iter()
+                .filter(|_| rng.gen())
+                .map(|name| DeprecatedHostFunction {
+                    name: name.to_string(),
+                    since: ProtocolVersion::from_parts(rng.gen_range(0..10), rng.gen(), rng.gen()),
+                    action: if rng.gen() {
+                        DeprecationAction::Warn
+                    } else {
+                        DeprecationAction::Reject
+                    },
+                })
+                .collect(),
+        );
+        let contract_package_pause_version =
+            ProtocolVersion::from_parts(rng.gen_range(0..10), rng.gen(), rng.gen());
+        let strict_bid_activation_version =
+            ProtocolVersion::from_parts(rng.gen_range(0..10), rng.gen(), rng.gen());
 
         CoreConfig {
             era_duration,
@@ -137,6 +279,22 @@ impl CoreConfig {
             strict_argument_checking,
             simultaneous_peer_requests,
             consensus_protocol,
+            replay_protection,
+            execution_results_merkle_tree_version,
+            priority_fees_version,
+            user_tags_version,
+            session_code_policy,
+            payment_lanes,
+            payment_lanes_version,
+            computation_quota_per_era,
+            protect_system_keys,
+            storage_rent_per_byte,
+            storage_rent_grace_eras,
+            direct_redelegation_version,
+            redelegation_cooldown_eras,
+            deprecated_host_functions,
+            contract_package_pause_version,
+            strict_bid_activation_version,
         }
     }
 }
@@ -160,6 +318,22 @@ impl ToBytes for CoreConfig {
         buffer.extend(self.strict_argument_checking.to_bytes()?);
         buffer.extend(self.simultaneous_peer_requests.to_bytes()?);
         buffer.extend(self.consensus_protocol.to_bytes()?);
+        buffer.extend(self.replay_protection.to_bytes()?);
+        buffer.extend(self.execution_results_merkle_tree_version.to_bytes()?);
+        buffer.extend(self.priority_fees_version.to_bytes()?);
+        buffer.extend(self.user_tags_version.to_bytes()?);
+        buffer.extend(self.session_code_policy.to_bytes()?);
+        buffer.extend(self.payment_lanes.to_bytes()?);
+        buffer.extend(self.payment_lanes_version.to_bytes()?);
+        buffer.extend(self.computation_quota_per_era.to_bytes()?);
+        buffer.extend(self.protect_system_keys.to_bytes()?);
+        buffer.extend(self.storage_rent_per_byte.to_bytes()?);
+        buffer.extend(self.storage_rent_grace_eras.to_bytes()?);
+        buffer.extend(self.direct_redelegation_version.to_bytes()?);
+        buffer.extend(self.redelegation_cooldown_eras.to_bytes()?);
+        buffer.extend(self.deprecated_host_functions.to_bytes()?);
+        buffer.extend(self.contract_package_pause_version.to_bytes()?);
+        buffer.extend(self.strict_bid_activation_version.to_bytes()?);
         Ok(buffer)
     }
 
@@ -180,6 +354,24 @@ impl ToBytes for CoreConfig {
             + self.strict_argument_checking.serialized_length()
             + self.simultaneous_peer_requests.serialized_length()
             + self.consensus_protocol.serialized_length()
+            + self.replay_protection.serialized_length()
+            + self
+                .execution_results_merkle_tree_version
+                .serialized_length()
+            + self.priority_fees_version.serialized_length()
+            + self.user_tags_version.serialized_length()
+            + self.session_code_policy.serialized_length()
+            + self.payment_lanes.serialized_length()
+            + self.payment_lanes_version.serialized_length()
+            + self.computation_quota_per_era.serialized_length()
+            + self.protect_system_keys.serialized_length()
+            + self.storage_rent_per_byte.serialized_length()
+            + self.storage_rent_grace_eras.serialized_length()
+            + self.direct_redelegation_version.serialized_length()
+            + self.redelegation_cooldown_eras.serialized_length()
+            + self.deprecated_host_functions.serialized_length()
+            + self.contract_package_pause_version.serialized_length()
+            + self.strict_bid_activation_version.serialized_length()
     }
 }
 
@@ -201,6 +393,24 @@ impl FromBytes for CoreConfig {
         let (strict_argument_checking, remainder) = bool::from_bytes(remainder)?;
         let (simultaneous_peer_requests, remainder) = u32::from_bytes(remainder)?;
         let (consensus_protocol, remainder) = ConsensusProtocolName::from_bytes(remainder)?;
+        let (replay_protection, remainder) = ReplayProtectionMode::from_bytes(remainder)?;
+        let (execution_results_merkle_tree_version, remainder) =
+            ProtocolVersion::from_bytes(remainder)?;
+        let (priority_fees_version, remainder) = ProtocolVersion::from_bytes(remainder)?;
+        let (user_tags_version, remainder) = ProtocolVersion::from_bytes(remainder)?;
+        let (session_code_policy, remainder) = SessionCodePolicy::from_bytes(remainder)?;
+        let (payment_lanes, remainder) = PaymentLanes::from_bytes(remainder)?;
+        let (payment_lanes_version, remainder) = ProtocolVersion::from_bytes(remainder)?;
+        let (computation_quota_per_era, remainder) = u64::from_bytes(remainder)?;
+        let (protect_system_keys, remainder) = bool::from_bytes(remainder)?;
+        let (storage_rent_per_byte, remainder) = u64::from_bytes(remainder)?;
+        let (storage_rent_grace_eras, remainder) = u64::from_bytes(remainder)?;
+        let (direct_redelegation_version, remainder) = ProtocolVersion::from_bytes(remainder)?;
+        let (redelegation_cooldown_eras, remainder) = u64::from_bytes(remainder)?;
+        let (deprecated_host_functions, remainder) =
+            HostFunctionDeprecations::from_bytes(remainder)?;
+        let (contract_package_pause_version, remainder) = ProtocolVersion::from_bytes(remainder)?;
+        let (strict_bid_activation_version, remainder) = ProtocolVersion::from_bytes(remainder)?;
         let config = CoreConfig {
             era_duration,
             minimum_era_height,
@@ -218,6 +428,22 @@ impl FromBytes for CoreConfig {
             strict_argument_checking,
             simultaneous_peer_requests,
             consensus_protocol,
+            replay_protection,
+            execution_results_merkle_tree_version,
+            priority_fees_version,
+            user_tags_version,
+            session_code_policy,
+            payment_lanes,
+            payment_lanes_version,
+            computation_quota_per_era,
+            protect_system_keys,
+            storage_rent_per_byte,
+            storage_rent_grace_eras,
+            direct_redelegation_version,
+            redelegation_cooldown_eras,
+            deprecated_host_functions,
+            contract_package_pause_version,
+            strict_bid_activation_version,
         };
         Ok((config, remainder))
     }
@@ -292,6 +518,86 @@ impl Distribution<ConsensusProtocolName> for Standard {
     }
 }
 
+/// The mechanism used to prevent deploys from being executed more than once.
+#[derive(Copy, Clone, DataSize, PartialEq, Eq, Debug)]
+pub(crate) enum ReplayProtectionMode {
+    /// Deploys are unique by hash and are only valid until their TTL expires.
+    TtlAndHash,
+    /// In addition to the TTL and hash checks, every deploy must carry the next sequence number
+    /// of its account, so deploys from a single account are executed in strict order.
+    AccountSequence,
+}
+
+impl ReplayProtectionMode {
+    /// Returns `true` if per-account sequence numbers are enforced.
+    pub(crate) fn account_sequence_enforced(&self) -> bool {
+        matches!(self, ReplayProtectionMode::AccountSequence)
+    }
+}
+
+impl Serialize for ReplayProtectionMode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            ReplayProtectionMode::TtlAndHash => "TtlAndHash",
+            ReplayProtectionMode::AccountSequence => "AccountSequence",
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ReplayProtectionMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match String::deserialize(deserializer)?.to_lowercase().as_str() {
+            "ttlandhash" => Ok(ReplayProtectionMode::TtlAndHash),
+            "accountsequence" => Ok(ReplayProtectionMode::AccountSequence),
+            _ => Err(DeError::custom("unknown replay protection mode")),
+        }
+    }
+}
+
+const REPLAY_PROTECTION_TTL_AND_HASH_TAG: u8 = 0;
+const REPLAY_PROTECTION_ACCOUNT_SEQUENCE_TAG: u8 = 1;
+
+impl ToBytes for ReplayProtectionMode {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let tag = match self {
+            ReplayProtectionMode::TtlAndHash => REPLAY_PROTECTION_TTL_AND_HASH_TAG,
+            ReplayProtectionMode::AccountSequence => REPLAY_PROTECTION_ACCOUNT_SEQUENCE_TAG,
+        };
+        Ok(vec![tag])
+    }
+
+    fn serialized_length(&self) -> usize {
+        1
+    }
+}
+
+impl FromBytes for ReplayProtectionMode {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, remainder) = u8::from_bytes(bytes)?;
+        let mode = match tag {
+            REPLAY_PROTECTION_TTL_AND_HASH_TAG => ReplayProtectionMode::TtlAndHash,
+            REPLAY_PROTECTION_ACCOUNT_SEQUENCE_TAG => ReplayProtectionMode::AccountSequence,
+            _ => return Err(bytesrepr::Error::Formatting),
+        };
+        Ok((mode, remainder))
+    }
+}
+
+#[cfg(test)]
+impl Distribution<ReplayProtectionMode> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ReplayProtectionMode {
+        if rng.gen() {
+            ReplayProtectionMode::TtlAndHash
+        } else {
+            ReplayProtectionMode::AccountSequence
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -312,6 +618,21 @@ mod tests {
         assert_eq!(config, decoded);
     }
 
+    #[test]
+    fn should_compute_era_duration_in_blocks() {
+        let mut rng = crate::new_rng();
+        let mut config = CoreConfig::random(&mut rng);
+        config.era_duration = TimeDiff::from_seconds(100);
+        config.minimum_block_time = TimeDiff::from_seconds(3);
+        config.minimum_era_height = 10;
+        assert_eq!(config.era_duration_in_blocks(), 34);
+        config.minimum_era_height = 50;
+        assert_eq!(config.era_duration_in_blocks(), 50);
+        config.era_duration = TimeDiff::from_seconds(0);
+        config.minimum_era_height = 10;
+        assert_eq!(config.era_duration_in_blocks(), 10);
+    }
+
     #[test]
     fn should_validate_for_finality_threshold() {
         let mut rng = crate::new_rng();
@@ -331,4 +652,36 @@ mod tests {
         config.finality_threshold_fraction = Ratio::new(u64::MAX, u64::MAX - 1);
         assert!(!config.is_valid());
     }
+
+    #[test]
+    fn should_activate_payment_lanes_from_their_protocol_version() {
+        let mut rng = crate::new_rng();
+        let mut config = CoreConfig::random(&mut rng);
+        config.payment_lanes = PaymentLanes::new(vec![PaymentLane {
+            name: "transfer".to_string(),
+            kind: LaneKind::NativeTransfer,
+            max_session_size: 0,
+            fixed_cost: 100,
+            max_per_block: 10,
+        }]);
+        config.payment_lanes_version = ProtocolVersion::from_parts(1, 5, 0);
+
+        let before = config.active_payment_lanes(ProtocolVersion::from_parts(1, 4, 9));
+        assert!(!before.is_enabled());
+        let from = config.active_payment_lanes(ProtocolVersion::from_parts(1, 5, 0));
+        assert_eq!(from, config.payment_lanes);
+        let after = config.active_payment_lanes(ProtocolVersion::from_parts(2, 0, 0));
+        assert_eq!(after, config.payment_lanes);
+    }
+
+    #[test]
+    fn should_parse_replay_protection_case_insensitively() {
+        let mode: ReplayProtectionMode = toml::Value::from("accountSEQUENCE").try_into().unwrap();
+        assert_eq!(mode, ReplayProtectionMode::AccountSequence);
+        let mode: ReplayProtectionMode = toml::Value::from("TtlAndHash").try_into().unwrap();
+        assert_eq!(mode, ReplayProtectionMode::TtlAndHash);
+        assert!(toml::Value::from("nonce")
+            .try_into::<ReplayProtectionMode>()
+            .is_err());
+    }
 }
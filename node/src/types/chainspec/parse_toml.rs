@@ -34,6 +34,8 @@ struct TomlProtocol {
     version: ProtocolVersion,
     hard_reset: bool,
     activation_point: ActivationPoint,
+    #[serde(default)]
+    migrate_legacy_withdraws: bool,
 }
 
 /// A chainspec configuration as laid out in the TOML-encoded configuration file.
@@ -56,12 +58,13 @@ impl From<&Chainspec> for TomlChainspec {
             version: chainspec.protocol_config.version,
             hard_reset: chainspec.protocol_config.hard_reset,
             activation_point: chainspec.protocol_config.activation_point,
+            migrate_legacy_withdraws: chainspec.protocol_config.migrate_legacy_withdraws,
         };
         let network = TomlNetwork {
             name: chainspec.network_config.name.clone(),
             maximum_net_message_size: chainspec.network_config.maximum_net_message_size,
         };
-        let core = chainspec.core_config;
+        let core = chainspec.core_config.clone();
         let deploys = chainspec.deploy_config;
         let highway = chainspec.highway_config;
         let wasm = chainspec.wasm_config;
@@ -112,6 +115,7 @@ pub(super) fn parse_toml<P: AsRef<Path>>(
         hard_reset: toml_chainspec.protocol.hard_reset,
         activation_point: toml_chainspec.protocol.activation_point,
         global_state_update,
+        migrate_legacy_withdraws: toml_chainspec.protocol.migrate_legacy_withdraws,
     };
 
     let chainspec = Chainspec {
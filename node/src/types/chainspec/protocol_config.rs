@@ -30,6 +30,9 @@ pub struct ProtocolConfig {
     /// Any arbitrary updates we might want to make to the global state at the start of the era
     /// specified in the activation point.
     pub(crate) global_state_update: Option<GlobalStateUpdate>,
+    /// Whether legacy withdraw purses should be migrated into unbonding purses during the upgrade.
+    #[serde(default)]
+    pub(crate) migrate_legacy_withdraws: bool,
 }
 
 impl ProtocolConfig {
@@ -78,6 +81,7 @@ impl ProtocolConfig {
             hard_reset: rng.gen(),
             activation_point,
             global_state_update: None,
+            migrate_legacy_withdraws: rng.gen(),
         }
     }
 }
@@ -89,6 +93,7 @@ impl ToBytes for ProtocolConfig {
         buffer.extend(self.hard_reset.to_bytes()?);
         buffer.extend(self.activation_point.to_bytes()?);
         buffer.extend(self.global_state_update.to_bytes()?);
+        buffer.extend(self.migrate_legacy_withdraws.to_bytes()?);
         Ok(buffer)
     }
 
@@ -97,6 +102,7 @@ impl ToBytes for ProtocolConfig {
             + self.hard_reset.serialized_length()
             + self.activation_point.serialized_length()
             + self.global_state_update.serialized_length()
+            + self.migrate_legacy_withdraws.serialized_length()
     }
 }
 
@@ -108,11 +114,13 @@ impl FromBytes for ProtocolConfig {
         let (hard_reset, remainder) = bool::from_bytes(remainder)?;
         let (activation_point, remainder) = ActivationPoint::from_bytes(remainder)?;
         let (global_state_update, remainder) = Option::<GlobalStateUpdate>::from_bytes(remainder)?;
+        let (migrate_legacy_withdraws, remainder) = bool::from_bytes(remainder)?;
         let protocol_config = ProtocolConfig {
             version,
             hard_reset,
             activation_point,
             global_state_update,
+            migrate_legacy_withdraws,
         };
         Ok((protocol_config, remainder))
     }
@@ -186,6 +194,7 @@ mod tests {
             hard_reset: false,
             activation_point: ActivationPoint::EraId(upgrade_era),
             global_state_update: None,
+            migrate_legacy_withdraws: false,
         };
 
         // The block before this protocol version: a switch block with previous era and version.
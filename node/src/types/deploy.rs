@@ -3,6 +3,9 @@
 
 mod approval;
 mod approvals_hash;
+mod builder;
+mod deploy_approvals;
+mod deploy_execution_result;
 mod deploy_hash;
 mod deploy_hash_with_approvals;
 mod deploy_header;
@@ -34,18 +37,19 @@ use tracing::{error, info, warn};
 #[cfg(test)]
 use casper_execution_engine::core::engine_state::MAX_PAYMENT;
 use casper_execution_engine::core::engine_state::{
-    executable_deploy_item::ExecutableDeployItem, DeployItem,
+    executable_deploy_item::ExecutableDeployItem, payment_lanes::session_wasm_size, DeployItem,
 };
 use casper_hashing::Digest;
-#[cfg(test)]
-use casper_types::bytesrepr::Bytes;
 #[cfg(any(feature = "testing", test))]
 use casper_types::testing::TestRng;
 use casper_types::{
-    bytesrepr::{self, FromBytes, ToBytes},
+    bytesrepr::{self, Bytes, FromBytes, ToBytes},
     crypto, runtime_args,
-    system::standard_payment::ARG_AMOUNT,
-    PublicKey, RuntimeArgs, SecretKey, TimeDiff, Timestamp, U512,
+    system::{
+        auction::{ARG_VALIDATOR_PUBLIC_KEY, METHOD_ACTIVATE_BID},
+        standard_payment::ARG_AMOUNT,
+    },
+    ContractHash, PublicKey, RuntimeArgs, SecretKey, TimeDiff, Timestamp, U512,
 };
 
 use crate::{
@@ -60,6 +64,11 @@ use crate::{
 };
 pub use approval::Approval;
 pub use approvals_hash::ApprovalsHash;
+pub use builder::{DeployBuilder, DeployBuilderError, TransferTarget};
+pub(crate) use deploy_approvals::DeployApprovals;
+pub(crate) use deploy_execution_result::{
+    DeployExecutionResult, DeployExecutionResultId, DeployExecutionResultValidationMetadata,
+};
 pub use deploy_hash::DeployHash;
 pub(crate) use deploy_hash_with_approvals::DeployHashWithApprovals;
 pub use deploy_header::DeployHeader;
@@ -72,6 +81,9 @@ pub use id::Id as DeployId;
 pub(crate) use legacy_deploy::LegacyDeploy;
 pub(crate) use metadata::{Metadata as DeployMetadata, MetadataExt as DeployMetadataExt};
 
+/// The maximum length in bytes of a deploy's user tag.
+pub(crate) const MAX_USER_TAG_LENGTH: usize = 64;
+
 static DEPLOY: Lazy<Deploy> = Lazy::new(|| {
     let payment_args = runtime_args! {
         "amount" => 1000
@@ -142,12 +154,41 @@ impl Deploy {
         session: ExecutableDeployItem,
         secret_key: &SecretKey,
         account: Option<PublicKey>,
+    ) -> Deploy {
+        let account = account.unwrap_or_else(|| PublicKey::from(secret_key));
+        let mut deploy = Deploy::new_unsigned(
+            timestamp,
+            ttl,
+            gas_price,
+            dependencies,
+            chain_name,
+            payment,
+            session,
+            account,
+            U512::zero(),
+            None,
+        );
+        deploy.sign(secret_key);
+        deploy
+    }
+
+    /// Constructs a new `Deploy` without any approvals, to be signed by the owners of `account`.
+    #[allow(clippy::too_many_arguments)]
+    fn new_unsigned(
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        gas_price: u64,
+        dependencies: Vec<DeployHash>,
+        chain_name: String,
+        payment: ExecutableDeployItem,
+        session: ExecutableDeployItem,
+        account: PublicKey,
+        priority_fee: U512,
+        user_tag: Option<Bytes>,
     ) -> Deploy {
         let serialized_body = serialize_body(&payment, &session);
         let body_hash = Digest::hash(&serialized_body);
 
-        let account = account.unwrap_or_else(|| PublicKey::from(secret_key));
-
         // Remove duplicates.
         let dependencies = dependencies.into_iter().unique().collect();
         let header = DeployHeader::new(
@@ -158,27 +199,65 @@ impl Deploy {
             body_hash,
             dependencies,
             chain_name,
-        );
+        )
+        .with_priority_fee(priority_fee)
+        .with_user_tag(user_tag);
         let serialized_header = serialize_header(&header);
         let hash = DeployHash::new(Digest::hash(&serialized_header));
 
-        let mut deploy = Deploy {
+        Deploy {
             hash,
             header,
             payment,
             session,
             approvals: BTreeSet::new(),
             is_valid: OnceCell::new(),
-        };
+        }
+    }
 
-        deploy.sign(secret_key);
-        deploy
+    /// Constructs a new signed `Deploy` which reactivates the bid of the validator owning
+    /// `secret_key`, e.g. after the bid was marked inactive due to a liveness fault.
+    ///
+    /// The session calls the auction contract's `activate_bid` entry point directly, so no Wasm is
+    /// needed, and standard payment of `payment_amount` is used.  The auction contract rejects the
+    /// deploy if the bid is already active or has no stake left; the `state_get_bid_status` RPC
+    /// can be used to check this beforehand.
+    pub fn new_activate_bid(
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        chain_name: String,
+        auction_contract_hash: ContractHash,
+        payment_amount: U512,
+        secret_key: &SecretKey,
+    ) -> Deploy {
+        let payment = ExecutableDeployItem::ModuleBytes {
+            module_bytes: Default::default(),
+            args: runtime_args! { ARG_AMOUNT => payment_amount },
+        };
+        let session = ExecutableDeployItem::StoredContractByHash {
+            hash: auction_contract_hash,
+            entry_point: METHOD_ACTIVATE_BID.to_string(),
+            args: runtime_args! { ARG_VALIDATOR_PUBLIC_KEY => PublicKey::from(secret_key) },
+        };
+        Deploy::new(
+            timestamp,
+            ttl,
+            1,
+            vec![],
+            chain_name,
+            payment,
+            session,
+            secret_key,
+            None,
+        )
     }
 
     /// Adds a signature of this deploy's hash to its approvals.
     pub fn sign(&mut self, secret_key: &SecretKey) {
         let approval = Approval::create(&self.hash, secret_key);
         self.approvals.insert(approval);
+        // A cached outcome of `is_valid` may no longer hold, e.g. if there were no approvals.
+        self.is_valid = OnceCell::new();
     }
 
     /// Returns the `DeployHash` identifying this `Deploy`.
@@ -227,6 +306,7 @@ impl Deploy {
 
     pub(crate) fn with_approvals(mut self, approvals: BTreeSet<Approval>) -> Self {
         self.approvals = approvals;
+        self.is_valid = OnceCell::new();
         self
     }
 
@@ -241,14 +321,28 @@ impl Deploy {
         };
         let size_estimate = self.serialized_length();
         let is_transfer = self.session.is_transfer();
+        let session_size = session_wasm_size(&self.session);
+        let priority_fee = self.priority_fee();
         Ok(DeployFootprint {
             header,
             gas_estimate,
             size_estimate,
             is_transfer,
+            session_size,
+            priority_fee,
         })
     }
 
+    /// Returns the priority fee this deploy offers its block proposer.
+    pub(crate) fn priority_fee(&self) -> U512 {
+        self.header.priority_fee()
+    }
+
+    /// Returns the opaque user tag of this deploy, or `None` if there is none.
+    pub(crate) fn user_tag(&self) -> Option<&Bytes> {
+        self.header.user_tag()
+    }
+
     /// Returns true if the serialized size of the deploy is not greater than `max_deploy_size`.
     fn is_valid_size(&self, max_deploy_size: u32) -> Result<(), ExcessiveSizeError> {
         let deploy_size = self.serialized_length();
@@ -274,7 +368,7 @@ impl Deploy {
         let serialized_header = serialize_header(&self.header);
         let hash = DeployHash::new(Digest::hash(&serialized_header));
         if hash != self.hash {
-            warn!(?self, ?hash, "invalid deploy hash");
+            warn!(?self, deploy_hash = %self.hash, computed_hash = %hash, "invalid deploy hash");
             return Err(DeployConfigurationFailure::InvalidDeployHash);
         }
         Ok(())
@@ -289,6 +383,11 @@ impl Deploy {
         self.is_valid.get_or_init(|| validate_deploy(self)).clone()
     }
 
+    /// Returns `true` if the outcome of `is_valid` is already known.
+    pub(crate) fn is_verification_cached(&self) -> bool {
+        self.is_valid.get().is_some()
+    }
+
     /// Returns true if and only if:
     ///   * the chain_name is correct,
     ///   * the configured parameters are complied with,
@@ -391,6 +490,21 @@ impl Deploy {
             });
         }
 
+        if let Some(user_tag) = header.user_tag() {
+            if user_tag.is_empty() || user_tag.len() > MAX_USER_TAG_LENGTH {
+                info!(
+                    deploy_hash = %self.hash(),
+                    user_tag_length = user_tag.len(),
+                    max_user_tag_length = MAX_USER_TAG_LENGTH,
+                    "invalid user tag length"
+                );
+                return Err(DeployConfigurationFailure::InvalidUserTagLength {
+                    max_length: MAX_USER_TAG_LENGTH,
+                    got: user_tag.len(),
+                });
+            }
+        }
+
         let session_args_length = self.session().args().serialized_length();
         if session_args_length > config.session_args_max_length as usize {
             info!(
@@ -569,6 +683,8 @@ impl GossipItem for Deploy {
 
     const ID_IS_COMPLETE_ITEM: bool = false;
     const REQUIRES_GOSSIP_RECEIVED_ANNOUNCEMENT: bool = false;
+    const REQUIRES_VERIFICATION_BEFORE_GOSSIP: bool = false;
+    const GOSSIPER_NAME: &'static str = "deploy_gossiper";
 
     fn gossip_id(&self) -> Self::Id {
         let deploy_hash = *self.hash();
@@ -623,6 +739,7 @@ impl From<Deploy> for DeployItem {
             authorization_keys,
             casper_types::DeployHash::new(deploy.hash().inner().value()),
         )
+        .with_priority_fee(deploy.header().priority_fee())
     }
 }
 
@@ -1112,6 +1229,39 @@ mod tests {
         bytesrepr::test_serialization_roundtrip(&deploy);
     }
 
+    #[test]
+    fn should_keep_original_header_format_unless_extended() {
+        let mut rng = crate::new_rng();
+        let deploy = Deploy::random(&mut rng);
+        let header = deploy.header().clone();
+        assert!(header.priority_fee().is_zero());
+        let original_bytes = header.to_bytes().unwrap();
+        assert_ne!(original_bytes[0], u8::MAX);
+
+        let extended = header.clone().with_priority_fee(U512::from(7));
+        let extended_bytes = extended.to_bytes().unwrap();
+        assert_eq!(extended_bytes[0], u8::MAX);
+        assert_eq!(
+            &extended_bytes[2..original_bytes.len() + 2],
+            &original_bytes[..]
+        );
+        bytesrepr::test_serialization_roundtrip(&extended);
+        let tagged = header
+            .clone()
+            .with_user_tag(Some(Bytes::from(b"deposit-0042".to_vec())));
+        assert_eq!(tagged.to_bytes().unwrap()[0], u8::MAX);
+        bytesrepr::test_serialization_roundtrip(&tagged);
+
+        // An extended header not using any added field has a second serialized form: reject it.
+        let mut non_canonical_bytes = vec![u8::MAX, 1];
+        non_canonical_bytes.extend(&original_bytes);
+        non_canonical_bytes.extend(U512::zero().to_bytes().unwrap());
+        assert_eq!(
+            bytesrepr::deserialize::<DeployHeader>(non_canonical_bytes),
+            Err(bytesrepr::Error::Formatting)
+        );
+    }
+
     fn create_deploy(
         rng: &mut TestRng,
         ttl: TimeDiff,
@@ -1200,6 +1350,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn activate_bid_deploy_is_valid() {
+        let mut rng = crate::new_rng();
+        let secret_key = SecretKey::random(&mut rng);
+        let auction_contract_hash = ContractHash::new(rng.gen());
+        let deploy = Deploy::new_activate_bid(
+            Timestamp::now(),
+            DeployConfig::default().max_ttl,
+            "net-1".to_string(),
+            auction_contract_hash,
+            U512::from(MAX_PAYMENT_AMOUNT),
+            &secret_key,
+        );
+        deploy.is_valid().expect("should be valid");
+
+        assert_eq!(deploy.header().account(), &PublicKey::from(&secret_key));
+        match deploy.session() {
+            ExecutableDeployItem::StoredContractByHash {
+                hash,
+                entry_point,
+                args,
+            } => {
+                assert_eq!(*hash, auction_contract_hash);
+                assert_eq!(entry_point, METHOD_ACTIVATE_BID);
+                let validator_public_key: PublicKey = args
+                    .get(ARG_VALIDATOR_PUBLIC_KEY)
+                    .expect("should have validator public key")
+                    .clone()
+                    .into_t()
+                    .expect("should be a public key");
+                assert_eq!(validator_public_key, PublicKey::from(&secret_key));
+            }
+            other => panic!("unexpected session {:?}", other),
+        }
+    }
+
     #[test]
     fn not_valid_due_to_invalid_body_hash() {
         let mut rng = crate::new_rng();
@@ -1561,6 +1747,43 @@ mod tests {
         )
     }
 
+    #[test]
+    fn not_acceptable_due_to_invalid_user_tag_length() {
+        let mut rng = crate::new_rng();
+        let chain_name = "net-1";
+        let deploy_config = DeployConfig::default();
+        let secret_key = SecretKey::random(&mut rng);
+        let create_tagged_deploy = |user_tag_length: usize| {
+            let session = ExecutableDeployItem::ModuleBytes {
+                module_bytes: Bytes::new(),
+                args: RuntimeArgs::new(),
+            };
+            DeployBuilder::new(chain_name, session)
+                .with_standard_payment(MAX_PAYMENT_AMOUNT)
+                .with_secret_key(&secret_key)
+                .with_user_tag(vec![1; user_tag_length])
+                .build()
+                .expect("should build deploy")
+        };
+
+        create_tagged_deploy(MAX_USER_TAG_LENGTH)
+            .is_config_compliant(chain_name, &deploy_config, DEFAULT_MAX_ASSOCIATED_KEYS)
+            .expect("should be acceptable");
+        for user_tag_length in [0, MAX_USER_TAG_LENGTH + 1] {
+            assert_eq!(
+                create_tagged_deploy(user_tag_length).is_config_compliant(
+                    chain_name,
+                    &deploy_config,
+                    DEFAULT_MAX_ASSOCIATED_KEYS
+                ),
+                Err(DeployConfigurationFailure::InvalidUserTagLength {
+                    max_length: MAX_USER_TAG_LENGTH,
+                    got: user_tag_length,
+                })
+            );
+        }
+    }
+
     #[test]
     fn not_acceptable_due_to_missing_transfer_amount() {
         let mut rng = crate::new_rng();
@@ -0,0 +1,306 @@
+//! Builder-style construction of deploys.
+//!
+//! [`DeployBuilder`] is meant for constructing deploys outside the node, e.g. in clients and tests,
+//! so that they are hashed and signed exactly as the node expects.  Deploys built here are not
+//! checked against a network's chainspec; use [`Deploy::is_config_compliant`] for that.
+
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::Bytes,
+    runtime_args,
+    system::mint::{ARG_AMOUNT, ARG_ID, ARG_TARGET},
+    PublicKey, SecretKey, TimeDiff, Timestamp, URef, U512,
+};
+
+use super::{Deploy, DeployHash};
+
+/// The recipient of a native transfer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransferTarget {
+    /// The main purse of the account with the given public key, which is created if missing.
+    PublicKey(PublicKey),
+    /// The main purse of the account with the given hash, which is created if missing.
+    AccountHash(AccountHash),
+    /// The given purse.
+    URef(URef),
+}
+
+/// Error returned when building a deploy.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum DeployBuilderError {
+    /// No payment code was given.
+    #[error("deploy has no payment code")]
+    MissingPayment,
+
+    /// Neither an account nor a secret key to sign with was given.
+    #[error("deploy has neither an account nor a secret key to sign with")]
+    MissingAccount,
+
+    /// The time-to-live is zero, so the deploy would expire right away.
+    #[error("deploy time-to-live must not be zero")]
+    ZeroTimeToLive,
+
+    /// The same dependency was listed more than once.
+    #[error("deploy lists dependency {0} more than once")]
+    DuplicateDependency(DeployHash),
+}
+
+/// A builder of [`Deploy`]s.
+///
+/// The deploy is signed with the secret key given, if any.  Otherwise an account must be given and
+/// the deploy is built without approvals, to be signed separately via [`Deploy::sign`].
+#[derive(Debug)]
+pub struct DeployBuilder<'a> {
+    account: Option<PublicKey>,
+    secret_key: Option<&'a SecretKey>,
+    timestamp: Timestamp,
+    ttl: TimeDiff,
+    gas_price: u64,
+    dependencies: Vec<DeployHash>,
+    chain_name: String,
+    payment: Option<ExecutableDeployItem>,
+    session: ExecutableDeployItem,
+    priority_fee: U512,
+    user_tag: Option<Bytes>,
+}
+
+impl<'a> DeployBuilder<'a> {
+    /// The default time-to-live of a deploy.
+    pub const DEFAULT_TTL: TimeDiff = TimeDiff::from_millis(30 * 60 * 1_000);
+    /// The default gas price of a deploy.
+    pub const DEFAULT_GAS_PRICE: u64 = 1;
+
+    /// Returns a builder of a deploy on the chain named `chain_name` running `session`.
+    ///
+    /// The timestamp defaults to now.  Payment code must be given before building.
+    pub fn new<C: Into<String>>(chain_name: C, session: ExecutableDeployItem) -> Self {
+        DeployBuilder {
+            account: None,
+            secret_key: None,
+            timestamp: Timestamp::now(),
+            ttl: Self::DEFAULT_TTL,
+            gas_price: Self::DEFAULT_GAS_PRICE,
+            dependencies: vec![],
+            chain_name: chain_name.into(),
+            payment: None,
+            session,
+            priority_fee: U512::zero(),
+            user_tag: None,
+        }
+    }
+
+    /// Returns a builder of a deploy natively transferring `amount` motes from the main purse of
+    /// the deploy's account to `target`.
+    pub fn new_transfer<C: Into<String>, A: Into<U512>>(
+        chain_name: C,
+        amount: A,
+        target: TransferTarget,
+        transfer_id: Option<u64>,
+    ) -> Self {
+        let mut args = runtime_args! {
+            ARG_AMOUNT => amount.into(),
+            ARG_ID => transfer_id,
+        };
+        let insert_result = match target {
+            TransferTarget::PublicKey(public_key) => args.insert(ARG_TARGET, public_key),
+            TransferTarget::AccountHash(account_hash) => args.insert(ARG_TARGET, account_hash),
+            TransferTarget::URef(uref) => args.insert(ARG_TARGET, uref),
+        };
+        insert_result.expect("should serialize transfer target");
+        DeployBuilder::new(chain_name, ExecutableDeployItem::Transfer { args })
+    }
+
+    /// Sets the account the deploy is executed in the context of.
+    ///
+    /// Defaults to the account of the secret key's public key.
+    pub fn with_account(mut self, account: PublicKey) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Sets the secret key to sign the deploy with.
+    pub fn with_secret_key(mut self, secret_key: &'a SecretKey) -> Self {
+        self.secret_key = Some(secret_key);
+        self
+    }
+
+    /// Sets the payment code to the standard payment of `amount` motes from the account's main
+    /// purse.
+    pub fn with_standard_payment<A: Into<U512>>(mut self, amount: A) -> Self {
+        self.payment = Some(ExecutableDeployItem::ModuleBytes {
+            module_bytes: Default::default(),
+            args: runtime_args! { ARG_AMOUNT => amount.into() },
+        });
+        self
+    }
+
+    /// Sets custom payment code.
+    pub fn with_payment(mut self, payment: ExecutableDeployItem) -> Self {
+        self.payment = Some(payment);
+        self
+    }
+
+    /// Sets the timestamp of the deploy.
+    pub fn with_timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Sets the time-to-live of the deploy.
+    pub fn with_ttl(mut self, ttl: TimeDiff) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the gas price of the deploy.
+    pub fn with_gas_price(mut self, gas_price: u64) -> Self {
+        self.gas_price = gas_price;
+        self
+    }
+
+    /// Sets the deploys which must be executed before this one.
+    pub fn with_dependencies(mut self, dependencies: Vec<DeployHash>) -> Self {
+        self.dependencies = dependencies;
+        self
+    }
+
+    /// Sets the priority fee in motes offered to the block proposer.
+    ///
+    /// Deploys offering a priority fee are only accepted by networks which enabled priority fees.
+    pub fn with_priority_fee<A: Into<U512>>(mut self, priority_fee: A) -> Self {
+        self.priority_fee = priority_fee.into();
+        self
+    }
+
+    /// Sets the opaque tag the deploy can be looked up by.
+    ///
+    /// Deploys carrying a user tag are only accepted by networks which enabled user tags.
+    pub fn with_user_tag<T: Into<Bytes>>(mut self, user_tag: T) -> Self {
+        self.user_tag = Some(user_tag.into());
+        self
+    }
+
+    /// Builds the deploy, computing its hashes and signing it if a secret key was given.
+    pub fn build(self) -> Result<Deploy, DeployBuilderError> {
+        let payment = self.payment.ok_or(DeployBuilderError::MissingPayment)?;
+        if self.ttl.millis() == 0 {
+            return Err(DeployBuilderError::ZeroTimeToLive);
+        }
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = self.dependencies.iter().find(|hash| !seen.insert(*hash)) {
+            return Err(DeployBuilderError::DuplicateDependency(*duplicate));
+        }
+        let account = match (self.account, self.secret_key) {
+            (Some(account), _) => account,
+            (None, Some(secret_key)) => PublicKey::from(secret_key),
+            (None, None) => return Err(DeployBuilderError::MissingAccount),
+        };
+
+        let mut deploy = Deploy::new_unsigned(
+            self.timestamp,
+            self.ttl,
+            self.gas_price,
+            self.dependencies,
+            self.chain_name,
+            payment,
+            self.session,
+            account,
+            self.priority_fee,
+            self.user_tag,
+        );
+        if let Some(secret_key) = self.secret_key {
+            deploy.sign(secret_key);
+        }
+        Ok(deploy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{testing::TestRng, RuntimeArgs};
+
+    use super::*;
+    use crate::types::DeployConfigurationFailure;
+
+    #[test]
+    fn should_build_signed_and_unsigned_deploys() {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random(&mut rng);
+        let target = TransferTarget::PublicKey(PublicKey::random(&mut rng));
+        let builder = || {
+            DeployBuilder::new_transfer("casper-example", 2_500_000_000_u64, target.clone(), None)
+                .with_standard_payment(10_000_u64)
+                .with_timestamp(Timestamp::from(1_000))
+        };
+
+        let signed = builder().with_secret_key(&secret_key).build().unwrap();
+        assert!(signed.is_valid().is_ok());
+        assert!(signed.session().is_transfer());
+
+        let mut unsigned = builder()
+            .with_account(PublicKey::from(&secret_key))
+            .build()
+            .unwrap();
+        assert_eq!(unsigned.hash(), signed.hash());
+        assert_eq!(
+            unsigned.is_valid(),
+            Err(DeployConfigurationFailure::EmptyApprovals)
+        );
+        unsigned.sign(&secret_key);
+        assert!(unsigned.is_valid().is_ok());
+    }
+
+    #[test]
+    fn should_reject_invalid_deploys() {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random(&mut rng);
+        let builder = || {
+            DeployBuilder::new_transfer(
+                "casper-example",
+                2_500_000_000_u64,
+                TransferTarget::AccountHash(AccountHash::new([1; 32])),
+                Some(1),
+            )
+            .with_standard_payment(10_000_u64)
+        };
+
+        assert_eq!(
+            builder().build().unwrap_err(),
+            DeployBuilderError::MissingAccount
+        );
+        assert_eq!(
+            builder()
+                .with_secret_key(&secret_key)
+                .with_ttl(TimeDiff::from_millis(0))
+                .build()
+                .unwrap_err(),
+            DeployBuilderError::ZeroTimeToLive
+        );
+        let dependency = DeployHash::random(&mut rng);
+        assert_eq!(
+            builder()
+                .with_secret_key(&secret_key)
+                .with_dependencies(vec![dependency, dependency])
+                .build()
+                .unwrap_err(),
+            DeployBuilderError::DuplicateDependency(dependency)
+        );
+        assert_eq!(
+            DeployBuilder::new(
+                "casper-example",
+                ExecutableDeployItem::Transfer {
+                    args: RuntimeArgs::new()
+                }
+            )
+            .with_secret_key(&secret_key)
+            .build()
+            .unwrap_err(),
+            DeployBuilderError::MissingPayment
+        );
+    }
+}
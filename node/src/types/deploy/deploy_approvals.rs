@@ -0,0 +1,137 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    crypto,
+};
+
+use super::{Approval, DeployConfigurationFailure, DeployHash};
+use crate::components::fetcher::{EmptyValidationMetadata, FetchItem, Tag};
+
+/// The approvals a node holds for a deploy, exchanged between peers so that approvals submitted
+/// to different nodes can be collected onto a single deploy.
+#[derive(Clone, DataSize, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct DeployApprovals {
+    deploy_hash: DeployHash,
+    approvals: BTreeSet<Approval>,
+}
+
+impl DeployApprovals {
+    pub(crate) fn new(deploy_hash: DeployHash, approvals: BTreeSet<Approval>) -> Self {
+        DeployApprovals {
+            deploy_hash,
+            approvals,
+        }
+    }
+
+    pub(crate) fn approvals(&self) -> &BTreeSet<Approval> {
+        &self.approvals
+    }
+
+    pub(crate) fn into_approvals(self) -> BTreeSet<Approval> {
+        self.approvals
+    }
+}
+
+impl FetchItem for DeployApprovals {
+    type Id = DeployHash;
+    type ValidationError = DeployConfigurationFailure;
+    type ValidationMetadata = EmptyValidationMetadata;
+
+    const TAG: Tag = Tag::DeployApprovals;
+
+    fn fetch_id(&self) -> Self::Id {
+        self.deploy_hash
+    }
+
+    fn validate(&self, _metadata: &EmptyValidationMetadata) -> Result<(), Self::ValidationError> {
+        if self.approvals.is_empty() {
+            return Err(DeployConfigurationFailure::EmptyApprovals);
+        }
+        for (index, approval) in self.approvals.iter().enumerate() {
+            if let Err(error) =
+                crypto::verify(&self.deploy_hash, approval.signature(), approval.signer())
+            {
+                return Err(DeployConfigurationFailure::InvalidApproval {
+                    index,
+                    error_msg: error.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ToBytes for DeployApprovals {
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        self.deploy_hash.write_bytes(writer)?;
+        self.approvals.write_bytes(writer)
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        self.write_bytes(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.deploy_hash.serialized_length() + self.approvals.serialized_length()
+    }
+}
+
+impl FromBytes for DeployApprovals {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (deploy_hash, remainder) = DeployHash::from_bytes(bytes)?;
+        let (approvals, remainder) = BTreeSet::<Approval>::from_bytes(remainder)?;
+        Ok((DeployApprovals::new(deploy_hash, approvals), remainder))
+    }
+}
+
+impl Display for DeployApprovals {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{} approvals for {}",
+            self.approvals.len(),
+            self.deploy_hash
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{testing::TestRng, SecretKey};
+
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_and_validate() {
+        let mut rng = TestRng::new();
+        let deploy_hash = DeployHash::random(&mut rng);
+        let approvals: BTreeSet<Approval> = (0..3)
+            .map(|_| Approval::create(&deploy_hash, &SecretKey::random(&mut rng)))
+            .collect();
+        let deploy_approvals = DeployApprovals::new(deploy_hash, approvals.clone());
+        bytesrepr::test_serialization_roundtrip(&deploy_approvals);
+        assert!(deploy_approvals.validate(&EmptyValidationMetadata).is_ok());
+
+        // Approvals of a different deploy don't validate.
+        let other_deploy_approvals = DeployApprovals::new(DeployHash::random(&mut rng), approvals);
+        assert!(matches!(
+            other_deploy_approvals.validate(&EmptyValidationMetadata),
+            Err(DeployConfigurationFailure::InvalidApproval { .. })
+        ));
+
+        let no_approvals = DeployApprovals::new(deploy_hash, BTreeSet::new());
+        assert_eq!(
+            no_approvals.validate(&EmptyValidationMetadata),
+            Err(DeployConfigurationFailure::EmptyApprovals)
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt::{self, Display, Formatter};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use casper_execution_engine::core::engine_state::execution_results_proof;
+use casper_hashing::{Digest, IndexedMerkleProof, MerkleVerificationError};
+use casper_types::{bytesrepr, ExecutionResult};
+
+use super::DeployHash;
+use crate::{
+    components::fetcher::{FetchItem, Tag},
+    types::BlockHash,
+};
+
+/// The identifier of the execution result of a deploy in a given block.
+#[derive(Clone, Copy, DataSize, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub(crate) struct DeployExecutionResultId {
+    block_hash: BlockHash,
+    deploy_hash: DeployHash,
+}
+
+impl DeployExecutionResultId {
+    pub(crate) fn new(block_hash: BlockHash, deploy_hash: DeployHash) -> Self {
+        DeployExecutionResultId {
+            block_hash,
+            deploy_hash,
+        }
+    }
+
+    pub(crate) fn block_hash(&self) -> &BlockHash {
+        &self.block_hash
+    }
+
+    pub(crate) fn deploy_hash(&self) -> &DeployHash {
+        &self.deploy_hash
+    }
+}
+
+impl Display for DeployExecutionResultId {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "execution result of {} in {}",
+            self.deploy_hash, self.block_hash
+        )
+    }
+}
+
+/// What a fetched [`DeployExecutionResult`] is validated against.
+#[derive(Clone, Copy, DataSize, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct DeployExecutionResultValidationMetadata {
+    /// The Merkle root of the block's execution results, as proven against the block's state root
+    /// hash by its [`ApprovalsHashes`](crate::types::ApprovalsHashes).
+    ///
+    /// Unlike reading it from the checksum registry, this doesn't need the block's global state,
+    /// which nodes backfilling execution results of historical blocks don't hold.
+    pub(crate) execution_results_root: Digest,
+    /// The position of the deploy among the deploys and transfers of the block.
+    pub(crate) index: u64,
+}
+
+/// Error returned when a fetched [`DeployExecutionResult`] fails to validate.
+#[derive(Debug, Error)]
+pub(crate) enum DeployExecutionResultValidationError {
+    /// The Merkle proof is malformed.
+    #[error(transparent)]
+    MerkleVerification(#[from] MerkleVerificationError),
+    /// The execution result could not be hashed.
+    #[error("failed to hash execution result: {0}")]
+    Bytesrepr(bytesrepr::Error),
+    /// The Merkle proof does not start at the leaf of the execution result.
+    #[error("Merkle proof does not prove the execution result")]
+    UnexpectedLeaf,
+    /// The Merkle proof is for another position in the block.
+    #[error("Merkle proof is for index {actual}, expected {expected}")]
+    UnexpectedIndex { expected: u64, actual: u64 },
+    /// The Merkle proof leads to a root other than the block's execution results root.
+    #[error("Merkle proof has root {actual}, expected {expected}")]
+    UnexpectedRootHash { expected: Digest, actual: Digest },
+}
+
+/// The execution result of a single deploy, along with a proof of its place among the execution
+/// results of its block.
+///
+/// Lets a node backfill single execution results it pruned or skipped while syncing, without
+/// fetching those of the whole block.  Only blocks executed once execution results Merkle trees
+/// were enabled can be proven this way.
+#[derive(Clone, DataSize, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct DeployExecutionResult {
+    id: DeployExecutionResultId,
+    execution_result: ExecutionResult,
+    merkle_proof: IndexedMerkleProof,
+}
+
+impl DeployExecutionResult {
+    /// Returns the execution result at `index` of `execution_results`, the results of all the
+    /// deploys of a block in execution order, along with its proof.
+    pub(crate) fn new(
+        block_hash: BlockHash,
+        execution_results: &[(DeployHash, ExecutionResult)],
+        index: usize,
+    ) -> Result<Self, String> {
+        let (deploy_hash, execution_result) = execution_results
+            .get(index)
+            .cloned()
+            .ok_or_else(|| format!("no execution result at index {}", index))?;
+        let leaves = execution_results
+            .iter()
+            .map(|(_, execution_result)| {
+                execution_results_proof::execution_result_leaf(execution_result)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())?;
+        let merkle_proof =
+            IndexedMerkleProof::new(leaves, index as u64).map_err(|error| error.to_string())?;
+        Ok(DeployExecutionResult {
+            id: DeployExecutionResultId::new(block_hash, deploy_hash),
+            execution_result,
+            merkle_proof,
+        })
+    }
+
+    pub(crate) fn id(&self) -> &DeployExecutionResultId {
+        &self.id
+    }
+
+    pub(crate) fn execution_result(&self) -> &ExecutionResult {
+        &self.execution_result
+    }
+
+    pub(crate) fn into_execution_result(self) -> ExecutionResult {
+        self.execution_result
+    }
+}
+
+impl FetchItem for DeployExecutionResult {
+    type Id = DeployExecutionResultId;
+    type ValidationError = DeployExecutionResultValidationError;
+    type ValidationMetadata = DeployExecutionResultValidationMetadata;
+
+    const TAG: Tag = Tag::DeployExecutionResult;
+
+    fn fetch_id(&self) -> Self::Id {
+        self.id
+    }
+
+    fn validate(
+        &self,
+        metadata: &DeployExecutionResultValidationMetadata,
+    ) -> Result<(), Self::ValidationError> {
+        self.merkle_proof.verify()?;
+        let leaf = execution_results_proof::execution_result_leaf(&self.execution_result)
+            .map_err(DeployExecutionResultValidationError::Bytesrepr)?;
+        if self.merkle_proof.merkle_proof().first() != Some(&leaf) {
+            return Err(DeployExecutionResultValidationError::UnexpectedLeaf);
+        }
+        if self.merkle_proof.index() != metadata.index {
+            return Err(DeployExecutionResultValidationError::UnexpectedIndex {
+                expected: metadata.index,
+                actual: self.merkle_proof.index(),
+            });
+        }
+        let root_hash = self.merkle_proof.root_hash();
+        if root_hash != metadata.execution_results_root {
+            return Err(DeployExecutionResultValidationError::UnexpectedRootHash {
+                expected: metadata.execution_results_root,
+                actual: root_hash,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Display for DeployExecutionResult {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        Display::fmt(&self.id, formatter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    #[test]
+    fn should_validate_against_execution_results_root() {
+        let mut rng = TestRng::new();
+        let block_hash = BlockHash::random(&mut rng);
+        let execution_results: Vec<(DeployHash, ExecutionResult)> = (0..5)
+            .map(|_| (DeployHash::random(&mut rng), rng.gen()))
+            .collect();
+        let execution_results_root = execution_results_proof::execution_results_merkle_root(
+            &execution_results
+                .iter()
+                .map(|(_, execution_result)| execution_result.clone())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+        let item = DeployExecutionResult::new(block_hash, &execution_results, 3).unwrap();
+        assert_eq!(
+            item.fetch_id(),
+            DeployExecutionResultId::new(block_hash, execution_results[3].0)
+        );
+        assert_eq!(item.execution_result, execution_results[3].1);
+        let metadata = DeployExecutionResultValidationMetadata {
+            execution_results_root,
+            index: 3,
+        };
+        item.validate(&metadata).unwrap();
+
+        // The result of another deploy of the block doesn't validate.
+        let other_index = DeployExecutionResultValidationMetadata {
+            index: 2,
+            ..metadata
+        };
+        assert!(matches!(
+            item.validate(&other_index),
+            Err(DeployExecutionResultValidationError::UnexpectedIndex { .. })
+        ));
+
+        // Nor does a result swapped in for the proven one.
+        let mut swapped = item.clone();
+        swapped.execution_result = execution_results[2].1.clone();
+        assert!(matches!(
+            swapped.validate(&metadata),
+            Err(DeployExecutionResultValidationError::UnexpectedLeaf)
+        ));
+
+        // Nor a result of another block.
+        let other_root = DeployExecutionResultValidationMetadata {
+            execution_results_root: Digest::hash([1; 32]),
+            ..metadata
+        };
+        assert!(matches!(
+            item.validate(&other_root),
+            Err(DeployExecutionResultValidationError::UnexpectedRootHash { .. })
+        ));
+    }
+}
@@ -6,8 +6,8 @@ use serde::{Deserialize, Serialize};
 
 use casper_hashing::Digest;
 use casper_types::{
-    bytesrepr::{self, FromBytes, ToBytes},
-    PublicKey, TimeDiff, Timestamp,
+    bytesrepr::{self, Bytes, FromBytes, ToBytes},
+    PublicKey, TimeDiff, Timestamp, U512,
 };
 
 #[cfg(doc)]
@@ -15,6 +15,15 @@ use super::Deploy;
 use super::DeployHash;
 use crate::{types::chainspec::DeployConfig, utils::DisplayIter};
 
+/// The first byte of a serialized header carrying fields added after the original format.
+///
+/// A header in the original format starts with the tag of the account's public key, which is never
+/// this value.  Headers not using any added field keep the original format, so deploys created
+/// before the fields were added still deserialize, and hash, exactly as before.
+const EXTENDED_HEADER_TAG: u8 = u8::MAX;
+/// The version of the extended header format, following [`EXTENDED_HEADER_TAG`].
+const EXTENDED_HEADER_VERSION: u8 = 1;
+
 /// The header portion of a [`Deploy`].
 #[derive(
     Clone, DataSize, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug, JsonSchema,
@@ -28,6 +37,11 @@ pub struct DeployHeader {
     body_hash: Digest,
     dependencies: Vec<DeployHash>,
     chain_name: String,
+    #[serde(default, skip_serializing_if = "U512::is_zero")]
+    priority_fee: U512,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    user_tag: Option<Bytes>,
 }
 
 impl DeployHeader {
@@ -48,9 +62,23 @@ impl DeployHeader {
             body_hash,
             dependencies,
             chain_name,
+            priority_fee: U512::zero(),
+            user_tag: None,
         }
     }
 
+    /// Sets the priority fee offered to the block proposer.
+    pub(super) fn with_priority_fee(mut self, priority_fee: U512) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
+    /// Sets the user tag the deploy can be looked up by.
+    pub(super) fn with_user_tag(mut self, user_tag: Option<Bytes>) -> Self {
+        self.user_tag = user_tag;
+        self
+    }
+
     /// The account within which the deploy will be run.
     pub fn account(&self) -> &PublicKey {
         &self.account
@@ -91,6 +119,26 @@ impl DeployHeader {
         &self.chain_name
     }
 
+    /// The priority fee in motes offered to the block proposer, taken out of the payment purse
+    /// before session code runs.
+    pub fn priority_fee(&self) -> U512 {
+        self.priority_fee
+    }
+
+    /// The opaque tag the deploy can be looked up by, if any.
+    ///
+    /// The tag isn't interpreted by the node: it only serves to look the deploy up, e.g. for an
+    /// exchange to correlate a deposit with its customer.
+    pub fn user_tag(&self) -> Option<&Bytes> {
+        self.user_tag.as_ref()
+    }
+
+    /// Returns `true` if any field added after the original format is set, requiring the extended
+    /// format.
+    fn is_extended(&self) -> bool {
+        !self.priority_fee.is_zero() || self.user_tag.is_some()
+    }
+
     /// Determine if this deploy header has valid values based on a `DeployConfig` and timestamp.
     pub fn is_valid(&self, deploy_config: &DeployConfig, current_timestamp: Timestamp) -> bool {
         let ttl_valid = self.ttl() <= deploy_config.max_ttl;
@@ -108,13 +156,23 @@ impl DeployHeader {
 
 impl ToBytes for DeployHeader {
     fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        let is_extended = self.is_extended();
+        if is_extended {
+            writer.push(EXTENDED_HEADER_TAG);
+            writer.push(EXTENDED_HEADER_VERSION);
+        }
         self.account.write_bytes(writer)?;
         self.timestamp.write_bytes(writer)?;
         self.ttl.write_bytes(writer)?;
         self.gas_price.write_bytes(writer)?;
         self.body_hash.write_bytes(writer)?;
         self.dependencies.write_bytes(writer)?;
-        self.chain_name.write_bytes(writer)
+        self.chain_name.write_bytes(writer)?;
+        if is_extended {
+            self.priority_fee.write_bytes(writer)?;
+            self.user_tag.write_bytes(writer)?;
+        }
+        Ok(())
     }
 
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
@@ -124,6 +182,14 @@ impl ToBytes for DeployHeader {
     }
 
     fn serialized_length(&self) -> usize {
+        let extended_length = if self.is_extended() {
+            EXTENDED_HEADER_TAG.serialized_length()
+                + EXTENDED_HEADER_VERSION.serialized_length()
+                + self.priority_fee.serialized_length()
+                + self.user_tag.serialized_length()
+        } else {
+            0
+        };
         self.account.serialized_length()
             + self.timestamp.serialized_length()
             + self.ttl.serialized_length()
@@ -131,18 +197,36 @@ impl ToBytes for DeployHeader {
             + self.body_hash.serialized_length()
             + self.dependencies.serialized_length()
             + self.chain_name.serialized_length()
+            + extended_length
     }
 }
 
 impl FromBytes for DeployHeader {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
-        let (account, remainder) = PublicKey::from_bytes(bytes)?;
+        let (is_extended, remainder) = match bytes.split_first() {
+            Some((&EXTENDED_HEADER_TAG, remainder)) => {
+                let (version, remainder) = u8::from_bytes(remainder)?;
+                if version != EXTENDED_HEADER_VERSION {
+                    return Err(bytesrepr::Error::Formatting);
+                }
+                (true, remainder)
+            }
+            _ => (false, bytes),
+        };
+        let (account, remainder) = PublicKey::from_bytes(remainder)?;
         let (timestamp, remainder) = Timestamp::from_bytes(remainder)?;
         let (ttl, remainder) = TimeDiff::from_bytes(remainder)?;
         let (gas_price, remainder) = u64::from_bytes(remainder)?;
         let (body_hash, remainder) = Digest::from_bytes(remainder)?;
         let (dependencies, remainder) = Vec::<DeployHash>::from_bytes(remainder)?;
         let (chain_name, remainder) = String::from_bytes(remainder)?;
+        let (priority_fee, user_tag, remainder) = if is_extended {
+            let (priority_fee, remainder) = U512::from_bytes(remainder)?;
+            let (user_tag, remainder) = Option::<Bytes>::from_bytes(remainder)?;
+            (priority_fee, user_tag, remainder)
+        } else {
+            (U512::zero(), None, remainder)
+        };
         let deploy_header = DeployHeader {
             account,
             timestamp,
@@ -151,7 +235,14 @@ impl FromBytes for DeployHeader {
             body_hash,
             dependencies,
             chain_name,
+            priority_fee,
+            user_tag,
         };
+        // A header not using any added field must be in the original format, so that it has a
+        // single serialized form, and hence a single hash.
+        if is_extended != deploy_header.is_extended() {
+            return Err(bytesrepr::Error::Formatting);
+        }
         Ok((deploy_header, remainder))
     }
 }
@@ -160,7 +251,7 @@ impl Display for DeployHeader {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         write!(
             formatter,
-            "deploy-header[account: {}, timestamp: {}, ttl: {}, gas_price: {}, body_hash: {}, dependencies: [{}], chain_name: {}]",
+            "deploy-header[account: {}, timestamp: {}, ttl: {}, gas_price: {}, body_hash: {}, dependencies: [{}], chain_name: {}, priority_fee: {}, user_tag: {}]",
             self.account,
             self.timestamp,
             self.ttl,
@@ -168,6 +259,10 @@ impl Display for DeployHeader {
             self.body_hash,
             DisplayIter::new(self.dependencies.iter()),
             self.chain_name,
+            self.priority_fee,
+            self.user_tag
+                .as_ref()
+                .map_or_else(String::new, base16::encode_lower),
         )
     }
 }
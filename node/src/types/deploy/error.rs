@@ -121,6 +121,15 @@ pub enum DeployConfigurationFailure {
         /// The chainspec limit for max_associated_keys.
         max_associated_keys: u32,
     },
+
+    /// The user tag is empty or longer than allowed.
+    #[error("user tag must be between 1 and {max_length} bytes long, got {got} bytes")]
+    InvalidUserTagLength {
+        /// The maximum length of a user tag.
+        max_length: usize,
+        /// The length of the received user tag.
+        got: usize,
+    },
 }
 
 /// Error returned when a Deploy is too large.
@@ -1,7 +1,7 @@
 use datasize::DataSize;
 use serde::{Deserialize, Serialize};
 
-use casper_types::Gas;
+use casper_types::{Gas, U512};
 
 use super::DeployHeader;
 
@@ -12,4 +12,7 @@ pub(crate) struct Footprint {
     pub(crate) gas_estimate: Gas,
     pub(crate) size_estimate: usize,
     pub(crate) is_transfer: bool,
+    /// The size of the session Wasm carried by the deploy, zero unless it carries module bytes.
+    pub(crate) session_size: usize,
+    pub(crate) priority_fee: U512,
 }
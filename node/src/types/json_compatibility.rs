@@ -8,7 +8,7 @@ mod stored_value;
 use casper_types::{contracts::NamedKeys, NamedKey};
 
 pub use account::Account;
-pub use auction_state::AuctionState;
+pub use auction_state::{AuctionState, JsonValidatorWeights};
 pub use contracts::{Contract, ContractPackage};
 pub use stored_value::StoredValue;
 
@@ -77,6 +77,12 @@ pub struct JsonValidatorWeights {
     weight: U512,
 }
 
+impl JsonValidatorWeights {
+    pub(crate) fn new(public_key: PublicKey, weight: U512) -> Self {
+        JsonValidatorWeights { public_key, weight }
+    }
+}
+
 /// The validators for the given era.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, JsonSchema)]
 #[serde(deny_unknown_fields)]
@@ -166,10 +172,7 @@ impl AuctionState {
         for (era_id, validator_weights) in era_validators.iter() {
             let mut json_validator_weights: Vec<JsonValidatorWeights> = Vec::new();
             for (public_key, weight) in validator_weights.iter() {
-                json_validator_weights.push(JsonValidatorWeights {
-                    public_key: public_key.clone(),
-                    weight: *weight,
-                });
+                json_validator_weights.push(JsonValidatorWeights::new(public_key.clone(), *weight));
             }
             json_era_validators.push(JsonEraValidators {
                 era_id: *era_id,
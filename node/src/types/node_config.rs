@@ -8,6 +8,11 @@ use casper_types::TimeDiff;
 const DEFAULT_IDLE_TOLERANCE: &str = "20min";
 const DEFAULT_MAX_ATTEMPTS: usize = 3;
 const DEFAULT_CONTROL_LOGIC_DEFAULT_DELAY: &str = "1sec";
+const DEFAULT_SHUTDOWN_DRAIN_TIMEOUT: &str = "5sec";
+const DEFAULT_SYNC_LEAP_CROSS_CHECK_PEERS: usize = 1;
+const DEFAULT_COMPONENT_RESTART_INITIAL_BACKOFF: &str = "1sec";
+const DEFAULT_COMPONENT_RESTART_MAX_BACKOFF: &str = "1min";
+const DEFAULT_MAX_COMPONENT_RESTARTS: u32 = 10;
 
 /// Node fast-sync configuration.
 #[derive(DataSize, Debug, Deserialize, Serialize, Clone)]
@@ -33,6 +38,26 @@ pub struct NodeConfig {
 
     /// Flag which forces the node to resync all of the blocks.
     pub force_resync: bool,
+
+    /// Upon shutdown, the time given to the RPC, REST and SSE servers to finish serving in-flight
+    /// requests.
+    pub shutdown_drain_timeout: TimeDiff,
+
+    /// The number of peers which must provide a sync leap, all agreeing on the switch blocks
+    /// (and hence era validator weights) they contain, before the node accepts one.
+    pub sync_leap_cross_check_peers: usize,
+
+    /// Delay before the first restart of a failed non-critical component (the REST and SSE
+    /// servers).  The delay doubles with every further failure in a row.
+    pub component_restart_initial_backoff: TimeDiff,
+
+    /// Upper bound on the delay before restarting a failed non-critical component.  A component
+    /// staying up this long after a restart is considered recovered.
+    pub component_restart_max_backoff: TimeDiff,
+
+    /// The number of times in a row a non-critical component may fail and be restarted before its
+    /// failure shuts down the node.
+    pub max_component_restarts: u32,
 }
 
 impl Default for NodeConfig {
@@ -44,6 +69,13 @@ impl Default for NodeConfig {
             max_attempts: DEFAULT_MAX_ATTEMPTS,
             control_logic_default_delay: DEFAULT_CONTROL_LOGIC_DEFAULT_DELAY.parse().unwrap(),
             force_resync: false,
+            shutdown_drain_timeout: DEFAULT_SHUTDOWN_DRAIN_TIMEOUT.parse().unwrap(),
+            sync_leap_cross_check_peers: DEFAULT_SYNC_LEAP_CROSS_CHECK_PEERS,
+            component_restart_initial_backoff: DEFAULT_COMPONENT_RESTART_INITIAL_BACKOFF
+                .parse()
+                .unwrap(),
+            component_restart_max_backoff: DEFAULT_COMPONENT_RESTART_MAX_BACKOFF.parse().unwrap(),
+            max_component_restarts: DEFAULT_MAX_COMPONENT_RESTARTS,
         }
     }
 }
@@ -1,15 +1,22 @@
-use std::fmt::{self, Debug, Display, Formatter};
+use std::{
+    convert::TryFrom,
+    fmt::{self, Debug, Display, Formatter},
+    str::FromStr,
+};
 
 #[cfg(test)]
 use casper_types::testing::TestRng;
 use datasize::DataSize;
-use hex_fmt::HexFmt;
 use once_cell::sync::Lazy;
 #[cfg(test)]
 use rand::Rng;
 use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{rpcs::docs::DocExample, tls::KeyFingerprint};
+use crate::{
+    rpcs::docs::DocExample,
+    tls::KeyFingerprint,
+    utils::short_id::{ShortId, NODE_ID_SCHEME},
+};
 
 /// The network identifier for a node.
 ///
@@ -29,6 +36,17 @@ impl NodeId {
     pub fn hash_bytes(&self) -> &[u8] {
         self.0.as_ref()
     }
+
+    /// Returns the checksummed short form of the ID, which is how it is displayed.
+    pub(crate) fn short_id(&self) -> ShortId {
+        ShortId::new(self.hash_bytes())
+    }
+}
+
+impl AsRef<[u8]> for NodeId {
+    fn as_ref(&self) -> &[u8] {
+        self.hash_bytes()
+    }
 }
 
 /// Used to serialize and deserialize `NodeID` where the (de)serializer isn't a human-readable type.
@@ -90,7 +108,25 @@ impl Debug for NodeId {
 
 impl Display for NodeId {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
-        write!(formatter, "tls:{:10}", HexFmt(&self.0))
+        write!(formatter, "{}{}", NODE_ID_SCHEME, self.short_id())
+    }
+}
+
+impl FromStr for NodeId {
+    type Err = String;
+
+    /// Parses the full base16-encoded ID, optionally prefixed with `tls:`.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let hex_value = input.strip_prefix(NODE_ID_SCHEME).unwrap_or(input);
+        let bytes = base16::decode(hex_value).map_err(|error| error.to_string())?;
+        let array = <[u8; KeyFingerprint::LENGTH]>::try_from(bytes.as_slice()).map_err(|_| {
+            format!(
+                "node ID must be {} bytes, got {}",
+                KeyFingerprint::LENGTH,
+                bytes.len()
+            )
+        })?;
+        Ok(NodeId(KeyFingerprint::from(array)))
     }
 }
 
@@ -173,6 +209,17 @@ mod tests {
         assert_eq!(serialized, expected);
     }
 
+    #[test]
+    fn should_display_and_parse_short_and_full_forms() {
+        let node_id = NodeId::from(EXAMPLE_HASH_RAW);
+        assert_eq!(node_id.to_string(), "tls:0001020304-6636");
+
+        let full = base16::encode_lower(&EXAMPLE_HASH_RAW);
+        assert_eq!(full.parse(), Ok(node_id));
+        assert_eq!(format!("tls:{}", full).parse(), Ok(node_id));
+        assert!(full[..100].parse::<NodeId>().is_err());
+    }
+
     #[test]
     fn json_roundtrip_tls() {
         let mut rng = crate::new_rng();
@@ -9,6 +9,8 @@ pub(crate) mod fmt_limit;
 pub(crate) mod opt_display;
 pub(crate) mod rlimit;
 pub(crate) mod round_robin;
+pub(crate) mod short_id;
+pub(crate) mod signature_verification;
 pub(crate) mod umask;
 pub mod work_queue;
 
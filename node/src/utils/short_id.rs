@@ -0,0 +1,182 @@
+//! Checksummed short forms of long identifiers.
+//!
+//! Node and connection IDs are long hex strings which operators copy from logs into diagnostics
+//! commands, often mangling them on the way.  The short form is the first [`ShortId::LENGTH`] bytes
+//! of an ID followed by a two byte checksum of them, e.g. `0a1b2c3d4e-91f0`, so a mis-copied short
+//! form is rejected rather than silently matching nothing or the wrong ID.
+
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use datasize::DataSize;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+use casper_hashing::Digest;
+
+/// The prefix node IDs are displayed with.
+pub(crate) const NODE_ID_SCHEME: &str = "tls:";
+/// The number of checksum bytes in the short form.
+const CHECKSUM_LENGTH: usize = 2;
+
+/// The checksummed short form of an ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, DataSize)]
+pub(crate) struct ShortId([u8; ShortId::LENGTH]);
+
+impl ShortId {
+    /// The number of leading bytes of the full ID kept in the short form.
+    pub(crate) const LENGTH: usize = 5;
+
+    /// Returns the short form of the full ID `id_bytes`.
+    ///
+    /// Panics if `id_bytes` is shorter than [`ShortId::LENGTH`].
+    pub(crate) fn new(id_bytes: &[u8]) -> Self {
+        let mut prefix = [0; ShortId::LENGTH];
+        prefix.copy_from_slice(&id_bytes[..ShortId::LENGTH]);
+        ShortId(prefix)
+    }
+
+    /// Returns whether `id_bytes` is a full ID with this short form.
+    pub(crate) fn matches(&self, id_bytes: &[u8]) -> bool {
+        id_bytes.starts_with(&self.0)
+    }
+
+    fn checksum(&self) -> [u8; CHECKSUM_LENGTH] {
+        let mut checksum = [0; CHECKSUM_LENGTH];
+        checksum.copy_from_slice(&Digest::hash(self.0).value()[..CHECKSUM_LENGTH]);
+        checksum
+    }
+}
+
+impl Display for ShortId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            base16::encode_lower(&self.0),
+            base16::encode_lower(&self.checksum())
+        )
+    }
+}
+
+impl Serialize for ShortId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Error parsing the short form of an ID.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub(crate) enum ParseShortIdError {
+    /// The input is not of the form `<prefix>-<checksum>`.
+    #[error(
+        "expected {} hex digits, a dash and {} hex digits",
+        2 * ShortId::LENGTH,
+        2 * CHECKSUM_LENGTH
+    )]
+    Format,
+    /// The checksum doesn't match the prefix, i.e. the short form was mis-copied.
+    #[error("checksum mismatch, the short ID was probably mis-copied")]
+    Checksum,
+}
+
+impl FromStr for ShortId {
+    type Err = ParseShortIdError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (prefix_hex, checksum_hex) = input.split_once('-').ok_or(ParseShortIdError::Format)?;
+        let prefix = decode_array::<{ ShortId::LENGTH }>(prefix_hex)?;
+        let checksum = decode_array::<CHECKSUM_LENGTH>(checksum_hex)?;
+        let short_id = ShortId(prefix);
+        if short_id.checksum() != checksum {
+            return Err(ParseShortIdError::Checksum);
+        }
+        Ok(short_id)
+    }
+}
+
+fn decode_array<const N: usize>(hex: &str) -> Result<[u8; N], ParseShortIdError> {
+    let bytes = base16::decode(hex).map_err(|_| ParseShortIdError::Format)?;
+    <[u8; N]>::try_from(bytes.as_slice()).map_err(|_| ParseShortIdError::Format)
+}
+
+/// An ID given either in full or in its short form, as accepted by diagnostics commands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum IdOrShortId<T> {
+    /// The full ID.
+    Full(T),
+    /// The short form of the ID.
+    Short(ShortId),
+}
+
+impl<T: PartialEq + AsRef<[u8]>> IdOrShortId<T> {
+    /// Returns whether `id` is the ID given.
+    pub(crate) fn matches(&self, id: &T) -> bool {
+        match self {
+            IdOrShortId::Full(full) => full == id,
+            IdOrShortId::Short(short) => short.matches(id.as_ref()),
+        }
+    }
+}
+
+impl<T: Display> Display for IdOrShortId<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            IdOrShortId::Full(full) => Display::fmt(full, f),
+            IdOrShortId::Short(short) => Display::fmt(short, f),
+        }
+    }
+}
+
+impl<T: FromStr> FromStr for IdOrShortId<T> {
+    type Err = String;
+
+    /// Parses the short form if the input contains a dash, the full ID otherwise.  The `tls:` node
+    /// IDs are displayed with is ignored.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.contains('-') {
+            let short = input.strip_prefix(NODE_ID_SCHEME).unwrap_or(input);
+            short
+                .parse()
+                .map(IdOrShortId::Short)
+                .map_err(|error: ParseShortIdError| error.to_string())
+        } else {
+            input
+                .parse()
+                .map(IdOrShortId::Full)
+                .map_err(|_| format!("not a valid full or short ID: {}", input))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_roundtrip_and_detect_miscopied_short_ids() {
+        let id_bytes = [1u8; 64];
+        let short_id = ShortId::new(&id_bytes);
+        let displayed = short_id.to_string();
+        assert_eq!(displayed, "0101010101-eb42");
+        assert_eq!(displayed.parse(), Ok(short_id));
+        assert!(short_id.matches(&id_bytes));
+        assert!(!short_id.matches(&[2u8; 64]));
+
+        assert_eq!(
+            "0101010111-eb42".parse::<ShortId>(),
+            Err(ParseShortIdError::Checksum)
+        );
+        assert_eq!(
+            "01010101-eb42".parse::<ShortId>(),
+            Err(ParseShortIdError::Format)
+        );
+        assert_eq!(
+            "0101010101eb42".parse::<ShortId>(),
+            Err(ParseShortIdError::Format)
+        );
+    }
+}
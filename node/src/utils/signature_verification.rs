@@ -0,0 +1,215 @@
+//! Verification of signatures on a dedicated thread pool.
+//!
+//! Checking the signatures of deploys and finality signatures is the most expensive part of
+//! accepting them. Doing so on the reactor's threads limits how quickly a node can absorb a burst
+//! of them, e.g. right after catching up, so they are instead verified on a rayon pool sized to the
+//! number of cores. The outcome is cached in the item itself, so the checks performed later on by
+//! the components are cheap.
+//!
+//! Ed25519 batch verification is deliberately not used: it is not equivalent to the strict
+//! verification done by `crypto::verify`, so a batch could accept a signature which verifying it
+//! on its own would reject. Batches are instead verified in parallel, one signature at a time.
+//!
+//! Batches wait in a bounded queue before reaching the pool. Finality signatures are favored over
+//! deploys, so that a flood of deploys doesn't hold up finality, and a full queue holds back the
+//! callers rather than piling up work on the pool. While the pool is shared by the whole process,
+//! each reactor has a queue of its own, held by its [`SignatureVerifier`], so that neither the
+//! queue nor its metrics are shared between the nodes of a test network.
+
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    num::NonZeroUsize,
+    sync::Arc,
+};
+
+use once_cell::sync::Lazy;
+use prometheus::Registry;
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use tokio::sync::oneshot;
+
+use crate::{
+    types::{Deploy, FinalitySignature},
+    utils::work_queue::{BoundedWorkQueue, BoundedWorkQueueMetrics, JobClassLimits},
+};
+
+/// The most batches of each kind waiting for verification.
+const QUEUE_CAPACITY: usize = 4096;
+
+static POOL: Lazy<ThreadPool> = Lazy::new(|| {
+    ThreadPoolBuilder::new()
+        .num_threads(num_cpus::get())
+        .thread_name(|index| format!("sig-verify-{}", index))
+        .build()
+        .expect("failed to build signature verification thread pool")
+});
+
+/// The kinds of items verified, in order of priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum VerificationKind {
+    FinalitySignatures,
+    Deploys,
+}
+
+impl Display for VerificationKind {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationKind::FinalitySignatures => write!(formatter, "finality_signatures"),
+            VerificationKind::Deploys => write!(formatter, "deploys"),
+        }
+    }
+}
+
+/// A batch of items to verify, along with the means to hand them back.
+type VerificationJob = Box<dyn FnOnce() + Send>;
+
+/// The queue of batches of a reactor waiting for verification on the shared pool.
+///
+/// Cheap to clone, with every clone sharing the queue, so that it can be handed to each component
+/// verifying signatures.
+#[derive(Clone)]
+pub(crate) struct SignatureVerifier {
+    queue: Arc<BoundedWorkQueue<VerificationKind, VerificationJob>>,
+}
+
+impl SignatureVerifier {
+    /// Creates a verifier with an empty queue, whose metrics are not registered until
+    /// [`SignatureVerifier::register_metrics`] is called.
+    pub(crate) fn new() -> Result<Self, prometheus::Error> {
+        let limits = |weight| JobClassLimits {
+            weight: NonZeroUsize::new(weight).expect("weight should be non-zero"),
+            capacity: NonZeroUsize::new(QUEUE_CAPACITY).expect("capacity should be non-zero"),
+        };
+        let queue = BoundedWorkQueue::new(
+            "signature_verification",
+            vec![
+                (VerificationKind::FinalitySignatures, limits(4)),
+                (VerificationKind::Deploys, limits(1)),
+            ],
+        )?;
+        Ok(SignatureVerifier {
+            queue: Arc::new(queue),
+        })
+    }
+
+    /// Registers the metrics of the verification queue with `registry`, until the returned value
+    /// is dropped.
+    pub(crate) fn register_metrics(
+        &self,
+        registry: &Registry,
+    ) -> Result<BoundedWorkQueueMetrics, prometheus::Error> {
+        self.queue.register_metrics(registry)
+    }
+
+    /// Runs `verify` on each of `items` in parallel on the verification pool and returns the items
+    /// once all are done.
+    async fn verify_on_pool<T, F>(self, kind: VerificationKind, items: Vec<T>, verify: F) -> Vec<T>
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) + Send + Sync + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let job: VerificationJob = Box::new(move || {
+            items.par_iter().for_each(verify);
+            let _ = sender.send(items);
+        });
+        self.queue.push_job(kind, job).await;
+        // Every job pushed is matched by exactly one task on the pool, which runs whichever job of
+        // the queue is due next, not necessarily this one.
+        let queue = self.queue;
+        POOL.spawn(move || {
+            if let Some((_, job)) = queue.pop_job() {
+                job();
+            }
+        });
+        receiver
+            .await
+            .expect("signature verification task should not panic")
+    }
+
+    /// Verifies the approvals of each of `deploys`, caching the outcome in the deploy.
+    pub(crate) async fn verify_deploys(self, deploys: Vec<Box<Deploy>>) -> Vec<Box<Deploy>> {
+        self.verify_on_pool(VerificationKind::Deploys, deploys, |deploy| {
+            let _ = deploy.is_valid();
+        })
+        .await
+    }
+
+    /// Verifies the approvals of `deploy`, caching the outcome in the deploy.
+    pub(crate) async fn verify_deploy(self, deploy: Box<Deploy>) -> Box<Deploy> {
+        self.verify_deploys(vec![deploy])
+            .await
+            .pop()
+            .expect("should return the deploy")
+    }
+
+    /// Verifies each of `finality_signatures`, caching the outcome in the signature.
+    pub(crate) async fn verify_finality_signatures(
+        self,
+        finality_signatures: Vec<Box<FinalitySignature>>,
+    ) -> Vec<Box<FinalitySignature>> {
+        self.verify_on_pool(
+            VerificationKind::FinalitySignatures,
+            finality_signatures,
+            |finality_signature| {
+                let _ = finality_signature.is_verified();
+            },
+        )
+        .await
+    }
+
+    /// Verifies `finality_signature`, caching the outcome in the signature.
+    pub(crate) async fn verify_finality_signature(
+        self,
+        finality_signature: Box<FinalitySignature>,
+    ) -> Box<FinalitySignature> {
+        self.verify_finality_signatures(vec![finality_signature])
+            .await
+            .pop()
+            .expect("should return the finality signature")
+    }
+}
+
+impl Debug for SignatureVerifier {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("SignatureVerifier").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+    use crate::types::BlockHash;
+
+    #[tokio::test]
+    async fn should_verify_and_cache_finality_signatures() {
+        let mut rng = TestRng::new();
+        let signed = FinalitySignature::random_for_block(BlockHash::random(&mut rng), 1);
+        let valid = FinalitySignature::new(
+            signed.block_hash,
+            signed.era_id,
+            signed.signature,
+            signed.public_key.clone(),
+        );
+        let invalid = FinalitySignature::new(
+            BlockHash::random(&mut rng),
+            signed.era_id,
+            signed.signature,
+            signed.public_key.clone(),
+        );
+        assert!(!valid.is_verification_cached());
+        assert!(!invalid.is_verification_cached());
+
+        let verified = SignatureVerifier::new()
+            .unwrap()
+            .verify_finality_signatures(vec![Box::new(valid), Box::new(invalid)])
+            .await;
+
+        assert!(verified
+            .iter()
+            .all(|finality_signature| finality_signature.is_verification_cached()));
+        assert!(verified[0].is_verified().is_ok());
+        assert!(verified[1].is_verified().is_err());
+    }
+}
@@ -1,16 +1,27 @@
-//! Work queue for finite work.
+//! Work queues.
 //!
-//! A queue that allows for processing a variable amount of work that may spawn more jobs, but is
+//! [`WorkQueue`] allows for processing a variable amount of work that may spawn more jobs, but is
 //! expected to finish eventually.
+//!
+//! [`BoundedWorkQueue`] holds jobs of several classes, each with its own capacity, and hands them
+//! out by weighted round-robin in order of priority. Producers wait for capacity rather than
+//! growing the queue without limit, and the number of queued, waiting and rejected jobs of each
+//! class is exported as metrics, so that backpressure is visible instead of turning into memory
+//! growth.
 
 use std::{
     collections::VecDeque,
+    fmt::{Debug, Display},
+    num::NonZeroUsize,
     sync::{Arc, Mutex},
 };
 
 use futures::{stream, Stream};
+use prometheus::{IntCounter, IntGauge, Registry};
 use tokio::sync::Notify;
 
+use crate::unregister_metric;
+
 /// Multi-producer, multi-consumer async job queue with end conditions.
 ///
 /// Keeps track of in-progress jobs and can indicate to workers that all work has been finished.
@@ -221,9 +232,294 @@ impl<T> Drop for JobHandle<T> {
     }
 }
 
+/// The limits of one class of jobs of a [`BoundedWorkQueue`].
+#[derive(Clone, Copy, Debug)]
+pub struct JobClassLimits {
+    /// The number of jobs of the class handed out in a row before moving on to the next class.
+    pub weight: NonZeroUsize,
+    /// The most jobs of the class held by the queue at once.
+    pub capacity: NonZeroUsize,
+}
+
+/// Bounded, prioritized multi-producer, multi-consumer job queue.
+///
+/// Jobs are pushed under a class, each with its own capacity. Classes are given in order of
+/// priority, and jobs are handed out by weighted round-robin: up to `weight` jobs of a class are
+/// popped before moving on to the next class which has any, so that a busy class of high priority
+/// cannot starve the others.
+///
+/// Pushing a job of a full class either fails, with [`BoundedWorkQueue::try_push_job`], or waits
+/// until a job of the class is popped, with [`BoundedWorkQueue::push_job`].
+#[derive(Debug)]
+pub struct BoundedWorkQueue<K, T> {
+    /// Inner workings of the queue.
+    inner: Mutex<BoundedQueueInner<K, T>>,
+    /// Notifier for tasks waiting for a job.
+    job_pushed: Notify,
+    /// Notifier for tasks waiting for capacity.
+    job_popped: Notify,
+}
+
+/// Bounded queue inner state.
+#[derive(Debug)]
+struct BoundedQueueInner<K, T> {
+    /// The classes of jobs, in order of priority.
+    classes: Vec<JobClass<K, T>>,
+    /// Index of the class currently being served.
+    current: usize,
+    /// Number of jobs handed out from the current class in the current round.
+    served: usize,
+}
+
+/// The jobs of one class, with their limits and metrics.
+#[derive(Debug)]
+struct JobClass<K, T> {
+    key: K,
+    limits: JobClassLimits,
+    jobs: VecDeque<T>,
+    metrics: JobClassMetrics,
+}
+
+/// The metrics of one class of jobs.
+#[derive(Clone, Debug)]
+struct JobClassMetrics {
+    /// Number of jobs in the queue.
+    queued: IntGauge,
+    /// Number of pushes which had to wait for capacity.
+    waited: IntCounter,
+    /// Number of pushes rejected for lack of capacity.
+    rejected: IntCounter,
+}
+
+impl JobClassMetrics {
+    fn new(queue_name: &str, class_name: &str) -> Result<Self, prometheus::Error> {
+        Ok(JobClassMetrics {
+            queued: IntGauge::new(
+                format!("{}_{}_queued", queue_name, class_name),
+                format!(
+                    "current number of {} jobs in the {} queue",
+                    class_name, queue_name
+                ),
+            )?,
+            waited: IntCounter::new(
+                format!("{}_{}_waited", queue_name, class_name),
+                format!(
+                    "number of {} jobs which waited for capacity in the {} queue",
+                    class_name, queue_name
+                ),
+            )?,
+            rejected: IntCounter::new(
+                format!("{}_{}_rejected", queue_name, class_name),
+                format!(
+                    "number of {} jobs rejected by the full {} queue",
+                    class_name, queue_name
+                ),
+            )?,
+        })
+    }
+}
+
+impl<K, T> BoundedQueueInner<K, T>
+where
+    K: Copy + Eq + Debug,
+{
+    fn class_mut(&mut self, key: K) -> &mut JobClass<K, T> {
+        self.classes
+            .iter_mut()
+            .find(|class| class.key == key)
+            .unwrap_or_else(|| panic!("job class {:?} was not configured", key))
+    }
+
+    /// Pushes `job` if its class has capacity left, otherwise returns it.
+    fn push(&mut self, key: K, job: T) -> Result<(), T> {
+        let class = self.class_mut(key);
+        if class.jobs.len() >= class.limits.capacity.get() {
+            return Err(job);
+        }
+        class.jobs.push_back(job);
+        class.metrics.queued.inc();
+        Ok(())
+    }
+
+    /// Pops the next job by weighted round-robin.
+    fn pop(&mut self) -> Option<(K, T)> {
+        // One pass over every class, plus a return to the current one with a fresh round.
+        for _ in 0..=self.classes.len() {
+            let class = &mut self.classes[self.current];
+            if self.served < class.limits.weight.get() {
+                if let Some(job) = class.jobs.pop_front() {
+                    class.metrics.queued.dec();
+                    self.served += 1;
+                    return Some((class.key, job));
+                }
+            }
+            self.current = (self.current + 1) % self.classes.len();
+            self.served = 0;
+        }
+        None
+    }
+}
+
+impl<K, T> BoundedWorkQueue<K, T>
+where
+    K: Copy + Eq + Debug + Display,
+{
+    /// Creates a new queue for the given classes of jobs, in order of priority.
+    ///
+    /// The metrics of each class are named after `name` and the class, and are not registered
+    /// until [`BoundedWorkQueue::register_metrics`] is called.
+    pub fn new(name: &str, classes: Vec<(K, JobClassLimits)>) -> Result<Self, prometheus::Error> {
+        assert!(!classes.is_empty(), "a work queue needs a class of jobs");
+        let classes = classes
+            .into_iter()
+            .map(|(key, limits)| {
+                Ok(JobClass {
+                    key,
+                    limits,
+                    jobs: VecDeque::new(),
+                    metrics: JobClassMetrics::new(name, &key.to_string())?,
+                })
+            })
+            .collect::<Result<_, prometheus::Error>>()?;
+        Ok(BoundedWorkQueue {
+            inner: Mutex::new(BoundedQueueInner {
+                classes,
+                current: 0,
+                served: 0,
+            }),
+            job_pushed: Notify::new(),
+            job_popped: Notify::new(),
+        })
+    }
+
+    /// Registers the metrics of the queue with `registry`, until the returned value is dropped.
+    pub fn register_metrics(
+        &self,
+        registry: &Registry,
+    ) -> Result<BoundedWorkQueueMetrics, prometheus::Error> {
+        let classes: Vec<JobClassMetrics> = self
+            .inner
+            .lock()
+            .expect("lock poisoned")
+            .classes
+            .iter()
+            .map(|class| class.metrics.clone())
+            .collect();
+        for metrics in &classes {
+            registry.register(Box::new(metrics.queued.clone()))?;
+            registry.register(Box::new(metrics.waited.clone()))?;
+            registry.register(Box::new(metrics.rejected.clone()))?;
+        }
+        Ok(BoundedWorkQueueMetrics {
+            classes,
+            registry: registry.clone(),
+        })
+    }
+
+    /// Pushes a job of class `key` onto the queue, waiting for capacity if the class is full.
+    ///
+    /// Panics if `key` is not one of the classes the queue was created with.
+    pub async fn push_job(&self, key: K, mut job: T) {
+        let mut has_waited = false;
+        loop {
+            let waiting;
+            {
+                let mut inner = self.inner.lock().expect("lock poisoned");
+                match inner.push(key, job) {
+                    Ok(()) => {
+                        self.job_pushed.notify_waiters();
+                        return;
+                    }
+                    Err(returned) => {
+                        if !has_waited {
+                            inner.class_mut(key).metrics.waited.inc();
+                            has_waited = true;
+                        }
+                        job = returned;
+                        waiting = self.job_popped.notified();
+                    }
+                }
+            }
+            // As in `WorkQueue::next_job`, the waiter is registered before the lock is released.
+            waiting.await;
+        }
+    }
+
+    /// Pushes a job of class `key` onto the queue, returning it if the class is full.
+    ///
+    /// Panics if `key` is not one of the classes the queue was created with.
+    pub fn try_push_job(&self, key: K, job: T) -> Result<(), T> {
+        let mut inner = self.inner.lock().expect("lock poisoned");
+        match inner.push(key, job) {
+            Ok(()) => {
+                self.job_pushed.notify_waiters();
+                Ok(())
+            }
+            Err(job) => {
+                inner.class_mut(key).metrics.rejected.inc();
+                Err(job)
+            }
+        }
+    }
+
+    /// Pops the next job along with its class, if any is queued.
+    pub fn pop_job(&self) -> Option<(K, T)> {
+        let popped = self.inner.lock().expect("lock poisoned").pop();
+        if popped.is_some() {
+            self.job_popped.notify_waiters();
+        }
+        popped
+    }
+
+    /// Pops the next job along with its class, waiting for one to be pushed if none is queued.
+    pub async fn next_job(&self) -> (K, T) {
+        loop {
+            let waiting;
+            {
+                let mut inner = self.inner.lock().expect("lock poisoned");
+                if let Some(popped) = inner.pop() {
+                    self.job_popped.notify_waiters();
+                    return popped;
+                }
+                waiting = self.job_pushed.notified();
+            }
+            waiting.await;
+        }
+    }
+
+    /// Returns the number of jobs of class `key` in the queue.
+    pub fn num_jobs(&self, key: K) -> usize {
+        self.inner
+            .lock()
+            .expect("lock poisoned")
+            .class_mut(key)
+            .jobs
+            .len()
+    }
+}
+
+/// The metrics of a [`BoundedWorkQueue`], unregistered when dropped.
+#[derive(Debug)]
+pub struct BoundedWorkQueueMetrics {
+    classes: Vec<JobClassMetrics>,
+    registry: Registry,
+}
+
+impl Drop for BoundedWorkQueueMetrics {
+    fn drop(&mut self) {
+        for metrics in &self.classes {
+            unregister_metric!(self.registry, metrics.queued);
+            unregister_metric!(self.registry, metrics.waited);
+            unregister_metric!(self.registry, metrics.rejected);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
+        fmt::{self, Display, Formatter},
+        num::NonZeroUsize,
         sync::{
             atomic::{AtomicU32, Ordering},
             Arc,
@@ -232,9 +528,10 @@ mod tests {
     };
 
     use futures::{FutureExt, StreamExt};
+    use prometheus::Registry;
     use tokio::sync::Notify;
 
-    use super::WorkQueue;
+    use super::{BoundedWorkQueue, JobClassLimits, WorkQueue};
 
     #[derive(Debug)]
     struct TestJob(u32);
@@ -358,4 +655,97 @@ mod tests {
         let expected_total = 285;
         assert_eq!(output.load(Ordering::SeqCst), expected_total);
     }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum TestClass {
+        Urgent,
+        Routine,
+    }
+
+    impl Display for TestClass {
+        fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+            match self {
+                TestClass::Urgent => write!(formatter, "urgent"),
+                TestClass::Routine => write!(formatter, "routine"),
+            }
+        }
+    }
+
+    fn bounded_queue(capacity: usize) -> BoundedWorkQueue<TestClass, u32> {
+        let limits = |weight| JobClassLimits {
+            weight: NonZeroUsize::new(weight).unwrap(),
+            capacity: NonZeroUsize::new(capacity).unwrap(),
+        };
+        BoundedWorkQueue::new(
+            "test_queue",
+            vec![
+                (TestClass::Urgent, limits(2)),
+                (TestClass::Routine, limits(1)),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn bounded_queue_serves_classes_by_weight() {
+        let q = bounded_queue(10);
+        for job in 0..5 {
+            q.try_push_job(TestClass::Urgent, job).unwrap();
+            q.try_push_job(TestClass::Routine, 10 + job).unwrap();
+        }
+
+        let popped: Vec<_> = std::iter::from_fn(|| q.pop_job()).collect();
+        let expected = vec![
+            (TestClass::Urgent, 0),
+            (TestClass::Urgent, 1),
+            (TestClass::Routine, 10),
+            (TestClass::Urgent, 2),
+            (TestClass::Urgent, 3),
+            (TestClass::Routine, 11),
+            (TestClass::Urgent, 4),
+            (TestClass::Routine, 12),
+            (TestClass::Routine, 13),
+            (TestClass::Routine, 14),
+        ];
+        assert_eq!(popped, expected);
+    }
+
+    #[test]
+    fn bounded_queue_rejects_jobs_beyond_capacity() {
+        let registry = Registry::new();
+        let q = bounded_queue(2);
+        let metrics = q.register_metrics(&registry).unwrap();
+
+        q.try_push_job(TestClass::Routine, 1).unwrap();
+        q.try_push_job(TestClass::Routine, 2).unwrap();
+        assert_eq!(q.try_push_job(TestClass::Routine, 3), Err(3));
+        // Other classes are unaffected.
+        q.try_push_job(TestClass::Urgent, 4).unwrap();
+
+        assert_eq!(q.num_jobs(TestClass::Routine), 2);
+        assert_eq!(metrics.classes[1].queued.get(), 2);
+        assert_eq!(metrics.classes[1].rejected.get(), 1);
+        assert_eq!(metrics.classes[0].rejected.get(), 0);
+
+        drop(metrics);
+        assert!(registry.gather().is_empty());
+    }
+
+    #[tokio::test]
+    async fn bounded_queue_push_waits_for_capacity() {
+        let q = Arc::new(bounded_queue(1));
+        q.push_job(TestClass::Routine, 1).await;
+
+        let pusher = {
+            let q = q.clone();
+            tokio::spawn(async move { q.push_job(TestClass::Routine, 2).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!pusher.is_finished());
+        assert_eq!(q.num_jobs(TestClass::Routine), 1);
+
+        assert_eq!(q.next_job().await, (TestClass::Routine, 1));
+        pusher.await.expect("task panicked");
+        assert_eq!(q.next_job().await, (TestClass::Routine, 2));
+    }
 }
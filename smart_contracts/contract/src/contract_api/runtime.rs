@@ -116,6 +116,57 @@ pub fn call_versioned_contract<T: CLTyped + FromBytes>(
     deserialize_contract_result(bytes_written)
 }
 
+/// Calls the given stored contract like [`call_contract`], but leaves the value it returns in the
+/// host buffer rather than reading and deserializing it.
+///
+/// Returns the size in bytes of the serialized return value, which can then be read piecewise via
+/// [`read_call_result_chunk`], e.g. to process a large value without holding all of it in memory.
+/// An empty return value is never buffered.
+pub fn call_contract_unread(
+    contract_hash: ContractHash,
+    entry_point_name: &str,
+    runtime_args: RuntimeArgs,
+) -> usize {
+    let (contract_hash_ptr, contract_hash_size, _bytes1) = contract_api::to_ptr(contract_hash);
+    let (entry_point_name_ptr, entry_point_name_size, _bytes2) =
+        contract_api::to_ptr(entry_point_name);
+    let (runtime_args_ptr, runtime_args_size, _bytes2) = contract_api::to_ptr(runtime_args);
+
+    let mut bytes_written = MaybeUninit::uninit();
+    let ret = unsafe {
+        ext_ffi::casper_call_contract(
+            contract_hash_ptr,
+            contract_hash_size,
+            entry_point_name_ptr,
+            entry_point_name_size,
+            runtime_args_ptr,
+            runtime_args_size,
+            bytes_written.as_mut_ptr(),
+        )
+    };
+    api_error::result_from(ret).unwrap_or_revert();
+    unsafe { bytes_written.assume_init() }
+}
+
+/// Copies the part of the value returned by the last [`call_contract_unread`] starting at `offset`
+/// into `dest`, returning the number of bytes copied.
+///
+/// The value is discarded once its last byte has been read, after which reading fails with
+/// [`ApiError::HostBufferEmpty`].
+pub fn read_call_result_chunk(offset: usize, dest: &mut [u8]) -> Result<usize, ApiError> {
+    let mut bytes_written = MaybeUninit::uninit();
+    let ret = unsafe {
+        ext_ffi::casper_read_host_buffer_chunk(
+            offset,
+            dest.as_mut_ptr(),
+            dest.len(),
+            bytes_written.as_mut_ptr(),
+        )
+    };
+    api_error::result_from(ret)?;
+    Ok(unsafe { bytes_written.assume_init() })
+}
+
 fn deserialize_contract_result<T: CLTyped + FromBytes>(bytes_written: usize) -> T {
     let serialized_result = if bytes_written == 0 {
         // If no bytes were written, the host buffer hasn't been set and hence shouldn't be read.
@@ -278,6 +329,8 @@ pub fn remove_key(name: &str) {
 }
 
 /// Returns the set of [`AccountHash`] from the calling account's context `authorization_keys`.
+///
+/// The host provides the keys in ascending order.
 pub fn list_authorization_keys() -> BTreeSet<AccountHash> {
     let (total_authorization_keys, result_size) = {
         let mut authorization_keys = MaybeUninit::uninit();
@@ -306,6 +359,9 @@ pub fn list_authorization_keys() -> BTreeSet<AccountHash> {
 ///
 /// The current context is either the caller's account or a stored contract depending on whether the
 /// currently-executing module is a direct call or a sub-call respectively.
+///
+/// The host provides the named keys in ascending order of name, so contracts reading the host
+/// buffer directly observe the same order on every node.
 pub fn list_named_keys() -> NamedKeys {
     let (total_keys, result_size) = {
         let mut total_keys = MaybeUninit::uninit();
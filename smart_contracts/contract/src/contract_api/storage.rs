@@ -6,7 +6,7 @@ use core::{convert::From, mem::MaybeUninit};
 use casper_types::{
     api_error,
     bytesrepr::{self, FromBytes, ToBytes},
-    contracts::{ContractVersion, EntryPoints, NamedKeys},
+    contracts::{AllowedCaller, ContractVersion, EntryPoints, NamedKeys},
     AccessRights, ApiError, CLTyped, CLValue, ContractHash, ContractPackageHash, HashAddr, Key,
     URef, DICTIONARY_ITEM_KEY_MAX_LENGTH, UREF_SERIALIZED_LENGTH,
 };
@@ -276,6 +276,56 @@ pub fn remove_contract_user_group(
     api_error::result_from(ret)
 }
 
+/// Allows `caller` to call the named entry point of a contract. The runtime rejects calls to
+/// an entry point restricted to callers unless the calling account or contract is listed. A public
+/// entry point becomes callable by `caller` only. Requires the access key of the contract's
+/// package to be present in the caller's context.
+pub fn add_allowed_caller(
+    contract_hash: ContractHash,
+    entry_point_name: &str,
+    caller: AllowedCaller,
+) -> Result<(), ApiError> {
+    let (contract_hash_ptr, contract_hash_size, _bytes1) = contract_api::to_ptr(contract_hash);
+    let (entry_point_name_ptr, entry_point_name_size, _bytes2) =
+        contract_api::to_ptr(entry_point_name);
+    let (caller_ptr, caller_size, _bytes3) = contract_api::to_ptr(caller);
+    let ret = unsafe {
+        ext_ffi::casper_add_allowed_caller(
+            contract_hash_ptr,
+            contract_hash_size,
+            entry_point_name_ptr,
+            entry_point_name_size,
+            caller_ptr,
+            caller_size,
+        )
+    };
+    api_error::result_from(ret)
+}
+
+/// Stops allowing `caller` to call the named entry point of a contract. Once the last caller
+/// is removed, the entry point is no longer callable from outside the contract.
+pub fn remove_allowed_caller(
+    contract_hash: ContractHash,
+    entry_point_name: &str,
+    caller: AllowedCaller,
+) -> Result<(), ApiError> {
+    let (contract_hash_ptr, contract_hash_size, _bytes1) = contract_api::to_ptr(contract_hash);
+    let (entry_point_name_ptr, entry_point_name_size, _bytes2) =
+        contract_api::to_ptr(entry_point_name);
+    let (caller_ptr, caller_size, _bytes3) = contract_api::to_ptr(caller);
+    let ret = unsafe {
+        ext_ffi::casper_remove_allowed_caller(
+            contract_hash_ptr,
+            contract_hash_size,
+            entry_point_name_ptr,
+            entry_point_name_size,
+            caller_ptr,
+            caller_size,
+        )
+    };
+    api_error::result_from(ret)
+}
+
 /// Add a new version of a contract to the contract stored at the given
 /// `Key`. Note that this contract must have been created by
 /// `create_contract` or `create_contract_package_at_hash` first.
@@ -341,6 +391,29 @@ pub fn disable_contract_version(
     api_error::result_from(result)
 }
 
+/// Pauses or resumes all the versions of the contract stored at the given `ContractPackageHash`.
+///
+/// While paused, none of the package's entry points can be called from outside the package,
+/// whether through `call_versioned_contract` or `call_contract` with the hash of one of its
+/// versions.  The caller must hold the package's access key, and the package must not be locked.
+pub fn set_contract_package_paused(
+    contract_package_hash: ContractPackageHash,
+    paused: bool,
+) -> Result<(), ApiError> {
+    let (contract_package_hash_ptr, contract_package_hash_size, _bytes) =
+        contract_api::to_ptr(contract_package_hash);
+
+    let result = unsafe {
+        ext_ffi::casper_set_contract_package_paused(
+            contract_package_hash_ptr,
+            contract_package_hash_size,
+            paused as u32,
+        )
+    };
+
+    api_error::result_from(result)
+}
+
 /// Creates new [`URef`] that represents a seed for a dictionary partition of the global state and
 /// puts it under named keys.
 pub fn new_dictionary(dictionary_name: &str) -> Result<URef, ApiError> {
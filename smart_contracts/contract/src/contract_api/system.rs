@@ -195,6 +195,25 @@ pub fn transfer_from_purse_to_public_key(
     transfer_from_purse_to_account(source, target, amount, id)
 }
 
+/// Transfers motes from `source` purse to each of the `(target, amount, id)` entries of
+/// `transfers`, creating the target accounts which do not exist yet.
+///
+/// At most [`casper_types::MAX_TRANSFER_BATCH_SIZE`] transfers can be made in one call.  The
+/// balance of `source` is checked and debited once for the total amount, which makes this
+/// considerably cheaper than the equivalent sequence of [`transfer_from_purse_to_account`] calls.
+/// The batch is atomic: if any transfer fails, none is made and no account is created.
+pub fn transfer_batch(
+    source: URef,
+    transfers: &[(AccountHash, U512, Option<u64>)],
+) -> Result<(), ApiError> {
+    let (source_ptr, source_size, _bytes1) = contract_api::to_ptr(source);
+    let (transfers_ptr, transfers_size, _bytes2) = contract_api::to_ptr(transfers.to_vec());
+    let result = unsafe {
+        ext_ffi::casper_transfer_batch(source_ptr, source_size, transfers_ptr, transfers_size)
+    };
+    api_error::result_from(result)
+}
+
 /// Transfers `amount` of motes from `source` purse to `target` purse.  If `target` does not exist
 /// the transfer fails.
 pub fn transfer_from_purse_to_purse(
@@ -67,12 +67,24 @@ extern "C" {
     /// The data will be available through the host buffer and can be copied to Wasm memory through
     /// [`casper_read_host_buffer`].
     ///
+    /// The keys are serialized as a list in ascending order of account hash.
+    ///
     /// # Arguments
     ///
     /// * `total_keys`: number of authorization keys used to sign this deploy
     /// * `result_size`: size of the data loaded in the host
     pub fn casper_load_authorization_keys(total_keys: *mut usize, result_size: *mut usize) -> i32;
+    /// This function loads the named keys of the current context from the host.
+    /// The data will be available through the host buffer and can be copied to Wasm memory through
+    /// [`casper_read_host_buffer`].
+    ///
+    /// The named keys are serialized as a map in ascending order of name, comparing the UTF-8
+    /// bytes of the names, whatever the order they were added in.
     ///
+    /// # Arguments
+    ///
+    /// * `total_keys`: number of named keys in the current context
+    /// * `result_size`: size of the data loaded in the host
     pub fn casper_load_named_keys(total_keys: *mut usize, result_size: *mut usize) -> i32;
     /// This function causes a `Trap`, terminating the currently running module,
     /// but first copies the bytes from `value_ptr` to `value_ptr + value_size` to
@@ -790,4 +802,96 @@ extern "C" {
     /// * `out_ptr` - pointer to the location where argument bytes will be copied from the host side
     /// * `out_size` - size of output pointer
     pub fn casper_random_bytes(out_ptr: *mut u8, out_size: usize) -> i32;
+    /// Copies part of the host buffer, starting at `offset`, into the given buffer.
+    ///
+    /// Unlike [`casper_read_host_buffer`], the host buffer is kept until its last byte has been
+    /// read, so a large value returned by a called contract can be read in several chunks.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - offset into the host buffer of the first byte to copy
+    /// * `dest_ptr` - pointer (offset in wasm memory) to the location where the chunk should be
+    ///   written
+    /// * `dest_size` - size of output buffer
+    /// * `bytes_written` - a pointer to a value where amount of bytes written will be set
+    pub fn casper_read_host_buffer_chunk(
+        offset: usize,
+        dest_ptr: *mut u8,
+        dest_size: usize,
+        bytes_written: *mut usize,
+    ) -> i32;
+    /// Allows an account or contract to call the given entry point of a contract.
+    ///
+    /// # Arguments
+    ///
+    /// * `contract_hash_ptr` - pointer to serialized contract hash.
+    /// * `contract_hash_size` - size of contract hash in serialized form.
+    /// * `entry_point_name_ptr` - pointer to serialized entry point name
+    /// * `entry_point_name_size` - size of serialized entry point name
+    /// * `caller_ptr` - pointer to serialized allowed caller
+    /// * `caller_size` - size of serialized allowed caller
+    pub fn casper_add_allowed_caller(
+        contract_hash_ptr: *const u8,
+        contract_hash_size: usize,
+        entry_point_name_ptr: *const u8,
+        entry_point_name_size: usize,
+        caller_ptr: *const u8,
+        caller_size: usize,
+    ) -> i32;
+    /// Stops allowing an account or contract to call the given entry point of a contract.
+    ///
+    /// # Arguments
+    ///
+    /// * `contract_hash_ptr` - pointer to serialized contract hash.
+    /// * `contract_hash_size` - size of contract hash in serialized form.
+    /// * `entry_point_name_ptr` - pointer to serialized entry point name
+    /// * `entry_point_name_size` - size of serialized entry point name
+    /// * `caller_ptr` - pointer to serialized allowed caller
+    /// * `caller_size` - size of serialized allowed caller
+    pub fn casper_remove_allowed_caller(
+        contract_hash_ptr: *const u8,
+        contract_hash_size: usize,
+        entry_point_name_ptr: *const u8,
+        entry_point_name_size: usize,
+        caller_ptr: *const u8,
+        caller_size: usize,
+    ) -> i32;
+    /// Transfers motes from a purse to each of a list of accounts, creating the accounts which do
+    /// not exist yet.
+    ///
+    /// The balance of the source purse is checked against the total amount once, before any
+    /// transfer is made; transfers back to the source purse are left out.  Each account created is
+    /// charged like a `casper_transfer_to_account` call.  The batch is atomic: if any transfer
+    /// fails, none of them is made.
+    ///
+    /// # Arguments
+    ///
+    /// * `source_ptr` - pointer in wasm memory to bytes representing the source `URef` to transfer
+    ///   from
+    /// * `source_size` - size of the source `URef` (in bytes)
+    /// * `transfers_ptr` - pointer in wasm memory to a serialized list of at most
+    ///   `MAX_TRANSFER_BATCH_SIZE` (target account hash, amount, id) tuples
+    /// * `transfers_size` - size of the serialized list of transfers (in bytes)
+    pub fn casper_transfer_batch(
+        source_ptr: *const u8,
+        source_size: usize,
+        transfers_ptr: *const u8,
+        transfers_size: usize,
+    ) -> i32;
+    /// Pauses or resumes every contract version of a contract package.
+    ///
+    /// While a package is paused, any call from outside the package to an entry point of one of
+    /// its contracts fails, including calls naming a contract hash directly.  Only the owner of
+    /// the package can change the flag, and a locked package can't be paused.
+    ///
+    /// # Arguments
+    ///
+    /// * `contract_package_hash_ptr` - pointer to serialized contract package hash.
+    /// * `contract_package_hash_size` - size of contract package hash in serialized form.
+    /// * `paused` - 1 to pause the package, 0 to resume it
+    pub fn casper_set_contract_package_paused(
+        contract_package_hash_ptr: *const u8,
+        contract_package_hash_size: usize,
+        paused: u32,
+    ) -> i32;
 }
@@ -0,0 +1,69 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    account::AccountHash,
+    contracts::{AllowedCaller, EntryPoint, EntryPoints},
+    ApiError, CLType, ContractHash, EntryPointAccess, EntryPointType,
+};
+
+const ENTRY_POINT_PING: &str = "ping";
+const HASH_KEY_NAME: &str = "restricted_hash";
+const PACKAGE_HASH_KEY_NAME: &str = "restricted_package_hash";
+const ACCESS_KEY_NAME: &str = "restricted_access";
+const ARG_ACTION: &str = "action";
+const ARG_CONTRACT_HASH: &str = "contract_hash";
+const ARG_ACCOUNT: &str = "account";
+const ACTION_INSTALL: &str = "install";
+const ACTION_ALLOW: &str = "allow";
+const ACTION_DISALLOW: &str = "disallow";
+
+#[no_mangle]
+pub extern "C" fn ping() {}
+
+fn install() {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_PING.to_string(),
+        vec![],
+        CLType::Unit,
+        EntryPointAccess::callers(&[]),
+        EntryPointType::Contract,
+    ));
+    let (contract_hash, _) = storage::new_contract(
+        entry_points,
+        None,
+        Some(PACKAGE_HASH_KEY_NAME.to_string()),
+        Some(ACCESS_KEY_NAME.to_string()),
+    );
+    runtime::put_key(HASH_KEY_NAME, contract_hash.into());
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let action: String = runtime::get_named_arg(ARG_ACTION);
+    match action.as_str() {
+        ACTION_INSTALL => install(),
+        ACTION_ALLOW | ACTION_DISALLOW => {
+            let contract_hash: ContractHash = runtime::get_named_arg(ARG_CONTRACT_HASH);
+            let account: AccountHash = runtime::get_named_arg(ARG_ACCOUNT);
+            let caller = AllowedCaller::Account(account);
+            if action == ACTION_ALLOW {
+                storage::add_allowed_caller(contract_hash, ENTRY_POINT_PING, caller)
+            } else {
+                storage::remove_allowed_caller(contract_hash, ENTRY_POINT_PING, caller)
+            }
+            .unwrap_or_revert();
+        }
+        _ => runtime::revert(ApiError::InvalidArgument),
+    }
+}
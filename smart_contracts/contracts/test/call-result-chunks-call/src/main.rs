@@ -0,0 +1,77 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::{vec, vec::Vec};
+
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    bytesrepr::{self, Bytes},
+    runtime_args, ApiError, ContractHash, RuntimeArgs,
+};
+
+const ENTRY_POINT_RETURN_BYTES: &str = "return_bytes";
+const ARG_SIZE: &str = "size";
+const ARG_CHUNK_SIZE: &str = "chunk_size";
+const CONTRACT_HASH_KEY: &str = "call_result_chunks_hash";
+const RESULT_VALUE: &str = "result_value";
+const RESULT_CHUNK_SIZES: &str = "result_chunk_sizes";
+const RESULT_PAST_END_ERROR: &str = "result_past_end_error";
+const RESULT_AFTER_LAST_CHUNK_ERROR: &str = "result_after_last_chunk_error";
+
+/// Calls the `return_bytes` entry point for a value of `size` bytes and reads the result in
+/// chunks of `chunk_size` bytes, storing the reassembled value, the size of each chunk read and
+/// the error codes of reading past the end and of reading after the last chunk under named keys.
+#[no_mangle]
+pub extern "C" fn call() {
+    let size: u32 = runtime::get_named_arg(ARG_SIZE);
+    let chunk_size: u32 = runtime::get_named_arg(ARG_CHUNK_SIZE);
+    let contract_hash = runtime::get_key(CONTRACT_HASH_KEY)
+        .ok_or(ApiError::MissingKey)
+        .unwrap_or_revert()
+        .into_hash()
+        .ok_or(ApiError::UnexpectedKeyVariant)
+        .map(ContractHash::new)
+        .unwrap_or_revert();
+
+    let total_size = runtime::call_contract_unread(
+        contract_hash,
+        ENTRY_POINT_RETURN_BYTES,
+        runtime_args! {
+            ARG_SIZE => size,
+        },
+    );
+
+    let mut chunk = vec![0; chunk_size as usize];
+    let past_end_error: Option<u32> = runtime::read_call_result_chunk(total_size, &mut chunk)
+        .err()
+        .map(u32::from);
+
+    let mut serialized_value = Vec::with_capacity(total_size);
+    let mut chunk_sizes: Vec<u32> = Vec::new();
+    while serialized_value.len() < total_size {
+        let bytes_written =
+            runtime::read_call_result_chunk(serialized_value.len(), &mut chunk).unwrap_or_revert();
+        chunk_sizes.push(bytes_written as u32);
+        serialized_value.extend_from_slice(&chunk[..bytes_written]);
+    }
+    let after_last_chunk_error: Option<u32> = runtime::read_call_result_chunk(0, &mut chunk)
+        .err()
+        .map(u32::from);
+
+    let value: Bytes = bytesrepr::deserialize(serialized_value).unwrap_or_revert();
+    runtime::put_key(RESULT_VALUE, storage::new_uref(value).into());
+    runtime::put_key(RESULT_CHUNK_SIZES, storage::new_uref(chunk_sizes).into());
+    runtime::put_key(
+        RESULT_PAST_END_ERROR,
+        storage::new_uref(past_end_error).into(),
+    );
+    runtime::put_key(
+        RESULT_AFTER_LAST_CHUNK_ERROR,
+        storage::new_uref(after_last_chunk_error).into(),
+    );
+}
@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::{vec, vec::Vec};
+
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    bytesrepr::Bytes, CLType, CLTyped, CLValue, EntryPoint, EntryPointAccess, EntryPointType,
+    EntryPoints, Parameter,
+};
+
+const ENTRY_POINT_RETURN_BYTES: &str = "return_bytes";
+const ARG_SIZE: &str = "size";
+const CONTRACT_HASH_KEY: &str = "call_result_chunks_hash";
+
+/// Returns `size` bytes, the first 256 of which count up from zero while the rest are zero.
+///
+/// Only a prefix is filled in so that large values stay cheap to create.
+#[no_mangle]
+pub extern "C" fn return_bytes() {
+    let size: u32 = runtime::get_named_arg(ARG_SIZE);
+    let mut value: Vec<u8> = vec![0; size as usize];
+    for (index, byte) in value.iter_mut().take(256).enumerate() {
+        *byte = index as u8;
+    }
+    runtime::ret(CLValue::from_t(Bytes::from(value)).unwrap_or_revert())
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_RETURN_BYTES,
+        vec![Parameter::new(ARG_SIZE, CLType::U32)],
+        Bytes::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    let (contract_hash, _version) = storage::new_contract(entry_points, None, None, None);
+    runtime::put_key(CONTRACT_HASH_KEY, contract_hash.into());
+}
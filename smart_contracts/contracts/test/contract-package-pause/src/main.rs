@@ -0,0 +1,91 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    contracts::{EntryPoint, EntryPoints},
+    system::CallStackElement,
+    ApiError, CLType, ContractPackageHash, EntryPointAccess, EntryPointType, RuntimeArgs,
+};
+
+const ENTRY_POINT_PING: &str = "ping";
+const ENTRY_POINT_PAUSE_AND_PING: &str = "pause_and_ping";
+const HASH_KEY_NAME: &str = "pausable_hash";
+const PACKAGE_HASH_KEY_NAME: &str = "pausable_package_hash";
+const ACCESS_KEY_NAME: &str = "pausable_access";
+const ARG_ACTION: &str = "action";
+const ARG_CONTRACT_PACKAGE_HASH: &str = "contract_package_hash";
+const ACTION_INSTALL: &str = "install";
+const ACTION_INSTALL_LOCKED: &str = "install_locked";
+const ACTION_PAUSE: &str = "pause";
+const ACTION_RESUME: &str = "resume";
+
+#[no_mangle]
+pub extern "C" fn ping() {}
+
+/// Pauses the package of the running stored session, then calls `ping` from within the package.
+#[no_mangle]
+pub extern "C" fn pause_and_ping() {
+    let contract_package_hash = match runtime::get_call_stack().last() {
+        Some(CallStackElement::StoredSession {
+            contract_package_hash,
+            ..
+        }) => *contract_package_hash,
+        _ => runtime::revert(ApiError::User(0)),
+    };
+    storage::set_contract_package_paused(contract_package_hash, true).unwrap_or_revert();
+    runtime::call_versioned_contract::<()>(
+        contract_package_hash,
+        None,
+        ENTRY_POINT_PING,
+        RuntimeArgs::default(),
+    );
+}
+
+fn install(locked: bool) {
+    let mut entry_points = EntryPoints::new();
+    for (name, entry_point_type) in [
+        (ENTRY_POINT_PING, EntryPointType::Contract),
+        (ENTRY_POINT_PAUSE_AND_PING, EntryPointType::Session),
+    ] {
+        entry_points.add_entry_point(EntryPoint::new(
+            name.to_string(),
+            vec![],
+            CLType::Unit,
+            EntryPointAccess::Public,
+            entry_point_type,
+        ));
+    }
+    let hash_name = Some(PACKAGE_HASH_KEY_NAME.to_string());
+    let access_name = Some(ACCESS_KEY_NAME.to_string());
+    let (contract_hash, _) = if locked {
+        storage::new_locked_contract(entry_points, None, hash_name, access_name)
+    } else {
+        storage::new_contract(entry_points, None, hash_name, access_name)
+    };
+    runtime::put_key(HASH_KEY_NAME, contract_hash.into());
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let action: String = runtime::get_named_arg(ARG_ACTION);
+    match action.as_str() {
+        ACTION_INSTALL => install(false),
+        ACTION_INSTALL_LOCKED => install(true),
+        ACTION_PAUSE | ACTION_RESUME => {
+            let contract_package_hash: ContractPackageHash =
+                runtime::get_named_arg(ARG_CONTRACT_PACKAGE_HASH);
+            storage::set_contract_package_paused(contract_package_hash, action == ACTION_PAUSE)
+                .unwrap_or_revert();
+        }
+        _ => runtime::revert(ApiError::InvalidArgument),
+    }
+}
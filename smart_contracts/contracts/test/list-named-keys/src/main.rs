@@ -3,31 +3,73 @@
 
 extern crate alloc;
 
-use alloc::{string::String, vec::Vec};
+use alloc::{string::String, vec, vec::Vec};
+use core::mem::MaybeUninit;
 
-use casper_contract::contract_api::runtime;
-use casper_types::contracts::NamedKeys;
+use casper_contract::{contract_api::runtime, ext_ffi, unwrap_or_revert::UnwrapOrRevert};
+use casper_types::{api_error, bytesrepr, contracts::NamedKeys, ApiError, Key};
 
 const ARG_INITIAL_NAMED_KEYS: &str = "initial_named_args";
 const ARG_NEW_NAMED_KEYS: &str = "new_named_keys";
 
+/// Returns the named keys in the order the host wrote them to the host buffer.
+fn named_keys_in_host_order() -> Vec<(String, Key)> {
+    let mut total_keys = MaybeUninit::uninit();
+    let mut result_size = 0;
+    let ret = unsafe {
+        ext_ffi::casper_load_named_keys(total_keys.as_mut_ptr(), &mut result_size as *mut usize)
+    };
+    api_error::result_from(ret).unwrap_or_revert();
+    if unsafe { total_keys.assume_init() } == 0 {
+        return Vec::new();
+    }
+
+    let mut bytes = vec![0u8; result_size];
+    let mut bytes_written = MaybeUninit::uninit();
+    let ret = unsafe {
+        ext_ffi::casper_read_host_buffer(
+            bytes.as_mut_ptr(),
+            bytes.len(),
+            bytes_written.as_mut_ptr(),
+        )
+    };
+    api_error::result_from(ret).unwrap_or_revert();
+    if unsafe { bytes_written.assume_init() } != result_size {
+        runtime::revert(ApiError::HostBufferEmpty);
+    }
+    bytesrepr::deserialize(bytes).unwrap_or_revert()
+}
+
+/// Asserts that `list_named_keys()` returns `expected_named_keys`, and that the host provides them
+/// in ascending order of name.
+fn assert_named_keys(expected_named_keys: &NamedKeys) {
+    let actual_named_keys = runtime::list_named_keys();
+    assert_eq!(*expected_named_keys, actual_named_keys);
+
+    let host_order = named_keys_in_host_order();
+    let expected_order: Vec<(String, Key)> = expected_named_keys
+        .iter()
+        .map(|(name, key)| (name.clone(), *key))
+        .collect();
+    assert_eq!(host_order, expected_order);
+}
+
 #[no_mangle]
 pub extern "C" fn call() {
     // Account starts with two known named keys: mint uref & handle payment uref.
     let expected_initial_named_keys: NamedKeys = runtime::get_named_arg(ARG_INITIAL_NAMED_KEYS);
+    assert_named_keys(&expected_initial_named_keys);
 
-    let actual_named_keys = runtime::list_named_keys();
-    assert_eq!(expected_initial_named_keys, actual_named_keys);
-
-    // Add further named keys and assert that each is returned in `list_named_keys()`.
-    let new_named_keys: NamedKeys = runtime::get_named_arg(ARG_NEW_NAMED_KEYS);
+    // Add further named keys and assert that each is returned in `list_named_keys()`.  The new
+    // named keys are passed as a list, so they are added in the order chosen by the caller rather
+    // than in order of name.
+    let new_named_keys: Vec<(String, Key)> = runtime::get_named_arg(ARG_NEW_NAMED_KEYS);
     let mut expected_named_keys = expected_initial_named_keys;
 
     for (key, value) in new_named_keys {
         runtime::put_key(&key, value);
         assert!(expected_named_keys.insert(key, value).is_none());
-        let actual_named_keys = runtime::list_named_keys();
-        assert_eq!(expected_named_keys, actual_named_keys);
+        assert_named_keys(&expected_named_keys);
     }
 
     // Remove all named keys and check that removed keys aren't returned in `list_named_keys()`.
@@ -35,7 +77,6 @@ pub extern "C" fn call() {
     for key in all_key_names {
         runtime::remove_key(&key);
         assert!(expected_named_keys.remove(&key).is_some());
-        let actual_named_keys = runtime::list_named_keys();
-        assert_eq!(expected_named_keys, actual_named_keys);
+        assert_named_keys(&expected_named_keys);
     }
 }
@@ -0,0 +1,26 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use casper_contract::contract_api::{account, runtime, storage, system};
+use casper_types::{account::AccountHash, U512};
+
+const ARG_TRANSFERS: &str = "transfers";
+const TRANSFER_BATCH_RESULT: &str = "transfer_batch_result";
+
+/// Makes the given transfers from the main purse as a batch, storing the error code of a failed
+/// batch under a named key instead of reverting.
+#[no_mangle]
+pub extern "C" fn call() {
+    let transfers: Vec<(AccountHash, U512, Option<u64>)> = runtime::get_named_arg(ARG_TRANSFERS);
+    let source = account::get_main_purse();
+
+    let maybe_error_code: Option<u32> = system::transfer_batch(source, &transfers)
+        .err()
+        .map(u32::from);
+    let result_uref = storage::new_uref(maybe_error_code);
+    runtime::put_key(TRANSFER_BATCH_RESULT, result_uref.into());
+}
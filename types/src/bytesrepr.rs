@@ -1343,6 +1343,27 @@ mod tests {
         assert_eq!(result.unwrap_err(), Error::Formatting);
     }
 
+    #[test]
+    fn should_serialize_maps_and_sets_in_ascending_order() {
+        // Host functions hand collections to Wasm in this encoding, so their order is observable.
+        let names = ["b", "B", "ab", "a", "\u{e9}", "", "aa"];
+        let mut map = BTreeMap::new();
+        let mut set = BTreeSet::new();
+        for (index, name) in names.iter().enumerate() {
+            map.insert(name.to_string(), index as u8);
+            set.insert(name.to_string());
+        }
+        let mut sorted_names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        sorted_names.sort_unstable_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+
+        let map_entries: Vec<(String, u8)> = deserialize(map.to_bytes().unwrap()).unwrap();
+        let map_names: Vec<String> = map_entries.into_iter().map(|(name, _)| name).collect();
+        assert_eq!(map_names, sorted_names);
+
+        let set_entries: Vec<String> = deserialize(set.to_bytes().unwrap()).unwrap();
+        assert_eq!(set_entries, sorted_names);
+    }
+
     #[cfg(debug_assertions)]
     #[test]
     #[should_panic(expected = "You should use Bytes newtype wrapper for efficiency")]
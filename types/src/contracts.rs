@@ -22,7 +22,7 @@ use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize, Seria
 
 use crate::{
     account,
-    account::TryFromSliceForAccountHashError,
+    account::{AccountHash, TryFromSliceForAccountHashError},
     bytesrepr::{self, FromBytes, ToBytes, U32_SERIALIZED_LENGTH},
     checksummed_hex,
     contract_wasm::ContractWasmHash,
@@ -35,6 +35,8 @@ use crate::{
 pub const MAX_GROUPS: u8 = 10;
 /// Maximum number of URefs which can be assigned across all user groups.
 pub const MAX_TOTAL_UREFS: usize = 100;
+/// Maximum number of callers which can be allowed to call a single entry point.
+pub const MAX_ALLOWED_CALLERS: usize = 100;
 
 const CONTRACT_STRING_PREFIX: &str = "contract-";
 const PACKAGE_STRING_PREFIX: &str = "contract-package-";
@@ -106,6 +108,31 @@ pub enum Error {
     /// assert_eq!(9, Error::URefAlreadyExists as u8);
     /// ```
     URefAlreadyExists = 9,
+    /// Attempted to change the allowed callers of an entry point which does not exist.
+    /// ```
+    /// # use casper_types::contracts::Error;
+    /// assert_eq!(10, Error::EntryPointNotFound as u8);
+    /// ```
+    EntryPointNotFound = 10,
+    /// Attempted to allow a new caller, which resulted in the number of allowed callers of the
+    /// entry point to exceed the allowed maximum.
+    /// ```
+    /// # use casper_types::contracts::Error;
+    /// assert_eq!(11, Error::MaxAllowedCallersExceeded as u8);
+    /// ```
+    MaxAllowedCallersExceeded = 11,
+    /// Attempted to allow a caller on an entry point restricted to user groups.
+    /// ```
+    /// # use casper_types::contracts::Error;
+    /// assert_eq!(12, Error::EntryPointRestrictedToGroups as u8);
+    /// ```
+    EntryPointRestrictedToGroups = 12,
+    /// Attempted to remove a caller which is not allowed to call the entry point.
+    /// ```
+    /// # use casper_types::contracts::Error;
+    /// assert_eq!(13, Error::UnableToRemoveCaller as u8);
+    /// ```
+    UnableToRemoveCaller = 13,
 }
 
 impl TryFrom<u8> for Error {
@@ -122,6 +149,12 @@ impl TryFrom<u8> for Error {
             v if v == Self::UnableToRemoveURef as u8 => Self::UnableToRemoveURef,
             v if v == Self::GroupInUse as u8 => Self::GroupInUse,
             v if v == Self::URefAlreadyExists as u8 => Self::URefAlreadyExists,
+            v if v == Self::EntryPointNotFound as u8 => Self::EntryPointNotFound,
+            v if v == Self::MaxAllowedCallersExceeded as u8 => Self::MaxAllowedCallersExceeded,
+            v if v == Self::EntryPointRestrictedToGroups as u8 => {
+                Self::EntryPointRestrictedToGroups
+            }
+            v if v == Self::UnableToRemoveCaller as u8 => Self::UnableToRemoveCaller,
             _ => return Err(()),
         };
         Ok(error)
@@ -1158,6 +1191,57 @@ impl Contract {
         self.named_keys.append(keys);
     }
 
+    /// Allows `caller` to call the entry point named `entry_point_name`.
+    ///
+    /// A public entry point becomes callable by `caller` only.
+    pub fn add_allowed_caller(
+        &mut self,
+        entry_point_name: &str,
+        caller: AllowedCaller,
+    ) -> Result<(), Error> {
+        let entry_point = self
+            .entry_points
+            .0
+            .get_mut(entry_point_name)
+            .ok_or(Error::EntryPointNotFound)?;
+        match &mut entry_point.access {
+            EntryPointAccess::Public => {
+                entry_point.access = EntryPointAccess::callers(&[caller]);
+            }
+            EntryPointAccess::Groups(_) => return Err(Error::EntryPointRestrictedToGroups),
+            EntryPointAccess::Callers(callers) => {
+                if callers.len() >= MAX_ALLOWED_CALLERS && !callers.contains(&caller) {
+                    return Err(Error::MaxAllowedCallersExceeded);
+                }
+                callers.insert(caller);
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops allowing `caller` to call the entry point named `entry_point_name`.
+    ///
+    /// Once its last caller is removed, the entry point is no longer callable from outside the
+    /// contract.
+    pub fn remove_allowed_caller(
+        &mut self,
+        entry_point_name: &str,
+        caller: &AllowedCaller,
+    ) -> Result<(), Error> {
+        let entry_point = self
+            .entry_points
+            .0
+            .get_mut(entry_point_name)
+            .ok_or(Error::EntryPointNotFound)?;
+        match &mut entry_point.access {
+            EntryPointAccess::Callers(callers) if callers.contains(caller) => {
+                callers.remove(caller);
+                Ok(())
+            }
+            _ => Err(Error::UnableToRemoveCaller),
+        }
+    }
+
     /// Removes given named key.
     pub fn remove_named_key(&mut self, key: &str) -> Option<Key> {
         self.named_keys.remove(key)
@@ -1447,10 +1531,15 @@ pub enum EntryPointAccess {
     /// list is empty then this method is not callable from outside the
     /// contract.
     Groups(Vec<Group>),
+    /// Only the listed accounts and contracts may call this method. Note: if
+    /// the set is empty then this method is not callable from outside the
+    /// contract.
+    Callers(BTreeSet<AllowedCaller>),
 }
 
 const ENTRYPOINTACCESS_PUBLIC_TAG: u8 = 1;
 const ENTRYPOINTACCESS_GROUPS_TAG: u8 = 2;
+const ENTRYPOINTACCESS_CALLERS_TAG: u8 = 3;
 
 impl EntryPointAccess {
     /// Constructor for access granted to only listed groups.
@@ -1458,6 +1547,11 @@ impl EntryPointAccess {
         let list: Vec<Group> = labels.iter().map(|s| Group(String::from(*s))).collect();
         EntryPointAccess::Groups(list)
     }
+
+    /// Constructor for access granted to only listed callers.
+    pub fn callers(callers: &[AllowedCaller]) -> Self {
+        EntryPointAccess::Callers(callers.iter().copied().collect())
+    }
 }
 
 impl ToBytes for EntryPointAccess {
@@ -1472,6 +1566,10 @@ impl ToBytes for EntryPointAccess {
                 result.push(ENTRYPOINTACCESS_GROUPS_TAG);
                 result.append(&mut groups.to_bytes()?);
             }
+            EntryPointAccess::Callers(callers) => {
+                result.push(ENTRYPOINTACCESS_CALLERS_TAG);
+                result.append(&mut callers.to_bytes()?);
+            }
         }
         Ok(result)
     }
@@ -1480,6 +1578,7 @@ impl ToBytes for EntryPointAccess {
         match self {
             EntryPointAccess::Public => 1,
             EntryPointAccess::Groups(groups) => 1 + groups.serialized_length(),
+            EntryPointAccess::Callers(callers) => 1 + callers.serialized_length(),
         }
     }
 
@@ -1492,6 +1591,10 @@ impl ToBytes for EntryPointAccess {
                 writer.push(ENTRYPOINTACCESS_GROUPS_TAG);
                 groups.write_bytes(writer)?;
             }
+            EntryPointAccess::Callers(callers) => {
+                writer.push(ENTRYPOINTACCESS_CALLERS_TAG);
+                callers.write_bytes(writer)?;
+            }
         }
         Ok(())
     }
@@ -1508,6 +1611,71 @@ impl FromBytes for EntryPointAccess {
                 let result = EntryPointAccess::Groups(groups);
                 Ok((result, bytes))
             }
+            ENTRYPOINTACCESS_CALLERS_TAG => {
+                let (callers, bytes) = BTreeSet::<AllowedCaller>::from_bytes(bytes)?;
+                let result = EntryPointAccess::Callers(callers);
+                Ok((result, bytes))
+            }
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+/// An account or contract which may call an entry point restricted by
+/// [`EntryPointAccess::Callers`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum AllowedCaller {
+    /// Session code or a stored session run by the given account.
+    Account(AccountHash),
+    /// The given stored contract.
+    Contract(ContractHash),
+}
+
+const ALLOWEDCALLER_ACCOUNT_TAG: u8 = 0;
+const ALLOWEDCALLER_CONTRACT_TAG: u8 = 1;
+
+impl ToBytes for AllowedCaller {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut result = bytesrepr::allocate_buffer(self)?;
+        self.write_bytes(&mut result)?;
+        Ok(result)
+    }
+
+    fn serialized_length(&self) -> usize {
+        1 + match self {
+            AllowedCaller::Account(account_hash) => account_hash.serialized_length(),
+            AllowedCaller::Contract(contract_hash) => contract_hash.serialized_length(),
+        }
+    }
+
+    fn write_bytes(&self, writer: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+        match self {
+            AllowedCaller::Account(account_hash) => {
+                writer.push(ALLOWEDCALLER_ACCOUNT_TAG);
+                account_hash.write_bytes(writer)
+            }
+            AllowedCaller::Contract(contract_hash) => {
+                writer.push(ALLOWEDCALLER_CONTRACT_TAG);
+                contract_hash.write_bytes(writer)
+            }
+        }
+    }
+}
+
+impl FromBytes for AllowedCaller {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, bytes) = u8::from_bytes(bytes)?;
+        match tag {
+            ALLOWEDCALLER_ACCOUNT_TAG => {
+                let (account_hash, bytes) = AccountHash::from_bytes(bytes)?;
+                Ok((AllowedCaller::Account(account_hash), bytes))
+            }
+            ALLOWEDCALLER_CONTRACT_TAG => {
+                let (contract_hash, bytes) = ContractHash::from_bytes(bytes)?;
+                Ok((AllowedCaller::Contract(contract_hash), bytes))
+            }
             _ => Err(bytesrepr::Error::Formatting),
         }
     }
@@ -1684,6 +1852,56 @@ mod tests {
         assert!(!contract_package.remove_group(&Group::new("Group 1"))); // Group no longer exists
     }
 
+    #[test]
+    fn should_add_and_remove_allowed_callers() {
+        let mut entry_points = EntryPoints::new();
+        entry_points.add_entry_point(EntryPoint::default_with_name("public"));
+        entry_points.add_entry_point(EntryPoint::new(
+            "grouped",
+            vec![],
+            CLType::Unit,
+            EntryPointAccess::groups(&["Group 1"]),
+            EntryPointType::Contract,
+        ));
+        let mut contract = Contract::new(
+            ContractPackageHash::new([41; 32]),
+            ContractWasmHash::new([43; 32]),
+            NamedKeys::new(),
+            entry_points,
+            ProtocolVersion::V1_0_0,
+        );
+        let account = AllowedCaller::Account(AccountHash::new([1; 32]));
+        let other_contract = AllowedCaller::Contract(ContractHash::new([2; 32]));
+
+        assert_eq!(
+            contract.add_allowed_caller("missing", account),
+            Err(Error::EntryPointNotFound)
+        );
+        assert_eq!(
+            contract.add_allowed_caller("grouped", account),
+            Err(Error::EntryPointRestrictedToGroups)
+        );
+
+        contract.add_allowed_caller("public", account).unwrap();
+        contract
+            .add_allowed_caller("public", other_contract)
+            .unwrap();
+        assert_eq!(
+            contract.entry_point("public").unwrap().access(),
+            &EntryPointAccess::callers(&[account, other_contract])
+        );
+
+        contract.remove_allowed_caller("public", &account).unwrap();
+        assert_eq!(
+            contract.remove_allowed_caller("public", &account),
+            Err(Error::UnableToRemoveCaller)
+        );
+        assert_eq!(
+            contract.entry_point("public").unwrap().access(),
+            &EntryPointAccess::callers(&[other_contract])
+        );
+    }
+
     #[test]
     fn should_disable_contract_version() {
         const CONTRACT_HASH: ContractHash = ContractHash::new([123; 32]);
@@ -23,9 +23,10 @@ use crate::{
         DELEGATION_RATE_DENOMINATOR,
     },
     transfer::TransferAddr,
-    AccessRights, CLType, CLValue, Contract, ContractHash, ContractPackage, ContractVersionKey,
-    ContractWasm, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, EraId, Group, Key,
-    NamedArg, Parameter, Phase, ProtocolVersion, SemVer, StoredValue, URef, U128, U256, U512,
+    AccessRights, AllowedCaller, CLType, CLValue, Contract, ContractHash, ContractPackage,
+    ContractVersionKey, ContractWasm, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints,
+    EraId, Group, Key, NamedArg, Parameter, Phase, ProtocolVersion, SemVer, StoredValue, URef,
+    U128, U256, U512,
 };
 
 use crate::deploy_info::gens::{deploy_hash_arb, transfer_addr_arb};
@@ -276,6 +277,14 @@ pub fn entry_point_access_arb() -> impl Strategy<Value = EntryPointAccess> {
     prop_oneof![
         Just(EntryPointAccess::Public),
         collection::vec(group_arb(), 0..32).prop_map(EntryPointAccess::Groups),
+        collection::btree_set(allowed_caller_arb(), 0..32).prop_map(EntryPointAccess::Callers),
+    ]
+}
+
+pub fn allowed_caller_arb() -> impl Strategy<Value = AllowedCaller> {
+    prop_oneof![
+        account_hash_arb().prop_map(AllowedCaller::Account),
+        u8_slice_32().prop_map(|bytes| AllowedCaller::Contract(ContractHash::new(bytes))),
     ]
 }
 
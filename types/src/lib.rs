@@ -70,7 +70,7 @@ pub use cl_value::{CLTypeMismatch, CLValue, CLValueError};
 pub use contract_wasm::{ContractWasm, ContractWasmHash};
 #[doc(inline)]
 pub use contracts::{
-    Contract, ContractHash, ContractPackage, ContractPackageHash, ContractVersion,
+    AllowedCaller, Contract, ContractHash, ContractPackage, ContractPackageHash, ContractVersion,
     ContractVersionKey, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Group,
     Parameter,
 };
@@ -100,7 +100,7 @@ pub use timestamp::serde_option_time_diff;
 pub use timestamp::{TimeDiff, Timestamp};
 pub use transfer::{
     DeployHash, FromStrError as TransferFromStrError, Transfer, TransferAddr, DEPLOY_HASH_LENGTH,
-    TRANSFER_ADDR_LENGTH,
+    MAX_TRANSFER_BATCH_SIZE, TRANSFER_ADDR_LENGTH,
 };
 pub use transfer_result::{TransferResult, TransferredTo};
 pub use uref::{
@@ -4,6 +4,7 @@ mod constants;
 mod delegator;
 mod entry_points;
 mod era_info;
+mod era_reward_report;
 mod error;
 mod seigniorage_recipient;
 mod unbonding_purse;
@@ -16,6 +17,7 @@ pub use constants::*;
 pub use delegator::Delegator;
 pub use entry_points::auction_entry_points;
 pub use era_info::{EraInfo, SeigniorageAllocation};
+pub use era_reward_report::{DelegatorRewards, EraRewardReport, ValidatorRewards};
 pub use error::Error;
 pub use seigniorage_recipient::SeigniorageRecipient;
 pub use unbonding_purse::UnbondingPurse;
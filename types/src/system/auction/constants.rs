@@ -96,3 +96,6 @@ pub const AUCTION_DELAY_KEY: &str = "auction_delay";
 pub const LOCKED_FUNDS_PERIOD_KEY: &str = "locked_funds_period";
 /// Unbonding delay expressed in eras.
 pub const UNBONDING_DELAY_KEY: &str = "unbonding_delay";
+/// Storage for the eras in which delegators last redelegated directly, created on the first
+/// direct redelegation.
+pub const REDELEGATION_ERAS_KEY: &str = "redelegation_eras";
@@ -0,0 +1,219 @@
+// TODO - remove once schemars stops causing warning.
+#![allow(clippy::field_reassign_with_default)]
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+#[cfg(feature = "datasize")]
+use datasize::DataSize;
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    system::auction::{EraInfo, SeigniorageAllocation},
+    EraId, PublicKey, U512,
+};
+
+/// The rewards paid out for a single validator's stake in an era.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorRewards {
+    /// The validator's public key.
+    pub validator_public_key: PublicKey,
+    /// The reward paid to the validator itself, including its delegation commission.
+    pub validator_reward: U512,
+    /// The sum of the rewards paid to the validator's delegators.
+    pub delegator_rewards: U512,
+    /// The number of delegators rewarded.
+    pub delegator_count: u32,
+}
+
+/// The rewards paid out to a single delegator in an era, summed over all its delegations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct DelegatorRewards {
+    /// The delegator's public key.
+    pub delegator_public_key: PublicKey,
+    /// The sum of the rewards paid to the delegator.
+    pub amount: U512,
+}
+
+/// A summary of the rewards distributed and the tokens burned at the end of an era.
+///
+/// The rewards are the same as those recorded in the [`EraInfo`] stored under the era's
+/// `Key::EraInfo`, aggregated per validator and per delegator so that clients don't need to
+/// re-derive them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "datasize", derive(DataSize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct EraRewardReport {
+    era_id: EraId,
+    validators: Vec<ValidatorRewards>,
+    delegators: Vec<DelegatorRewards>,
+    total_validator_rewards: U512,
+    total_delegator_rewards: U512,
+    total_burned: U512,
+}
+
+impl EraRewardReport {
+    /// Constructs an [`EraRewardReport`] for `era_id` from the allocations in `era_info`.
+    ///
+    /// `total_burned` is the amount removed from the total supply when slashing validators.
+    pub fn new(era_id: EraId, era_info: &EraInfo, total_burned: U512) -> Self {
+        let mut validators: BTreeMap<PublicKey, ValidatorRewards> = BTreeMap::new();
+        let mut delegators: BTreeMap<PublicKey, U512> = BTreeMap::new();
+        let mut total_validator_rewards = U512::zero();
+        let mut total_delegator_rewards = U512::zero();
+
+        for allocation in era_info.seigniorage_allocations() {
+            let validator_public_key = match allocation {
+                SeigniorageAllocation::Validator {
+                    validator_public_key,
+                    ..
+                }
+                | SeigniorageAllocation::Delegator {
+                    validator_public_key,
+                    ..
+                } => validator_public_key,
+            };
+            let rewards = validators
+                .entry(validator_public_key.clone())
+                .or_insert_with(|| ValidatorRewards {
+                    validator_public_key: validator_public_key.clone(),
+                    validator_reward: U512::zero(),
+                    delegator_rewards: U512::zero(),
+                    delegator_count: 0,
+                });
+            match allocation {
+                SeigniorageAllocation::Validator { amount, .. } => {
+                    rewards.validator_reward = rewards.validator_reward.saturating_add(*amount);
+                    total_validator_rewards = total_validator_rewards.saturating_add(*amount);
+                }
+                SeigniorageAllocation::Delegator {
+                    delegator_public_key,
+                    amount,
+                    ..
+                } => {
+                    rewards.delegator_rewards = rewards.delegator_rewards.saturating_add(*amount);
+                    rewards.delegator_count += 1;
+                    total_delegator_rewards = total_delegator_rewards.saturating_add(*amount);
+                    let delegator_total = delegators
+                        .entry(delegator_public_key.clone())
+                        .or_insert_with(U512::zero);
+                    *delegator_total = delegator_total.saturating_add(*amount);
+                }
+            }
+        }
+
+        EraRewardReport {
+            era_id,
+            validators: validators.into_values().collect(),
+            delegators: delegators
+                .into_iter()
+                .map(|(delegator_public_key, amount)| DelegatorRewards {
+                    delegator_public_key,
+                    amount,
+                })
+                .collect(),
+            total_validator_rewards,
+            total_delegator_rewards,
+            total_burned,
+        }
+    }
+
+    /// Returns the era in which the rewards were earned.
+    pub fn era_id(&self) -> EraId {
+        self.era_id
+    }
+
+    /// Returns the rewards per validator, ordered by public key.
+    pub fn validators(&self) -> &[ValidatorRewards] {
+        &self.validators
+    }
+
+    /// Returns the rewards per delegator, ordered by public key.
+    pub fn delegators(&self) -> &[DelegatorRewards] {
+        &self.delegators
+    }
+
+    /// Returns the sum of the rewards paid to validators.
+    pub fn total_validator_rewards(&self) -> U512 {
+        self.total_validator_rewards
+    }
+
+    /// Returns the sum of the rewards paid to delegators.
+    pub fn total_delegator_rewards(&self) -> U512 {
+        self.total_delegator_rewards
+    }
+
+    /// Returns the total amount minted as rewards and added to the recipients' stakes.
+    pub fn total_accumulated(&self) -> U512 {
+        self.total_validator_rewards
+            .saturating_add(self.total_delegator_rewards)
+    }
+
+    /// Returns the total amount burned by slashing.
+    pub fn total_burned(&self) -> U512 {
+        self.total_burned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SecretKey;
+
+    use super::*;
+
+    fn public_key(seed: u8) -> PublicKey {
+        PublicKey::from(&SecretKey::ed25519_from_bytes([seed; 32]).unwrap())
+    }
+
+    #[test]
+    fn should_aggregate_allocations_per_validator_and_delegator() {
+        let (validator_1, validator_2, delegator) = (public_key(1), public_key(2), public_key(3));
+        let mut era_info = EraInfo::new();
+        *era_info.seigniorage_allocations_mut() = vec![
+            SeigniorageAllocation::validator(validator_1.clone(), U512::from(10)),
+            SeigniorageAllocation::delegator(delegator.clone(), validator_1.clone(), U512::from(3)),
+            SeigniorageAllocation::validator(validator_2.clone(), U512::from(20)),
+            SeigniorageAllocation::delegator(delegator.clone(), validator_2.clone(), U512::from(4)),
+        ];
+
+        let report = EraRewardReport::new(EraId::new(5), &era_info, U512::from(7));
+
+        assert_eq!(report.era_id(), EraId::new(5));
+        assert_eq!(
+            report.validators(),
+            &[
+                ValidatorRewards {
+                    validator_public_key: validator_1,
+                    validator_reward: U512::from(10),
+                    delegator_rewards: U512::from(3),
+                    delegator_count: 1,
+                },
+                ValidatorRewards {
+                    validator_public_key: validator_2,
+                    validator_reward: U512::from(20),
+                    delegator_rewards: U512::from(4),
+                    delegator_count: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            report.delegators(),
+            &[DelegatorRewards {
+                delegator_public_key: delegator,
+                amount: U512::from(7),
+            }]
+        );
+        assert_eq!(report.total_validator_rewards(), U512::from(30));
+        assert_eq!(report.total_delegator_rewards(), U512::from(7));
+        assert_eq!(report.total_accumulated(), U512::from(37));
+        assert_eq!(report.total_burned(), U512::from(7));
+    }
+}
@@ -308,6 +308,18 @@ pub enum Error {
     /// assert_eq!(46, Error::RuntimeStack as u8);
     /// ```
     RuntimeStack = 46,
+    /// Attempted to activate a bid which is already active.
+    /// ```
+    /// # use casper_types::system::auction::Error;
+    /// assert_eq!(47, Error::BidNotInactive as u8);
+    /// ```
+    BidNotInactive = 47,
+    /// Attempted to redelegate before the redelegation cooldown has passed.
+    /// ```
+    /// # use casper_types::system::auction::Error;
+    /// assert_eq!(48, Error::RedelegationCooldown as u8);
+    /// ```
+    RedelegationCooldown = 48,
 }
 
 impl Display for Error {
@@ -360,6 +372,8 @@ impl Display for Error {
             Error::GlobalDelegatorCapacityReached => formatter.write_str("The global delegator capacity has been reached"),
             Error::DelegationAmountTooSmall => formatter.write_str("The delegated amount is below the minimum allowed"),
             Error::RuntimeStack => formatter.write_str("Runtime stack error"),
+            Error::BidNotInactive => formatter.write_str("The bid is not inactive"),
+            Error::RedelegationCooldown => formatter.write_str("The delegator redelegated too recently"),
         }
     }
 }
@@ -438,6 +452,8 @@ impl TryFrom<u8> for Error {
             }
             d if d == Error::DelegationAmountTooSmall as u8 => Ok(Error::DelegationAmountTooSmall),
             d if d == Error::RuntimeStack as u8 => Ok(Error::RuntimeStack),
+            d if d == Error::BidNotInactive as u8 => Ok(Error::BidNotInactive),
+            d if d == Error::RedelegationCooldown as u8 => Ok(Error::RedelegationCooldown),
             _ => Err(TryFromU8ForError(())),
         }
     }
@@ -28,6 +28,8 @@ use crate::{
 pub const DEPLOY_HASH_LENGTH: usize = 32;
 /// The length of a transfer address.
 pub const TRANSFER_ADDR_LENGTH: usize = 32;
+/// The maximum number of transfers which can be made in a single call to `casper_transfer_batch`.
+pub const MAX_TRANSFER_BATCH_SIZE: usize = 100;
 pub(super) const TRANSFER_ADDR_FORMATTED_STRING_PREFIX: &str = "transfer-";
 
 /// A newtype wrapping a <code>[u8; [DEPLOY_HASH_LENGTH]]</code> which is the raw bytes of the
@@ -43,6 +43,10 @@ pub(crate) fn generate_generic_update(matches: &ArgMatches<'_>) {
 fn get_update<T: StateReader>(reader: T, config: Config) -> Update {
     let mut state_tracker = StateTracker::new(reader);
 
+    if config.migrate_legacy_withdraws {
+        state_tracker.migrate_withdraws();
+    }
+
     process_transfers(&mut state_tracker, &config.transfers);
 
     update_account_balances(&mut state_tracker, &config.accounts);
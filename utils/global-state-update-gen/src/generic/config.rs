@@ -14,6 +14,8 @@ pub struct Config {
     pub only_listed_validators: bool,
     #[serde(default)]
     pub slash_instead_of_unbonding: bool,
+    #[serde(default)]
+    pub migrate_legacy_withdraws: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
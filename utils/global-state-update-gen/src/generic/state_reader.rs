@@ -2,7 +2,7 @@ use casper_engine_test_support::LmdbWasmTestBuilder;
 use casper_types::{
     account::{Account, AccountHash},
     system::{
-        auction::{Bids, UnbondingPurses, SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY},
+        auction::{Bids, UnbondingPurses, WithdrawPurses, SEIGNIORAGE_RECIPIENTS_SNAPSHOT_KEY},
         mint::TOTAL_SUPPLY_KEY,
     },
     Key, StoredValue,
@@ -20,6 +20,8 @@ pub trait StateReader {
     fn get_bids(&mut self) -> Bids;
 
     fn get_unbonds(&mut self) -> UnbondingPurses;
+
+    fn get_withdraws(&mut self) -> WithdrawPurses;
 }
 
 impl<'a, T> StateReader for &'a mut T
@@ -49,6 +51,10 @@ where
     fn get_unbonds(&mut self) -> UnbondingPurses {
         T::get_unbonds(self)
     }
+
+    fn get_withdraws(&mut self) -> WithdrawPurses {
+        T::get_withdraws(self)
+    }
 }
 
 impl StateReader for LmdbWasmTestBuilder {
@@ -85,4 +91,8 @@ impl StateReader for LmdbWasmTestBuilder {
     fn get_unbonds(&mut self) -> UnbondingPurses {
         LmdbWasmTestBuilder::get_unbonds(self)
     }
+
+    fn get_withdraws(&mut self) -> WithdrawPurses {
+        LmdbWasmTestBuilder::get_withdraw_purses(self)
+    }
 }
@@ -8,7 +8,7 @@ use rand::Rng;
 
 use casper_types::{
     account::{Account, AccountHash},
-    system::auction::{Bid, Bids, SeigniorageRecipientsSnapshot, UnbondingPurse},
+    system::auction::{Bid, Bids, SeigniorageRecipientsSnapshot, UnbondingPurse, WithdrawPurse},
     AccessRights, CLValue, Key, PublicKey, StoredValue, URef, U512,
 };
 
@@ -315,16 +315,47 @@ impl<T: StateReader> StateTracker<T> {
         }
     }
 
-    pub fn create_unbonding_purse(
-        &mut self,
-        bonding_purse: URef,
-        validator_key: &PublicKey,
-        unbonder_key: &PublicKey,
-        amount: U512,
-    ) {
-        let account_hash = validator_key.to_account_hash();
-        let unbonding_era = self.read_snapshot().1.keys().next().copied().unwrap();
-        let unbonding_purses = match self.unbonds_cache.entry(account_hash) {
+    /// Generates the writes to the global state that will move all the legacy withdraw purses
+    /// into the unbonding purses stored under the same account hash, and clear the withdraws.
+    ///
+    /// Panics if the total amount held in unbonding and withdraw purses changes as a result.
+    pub fn migrate_withdraws(&mut self) {
+        let withdraws = self.reader.get_withdraws();
+        for (account_hash, withdraw_purses) in withdraws {
+            if withdraw_purses.is_empty() {
+                continue;
+            }
+
+            let unbonding_purses = self.get_unbonding_purses_mut(account_hash);
+            let total_before = unbonding_purses
+                .iter()
+                .map(UnbondingPurse::amount)
+                .chain(withdraw_purses.iter().map(WithdrawPurse::amount))
+                .fold(U512::zero(), |sum, amount| sum + *amount);
+
+            unbonding_purses.extend(withdraw_purses.into_iter().map(UnbondingPurse::from));
+            let unbonding_purses = unbonding_purses.clone();
+
+            let total_after = unbonding_purses
+                .iter()
+                .map(UnbondingPurse::amount)
+                .fold(U512::zero(), |sum, amount| sum + *amount);
+            assert_eq!(
+                total_before, total_after,
+                "migrating withdraws of {} changed the total amount",
+                account_hash
+            );
+
+            self.write_entry(
+                Key::Unbond(account_hash),
+                StoredValue::Unbonding(unbonding_purses),
+            );
+            self.write_entry(Key::Withdraw(account_hash), StoredValue::Withdraw(vec![]));
+        }
+    }
+
+    fn get_unbonding_purses_mut(&mut self, account_hash: AccountHash) -> &mut Vec<UnbondingPurse> {
+        match self.unbonds_cache.entry(account_hash) {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(entry) => {
                 let existing_purses = self
@@ -334,7 +365,19 @@ impl<T: StateReader> StateTracker<T> {
                     .unwrap_or_default();
                 entry.insert(existing_purses)
             }
-        };
+        }
+    }
+
+    pub fn create_unbonding_purse(
+        &mut self,
+        bonding_purse: URef,
+        validator_key: &PublicKey,
+        unbonder_key: &PublicKey,
+        amount: U512,
+    ) {
+        let account_hash = validator_key.to_account_hash();
+        let unbonding_era = self.read_snapshot().1.keys().next().copied().unwrap();
+        let unbonding_purses = self.get_unbonding_purses_mut(account_hash);
         // Take the first era from the snapshot as the unbonding era.
         let new_purse = UnbondingPurse::new(
             bonding_purse,
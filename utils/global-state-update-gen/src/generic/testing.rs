@@ -6,10 +6,10 @@ use casper_types::{
     account::{Account, AccountHash},
     system::auction::{
         Bid, Bids, Delegator, SeigniorageRecipient, SeigniorageRecipients,
-        SeigniorageRecipientsSnapshot, UnbondingPurses,
+        SeigniorageRecipientsSnapshot, UnbondingPurses, WithdrawPurse, WithdrawPurses,
     },
     testing::TestRng,
-    AccessRights, CLValue, Key, PublicKey, StoredValue, URef, URefAddr, U512,
+    AccessRights, CLValue, EraId, Key, PublicKey, StoredValue, URef, URefAddr, U512,
 };
 
 use super::{
@@ -29,6 +29,7 @@ struct MockStateReader {
     total_supply: U512,
     seigniorage_recipients: SeigniorageRecipientsSnapshot,
     bids: Bids,
+    withdraws: WithdrawPurses,
 }
 
 impl MockStateReader {
@@ -39,9 +40,18 @@ impl MockStateReader {
             total_supply: U512::zero(),
             seigniorage_recipients: SeigniorageRecipientsSnapshot::new(),
             bids: Bids::new(),
+            withdraws: WithdrawPurses::new(),
         }
     }
 
+    fn with_withdraw(mut self, withdraw_purse: WithdrawPurse) -> Self {
+        self.withdraws
+            .entry(withdraw_purse.validator_public_key().to_account_hash())
+            .or_default()
+            .push(withdraw_purse);
+        self
+    }
+
     fn with_account<R: Rng>(
         mut self,
         account_hash: AccountHash,
@@ -159,6 +169,10 @@ impl StateReader for MockStateReader {
     fn get_unbonds(&mut self) -> UnbondingPurses {
         UnbondingPurses::new()
     }
+
+    fn get_withdraws(&mut self) -> WithdrawPurses {
+        self.withdraws.clone()
+    }
 }
 
 impl ValidatorInfo {
@@ -1375,3 +1389,62 @@ fn should_remove_the_delegator_with_unbonding() {
     // - unbonding purse for delegator
     assert_eq!(update.len(), 5);
 }
+
+#[test]
+fn should_migrate_legacy_withdraws() {
+    let mut rng = TestRng::new();
+
+    let validator_key = PublicKey::random(&mut rng);
+    let delegator_key = PublicKey::random(&mut rng);
+    let validator_purse = URef::new(rng.gen(), AccessRights::READ_ADD_WRITE);
+    let delegator_purse = URef::new(rng.gen(), AccessRights::READ_ADD_WRITE);
+
+    let mut reader = MockStateReader::new()
+        .with_validators(
+            vec![(
+                validator_key.clone(),
+                U512::from(101),
+                ValidatorConfig {
+                    bonded_amount: U512::from(101),
+                    ..Default::default()
+                },
+            )],
+            &mut rng,
+        )
+        .with_withdraw(WithdrawPurse::new(
+            validator_purse,
+            validator_key.clone(),
+            validator_key.clone(),
+            EraId::new(1),
+            U512::from(10),
+        ))
+        .with_withdraw(WithdrawPurse::new(
+            delegator_purse,
+            validator_key.clone(),
+            delegator_key.clone(),
+            EraId::new(2),
+            U512::from(20),
+        ));
+
+    let config = Config {
+        migrate_legacy_withdraws: true,
+        ..Default::default()
+    };
+
+    let update = get_update(&mut reader, config);
+
+    update.assert_validators_unchanged();
+
+    // both withdraws should now be unbonding purses of the validator
+    update.assert_unbonding_purse(validator_purse, &validator_key, &validator_key, 10);
+    update.assert_unbonding_purse(delegator_purse, &validator_key, &delegator_key, 20);
+
+    // the withdraws should be cleared
+    let account_hash = validator_key.to_account_hash();
+    update.assert_written_withdraws_cleared(account_hash);
+
+    // 2 keys should be written:
+    // - unbonds of the validator
+    // - withdraws of the validator
+    assert_eq!(update.len(), 2);
+}
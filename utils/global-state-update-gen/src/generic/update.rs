@@ -128,6 +128,13 @@ impl Update {
         ))
     }
 
+    pub(crate) fn assert_written_withdraws_cleared(&self, account: AccountHash) {
+        assert_eq!(
+            self.entries.get(&Key::Withdraw(account)),
+            Some(&StoredValue::Withdraw(vec![]))
+        );
+    }
+
     pub(crate) fn assert_key_absent(&self, key: &Key) {
         assert!(!self.entries.contains_key(key))
     }